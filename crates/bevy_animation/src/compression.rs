@@ -0,0 +1,324 @@
+//! Compression of animation clips.
+//!
+//! Imported clips usually contain a keyframe per frame for every animated
+//! bone, most of which could be reconstructed by interpolation. Compressing a
+//! clip with [`AnimationClip::compress`] removes these redundant keyframes and
+//! stores rotations in 8 bytes instead of 16, which reduces the memory used by
+//! the clip and the amount of memory touched while sampling it.
+
+use bevy_math::{Quat, Vec3};
+use bevy_reflect::Reflect;
+use serde::{Deserialize, Serialize};
+
+use crate::{AnimationClip, Interpolation, Keyframes, VariableCurve};
+
+/// Number of bits used to store each of the three smallest components of a
+/// [`QuantizedQuat`].
+const COMPONENT_BITS: u32 = 20;
+const COMPONENT_MASK: u64 = (1 << COMPONENT_BITS) - 1;
+/// The components other than the largest one are in the range `[-1/√2, 1/√2]`.
+const COMPONENT_RANGE: f32 = std::f32::consts::FRAC_1_SQRT_2;
+
+/// A unit quaternion quantized to 64 bits.
+///
+/// This uses the "smallest three" encoding: the index of the largest component
+/// is stored in 2 bits, and the three other components in 20 bits each. The
+/// largest component is recovered from the unit length of the quaternion. The
+/// resulting error is about 1e-6 per component.
+#[derive(Reflect, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct QuantizedQuat(pub u64);
+
+impl QuantizedQuat {
+    /// Quantizes `rotation`, which is normalized first.
+    pub fn from_quat(rotation: Quat) -> Self {
+        let mut components = rotation.normalize().to_array();
+        let largest = (0..4)
+            .max_by(|&a, &b| components[a].abs().total_cmp(&components[b].abs()))
+            .unwrap();
+        // `q` and `-q` are the same rotation: make the largest component
+        // positive so that it can be recovered from the others.
+        if components[largest] < 0.0 {
+            components
+                .iter_mut()
+                .for_each(|component| *component = -*component);
+        }
+
+        let mut bits = (largest as u64) << (3 * COMPONENT_BITS);
+        let mut shift = 2 * COMPONENT_BITS;
+        for (index, component) in components.into_iter().enumerate() {
+            if index == largest {
+                continue;
+            }
+            let normalized = (component / COMPONENT_RANGE).clamp(-1.0, 1.0) * 0.5 + 0.5;
+            let quantized = (normalized * COMPONENT_MASK as f32).round() as u64;
+            bits |= quantized << shift;
+            shift = shift.saturating_sub(COMPONENT_BITS);
+        }
+        Self(bits)
+    }
+
+    /// Recovers the quantized rotation.
+    pub fn to_quat(self) -> Quat {
+        let largest = (self.0 >> (3 * COMPONENT_BITS)) as usize & 0b11;
+        let mut components = [0.0; 4];
+        let mut shift = 2 * COMPONENT_BITS;
+        let mut sum_of_squares = 0.0;
+        for (index, component) in components.iter_mut().enumerate() {
+            if index == largest {
+                continue;
+            }
+            let quantized = (self.0 >> shift) & COMPONENT_MASK;
+            *component = (quantized as f32 / COMPONENT_MASK as f32 - 0.5) * 2.0 * COMPONENT_RANGE;
+            sum_of_squares += *component * *component;
+            shift = shift.saturating_sub(COMPONENT_BITS);
+        }
+        components[largest] = (1.0 - sum_of_squares).max(0.0).sqrt();
+        Quat::from_array(components).normalize()
+    }
+}
+
+impl From<Quat> for QuantizedQuat {
+    fn from(rotation: Quat) -> Self {
+        Self::from_quat(rotation)
+    }
+}
+
+impl From<QuantizedQuat> for Quat {
+    fn from(rotation: QuantizedQuat) -> Self {
+        rotation.to_quat()
+    }
+}
+
+/// Settings for [`AnimationClip::compress`].
+///
+/// Keyframes that can be reconstructed from their neighbors within these
+/// tolerances are removed.
+#[derive(Reflect, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct AnimationCompressionSettings {
+    /// The maximum error allowed on translations, in world units.
+    pub translation_tolerance: f32,
+    /// The maximum error allowed on rotations, in radians.
+    pub rotation_tolerance: f32,
+    /// The maximum error allowed on scales.
+    pub scale_tolerance: f32,
+    /// Whether to store rotations as [`QuantizedQuat`]s.
+    ///
+    /// Only curves using [`Interpolation::Linear`] or [`Interpolation::Step`]
+    /// are quantized, as cubic spline tangents aren't unit quaternions.
+    pub quantize_rotations: bool,
+}
+
+impl Default for AnimationCompressionSettings {
+    fn default() -> Self {
+        Self {
+            translation_tolerance: 1e-4,
+            rotation_tolerance: 1e-4,
+            scale_tolerance: 1e-4,
+            quantize_rotations: true,
+        }
+    }
+}
+
+impl AnimationClip {
+    /// Compresses the curves of this clip according to `settings`.
+    ///
+    /// Morph weights, cubic spline curves and property curves aren't
+    /// compressed.
+    pub fn compress(&mut self, settings: &AnimationCompressionSettings) {
+        for curves in self.curves.values_mut() {
+            for curve in curves.iter_mut() {
+                curve.compress(settings);
+            }
+        }
+    }
+}
+
+impl VariableCurve {
+    fn compress(&mut self, settings: &AnimationCompressionSettings) {
+        let interpolation = self.interpolation.clone();
+        let timestamps = &self.keyframe_timestamps;
+        let kept = match &self.keyframes {
+            Keyframes::Translation(keyframes) => {
+                reduce_keyframes(timestamps, keyframes, &interpolation, Vec3::lerp, |a, b| {
+                    a.distance(b) <= settings.translation_tolerance
+                })
+            }
+            Keyframes::Scale(keyframes) => {
+                reduce_keyframes(timestamps, keyframes, &interpolation, Vec3::lerp, |a, b| {
+                    a.distance(b) <= settings.scale_tolerance
+                })
+            }
+            Keyframes::Rotation(keyframes) => reduce_keyframes(
+                timestamps,
+                keyframes,
+                &interpolation,
+                slerp_shortest,
+                |a, b| rotation_error(a, b) <= settings.rotation_tolerance,
+            ),
+            Keyframes::QuantizedRotation(keyframes) => {
+                let keyframes: Vec<Quat> = keyframes.iter().map(|key| key.to_quat()).collect();
+                reduce_keyframes(
+                    timestamps,
+                    &keyframes,
+                    &interpolation,
+                    slerp_shortest,
+                    |a, b| rotation_error(a, b) <= settings.rotation_tolerance,
+                )
+            }
+            Keyframes::Weights(_) => None,
+        };
+
+        if let Some(kept) = kept {
+            self.keyframe_timestamps = kept.iter().map(|&index| timestamps[index]).collect();
+            match &mut self.keyframes {
+                Keyframes::Translation(keyframes) | Keyframes::Scale(keyframes) => {
+                    *keyframes = kept.iter().map(|&index| keyframes[index]).collect();
+                }
+                Keyframes::Rotation(keyframes) => {
+                    *keyframes = kept.iter().map(|&index| keyframes[index]).collect();
+                }
+                Keyframes::QuantizedRotation(keyframes) => {
+                    *keyframes = kept.iter().map(|&index| keyframes[index]).collect();
+                }
+                Keyframes::Weights(_) => {}
+            }
+        }
+
+        if settings.quantize_rotations && !matches!(interpolation, Interpolation::CubicSpline) {
+            if let Keyframes::Rotation(keyframes) = &self.keyframes {
+                self.keyframes = Keyframes::QuantizedRotation(
+                    keyframes.iter().copied().map(QuantizedQuat::from).collect(),
+                );
+            }
+        }
+    }
+}
+
+/// Interpolates between two rotations along the shortest path, like the
+/// sampling of [`Keyframes::Rotation`] does.
+fn slerp_shortest(start: Quat, mut end: Quat, t: f32) -> Quat {
+    if end.dot(start) < 0.0 {
+        end = -end;
+    }
+    start.normalize().slerp(end.normalize(), t)
+}
+
+/// The angle between two rotations.
+///
+/// Unlike [`Quat::angle_between`], which uses the arc cosine of their dot
+/// product, this stays precise for small angles.
+fn rotation_error(a: Quat, b: Quat) -> f32 {
+    let difference = a.normalize().conjugate() * b.normalize();
+    2.0 * difference.xyz().length().min(1.0).asin()
+}
+
+/// Finds the keyframes to keep so that every removed keyframe can be
+/// reconstructed by interpolating between the kept ones.
+///
+/// Returns [`None`] if all the keyframes are needed, or if the curve can't be
+/// reduced.
+fn reduce_keyframes<T: Copy>(
+    timestamps: &[f32],
+    keyframes: &[T],
+    interpolation: &Interpolation,
+    lerp: impl Fn(T, T, f32) -> T,
+    within_tolerance: impl Fn(T, T) -> bool,
+) -> Option<Vec<usize>> {
+    if keyframes.len() <= 2 || keyframes.len() != timestamps.len() {
+        return None;
+    }
+
+    let mut kept = vec![0];
+    let mut anchor = 0;
+    for candidate in 2..keyframes.len() {
+        // Check that all the keyframes between the anchor and the candidate
+        // can be skipped.
+        let skippable = (anchor + 1..candidate).all(|index| {
+            let reconstructed = match interpolation {
+                Interpolation::Step => keyframes[anchor],
+                Interpolation::Linear => {
+                    let t = (timestamps[index] - timestamps[anchor])
+                        / (timestamps[candidate] - timestamps[anchor]);
+                    lerp(keyframes[anchor], keyframes[candidate], t)
+                }
+                Interpolation::CubicSpline => return false,
+            };
+            within_tolerance(reconstructed, keyframes[index])
+        });
+        if !skippable {
+            anchor = candidate - 1;
+            kept.push(anchor);
+        }
+    }
+    kept.push(keyframes.len() - 1);
+
+    (kept.len() < keyframes.len()).then_some(kept)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AnimationTargetId;
+    use bevy_core::Name;
+
+    #[test]
+    fn quantized_quat_roundtrip() {
+        let rotations = [
+            Quat::IDENTITY,
+            Quat::from_rotation_x(0.3),
+            Quat::from_euler(bevy_math::EulerRot::XYZ, 1.0, -2.0, 3.0),
+            -Quat::from_rotation_y(2.5),
+            Quat::from_xyzw(0.5, 0.5, 0.5, 0.5),
+        ];
+        for rotation in rotations {
+            let decoded = QuantizedQuat::from_quat(rotation).to_quat();
+            assert!(
+                decoded.angle_between(rotation) < 1e-5,
+                "{rotation} -> {decoded}"
+            );
+        }
+    }
+
+    #[test]
+    fn compress_removes_redundant_keyframes() {
+        let target = AnimationTargetId::from_name(&Name::new("Bone"));
+        let timestamps: Vec<f32> = (0..=10).map(|frame| frame as f32 / 10.0).collect();
+        let mut clip = AnimationClip::default();
+        // A straight line only needs its end points.
+        clip.add_curve_to_target(
+            target,
+            VariableCurve {
+                keyframe_timestamps: timestamps.clone(),
+                keyframes: Keyframes::Translation(
+                    timestamps.iter().map(|&t| Vec3::X * t).collect(),
+                ),
+                interpolation: Interpolation::Linear,
+            },
+        );
+        // A rotation that stops half-way needs the keyframe where it stops.
+        clip.add_curve_to_target(
+            target,
+            VariableCurve {
+                keyframe_timestamps: timestamps.clone(),
+                keyframes: Keyframes::Rotation(
+                    timestamps
+                        .iter()
+                        .map(|&t| Quat::from_rotation_z(t.min(0.5)))
+                        .collect(),
+                ),
+                interpolation: Interpolation::Linear,
+            },
+        );
+        clip.compress(&AnimationCompressionSettings::default());
+
+        let curves = clip.curves_for_target(target).unwrap();
+        assert_eq!(curves[0].keyframe_timestamps, vec![0.0, 1.0]);
+        assert_eq!(curves[1].keyframe_timestamps, vec![0.0, 0.5, 1.0]);
+        let Keyframes::QuantizedRotation(rotations) = &curves[1].keyframes else {
+            panic!("rotations should be quantized");
+        };
+        assert!(rotations[1]
+            .to_quat()
+            .abs_diff_eq(Quat::from_rotation_z(0.5), 1e-5));
+    }
+}
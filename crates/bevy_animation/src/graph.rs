@@ -0,0 +1,674 @@
+//! Animation graphs, which blend many clips into a single pose.
+//!
+//! An [`AnimationPlayer`] can only crossfade between whole clips on its own.
+//! An [`AnimationGraph`] describes how to blend a set of clips instead: its
+//! nodes are clips, weighted blends, 1D and 2D blend spaces (e.g. idle, walk
+//! and run by speed) and additive layers, driven by named parameters that
+//! systems set on the player with [`AnimationPlayer::set_graph_parameter`].
+//!
+//! The graph of a player is evaluated in place of its main animation, under its
+//! transitions and layers.
+
+use bevy_asset::{Asset, Assets, Handle};
+use bevy_ecs::prelude::*;
+use bevy_math::Vec2;
+use bevy_reflect::Reflect;
+use bevy_utils::HashMap;
+use std::time::Duration;
+
+use crate::{
+    AnimationBlendMode, AnimationClip, AnimationEvent, AnimationMask, AnimationPlayer,
+    PlayingAnimation, RepeatAnimation,
+};
+
+/// The index of a node in an [`AnimationGraph`].
+#[derive(Reflect, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct AnimationNodeIndex(pub usize);
+
+/// The weight of a child of an [`AnimationGraphNode`].
+#[derive(Reflect, Clone, Debug, PartialEq)]
+pub enum AnimationGraphWeight {
+    /// A fixed weight.
+    Constant(f32),
+    /// The value of a parameter of the graph.
+    Parameter(String),
+}
+
+impl From<f32> for AnimationGraphWeight {
+    fn from(weight: f32) -> Self {
+        Self::Constant(weight)
+    }
+}
+
+/// A clip played by an [`AnimationGraph`].
+#[derive(Reflect, Clone, Debug)]
+pub struct ClipNode {
+    /// The clip to play.
+    pub clip: Handle<AnimationClip>,
+    /// The targets the clip is restricted to, if any.
+    pub mask: Option<Handle<AnimationMask>>,
+    /// The playback speed of the clip.
+    pub speed: f32,
+    /// The repetition behavior of the clip.
+    pub repeat: RepeatAnimation,
+}
+
+impl ClipNode {
+    /// Plays `clip` forever, at normal speed.
+    pub fn new(clip: Handle<AnimationClip>) -> Self {
+        Self {
+            clip,
+            mask: None,
+            speed: 1.0,
+            repeat: RepeatAnimation::Forever,
+        }
+    }
+}
+
+/// A node of an [`AnimationGraph`].
+#[derive(Reflect, Clone, Debug)]
+pub enum AnimationGraphNode {
+    /// Plays a clip.
+    Clip(ClipNode),
+    /// Blends its children by their weights, which are normalized so that
+    /// they sum to one.
+    Blend {
+        /// The children and their weights.
+        children: Vec<(AnimationNodeIndex, AnimationGraphWeight)>,
+    },
+    /// Blends the two children surrounding the value of a parameter, linearly.
+    ///
+    /// Typically used for locomotion, e.g. with idle, walk and run clips placed
+    /// at their speed and the speed of the character as the parameter.
+    BlendSpace1d {
+        /// The parameter the children are placed along.
+        parameter: String,
+        /// The children and their position along the parameter.
+        children: Vec<(f32, AnimationNodeIndex)>,
+    },
+    /// Blends the children closest to the values of two parameters, with
+    /// gradient band interpolation.
+    ///
+    /// Typically used for strafing, e.g. with walk clips in each direction
+    /// placed at their velocity and the velocity of the character as the
+    /// parameters.
+    BlendSpace2d {
+        /// The parameter along the X axis.
+        x_parameter: String,
+        /// The parameter along the Y axis.
+        y_parameter: String,
+        /// The children and their position in the blend space.
+        children: Vec<(Vec2, AnimationNodeIndex)>,
+    },
+    /// Adds the difference between the pose of `additive` and its pose at
+    /// `reference_time` to the pose of `base`, scaled by `weight`.
+    ///
+    /// See [`AnimationBlendMode::Additive`].
+    Additive {
+        /// The pose the deltas are added to.
+        base: AnimationNodeIndex,
+        /// The node the deltas are taken from.
+        additive: AnimationNodeIndex,
+        /// How much of the deltas to add.
+        weight: AnimationGraphWeight,
+        /// The time, in seconds, of the reference pose inside of the clips of
+        /// `additive`.
+        reference_time: f32,
+    },
+}
+
+/// Describes how to blend a set of [`AnimationClip`]s into a single pose.
+///
+/// The graph is evaluated from its root by an [`AnimationPlayer`] playing it,
+/// see [`AnimationPlayer::play_graph`]. Every clip of the graph is advanced
+/// each frame, whether it currently contributes to the pose or not.
+///
+/// A node should only have a single parent, as the playback state of its clip
+/// is shared by all of its parents.
+///
+/// ```
+/// # use bevy_animation::{graph::{AnimationGraph, ClipNode}, AnimationClip};
+/// # use bevy_asset::Handle;
+/// # let (idle, walk, run): (Handle<AnimationClip>, Handle<AnimationClip>, Handle<AnimationClip>) =
+/// #     Default::default();
+/// let mut graph = AnimationGraph::new();
+/// let idle = graph.add_clip(ClipNode::new(idle));
+/// let walk = graph.add_clip(ClipNode::new(walk));
+/// let run = graph.add_clip(ClipNode::new(run));
+/// let locomotion = graph.add_blend_space_1d("speed", [(0.0, idle), (1.5, walk), (5.0, run)]);
+/// graph.set_root(locomotion);
+/// ```
+#[derive(Asset, Reflect, Clone, Debug, Default)]
+pub struct AnimationGraph {
+    nodes: Vec<AnimationGraphNode>,
+    root: Option<AnimationNodeIndex>,
+    parameters: HashMap<String, f32>,
+}
+
+impl AnimationGraph {
+    /// Creates an empty graph.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `node` to the graph, returning its index.
+    pub fn add_node(&mut self, node: AnimationGraphNode) -> AnimationNodeIndex {
+        self.nodes.push(node);
+        AnimationNodeIndex(self.nodes.len() - 1)
+    }
+
+    /// Adds a node playing a clip, returning its index.
+    pub fn add_clip(&mut self, clip: ClipNode) -> AnimationNodeIndex {
+        self.add_node(AnimationGraphNode::Clip(clip))
+    }
+
+    /// Adds a node blending `children` by their weights, returning its index.
+    pub fn add_blend(
+        &mut self,
+        children: impl IntoIterator<Item = (AnimationNodeIndex, AnimationGraphWeight)>,
+    ) -> AnimationNodeIndex {
+        self.add_node(AnimationGraphNode::Blend {
+            children: children.into_iter().collect(),
+        })
+    }
+
+    /// Adds a node blending `children` by their position along `parameter`,
+    /// returning its index.
+    pub fn add_blend_space_1d(
+        &mut self,
+        parameter: impl Into<String>,
+        children: impl IntoIterator<Item = (f32, AnimationNodeIndex)>,
+    ) -> AnimationNodeIndex {
+        self.add_node(AnimationGraphNode::BlendSpace1d {
+            parameter: parameter.into(),
+            children: children.into_iter().collect(),
+        })
+    }
+
+    /// Adds a node blending `children` by their position in the space of
+    /// `x_parameter` and `y_parameter`, returning its index.
+    pub fn add_blend_space_2d(
+        &mut self,
+        x_parameter: impl Into<String>,
+        y_parameter: impl Into<String>,
+        children: impl IntoIterator<Item = (Vec2, AnimationNodeIndex)>,
+    ) -> AnimationNodeIndex {
+        self.add_node(AnimationGraphNode::BlendSpace2d {
+            x_parameter: x_parameter.into(),
+            y_parameter: y_parameter.into(),
+            children: children.into_iter().collect(),
+        })
+    }
+
+    /// Adds a node adding the deltas of `additive` from its first frame to
+    /// `base`, returning its index.
+    pub fn add_additive(
+        &mut self,
+        base: AnimationNodeIndex,
+        additive: AnimationNodeIndex,
+        weight: impl Into<AnimationGraphWeight>,
+    ) -> AnimationNodeIndex {
+        self.add_node(AnimationGraphNode::Additive {
+            base,
+            additive,
+            weight: weight.into(),
+            reference_time: 0.0,
+        })
+    }
+
+    /// The node at `index`.
+    pub fn node(&self, index: AnimationNodeIndex) -> Option<&AnimationGraphNode> {
+        self.nodes.get(index.0)
+    }
+
+    /// The node at `index`, mutably.
+    pub fn node_mut(&mut self, index: AnimationNodeIndex) -> Option<&mut AnimationGraphNode> {
+        self.nodes.get_mut(index.0)
+    }
+
+    /// The nodes of the graph, by index.
+    pub fn nodes(&self) -> &[AnimationGraphNode] {
+        &self.nodes
+    }
+
+    /// The node the graph is evaluated from.
+    pub fn root(&self) -> Option<AnimationNodeIndex> {
+        self.root
+    }
+
+    /// Sets the node the graph is evaluated from.
+    pub fn set_root(&mut self, root: AnimationNodeIndex) -> &mut Self {
+        self.root = Some(root);
+        self
+    }
+
+    /// Declares a parameter of the graph with its default value, used by
+    /// players that don't set it.
+    ///
+    /// Parameters that are neither declared nor set default to `0.0`.
+    pub fn add_parameter(&mut self, name: impl Into<String>, default: f32) -> &mut Self {
+        self.parameters.insert(name.into(), default);
+        self
+    }
+
+    /// The default value of the parameter `name`, if it's declared.
+    pub fn parameter(&self, name: &str) -> Option<f32> {
+        self.parameters.get(name).copied()
+    }
+
+    /// Computes the clips to blend, in order, and how to blend them.
+    fn evaluate(&self, parameters: &HashMap<String, f32>, clips: &mut Vec<GraphClip>) {
+        clips.clear();
+        if let Some(root) = self.root {
+            let mut context = GraphEvaluation {
+                graph: self,
+                parameters,
+                clips,
+            };
+            context.evaluate(root, 1.0, 1.0, None, 0);
+        }
+    }
+}
+
+/// A clip of an [`AnimationGraph`] to blend into the pose.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct GraphClip {
+    /// The index of the clip node.
+    pub(crate) node: usize,
+    /// The factor the pose is blended toward the clip with, or the deltas of
+    /// the clip are added with.
+    pub(crate) factor: f32,
+    /// The contribution of the clip to the final pose, for events.
+    weight: f32,
+    blend_mode: AnimationBlendMode,
+}
+
+struct GraphEvaluation<'a> {
+    graph: &'a AnimationGraph,
+    parameters: &'a HashMap<String, f32>,
+    clips: &'a mut Vec<GraphClip>,
+}
+
+impl GraphEvaluation<'_> {
+    fn parameter(&self, name: &str) -> f32 {
+        self.parameters
+            .get(name)
+            .copied()
+            .or_else(|| self.graph.parameter(name))
+            .unwrap_or(0.0)
+    }
+
+    fn weight(&self, weight: &AnimationGraphWeight) -> f32 {
+        match weight {
+            AnimationGraphWeight::Constant(weight) => *weight,
+            AnimationGraphWeight::Parameter(name) => self.parameter(name),
+        }
+        .max(0.0)
+    }
+
+    /// Emits the clips that blend the current pose toward the pose of `node`
+    /// by `factor`, or that add its deltas scaled by `factor` when
+    /// `reference_time` is set.
+    fn evaluate(
+        &mut self,
+        node: AnimationNodeIndex,
+        factor: f32,
+        weight: f32,
+        reference_time: Option<f32>,
+        depth: usize,
+    ) {
+        // Guard against cycles.
+        if factor <= 0.0 || depth > self.graph.nodes.len() {
+            return;
+        }
+        let Some(graph_node) = self.graph.nodes.get(node.0) else {
+            return;
+        };
+
+        let children: Vec<(AnimationNodeIndex, f32)> = match graph_node {
+            AnimationGraphNode::Clip(_) => {
+                self.clips.push(GraphClip {
+                    node: node.0,
+                    factor,
+                    weight,
+                    blend_mode: match reference_time {
+                        Some(reference_time) => AnimationBlendMode::Additive { reference_time },
+                        None => AnimationBlendMode::Override,
+                    },
+                });
+                return;
+            }
+            AnimationGraphNode::Blend { children } => children
+                .iter()
+                .map(|(child, child_weight)| (*child, self.weight(child_weight)))
+                .collect(),
+            AnimationGraphNode::BlendSpace1d {
+                parameter,
+                children,
+            } => {
+                let positions: Vec<f32> = children.iter().map(|(position, _)| *position).collect();
+                blend_space_1d_weights(&positions, self.parameter(parameter))
+                    .into_iter()
+                    .zip(children)
+                    .map(|(child_weight, (_, child))| (*child, child_weight))
+                    .collect()
+            }
+            AnimationGraphNode::BlendSpace2d {
+                x_parameter,
+                y_parameter,
+                children,
+            } => {
+                let positions: Vec<Vec2> = children.iter().map(|(position, _)| *position).collect();
+                let point = Vec2::new(self.parameter(x_parameter), self.parameter(y_parameter));
+                blend_space_2d_weights(&positions, point)
+                    .into_iter()
+                    .zip(children)
+                    .map(|(child_weight, (_, child))| (*child, child_weight))
+                    .collect()
+            }
+            AnimationGraphNode::Additive {
+                base,
+                additive,
+                weight: additive_weight,
+                reference_time: additive_reference_time,
+            } => {
+                let additive_weight = self.weight(additive_weight);
+                self.evaluate(*base, factor, weight, reference_time, depth + 1);
+                self.evaluate(
+                    *additive,
+                    factor * additive_weight,
+                    weight * additive_weight,
+                    Some(reference_time.unwrap_or(*additive_reference_time)),
+                    depth + 1,
+                );
+                return;
+            }
+        };
+
+        let total: f32 = children.iter().map(|(_, child_weight)| child_weight).sum();
+        if total <= 0.0 {
+            return;
+        }
+
+        // Blending the pose toward each child in turn only yields the weighted
+        // average of the children if the earlier children are given more
+        // weight, as the later ones fade them out. Additive deltas just sum.
+        let mut remaining = 1.0;
+        for (child, child_weight) in children {
+            let child_weight = child_weight / total;
+            remaining -= child_weight;
+            let child_factor = if reference_time.is_some() {
+                factor * child_weight
+            } else {
+                factor * child_weight / (1.0 - factor * remaining.max(0.0))
+            };
+            self.evaluate(
+                child,
+                child_factor,
+                weight * child_weight,
+                reference_time,
+                depth + 1,
+            );
+        }
+    }
+}
+
+/// The weights of the children of a 1D blend space at `position`.
+fn blend_space_1d_weights(positions: &[f32], position: f32) -> Vec<f32> {
+    let mut weights = vec![0.0; positions.len()];
+
+    // The children surrounding the position, if any.
+    let below = (0..positions.len())
+        .filter(|&i| positions[i] <= position)
+        .max_by(|&a, &b| positions[a].total_cmp(&positions[b]));
+    let above = (0..positions.len())
+        .filter(|&i| positions[i] > position)
+        .min_by(|&a, &b| positions[a].total_cmp(&positions[b]));
+
+    match (below, above) {
+        (Some(below), Some(above)) => {
+            let t = (position - positions[below]) / (positions[above] - positions[below]);
+            weights[below] = 1.0 - t;
+            weights[above] = t;
+        }
+        (Some(closest), None) | (None, Some(closest)) => weights[closest] = 1.0,
+        (None, None) => {}
+    }
+    weights
+}
+
+/// The weights of the children of a 2D blend space at `point`, with gradient
+/// band interpolation.
+fn blend_space_2d_weights(positions: &[Vec2], point: Vec2) -> Vec<f32> {
+    let mut weights: Vec<f32> = positions
+        .iter()
+        .enumerate()
+        .map(|(i, &position)| {
+            positions
+                .iter()
+                .enumerate()
+                .filter(|&(j, &other)| j != i && other != position)
+                .map(|(_, &other)| {
+                    let edge = other - position;
+                    1.0 - (point - position).dot(edge) / edge.length_squared()
+                })
+                .fold(1.0_f32, f32::min)
+                .max(0.0)
+        })
+        .collect();
+
+    let total: f32 = weights.iter().sum();
+    if total > 0.0 {
+        for weight in &mut weights {
+            *weight /= total;
+        }
+    }
+    weights
+}
+
+/// The playback state of an [`AnimationGraph`] on an [`AnimationPlayer`].
+#[derive(Default)]
+pub(crate) struct AnimationGraphState {
+    /// The values of the parameters set on the player.
+    pub(crate) parameters: HashMap<String, f32>,
+    /// The playback state of each node, only used by the clip nodes.
+    pub(crate) nodes: Vec<PlayingAnimation>,
+    /// The clips to blend this frame, in order.
+    pub(crate) clips: Vec<GraphClip>,
+}
+
+impl AnimationGraphState {
+    /// Advances the clips of `graph`, and computes how to blend them for this
+    /// frame.
+    pub(crate) fn advance(
+        &mut self,
+        graph: &AnimationGraph,
+        clips: &Assets<AnimationClip>,
+        delta: f32,
+        player: Entity,
+        events: &mut EventWriter<AnimationEvent>,
+    ) {
+        graph.evaluate(&self.parameters, &mut self.clips);
+
+        self.nodes
+            .resize_with(graph.nodes.len(), PlayingAnimation::default);
+        for (index, node) in graph.nodes.iter().enumerate() {
+            let AnimationGraphNode::Clip(clip_node) = node else {
+                continue;
+            };
+            let animation = &mut self.nodes[index];
+            animation.animation_clip = clip_node.clip.clone();
+            animation.mask = clip_node.mask.clone();
+            animation.speed = clip_node.speed;
+            animation.repeat = clip_node.repeat;
+
+            let Some(clip) = clips.get(&clip_node.clip) else {
+                continue;
+            };
+            let weight: f32 = self
+                .clips
+                .iter()
+                .filter(|graph_clip| graph_clip.node == index)
+                .map(|graph_clip| graph_clip.weight)
+                .sum();
+            if weight > 0.0 {
+                animation.advance(delta, clip, player, weight, events);
+            } else {
+                animation.update(delta, clip.duration);
+            }
+        }
+
+        for graph_clip in &self.clips {
+            self.nodes[graph_clip.node].blend_mode = graph_clip.blend_mode;
+        }
+    }
+
+    /// Resets all the clips to their initial state.
+    pub(crate) fn replay(&mut self) {
+        for animation in &mut self.nodes {
+            animation.replay();
+        }
+    }
+}
+
+impl AnimationPlayer {
+    /// Starts evaluating `graph` in place of the main animation, resetting the
+    /// state of the player.
+    ///
+    /// Starting a clip stops the graph.
+    pub fn play_graph(&mut self, graph: Handle<AnimationGraph>) -> &mut Self {
+        self.graph = Some(graph);
+        self.graph_state.replay();
+        self.transitions.clear();
+        self
+    }
+
+    /// Starts evaluating `graph` in place of the main animation, fading out
+    /// the main animation over `transition_duration`.
+    pub fn play_graph_with_transition(
+        &mut self,
+        graph: Handle<AnimationGraph>,
+        transition_duration: Duration,
+    ) -> &mut Self {
+        if self.graph.is_none() {
+            let animation = std::mem::take(&mut self.animation);
+            self.push_transition(animation, transition_duration);
+        }
+        self.graph = Some(graph);
+        self.graph_state.replay();
+        self
+    }
+
+    /// The graph evaluated in place of the main animation, if any.
+    pub fn graph(&self) -> Option<&Handle<AnimationGraph>> {
+        self.graph.as_ref()
+    }
+
+    /// Sets the parameter `name` of the graph to `value`.
+    pub fn set_graph_parameter(&mut self, name: impl Into<String>, value: f32) -> &mut Self {
+        self.graph_state.parameters.insert(name.into(), value);
+        self
+    }
+
+    /// The value of the parameter `name` set on this player, if any.
+    pub fn graph_parameter(&self, name: &str) -> Option<f32> {
+        self.graph_state.parameters.get(name).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn evaluate(graph: &AnimationGraph, parameters: &[(&str, f32)]) -> Vec<GraphClip> {
+        let parameters = parameters
+            .iter()
+            .map(|(name, value)| (name.to_string(), *value))
+            .collect();
+        let mut clips = Vec::new();
+        graph.evaluate(&parameters, &mut clips);
+        clips
+    }
+
+    fn clip_graph(count: usize) -> (AnimationGraph, Vec<AnimationNodeIndex>) {
+        let mut graph = AnimationGraph::new();
+        let clips = (0..count)
+            .map(|_| graph.add_clip(ClipNode::new(Handle::default())))
+            .collect();
+        (graph, clips)
+    }
+
+    #[test]
+    fn blend_space_1d_interpolates_between_neighbors() {
+        assert_eq!(
+            blend_space_1d_weights(&[0.0, 2.0, 4.0], 3.0),
+            [0.0, 0.5, 0.5]
+        );
+        assert_eq!(
+            blend_space_1d_weights(&[4.0, 0.0, 2.0], -1.0),
+            [0.0, 1.0, 0.0]
+        );
+        assert_eq!(
+            blend_space_1d_weights(&[4.0, 0.0, 2.0], 9.0),
+            [1.0, 0.0, 0.0]
+        );
+    }
+
+    #[test]
+    fn blend_space_2d_picks_children_at_their_position() {
+        let positions = [Vec2::ZERO, Vec2::X, Vec2::Y, Vec2::NEG_X];
+        assert_eq!(
+            blend_space_2d_weights(&positions, Vec2::X),
+            [0.0, 1.0, 0.0, 0.0]
+        );
+
+        let weights = blend_space_2d_weights(&positions, Vec2::new(0.5, 0.0));
+        assert!((weights.iter().sum::<f32>() - 1.0).abs() < 1e-6);
+        assert!((weights[0] - weights[1]).abs() < 1e-6);
+        assert_eq!(weights[2], 0.0);
+    }
+
+    #[test]
+    fn blend_factors_average_the_children() {
+        let (mut graph, clips) = clip_graph(3);
+        let blend = graph.add_blend(clips.iter().map(|&clip| (clip, 1.0.into())));
+        graph.set_root(blend);
+
+        // Blending a pose toward each clip in turn by its factor must leave
+        // each clip with a third of the final pose.
+        let mut pose = [0.0; 3];
+        for graph_clip in evaluate(&graph, &[]) {
+            for (i, value) in pose.iter_mut().enumerate() {
+                let target = if i == graph_clip.node { 1.0 } else { 0.0 };
+                *value += (target - *value) * graph_clip.factor;
+            }
+        }
+        for value in pose {
+            assert!((value - 1.0 / 3.0).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn additive_node_uses_parameter_weight() {
+        let (mut graph, clips) = clip_graph(2);
+        let additive = graph.add_additive(
+            clips[0],
+            clips[1],
+            AnimationGraphWeight::Parameter("lean".into()),
+        );
+        graph.set_root(additive).add_parameter("lean", 0.25);
+
+        let evaluated = evaluate(&graph, &[]);
+        assert_eq!(evaluated.len(), 2);
+        assert_eq!(evaluated[0].blend_mode, AnimationBlendMode::Override);
+        assert_eq!(evaluated[1].factor, 0.25);
+        assert_eq!(
+            evaluated[1].blend_mode,
+            AnimationBlendMode::Additive {
+                reference_time: 0.0
+            }
+        );
+
+        assert_eq!(evaluate(&graph, &[("lean", 0.0)]).len(), 1);
+    }
+}
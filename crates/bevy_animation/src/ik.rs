@@ -0,0 +1,608 @@
+//! Inverse kinematics constraints, applied on top of the sampled animation.
+//!
+//! The constraints in this module rotate the [`Transform`]s of a chain of
+//! joints so that the end of the chain reaches a target, which is useful for
+//! foot placement, grabbing, or aiming. They are evaluated by [`solve_ik`],
+//! which runs after [`animate_targets`](crate::animate_targets) and before
+//! [`GlobalTransform`](bevy_transform::prelude::GlobalTransform) propagation,
+//! so they always see the pose of the current frame.
+
+use bevy_ecs::entity::MapEntities;
+use bevy_ecs::prelude::*;
+use bevy_ecs::reflect::ReflectMapEntities;
+use bevy_hierarchy::Parent;
+use bevy_math::{Quat, Vec3};
+use bevy_reflect::Reflect;
+use bevy_transform::prelude::Transform;
+
+/// Below this length, directions are considered degenerate and ignored.
+const EPSILON: f32 = 1e-5;
+
+/// Analytically solves a chain of two bones (e.g. a thigh and a shin) so that
+/// the end effector (e.g. a foot) reaches a target.
+///
+/// This component goes on the end effector: its parent is the middle joint
+/// (e.g. the knee) and its grandparent is the root joint (e.g. the hip) of
+/// the chain. Only the root and middle joints are rotated.
+#[derive(Component, Reflect, Clone, Debug)]
+#[reflect(Component, MapEntities)]
+pub struct TwoBoneIk {
+    /// The entity the end effector should reach.
+    pub target: Entity,
+    /// An optional entity the middle joint bends towards (e.g. in front of
+    /// the knee).
+    ///
+    /// Without a pole, the chain keeps bending in the plane it's currently in.
+    pub pole: Option<Entity>,
+    /// An optional direction the middle joint bends towards, in the space of
+    /// the parent of the root joint (e.g. forward for a knee).
+    ///
+    /// This is used when [`TwoBoneIk::pole`] isn't set, and saves spawning an
+    /// entity when the bending direction is fixed relative to the character.
+    pub pole_vector: Option<Vec3>,
+    /// How much the solution overrides the animated pose, from `0.0` to `1.0`.
+    pub weight: f32,
+}
+
+impl TwoBoneIk {
+    /// Creates a new [`TwoBoneIk`] reaching for `target` with full weight.
+    pub fn new(target: Entity) -> Self {
+        Self {
+            target,
+            pole: None,
+            pole_vector: None,
+            weight: 1.0,
+        }
+    }
+
+    /// Sets the [`TwoBoneIk::pole`] the middle joint bends towards.
+    pub fn with_pole(mut self, pole: Entity) -> Self {
+        self.pole = Some(pole);
+        self
+    }
+
+    /// Sets the [`TwoBoneIk::pole_vector`] the middle joint bends towards.
+    pub fn with_pole_vector(mut self, pole_vector: Vec3) -> Self {
+        self.pole_vector = Some(pole_vector);
+        self
+    }
+}
+
+/// The algorithm used to solve an [`IkChain`].
+#[derive(Reflect, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum IkSolver {
+    /// Forward And Backward Reaching Inverse Kinematics.
+    ///
+    /// Distributes the rotation smoothly along the chain. Well suited for
+    /// tails, tentacles and spines.
+    #[default]
+    Fabrik,
+    /// Cyclic Coordinate Descent.
+    ///
+    /// Rotates the joints closest to the end effector the most. Well suited
+    /// for fingers and mechanical arms.
+    Ccd,
+}
+
+/// Iteratively solves a chain of an arbitrary number of bones so that the end
+/// effector reaches a target.
+///
+/// This component goes on the end effector. The chain is made of its
+/// `joint_count` closest ancestors, which are the joints that get rotated.
+#[derive(Component, Reflect, Clone, Debug)]
+#[reflect(Component, MapEntities)]
+pub struct IkChain {
+    /// The entity the end effector should reach.
+    pub target: Entity,
+    /// The number of ancestors of the end effector that are part of the chain.
+    pub joint_count: usize,
+    /// The algorithm used to solve the chain.
+    pub solver: IkSolver,
+    /// The maximum number of iterations of the solver.
+    pub iterations: u32,
+    /// The distance to the target under which the chain is considered solved.
+    pub tolerance: f32,
+    /// How much the solution overrides the animated pose, from `0.0` to `1.0`.
+    pub weight: f32,
+}
+
+impl IkChain {
+    /// Creates a new [`IkChain`] of `joint_count` joints reaching for
+    /// `target` with the given `solver`.
+    pub fn new(target: Entity, joint_count: usize, solver: IkSolver) -> Self {
+        Self {
+            target,
+            joint_count,
+            solver,
+            iterations: 10,
+            tolerance: 0.001,
+            weight: 1.0,
+        }
+    }
+}
+
+/// Rotates a joint so that one of its local axes points towards a target,
+/// e.g. to make a head look at something or to aim a weapon.
+#[derive(Component, Reflect, Clone, Debug)]
+#[reflect(Component, MapEntities)]
+pub struct LookAtConstraint {
+    /// The entity to look at.
+    pub target: Entity,
+    /// The local axis of the joint that should point towards the target.
+    pub forward: Vec3,
+    /// The maximum angle, in radians, the joint is allowed to rotate away
+    /// from its animated pose.
+    pub max_angle: f32,
+    /// How much the constraint overrides the animated pose, from `0.0` to `1.0`.
+    pub weight: f32,
+}
+
+impl LookAtConstraint {
+    /// Creates a new [`LookAtConstraint`] pointing the local
+    /// [`Transform::forward`] axis (-Z) towards `target`.
+    pub fn new(target: Entity) -> Self {
+        Self {
+            target,
+            forward: Vec3::NEG_Z,
+            max_angle: std::f32::consts::PI,
+            weight: 1.0,
+        }
+    }
+}
+
+impl MapEntities for TwoBoneIk {
+    fn map_entities<M: EntityMapper>(&mut self, entity_mapper: &mut M) {
+        self.target = entity_mapper.map_entity(self.target);
+        self.pole = self.pole.map(|pole| entity_mapper.map_entity(pole));
+    }
+}
+
+impl MapEntities for IkChain {
+    fn map_entities<M: EntityMapper>(&mut self, entity_mapper: &mut M) {
+        self.target = entity_mapper.map_entity(self.target);
+    }
+}
+
+impl MapEntities for LookAtConstraint {
+    fn map_entities<M: EntityMapper>(&mut self, entity_mapper: &mut M) {
+        self.target = entity_mapper.map_entity(self.target);
+    }
+}
+
+/// A system that applies the [`IkChain`], [`TwoBoneIk`] and
+/// [`LookAtConstraint`] constraints, in that order.
+///
+/// Global transforms are recomputed from the local [`Transform`]s of the
+/// hierarchy, so constraints see the effects of the constraints evaluated
+/// before them.
+pub fn solve_ik(
+    chains: Query<(Entity, &IkChain)>,
+    two_bones: Query<(Entity, &TwoBoneIk)>,
+    look_ats: Query<(Entity, &LookAtConstraint)>,
+    parents: Query<&Parent>,
+    mut transforms: Query<&mut Transform>,
+) {
+    for (entity, chain) in &chains {
+        let Some(target) = global_transform(chain.target, &parents, &transforms) else {
+            continue;
+        };
+        let Some(mut joints) = JointChain::new(entity, chain.joint_count, &parents, &transforms)
+        else {
+            continue;
+        };
+        match chain.solver {
+            IkSolver::Fabrik => joints.solve_fabrik(target.translation, chain),
+            IkSolver::Ccd => joints.solve_ccd(target.translation, chain),
+        }
+        joints.write_back(chain.weight, &mut transforms);
+    }
+
+    for (entity, two_bone) in &two_bones {
+        let Some(target) = global_transform(two_bone.target, &parents, &transforms) else {
+            continue;
+        };
+        let Some(mut joints) = JointChain::new(entity, 2, &parents, &transforms) else {
+            continue;
+        };
+        let pole = match two_bone.pole {
+            Some(pole) => {
+                global_transform(pole, &parents, &transforms).map(|pole| pole.translation)
+            }
+            None => two_bone.pole_vector.map(|pole_vector| {
+                joints.globals[1].translation + joints.parent_global.rotation * pole_vector
+            }),
+        };
+        joints.solve_two_bone(target.translation, pole);
+        joints.write_back(two_bone.weight, &mut transforms);
+    }
+
+    for (entity, look_at) in &look_ats {
+        let Some(target) = global_transform(look_at.target, &parents, &transforms) else {
+            continue;
+        };
+        let Some(mut joints) = JointChain::new(entity, 0, &parents, &transforms) else {
+            continue;
+        };
+        let global = joints.globals[0];
+        let (Some(from), Some(to)) = (
+            (global.rotation * look_at.forward).try_normalize(),
+            (target.translation - global.translation).try_normalize(),
+        ) else {
+            continue;
+        };
+        let mut rotation = Quat::from_rotation_arc(from, to);
+        let angle = rotation.angle_between(Quat::IDENTITY);
+        if angle > look_at.max_angle {
+            rotation = Quat::IDENTITY.slerp(rotation, look_at.max_angle / angle);
+        }
+        joints.rotate_joint(0, rotation);
+        joints.write_back(look_at.weight, &mut transforms);
+    }
+}
+
+/// Computes the global transform of `entity` from the local [`Transform`]s of
+/// its ancestors.
+///
+/// This is used instead of [`GlobalTransform`](bevy_transform::prelude::GlobalTransform),
+/// which hasn't been propagated yet for the current frame.
+pub(crate) fn global_transform(
+    entity: Entity,
+    parents: &Query<&Parent>,
+    transforms: &Query<&mut Transform>,
+) -> Option<Transform> {
+    let mut global = *transforms.get(entity).ok()?;
+    let mut current = entity;
+    while let Ok(parent) = parents.get(current) {
+        current = parent.get();
+        let Ok(parent_transform) = transforms.get(current) else {
+            break;
+        };
+        global = parent_transform.mul_transform(global);
+    }
+    Some(global)
+}
+
+/// The joints of a chain, from the root joint to the end effector.
+struct JointChain {
+    entities: Vec<Entity>,
+    /// The global transform of the parent of the root joint.
+    parent_global: Transform,
+    /// The animated local transforms, used for blending.
+    original: Vec<Transform>,
+    locals: Vec<Transform>,
+    globals: Vec<Transform>,
+}
+
+impl JointChain {
+    /// Collects `joint_count` ancestors of `end` and `end` itself.
+    fn new(
+        end: Entity,
+        joint_count: usize,
+        parents: &Query<&Parent>,
+        transforms: &Query<&mut Transform>,
+    ) -> Option<Self> {
+        let mut entities = vec![end];
+        for _ in 0..joint_count {
+            let parent = parents.get(*entities.last().unwrap()).ok()?.get();
+            entities.push(parent);
+        }
+        entities.reverse();
+
+        let parent_global = parents
+            .get(entities[0])
+            .ok()
+            .and_then(|parent| global_transform(parent.get(), parents, transforms))
+            .unwrap_or_default();
+        let locals = entities
+            .iter()
+            .map(|&entity| transforms.get(entity).ok().copied())
+            .collect::<Option<Vec<_>>>()?;
+
+        let mut chain = Self {
+            entities,
+            parent_global,
+            original: locals.clone(),
+            locals,
+            globals: Vec::new(),
+        };
+        chain.update_globals();
+        Some(chain)
+    }
+
+    fn update_globals(&mut self) {
+        self.globals.clear();
+        let mut parent = self.parent_global;
+        for local in &self.locals {
+            parent = parent.mul_transform(*local);
+            self.globals.push(parent);
+        }
+    }
+
+    fn positions(&self) -> Vec<Vec3> {
+        self.globals
+            .iter()
+            .map(|global| global.translation)
+            .collect()
+    }
+
+    /// Applies a rotation expressed in world space to the joint at `index`.
+    fn rotate_joint(&mut self, index: usize, rotation: Quat) {
+        let parent_rotation = match index {
+            0 => self.parent_global.rotation,
+            _ => self.globals[index - 1].rotation,
+        };
+        let global_rotation = rotation * self.globals[index].rotation;
+        self.locals[index].rotation = (parent_rotation.inverse() * global_rotation).normalize();
+        self.update_globals();
+    }
+
+    /// Rotates the joint at `index` so that the direction towards `from`
+    /// points towards `to`.
+    fn rotate_joint_towards(&mut self, index: usize, from: Vec3, to: Vec3) {
+        let origin = self.globals[index].translation;
+        let (Some(from), Some(to)) = (
+            (from - origin).try_normalize(),
+            (to - origin).try_normalize(),
+        ) else {
+            return;
+        };
+        self.rotate_joint(index, Quat::from_rotation_arc(from, to));
+    }
+
+    fn solve_two_bone(&mut self, target: Vec3, pole: Option<Vec3>) {
+        let [a, b, c] = [
+            self.globals[0].translation,
+            self.globals[1].translation,
+            self.globals[2].translation,
+        ];
+        let length_ab = a.distance(b);
+        let length_bc = b.distance(c);
+        if length_ab < EPSILON || length_bc < EPSILON {
+            return;
+        }
+        let length_at = a
+            .distance(target)
+            .clamp(EPSILON, length_ab + length_bc - EPSILON);
+
+        // Bend the chain in the plane it's currently in, so that the distance
+        // between the root and the end effector matches the target distance.
+        // A straight chain has no plane, so pick one from the pole instead.
+        let axis = (c - a)
+            .cross(b - a)
+            .try_normalize()
+            .or_else(|| pole.and_then(|pole| (c - a).cross(pole - a).try_normalize()))
+            .unwrap_or_else(|| (c - a).any_orthonormal_vector());
+
+        let angle_between = |u: Vec3, v: Vec3| {
+            u.normalize_or_zero()
+                .dot(v.normalize_or_zero())
+                .clamp(-1.0, 1.0)
+                .acos()
+        };
+        let law_of_cosines = |adjacent_1: f32, adjacent_2: f32, opposite: f32| {
+            ((adjacent_1 * adjacent_1 + adjacent_2 * adjacent_2 - opposite * opposite)
+                / (2.0 * adjacent_1 * adjacent_2))
+                .clamp(-1.0, 1.0)
+                .acos()
+        };
+        let ac_ab_current = angle_between(c - a, b - a);
+        let ba_bc_current = angle_between(a - b, c - b);
+        let ac_ab_desired = law_of_cosines(length_ab, length_at, length_bc);
+        let ba_bc_desired = law_of_cosines(length_ab, length_bc, length_at);
+
+        self.rotate_joint(
+            0,
+            Quat::from_axis_angle(axis, ac_ab_desired - ac_ab_current),
+        );
+        self.rotate_joint(
+            1,
+            Quat::from_axis_angle(axis, ba_bc_desired - ba_bc_current),
+        );
+
+        // Swing the whole chain towards the target.
+        self.rotate_joint_towards(0, self.globals[2].translation, target);
+
+        // Twist the chain around the root-to-target axis so that the middle
+        // joint points towards the pole.
+        if let Some(pole) = pole {
+            let Some(twist_axis) = (target - a).try_normalize() else {
+                return;
+            };
+            let project = |point: Vec3| {
+                let direction = point - a;
+                (direction - twist_axis * direction.dot(twist_axis)).try_normalize()
+            };
+            if let (Some(from), Some(to)) = (project(self.globals[1].translation), project(pole)) {
+                self.rotate_joint(0, Quat::from_rotation_arc(from, to));
+            }
+        }
+    }
+
+    fn solve_fabrik(&mut self, target: Vec3, chain: &IkChain) {
+        let end = self.entities.len() - 1;
+        let mut positions = self.positions();
+        let lengths: Vec<f32> = positions
+            .windows(2)
+            .map(|pair| pair[0].distance(pair[1]))
+            .collect();
+        let root = positions[0];
+
+        if root.distance(target) >= lengths.iter().sum::<f32>() {
+            // The target is out of reach: stretch the chain towards it.
+            for i in 0..end {
+                let direction = (target - positions[i]).normalize_or_zero();
+                positions[i + 1] = positions[i] + direction * lengths[i];
+            }
+        } else {
+            for _ in 0..chain.iterations {
+                if positions[end].distance(target) <= chain.tolerance {
+                    break;
+                }
+                // Backward pass: pin the end effector to the target.
+                positions[end] = target;
+                for i in (0..end).rev() {
+                    let direction = (positions[i] - positions[i + 1]).normalize_or_zero();
+                    positions[i] = positions[i + 1] + direction * lengths[i];
+                }
+                // Forward pass: pin the root back to its original position.
+                positions[0] = root;
+                for i in 0..end {
+                    let direction = (positions[i + 1] - positions[i]).normalize_or_zero();
+                    positions[i + 1] = positions[i] + direction * lengths[i];
+                }
+            }
+        }
+
+        for i in 0..end {
+            self.rotate_joint_towards(i, self.globals[i + 1].translation, positions[i + 1]);
+        }
+    }
+
+    fn solve_ccd(&mut self, target: Vec3, chain: &IkChain) {
+        let end = self.entities.len() - 1;
+        for _ in 0..chain.iterations {
+            if self.globals[end].translation.distance(target) <= chain.tolerance {
+                break;
+            }
+            for i in (0..end).rev() {
+                self.rotate_joint_towards(i, self.globals[end].translation, target);
+            }
+        }
+    }
+
+    /// Writes the solved rotations back, blended with the animated pose.
+    fn write_back(&self, weight: f32, transforms: &mut Query<&mut Transform>) {
+        let weight = weight.clamp(0.0, 1.0);
+        if weight <= 0.0 {
+            return;
+        }
+        for ((entity, original), solved) in
+            self.entities.iter().zip(&self.original).zip(&self.locals)
+        {
+            if let Ok(mut transform) = transforms.get_mut(*entity) {
+                transform.rotation = original.rotation.slerp(solved.rotation, weight);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_ecs::system::RunSystemOnce;
+    use bevy_hierarchy::BuildWorldChildren;
+
+    /// Spawns a vertical chain of `bone_count` bones of length 1, returning
+    /// the entities from the root to the end effector.
+    fn spawn_chain(world: &mut World, bone_count: usize) -> Vec<Entity> {
+        let mut entities = vec![world.spawn(Transform::default()).id()];
+        for _ in 0..bone_count {
+            let parent = *entities.last().unwrap();
+            let child = world.spawn(Transform::from_xyz(0.0, 1.0, 0.0)).id();
+            world.entity_mut(parent).add_child(child);
+            entities.push(child);
+        }
+        entities
+    }
+
+    fn global_translation(world: &mut World, entity: Entity) -> Vec3 {
+        world.run_system_once(
+            move |parents: Query<&Parent>, transforms: Query<&mut Transform>| {
+                global_transform(entity, &parents, &transforms)
+                    .unwrap()
+                    .translation
+            },
+        )
+    }
+
+    #[test]
+    fn two_bone_reaches_target() {
+        let mut world = World::new();
+        let joints = spawn_chain(&mut world, 2);
+        let target = world.spawn(Transform::from_xyz(1.0, 1.0, 0.0)).id();
+        let pole = world.spawn(Transform::from_xyz(0.0, 1.0, 5.0)).id();
+        world
+            .entity_mut(joints[2])
+            .insert(TwoBoneIk::new(target).with_pole(pole));
+
+        world.run_system_once(solve_ik);
+
+        let end = global_translation(&mut world, joints[2]);
+        assert!(end.distance(Vec3::new(1.0, 1.0, 0.0)) < 1e-3, "{end}");
+        // The knee bends towards the pole.
+        let knee = global_translation(&mut world, joints[1]);
+        assert!(knee.z > 0.1, "{knee}");
+    }
+
+    #[test]
+    fn two_bone_bends_towards_pole_vector() {
+        let mut world = World::new();
+        let joints = spawn_chain(&mut world, 2);
+        let target = world.spawn(Transform::from_xyz(1.0, 1.0, 0.0)).id();
+        world
+            .entity_mut(joints[2])
+            .insert(TwoBoneIk::new(target).with_pole_vector(Vec3::NEG_Z));
+
+        world.run_system_once(solve_ik);
+
+        let end = global_translation(&mut world, joints[2]);
+        assert!(end.distance(Vec3::new(1.0, 1.0, 0.0)) < 1e-3, "{end}");
+        let knee = global_translation(&mut world, joints[1]);
+        assert!(knee.z < -0.1, "{knee}");
+    }
+
+    #[test]
+    fn two_bone_stretches_towards_unreachable_target() {
+        let mut world = World::new();
+        let joints = spawn_chain(&mut world, 2);
+        let target = world.spawn(Transform::from_xyz(10.0, 0.0, 0.0)).id();
+        world.entity_mut(joints[2]).insert(TwoBoneIk::new(target));
+
+        world.run_system_once(solve_ik);
+
+        let end = global_translation(&mut world, joints[2]);
+        assert!(end.distance(Vec3::new(2.0, 0.0, 0.0)) < 1e-2, "{end}");
+    }
+
+    #[test]
+    fn chain_solvers_reach_target() {
+        for solver in [IkSolver::Fabrik, IkSolver::Ccd] {
+            let mut world = World::new();
+            let joints = spawn_chain(&mut world, 4);
+            let target = world.spawn(Transform::from_xyz(2.0, 2.0, 0.5)).id();
+            let mut chain = IkChain::new(target, 4, solver);
+            chain.iterations = 50;
+            world.entity_mut(joints[4]).insert(chain);
+
+            world.run_system_once(solve_ik);
+
+            let end = global_translation(&mut world, joints[4]);
+            assert!(
+                end.distance(Vec3::new(2.0, 2.0, 0.5)) < 1e-2,
+                "{solver:?}: {end}"
+            );
+            // Bone lengths are preserved.
+            let root = global_translation(&mut world, joints[0]);
+            let first = global_translation(&mut world, joints[1]);
+            assert!((root.distance(first) - 1.0).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn look_at_respects_weight_and_max_angle() {
+        let mut world = World::new();
+        let head = world.spawn(Transform::default()).id();
+        let target = world.spawn(Transform::from_xyz(1.0, 0.0, 0.0)).id();
+        let mut look_at = LookAtConstraint::new(target);
+        look_at.max_angle = std::f32::consts::FRAC_PI_4;
+        world.entity_mut(head).insert(look_at);
+
+        world.run_system_once(solve_ik);
+
+        let rotation = world.get::<Transform>(head).unwrap().rotation;
+        let angle = rotation.angle_between(Quat::IDENTITY);
+        assert!((angle - std::f32::consts::FRAC_PI_4).abs() < 1e-4);
+        let forward = rotation * Vec3::NEG_Z;
+        assert!(forward.x > 0.0);
+    }
+}
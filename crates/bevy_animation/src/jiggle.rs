@@ -0,0 +1,298 @@
+//! Procedural secondary motion, such as hair, tails or cloth flaps swinging
+//! as the character moves.
+//!
+//! A [`JiggleBone`] simulates the tip of a bone as a damped spring pulled
+//! towards its animated position, and rotates the bone towards the simulated
+//! tip. Bones are simulated by [`simulate_jiggle_bones`] after animation
+//! sampling and inverse kinematics, parents before their children, so chains
+//! of jiggle bones swing naturally.
+
+use std::f32::consts::PI;
+
+use bevy_ecs::prelude::*;
+use bevy_hierarchy::{HierarchyQueryExt, Parent};
+use bevy_math::{Quat, Vec3};
+use bevy_reflect::Reflect;
+use bevy_time::Time;
+use bevy_transform::prelude::Transform;
+
+use crate::ik::global_transform;
+
+/// The longest time step of the simulation, in seconds.
+///
+/// Longer frames are split in several steps to keep stiff springs stable.
+const MAX_STEP: f32 = 1.0 / 120.0;
+/// The maximum number of steps simulated in a frame.
+const MAX_STEPS: u32 = 8;
+/// Below this length, directions are considered degenerate and ignored.
+const EPSILON: f32 = 1e-5;
+
+/// Applies damped-spring dynamics to the rotation of a bone.
+#[derive(Component, Reflect, Clone, Debug)]
+#[reflect(Component)]
+pub struct JiggleBone {
+    /// The tip of the bone, in the local space of the bone.
+    ///
+    /// This is the point that lags behind the animation. It's typically the
+    /// translation of the child bone.
+    pub tip: Vec3,
+    /// How strongly the tip is pulled towards its animated position.
+    pub stiffness: f32,
+    /// How quickly the motion of the tip slows down.
+    pub damping: f32,
+    /// An acceleration applied to the tip, in world space.
+    pub gravity: Vec3,
+    /// The maximum angle between the animated and the simulated directions
+    /// of the bone, in radians.
+    pub max_angle: f32,
+    /// How much the simulation overrides the animated pose, from `0.0` to
+    /// `1.0`.
+    pub weight: f32,
+    #[reflect(ignore)]
+    state: Option<JiggleState>,
+    #[reflect(ignore)]
+    pose: Option<JigglePose>,
+}
+
+/// The local rotation of a [`JiggleBone`] before and after the simulation
+/// last rotated it.
+///
+/// Bones which aren't animated keep the rotation written by the simulation,
+/// which is replaced by the rest rotation before simulating the next frame,
+/// so that the rotations don't accumulate.
+#[derive(Clone, Copy, Debug)]
+struct JigglePose {
+    rest: Quat,
+    simulated: Quat,
+}
+
+/// The simulated tip of a [`JiggleBone`], in world space.
+#[derive(Clone, Copy, Debug)]
+struct JiggleState {
+    position: Vec3,
+    velocity: Vec3,
+}
+
+impl Default for JiggleBone {
+    fn default() -> Self {
+        Self {
+            tip: Vec3::Y,
+            stiffness: 100.0,
+            damping: 10.0,
+            gravity: Vec3::ZERO,
+            max_angle: PI,
+            weight: 1.0,
+            state: None,
+            pose: None,
+        }
+    }
+}
+
+impl JiggleBone {
+    /// Creates a jiggle bone whose tip is at `tip` in the local space of the
+    /// bone.
+    pub fn new(tip: Vec3) -> Self {
+        Self {
+            tip,
+            ..Default::default()
+        }
+    }
+
+    /// Sets the [`JiggleBone::stiffness`] and [`JiggleBone::damping`] of the
+    /// spring.
+    pub fn with_spring(mut self, stiffness: f32, damping: f32) -> Self {
+        self.stiffness = stiffness;
+        self.damping = damping;
+        self
+    }
+
+    /// Sets the [`JiggleBone::gravity`] applied to the tip.
+    pub fn with_gravity(mut self, gravity: Vec3) -> Self {
+        self.gravity = gravity;
+        self
+    }
+
+    /// Sets the [`JiggleBone::max_angle`] of the bone.
+    pub fn with_max_angle(mut self, max_angle: f32) -> Self {
+        self.max_angle = max_angle;
+        self
+    }
+
+    /// Restarts the simulation from the animated pose, e.g. after teleporting
+    /// the character.
+    pub fn reset(&mut self) {
+        self.state = None;
+    }
+
+    /// Advances the simulation by `delta` seconds, and returns the rotation to
+    /// apply to the bone in world space.
+    fn simulate(&mut self, global: &Transform, delta: f32) -> Option<Quat> {
+        let origin = global.translation;
+        let animated_tip = global.transform_point(self.tip);
+        let length = animated_tip.distance(origin);
+        let animated_direction = (animated_tip - origin).try_normalize()?;
+
+        let state = self.state.get_or_insert(JiggleState {
+            position: animated_tip,
+            velocity: Vec3::ZERO,
+        });
+        let steps = ((delta / MAX_STEP).ceil() as u32).clamp(1, MAX_STEPS);
+        let step = delta / steps as f32;
+        for _ in 0..steps {
+            let acceleration = (animated_tip - state.position) * self.stiffness
+                - state.velocity * self.damping
+                + self.gravity;
+            state.velocity += acceleration * step;
+            state.position += state.velocity * step;
+        }
+
+        // The bone doesn't stretch: keep the tip at the length of the bone.
+        let direction = (state.position - origin)
+            .try_normalize()
+            .unwrap_or(animated_direction);
+        let mut rotation = Quat::from_rotation_arc(animated_direction, direction);
+        let angle = rotation.angle_between(Quat::IDENTITY);
+        if angle > self.max_angle && angle > EPSILON {
+            rotation = Quat::IDENTITY.slerp(rotation, self.max_angle / angle);
+        }
+        state.position = origin + rotation * animated_direction * length;
+
+        Some(Quat::IDENTITY.slerp(rotation, self.weight.clamp(0.0, 1.0)))
+    }
+}
+
+/// Simulates the [`JiggleBone`]s and rotates their [`Transform`]s.
+///
+/// Global transforms are recomputed from the local [`Transform`]s of the
+/// hierarchy, so children see the motion of their jiggling parents.
+pub fn simulate_jiggle_bones(
+    time: Res<Time>,
+    mut bones: Query<(Entity, &mut JiggleBone)>,
+    parents: Query<&Parent>,
+    mut transforms: Query<&mut Transform>,
+) {
+    let delta = time.delta_seconds();
+    if delta <= 0.0 {
+        return;
+    }
+
+    // Simulate parents before their children.
+    let mut order: Vec<(usize, Entity)> = bones
+        .iter()
+        .map(|(entity, _)| (parents.iter_ancestors(entity).count(), entity))
+        .collect();
+    order.sort_unstable_by_key(|(depth, _)| *depth);
+
+    for (_, entity) in order {
+        let Ok((_, mut bone)) = bones.get_mut(entity) else {
+            continue;
+        };
+        let Ok(mut transform) = transforms.get_mut(entity) else {
+            continue;
+        };
+        // Unless an animation set a new pose since the last frame, simulate
+        // from the rest rotation rather than the previously simulated one.
+        let rest = match bone.pose {
+            Some(pose) if transform.rotation == pose.simulated => pose.rest,
+            _ => transform.rotation,
+        };
+        transform.rotation = rest;
+
+        let Some(global) = global_transform(entity, &parents, &transforms) else {
+            continue;
+        };
+        let Some(rotation) = bone.simulate(&global, delta) else {
+            continue;
+        };
+        let Ok(mut transform) = transforms.get_mut(entity) else {
+            continue;
+        };
+        // Convert the world space rotation to the local space of the parent.
+        let parent_rotation = global.rotation * rest.inverse();
+        transform.rotation = (parent_rotation.inverse() * rotation * global.rotation).normalize();
+        bone.pose = Some(JigglePose {
+            rest,
+            simulated: transform.rotation,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_ecs::system::RunSystemOnce;
+    use std::time::Duration;
+
+    fn step(world: &mut World) {
+        world
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs_f32(1.0 / 60.0));
+        world.run_system_once(simulate_jiggle_bones);
+    }
+
+    #[test]
+    fn jiggle_bones_follow_the_animation_when_at_rest() {
+        let mut world = World::new();
+        world.init_resource::<Time>();
+        let bone = world
+            .spawn((Transform::default(), JiggleBone::new(Vec3::Y)))
+            .id();
+        for _ in 0..10 {
+            step(&mut world);
+        }
+        let rotation = world.get::<Transform>(bone).unwrap().rotation;
+        assert!(rotation.abs_diff_eq(Quat::IDENTITY, 1e-5));
+    }
+
+    #[test]
+    fn jiggle_bones_sag_under_gravity_within_limits() {
+        let mut world = World::new();
+        world.init_resource::<Time>();
+        let bone = world
+            .spawn((
+                Transform::default(),
+                JiggleBone::new(Vec3::X)
+                    .with_gravity(Vec3::NEG_Y * 50.0)
+                    .with_max_angle(0.3),
+            ))
+            .id();
+        for _ in 0..60 {
+            // The animation resets the pose every frame.
+            *world.get_mut::<Transform>(bone).unwrap() = Transform::default();
+            step(&mut world);
+        }
+        let transform = world.get::<Transform>(bone).unwrap();
+        let tip = transform.rotation * Vec3::X;
+        assert!(tip.y < -0.1, "{tip}");
+        assert!(transform.rotation.angle_between(Quat::IDENTITY) <= 0.3 + 1e-4);
+    }
+
+    #[test]
+    fn unanimated_jiggle_bones_settle_back_to_rest() {
+        let mut world = World::new();
+        world.init_resource::<Time>();
+        let rest = Quat::from_rotation_z(0.5);
+        let bone = world
+            .spawn((
+                Transform::from_rotation(rest),
+                JiggleBone::new(Vec3::X)
+                    .with_gravity(Vec3::NEG_Y * 50.0)
+                    .with_max_angle(0.3),
+            ))
+            .id();
+
+        // Nothing resets the pose, so the rotations must not accumulate.
+        for _ in 0..120 {
+            step(&mut world);
+            let rotation = world.get::<Transform>(bone).unwrap().rotation;
+            assert!(rotation.angle_between(rest) <= 0.3 + 1e-4);
+        }
+
+        world.get_mut::<JiggleBone>(bone).unwrap().gravity = Vec3::ZERO;
+        for _ in 0..300 {
+            step(&mut world);
+        }
+        let rotation = world.get::<Transform>(bone).unwrap().rotation;
+        assert!(rotation.abs_diff_eq(rest, 1e-3), "{rotation}");
+    }
+}
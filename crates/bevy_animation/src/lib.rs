@@ -1,6 +1,15 @@
 //! Animation for the game engine Bevy
 
 mod animatable;
+pub mod compression;
+pub mod graph;
+pub mod ik;
+pub mod jiggle;
+pub mod lod;
+pub mod property;
+pub mod retarget;
+pub mod root_motion;
+pub mod tween;
 mod util;
 
 use std::hash::{Hash, Hasher};
@@ -9,11 +18,12 @@ use std::ops::{Add, Mul};
 use std::time::Duration;
 
 use bevy_app::{App, Plugin, PostUpdate};
-use bevy_asset::{Asset, AssetApp, Assets, Handle};
+use bevy_asset::{Asset, AssetApp, AssetId, Assets, Handle};
 use bevy_core::Name;
 use bevy_ecs::entity::MapEntities;
 use bevy_ecs::prelude::*;
 use bevy_ecs::reflect::ReflectMapEntities;
+use bevy_hierarchy::{Children, HierarchyQueryExt};
 use bevy_log::error;
 use bevy_math::{FloatExt, Quat, Vec3};
 use bevy_reflect::Reflect;
@@ -22,14 +32,29 @@ use bevy_time::Time;
 use bevy_transform::{prelude::Transform, TransformSystem};
 use bevy_utils::hashbrown::HashMap;
 use bevy_utils::{NoOpHash, Uuid};
+use compression::QuantizedQuat;
+use graph::{AnimationGraph, AnimationGraphState};
+use lod::{AnimationLod, AnimationLodPose};
+use property::PropertyCurve;
+use root_motion::RootMotion;
 use sha1_smol::Sha1;
+use tween::TweenApp;
 
 #[allow(missing_docs)]
 pub mod prelude {
     #[doc(hidden)]
     pub use crate::{
-        animatable::*, AnimationClip, AnimationPlayer, AnimationPlugin, Interpolation, Keyframes,
-        VariableCurve,
+        animatable::*,
+        compression::AnimationCompressionSettings,
+        graph::{AnimationGraph, AnimationGraphWeight, ClipNode},
+        ik::{IkChain, IkSolver, LookAtConstraint, TwoBoneIk},
+        jiggle::JiggleBone,
+        lod::{AnimationLod, AnimationLodLevel},
+        property::{AnimatedProperty, PropertyCurve, PropertyKeyframes},
+        root_motion::RootMotion,
+        tween::{MorphWeightLens, Tween, TweenApp, TweenCommandsExt, TweenCompleted, Tweens},
+        AnimationBlendMode, AnimationClip, AnimationEvent, AnimationLayer, AnimationMask,
+        AnimationPlayer, AnimationPlugin, Interpolation, Keyframes, VariableCurve,
     };
 }
 
@@ -43,6 +68,11 @@ pub static ANIMATION_TARGET_NAMESPACE: Uuid = Uuid::from_u128(0x3179f519d9274ff2
 pub enum Keyframes {
     /// Keyframes for rotation.
     Rotation(Vec<Quat>),
+    /// Keyframes for rotation, quantized to save memory.
+    ///
+    /// This is produced by [`AnimationClip::compress`], and only supports
+    /// [`Interpolation::Linear`] and [`Interpolation::Step`].
+    QuantizedRotation(Vec<QuantizedQuat>),
     /// Keyframes for translation.
     Translation(Vec<Vec3>),
     /// Keyframes for scale.
@@ -65,6 +95,7 @@ impl Keyframes {
             Keyframes::Weights(vec) => vec.len(),
             Keyframes::Translation(vec) | Keyframes::Scale(vec) => vec.len(),
             Keyframes::Rotation(vec) => vec.len(),
+            Keyframes::QuantizedRotation(vec) => vec.len(),
         }
     }
 
@@ -100,9 +131,23 @@ impl VariableCurve {
     /// To be more precise, this returns [`None`] if the frame is at or past the last keyframe:
     /// we cannot get the *next* keyframe to interpolate to in that case.
     pub fn find_current_keyframe(&self, seek_time: f32) -> Option<usize> {
+        // Keyframes are usually uniformly spaced, as they are sampled at a
+        // fixed frame rate when exported: try to find the keyframe directly
+        // before falling back to a binary search.
+        let timestamps = &self.keyframe_timestamps;
+        if let [first, .., last] = timestamps[..] {
+            let guess = ((seek_time - first) / (last - first) * (timestamps.len() - 1) as f32)
+                .floor() as usize;
+            if guess < timestamps.len() - 1
+                && timestamps[guess] <= seek_time
+                && seek_time < timestamps[guess + 1]
+            {
+                return Some(guess);
+            }
+        }
+
         // An Ok(keyframe_index) result means an exact result was found by binary search
         // An Err result means the keyframe was not found, and the index is the keyframe
-        // PERF: finding the current keyframe can be optimised
         let search_result = self
             .keyframe_timestamps
             .binary_search_by(|probe| probe.partial_cmp(&seek_time).unwrap());
@@ -157,9 +202,50 @@ pub enum Interpolation {
 #[derive(Asset, Reflect, Clone, Debug, Default)]
 pub struct AnimationClip {
     curves: AnimationCurves,
+    properties: HashMap<AnimationTargetId, Vec<PropertyCurve>, NoOpHash>,
+    events: Vec<AnimationEventMarker>,
     duration: f32,
 }
 
+/// A named marker at a point in time of an [`AnimationClip`].
+///
+/// Markers sharing the same name form an event *track* (e.g. all the
+/// `footstep` markers of a walk cycle). When playback crosses a marker, an
+/// [`AnimationEvent`] is sent.
+#[derive(Reflect, Clone, Debug)]
+pub struct AnimationEventMarker {
+    /// The time of the marker inside of the clip, in seconds.
+    pub time: f32,
+    /// The name of the track this marker belongs to.
+    pub name: Name,
+}
+
+/// Sent when the playback of an [`AnimationClip`] crosses one of its
+/// [`AnimationEventMarker`]s.
+///
+/// Events are sent from [`advance_animations`], in the order the markers are
+/// crossed. Playback in reverse crosses markers from the end of the clip to
+/// its start, and a looping clip sends the events of each loop it went
+/// through. Clips that are being faded out by a transition keep sending
+/// events: use [`AnimationEvent::weight`] to ignore the ones that barely
+/// contribute to the pose.
+#[derive(Event, Clone, Debug)]
+pub struct AnimationEvent {
+    /// The entity holding the [`AnimationPlayer`] playing the clip.
+    pub player: Entity,
+    /// The clip containing the marker.
+    pub clip: AssetId<AnimationClip>,
+    /// The name of the track of the marker.
+    pub name: Name,
+    /// The time of the marker inside of the clip, in seconds.
+    pub time: f32,
+    /// The blend weight of the clip when the marker was crossed.
+    ///
+    /// This is `1.0` for the main animation of the player, and the current
+    /// transition weight for animations that are being faded out.
+    pub weight: f32,
+}
+
 /// A mapping from [`AnimationTargetId`] (e.g. bone in a skinned mesh) to the
 /// animation curves.
 pub type AnimationCurves = HashMap<AnimationTargetId, Vec<VariableCurve>, NoOpHash>;
@@ -265,6 +351,259 @@ impl AnimationClip {
             .max(*curve.keyframe_timestamps.last().unwrap_or(&0.0));
         self.curves.entry(target_id).or_default().push(curve);
     }
+
+    /// Gets the curves animating reflected properties of a single animation
+    /// target.
+    ///
+    /// Returns `None` if this clip doesn't animate properties of the target.
+    #[inline]
+    pub fn property_curves_for_target(
+        &self,
+        target_id: AnimationTargetId,
+    ) -> Option<&'_ Vec<PropertyCurve>> {
+        self.properties.get(&target_id)
+    }
+
+    /// Adds a [`PropertyCurve`] animating a reflected property of an
+    /// [`AnimationTarget`] named by an [`AnimationTargetId`].
+    ///
+    /// If the curve extends beyond the current duration of this clip, this
+    /// method lengthens this clip to include the entire time span that the
+    /// curve covers.
+    pub fn add_property_curve_to_target(
+        &mut self,
+        target_id: AnimationTargetId,
+        curve: PropertyCurve,
+    ) {
+        self.duration = self
+            .duration
+            .max(*curve.keyframe_timestamps.last().unwrap_or(&0.0));
+        self.properties.entry(target_id).or_default().push(curve);
+    }
+
+    /// The [`AnimationEventMarker`]s of this clip, sorted by time.
+    #[inline]
+    pub fn events(&self) -> &[AnimationEventMarker] {
+        &self.events
+    }
+
+    /// Iterates over the times of the markers of the event track named `name`.
+    pub fn event_track<'a>(&'a self, name: &'a str) -> impl Iterator<Item = f32> + 'a {
+        self.events
+            .iter()
+            .filter(move |marker| marker.name.as_str() == name)
+            .map(|marker| marker.time)
+    }
+
+    /// Adds a marker at `time` to the event track named `name`.
+    ///
+    /// If the marker is beyond the current duration of this clip, this method
+    /// lengthens this clip to include it.
+    pub fn add_event(&mut self, time: f32, name: impl Into<Name>) {
+        self.duration = self.duration.max(time);
+        let index = self.events.partition_point(|marker| marker.time <= time);
+        self.events.insert(
+            index,
+            AnimationEventMarker {
+                time,
+                name: name.into(),
+            },
+        );
+    }
+
+    /// Calls `on_marker` with each marker crossed when playback moves by
+    /// `travel` seconds of clip time from `seek_time`, wrapping around the
+    /// clip boundaries as needed.
+    ///
+    /// Forward playback crosses the markers in `[start, end)`, and reverse
+    /// playback the ones in `(end, start]`. A boundary of the clip is included
+    /// whenever playback reaches it, so markers placed at the very start or
+    /// end of a clip fire once per loop. Each marker fires at most
+    /// [`MAX_CROSSED_LOOPS`] times for the whole loops of the clip crossed
+    /// at once.
+    fn for_each_crossed_event(
+        &self,
+        seek_time: f32,
+        travel: f32,
+        mut on_marker: impl FnMut(&AnimationEventMarker),
+    ) {
+        if self.events.is_empty() || travel == 0.0 || travel.is_nan() || self.duration <= 0.0 {
+            return;
+        }
+
+        let mut start = seek_time.clamp(0.0, self.duration);
+        // Playback sitting on the boundary it moves towards wraps around first.
+        if travel > 0.0 && start >= self.duration {
+            start = 0.0;
+        } else if travel < 0.0 && start <= 0.0 {
+            start = self.duration;
+        }
+        let forward = travel > 0.0;
+        let distance = travel.abs();
+        let mut crossed = |range: &dyn Fn(f32) -> bool| {
+            if forward {
+                self.events
+                    .iter()
+                    .filter(|marker| range(marker.time))
+                    .for_each(&mut on_marker);
+            } else {
+                self.events
+                    .iter()
+                    .rev()
+                    .filter(|marker| range(marker.time))
+                    .for_each(&mut on_marker);
+            }
+        };
+
+        // The part of the loop before the boundary playback moves towards.
+        let to_boundary = if forward {
+            self.duration - start
+        } else {
+            start
+        };
+        if distance < to_boundary {
+            if forward {
+                crossed(&|time| time >= start && time < start + distance);
+            } else {
+                crossed(&|time| time <= start && time > start - distance);
+            }
+            return;
+        }
+        if forward {
+            crossed(&|time| time >= start);
+        } else {
+            crossed(&|time| time <= start);
+        }
+
+        // The whole loops, then the part of the last loop after the boundary.
+        let remaining = distance - to_boundary;
+        let loops = remaining.div_euclid(self.duration);
+        for _ in 0..(loops as u32).min(MAX_CROSSED_LOOPS) {
+            crossed(&|_| true);
+        }
+        let rest = remaining.rem_euclid(self.duration);
+        if forward {
+            crossed(&|time| time < rest);
+        } else {
+            crossed(&|time| time > self.duration - rest);
+        }
+    }
+}
+
+/// The maximum number of whole loops of an [`AnimationClip`] whose events
+/// fire when playback crosses them at once, e.g. after a large seek or a
+/// long frame.
+const MAX_CROSSED_LOOPS: u32 = 16;
+
+/// Restricts an animation to a subset of the animation targets (e.g. the bones
+/// of the upper body), optionally with a different weight for each target.
+///
+/// Targets that aren't part of the mask aren't affected by the animation at
+/// all. Masks are typically used on [`AnimationLayer`]s, so that e.g. an
+/// upper-body reload animation can play over a full-body running animation
+/// without fighting it.
+#[derive(Asset, Reflect, Clone, Debug, Default)]
+pub struct AnimationMask {
+    weights: HashMap<AnimationTargetId, f32, NoOpHash>,
+}
+
+impl AnimationMask {
+    /// Creates a mask that includes all the given targets with full weight.
+    pub fn from_targets(targets: impl IntoIterator<Item = AnimationTargetId>) -> Self {
+        let mut mask = Self::default();
+        for target in targets {
+            mask.add_target(target, 1.0);
+        }
+        mask
+    }
+
+    /// Includes `target` in this mask with the given weight, from `0.0` to
+    /// `1.0`.
+    pub fn add_target(&mut self, target: AnimationTargetId, weight: f32) -> &mut Self {
+        self.weights.insert(target, weight.clamp(0.0, 1.0));
+        self
+    }
+
+    /// Includes `target` in this mask with the given weight, from `0.0` to
+    /// `1.0`.
+    pub fn with_target(mut self, target: AnimationTargetId, weight: f32) -> Self {
+        self.add_target(target, weight);
+        self
+    }
+
+    /// Includes the target at the end of `path`, the names of the bones from
+    /// the root of the armature, in this mask with the given weight, from
+    /// `0.0` to `1.0`.
+    ///
+    /// See [`AnimationTargetId::from_names`].
+    pub fn add_path<'a>(
+        &mut self,
+        path: impl IntoIterator<Item = &'a Name>,
+        weight: f32,
+    ) -> &mut Self {
+        self.add_target(AnimationTargetId::from_names(path.into_iter()), weight)
+    }
+
+    /// Includes the animation target of `root` and of all of its descendants
+    /// in this mask with the given weight, from `0.0` to `1.0`.
+    ///
+    /// This is typically used to build a mask from a bone of a spawned
+    /// armature, e.g. all the bones from the spine up for an upper-body mask.
+    /// Descendants without an [`AnimationTarget`] are skipped.
+    pub fn add_hierarchy(
+        &mut self,
+        root: Entity,
+        weight: f32,
+        targets: &Query<&AnimationTarget>,
+        children: &Query<&Children>,
+    ) -> &mut Self {
+        for entity in iter::once(root).chain(children.iter_descendants(root)) {
+            if let Ok(target) = targets.get(entity) {
+                self.add_target(target.id, weight);
+            }
+        }
+        self
+    }
+
+    /// Iterates over the targets of this mask with their weights.
+    pub fn targets(&self) -> impl Iterator<Item = (AnimationTargetId, f32)> + '_ {
+        self.weights
+            .iter()
+            .map(|(target, weight)| (*target, *weight))
+    }
+
+    /// Excludes `target` from this mask.
+    pub fn remove_target(&mut self, target: AnimationTargetId) -> &mut Self {
+        self.weights.remove(&target);
+        self
+    }
+
+    /// The weight of `target` in this mask.
+    ///
+    /// Returns `0.0` for targets that aren't part of this mask.
+    #[inline]
+    pub fn weight(&self, target: AnimationTargetId) -> f32 {
+        self.weights.get(&target).copied().unwrap_or(0.0)
+    }
+}
+
+/// How an animation is combined with the animations evaluated before it.
+#[derive(Reflect, Debug, PartialEq, Copy, Clone, Default)]
+pub enum AnimationBlendMode {
+    /// The animation replaces the current pose, by its weight.
+    #[default]
+    Override,
+    /// The animation adds the difference between its current pose and its pose
+    /// at `reference_time` to the current pose, scaled by its weight.
+    ///
+    /// This is typically used for lean, recoil or breathing animations played
+    /// on top of a base locomotion animation: the clip is authored relative to
+    /// a reference frame (usually the first one), and only the deltas from
+    /// that frame are applied.
+    Additive {
+        /// The time, in seconds, of the reference pose inside of the clip.
+        reference_time: f32,
+    },
 }
 
 /// Repetition behavior of an animation.
@@ -292,6 +631,14 @@ struct PlayingAnimation {
     /// Note: This will always be in the range [0.0, animation clip duration]
     seek_time: f32,
     animation_clip: Handle<AnimationClip>,
+    /// The targets this animation is restricted to, if any.
+    mask: Option<Handle<AnimationMask>>,
+    blend_mode: AnimationBlendMode,
+    /// The timestamp inside of the animation clip before the last update.
+    previous_seek_time: f32,
+    /// How far playback moved inside of the clip during the last update, in
+    /// seconds.
+    travel: f32,
     /// Number of times the animation has completed.
     /// If the animation is playing in reverse, this increments when the animation passes the start.
     completions: u32,
@@ -305,6 +652,10 @@ impl Default for PlayingAnimation {
             elapsed: 0.0,
             seek_time: 0.0,
             animation_clip: Default::default(),
+            mask: None,
+            blend_mode: AnimationBlendMode::default(),
+            previous_seek_time: 0.0,
+            travel: 0.0,
             completions: 0,
         }
     }
@@ -324,14 +675,25 @@ impl PlayingAnimation {
     }
 
     /// Update the animation given the delta time and the duration of the clip being played.
+    ///
+    /// Returns how far playback moved inside of the clip, in seconds. This is
+    /// negative when playing in reverse.
     #[inline]
-    fn update(&mut self, delta: f32, clip_duration: f32) {
+    fn update(&mut self, delta: f32, clip_duration: f32) -> f32 {
+        self.previous_seek_time = self.seek_time;
+        self.travel = self.step(delta, clip_duration);
+        self.travel
+    }
+
+    fn step(&mut self, delta: f32, clip_duration: f32) -> f32 {
         if self.is_finished() {
-            return;
+            return 0.0;
         }
 
+        let previous_seek_time = self.seek_time;
+        let travel = delta * self.speed;
         self.elapsed += delta;
-        self.seek_time += delta * self.speed;
+        self.seek_time += travel;
 
         let over_time = self.speed > 0.0 && self.seek_time >= clip_duration;
         let under_time = self.speed < 0.0 && self.seek_time < 0.0;
@@ -340,7 +702,12 @@ impl PlayingAnimation {
             self.completions += 1;
 
             if self.is_finished() {
-                return;
+                // Playback stops at the boundary of the clip.
+                return if over_time {
+                    clip_duration - previous_seek_time
+                } else {
+                    -previous_seek_time
+                };
             }
         }
         if self.seek_time >= clip_duration {
@@ -350,6 +717,31 @@ impl PlayingAnimation {
         if self.seek_time < 0.0 {
             self.seek_time += clip_duration;
         }
+
+        travel
+    }
+
+    /// Advances the animation like [`PlayingAnimation::update`], sending an
+    /// [`AnimationEvent`] for each marker of the clip that was crossed.
+    fn advance(
+        &mut self,
+        delta: f32,
+        clip: &AnimationClip,
+        player: Entity,
+        weight: f32,
+        events: &mut EventWriter<AnimationEvent>,
+    ) {
+        let previous_seek_time = self.seek_time;
+        let travel = self.update(delta, clip.duration);
+        clip.for_each_crossed_event(previous_seek_time, travel, |marker| {
+            events.send(AnimationEvent {
+                player,
+                clip: self.animation_clip.id(),
+                name: marker.name.clone(),
+                time: marker.time,
+                weight,
+            });
+        });
     }
 
     /// Reset back to the initial state as if no time has elapsed.
@@ -357,6 +749,8 @@ impl PlayingAnimation {
         self.completions = 0;
         self.elapsed = 0.0;
         self.seek_time = 0.0;
+        self.previous_seek_time = 0.0;
+        self.travel = 0.0;
     }
 }
 
@@ -370,6 +764,129 @@ struct AnimationTransition {
     animation: PlayingAnimation,
 }
 
+/// An animation played by an [`AnimationPlayer`] on top of its main
+/// animation, usually restricted to some targets with an [`AnimationMask`].
+///
+/// Layers are blended in order, after the main animation and its transitions,
+/// each one overriding the targets it animates by its weight.
+#[derive(Default, Reflect)]
+pub struct AnimationLayer {
+    animation: PlayingAnimation,
+    weight: f32,
+}
+
+impl AnimationLayer {
+    /// Creates a new layer playing `clip` once with full weight.
+    pub fn new(clip: Handle<AnimationClip>) -> Self {
+        Self {
+            animation: PlayingAnimation {
+                animation_clip: clip,
+                ..Default::default()
+            },
+            weight: 1.0,
+        }
+    }
+
+    /// Restricts this layer to the targets of `mask`.
+    pub fn with_mask(mut self, mask: Handle<AnimationMask>) -> Self {
+        self.animation.mask = Some(mask);
+        self
+    }
+
+    /// Sets the weight of this layer.
+    pub fn with_weight(mut self, weight: f32) -> Self {
+        self.weight = weight;
+        self
+    }
+
+    /// Sets how this layer is combined with the animations below it.
+    pub fn with_blend_mode(mut self, blend_mode: AnimationBlendMode) -> Self {
+        self.animation.blend_mode = blend_mode;
+        self
+    }
+
+    /// How this layer is combined with the animations below it.
+    pub fn blend_mode(&self) -> AnimationBlendMode {
+        self.animation.blend_mode
+    }
+
+    /// Sets how this layer is combined with the animations below it.
+    pub fn set_blend_mode(&mut self, blend_mode: AnimationBlendMode) -> &mut Self {
+        self.animation.blend_mode = blend_mode;
+        self
+    }
+
+    /// Handle to the animation clip being played.
+    pub fn animation_clip(&self) -> &Handle<AnimationClip> {
+        &self.animation.animation_clip
+    }
+
+    /// The mask restricting this layer, if any.
+    pub fn mask(&self) -> Option<&Handle<AnimationMask>> {
+        self.animation.mask.as_ref()
+    }
+
+    /// Sets or clears the mask restricting this layer.
+    pub fn set_mask(&mut self, mask: Option<Handle<AnimationMask>>) -> &mut Self {
+        self.animation.mask = mask;
+        self
+    }
+
+    /// The weight this layer is blended with, from `0.0` to `1.0`.
+    pub fn weight(&self) -> f32 {
+        self.weight
+    }
+
+    /// Sets the weight this layer is blended with, from `0.0` to `1.0`.
+    pub fn set_weight(&mut self, weight: f32) -> &mut Self {
+        self.weight = weight;
+        self
+    }
+
+    /// Set the repetition behaviour of this layer.
+    pub fn set_repeat(&mut self, repeat: RepeatAnimation) -> &mut Self {
+        self.animation.repeat = repeat;
+        self
+    }
+
+    /// Sets repeat to [`RepeatAnimation::Forever`].
+    pub fn repeat(&mut self) -> &mut Self {
+        self.set_repeat(RepeatAnimation::Forever)
+    }
+
+    /// Check if this layer has finished, according to the repetition behavior.
+    pub fn is_finished(&self) -> bool {
+        self.animation.is_finished()
+    }
+
+    /// Speed of the playback of this layer.
+    pub fn speed(&self) -> f32 {
+        self.animation.speed
+    }
+
+    /// Set the speed of the playback of this layer.
+    pub fn set_speed(&mut self, speed: f32) -> &mut Self {
+        self.animation.speed = speed;
+        self
+    }
+
+    /// Seek time inside of the clip of this layer.
+    pub fn seek_time(&self) -> f32 {
+        self.animation.seek_time
+    }
+
+    /// Seek to a specific time in the clip of this layer.
+    pub fn seek_to(&mut self, seek_time: f32) -> &mut Self {
+        self.animation.seek_time = seek_time;
+        self
+    }
+
+    /// Reset this layer to its initial state, as if no time has elapsed.
+    pub fn replay(&mut self) {
+        self.animation.replay();
+    }
+}
+
 /// Animation controls
 #[derive(Component, Default, Reflect)]
 #[reflect(Component)]
@@ -378,6 +895,15 @@ pub struct AnimationPlayer {
 
     animation: PlayingAnimation,
 
+    /// The graph evaluated in place of the main animation, if any.
+    graph: Option<Handle<AnimationGraph>>,
+
+    #[reflect(ignore)]
+    graph_state: AnimationGraphState,
+
+    /// Animations blended on top of the main animation, in order.
+    layers: Vec<AnimationLayer>,
+
     // List of previous animations we're currently transitioning away from.
     // Usually this is empty, when transitioning between animations, there is
     // one entry. When another animation transition happens while a transition
@@ -400,11 +926,14 @@ struct AnimationTargetContext<'a> {
 impl AnimationPlayer {
     /// Start playing an animation, resetting state of the player.
     /// This will use a linear blending between the previous and the new animation to make a smooth transition.
+    ///
+    /// This stops the graph of the player, if any.
     pub fn start(&mut self, handle: Handle<AnimationClip>) -> &mut Self {
         self.animation = PlayingAnimation {
             animation_clip: handle,
             ..Default::default()
         };
+        self.graph = None;
 
         // We want a hard transition.
         // In case any previous transitions are still playing, stop them
@@ -415,6 +944,8 @@ impl AnimationPlayer {
 
     /// Start playing an animation, resetting state of the player.
     /// This will use a linear blending between the previous and the new animation to make a smooth transition.
+    ///
+    /// This stops the graph of the player, if any, without a transition.
     pub fn start_with_transition(
         &mut self,
         handle: Handle<AnimationClip>,
@@ -425,7 +956,15 @@ impl AnimationPlayer {
             ..Default::default()
         };
         std::mem::swap(&mut animation, &mut self.animation);
+        if self.graph.take().is_none() {
+            self.push_transition(animation, transition_duration);
+        }
 
+        self
+    }
+
+    /// Fades out `animation` over `transition_duration`.
+    fn push_transition(&mut self, animation: PlayingAnimation, transition_duration: Duration) {
         // Add the current transition. If other transitions are still ongoing,
         // this will keep those transitions running and cause a transition between
         // the output of that previous transition to the new animation.
@@ -434,8 +973,6 @@ impl AnimationPlayer {
             weight_decline_per_sec: 1.0 / transition_duration.as_secs_f32(),
             animation,
         });
-
-        self
     }
 
     /// Start playing an animation, resetting state of the player, unless the requested animation is already playing.
@@ -464,9 +1001,9 @@ impl AnimationPlayer {
         &self.animation.animation_clip
     }
 
-    /// Check if the given animation clip is being played.
+    /// Check if the given animation clip is being played, as the main animation.
     pub fn is_playing_clip(&self, handle: &Handle<AnimationClip>) -> bool {
-        self.animation_clip() == handle
+        self.graph.is_none() && self.animation_clip() == handle
     }
 
     /// Check if the playing animation has finished, according to the repetition behavior.
@@ -549,25 +1086,119 @@ impl AnimationPlayer {
     pub fn replay(&mut self) {
         self.animation.replay();
     }
+
+    /// The mask restricting the main animation, if any.
+    pub fn mask(&self) -> Option<&Handle<AnimationMask>> {
+        self.animation.mask.as_ref()
+    }
+
+    /// Restricts the main animation to the targets of `mask`, or clears the
+    /// restriction.
+    ///
+    /// Starting a new animation clears the mask.
+    pub fn set_mask(&mut self, mask: Option<Handle<AnimationMask>>) -> &mut Self {
+        self.animation.mask = mask;
+        self
+    }
+
+    /// Adds a layer blended on top of the main animation and of the previously
+    /// added layers, returning its index.
+    pub fn add_layer(&mut self, layer: AnimationLayer) -> usize {
+        self.layers.push(layer);
+        self.layers.len() - 1
+    }
+
+    /// Removes the layer at `index`, shifting the layers above it down.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no layer at `index`.
+    pub fn remove_layer(&mut self, index: usize) -> AnimationLayer {
+        self.layers.remove(index)
+    }
+
+    /// The layers blended on top of the main animation, in order.
+    pub fn layers(&self) -> &[AnimationLayer] {
+        &self.layers
+    }
+
+    /// Gets the layer at `index`.
+    pub fn layer(&self, index: usize) -> Option<&AnimationLayer> {
+        self.layers.get(index)
+    }
+
+    /// Gets the layer at `index` mutably.
+    pub fn layer_mut(&mut self, index: usize) -> Option<&mut AnimationLayer> {
+        self.layers.get_mut(index)
+    }
+
+    /// Iterates over all the animations to apply with their weights, in
+    /// blending order: the main animation or the clips of the graph, the
+    /// transitions, then the layers.
+    fn playing_animations(&self) -> impl Iterator<Item = (&PlayingAnimation, f32)> {
+        let graph_clips = match self.graph {
+            Some(_) => &self.graph_state.clips[..],
+            None => &[],
+        };
+        self.graph
+            .is_none()
+            .then_some((&self.animation, 1.0))
+            .into_iter()
+            .chain(
+                graph_clips.iter().map(|graph_clip| {
+                    (&self.graph_state.nodes[graph_clip.node], graph_clip.factor)
+                }),
+            )
+            .chain(
+                self.transitions
+                    .iter()
+                    .map(|transition| (&transition.animation, transition.current_weight)),
+            )
+            .chain(
+                self.layers
+                    .iter()
+                    .map(|layer| (&layer.animation, layer.weight)),
+            )
+    }
 }
 
 /// A system that advances the time for all playing animations.
+///
+/// This also sends an [`AnimationEvent`] for each event marker that playback
+/// crossed.
 pub fn advance_animations(
     time: Res<Time>,
     animation_clips: Res<Assets<AnimationClip>>,
-    mut players: Query<&mut AnimationPlayer>,
+    graphs: Res<Assets<AnimationGraph>>,
+    mut players: Query<(Entity, &mut AnimationPlayer)>,
+    mut events: EventWriter<AnimationEvent>,
 ) {
-    for mut player in players.iter_mut() {
+    for (entity, mut player) in players.iter_mut() {
         let paused = player.paused;
         if paused {
             continue;
         }
 
-        // Advance the main animation.
-        if let Some(animation_clip) = animation_clips.get(&player.animation.animation_clip) {
-            player
-                .animation
-                .update(time.delta_seconds(), animation_clip.duration);
+        // Advance the main animation, or the graph that replaces it.
+        let player = &mut *player;
+        if let Some(graph) = &player.graph {
+            if let Some(graph) = graphs.get(graph) {
+                player.graph_state.advance(
+                    graph,
+                    &animation_clips,
+                    time.delta_seconds(),
+                    entity,
+                    &mut events,
+                );
+            }
+        } else if let Some(animation_clip) = animation_clips.get(&player.animation.animation_clip) {
+            player.animation.advance(
+                time.delta_seconds(),
+                animation_clip,
+                entity,
+                1.0,
+                &mut events,
+            );
         };
 
         // Advance transition animations.
@@ -580,13 +1211,30 @@ pub fn advance_animations(
 
             if let Some(animation_clip) = animation_clips.get(&transition.animation.animation_clip)
             {
-                transition
-                    .animation
-                    .update(time.delta_seconds(), animation_clip.duration);
+                transition.animation.advance(
+                    time.delta_seconds(),
+                    animation_clip,
+                    entity,
+                    transition.current_weight,
+                    &mut events,
+                );
             };
 
             true
         });
+
+        // Advance layers.
+        for layer in &mut player.layers {
+            if let Some(animation_clip) = animation_clips.get(&layer.animation.animation_clip) {
+                layer.animation.advance(
+                    time.delta_seconds(),
+                    animation_clip,
+                    entity,
+                    layer.weight,
+                    &mut events,
+                );
+            }
+        }
     }
 }
 
@@ -594,12 +1242,14 @@ pub fn advance_animations(
 /// according to the currently-playing animation.
 pub fn animate_targets(
     clips: Res<Assets<AnimationClip>>,
-    players: Query<&AnimationPlayer>,
+    masks: Res<Assets<AnimationMask>>,
+    players: Query<(&AnimationPlayer, Option<&AnimationLod>, Option<&RootMotion>)>,
     mut targets: Query<(
         Entity,
         &AnimationTarget,
         Option<&Name>,
         AnyOf<(&mut Transform, &mut MorphWeights)>,
+        Option<&mut AnimationLodPose>,
     )>,
 ) {
     // We use two queries here: one read-only query for animation players and
@@ -610,7 +1260,7 @@ pub fn animate_targets(
     // Iterate over all animation targets in parallel.
     targets
         .par_iter_mut()
-        .for_each(|(id, target, name, (transform, morph_weights))| {
+        .for_each(|(id, target, name, (transform, morph_weights), lod_pose)| {
             let mut target_context = AnimationTargetContext {
                 entity: id,
                 target,
@@ -619,7 +1269,7 @@ pub fn animate_targets(
                 morph_weights,
             };
 
-            let Ok(player) = players.get(target.player) else {
+            let Ok((player, lod, root_motion)) = players.get(target.player) else {
                 error!(
                     "Couldn't find the animation player {:?} for the target entity {:?} ({:?})",
                     target.player, target_context.entity, target_context.name,
@@ -627,12 +1277,45 @@ pub fn animate_targets(
                 return;
             };
 
-            player.animation.apply(&clips, 1.0, &mut target_context);
+            let root_motion = root_motion.filter(|root_motion| root_motion.root == target.id);
 
-            for transition in &player.transitions {
-                transition
-                    .animation
-                    .apply(&clips, transition.current_weight, &mut target_context);
+            let Some(lod) = lod else {
+                let previous = target_context.transform.as_deref().copied();
+                for (animation, weight) in player.playing_animations() {
+                    animation.apply(&clips, &masks, weight, &mut target_context);
+                }
+                if let (Some(root_motion), Some(previous), Some(transform)) =
+                    (root_motion, previous, target_context.transform)
+                {
+                    root_motion.strip(&previous, transform.into_inner());
+                }
+                return;
+            };
+
+            if !lod.animates(&masks, target.id) {
+                return;
+            }
+            if !lod.is_sampling() {
+                if let (Some(pose), Some(transform)) = (lod_pose, target_context.transform) {
+                    pose.interpolate(transform.into_inner(), lod.progress());
+                }
+                return;
+            }
+            let previous = target_context.transform.as_deref().copied();
+            for (animation, weight) in player.playing_animations() {
+                animation.apply(&clips, &masks, weight, &mut target_context);
+            }
+            if let (Some(root_motion), Some(previous), Some(transform)) = (
+                root_motion,
+                previous,
+                target_context.transform.as_deref_mut(),
+            ) {
+                root_motion.strip(&previous, transform);
+            }
+            if let (Some(mut pose), Some(transform), Some(previous)) =
+                (lod_pose, target_context.transform, previous)
+            {
+                pose.push(transform.into_inner(), previous, lod.progress());
             }
         });
 }
@@ -687,15 +1370,52 @@ impl Plugin for AnimationPlugin {
     fn build(&self, app: &mut App) {
         app.init_asset::<AnimationClip>()
             .register_asset_reflect::<AnimationClip>()
+            .init_asset::<AnimationMask>()
+            .register_asset_reflect::<AnimationMask>()
+            .init_asset::<AnimationGraph>()
+            .register_asset_reflect::<AnimationGraph>()
             .register_type::<AnimationPlayer>()
+            .register_type::<AnimationLayer>()
+            .register_type::<AnimationBlendMode>()
+            .register_type::<graph::AnimationGraphNode>()
+            .register_type::<graph::AnimationGraphWeight>()
+            .register_type::<graph::AnimationNodeIndex>()
+            .register_type::<graph::ClipNode>()
             .register_type::<VariableCurve>()
             .register_type::<Vec<VariableCurve>>()
             .register_type::<Interpolation>()
             .register_type::<Keyframes>()
+            .register_type::<QuantizedQuat>()
+            .register_type::<compression::AnimationCompressionSettings>()
             .register_type::<AnimationTarget>()
+            .register_type::<AnimationEventMarker>()
+            .register_type::<AnimationLod>()
+            .register_type::<lod::AnimationLodLevel>()
+            .register_type::<ik::TwoBoneIk>()
+            .register_type::<ik::IkChain>()
+            .register_type::<ik::IkSolver>()
+            .register_type::<ik::LookAtConstraint>()
+            .register_type::<jiggle::JiggleBone>()
+            .register_type::<RootMotion>()
+            .register_type::<property::PropertyCurve>()
+            .register_type::<Vec<property::PropertyCurve>>()
+            .register_type::<property::AnimatedProperty>()
+            .register_type::<property::PropertyKeyframes>()
+            .add_event::<AnimationEvent>()
+            .register_tweenable::<Transform>()
+            .register_tweenable::<MorphWeights>()
             .add_systems(
                 PostUpdate,
-                (advance_animations, animate_targets)
+                (
+                    lod::update_animation_lod,
+                    lod::insert_animation_lod_poses,
+                    advance_animations,
+                    root_motion::extract_root_motion,
+                    animate_targets,
+                    property::animate_properties,
+                    ik::solve_ik,
+                    jiggle::simulate_jiggle_bones,
+                )
                     .chain()
                     .before(TransformSystem::TransformPropagate),
             );
@@ -703,9 +1423,27 @@ impl Plugin for AnimationPlugin {
 }
 
 impl PlayingAnimation {
+    /// The weight this animation is applied to `target` with, taking its mask
+    /// into account.
+    fn target_weight(
+        &self,
+        masks: &Assets<AnimationMask>,
+        target: AnimationTargetId,
+        weight: f32,
+    ) -> f32 {
+        match &self.mask {
+            // Don't animate anything until the mask is loaded.
+            Some(mask) => masks
+                .get(mask)
+                .map_or(0.0, |mask| weight * mask.weight(target)),
+            None => weight,
+        }
+    }
+
     fn apply(
         &self,
         clips: &Assets<AnimationClip>,
+        masks: &Assets<AnimationMask>,
         weight: f32,
         target_context: &mut AnimationTargetContext,
     ) {
@@ -714,103 +1452,147 @@ impl PlayingAnimation {
             return;
         };
 
+        let weight = self.target_weight(masks, target_context.target.id, weight);
+        if weight <= 0.0 {
+            return;
+        }
+
         let Some(curves) = clip.curves_for_target(target_context.target.id) else {
             return;
         };
 
         for curve in curves {
-            // Some curves have only one keyframe used to set a transform
-            if curve.keyframe_timestamps.len() == 1 {
-                self.apply_single_keyframe(curve, weight, target_context);
-                return;
+            if matches!(curve.keyframes, Keyframes::Weights(_))
+                && target_context.morph_weights.is_none()
+            {
+                // Some curves have only one keyframe used to set a pose, which
+                // is likely to be meant for another entity.
+                if curve.keyframe_timestamps.len() == 1 {
+                    error!(
+                        "Tried to animate morphs on {:?} ({:?}), but no `MorphWeights` was found",
+                        target_context.entity, target_context.name,
+                    );
+                }
+                continue;
             }
 
-            // Find the current keyframe
-            let Some(step_start) = curve.find_current_keyframe(self.seek_time) else {
-                return;
+            // Curves may animate fewer morph targets than the entity has, e.g.
+            // a facial clip layered over a clip animating the whole mesh.
+            let morph_target_count = curve.morph_target_count();
+            let Some(sample) = curve.sample(self.seek_time, morph_target_count) else {
+                continue;
             };
 
-            let timestamp_start = curve.keyframe_timestamps[step_start];
-            let timestamp_end = curve.keyframe_timestamps[step_start + 1];
-            // Compute how far we are through the keyframe, normalized to [0, 1]
-            let lerp = f32::inverse_lerp(timestamp_start, timestamp_end, self.seek_time);
+            match self.blend_mode {
+                AnimationBlendMode::Override => sample.blend(weight, target_context),
+                AnimationBlendMode::Additive { reference_time } => {
+                    let Some(reference) = curve.sample_clamped(reference_time, morph_target_count)
+                    else {
+                        continue;
+                    };
+                    sample.add(&reference, weight, target_context);
+                }
+            }
+        }
+    }
+}
+
+/// A value sampled from a [`VariableCurve`].
+enum CurveSample {
+    Rotation(Quat),
+    Translation(Vec3),
+    Scale(Vec3),
+    Weights(Vec<f32>),
+}
 
-            self.apply_tweened_keyframe(
-                curve,
-                step_start,
-                weight,
-                lerp,
-                timestamp_end - timestamp_start,
-                target_context,
-            );
+impl VariableCurve {
+    /// The number of morph targets animated by each keyframe of a
+    /// [`Keyframes::Weights`] curve, or 0 for other curves.
+    fn morph_target_count(&self) -> usize {
+        let Keyframes::Weights(weights) = &self.keyframes else {
+            return 0;
+        };
+        let values_per_keyframe = match self.interpolation {
+            Interpolation::CubicSpline => 3,
+            Interpolation::Linear | Interpolation::Step => 1,
+        };
+        match self.keyframe_timestamps.len() * values_per_keyframe {
+            0 => 0,
+            value_count => weights.len() / value_count,
         }
     }
 
-    fn apply_single_keyframe(
-        &self,
-        curve: &VariableCurve,
-        weight: f32,
-        target_context: &mut AnimationTargetContext,
-    ) {
-        match &curve.keyframes {
-            Keyframes::Rotation(keyframes) => {
-                if let Some(ref mut transform) = target_context.transform {
-                    transform.rotation = transform.rotation.slerp(keyframes[0], weight);
-                }
-            }
+    /// Samples this curve at `seek_time`.
+    ///
+    /// Returns [`None`] if `seek_time` is outside of the keyframes of a curve
+    /// with more than one keyframe.
+    fn sample(&self, seek_time: f32, morph_target_count: usize) -> Option<CurveSample> {
+        // Some curves have only one keyframe used to set a transform
+        if self.keyframe_timestamps.len() == 1 {
+            return Some(self.keyframe_value(0, morph_target_count));
+        }
 
-            Keyframes::Translation(keyframes) => {
-                if let Some(ref mut transform) = target_context.transform {
-                    transform.translation = transform.translation.lerp(keyframes[0], weight);
-                }
-            }
+        // Find the current keyframe
+        let step_start = self.find_current_keyframe(seek_time)?;
 
-            Keyframes::Scale(keyframes) => {
-                if let Some(ref mut transform) = target_context.transform {
-                    transform.scale = transform.scale.lerp(keyframes[0], weight);
-                }
-            }
+        let timestamp_start = self.keyframe_timestamps[step_start];
+        let timestamp_end = self.keyframe_timestamps[step_start + 1];
+        // Compute how far we are through the keyframe, normalized to [0, 1]
+        let lerp = f32::inverse_lerp(timestamp_start, timestamp_end, seek_time);
 
-            Keyframes::Weights(keyframes) => {
-                let Some(ref mut morphs) = target_context.morph_weights else {
-                    error!(
-                        "Tried to animate morphs on {:?} ({:?}), but no `MorphWeights` was found",
-                        target_context.entity, target_context.name,
-                    );
-                    return;
-                };
+        Some(self.tweened_value(
+            step_start,
+            lerp,
+            timestamp_end - timestamp_start,
+            morph_target_count,
+        ))
+    }
 
-                let target_count = morphs.weights().len();
-                lerp_morph_weights(
-                    morphs.weights_mut(),
-                    get_keyframe(target_count, keyframes, 0).iter().copied(),
-                    weight,
-                );
+    /// Like [`VariableCurve::sample`], but holds the first and last keyframes
+    /// outside of the keyframes.
+    fn sample_clamped(&self, seek_time: f32, morph_target_count: usize) -> Option<CurveSample> {
+        let first = *self.keyframe_timestamps.first()?;
+        let last = *self.keyframe_timestamps.last()?;
+        if seek_time <= first {
+            Some(self.keyframe_value(0, morph_target_count))
+        } else if seek_time >= last {
+            Some(self.keyframe_value(self.keyframe_timestamps.len() - 1, morph_target_count))
+        } else {
+            self.sample(seek_time, morph_target_count)
+        }
+    }
+
+    /// The value of the keyframe at `index`, skipping the tangents of cubic
+    /// splines.
+    fn keyframe_value(&self, index: usize, morph_target_count: usize) -> CurveSample {
+        let index = match self.interpolation {
+            Interpolation::CubicSpline => index * 3 + 1,
+            Interpolation::Linear | Interpolation::Step => index,
+        };
+        match &self.keyframes {
+            Keyframes::Rotation(keyframes) => CurveSample::Rotation(keyframes[index]),
+            Keyframes::QuantizedRotation(keyframes) => {
+                CurveSample::Rotation(keyframes[index].to_quat())
+            }
+            Keyframes::Translation(keyframes) => CurveSample::Translation(keyframes[index]),
+            Keyframes::Scale(keyframes) => CurveSample::Scale(keyframes[index]),
+            Keyframes::Weights(keyframes) => {
+                CurveSample::Weights(get_keyframe(morph_target_count, keyframes, index).to_vec())
             }
         }
     }
 
-    fn apply_tweened_keyframe(
+    fn tweened_value(
         &self,
-        curve: &VariableCurve,
         step_start: usize,
-        weight: f32,
         lerp: f32,
         duration: f32,
-        target_context: &mut AnimationTargetContext,
-    ) {
-        match (&curve.interpolation, &curve.keyframes) {
-            (Interpolation::Step, Keyframes::Rotation(keyframes)) => {
-                if let Some(ref mut transform) = target_context.transform {
-                    transform.rotation = transform.rotation.slerp(keyframes[step_start], weight);
-                }
-            }
+        morph_target_count: usize,
+    ) -> CurveSample {
+        match (&self.interpolation, &self.keyframes) {
+            (Interpolation::Step, _) => self.keyframe_value(step_start, morph_target_count),
 
             (Interpolation::Linear, Keyframes::Rotation(keyframes)) => {
-                let Some(ref mut transform) = target_context.transform else {
-                    return;
-                };
-
                 let rot_start = keyframes[step_start];
                 let mut rot_end = keyframes[step_start + 1];
                 // Choose the smallest angle for the rotation
@@ -818,15 +1600,27 @@ impl PlayingAnimation {
                     rot_end = -rot_end;
                 }
                 // Rotations are using a spherical linear interpolation
-                let rot = rot_start.normalize().slerp(rot_end.normalize(), lerp);
-                transform.rotation = transform.rotation.slerp(rot, weight);
+                CurveSample::Rotation(rot_start.normalize().slerp(rot_end.normalize(), lerp))
             }
 
-            (Interpolation::CubicSpline, Keyframes::Rotation(keyframes)) => {
-                let Some(ref mut transform) = target_context.transform else {
-                    return;
-                };
+            (Interpolation::Linear, Keyframes::QuantizedRotation(keyframes)) => {
+                let rot_start = keyframes[step_start].to_quat();
+                let mut rot_end = keyframes[step_start + 1].to_quat();
+                // Choose the smallest angle for the rotation
+                if rot_end.dot(rot_start) < 0.0 {
+                    rot_end = -rot_end;
+                }
+                CurveSample::Rotation(rot_start.slerp(rot_end, lerp))
+            }
+
+            (Interpolation::CubicSpline, Keyframes::QuantizedRotation(keyframes)) => {
+                // Tangents can't be quantized: fall back to linear interpolation.
+                let rot_start = keyframes[step_start * 3 + 1].to_quat();
+                let rot_end = keyframes[(step_start + 1) * 3 + 1].to_quat();
+                CurveSample::Rotation(rot_start.slerp(rot_end, lerp))
+            }
 
+            (Interpolation::CubicSpline, Keyframes::Rotation(keyframes)) => {
                 let value_start = keyframes[step_start * 3 + 1];
                 let tangent_out_start = keyframes[step_start * 3 + 2];
                 let tangent_in_end = keyframes[(step_start + 1) * 3];
@@ -839,142 +1633,169 @@ impl PlayingAnimation {
                     lerp,
                     duration,
                 );
-                transform.rotation = transform.rotation.slerp(result.normalize(), weight);
-            }
-
-            (Interpolation::Step, Keyframes::Translation(keyframes)) => {
-                if let Some(ref mut transform) = target_context.transform {
-                    transform.translation =
-                        transform.translation.lerp(keyframes[step_start], weight);
-                }
+                CurveSample::Rotation(result.normalize())
             }
 
             (Interpolation::Linear, Keyframes::Translation(keyframes)) => {
-                let Some(ref mut transform) = target_context.transform else {
-                    return;
-                };
-
                 let translation_start = keyframes[step_start];
                 let translation_end = keyframes[step_start + 1];
-                let result = translation_start.lerp(translation_end, lerp);
-                transform.translation = transform.translation.lerp(result, weight);
+                CurveSample::Translation(translation_start.lerp(translation_end, lerp))
             }
 
             (Interpolation::CubicSpline, Keyframes::Translation(keyframes)) => {
-                let Some(ref mut transform) = target_context.transform else {
-                    return;
-                };
-
                 let value_start = keyframes[step_start * 3 + 1];
                 let tangent_out_start = keyframes[step_start * 3 + 2];
                 let tangent_in_end = keyframes[(step_start + 1) * 3];
                 let value_end = keyframes[(step_start + 1) * 3 + 1];
-                let result = cubic_spline_interpolation(
+                CurveSample::Translation(cubic_spline_interpolation(
                     value_start,
                     tangent_out_start,
                     tangent_in_end,
                     value_end,
                     lerp,
                     duration,
-                );
-                transform.translation = transform.translation.lerp(result, weight);
-            }
-
-            (Interpolation::Step, Keyframes::Scale(keyframes)) => {
-                if let Some(ref mut transform) = target_context.transform {
-                    transform.scale = transform.scale.lerp(keyframes[step_start], weight);
-                }
+                ))
             }
 
             (Interpolation::Linear, Keyframes::Scale(keyframes)) => {
-                let Some(ref mut transform) = target_context.transform else {
-                    return;
-                };
-
                 let scale_start = keyframes[step_start];
                 let scale_end = keyframes[step_start + 1];
-                let result = scale_start.lerp(scale_end, lerp);
-                transform.scale = transform.scale.lerp(result, weight);
+                CurveSample::Scale(scale_start.lerp(scale_end, lerp))
             }
 
             (Interpolation::CubicSpline, Keyframes::Scale(keyframes)) => {
-                let Some(ref mut transform) = target_context.transform else {
-                    return;
-                };
-
                 let value_start = keyframes[step_start * 3 + 1];
                 let tangent_out_start = keyframes[step_start * 3 + 2];
                 let tangent_in_end = keyframes[(step_start + 1) * 3];
                 let value_end = keyframes[(step_start + 1) * 3 + 1];
-                let result = cubic_spline_interpolation(
+                CurveSample::Scale(cubic_spline_interpolation(
                     value_start,
                     tangent_out_start,
                     tangent_in_end,
                     value_end,
                     lerp,
                     duration,
-                );
-                transform.scale = transform.scale.lerp(result, weight);
-            }
-
-            (Interpolation::Step, Keyframes::Weights(keyframes)) => {
-                let Some(ref mut morphs) = target_context.morph_weights else {
-                    return;
-                };
-
-                let target_count = morphs.weights().len();
-                let morph_start = get_keyframe(target_count, keyframes, step_start);
-                lerp_morph_weights(morphs.weights_mut(), morph_start.iter().copied(), weight);
+                ))
             }
 
             (Interpolation::Linear, Keyframes::Weights(keyframes)) => {
-                let Some(ref mut morphs) = target_context.morph_weights else {
-                    return;
-                };
-
-                let target_count = morphs.weights().len();
-                let morph_start = get_keyframe(target_count, keyframes, step_start);
-                let morph_end = get_keyframe(target_count, keyframes, step_start + 1);
-                let result = morph_start
-                    .iter()
-                    .zip(morph_end)
-                    .map(|(a, b)| a.lerp(*b, lerp));
-                lerp_morph_weights(morphs.weights_mut(), result, weight);
+                let morph_start = get_keyframe(morph_target_count, keyframes, step_start);
+                let morph_end = get_keyframe(morph_target_count, keyframes, step_start + 1);
+                CurveSample::Weights(
+                    morph_start
+                        .iter()
+                        .zip(morph_end)
+                        .map(|(a, b)| a.lerp(*b, lerp))
+                        .collect(),
+                )
             }
 
             (Interpolation::CubicSpline, Keyframes::Weights(keyframes)) => {
-                let Some(ref mut morphs) = target_context.morph_weights else {
-                    return;
-                };
-
-                let target_count = morphs.weights().len();
+                let target_count = morph_target_count;
                 let morph_start = get_keyframe(target_count, keyframes, step_start * 3 + 1);
                 let tangents_out_start = get_keyframe(target_count, keyframes, step_start * 3 + 2);
                 let tangents_in_end = get_keyframe(target_count, keyframes, (step_start + 1) * 3);
                 let morph_end = get_keyframe(target_count, keyframes, (step_start + 1) * 3 + 1);
-                let result = morph_start
-                    .iter()
-                    .zip(tangents_out_start)
-                    .zip(tangents_in_end)
-                    .zip(morph_end)
-                    .map(
-                        |(((&value_start, &tangent_out_start), &tangent_in_end), &value_end)| {
-                            cubic_spline_interpolation(
-                                value_start,
-                                tangent_out_start,
-                                tangent_in_end,
-                                value_end,
-                                lerp,
-                                duration,
-                            )
-                        },
-                    );
-                lerp_morph_weights(morphs.weights_mut(), result, weight);
+                CurveSample::Weights(
+                    morph_start
+                        .iter()
+                        .zip(tangents_out_start)
+                        .zip(tangents_in_end)
+                        .zip(morph_end)
+                        .map(
+                            |(
+                                ((&value_start, &tangent_out_start), &tangent_in_end),
+                                &value_end,
+                            )| {
+                                cubic_spline_interpolation(
+                                    value_start,
+                                    tangent_out_start,
+                                    tangent_in_end,
+                                    value_end,
+                                    lerp,
+                                    duration,
+                                )
+                            },
+                        )
+                        .collect(),
+                )
             }
         }
     }
 }
 
+impl CurveSample {
+    /// Blends this sample over the current value of the target by `weight`.
+    fn blend(&self, weight: f32, target_context: &mut AnimationTargetContext) {
+        match self {
+            CurveSample::Rotation(rotation) => {
+                if let Some(ref mut transform) = target_context.transform {
+                    transform.rotation = transform.rotation.slerp(*rotation, weight);
+                }
+            }
+            CurveSample::Translation(translation) => {
+                if let Some(ref mut transform) = target_context.transform {
+                    transform.translation = transform.translation.lerp(*translation, weight);
+                }
+            }
+            CurveSample::Scale(scale) => {
+                if let Some(ref mut transform) = target_context.transform {
+                    transform.scale = transform.scale.lerp(*scale, weight);
+                }
+            }
+            CurveSample::Weights(weights) => {
+                if let Some(ref mut morphs) = target_context.morph_weights {
+                    lerp_morph_weights(morphs.weights_mut(), weights.iter().copied(), weight);
+                }
+            }
+        }
+    }
+
+    /// Adds the difference between this sample and `reference`, scaled by
+    /// `weight`, to the current value of the target.
+    fn add(
+        &self,
+        reference: &CurveSample,
+        weight: f32,
+        target_context: &mut AnimationTargetContext,
+    ) {
+        match (self, reference) {
+            (CurveSample::Rotation(rotation), CurveSample::Rotation(reference)) => {
+                if let Some(ref mut transform) = target_context.transform {
+                    let delta = reference.inverse() * *rotation;
+                    transform.rotation =
+                        (transform.rotation * Quat::IDENTITY.slerp(delta, weight)).normalize();
+                }
+            }
+            (CurveSample::Translation(translation), CurveSample::Translation(reference)) => {
+                if let Some(ref mut transform) = target_context.transform {
+                    transform.translation += (*translation - *reference) * weight;
+                }
+            }
+            (CurveSample::Scale(scale), CurveSample::Scale(reference)) => {
+                if let Some(ref mut transform) = target_context.transform {
+                    let ratio = *scale / *reference;
+                    if ratio.is_finite() {
+                        transform.scale *= Vec3::ONE.lerp(ratio, weight);
+                    }
+                }
+            }
+            (CurveSample::Weights(weights), CurveSample::Weights(reference)) => {
+                if let Some(ref mut morphs) = target_context.morph_weights {
+                    let deltas = weights
+                        .iter()
+                        .zip(reference)
+                        .map(|(value, reference)| value - reference);
+                    for (morph_weight, delta) in morphs.weights_mut().iter_mut().zip(deltas) {
+                        *morph_weight += delta * weight;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
 impl AnimationTargetId {
     /// Creates a new [`AnimationTargetId`] by hashing a list of names.
     ///
@@ -1008,8 +1829,308 @@ impl MapEntities for AnimationTarget {
 
 #[cfg(test)]
 mod tests {
-    use crate::VariableCurve;
-    use bevy_math::Vec3;
+    use crate::*;
+    use bevy_ecs::system::RunSystemOnce;
+    use bevy_hierarchy::BuildWorldChildren;
+
+    fn test_event_clip() -> AnimationClip {
+        let mut clip = AnimationClip::default();
+        clip.add_event(0.0, "start");
+        clip.add_event(0.5, "footstep");
+        clip.add_event(1.5, "footstep");
+        clip.add_event(2.0, "end");
+        clip
+    }
+
+    /// Advances `animation` by `delta` and returns the names of the crossed markers.
+    fn crossed_events(
+        clip: &AnimationClip,
+        animation: &mut PlayingAnimation,
+        delta: f32,
+    ) -> Vec<String> {
+        let seek_time = animation.seek_time;
+        let travel = animation.update(delta, clip.duration());
+        let mut names = Vec::new();
+        clip.for_each_crossed_event(seek_time, travel, |marker| {
+            names.push(marker.name.to_string());
+        });
+        names
+    }
+
+    #[test]
+    fn layer_masks_restrict_targets() {
+        let mut world = World::new();
+        world.init_resource::<Assets<AnimationClip>>();
+        world.init_resource::<Assets<AnimationMask>>();
+
+        let upper = AnimationTargetId::from_name(&Name::new("Upper"));
+        let lower = AnimationTargetId::from_name(&Name::new("Lower"));
+        let clip_moving_to = |world: &mut World, x: f32| {
+            let mut clip = AnimationClip::default();
+            for target in [upper, lower] {
+                clip.add_curve_to_target(
+                    target,
+                    VariableCurve {
+                        keyframe_timestamps: vec![0.0],
+                        keyframes: Keyframes::Translation(vec![Vec3::X * x]),
+                        interpolation: Interpolation::Linear,
+                    },
+                );
+            }
+            world.resource_mut::<Assets<AnimationClip>>().add(clip)
+        };
+        let base = clip_moving_to(&mut world, 1.0);
+        let overlay = clip_moving_to(&mut world, 5.0);
+        let mask = world
+            .resource_mut::<Assets<AnimationMask>>()
+            .add(AnimationMask::from_targets([upper]));
+
+        let mut player = AnimationPlayer::default();
+        player.start(base);
+        player.add_layer(AnimationLayer::new(overlay).with_mask(mask));
+        let player = world.spawn(player).id();
+        let [upper_entity, lower_entity] = [upper, lower].map(|id| {
+            world
+                .spawn((AnimationTarget { id, player }, Transform::default()))
+                .id()
+        });
+
+        world.run_system_once(animate_targets);
+
+        let x = |entity| world.get::<Transform>(entity).unwrap().translation.x;
+        assert_eq!(x(upper_entity), 5.0);
+        assert_eq!(x(lower_entity), 1.0);
+    }
+
+    #[test]
+    fn masks_include_hierarchies() {
+        let mut world = World::new();
+        let names = ["Hips", "Spine", "Head", "Leg"].map(Name::new);
+        let [hips, spine, head, leg] = names.clone().map(|name| {
+            let id = AnimationTargetId::from_name(&name);
+            world
+                .spawn((
+                    AnimationTarget {
+                        id,
+                        player: Entity::PLACEHOLDER,
+                    },
+                    name,
+                ))
+                .id()
+        });
+        world.entity_mut(hips).push_children(&[spine, leg]);
+        world.entity_mut(spine).push_children(&[head]);
+
+        let mask = world.run_system_once(
+            move |targets: Query<&AnimationTarget>, children: Query<&Children>| {
+                let mut mask = AnimationMask::default();
+                mask.add_hierarchy(spine, 0.5, &targets, &children);
+                mask
+            },
+        );
+
+        let [hips, spine, head, leg] = names.map(|name| AnimationTargetId::from_name(&name));
+        assert_eq!(mask.weight(spine), 0.5);
+        assert_eq!(mask.weight(head), 0.5);
+        assert_eq!(mask.weight(hips), 0.0);
+        assert_eq!(mask.weight(leg), 0.0);
+    }
+
+    #[test]
+    fn morph_weights_blend_across_layers() {
+        let mut world = World::new();
+        world.init_resource::<Assets<AnimationClip>>();
+        world.init_resource::<Assets<AnimationMask>>();
+
+        let face = AnimationTargetId::from_name(&Name::new("Face"));
+        let clip_with_weights = |world: &mut World, weights: Vec<f32>| {
+            let mut clip = AnimationClip::default();
+            clip.add_curve_to_target(
+                face,
+                VariableCurve {
+                    keyframe_timestamps: vec![0.0],
+                    keyframes: Keyframes::Weights(weights),
+                    interpolation: Interpolation::Linear,
+                },
+            );
+            world.resource_mut::<Assets<AnimationClip>>().add(clip)
+        };
+        let body = clip_with_weights(&mut world, vec![0.2, 0.2, 0.2]);
+        // The facial clip only animates the first two morph targets.
+        let smile = clip_with_weights(&mut world, vec![1.0, 0.5]);
+
+        let mut player = AnimationPlayer::default();
+        player.start(body);
+        player.add_layer(AnimationLayer::new(smile).with_weight(0.5));
+        let player = world.spawn(player).id();
+        let face_entity = world
+            .spawn((
+                AnimationTarget { id: face, player },
+                MorphWeights::new(vec![0.0; 3], None).unwrap(),
+            ))
+            .id();
+
+        world.run_system_once(animate_targets);
+
+        let weights = world.get::<MorphWeights>(face_entity).unwrap().weights();
+        assert!((weights[0] - 0.6).abs() < 1e-6);
+        assert!((weights[1] - 0.35).abs() < 1e-6);
+        assert!((weights[2] - 0.2).abs() < 1e-6);
+    }
+
+    #[test]
+    fn additive_layers_add_deltas_from_reference_pose() {
+        let mut world = World::new();
+        world.init_resource::<Assets<AnimationClip>>();
+        world.init_resource::<Assets<AnimationMask>>();
+
+        let bone = AnimationTargetId::from_name(&Name::new("Spine"));
+        let mut clip = AnimationClip::default();
+        clip.add_curve_to_target(
+            bone,
+            VariableCurve {
+                keyframe_timestamps: vec![0.0, 1.0],
+                keyframes: Keyframes::Translation(vec![Vec3::Y, Vec3::new(2.0, 1.0, 0.0)]),
+                interpolation: Interpolation::Linear,
+            },
+        );
+        clip.add_curve_to_target(
+            bone,
+            VariableCurve {
+                keyframe_timestamps: vec![0.0, 1.0],
+                keyframes: Keyframes::Rotation(vec![
+                    Quat::IDENTITY,
+                    Quat::from_rotation_z(std::f32::consts::FRAC_PI_2),
+                ]),
+                interpolation: Interpolation::Linear,
+            },
+        );
+        let clip = world.resource_mut::<Assets<AnimationClip>>().add(clip);
+
+        let mut player = AnimationPlayer::default();
+        let layer = player.add_layer(
+            AnimationLayer::new(clip)
+                .with_blend_mode(AnimationBlendMode::Additive {
+                    reference_time: 0.0,
+                })
+                .with_weight(0.5),
+        );
+        player.layer_mut(layer).unwrap().seek_to(0.5);
+        let player = world.spawn(player).id();
+        let base = Transform::from_xyz(10.0, 0.0, 0.0)
+            .with_rotation(Quat::from_rotation_y(std::f32::consts::FRAC_PI_2));
+        let target = world
+            .spawn((AnimationTarget { id: bone, player }, base))
+            .id();
+
+        world.run_system_once(animate_targets);
+
+        // Half of the delta from the reference pose, at half of the clip.
+        let transform = world.get::<Transform>(target).unwrap();
+        assert!(transform
+            .translation
+            .abs_diff_eq(Vec3::new(10.5, 0.0, 0.0), 1e-5));
+        let expected = base.rotation * Quat::from_rotation_z(std::f32::consts::FRAC_PI_8);
+        assert!(transform.rotation.abs_diff_eq(expected, 1e-5));
+    }
+
+    #[test]
+    fn events_are_sorted_and_grouped_in_tracks() {
+        let mut clip = test_event_clip();
+        clip.add_event(1.0, "attack");
+        let times: Vec<_> = clip.events().iter().map(|marker| marker.time).collect();
+        assert_eq!(times, vec![0.0, 0.5, 1.0, 1.5, 2.0]);
+        assert_eq!(clip.event_track("footstep").collect::<Vec<_>>(), [0.5, 1.5]);
+        assert_eq!(clip.duration(), 2.0);
+    }
+
+    #[test]
+    fn events_fire_once_when_crossed() {
+        let clip = test_event_clip();
+        let mut animation = PlayingAnimation::default();
+
+        assert_eq!(
+            crossed_events(&clip, &mut animation, 0.25),
+            ["start".to_string()]
+        );
+        assert!(crossed_events(&clip, &mut animation, 0.2).is_empty());
+        assert_eq!(
+            crossed_events(&clip, &mut animation, 0.1),
+            ["footstep".to_string()]
+        );
+        assert!(crossed_events(&clip, &mut animation, 0.5).is_empty());
+        assert_eq!(
+            crossed_events(&clip, &mut animation, 10.0),
+            ["footstep".to_string(), "end".to_string()]
+        );
+        assert!(animation.is_finished());
+        assert!(crossed_events(&clip, &mut animation, 1.0).is_empty());
+    }
+
+    #[test]
+    fn events_fire_on_each_loop() {
+        let clip = test_event_clip();
+        let mut animation = PlayingAnimation {
+            repeat: RepeatAnimation::Forever,
+            speed: 2.0,
+            seek_time: 1.0,
+            ..Default::default()
+        };
+
+        // Moves 1.0 -> 2.0 (wrap) -> 0.0 -> 1.0
+        assert_eq!(
+            crossed_events(&clip, &mut animation, 1.0),
+            ["footstep", "end", "start", "footstep"].map(String::from)
+        );
+        assert!((animation.seek_time - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn events_fire_in_reverse() {
+        let clip = test_event_clip();
+        let mut animation = PlayingAnimation {
+            repeat: RepeatAnimation::Forever,
+            speed: -1.0,
+            seek_time: 1.0,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            crossed_events(&clip, &mut animation, 1.0),
+            ["footstep".to_string(), "start".to_string()]
+        );
+        assert_eq!(
+            crossed_events(&clip, &mut animation, 0.6),
+            ["end".to_string(), "footstep".to_string()]
+        );
+    }
+
+    #[test]
+    fn events_fire_for_whole_loops_crossed_at_once() {
+        let clip = test_event_clip();
+        let crossed = |seek_time, travel| {
+            let mut names = Vec::new();
+            clip.for_each_crossed_event(seek_time, travel, |marker| {
+                names.push(marker.name.to_string());
+            });
+            names
+        };
+
+        assert_eq!(
+            crossed(1.0, 4.25),
+            ["footstep", "end", "start", "footstep", "footstep", "end", "start", "footstep"]
+                .map(String::from)
+        );
+        assert_eq!(
+            crossed(1.0, -3.0),
+            ["footstep", "start", "end", "footstep", "footstep", "start"].map(String::from)
+        );
+
+        // Huge travels don't fire the events forever.
+        let markers = clip.events.len() * (MAX_CROSSED_LOOPS as usize + 2);
+        assert!(crossed(1.0, 1e30).len() <= markers);
+        assert!(crossed(1.0, -f32::INFINITY).len() <= markers);
+    }
 
     fn test_variable_curve() -> VariableCurve {
         let keyframe_timestamps = vec![1.0, 2.0, 3.0, 4.0];
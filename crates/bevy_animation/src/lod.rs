@@ -0,0 +1,246 @@
+//! Distance-based level of detail for animation.
+//!
+//! Sampling every bone of every character on every frame doesn't scale to
+//! crowds, and far away characters don't need it. An [`AnimationLod`] on an
+//! [`AnimationPlayer`] entity reduces how often its targets are sampled, and
+//! optionally which of them are animated at all, depending on its distance to
+//! the nearest active camera.
+
+use bevy_asset::{Assets, Handle};
+use bevy_ecs::prelude::*;
+use bevy_reflect::Reflect;
+use bevy_render::camera::Camera;
+use bevy_transform::prelude::{GlobalTransform, Transform};
+
+use crate::{AnimationMask, AnimationPlayer, AnimationTarget, AnimationTargetId};
+
+/// A level of detail of an [`AnimationLod`].
+#[derive(Reflect, Clone, Debug)]
+pub struct AnimationLodLevel {
+    /// The distance to the camera from which this level is used.
+    pub min_distance: f32,
+    /// The targets are sampled once every `update_interval` frames.
+    ///
+    /// On the frames in between, the transforms of the targets are
+    /// interpolated between their two last sampled poses. This smooths the
+    /// motion at the cost of a delay of `update_interval` frames.
+    pub update_interval: u32,
+    /// If set, only the targets of this mask are animated at this level, e.g.
+    /// to stop animating fingers and facial bones.
+    pub mask: Option<Handle<AnimationMask>>,
+}
+
+impl AnimationLodLevel {
+    /// Creates a level used from `min_distance`, sampling the targets once
+    /// every `update_interval` frames.
+    pub fn new(min_distance: f32, update_interval: u32) -> Self {
+        Self {
+            min_distance,
+            update_interval,
+            mask: None,
+        }
+    }
+
+    /// Only animates the targets of `mask` at this level.
+    pub fn with_mask(mut self, mask: Handle<AnimationMask>) -> Self {
+        self.mask = Some(mask);
+        self
+    }
+}
+
+/// Reduces the update rate and the number of animated targets of the
+/// [`AnimationPlayer`] on the same entity, depending on the distance of the
+/// entity to the nearest active [`Camera`].
+///
+/// Below the [`AnimationLodLevel::min_distance`] of the first level, the
+/// animation is sampled every frame.
+#[derive(Component, Reflect, Clone, Debug, Default)]
+#[reflect(Component)]
+pub struct AnimationLod {
+    levels: Vec<AnimationLodLevel>,
+    level: Option<usize>,
+    frame: u32,
+}
+
+impl AnimationLod {
+    /// Creates a level of detail with the given levels, which don't need to
+    /// be sorted.
+    pub fn new(levels: impl IntoIterator<Item = AnimationLodLevel>) -> Self {
+        let mut levels: Vec<_> = levels.into_iter().collect();
+        levels.sort_by(|a, b| a.min_distance.total_cmp(&b.min_distance));
+        Self {
+            levels,
+            level: None,
+            frame: 0,
+        }
+    }
+
+    /// The levels of detail, sorted by distance.
+    pub fn levels(&self) -> &[AnimationLodLevel] {
+        &self.levels
+    }
+
+    /// The level of detail currently in use, if the entity is far enough from
+    /// the camera.
+    pub fn current_level(&self) -> Option<&AnimationLodLevel> {
+        self.levels.get(self.level?)
+    }
+
+    fn update_interval(&self) -> u32 {
+        self.current_level()
+            .map_or(1, |level| level.update_interval.max(1))
+    }
+
+    /// Whether the targets are sampled this frame.
+    pub(crate) fn is_sampling(&self) -> bool {
+        self.frame == 0
+    }
+
+    /// How far the interpolated pose is between the two last sampled poses.
+    pub(crate) fn progress(&self) -> f32 {
+        (self.frame + 1) as f32 / self.update_interval() as f32
+    }
+
+    /// Whether the target `target` is animated at the current level.
+    pub(crate) fn animates(
+        &self,
+        masks: &Assets<AnimationMask>,
+        target: AnimationTargetId,
+    ) -> bool {
+        match self.current_level().and_then(|level| level.mask.as_ref()) {
+            Some(mask) => match masks.get(mask) {
+                Some(mask) => mask.weight(target) > 0.0,
+                // Animate all the targets until the mask is loaded.
+                None => true,
+            },
+            None => true,
+        }
+    }
+
+    fn select_level(&mut self, distance: f32, stagger: u32) {
+        let level = self
+            .levels
+            .iter()
+            .rposition(|level| level.min_distance <= distance);
+        if level != self.level {
+            self.level = level;
+            // Spread the sampling of the players using the same level over
+            // several frames.
+            self.frame = stagger % self.update_interval();
+        } else {
+            self.frame = (self.frame + 1) % self.update_interval();
+        }
+    }
+}
+
+/// The two last sampled poses of an animation target whose player has an
+/// [`AnimationLod`], to interpolate between them on the frames where the
+/// target isn't sampled.
+///
+/// This is added automatically.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct AnimationLodPose {
+    from: Transform,
+    to: Transform,
+}
+
+impl AnimationLodPose {
+    /// Records a newly sampled pose, and moves `transform` to the interpolated
+    /// pose.
+    pub(crate) fn push(&mut self, transform: &mut Transform, previous: Transform, progress: f32) {
+        self.from = previous;
+        self.to = *transform;
+        self.interpolate(transform, progress);
+    }
+
+    /// Moves `transform` to the interpolated pose.
+    pub(crate) fn interpolate(&self, transform: &mut Transform, progress: f32) {
+        let progress = progress.clamp(0.0, 1.0);
+        transform.translation = self.from.translation.lerp(self.to.translation, progress);
+        transform.rotation = self.from.rotation.slerp(self.to.rotation, progress);
+        transform.scale = self.from.scale.lerp(self.to.scale, progress);
+    }
+}
+
+/// Selects the level of detail of each [`AnimationLod`] from its distance to
+/// the nearest active camera.
+pub fn update_animation_lod(
+    mut players: Query<
+        (Entity, &mut AnimationLod, Option<&GlobalTransform>),
+        With<AnimationPlayer>,
+    >,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+) {
+    for (entity, mut lod, transform) in &mut players {
+        let distance = transform
+            .and_then(|transform| {
+                cameras
+                    .iter()
+                    .filter(|(camera, _)| camera.is_active)
+                    .map(|(_, camera_transform)| {
+                        camera_transform
+                            .translation()
+                            .distance(transform.translation())
+                    })
+                    .min_by(f32::total_cmp)
+            })
+            .unwrap_or(0.0);
+        lod.select_level(distance, entity.index());
+    }
+}
+
+/// Adds an [`AnimationLodPose`] to the targets of the players with an
+/// [`AnimationLod`].
+pub fn insert_animation_lod_poses(
+    mut commands: Commands,
+    players: Query<(), With<AnimationLod>>,
+    targets: Query<(Entity, &AnimationTarget), (With<Transform>, Without<AnimationLodPose>)>,
+) {
+    for (entity, target) in &targets {
+        if players.contains(target.player) {
+            commands.entity(entity).insert(AnimationLodPose::default());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn levels_are_selected_by_distance() {
+        let mut lod = AnimationLod::new([
+            AnimationLodLevel::new(50.0, 4),
+            AnimationLodLevel::new(10.0, 2),
+        ]);
+        assert_eq!(lod.levels()[0].min_distance, 10.0);
+
+        lod.select_level(5.0, 0);
+        assert!(lod.current_level().is_none());
+        assert!(lod.is_sampling());
+        assert_eq!(lod.progress(), 1.0);
+
+        lod.select_level(60.0, 1);
+        assert_eq!(lod.current_level().unwrap().update_interval, 4);
+        let sampled: Vec<bool> = (0..8)
+            .map(|_| {
+                lod.select_level(60.0, 1);
+                lod.is_sampling()
+            })
+            .collect();
+        assert_eq!(
+            sampled,
+            [false, false, true, false, false, false, true, false]
+        );
+    }
+
+    #[test]
+    fn skipped_frames_are_interpolated() {
+        let mut pose = AnimationLodPose::default();
+        let mut transform = Transform::from_xyz(4.0, 0.0, 0.0);
+        pose.push(&mut transform, Transform::IDENTITY, 0.25);
+        assert_eq!(transform.translation.x, 1.0);
+        pose.interpolate(&mut transform, 0.75);
+        assert_eq!(transform.translation.x, 3.0);
+    }
+}
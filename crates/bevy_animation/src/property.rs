@@ -0,0 +1,552 @@
+//! Animation of arbitrary reflected properties.
+//!
+//! Besides transforms and morph weights, an [`AnimationClip`] can animate any
+//! field reachable through a [reflect path] from a component of an animation
+//! target, or from the asset one of its [`Handle`] components points to. This
+//! makes it possible to animate e.g. the intensity of a light or the emissive
+//! color of a material from the same clip as the armature.
+//!
+//! [reflect path]: bevy_reflect::GetPath
+
+use std::ops::{Add, Mul};
+
+use bevy_asset::{Asset, Assets, Handle, ReflectAsset, ReflectHandle};
+use bevy_ecs::prelude::*;
+use bevy_math::{FloatExt, Quat, Vec2, Vec3, Vec4};
+use bevy_reflect::{GetPath, Reflect, TypePath, TypeRegistry};
+use bevy_render::color::Color;
+use bevy_utils::warn_once;
+
+use crate::{
+    cubic_spline_interpolation, AnimationBlendMode, AnimationClip, AnimationMask, AnimationPlayer,
+    AnimationTarget, Interpolation,
+};
+
+/// A reflected field of an animation target, animated by a [`PropertyCurve`].
+#[derive(Reflect, Clone, Debug, PartialEq, Eq)]
+pub enum AnimatedProperty {
+    /// A field of a component of the animation target.
+    Component {
+        /// The [type path](TypePath::type_path) of the component.
+        component: String,
+        /// The reflect path of the field inside of the component.
+        path: String,
+    },
+    /// A field of the asset pointed to by a [`Handle`] component of the
+    /// animation target.
+    Asset {
+        /// The [type path](TypePath::type_path) of the handle component.
+        handle: String,
+        /// The reflect path of the field inside of the asset.
+        path: String,
+    },
+}
+
+impl AnimatedProperty {
+    /// The field at `path` of the component `C`, e.g. `intensity` of a
+    /// `PointLight`.
+    pub fn component<C: Component + TypePath>(path: impl Into<String>) -> Self {
+        Self::Component {
+            component: C::type_path().to_string(),
+            path: path.into(),
+        }
+    }
+
+    /// The field at `path` of the asset `A` pointed to by the [`Handle<A>`]
+    /// component, e.g. `emissive` of a `StandardMaterial`.
+    ///
+    /// Note that all the entities sharing the asset are affected.
+    pub fn asset<A: Asset>(path: impl Into<String>) -> Self {
+        Self::Asset {
+            handle: Handle::<A>::type_path().to_string(),
+            path: path.into(),
+        }
+    }
+
+    /// The reflect path of the animated field.
+    pub fn path(&self) -> &str {
+        match self {
+            AnimatedProperty::Component { path, .. } | AnimatedProperty::Asset { path, .. } => path,
+        }
+    }
+}
+
+/// The keyframes of a [`PropertyCurve`], with the type of the animated field.
+#[derive(Reflect, Clone, Debug)]
+pub enum PropertyKeyframes {
+    /// Keyframes for an `f32` field.
+    F32(Vec<f32>),
+    /// Keyframes for a [`Vec2`] field.
+    Vec2(Vec<Vec2>),
+    /// Keyframes for a [`Vec3`] field.
+    Vec3(Vec<Vec3>),
+    /// Keyframes for a [`Vec4`] field.
+    Vec4(Vec<Vec4>),
+    /// Keyframes for a [`Quat`] field.
+    Quat(Vec<Quat>),
+    /// Keyframes for a [`Color`] field.
+    ///
+    /// Colors are interpolated in linear RGBA space.
+    Color(Vec<Color>),
+}
+
+impl PropertyKeyframes {
+    /// Returns the number of keyframes.
+    pub fn len(&self) -> usize {
+        match self {
+            PropertyKeyframes::F32(vec) => vec.len(),
+            PropertyKeyframes::Vec2(vec) => vec.len(),
+            PropertyKeyframes::Vec3(vec) => vec.len(),
+            PropertyKeyframes::Vec4(vec) => vec.len(),
+            PropertyKeyframes::Quat(vec) => vec.len(),
+            PropertyKeyframes::Color(vec) => vec.len(),
+        }
+    }
+
+    /// Returns true if the number of keyframes is zero.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Describes how a reflected field of an animation target should be animated.
+///
+/// `keyframe_timestamps` and `keyframes` should have the same length, except
+/// for [`Interpolation::CubicSpline`], which has three keyframes per timestamp
+/// like [`VariableCurve`](crate::VariableCurve).
+#[derive(Reflect, Clone, Debug)]
+pub struct PropertyCurve {
+    /// The animated field.
+    pub property: AnimatedProperty,
+    /// Timestamp for each of the keyframes.
+    pub keyframe_timestamps: Vec<f32>,
+    /// List of the keyframes.
+    pub keyframes: PropertyKeyframes,
+    /// Interpolation method to use between keyframes.
+    pub interpolation: Interpolation,
+}
+
+/// A value sampled from a [`PropertyCurve`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum PropertyValue {
+    F32(f32),
+    Vec2(Vec2),
+    Vec3(Vec3),
+    Vec4(Vec4),
+    Quat(Quat),
+    /// A color in linear RGBA space.
+    Color(Vec4),
+}
+
+impl PropertyCurve {
+    /// Samples this curve at `seek_time`.
+    ///
+    /// Returns [`None`] if `seek_time` is outside of the keyframes of a curve
+    /// with more than one keyframe.
+    fn sample(&self, seek_time: f32) -> Option<PropertyValue> {
+        let timestamps = &self.keyframe_timestamps;
+        if timestamps.len() == 1 {
+            return Some(self.keyframe_value(0));
+        }
+
+        let step_end = timestamps.partition_point(|&timestamp| timestamp <= seek_time);
+        if step_end == 0 || step_end >= timestamps.len() {
+            return None;
+        }
+        let step_start = step_end - 1;
+        let timestamp_start = timestamps[step_start];
+        let timestamp_end = timestamps[step_end];
+        let lerp = f32::inverse_lerp(timestamp_start, timestamp_end, seek_time);
+        let duration = timestamp_end - timestamp_start;
+
+        let interpolation = &self.interpolation;
+        Some(match &self.keyframes {
+            PropertyKeyframes::F32(keyframes) => PropertyValue::F32(tween(
+                |i| keyframes[i],
+                interpolation,
+                step_start,
+                lerp,
+                duration,
+                f32::lerp,
+            )),
+            PropertyKeyframes::Vec2(keyframes) => PropertyValue::Vec2(tween(
+                |i| keyframes[i],
+                interpolation,
+                step_start,
+                lerp,
+                duration,
+                Vec2::lerp,
+            )),
+            PropertyKeyframes::Vec3(keyframes) => PropertyValue::Vec3(tween(
+                |i| keyframes[i],
+                interpolation,
+                step_start,
+                lerp,
+                duration,
+                Vec3::lerp,
+            )),
+            PropertyKeyframes::Vec4(keyframes) => PropertyValue::Vec4(tween(
+                |i| keyframes[i],
+                interpolation,
+                step_start,
+                lerp,
+                duration,
+                Vec4::lerp,
+            )),
+            PropertyKeyframes::Quat(keyframes) => PropertyValue::Quat(
+                tween(
+                    |i| keyframes[i],
+                    interpolation,
+                    step_start,
+                    lerp,
+                    duration,
+                    |start, mut end, lerp| {
+                        // Choose the smallest angle for the rotation
+                        if end.dot(start) < 0.0 {
+                            end = -end;
+                        }
+                        start.normalize().slerp(end.normalize(), lerp)
+                    },
+                )
+                .normalize(),
+            ),
+            PropertyKeyframes::Color(keyframes) => PropertyValue::Color(tween(
+                |i| keyframes[i].rgba_linear_to_vec4(),
+                interpolation,
+                step_start,
+                lerp,
+                duration,
+                Vec4::lerp,
+            )),
+        })
+    }
+
+    /// Like [`PropertyCurve::sample`], but holds the first and last keyframes
+    /// outside of the keyframes.
+    fn sample_clamped(&self, seek_time: f32) -> Option<PropertyValue> {
+        let first = *self.keyframe_timestamps.first()?;
+        let last = *self.keyframe_timestamps.last()?;
+        if seek_time <= first {
+            Some(self.keyframe_value(0))
+        } else if seek_time >= last {
+            Some(self.keyframe_value(self.keyframe_timestamps.len() - 1))
+        } else {
+            self.sample(seek_time)
+        }
+    }
+
+    /// The value of the keyframe at `index`, skipping the tangents of cubic
+    /// splines.
+    fn keyframe_value(&self, index: usize) -> PropertyValue {
+        let index = match self.interpolation {
+            Interpolation::CubicSpline => index * 3 + 1,
+            Interpolation::Linear | Interpolation::Step => index,
+        };
+        match &self.keyframes {
+            PropertyKeyframes::F32(keyframes) => PropertyValue::F32(keyframes[index]),
+            PropertyKeyframes::Vec2(keyframes) => PropertyValue::Vec2(keyframes[index]),
+            PropertyKeyframes::Vec3(keyframes) => PropertyValue::Vec3(keyframes[index]),
+            PropertyKeyframes::Vec4(keyframes) => PropertyValue::Vec4(keyframes[index]),
+            PropertyKeyframes::Quat(keyframes) => PropertyValue::Quat(keyframes[index]),
+            PropertyKeyframes::Color(keyframes) => {
+                PropertyValue::Color(keyframes[index].rgba_linear_to_vec4())
+            }
+        }
+    }
+}
+
+/// Interpolates between the keyframes returned by `keyframe`, which is
+/// indexed like the keyframes of a [`PropertyCurve`].
+fn tween<T>(
+    keyframe: impl Fn(usize) -> T,
+    interpolation: &Interpolation,
+    step_start: usize,
+    lerp: f32,
+    duration: f32,
+    linear: impl Fn(T, T, f32) -> T,
+) -> T
+where
+    T: Mul<f32, Output = T> + Add<Output = T>,
+{
+    match interpolation {
+        Interpolation::Step => keyframe(step_start),
+        Interpolation::Linear => linear(keyframe(step_start), keyframe(step_start + 1), lerp),
+        Interpolation::CubicSpline => cubic_spline_interpolation(
+            keyframe(step_start * 3 + 1),
+            keyframe(step_start * 3 + 2),
+            keyframe((step_start + 1) * 3),
+            keyframe((step_start + 1) * 3 + 1),
+            lerp,
+            duration,
+        ),
+    }
+}
+
+impl PropertyValue {
+    /// Blends this value into `field` by `weight`, or adds its difference from
+    /// `reference` if one is given.
+    ///
+    /// Returns `false` if the type of `field` doesn't match.
+    fn apply(
+        &self,
+        reference: Option<&PropertyValue>,
+        weight: f32,
+        field: &mut dyn Reflect,
+    ) -> bool {
+        macro_rules! apply_linear {
+            ($value:expr, $ty:ty, $variant:ident) => {{
+                let Some(field) = field.downcast_mut::<$ty>() else {
+                    return false;
+                };
+                match reference {
+                    Some(PropertyValue::$variant(reference)) => {
+                        *field += (*$value - *reference) * weight;
+                    }
+                    _ => *field = field.lerp(*$value, weight),
+                }
+            }};
+        }
+
+        match self {
+            PropertyValue::F32(value) => apply_linear!(value, f32, F32),
+            PropertyValue::Vec2(value) => apply_linear!(value, Vec2, Vec2),
+            PropertyValue::Vec3(value) => apply_linear!(value, Vec3, Vec3),
+            PropertyValue::Vec4(value) => apply_linear!(value, Vec4, Vec4),
+            PropertyValue::Quat(value) => {
+                let Some(field) = field.downcast_mut::<Quat>() else {
+                    return false;
+                };
+                match reference {
+                    Some(PropertyValue::Quat(reference)) => {
+                        let delta = reference.inverse() * *value;
+                        *field = (*field * Quat::IDENTITY.slerp(delta, weight)).normalize();
+                    }
+                    _ => *field = field.slerp(*value, weight),
+                }
+            }
+            PropertyValue::Color(value) => {
+                let Some(field) = field.downcast_mut::<Color>() else {
+                    return false;
+                };
+                let current = field.rgba_linear_to_vec4();
+                let blended = match reference {
+                    Some(PropertyValue::Color(reference)) => {
+                        current + (*value - *reference) * weight
+                    }
+                    _ => current.lerp(*value, weight),
+                };
+                // Keep the color space the field was authored in.
+                let blended = Color::rgba_linear_from_array(blended);
+                *field = match field {
+                    Color::Rgba { .. } => blended.as_rgba(),
+                    Color::RgbaLinear { .. } => blended,
+                    Color::Hsla { .. } => blended.as_hsla(),
+                    Color::Lcha { .. } => blended.as_lcha(),
+                };
+            }
+        }
+        true
+    }
+}
+
+/// A system that animates the [`PropertyCurve`]s of the clips played by
+/// [`AnimationPlayer`]s.
+///
+/// This is an exclusive system, as the animated components and assets are
+/// only known at runtime.
+pub fn animate_properties(world: &mut World, targets: &mut QueryState<(Entity, &AnimationTarget)>) {
+    // Collect the animations to apply first, as applying them needs mutable
+    // access to the world.
+    let mut animations = Vec::new();
+    {
+        let clips = world.resource::<Assets<AnimationClip>>();
+        let masks = world.resource::<Assets<AnimationMask>>();
+        for (entity, target) in targets.iter(world) {
+            let Some(player) = world.get::<AnimationPlayer>(target.player) else {
+                continue;
+            };
+            for (animation, weight) in player.playing_animations() {
+                let has_properties = clips
+                    .get(&animation.animation_clip)
+                    .is_some_and(|clip| clip.properties.contains_key(&target.id));
+                let weight = animation.target_weight(masks, target.id, weight);
+                if has_properties && weight > 0.0 {
+                    animations.push((
+                        entity,
+                        target.id,
+                        animation.animation_clip.id(),
+                        animation.seek_time,
+                        animation.blend_mode,
+                        weight,
+                    ));
+                }
+            }
+        }
+    }
+    if animations.is_empty() {
+        return;
+    }
+
+    let type_registry = world.resource::<AppTypeRegistry>().clone();
+    let type_registry = type_registry.read();
+    world.resource_scope(|world, clips: Mut<Assets<AnimationClip>>| {
+        for (entity, target, clip, seek_time, blend_mode, weight) in animations {
+            let Some(curves) = clips
+                .get(clip)
+                .and_then(|clip| clip.properties.get(&target))
+            else {
+                continue;
+            };
+            for curve in curves {
+                let Some(value) = curve.sample(seek_time) else {
+                    continue;
+                };
+                let reference = match blend_mode {
+                    AnimationBlendMode::Override => None,
+                    AnimationBlendMode::Additive { reference_time } => {
+                        let Some(reference) = curve.sample_clamped(reference_time) else {
+                            continue;
+                        };
+                        Some(reference)
+                    }
+                };
+                let applied =
+                    with_property(world, &type_registry, entity, &curve.property, |field| {
+                        value.apply(reference.as_ref(), weight, field)
+                    });
+                if applied != Some(true) {
+                    warn_once!(
+                        "Couldn't animate the property {:?} of {:?}: the property is missing, \
+                        isn't registered for reflection, or has another type than the keyframes",
+                        curve.property,
+                        entity,
+                    );
+                }
+            }
+        }
+    });
+}
+
+/// Calls `f` with the field of `entity` described by `property`.
+///
+/// Returns [`None`] if the field couldn't be found.
+fn with_property<R>(
+    world: &mut World,
+    type_registry: &TypeRegistry,
+    entity: Entity,
+    property: &AnimatedProperty,
+    f: impl FnOnce(&mut dyn Reflect) -> R,
+) -> Option<R> {
+    match property {
+        AnimatedProperty::Component { component, path } => {
+            let reflect_component = type_registry
+                .get_with_type_path(component)?
+                .data::<ReflectComponent>()?;
+            let mut entity = world.get_entity_mut(entity)?;
+            let mut component = reflect_component.reflect_mut(&mut entity)?;
+            let field = component.reflect_path_mut(path.as_str()).ok()?;
+            Some(f(field))
+        }
+        AnimatedProperty::Asset { handle, path } => {
+            let registration = type_registry.get_with_type_path(handle)?;
+            let reflect_handle = registration.data::<ReflectHandle>()?;
+            let handle = registration
+                .data::<ReflectComponent>()?
+                .reflect(world.get_entity(entity)?)
+                .and_then(|handle| reflect_handle.downcast_handle_untyped(handle.as_any()))?;
+            let asset = type_registry
+                .get_type_data::<ReflectAsset>(reflect_handle.asset_type_id())?
+                .get_mut(world, handle)?;
+            let field = asset.reflect_path_mut(path.as_str()).ok()?;
+            Some(f(field))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AnimationLayer, AnimationTargetId};
+    use bevy_asset::AssetApp;
+    use bevy_core::Name;
+    use bevy_ecs::system::RunSystemOnce;
+
+    #[derive(Component, Reflect, Default)]
+    #[reflect(Component)]
+    struct Lamp {
+        intensity: f32,
+        color: Color,
+    }
+
+    #[derive(Asset, Reflect)]
+    struct Glow {
+        emissive: Color,
+    }
+
+    #[test]
+    fn animates_component_and_asset_fields() {
+        let mut app = bevy_app::App::new();
+        app.add_plugins(bevy_asset::AssetPlugin::default())
+            .init_asset::<AnimationClip>()
+            .init_asset::<AnimationMask>()
+            .init_asset::<Glow>()
+            .register_asset_reflect::<Glow>()
+            .register_type::<Lamp>();
+        let world = &mut app.world;
+
+        let id = AnimationTargetId::from_name(&Name::new("Lamp"));
+        let mut clip = AnimationClip::default();
+        clip.add_property_curve_to_target(
+            id,
+            PropertyCurve {
+                property: AnimatedProperty::component::<Lamp>("intensity"),
+                keyframe_timestamps: vec![0.0, 2.0],
+                keyframes: PropertyKeyframes::F32(vec![0.0, 100.0]),
+                interpolation: Interpolation::Linear,
+            },
+        );
+        clip.add_property_curve_to_target(
+            id,
+            PropertyCurve {
+                property: AnimatedProperty::asset::<Glow>("emissive"),
+                keyframe_timestamps: vec![0.0],
+                keyframes: PropertyKeyframes::Color(vec![Color::rgba_linear(1.0, 0.5, 0.0, 1.0)]),
+                interpolation: Interpolation::Step,
+            },
+        );
+        assert_eq!(clip.duration(), 2.0);
+        let clip = world.resource_mut::<Assets<AnimationClip>>().add(clip);
+        let glow = world.resource_mut::<Assets<Glow>>().add(Glow {
+            emissive: Color::BLACK,
+        });
+
+        let mut player = AnimationPlayer::default();
+        player.add_layer(AnimationLayer::new(clip).with_weight(0.5));
+        player.layer_mut(0).unwrap().seek_to(1.0);
+        let player = world.spawn(player).id();
+        let lamp = world
+            .spawn((
+                AnimationTarget { id, player },
+                Lamp {
+                    intensity: 10.0,
+                    color: Color::WHITE,
+                },
+                glow.clone(),
+            ))
+            .id();
+
+        world.run_system_once(animate_properties);
+
+        // Half-way between the current intensity and the sampled one.
+        assert_eq!(world.get::<Lamp>(lamp).unwrap().intensity, 30.0);
+        let emissive = world
+            .resource::<Assets<Glow>>()
+            .get(&glow)
+            .unwrap()
+            .emissive;
+        assert!(emissive
+            .rgba_linear_to_vec4()
+            .abs_diff_eq(Vec4::new(0.5, 0.25, 0.0, 1.0), 1e-5));
+        assert!(matches!(emissive, Color::Rgba { .. }));
+    }
+}
@@ -0,0 +1,298 @@
+//! Retargeting of animation clips from one skeleton to another.
+//!
+//! An [`AnimationClip`] refers to the bones it animates by
+//! [`AnimationTargetId`], and stores their local transforms as authored for
+//! one specific skeleton. A [`RetargetMap`] converts such a clip into a clip
+//! animating another skeleton, whose bones may have different names, a
+//! different rest pose, and different proportions.
+
+use bevy_core::Name;
+use bevy_math::{Quat, Vec3};
+use bevy_reflect::Reflect;
+use bevy_transform::prelude::Transform;
+use bevy_utils::hashbrown::HashMap;
+use bevy_utils::NoOpHash;
+
+use crate::{
+    compression::QuantizedQuat, AnimationClip, AnimationTargetId, Interpolation, Keyframes,
+    VariableCurve,
+};
+
+/// Below this length, a rest translation is considered to be zero.
+const EPSILON: f32 = 1e-5;
+
+/// A bone of a skeleton, as needed to build a [`RetargetMap`].
+///
+/// This is typically collected from the [`AnimationTarget`](crate::AnimationTarget),
+/// [`Name`] and [`Transform`] components of a freshly spawned skeleton, so
+/// that the transform is the rest pose.
+#[derive(Clone, Debug)]
+pub struct SkeletonBone {
+    /// The ID the animation clips use to refer to this bone.
+    pub id: AnimationTargetId,
+    /// The name of this bone, used to match bones across skeletons.
+    pub name: Name,
+    /// The local transform of this bone in the rest pose.
+    pub rest: Transform,
+}
+
+/// How a single bone is retargeted.
+#[derive(Reflect, Clone, Debug)]
+pub struct RetargetBone {
+    /// The bone animated by retargeted clips.
+    pub target: AnimationTargetId,
+    /// The local rest pose of the bone in the source skeleton.
+    pub source_rest: Transform,
+    /// The local rest pose of the bone in the target skeleton.
+    pub target_rest: Transform,
+    /// The factor applied to translations relative to the rest pose.
+    ///
+    /// This compensates for the difference of length of the bone between the
+    /// two skeletons.
+    pub translation_scale: f32,
+}
+
+/// Maps the bones of a source skeleton onto the bones of a target skeleton,
+/// to convert animation clips authored for the former into clips for the
+/// latter.
+///
+/// For each mapped bone:
+/// - rotations are applied relative to the rest pose, so that the rotation
+///   from the source rest pose is applied on top of the target rest pose,
+/// - translations are applied relative to the rest pose, scaled by the ratio
+///   of the bone lengths,
+/// - scales, morph weights and reflected properties are copied as-is.
+///
+/// Curves of bones that aren't mapped are dropped.
+#[derive(Reflect, Clone, Debug, Default)]
+pub struct RetargetMap {
+    bones: HashMap<AnimationTargetId, RetargetBone, NoOpHash>,
+}
+
+impl RetargetMap {
+    /// Creates a map between the bones of two skeletons.
+    ///
+    /// `rename` gives the name in the target skeleton of a bone of the source
+    /// skeleton, e.g. `|name| name.clone()` when both skeletons use the same
+    /// naming convention. Source bones whose name isn't found in the target
+    /// skeleton aren't mapped.
+    pub fn from_skeletons(
+        source: &[SkeletonBone],
+        target: &[SkeletonBone],
+        rename: impl Fn(&Name) -> Name,
+    ) -> Self {
+        let mut map = Self::default();
+        for source_bone in source {
+            let target_name = rename(&source_bone.name);
+            if let Some(target_bone) = target.iter().find(|bone| bone.name == target_name) {
+                map.add_bone(
+                    source_bone.id,
+                    target_bone.id,
+                    source_bone.rest,
+                    target_bone.rest,
+                );
+            }
+        }
+        map
+    }
+
+    /// Maps the bone `source` to the bone `target`, given their rest poses.
+    ///
+    /// The translation scale is the ratio of the lengths of the rest
+    /// translations of the bones, or `1.0` if the source bone has no length.
+    pub fn add_bone(
+        &mut self,
+        source: AnimationTargetId,
+        target: AnimationTargetId,
+        source_rest: Transform,
+        target_rest: Transform,
+    ) -> &mut Self {
+        let source_length = source_rest.translation.length();
+        let translation_scale = if source_length > EPSILON {
+            target_rest.translation.length() / source_length
+        } else {
+            1.0
+        };
+        self.bones.insert(
+            source,
+            RetargetBone {
+                target,
+                source_rest,
+                target_rest,
+                translation_scale,
+            },
+        );
+        self
+    }
+
+    /// Gets how the bone `source` is retargeted, if it is mapped.
+    pub fn bone(&self, source: AnimationTargetId) -> Option<&RetargetBone> {
+        self.bones.get(&source)
+    }
+
+    /// Gets how the bone `source` is retargeted mutably, e.g. to override its
+    /// [`RetargetBone::translation_scale`].
+    pub fn bone_mut(&mut self, source: AnimationTargetId) -> Option<&mut RetargetBone> {
+        self.bones.get_mut(&source)
+    }
+
+    /// Converts `clip` into a clip animating the target skeleton.
+    ///
+    /// The event tracks of the clip are kept as-is.
+    pub fn retarget(&self, clip: &AnimationClip) -> AnimationClip {
+        let mut retargeted = AnimationClip {
+            events: clip.events.clone(),
+            duration: clip.duration,
+            ..Default::default()
+        };
+        for (source, curves) in &clip.curves {
+            let Some(bone) = self.bones.get(source) else {
+                continue;
+            };
+            for curve in curves {
+                retargeted.add_curve_to_target(bone.target, bone.retarget_curve(curve));
+            }
+        }
+        for (source, curves) in &clip.properties {
+            if let Some(bone) = self.bones.get(source) {
+                retargeted.properties.insert(bone.target, curves.clone());
+            }
+        }
+        retargeted
+    }
+}
+
+impl RetargetBone {
+    fn retarget_curve(&self, curve: &VariableCurve) -> VariableCurve {
+        let is_tangent = |index: usize| {
+            matches!(curve.interpolation, Interpolation::CubicSpline) && index % 3 != 1
+        };
+        let keyframes = match &curve.keyframes {
+            Keyframes::Rotation(keyframes) => {
+                // Left-multiplying by a constant is linear, so tangents are
+                // converted the same way as values.
+                let offset = self.target_rest.rotation * self.source_rest.rotation.inverse();
+                Keyframes::Rotation(
+                    keyframes
+                        .iter()
+                        .enumerate()
+                        .map(|(index, &rotation)| {
+                            let rotation = offset * rotation;
+                            if is_tangent(index) {
+                                rotation
+                            } else {
+                                rotation.normalize()
+                            }
+                        })
+                        .collect::<Vec<Quat>>(),
+                )
+            }
+            Keyframes::QuantizedRotation(keyframes) => {
+                let offset = self.target_rest.rotation * self.source_rest.rotation.inverse();
+                Keyframes::QuantizedRotation(
+                    keyframes
+                        .iter()
+                        .map(|&rotation| QuantizedQuat::from_quat(offset * rotation.to_quat()))
+                        .collect(),
+                )
+            }
+            Keyframes::Translation(keyframes) => Keyframes::Translation(
+                keyframes
+                    .iter()
+                    .enumerate()
+                    .map(|(index, &translation)| {
+                        if is_tangent(index) {
+                            translation * self.translation_scale
+                        } else {
+                            self.target_rest.translation
+                                + (translation - self.source_rest.translation)
+                                    * self.translation_scale
+                        }
+                    })
+                    .collect::<Vec<Vec3>>(),
+            ),
+            keyframes @ (Keyframes::Scale(_) | Keyframes::Weights(_)) => keyframes.clone(),
+        };
+        VariableCurve {
+            keyframe_timestamps: curve.keyframe_timestamps.clone(),
+            keyframes,
+            interpolation: curve.interpolation.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bone(name: &str, rest: Transform) -> SkeletonBone {
+        let name = Name::new(name.to_string());
+        SkeletonBone {
+            id: AnimationTargetId::from_names([Name::new("Root"), name.clone()].iter()),
+            name,
+            rest,
+        }
+    }
+
+    #[test]
+    fn retargets_rotations_and_translations_relative_to_rest_pose() {
+        let source = [bone("Hips", Transform::from_xyz(0.0, 1.0, 0.0))];
+        let target = [bone(
+            "mixamorig:Hips",
+            Transform::from_xyz(0.0, 2.0, 0.0).with_rotation(Quat::from_rotation_y(1.0)),
+        )];
+        let map = RetargetMap::from_skeletons(&source, &target, |name| {
+            Name::new(format!("mixamorig:{name}"))
+        });
+
+        let mut clip = AnimationClip::default();
+        clip.add_event(0.5, "footstep");
+        clip.add_curve_to_target(
+            source[0].id,
+            VariableCurve {
+                keyframe_timestamps: vec![0.0, 1.0],
+                keyframes: Keyframes::Translation(vec![
+                    Vec3::new(0.0, 1.0, 0.0),
+                    Vec3::new(0.5, 1.0, 0.0),
+                ]),
+                interpolation: Interpolation::Linear,
+            },
+        );
+        clip.add_curve_to_target(
+            source[0].id,
+            VariableCurve {
+                keyframe_timestamps: vec![0.0],
+                keyframes: Keyframes::Rotation(vec![Quat::from_rotation_x(0.5)]),
+                interpolation: Interpolation::Step,
+            },
+        );
+        // Unmapped bones are dropped.
+        clip.add_curve_to_target(
+            AnimationTargetId::from_name(&Name::new("Tail")),
+            VariableCurve {
+                keyframe_timestamps: vec![0.0],
+                keyframes: Keyframes::Scale(vec![Vec3::ONE]),
+                interpolation: Interpolation::Step,
+            },
+        );
+
+        let retargeted = map.retarget(&clip);
+        assert_eq!(retargeted.curves().len(), 1);
+        assert_eq!(retargeted.events().len(), 1);
+        assert!(retargeted.curves_for_target(source[0].id).is_none());
+
+        let curves = retargeted.curves_for_target(target[0].id).unwrap();
+        let Keyframes::Translation(translations) = &curves[0].keyframes else {
+            panic!("expected translations");
+        };
+        // Twice as long a bone moves twice as far.
+        assert!(translations[0].abs_diff_eq(Vec3::new(0.0, 2.0, 0.0), 1e-6));
+        assert!(translations[1].abs_diff_eq(Vec3::new(1.0, 2.0, 0.0), 1e-6));
+
+        let Keyframes::Rotation(rotations) = &curves[1].keyframes else {
+            panic!("expected rotations");
+        };
+        let expected = Quat::from_rotation_y(1.0) * Quat::from_rotation_x(0.5);
+        assert!(rotations[0].abs_diff_eq(expected, 1e-6));
+    }
+}
@@ -0,0 +1,289 @@
+//! Root motion, which moves characters by the motion authored in their
+//! animations.
+//!
+//! Animations that move the root bone of a character (e.g. a walk cycle that
+//! moves forward) make the character slide in place, as its own [`Transform`]
+//! doesn't follow. A [`RootMotion`] on an [`AnimationPlayer`] entity strips the
+//! horizontal translation and the heading of its root bone from the animated
+//! pose, and exposes how much they moved each frame instead, for gameplay code
+//! to apply to the character's [`Transform`] or to a physics controller.
+
+use bevy_asset::Assets;
+use bevy_ecs::prelude::*;
+use bevy_math::{Quat, Vec3};
+use bevy_reflect::Reflect;
+use bevy_transform::prelude::Transform;
+
+use crate::{
+    AnimationBlendMode, AnimationClip, AnimationMask, AnimationPlayer, AnimationTargetId,
+    CurveSample, PlayingAnimation,
+};
+
+/// Extracts the motion of the root bone of the [`AnimationPlayer`] on the same
+/// entity.
+///
+/// The root bone keeps the horizontal translation and the heading it had
+/// before it was first animated, and the motion the animations would have
+/// given it is exposed by [`RootMotion::translation_delta`] and
+/// [`RootMotion::rotation_delta`] instead. The motion is extracted after the
+/// animations are advanced, and before they're applied to their targets.
+///
+/// The axes are those of the parent of the root bone, which is usually the
+/// character, with Y up. A system moving the character would look like this:
+///
+/// ```
+/// # use bevy_animation::root_motion::RootMotion;
+/// # use bevy_ecs::prelude::*;
+/// # use bevy_transform::prelude::Transform;
+/// fn apply_root_motion(mut characters: Query<(&RootMotion, &mut Transform)>) {
+///     for (root_motion, mut transform) in &mut characters {
+///         let translation = transform.rotation * root_motion.translation_delta();
+///         transform.translation += translation;
+///         transform.rotation *= root_motion.rotation_delta();
+///     }
+/// }
+/// ```
+#[derive(Component, Reflect, Clone, Debug)]
+#[reflect(Component)]
+pub struct RootMotion {
+    /// The bone whose motion is extracted, usually the hips.
+    pub root: AnimationTargetId,
+    /// Whether to extract the translation of the root bone on the horizontal
+    /// plane.
+    pub translation: bool,
+    /// Whether to extract the vertical translation of the root bone too.
+    ///
+    /// This is usually left off, so that jumps and crouches stay in the
+    /// animation.
+    pub vertical_translation: bool,
+    /// Whether to extract the rotation of the root bone around the vertical
+    /// axis.
+    ///
+    /// When enabled, [`RootMotion::translation_delta`] is relative to the
+    /// heading of the root bone, so that it follows the turns of the
+    /// character.
+    pub rotation: bool,
+    translation_delta: Vec3,
+    rotation_delta: Quat,
+}
+
+impl RootMotion {
+    /// Extracts the horizontal translation and the heading of `root`.
+    pub fn new(root: AnimationTargetId) -> Self {
+        Self {
+            root,
+            translation: true,
+            vertical_translation: false,
+            rotation: true,
+            translation_delta: Vec3::ZERO,
+            rotation_delta: Quat::IDENTITY,
+        }
+    }
+
+    /// How far the root bone moved during the last frame.
+    pub fn translation_delta(&self) -> Vec3 {
+        self.translation_delta
+    }
+
+    /// How much the root bone turned around the vertical axis during the last
+    /// frame.
+    pub fn rotation_delta(&self) -> Quat {
+        self.rotation_delta
+    }
+
+    /// The mask of the axes of the translation that are extracted.
+    fn translation_mask(&self) -> Vec3 {
+        match (self.translation, self.vertical_translation) {
+            (false, _) => Vec3::ZERO,
+            (true, false) => Vec3::new(1.0, 0.0, 1.0),
+            (true, true) => Vec3::ONE,
+        }
+    }
+
+    /// Strips the extracted motion from the freshly animated `transform` of the
+    /// root bone, restoring the translation and heading it had in `previous`.
+    pub(crate) fn strip(&self, previous: &Transform, transform: &mut Transform) {
+        let mask = self.translation_mask();
+        transform.translation = previous.translation * mask + transform.translation * (1.0 - mask);
+        if self.rotation {
+            transform.rotation = heading(previous.rotation)
+                * heading(transform.rotation).inverse()
+                * transform.rotation;
+        }
+    }
+
+    /// The motion of the root bone over the last update of `animation`.
+    fn animation_delta(
+        &self,
+        clip: &AnimationClip,
+        animation: &PlayingAnimation,
+    ) -> Option<(Vec3, Quat)> {
+        let curves = clip.curves_for_target(self.root)?;
+        let sample = |time: f32| {
+            let mut translation = None;
+            let mut rotation = None;
+            for curve in curves {
+                match curve.sample_clamped(time, 0) {
+                    Some(CurveSample::Translation(value)) => translation = Some(value),
+                    Some(CurveSample::Rotation(value)) => rotation = Some(heading(value)),
+                    _ => {}
+                }
+            }
+            (translation, rotation)
+        };
+
+        // Split playback at the end of the clip when it loops.
+        let duration = clip.duration();
+        let start = animation.previous_seek_time;
+        let end = start + animation.travel;
+        let spans = if end > duration {
+            [(start, duration), (0.0, end - duration)]
+        } else if end < 0.0 {
+            [(start, 0.0), (duration, end + duration)]
+        } else {
+            [(start, end), (end, end)]
+        };
+
+        let mut translation_delta = None;
+        let mut rotation_delta = None;
+        for (from, to) in spans {
+            let (from_translation, from_rotation) = sample(from);
+            let (to_translation, to_rotation) = sample(to);
+            if let (Some(from_rotation), Some(to_rotation)) = (from_rotation, to_rotation) {
+                let delta = to_rotation * from_rotation.inverse();
+                rotation_delta = Some(delta * rotation_delta.unwrap_or(Quat::IDENTITY));
+            }
+            if let (Some(from_translation), Some(to_translation)) =
+                (from_translation, to_translation)
+            {
+                let mut delta = to_translation - from_translation;
+                if self.rotation {
+                    delta = from_rotation.unwrap_or(Quat::IDENTITY).inverse() * delta;
+                }
+                translation_delta = Some(translation_delta.unwrap_or(Vec3::ZERO) + delta);
+            }
+        }
+
+        if translation_delta.is_none() && rotation_delta.is_none() {
+            return None;
+        }
+        let rotation_delta = if self.rotation {
+            rotation_delta.unwrap_or(Quat::IDENTITY)
+        } else {
+            Quat::IDENTITY
+        };
+        Some((
+            translation_delta.unwrap_or(Vec3::ZERO) * self.translation_mask(),
+            rotation_delta,
+        ))
+    }
+}
+
+/// The rotation of `rotation` around the vertical axis.
+fn heading(rotation: Quat) -> Quat {
+    let twist = Quat::from_xyzw(0.0, rotation.y, 0.0, rotation.w);
+    if twist.length_squared() < 1e-8 {
+        return Quat::IDENTITY;
+    }
+    twist.normalize()
+}
+
+/// A system that computes the motion of the root bones of the players with a
+/// [`RootMotion`], blending the motion of their animations like their poses.
+pub fn extract_root_motion(
+    clips: Res<Assets<AnimationClip>>,
+    masks: Res<Assets<AnimationMask>>,
+    mut players: Query<(&AnimationPlayer, &mut RootMotion)>,
+) {
+    for (player, mut root_motion) in &mut players {
+        let mut translation = Vec3::ZERO;
+        let mut rotation = Quat::IDENTITY;
+
+        if !player.is_paused() {
+            for (animation, weight) in player.playing_animations() {
+                let weight = animation.target_weight(&masks, root_motion.root, weight);
+                if weight <= 0.0 {
+                    continue;
+                }
+                let Some(clip) = clips.get(&animation.animation_clip) else {
+                    continue;
+                };
+                let Some((translation_delta, rotation_delta)) =
+                    root_motion.animation_delta(clip, animation)
+                else {
+                    continue;
+                };
+
+                match animation.blend_mode {
+                    AnimationBlendMode::Override => {
+                        translation = translation.lerp(translation_delta, weight);
+                        rotation = rotation.slerp(rotation_delta, weight);
+                    }
+                    AnimationBlendMode::Additive { .. } => {
+                        translation += translation_delta * weight;
+                        rotation = Quat::IDENTITY.slerp(rotation_delta, weight) * rotation;
+                    }
+                }
+            }
+        }
+
+        root_motion.translation_delta = translation;
+        root_motion.rotation_delta = rotation;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Interpolation, Keyframes, RepeatAnimation, VariableCurve};
+    use bevy_asset::Handle;
+
+    fn walk_clip(root: AnimationTargetId) -> AnimationClip {
+        let mut clip = AnimationClip::default();
+        clip.add_curve_to_target(
+            root,
+            VariableCurve {
+                keyframe_timestamps: vec![0.0, 1.0],
+                keyframes: Keyframes::Translation(vec![Vec3::ZERO, Vec3::new(0.0, 0.5, 2.0)]),
+                interpolation: Interpolation::Linear,
+            },
+        );
+        clip
+    }
+
+    #[test]
+    fn root_motion_follows_looping_playback() {
+        let root = AnimationTargetId(bevy_utils::Uuid::from_u128(1));
+        let clip = walk_clip(root);
+        let root_motion = RootMotion::new(root);
+
+        let mut animation = PlayingAnimation {
+            animation_clip: Handle::default(),
+            repeat: RepeatAnimation::Forever,
+            ..Default::default()
+        };
+        animation.seek_time = 0.75;
+        animation.update(0.5, clip.duration());
+        assert_eq!(animation.seek_time, 0.25);
+
+        // The clip moves 2 units forward per loop: 0.25s before the end of the
+        // clip, and 0.25s after its start.
+        let (translation, rotation) = root_motion.animation_delta(&clip, &animation).unwrap();
+        assert!((translation - Vec3::new(0.0, 0.0, 1.0)).length() < 1e-5);
+        assert_eq!(rotation, Quat::IDENTITY);
+    }
+
+    #[test]
+    fn strip_keeps_vertical_motion() {
+        let root_motion = RootMotion::new(AnimationTargetId(bevy_utils::Uuid::from_u128(1)));
+        let previous = Transform::from_xyz(1.0, 1.0, 1.0);
+        let mut transform = Transform::from_xyz(3.0, 2.0, 5.0)
+            .with_rotation(Quat::from_rotation_y(1.0) * Quat::from_rotation_x(0.5));
+        root_motion.strip(&previous, &mut transform);
+
+        assert_eq!(transform.translation, Vec3::new(1.0, 2.0, 1.0));
+        assert!(transform
+            .rotation
+            .abs_diff_eq(Quat::from_rotation_x(0.5), 1e-5));
+    }
+}
@@ -0,0 +1,543 @@
+//! Lightweight tweening of component fields.
+//!
+//! A tween moves a value from its current state to a target over a duration, following an
+//! [`EaseFunction`]. Unlike [`AnimationClip`](crate::AnimationClip)s, tweens don't need any asset
+//! and are started from code:
+//!
+//! ```
+//! # use std::time::Duration;
+//! # use bevy_animation::tween::{lens, TweenCommandsExt};
+//! # use bevy_ecs::prelude::*;
+//! # use bevy_math::{curve::EaseFunction, Vec3};
+//! fn pop(mut commands: Commands, entity: Entity) {
+//!     commands.entity(entity).tween(
+//!         lens::translation,
+//!         Vec3::new(0.0, 1.0, 0.0),
+//!         Duration::from_secs_f32(0.5),
+//!         EaseFunction::CubicOut,
+//!     );
+//! }
+//! ```
+//!
+//! Tweens of the same component run concurrently, and can be sequenced with [`Tween::then`].
+//! Tweening a component other than [`Transform`](bevy_transform::prelude::Transform) requires registering it with
+//! [`TweenApp::register_tweenable`].
+
+use std::time::Duration;
+
+use bevy_app::{App, PostUpdate};
+use bevy_core::Name;
+use bevy_ecs::{prelude::*, system::EntityCommands};
+use bevy_math::curve::{EaseFunction, Interpolate};
+use bevy_render::mesh::morph::MorphWeights;
+use bevy_time::Time;
+use bevy_transform::TransformSystem;
+
+use crate::RepeatAnimation;
+
+/// Common lenses for [`Tween::new`] and [`TweenCommandsExt::tween`].
+pub mod lens {
+    use bevy_math::{Quat, Vec3};
+    use bevy_transform::prelude::Transform;
+
+    /// Targets [`Transform::translation`].
+    pub fn translation(transform: &mut Transform) -> &mut Vec3 {
+        &mut transform.translation
+    }
+
+    /// Targets [`Transform::rotation`].
+    pub fn rotation(transform: &mut Transform) -> &mut Quat {
+        &mut transform.rotation
+    }
+
+    /// Targets [`Transform::scale`].
+    pub fn scale(transform: &mut Transform) -> &mut Vec3 {
+        &mut transform.scale
+    }
+}
+
+/// Writes the state of a tween in progress to a component of type `C`.
+pub trait TweenLens<C>: Send + Sync + 'static {
+    /// Updates `component` for the given eased `progress`, which starts at 0 and ends at 1 but
+    /// may overshoot this range.
+    fn apply(&mut self, component: &mut C, progress: f32);
+}
+
+/// The [`TweenLens`] of [`Tween::new`], interpolating a field of a component from its value at
+/// the start of the tween to a target value.
+struct FieldLens<C, T> {
+    field: fn(&mut C) -> &mut T,
+    start: Option<T>,
+    end: T,
+}
+
+impl<C: 'static, T: Interpolate + Send + Sync + 'static> TweenLens<C> for FieldLens<C, T> {
+    fn apply(&mut self, component: &mut C, progress: f32) {
+        let field = (self.field)(component);
+        let start = self.start.get_or_insert_with(|| field.clone());
+        *field = start.interpolate(&self.end, progress);
+    }
+}
+
+/// A [`TweenLens`] moving the weight of a single morph target of [`MorphWeights`] to a target
+/// value.
+///
+/// Use [`MorphWeights::target_index`] to find the index of a morph target from its name, as
+/// imported from glTF.
+#[derive(Clone, Debug)]
+pub struct MorphWeightLens {
+    index: usize,
+    start: Option<f32>,
+    end: f32,
+}
+
+impl MorphWeightLens {
+    /// Creates a lens moving the weight of the morph target `index` to `target`.
+    pub fn new(index: usize, target: f32) -> Self {
+        Self {
+            index,
+            start: None,
+            end: target,
+        }
+    }
+}
+
+impl TweenLens<MorphWeights> for MorphWeightLens {
+    fn apply(&mut self, morph_weights: &mut MorphWeights, progress: f32) {
+        let Some(weight) = morph_weights.weights_mut().get_mut(self.index) else {
+            return;
+        };
+        let start = *self.start.get_or_insert(*weight);
+        *weight = start.interpolate(&self.end, progress);
+    }
+}
+
+/// A transition of a component of type `C`, played by adding it to the [`Tweens<C>`] of an
+/// entity.
+pub struct Tween<C> {
+    lens: Box<dyn TweenLens<C>>,
+    duration: Duration,
+    elapsed: Duration,
+    ease: EaseFunction,
+    repeat: RepeatAnimation,
+    completions: u32,
+    name: Option<Name>,
+    next: Option<Box<Tween<C>>>,
+}
+
+impl<C: Component> Tween<C> {
+    /// Creates a tween moving the field given by `field` from its value when the tween starts to
+    /// `target`.
+    pub fn new<T: Interpolate + Send + Sync + 'static>(
+        field: fn(&mut C) -> &mut T,
+        target: T,
+        duration: Duration,
+        ease: EaseFunction,
+    ) -> Self {
+        Self::from_lens(
+            FieldLens {
+                field,
+                start: None,
+                end: target,
+            },
+            duration,
+            ease,
+        )
+    }
+
+    /// Creates a tween applying its progress with a custom [`TweenLens`].
+    pub fn from_lens(lens: impl TweenLens<C>, duration: Duration, ease: EaseFunction) -> Self {
+        Self {
+            lens: Box::new(lens),
+            duration,
+            elapsed: Duration::ZERO,
+            ease,
+            repeat: RepeatAnimation::Never,
+            completions: 0,
+            name: None,
+            next: None,
+        }
+    }
+
+    /// Names this tween, to recognize it in [`TweenCompleted`] events.
+    pub fn with_name(mut self, name: impl Into<Name>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Sets how many times the tween plays before completing.
+    ///
+    /// Each repetition starts over from the value the field had when the tween first started.
+    pub fn with_repeat(mut self, repeat: RepeatAnimation) -> Self {
+        self.repeat = repeat;
+        self
+    }
+
+    /// Plays `next` once this tween, and the tweens already sequenced after it, complete.
+    pub fn then(mut self, next: Tween<C>) -> Self {
+        let mut last = &mut self;
+        while let Some(ref mut following) = last.next {
+            last = following;
+        }
+        last.next = Some(Box::new(next));
+        self
+    }
+
+    /// The name of this tween, if any.
+    pub fn name(&self) -> Option<&Name> {
+        self.name.as_ref()
+    }
+
+    /// The time elapsed since the start of the current repetition of this tween.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// The duration of a single repetition of this tween.
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// The progress of the current repetition of this tween, between 0 and 1, before easing.
+    pub fn progress(&self) -> f32 {
+        if self.duration.is_zero() {
+            1.0
+        } else {
+            (self.elapsed.as_secs_f32() / self.duration.as_secs_f32()).min(1.0)
+        }
+    }
+
+    fn is_finished(&self) -> bool {
+        match self.repeat {
+            RepeatAnimation::Never => self.completions >= 1,
+            RepeatAnimation::Count(count) => self.completions >= count,
+            RepeatAnimation::Forever => false,
+        }
+    }
+
+    /// Advances the tween by `delta` and applies it to `component`.
+    ///
+    /// Returns `true` when the tween completes.
+    fn advance(&mut self, delta: Duration, component: &mut C) -> bool {
+        self.elapsed += delta;
+        while self.elapsed >= self.duration {
+            self.completions += 1;
+            if self.is_finished() || self.duration.is_zero() {
+                self.elapsed = self.duration;
+                self.lens.apply(component, self.ease.ease(1.0));
+                return true;
+            }
+            self.elapsed -= self.duration;
+        }
+        self.lens.apply(component, self.ease.ease(self.progress()));
+        false
+    }
+}
+
+/// The tweens currently playing on the component `C` of an entity.
+///
+/// Usually created with [`TweenCommandsExt::tween`] or [`TweenCommandsExt::add_tween`].
+#[derive(Component)]
+pub struct Tweens<C: Component> {
+    tweens: Vec<Tween<C>>,
+    paused: bool,
+}
+
+impl<C: Component> Default for Tweens<C> {
+    fn default() -> Self {
+        Self {
+            tweens: Vec::new(),
+            paused: false,
+        }
+    }
+}
+
+impl<C: Component> Tweens<C> {
+    /// Starts playing `tween`, concurrently with the tweens already playing.
+    pub fn add(&mut self, tween: Tween<C>) -> &mut Self {
+        self.tweens.push(tween);
+        self
+    }
+
+    /// The tweens currently playing.
+    pub fn iter(&self) -> impl Iterator<Item = &Tween<C>> {
+        self.tweens.iter()
+    }
+
+    /// Returns `true` if no tween is playing.
+    pub fn is_empty(&self) -> bool {
+        self.tweens.is_empty()
+    }
+
+    /// Pauses all the tweens.
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    /// Resumes all the tweens.
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    /// Returns `true` if the tweens are paused.
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Stops all the tweens, leaving the component in its current state.
+    ///
+    /// No [`TweenCompleted`] event is sent for cancelled tweens.
+    pub fn cancel(&mut self) {
+        self.tweens.clear();
+    }
+
+    /// Stops the tweens named `name`, and the tweens sequenced after them.
+    pub fn cancel_named(&mut self, name: &str) {
+        self.tweens
+            .retain(|tween| tween.name.as_ref().map(Name::as_str) != Some(name));
+    }
+}
+
+/// Event sent when a [`Tween`] completes.
+#[derive(Event, Clone, Debug)]
+pub struct TweenCompleted {
+    /// The entity whose component was tweened.
+    pub entity: Entity,
+    /// The name of the tween, if it was given one with [`Tween::with_name`].
+    pub name: Option<Name>,
+}
+
+/// Advances the [`Tweens`] of the component `C`, and applies them.
+pub fn advance_tweens<C: Component>(
+    time: Res<Time>,
+    mut tweened: Query<(Entity, &mut Tweens<C>, &mut C)>,
+    mut completed: EventWriter<TweenCompleted>,
+) {
+    let delta = time.delta();
+    for (entity, mut tweens, mut component) in &mut tweened {
+        if tweens.paused || tweens.tweens.is_empty() {
+            continue;
+        }
+        let mut index = 0;
+        while index < tweens.tweens.len() {
+            let tween = &mut tweens.tweens[index];
+            if !tween.advance(delta, &mut component) {
+                index += 1;
+                continue;
+            }
+            completed.send(TweenCompleted {
+                entity,
+                name: tween.name.clone(),
+            });
+            match tween.next.take() {
+                // The next tween starts on the next frame.
+                Some(next) => {
+                    tweens.tweens[index] = *next;
+                    index += 1;
+                }
+                None => {
+                    tweens.tweens.swap_remove(index);
+                }
+            }
+        }
+    }
+}
+
+/// Adds tween-related builder methods to [`App`].
+pub trait TweenApp {
+    /// Allows tweening the component `C`.
+    ///
+    /// [`Transform`](bevy_transform::prelude::Transform) and [`MorphWeights`] are registered by [`AnimationPlugin`](crate::AnimationPlugin).
+    fn register_tweenable<C: Component>(&mut self) -> &mut Self;
+}
+
+impl TweenApp for App {
+    fn register_tweenable<C: Component>(&mut self) -> &mut Self {
+        self.add_event::<TweenCompleted>().add_systems(
+            PostUpdate,
+            advance_tweens::<C>
+                // Tweens override the animated pose.
+                .after(crate::jiggle::simulate_jiggle_bones)
+                .before(TransformSystem::TransformPropagate),
+        )
+    }
+}
+
+/// Adds tween-related methods to [`EntityCommands`].
+pub trait TweenCommandsExt {
+    /// Tweens the field given by `field` of the component `C` of this entity to `target`.
+    ///
+    /// This is a shorthand for [`TweenCommandsExt::add_tween`] with [`Tween::new`].
+    fn tween<C: Component, T: Interpolate + Send + Sync + 'static>(
+        &mut self,
+        field: fn(&mut C) -> &mut T,
+        target: T,
+        duration: Duration,
+        ease: EaseFunction,
+    ) -> &mut Self;
+
+    /// Starts playing `tween` on the component `C` of this entity.
+    fn add_tween<C: Component>(&mut self, tween: Tween<C>) -> &mut Self;
+
+    /// Pauses the tweens of the component `C` of this entity.
+    fn pause_tweens<C: Component>(&mut self) -> &mut Self;
+
+    /// Resumes the tweens of the component `C` of this entity.
+    fn resume_tweens<C: Component>(&mut self) -> &mut Self;
+
+    /// Cancels the tweens of the component `C` of this entity.
+    fn cancel_tweens<C: Component>(&mut self) -> &mut Self;
+}
+
+impl TweenCommandsExt for EntityCommands<'_> {
+    fn tween<C: Component, T: Interpolate + Send + Sync + 'static>(
+        &mut self,
+        field: fn(&mut C) -> &mut T,
+        target: T,
+        duration: Duration,
+        ease: EaseFunction,
+    ) -> &mut Self {
+        self.add_tween(Tween::new(field, target, duration, ease))
+    }
+
+    fn add_tween<C: Component>(&mut self, tween: Tween<C>) -> &mut Self {
+        self.add(move |mut entity: EntityWorldMut| {
+            match entity.get_mut::<Tweens<C>>() {
+                Some(mut tweens) => {
+                    tweens.add(tween);
+                }
+                None => {
+                    let mut tweens = Tweens::default();
+                    tweens.add(tween);
+                    entity.insert(tweens);
+                }
+            };
+        })
+    }
+
+    fn pause_tweens<C: Component>(&mut self) -> &mut Self {
+        self.add(|mut entity: EntityWorldMut| {
+            if let Some(mut tweens) = entity.get_mut::<Tweens<C>>() {
+                tweens.pause();
+            }
+        })
+    }
+
+    fn resume_tweens<C: Component>(&mut self) -> &mut Self {
+        self.add(|mut entity: EntityWorldMut| {
+            if let Some(mut tweens) = entity.get_mut::<Tweens<C>>() {
+                tweens.resume();
+            }
+        })
+    }
+
+    fn cancel_tweens<C: Component>(&mut self) -> &mut Self {
+        self.add(|mut entity: EntityWorldMut| {
+            entity.remove::<Tweens<C>>();
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_ecs::system::RunSystemOnce;
+    use bevy_math::Vec3;
+    use bevy_transform::prelude::Transform;
+
+    fn run(world: &mut World, seconds: f32) {
+        world
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs_f32(seconds));
+        world.run_system_once(advance_tweens::<Transform>);
+    }
+
+    fn completed(world: &mut World) -> Vec<Option<Name>> {
+        world
+            .resource_mut::<Events<TweenCompleted>>()
+            .drain()
+            .map(|event| event.name)
+            .collect()
+    }
+
+    #[test]
+    fn tweens_are_sequenced_and_complete() {
+        let mut world = World::new();
+        world.init_resource::<Time>();
+        world.init_resource::<Events<TweenCompleted>>();
+        let entity = world.spawn(Transform::default()).id();
+        let mut tweens = Tweens::default();
+        tweens.add(
+            Tween::new(
+                lens::translation,
+                Vec3::X,
+                Duration::from_secs(1),
+                EaseFunction::Linear,
+            )
+            .with_name("right")
+            .then(
+                Tween::new(
+                    lens::translation,
+                    Vec3::ZERO,
+                    Duration::from_secs(1),
+                    EaseFunction::Linear,
+                )
+                .with_name("back"),
+            ),
+        );
+        tweens.add(Tween::new(
+            lens::scale,
+            Vec3::splat(2.0),
+            Duration::from_secs(2),
+            EaseFunction::Linear,
+        ));
+        world.entity_mut(entity).insert(tweens);
+
+        run(&mut world, 0.5);
+        let transform = world.get::<Transform>(entity).unwrap();
+        assert!(transform.translation.abs_diff_eq(Vec3::X * 0.5, 1e-5));
+        assert!(transform.scale.abs_diff_eq(Vec3::splat(1.25), 1e-5));
+        assert!(completed(&mut world).is_empty());
+
+        run(&mut world, 0.5);
+        assert_eq!(completed(&mut world), vec![Some(Name::new("right"))]);
+        assert_eq!(world.get::<Transform>(entity).unwrap().translation, Vec3::X);
+
+        // The second tween starts from where the first one ended.
+        run(&mut world, 0.5);
+        let transform = world.get::<Transform>(entity).unwrap();
+        assert!(transform.translation.abs_diff_eq(Vec3::X * 0.5, 1e-5));
+
+        run(&mut world, 0.5);
+        assert_eq!(completed(&mut world), vec![Some(Name::new("back")), None]);
+        assert!(world.get::<Tweens<Transform>>(entity).unwrap().is_empty());
+    }
+
+    #[test]
+    fn paused_tweens_dont_advance() {
+        let mut world = World::new();
+        world.init_resource::<Time>();
+        world.init_resource::<Events<TweenCompleted>>();
+        let entity = world.spawn(Transform::default()).id();
+        let mut tweens = Tweens::default();
+        tweens.add(Tween::new(
+            lens::translation,
+            Vec3::X,
+            Duration::from_secs(1),
+            EaseFunction::Linear,
+        ));
+        tweens.pause();
+        world.entity_mut(entity).insert(tweens);
+
+        run(&mut world, 0.5);
+        assert_eq!(
+            world.get::<Transform>(entity).unwrap().translation,
+            Vec3::ZERO
+        );
+
+        world.get_mut::<Tweens<Transform>>(entity).unwrap().resume();
+        run(&mut world, 0.5);
+        let transform = world.get::<Transform>(entity).unwrap();
+        assert!(transform.translation.abs_diff_eq(Vec3::X * 0.5, 1e-5));
+    }
+}
@@ -513,6 +513,43 @@ impl App {
         self
     }
 
+    /// Setup the application to index the entities by the value of their component `T`.
+    ///
+    /// This is done by adding a [`Resource`] of type [`ComponentIndex::<T>`],
+    /// and inserting an [`index_update_system`] into [`First`].
+    ///
+    /// The entities can then be looked up with the [`Index<T>`] system parameter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bevy_app::prelude::*;
+    /// # use bevy_ecs::prelude::*;
+    /// #
+    /// # #[derive(Component, Clone, PartialEq, Eq, Hash)]
+    /// # struct GridPosition(i32, i32);
+    /// # let mut app = App::new();
+    /// #
+    /// app.add_index::<GridPosition>();
+    /// ```
+    ///
+    /// [`ComponentIndex::<T>`]: bevy_ecs::index::ComponentIndex
+    /// [`index_update_system`]: bevy_ecs::index::index_update_system
+    /// [`Index<T>`]: bevy_ecs::index::Index
+    pub fn add_index<T>(&mut self) -> &mut Self
+    where
+        T: Component + Eq + std::hash::Hash + Clone,
+    {
+        if !self
+            .world
+            .contains_resource::<bevy_ecs::index::ComponentIndex<T>>()
+        {
+            self.init_resource::<bevy_ecs::index::ComponentIndex<T>>()
+                .add_systems(First, bevy_ecs::index::index_update_system::<T>);
+        }
+        self
+    }
+
     /// Inserts a [`Resource`] to the current [`App`] and overwrites any [`Resource`] previously added of the same type.
     ///
     /// A [`Resource`] in Bevy represents globally unique data. [`Resource`]s must be added to Bevy apps
@@ -1262,6 +1299,41 @@ mod tests {
         );
     }
 
+    #[test]
+    fn index_lookups_follow_component_changes() {
+        use bevy_ecs::{component::Component, entity::Entity, index::Index, system::ResMut};
+
+        use super::Resource;
+        use crate::Update;
+
+        #[derive(Component, Clone, PartialEq, Eq, Hash)]
+        struct GridPosition(i32, i32);
+
+        #[derive(Resource, Default)]
+        struct Occupant(Option<Entity>);
+
+        fn find_occupant(mut index: Index<GridPosition>, mut occupant: ResMut<Occupant>) {
+            occupant.0 = index.get_single(&GridPosition(1, 2));
+        }
+
+        let mut app = App::new();
+        app.add_index::<GridPosition>()
+            .init_resource::<Occupant>()
+            .add_systems(Update, find_occupant);
+
+        let entity = app.world.spawn(GridPosition(0, 0)).id();
+        app.update();
+        assert_eq!(app.world.resource::<Occupant>().0, None);
+
+        app.world.entity_mut(entity).insert(GridPosition(1, 2));
+        app.update();
+        assert_eq!(app.world.resource::<Occupant>().0, Some(entity));
+
+        app.world.entity_mut(entity).remove::<GridPosition>();
+        app.update();
+        assert_eq!(app.world.resource::<Occupant>().0, None);
+    }
+
     /// Custom runners should be in charge of when `app::update` gets called as they may need to
     /// coordinate some state.
     /// bug: <https://github.com/bevyengine/bevy/issues/10385>
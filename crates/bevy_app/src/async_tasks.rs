@@ -0,0 +1,21 @@
+use crate::{App, Plugin, PreUpdate};
+use bevy_ecs::{
+    schedule::IntoSystemConfigs,
+    system::{any_async_tasks, run_async_tasks, AsyncTasks},
+};
+
+/// Runs the [`AsyncTasks`] of an [`App`] during [`PreUpdate`].
+///
+/// Tasks spawned with [`Commands::spawn_task`](bevy_ecs::system::Commands::spawn_task) only make
+/// progress once this plugin is added. It's part of `DefaultPlugins` and `MinimalPlugins`, apps
+/// built from individual plugins must add it themselves. [`run_async_tasks`] is an exclusive
+/// system, so it's skipped while no task is running.
+#[derive(Default)]
+pub struct AsyncTasksPlugin;
+
+impl Plugin for AsyncTasksPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AsyncTasks>()
+            .add_systems(PreUpdate, run_async_tasks.run_if(any_async_tasks));
+    }
+}
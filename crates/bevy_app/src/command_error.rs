@@ -0,0 +1,29 @@
+use crate::{App, Plugin};
+use bevy_ecs::system::CommandError;
+
+/// Registers the [`CommandError`] event, which the fallible commands send when the
+/// [`CommandErrorPolicy::SendEvent`](bevy_ecs::system::CommandErrorPolicy::SendEvent) policy is
+/// used.
+///
+/// ```
+/// # use bevy_app::{prelude::*, CommandErrorPlugin};
+/// # use bevy_ecs::{prelude::*, system::{CommandError, CommandErrorPolicy}};
+/// fn report_command_errors(mut errors: EventReader<CommandError>) {
+///     for error in errors.read() {
+///         eprintln!("{error}");
+///     }
+/// }
+///
+/// App::new()
+///     .add_plugins(CommandErrorPlugin)
+///     .insert_resource(CommandErrorPolicy::SendEvent)
+///     .add_systems(Update, report_command_errors);
+/// ```
+#[derive(Default)]
+pub struct CommandErrorPlugin;
+
+impl Plugin for CommandErrorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<CommandError>();
+    }
+}
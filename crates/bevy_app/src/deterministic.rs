@@ -0,0 +1,172 @@
+use crate::{App, Last, Plugin};
+use bevy_ecs::{
+    schedule::Schedules,
+    system::{ResMut, Resource},
+};
+use std::ops::Range;
+
+/// Makes an [`App`] run deterministically, for simulations that must give bit-identical results
+/// across runs, like lockstep networking.
+///
+/// This plugin:
+/// - makes all the schedules of the app [deterministic](bevy_ecs::schedule::Schedule::set_deterministic),
+///   including the ones added after it,
+/// - inserts a [`DeterministicRng`] resource seeded with [`DeterministicPlugin::seed`], which is
+///   stepped at the end of each update.
+///
+/// Use [`Schedule::check_determinism`](bevy_ecs::schedule::Schedule::check_determinism) in tests
+/// to find the systems whose relative order isn't specified but matters.
+#[derive(Default)]
+pub struct DeterministicPlugin {
+    /// The seed of the [`DeterministicRng`] resource.
+    pub seed: u64,
+}
+
+impl Plugin for DeterministicPlugin {
+    fn build(&self, app: &mut App) {
+        app.world
+            .resource_mut::<Schedules>()
+            .set_deterministic(true);
+        app.insert_resource(DeterministicRng::new(self.seed))
+            .add_systems(Last, step_deterministic_rng);
+    }
+}
+
+/// A seeded random number generator, which gives the same numbers on every run.
+///
+/// The numbers of each step only depend on the seed and the index of the step, and not on how many
+/// numbers were drawn during the previous steps, so that a simulation rolled back to a step draws
+/// the same numbers again. The [`DeterministicPlugin`] advances it to the next step at the end of
+/// each update.
+///
+/// The generator is not cryptographically secure.
+#[derive(Resource, Clone, Debug)]
+pub struct DeterministicRng {
+    seed: u64,
+    step: u64,
+    state: u64,
+}
+
+impl DeterministicRng {
+    /// Creates a generator at step `0` for the given `seed`.
+    pub fn new(seed: u64) -> Self {
+        let mut rng = Self {
+            seed,
+            step: 0,
+            state: 0,
+        };
+        rng.set_step(0);
+        rng
+    }
+
+    /// Returns the seed of the generator.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Returns the index of the current step.
+    pub fn step(&self) -> u64 {
+        self.step
+    }
+
+    /// Moves the generator to the start of the step `step`, for example to roll it back.
+    pub fn set_step(&mut self, step: u64) {
+        let mut step_state = step;
+        self.step = step;
+        self.state = self.seed ^ splitmix64(&mut step_state);
+    }
+
+    /// Moves the generator to the start of the next step.
+    pub fn advance_step(&mut self) {
+        self.set_step(self.step.wrapping_add(1));
+    }
+
+    /// Returns a random `u64`.
+    pub fn next_u64(&mut self) -> u64 {
+        splitmix64(&mut self.state)
+    }
+
+    /// Returns a random `u32`.
+    pub fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    /// Returns a random `f32` in `0.0..1.0`.
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// Returns a random `f64` in `0.0..1.0`.
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Returns a random `u64` in `range`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is empty.
+    pub fn range_u64(&mut self, range: Range<u64>) -> u64 {
+        assert!(!range.is_empty(), "cannot sample an empty range");
+        let len = range.end - range.start;
+        range.start + ((self.next_u64() as u128 * len as u128) >> 64) as u64
+    }
+}
+
+/// Advances the [`DeterministicRng`] to its next step.
+pub fn step_deterministic_rng(mut rng: ResMut<DeterministicRng>) {
+    rng.advance_step();
+}
+
+/// Advances `state` and returns the next output of the `SplitMix64` generator.
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Update;
+
+    #[test]
+    fn rng_steps_are_reproducible() {
+        let mut rng = DeterministicRng::new(7);
+        let first: Vec<u64> = (0..4).map(|_| rng.next_u64()).collect();
+        rng.advance_step();
+        let second = rng.next_u64();
+
+        // Drawing a different amount of numbers doesn't change the next steps.
+        let mut other = DeterministicRng::new(7);
+        other.next_u64();
+        other.advance_step();
+        assert_eq!(other.next_u64(), second);
+
+        other.set_step(0);
+        let replayed: Vec<u64> = (0..4).map(|_| other.next_u64()).collect();
+        assert_eq!(first, replayed);
+
+        assert_ne!(DeterministicRng::new(8).next_u64(), first[0]);
+        for _ in 0..100 {
+            assert!((10..20).contains(&rng.range_u64(10..20)));
+            assert!((0.0..1.0).contains(&rng.next_f32()));
+        }
+    }
+
+    #[test]
+    fn plugin_makes_schedules_deterministic() {
+        let mut app = App::new();
+        app.add_plugins(DeterministicPlugin { seed: 3 });
+        app.add_systems(Update, || {});
+        app.update();
+
+        let schedules = app.world.resource::<Schedules>();
+        assert!(schedules
+            .iter()
+            .all(|(_, schedule)| schedule.is_deterministic()));
+        assert_eq!(app.world.resource::<DeterministicRng>().step(), 1);
+    }
+}
@@ -1,6 +1,9 @@
 //! This crate is about everything concerning the highest-level, application layer of a Bevy app.
 
 mod app;
+mod async_tasks;
+mod command_error;
+mod deterministic;
 mod main_schedule;
 mod plugin;
 mod plugin_group;
@@ -10,7 +13,10 @@ mod schedule_runner;
 pub mod ci_testing;
 
 pub use app::*;
+pub use async_tasks::*;
 pub use bevy_derive::DynamicPlugin;
+pub use command_error::*;
+pub use deterministic::*;
 pub use main_schedule::*;
 pub use plugin::*;
 pub use plugin_group::*;
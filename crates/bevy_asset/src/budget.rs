@@ -0,0 +1,197 @@
+use crate::{Asset, AssetEvent, AssetEvents, AssetId, AssetPath, AssetServer, Assets};
+use bevy_app::{App, Plugin};
+use bevy_ecs::prelude::*;
+use bevy_utils::HashMap;
+use std::marker::PhantomData;
+
+/// An [`Asset`] whose memory usage can be measured, so that it can be limited by an [`AssetBudget`].
+pub trait MeasuredAsset: Asset {
+    /// Returns the number of bytes used by this asset, in CPU or GPU memory.
+    fn memory_size(&self) -> usize;
+}
+
+/// Limits the memory used by the assets of type `A`, with an [`AssetBudget`].
+///
+/// When the assets use more memory than their budget, the least recently used assets that were
+/// loaded from a path, and can thus be reloaded, are removed from [`Assets<A>`] until they fit in
+/// it again. An [`AssetEvicted`] event is sent for each of them. Their strong [`Handle`](crate::Handle)s stay
+/// valid, and an evicted asset is reloaded the next time it's used with [`AssetBudget::touch`].
+/// Assets that weren't loaded from a path are never evicted.
+///
+/// The asset type must be initialized with [`AssetApp::init_asset`](crate::AssetApp::init_asset)
+/// before adding this plugin.
+pub struct AssetBudgetPlugin<A: MeasuredAsset> {
+    /// The maximum number of bytes used by the assets.
+    pub max_bytes: usize,
+    marker: PhantomData<fn() -> A>,
+}
+
+impl<A: MeasuredAsset> AssetBudgetPlugin<A> {
+    /// Creates a plugin limiting the assets of type `A` to `max_bytes`.
+    pub fn new(max_bytes: usize) -> Self {
+        Self {
+            max_bytes,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<A: MeasuredAsset> Plugin for AssetBudgetPlugin<A> {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(AssetBudget::<A>::new(self.max_bytes))
+            .add_event::<AssetEvicted<A>>()
+            .add_systems(
+                AssetEvents,
+                enforce_asset_budget::<A>.after(Assets::<A>::asset_events),
+            );
+    }
+}
+
+/// The memory budget of the assets of type `A`, which tracks their size and when they were last
+/// used. See [`AssetBudgetPlugin`].
+///
+/// An asset is used when it's added or modified, and when [`AssetBudget::touch`] is called with
+/// its id. Assets used during the current frame are never evicted, and evicted assets are reloaded
+/// when they're used again.
+#[derive(Resource)]
+pub struct AssetBudget<A: MeasuredAsset> {
+    /// The maximum number of bytes used by the assets.
+    pub max_bytes: usize,
+    used_bytes: usize,
+    frame: u64,
+    usages: HashMap<AssetId<A>, AssetUsage>,
+    evicted: HashMap<AssetId<A>, AssetPath<'static>>,
+    reloads: Vec<AssetPath<'static>>,
+}
+
+struct AssetUsage {
+    size: usize,
+    last_used: u64,
+}
+
+impl<A: MeasuredAsset> AssetBudget<A> {
+    /// Creates a budget of `max_bytes`.
+    pub fn new(max_bytes: usize) -> Self {
+        Self {
+            max_bytes,
+            used_bytes: 0,
+            frame: 0,
+            usages: HashMap::default(),
+            evicted: HashMap::default(),
+            reloads: Vec::new(),
+        }
+    }
+
+    /// Returns the number of bytes used by the assets.
+    pub fn used_bytes(&self) -> usize {
+        self.used_bytes
+    }
+
+    /// Returns the size of the asset `id`, if it's tracked by this budget.
+    pub fn size(&self, id: impl Into<AssetId<A>>) -> Option<usize> {
+        self.usages.get(&id.into()).map(|usage| usage.size)
+    }
+
+    /// Returns `true` if the asset `id` was evicted and hasn't been loaded again yet.
+    pub fn is_evicted(&self, id: impl Into<AssetId<A>>) -> bool {
+        self.evicted.contains_key(&id.into())
+    }
+
+    /// Marks the asset `id` as used during the current frame.
+    ///
+    /// If the asset was evicted, it's reloaded from its path.
+    pub fn touch(&mut self, id: impl Into<AssetId<A>>) {
+        let id = id.into();
+        if let Some(usage) = self.usages.get_mut(&id) {
+            usage.last_used = self.frame;
+        } else if let Some(path) = self.evicted.remove(&id) {
+            self.reloads.push(path);
+        }
+    }
+
+    fn track(&mut self, id: AssetId<A>, size: usize) {
+        let usage = AssetUsage {
+            size,
+            last_used: self.frame,
+        };
+        if let Some(previous) = self.usages.insert(id, usage) {
+            self.used_bytes -= previous.size;
+        }
+        self.used_bytes += size;
+    }
+
+    fn untrack(&mut self, id: AssetId<A>) -> Option<usize> {
+        let usage = self.usages.remove(&id)?;
+        self.used_bytes -= usage.size;
+        Some(usage.size)
+    }
+}
+
+/// Sent when an asset is removed from [`Assets<A>`] to fit in its [`AssetBudget`].
+///
+/// The strong handles of the asset are still valid. The asset is loaded again when it's used with
+/// [`AssetBudget::touch`], or with [`AssetServer::reload`].
+#[derive(Event)]
+pub struct AssetEvicted<A: Asset> {
+    /// The id of the evicted asset.
+    pub id: AssetId<A>,
+    /// The path the evicted asset was loaded from.
+    pub path: AssetPath<'static>,
+    /// The number of bytes the evicted asset used.
+    pub size: usize,
+}
+
+/// Tracks the assets of type `A` in their [`AssetBudget`], evicts the least recently used ones
+/// when they don't fit in it, and reloads the evicted assets that were used again.
+pub fn enforce_asset_budget<A: MeasuredAsset>(
+    mut budget: ResMut<AssetBudget<A>>,
+    mut assets: ResMut<Assets<A>>,
+    mut events: EventReader<AssetEvent<A>>,
+    asset_server: Res<AssetServer>,
+    mut evicted: EventWriter<AssetEvicted<A>>,
+) {
+    for event in events.read() {
+        match *event {
+            AssetEvent::Added { id } | AssetEvent::Modified { id } => {
+                if let Some(asset) = assets.get(id) {
+                    budget.evicted.remove(&id);
+                    budget.track(id, asset.memory_size());
+                }
+            }
+            AssetEvent::Removed { id } => {
+                budget.untrack(id);
+            }
+            AssetEvent::Unused { id } => {
+                // The asset is freed along with its last strong handle, there's nothing to reload.
+                budget.evicted.remove(&id);
+            }
+            AssetEvent::LoadedWithDependencies { .. } => {}
+        }
+    }
+
+    for path in std::mem::take(&mut budget.reloads) {
+        asset_server.reload(path);
+    }
+
+    let frame = budget.frame;
+    while budget.used_bytes > budget.max_bytes {
+        let Some((id, path)) = budget
+            .usages
+            .iter()
+            .filter(|(_, usage)| usage.last_used < frame)
+            .filter_map(|(id, usage)| {
+                let path = asset_server.get_path(*id)?.into_owned();
+                Some((usage.last_used, *id, path))
+            })
+            .min_by_key(|(last_used, ..)| *last_used)
+            .map(|(_, id, path)| (id, path))
+        else {
+            break;
+        };
+        let size = budget.untrack(id).unwrap_or_default();
+        assets.remove(id);
+        budget.evicted.insert(id, path.clone());
+        evicted.send(AssetEvicted { id, path, size });
+    }
+    budget.frame += 1;
+}
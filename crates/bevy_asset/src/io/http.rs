@@ -0,0 +1,683 @@
+use crate::{
+    io::{
+        get_meta_path, AssetReader, AssetReaderError, AssetSource, AssetSourceBuilder,
+        AssetSourceBuilders, EmptyPathStream, PathStream, Reader, VecReader,
+    },
+    AssetPath,
+};
+use bevy_ecs::system::Resource;
+use bevy_log::{error, warn};
+use bevy_utils::{BoxedFuture, HashMap};
+use parking_lot::RwLock;
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+/// Performs the HTTP `GET` requests of an [`HttpAssetReader`].
+pub trait HttpClient: Send + Sync + 'static {
+    /// Fetches the body of the resource at `url`, reporting its progress to `reporter` as it's
+    /// received.
+    ///
+    /// Implementations should return [`AssetReaderError::NotFound`] when the server responds with
+    /// a `404` status, and [`AssetReaderError::HttpError`] for the other unsuccessful statuses.
+    fn get<'a>(
+        &'a self,
+        url: &'a str,
+        reporter: DownloadReporter,
+    ) -> BoxedFuture<'a, Result<Vec<u8>, AssetReaderError>>;
+}
+
+/// The default [`HttpClient`].
+///
+/// On `wasm32`, requests are made with the `fetch` API of the browser. On other platforms, they're
+/// made with [`ureq`], which supports `https` through `rustls`. Both follow redirects.
+pub struct DefaultHttpClient {
+    #[cfg(not(target_arch = "wasm32"))]
+    agent: ureq::Agent,
+}
+
+impl DefaultHttpClient {
+    /// The default time allowed to establish a connection.
+    pub const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+    /// The default time allowed between two reads of the response.
+    pub const DEFAULT_READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+    /// Creates a client that fails the requests that take more than `connect_timeout` to connect
+    /// to the server, or more than `read_timeout` between two reads of the response.
+    ///
+    /// The timeouts of the `fetch` API can't be configured, so they're ignored on `wasm32`.
+    #[cfg_attr(target_arch = "wasm32", allow(unused_variables))]
+    pub fn with_timeouts(connect_timeout: Duration, read_timeout: Duration) -> Self {
+        Self {
+            #[cfg(not(target_arch = "wasm32"))]
+            agent: ureq::AgentBuilder::new()
+                .timeout_connect(connect_timeout)
+                .timeout_read(read_timeout)
+                .user_agent("bevy_asset")
+                .build(),
+        }
+    }
+}
+
+impl Default for DefaultHttpClient {
+    fn default() -> Self {
+        Self::with_timeouts(Self::DEFAULT_CONNECT_TIMEOUT, Self::DEFAULT_READ_TIMEOUT)
+    }
+}
+
+impl HttpClient for DefaultHttpClient {
+    fn get<'a>(
+        &'a self,
+        url: &'a str,
+        reporter: DownloadReporter,
+    ) -> BoxedFuture<'a, Result<Vec<u8>, AssetReaderError>> {
+        // The `fetch` API only gives the body once it's entirely received.
+        #[cfg(target_arch = "wasm32")]
+        return Box::pin(async move {
+            let bytes = crate::io::wasm::fetch(Path::new(url)).await?;
+            let len = bytes.len() as u64;
+            reporter.report(DownloadProgress {
+                received: len,
+                total: Some(len),
+            });
+            Ok(bytes)
+        });
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let agent = self.agent.clone();
+            let url = url.to_owned();
+            Box::pin(blocking::unblock(move || {
+                native::get(&agent, &url, &reporter)
+            }))
+        }
+    }
+}
+
+/// How much of an asset an [`HttpAssetReader`] has downloaded.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DownloadProgress {
+    /// The number of bytes received.
+    pub received: u64,
+    /// The size of the asset in bytes, if the server sent it.
+    pub total: Option<u64>,
+}
+
+impl DownloadProgress {
+    /// Returns the downloaded fraction of the asset, between `0.0` and `1.0`, if its size is known.
+    pub fn fraction(&self) -> Option<f32> {
+        let total = self.total?;
+        if total == 0 {
+            return Some(1.0);
+        }
+        Some((self.received as f64 / total as f64).min(1.0) as f32)
+    }
+}
+
+/// The progress of the downloads of the [`HttpAssetReader`]s sharing this resource.
+///
+/// While an asset from an `http` or `https` source is [`LoadState::Loading`](crate::LoadState),
+/// its download progress can be queried with [`HttpDownloads::progress`]. The
+/// [`AssetPlugin`](crate::AssetPlugin) inserts this resource and shares it with the sources it
+/// registers. Custom sources can share it through [`HttpSourceSettings::downloads`].
+#[derive(Resource, Clone, Default)]
+pub struct HttpDownloads(Arc<RwLock<HashMap<AssetPath<'static>, DownloadProgress>>>);
+
+impl HttpDownloads {
+    /// Returns the progress of the download of the asset at `path`, if it's being downloaded.
+    pub fn progress<'a>(&self, path: impl Into<AssetPath<'a>>) -> Option<DownloadProgress> {
+        let path = path.into();
+        // Labels are part of the loaded asset, not of the downloaded resource.
+        let path = path.without_label();
+        self.0.read().get(&path).copied()
+    }
+
+    fn start(&self, path: AssetPath<'static>) -> DownloadReporter {
+        self.0
+            .write()
+            .insert(path.clone(), DownloadProgress::default());
+        DownloadReporter {
+            downloads: self.clone(),
+            path,
+        }
+    }
+
+    fn finish(&self, path: &AssetPath<'static>) {
+        self.0.write().remove(path);
+    }
+}
+
+/// Reports the progress of a download to [`HttpDownloads`]. See [`HttpClient::get`].
+#[derive(Clone)]
+pub struct DownloadReporter {
+    downloads: HttpDownloads,
+    path: AssetPath<'static>,
+}
+
+impl DownloadReporter {
+    /// Reports that the download reached `progress`.
+    pub fn report(&self, progress: DownloadProgress) {
+        if let Some(entry) = self.downloads.0.write().get_mut(&self.path) {
+            *entry = progress;
+        }
+    }
+}
+
+/// How an [`HttpAssetReader`] retries the requests that failed because of a transient error,
+/// like a lost connection or a `503 Service Unavailable` status.
+///
+/// The delay before each retry doubles, starting from `initial_backoff` up to `max_backoff`.
+#[derive(Clone, Debug)]
+pub struct HttpRetryPolicy {
+    /// The number of times a request is retried before failing.
+    pub max_retries: u32,
+    /// The delay before the first retry.
+    pub initial_backoff: Duration,
+    /// The maximum delay before a retry.
+    pub max_backoff: Duration,
+}
+
+impl Default for HttpRetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(8),
+        }
+    }
+}
+
+impl HttpRetryPolicy {
+    /// Never retries failed requests.
+    pub const NONE: Self = Self {
+        max_retries: 0,
+        initial_backoff: Duration::ZERO,
+        max_backoff: Duration::ZERO,
+    };
+
+    /// Returns the delay before the retry of index `retry`, starting at `0`.
+    pub fn backoff(&self, retry: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_backoff)
+    }
+}
+
+/// When an [`HttpAssetReader`] reads the assets cached on disk.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum HttpCacheMode {
+    /// Always requests the asset, and only reads the cached copy if the request fails, for example
+    /// when offline.
+    #[default]
+    NetworkFirst,
+    /// Reads the cached copy of the asset if there is one, and only requests the asset otherwise.
+    CacheFirst,
+}
+
+/// A cache on disk for the assets loaded by an [`HttpAssetReader`].
+///
+/// Each asset is cached in a file named after the hash of its URL, so that URLs can't point outside
+/// of the cache directory. The cache isn't supported on `wasm32`, where the HTTP cache of the
+/// browser is used instead.
+#[derive(Clone, Debug)]
+pub struct HttpCache {
+    /// The directory in which the assets are cached.
+    pub path: PathBuf,
+    /// When the cached assets are read.
+    pub mode: HttpCacheMode,
+}
+
+/// The settings of an [`HttpAssetReader`].
+#[derive(Clone)]
+pub struct HttpSourceSettings {
+    /// The client making the requests.
+    pub client: Arc<dyn HttpClient>,
+    /// How failed requests are retried.
+    pub retry: HttpRetryPolicy,
+    /// The cache on disk for the loaded assets, if any.
+    pub cache: Option<HttpCache>,
+    /// Where the progress of the downloads is reported.
+    pub downloads: HttpDownloads,
+}
+
+impl Default for HttpSourceSettings {
+    fn default() -> Self {
+        Self {
+            client: Arc::new(DefaultHttpClient::default()),
+            retry: HttpRetryPolicy::default(),
+            cache: None,
+            downloads: HttpDownloads::default(),
+        }
+    }
+}
+
+/// Reader implementation for loading assets from URLs, such as `https://cdn.example.com/model.glb`.
+///
+/// The [`AssetPlugin`](crate::AssetPlugin) registers an `http` and an `https` source using this
+/// reader with the default [`HttpSourceSettings`]. To use other settings, register these sources
+/// with [`HttpAssetReader::source`] before adding the [`AssetPlugin`](crate::AssetPlugin):
+///
+/// ```no_run
+/// # use bevy_app::App;
+/// # use bevy_asset::{AssetApp, AssetPlugin, io::http::{HttpAssetReader, HttpCache, HttpSourceSettings}};
+/// # use bevy_asset::io::http::HttpDownloads;
+/// let mut app = App::new();
+/// let settings = HttpSourceSettings {
+///     cache: Some(HttpCache {
+///         path: "cache/assets".into(),
+///         mode: Default::default(),
+///     }),
+///     downloads: app.world.get_resource_or_insert_with(HttpDownloads::default).clone(),
+///     ..Default::default()
+/// };
+/// app.register_asset_source("https", HttpAssetReader::source("https", settings))
+///     .add_plugins(AssetPlugin::default());
+/// ```
+///
+/// Failed requests are reported through the load state of the asset, like any other reader error,
+/// and the progress of the downloads through [`HttpDownloads`].
+pub struct HttpAssetReader {
+    scheme: String,
+    settings: HttpSourceSettings,
+}
+
+impl HttpAssetReader {
+    /// Creates a new [`HttpAssetReader`] for the URLs of the given `scheme`, such as `https`.
+    pub fn new(scheme: impl Into<String>, settings: HttpSourceSettings) -> Self {
+        Self {
+            scheme: scheme.into(),
+            settings,
+        }
+    }
+
+    /// Returns an [`AssetSourceBuilder`] for the asset source of the given `scheme`, which should
+    /// be registered with the same name as `scheme`.
+    pub fn source(scheme: &'static str, settings: HttpSourceSettings) -> AssetSourceBuilder {
+        let processed_settings = settings.clone();
+        AssetSource::build()
+            .with_reader(move || Box::new(HttpAssetReader::new(scheme, settings.clone())))
+            .with_processed_reader(move || {
+                Box::new(HttpAssetReader::new(scheme, processed_settings.clone()))
+            })
+    }
+
+    async fn fetch_bytes<'a>(&self, path: &Path) -> Result<Box<Reader<'a>>, AssetReaderError> {
+        let url = format!(
+            "{}://{}",
+            self.scheme,
+            path.to_string_lossy().replace('\\', "/")
+        );
+        let cache = self.settings.cache.as_ref();
+        let cache_path = cache.map(|cache| cache_path(&cache.path, &url));
+
+        if cache.is_some_and(|cache| cache.mode == HttpCacheMode::CacheFirst) {
+            if let Some(bytes) = read_cache(cache_path.as_deref()).await {
+                return Ok(Box::new(VecReader::new(bytes)));
+            }
+        }
+        let asset_path = AssetPath::from(path.to_path_buf()).with_source(self.scheme.clone());
+        let reporter = self.settings.downloads.start(asset_path.clone());
+        let result = self.get_with_retries(&url, &reporter).await;
+        self.settings.downloads.finish(&asset_path);
+        let bytes = match result {
+            Ok(bytes) => {
+                write_cache(cache_path.as_deref(), &bytes).await;
+                bytes
+            }
+            // The asset was removed, so the cached copy is stale.
+            Err(AssetReaderError::NotFound(url)) => return Err(AssetReaderError::NotFound(url)),
+            Err(err) => {
+                let cached = if cache.is_some_and(|cache| cache.mode == HttpCacheMode::NetworkFirst)
+                {
+                    read_cache(cache_path.as_deref()).await
+                } else {
+                    None
+                };
+                match cached {
+                    Some(bytes) => {
+                        warn!("Failed to fetch {url}, falling back to the cached copy: {err}");
+                        bytes
+                    }
+                    None => return Err(err),
+                }
+            }
+        };
+        Ok(Box::new(VecReader::new(bytes)))
+    }
+
+    async fn get_with_retries(
+        &self,
+        url: &str,
+        reporter: &DownloadReporter,
+    ) -> Result<Vec<u8>, AssetReaderError> {
+        let retry_policy = &self.settings.retry;
+        let mut retry = 0;
+        loop {
+            match self.settings.client.get(url, reporter.clone()).await {
+                Err(err) if retry < retry_policy.max_retries && is_transient(&err) => {
+                    let backoff = retry_policy.backoff(retry);
+                    warn!("Failed to fetch {url}, retrying in {backoff:?}: {err}");
+                    sleep(backoff).await;
+                    retry += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+impl AssetReader for HttpAssetReader {
+    fn read<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> BoxedFuture<'a, Result<Box<Reader<'a>>, AssetReaderError>> {
+        Box::pin(self.fetch_bytes(path))
+    }
+
+    fn read_meta<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> BoxedFuture<'a, Result<Box<Reader<'a>>, AssetReaderError>> {
+        Box::pin(async move {
+            let meta_path = get_meta_path(path);
+            self.fetch_bytes(&meta_path).await
+        })
+    }
+
+    fn read_directory<'a>(
+        &'a self,
+        _path: &'a Path,
+    ) -> BoxedFuture<'a, Result<Box<PathStream>, AssetReaderError>> {
+        let stream: Box<PathStream> = Box::new(EmptyPathStream);
+        error!("Reading directories is not supported with the HttpAssetReader");
+        Box::pin(async move { Ok(stream) })
+    }
+
+    fn is_directory<'a>(
+        &'a self,
+        _path: &'a Path,
+    ) -> BoxedFuture<'a, std::result::Result<bool, AssetReaderError>> {
+        error!("Reading directories is not supported with the HttpAssetReader");
+        Box::pin(async move { Ok(false) })
+    }
+}
+
+/// Registers the `http` and `https` asset sources with the default [`HttpSourceSettings`], unless
+/// they're already registered. Their progress is reported to `downloads`.
+pub(crate) fn register_http_sources(sources: &mut AssetSourceBuilders, downloads: &HttpDownloads) {
+    for scheme in ["http", "https"] {
+        if sources.get_mut(scheme).is_none() {
+            let settings = HttpSourceSettings {
+                downloads: downloads.clone(),
+                ..Default::default()
+            };
+            sources.insert(scheme, HttpAssetReader::source(scheme, settings));
+        }
+    }
+}
+
+/// Returns the path of the cached copy of the asset at `url`, in the cache directory `root`.
+fn cache_path(root: &Path, url: &str) -> PathBuf {
+    root.join(blake3::hash(url.as_bytes()).to_hex().as_str())
+}
+
+/// Returns `true` if a request that failed with `error` may succeed when retried.
+fn is_transient(error: &AssetReaderError) -> bool {
+    match error {
+        AssetReaderError::NotFound(_) => false,
+        AssetReaderError::Io(error) => error.kind() != std::io::ErrorKind::Unsupported,
+        AssetReaderError::HttpError(status) => matches!(status, 408 | 429 | 500..=599),
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+async fn sleep(duration: Duration) {
+    blocking::unblock(move || std::thread::sleep(duration)).await;
+}
+
+#[cfg(target_arch = "wasm32")]
+async fn sleep(duration: Duration) {
+    let promise = js_sys::Promise::new(&mut |resolve, _| {
+        web_sys::window()
+            .unwrap()
+            .set_timeout_with_callback_and_timeout_and_arguments_0(
+                &resolve,
+                duration.as_millis() as i32,
+            )
+            .unwrap();
+    });
+    let _ = wasm_bindgen_futures::JsFuture::from(promise).await;
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+async fn read_cache(path: Option<&Path>) -> Option<Vec<u8>> {
+    async_fs::read(path?).await.ok()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+async fn write_cache(path: Option<&Path>, bytes: &[u8]) {
+    let Some(path) = path else {
+        return;
+    };
+    let result = async {
+        if let Some(parent) = path.parent() {
+            async_fs::create_dir_all(parent).await?;
+        }
+        async_fs::write(path, bytes).await
+    };
+    if let Err(err) = result.await {
+        warn!("Failed to cache the asset at {path:?}: {err}");
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+async fn read_cache(_path: Option<&Path>) -> Option<Vec<u8>> {
+    None
+}
+
+#[cfg(target_arch = "wasm32")]
+async fn write_cache(_path: Option<&Path>, _bytes: &[u8]) {}
+
+/// The blocking requests of the [`DefaultHttpClient`].
+#[cfg(not(target_arch = "wasm32"))]
+mod native {
+    use super::{DownloadProgress, DownloadReporter};
+    use crate::io::AssetReaderError;
+    use std::{
+        io::{Error, ErrorKind, Read},
+        path::PathBuf,
+    };
+
+    const CHUNK_SIZE: usize = 64 * 1024;
+
+    pub(super) fn get(
+        agent: &ureq::Agent,
+        url: &str,
+        reporter: &DownloadReporter,
+    ) -> Result<Vec<u8>, AssetReaderError> {
+        let response = match agent.get(url).call() {
+            Ok(response) => response,
+            Err(ureq::Error::Status(404, _)) => {
+                return Err(AssetReaderError::NotFound(PathBuf::from(url)))
+            }
+            Err(ureq::Error::Status(status, _)) => return Err(AssetReaderError::HttpError(status)),
+            Err(ureq::Error::Transport(transport)) => {
+                let kind = match transport.kind() {
+                    ureq::ErrorKind::InvalidUrl | ureq::ErrorKind::UnknownScheme => {
+                        ErrorKind::Unsupported
+                    }
+                    _ => ErrorKind::Other,
+                };
+                return Err(Error::new(kind, transport).into());
+            }
+        };
+
+        let total = response
+            .header("Content-Length")
+            .and_then(|length| length.parse::<u64>().ok());
+        let mut progress = DownloadProgress { received: 0, total };
+        reporter.report(progress);
+        let mut reader = response.into_reader();
+        let mut body = Vec::with_capacity(total.unwrap_or_default().min(1 << 26) as usize);
+        let mut chunk = vec![0; CHUNK_SIZE];
+        loop {
+            let read = match reader.read(&mut chunk) {
+                Ok(0) => break,
+                Ok(read) => read,
+                Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err.into()),
+            };
+            body.extend_from_slice(&chunk[..read]);
+            progress.received += read as u64;
+            reporter.report(progress);
+        }
+        Ok(body)
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use futures_lite::{future::block_on, AsyncReadExt};
+    use std::{
+        io::{Read, Write},
+        net::TcpListener,
+    };
+
+    /// Serves the given raw `responses` in order, one per connection, and returns the address of
+    /// the server.
+    fn serve(responses: Vec<&'static str>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        std::thread::spawn(move || {
+            for response in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut request = Vec::new();
+                let mut byte = [0];
+                while !request.ends_with(b"\r\n\r\n") {
+                    stream.read_exact(&mut byte).unwrap();
+                    request.push(byte[0]);
+                }
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+        address
+    }
+
+    fn read(reader: &HttpAssetReader, path: &str) -> Result<Vec<u8>, AssetReaderError> {
+        block_on(async {
+            let mut bytes = Vec::new();
+            reader
+                .read(Path::new(path))
+                .await?
+                .read_to_end(&mut bytes)
+                .await?;
+            Ok(bytes)
+        })
+    }
+
+    #[test]
+    fn retries_and_decodes_responses() {
+        let address = serve(vec![
+            "HTTP/1.1 503 Service Unavailable\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+            "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\nConnection: close\r\n\r\n4\r\nbevy\r\n6\r\n asset\r\n0\r\n\r\n",
+            "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+        ]);
+        let reader = HttpAssetReader::new(
+            "http",
+            HttpSourceSettings {
+                retry: HttpRetryPolicy {
+                    max_retries: 1,
+                    ..HttpRetryPolicy::NONE
+                },
+                ..Default::default()
+            },
+        );
+        let path = format!("{address}/model.glb");
+        assert_eq!(read(&reader, &path).unwrap(), b"bevy asset");
+        assert!(matches!(
+            read(&reader, &path),
+            Err(AssetReaderError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn falls_back_to_cache() {
+        let address = serve(vec![
+            "HTTP/1.1 200 OK\r\nContent-Length: 4\r\nConnection: close\r\n\r\nbevy",
+            "HTTP/1.1 500 Internal Server Error\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+        ]);
+        let cache_path = std::env::temp_dir().join(format!("bevy_http_cache_{address}"));
+        let reader = HttpAssetReader::new(
+            "http",
+            HttpSourceSettings {
+                retry: HttpRetryPolicy::NONE,
+                cache: Some(HttpCache {
+                    path: cache_path.clone(),
+                    mode: HttpCacheMode::NetworkFirst,
+                }),
+                ..Default::default()
+            },
+        );
+        let path = format!("{address}/image.png");
+        assert_eq!(read(&reader, &path).unwrap(), b"bevy");
+        assert_eq!(read(&reader, &path).unwrap(), b"bevy");
+        let _ = std::fs::remove_dir_all(cache_path);
+    }
+
+    #[test]
+    fn reports_download_progress() {
+        struct HalfwayClient(HttpDownloads);
+
+        impl HttpClient for HalfwayClient {
+            fn get<'a>(
+                &'a self,
+                _url: &'a str,
+                reporter: DownloadReporter,
+            ) -> BoxedFuture<'a, Result<Vec<u8>, AssetReaderError>> {
+                Box::pin(async move {
+                    reporter.report(DownloadProgress {
+                        received: 2,
+                        total: Some(4),
+                    });
+                    let progress = self.0.progress("http://example.com/image.png#label");
+                    assert_eq!(progress.and_then(|progress| progress.fraction()), Some(0.5));
+                    Ok(b"bevy".to_vec())
+                })
+            }
+        }
+
+        let downloads = HttpDownloads::default();
+        let reader = HttpAssetReader::new(
+            "http",
+            HttpSourceSettings {
+                client: Arc::new(HalfwayClient(downloads.clone())),
+                downloads: downloads.clone(),
+                ..Default::default()
+            },
+        );
+        assert_eq!(read(&reader, "example.com/image.png").unwrap(), b"bevy");
+        assert_eq!(downloads.progress("http://example.com/image.png"), None);
+    }
+
+    #[test]
+    fn cache_paths_stay_in_cache_directory() {
+        let root = Path::new("cache");
+        let path = cache_path(root, "http://example.com/../../../etc/passwd");
+        assert_eq!(path.parent(), Some(root));
+        assert_ne!(path, cache_path(root, "http://example.com/etc/passwd"));
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_max() {
+        let policy = HttpRetryPolicy {
+            max_retries: 10,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(500),
+        };
+        assert_eq!(policy.backoff(0), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(400));
+        assert_eq!(policy.backoff(3), Duration::from_millis(500));
+    }
+}
@@ -11,7 +11,10 @@ pub mod embedded;
 #[cfg(not(target_arch = "wasm32"))]
 pub mod file;
 pub mod gated;
+#[cfg(feature = "http_source")]
+pub mod http;
 pub mod memory;
+pub mod pack;
 pub mod processor_gated;
 #[cfg(target_arch = "wasm32")]
 pub mod wasm;
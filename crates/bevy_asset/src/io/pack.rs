@@ -0,0 +1,575 @@
+//! Packing of asset folders into a single indexed archive, and an [`AssetReader`] reading from it.
+//!
+//! Shipping thousands of loose files is impractical for many platforms and stores. The assets
+//! folder can instead be packed into a single archive at build time with [`pack_directory`], for
+//! example from a build script, and then read at runtime with the same paths by registering an
+//! [`AssetPackReader`] as the asset source:
+//!
+//! ```no_run
+//! # use bevy_app::App;
+//! # use bevy_asset::{AssetApp, AssetPlugin, io::{AssetSource, AssetSourceId, pack::AssetPackReader}};
+//! App::new()
+//!     .register_asset_source(
+//!         AssetSourceId::Default,
+//!         AssetSource::build().with_reader(|| {
+//!             let reader = AssetPackReader::open("assets.pack").expect("failed to open the asset pack");
+//!             // In development, assets that were added after packing are read from the folder.
+//!             if cfg!(debug_assertions) {
+//!                 Box::new(reader.with_fallback(AssetSource::get_default_reader("assets".to_string())()))
+//!             } else {
+//!                 Box::new(reader)
+//!             }
+//!         }),
+//!     )
+//!     .add_plugins(AssetPlugin::default());
+//! ```
+//!
+//! The content of the archive can be compressed or encrypted with an [`AssetPackCodec`].
+
+use crate::io::{get_meta_path, AssetReader, AssetReaderError, PathStream, Reader, VecReader};
+use bevy_utils::{BoxedFuture, HashSet};
+use std::{
+    collections::BTreeMap,
+    io::{Error, ErrorKind, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+const MAGIC: &[u8; 8] = b"BEVYPACK";
+const VERSION: u32 = 1;
+const FLAG_ENCODED: u32 = 1;
+
+/// Transforms the content of the files of an asset pack, for example to compress or encrypt it.
+pub trait AssetPackCodec: Send + Sync + 'static {
+    /// Encodes the content of the file at `path` when it's packed.
+    fn encode(&self, path: &Path, bytes: Vec<u8>) -> Vec<u8>;
+
+    /// Decodes the content of the file at `path` encoded by [`AssetPackCodec::encode`].
+    fn decode(&self, path: &Path, bytes: Vec<u8>) -> Result<Vec<u8>, Error>;
+}
+
+/// Packs the files of the folder `source`, recursively, into the archive `destination`, to be
+/// read by an [`AssetPackReader`].
+///
+/// The content of the files is encoded with `codec` if there is one.
+pub fn pack_directory(
+    source: impl AsRef<Path>,
+    destination: impl AsRef<Path>,
+    codec: Option<&dyn AssetPackCodec>,
+) -> Result<(), Error> {
+    let source = source.as_ref();
+    let mut files = Vec::new();
+    let mut directories = vec![source.to_path_buf()];
+    while let Some(directory) = directories.pop() {
+        for entry in std::fs::read_dir(directory)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                directories.push(path);
+            } else {
+                let relative_path = path.strip_prefix(source).unwrap().to_owned();
+                files.push((relative_path, std::fs::read(&path)?));
+            }
+        }
+    }
+    let file = std::fs::File::create(destination)?;
+    pack_files(files, codec, std::io::BufWriter::new(file))
+}
+
+/// Packs the given files, identified by their path relative to the root of the asset source, into
+/// an archive written to `writer`, to be read by an [`AssetPackReader`].
+///
+/// The content of the files is encoded with `codec` if there is one.
+pub fn pack_files(
+    files: impl IntoIterator<Item = (PathBuf, Vec<u8>)>,
+    codec: Option<&dyn AssetPackCodec>,
+    mut writer: impl Write,
+) -> Result<(), Error> {
+    let files: BTreeMap<String, Vec<u8>> = files
+        .into_iter()
+        .map(|(path, bytes)| {
+            let bytes = match codec {
+                Some(codec) => codec.encode(&path, bytes),
+                None => bytes,
+            };
+            (normalize(&path), bytes)
+        })
+        .collect();
+
+    writer.write_all(MAGIC)?;
+    writer.write_all(&VERSION.to_le_bytes())?;
+    let flags = if codec.is_some() { FLAG_ENCODED } else { 0 };
+    writer.write_all(&flags.to_le_bytes())?;
+    writer.write_all(&(files.len() as u32).to_le_bytes())?;
+    let mut offset = 0u64;
+    for (path, bytes) in &files {
+        writer.write_all(&(path.len() as u32).to_le_bytes())?;
+        writer.write_all(path.as_bytes())?;
+        writer.write_all(&offset.to_le_bytes())?;
+        writer.write_all(&(bytes.len() as u64).to_le_bytes())?;
+        offset += bytes.len() as u64;
+    }
+    for bytes in files.values() {
+        writer.write_all(bytes)?;
+    }
+    writer.flush()
+}
+
+/// Returns `path` as a string with `/` separators, as stored in the index of an asset pack.
+fn normalize(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/")
+}
+
+/// The location of a file in an asset pack.
+#[derive(Clone, Copy, Debug)]
+struct Entry {
+    offset: u64,
+    len: u64,
+}
+
+enum Storage {
+    #[cfg(not(target_arch = "wasm32"))]
+    File(PathBuf),
+    Bytes(Arc<[u8]>),
+}
+
+/// Reader implementation for loading assets from an archive packed with [`pack_directory`] or
+/// [`pack_files`].
+///
+/// The index of the archive is read when it's opened, and the files are then read on demand.
+/// Paths that aren't in the archive can be read from a fallback reader, set with
+/// [`AssetPackReader::with_fallback`].
+pub struct AssetPackReader {
+    storage: Storage,
+    entries: BTreeMap<String, Entry>,
+    directories: HashSet<String>,
+    data_offset: u64,
+    encoded: bool,
+    codec: Option<Box<dyn AssetPackCodec>>,
+    fallback: Option<Box<dyn AssetReader>>,
+}
+
+impl AssetPackReader {
+    /// Opens the archive at `path`, reading its index.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, Error> {
+        use std::io::{BufReader, Read};
+
+        let path = path.as_ref();
+        let file = std::fs::File::open(path)?;
+        let pack_len = file.metadata()?.len();
+        let mut reader = BufReader::new(file);
+        // The index must be read entirely, but its size is only known while reading it.
+        let mut header = Vec::new();
+        let mut chunk = [0; 4096];
+        let index = loop {
+            let read = reader.read(&mut chunk)?;
+            header.extend_from_slice(&chunk[..read]);
+            match Index::parse(&header, pack_len) {
+                Ok(index) => break index,
+                Err(err) if read == 0 || err.kind() != ErrorKind::UnexpectedEof => return Err(err),
+                Err(_) => {}
+            }
+        };
+        Ok(Self::from_index(Storage::File(path.to_owned()), index))
+    }
+
+    /// Reads the archive from `bytes`, for example an archive embedded in the executable with
+    /// [`include_bytes!`].
+    pub fn from_bytes(bytes: impl Into<Arc<[u8]>>) -> Result<Self, Error> {
+        let bytes = bytes.into();
+        let index = Index::parse(&bytes, bytes.len() as u64)?;
+        Ok(Self::from_index(Storage::Bytes(bytes), index))
+    }
+
+    fn from_index(storage: Storage, index: Index) -> Self {
+        let mut directories = HashSet::new();
+        for path in index.entries.keys() {
+            let mut path = path.as_str();
+            while let Some((parent, _)) = path.rsplit_once('/') {
+                directories.insert(parent.to_owned());
+                path = parent;
+            }
+        }
+        Self {
+            storage,
+            entries: index.entries,
+            directories,
+            data_offset: index.data_offset,
+            encoded: index.encoded,
+            codec: None,
+            fallback: None,
+        }
+    }
+
+    /// Sets the codec decoding the content of the files of the archive, which must be the codec
+    /// used to pack it.
+    pub fn with_codec(mut self, codec: impl AssetPackCodec) -> Self {
+        self.codec = Some(Box::new(codec));
+        self
+    }
+
+    /// Sets the reader used to read the paths that aren't in the archive, for example a reader of
+    /// the loose files of the assets folder during development.
+    pub fn with_fallback(mut self, fallback: Box<dyn AssetReader>) -> Self {
+        self.fallback = Some(fallback);
+        self
+    }
+
+    /// Returns `true` if the archive contains the file at `path`.
+    pub fn contains(&self, path: &Path) -> bool {
+        self.entries.contains_key(&normalize(path))
+    }
+
+    /// Returns an iterator over the paths of the files of the archive.
+    pub fn paths(&self) -> impl Iterator<Item = &str> {
+        self.entries.keys().map(String::as_str)
+    }
+
+    fn is_pack_directory(&self, path: &Path) -> bool {
+        let path = normalize(path);
+        path.is_empty() || self.directories.contains(&path)
+    }
+
+    async fn read_entry(&self, path: &Path, entry: Entry) -> Result<Vec<u8>, Error> {
+        // The index was checked to fit in the archive when it was parsed.
+        let start = self.data_offset + entry.offset;
+        let len =
+            usize::try_from(entry.len).map_err(|_| invalid_data("asset pack entry too large"))?;
+        let bytes = match &self.storage {
+            #[cfg(not(target_arch = "wasm32"))]
+            Storage::File(pack_path) => {
+                use futures_lite::{AsyncReadExt, AsyncSeekExt};
+
+                let mut file = async_fs::File::open(pack_path).await?;
+                file.seek(std::io::SeekFrom::Start(start)).await?;
+                let mut bytes = vec![0; len];
+                file.read_exact(&mut bytes).await?;
+                bytes
+            }
+            Storage::Bytes(pack) => pack
+                .get(start as usize..start as usize + len)
+                .ok_or_else(|| invalid_data("truncated asset pack"))?
+                .to_vec(),
+        };
+        match (&self.codec, self.encoded) {
+            (Some(codec), true) => codec.decode(path, bytes),
+            (None, true) => Err(invalid_data(
+                "the asset pack is encoded, but the AssetPackReader has no codec",
+            )),
+            (_, false) => Ok(bytes),
+        }
+    }
+
+    async fn read_file<'a>(&'a self, path: &'a Path) -> Result<Box<Reader<'a>>, AssetReaderError> {
+        if let Some(entry) = self.entries.get(&normalize(path)) {
+            let bytes = self.read_entry(path, *entry).await?;
+            let reader: Box<Reader> = Box::new(VecReader::new(bytes));
+            return Ok(reader);
+        }
+        match &self.fallback {
+            Some(fallback) => fallback.read(path).await,
+            None => Err(AssetReaderError::NotFound(path.to_owned())),
+        }
+    }
+}
+
+impl AssetReader for AssetPackReader {
+    fn read<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> BoxedFuture<'a, Result<Box<Reader<'a>>, AssetReaderError>> {
+        Box::pin(self.read_file(path))
+    }
+
+    fn read_meta<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> BoxedFuture<'a, Result<Box<Reader<'a>>, AssetReaderError>> {
+        Box::pin(async move {
+            // The meta file of a packed asset is packed alongside it.
+            if !self.contains(path) {
+                if let Some(fallback) = &self.fallback {
+                    return fallback.read_meta(path).await;
+                }
+            }
+            let meta_path = get_meta_path(path);
+            match self.entries.get(&normalize(&meta_path)) {
+                Some(entry) => {
+                    let bytes = self.read_entry(&meta_path, *entry).await?;
+                    let reader: Box<Reader> = Box::new(VecReader::new(bytes));
+                    Ok(reader)
+                }
+                None => Err(AssetReaderError::NotFound(meta_path)),
+            }
+        })
+    }
+
+    fn read_directory<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> BoxedFuture<'a, Result<Box<PathStream>, AssetReaderError>> {
+        Box::pin(async move {
+            if !self.is_pack_directory(path) {
+                return match &self.fallback {
+                    Some(fallback) => fallback.read_directory(path).await,
+                    None => Err(AssetReaderError::NotFound(path.to_owned())),
+                };
+            }
+            let prefix = normalize(path);
+            let prefix = if prefix.is_empty() {
+                prefix
+            } else {
+                format!("{prefix}/")
+            };
+            let mut children = Vec::new();
+            for child in self.entries.keys().filter_map(|p| p.strip_prefix(&prefix)) {
+                let child = match child.split_once('/') {
+                    Some((directory, _)) => directory,
+                    // Meta files are not considered assets.
+                    None if child.ends_with(".meta") => continue,
+                    None => child,
+                };
+                let child = PathBuf::from(format!("{prefix}{child}"));
+                if children.last() != Some(&child) {
+                    children.push(child);
+                }
+            }
+            let stream: Box<PathStream> = Box::new(futures_lite::stream::iter(children));
+            Ok(stream)
+        })
+    }
+
+    fn is_directory<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> BoxedFuture<'a, Result<bool, AssetReaderError>> {
+        Box::pin(async move {
+            if self.is_pack_directory(path) {
+                return Ok(true);
+            }
+            match &self.fallback {
+                Some(fallback) if !self.contains(path) => fallback.is_directory(path).await,
+                _ => Ok(false),
+            }
+        })
+    }
+}
+
+/// The index of an asset pack.
+struct Index {
+    entries: BTreeMap<String, Entry>,
+    data_offset: u64,
+    encoded: bool,
+}
+
+impl Index {
+    /// Parses the index at the start of `bytes`, failing with [`ErrorKind::UnexpectedEof`] if
+    /// `bytes` doesn't contain all of it.
+    ///
+    /// `pack_len` is the size of the whole archive, which every file of the index must fit in.
+    fn parse(bytes: &[u8], pack_len: u64) -> Result<Self, Error> {
+        let mut cursor = 0usize;
+        let mut take = |len: usize| -> Result<&[u8], Error> {
+            let taken = cursor
+                .checked_add(len)
+                .and_then(|end| bytes.get(cursor..end))
+                .ok_or_else(|| Error::from(ErrorKind::UnexpectedEof))?;
+            cursor += len;
+            Ok(taken)
+        };
+        let read_u32 = |bytes: &[u8]| u32::from_le_bytes(bytes.try_into().unwrap());
+        let read_u64 = |bytes: &[u8]| u64::from_le_bytes(bytes.try_into().unwrap());
+
+        if take(MAGIC.len())? != MAGIC {
+            return Err(invalid_data("not an asset pack"));
+        }
+        let version = read_u32(take(4)?);
+        if version != VERSION {
+            return Err(invalid_data(&format!(
+                "unsupported asset pack version {version}"
+            )));
+        }
+        let flags = read_u32(take(4)?);
+        let count = read_u32(take(4)?);
+        let mut entries = BTreeMap::new();
+        for _ in 0..count {
+            let path_len = read_u32(take(4)?) as usize;
+            let path = std::str::from_utf8(take(path_len)?)
+                .map_err(|_| invalid_data("invalid path in asset pack"))?
+                .to_owned();
+            let offset = read_u64(take(8)?);
+            let len = read_u64(take(8)?);
+            entries.insert(path, Entry { offset, len });
+        }
+        let data_offset = cursor as u64;
+        for entry in entries.values() {
+            let end = data_offset
+                .checked_add(entry.offset)
+                .and_then(|start| start.checked_add(entry.len));
+            if end.filter(|&end| end <= pack_len).is_none() {
+                return Err(invalid_data("asset pack entry out of bounds"));
+            }
+        }
+        Ok(Self {
+            entries,
+            data_offset,
+            encoded: flags & FLAG_ENCODED != 0,
+        })
+    }
+}
+
+fn invalid_data(message: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, message.to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_lite::{future::block_on, AsyncReadExt, StreamExt};
+
+    struct Xor(u8);
+
+    impl AssetPackCodec for Xor {
+        fn encode(&self, _path: &Path, bytes: Vec<u8>) -> Vec<u8> {
+            bytes.into_iter().map(|byte| byte ^ self.0).collect()
+        }
+
+        fn decode(&self, path: &Path, bytes: Vec<u8>) -> Result<Vec<u8>, Error> {
+            Ok(self.encode(path, bytes))
+        }
+    }
+
+    fn pack(codec: Option<&dyn AssetPackCodec>) -> Vec<u8> {
+        let files = [
+            ("hello.txt", "hello"),
+            ("hello.txt.meta", "meta"),
+            ("models/tree.gltf", "tree"),
+            ("models/textures/bark.png", "bark"),
+        ];
+        let mut pack = Vec::new();
+        pack_files(
+            files.map(|(path, content)| (PathBuf::from(path), content.as_bytes().to_vec())),
+            codec,
+            &mut pack,
+        )
+        .unwrap();
+        pack
+    }
+
+    fn read(reader: &AssetPackReader, path: &str) -> Result<String, AssetReaderError> {
+        block_on(async {
+            let mut content = String::new();
+            reader
+                .read(Path::new(path))
+                .await?
+                .read_to_string(&mut content)
+                .await?;
+            Ok(content)
+        })
+    }
+
+    #[test]
+    fn read_packed_files() {
+        let reader = AssetPackReader::from_bytes(pack(None)).unwrap();
+        assert_eq!(read(&reader, "hello.txt").unwrap(), "hello");
+        assert_eq!(read(&reader, "models/textures/bark.png").unwrap(), "bark");
+        assert!(matches!(
+            read(&reader, "missing.txt"),
+            Err(AssetReaderError::NotFound(_))
+        ));
+
+        let mut meta = String::new();
+        block_on(async {
+            reader
+                .read_meta(Path::new("hello.txt"))
+                .await
+                .unwrap()
+                .read_to_string(&mut meta)
+                .await
+                .unwrap();
+        });
+        assert_eq!(meta, "meta");
+
+        let children: Vec<PathBuf> = block_on(async {
+            reader
+                .read_directory(Path::new("models"))
+                .await
+                .unwrap()
+                .collect()
+                .await
+        });
+        assert_eq!(
+            children,
+            [
+                PathBuf::from("models/textures"),
+                PathBuf::from("models/tree.gltf")
+            ]
+        );
+        assert!(block_on(reader.is_directory(Path::new("models/textures"))).unwrap());
+        assert!(!block_on(reader.is_directory(Path::new("hello.txt"))).unwrap());
+    }
+
+    #[test]
+    fn encoded_pack_requires_codec() {
+        let pack = pack(Some(&Xor(42)));
+        let reader = AssetPackReader::from_bytes(pack.clone()).unwrap();
+        assert!(read(&reader, "hello.txt").is_err());
+        let reader = AssetPackReader::from_bytes(pack)
+            .unwrap()
+            .with_codec(Xor(42));
+        assert_eq!(read(&reader, "hello.txt").unwrap(), "hello");
+    }
+
+    #[test]
+    fn entries_out_of_bounds_are_rejected() {
+        // The offset of the first entry, after the header and its path.
+        let offset_position = MAGIC.len() + 12 + 4 + "hello.txt".len();
+        let pack_len = pack(None).len() as u64;
+        for (position, value) in [
+            (offset_position, pack_len),
+            (offset_position + 8, pack_len),
+            (offset_position + 8, u64::MAX),
+        ] {
+            let mut pack = pack(None);
+            pack[position..position + 8].copy_from_slice(&value.to_le_bytes());
+            let Err(err) = AssetPackReader::from_bytes(pack) else {
+                panic!("the index should be rejected");
+            };
+            assert_eq!(err.kind(), ErrorKind::InvalidData);
+        }
+    }
+
+    #[test]
+    fn fallback_reader() {
+        let fallback = AssetPackReader::from_bytes({
+            let mut pack = Vec::new();
+            pack_files(
+                [(PathBuf::from("new.txt"), b"new".to_vec())],
+                None,
+                &mut pack,
+            )
+            .unwrap();
+            pack
+        })
+        .unwrap();
+        let reader = AssetPackReader::from_bytes(pack(None))
+            .unwrap()
+            .with_fallback(Box::new(fallback));
+        assert_eq!(read(&reader, "hello.txt").unwrap(), "hello");
+        assert_eq!(read(&reader, "new.txt").unwrap(), "new");
+    }
+
+    #[test]
+    fn pack_directory_to_file() {
+        let root = std::env::temp_dir().join("bevy_asset_pack_directory");
+        let assets = root.join("assets");
+        std::fs::create_dir_all(assets.join("sounds")).unwrap();
+        std::fs::write(assets.join("sounds/jump.ogg"), "jump").unwrap();
+        let pack_path = root.join("assets.pack");
+        pack_directory(&assets, &pack_path, None).unwrap();
+
+        let reader = AssetPackReader::open(&pack_path).unwrap();
+        assert_eq!(read(&reader, "sounds/jump.ogg").unwrap(), "jump");
+        let _ = std::fs::remove_dir_all(root);
+    }
+}
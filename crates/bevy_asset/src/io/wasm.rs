@@ -38,23 +38,28 @@ fn js_value_to_err<'a>(context: &'a str) -> impl FnOnce(JsValue) -> std::io::Err
 
 impl HttpWasmAssetReader {
     async fn fetch_bytes<'a>(&self, path: PathBuf) -> Result<Box<Reader<'a>>, AssetReaderError> {
-        let window = web_sys::window().unwrap();
-        let resp_value = JsFuture::from(window.fetch_with_str(path.to_str().unwrap()))
-            .await
-            .map_err(js_value_to_err("fetch path"))?;
-        let resp = resp_value
-            .dyn_into::<Response>()
-            .map_err(js_value_to_err("convert fetch to Response"))?;
-        match resp.status() {
-            200 => {
-                let data = JsFuture::from(resp.array_buffer().unwrap()).await.unwrap();
-                let bytes = Uint8Array::new(&data).to_vec();
-                let reader: Box<Reader> = Box::new(VecReader::new(bytes));
-                Ok(reader)
-            }
-            404 => Err(AssetReaderError::NotFound(path)),
-            status => Err(AssetReaderError::HttpError(status as u16)),
+        let bytes = fetch(&path).await?;
+        let reader: Box<Reader> = Box::new(VecReader::new(bytes));
+        Ok(reader)
+    }
+}
+
+/// Fetches the bytes at the URL `path` with the `fetch` API of the browser.
+pub(crate) async fn fetch(path: &Path) -> Result<Vec<u8>, AssetReaderError> {
+    let window = web_sys::window().unwrap();
+    let resp_value = JsFuture::from(window.fetch_with_str(path.to_str().unwrap()))
+        .await
+        .map_err(js_value_to_err("fetch path"))?;
+    let resp = resp_value
+        .dyn_into::<Response>()
+        .map_err(js_value_to_err("convert fetch to Response"))?;
+    match resp.status() {
+        200 => {
+            let data = JsFuture::from(resp.array_buffer().unwrap()).await.unwrap();
+            Ok(Uint8Array::new(&data).to_vec())
         }
+        404 => Err(AssetReaderError::NotFound(path.to_owned())),
+        status => Err(AssetReaderError::HttpError(status as u16)),
     }
 }
 
@@ -16,6 +16,7 @@ pub mod prelude {
 }
 
 mod assets;
+mod budget;
 mod event;
 mod folder;
 mod handle;
@@ -27,6 +28,7 @@ mod server;
 
 pub use assets::*;
 pub use bevy_asset_macros::Asset;
+pub use budget::*;
 pub use event::*;
 pub use folder::*;
 pub use futures_lite::{AsyncReadExt, AsyncWriteExt};
@@ -148,6 +150,11 @@ impl Plugin for AssetPlugin {
     fn build(&self, app: &mut App) {
         app.init_schedule(UpdateAssets).init_schedule(AssetEvents);
         let embedded = EmbeddedAssetRegistry::default();
+        #[cfg(feature = "http_source")]
+        let downloads = app
+            .world
+            .get_resource_or_insert_with(io::http::HttpDownloads::default)
+            .clone();
         {
             let mut sources = app
                 .world
@@ -158,6 +165,8 @@ impl Plugin for AssetPlugin {
                     .then_some(self.processed_file_path.as_str()),
             );
             embedded.register_source(&mut sources);
+            #[cfg(feature = "http_source")]
+            io::http::register_http_sources(&mut sources, &downloads);
         }
         {
             let mut watch = cfg!(feature = "watch");
@@ -444,9 +453,9 @@ mod tests {
             AssetReader, AssetReaderError, AssetSource, AssetSourceId, Reader,
         },
         loader::{AssetLoader, LoadContext},
-        Asset, AssetApp, AssetEvent, AssetId, AssetLoadError, AssetLoadFailedEvent, AssetPath,
-        AssetPlugin, AssetServer, Assets, DependencyLoadState, LoadState,
-        RecursiveDependencyLoadState,
+        Asset, AssetApp, AssetBudget, AssetBudgetPlugin, AssetEvent, AssetEvicted, AssetId,
+        AssetLoadError, AssetLoadFailedEvent, AssetPath, AssetPlugin, AssetServer, Assets,
+        DependencyLoadState, LoadState, MeasuredAsset, RecursiveDependencyLoadState,
     };
     use bevy_app::{App, Update};
     use bevy_core::TaskPoolPlugin;
@@ -476,6 +485,12 @@ mod tests {
         pub sub_texts: Vec<Handle<SubText>>,
     }
 
+    impl MeasuredAsset for CoolText {
+        fn memory_size(&self) -> usize {
+            self.text.len()
+        }
+    }
+
     #[derive(Asset, TypePath, Debug)]
     pub struct SubText {
         text: String,
@@ -1444,6 +1459,157 @@ mod tests {
         });
     }
 
+    #[test]
+    fn evict_unused_assets_over_budget() {
+        let dir = Dir::default();
+        for name in ["a", "b"] {
+            let ron = format!(
+                "(text: \"{name}\", dependencies: [], embedded_dependencies: [], sub_texts: [])"
+            );
+            dir.insert_asset_text(Path::new(&format!("{name}.cool.ron")), &ron);
+        }
+
+        let mut app = App::new();
+        app.register_asset_source(
+            AssetSourceId::Default,
+            AssetSource::build()
+                .with_reader(move || Box::new(MemoryAssetReader { root: dir.clone() })),
+        )
+        .add_plugins((
+            TaskPoolPlugin::default(),
+            LogPlugin::default(),
+            AssetPlugin::default(),
+        ))
+        .init_asset::<CoolText>()
+        .register_asset_loader(CoolTextLoader)
+        .add_plugins(AssetBudgetPlugin::<CoolText>::new(6));
+
+        // Assets that weren't loaded from a path can't be reloaded, and are never evicted.
+        let added = app.world.resource_mut::<Assets<CoolText>>().add(CoolText {
+            text: "added".to_string(),
+            embedded: String::new(),
+            dependencies: Vec::new(),
+            sub_texts: Vec::new(),
+        });
+        let asset_server = app.world.resource::<AssetServer>().clone();
+        let a: Handle<CoolText> = asset_server.load("a.cool.ron");
+        run_app_until(&mut app, |world| {
+            (world.resource::<AssetBudget<CoolText>>().used_bytes() == 6).then_some(())
+        });
+        app.update();
+
+        // Loading `b` exceeds the budget, so `a`, which wasn't used since, is evicted even though
+        // it's still strongly held.
+        let b: Handle<CoolText> = asset_server.load("b.cool.ron");
+        run_app_until(&mut app, |world| {
+            let assets = world.resource::<Assets<CoolText>>();
+            (assets.contains(&b) && !assets.contains(&a)).then_some(())
+        });
+        let budget = app.world.resource::<AssetBudget<CoolText>>();
+        assert!(budget.is_evicted(&a));
+        assert_eq!(budget.used_bytes(), 6);
+        assert!(app.world.resource::<Assets<CoolText>>().contains(&added));
+        let evicted = app.world.resource::<Events<AssetEvicted<CoolText>>>();
+        let mut reader = evicted.get_reader();
+        let evicted: Vec<_> = reader.read(evicted).collect();
+        assert_eq!(evicted.len(), 1);
+        assert_eq!(evicted[0].id, a.id());
+        assert_eq!(evicted[0].path, AssetPath::from("a.cool.ron"));
+        assert_eq!(evicted[0].size, 1);
+
+        // Using `a` again reloads it, which evicts `b` in turn.
+        app.world.resource_mut::<AssetBudget<CoolText>>().touch(&a);
+        run_app_until(&mut app, |world| {
+            let assets = world.resource::<Assets<CoolText>>();
+            (assets.contains(&a) && !assets.contains(&b)).then_some(())
+        });
+        let budget = app.world.resource::<AssetBudget<CoolText>>();
+        assert!(!budget.is_evicted(&a));
+        assert!(budget.is_evicted(&b));
+        assert_eq!(
+            app.world
+                .resource::<Assets<CoolText>>()
+                .get(&a)
+                .unwrap()
+                .text,
+            "a"
+        );
+    }
+
+    #[test]
+    fn label_aliases_resolve_to_the_labeled_asset() {
+        struct AliasLoader;
+
+        impl AssetLoader for AliasLoader {
+            type Asset = CoolText;
+            type Settings = ();
+            type Error = std::io::Error;
+
+            fn load<'a>(
+                &'a self,
+                _reader: &'a mut Reader,
+                _settings: &'a Self::Settings,
+                load_context: &'a mut LoadContext,
+            ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+                Box::pin(async move {
+                    load_context.add_label_alias("Named(hello)", "Text0");
+                    let sub_text = load_context.add_labeled_asset(
+                        "Text0".to_string(),
+                        SubText {
+                            text: "hello".to_string(),
+                        },
+                    );
+                    Ok(CoolText {
+                        text: String::new(),
+                        embedded: String::new(),
+                        dependencies: Vec::new(),
+                        sub_texts: vec![sub_text],
+                    })
+                })
+            }
+
+            fn extensions(&self) -> &[&str] {
+                &["alias"]
+            }
+        }
+
+        let dir = Dir::default();
+        dir.insert_asset_text(Path::new("a.alias"), "");
+
+        let mut app = App::new();
+        app.register_asset_source(
+            AssetSourceId::Default,
+            AssetSource::build()
+                .with_reader(move || Box::new(MemoryAssetReader { root: dir.clone() })),
+        )
+        .add_plugins((
+            TaskPoolPlugin::default(),
+            LogPlugin::default(),
+            AssetPlugin::default(),
+        ))
+        .init_asset::<CoolText>()
+        .init_asset::<SubText>()
+        .register_asset_loader(AliasLoader);
+
+        let asset_server = app.world.resource::<AssetServer>().clone();
+        // The alias is requested before the loader registers it.
+        let named: Handle<SubText> = asset_server.load("a.alias#Named(hello)");
+        let indexed: Handle<SubText> = asset_server.load("a.alias#Text0");
+        assert_eq!(named.id(), indexed.id());
+        run_app_until(&mut app, |world| {
+            get::<SubText>(world, indexed.id()).map(|_| ())
+        });
+
+        // The sub-asset is only stored once.
+        let sub_texts = app.world.resource::<Assets<SubText>>();
+        assert_eq!(sub_texts.len(), 1);
+        assert_eq!(sub_texts.get(&named).unwrap().text, "hello");
+        assert_eq!(
+            asset_server.load_state(&named),
+            asset_server.load_state(&indexed)
+        );
+    }
+
     #[test]
     fn ignore_system_ambiguities_on_assets() {
         let mut app = App::new();
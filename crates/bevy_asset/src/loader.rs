@@ -276,6 +276,7 @@ pub struct LoadContext<'a> {
     /// Direct dependencies used by this loader.
     loader_dependencies: HashMap<AssetPath<'static>, AssetHash>,
     labeled_assets: HashMap<CowArc<'static, str>, LabeledAsset>,
+    label_aliases: HashMap<CowArc<'static, str>, Vec<CowArc<'static, str>>>,
 }
 
 impl<'a> LoadContext<'a> {
@@ -294,6 +295,7 @@ impl<'a> LoadContext<'a> {
             dependencies: HashSet::default(),
             loader_dependencies: HashMap::default(),
             labeled_assets: HashMap::default(),
+            label_aliases: HashMap::default(),
         }
     }
 
@@ -381,9 +383,16 @@ impl<'a> LoadContext<'a> {
         let label = label.into();
         let loaded_asset: ErasedLoadedAsset = loaded_asset.into();
         let labeled_path = self.asset_path.clone().with_label(label.clone());
+        let alias_paths = self
+            .label_aliases
+            .remove(&label)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|alias| self.asset_path.clone().with_label(alias))
+            .collect();
         let handle = self
             .asset_server
-            .get_or_create_path_handle(labeled_path, None);
+            .get_or_create_aliased_path_handle(labeled_path, alias_paths);
         self.labeled_assets.insert(
             label,
             LabeledAsset {
@@ -394,6 +403,24 @@ impl<'a> LoadContext<'a> {
         handle
     }
 
+    /// Makes the labeled asset `label` also available under the `alias` label, for example to
+    /// request a sub-asset by name rather than by index. The asset is only stored once: both
+    /// labels resolve to the same [`Handle`].
+    ///
+    /// This must be called before the labeled asset `label` is added to this context.
+    ///
+    /// See [`AssetPath`] for more on labeled assets.
+    pub fn add_label_alias(
+        &mut self,
+        alias: impl Into<CowArc<'static, str>>,
+        label: impl Into<CowArc<'static, str>>,
+    ) {
+        self.label_aliases
+            .entry(label.into())
+            .or_default()
+            .push(alias.into());
+    }
+
     /// Returns `true` if an asset with the label `label` exists in this context.
     ///
     /// See [`AssetPath`] for more on labeled assets.
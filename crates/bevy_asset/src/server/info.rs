@@ -18,6 +18,9 @@ use thiserror::Error;
 pub(crate) struct AssetInfo {
     weak_handle: Weak<StrongHandle>,
     pub(crate) path: Option<AssetPath<'static>>,
+    /// The other paths this asset can be requested with, like the name-based label of a labeled
+    /// asset.
+    aliases: Vec<AssetPath<'static>>,
     pub(crate) load_state: LoadState,
     pub(crate) dep_load_state: DependencyLoadState,
     pub(crate) rec_dep_load_state: RecursiveDependencyLoadState,
@@ -44,6 +47,7 @@ impl AssetInfo {
         Self {
             weak_handle,
             path,
+            aliases: Vec::new(),
             load_state: LoadState::NotLoaded,
             dep_load_state: DependencyLoadState::NotLoaded,
             rec_dep_load_state: RecursiveDependencyLoadState::NotLoaded,
@@ -267,6 +271,63 @@ impl AssetInfos {
         }
     }
 
+    /// Retrieves the handle of the asset at `path`, or creates it, and makes `aliases` point to
+    /// the same asset.
+    ///
+    /// If one of the aliases was requested before `path`, its handle is used for the asset, so
+    /// that it's loaded too. Aliases already pointing to another asset are left untouched.
+    pub(crate) fn get_or_create_aliased_path_handle<A: Asset>(
+        &mut self,
+        path: AssetPath<'static>,
+        aliases: Vec<AssetPath<'static>>,
+    ) -> Handle<A> {
+        let type_id = TypeId::of::<A>();
+        let id_of = |infos: &Self, path: &AssetPath<'static>| {
+            infos
+                .path_to_id
+                .get(path)
+                .and_then(|ids| ids.get(&type_id))
+                .copied()
+        };
+        let requested_path = if id_of(self, &path).is_some() {
+            &path
+        } else {
+            aliases
+                .iter()
+                .find(|alias| id_of(self, alias).is_some())
+                .unwrap_or(&path)
+        }
+        .clone();
+        let (handle, _) = self.get_or_create_path_handle::<A>(
+            requested_path.clone(),
+            HandleLoadingMode::NotLoading,
+            None,
+        );
+        let id = handle.id().untyped();
+        for alias in std::iter::once(path).chain(aliases) {
+            if alias == requested_path {
+                continue;
+            }
+            match id_of(self, &alias) {
+                None => {
+                    self.path_to_id
+                        .entry(alias.clone())
+                        .or_default()
+                        .insert(type_id, id);
+                    self.infos.get_mut(&id).unwrap().aliases.push(alias);
+                }
+                Some(alias_id) if alias_id != id => {
+                    warn!(
+                        "{alias} was requested separately from {requested_path} before they were \
+                        loaded, so it won't be loaded. Request only one of them."
+                    );
+                }
+                Some(_) => {}
+            }
+        }
+        handle
+    }
+
     pub(crate) fn get(&self, id: UntypedAssetId) -> Option<&AssetInfo> {
         self.infos.get(&id)
     }
@@ -673,13 +734,17 @@ impl AssetInfos {
             );
         }
 
-        if let Some(map) = path_to_id.get_mut(path) {
-            map.remove(&type_id);
+        for path in std::iter::once(path).chain(&info.aliases) {
+            if let Some(map) = path_to_id.get_mut(path) {
+                if map.get(&type_id) == Some(&id) {
+                    map.remove(&type_id);
+                }
 
-            if map.is_empty() {
-                path_to_id.remove(path);
-            }
-        };
+                if map.is_empty() {
+                    path_to_id.remove(path);
+                }
+            };
+        }
 
         true
     }
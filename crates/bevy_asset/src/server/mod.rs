@@ -502,8 +502,14 @@ impl AssetServer {
         {
             Ok(loaded_asset) => {
                 let final_handle = if let Some(label) = path.label_cow() {
-                    match loaded_asset.labeled_assets.get(&label) {
-                        Some(labeled_asset) => labeled_asset.handle.clone(),
+                    let labeled_handle = loaded_asset
+                        .labeled_assets
+                        .get(&label)
+                        .map(|labeled_asset| labeled_asset.handle.clone())
+                        // The label may be an alias of another label, registered by the loader.
+                        .or_else(|| self.data.infos.read().get_path_handles(&path).next());
+                    match labeled_handle {
+                        Some(handle) => handle,
                         None => {
                             let mut all_labels: Vec<String> = loaded_asset
                                 .labeled_assets
@@ -903,6 +909,17 @@ impl AssetServer {
             .0
     }
 
+    /// Retrieves the handle of the asset at `path`, which can also be requested with the `aliases`
+    /// paths. See [`LoadContext::add_label_alias`](crate::LoadContext::add_label_alias).
+    pub(crate) fn get_or_create_aliased_path_handle<A: Asset>(
+        &self,
+        path: AssetPath<'static>,
+        aliases: Vec<AssetPath<'static>>,
+    ) -> Handle<A> {
+        let mut infos = self.data.infos.write();
+        infos.get_or_create_aliased_path_handle(path, aliases)
+    }
+
     pub(crate) async fn get_meta_loader_and_reader<'a>(
         &'a self,
         asset_path: &'a AssetPath<'_>,
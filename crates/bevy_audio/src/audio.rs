@@ -1,9 +1,10 @@
-use crate::{AudioSource, Decodable};
+use crate::{AudioBus, AudioSource, Decodable};
 use bevy_asset::{Asset, Handle};
 use bevy_derive::Deref;
 use bevy_ecs::prelude::*;
 use bevy_math::Vec3;
 use bevy_reflect::prelude::*;
+use std::time::Duration;
 
 /// A volume level equivalent to a non-negative float.
 #[derive(Clone, Copy, Deref, Debug, Reflect)]
@@ -35,7 +36,8 @@ impl Volume {
 pub enum PlaybackMode {
     /// Play the sound once. Do nothing when it ends.
     Once,
-    /// Repeat the sound forever.
+    /// Repeat the sound forever, between the loop points set by
+    /// [`PlaybackSettings::loop_start`] and [`PlaybackSettings::loop_end`].
     Loop,
     /// Despawn the entity when the sound finishes playing.
     Despawn,
@@ -64,12 +66,31 @@ pub struct PlaybackSettings {
     ///
     /// See also: [`SpatialListener`].
     ///
-    /// Note: Bevy does not currently support HRTF or any other high-quality 3D sound rendering
-    /// features. Spatial audio is implemented via simple left-right stereo panning.
+    /// The attenuation, directivity and doppler effect of the sound are configured by its
+    /// [`SpatialAudioEmitter`](crate::SpatialAudioEmitter).
+    ///
+    /// Spatial audio is rendered by left-right stereo panning, or by an HRTF model if the
+    /// listener uses [`SpatialMode::Hrtf`].
     pub spatial: bool,
     /// Optional scale factor applied to the positions of this audio source and the listener,
     /// overriding the default value configured on [`AudioPlugin::default_spatial_scale`](crate::AudioPlugin::default_spatial_scale).
     pub spatial_scale: Option<SpatialScale>,
+    /// The mixer bus to play on, whose volume is controlled through
+    /// [`AudioBuses`](crate::AudioBuses).
+    pub bus: AudioBus,
+    /// If not zero, the sound starts silent and fades in over this duration.
+    ///
+    /// To fade out a playing sound, see [`AudioSinkPlayback::fade_out`](crate::AudioSinkPlayback::fade_out).
+    pub fade_in: Duration,
+    /// The position the sound restarts from when it loops, if its mode is [`PlaybackMode::Loop`].
+    ///
+    /// The loop points are rounded to the nearest frame of the sound, so they're sample-accurate:
+    /// the loop point at frame `n` of a sound sampled at `rate` is
+    /// `Duration::from_secs_f64(n as f64 / rate as f64)`.
+    pub loop_start: Duration,
+    /// The position at which the sound restarts from [`loop_start`](Self::loop_start), or `None`
+    /// to loop at the end of the sound.
+    pub loop_end: Option<Duration>,
 }
 
 impl Default for PlaybackSettings {
@@ -88,6 +109,10 @@ impl PlaybackSettings {
         paused: false,
         spatial: false,
         spatial_scale: None,
+        bus: AudioBus::MASTER,
+        fade_in: Duration::ZERO,
+        loop_start: Duration::ZERO,
+        loop_end: None,
     };
 
     /// Will play the associated audio source in a loop.
@@ -137,12 +162,36 @@ impl PlaybackSettings {
         self.spatial_scale = Some(spatial_scale);
         self
     }
+
+    /// Helper to play on a mixer bus.
+    pub const fn with_bus(mut self, bus: AudioBus) -> Self {
+        self.bus = bus;
+        self
+    }
+
+    /// Helper to fade in the sound when it starts playing.
+    pub const fn with_fade_in(mut self, duration: Duration) -> Self {
+        self.fade_in = duration;
+        self
+    }
+
+    /// Helper to loop between `start` and `end`, e.g. to play the intro of a music track once,
+    /// and then loop its body.
+    pub const fn with_loop_points(mut self, start: Duration, end: Option<Duration>) -> Self {
+        self.mode = PlaybackMode::Loop;
+        self.loop_start = start;
+        self.loop_end = end;
+        self
+    }
 }
 
 /// Settings for the listener for spatial audio sources.
 ///
 /// This must be accompanied by `Transform` and `GlobalTransform`.
-/// Only one entity with a `SpatialListener` should be present at any given time.
+///
+/// There can be several listeners, e.g. one per player of a split-screen game. Each spatial
+/// sound is rendered for every listener, and mixed into the output [`channels`](Self::channels)
+/// of the listener.
 #[derive(Component, Clone, Debug, Reflect)]
 #[reflect(Default, Component)]
 pub struct SpatialListener {
@@ -150,6 +199,15 @@ pub struct SpatialListener {
     pub left_ear_offset: Vec3,
     /// Right ear position relative to the `GlobalTransform`.
     pub right_ear_offset: Vec3,
+    /// How spatial sounds are rendered for this listener.
+    pub mode: SpatialMode,
+    /// The output channels the left and right ears of this listener are mixed into.
+    ///
+    /// The number of output channels is set by
+    /// [`AudioPlugin::output_channels`](crate::AudioPlugin::output_channels), and channels
+    /// beyond the default output device can be played on other devices with
+    /// [`AudioOutputDevices`](crate::AudioOutputDevices).
+    pub channels: [u16; 2],
 }
 
 impl Default for SpatialListener {
@@ -167,8 +225,36 @@ impl SpatialListener {
         SpatialListener {
             left_ear_offset: Vec3::X * gap / -2.0,
             right_ear_offset: Vec3::X * gap / 2.0,
+            mode: SpatialMode::Panning,
+            channels: [0, 1],
         }
     }
+
+    /// Helper to render spatial sounds with `mode`.
+    pub fn with_mode(mut self, mode: SpatialMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Helper to mix the left and right ears into the output channels `left` and `right`.
+    pub fn with_channels(mut self, left: u16, right: u16) -> Self {
+        self.channels = [left, right];
+        self
+    }
+}
+
+/// How spatial sounds are rendered for a [`SpatialListener`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+pub enum SpatialMode {
+    /// Pans sounds between the left and right channels, which works on any speakers.
+    #[default]
+    Panning,
+    /// Filters sounds with a head-related transfer function (HRTF), modeling the delay between
+    /// the ears and the filtering of the head and the outer ears.
+    ///
+    /// This localizes sounds much better, including above, below and behind the listener, but
+    /// only on headphones.
+    Hrtf,
 }
 
 /// Use this [`Resource`] to control the global volume of all audio.
@@ -1,13 +1,28 @@
+use std::{sync::Arc, time::Duration};
+
 use crate::{
-    AudioSourceBundle, Decodable, DefaultSpatialScale, GlobalVolume, PlaybackMode,
-    PlaybackSettings, SpatialAudioSink, SpatialListener,
+    effects::SharedEffects,
+    fade::Fade,
+    playhead::{decode, DecodedSound, Playhead, SeekableSource},
+    recording::OutputMixer,
+    spatial::SpatialPositions,
+    AudioBus, AudioBuses, AudioEffects, AudioOcclusion, AudioSinkPlayback, AudioSourceBundle,
+    Crossfade, Decodable, DefaultSpatialScale, GlobalVolume, PlaybackMode, PlaybackSettings,
+    SpatialAudioEmitter, SpatialAudioSink, SpatialListener, SpatialMode, SpeedOfSound,
 };
 use bevy_asset::{Asset, Assets, Handle};
 use bevy_ecs::{prelude::*, system::SystemParam};
 use bevy_math::Vec3;
+use bevy_time::Time;
 use bevy_transform::prelude::GlobalTransform;
 use bevy_utils::tracing::warn;
-use rodio::{OutputStream, OutputStreamHandle, Sink, Source, SpatialSink};
+use rodio::{
+    cpal::{
+        self,
+        traits::{DeviceTrait, HostTrait},
+    },
+    OutputStream, Sink, Source,
+};
 
 use crate::AudioSink;
 
@@ -25,23 +40,39 @@ use crate::AudioSink;
 /// However, repeatedly inserting this resource into the app will **leak more memory**.
 #[derive(Resource)]
 pub(crate) struct AudioOutput {
-    stream_handle: Option<OutputStreamHandle>,
+    pub(crate) mixer: Option<OutputMixer>,
 }
 
 impl Default for AudioOutput {
     fn default() -> Self {
-        if let Ok((stream, stream_handle)) = OutputStream::try_default() {
-            // We leak `OutputStream` to prevent the audio from stopping.
-            std::mem::forget(stream);
-            Self {
-                stream_handle: Some(stream_handle),
-            }
-        } else {
+        Self::new(None)
+    }
+}
+
+impl AudioOutput {
+    /// Opens the default output device, mixing `channels` channels, or as many as the device
+    /// has if `None`.
+    pub(crate) fn new(channels: Option<u16>) -> Self {
+        let Ok((stream, stream_handle)) = OutputStream::try_default() else {
             warn!("No audio device found.");
-            Self {
-                stream_handle: None,
-            }
+            return Self { mixer: None };
+        };
+        // Mix in the format of the device, like the output stream.
+        let (device_channels, sample_rate) = cpal::default_host()
+            .default_output_device()
+            .and_then(|device| device.default_output_config().ok())
+            .map_or((2, 44100), |config| {
+                (config.channels(), config.sample_rate().0)
+            });
+        let channels = channels.unwrap_or(device_channels).max(1);
+        let (mixer, output) = OutputMixer::new(channels, sample_rate);
+        if let Err(err) = stream_handle.play_raw(output) {
+            warn!("Error playing audio output: {err:?}");
+            return Self { mixer: None };
         }
+        // We leak `OutputStream` to prevent the audio from stopping.
+        std::mem::forget(stream);
+        Self { mixer: Some(mixer) }
     }
 }
 
@@ -58,34 +89,66 @@ pub(crate) struct EarPositions<'w, 's> {
     pub(crate) query: Query<'w, 's, (Entity, &'static GlobalTransform, &'static SpatialListener)>,
 }
 impl<'w, 's> EarPositions<'w, 's> {
-    /// Gets a set of transformed ear positions.
+    /// Gets the positions of a sound emitted at `emitter` towards `forward`, relative to each
+    /// listener, scaled by `scale`.
     ///
-    /// If there are no listeners, use the default values. If a user has added multiple
-    /// listeners for whatever reason, we will return the first value.
-    pub(crate) fn get(&self) -> (Vec3, Vec3) {
-        let (left_ear, right_ear) = self
-            .query
-            .iter()
-            .next()
-            .map(|(_, transform, settings)| {
-                (
-                    transform.transform_point(settings.left_ear_offset),
-                    transform.transform_point(settings.right_ear_offset),
-                )
+    /// If there are no listeners, a listener with the default values is used.
+    pub(crate) fn get(&self, emitter: Vec3, forward: Vec3, scale: Vec3) -> Vec<SpatialPositions> {
+        let mut listeners: Vec<_> = self.query.iter().collect();
+        // Keep the order of the listeners stable, as sounds track the state of each listener.
+        listeners.sort_by_key(|(entity, ..)| *entity);
+        let mut positions: Vec<_> = listeners
+            .into_iter()
+            .map(|(_, transform, settings)| SpatialPositions {
+                emitter: emitter * scale,
+                forward,
+                left_ear: transform.transform_point(settings.left_ear_offset) * scale,
+                right_ear: transform.transform_point(settings.right_ear_offset) * scale,
+                listener_forward: transform.forward(),
+                hrtf: settings.mode == SpatialMode::Hrtf,
+                channels: settings.channels,
             })
-            .unwrap_or_else(|| {
-                let settings = SpatialListener::default();
-                (settings.left_ear_offset, settings.right_ear_offset)
+            .collect();
+        if positions.is_empty() {
+            let settings = SpatialListener::default();
+            positions.push(SpatialPositions {
+                emitter: emitter * scale,
+                forward,
+                left_ear: settings.left_ear_offset * scale,
+                right_ear: settings.right_ear_offset * scale,
+                listener_forward: Vec3::NEG_Z,
+                hrtf: false,
+                channels: settings.channels,
             });
-
-        (left_ear, right_ear)
+        }
+        positions
     }
+}
 
-    pub(crate) fn multiple_listeners(&self) -> bool {
-        self.query.iter().len() > 1
+/// Appends sources whose samples are already converted to `f32`.
+///
+/// Calling [`Sink::append`] directly in a function with a
+/// `f32: FromSample<Source::DecoderItem>` bound confuses type inference.
+trait AppendF32 {
+    fn append_f32<S: Source<Item = f32> + Send + 'static>(&self, source: S);
+}
+
+impl AppendF32 for Sink {
+    fn append_f32<S: Source<Item = f32> + Send + 'static>(&self, source: S) {
+        self.append(source);
     }
 }
 
+/// Applies the effects of the sound and the effects and volume of its bus to `source`.
+fn mix<S: Source<Item = f32>>(
+    buses: &AudioBuses,
+    bus: AudioBus,
+    effects: &SharedEffects,
+    source: S,
+) -> impl Source<Item = f32> {
+    buses.route(bus, effects.apply(source))
+}
+
 /// Plays "queued" audio through the [`AudioOutput`] resource.
 ///
 /// "Queued" audio is any audio entity (with the components from
@@ -94,68 +157,118 @@ impl<'w, 's> EarPositions<'w, 's> {
 ///
 /// This system detects such entities, checks if their source asset
 /// data is available, and creates/inserts the sink.
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn play_queued_audio_system<Source: Asset + Decodable>(
     audio_output: Res<AudioOutput>,
     audio_sources: Res<Assets<Source>>,
     global_volume: Res<GlobalVolume>,
+    buses: Res<AudioBuses>,
     query_nonplaying: Query<
         (
             Entity,
             &Handle<Source>,
             &PlaybackSettings,
             Option<&GlobalTransform>,
+            Option<&AudioEffects>,
+            Option<&SpatialAudioEmitter>,
+            Option<&AudioOcclusion>,
+            Option<&Crossfade>,
         ),
         (Without<AudioSink>, Without<SpatialAudioSink>),
     >,
+    query_playing: Query<(Option<&AudioSink>, Option<&SpatialAudioSink>)>,
     ear_positions: EarPositions,
     default_spatial_scale: Res<DefaultSpatialScale>,
     mut commands: Commands,
 ) where
     f32: rodio::cpal::FromSample<Source::DecoderItem>,
 {
-    let Some(stream_handle) = audio_output.stream_handle.as_ref() else {
+    let Some(mixer) = audio_output.mixer.as_ref() else {
         // audio output unavailable; cannot play sound
         return;
     };
 
-    for (entity, source_handle, settings, maybe_emitter_transform) in &query_nonplaying {
+    for (
+        entity,
+        source_handle,
+        settings,
+        maybe_emitter_transform,
+        maybe_effects,
+        maybe_emitter,
+        maybe_occlusion,
+        maybe_crossfade,
+    ) in &query_nonplaying
+    {
         let Some(audio_source) = audio_sources.get(source_handle) else {
             continue;
         };
         // audio data is available (has loaded), begin playback and insert sink component
-        if settings.spatial {
-            let (left_ear, right_ear) = ear_positions.get();
-
-            // We can only use one `SpatialListener`. If there are more than that, then
-            // the user may have made a mistake.
-            if ear_positions.multiple_listeners() {
-                warn!(
-                    "Multiple SpatialListeners found. Using {:?}.",
-                    ear_positions.query.iter().next().unwrap().0
-                );
+        let effects = maybe_effects
+            .map(|effects| {
+                effects.shared.set(&effects.chain);
+                effects.shared.clone()
+            })
+            .unwrap_or_default();
+        let sound = decode(audio_source, Duration::ZERO);
+        let (source, playhead) = match settings.mode {
+            PlaybackMode::Loop => {
+                let (start, end) = (settings.loop_start, settings.loop_end);
+                match audio_source.decoder_at(start) {
+                    // Decode the next loop ahead, so the sound loops seamlessly.
+                    Some(decoder) => {
+                        let next_loop = DecodedSound::from_decoder::<Source>(decoder);
+                        let playhead = Arc::new(Playhead::decoding_loops(start, next_loop));
+                        let source =
+                            SeekableSource::decoding_loops(sound, playhead.clone(), start, end);
+                        (source, playhead)
+                    }
+                    None => {
+                        let playhead = Arc::new(Playhead::default());
+                        let source = SeekableSource::looping(sound, playhead.clone(), start, end);
+                        (source, playhead)
+                    }
+                }
             }
-
+            _ => {
+                let playhead = Arc::new(Playhead::default());
+                (SeekableSource::once(sound, playhead.clone()), playhead)
+            }
+        };
+        let fade = Arc::new(Fade::default());
+        let fade_in = match maybe_crossfade {
+            Some(crossfade) => {
+                if let Ok((sink, spatial_sink)) = query_playing.get(crossfade.from) {
+                    match (sink, spatial_sink) {
+                        (Some(sink), _) => sink.fade_out(crossfade.duration),
+                        (_, Some(sink)) => sink.fade_out(crossfade.duration),
+                        (None, None) => {}
+                    }
+                }
+                crossfade.duration
+            }
+            None => settings.fade_in,
+        };
+        let source = mix(&buses, settings.bus, &effects, fade.apply(source, fade_in));
+        if settings.spatial {
             let scale = settings.spatial_scale.unwrap_or(default_spatial_scale.0).0;
 
-            let emitter_translation = if let Some(emitter_transform) = maybe_emitter_transform {
-                (emitter_transform.translation() * scale).into()
-            } else {
-                warn!("Spatial AudioBundle with no GlobalTransform component. Using zero.");
-                Vec3::ZERO.into()
-            };
+            let (emitter_translation, emitter_forward) =
+                if let Some(emitter_transform) = maybe_emitter_transform {
+                    (emitter_transform.translation(), emitter_transform.forward())
+                } else {
+                    warn!("Spatial AudioBundle with no GlobalTransform component. Using zero.");
+                    (Vec3::ZERO, Vec3::NEG_Z)
+                };
 
-            let sink = match SpatialSink::try_new(
-                stream_handle,
-                emitter_translation,
-                (left_ear * scale).into(),
-                (right_ear * scale).into(),
-            ) {
-                Ok(sink) => sink,
-                Err(err) => {
-                    warn!("Error creating spatial sink: {err:?}");
-                    continue;
-                }
-            };
+            let sink = SpatialAudioSink::new(
+                mixer.sink(settings.bus),
+                ear_positions.get(emitter_translation, emitter_forward, scale),
+                maybe_emitter.cloned().unwrap_or_default(),
+                maybe_occlusion.copied().unwrap_or_default(),
+                mixer.channels,
+                playhead,
+                fade,
+            );
 
             sink.set_speed(settings.speed);
             sink.set_volume(settings.volume.0 * global_volume.volume.0);
@@ -164,38 +277,26 @@ pub(crate) fn play_queued_audio_system<Source: Asset + Decodable>(
                 sink.pause();
             }
 
+            sink.append(source);
             match settings.mode {
-                PlaybackMode::Loop => {
-                    sink.append(audio_source.decoder().repeat_infinite());
-                    commands.entity(entity).insert(SpatialAudioSink { sink });
-                }
-                PlaybackMode::Once => {
-                    sink.append(audio_source.decoder());
-                    commands.entity(entity).insert(SpatialAudioSink { sink });
+                PlaybackMode::Loop | PlaybackMode::Once => {
+                    commands.entity(entity).insert(sink);
                 }
                 PlaybackMode::Despawn => {
-                    sink.append(audio_source.decoder());
                     commands
                         .entity(entity)
                         // PERF: insert as bundle to reduce archetype moves
-                        .insert((SpatialAudioSink { sink }, PlaybackDespawnMarker));
+                        .insert((sink, PlaybackDespawnMarker));
                 }
                 PlaybackMode::Remove => {
-                    sink.append(audio_source.decoder());
                     commands
                         .entity(entity)
                         // PERF: insert as bundle to reduce archetype moves
-                        .insert((SpatialAudioSink { sink }, PlaybackRemoveMarker));
+                        .insert((sink, PlaybackRemoveMarker));
                 }
             };
         } else {
-            let sink = match Sink::try_new(stream_handle) {
-                Ok(sink) => sink,
-                Err(err) => {
-                    warn!("Error creating sink: {err:?}");
-                    continue;
-                }
-            };
+            let sink = mixer.sink(settings.bus);
 
             sink.set_speed(settings.speed);
             sink.set_volume(settings.volume.0 * global_volume.volume.0);
@@ -204,28 +305,27 @@ pub(crate) fn play_queued_audio_system<Source: Asset + Decodable>(
                 sink.pause();
             }
 
+            sink.append_f32(source);
+            let sink = AudioSink {
+                sink,
+                playhead,
+                fade,
+            };
             match settings.mode {
-                PlaybackMode::Loop => {
-                    sink.append(audio_source.decoder().repeat_infinite());
-                    commands.entity(entity).insert(AudioSink { sink });
-                }
-                PlaybackMode::Once => {
-                    sink.append(audio_source.decoder());
-                    commands.entity(entity).insert(AudioSink { sink });
+                PlaybackMode::Loop | PlaybackMode::Once => {
+                    commands.entity(entity).insert(sink);
                 }
                 PlaybackMode::Despawn => {
-                    sink.append(audio_source.decoder());
                     commands
                         .entity(entity)
                         // PERF: insert as bundle to reduce archetype moves
-                        .insert((AudioSink { sink }, PlaybackDespawnMarker));
+                        .insert((sink, PlaybackDespawnMarker));
                 }
                 PlaybackMode::Remove => {
-                    sink.append(audio_source.decoder());
                     commands
                         .entity(entity)
                         // PERF: insert as bundle to reduce archetype moves
-                        .insert((AudioSink { sink }, PlaybackRemoveMarker));
+                        .insert((sink, PlaybackRemoveMarker));
                 }
             };
         }
@@ -279,51 +379,39 @@ pub(crate) fn cleanup_finished_audio<T: Decodable + Asset>(
 
 /// Run Condition to only play audio if the audio output is available
 pub(crate) fn audio_output_available(audio_output: Res<AudioOutput>) -> bool {
-    audio_output.stream_handle.is_some()
+    audio_output.mixer.is_some()
 }
 
-/// Updates spatial audio sinks when emitter positions change.
-pub(crate) fn update_emitter_positions(
-    mut emitters: Query<
-        (&GlobalTransform, &SpatialAudioSink, &PlaybackSettings),
-        Or<(Changed<GlobalTransform>, Changed<PlaybackSettings>)>,
-    >,
-    default_spatial_scale: Res<DefaultSpatialScale>,
-) {
-    for (transform, sink, settings) in emitters.iter_mut() {
-        let scale = settings.spatial_scale.unwrap_or(default_spatial_scale.0).0;
-
-        let translation = transform.translation() * scale;
-        sink.set_emitter_position(translation);
-    }
-}
-
-/// Updates spatial audio sink ear positions when spatial listeners change.
-pub(crate) fn update_listener_positions(
-    mut emitters: Query<(&SpatialAudioSink, &PlaybackSettings)>,
-    changed_listener: Query<
-        (),
-        (
-            Or<(
-                Changed<SpatialListener>,
-                Changed<GlobalTransform>,
-                Changed<PlaybackSettings>,
-            )>,
-            With<SpatialListener>,
-        ),
-    >,
+/// Updates the positions of the emitters and listeners of spatial audio sinks, their doppler
+/// effect and their occlusion.
+pub(crate) fn update_spatial_audio(
+    time: Res<Time>,
+    emitters: Query<(
+        &SpatialAudioSink,
+        &PlaybackSettings,
+        Option<&GlobalTransform>,
+        Option<&SpatialAudioEmitter>,
+        Option<&AudioOcclusion>,
+    )>,
     ear_positions: EarPositions,
     default_spatial_scale: Res<DefaultSpatialScale>,
+    speed_of_sound: Res<SpeedOfSound>,
 ) {
-    if !default_spatial_scale.is_changed() && changed_listener.is_empty() {
-        return;
-    }
-
-    let (left_ear, right_ear) = ear_positions.get();
+    let default_emitter = SpatialAudioEmitter::default();
+    let default_occlusion = AudioOcclusion::default();
 
-    for (sink, settings) in emitters.iter_mut() {
+    for (sink, settings, transform, emitter, occlusion) in &emitters {
         let scale = settings.spatial_scale.unwrap_or(default_spatial_scale.0).0;
+        let (translation, forward) = transform.map_or((Vec3::ZERO, Vec3::NEG_Z), |transform| {
+            (transform.translation(), transform.forward())
+        });
 
-        sink.set_ears_position(left_ear * scale, right_ear * scale);
+        sink.update(
+            ear_positions.get(translation, forward, scale),
+            emitter.unwrap_or(&default_emitter),
+            occlusion.unwrap_or(&default_occlusion),
+            time.delta_seconds(),
+            speed_of_sound.0,
+        );
     }
 }
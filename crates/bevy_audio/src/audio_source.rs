@@ -4,7 +4,11 @@ use bevy_asset::{
 };
 use bevy_reflect::TypePath;
 use bevy_utils::BoxedFuture;
-use std::{io::Cursor, sync::Arc};
+use std::{
+    io::Cursor,
+    sync::{atomic::AtomicBool, Arc},
+    time::Duration,
+};
 
 /// A source of audio data
 #[derive(Asset, Debug, Clone, TypePath)]
@@ -94,6 +98,26 @@ pub trait Decodable: Send + Sync + 'static {
 
     /// Build and return a [`Self::Decoder`] of the implementing type
     fn decoder(&self) -> Self::Decoder;
+
+    /// Build and return a [`Self::Decoder`] starting at `position`, if the implementing type can
+    /// skip the sound before `position` without decoding it on the calling thread.
+    ///
+    /// Otherwise, seeking decodes the sound from the start and skips its samples, and looping
+    /// sounds are kept in memory once decoded. When this returns a decoder, looping sounds are
+    /// decoded again from their loop start at each loop instead.
+    fn decoder_at(&self, position: Duration) -> Option<Self::Decoder> {
+        let _ = position;
+        None
+    }
+
+    /// Returns a flag set while `decoder` plays silence, waiting for its samples to be decoded,
+    /// e.g. while a streamed sound is downloaded.
+    ///
+    /// The playback position doesn't advance while the flag is set.
+    fn stalled(decoder: &Self::Decoder) -> Option<Arc<AtomicBool>> {
+        let _ = decoder;
+        None
+    }
 }
 
 impl Decodable for AudioSource {
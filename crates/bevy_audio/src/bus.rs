@@ -0,0 +1,536 @@
+use std::sync::{
+    atomic::{AtomicU32, Ordering},
+    Arc,
+};
+use std::time::Duration;
+
+use bevy_ecs::prelude::*;
+use bevy_reflect::prelude::*;
+use bevy_time::Time;
+use bevy_utils::{tracing::warn, HashMap};
+use rodio::Source;
+
+use crate::{
+    effects::{EffectSource, SharedEffects},
+    AudioEffect, Volume,
+};
+
+/// How long the gain of a playing sound takes to follow a volume change from silence to full
+/// volume, in seconds.
+///
+/// This avoids audible clicks when the volume of a bus changes abruptly.
+pub(crate) const SMOOTHING_TIME: f32 = 0.01;
+
+/// A named mixer bus, which groups sounds so their volume can be controlled together.
+///
+/// Sounds are assigned to a bus with [`PlaybackSettings::bus`](crate::PlaybackSettings::bus),
+/// and the volume of each bus is controlled through the [`AudioBuses`] resource. Every bus is
+/// routed to the [`AudioBus::MASTER`] bus, or to the bus set with [`AudioBuses::set_output`], and
+/// its volume is scaled by the volume of the buses it's routed to.
+///
+/// Custom buses can be added with [`AudioBuses::add_bus`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Reflect)]
+#[reflect(Default, PartialEq, Hash)]
+pub struct AudioBus(pub &'static str);
+
+impl AudioBus {
+    /// The bus controlling the volume of all sounds.
+    pub const MASTER: Self = Self("master");
+    /// The bus for background music.
+    pub const MUSIC: Self = Self("music");
+    /// The bus for sound effects.
+    pub const SFX: Self = Self("sfx");
+    /// The bus for dialogs and voice-overs.
+    pub const VOICE: Self = Self("voice");
+}
+
+impl Default for AudioBus {
+    fn default() -> Self {
+        Self::MASTER
+    }
+}
+
+/// The gain of a bus, shared with the sounds playing on the audio thread.
+#[derive(Clone, Debug)]
+pub(crate) struct BusGain(Arc<AtomicU32>);
+
+impl BusGain {
+    fn new(gain: f32) -> Self {
+        Self(Arc::new(AtomicU32::new(gain.to_bits())))
+    }
+
+    fn get(&self) -> f32 {
+        f32::from_bits(self.0.load(Ordering::Relaxed))
+    }
+
+    fn set(&self, gain: f32) {
+        self.0.store(gain.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Wraps `source` so its samples are scaled by the gain of the bus.
+    fn apply<S: Source<Item = f32>>(&self, source: S) -> BusSource<S> {
+        let gain = self.get();
+        BusSource {
+            input: source,
+            gain: self.clone(),
+            current: gain,
+        }
+    }
+}
+
+/// A [`Source`] whose samples are scaled by the gain of a bus.
+///
+/// Changes of the gain are smoothed over [`SMOOTHING_TIME`].
+pub(crate) struct BusSource<I> {
+    input: I,
+    gain: BusGain,
+    current: f32,
+}
+
+impl<I> Iterator for BusSource<I>
+where
+    I: Source<Item = f32>,
+{
+    type Item = f32;
+
+    #[inline]
+    fn next(&mut self) -> Option<f32> {
+        let sample = self.input.next()?;
+        let target = self.gain.get();
+        if self.current != target {
+            let samples_per_second = self.input.sample_rate() as f32 * self.input.channels() as f32;
+            let step = 1.0 / (SMOOTHING_TIME * samples_per_second).max(1.0);
+            self.current = if self.current < target {
+                (self.current + step).min(target)
+            } else {
+                (self.current - step).max(target)
+            };
+        }
+        Some(sample * self.current)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I> Source for BusSource<I>
+where
+    I: Source<Item = f32>,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+}
+
+/// A volume transition in progress.
+#[derive(Clone, Debug)]
+struct VolumeRamp {
+    from: f32,
+    to: f32,
+    duration: Duration,
+    elapsed: Duration,
+}
+
+#[derive(Clone, Debug)]
+struct BusState {
+    volume: f32,
+    muted: bool,
+    ramp: Option<VolumeRamp>,
+    /// The bus this bus is routed to, or `None` for the [`AudioBus::MASTER`] bus.
+    output: Option<AudioBus>,
+    gain: BusGain,
+    effects: Vec<AudioEffect>,
+    /// The effects of this bus followed by the effects of the buses it's routed to.
+    shared_effects: SharedEffects,
+}
+
+impl BusState {
+    fn new(output: Option<AudioBus>) -> Self {
+        Self {
+            volume: 1.0,
+            muted: false,
+            ramp: None,
+            output,
+            gain: BusGain::new(1.0),
+            effects: Vec::new(),
+            shared_effects: SharedEffects::default(),
+        }
+    }
+
+    fn tick(&mut self, delta: Duration) {
+        let Some(ramp) = &mut self.ramp else {
+            return;
+        };
+        ramp.elapsed += delta;
+        if ramp.elapsed >= ramp.duration {
+            self.volume = ramp.to;
+            self.ramp = None;
+        } else {
+            let t = ramp.elapsed.as_secs_f32() / ramp.duration.as_secs_f32();
+            self.volume = ramp.from + (ramp.to - ramp.from) * t;
+        }
+    }
+
+    fn gain(&self) -> f32 {
+        if self.muted {
+            0.0
+        } else {
+            self.volume
+        }
+    }
+}
+
+/// The volume and mute state of the [`AudioBus`]es.
+///
+/// Unlike [`GlobalVolume`](crate::GlobalVolume), changes to this resource also apply to the
+/// sounds that are already playing.
+///
+/// ```
+/// # use bevy_ecs::prelude::*;
+/// # use bevy_audio::{AudioBus, AudioBuses, Volume};
+/// # use std::time::Duration;
+/// fn duck_music(mut buses: ResMut<AudioBuses>) {
+///     buses.ramp_volume(AudioBus::MUSIC, Volume::new(0.2), Duration::from_millis(500));
+/// }
+/// ```
+#[derive(Resource, Clone, Debug)]
+pub struct AudioBuses {
+    buses: HashMap<AudioBus, BusState>,
+}
+
+impl Default for AudioBuses {
+    fn default() -> Self {
+        let mut buses = Self {
+            buses: HashMap::default(),
+        };
+        for bus in [
+            AudioBus::MASTER,
+            AudioBus::MUSIC,
+            AudioBus::SFX,
+            AudioBus::VOICE,
+        ] {
+            buses.add_bus(bus);
+        }
+        buses
+    }
+}
+
+impl AudioBuses {
+    /// Adds a custom bus, at full volume and routed to the [`AudioBus::MASTER`] bus. Does nothing
+    /// if the bus already exists.
+    pub fn add_bus(&mut self, bus: AudioBus) {
+        let output = (bus != AudioBus::MASTER).then_some(AudioBus::MASTER);
+        self.buses
+            .entry(bus)
+            .or_insert_with(|| BusState::new(output));
+    }
+
+    /// Gets the bus `bus` is routed to, or `None` for the [`AudioBus::MASTER`] bus.
+    pub fn output(&self, bus: AudioBus) -> Option<AudioBus> {
+        self.buses.get(&bus).and_then(|state| state.output)
+    }
+
+    /// Routes `bus` to `output`, so the sounds of `bus` are also processed by the volume and
+    /// the effects of `output` and of the buses it's routed to, up to the [`AudioBus::MASTER`]
+    /// bus.
+    ///
+    /// Like the other changes to this resource, this also applies to the sounds that are already
+    /// playing. Routing the master bus, or routing a bus to itself or to one of the buses routed
+    /// to it, is ignored.
+    ///
+    /// ```
+    /// # use bevy_ecs::prelude::*;
+    /// # use bevy_audio::{AudioBus, AudioBuses, AudioEffect};
+    /// const WORLD: AudioBus = AudioBus("world");
+    /// const MENU: AudioBus = AudioBus("menu");
+    ///
+    /// fn setup_buses(mut buses: ResMut<AudioBuses>) {
+    ///     buses.add_bus(WORLD);
+    ///     buses.add_bus(MENU);
+    ///     buses.set_output(AudioBus::MUSIC, WORLD);
+    ///     buses.set_output(AudioBus::SFX, WORLD);
+    /// }
+    ///
+    /// // Muffles the music and sound effects, but not the sounds of the menu.
+    /// fn pause(mut buses: ResMut<AudioBuses>) {
+    ///     buses.set_effects(WORLD, [AudioEffect::low_pass(800.0)]);
+    /// }
+    /// ```
+    pub fn set_output(&mut self, bus: AudioBus, output: AudioBus) {
+        if bus == AudioBus::MASTER {
+            warn!("The master audio bus can't be routed to another bus.");
+            return;
+        }
+        if !self.contains(output) {
+            warn!("Unknown audio bus {:?}.", output.0);
+            return;
+        }
+        if self.route_of(output).any(|routed| routed == bus) {
+            warn!(
+                "Audio bus {:?} can't be routed to {:?}, which is routed to it.",
+                bus.0, output.0
+            );
+            return;
+        }
+        if let Some(state) = self.state_mut(bus) {
+            state.output = Some(output);
+        }
+    }
+
+    /// Returns `true` if `bus` exists.
+    pub fn contains(&self, bus: AudioBus) -> bool {
+        self.buses.contains_key(&bus)
+    }
+
+    /// Iterates over the existing buses, e.g. to build a volume settings screen.
+    pub fn buses(&self) -> impl Iterator<Item = AudioBus> + '_ {
+        self.buses.keys().copied()
+    }
+
+    /// Gets the volume of `bus`, ignoring whether it is muted.
+    ///
+    /// While the volume is ramping, this is the current volume of the ramp.
+    pub fn volume(&self, bus: AudioBus) -> Volume {
+        self.buses
+            .get(&bus)
+            .map_or(Volume::default(), |state| Volume(state.volume))
+    }
+
+    /// Sets the volume of `bus` immediately, interrupting any ramp in progress.
+    pub fn set_volume(&mut self, bus: AudioBus, volume: Volume) {
+        if let Some(state) = self.state_mut(bus) {
+            state.volume = volume.0;
+            state.ramp = None;
+        }
+    }
+
+    /// Smoothly changes the volume of `bus` to `volume` over `duration`, starting from its current
+    /// volume.
+    pub fn ramp_volume(&mut self, bus: AudioBus, volume: Volume, duration: Duration) {
+        if let Some(state) = self.state_mut(bus) {
+            state.ramp = Some(VolumeRamp {
+                from: state.volume,
+                to: volume.0,
+                duration,
+                elapsed: Duration::ZERO,
+            });
+        }
+    }
+
+    /// Returns `true` if `bus` is muted.
+    pub fn is_muted(&self, bus: AudioBus) -> bool {
+        self.buses.get(&bus).is_some_and(|state| state.muted)
+    }
+
+    /// Mutes or unmutes `bus`, keeping its volume.
+    pub fn set_muted(&mut self, bus: AudioBus, muted: bool) {
+        if let Some(state) = self.state_mut(bus) {
+            state.muted = muted;
+        }
+    }
+
+    /// Mutes `bus` if it's unmuted, and unmutes it otherwise.
+    pub fn toggle_mute(&mut self, bus: AudioBus) {
+        let muted = self.is_muted(bus);
+        self.set_muted(bus, !muted);
+    }
+
+    /// Gets the volume applied to the sounds of `bus`, taking into account the buses it's routed
+    /// to and whether the buses are muted.
+    pub fn effective_volume(&self, bus: AudioBus) -> f32 {
+        self.route_of(bus)
+            .map(|bus| self.buses[&bus].gain())
+            .product()
+    }
+
+    /// Gets the [`AudioEffect`]s applied to the sounds of `bus`.
+    pub fn effects(&self, bus: AudioBus) -> &[AudioEffect] {
+        self.buses
+            .get(&bus)
+            .map_or(&[], |state| state.effects.as_slice())
+    }
+
+    /// Gets a mutable reference to the [`AudioEffect`]s applied to the sounds of `bus`, e.g. to
+    /// change their parameters.
+    pub fn effects_mut(&mut self, bus: AudioBus) -> Option<&mut Vec<AudioEffect>> {
+        self.state_mut(bus).map(|state| &mut state.effects)
+    }
+
+    /// Sets the [`AudioEffect`]s applied, in order, to the sounds of `bus`, including the sounds
+    /// that are already playing.
+    ///
+    /// The effects of a bus are applied to the sounds of the bus before the effects of the buses
+    /// it's routed to.
+    ///
+    /// Note: each sound is processed separately, so effects which don't just filter the sound,
+    /// like [`AudioEffect::Compressor`], react to the loudness of each sound rather than to the
+    /// loudness of the whole bus.
+    pub fn set_effects(&mut self, bus: AudioBus, effects: impl IntoIterator<Item = AudioEffect>) {
+        if let Some(state) = self.state_mut(bus) {
+            state.effects = effects.into_iter().collect();
+        }
+    }
+
+    /// Gets the state of `bus`, falling back to the [`AudioBus::MASTER`] bus if `bus` doesn't
+    /// exist.
+    fn state(&self, bus: AudioBus) -> &BusState {
+        self.buses.get(&bus).unwrap_or_else(|| {
+            warn!("Unknown audio bus {:?}. Using the master bus.", bus.0);
+            &self.buses[&AudioBus::MASTER]
+        })
+    }
+
+    /// Iterates over `bus` and the buses it's routed to, ending with the [`AudioBus::MASTER`] bus.
+    fn route_of(&self, bus: AudioBus) -> impl Iterator<Item = AudioBus> + '_ {
+        let mut next = self.contains(bus).then_some(bus);
+        // Guards against routing loops, which `set_output` prevents.
+        let mut remaining = self.buses.len();
+        std::iter::from_fn(move || {
+            let bus = next.filter(|_| remaining > 0)?;
+            remaining -= 1;
+            next = self.buses.get(&bus).and_then(|state| state.output);
+            Some(bus)
+        })
+    }
+
+    /// Wraps `source` so it's processed by the effects and the volume of `bus` and of the buses
+    /// it's routed to.
+    pub(crate) fn route<S: Source<Item = f32>>(
+        &self,
+        bus: AudioBus,
+        source: S,
+    ) -> BusSource<EffectSource<S>> {
+        let state = self.state(bus);
+        state.gain.apply(state.shared_effects.apply(source))
+    }
+
+    fn state_mut(&mut self, bus: AudioBus) -> Option<&mut BusState> {
+        let state = self.buses.get_mut(&bus);
+        if state.is_none() {
+            warn!("Unknown audio bus {:?}.", bus.0);
+        }
+        state
+    }
+
+    fn tick(&mut self, delta: Duration) {
+        for state in self.buses.values_mut() {
+            state.tick(delta);
+        }
+        for (bus, state) in &self.buses {
+            state.gain.set(self.effective_volume(*bus));
+            let effects = self
+                .route_of(*bus)
+                .flat_map(|bus| self.buses[&bus].effects.iter().cloned())
+                .collect::<Vec<_>>();
+            state.shared_effects.set(&effects);
+        }
+    }
+}
+
+/// Advances the volume ramps of the [`AudioBuses`], and applies their volume and effects to the
+/// playing sounds.
+pub(crate) fn update_audio_buses(time: Res<Time>, mut buses: ResMut<AudioBuses>) {
+    let ramping = buses.buses.values().any(|state| state.ramp.is_some());
+    if ramping || buses.is_changed() {
+        buses.tick(time.delta());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rodio::buffer::SamplesBuffer;
+
+    #[test]
+    fn bus_volume_is_scaled_by_master_and_mute() {
+        let mut buses = AudioBuses::default();
+        buses.set_volume(AudioBus::MASTER, Volume::new(0.5));
+        buses.set_volume(AudioBus::MUSIC, Volume::new(0.5));
+        assert_eq!(buses.effective_volume(AudioBus::MUSIC), 0.25);
+        assert_eq!(buses.effective_volume(AudioBus::SFX), 0.5);
+
+        buses.toggle_mute(AudioBus::MUSIC);
+        assert!(buses.is_muted(AudioBus::MUSIC));
+        assert_eq!(buses.effective_volume(AudioBus::MUSIC), 0.0);
+        assert_eq!(buses.volume(AudioBus::MUSIC).get(), 0.5);
+
+        buses.set_muted(AudioBus::MASTER, true);
+        assert_eq!(buses.effective_volume(AudioBus::SFX), 0.0);
+    }
+
+    #[test]
+    fn buses_are_routed_to_their_output() {
+        const WORLD: AudioBus = AudioBus("world");
+        let mut buses = AudioBuses::default();
+        buses.add_bus(WORLD);
+        assert_eq!(buses.output(WORLD), Some(AudioBus::MASTER));
+        assert_eq!(buses.output(AudioBus::MASTER), None);
+
+        buses.set_output(AudioBus::SFX, WORLD);
+        buses.set_volume(AudioBus::MASTER, Volume::new(0.5));
+        buses.set_volume(WORLD, Volume::new(0.5));
+        assert_eq!(buses.effective_volume(AudioBus::SFX), 0.25);
+        assert_eq!(buses.effective_volume(AudioBus::VOICE), 0.5);
+
+        // Loops are ignored.
+        buses.set_output(WORLD, AudioBus::SFX);
+        buses.set_output(AudioBus::MASTER, WORLD);
+        assert_eq!(buses.output(WORLD), Some(AudioBus::MASTER));
+        assert_eq!(buses.output(AudioBus::MASTER), None);
+
+        buses.set_effects(WORLD, [AudioEffect::low_pass(800.0)]);
+        buses.set_effects(AudioBus::SFX, [AudioEffect::high_pass(200.0)]);
+        buses.tick(Duration::ZERO);
+        let sfx_effects = buses.state(AudioBus::SFX).shared_effects.chain();
+        assert_eq!(
+            sfx_effects,
+            [AudioEffect::high_pass(200.0), AudioEffect::low_pass(800.0)]
+        );
+        assert!(buses
+            .state(AudioBus::VOICE)
+            .shared_effects
+            .chain()
+            .is_empty());
+    }
+
+    #[test]
+    fn bus_volume_ramps_and_applies_to_playing_sounds() {
+        let mut buses = AudioBuses::default();
+        let gain = buses.state(AudioBus::SFX).gain.clone();
+        buses.ramp_volume(AudioBus::SFX, Volume::ZERO, Duration::from_secs(1));
+
+        buses.tick(Duration::from_millis(250));
+        assert_eq!(buses.volume(AudioBus::SFX).get(), 0.75);
+        assert_eq!(gain.get(), 0.75);
+
+        buses.tick(Duration::from_secs(1));
+        assert_eq!(buses.volume(AudioBus::SFX).get(), 0.0);
+
+        // A sample rate of 100 samples per second smooths the gain over a single sample.
+        let source = SamplesBuffer::new(1, 100, vec![1.0f32; 3]);
+        let gain = BusGain::new(1.0);
+        let mut source = gain.apply(source);
+        assert_eq!(source.next(), Some(1.0));
+        gain.set(0.5);
+        assert_eq!(source.next(), Some(0.5));
+        assert_eq!(source.next(), Some(0.5));
+    }
+}
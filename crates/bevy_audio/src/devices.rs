@@ -0,0 +1,390 @@
+use std::sync::{
+    atomic::{AtomicU32, Ordering},
+    Arc, Mutex,
+};
+use std::time::Duration;
+
+use bevy_ecs::{prelude::*, world::FromWorld};
+use crossbeam_channel::Sender;
+use rodio::{
+    cpal::{
+        self,
+        traits::{DeviceTrait, HostTrait},
+    },
+    OutputStream, Source,
+};
+use thiserror::Error;
+
+use crate::{audio_output::AudioOutput, procedural::SampleRingBuffer};
+
+/// The duration of audio buffered for the devices playing some channels of the output.
+const DEVICE_BUFFER: Duration = Duration::from_millis(100);
+
+/// Plays channels of the mixed audio output on other output devices than the default one.
+///
+/// The default output device plays the first channels of the output. When the output has more
+/// channels, as set by [`AudioPlugin::output_channels`](crate::AudioPlugin::output_channels),
+/// the other channels can be played on other devices, e.g. to give each player of a split-screen
+/// game their own headphones. [`SpatialListener::channels`](crate::SpatialListener::channels)
+/// selects the channels of each listener.
+///
+/// ```
+/// # use bevy_ecs::prelude::*;
+/// # use bevy_audio::AudioOutputDevices;
+/// fn use_second_headphones(mut devices: ResMut<AudioOutputDevices>) {
+///     if let Some(name) = AudioOutputDevices::available().get(1) {
+///         // Channels 2 and 3 go to the second device.
+///         if let Err(err) = devices.play(name, 2) {
+///             eprintln!("Can't play on {name}: {err}");
+///         }
+///     }
+/// }
+/// ```
+#[derive(Resource)]
+pub struct AudioOutputDevices {
+    routes: Option<Arc<OutputRoutes>>,
+    channels: u16,
+    sample_rate: u32,
+    outputs: Vec<DeviceOutput>,
+}
+
+/// A device playing some channels of the output. Dropping it stops the device.
+struct DeviceOutput {
+    name: String,
+    first_channel: u16,
+    channels: u16,
+    /// Disconnected to stop the thread owning the stream.
+    _stop: Sender<()>,
+}
+
+/// An error playing the output on a device.
+#[derive(Error, Debug)]
+pub enum AudioOutputError {
+    /// Audio output is unavailable.
+    #[error("audio output is unavailable")]
+    Unavailable,
+    /// The output device doesn't exist.
+    #[error("no audio output device named {0}")]
+    NoDevice(String),
+    /// The first channel is out of the channels of the output.
+    #[error("channel {channel} is out of the {channels} channels of the output")]
+    ChannelOutOfRange {
+        /// The first channel to play on the device.
+        channel: u16,
+        /// The number of channels of the output.
+        channels: u16,
+    },
+    /// The device couldn't be opened.
+    #[error("failed to open the audio output device: {0}")]
+    Stream(#[from] rodio::StreamError),
+    /// The device couldn't play the output.
+    #[error("failed to play on the audio output device: {0}")]
+    Play(#[from] rodio::PlayError),
+    /// The thread running the device couldn't be started.
+    #[error("failed to start the audio output thread: {0}")]
+    Thread(#[from] std::io::Error),
+}
+
+impl FromWorld for AudioOutputDevices {
+    fn from_world(world: &mut World) -> Self {
+        let mixer = world
+            .get_resource::<AudioOutput>()
+            .and_then(|output| output.mixer.as_ref());
+        Self {
+            routes: mixer.map(|mixer| mixer.routes.clone()),
+            channels: mixer.map_or(0, |mixer| mixer.channels),
+            sample_rate: mixer.map_or(0, |mixer| mixer.sample_rate),
+            outputs: Vec::new(),
+        }
+    }
+}
+
+impl AudioOutputDevices {
+    /// The names of the available output devices.
+    pub fn available() -> Vec<String> {
+        let host = cpal::default_host();
+        let Ok(devices) = host.output_devices() else {
+            return Vec::new();
+        };
+        devices.filter_map(|device| device.name().ok()).collect()
+    }
+
+    /// The number of channels of the mixed output.
+    pub fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    /// Plays the channels of the output from `first_channel` on the device named `device`, as
+    /// many as the device has. Stops the device playing other channels before.
+    pub fn play(&mut self, device: &str, first_channel: u16) -> Result<(), AudioOutputError> {
+        let routes = self.routes.clone().ok_or(AudioOutputError::Unavailable)?;
+        if first_channel >= self.channels {
+            return Err(AudioOutputError::ChannelOutOfRange {
+                channel: first_channel,
+                channels: self.channels,
+            });
+        }
+        self.stop(device);
+
+        let (result_sender, result) = crossbeam_channel::bounded(1);
+        let (stop, stopped) = crossbeam_channel::bounded::<()>(0);
+        let name = device.to_string();
+        let sample_rate = self.sample_rate;
+        let max_channels = self.channels - first_channel;
+        // Streams can't be sent between threads on every platform, so a thread owns it until
+        // `stop` is dropped.
+        std::thread::Builder::new()
+            .name("audio output".to_string())
+            .spawn(
+                move || match open_device(&name, max_channels, sample_rate) {
+                    Ok((stream, ring, channels)) => {
+                        let _ = result_sender.send(Ok((ring, channels)));
+                        let _ = stopped.recv();
+                        drop(stream);
+                    }
+                    Err(err) => {
+                        let _ = result_sender.send(Err(err));
+                    }
+                },
+            )?;
+        let (ring, channels) = result
+            .recv()
+            .unwrap_or_else(|_| Err(AudioOutputError::NoDevice(device.to_string())))?;
+
+        routes.add(OutputRoute {
+            device: device.to_string(),
+            first_channel,
+            channels,
+            samples: ring,
+        });
+        self.outputs.push(DeviceOutput {
+            name: device.to_string(),
+            first_channel,
+            channels,
+            _stop: stop,
+        });
+        Ok(())
+    }
+
+    /// Stops playing the output on the device named `device`.
+    pub fn stop(&mut self, device: &str) {
+        self.outputs.retain(|output| output.name != device);
+        if let Some(routes) = &self.routes {
+            routes.remove(device);
+        }
+    }
+
+    /// The devices playing channels of the output, with the first channel they play and their
+    /// number of channels.
+    pub fn outputs(&self) -> impl Iterator<Item = (&str, u16, u16)> {
+        self.outputs
+            .iter()
+            .map(|output| (output.name.as_str(), output.first_channel, output.channels))
+    }
+}
+
+/// Opens the device named `name`, playing at most `max_channels` channels from a ring buffer.
+fn open_device(
+    name: &str,
+    max_channels: u16,
+    sample_rate: u32,
+) -> Result<(OutputStream, Arc<SampleRingBuffer>, u16), AudioOutputError> {
+    let device = cpal::default_host()
+        .output_devices()
+        .ok()
+        .and_then(|mut devices| devices.find(|device| device.name().is_ok_and(|n| n == name)))
+        .ok_or_else(|| AudioOutputError::NoDevice(name.to_string()))?;
+    let channels = device
+        .default_output_config()
+        .map_or(2, |config| config.channels())
+        .min(max_channels)
+        .max(1);
+    let (stream, stream_handle) = OutputStream::try_from_device(&device)?;
+    let frames = (DEVICE_BUFFER.as_secs_f32() * sample_rate as f32) as usize;
+    let ring = Arc::new(SampleRingBuffer::new(frames.max(1) * channels as usize));
+    stream_handle.play_raw(RingSource {
+        samples: ring.clone(),
+        channels,
+        sample_rate,
+    })?;
+    Ok((stream, ring, channels))
+}
+
+/// Channels of the output sent to a device.
+#[derive(Clone)]
+struct OutputRoute {
+    device: String,
+    first_channel: u16,
+    channels: u16,
+    samples: Arc<SampleRingBuffer>,
+}
+
+/// The devices the channels of the output are sent to, shared with the audio thread.
+#[derive(Default)]
+pub(crate) struct OutputRoutes {
+    routes: Mutex<Vec<OutputRoute>>,
+    version: AtomicU32,
+}
+
+impl OutputRoutes {
+    fn add(&self, route: OutputRoute) {
+        self.routes.lock().unwrap().push(route);
+        self.version.fetch_add(1, Ordering::Release);
+    }
+
+    fn remove(&self, device: &str) {
+        self.routes
+            .lock()
+            .unwrap()
+            .retain(|route| route.device != device);
+        self.version.fetch_add(1, Ordering::Release);
+    }
+}
+
+/// A [`Source`] sending channels of its frames to other devices.
+pub(crate) struct SplitSource<I> {
+    input: I,
+    routes: Arc<OutputRoutes>,
+    version: u32,
+    current: Vec<OutputRoute>,
+    frame: Vec<f32>,
+}
+
+impl<I: Source<Item = f32>> SplitSource<I> {
+    pub(crate) fn new(input: I, routes: Arc<OutputRoutes>) -> Self {
+        Self {
+            input,
+            routes,
+            version: 0,
+            current: Vec::new(),
+            frame: Vec::new(),
+        }
+    }
+
+    fn send_frame(&mut self) {
+        let version = self.routes.version.load(Ordering::Acquire);
+        if version != self.version {
+            // Never block the audio thread: try again on the next frame.
+            if let Ok(routes) = self.routes.routes.try_lock() {
+                self.current.clone_from(&routes);
+                self.version = version;
+            }
+        }
+        for route in &self.current {
+            let first = (route.first_channel as usize).min(self.frame.len());
+            let last = (first + route.channels as usize).min(self.frame.len());
+            // When the device is late, drop the frame rather than waiting.
+            route.samples.push_all(&self.frame[first..last]);
+        }
+        self.frame.clear();
+    }
+}
+
+impl<I: Source<Item = f32>> Iterator for SplitSource<I> {
+    type Item = f32;
+
+    #[inline]
+    fn next(&mut self) -> Option<f32> {
+        let sample = self.input.next()?;
+        self.frame.push(sample);
+        if self.frame.len() >= self.input.channels() as usize {
+            self.send_frame();
+        }
+        Some(sample)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I: Source<Item = f32>> Source for SplitSource<I> {
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+}
+
+/// A [`Source`] playing the channels sent to a device, or silence when they are late.
+struct RingSource {
+    samples: Arc<SampleRingBuffer>,
+    channels: u16,
+    sample_rate: u32,
+}
+
+impl Iterator for RingSource {
+    type Item = f32;
+
+    #[inline]
+    fn next(&mut self) -> Option<f32> {
+        Some(self.samples.pop().unwrap_or(0.0))
+    }
+}
+
+impl Source for RingSource {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rodio::buffer::SamplesBuffer;
+
+    #[test]
+    fn split_sources_send_channels_to_devices() {
+        let routes = Arc::new(OutputRoutes::default());
+        let samples = Arc::new(SampleRingBuffer::new(4));
+        routes.add(OutputRoute {
+            device: "headphones".to_string(),
+            first_channel: 2,
+            channels: 2,
+            samples: samples.clone(),
+        });
+        let output = SamplesBuffer::new(4, 44100, vec![0.0f32, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0]);
+        let source = SplitSource::new(output, routes.clone());
+        assert_eq!(source.count(), 8);
+
+        let mut device = RingSource {
+            samples,
+            channels: 2,
+            sample_rate: 44100,
+        };
+        assert_eq!(
+            device.by_ref().take(5).collect::<Vec<_>>(),
+            [2.0, 3.0, 6.0, 7.0, 0.0]
+        );
+
+        routes.remove("headphones");
+        assert!(routes.routes.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,560 @@
+use std::f32::consts::PI;
+use std::sync::{
+    atomic::{AtomicU32, Ordering},
+    Arc, Mutex,
+};
+use std::time::Duration;
+
+use bevy_ecs::prelude::*;
+use bevy_reflect::prelude::*;
+use rodio::Source;
+
+/// An audio effect, processing the samples of a sound or of an [`AudioBus`](crate::AudioBus).
+///
+/// Frequencies are in hertz, gains in decibels and times in seconds.
+#[derive(Clone, Debug, PartialEq, Reflect)]
+pub enum AudioEffect {
+    /// Attenuates the frequencies above `cutoff`, e.g. to muffle occluded or underwater sounds.
+    LowPass {
+        /// The frequency above which the sound is attenuated.
+        cutoff: f32,
+        /// The resonance of the filter. `0.707` gives a flat response.
+        q: f32,
+    },
+    /// Attenuates the frequencies below `cutoff`, e.g. for radio or telephone voices.
+    HighPass {
+        /// The frequency below which the sound is attenuated.
+        cutoff: f32,
+        /// The resonance of the filter. `0.707` gives a flat response.
+        q: f32,
+    },
+    /// Boosts or cuts frequency bands.
+    Equalizer {
+        /// The bands of the equalizer.
+        bands: Vec<EqBand>,
+    },
+    /// Simulates the reflections of a room.
+    Reverb {
+        /// The size of the room, from `0.0` to `1.0`. Larger rooms ring longer.
+        room_size: f32,
+        /// How quickly the high frequencies of the reflections fade, from `0.0` to `1.0`.
+        damping: f32,
+        /// The proportion of reverberated sound in the output, from `0.0` to `1.0`.
+        wet: f32,
+    },
+    /// Reduces the volume of the sound when it's louder than `threshold`.
+    Compressor {
+        /// The level above which the sound is compressed, in decibels relative to full scale.
+        threshold: f32,
+        /// How much the sound above the threshold is reduced, e.g. `4.0` for a 4:1 compression.
+        ratio: f32,
+        /// How quickly the compression reacts to loud sounds.
+        attack: f32,
+        /// How quickly the compression stops after loud sounds.
+        release: f32,
+        /// A gain applied after the compression.
+        makeup_gain: f32,
+    },
+}
+
+impl AudioEffect {
+    /// Creates a [`AudioEffect::LowPass`] filter with a flat response.
+    pub fn low_pass(cutoff: f32) -> Self {
+        Self::LowPass {
+            cutoff,
+            q: std::f32::consts::FRAC_1_SQRT_2,
+        }
+    }
+
+    /// Creates a [`AudioEffect::HighPass`] filter with a flat response.
+    pub fn high_pass(cutoff: f32) -> Self {
+        Self::HighPass {
+            cutoff,
+            q: std::f32::consts::FRAC_1_SQRT_2,
+        }
+    }
+}
+
+/// A band of an [`AudioEffect::Equalizer`].
+#[derive(Clone, Copy, Debug, PartialEq, Reflect)]
+pub struct EqBand {
+    /// The center frequency of the band.
+    pub frequency: f32,
+    /// The gain applied to the band. Negative values cut the band.
+    pub gain: f32,
+    /// The narrowness of the band. Higher values affect fewer frequencies.
+    pub q: f32,
+}
+
+/// A chain of [`AudioEffect`]s applied, in order, to the sound of this entity.
+///
+/// This must be present when the sound starts playing. Changes to the effects are then applied
+/// to the playing sound, e.g. to muffle it while the listener is underwater.
+///
+/// To apply effects to several sounds at once, see
+/// [`AudioBuses::set_effects`](crate::AudioBuses::set_effects).
+#[derive(Component, Reflect, Debug, Default)]
+#[reflect(Component, Default)]
+pub struct AudioEffects {
+    /// The effects, in processing order.
+    pub chain: Vec<AudioEffect>,
+    #[reflect(ignore)]
+    pub(crate) shared: SharedEffects,
+}
+
+impl AudioEffects {
+    /// Creates a chain of effects.
+    pub fn new(chain: impl IntoIterator<Item = AudioEffect>) -> Self {
+        Self {
+            chain: chain.into_iter().collect(),
+            shared: SharedEffects::default(),
+        }
+    }
+}
+
+impl Clone for AudioEffects {
+    fn clone(&self) -> Self {
+        // The clone controls its own sound.
+        Self::new(self.chain.clone())
+    }
+}
+
+/// A chain of effects shared with the sounds playing on the audio thread.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct SharedEffects {
+    chain: Arc<Mutex<Vec<AudioEffect>>>,
+    version: Arc<AtomicU32>,
+}
+
+impl SharedEffects {
+    /// Updates the effects of the playing sounds, if they changed.
+    pub(crate) fn set(&self, chain: &[AudioEffect]) {
+        let mut shared = self.chain.lock().unwrap();
+        if *shared != chain {
+            *shared = chain.to_vec();
+            self.version.fetch_add(1, Ordering::Release);
+        }
+    }
+
+    /// Returns the current effects.
+    #[cfg(test)]
+    pub(crate) fn chain(&self) -> Vec<AudioEffect> {
+        self.chain.lock().unwrap().clone()
+    }
+
+    /// Wraps `source` so its samples are processed by the effects.
+    pub(crate) fn apply<S: Source<Item = f32>>(&self, source: S) -> EffectSource<S> {
+        EffectSource {
+            input: source,
+            shared: self.clone(),
+            version: 0,
+            processors: Vec::new(),
+            sample_rate: 0,
+            channels: 0,
+            channel: 0,
+        }
+    }
+}
+
+/// A [`Source`] whose samples are processed by a chain of effects.
+pub(crate) struct EffectSource<I> {
+    input: I,
+    shared: SharedEffects,
+    version: u32,
+    processors: Vec<Processor>,
+    sample_rate: u32,
+    channels: u16,
+    channel: usize,
+}
+
+impl<I: Source<Item = f32>> EffectSource<I> {
+    fn sync(&mut self) {
+        let sample_rate = self.input.sample_rate();
+        let channels = self.input.channels().max(1);
+        let version = self.shared.version.load(Ordering::Acquire);
+        if sample_rate != self.sample_rate || channels != self.channels {
+            self.sample_rate = sample_rate;
+            self.channels = channels;
+            self.channel = 0;
+            self.processors.clear();
+            // Rebuilds the effects for the new format.
+            self.version = version.wrapping_sub(1);
+        }
+        if version == self.version {
+            return;
+        }
+        // Never block the audio thread: try again on the next sample.
+        let Ok(chain) = self.shared.chain.try_lock() else {
+            return;
+        };
+        let sample_rate = sample_rate as f32;
+        let channels = channels as usize;
+        let reusable = self.processors.len() == chain.len()
+            && self
+                .processors
+                .iter_mut()
+                .zip(chain.iter())
+                .all(|(processor, effect)| processor.update(effect, sample_rate, channels));
+        if !reusable {
+            self.processors = chain
+                .iter()
+                .map(|effect| Processor::new(effect, sample_rate, channels))
+                .collect();
+        }
+        self.version = version;
+    }
+}
+
+impl<I: Source<Item = f32>> Iterator for EffectSource<I> {
+    type Item = f32;
+
+    #[inline]
+    fn next(&mut self) -> Option<f32> {
+        let mut sample = self.input.next()?;
+        self.sync();
+        for processor in &mut self.processors {
+            sample = processor.process(self.channel, sample);
+        }
+        self.channel = (self.channel + 1) % self.channels as usize;
+        Some(sample)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I: Source<Item = f32>> Source for EffectSource<I> {
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+}
+
+/// Applies the changes of [`AudioEffects`] to the playing sounds.
+pub(crate) fn update_audio_effects(effects: Query<&AudioEffects, Changed<AudioEffects>>) {
+    for effects in &effects {
+        effects.shared.set(&effects.chain);
+    }
+}
+
+/// The processing state of an [`AudioEffect`].
+enum Processor {
+    Filter(Biquad),
+    Equalizer(Vec<Biquad>),
+    Reverb(Vec<Reverb>),
+    Compressor(Compressor),
+}
+
+impl Processor {
+    fn new(effect: &AudioEffect, sample_rate: f32, channels: usize) -> Self {
+        let mut processor = match effect {
+            AudioEffect::LowPass { .. } | AudioEffect::HighPass { .. } => {
+                Self::Filter(Biquad::new(channels))
+            }
+            AudioEffect::Equalizer { bands } => {
+                Self::Equalizer(bands.iter().map(|_| Biquad::new(channels)).collect())
+            }
+            AudioEffect::Reverb { .. } => Self::Reverb(
+                (0..channels)
+                    .map(|channel| Reverb::new(sample_rate, channel))
+                    .collect(),
+            ),
+            AudioEffect::Compressor { .. } => Self::Compressor(Compressor::default()),
+        };
+        processor.update(effect, sample_rate, channels);
+        processor
+    }
+
+    /// Updates the parameters of the processor, keeping its state. Returns `false` if the
+    /// processor can't process `effect`.
+    fn update(&mut self, effect: &AudioEffect, sample_rate: f32, channels: usize) -> bool {
+        match (self, effect) {
+            (Self::Filter(filter), AudioEffect::LowPass { cutoff, q }) => {
+                filter.set_low_pass(*cutoff, *q, sample_rate);
+            }
+            (Self::Filter(filter), AudioEffect::HighPass { cutoff, q }) => {
+                filter.set_high_pass(*cutoff, *q, sample_rate);
+            }
+            (Self::Equalizer(filters), AudioEffect::Equalizer { bands })
+                if filters.len() == bands.len() =>
+            {
+                for (filter, band) in filters.iter_mut().zip(bands) {
+                    filter.set_peaking(band, sample_rate);
+                }
+            }
+            (
+                Self::Reverb(reverbs),
+                AudioEffect::Reverb {
+                    room_size,
+                    damping,
+                    wet,
+                },
+            ) => {
+                for reverb in reverbs {
+                    reverb.feedback = 0.7 + 0.28 * room_size.clamp(0.0, 1.0);
+                    reverb.damping = 0.4 * damping.clamp(0.0, 1.0);
+                    reverb.wet = wet.clamp(0.0, 1.0);
+                }
+            }
+            (
+                Self::Compressor(compressor),
+                AudioEffect::Compressor {
+                    threshold,
+                    ratio,
+                    attack,
+                    release,
+                    makeup_gain,
+                },
+            ) => {
+                // The envelope is updated for every interleaved sample.
+                let samples_per_second = sample_rate * channels as f32;
+                compressor.threshold = *threshold;
+                compressor.slope = 1.0 - 1.0 / ratio.max(1.0);
+                compressor.makeup_gain = *makeup_gain;
+                compressor.attack = Compressor::coefficient(*attack, samples_per_second);
+                compressor.release = Compressor::coefficient(*release, samples_per_second);
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    #[inline]
+    fn process(&mut self, channel: usize, sample: f32) -> f32 {
+        match self {
+            Self::Filter(filter) => filter.process(channel, sample),
+            Self::Equalizer(filters) => filters
+                .iter_mut()
+                .fold(sample, |sample, filter| filter.process(channel, sample)),
+            Self::Reverb(reverbs) => reverbs[channel].process(sample),
+            Self::Compressor(compressor) => compressor.process(sample),
+        }
+    }
+}
+
+/// A second order filter, from the "Audio EQ Cookbook" by Robert Bristow-Johnson.
+struct Biquad {
+    /// `b0`, `b1`, `b2`, `a1` and `a2`, normalized by `a0`.
+    coefficients: [f32; 5],
+    /// The state of each channel, in transposed direct form II.
+    states: Vec<[f32; 2]>,
+}
+
+impl Biquad {
+    fn new(channels: usize) -> Self {
+        Self {
+            coefficients: [1.0, 0.0, 0.0, 0.0, 0.0],
+            states: vec![[0.0; 2]; channels],
+        }
+    }
+
+    /// Returns the cosine and the `alpha` term of the cookbook formulas.
+    fn terms(frequency: f32, q: f32, sample_rate: f32) -> (f32, f32) {
+        let frequency = frequency.clamp(10.0, sample_rate * 0.49);
+        let w0 = 2.0 * PI * frequency / sample_rate;
+        (w0.cos(), w0.sin() / (2.0 * q.max(0.01)))
+    }
+
+    fn set(&mut self, b: [f32; 3], a: [f32; 3]) {
+        self.coefficients = [
+            b[0] / a[0],
+            b[1] / a[0],
+            b[2] / a[0],
+            a[1] / a[0],
+            a[2] / a[0],
+        ];
+    }
+
+    fn set_low_pass(&mut self, cutoff: f32, q: f32, sample_rate: f32) {
+        let (cos, alpha) = Self::terms(cutoff, q, sample_rate);
+        self.set(
+            [(1.0 - cos) / 2.0, 1.0 - cos, (1.0 - cos) / 2.0],
+            [1.0 + alpha, -2.0 * cos, 1.0 - alpha],
+        );
+    }
+
+    fn set_high_pass(&mut self, cutoff: f32, q: f32, sample_rate: f32) {
+        let (cos, alpha) = Self::terms(cutoff, q, sample_rate);
+        self.set(
+            [(1.0 + cos) / 2.0, -(1.0 + cos), (1.0 + cos) / 2.0],
+            [1.0 + alpha, -2.0 * cos, 1.0 - alpha],
+        );
+    }
+
+    fn set_peaking(&mut self, band: &EqBand, sample_rate: f32) {
+        let (cos, alpha) = Self::terms(band.frequency, band.q, sample_rate);
+        let a = 10f32.powf(band.gain / 40.0);
+        self.set(
+            [1.0 + alpha * a, -2.0 * cos, 1.0 - alpha * a],
+            [1.0 + alpha / a, -2.0 * cos, 1.0 - alpha / a],
+        );
+    }
+
+    #[inline]
+    fn process(&mut self, channel: usize, sample: f32) -> f32 {
+        let [b0, b1, b2, a1, a2] = self.coefficients;
+        let state = &mut self.states[channel];
+        let output = b0 * sample + state[0];
+        state[0] = b1 * sample - a1 * output + state[1];
+        state[1] = b2 * sample - a2 * output;
+        output
+    }
+}
+
+/// The delays of the comb filters of [`Reverb`], in samples at 44.1 kHz.
+const COMB_DELAYS: [usize; 4] = [1116, 1188, 1277, 1356];
+/// The delays of the all-pass filters of [`Reverb`], in samples at 44.1 kHz.
+const ALLPASS_DELAYS: [usize; 2] = [556, 441];
+/// The delay added to the filters of odd channels, to decorrelate stereo channels.
+const STEREO_SPREAD: usize = 23;
+
+/// A reverb for a single channel, in the style of Freeverb.
+struct Reverb {
+    combs: Vec<(Vec<f32>, usize, f32)>,
+    allpasses: Vec<(Vec<f32>, usize)>,
+    feedback: f32,
+    damping: f32,
+    wet: f32,
+}
+
+impl Reverb {
+    fn new(sample_rate: f32, channel: usize) -> Self {
+        let spread = if channel % 2 == 1 { STEREO_SPREAD } else { 0 };
+        let scaled =
+            |delay: usize| (((delay + spread) as f32 * sample_rate / 44100.0) as usize).max(1);
+        Self {
+            combs: COMB_DELAYS
+                .iter()
+                .map(|delay| (vec![0.0; scaled(*delay)], 0, 0.0))
+                .collect(),
+            allpasses: ALLPASS_DELAYS
+                .iter()
+                .map(|delay| (vec![0.0; scaled(*delay)], 0))
+                .collect(),
+            feedback: 0.0,
+            damping: 0.0,
+            wet: 0.0,
+        }
+    }
+
+    #[inline]
+    fn process(&mut self, sample: f32) -> f32 {
+        let input = sample * 0.05;
+        let mut output = 0.0;
+        for (buffer, index, filtered) in &mut self.combs {
+            let delayed = buffer[*index];
+            *filtered = delayed * (1.0 - self.damping) + *filtered * self.damping;
+            buffer[*index] = input + *filtered * self.feedback;
+            *index = (*index + 1) % buffer.len();
+            output += delayed;
+        }
+        for (buffer, index) in &mut self.allpasses {
+            let delayed = buffer[*index];
+            buffer[*index] = output + delayed * 0.5;
+            *index = (*index + 1) % buffer.len();
+            output = delayed - output;
+        }
+        sample * (1.0 - self.wet) + output * self.wet
+    }
+}
+
+/// A feed-forward compressor, whose envelope is shared by all the channels.
+#[derive(Default)]
+struct Compressor {
+    threshold: f32,
+    slope: f32,
+    makeup_gain: f32,
+    /// The smoothing coefficients of the envelope when the level rises and falls.
+    attack: f32,
+    release: f32,
+    envelope: f32,
+}
+
+impl Compressor {
+    fn coefficient(time: f32, samples_per_second: f32) -> f32 {
+        if time <= 0.0 {
+            0.0
+        } else {
+            (-1.0 / (time * samples_per_second)).exp()
+        }
+    }
+
+    #[inline]
+    fn process(&mut self, sample: f32) -> f32 {
+        let level = sample.abs();
+        let coefficient = if level > self.envelope {
+            self.attack
+        } else {
+            self.release
+        };
+        self.envelope = level + coefficient * (self.envelope - level);
+
+        let level = 20.0 * self.envelope.max(1e-6).log10();
+        let reduction = (level - self.threshold).max(0.0) * self.slope;
+        sample * 10f32.powf((self.makeup_gain - reduction) / 20.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rodio::buffer::SamplesBuffer;
+
+    fn sine(frequency: f32) -> SamplesBuffer<f32> {
+        let samples = (0..4800)
+            .map(|i| (2.0 * PI * frequency * i as f32 / 48000.0).sin())
+            .collect::<Vec<_>>();
+        SamplesBuffer::new(1, 48000, samples)
+    }
+
+    fn peak(source: impl Iterator<Item = f32>) -> f32 {
+        source.fold(0.0, |peak, sample| sample.abs().max(peak))
+    }
+
+    #[test]
+    fn filters_attenuate_frequencies_outside_their_band() {
+        let shared = SharedEffects::default();
+        shared.set(&[AudioEffect::low_pass(500.0)]);
+        // Skip the transient response of the filters.
+        assert!(peak(shared.apply(sine(100.0)).skip(2400)) > 0.9);
+        assert!(peak(shared.apply(sine(8000.0)).skip(2400)) < 0.05);
+
+        shared.set(&[AudioEffect::high_pass(500.0)]);
+        assert!(peak(shared.apply(sine(100.0)).skip(2400)) < 0.1);
+        assert!(peak(shared.apply(sine(8000.0)).skip(2400)) > 0.8);
+    }
+
+    #[test]
+    fn effects_update_while_playing() {
+        let shared = SharedEffects::default();
+        let mut source = shared.apply(sine(8000.0));
+        assert!(peak(source.by_ref().take(2400)) > 0.8);
+
+        shared.set(&[AudioEffect::Compressor {
+            threshold: -12.0,
+            ratio: 100.0,
+            attack: 0.0,
+            release: 0.1,
+            makeup_gain: 0.0,
+        }]);
+        let compressed = peak(source);
+        assert!(compressed < 0.3, "{compressed}");
+    }
+}
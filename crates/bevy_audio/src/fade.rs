@@ -0,0 +1,202 @@
+use std::sync::{
+    atomic::{AtomicU32, Ordering},
+    Arc, Mutex,
+};
+use std::time::Duration;
+
+use bevy_ecs::prelude::*;
+use bevy_reflect::prelude::*;
+use rodio::Source;
+
+/// Crossfades from the sound of another entity when the sound of this entity starts playing.
+///
+/// The sound of [`from`](Self::from) fades out and stops while this sound fades in, which is
+/// useful to transition between music tracks.
+///
+/// ```
+/// # use bevy_ecs::prelude::*;
+/// # use bevy_asset::AssetServer;
+/// # use bevy_audio::{AudioBundle, Crossfade, PlaybackSettings};
+/// # use std::time::Duration;
+/// fn change_track(mut commands: Commands, asset_server: Res<AssetServer>, current: Entity) {
+///     commands.spawn((
+///         AudioBundle {
+///             source: asset_server.load("next_track.ogg"),
+///             settings: PlaybackSettings::LOOP,
+///         },
+///         Crossfade::new(current, Duration::from_secs(2)),
+///     ));
+/// }
+/// ```
+#[derive(Component, Clone, Copy, Debug, Reflect)]
+#[reflect(Component)]
+pub struct Crossfade {
+    /// The entity whose sound fades out.
+    pub from: Entity,
+    /// The duration of the crossfade.
+    pub duration: Duration,
+}
+
+impl Crossfade {
+    /// Creates a crossfade from the sound of `from`, lasting `duration`.
+    pub fn new(from: Entity, duration: Duration) -> Self {
+        Self { from, duration }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct FadeRequest {
+    volume: f32,
+    duration: Duration,
+    /// Whether to end the sound once it's faded.
+    stop: bool,
+}
+
+/// A fade requested from the ECS, shared with the audio thread.
+#[derive(Debug, Default)]
+pub(crate) struct Fade {
+    request: Mutex<FadeRequest>,
+    version: AtomicU32,
+}
+
+impl Fade {
+    /// Smoothly changes the fade volume to `volume` over `duration`, and ends the sound
+    /// afterwards if `stop` is `true`.
+    pub(crate) fn fade_to(&self, volume: f32, duration: Duration, stop: bool) {
+        *self.request.lock().unwrap() = FadeRequest {
+            volume: volume.max(0.0),
+            duration,
+            stop,
+        };
+        self.version.fetch_add(1, Ordering::Release);
+    }
+
+    /// Wraps `source` so its samples are scaled by the fade volume.
+    ///
+    /// If `fade_in` isn't zero, the sound starts silent and fades in over `fade_in`.
+    pub(crate) fn apply<S: Source<Item = f32>>(
+        self: &Arc<Self>,
+        source: S,
+        fade_in: Duration,
+    ) -> FadeSource<S> {
+        let mut volume = 1.0;
+        if !fade_in.is_zero() {
+            volume = 0.0;
+            self.fade_to(1.0, fade_in, false);
+        }
+        FadeSource {
+            input: source,
+            fade: self.clone(),
+            version: 0,
+            volume,
+            target: volume,
+            step: 0.0,
+            stop: false,
+        }
+    }
+}
+
+/// A [`Source`] whose samples are scaled by a fade volume, interpolated on every sample.
+pub(crate) struct FadeSource<I> {
+    input: I,
+    fade: Arc<Fade>,
+    version: u32,
+    volume: f32,
+    target: f32,
+    /// The change of `volume` on each sample.
+    step: f32,
+    stop: bool,
+}
+
+impl<I: Source<Item = f32>> FadeSource<I> {
+    fn sync(&mut self) {
+        let version = self.fade.version.load(Ordering::Acquire);
+        if version == self.version {
+            return;
+        }
+        // Never block the audio thread: try again on the next sample.
+        let Ok(request) = self.fade.request.try_lock() else {
+            return;
+        };
+        let samples = request.duration.as_secs_f32()
+            * self.input.sample_rate() as f32
+            * self.input.channels() as f32;
+        self.target = request.volume;
+        self.step = (self.target - self.volume).abs() / samples.max(1.0);
+        self.stop = request.stop;
+        self.version = version;
+    }
+}
+
+impl<I: Source<Item = f32>> Iterator for FadeSource<I> {
+    type Item = f32;
+
+    #[inline]
+    fn next(&mut self) -> Option<f32> {
+        self.sync();
+        if self.stop && self.volume == self.target {
+            return None;
+        }
+        let sample = self.input.next()?;
+        if self.volume < self.target {
+            self.volume = (self.volume + self.step).min(self.target);
+        } else if self.volume > self.target {
+            self.volume = (self.volume - self.step).max(self.target);
+        }
+        Some(sample * self.volume)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I: Source<Item = f32>> Source for FadeSource<I> {
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rodio::buffer::SamplesBuffer;
+
+    fn ones(len: usize) -> SamplesBuffer<f32> {
+        // One sample per second.
+        SamplesBuffer::new(1, 1, vec![1.0f32; len])
+    }
+
+    #[test]
+    fn sounds_fade_in_on_every_sample() {
+        let fade = Arc::new(Fade::default());
+        let source = fade.apply(ones(6), Duration::from_secs(4));
+        assert_eq!(source.collect::<Vec<_>>(), [0.25, 0.5, 0.75, 1.0, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn fading_out_ends_sounds() {
+        let fade = Arc::new(Fade::default());
+        let mut source = fade.apply(ones(10), Duration::ZERO);
+        assert_eq!(source.next(), Some(1.0));
+        fade.fade_to(0.0, Duration::from_secs(2), true);
+        assert_eq!(source.collect::<Vec<_>>(), [0.5, 0.0]);
+    }
+}
@@ -0,0 +1,259 @@
+use bevy_ecs::prelude::*;
+use bevy_utils::tracing::warn;
+use crossbeam_channel::{Receiver, Sender};
+use rodio::cpal::{
+    self,
+    traits::{DeviceTrait, HostTrait, StreamTrait},
+    FromSample, SizedSample,
+};
+use thiserror::Error;
+
+/// Captures audio from an input device, e.g. a microphone.
+///
+/// The captured samples are sent as [`AudioInputBuffer`] events every frame.
+///
+/// ```
+/// # use bevy_ecs::prelude::*;
+/// # use bevy_audio::{AudioInput, AudioInputBuffer};
+/// fn start_voice_chat(mut input: ResMut<AudioInput>) {
+///     if let Err(err) = input.start(None) {
+///         eprintln!("Can't use the microphone: {err}");
+///     }
+/// }
+///
+/// fn send_voice(mut buffers: EventReader<AudioInputBuffer>) {
+///     for buffer in buffers.read() {
+///         // Encode and send `buffer.samples`...
+///     }
+/// }
+/// ```
+///
+/// The operating system may ask the user for permission to use the microphone when capture
+/// starts. Capture is unsupported on the web.
+#[derive(Resource, Default)]
+pub struct AudioInput {
+    capture: Option<Capture>,
+}
+
+/// A running capture. Dropping it stops the capture.
+struct Capture {
+    device: String,
+    channels: u16,
+    sample_rate: u32,
+    buffers: Receiver<Vec<f32>>,
+    /// Disconnected to stop the thread owning the stream.
+    _stop: Sender<()>,
+}
+
+/// An error starting an audio capture.
+#[derive(Error, Debug)]
+pub enum AudioInputError {
+    /// The input device doesn't exist.
+    #[error("no audio input device found")]
+    NoDevice,
+    /// The configuration of the input device couldn't be read.
+    #[error("failed to read the audio input configuration: {0}")]
+    Config(#[from] cpal::DefaultStreamConfigError),
+    /// The device doesn't support the sample format of its configuration.
+    #[error("unsupported audio input sample format {0}")]
+    SampleFormat(cpal::SampleFormat),
+    /// The capture couldn't be created.
+    #[error("failed to create the audio input stream: {0}")]
+    Build(#[from] cpal::BuildStreamError),
+    /// The capture couldn't be started.
+    #[error("failed to start the audio input stream: {0}")]
+    Play(#[from] cpal::PlayStreamError),
+    /// The thread running the capture couldn't be started.
+    #[error("failed to start the audio input thread: {0}")]
+    Thread(#[from] std::io::Error),
+}
+
+/// Samples captured by [`AudioInput`].
+#[derive(Event, Clone, Debug)]
+pub struct AudioInputBuffer {
+    /// The interleaved samples.
+    pub samples: Vec<f32>,
+    /// The number of channels of the samples.
+    pub channels: u16,
+    /// The sample rate of the samples.
+    pub sample_rate: u32,
+}
+
+impl AudioInput {
+    /// The names of the available input devices.
+    pub fn devices() -> Vec<String> {
+        let host = cpal::default_host();
+        let Ok(devices) = host.input_devices() else {
+            return Vec::new();
+        };
+        devices.filter_map(|device| device.name().ok()).collect()
+    }
+
+    /// The name of the default input device, if there is one.
+    pub fn default_device() -> Option<String> {
+        cpal::default_host().default_input_device()?.name().ok()
+    }
+
+    /// Starts capturing from the device named `device`, or from the default input device if
+    /// `device` is `None`, stopping the current capture.
+    pub fn start(&mut self, device: Option<&str>) -> Result<(), AudioInputError> {
+        self.stop();
+
+        let (result_sender, result) = crossbeam_channel::unbounded();
+        let (buffer_sender, buffers) = crossbeam_channel::unbounded();
+        let (stop, stopped) = crossbeam_channel::bounded::<()>(0);
+        let device = device.map(str::to_string);
+        // Streams can't be sent between threads on every platform, so a thread owns it until
+        // `stop` is dropped.
+        std::thread::Builder::new()
+            .name("audio input".to_string())
+            .spawn(
+                move || match build_stream(device.as_deref(), buffer_sender) {
+                    Ok((stream, format)) => {
+                        let _ = result_sender.send(Ok(format));
+                        let _ = stopped.recv();
+                        drop(stream);
+                    }
+                    Err(err) => {
+                        let _ = result_sender.send(Err(err));
+                    }
+                },
+            )?;
+        let (device, channels, sample_rate) =
+            result.recv().unwrap_or(Err(AudioInputError::NoDevice))?;
+        self.capture = Some(Capture {
+            device,
+            channels,
+            sample_rate,
+            buffers,
+            _stop: stop,
+        });
+        Ok(())
+    }
+
+    /// Stops capturing.
+    pub fn stop(&mut self) {
+        self.capture = None;
+    }
+
+    /// Returns `true` if audio is being captured.
+    pub fn is_capturing(&self) -> bool {
+        self.capture.is_some()
+    }
+
+    /// The name of the device being captured.
+    pub fn device(&self) -> Option<&str> {
+        self.capture.as_ref().map(|capture| capture.device.as_str())
+    }
+
+    /// The number of channels captured.
+    pub fn channels(&self) -> Option<u16> {
+        self.capture.as_ref().map(|capture| capture.channels)
+    }
+
+    /// The sample rate of the captured audio.
+    pub fn sample_rate(&self) -> Option<u32> {
+        self.capture.as_ref().map(|capture| capture.sample_rate)
+    }
+}
+
+/// Creates and plays a stream capturing `device`, and returns it with the name, the number of
+/// channels and the sample rate of the device.
+fn build_stream(
+    device: Option<&str>,
+    buffers: Sender<Vec<f32>>,
+) -> Result<(cpal::Stream, (String, u16, u32)), AudioInputError> {
+    let host = cpal::default_host();
+    let device = match device {
+        Some(name) => host.input_devices().ok().and_then(|mut devices| {
+            devices.find(|device| device.name().is_ok_and(|device| device == name))
+        }),
+        None => host.default_input_device(),
+    }
+    .ok_or(AudioInputError::NoDevice)?;
+    let config = device.default_input_config()?;
+
+    let stream = match config.sample_format() {
+        cpal::SampleFormat::I8 => build_typed_stream::<i8>(&device, &config.config(), buffers),
+        cpal::SampleFormat::I16 => build_typed_stream::<i16>(&device, &config.config(), buffers),
+        cpal::SampleFormat::I32 => build_typed_stream::<i32>(&device, &config.config(), buffers),
+        cpal::SampleFormat::U8 => build_typed_stream::<u8>(&device, &config.config(), buffers),
+        cpal::SampleFormat::U16 => build_typed_stream::<u16>(&device, &config.config(), buffers),
+        cpal::SampleFormat::U32 => build_typed_stream::<u32>(&device, &config.config(), buffers),
+        cpal::SampleFormat::F32 => build_typed_stream::<f32>(&device, &config.config(), buffers),
+        cpal::SampleFormat::F64 => build_typed_stream::<f64>(&device, &config.config(), buffers),
+        format => return Err(AudioInputError::SampleFormat(format)),
+    }?;
+    stream.play()?;
+
+    let name = device.name().unwrap_or_default();
+    Ok((stream, (name, config.channels(), config.sample_rate().0)))
+}
+
+fn build_typed_stream<T>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    buffers: Sender<Vec<f32>>,
+) -> Result<cpal::Stream, cpal::BuildStreamError>
+where
+    T: SizedSample,
+    f32: FromSample<T>,
+{
+    device.build_input_stream(
+        config,
+        move |samples: &[T], _| {
+            let samples = samples.iter().map(|sample| sample.to_sample::<f32>());
+            let _ = buffers.send(samples.collect());
+        },
+        |err| warn!("Audio input error: {err}"),
+        None,
+    )
+}
+
+/// Sends the samples captured since the last frame as [`AudioInputBuffer`] events.
+pub(crate) fn receive_audio_input(
+    input: Res<AudioInput>,
+    mut events: EventWriter<AudioInputBuffer>,
+) {
+    let Some(capture) = &input.capture else {
+        return;
+    };
+    events.send_batch(capture.buffers.try_iter().map(|samples| AudioInputBuffer {
+        samples,
+        channels: capture.channels,
+        sample_rate: capture.sample_rate,
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_ecs::{event::Events, system::RunSystemOnce};
+
+    #[test]
+    fn captured_samples_are_sent_as_events() {
+        let mut world = World::new();
+        world.init_resource::<Events<AudioInputBuffer>>();
+        let (sender, buffers) = crossbeam_channel::unbounded();
+        let (stop, _stopped) = crossbeam_channel::unbounded();
+        world.insert_resource(AudioInput {
+            capture: Some(Capture {
+                device: "test".to_string(),
+                channels: 1,
+                sample_rate: 8000,
+                buffers,
+                _stop: stop,
+            }),
+        });
+        sender.send(vec![0.5, 0.25]).unwrap();
+        sender.send(vec![1.0]).unwrap();
+
+        world.run_system_once(receive_audio_input);
+        let events = world.resource::<Events<AudioInputBuffer>>();
+        let samples: Vec<_> = events
+            .iter_current_update_events()
+            .map(|buffer| buffer.samples.clone())
+            .collect();
+        assert_eq!(samples, [vec![0.5, 0.25], vec![1.0]]);
+    }
+}
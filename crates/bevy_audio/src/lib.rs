@@ -25,26 +25,51 @@
 mod audio;
 mod audio_output;
 mod audio_source;
+mod bus;
+mod devices;
+mod effects;
+mod fade;
+mod input;
 mod pitch;
+mod playhead;
+mod procedural;
+mod recording;
 mod sinks;
+mod spatial;
+mod streaming;
 
 #[allow(missing_docs)]
 pub mod prelude {
     #[doc(hidden)]
     pub use crate::{
-        AudioBundle, AudioSink, AudioSinkPlayback, AudioSource, AudioSourceBundle, Decodable,
-        GlobalVolume, Pitch, PitchBundle, PlaybackSettings, SpatialAudioSink, SpatialListener,
+        AudioBundle, AudioBus, AudioBuses, AudioEffect, AudioEffects, AudioSink, AudioSinkPlayback,
+        AudioSource, AudioSourceBundle, Crossfade, Decodable, GlobalVolume, Pitch, PitchBundle,
+        PlaybackSettings, ProceduralAudio, ProceduralAudioBundle, SpatialAudioEmitter,
+        SpatialAudioSink, SpatialListener,
     };
 }
 
 pub use audio::*;
 pub use audio_source::*;
+pub use bus::{AudioBus, AudioBuses};
+pub use devices::{AudioOutputDevices, AudioOutputError};
+pub use effects::{AudioEffect, AudioEffects, EqBand};
+pub use fade::Crossfade;
+pub use input::{AudioInput, AudioInputBuffer, AudioInputError};
 pub use pitch::*;
+pub use procedural::{
+    AudioStreamSender, ProceduralAudio, ProceduralAudioBundle, ProceduralDecoder,
+};
+pub use recording::{AudioRecorder, RecordedAudio, RecordingOutput};
 
 pub use rodio::cpal::Sample as CpalSample;
 pub use rodio::source::Source;
 pub use rodio::Sample;
 pub use sinks::*;
+pub use spatial::{
+    AudioCone, AudioOcclusion, Rolloff, SpatialAttenuation, SpatialAudioEmitter, SpeedOfSound,
+};
+pub use streaming::{StreamingAudio, StreamingAudioBundle, StreamingDecoder, StreamingSettings};
 
 use bevy_app::prelude::*;
 use bevy_asset::{Asset, AssetApp};
@@ -52,10 +77,18 @@ use bevy_ecs::prelude::*;
 use bevy_transform::TransformSystem;
 
 use audio_output::*;
+use bus::update_audio_buses;
+use effects::update_audio_effects;
+use input::receive_audio_input;
+use playhead::seek_audio;
+use recording::record_audio;
 
-/// Set for the audio playback systems, so they can share a run condition
+/// Set for the audio playback systems, so they can share a run condition.
+///
+/// Systems changing the sounds that are playing, e.g. their [`AudioOcclusion`], should run
+/// before this set.
 #[derive(SystemSet, Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
-struct AudioPlaySet;
+pub struct AudioPlaySet;
 
 /// Adds support for audio playback to a Bevy Application
 ///
@@ -67,6 +100,12 @@ pub struct AudioPlugin {
     /// The scale factor applied to the positions of audio sources and listeners for
     /// spatial audio.
     pub default_spatial_scale: SpatialScale,
+    /// The number of channels of the mixed audio output, or `None` for the number of channels of
+    /// the default output device.
+    ///
+    /// The channels the default device doesn't have can be played on other devices through
+    /// [`AudioOutputDevices`].
+    pub output_channels: Option<u16>,
 }
 
 impl Plugin for AudioPlugin {
@@ -74,10 +113,24 @@ impl Plugin for AudioPlugin {
         app.register_type::<Volume>()
             .register_type::<GlobalVolume>()
             .register_type::<SpatialListener>()
+            .register_type::<SpatialMode>()
             .register_type::<DefaultSpatialScale>()
             .register_type::<PlaybackMode>()
             .register_type::<PlaybackSettings>()
+            .register_type::<AudioBus>()
+            .register_type::<AudioEffect>()
+            .register_type::<AudioEffects>()
+            .register_type::<EqBand>()
+            .register_type::<SpatialAudioEmitter>()
+            .register_type::<AudioOcclusion>()
+            .register_type::<Crossfade>()
+            .register_type::<SpeedOfSound>()
             .insert_resource(self.global_volume)
+            .init_resource::<AudioBuses>()
+            .init_resource::<SpeedOfSound>()
+            .init_resource::<AudioInput>()
+            .add_event::<AudioInputBuffer>()
+            .add_event::<RecordedAudio>()
             .insert_resource(DefaultSpatialScale(self.default_spatial_scale))
             .configure_sets(
                 PostUpdate,
@@ -87,9 +140,13 @@ impl Plugin for AudioPlugin {
             )
             .add_systems(
                 PostUpdate,
-                (update_emitter_positions, update_listener_positions).in_set(AudioPlaySet),
+                (update_audio_buses, update_audio_effects).before(AudioPlaySet),
             )
-            .init_resource::<AudioOutput>();
+            .add_systems(PostUpdate, update_spatial_audio.in_set(AudioPlaySet))
+            .add_systems(PreUpdate, receive_audio_input)
+            .add_systems(PostUpdate, record_audio.after(AudioPlaySet))
+            .insert_resource(AudioOutput::new(self.output_channels))
+            .init_resource::<AudioOutputDevices>();
 
         #[cfg(any(feature = "mp3", feature = "flac", feature = "wav", feature = "vorbis"))]
         {
@@ -98,6 +155,8 @@ impl Plugin for AudioPlugin {
         }
 
         app.add_audio_source::<Pitch>();
+        app.add_audio_source::<ProceduralAudio>();
+        app.add_audio_source::<StreamingAudio>();
     }
 }
 
@@ -109,7 +168,12 @@ impl AddAudioSource for App {
     {
         self.init_asset::<T>().add_systems(
             PostUpdate,
-            (play_queued_audio_system::<T>, cleanup_finished_audio::<T>).in_set(AudioPlaySet),
+            (
+                play_queued_audio_system::<T>,
+                seek_audio::<T>,
+                cleanup_finished_audio::<T>,
+            )
+                .in_set(AudioPlaySet),
         );
         self
     }
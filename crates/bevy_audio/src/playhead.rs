@@ -0,0 +1,614 @@
+use std::sync::{
+    atomic::{AtomicBool, AtomicU64, Ordering},
+    Arc, Mutex,
+};
+use std::time::Duration;
+
+use bevy_asset::{Asset, Assets, Handle};
+use bevy_ecs::prelude::*;
+use rodio::{source::Buffered, Source};
+
+use crate::{AudioSink, Decodable, SpatialAudioSink};
+
+/// A sound whose samples are converted to `f32`.
+pub(crate) type BoxedSource = Box<dyn Source<Item = f32> + Send>;
+
+/// A decoded sound, and the flag set while it plays silence waiting for its samples.
+pub(crate) struct DecodedSound {
+    source: BoxedSource,
+    stalled: Option<Arc<AtomicBool>>,
+}
+
+impl DecodedSound {
+    fn new(source: BoxedSource) -> Self {
+        Self {
+            source,
+            stalled: None,
+        }
+    }
+
+    pub(crate) fn from_decoder<T: Decodable>(decoder: T::Decoder) -> Self
+    where
+        f32: rodio::cpal::FromSample<T::DecoderItem>,
+    {
+        Self {
+            stalled: T::stalled(&decoder),
+            source: Box::new(decoder.convert_samples()),
+        }
+    }
+}
+
+/// Decodes `audio_source` from `position`.
+pub(crate) fn decode<T: Decodable>(audio_source: &T, position: Duration) -> DecodedSound
+where
+    f32: rodio::cpal::FromSample<T::DecoderItem>,
+{
+    match audio_source.decoder_at(position) {
+        Some(decoder) => DecodedSound::from_decoder::<T>(decoder),
+        None => {
+            let decoder = audio_source.decoder();
+            DecodedSound {
+                stalled: T::stalled(&decoder),
+                source: decode_from(decoder, position),
+            }
+        }
+    }
+}
+
+/// The playback position of a sound, shared with the audio thread, and its pending seek.
+#[derive(Default)]
+pub(crate) struct Playhead {
+    /// The position in nanoseconds.
+    position: AtomicU64,
+    /// Whether a seek is pending. The position isn't updated until it's applied.
+    seeking: AtomicBool,
+    seek: Mutex<Seek>,
+    /// The loop start of a looping sound decoded again at each loop.
+    loop_start: Option<Duration>,
+    /// The sound to play from `loop_start` at the end of the loop.
+    next_loop: Mutex<Option<DecodedSound>>,
+}
+
+#[derive(Default)]
+struct Seek {
+    position: Duration,
+    /// The sound to play from `position`, once it's decoded.
+    source: Option<DecodedSound>,
+}
+
+impl Playhead {
+    /// Creates the playhead of a looping sound decoded again from `loop_start` at each loop,
+    /// starting with `next_loop`.
+    pub(crate) fn decoding_loops(loop_start: Duration, next_loop: DecodedSound) -> Self {
+        Self {
+            loop_start: Some(loop_start),
+            next_loop: Mutex::new(Some(next_loop)),
+            ..Self::default()
+        }
+    }
+
+    pub(crate) fn position(&self) -> Duration {
+        Duration::from_nanos(self.position.load(Ordering::Relaxed))
+    }
+
+    /// Requests the sound to continue from `position`.
+    ///
+    /// The request is fulfilled by [`seek_audio`], which decodes the sound from the new position.
+    pub(crate) fn seek(&self, position: Duration) {
+        let mut seek = self.seek.lock().unwrap();
+        seek.position = position;
+        seek.source = None;
+        self.position
+            .store(position.as_nanos() as u64, Ordering::Relaxed);
+        self.seeking.store(true, Ordering::Release);
+    }
+
+    /// Gets the position of the seek waiting for its sound to be decoded.
+    fn requested_seek(&self) -> Option<Duration> {
+        if !self.seeking.load(Ordering::Acquire) {
+            return None;
+        }
+        let seek = self.seek.lock().unwrap();
+        seek.source.is_none().then_some(seek.position)
+    }
+
+    /// Provides the sound to play from the requested seek position.
+    fn fulfill_seek(&self, source: DecodedSound) {
+        self.seek.lock().unwrap().source = Some(source);
+    }
+
+    /// Gets the loop start of the sound, if its next loop needs to be decoded.
+    fn requested_loop(&self) -> Option<Duration> {
+        let loop_start = self.loop_start?;
+        self.next_loop
+            .lock()
+            .unwrap()
+            .is_none()
+            .then_some(loop_start)
+    }
+
+    /// Provides the sound to play at the end of the loop.
+    fn fulfill_loop(&self, source: DecodedSound) {
+        *self.next_loop.lock().unwrap() = Some(source);
+    }
+}
+
+/// The loop of a [`SeekableSource`].
+struct Loop {
+    start: Duration,
+    end: Option<Duration>,
+    /// The sound kept in memory, to restart it when looping. When `None`, the sound is decoded
+    /// again for each loop by [`seek_audio`].
+    buffered: Option<LoopStart>,
+}
+
+/// A sound kept in memory, moved to its loop start while the sound plays, so the audio thread
+/// doesn't skip all the samples before the loop start at each loop.
+struct LoopStart {
+    source: Buffered<BoxedSource>,
+    /// The number of samples left to skip to reach the loop start.
+    skipped: u64,
+}
+
+impl LoopStart {
+    fn new(source: Buffered<BoxedSource>, start: Duration) -> Self {
+        let frames = (start.as_secs_f64() * source.sample_rate() as f64).round() as u64;
+        Self {
+            skipped: frames * source.channels() as u64,
+            source,
+        }
+    }
+
+    /// Skips a sample towards the loop start.
+    #[inline]
+    fn advance(&mut self) {
+        if self.skipped > 0 {
+            self.skipped -= 1;
+            self.source.next();
+        }
+    }
+
+    /// Returns the sound from the loop start.
+    fn sound(&mut self) -> DecodedSound {
+        // Only happens if the sound reached its loop end before playing as long as the loop
+        // start, after a seek.
+        while self.skipped > 0 {
+            self.advance();
+        }
+        DecodedSound::new(Box::new(self.source.clone()))
+    }
+}
+
+/// A [`Source`] reporting its position to a [`Playhead`], switching to a new sound when the
+/// playhead is moved, and looping between its loop points.
+pub(crate) struct SeekableSource {
+    source: BoxedSource,
+    /// Set while `source` plays silence waiting for its samples, which doesn't move the playhead.
+    stalled: Option<Arc<AtomicBool>>,
+    looping: Option<Loop>,
+    playhead: Arc<Playhead>,
+    /// The position at which `source` started.
+    start: Duration,
+    /// The number of samples of `source` played since `start`.
+    played: u64,
+    channel: u16,
+    /// The number of samples of silence left to play while the next loop is decoded.
+    silence: u16,
+}
+
+impl SeekableSource {
+    /// Plays `sound` once.
+    pub(crate) fn once(sound: DecodedSound, playhead: Arc<Playhead>) -> Self {
+        Self {
+            source: sound.source,
+            stalled: sound.stalled,
+            looping: None,
+            playhead,
+            start: Duration::ZERO,
+            played: 0,
+            channel: 0,
+            silence: 0,
+        }
+    }
+
+    /// Plays `sound`, then loops from `start` to `end`, or to the end of the sound. The sound is
+    /// only decoded once and kept in memory.
+    pub(crate) fn looping(
+        sound: DecodedSound,
+        playhead: Arc<Playhead>,
+        start: Duration,
+        end: Option<Duration>,
+    ) -> Self {
+        let source = sound.source.buffered();
+        Self {
+            looping: Some(Loop {
+                start,
+                end,
+                buffered: Some(LoopStart::new(source.clone(), start)),
+            }),
+            ..Self::once(
+                DecodedSound {
+                    source: Box::new(source),
+                    ..sound
+                },
+                playhead,
+            )
+        }
+    }
+
+    /// Plays `sound`, then loops from `start` to `end`, or to the end of the sound. Each loop is
+    /// decoded again, from the sounds provided to the [`Playhead`].
+    pub(crate) fn decoding_loops(
+        sound: DecodedSound,
+        playhead: Arc<Playhead>,
+        start: Duration,
+        end: Option<Duration>,
+    ) -> Self {
+        Self {
+            looping: Some(Loop {
+                start,
+                end,
+                buffered: None,
+            }),
+            ..Self::once(sound, playhead)
+        }
+    }
+
+    fn restart(&mut self, sound: DecodedSound, start: Duration) {
+        self.source = sound.source;
+        self.stalled = sound.stalled;
+        self.start = start;
+        self.played = 0;
+        if !self.playhead.seeking.load(Ordering::Relaxed) {
+            self.playhead
+                .position
+                .store(start.as_nanos() as u64, Ordering::Relaxed);
+        }
+    }
+
+    /// Switches to the sound of the pending seek, if it's decoded.
+    fn apply_seek(&mut self) {
+        // Never block the audio thread: try again on the next frame.
+        let Ok(mut seek) = self.playhead.seek.try_lock() else {
+            return;
+        };
+        if let Some(sound) = seek.source.take() {
+            let position = seek.position;
+            drop(seek);
+            self.restart(sound, position);
+            self.playhead.seeking.store(false, Ordering::Release);
+        }
+    }
+
+    /// Returns `true` if the playback reached the loop end.
+    fn at_loop_end(&self) -> bool {
+        let Some(end) = self.looping.as_ref().and_then(|looping| looping.end) else {
+            return false;
+        };
+        let sample_rate = self.source.sample_rate() as f64;
+        let frame = |position: Duration| (position.as_secs_f64() * sample_rate).round() as u64;
+        frame(self.start) + self.played / self.source.channels().max(1) as u64 >= frame(end)
+    }
+
+    /// Restarts the sound from the loop start. Returns `false` if the sound doesn't loop, or if
+    /// its next loop isn't decoded yet.
+    fn restart_loop(&mut self) -> bool {
+        let Some(looping) = &mut self.looping else {
+            return false;
+        };
+        let start = looping.start;
+        let sound = match &mut looping.buffered {
+            Some(buffered) => buffered.sound(),
+            None => {
+                // Never block the audio thread: try again on the next frame.
+                let Ok(mut next_loop) = self.playhead.next_loop.try_lock() else {
+                    return false;
+                };
+                let Some(sound) = next_loop.take() else {
+                    return false;
+                };
+                sound
+            }
+        };
+        self.restart(sound, start);
+        true
+    }
+
+    /// Plays a frame of silence, while waiting for the next loop.
+    fn wait_for_loop(&mut self) -> Option<f32> {
+        self.looping.as_ref()?;
+        self.silence = self.source.channels().max(1) - 1;
+        Some(0.0)
+    }
+}
+
+impl Iterator for SeekableSource {
+    type Item = f32;
+
+    #[inline]
+    fn next(&mut self) -> Option<f32> {
+        if self.silence > 0 {
+            self.silence -= 1;
+            return Some(0.0);
+        }
+
+        // Only switch sounds between frames, to keep the channels aligned.
+        if self.channel == 0 {
+            if self.playhead.seeking.load(Ordering::Acquire) {
+                self.apply_seek();
+            }
+            if self.at_loop_end() && !self.restart_loop() {
+                return self.wait_for_loop();
+            }
+        }
+
+        let sample = match self.source.next() {
+            Some(sample) => sample,
+            None if self.channel == 0 => {
+                if !self.restart_loop() {
+                    return self.wait_for_loop();
+                }
+                // An empty sound would loop forever.
+                self.source.next()?
+            }
+            None => return None,
+        };
+
+        if self
+            .stalled
+            .as_ref()
+            .is_some_and(|stalled| stalled.load(Ordering::Relaxed))
+        {
+            return Some(sample);
+        }
+        let channels = self.source.channels().max(1);
+        self.channel = (self.channel + 1) % channels;
+        self.played += 1;
+        if let Some(buffered) = self
+            .looping
+            .as_mut()
+            .and_then(|looping| looping.buffered.as_mut())
+        {
+            buffered.advance();
+        }
+        if !self.playhead.seeking.load(Ordering::Relaxed) {
+            let elapsed = self.played as f64 / (self.source.sample_rate() as f64 * channels as f64);
+            let position = self.start + Duration::from_secs_f64(elapsed);
+            self.playhead
+                .position
+                .store(position.as_nanos() as u64, Ordering::Relaxed);
+        }
+        Some(sample)
+    }
+}
+
+impl Source for SeekableSource {
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        if self.silence > 0 {
+            return Some(self.silence as usize);
+        }
+        self.source.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.source.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.source.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        if self.looping.is_some() {
+            None
+        } else {
+            self.source.total_duration()
+        }
+    }
+}
+
+/// Converts `source` to `f32` samples, and skips its samples until `position`.
+pub(crate) fn decode_from<S>(source: S, position: Duration) -> BoxedSource
+where
+    S: Source + Send + 'static,
+    S::Item: rodio::Sample,
+    f32: rodio::cpal::FromSample<S::Item>,
+{
+    let mut source = source.convert_samples::<f32>();
+    let frames = (position.as_secs_f64() * source.sample_rate() as f64).round() as u64;
+    let samples = frames * source.channels() as u64;
+    if samples > 0 {
+        source.nth(samples as usize - 1);
+    }
+    Box::new(source)
+}
+
+/// Decodes the sounds of the sinks whose [`Playhead`] was moved, from their new position, and
+/// the next loops of the looping sounds decoded again at each loop.
+pub(crate) fn seek_audio<Source: Asset + Decodable>(
+    audio_sources: Res<Assets<Source>>,
+    sinks: Query<
+        (
+            &Handle<Source>,
+            Option<&AudioSink>,
+            Option<&SpatialAudioSink>,
+        ),
+        Or<(With<AudioSink>, With<SpatialAudioSink>)>,
+    >,
+) where
+    f32: rodio::cpal::FromSample<Source::DecoderItem>,
+{
+    for (source_handle, sink, spatial_sink) in &sinks {
+        let playhead = match (sink, spatial_sink) {
+            (Some(sink), _) => &sink.playhead,
+            (_, Some(sink)) => &sink.playhead,
+            (None, None) => continue,
+        };
+        let seek = playhead.requested_seek();
+        let loop_start = playhead.requested_loop();
+        if seek.is_none() && loop_start.is_none() {
+            continue;
+        }
+        let Some(audio_source) = audio_sources.get(source_handle) else {
+            continue;
+        };
+        if let Some(position) = seek {
+            playhead.fulfill_seek(decode(audio_source, position));
+        }
+        if let Some(loop_start) = loop_start {
+            playhead.fulfill_loop(decode(audio_source, loop_start));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rodio::buffer::SamplesBuffer;
+
+    fn ramp() -> DecodedSound {
+        // Two stereo frames per second.
+        DecodedSound::new(Box::new(SamplesBuffer::new(
+            2,
+            2,
+            vec![0.0f32, 0.0, 1.0, 1.0, 2.0, 2.0],
+        )))
+    }
+
+    #[test]
+    fn playhead_follows_playback_and_loops() {
+        let playhead = Arc::new(Playhead::default());
+        let mut source = SeekableSource::looping(ramp(), playhead.clone(), Duration::ZERO, None);
+        assert_eq!(
+            source.by_ref().take(4).collect::<Vec<_>>(),
+            [0.0, 0.0, 1.0, 1.0]
+        );
+        assert_eq!(playhead.position(), Duration::from_secs(1));
+
+        assert_eq!(
+            source.by_ref().take(4).collect::<Vec<_>>(),
+            [2.0, 2.0, 0.0, 0.0]
+        );
+        assert_eq!(playhead.position(), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn seeking_switches_sounds_between_frames() {
+        let playhead = Arc::new(Playhead::default());
+        let mut source = SeekableSource::once(ramp(), playhead.clone());
+        assert_eq!(source.next(), Some(0.0));
+
+        playhead.seek(Duration::from_secs(1));
+        assert_eq!(playhead.requested_seek(), Some(Duration::from_secs(1)));
+        playhead.fulfill_seek(DecodedSound::new(decode_from(
+            ramp().source,
+            Duration::from_secs(1),
+        )));
+        assert_eq!(playhead.requested_seek(), None);
+        assert_eq!(playhead.position(), Duration::from_secs(1));
+
+        assert_eq!(source.collect::<Vec<_>>(), [0.0, 2.0, 2.0]);
+        assert_eq!(playhead.position(), Duration::from_millis(1500));
+    }
+
+    #[test]
+    fn sounds_loop_between_loop_points() {
+        let playhead = Arc::new(Playhead::default());
+        let mut source = SeekableSource::looping(
+            ramp(),
+            playhead.clone(),
+            Duration::from_millis(500),
+            Some(Duration::from_secs(1)),
+        );
+        assert_eq!(
+            source.by_ref().take(6).collect::<Vec<_>>(),
+            [0.0, 0.0, 1.0, 1.0, 1.0, 1.0]
+        );
+        assert_eq!(playhead.position(), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn buffered_loops_restart_without_skipping_samples() {
+        let playhead = Arc::new(Playhead::default());
+        let mut source =
+            SeekableSource::looping(ramp(), playhead.clone(), Duration::from_millis(500), None);
+        let loop_start = |source: &SeekableSource| {
+            source
+                .looping
+                .as_ref()
+                .unwrap()
+                .buffered
+                .as_ref()
+                .unwrap()
+                .skipped
+        };
+        assert_eq!(loop_start(&source), 2);
+        // The loop start is reached while the sound plays.
+        assert_eq!(source.by_ref().take(2).collect::<Vec<_>>(), [0.0, 0.0]);
+        assert_eq!(loop_start(&source), 0);
+        assert_eq!(
+            source.by_ref().take(8).collect::<Vec<_>>(),
+            [1.0, 1.0, 2.0, 2.0, 1.0, 1.0, 2.0, 2.0]
+        );
+
+        // After seeking past the loop end, the loop start is reached when looping.
+        let mut source =
+            SeekableSource::looping(ramp(), playhead.clone(), Duration::from_millis(500), None);
+        playhead.seek(Duration::from_secs(1));
+        playhead.fulfill_seek(DecodedSound::new(decode_from(
+            ramp().source,
+            Duration::from_secs(1),
+        )));
+        assert_eq!(
+            source.by_ref().take(4).collect::<Vec<_>>(),
+            [2.0, 2.0, 1.0, 1.0]
+        );
+    }
+
+    #[test]
+    fn decoded_loops_wait_for_their_sound() {
+        let stalled = Arc::new(AtomicBool::new(false));
+        // The next loop starts with a frame of silence, while it waits for its samples.
+        let next_loop = DecodedSound {
+            stalled: Some(stalled.clone()),
+            ..ramp()
+        };
+        let playhead = Arc::new(Playhead::decoding_loops(
+            Duration::from_millis(500),
+            next_loop,
+        ));
+        let mut source = SeekableSource::decoding_loops(
+            ramp(),
+            playhead.clone(),
+            Duration::from_millis(500),
+            None,
+        );
+        assert_eq!(source.by_ref().take(6).count(), 6);
+        assert_eq!(playhead.requested_loop(), None);
+
+        stalled.store(true, Ordering::Relaxed);
+        assert_eq!(source.by_ref().take(2).collect::<Vec<_>>(), [0.0, 0.0]);
+        assert_eq!(playhead.position(), Duration::from_millis(500));
+        stalled.store(false, Ordering::Relaxed);
+        assert_eq!(
+            source.by_ref().take(4).collect::<Vec<_>>(),
+            [1.0, 1.0, 2.0, 2.0]
+        );
+        assert_eq!(playhead.position(), Duration::from_millis(1500));
+
+        // The following loop isn't decoded yet: play silence until it is.
+        assert_eq!(playhead.requested_loop(), Some(Duration::from_millis(500)));
+        assert_eq!(source.by_ref().take(2).collect::<Vec<_>>(), [0.0, 0.0]);
+        assert_eq!(playhead.position(), Duration::from_millis(1500));
+        playhead.fulfill_loop(DecodedSound::new(decode_from(
+            ramp().source,
+            Duration::from_millis(500),
+        )));
+        assert_eq!(source.next(), Some(1.0));
+        assert_eq!(playhead.position(), Duration::from_millis(750));
+    }
+}
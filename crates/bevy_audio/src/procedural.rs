@@ -0,0 +1,325 @@
+use crate::{AudioSourceBundle, Decodable};
+use bevy_asset::Asset;
+use bevy_reflect::TypePath;
+use rodio::Source;
+use std::sync::{
+    atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering},
+    Arc,
+};
+use std::time::Duration;
+
+/// The number of frames generated at once by the callback of a [`ProceduralAudio`].
+const BLOCK_FRAMES: usize = 256;
+
+type GeneratorFactory = dyn Fn() -> Box<dyn FnMut(&mut [f32]) + Send> + Send + Sync;
+
+/// A sound generated on the audio thread while it plays, e.g. by a synthesizer or an engine
+/// simulation, or streamed from the ECS.
+///
+/// Play it like any other sound, with a [`ProceduralAudioBundle`] holding a handle to it. The sound never ends by itself: stop or fade out its sink instead.
+#[derive(Asset, TypePath, Clone)]
+pub struct ProceduralAudio {
+    channels: u16,
+    sample_rate: u32,
+    generator: Generator,
+}
+
+#[derive(Clone)]
+enum Generator {
+    Callback(Arc<GeneratorFactory>),
+    Stream(Arc<SampleRingBuffer>),
+}
+
+impl std::fmt::Debug for ProceduralAudio {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProceduralAudio")
+            .field("channels", &self.channels)
+            .field("sample_rate", &self.sample_rate)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ProceduralAudio {
+    /// Creates a sound generated by a callback filling blocks of interleaved samples.
+    ///
+    /// `factory` is called each time the sound starts playing, to create the callback with its
+    /// own state. To control the callback from systems, share atomics with it.
+    ///
+    /// ```
+    /// # use bevy_audio::ProceduralAudio;
+    /// let sine = ProceduralAudio::from_fn(1, 44100, || {
+    ///     let mut phase = 0.0f32;
+    ///     move |samples: &mut [f32]| {
+    ///         for sample in samples {
+    ///             *sample = (phase * std::f32::consts::TAU).sin() * 0.2;
+    ///             phase = (phase + 440.0 / 44100.0).fract();
+    ///         }
+    ///     }
+    /// });
+    /// ```
+    pub fn from_fn<F, G>(channels: u16, sample_rate: u32, factory: F) -> Self
+    where
+        F: Fn() -> G + Send + Sync + 'static,
+        G: FnMut(&mut [f32]) + Send + 'static,
+    {
+        Self {
+            channels: channels.max(1),
+            sample_rate,
+            generator: Generator::Callback(Arc::new(move || Box::new(factory()))),
+        }
+    }
+
+    /// Creates a sound playing the samples pushed to the returned [`AudioStreamSender`], through
+    /// a lock-free ring buffer holding up to `capacity` samples.
+    ///
+    /// The sound plays silence when the buffer runs empty, and ends once the sender is dropped
+    /// and the buffer is empty. Only play the sound once at a time: the sounds would share the
+    /// samples otherwise.
+    pub fn stream(channels: u16, sample_rate: u32, capacity: usize) -> (Self, AudioStreamSender) {
+        let buffer = Arc::new(SampleRingBuffer::new(capacity.max(1)));
+        (
+            Self {
+                channels: channels.max(1),
+                sample_rate,
+                generator: Generator::Stream(buffer.clone()),
+            },
+            AudioStreamSender { buffer },
+        )
+    }
+}
+
+/// Bundle for playing a [`ProceduralAudio`].
+pub type ProceduralAudioBundle = AudioSourceBundle<ProceduralAudio>;
+
+/// Pushes samples to a [`ProceduralAudio::stream`].
+///
+/// Dropping the sender ends the sound once the pushed samples are played.
+pub struct AudioStreamSender {
+    buffer: Arc<SampleRingBuffer>,
+}
+
+impl AudioStreamSender {
+    /// Pushes interleaved samples to the stream, and returns how many were pushed, which is less
+    /// than `samples.len()` if the buffer is full.
+    pub fn push(&mut self, samples: &[f32]) -> usize {
+        samples
+            .iter()
+            .take_while(|sample| self.buffer.push(**sample))
+            .count()
+    }
+
+    /// The number of samples that can be pushed before the buffer is full.
+    pub fn free_capacity(&self) -> usize {
+        self.buffer.capacity() - self.buffer.len()
+    }
+
+    /// The number of samples waiting to be played.
+    pub fn len(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Returns `true` if all the pushed samples have been played.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Drop for AudioStreamSender {
+    fn drop(&mut self) {
+        self.buffer.closed.store(true, Ordering::Release);
+    }
+}
+
+/// A single producer, single consumer queue of samples.
+pub(crate) struct SampleRingBuffer {
+    samples: Box<[AtomicU32]>,
+    /// The number of samples read since the creation of the buffer.
+    read: AtomicUsize,
+    /// The number of samples written since the creation of the buffer.
+    write: AtomicUsize,
+    /// Whether the producer is done pushing samples.
+    pub(crate) closed: AtomicBool,
+}
+
+impl SampleRingBuffer {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            samples: (0..capacity).map(|_| AtomicU32::new(0)).collect(),
+            read: AtomicUsize::new(0),
+            write: AtomicUsize::new(0),
+            closed: AtomicBool::new(false),
+        }
+    }
+
+    pub(crate) fn capacity(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        let write = self.write.load(Ordering::Acquire);
+        write.wrapping_sub(self.read.load(Ordering::Acquire))
+    }
+
+    fn push(&self, sample: f32) -> bool {
+        let write = self.write.load(Ordering::Relaxed);
+        if write.wrapping_sub(self.read.load(Ordering::Acquire)) >= self.capacity() {
+            return false;
+        }
+        self.samples[write % self.capacity()].store(sample.to_bits(), Ordering::Relaxed);
+        self.write.store(write.wrapping_add(1), Ordering::Release);
+        true
+    }
+
+    /// Pushes all of `samples`, or none of them if they don't fit, so the consumer never sees
+    /// part of them.
+    pub(crate) fn push_all(&self, samples: &[f32]) -> bool {
+        let write = self.write.load(Ordering::Relaxed);
+        let len = write.wrapping_sub(self.read.load(Ordering::Acquire));
+        if len + samples.len() > self.capacity() {
+            return false;
+        }
+        for (i, sample) in samples.iter().enumerate() {
+            self.samples[write.wrapping_add(i) % self.capacity()]
+                .store(sample.to_bits(), Ordering::Relaxed);
+        }
+        self.write
+            .store(write.wrapping_add(samples.len()), Ordering::Release);
+        true
+    }
+
+    pub(crate) fn pop(&self) -> Option<f32> {
+        let read = self.read.load(Ordering::Relaxed);
+        if read == self.write.load(Ordering::Acquire) {
+            return None;
+        }
+        let sample = f32::from_bits(self.samples[read % self.capacity()].load(Ordering::Relaxed));
+        self.read.store(read.wrapping_add(1), Ordering::Release);
+        Some(sample)
+    }
+}
+
+/// The [`Source`] of a [`ProceduralAudio`].
+pub struct ProceduralDecoder {
+    channels: u16,
+    sample_rate: u32,
+    state: DecoderState,
+}
+
+enum DecoderState {
+    Callback {
+        callback: Box<dyn FnMut(&mut [f32]) + Send>,
+        block: Vec<f32>,
+        index: usize,
+    },
+    Stream(Arc<SampleRingBuffer>),
+}
+
+impl Iterator for ProceduralDecoder {
+    type Item = f32;
+
+    #[inline]
+    fn next(&mut self) -> Option<f32> {
+        match &mut self.state {
+            DecoderState::Callback {
+                callback,
+                block,
+                index,
+            } => {
+                if *index == block.len() {
+                    block.fill(0.0);
+                    callback(block);
+                    *index = 0;
+                }
+                *index += 1;
+                Some(block[*index - 1])
+            }
+            DecoderState::Stream(buffer) => match buffer.pop() {
+                Some(sample) => Some(sample),
+                // Check that the buffer is still empty once closed, as samples could have been
+                // pushed just before.
+                None if buffer.closed.load(Ordering::Acquire) => buffer.pop(),
+                // Play silence until more samples are pushed.
+                None => Some(0.0),
+            },
+        }
+    }
+}
+
+impl Source for ProceduralDecoder {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+impl Decodable for ProceduralAudio {
+    type DecoderItem = f32;
+    type Decoder = ProceduralDecoder;
+
+    fn decoder(&self) -> Self::Decoder {
+        let state = match &self.generator {
+            Generator::Callback(factory) => DecoderState::Callback {
+                callback: factory(),
+                block: vec![0.0; BLOCK_FRAMES * self.channels as usize],
+                index: BLOCK_FRAMES * self.channels as usize,
+            },
+            Generator::Stream(buffer) => DecoderState::Stream(buffer.clone()),
+        };
+        ProceduralDecoder {
+            channels: self.channels,
+            sample_rate: self.sample_rate,
+            state,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn callbacks_generate_samples_in_blocks() {
+        let audio = ProceduralAudio::from_fn(2, 44100, || {
+            let mut value = 0.0;
+            move |samples: &mut [f32]| {
+                for sample in samples {
+                    value += 1.0;
+                    *sample = value;
+                }
+            }
+        });
+        let samples: Vec<f32> = audio.decoder().skip(511).take(2).collect();
+        assert_eq!(samples, [512.0, 513.0]);
+        // Each playback has its own state.
+        assert_eq!(audio.decoder().next(), Some(1.0));
+    }
+
+    #[test]
+    fn streams_play_pushed_samples_until_closed() {
+        let (audio, mut sender) = ProceduralAudio::stream(1, 44100, 2);
+        let mut decoder = audio.decoder();
+        assert_eq!(sender.push(&[1.0, 2.0, 3.0]), 2);
+        assert_eq!(sender.free_capacity(), 0);
+        assert_eq!(decoder.next(), Some(1.0));
+        assert_eq!(sender.push(&[3.0]), 1);
+        assert_eq!(
+            decoder.by_ref().take(3).collect::<Vec<_>>(),
+            [2.0, 3.0, 0.0]
+        );
+
+        sender.push(&[4.0]);
+        drop(sender);
+        assert_eq!(decoder.collect::<Vec<_>>(), [4.0]);
+    }
+}
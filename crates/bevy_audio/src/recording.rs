@@ -0,0 +1,454 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex,
+};
+use std::time::Duration;
+
+use bevy_ecs::prelude::*;
+use bevy_utils::{tracing::warn, HashMap};
+use crossbeam_channel::{Receiver, Sender};
+use rodio::{
+    dynamic_mixer::{self, DynamicMixer, DynamicMixerController},
+    source::Zero,
+    Sink, Source,
+};
+
+use crate::{
+    audio_output::AudioOutput,
+    devices::{OutputRoutes, SplitSource},
+    AudioBus,
+};
+
+/// The number of frames sent at once to recorders.
+const TAP_FRAMES: usize = 1024;
+
+/// Records the mixed audio output, or the audio of a bus, while this component exists.
+///
+/// ```
+/// # use bevy_ecs::prelude::*;
+/// # use bevy_audio::{AudioBus, AudioRecorder};
+/// fn record_music(mut commands: Commands) {
+///     commands.spawn(AudioRecorder::wav("music.wav").with_bus(AudioBus::MUSIC));
+/// }
+/// ```
+#[derive(Component)]
+pub struct AudioRecorder {
+    bus: Option<AudioBus>,
+    output: RecordingOutput,
+    recording: Option<Recording>,
+}
+
+/// Where an [`AudioRecorder`] writes the recorded audio.
+#[derive(Clone, Debug)]
+pub enum RecordingOutput {
+    /// Writes a 32-bit float WAV file, finished when the recorder is removed.
+    Wav(PathBuf),
+    /// Sends the recorded samples as [`RecordedAudio`] events.
+    Events,
+}
+
+/// Samples recorded by an [`AudioRecorder`] recording to [`RecordingOutput::Events`].
+#[derive(Event, Clone, Debug)]
+pub struct RecordedAudio {
+    /// The entity of the recorder.
+    pub recorder: Entity,
+    /// The interleaved samples.
+    pub samples: Vec<f32>,
+    /// The number of channels of the samples.
+    pub channels: u16,
+    /// The sample rate of the samples.
+    pub sample_rate: u32,
+}
+
+struct Recording {
+    samples: Receiver<Vec<f32>>,
+    /// The number of samples recorded.
+    recorded: u64,
+    channels: u16,
+    sample_rate: u32,
+    /// The file being written, if recording to a file.
+    wav: Option<WavWriter<BufWriter<File>>>,
+}
+
+impl AudioRecorder {
+    /// Records to a WAV file at `path`.
+    pub fn wav(path: impl Into<PathBuf>) -> Self {
+        Self {
+            bus: None,
+            output: RecordingOutput::Wav(path.into()),
+            recording: None,
+        }
+    }
+
+    /// Records to [`RecordedAudio`] events.
+    pub fn events() -> Self {
+        Self {
+            bus: None,
+            output: RecordingOutput::Events,
+            recording: None,
+        }
+    }
+
+    /// Records the audio of `bus` instead of the mixed output.
+    ///
+    /// Only the sounds played on `bus` are recorded, not the sounds of the buses routed to it
+    /// with [`AudioBuses::set_output`](crate::AudioBuses::set_output).
+    pub fn with_bus(mut self, bus: AudioBus) -> Self {
+        self.bus = Some(bus);
+        self
+    }
+
+    /// The bus being recorded, or `None` for the mixed output.
+    pub fn bus(&self) -> Option<AudioBus> {
+        self.bus
+    }
+
+    /// Where the recorded audio is written.
+    pub fn output(&self) -> &RecordingOutput {
+        &self.output
+    }
+
+    /// The duration recorded so far.
+    pub fn duration(&self) -> Duration {
+        let Some(recording) = &self.recording else {
+            return Duration::ZERO;
+        };
+        let frames = recording.recorded / recording.channels.max(1) as u64;
+        Duration::from_secs_f64(frames as f64 / recording.sample_rate as f64)
+    }
+}
+
+/// Starts the recording of new [`AudioRecorder`]s, and writes the recorded samples.
+pub(crate) fn record_audio(
+    audio_output: Res<AudioOutput>,
+    mut recorders: Query<(Entity, &mut AudioRecorder)>,
+    mut events: EventWriter<RecordedAudio>,
+) {
+    let Some(mixer) = audio_output.mixer.as_ref() else {
+        return;
+    };
+    for (entity, mut recorder) in &mut recorders {
+        let recorder = &mut *recorder;
+        let recording = match &mut recorder.recording {
+            Some(recording) => recording,
+            None => {
+                let wav = match &recorder.output {
+                    RecordingOutput::Wav(path) => match File::create(path).and_then(|file| {
+                        WavWriter::new(BufWriter::new(file), mixer.channels, mixer.sample_rate)
+                    }) {
+                        Ok(wav) => Some(wav),
+                        Err(err) => {
+                            warn!("Failed to record audio to {}: {err}", path.display());
+                            // Don't try again every frame.
+                            recorder.output = RecordingOutput::Events;
+                            None
+                        }
+                    },
+                    RecordingOutput::Events => None,
+                };
+                recorder.recording.insert(Recording {
+                    samples: mixer.tap(recorder.bus),
+                    recorded: 0,
+                    channels: mixer.channels,
+                    sample_rate: mixer.sample_rate,
+                    wav,
+                })
+            }
+        };
+
+        for samples in recording.samples.try_iter() {
+            recording.recorded += samples.len() as u64;
+            match &mut recording.wav {
+                Some(wav) => {
+                    if let Err(err) = wav.write(&samples) {
+                        warn!("Failed to write recorded audio: {err}");
+                    }
+                }
+                None => {
+                    events.send(RecordedAudio {
+                        recorder: entity,
+                        samples,
+                        channels: recording.channels,
+                        sample_rate: recording.sample_rate,
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// Writes samples to a 32-bit float WAV file.
+struct WavWriter<W: Write + Seek> {
+    writer: W,
+    /// The number of samples written.
+    samples: u64,
+}
+
+impl<W: Write + Seek> WavWriter<W> {
+    const HEADER_LEN: u32 = 44;
+
+    fn new(mut writer: W, channels: u16, sample_rate: u32) -> io::Result<Self> {
+        let block_align = channels * 4;
+        writer.write_all(b"RIFF")?;
+        // The sizes are written when the file is finished.
+        writer.write_all(&(Self::HEADER_LEN - 8).to_le_bytes())?;
+        writer.write_all(b"WAVEfmt ")?;
+        writer.write_all(&16u32.to_le_bytes())?;
+        // IEEE float.
+        writer.write_all(&3u16.to_le_bytes())?;
+        writer.write_all(&channels.to_le_bytes())?;
+        writer.write_all(&sample_rate.to_le_bytes())?;
+        writer.write_all(&(sample_rate * block_align as u32).to_le_bytes())?;
+        writer.write_all(&block_align.to_le_bytes())?;
+        writer.write_all(&32u16.to_le_bytes())?;
+        writer.write_all(b"data")?;
+        writer.write_all(&0u32.to_le_bytes())?;
+        Ok(Self { writer, samples: 0 })
+    }
+
+    fn write(&mut self, samples: &[f32]) -> io::Result<()> {
+        for sample in samples {
+            self.writer.write_all(&sample.to_le_bytes())?;
+        }
+        self.samples += samples.len() as u64;
+        Ok(())
+    }
+
+    /// Writes the sizes of the file to its header.
+    fn finish(&mut self) -> io::Result<()> {
+        let data_len = (self.samples * 4).min((u32::MAX - Self::HEADER_LEN) as u64) as u32;
+        self.writer.seek(SeekFrom::Start(4))?;
+        self.writer
+            .write_all(&(data_len + Self::HEADER_LEN - 8).to_le_bytes())?;
+        self.writer.seek(SeekFrom::Start(40))?;
+        self.writer.write_all(&data_len.to_le_bytes())?;
+        self.writer.seek(SeekFrom::End(0))?;
+        self.writer.flush()
+    }
+}
+
+impl<W: Write + Seek> Drop for WavWriter<W> {
+    fn drop(&mut self) {
+        if let Err(err) = self.finish() {
+            warn!("Failed to finish recorded audio: {err}");
+        }
+    }
+}
+
+/// Sends the samples going through a point of the mix to the recorders listening to it.
+#[derive(Default)]
+pub(crate) struct Tap {
+    /// Whether there are recorders, to avoid copying samples otherwise.
+    active: AtomicBool,
+    recorders: Mutex<Vec<Sender<Vec<f32>>>>,
+}
+
+impl Tap {
+    fn listen(&self) -> Receiver<Vec<f32>> {
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        self.recorders.lock().unwrap().push(sender);
+        self.active.store(true, Ordering::Release);
+        receiver
+    }
+}
+
+/// A [`Source`] sending its samples to a [`Tap`] in blocks.
+pub(crate) struct TapSource<I> {
+    input: I,
+    tap: Arc<Tap>,
+    block: Vec<f32>,
+    /// The channel of the next sample.
+    channel: u16,
+}
+
+impl<I: Source<Item = f32>> TapSource<I> {
+    fn new(input: I, tap: Arc<Tap>) -> Self {
+        Self {
+            input,
+            tap,
+            block: Vec::new(),
+            channel: 0,
+        }
+    }
+
+    fn flush(&mut self) {
+        // Never block the audio thread: keep the samples until the next block.
+        let Ok(mut recorders) = self.tap.recorders.try_lock() else {
+            return;
+        };
+        recorders.retain(|recorder| recorder.send(self.block.clone()).is_ok());
+        if recorders.is_empty() {
+            self.tap.active.store(false, Ordering::Release);
+        }
+        self.block.clear();
+    }
+}
+
+impl<I: Source<Item = f32>> Iterator for TapSource<I> {
+    type Item = f32;
+
+    #[inline]
+    fn next(&mut self) -> Option<f32> {
+        let sample = self.input.next()?;
+        let channel = self.channel;
+        self.channel = (channel + 1) % self.input.channels().max(1);
+        if self.tap.active.load(Ordering::Acquire) {
+            // Only start blocks on the first channel, to keep the channels aligned.
+            if channel != 0 && self.block.is_empty() {
+                return Some(sample);
+            }
+            self.block.push(sample);
+            if self.block.len() >= TAP_FRAMES * self.input.channels() as usize {
+                self.flush();
+            }
+        } else if !self.block.is_empty() {
+            self.block.clear();
+        }
+        Some(sample)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I: Source<Item = f32>> Source for TapSource<I> {
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+}
+
+/// A mixer of the sounds of a bus, whose output goes to the [`OutputMixer`].
+struct BusMixer {
+    mixer: Arc<DynamicMixerController<f32>>,
+    tap: Arc<Tap>,
+}
+
+/// Mixes the sounds of each bus, and then the buses, so each of them can be recorded.
+pub(crate) struct OutputMixer {
+    pub(crate) channels: u16,
+    pub(crate) sample_rate: u32,
+    master: Arc<DynamicMixerController<f32>>,
+    tap: Arc<Tap>,
+    buses: Mutex<HashMap<AudioBus, BusMixer>>,
+    /// The devices playing channels of the output, other than the default device.
+    pub(crate) routes: Arc<OutputRoutes>,
+}
+
+impl OutputMixer {
+    /// Creates a mixer, and the source playing its output.
+    pub(crate) fn new(
+        channels: u16,
+        sample_rate: u32,
+    ) -> (Self, TapSource<SplitSource<DynamicMixer<f32>>>) {
+        let (master, output) = Self::mixer(channels, sample_rate);
+        let tap = Arc::new(Tap::default());
+        let routes = Arc::new(OutputRoutes::default());
+        let output = TapSource::new(SplitSource::new(output, routes.clone()), tap.clone());
+        let mixer = Self {
+            channels,
+            sample_rate,
+            master,
+            tap,
+            buses: Default::default(),
+            routes,
+        };
+        (mixer, output)
+    }
+
+    /// Creates a mixer that never ends, even without sounds.
+    fn mixer(
+        channels: u16,
+        sample_rate: u32,
+    ) -> (Arc<DynamicMixerController<f32>>, DynamicMixer<f32>) {
+        let (controller, mixer) = dynamic_mixer::mixer(channels, sample_rate);
+        controller.add(Zero::new(channels, sample_rate));
+        (controller, mixer)
+    }
+
+    fn with_bus<T>(&self, bus: AudioBus, f: impl FnOnce(&BusMixer) -> T) -> T {
+        let mut buses = self.buses.lock().unwrap();
+        let bus_mixer = buses.entry(bus).or_insert_with(|| {
+            let (mixer, output) = Self::mixer(self.channels, self.sample_rate);
+            let tap = Arc::new(Tap::default());
+            self.master.add(TapSource::new(output, tap.clone()));
+            BusMixer { mixer, tap }
+        });
+        f(bus_mixer)
+    }
+
+    /// Creates a sink playing through `bus`.
+    pub(crate) fn sink(&self, bus: AudioBus) -> Sink {
+        let (sink, output) = Sink::new_idle();
+        self.with_bus(bus, |bus_mixer| bus_mixer.mixer.add(output));
+        sink
+    }
+
+    /// Receives the samples of `bus`, or of the mixed output if `bus` is `None`.
+    fn tap(&self, bus: Option<AudioBus>) -> Receiver<Vec<f32>> {
+        match bus {
+            Some(bus) => self.with_bus(bus, |bus_mixer| bus_mixer.tap.listen()),
+            None => self.tap.listen(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rodio::buffer::SamplesBuffer;
+    use std::io::Cursor;
+
+    #[test]
+    fn taps_send_samples_in_blocks_while_listened_to() {
+        let tap = Arc::new(Tap::default());
+        let samples = (0..TAP_FRAMES * 3).map(|i| i as f32).collect::<Vec<_>>();
+        let mut source = TapSource::new(SamplesBuffer::new(1, 44100, samples), tap.clone());
+
+        source.by_ref().take(TAP_FRAMES).for_each(drop);
+        let receiver = tap.listen();
+        source.by_ref().take(TAP_FRAMES).for_each(drop);
+        let block = receiver.try_recv().unwrap();
+        assert_eq!(block.len(), TAP_FRAMES);
+        assert_eq!(block[0], TAP_FRAMES as f32);
+
+        drop(receiver);
+        source.by_ref().take(TAP_FRAMES).for_each(drop);
+        assert!(!tap.active.load(Ordering::Acquire));
+    }
+
+    #[test]
+    fn wav_headers_are_finished_on_drop() {
+        let mut bytes = Vec::new();
+        let mut wav = WavWriter::new(Cursor::new(&mut bytes), 2, 48000).unwrap();
+        wav.write(&[0.5, -0.5, 1.0, -1.0]).unwrap();
+        drop(wav);
+
+        assert_eq!(bytes.len(), 44 + 16);
+        assert_eq!(&bytes[0..4], b"RIFF");
+        assert_eq!(u32::from_le_bytes(bytes[4..8].try_into().unwrap()), 36 + 16);
+        assert_eq!(u16::from_le_bytes(bytes[22..24].try_into().unwrap()), 2);
+        assert_eq!(u32::from_le_bytes(bytes[40..44].try_into().unwrap()), 16);
+        assert_eq!(f32::from_le_bytes(bytes[44..48].try_into().unwrap()), 0.5);
+    }
+}
@@ -1,7 +1,17 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
 use bevy_ecs::component::Component;
 use bevy_math::Vec3;
 use bevy_transform::prelude::Transform;
-use rodio::{Sink, SpatialSink};
+use rodio::{Sink, Source};
+
+use crate::{
+    fade::Fade,
+    playhead::Playhead,
+    spatial::{SpatialGains, SpatialPositions, SpatialSource},
+    AudioOcclusion, SpatialAudioEmitter,
+};
 
 /// Common interactions with an audio sink.
 pub trait AudioSinkPlayback {
@@ -71,6 +81,32 @@ pub trait AudioSinkPlayback {
 
     /// Returns true if this sink has no more sounds to play.
     fn empty(&self) -> bool;
+
+    /// Gets the position of the playback in the sound.
+    ///
+    /// The position isn't affected by the speed of the sound, and moves back to the loop start
+    /// when a looping sound loops. It doesn't advance while a streamed sound waits for its
+    /// samples to be decoded, so it can be used to synchronize gameplay with music.
+    fn position(&self) -> Duration;
+
+    /// Moves the playback to `position` in the sound, e.g. to synchronize it with a video or to
+    /// scrub through it.
+    ///
+    /// The sound is decoded from the new position during the next update of the audio systems,
+    /// but [`position`](Self::position) returns the new position immediately. Seeking past the
+    /// end of the sound ends it, or restarts it if it's looping.
+    fn seek(&self, position: Duration);
+
+    /// Smoothly changes the volume of the sound to `volume` over `duration`.
+    ///
+    /// The fade is interpolated on every sample, and its volume is multiplied with the
+    /// [`volume`](Self::volume) of the sink.
+    fn fade_to(&self, volume: f32, duration: Duration);
+
+    /// Fades out the sound over `duration`, and stops it afterwards.
+    ///
+    /// See also [`Crossfade`](crate::Crossfade) to fade in another sound at the same time.
+    fn fade_out(&self, duration: Duration);
 }
 
 /// Used to control audio during playback.
@@ -86,9 +122,27 @@ pub trait AudioSinkPlayback {
 #[derive(Component)]
 pub struct AudioSink {
     pub(crate) sink: Sink,
+    pub(crate) playhead: Arc<Playhead>,
+    pub(crate) fade: Arc<Fade>,
 }
 
 impl AudioSinkPlayback for AudioSink {
+    fn position(&self) -> Duration {
+        self.playhead.position()
+    }
+
+    fn seek(&self, position: Duration) {
+        self.playhead.seek(position);
+    }
+
+    fn fade_to(&self, volume: f32, duration: Duration) {
+        self.fade.fade_to(volume, duration, false);
+    }
+
+    fn fade_out(&self, duration: Duration) {
+        self.fade.fade_to(0.0, duration, true);
+    }
+
     fn volume(&self) -> f32 {
         self.sink.volume()
     }
@@ -138,10 +192,45 @@ impl AudioSinkPlayback for AudioSink {
 /// that source is unchanged, that translates to the audio restarting.
 #[derive(Component)]
 pub struct SpatialAudioSink {
-    pub(crate) sink: SpatialSink,
+    pub(crate) sink: Sink,
+    pub(crate) spatial: Mutex<SpatialState>,
+    pub(crate) gains: Arc<SpatialGains>,
+    pub(crate) playhead: Arc<Playhead>,
+    pub(crate) fade: Arc<Fade>,
+}
+
+/// The spatial state of a [`SpatialAudioSink`].
+pub(crate) struct SpatialState {
+    /// The positions of the sound relative to each listener.
+    positions: Vec<SpatialPositions>,
+    /// The positions of the previous update, to compute the doppler effect.
+    previous: Option<Vec<SpatialPositions>>,
+    settings: SpatialAudioEmitter,
+    occlusion: AudioOcclusion,
+    /// The speed set by the user, before the doppler effect.
+    speed: f32,
+    doppler: f32,
+    /// The number of output channels.
+    channels: u16,
 }
 
 impl AudioSinkPlayback for SpatialAudioSink {
+    fn position(&self) -> Duration {
+        self.playhead.position()
+    }
+
+    fn seek(&self, position: Duration) {
+        self.playhead.seek(position);
+    }
+
+    fn fade_to(&self, volume: f32, duration: Duration) {
+        self.fade.fade_to(volume, duration, false);
+    }
+
+    fn fade_out(&self, duration: Duration) {
+        self.fade.fade_to(0.0, duration, true);
+    }
+
     fn volume(&self) -> f32 {
         self.sink.volume()
     }
@@ -151,11 +240,13 @@ impl AudioSinkPlayback for SpatialAudioSink {
     }
 
     fn speed(&self) -> f32 {
-        self.sink.speed()
+        self.spatial.lock().unwrap().speed
     }
 
     fn set_speed(&self, speed: f32) {
-        self.sink.set_speed(speed);
+        let mut spatial = self.spatial.lock().unwrap();
+        spatial.speed = speed;
+        self.sink.set_speed(speed * spatial.doppler);
     }
 
     fn play(&self) {
@@ -180,22 +271,112 @@ impl AudioSinkPlayback for SpatialAudioSink {
 }
 
 impl SpatialAudioSink {
-    /// Set the two ears position.
+    pub(crate) fn new(
+        sink: Sink,
+        positions: Vec<SpatialPositions>,
+        settings: SpatialAudioEmitter,
+        occlusion: AudioOcclusion,
+        channels: u16,
+        playhead: Arc<Playhead>,
+        fade: Arc<Fade>,
+    ) -> Self {
+        let gains = Arc::new(SpatialGains::default());
+        gains.update(&positions, &settings, &occlusion);
+        let speed = sink.speed();
+        Self {
+            sink,
+            spatial: Mutex::new(SpatialState {
+                positions,
+                previous: None,
+                settings,
+                occlusion,
+                speed,
+                doppler: 1.0,
+                channels,
+            }),
+            gains,
+            playhead,
+            fade,
+        }
+    }
+
+    /// Plays `source`, panned and attenuated according to the positions of the emitter and the
+    /// listeners.
+    pub(crate) fn append<S: Source<Item = f32> + Send + 'static>(&self, source: S) {
+        let channels = self.spatial.lock().unwrap().channels;
+        self.sink
+            .append(SpatialSource::new(source, self.gains.clone(), channels));
+    }
+
+    /// Moves the emitter and the listeners, applies the doppler effect of their motion during
+    /// the last `delta` seconds, and applies the occlusion of the sound.
+    ///
+    /// The sink plays at a single speed, so the doppler effect follows the first listener.
+    pub(crate) fn update(
+        &self,
+        positions: Vec<SpatialPositions>,
+        settings: &SpatialAudioEmitter,
+        occlusion: &AudioOcclusion,
+        delta: f32,
+        speed_of_sound: f32,
+    ) {
+        let mut spatial = self.spatial.lock().unwrap();
+        if spatial.settings != *settings {
+            spatial.settings = settings.clone();
+        }
+        spatial.occlusion = *occlusion;
+        let doppler = match (positions.first(), spatial.previous.as_ref()) {
+            (Some(current), Some(previous)) if delta > 0.0 => match previous.first() {
+                Some(previous) => {
+                    current.doppler(previous, delta, settings.doppler_factor, speed_of_sound)
+                }
+                None => spatial.doppler,
+            },
+            _ => spatial.doppler,
+        };
+        if doppler != spatial.doppler {
+            spatial.doppler = doppler;
+            self.sink.set_speed(spatial.speed * doppler);
+        }
+        self.gains.update(&positions, settings, occlusion);
+        spatial.previous = Some(positions.clone());
+        spatial.positions = positions;
+    }
+
+    fn update_positions(&self, update: impl FnOnce(&mut [SpatialPositions])) {
+        let mut spatial = self.spatial.lock().unwrap();
+        update(&mut spatial.positions);
+        self.gains
+            .update(&spatial.positions, &spatial.settings, &spatial.occlusion);
+    }
+
+    /// Set the two ears position of the first listener.
     pub fn set_ears_position(&self, left_position: Vec3, right_position: Vec3) {
-        self.sink.set_left_ear_position(left_position.to_array());
-        self.sink.set_right_ear_position(right_position.to_array());
+        self.update_positions(|positions| {
+            if let Some(positions) = positions.first_mut() {
+                positions.left_ear = left_position;
+                positions.right_ear = right_position;
+            }
+        });
     }
 
-    /// Set the listener position, with an ear on each side separated by `gap`.
+    /// Set the position of the first listener, with an ear on each side separated by `gap`.
     pub fn set_listener_position(&self, position: Transform, gap: f32) {
-        self.set_ears_position(
-            position.translation + position.left() * gap / 2.0,
-            position.translation + position.right() * gap / 2.0,
-        );
+        self.update_positions(|positions| {
+            if let Some(positions) = positions.first_mut() {
+                positions.left_ear = position.translation + position.left() * gap / 2.0;
+                positions.right_ear = position.translation + position.right() * gap / 2.0;
+                positions.listener_forward = *position.forward();
+            }
+        });
     }
 
     /// Set the emitter position.
     pub fn set_emitter_position(&self, position: Vec3) {
-        self.sink.set_emitter_position(position.to_array());
+        self.update_positions(|positions| {
+            for positions in positions {
+                positions.emitter = position;
+            }
+        });
     }
 }
@@ -0,0 +1,886 @@
+use std::f32::consts::{FRAC_PI_2, PI, TAU};
+use std::sync::{
+    atomic::{AtomicU32, Ordering},
+    Arc, Mutex,
+};
+use std::time::Duration;
+
+use bevy_ecs::prelude::*;
+use bevy_math::{Vec2, Vec3};
+use bevy_reflect::prelude::*;
+use rodio::Source;
+
+use crate::bus::SMOOTHING_TIME;
+
+/// Below this length, directions are considered degenerate and ignored.
+const EPSILON: f32 = 1e-5;
+
+/// The radius of the head of the HRTF model, in meters.
+const HEAD_RADIUS: f32 = 0.0875;
+
+/// The speed of sound in air around the head of the HRTF model, in meters per second.
+///
+/// Unlike [`SpeedOfSound`], this isn't affected by the scale of the world.
+const HEAD_SPEED_OF_SOUND: f32 = 343.0;
+
+/// The maximum delay of the HRTF model, between the ears and in the outer ear, in seconds.
+const MAX_HRTF_DELAY: f32 = 0.0015;
+
+/// The reflections of the outer ear in the HRTF model of Brown and Duda: their reflection
+/// coefficient, and the `A`, `B` and `D` coefficients of their delay, in samples at 44.1kHz.
+const PINNA_REFLECTIONS: [(f32, f32, f32, f32); 5] = [
+    (0.5, 1.0, 2.0, 1.0),
+    (-1.0, 5.0, 4.0, 0.5),
+    (0.5, 5.0, 7.0, 0.5),
+    (-0.25, 5.0, 11.0, 0.5),
+    (0.25, 5.0, 13.0, 0.5),
+];
+
+/// How the volume of a spatial sound decreases with its distance to the listener.
+#[derive(Clone, Debug, PartialEq, Reflect)]
+pub enum Rolloff {
+    /// The volume decreases linearly from full volume at
+    /// [`SpatialAttenuation::min_distance`] to silence at
+    /// [`SpatialAttenuation::max_distance`].
+    Linear,
+    /// The volume is inversely proportional to the distance, like real world sounds.
+    ///
+    /// The volume is full at [`SpatialAttenuation::min_distance`], halves when the distance
+    /// doubles, and stops decreasing at [`SpatialAttenuation::max_distance`].
+    Logarithmic,
+    /// The volume follows a piecewise linear curve, whose points are distances and volumes,
+    /// sorted by distance. The distances are clamped between
+    /// [`SpatialAttenuation::min_distance`] and [`SpatialAttenuation::max_distance`].
+    Custom(Vec<Vec2>),
+}
+
+/// How the volume of a spatial sound decreases with its distance to the listener.
+///
+/// Distances are measured after applying the [`SpatialScale`](crate::SpatialScale).
+#[derive(Clone, Debug, PartialEq, Reflect)]
+pub struct SpatialAttenuation {
+    /// The distance under which the sound plays at full volume.
+    pub min_distance: f32,
+    /// The distance from which the volume stops decreasing.
+    pub max_distance: f32,
+    /// How the volume decreases between the two distances.
+    pub rolloff: Rolloff,
+}
+
+impl Default for SpatialAttenuation {
+    fn default() -> Self {
+        Self {
+            min_distance: 1.0,
+            max_distance: 100.0,
+            rolloff: Rolloff::Logarithmic,
+        }
+    }
+}
+
+impl SpatialAttenuation {
+    /// Gets the volume of a sound at `distance` from the listener.
+    pub fn volume(&self, distance: f32) -> f32 {
+        let min = self.min_distance.max(EPSILON);
+        let max = self.max_distance.max(min);
+        let distance = distance.clamp(min, max);
+        match &self.rolloff {
+            Rolloff::Linear if max > min => 1.0 - (distance - min) / (max - min),
+            Rolloff::Linear => 1.0,
+            Rolloff::Logarithmic => min / distance,
+            Rolloff::Custom(points) => {
+                let next = points.partition_point(|point| point.x < distance);
+                match (
+                    next.checked_sub(1).map(|i| points[i]),
+                    points.get(next).copied(),
+                ) {
+                    (Some(a), Some(b)) if b.x > a.x => {
+                        a.y + (b.y - a.y) * (distance - a.x) / (b.x - a.x)
+                    }
+                    (_, Some(point)) | (Some(point), None) => point.y,
+                    (None, None) => 1.0,
+                }
+            }
+        }
+    }
+}
+
+/// A cone in which a spatial sound is emitted at full volume, e.g. for loudspeakers or
+/// characters speaking.
+///
+/// The cone is centered on the forward direction of the emitter.
+#[derive(Clone, Copy, Debug, PartialEq, Reflect)]
+pub struct AudioCone {
+    /// The angle of the cone in which the sound plays at full volume, in radians.
+    pub inner_angle: f32,
+    /// The angle of the cone out of which the sound plays at
+    /// [`outer_volume`](Self::outer_volume), in radians.
+    ///
+    /// Between the two cones, the volume is interpolated.
+    pub outer_angle: f32,
+    /// The volume of the sound out of the outer cone.
+    pub outer_volume: f32,
+}
+
+impl AudioCone {
+    /// Gets the volume of a sound heard at `angle` from the forward direction of the emitter.
+    pub fn volume(&self, angle: f32) -> f32 {
+        let inner = self.inner_angle / 2.0;
+        let outer = (self.outer_angle / 2.0).max(inner);
+        if angle <= inner {
+            1.0
+        } else if angle >= outer {
+            self.outer_volume
+        } else {
+            1.0 + (self.outer_volume - 1.0) * (angle - inner) / (outer - inner)
+        }
+    }
+}
+
+/// Settings of a spatial sound, configuring how its volume and pitch change with its position
+/// relative to the listener.
+///
+/// Sounds without this component use its default value.
+#[derive(Component, Clone, Debug, Default, PartialEq, Reflect)]
+#[reflect(Component, Default)]
+pub struct SpatialAudioEmitter {
+    /// How the volume decreases with the distance to the listener.
+    pub attenuation: SpatialAttenuation,
+    /// If set, the sound is louder in front of the emitter.
+    pub cone: Option<AudioCone>,
+    /// How much the pitch changes when the emitter and the listener move towards or away from
+    /// each other. `1.0` is physically accurate, and `0.0` disables the doppler effect.
+    pub doppler_factor: f32,
+}
+
+/// How much a spatial sound is blocked by obstacles between its emitter and the listeners, which
+/// muffles it.
+///
+/// Bevy doesn't detect obstacles itself: gameplay systems set the [`amount`](Self::amount) of
+/// occlusion of the emitters, e.g. with raycasts from the listener, before the
+/// [`AudioPlaySet`](crate::AudioPlaySet). Changes are applied smoothly to the playing sounds.
+///
+/// ```
+/// # use bevy_ecs::prelude::*;
+/// # use bevy_math::Vec3;
+/// # use bevy_transform::prelude::*;
+/// # use bevy_audio::{AudioOcclusion, AudioPlaySet, SpatialListener};
+/// # fn is_wall_between(from: Vec3, to: Vec3) -> bool { false }
+/// fn occlude_sounds(
+///     listener: Query<&GlobalTransform, With<SpatialListener>>,
+///     mut emitters: Query<(&GlobalTransform, &mut AudioOcclusion)>,
+/// ) {
+///     let Ok(listener) = listener.get_single() else {
+///         return;
+///     };
+///     for (transform, mut occlusion) in &mut emitters {
+///         let occluded = is_wall_between(listener.translation(), transform.translation());
+///         occlusion.amount = if occluded { 1.0 } else { 0.0 };
+///     }
+/// }
+/// # bevy_ecs::system::assert_is_system(occlude_sounds);
+/// ```
+#[derive(Component, Clone, Copy, Debug, PartialEq, Reflect)]
+#[reflect(Component, Default)]
+pub struct AudioOcclusion {
+    /// How much the sound is blocked, from `0.0` for an unobstructed sound to `1.0` for a fully
+    /// occluded sound.
+    pub amount: f32,
+    /// The volume of a fully occluded sound.
+    pub occluded_volume: f32,
+    /// The frequency above which a fully occluded sound is attenuated, in hertz.
+    pub occluded_cutoff: f32,
+}
+
+impl Default for AudioOcclusion {
+    fn default() -> Self {
+        Self {
+            amount: 0.0,
+            occluded_volume: 0.5,
+            occluded_cutoff: 800.0,
+        }
+    }
+}
+
+impl AudioOcclusion {
+    /// The cutoff frequency of an unobstructed sound, at the limit of human hearing.
+    const MAX_CUTOFF: f32 = 20000.0;
+
+    /// Creates an occlusion of `amount`, with the default muffling.
+    pub fn new(amount: f32) -> Self {
+        Self {
+            amount,
+            ..Default::default()
+        }
+    }
+
+    /// Gets the volume of the sound, interpolated between full volume and the
+    /// [`occluded_volume`](Self::occluded_volume).
+    pub fn volume(&self) -> f32 {
+        let amount = self.amount.clamp(0.0, 1.0);
+        1.0 + (self.occluded_volume - 1.0) * amount
+    }
+
+    /// Gets the frequency above which the sound is attenuated, interpolated logarithmically
+    /// towards the [`occluded_cutoff`](Self::occluded_cutoff), or `None` if the sound isn't
+    /// occluded.
+    pub fn cutoff(&self) -> Option<f32> {
+        let amount = self.amount.clamp(0.0, 1.0);
+        (amount > 0.0).then(|| {
+            let cutoff = self.occluded_cutoff.clamp(1.0, Self::MAX_CUTOFF);
+            Self::MAX_CUTOFF * (cutoff / Self::MAX_CUTOFF).powf(amount)
+        })
+    }
+}
+
+/// The speed of sound, used to compute the doppler effect of the [`SpatialAudioEmitter`]s.
+///
+/// This is measured after applying the [`SpatialScale`](crate::SpatialScale). Defaults to `343.0`,
+/// the speed of sound in air in meters per second.
+#[derive(Resource, Clone, Copy, Debug, Reflect)]
+#[reflect(Resource)]
+pub struct SpeedOfSound(pub f32);
+
+impl Default for SpeedOfSound {
+    fn default() -> Self {
+        Self(343.0)
+    }
+}
+
+/// The positions of a spatial sound and of the ears of one of its listeners.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct SpatialPositions {
+    pub(crate) emitter: Vec3,
+    /// The forward direction of the emitter.
+    pub(crate) forward: Vec3,
+    pub(crate) left_ear: Vec3,
+    pub(crate) right_ear: Vec3,
+    /// The forward direction of the listener.
+    pub(crate) listener_forward: Vec3,
+    /// Whether the listener renders sounds with an HRTF.
+    pub(crate) hrtf: bool,
+    /// The output channels of the left and right ears of the listener.
+    pub(crate) channels: [u16; 2],
+}
+
+impl SpatialPositions {
+    fn listener(&self) -> Vec3 {
+        (self.left_ear + self.right_ear) / 2.0
+    }
+
+    /// Computes the volume of the left and right channels.
+    pub(crate) fn gains(&self, settings: &SpatialAudioEmitter) -> [f32; 2] {
+        let listener = self.listener();
+        let mut volume = settings.attenuation.volume(self.emitter.distance(listener));
+        if let (Some(cone), Some(forward), Some(direction)) = (
+            settings.cone,
+            self.forward.try_normalize(),
+            (listener - self.emitter).try_normalize(),
+        ) {
+            volume *= cone.volume(forward.angle_between(direction));
+        }
+        if self.hrtf {
+            // The HRTF filters the sound of each ear instead.
+            return [volume; 2];
+        }
+
+        // Pan towards the nearest ear, without fully silencing the other ear.
+        let gap = self.left_ear.distance(self.right_ear).max(EPSILON);
+        let pan = ((self.emitter.distance(self.right_ear) - self.emitter.distance(self.left_ear))
+            / gap)
+            .clamp(-1.0, 1.0);
+        [
+            volume * ((pan + 1.0) / 4.0 + 0.5),
+            volume * ((1.0 - pan) / 4.0 + 0.5),
+        ]
+    }
+
+    /// Computes the filters of the ears if the listener uses an HRTF, following the structural
+    /// model of Brown and Duda: a delay between the ears, the shadow of the head and the
+    /// reflections of the outer ears.
+    pub(crate) fn ear_filters(&self) -> Option<[EarFilter; 2]> {
+        if !self.hrtf {
+            return None;
+        }
+        let right = (self.right_ear - self.left_ear)
+            .try_normalize()
+            .unwrap_or(Vec3::X);
+        let forward = (self.listener_forward - right * self.listener_forward.dot(right))
+            .try_normalize()
+            .unwrap_or_else(|| Vec3::Y.cross(right).try_normalize().unwrap_or(Vec3::NEG_Z));
+        let up = right.cross(forward);
+        let direction = (self.emitter - self.listener())
+            .try_normalize()
+            .unwrap_or(forward);
+
+        // The angle from the median plane, positive on the right.
+        let lateral = direction.dot(right).clamp(-1.0, 1.0).asin();
+        // The angle around the interaural axis, from the front to above, and behind.
+        let mut elevation = direction.dot(up).atan2(direction.dot(forward));
+        if elevation < -FRAC_PI_2 {
+            elevation += TAU;
+        }
+        let pinna = PINNA_REFLECTIONS.map(|(_, a, b, d)| {
+            (a * (lateral / 2.0).cos() * (d * (FRAC_PI_2 - elevation)).sin() + b) / 44100.0
+        });
+        // The sound goes around the head to reach the far ear.
+        let delay = HEAD_RADIUS / HEAD_SPEED_OF_SOUND * (lateral.abs() + lateral.abs().sin());
+
+        let ear = |side: f32| EarFilter {
+            delay: if lateral * side < 0.0 { delay } else { 0.0 },
+            shadow: head_shadow(direction.dot(right * side).clamp(-1.0, 1.0).acos()),
+            pinna,
+        };
+        Some([ear(-1.0), ear(1.0)])
+    }
+
+    /// Computes the pitch change of the doppler effect, given the positions of the previous frame.
+    pub(crate) fn doppler(
+        &self,
+        previous: &SpatialPositions,
+        delta: f32,
+        doppler_factor: f32,
+        speed_of_sound: f32,
+    ) -> f32 {
+        let listener = self.listener();
+        let Some(direction) = (listener - self.emitter).try_normalize() else {
+            return 1.0;
+        };
+        if delta <= 0.0 || doppler_factor == 0.0 || speed_of_sound <= 0.0 {
+            return 1.0;
+        }
+        let emitter_speed = (self.emitter - previous.emitter).dot(direction) / delta;
+        let listener_speed = (listener - previous.listener()).dot(direction) / delta;
+        // Sounds can't move faster than the speed of sound.
+        let limit = speed_of_sound * 0.9;
+        let emitter_speed = (emitter_speed * doppler_factor).clamp(-limit, limit);
+        let listener_speed = (listener_speed * doppler_factor).clamp(-limit, limit);
+        (speed_of_sound - listener_speed) / (speed_of_sound - emitter_speed)
+    }
+}
+
+/// The gain of the high frequencies reaching an ear, given the angle between the direction of
+/// the sound and the direction of the ear.
+fn head_shadow(incidence: f32) -> f32 {
+    const MIN_GAIN: f32 = 0.1;
+    const MIN_INCIDENCE: f32 = 5.0 * PI / 6.0;
+    (1.0 + MIN_GAIN / 2.0) + (1.0 - MIN_GAIN / 2.0) * (incidence / MIN_INCIDENCE * PI).cos()
+}
+
+/// The HRTF filter of an ear.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub(crate) struct EarFilter {
+    /// The delay of the sound reaching the ear, in seconds.
+    delay: f32,
+    /// The gain of the high frequencies, reduced when the head is in the way.
+    shadow: f32,
+    /// The delays of the reflections of the outer ear, in seconds.
+    pinna: [f32; 5],
+}
+
+impl EarFilter {
+    /// Moves the filter towards `target` by `amount`, between `0.0` and `1.0`.
+    fn approach(&mut self, target: &EarFilter, amount: f32) {
+        self.delay += (target.delay - self.delay) * amount;
+        self.shadow += (target.shadow - self.shadow) * amount;
+        for (delay, target) in self.pinna.iter_mut().zip(target.pinna) {
+            *delay += (target - *delay) * amount;
+        }
+    }
+}
+
+/// The volume of the left and right channels of a spatial sound for a listener, the filters of
+/// the ears if the listener uses an HRTF, the occlusion filter, and the output channels of the
+/// listener.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct ListenerGains {
+    gains: [f32; 2],
+    ear_filters: Option<[EarFilter; 2]>,
+    /// The cutoff frequency of the low-pass filter of the occlusion.
+    cutoff: Option<f32>,
+    channels: [u16; 2],
+}
+
+/// The [`ListenerGains`] of a spatial sound for each listener, shared with the audio thread.
+#[derive(Debug, Default)]
+pub(crate) struct SpatialGains {
+    listeners: Mutex<Vec<ListenerGains>>,
+    version: AtomicU32,
+}
+
+impl SpatialGains {
+    /// Updates the gains and the filters from the positions of the sound and the listeners, and
+    /// from the occlusion of the sound.
+    pub(crate) fn update(
+        &self,
+        positions: &[SpatialPositions],
+        settings: &SpatialAudioEmitter,
+        occlusion: &AudioOcclusion,
+    ) {
+        let volume = occlusion.volume();
+        self.set(
+            positions
+                .iter()
+                .map(|positions| ListenerGains {
+                    gains: positions.gains(settings).map(|gain| gain * volume),
+                    ear_filters: positions.ear_filters(),
+                    cutoff: occlusion.cutoff(),
+                    channels: positions.channels,
+                })
+                .collect(),
+        );
+    }
+
+    pub(crate) fn set(&self, gains: Vec<ListenerGains>) {
+        let mut listeners = self.listeners.lock().unwrap();
+        if *listeners != gains {
+            *listeners = gains;
+            self.version.fetch_add(1, Ordering::Release);
+        }
+    }
+}
+
+/// The state of the HRTF filters of a spatial sound.
+struct HrtfState {
+    /// The last samples of the sound, to delay them.
+    history: Vec<f32>,
+    /// The index of the current sample in `history`.
+    position: usize,
+    sample_rate: u32,
+    /// The current filters, moving towards their targets to avoid clicks.
+    ears: [EarFilter; 2],
+    /// The last input and output of the head shadow filter of each ear.
+    shadow: [(f32, f32); 2],
+}
+
+impl HrtfState {
+    fn new(sample_rate: u32, ears: [EarFilter; 2]) -> Self {
+        let len = (MAX_HRTF_DELAY * sample_rate as f32).ceil() as usize + 2;
+        Self {
+            history: vec![0.0; len],
+            position: 0,
+            sample_rate,
+            ears,
+            shadow: [(0.0, 0.0); 2],
+        }
+    }
+
+    /// Reads the sample from `delay` seconds ago, interpolating between samples.
+    fn read(&self, delay: f32) -> f32 {
+        let len = self.history.len();
+        let delay = (delay * self.sample_rate as f32).clamp(0.0, (len - 2) as f32);
+        let whole = delay as usize;
+        let a = self.history[(self.position + len - whole) % len];
+        let b = self.history[(self.position + len - whole - 1) % len];
+        a + (b - a) * (delay - whole as f32)
+    }
+
+    fn process(&mut self, sample: f32, targets: &[EarFilter; 2]) -> [f32; 2] {
+        self.history[self.position] = sample;
+        let sample_rate = self.sample_rate as f32;
+        let smoothing = 1.0 / (SMOOTHING_TIME * sample_rate).max(1.0);
+        // The head shadow is a one-pole, one-zero filter, discretized by bilinear transform.
+        let pole = HEAD_SPEED_OF_SOUND / HEAD_RADIUS;
+        let k = 2.0 * sample_rate;
+
+        let mut output = [0.0; 2];
+        for (i, target) in targets.iter().enumerate() {
+            self.ears[i].approach(target, smoothing);
+            let ear = self.ears[i];
+            let mut x = self.read(ear.delay);
+            for ((reflection, ..), delay) in PINNA_REFLECTIONS.iter().zip(ear.pinna) {
+                x += reflection * self.read(ear.delay + delay);
+            }
+            let (x1, y1) = self.shadow[i];
+            let b0 = (pole + ear.shadow * k) / (pole + k);
+            let b1 = (pole - ear.shadow * k) / (pole + k);
+            let a1 = (pole - k) / (pole + k);
+            let y = b0 * x + b1 * x1 - a1 * y1;
+            self.shadow[i] = (x, y);
+            output[i] = y;
+        }
+        self.position = (self.position + 1) % self.history.len();
+        output
+    }
+}
+
+/// The state of a spatial sound for a listener.
+struct ListenerState {
+    /// The current volumes, moving towards their targets to avoid clicks.
+    current: [f32; 2],
+    hrtf: Option<HrtfState>,
+    /// The last output of the low-pass filter of the occlusion.
+    occlusion: f32,
+}
+
+/// A [`Source`] mixing down the channels of its input, and mixing it into the output channels of
+/// each listener, applying the volumes of the left and right channels of the spatial sound for
+/// the listener, and the HRTF filters of its ears.
+pub(crate) struct SpatialSource<I> {
+    input: I,
+    gains: Arc<SpatialGains>,
+    targets: Vec<ListenerGains>,
+    version: u32,
+    listeners: Vec<ListenerState>,
+    /// The number of output channels.
+    channels: u16,
+    /// The current output frame, and the index of the next sample to return.
+    frame: Vec<f32>,
+    position: usize,
+}
+
+impl<I: Source<Item = f32>> SpatialSource<I> {
+    pub(crate) fn new(input: I, gains: Arc<SpatialGains>, channels: u16) -> Self {
+        let targets = gains.listeners.lock().unwrap().clone();
+        let version = gains.version.load(Ordering::Acquire);
+        let channels = channels.max(1);
+        Self {
+            input,
+            gains,
+            targets,
+            version,
+            listeners: Vec::new(),
+            channels,
+            frame: vec![0.0; channels as usize],
+            position: channels as usize,
+        }
+    }
+
+    fn sync_targets(&mut self) {
+        let version = self.gains.version.load(Ordering::Acquire);
+        if version == self.version {
+            return;
+        }
+        // Never block the audio thread: try again on the next sample.
+        if let Ok(listeners) = self.gains.listeners.try_lock() {
+            self.targets.clone_from(&listeners);
+            self.version = version;
+        }
+    }
+
+    fn pending(&self) -> usize {
+        self.frame.len() - self.position
+    }
+}
+
+impl<I: Source<Item = f32>> Iterator for SpatialSource<I> {
+    type Item = f32;
+
+    #[inline]
+    fn next(&mut self) -> Option<f32> {
+        if let Some(&sample) = self.frame.get(self.position) {
+            self.position += 1;
+            return Some(sample);
+        }
+        let channels = self.input.channels().max(1);
+        let mut sample = self.input.next()?;
+        for _ in 1..channels {
+            sample += self.input.next().unwrap_or(0.0);
+        }
+        sample /= channels as f32;
+
+        self.sync_targets();
+        // New listeners start at their target volumes.
+        self.listeners.truncate(self.targets.len());
+        for target in &self.targets[self.listeners.len()..] {
+            self.listeners.push(ListenerState {
+                current: target.gains,
+                hrtf: None,
+                occlusion: sample,
+            });
+        }
+
+        let sample_rate = self.input.sample_rate();
+        let step = 1.0 / (SMOOTHING_TIME * sample_rate as f32).max(1.0);
+        self.frame.fill(0.0);
+        for (state, target) in self.listeners.iter_mut().zip(&self.targets) {
+            // The occlusion is a one-pole low-pass filter, which follows the sample when the
+            // sound isn't occluded so the filter starts without a click.
+            let sample = match target.cutoff {
+                Some(cutoff) => {
+                    let alpha = 1.0 - (-TAU * cutoff / sample_rate as f32).exp();
+                    state.occlusion += alpha * (sample - state.occlusion);
+                    state.occlusion
+                }
+                None => {
+                    state.occlusion = sample;
+                    sample
+                }
+            };
+            let ears = match target.ear_filters {
+                Some(ear_filters) => {
+                    if !matches!(&state.hrtf, Some(hrtf) if hrtf.sample_rate == sample_rate) {
+                        state.hrtf = Some(HrtfState::new(sample_rate, ear_filters));
+                    }
+                    state.hrtf.as_mut().unwrap().process(sample, &ear_filters)
+                }
+                None => {
+                    state.hrtf = None;
+                    [sample; 2]
+                }
+            };
+            let ears = ears.iter().zip(&mut state.current);
+            for ((ear, current), (gain, channel)) in
+                ears.zip(target.gains.into_iter().zip(target.channels))
+            {
+                *current = if *current < gain {
+                    (*current + step).min(gain)
+                } else {
+                    (*current - step).max(gain)
+                };
+                // Channels the output doesn't have are dropped.
+                if let Some(output) = self.frame.get_mut(channel as usize) {
+                    *output += ear * *current;
+                }
+            }
+        }
+        self.position = 1;
+        Some(self.frame[0])
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let channels = self.input.channels().max(1) as usize;
+        let output = self.channels as usize;
+        let pending = self.pending();
+        let (min, max) = self.input.size_hint();
+        (
+            min / channels * output + pending,
+            max.map(|max| max / channels * output + pending),
+        )
+    }
+}
+
+impl<I: Source<Item = f32>> Source for SpatialSource<I> {
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        let channels = self.input.channels().max(1) as usize;
+        let pending = self.pending();
+        self.input
+            .current_frame_len()
+            .map(|len| len / channels * self.channels as usize + pending)
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rodio::buffer::SamplesBuffer;
+
+    #[test]
+    fn attenuation_follows_rolloff() {
+        let mut attenuation = SpatialAttenuation {
+            min_distance: 2.0,
+            max_distance: 10.0,
+            rolloff: Rolloff::Logarithmic,
+        };
+        assert_eq!(attenuation.volume(1.0), 1.0);
+        assert_eq!(attenuation.volume(4.0), 0.5);
+        assert_eq!(attenuation.volume(100.0), 0.2);
+
+        attenuation.rolloff = Rolloff::Linear;
+        assert_eq!(attenuation.volume(6.0), 0.5);
+        assert_eq!(attenuation.volume(100.0), 0.0);
+
+        attenuation.rolloff = Rolloff::Custom(vec![Vec2::new(2.0, 1.0), Vec2::new(4.0, 0.0)]);
+        assert_eq!(attenuation.volume(3.0), 0.5);
+        assert_eq!(attenuation.volume(8.0), 0.0);
+    }
+
+    #[test]
+    fn gains_pan_and_apply_cone() {
+        let positions = SpatialPositions {
+            emitter: Vec3::new(-10.0, 0.0, 0.0),
+            forward: Vec3::X,
+            left_ear: Vec3::new(-1.0, 0.0, 0.0),
+            right_ear: Vec3::new(1.0, 0.0, 0.0),
+            ..Default::default()
+        };
+        let mut settings = SpatialAudioEmitter {
+            attenuation: SpatialAttenuation {
+                min_distance: 20.0,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert_eq!(positions.gains(&settings), [1.0, 0.5]);
+
+        settings.cone = Some(AudioCone {
+            inner_angle: 1.0,
+            outer_angle: 2.0,
+            outer_volume: 0.2,
+        });
+        assert_eq!(positions.gains(&settings), [1.0, 0.5]);
+        let behind = SpatialPositions {
+            forward: Vec3::NEG_X,
+            ..positions
+        };
+        assert_eq!(behind.gains(&settings), [0.2, 0.1]);
+    }
+
+    #[test]
+    fn doppler_raises_pitch_of_approaching_sounds() {
+        let previous = SpatialPositions {
+            emitter: Vec3::new(-100.0, 0.0, 0.0),
+            ..Default::default()
+        };
+        let current = SpatialPositions {
+            emitter: Vec3::new(-50.0, 0.0, 0.0),
+            ..Default::default()
+        };
+        let pitch = current.doppler(&previous, 1.0, 1.0, 100.0);
+        assert_eq!(pitch, 2.0);
+        assert_eq!(current.doppler(&previous, 1.0, 0.0, 100.0), 1.0);
+        assert!(previous.doppler(&current, 1.0, 1.0, 100.0) < 1.0);
+    }
+
+    #[test]
+    fn spatial_source_outputs_panned_stereo() {
+        let gains = Arc::new(SpatialGains::default());
+        gains.set(vec![ListenerGains {
+            gains: [1.0, 0.5],
+            ear_filters: None,
+            cutoff: None,
+            channels: [0, 1],
+        }]);
+        let source = SamplesBuffer::new(2, 44100, vec![1.0f32, 0.0, 0.5, 0.5]);
+        let source = SpatialSource::new(source, gains, 2);
+        assert_eq!(source.collect::<Vec<_>>(), [0.5, 0.25, 0.5, 0.25]);
+    }
+
+    #[test]
+    fn spatial_source_mixes_listeners_into_their_channels() {
+        let listener = |gains, channels| ListenerGains {
+            gains,
+            ear_filters: None,
+            cutoff: None,
+            channels,
+        };
+        let gains = Arc::new(SpatialGains::default());
+        gains.set(vec![
+            listener([1.0, 0.5], [0, 1]),
+            listener([0.25, 0.75], [2, 3]),
+            listener([1.0, 1.0], [1, 4]),
+        ]);
+        let source = SamplesBuffer::new(1, 44100, vec![1.0f32, 0.5]);
+        let source = SpatialSource::new(source, gains, 4);
+        assert_eq!(source.channels(), 4);
+        assert_eq!(
+            source.collect::<Vec<_>>(),
+            [1.0, 1.5, 0.25, 0.75, 0.5, 0.75, 0.125, 0.375]
+        );
+    }
+
+    #[test]
+    fn hrtf_delays_and_shadows_the_far_ear() {
+        let positions = SpatialPositions {
+            emitter: Vec3::new(1.0, 0.0, 0.0),
+            left_ear: Vec3::new(-0.1, 0.0, 0.0),
+            right_ear: Vec3::new(0.1, 0.0, 0.0),
+            listener_forward: Vec3::NEG_Z,
+            hrtf: true,
+            channels: [0, 1],
+            ..Default::default()
+        };
+        let [left, right] = positions.ear_filters().unwrap();
+        assert!(left.delay > 0.0006);
+        assert_eq!(right.delay, 0.0);
+        assert!(left.shadow < 1.0 && right.shadow > 1.0);
+
+        let gains = Arc::new(SpatialGains::default());
+        gains.update(
+            std::slice::from_ref(&positions),
+            &SpatialAudioEmitter::default(),
+            &AudioOcclusion::default(),
+        );
+        let mut impulse = vec![0.0f32; 100];
+        impulse[0] = 1.0;
+        let output =
+            SpatialSource::new(SamplesBuffer::new(1, 44100, impulse), gains, 2).collect::<Vec<_>>();
+        let arrival = |channel: usize| {
+            output
+                .iter()
+                .skip(channel)
+                .step_by(2)
+                .position(|sample| sample.abs() > 0.1)
+        };
+        assert_eq!(arrival(1), Some(0));
+        assert!(arrival(0).unwrap() >= 26);
+    }
+
+    #[test]
+    fn occlusion_muffles_sounds() {
+        let occlusion = AudioOcclusion::default();
+        assert_eq!(occlusion.volume(), 1.0);
+        assert_eq!(occlusion.cutoff(), None);
+        let occlusion = AudioOcclusion::new(1.0);
+        assert_eq!(occlusion.volume(), 0.5);
+        assert_eq!(occlusion.cutoff(), Some(800.0));
+        let cutoff = AudioOcclusion::new(0.5).cutoff().unwrap();
+        assert!((cutoff - 4000.0).abs() < 1.0);
+
+        let positions = SpatialPositions {
+            left_ear: Vec3::new(-1.0, 0.0, 0.0),
+            right_ear: Vec3::new(1.0, 0.0, 0.0),
+            channels: [0, 1],
+            ..Default::default()
+        };
+        let gains = Arc::new(SpatialGains::default());
+        gains.update(
+            std::slice::from_ref(&positions),
+            &SpatialAudioEmitter::default(),
+            &AudioOcclusion {
+                amount: 1.0,
+                occluded_volume: 1.0,
+                occluded_cutoff: 100.0,
+            },
+        );
+        // A high frequency square wave is attenuated, unlike a constant signal. Both channels
+        // have a gain of 0.75, as the emitter is between the ears.
+        let square = (0..2000).map(|i| if i % 2 == 0 { 1.0 } else { -1.0 });
+        let output = SpatialSource::new(
+            SamplesBuffer::new(1, 44100, square.collect::<Vec<f32>>()),
+            gains.clone(),
+            2,
+        );
+        assert!(output.skip(2000).all(|sample| sample.abs() < 0.05));
+        let constant = SamplesBuffer::new(1, 44100, vec![1.0f32; 200]);
+        let output = SpatialSource::new(constant, gains, 2).collect::<Vec<_>>();
+        assert!(output.iter().all(|sample| *sample == 0.75));
+    }
+
+    #[test]
+    fn hrtf_distinguishes_front_and_back() {
+        let front = SpatialPositions {
+            emitter: Vec3::new(0.0, 0.0, -10.0),
+            left_ear: Vec3::new(-0.1, 0.0, 0.0),
+            right_ear: Vec3::new(0.1, 0.0, 0.0),
+            listener_forward: Vec3::NEG_Z,
+            hrtf: true,
+            ..Default::default()
+        };
+        let back = SpatialPositions {
+            emitter: Vec3::new(0.0, 0.0, 10.0),
+            ..front.clone()
+        };
+        let [front_left, front_right] = front.ear_filters().unwrap();
+        let [back_left, _] = back.ear_filters().unwrap();
+        assert_eq!(front_left, front_right);
+        assert_ne!(front_left.pinna, back_left.pinna);
+        assert_eq!(front.gains(&SpatialAudioEmitter::default()), [0.1, 0.1],);
+    }
+}
@@ -0,0 +1,660 @@
+use crate::{procedural::SampleRingBuffer, AudioSourceBundle, Decodable};
+use bevy_asset::{Asset, AssetPath, AssetServer};
+use bevy_reflect::TypePath;
+use bevy_tasks::IoTaskPool;
+use bevy_utils::{
+    tracing::{error, warn},
+    HashMap,
+};
+use futures_lite::{AsyncRead, AsyncReadExt};
+use rodio::Source;
+use std::collections::VecDeque;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Condvar, Mutex, OnceLock,
+};
+use std::time::Duration;
+
+/// The number of frames of silence played at once while buffering.
+const SILENCE_FRAMES: usize = 512;
+
+/// The buffering of a [`StreamingAudio`].
+#[derive(Clone, Copy, Debug)]
+pub struct StreamingSettings {
+    /// The duration of audio decoded before the sound starts playing, and before it resumes after
+    /// running out of decoded audio.
+    pub start_buffer: Duration,
+    /// The maximum duration of audio decoded ahead of playback.
+    pub max_buffer: Duration,
+    /// Keeps all the downloaded bytes, instead of dropping them once they're decoded, so that
+    /// sounds streamed [from a reader](StreamingAudio::from_reader) can be played again, seeked
+    /// or looped.
+    pub keep_bytes: bool,
+}
+
+impl Default for StreamingSettings {
+    fn default() -> Self {
+        Self {
+            start_buffer: Duration::from_millis(500),
+            max_buffer: Duration::from_secs(5),
+            keep_bytes: false,
+        }
+    }
+}
+
+/// A sound played while its bytes are downloaded, e.g. a long music track or a voice chat stream.
+///
+/// The sound is decoded on a separate thread while it plays, and plays silence until enough of it
+/// is decoded, as set by its [`StreamingSettings`]. Only
+/// [`max_buffer`](StreamingSettings::max_buffer) of the sound is decoded ahead, and the
+/// downloaded bytes are dropped once every playback of the sound decoded them, so long music
+/// tracks don't take much memory.
+///
+/// Seeking with [`AudioSinkPlayback::seek`](crate::AudioSinkPlayback::seek) decodes the sound
+/// again from its start, skipping the sound before the new position on the decoding thread.
+/// Looping sounds are decoded again at each loop, and the next loop is decoded ahead so the sound
+/// loops seamlessly between its [loop points](crate::PlaybackSettings::loop_start). The playback
+/// position doesn't advance while the sound waits for its samples to be decoded.
+///
+/// Decoding the sound again after its first bytes were dropped downloads it again when it's
+/// streamed [from a path](Self::from_path). Sounds streamed [from a reader](Self::from_reader)
+/// can't be downloaded again, so they can only be played once, unless
+/// [`keep_bytes`](StreamingSettings::keep_bytes) is set.
+///
+/// Streaming needs threads, so it's unavailable on the web.
+#[derive(Asset, TypePath, Clone)]
+pub struct StreamingAudio {
+    /// The bytes of the latest download of the sound.
+    bytes: Arc<Mutex<Arc<ByteStream>>>,
+    /// Downloads the sound again.
+    download: Option<Arc<dyn Fn() -> Arc<ByteStream> + Send + Sync>>,
+    settings: StreamingSettings,
+}
+
+impl std::fmt::Debug for StreamingAudio {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StreamingAudio")
+            .field("downloaded", &self.downloaded())
+            .field("settings", &self.settings)
+            .finish_non_exhaustive()
+    }
+}
+
+impl StreamingAudio {
+    /// Streams the sound read from `reader`, which is read on the [`IoTaskPool`].
+    pub fn from_reader(
+        reader: impl AsyncRead + Unpin + Send + 'static,
+        settings: StreamingSettings,
+    ) -> Self {
+        let bytes = Arc::new(ByteStream::new(settings.keep_bytes));
+        let stream = bytes.clone();
+        IoTaskPool::get()
+            .spawn(async move { stream.download(reader).await })
+            .detach();
+        Self {
+            bytes: Arc::new(Mutex::new(bytes)),
+            download: None,
+            settings,
+        }
+    }
+
+    /// Streams the sound at `path`, read from its [`AssetSource`](bevy_asset::io::AssetSource).
+    ///
+    /// This streams URLs when the asset source reads them over HTTP.
+    pub fn from_path<'a>(
+        asset_server: &AssetServer,
+        path: impl Into<AssetPath<'a>>,
+        settings: StreamingSettings,
+    ) -> Self {
+        let server = asset_server.clone();
+        let path = path.into().into_owned();
+        let keep_bytes = settings.keep_bytes;
+        let download = move || ByteStream::download_path(server.clone(), path.clone(), keep_bytes);
+        Self {
+            bytes: Arc::new(Mutex::new(download())),
+            download: Some(Arc::new(download)),
+            settings,
+        }
+    }
+
+    /// The number of bytes of the sound downloaded so far.
+    pub fn downloaded(&self) -> usize {
+        self.bytes().state.lock().unwrap().end()
+    }
+
+    /// Returns `true` if the whole sound is downloaded.
+    pub fn is_downloaded(&self) -> bool {
+        self.bytes().state.lock().unwrap().finished
+    }
+
+    /// The buffering of the sound.
+    pub fn settings(&self) -> StreamingSettings {
+        self.settings
+    }
+
+    fn bytes(&self) -> Arc<ByteStream> {
+        self.bytes.lock().unwrap().clone()
+    }
+
+    /// Starts reading the sound from its first byte for `decoded`, downloading the sound again if
+    /// its first bytes were dropped.
+    fn reader(&self, decoded: &Arc<DecodedStream>) -> Option<ByteReader> {
+        let mut bytes = self.bytes.lock().unwrap();
+        if let Some(reader) = ByteReader::new(bytes.clone(), decoded.clone()) {
+            return Some(reader);
+        }
+        *bytes = self.download.as_ref()?();
+        ByteReader::new(bytes.clone(), decoded.clone())
+    }
+
+    /// Decodes the sound from `start` on a new thread.
+    fn decode_from(&self, start: Duration) -> StreamingDecoder {
+        let stream = Arc::new(DecodedStream::default());
+        let settings = self.settings;
+        let Some(reader) = self.reader(&stream) else {
+            warn!("Can't play streamed audio again, its bytes were dropped as it played");
+            stream.failed.store(true, Ordering::Release);
+            return StreamingDecoder::new(stream, self.bytes(), settings);
+        };
+        let bytes = reader.stream.clone();
+        let decoding = stream.clone();
+        if let Err(err) = std::thread::Builder::new()
+            .name("audio stream decoder".to_string())
+            .spawn(move || decoding.decode(reader, settings, start))
+        {
+            warn!("Failed to start decoding streamed audio: {err}");
+            stream.failed.store(true, Ordering::Release);
+        }
+        StreamingDecoder::new(stream, bytes, settings)
+    }
+}
+
+/// Bundle for playing a [`StreamingAudio`].
+pub type StreamingAudioBundle = AudioSourceBundle<StreamingAudio>;
+
+#[derive(Default)]
+struct ByteState {
+    /// The downloaded bytes that weren't dropped yet.
+    bytes: VecDeque<u8>,
+    /// The number of bytes dropped before `bytes`.
+    offset: usize,
+    finished: bool,
+    /// The positions of the readers that stream the bytes, by reader id. Bytes before all these
+    /// positions are dropped.
+    readers: HashMap<usize, usize>,
+    next_reader: usize,
+    /// Set to never drop bytes.
+    keep_bytes: bool,
+}
+
+impl ByteState {
+    /// The number of bytes downloaded so far.
+    fn end(&self) -> usize {
+        self.offset + self.bytes.len()
+    }
+}
+
+/// The bytes of a sound, growing while it's downloaded, and shrinking as they're decoded.
+#[derive(Default)]
+struct ByteStream {
+    state: Mutex<ByteState>,
+    /// Notified when bytes are added, when the download finishes, or when a decoder is dropped.
+    changed: Condvar,
+}
+
+impl ByteStream {
+    fn new(keep_bytes: bool) -> Self {
+        Self {
+            state: Mutex::new(ByteState {
+                keep_bytes,
+                ..Default::default()
+            }),
+            changed: Condvar::new(),
+        }
+    }
+
+    /// Downloads the sound at `path` on the [`IoTaskPool`].
+    fn download_path(server: AssetServer, path: AssetPath<'static>, keep_bytes: bool) -> Arc<Self> {
+        let bytes = Arc::new(ByteStream::new(keep_bytes));
+        let stream = bytes.clone();
+        IoTaskPool::get()
+            .spawn(async move {
+                let Ok(source) = server.get_source(path.source()) else {
+                    error!(
+                        "Failed to stream {path}. AssetSource {:?} does not exist",
+                        path.source()
+                    );
+                    stream.finish();
+                    return;
+                };
+                match source.reader().read(path.path()).await {
+                    Ok(reader) => stream.download(reader).await,
+                    Err(err) => {
+                        error!("Failed to stream {path}: {err}");
+                        stream.finish();
+                    }
+                }
+            })
+            .detach();
+        bytes
+    }
+
+    async fn download(&self, mut reader: impl AsyncRead + Unpin) {
+        let mut chunk = [0; 16 * 1024];
+        loop {
+            match reader.read(&mut chunk).await {
+                Ok(0) => break,
+                Ok(read) => {
+                    self.state.lock().unwrap().bytes.extend(&chunk[..read]);
+                    self.changed.notify_all();
+                }
+                Err(err) => {
+                    error!("Failed to stream audio: {err}");
+                    break;
+                }
+            }
+        }
+        self.finish();
+    }
+
+    fn finish(&self) {
+        self.state.lock().unwrap().finished = true;
+        self.changed.notify_all();
+    }
+}
+
+/// Reads a [`ByteStream`] for a [`DecodedStream`], waiting for the bytes that aren't downloaded
+/// yet.
+///
+/// Once the format of the sound is known, the bytes before the reader are dropped if no other
+/// reader needs them. Until then, the decoder may seek back to probe the format.
+struct ByteReader {
+    stream: Arc<ByteStream>,
+    decoded: Arc<DecodedStream>,
+    id: usize,
+    position: usize,
+}
+
+impl ByteReader {
+    /// Starts reading `stream` from its first byte, unless it was already dropped.
+    fn new(stream: Arc<ByteStream>, decoded: Arc<DecodedStream>) -> Option<Self> {
+        let mut state = stream.state.lock().unwrap();
+        if state.offset > 0 {
+            return None;
+        }
+        let id = state.next_reader;
+        state.next_reader += 1;
+        state.readers.insert(id, 0);
+        drop(state);
+        Some(Self {
+            stream,
+            decoded,
+            id,
+            position: 0,
+        })
+    }
+}
+
+impl Read for ByteReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut state = self.stream.state.lock().unwrap();
+        while state.end() <= self.position && !state.finished {
+            if self.decoded.closed.load(Ordering::Acquire) {
+                return Ok(0);
+            }
+            state = self.stream.changed.wait(state).unwrap();
+        }
+        let start = self
+            .position
+            .checked_sub(state.offset)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "bytes were dropped"))?
+            .min(state.bytes.len());
+        let read = buf.len().min(state.bytes.len() - start);
+        for (byte, downloaded) in buf.iter_mut().zip(state.bytes.range(start..start + read)) {
+            *byte = *downloaded;
+        }
+        self.position += read;
+
+        if self.decoded.decoded.get().is_some() && !state.keep_bytes {
+            state.readers.insert(self.id, self.position);
+            let consumed = state.readers.values().min().copied().unwrap_or(0);
+            let dropped = consumed.saturating_sub(state.offset);
+            state.bytes.drain(..dropped);
+            state.offset += dropped;
+        }
+        Ok(read)
+    }
+}
+
+impl Seek for ByteReader {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(position) => Some(position),
+            SeekFrom::Current(offset) => (self.position as u64).checked_add_signed(offset),
+            SeekFrom::End(offset) => {
+                let mut state = self.stream.state.lock().unwrap();
+                while !state.finished && !self.decoded.closed.load(Ordering::Acquire) {
+                    state = self.stream.changed.wait(state).unwrap();
+                }
+                (state.end() as u64).checked_add_signed(offset)
+            }
+        };
+        self.position = position
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "seek before the start"))?
+            as usize;
+        Ok(self.position as u64)
+    }
+}
+
+impl Drop for ByteReader {
+    fn drop(&mut self) {
+        self.stream.state.lock().unwrap().readers.remove(&self.id);
+    }
+}
+
+/// The samples of a sound, decoded ahead of playback.
+struct Decoded {
+    channels: u16,
+    sample_rate: u32,
+    samples: SampleRingBuffer,
+}
+
+#[derive(Default)]
+struct DecodedStream {
+    /// Set once the format of the sound is known.
+    decoded: OnceLock<Decoded>,
+    failed: AtomicBool,
+    /// Set once the [`StreamingDecoder`] is dropped, to stop decoding.
+    closed: AtomicBool,
+}
+
+impl DecodedStream {
+    /// Decodes the bytes of `reader` from `start` to this stream until they end, or until the
+    /// stream is closed.
+    fn decode(self: Arc<Self>, reader: ByteReader, settings: StreamingSettings, start: Duration) {
+        let decoder = match rodio::Decoder::new(reader) {
+            Ok(decoder) => decoder,
+            Err(err) => {
+                warn!("Failed to decode streamed audio: {err}");
+                self.failed.store(true, Ordering::Release);
+                return;
+            }
+        };
+        let channels = decoder.channels().max(1);
+        let sample_rate = decoder.sample_rate();
+        let frames = settings.max_buffer.as_secs_f64() * sample_rate as f64;
+        let decoded = self.decoded.get_or_init(|| Decoded {
+            channels,
+            sample_rate,
+            samples: SampleRingBuffer::new((frames as usize).max(1) * channels as usize),
+        });
+
+        let skipped = (start.as_secs_f64() * sample_rate as f64).round() as usize;
+        let mut samples = decoder.convert_samples::<f32>();
+        if skipped > 0 {
+            samples.nth(skipped * channels as usize - 1);
+        }
+
+        // Push whole frames, so playback never runs out of samples in the middle of a frame.
+        let mut frame = Vec::with_capacity(channels as usize);
+        for sample in samples {
+            frame.push(sample);
+            if frame.len() < channels as usize {
+                continue;
+            }
+            while !decoded.samples.push_all(&frame) {
+                if self.closed.load(Ordering::Acquire) {
+                    return;
+                }
+                std::thread::sleep(Duration::from_millis(10));
+            }
+            frame.clear();
+        }
+        decoded.samples.closed.store(true, Ordering::Release);
+    }
+}
+
+/// The [`Source`] of a [`StreamingAudio`].
+pub struct StreamingDecoder {
+    stream: Arc<DecodedStream>,
+    /// The bytes decoded to `stream`.
+    bytes: Arc<ByteStream>,
+    settings: StreamingSettings,
+    channels: u16,
+    sample_rate: u32,
+    buffering: bool,
+    /// Set while the decoder plays silence.
+    stalled: Arc<AtomicBool>,
+    /// The number of samples of silence left to play while buffering.
+    silence: usize,
+}
+
+impl StreamingDecoder {
+    fn new(
+        stream: Arc<DecodedStream>,
+        bytes: Arc<ByteStream>,
+        settings: StreamingSettings,
+    ) -> Self {
+        let mut decoder = Self {
+            stream,
+            bytes,
+            settings,
+            channels: 2,
+            sample_rate: 44100,
+            buffering: true,
+            stalled: Arc::new(AtomicBool::new(true)),
+            silence: 0,
+        };
+        decoder.buffer();
+        decoder
+    }
+
+    /// Decides what to play after a frame of silence: stops buffering once enough samples are
+    /// decoded, or plays another frame of silence.
+    fn buffer(&mut self) {
+        if let Some(decoded) = self.stream.decoded.get() {
+            self.channels = decoded.channels;
+            self.sample_rate = decoded.sample_rate;
+            let frames = self.settings.start_buffer.as_secs_f64() * self.sample_rate as f64;
+            let start = (frames as usize * self.channels as usize).min(decoded.samples.capacity());
+            if decoded.samples.len() >= start.max(1)
+                || decoded.samples.closed.load(Ordering::Acquire)
+            {
+                self.buffering = false;
+                return;
+            }
+        }
+        self.silence = SILENCE_FRAMES * self.channels as usize;
+    }
+}
+
+impl Iterator for StreamingDecoder {
+    type Item = f32;
+
+    #[inline]
+    fn next(&mut self) -> Option<f32> {
+        if self.buffering {
+            if self.stream.failed.load(Ordering::Acquire) {
+                return None;
+            }
+            self.stalled.store(true, Ordering::Relaxed);
+            self.silence -= 1;
+            if self.silence == 0 {
+                self.buffer();
+            }
+            return Some(0.0);
+        }
+
+        self.stalled.store(false, Ordering::Relaxed);
+        let samples = &self.stream.decoded.get()?.samples;
+        match samples.pop() {
+            Some(sample) => Some(sample),
+            // Check that the buffer is still empty once closed, as samples could have been
+            // pushed just before.
+            None if samples.closed.load(Ordering::Acquire) => samples.pop(),
+            // Ran out of decoded samples: buffer again.
+            None => {
+                self.buffering = true;
+                self.silence = SILENCE_FRAMES * self.channels as usize;
+                self.next()
+            }
+        }
+    }
+}
+
+impl Drop for StreamingDecoder {
+    fn drop(&mut self) {
+        self.stream.closed.store(true, Ordering::Release);
+        // Wake the decoding thread if it waits for bytes. Locking the state ensures it either
+        // sees the flag before waiting, or is notified.
+        let _state = self.bytes.state.lock().unwrap();
+        self.bytes.changed.notify_all();
+    }
+}
+
+impl Source for StreamingDecoder {
+    fn current_frame_len(&self) -> Option<usize> {
+        // The format is only known once the sound starts decoding, so it can change after the
+        // silence played before.
+        self.buffering.then_some(self.silence)
+    }
+
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+impl Decodable for StreamingAudio {
+    type DecoderItem = f32;
+    type Decoder = StreamingDecoder;
+
+    fn decoder(&self) -> Self::Decoder {
+        self.decode_from(Duration::ZERO)
+    }
+
+    fn decoder_at(&self, position: Duration) -> Option<Self::Decoder> {
+        Some(self.decode_from(position))
+    }
+
+    fn stalled(decoder: &Self::Decoder) -> Option<Arc<AtomicBool>> {
+        Some(decoder.stalled.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings() -> StreamingSettings {
+        // One sample per second.
+        StreamingSettings {
+            start_buffer: Duration::from_secs(2),
+            max_buffer: Duration::from_secs(4),
+            keep_bytes: false,
+        }
+    }
+
+    fn decoded() -> Decoded {
+        Decoded {
+            channels: 1,
+            sample_rate: 1,
+            samples: SampleRingBuffer::new(4),
+        }
+    }
+
+    #[test]
+    fn byte_readers_wait_for_downloads() {
+        let bytes = Arc::new(ByteStream::default());
+        let mut reader = ByteReader::new(bytes.clone(), Arc::default()).unwrap();
+        let download = std::thread::spawn(move || {
+            bytes.state.lock().unwrap().bytes.extend([1, 2]);
+            bytes.changed.notify_all();
+            bytes.finish();
+        });
+        let mut read = Vec::new();
+        reader.read_to_end(&mut read).unwrap();
+        download.join().unwrap();
+        assert_eq!(read, [1, 2]);
+        assert_eq!(reader.seek(SeekFrom::End(-1)).unwrap(), 1);
+    }
+
+    #[test]
+    fn decoded_bytes_are_dropped() {
+        let bytes = Arc::new(ByteStream::default());
+        bytes.state.lock().unwrap().bytes.extend([1, 2, 3, 4]);
+        let first = Arc::new(DecodedStream::default());
+        let second = Arc::new(DecodedStream::default());
+        let mut first_reader = ByteReader::new(bytes.clone(), first.clone()).unwrap();
+        let mut second_reader = ByteReader::new(bytes.clone(), second.clone()).unwrap();
+
+        // Bytes are kept while the format is probed.
+        let mut read = [0; 2];
+        first_reader.read_exact(&mut read).unwrap();
+        assert_eq!(bytes.state.lock().unwrap().offset, 0);
+
+        // Then they're dropped once both readers decoded them.
+        assert!(first.decoded.set(decoded()).is_ok());
+        assert!(second.decoded.set(decoded()).is_ok());
+        first_reader.read_exact(&mut read).unwrap();
+        assert_eq!(read, [3, 4]);
+        assert_eq!(bytes.state.lock().unwrap().offset, 0);
+        second_reader.read_exact(&mut read).unwrap();
+        assert_eq!(read, [1, 2]);
+        assert_eq!(bytes.state.lock().unwrap().offset, 2);
+        assert_eq!(bytes.state.lock().unwrap().bytes, [3, 4]);
+
+        // The sound can't be read from its start anymore.
+        assert!(ByteReader::new(bytes.clone(), Arc::default()).is_none());
+        assert!(first_reader.seek(SeekFrom::Start(0)).is_ok());
+        assert!(first_reader.read(&mut read).is_err());
+    }
+
+    #[test]
+    fn dropping_the_decoder_stops_decoding() {
+        let bytes = Arc::new(ByteStream::default());
+        let stream = Arc::new(DecodedStream::default());
+        let mut reader = ByteReader::new(bytes.clone(), stream.clone()).unwrap();
+        let decoding = std::thread::spawn(move || reader.read(&mut [0; 4]).unwrap());
+        let decoder = StreamingDecoder::new(stream, bytes, settings());
+        drop(decoder);
+        // The download never finishes, but the reader stops waiting for it.
+        assert_eq!(decoding.join().unwrap(), 0);
+    }
+
+    #[test]
+    fn playback_buffers_until_enough_samples_are_decoded() {
+        let stream = Arc::new(DecodedStream::default());
+        let mut decoder = StreamingDecoder::new(stream.clone(), Arc::default(), settings());
+        assert_eq!(decoder.current_frame_len(), Some(SILENCE_FRAMES * 2));
+        assert!(decoder.by_ref().take(SILENCE_FRAMES * 2).all(|s| s == 0.0));
+
+        let decoded = decoded();
+        decoded.samples.push_all(&[1.0]);
+        assert!(stream.decoded.set(decoded).is_ok());
+        // Still buffering: the frame of silence ends on the format of the sound.
+        assert_eq!(decoder.next(), Some(0.0));
+        assert!(decoder
+            .by_ref()
+            .take(SILENCE_FRAMES * 2 - 1)
+            .all(|s| s == 0.0));
+        assert_eq!(decoder.channels(), 1);
+        assert_eq!(decoder.current_frame_len(), Some(SILENCE_FRAMES));
+
+        let samples = &stream.decoded.get().unwrap().samples;
+        samples.push_all(&[2.0]);
+        assert!(decoder.by_ref().take(SILENCE_FRAMES).all(|s| s == 0.0));
+        assert_eq!(decoder.current_frame_len(), None);
+        samples.push_all(&[3.0]);
+        samples.closed.store(true, Ordering::Release);
+        assert_eq!(decoder.collect::<Vec<_>>(), [1.0, 2.0, 3.0]);
+    }
+}
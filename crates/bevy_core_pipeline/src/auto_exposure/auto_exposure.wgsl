@@ -0,0 +1,122 @@
+// Automatic exposure.
+//
+// `compute_histogram` counts the pixels of the view in a histogram of exposure values, in stops
+// relative to the middle grey. `compute_average` then averages the histogram between the filter
+// percentiles, clears it for the next frame, and moves the exposure compensation of the view
+// toward the average.
+
+#import bevy_render::globals::Globals
+
+struct AutoExposureSettings {
+    min_ev: f32,
+    max_ev: f32,
+    low_percent: f32,
+    high_percent: f32,
+    speed_brighten: f32,
+    speed_darken: f32,
+    exponential_transition_distance: f32,
+}
+
+struct AutoExposureState {
+    ev: f32,
+    initialized: u32,
+}
+
+@group(0) @binding(0) var<uniform> globals: Globals;
+@group(0) @binding(1) var<uniform> settings: AutoExposureSettings;
+@group(0) @binding(2) var hdr_texture: texture_2d<f32>;
+@group(0) @binding(3) var<storage, read_write> histogram: array<atomic<u32>, 64>;
+@group(0) @binding(4) var<storage, read_write> state: AutoExposureState;
+
+const HISTOGRAM_BIN_COUNT: u32 = 64u;
+const MIDDLE_GREY: f32 = 0.18;
+
+var<workgroup> histogram_shared: array<atomic<u32>, HISTOGRAM_BIN_COUNT>;
+var<workgroup> bins: array<u32, HISTOGRAM_BIN_COUNT>;
+
+fn ev_to_bin(ev: f32) -> u32 {
+    let t = saturate((ev - settings.min_ev) / max(settings.max_ev - settings.min_ev, 1e-4));
+    return min(u32(t * f32(HISTOGRAM_BIN_COUNT)), HISTOGRAM_BIN_COUNT - 1u);
+}
+
+fn bin_to_ev(bin: u32) -> f32 {
+    let t = (f32(bin) + 0.5) / f32(HISTOGRAM_BIN_COUNT);
+    return mix(settings.min_ev, settings.max_ev, t);
+}
+
+@compute @workgroup_size(16, 16, 1)
+fn compute_histogram(
+    @builtin(global_invocation_id) global_id: vec3<u32>,
+    @builtin(local_invocation_index) local_index: u32,
+) {
+    if local_index < HISTOGRAM_BIN_COUNT {
+        atomicStore(&histogram_shared[local_index], 0u);
+    }
+    workgroupBarrier();
+
+    let size = textureDimensions(hdr_texture);
+    if all(global_id.xy < size) {
+        let color = textureLoad(hdr_texture, global_id.xy, 0).rgb;
+        let luminance = dot(color, vec3(0.2126, 0.7152, 0.0722));
+        // Black pixels go to the darkest bin.
+        let ev = log2(max(luminance, 1e-10) / MIDDLE_GREY);
+        atomicAdd(&histogram_shared[ev_to_bin(ev)], 1u);
+    }
+    workgroupBarrier();
+
+    if local_index < HISTOGRAM_BIN_COUNT {
+        atomicAdd(&histogram[local_index], atomicLoad(&histogram_shared[local_index]));
+    }
+}
+
+@compute @workgroup_size(64, 1, 1)
+fn compute_average(@builtin(local_invocation_index) local_index: u32) {
+    bins[local_index] = atomicLoad(&histogram[local_index]);
+    atomicStore(&histogram[local_index], 0u);
+    workgroupBarrier();
+
+    if local_index != 0u {
+        return;
+    }
+
+    var pixel_count = 0u;
+    for (var i = 0u; i < HISTOGRAM_BIN_COUNT; i += 1u) {
+        pixel_count += bins[i];
+    }
+
+    // Average the exposure values of the pixels between the filter percentiles.
+    let low = f32(pixel_count) * settings.low_percent;
+    let high = f32(pixel_count) * max(settings.high_percent, settings.low_percent);
+    var sum = 0.0;
+    var weight = 0.0;
+    var pixels_below = 0.0;
+    for (var i = 0u; i < HISTOGRAM_BIN_COUNT; i += 1u) {
+        let count = f32(bins[i]);
+        let bin_weight = max(min(pixels_below + count, high) - max(pixels_below, low), 0.0);
+        sum += bin_to_ev(i) * bin_weight;
+        weight += bin_weight;
+        pixels_below += count;
+    }
+
+    if weight <= 0.0 {
+        return;
+    }
+    let target_ev = sum / weight;
+
+    if state.initialized == 0u {
+        state.ev = target_ev;
+        state.initialized = 1u;
+        return;
+    }
+
+    // Move at a constant speed far from the target, and slow down exponentially close to it.
+    let delta = target_ev - state.ev;
+    let speed = select(settings.speed_brighten, settings.speed_darken, delta > 0.0);
+    let step = speed * globals.delta_time;
+    let transition_distance = max(settings.exponential_transition_distance, 1e-4);
+    if abs(delta) > transition_distance {
+        state.ev += sign(delta) * min(step, abs(delta));
+    } else {
+        state.ev += delta * (1.0 - exp(-step / transition_distance));
+    }
+}
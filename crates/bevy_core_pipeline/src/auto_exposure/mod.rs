@@ -0,0 +1,345 @@
+//! Automatic exposure, which adapts the exposure of a camera to the brightness of the scene over
+//! time, like the eye adapts when going from a dark room to the sunlight.
+//!
+//! Add [`AutoExposureSettings`] to an HDR camera to enable it.
+
+use crate::{
+    core_2d::graph::{Core2d, Node2d},
+    core_3d::graph::{Core3d, Node3d},
+};
+use bevy_app::{App, Plugin};
+use bevy_asset::{load_internal_asset, Handle};
+use bevy_ecs::{entity::EntityHashMap, prelude::*, query::QueryItem};
+use bevy_log::warn;
+use bevy_reflect::{std_traits::ReflectDefault, Reflect};
+use bevy_render::{
+    camera::Camera,
+    extract_component::{
+        ComponentUniforms, DynamicUniformIndex, ExtractComponent, ExtractComponentPlugin,
+        UniformComponentPlugin,
+    },
+    globals::{GlobalsBuffer, GlobalsUniform},
+    render_graph::{NodeRunError, RenderGraphApp, RenderGraphContext, ViewNode, ViewNodeRunner},
+    render_resource::{
+        binding_types::{storage_buffer_sized, texture_2d, uniform_buffer},
+        *,
+    },
+    renderer::{RenderContext, RenderDevice},
+    view::ViewTarget,
+    Render, RenderApp, RenderSet,
+};
+use std::ops::RangeInclusive;
+
+const AUTO_EXPOSURE_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(172043880921735530);
+
+/// The number of bins of the luminance histogram, which must match `auto_exposure.wgsl`.
+const HISTOGRAM_BIN_COUNT: u64 = 64;
+
+/// Adds support for automatic exposure.
+///
+/// See [`AutoExposureSettings`] for more details.
+pub struct AutoExposurePlugin;
+
+impl Plugin for AutoExposurePlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(
+            app,
+            AUTO_EXPOSURE_SHADER_HANDLE,
+            "auto_exposure.wgsl",
+            Shader::from_wgsl
+        );
+
+        app.register_type::<AutoExposureSettings>().add_plugins((
+            ExtractComponentPlugin::<AutoExposureSettings>::default(),
+            UniformComponentPlugin::<AutoExposureUniform>::default(),
+        ));
+    }
+
+    fn finish(&self, app: &mut App) {
+        let Ok(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        let limits = render_app.world.resource::<RenderDevice>().limits();
+        if limits.max_compute_workgroup_storage_size == 0
+            || limits.max_storage_buffers_per_shader_stage < 2
+        {
+            warn!("AutoExposurePlugin not loaded. GPU lacks support for compute shaders with storage buffers.");
+            return;
+        }
+
+        render_app
+            .init_resource::<AutoExposurePipeline>()
+            .init_resource::<AutoExposureBuffers>()
+            .add_systems(
+                Render,
+                prepare_auto_exposure_buffers.in_set(RenderSet::PrepareResources),
+            )
+            .add_render_graph_node::<ViewNodeRunner<AutoExposureNode>>(Core3d, Node3d::AutoExposure)
+            .add_render_graph_edges(
+                Core3d,
+                (
+                    Node3d::DepthOfField,
+                    Node3d::AutoExposure,
+                    Node3d::Tonemapping,
+                ),
+            )
+            .add_render_graph_node::<ViewNodeRunner<AutoExposureNode>>(Core2d, Node2d::AutoExposure)
+            .add_render_graph_edges(
+                Core2d,
+                (Node2d::Bloom, Node2d::AutoExposure, Node2d::Tonemapping),
+            );
+    }
+}
+
+/// Component to adapt the exposure of an HDR camera to the brightness of what it sees.
+///
+/// Each frame, a compute pass builds a histogram of the luminance of the image rendered by the
+/// camera, before tonemapping. The average of its values, between the percentiles of
+/// [`filter`](Self::filter), is the exposure compensation the camera adapts to over time, at the
+/// speeds of [`speed_brighten`](Self::speed_brighten) and [`speed_darken`](Self::speed_darken).
+/// The compensation is applied by the tonemapping pass, on top of the
+/// [`Exposure`](bevy_render::camera::Exposure) of the camera.
+///
+/// Exposure values are measured in stops relative to the middle grey, a luminance of 0.18, in the
+/// image exposed by the [`Exposure`](bevy_render::camera::Exposure) of the camera: an exposure
+/// value of 1.0 is twice as bright as the middle grey, and needs the exposure of the camera to be
+/// halved.
+///
+/// Requires compute shaders, so it has no effect on WebGL 2.
+#[derive(Component, Reflect, Clone, Debug, PartialEq)]
+#[reflect(Component, Default)]
+pub struct AutoExposureSettings {
+    /// The range of exposure values the camera adapts over.
+    ///
+    /// Pixels outside of this range count toward its closest bound in the histogram.
+    ///
+    /// The default value is -8.0..=8.0.
+    pub range: RangeInclusive<f32>,
+    /// The range of the histogram that is averaged, as fractions of the pixels sorted by
+    /// luminance.
+    ///
+    /// Ignoring the darkest and the brightest pixels keeps small highlights, like the sun or a
+    /// lamp, and deep shadows from driving the exposure of the whole image.
+    ///
+    /// The default value is 0.10..=0.90.
+    pub filter: RangeInclusive<f32>,
+    /// The speed at which the image brightens when the scene gets darker, in stops per second.
+    ///
+    /// The default value is 3.0.
+    pub speed_brighten: f32,
+    /// The speed at which the image darkens when the scene gets brighter, in stops per second.
+    ///
+    /// The default value is 1.0.
+    pub speed_darken: f32,
+    /// The distance to the target exposure, in stops, under which the adaptation slows down
+    /// exponentially instead of moving at a constant speed.
+    ///
+    /// This smooths the end of the transitions, and keeps small changes in the scene from
+    /// visibly shifting the exposure.
+    ///
+    /// The default value is 1.5.
+    pub exponential_transition_distance: f32,
+}
+
+impl Default for AutoExposureSettings {
+    fn default() -> Self {
+        Self {
+            range: -8.0..=8.0,
+            filter: 0.10..=0.90,
+            speed_brighten: 3.0,
+            speed_darken: 1.0,
+            exponential_transition_distance: 1.5,
+        }
+    }
+}
+
+/// The uniform struct extracted from [`AutoExposureSettings`] attached to a [`Camera`].
+#[doc(hidden)]
+#[derive(Component, ShaderType, Clone)]
+pub struct AutoExposureUniform {
+    min_ev: f32,
+    max_ev: f32,
+    low_percent: f32,
+    high_percent: f32,
+    speed_brighten: f32,
+    speed_darken: f32,
+    exponential_transition_distance: f32,
+}
+
+impl ExtractComponent for AutoExposureSettings {
+    type QueryData = (&'static Self, &'static Camera);
+    type QueryFilter = ();
+    type Out = AutoExposureUniform;
+
+    fn extract_component((settings, camera): QueryItem<Self::QueryData>) -> Option<Self::Out> {
+        if !camera.hdr {
+            return None;
+        }
+        Some(AutoExposureUniform {
+            min_ev: *settings.range.start(),
+            max_ev: settings.range.end().max(*settings.range.start()),
+            low_percent: settings.filter.start().clamp(0.0, 1.0),
+            high_percent: settings.filter.end().clamp(0.0, 1.0),
+            speed_brighten: settings.speed_brighten.max(0.0),
+            speed_darken: settings.speed_darken.max(0.0),
+            exponential_transition_distance: settings.exponential_transition_distance.max(0.0),
+        })
+    }
+}
+
+/// The buffers of the automatic exposure of a view, which persist across frames.
+#[derive(Component, Clone)]
+pub struct ViewAutoExposureBuffers {
+    /// The luminance histogram of the view, which is cleared once averaged.
+    histogram: Buffer,
+    /// The exposure compensation the view has adapted to, read by the tonemapping pass.
+    pub state: Buffer,
+}
+
+/// The [`ViewAutoExposureBuffers`] of each view, by the entity of the view.
+#[derive(Resource, Default)]
+pub struct AutoExposureBuffers(EntityHashMap<ViewAutoExposureBuffers>);
+
+fn prepare_auto_exposure_buffers(
+    mut commands: Commands,
+    render_device: Res<RenderDevice>,
+    mut buffers: ResMut<AutoExposureBuffers>,
+    views: Query<Entity, (With<ViewTarget>, With<AutoExposureUniform>)>,
+) {
+    let mut previous_buffers = std::mem::take(&mut buffers.0);
+
+    for entity in &views {
+        // New buffers are zeroed, so the exposure snaps to the scene in the first frame.
+        let view_buffers =
+            previous_buffers
+                .remove(&entity)
+                .unwrap_or_else(|| ViewAutoExposureBuffers {
+                    histogram: render_device.create_buffer(&BufferDescriptor {
+                        label: Some("auto_exposure_histogram_buffer"),
+                        size: HISTOGRAM_BIN_COUNT * 4,
+                        usage: BufferUsages::STORAGE,
+                        mapped_at_creation: false,
+                    }),
+                    state: render_device.create_buffer(&BufferDescriptor {
+                        label: Some("auto_exposure_state_buffer"),
+                        size: 8,
+                        usage: BufferUsages::STORAGE | BufferUsages::UNIFORM,
+                        mapped_at_creation: false,
+                    }),
+                });
+
+        commands.entity(entity).insert(view_buffers.clone());
+        buffers.0.insert(entity, view_buffers);
+    }
+}
+
+#[derive(Resource)]
+pub struct AutoExposurePipeline {
+    layout: BindGroupLayout,
+    histogram_pipeline: CachedComputePipelineId,
+    average_pipeline: CachedComputePipelineId,
+}
+
+impl FromWorld for AutoExposurePipeline {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+
+        let layout = render_device.create_bind_group_layout(
+            "auto_exposure_bind_group_layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::COMPUTE,
+                (
+                    uniform_buffer::<GlobalsUniform>(false),
+                    uniform_buffer::<AutoExposureUniform>(true),
+                    texture_2d(TextureSampleType::Float { filterable: false }),
+                    storage_buffer_sized(false, None),
+                    storage_buffer_sized(false, None),
+                ),
+            ),
+        );
+
+        let pipeline_cache = world.resource::<PipelineCache>();
+        let pipeline = |label: &'static str, entry_point: &'static str| {
+            pipeline_cache.queue_compute_pipeline(ComputePipelineDescriptor {
+                label: Some(label.into()),
+                layout: vec![layout.clone()],
+                push_constant_ranges: vec![],
+                shader: AUTO_EXPOSURE_SHADER_HANDLE,
+                shader_defs: Vec::new(),
+                entry_point: entry_point.into(),
+            })
+        };
+        let histogram_pipeline = pipeline("auto_exposure_histogram_pipeline", "compute_histogram");
+        let average_pipeline = pipeline("auto_exposure_average_pipeline", "compute_average");
+
+        Self {
+            layout,
+            histogram_pipeline,
+            average_pipeline,
+        }
+    }
+}
+
+/// Render [`bevy_render::render_graph::Node`] adapting the exposure of the views with
+/// [`AutoExposureSettings`].
+#[derive(Default)]
+pub struct AutoExposureNode;
+
+impl ViewNode for AutoExposureNode {
+    type ViewQuery = (
+        &'static ViewTarget,
+        &'static ViewAutoExposureBuffers,
+        &'static DynamicUniformIndex<AutoExposureUniform>,
+    );
+
+    fn run(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        (view_target, buffers, uniform_index): QueryItem<Self::ViewQuery>,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        let auto_exposure_pipeline = world.resource::<AutoExposurePipeline>();
+        let pipeline_cache = world.resource::<PipelineCache>();
+        let (Some(histogram_pipeline), Some(average_pipeline), Some(globals), Some(uniforms)) = (
+            pipeline_cache.get_compute_pipeline(auto_exposure_pipeline.histogram_pipeline),
+            pipeline_cache.get_compute_pipeline(auto_exposure_pipeline.average_pipeline),
+            world.resource::<GlobalsBuffer>().buffer.binding(),
+            world
+                .resource::<ComponentUniforms<AutoExposureUniform>>()
+                .binding(),
+        ) else {
+            return Ok(());
+        };
+
+        let source = view_target.main_texture_view();
+        let bind_group = render_context.render_device().create_bind_group(
+            "auto_exposure_bind_group",
+            &auto_exposure_pipeline.layout,
+            &BindGroupEntries::sequential((
+                globals,
+                uniforms,
+                source,
+                buffers.histogram.as_entire_binding(),
+                buffers.state.as_entire_binding(),
+            )),
+        );
+
+        let size = view_target.main_texture().size();
+        let mut compute_pass =
+            render_context
+                .command_encoder()
+                .begin_compute_pass(&ComputePassDescriptor {
+                    label: Some("auto_exposure_pass"),
+                    timestamp_writes: None,
+                });
+        compute_pass.set_bind_group(0, &bind_group, &[uniform_index.index()]);
+        compute_pass.set_pipeline(histogram_pipeline);
+        compute_pass.dispatch_workgroups(size.width.div_ceil(16), size.height.div_ceil(16), 1);
+        compute_pass.set_pipeline(average_pipeline);
+        compute_pass.dispatch_workgroups(1, 1, 1);
+
+        Ok(())
+    }
+}
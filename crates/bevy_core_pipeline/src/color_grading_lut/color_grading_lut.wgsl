@@ -0,0 +1,46 @@
+// Color grading with a 3D LUT, after tonemapping.
+//
+// The LUT maps sRGB encoded colors to sRGB encoded colors, so the linear colors of the view are
+// encoded before the lookup and decoded after it.
+
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+@group(0) @binding(1) var screen_sampler: sampler;
+@group(0) @binding(2) var lut_texture: texture_3d<f32>;
+
+fn linear_to_srgb(color: vec3<f32>) -> vec3<f32> {
+    let low = color * 12.92;
+    let high = 1.055 * pow(color, vec3(1.0 / 2.4)) - 0.055;
+    return select(high, low, color <= vec3(0.0031308));
+}
+
+fn srgb_to_linear(color: vec3<f32>) -> vec3<f32> {
+    let low = color / 12.92;
+    let high = pow((color + 0.055) / 1.055, vec3(2.4));
+    return select(high, low, color <= vec3(0.04045));
+}
+
+// Trilinear interpolation between the texels of the LUT, which isn't filterable.
+fn sample_lut(encoded: vec3<f32>) -> vec3<f32> {
+    let size = vec3<i32>(textureDimensions(lut_texture));
+    let position = encoded * vec3<f32>(size - 1);
+    let texel = min(vec3<i32>(floor(position)), size - 2);
+    let t = position - vec3<f32>(texel);
+
+    var result = vec3(0.0);
+    for (var i = 0; i < 8; i += 1) {
+        let offset = vec3(i & 1, (i >> 1u) & 1, (i >> 2u) & 1);
+        let weights = select(1.0 - t, t, offset == vec3(1));
+        let value = textureLoad(lut_texture, texel + offset, 0).rgb;
+        result += value * weights.x * weights.y * weights.z;
+    }
+    return result;
+}
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(screen_texture, screen_sampler, in.uv);
+    let graded = sample_lut(linear_to_srgb(saturate(color.rgb)));
+    return vec4(srgb_to_linear(max(graded, vec3(0.0))), color.a);
+}
@@ -0,0 +1,197 @@
+use bevy_asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext};
+use bevy_render::{
+    render_asset::RenderAssetUsages,
+    render_resource::{Extent3d, TextureDimension, TextureFormat},
+    texture::Image,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Loads the 3D LUTs (look up tables) of `.cube` files as 3D textures, for use with
+/// [`ColorGradingLut`](super::ColorGradingLut).
+///
+/// The red input varies along the X axis of the texture, green along Y and blue along Z. Only 3D
+/// LUTs over the default domain, from 0.0 to 1.0, are supported.
+#[derive(Clone, Default)]
+pub struct CubeLutLoader;
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct CubeLutLoaderSettings {
+    pub asset_usage: RenderAssetUsages,
+}
+
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum CubeLutLoaderError {
+    #[error("Could not load .cube file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid line {0} in .cube file")]
+    InvalidLine(usize),
+    #[error("The .cube file has no LUT_3D_SIZE")]
+    MissingSize,
+    #[error("1D LUTs are not supported")]
+    OneDimensional,
+    #[error("Only the domain from 0.0 to 1.0 is supported")]
+    UnsupportedDomain,
+    #[error("Expected {expected} entries in the LUT, found {found}")]
+    WrongEntryCount { expected: usize, found: usize },
+}
+
+impl AssetLoader for CubeLutLoader {
+    type Asset = Image;
+    type Settings = CubeLutLoaderSettings;
+    type Error = CubeLutLoaderError;
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        settings: &'a Self::Settings,
+        _load_context: &'a mut LoadContext,
+    ) -> bevy_utils::BoxedFuture<'a, Result<Image, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            let (size, entries) = parse_cube_lut(&String::from_utf8_lossy(&bytes))?;
+
+            let mut data = Vec::with_capacity(entries.len() * 4 * 4);
+            for [r, g, b] in entries {
+                for component in [r, g, b, 1.0] {
+                    data.extend_from_slice(&component.to_ne_bytes());
+                }
+            }
+
+            Ok(Image::new(
+                Extent3d {
+                    width: size,
+                    height: size,
+                    depth_or_array_layers: size,
+                },
+                TextureDimension::D3,
+                data,
+                TextureFormat::Rgba32Float,
+                settings.asset_usage,
+            ))
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["cube"]
+    }
+}
+
+/// Parses the size and the entries of a 3D LUT in the `.cube` format, with red varying the
+/// fastest.
+fn parse_cube_lut(source: &str) -> Result<(u32, Vec<[f32; 3]>), CubeLutLoaderError> {
+    let mut size = None;
+    let mut entries = Vec::new();
+
+    for (index, line) in source.lines().enumerate() {
+        let line_number = index + 1;
+        let mut tokens = line.split_whitespace();
+        let Some(keyword) = tokens.next() else {
+            continue;
+        };
+
+        let floats = |tokens: std::str::SplitWhitespace| {
+            tokens
+                .map(str::parse::<f32>)
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| CubeLutLoaderError::InvalidLine(line_number))
+        };
+
+        match keyword {
+            _ if keyword.starts_with('#') => {}
+            "TITLE" => {}
+            "LUT_1D_SIZE" => return Err(CubeLutLoaderError::OneDimensional),
+            "LUT_3D_SIZE" => {
+                let value = tokens
+                    .next()
+                    .and_then(|token| token.parse::<u32>().ok())
+                    .filter(|&value| value >= 2)
+                    .ok_or(CubeLutLoaderError::InvalidLine(line_number))?;
+                size = Some(value);
+            }
+            "DOMAIN_MIN" | "DOMAIN_MAX" | "LUT_3D_INPUT_RANGE" => {
+                let default_domain: &[f32] = match keyword {
+                    "DOMAIN_MIN" => &[0.0; 3],
+                    "DOMAIN_MAX" => &[1.0; 3],
+                    _ => &[0.0, 1.0],
+                };
+                if floats(tokens)? != default_domain {
+                    return Err(CubeLutLoaderError::UnsupportedDomain);
+                }
+            }
+            _ if keyword.parse::<f32>().is_ok() => {
+                let values = floats(line.split_whitespace())?;
+                let [r, g, b] = values[..] else {
+                    return Err(CubeLutLoaderError::InvalidLine(line_number));
+                };
+                entries.push([r, g, b]);
+            }
+            // Other keywords are extensions of some applications, which don't change the LUT.
+            _ => {}
+        }
+    }
+
+    let size = size.ok_or(CubeLutLoaderError::MissingSize)?;
+    let expected = (size as usize).pow(3);
+    if entries.len() != expected {
+        return Err(CubeLutLoaderError::WrongEntryCount {
+            expected,
+            found: entries.len(),
+        });
+    }
+
+    Ok((size, entries))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_cube_lut, CubeLutLoaderError};
+
+    #[test]
+    fn parse_identity() {
+        let source = "\
+# Identity
+TITLE \"Identity\"
+LUT_3D_SIZE 2
+DOMAIN_MIN 0.0 0.0 0.0
+DOMAIN_MAX 1.0 1.0 1.0
+
+0 0 0
+1 0 0
+0 1 0
+1 1 0
+0 0 1
+1 0 1
+0 1 1
+1 1 1
+";
+        let (size, entries) = parse_cube_lut(source).unwrap();
+        assert_eq!(size, 2);
+        assert_eq!(entries[1], [1.0, 0.0, 0.0]);
+        assert_eq!(entries[6], [0.0, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(matches!(
+            parse_cube_lut("LUT_3D_SIZE 2\n0 0 0\n"),
+            Err(CubeLutLoaderError::WrongEntryCount {
+                expected: 8,
+                found: 1
+            })
+        ));
+        assert!(matches!(
+            parse_cube_lut("LUT_1D_SIZE 16\n"),
+            Err(CubeLutLoaderError::OneDimensional)
+        ));
+        assert!(matches!(
+            parse_cube_lut("LUT_3D_SIZE 2\nDOMAIN_MAX 2 2 2\n"),
+            Err(CubeLutLoaderError::UnsupportedDomain)
+        ));
+        assert!(matches!(
+            parse_cube_lut("LUT_3D_SIZE 2\n0 0\n"),
+            Err(CubeLutLoaderError::InvalidLine(2))
+        ));
+    }
+}
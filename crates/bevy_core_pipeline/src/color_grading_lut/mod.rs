@@ -0,0 +1,254 @@
+//! Color grading with 3D LUTs (look up tables), like the ones exported by video editors.
+//!
+//! Add a [`ColorGradingLut`] to a camera to enable it, with a LUT loaded from a `.cube` file by
+//! the [`CubeLutLoader`].
+
+use crate::{
+    core_2d::graph::{Core2d, Node2d},
+    core_3d::graph::{Core3d, Node3d},
+    fullscreen_vertex_shader::fullscreen_shader_vertex_state,
+};
+use bevy_app::{App, Plugin};
+use bevy_asset::{load_internal_asset, AssetApp, Handle};
+use bevy_ecs::{prelude::*, query::QueryItem};
+use bevy_reflect::{std_traits::ReflectDefault, Reflect};
+use bevy_render::{
+    extract_component::{ExtractComponent, ExtractComponentPlugin},
+    prelude::Camera,
+    render_asset::RenderAssets,
+    render_graph::{NodeRunError, RenderGraphApp, RenderGraphContext, ViewNode, ViewNodeRunner},
+    render_resource::{
+        binding_types::{sampler, texture_2d, texture_3d},
+        *,
+    },
+    renderer::{RenderContext, RenderDevice},
+    texture::{BevyDefault, Image},
+    view::{ExtractedView, ViewTarget},
+    Render, RenderApp, RenderSet,
+};
+
+mod cube_lut_loader;
+
+pub use cube_lut_loader::{CubeLutLoader, CubeLutLoaderError, CubeLutLoaderSettings};
+
+const COLOR_GRADING_LUT_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(310548169930581734);
+
+/// Adds support for color grading with 3D LUTs, and the loader of `.cube` files.
+///
+/// See [`ColorGradingLut`] for more details.
+pub struct ColorGradingLutPlugin;
+
+impl Plugin for ColorGradingLutPlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(
+            app,
+            COLOR_GRADING_LUT_SHADER_HANDLE,
+            "color_grading_lut.wgsl",
+            Shader::from_wgsl
+        );
+
+        app.init_asset_loader::<CubeLutLoader>()
+            .register_type::<ColorGradingLut>()
+            .add_plugins(ExtractComponentPlugin::<ColorGradingLut>::default());
+
+        let Ok(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        render_app
+            .init_resource::<SpecializedRenderPipelines<ColorGradingLutPipeline>>()
+            .add_systems(
+                Render,
+                prepare_color_grading_lut_pipelines.in_set(RenderSet::Prepare),
+            )
+            .add_render_graph_node::<ViewNodeRunner<ColorGradingLutNode>>(
+                Core3d,
+                Node3d::ColorGradingLut,
+            )
+            .add_render_graph_edges(
+                Core3d,
+                (Node3d::Tonemapping, Node3d::ColorGradingLut, Node3d::Fxaa),
+            )
+            .add_render_graph_node::<ViewNodeRunner<ColorGradingLutNode>>(
+                Core2d,
+                Node2d::ColorGradingLut,
+            )
+            .add_render_graph_edges(
+                Core2d,
+                (Node2d::Tonemapping, Node2d::ColorGradingLut, Node2d::Fxaa),
+            );
+    }
+
+    fn finish(&self, app: &mut App) {
+        let Ok(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        render_app.init_resource::<ColorGradingLutPipeline>();
+    }
+}
+
+/// Component to grade the colors of a camera with a 3D LUT (look up table), after tonemapping.
+///
+/// The LUT maps the sRGB encoded colors of the tonemapped image to new sRGB encoded colors, like
+/// the `.cube` files exported by color grading tools. It is a 3D texture, with the red input along
+/// its X axis, green along Y and blue along Z, and is sampled with trilinear interpolation between
+/// its texels.
+///
+/// The LUT is usually loaded from a `.cube` file by the [`CubeLutLoader`]. Nothing is graded while
+/// it is loading.
+#[derive(Component, Reflect, Clone, Debug, Default, ExtractComponent)]
+#[extract_component_filter(With<Camera>)]
+#[reflect(Component, Default)]
+pub struct ColorGradingLut(pub Handle<Image>);
+
+#[derive(Resource)]
+pub struct ColorGradingLutPipeline {
+    layout: BindGroupLayout,
+    sampler: Sampler,
+}
+
+impl FromWorld for ColorGradingLutPipeline {
+    fn from_world(render_world: &mut World) -> Self {
+        let render_device = render_world.resource::<RenderDevice>();
+        let layout = render_device.create_bind_group_layout(
+            "color_grading_lut_bind_group_layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::FRAGMENT,
+                (
+                    texture_2d(TextureSampleType::Float { filterable: true }),
+                    sampler(SamplerBindingType::Filtering),
+                    // The LUT is interpolated in the shader, as 32-bit float textures aren't
+                    // filterable everywhere.
+                    texture_3d(TextureSampleType::Float { filterable: false }),
+                ),
+            ),
+        );
+
+        let sampler = render_device.create_sampler(&SamplerDescriptor::default());
+
+        ColorGradingLutPipeline { layout, sampler }
+    }
+}
+
+#[derive(Component)]
+pub struct ViewColorGradingLutPipeline(CachedRenderPipelineId);
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy)]
+pub struct ColorGradingLutPipelineKey {
+    texture_format: TextureFormat,
+}
+
+impl SpecializedRenderPipeline for ColorGradingLutPipeline {
+    type Key = ColorGradingLutPipelineKey;
+
+    fn specialize(&self, key: Self::Key) -> RenderPipelineDescriptor {
+        RenderPipelineDescriptor {
+            label: Some("color_grading_lut_pipeline".into()),
+            layout: vec![self.layout.clone()],
+            vertex: fullscreen_shader_vertex_state(),
+            fragment: Some(FragmentState {
+                shader: COLOR_GRADING_LUT_SHADER_HANDLE,
+                shader_defs: Vec::new(),
+                entry_point: "fragment".into(),
+                targets: vec![Some(ColorTargetState {
+                    format: key.texture_format,
+                    blend: None,
+                    write_mask: ColorWrites::ALL,
+                })],
+            }),
+            primitive: PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: MultisampleState::default(),
+            push_constant_ranges: Vec::new(),
+        }
+    }
+}
+
+pub fn prepare_color_grading_lut_pipelines(
+    mut commands: Commands,
+    pipeline_cache: Res<PipelineCache>,
+    mut pipelines: ResMut<SpecializedRenderPipelines<ColorGradingLutPipeline>>,
+    color_grading_lut_pipeline: Res<ColorGradingLutPipeline>,
+    views: Query<(Entity, &ExtractedView), With<ColorGradingLut>>,
+) {
+    for (entity, view) in &views {
+        let pipeline_id = pipelines.specialize(
+            &pipeline_cache,
+            &color_grading_lut_pipeline,
+            ColorGradingLutPipelineKey {
+                texture_format: if view.hdr {
+                    ViewTarget::TEXTURE_FORMAT_HDR
+                } else {
+                    TextureFormat::bevy_default()
+                },
+            },
+        );
+
+        commands
+            .entity(entity)
+            .insert(ViewColorGradingLutPipeline(pipeline_id));
+    }
+}
+
+/// Render [`bevy_render::render_graph::Node`] applying the [`ColorGradingLut`] of a view.
+#[derive(Default)]
+pub struct ColorGradingLutNode;
+
+impl ViewNode for ColorGradingLutNode {
+    type ViewQuery = (
+        &'static ViewTarget,
+        &'static ViewColorGradingLutPipeline,
+        &'static ColorGradingLut,
+    );
+
+    fn run(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        (target, pipeline_id, lut): QueryItem<Self::ViewQuery>,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        let pipeline_cache = world.resource::<PipelineCache>();
+        let color_grading_lut_pipeline = world.resource::<ColorGradingLutPipeline>();
+        let (Some(pipeline), Some(lut_image)) = (
+            pipeline_cache.get_render_pipeline(pipeline_id.0),
+            world.resource::<RenderAssets<Image>>().get(&lut.0),
+        ) else {
+            return Ok(());
+        };
+
+        let post_process = target.post_process_write();
+        let bind_group = render_context.render_device().create_bind_group(
+            "color_grading_lut_bind_group",
+            &color_grading_lut_pipeline.layout,
+            &BindGroupEntries::sequential((
+                post_process.source,
+                &color_grading_lut_pipeline.sampler,
+                &lut_image.texture_view,
+            )),
+        );
+
+        let pass_descriptor = RenderPassDescriptor {
+            label: Some("color_grading_lut_pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: post_process.destination,
+                resolve_target: None,
+                ops: Operations::default(),
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        };
+
+        let mut render_pass = render_context
+            .command_encoder()
+            .begin_render_pass(&pass_descriptor);
+
+        render_pass.set_pipeline(pipeline);
+        render_pass.set_bind_group(0, &bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+
+        Ok(())
+    }
+}
@@ -16,7 +16,9 @@ pub mod graph {
         MsaaWriteback,
         MainPass,
         Bloom,
+        AutoExposure,
         Tonemapping,
+        ColorGradingLut,
         Fxaa,
         Upscaling,
         ContrastAdaptiveSharpening,
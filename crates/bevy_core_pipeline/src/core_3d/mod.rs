@@ -20,14 +20,20 @@ pub mod graph {
         DeferredPrepass,
         CopyDeferredLightingId,
         EndPrepasses,
+        OcclusionCulling,
         StartMainPass,
         MainOpaquePass,
         MainTransmissivePass,
         MainTransparentPass,
+        OitResolve,
         EndMainPass,
+        MotionBlur,
         Taa,
         Bloom,
+        DepthOfField,
+        AutoExposure,
         Tonemapping,
+        ColorGradingLut,
         Fxaa,
         Upscaling,
         ContrastAdaptiveSharpening,
@@ -52,18 +58,19 @@ use bevy_render::{
     color::Color,
     extract_component::ExtractComponentPlugin,
     mesh::Mesh,
+    occlusion_culling::{OcclusionCulling, OcclusionCullingNode},
     prelude::Msaa,
     render_graph::{EmptyNode, RenderGraphApp, ViewNodeRunner},
     render_phase::{
-        sort_phase_system, CachedRenderPipelinePhaseItem, DrawFunctionId, DrawFunctions, PhaseItem,
-        RenderPhase,
+        sort_binned, sort_phase_system, BinnedPhaseItem, CachedRenderPipelinePhaseItem,
+        DrawFunctionId, DrawFunctions, PhaseItem, RenderPhase,
     },
     render_resource::{
-        CachedRenderPipelineId, Extent3d, FilterMode, Sampler, SamplerDescriptor, Texture,
-        TextureDescriptor, TextureDimension, TextureFormat, TextureUsages, TextureView,
+        BindGroupId, CachedRenderPipelineId, Extent3d, FilterMode, Sampler, SamplerDescriptor,
+        Texture, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages, TextureView,
     },
     renderer::RenderDevice,
-    texture::{BevyDefault, ColorAttachment, TextureCache},
+    texture::{BevyDefault, ColorAttachment, Image, TextureCache},
     view::{ExtractedView, ViewDepthTexture, ViewTarget},
     Extract, ExtractSchedule, Render, RenderApp, RenderSet,
 };
@@ -97,7 +104,10 @@ impl Plugin for Core3dPlugin {
             .register_type::<Camera3dDepthTextureUsage>()
             .register_type::<ScreenSpaceTransmissionQuality>()
             .add_plugins((SkyboxPlugin, ExtractComponentPlugin::<Camera3d>::default()))
-            .add_systems(PostUpdate, check_msaa);
+            .add_systems(
+                PostUpdate,
+                (check_msaa, configure_occlusion_culling_depth_textures),
+            );
 
         let Ok(render_app) = app.get_sub_app_mut(RenderApp) else {
             return;
@@ -143,6 +153,10 @@ impl Plugin for Core3dPlugin {
                 Node3d::CopyDeferredLightingId,
             )
             .add_render_graph_node::<EmptyNode>(Core3d, Node3d::EndPrepasses)
+            .add_render_graph_node::<ViewNodeRunner<OcclusionCullingNode>>(
+                Core3d,
+                Node3d::OcclusionCulling,
+            )
             .add_render_graph_node::<EmptyNode>(Core3d, Node3d::StartMainPass)
             .add_render_graph_node::<ViewNodeRunner<MainOpaquePass3dNode>>(
                 Core3d,
@@ -167,6 +181,7 @@ impl Plugin for Core3dPlugin {
                     Node3d::DeferredPrepass,
                     Node3d::CopyDeferredLightingId,
                     Node3d::EndPrepasses,
+                    Node3d::OcclusionCulling,
                     Node3d::StartMainPass,
                     Node3d::MainOpaquePass,
                     Node3d::MainTransmissivePass,
@@ -180,8 +195,24 @@ impl Plugin for Core3dPlugin {
     }
 }
 
+/// The data that must be equal for the draws of opaque 3D meshes to be batched together, besides
+/// their pipeline and draw function.
+///
+/// This is the [`BinnedPhaseItem::BinKey`] of the opaque 3D phases and of the shadow phase.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Opaque3dBinKey {
+    /// The mesh.
+    pub asset_id: AssetId<Mesh>,
+    /// The bind group of the material.
+    pub material_bind_group_id: Option<BindGroupId>,
+    /// The lightmap of the mesh, if it has one.
+    pub lightmap_image: Option<AssetId<Image>>,
+}
+
 pub struct Opaque3d {
     pub asset_id: AssetId<Mesh>,
+    pub material_bind_group_id: Option<BindGroupId>,
+    pub lightmap_image: Option<AssetId<Image>>,
     pub pipeline: CachedRenderPipelineId,
     pub entity: Entity,
     pub draw_function: DrawFunctionId,
@@ -210,7 +241,7 @@ impl PhaseItem for Opaque3d {
 
     #[inline]
     fn sort(items: &mut [Self]) {
-        items.sort_unstable_by_key(Self::sort_key);
+        sort_binned(items);
     }
 
     #[inline]
@@ -234,6 +265,19 @@ impl PhaseItem for Opaque3d {
     }
 }
 
+impl BinnedPhaseItem for Opaque3d {
+    type BinKey = Opaque3dBinKey;
+
+    #[inline]
+    fn bin_key(&self) -> Self::BinKey {
+        Opaque3dBinKey {
+            asset_id: self.asset_id,
+            material_bind_group_id: self.material_bind_group_id,
+            lightmap_image: self.lightmap_image,
+        }
+    }
+}
+
 impl CachedRenderPipelinePhaseItem for Opaque3d {
     #[inline]
     fn cached_pipeline(&self) -> CachedRenderPipelineId {
@@ -685,6 +729,19 @@ pub fn check_msaa(
     }
 }
 
+/// Lets the occlusion culling pass read the depth textures of the cameras with
+/// [`OcclusionCulling`].
+fn configure_occlusion_culling_depth_textures(
+    mut cameras: Query<&mut Camera3d, With<OcclusionCulling>>,
+) {
+    for mut camera_3d in &mut cameras {
+        let usages = TextureUsages::from(camera_3d.depth_texture_usages);
+        if !usages.contains(TextureUsages::TEXTURE_BINDING) {
+            camera_3d.depth_texture_usages = (usages | TextureUsages::TEXTURE_BINDING).into();
+        }
+    }
+}
+
 // Prepares the textures used by the prepass
 pub fn prepare_prepass_textures(
     mut commands: Commands,
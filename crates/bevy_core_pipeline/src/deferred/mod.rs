@@ -7,23 +7,30 @@ use bevy_asset::AssetId;
 use bevy_ecs::prelude::*;
 use bevy_render::{
     mesh::Mesh,
-    render_phase::{CachedRenderPipelinePhaseItem, DrawFunctionId, PhaseItem},
-    render_resource::{CachedRenderPipelineId, TextureFormat},
+    render_phase::{
+        sort_binned, BinnedPhaseItem, CachedRenderPipelinePhaseItem, DrawFunctionId, PhaseItem,
+    },
+    render_resource::{BindGroupId, CachedRenderPipelineId, TextureFormat},
+    texture::Image,
 };
 use bevy_utils::{nonmax::NonMaxU32, FloatOrd};
 
+use crate::core_3d::Opaque3dBinKey;
+
 pub const DEFERRED_PREPASS_FORMAT: TextureFormat = TextureFormat::Rgba32Uint;
 pub const DEFERRED_LIGHTING_PASS_ID_FORMAT: TextureFormat = TextureFormat::R8Uint;
 pub const DEFERRED_LIGHTING_PASS_ID_DEPTH_FORMAT: TextureFormat = TextureFormat::Depth16Unorm;
 
 /// Opaque phase of the 3D Deferred pass.
 ///
-/// Sorted front-to-back by the z-distance in front of the camera.
+/// Binned by pipeline, mesh, material and lightmap, see [`BinnedPhaseItem`].
 ///
 /// Used to render all 3D meshes with materials that have no transparency.
 pub struct Opaque3dDeferred {
     pub entity: Entity,
     pub asset_id: AssetId<Mesh>,
+    pub material_bind_group_id: Option<BindGroupId>,
+    pub lightmap_image: Option<AssetId<Image>>,
     pub pipeline_id: CachedRenderPipelineId,
     pub draw_function: DrawFunctionId,
     pub batch_range: Range<u32>,
@@ -51,7 +58,7 @@ impl PhaseItem for Opaque3dDeferred {
 
     #[inline]
     fn sort(items: &mut [Self]) {
-        items.sort_unstable_by_key(Self::sort_key);
+        sort_binned(items);
     }
 
     #[inline]
@@ -75,6 +82,19 @@ impl PhaseItem for Opaque3dDeferred {
     }
 }
 
+impl BinnedPhaseItem for Opaque3dDeferred {
+    type BinKey = Opaque3dBinKey;
+
+    #[inline]
+    fn bin_key(&self) -> Self::BinKey {
+        Opaque3dBinKey {
+            asset_id: self.asset_id,
+            material_bind_group_id: self.material_bind_group_id,
+            lightmap_image: self.lightmap_image,
+        }
+    }
+}
+
 impl CachedRenderPipelinePhaseItem for Opaque3dDeferred {
     #[inline]
     fn cached_pipeline(&self) -> CachedRenderPipelineId {
@@ -0,0 +1,119 @@
+// Depth of field.
+//
+// The bokeh blur gathers the pixels around each pixel on a golden angle spiral, after
+// "Bokeh depth of field in a single pass" by Dennis Gustafsson:
+// https://blog.voxagon.se/2018/05/04/bokeh-depth-of-field-in-single-pass.html
+
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+#import bevy_render::view::View
+
+struct DepthOfFieldParams {
+    focal_distance: f32,
+    coc_scale_factor: f32,
+    max_circle_of_confusion_diameter: f32,
+    max_depth: f32,
+}
+
+@group(0) @binding(0) var<uniform> view: View;
+@group(0) @binding(1) var<uniform> dof_params: DepthOfFieldParams;
+@group(0) @binding(2) var color_texture: texture_2d<f32>;
+#ifdef MULTISAMPLED
+@group(0) @binding(3) var depth_texture: texture_depth_multisampled_2d;
+#else
+@group(0) @binding(3) var depth_texture: texture_depth_2d;
+#endif
+@group(0) @binding(4) var color_sampler: sampler;
+
+const GOLDEN_ANGLE: f32 = 2.39996323;
+// The maximum number of pixels gathered around each pixel by the bokeh blur.
+const MAX_BOKEH_SAMPLES: f32 = 256.0;
+
+// The distance along the view direction to the scene at the pixel `coords`, in meters.
+fn view_distance(coords: vec2<f32>) -> f32 {
+    let size = vec2<i32>(textureDimensions(depth_texture));
+    let texel = clamp(vec2<i32>(coords), vec2(0), size - 1);
+    let depth = textureLoad(depth_texture, texel, 0);
+    let view_position = view.inverse_projection * vec4(0.0, 0.0, depth, 1.0);
+    return -view_position.z / view_position.w;
+}
+
+// The diameter in pixels of the circle of confusion of the points at the `distance`.
+fn circle_of_confusion(distance: f32) -> f32 {
+    let clamped_distance = min(distance, dof_params.max_depth);
+    let coc = dof_params.coc_scale_factor * abs(1.0 - dof_params.focal_distance / clamped_distance);
+    return min(coc, dof_params.max_circle_of_confusion_diameter);
+}
+
+fn sample_color(coords: vec2<f32>) -> vec4<f32> {
+    let texel_size = 1.0 / vec2<f32>(textureDimensions(color_texture));
+    return textureSampleLevel(color_texture, color_sampler, coords * texel_size, 0.0);
+}
+
+@fragment
+fn bokeh(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let frag_coord = in.position.xy;
+    let center_distance = view_distance(frag_coord);
+    let center_radius = circle_of_confusion(center_distance) * 0.5;
+
+    var color = sample_color(frag_coord);
+    var total_weight = 1.0;
+
+    // The samples get further apart for wide circles of confusion, to stay under
+    // `MAX_BOKEH_SAMPLES`. The foreground can blur over this pixel even when it's in focus, so the
+    // whole spiral is always gathered.
+    let max_radius = dof_params.max_circle_of_confusion_diameter * 0.5;
+    let radius_scale = max(max_radius * max_radius / (2.0 * MAX_BOKEH_SAMPLES), 0.5);
+    var radius = radius_scale;
+    var angle = 0.0;
+    while radius < max_radius {
+        let sample_coord = frag_coord + vec2(cos(angle), sin(angle)) * radius;
+        let sample_distance = view_distance(sample_coord);
+        var sample_radius = circle_of_confusion(sample_distance) * 0.5;
+        // The background doesn't blur over the pixels in front of it.
+        if sample_distance > center_distance {
+            sample_radius = clamp(sample_radius, 0.0, center_radius * 2.0);
+        }
+
+        // The sample contributes if its circle of confusion reaches this pixel, otherwise the
+        // average so far is used in its place.
+        let weight = smoothstep(radius - 0.5, radius + 0.5, sample_radius);
+        color += mix(color / total_weight, sample_color(sample_coord), weight);
+        total_weight += 1.0;
+
+        radius += radius_scale / radius;
+        angle += GOLDEN_ANGLE;
+    }
+
+    return color / total_weight;
+}
+
+// Blurs the pixel along the `direction` with a gaussian kernel covering its circle of confusion.
+fn gaussian_blur(frag_coord: vec2<f32>, direction: vec2<f32>) -> vec4<f32> {
+    let coc = circle_of_confusion(view_distance(frag_coord));
+    // The circle of confusion covers four standard deviations of the kernel.
+    let sigma = coc * 0.25;
+    var color = sample_color(frag_coord);
+    if sigma < 0.01 {
+        return color;
+    }
+
+    var total_weight = 1.0;
+    let radius = i32(ceil(sigma * 3.0));
+    for (var i = 1; i <= radius; i += 1) {
+        let weight = exp(-f32(i * i) / (2.0 * sigma * sigma));
+        let offset = direction * f32(i);
+        color += weight * (sample_color(frag_coord + offset) + sample_color(frag_coord - offset));
+        total_weight += 2.0 * weight;
+    }
+    return color / total_weight;
+}
+
+@fragment
+fn gaussian_horizontal(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    return gaussian_blur(in.position.xy, vec2(1.0, 0.0));
+}
+
+@fragment
+fn gaussian_vertical(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    return gaussian_blur(in.position.xy, vec2(0.0, 1.0));
+}
@@ -0,0 +1,527 @@
+//! Depth of field: blurs the parts of the image which are out of the focus of the camera, like a
+//! physical camera lens does.
+//!
+//! Add a [`DepthOfFieldSettings`] to a 3D camera with a perspective projection to enable it.
+
+use crate::{
+    core_3d::{
+        graph::{Core3d, Node3d},
+        Camera3d,
+    },
+    fullscreen_vertex_shader::fullscreen_shader_vertex_state,
+};
+use bevy_app::prelude::*;
+use bevy_asset::{load_internal_asset, Handle};
+use bevy_ecs::{prelude::*, query::QueryItem};
+use bevy_reflect::{std_traits::ReflectDefault, Reflect};
+use bevy_render::{
+    camera::{Camera, ExtractedCamera, Projection},
+    extract_component::{ComponentUniforms, DynamicUniformIndex, UniformComponentPlugin},
+    render_graph::{NodeRunError, RenderGraphApp, RenderGraphContext, ViewNode, ViewNodeRunner},
+    render_resource::{
+        binding_types::{
+            sampler, texture_2d, texture_depth_2d, texture_depth_2d_multisampled, uniform_buffer,
+        },
+        *,
+    },
+    renderer::{RenderContext, RenderDevice},
+    texture::{BevyDefault, CachedTexture, TextureCache},
+    view::{
+        ExtractedView, Msaa, ViewDepthTexture, ViewTarget, ViewUniform, ViewUniformOffset,
+        ViewUniforms,
+    },
+    Extract, ExtractSchedule, Render, RenderApp, RenderSet,
+};
+
+const DOF_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(2031861180739216043);
+
+/// The sensor height of a Super 35 film camera, in meters.
+const DEFAULT_SENSOR_HEIGHT: f32 = 0.01866;
+
+/// Adds support for depth of field.
+///
+/// See [`DepthOfFieldSettings`] for more details.
+pub struct DepthOfFieldPlugin;
+
+impl Plugin for DepthOfFieldPlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(app, DOF_SHADER_HANDLE, "dof.wgsl", Shader::from_wgsl);
+
+        app.register_type::<DepthOfFieldSettings>()
+            .register_type::<DepthOfFieldMode>()
+            .add_plugins(UniformComponentPlugin::<DepthOfFieldUniform>::default())
+            .add_systems(PostUpdate, configure_depth_of_field_depth_textures);
+
+        let Ok(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        render_app
+            .init_resource::<SpecializedRenderPipelines<DepthOfFieldPipeline>>()
+            .add_systems(ExtractSchedule, extract_depth_of_field_settings)
+            .add_systems(
+                Render,
+                (
+                    prepare_depth_of_field_pipelines.in_set(RenderSet::Prepare),
+                    prepare_depth_of_field_textures.in_set(RenderSet::PrepareResources),
+                ),
+            )
+            .add_render_graph_node::<ViewNodeRunner<DepthOfFieldNode>>(Core3d, Node3d::DepthOfField)
+            .add_render_graph_edges(
+                Core3d,
+                (Node3d::Bloom, Node3d::DepthOfField, Node3d::Tonemapping),
+            );
+    }
+
+    fn finish(&self, app: &mut App) {
+        let Ok(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        render_app.init_resource::<DepthOfFieldPipeline>();
+    }
+}
+
+/// Component to apply depth of field to a 3D camera with a perspective projection.
+///
+/// The objects at the [`focal_distance`](Self::focal_distance) from the camera are sharp, and the
+/// other objects are blurred by their circle of confusion: the disk on the sensor of the camera
+/// which the light of a point at their distance covers, as computed by the thin lens model from
+/// the field of view of the camera, the [`sensor_height`](Self::sensor_height) and the
+/// [`aperture_f_stops`](Self::aperture_f_stops).
+///
+/// The depth of the scene is read from the depth texture of the camera, so the transparent objects
+/// are blurred like the opaque objects behind them.
+#[derive(Component, Reflect, Clone, Copy, Debug, PartialEq)]
+#[reflect(Component, Default)]
+pub struct DepthOfFieldSettings {
+    /// How the out of focus parts of the image are blurred.
+    pub mode: DepthOfFieldMode,
+
+    /// The distance from the camera to the objects in focus, in meters.
+    ///
+    /// The default value is 10.0.
+    pub focal_distance: f32,
+
+    /// The height of the sensor of the camera, in meters.
+    ///
+    /// With the field of view of the camera, this gives the focal length of its lens. The default
+    /// value is the height of a Super 35 sensor, 18.66 mm.
+    pub sensor_height: f32,
+
+    /// The f-number of the camera: the ratio of the focal length of its lens to the diameter of
+    /// its aperture.
+    ///
+    /// Smaller values open the aperture, which makes the depth of field shallower and the blur
+    /// stronger. The default value is 1.0.
+    pub aperture_f_stops: f32,
+
+    /// The maximum diameter of the circle of confusion, in pixels.
+    ///
+    /// This limits the blur, which gets more expensive as it gets wider. The default value is 64.0.
+    pub max_circle_of_confusion_diameter: f32,
+
+    /// The distance beyond which the objects are blurred like at this distance, in meters.
+    ///
+    /// This can be used to keep the background, such as a skybox, from being blurred too much.
+    /// The default value is infinity.
+    pub max_depth: f32,
+}
+
+impl Default for DepthOfFieldSettings {
+    fn default() -> Self {
+        Self {
+            mode: DepthOfFieldMode::default(),
+            focal_distance: 10.0,
+            sensor_height: DEFAULT_SENSOR_HEIGHT,
+            aperture_f_stops: 1.0,
+            max_circle_of_confusion_diameter: 64.0,
+            max_depth: f32::INFINITY,
+        }
+    }
+}
+
+/// How [`DepthOfFieldSettings`] blurs the out of focus parts of the image.
+#[derive(Reflect, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[reflect(Default, PartialEq, Hash)]
+pub enum DepthOfFieldMode {
+    /// Gathers the pixels around each pixel in a disk, which gives the round highlights of a
+    /// physical lens, and keeps the sharp foreground from bleeding onto the blurred background.
+    #[default]
+    Bokeh,
+    /// A separable gaussian blur, which is faster than [`DepthOfFieldMode::Bokeh`] but looks less
+    /// like a physical lens.
+    Gaussian,
+}
+
+/// The uniform of the depth of field shader, extracted from the [`DepthOfFieldSettings`] and the
+/// projection of a camera.
+#[doc(hidden)]
+#[derive(Component, ShaderType, Clone, Copy)]
+pub struct DepthOfFieldUniform {
+    focal_distance: f32,
+    /// The diameter in pixels of the circle of confusion of a point at an infinite distance.
+    coc_scale_factor: f32,
+    max_circle_of_confusion_diameter: f32,
+    max_depth: f32,
+}
+
+impl DepthOfFieldUniform {
+    /// Computes the uniform of a camera with a vertical field of view of `fov` radians, rendering
+    /// a viewport `viewport_height` pixels high.
+    fn new(settings: &DepthOfFieldSettings, fov: f32, viewport_height: f32) -> Self {
+        let focal_length = settings.sensor_height / (2.0 * (fov * 0.5).tan());
+        // The diameter of the circle of confusion of a point at the distance `d` is, with the
+        // thin lens model, `f² / (N (D - f)) * |1 - D / d|` on the sensor.
+        let coc_on_sensor = focal_length * focal_length
+            / (settings.aperture_f_stops * (settings.focal_distance - focal_length));
+        Self {
+            focal_distance: settings.focal_distance,
+            coc_scale_factor: (coc_on_sensor / settings.sensor_height * viewport_height).abs(),
+            max_circle_of_confusion_diameter: settings.max_circle_of_confusion_diameter,
+            max_depth: settings.max_depth,
+        }
+    }
+}
+
+/// Render [`bevy_render::render_graph::Node`] applying depth of field.
+#[derive(Default)]
+pub struct DepthOfFieldNode;
+
+impl ViewNode for DepthOfFieldNode {
+    type ViewQuery = (
+        &'static ExtractedCamera,
+        &'static ViewTarget,
+        &'static ViewDepthTexture,
+        &'static ViewUniformOffset,
+        &'static DynamicUniformIndex<DepthOfFieldUniform>,
+        &'static DepthOfFieldPipelineIds,
+        Option<&'static DepthOfFieldAuxiliaryTexture>,
+    );
+
+    fn run(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        (
+            camera,
+            view_target,
+            depth,
+            view_uniform_offset,
+            dof_uniform_index,
+            pipeline_ids,
+            auxiliary_texture,
+        ): QueryItem<Self::ViewQuery>,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        let dof_pipeline = world.resource::<DepthOfFieldPipeline>();
+        let pipeline_cache = world.resource::<PipelineCache>();
+        let (Some(view_uniforms), Some(dof_uniforms)) = (
+            world.resource::<ViewUniforms>().uniforms.binding(),
+            world
+                .resource::<ComponentUniforms<DepthOfFieldUniform>>()
+                .binding(),
+        ) else {
+            return Ok(());
+        };
+
+        // The Gaussian blur runs horizontally into the auxiliary texture, then vertically.
+        let passes: Vec<(CachedRenderPipelineId, Option<&TextureView>)> = match pipeline_ids {
+            DepthOfFieldPipelineIds::Bokeh(pipeline) => vec![(*pipeline, None)],
+            DepthOfFieldPipelineIds::Gaussian {
+                horizontal,
+                vertical,
+            } => {
+                let Some(auxiliary_texture) = auxiliary_texture else {
+                    return Ok(());
+                };
+                vec![
+                    (*horizontal, Some(&auxiliary_texture.0.default_view)),
+                    (*vertical, None),
+                ]
+            }
+        };
+        let mut pipelines = Vec::with_capacity(passes.len());
+        for (pipeline_id, _) in &passes {
+            let Some(pipeline) = pipeline_cache.get_render_pipeline(*pipeline_id) else {
+                return Ok(());
+            };
+            pipelines.push(pipeline);
+        }
+
+        let layout = if depth.texture.sample_count() > 1 {
+            &dof_pipeline.multisampled_layout
+        } else {
+            &dof_pipeline.layout
+        };
+        let post_process = view_target.post_process_write();
+        let mut input = post_process.source;
+        for ((_, auxiliary_output), pipeline) in passes.iter().zip(pipelines) {
+            let output = auxiliary_output.unwrap_or(post_process.destination);
+            let bind_group = render_context.render_device().create_bind_group(
+                "depth_of_field_bind_group",
+                layout,
+                &BindGroupEntries::sequential((
+                    view_uniforms.clone(),
+                    dof_uniforms.clone(),
+                    input,
+                    depth.view(),
+                    &dof_pipeline.sampler,
+                )),
+            );
+
+            let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+                label: Some("depth_of_field_pass"),
+                color_attachments: &[Some(RenderPassColorAttachment {
+                    view: output,
+                    resolve_target: None,
+                    ops: Operations::default(),
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            render_pass.set_render_pipeline(pipeline);
+            render_pass.set_bind_group(
+                0,
+                &bind_group,
+                &[view_uniform_offset.offset, dof_uniform_index.index()],
+            );
+            if let Some(viewport) = camera.viewport.as_ref() {
+                render_pass.set_camera_viewport(viewport);
+            }
+            render_pass.draw(0..3, 0..1);
+
+            input = output;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Resource)]
+pub struct DepthOfFieldPipeline {
+    layout: BindGroupLayout,
+    multisampled_layout: BindGroupLayout,
+    sampler: Sampler,
+}
+
+impl FromWorld for DepthOfFieldPipeline {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+
+        let layout = |label, depth| {
+            render_device.create_bind_group_layout(
+                label,
+                &BindGroupLayoutEntries::sequential(
+                    ShaderStages::FRAGMENT,
+                    (
+                        uniform_buffer::<ViewUniform>(true),
+                        uniform_buffer::<DepthOfFieldUniform>(true),
+                        // Color
+                        texture_2d(TextureSampleType::Float { filterable: true }),
+                        // Depth
+                        depth,
+                        sampler(SamplerBindingType::Filtering),
+                    ),
+                ),
+            )
+        };
+
+        DepthOfFieldPipeline {
+            layout: layout("depth_of_field_bind_group_layout", texture_depth_2d()),
+            multisampled_layout: layout(
+                "depth_of_field_multisampled_bind_group_layout",
+                texture_depth_2d_multisampled(),
+            ),
+            sampler: render_device.create_sampler(&SamplerDescriptor {
+                label: Some("depth_of_field_sampler"),
+                mag_filter: FilterMode::Linear,
+                min_filter: FilterMode::Linear,
+                ..SamplerDescriptor::default()
+            }),
+        }
+    }
+}
+
+/// A pass of the depth of field shader.
+#[derive(PartialEq, Eq, Hash, Clone, Copy)]
+enum DepthOfFieldPass {
+    Bokeh,
+    GaussianHorizontal,
+    GaussianVertical,
+}
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy)]
+pub struct DepthOfFieldPipelineKey {
+    pass: DepthOfFieldPass,
+    hdr: bool,
+    multisampled: bool,
+}
+
+impl SpecializedRenderPipeline for DepthOfFieldPipeline {
+    type Key = DepthOfFieldPipelineKey;
+
+    fn specialize(&self, key: Self::Key) -> RenderPipelineDescriptor {
+        let mut shader_defs = vec![];
+        let layout = if key.multisampled {
+            shader_defs.push("MULTISAMPLED".into());
+            self.multisampled_layout.clone()
+        } else {
+            self.layout.clone()
+        };
+        let entry_point = match key.pass {
+            DepthOfFieldPass::Bokeh => "bokeh",
+            DepthOfFieldPass::GaussianHorizontal => "gaussian_horizontal",
+            DepthOfFieldPass::GaussianVertical => "gaussian_vertical",
+        };
+
+        RenderPipelineDescriptor {
+            label: Some("depth_of_field_pipeline".into()),
+            layout: vec![layout],
+            vertex: fullscreen_shader_vertex_state(),
+            fragment: Some(FragmentState {
+                shader: DOF_SHADER_HANDLE,
+                shader_defs,
+                entry_point: entry_point.into(),
+                targets: vec![Some(ColorTargetState {
+                    format: if key.hdr {
+                        ViewTarget::TEXTURE_FORMAT_HDR
+                    } else {
+                        TextureFormat::bevy_default()
+                    },
+                    blend: None,
+                    write_mask: ColorWrites::ALL,
+                })],
+            }),
+            primitive: PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: MultisampleState::default(),
+            push_constant_ranges: Vec::new(),
+        }
+    }
+}
+
+/// The pipelines of the passes applying depth of field to a view.
+#[derive(Component)]
+pub enum DepthOfFieldPipelineIds {
+    Bokeh(CachedRenderPipelineId),
+    Gaussian {
+        horizontal: CachedRenderPipelineId,
+        vertical: CachedRenderPipelineId,
+    },
+}
+
+/// The texture which the horizontal pass of the [`DepthOfFieldMode::Gaussian`] blur renders to.
+#[derive(Component)]
+pub struct DepthOfFieldAuxiliaryTexture(CachedTexture);
+
+/// Lets the depth of field shader read the depth textures of the cameras with
+/// [`DepthOfFieldSettings`].
+fn configure_depth_of_field_depth_textures(
+    mut cameras: Query<&mut Camera3d, With<DepthOfFieldSettings>>,
+) {
+    for mut camera_3d in &mut cameras {
+        let usages = TextureUsages::from(camera_3d.depth_texture_usages);
+        if !usages.contains(TextureUsages::TEXTURE_BINDING) {
+            camera_3d.depth_texture_usages = (usages | TextureUsages::TEXTURE_BINDING).into();
+        }
+    }
+}
+
+fn extract_depth_of_field_settings(
+    mut commands: Commands,
+    cameras: Extract<Query<(Entity, &Camera, &Projection, &DepthOfFieldSettings), With<Camera3d>>>,
+) {
+    for (entity, camera, projection, settings) in &cameras {
+        let (Projection::Perspective(perspective), Some(viewport_size)) =
+            (projection, camera.physical_viewport_size())
+        else {
+            continue;
+        };
+        if !camera.is_active {
+            continue;
+        }
+        commands.get_or_spawn(entity).insert((
+            *settings,
+            DepthOfFieldUniform::new(settings, perspective.fov, viewport_size.y as f32),
+        ));
+    }
+}
+
+fn prepare_depth_of_field_pipelines(
+    mut commands: Commands,
+    pipeline_cache: Res<PipelineCache>,
+    mut pipelines: ResMut<SpecializedRenderPipelines<DepthOfFieldPipeline>>,
+    pipeline: Res<DepthOfFieldPipeline>,
+    msaa: Res<Msaa>,
+    views: Query<(Entity, &ExtractedView, &DepthOfFieldSettings)>,
+) {
+    for (entity, view, settings) in &views {
+        let mut specialize = |pass| {
+            pipelines.specialize(
+                &pipeline_cache,
+                &pipeline,
+                DepthOfFieldPipelineKey {
+                    pass,
+                    hdr: view.hdr,
+                    multisampled: msaa.samples() > 1,
+                },
+            )
+        };
+        let pipeline_ids = match settings.mode {
+            DepthOfFieldMode::Bokeh => {
+                DepthOfFieldPipelineIds::Bokeh(specialize(DepthOfFieldPass::Bokeh))
+            }
+            DepthOfFieldMode::Gaussian => DepthOfFieldPipelineIds::Gaussian {
+                horizontal: specialize(DepthOfFieldPass::GaussianHorizontal),
+                vertical: specialize(DepthOfFieldPass::GaussianVertical),
+            },
+        };
+        commands.entity(entity).insert(pipeline_ids);
+    }
+}
+
+fn prepare_depth_of_field_textures(
+    mut commands: Commands,
+    mut texture_cache: ResMut<TextureCache>,
+    render_device: Res<RenderDevice>,
+    views: Query<(
+        Entity,
+        &ExtractedCamera,
+        &ExtractedView,
+        &DepthOfFieldSettings,
+    )>,
+) {
+    for (entity, camera, view, settings) in &views {
+        let (DepthOfFieldMode::Gaussian, Some(physical_viewport_size)) =
+            (settings.mode, camera.physical_viewport_size)
+        else {
+            continue;
+        };
+        let texture = texture_cache.get(
+            &render_device,
+            TextureDescriptor {
+                label: Some("depth_of_field_auxiliary_texture"),
+                size: Extent3d {
+                    depth_or_array_layers: 1,
+                    width: physical_viewport_size.x,
+                    height: physical_viewport_size.y,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: TextureDimension::D2,
+                format: if view.hdr {
+                    ViewTarget::TEXTURE_FORMAT_HDR
+                } else {
+                    TextureFormat::bevy_default()
+                },
+                usage: TextureUsages::TEXTURE_BINDING | TextureUsages::RENDER_ATTACHMENT,
+                view_formats: &[],
+            },
+        );
+        commands
+            .entity(entity)
+            .insert(DepthOfFieldAuxiliaryTexture(texture));
+    }
+}
@@ -1,15 +1,21 @@
 // FIXME(3492): remove once docs are ready
 #![allow(missing_docs)]
 
+pub mod auto_exposure;
 pub mod blit;
 pub mod bloom;
+pub mod color_grading_lut;
 pub mod contrast_adaptive_sharpening;
 pub mod core_2d;
 pub mod core_3d;
 pub mod deferred;
+pub mod dof;
 pub mod fullscreen_vertex_shader;
 pub mod fxaa;
+pub mod motion_blur;
 pub mod msaa_writeback;
+pub mod oit;
+pub mod post_process;
 pub mod prepass;
 mod skybox;
 mod taa;
@@ -37,15 +43,20 @@ pub mod prelude {
 }
 
 use crate::{
+    auto_exposure::AutoExposurePlugin,
     blit::BlitPlugin,
     bloom::BloomPlugin,
+    color_grading_lut::ColorGradingLutPlugin,
     contrast_adaptive_sharpening::CASPlugin,
     core_2d::Core2dPlugin,
     core_3d::Core3dPlugin,
     deferred::copy_lighting_id::CopyDeferredLightingIdPlugin,
+    dof::DepthOfFieldPlugin,
     fullscreen_vertex_shader::FULLSCREEN_SHADER_HANDLE,
     fxaa::FxaaPlugin,
+    motion_blur::MotionBlurPlugin,
     msaa_writeback::MsaaWritebackPlugin,
+    oit::OrderIndependentTransparencyPlugin,
     prepass::{DeferredPrepass, DepthPrepass, MotionVectorPrepass, NormalPrepass},
     tonemapping::TonemappingPlugin,
     upscaling::UpscalingPlugin,
@@ -81,6 +92,11 @@ impl Plugin for CorePipelinePlugin {
                 BloomPlugin,
                 FxaaPlugin,
                 CASPlugin,
+                DepthOfFieldPlugin,
+                MotionBlurPlugin,
+                AutoExposurePlugin,
+                ColorGradingLutPlugin,
+                OrderIndependentTransparencyPlugin,
             ));
     }
 }
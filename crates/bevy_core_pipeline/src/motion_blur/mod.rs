@@ -0,0 +1,358 @@
+//! Per-object and camera motion blur, from the motion vectors of the prepass.
+//!
+//! Add a [`MotionBlurBundle`] to a 3D camera to enable it.
+
+use crate::{
+    core_3d::graph::{Core3d, Node3d},
+    fullscreen_vertex_shader::fullscreen_shader_vertex_state,
+    prepass::{DepthPrepass, MotionVectorPrepass, ViewPrepassTextures},
+};
+use bevy_app::{App, Plugin};
+use bevy_asset::{load_internal_asset, Handle};
+use bevy_ecs::{prelude::*, query::QueryItem};
+use bevy_reflect::{std_traits::ReflectDefault, Reflect};
+use bevy_render::{
+    camera::{Camera, ExtractedCamera},
+    extract_component::{
+        ComponentUniforms, DynamicUniformIndex, ExtractComponent, ExtractComponentPlugin,
+        UniformComponentPlugin,
+    },
+    render_graph::{NodeRunError, RenderGraphApp, RenderGraphContext, ViewNode, ViewNodeRunner},
+    render_resource::{
+        binding_types::{
+            sampler, texture_2d, texture_2d_multisampled, texture_depth_2d,
+            texture_depth_2d_multisampled, uniform_buffer,
+        },
+        *,
+    },
+    renderer::{RenderContext, RenderDevice},
+    texture::BevyDefault,
+    view::{ExtractedView, Msaa, ViewTarget},
+    Render, RenderApp, RenderSet,
+};
+
+const MOTION_BLUR_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(987457899187986082);
+
+/// Adds support for motion blur.
+///
+/// See [`MotionBlurSettings`] for more details.
+pub struct MotionBlurPlugin;
+
+impl Plugin for MotionBlurPlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(
+            app,
+            MOTION_BLUR_SHADER_HANDLE,
+            "motion_blur.wgsl",
+            Shader::from_wgsl
+        );
+
+        app.register_type::<MotionBlurSettings>().add_plugins((
+            ExtractComponentPlugin::<MotionBlurSettings>::default(),
+            UniformComponentPlugin::<MotionBlurUniform>::default(),
+        ));
+
+        let Ok(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        render_app
+            .init_resource::<SpecializedRenderPipelines<MotionBlurPipeline>>()
+            .add_systems(
+                Render,
+                prepare_motion_blur_pipelines.in_set(RenderSet::Prepare),
+            )
+            .add_render_graph_node::<ViewNodeRunner<MotionBlurNode>>(Core3d, Node3d::MotionBlur)
+            .add_render_graph_edges(
+                Core3d,
+                (Node3d::EndMainPass, Node3d::MotionBlur, Node3d::Bloom),
+            );
+    }
+
+    fn finish(&self, app: &mut App) {
+        let Ok(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        render_app.init_resource::<MotionBlurPipeline>();
+    }
+}
+
+/// Bundle to apply motion blur to a 3D camera, with the prepasses it reads from.
+#[derive(Bundle, Default)]
+pub struct MotionBlurBundle {
+    pub settings: MotionBlurSettings,
+    pub depth_prepass: DepthPrepass,
+    pub motion_vector_prepass: MotionVectorPrepass,
+}
+
+/// Component to apply motion blur to a 3D camera with a [`DepthPrepass`] and a
+/// [`MotionVectorPrepass`].
+///
+/// Each pixel is blurred along the motion of the object it shows on the screen, which includes the
+/// motion of the camera. The objects in front of a pixel only blur over it when they move far
+/// enough, so moving objects don't smear the static objects in front of them.
+///
+/// Motion blur runs before temporal anti-aliasing, which smooths the noise of its samples.
+#[derive(Component, Reflect, Clone, Copy, Debug, PartialEq)]
+#[reflect(Component, Default)]
+pub struct MotionBlurSettings {
+    /// The fraction of the duration of a frame during which the shutter of the camera is open.
+    ///
+    /// The blur gets longer as the shutter stays open longer: 0.5 is the 180° shutter of films,
+    /// and 1.0 blurs over the whole motion since the previous frame. A value of 0.0 disables
+    /// motion blur.
+    ///
+    /// The default value is 0.5.
+    pub shutter_angle: f32,
+    /// The number of samples taken on each side of a pixel along its motion.
+    ///
+    /// More samples give a smoother blur, but are more expensive. A value of 0 disables motion
+    /// blur.
+    ///
+    /// The default value is 4.
+    pub samples: u32,
+}
+
+impl Default for MotionBlurSettings {
+    fn default() -> Self {
+        Self {
+            shutter_angle: 0.5,
+            samples: 4,
+        }
+    }
+}
+
+/// The uniform struct extracted from [`MotionBlurSettings`] attached to a [`Camera`].
+#[doc(hidden)]
+#[derive(Component, ShaderType, Clone)]
+pub struct MotionBlurUniform {
+    shutter_angle: f32,
+    samples: u32,
+    #[cfg(all(feature = "webgl", target_arch = "wasm32", not(feature = "webgpu")))]
+    _wasm_padding_12b: u32,
+    #[cfg(all(feature = "webgl", target_arch = "wasm32", not(feature = "webgpu")))]
+    _wasm_padding_16b: u32,
+}
+
+impl ExtractComponent for MotionBlurSettings {
+    type QueryData = &'static Self;
+    type QueryFilter = (With<Camera>, With<DepthPrepass>, With<MotionVectorPrepass>);
+    type Out = MotionBlurUniform;
+
+    fn extract_component(item: QueryItem<Self::QueryData>) -> Option<Self::Out> {
+        if item.shutter_angle <= 0.0 || item.samples == 0 {
+            return None;
+        }
+        Some(MotionBlurUniform {
+            shutter_angle: item.shutter_angle,
+            samples: item.samples,
+            #[cfg(all(feature = "webgl", target_arch = "wasm32", not(feature = "webgpu")))]
+            _wasm_padding_12b: 0,
+            #[cfg(all(feature = "webgl", target_arch = "wasm32", not(feature = "webgpu")))]
+            _wasm_padding_16b: 0,
+        })
+    }
+}
+
+/// Render [`bevy_render::render_graph::Node`] applying motion blur.
+#[derive(Default)]
+pub struct MotionBlurNode;
+
+impl ViewNode for MotionBlurNode {
+    type ViewQuery = (
+        &'static ExtractedCamera,
+        &'static ViewTarget,
+        &'static ViewPrepassTextures,
+        &'static MotionBlurPipelineId,
+        &'static DynamicUniformIndex<MotionBlurUniform>,
+    );
+
+    fn run(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        (camera, view_target, prepass_textures, pipeline_id, uniform_index): QueryItem<
+            Self::ViewQuery,
+        >,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        let motion_blur_pipeline = world.resource::<MotionBlurPipeline>();
+        let pipeline_cache = world.resource::<PipelineCache>();
+        let (
+            Some(pipeline),
+            Some(uniforms),
+            Some(prepass_motion_vectors_texture),
+            Some(prepass_depth_texture),
+        ) = (
+            pipeline_cache.get_render_pipeline(pipeline_id.0),
+            world
+                .resource::<ComponentUniforms<MotionBlurUniform>>()
+                .binding(),
+            &prepass_textures.motion_vectors,
+            &prepass_textures.depth,
+        )
+        else {
+            return Ok(());
+        };
+
+        let layout = if prepass_depth_texture.texture.texture.sample_count() > 1 {
+            &motion_blur_pipeline.multisampled_layout
+        } else {
+            &motion_blur_pipeline.layout
+        };
+        let post_process = view_target.post_process_write();
+        let bind_group = render_context.render_device().create_bind_group(
+            "motion_blur_bind_group",
+            layout,
+            &BindGroupEntries::sequential((
+                post_process.source,
+                &prepass_motion_vectors_texture.texture.default_view,
+                &prepass_depth_texture.texture.default_view,
+                &motion_blur_pipeline.sampler,
+                uniforms,
+            )),
+        );
+
+        let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+            label: Some("motion_blur_pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: post_process.destination,
+                resolve_target: None,
+                ops: Operations::default(),
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        render_pass.set_render_pipeline(pipeline);
+        render_pass.set_bind_group(0, &bind_group, &[uniform_index.index()]);
+        if let Some(viewport) = camera.viewport.as_ref() {
+            render_pass.set_camera_viewport(viewport);
+        }
+        render_pass.draw(0..3, 0..1);
+
+        Ok(())
+    }
+}
+
+#[derive(Resource)]
+pub struct MotionBlurPipeline {
+    layout: BindGroupLayout,
+    multisampled_layout: BindGroupLayout,
+    sampler: Sampler,
+}
+
+impl FromWorld for MotionBlurPipeline {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+
+        let layout = |label, motion_vectors, depth| {
+            render_device.create_bind_group_layout(
+                label,
+                &BindGroupLayoutEntries::sequential(
+                    ShaderStages::FRAGMENT,
+                    (
+                        // View target (read)
+                        texture_2d(TextureSampleType::Float { filterable: true }),
+                        // Motion vectors
+                        motion_vectors,
+                        // Depth
+                        depth,
+                        sampler(SamplerBindingType::Filtering),
+                        uniform_buffer::<MotionBlurUniform>(true),
+                    ),
+                ),
+            )
+        };
+
+        MotionBlurPipeline {
+            layout: layout(
+                "motion_blur_bind_group_layout",
+                texture_2d(TextureSampleType::Float { filterable: false }),
+                texture_depth_2d(),
+            ),
+            multisampled_layout: layout(
+                "motion_blur_multisampled_bind_group_layout",
+                texture_2d_multisampled(TextureSampleType::Float { filterable: false }),
+                texture_depth_2d_multisampled(),
+            ),
+            sampler: render_device.create_sampler(&SamplerDescriptor {
+                label: Some("motion_blur_sampler"),
+                mag_filter: FilterMode::Linear,
+                min_filter: FilterMode::Linear,
+                ..SamplerDescriptor::default()
+            }),
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy)]
+pub struct MotionBlurPipelineKey {
+    hdr: bool,
+    multisampled: bool,
+}
+
+impl SpecializedRenderPipeline for MotionBlurPipeline {
+    type Key = MotionBlurPipelineKey;
+
+    fn specialize(&self, key: Self::Key) -> RenderPipelineDescriptor {
+        let mut shader_defs = vec![];
+        let layout = if key.multisampled {
+            shader_defs.push("MULTISAMPLED".into());
+            self.multisampled_layout.clone()
+        } else {
+            self.layout.clone()
+        };
+
+        RenderPipelineDescriptor {
+            label: Some("motion_blur_pipeline".into()),
+            layout: vec![layout],
+            vertex: fullscreen_shader_vertex_state(),
+            fragment: Some(FragmentState {
+                shader: MOTION_BLUR_SHADER_HANDLE,
+                shader_defs,
+                entry_point: "fragment".into(),
+                targets: vec![Some(ColorTargetState {
+                    format: if key.hdr {
+                        ViewTarget::TEXTURE_FORMAT_HDR
+                    } else {
+                        TextureFormat::bevy_default()
+                    },
+                    blend: None,
+                    write_mask: ColorWrites::ALL,
+                })],
+            }),
+            primitive: PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: MultisampleState::default(),
+            push_constant_ranges: Vec::new(),
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct MotionBlurPipelineId(CachedRenderPipelineId);
+
+fn prepare_motion_blur_pipelines(
+    mut commands: Commands,
+    pipeline_cache: Res<PipelineCache>,
+    mut pipelines: ResMut<SpecializedRenderPipelines<MotionBlurPipeline>>,
+    pipeline: Res<MotionBlurPipeline>,
+    msaa: Res<Msaa>,
+    views: Query<(Entity, &ExtractedView), With<MotionBlurUniform>>,
+) {
+    for (entity, view) in &views {
+        let pipeline_id = pipelines.specialize(
+            &pipeline_cache,
+            &pipeline,
+            MotionBlurPipelineKey {
+                hdr: view.hdr,
+                multisampled: msaa.samples() > 1,
+            },
+        );
+        commands
+            .entity(entity)
+            .insert(MotionBlurPipelineId(pipeline_id));
+    }
+}
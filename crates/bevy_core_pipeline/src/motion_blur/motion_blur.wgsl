@@ -0,0 +1,74 @@
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+
+struct MotionBlurParams {
+    shutter_angle: f32,
+    samples: u32,
+#ifdef SIXTEEN_BYTE_ALIGNMENT
+    _wasm_padding_12b: u32,
+    _wasm_padding_16b: u32,
+#endif
+}
+
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+#ifdef MULTISAMPLED
+@group(0) @binding(1) var motion_vectors: texture_multisampled_2d<f32>;
+@group(0) @binding(2) var depth: texture_depth_multisampled_2d;
+#else
+@group(0) @binding(1) var motion_vectors: texture_2d<f32>;
+@group(0) @binding(2) var depth: texture_depth_2d;
+#endif
+@group(0) @binding(3) var screen_sampler: sampler;
+@group(0) @binding(4) var<uniform> settings: MotionBlurParams;
+
+fn load_texel(coords: vec2<f32>) -> vec2<i32> {
+    let size = vec2<i32>(textureDimensions(depth));
+    return clamp(vec2<i32>(coords), vec2(0), size - 1);
+}
+
+// The motion of the pixel during the exposure of the frame, in pixels.
+fn load_motion(coords: vec2<f32>) -> vec2<f32> {
+    let motion_vector = textureLoad(motion_vectors, load_texel(coords), 0).xy;
+    return motion_vector * vec2<f32>(textureDimensions(screen_texture)) * settings.shutter_angle;
+}
+
+fn sample_color(coords: vec2<f32>) -> vec4<f32> {
+    let uv = coords / vec2<f32>(textureDimensions(screen_texture));
+    return textureSampleLevel(screen_texture, screen_sampler, uv, 0.0);
+}
+
+// Jitters the samples of neighboring pixels, trading banding for noise.
+// https://www.iryoku.com/next-generation-post-processing-in-call-of-duty-advanced-warfare
+fn interleaved_gradient_noise(coords: vec2<f32>) -> f32 {
+    return fract(52.9829189 * fract(dot(coords, vec2(0.06711056, 0.00583715))));
+}
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let frag_coord = in.position.xy;
+    let motion = load_motion(frag_coord);
+    let center_depth = textureLoad(depth, load_texel(frag_coord), 0);
+    let noise = interleaved_gradient_noise(frag_coord);
+
+    var color = sample_color(frag_coord);
+    var total_weight = 1.0;
+    // The pixel is blurred along its motion, centered on its position in the middle of the
+    // exposure.
+    for (var i = 0u; i < settings.samples; i += 1u) {
+        let t = (f32(i) + noise) / f32(settings.samples) * 0.5;
+        for (var side = 0u; side < 2u; side += 1u) {
+            let offset = motion * select(t, -t, side == 1u);
+            let sample_coords = frag_coord + offset;
+            // With the reversed depth, the samples in front of the pixel have a greater depth.
+            // They only blur over the pixel if their own motion reaches it.
+            let sample_depth = textureLoad(depth, load_texel(sample_coords), 0);
+            let sample_motion = load_motion(sample_coords);
+            if sample_depth > center_depth && length(sample_motion) * 0.5 < length(offset) {
+                continue;
+            }
+            color += sample_color(sample_coords);
+            total_weight += 1.0;
+        }
+    }
+
+    return color / total_weight;
+}
@@ -0,0 +1,451 @@
+//! Order independent transparency, which blends the transparent fragments of each pixel in the
+//! order of their depth, instead of the order of the meshes they belong to.
+//!
+//! Add [`OrderIndependentTransparencySettings`] to a 3D camera to enable it.
+
+use crate::{
+    core_3d::{
+        graph::{Core3d, Node3d},
+        Camera3d,
+    },
+    fullscreen_vertex_shader::fullscreen_shader_vertex_state,
+};
+use bevy_app::{App, Plugin, PostUpdate};
+use bevy_asset::{load_internal_asset, Handle};
+use bevy_ecs::{entity::EntityHashMap, prelude::*, query::QueryItem};
+use bevy_log::warn;
+use bevy_math::UVec2;
+use bevy_reflect::{std_traits::ReflectDefault, Reflect};
+use bevy_render::{
+    camera::{Camera, ExtractedCamera},
+    render_graph::{NodeRunError, RenderGraphApp, RenderGraphContext, ViewNode, ViewNodeRunner},
+    render_resource::{
+        binding_types::{
+            storage_buffer_sized, texture_depth_2d, texture_depth_2d_multisampled, uniform_buffer,
+        },
+        *,
+    },
+    renderer::{RenderContext, RenderDevice, RenderQueue},
+    texture::BevyDefault,
+    view::{
+        ExtractedView, Msaa, ViewDepthTexture, ViewTarget, ViewUniform, ViewUniformOffset,
+        ViewUniforms,
+    },
+    Extract, ExtractSchedule, Render, RenderApp, RenderSet,
+};
+
+const OIT_DRAW_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(4042527984320512714);
+const OIT_RESOLVE_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(7698420424769536510);
+
+/// The size of a fragment stored in the layers, which must match `oit_draw.wgsl`.
+const OIT_FRAGMENT_SIZE: u64 = 8;
+
+/// Adds support for order independent transparency.
+///
+/// See [`OrderIndependentTransparencySettings`] for more details.
+pub struct OrderIndependentTransparencyPlugin;
+
+impl Plugin for OrderIndependentTransparencyPlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(
+            app,
+            OIT_DRAW_SHADER_HANDLE,
+            "oit_draw.wgsl",
+            Shader::from_wgsl
+        );
+        load_internal_asset!(
+            app,
+            OIT_RESOLVE_SHADER_HANDLE,
+            "oit_resolve.wgsl",
+            Shader::from_wgsl
+        );
+
+        app.register_type::<OrderIndependentTransparencySettings>()
+            .add_systems(PostUpdate, configure_oit_depth_textures);
+    }
+
+    fn finish(&self, app: &mut App) {
+        let Ok(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        if !oit_is_supported(render_app.world.resource::<RenderDevice>()) {
+            warn!("OrderIndependentTransparencyPlugin not loaded. GPU lacks support for storage buffers in fragment shaders.");
+            return;
+        }
+
+        render_app
+            .init_resource::<OitResolvePipeline>()
+            .init_resource::<SpecializedRenderPipelines<OitResolvePipeline>>()
+            .init_resource::<OitBuffers>()
+            .add_systems(ExtractSchedule, extract_oit_settings)
+            .add_systems(
+                Render,
+                (
+                    prepare_oit_resolve_pipelines.in_set(RenderSet::Prepare),
+                    prepare_oit_buffers.in_set(RenderSet::PrepareResources),
+                ),
+            )
+            .add_render_graph_node::<ViewNodeRunner<OitResolveNode>>(Core3d, Node3d::OitResolve)
+            .add_render_graph_edges(
+                Core3d,
+                (
+                    Node3d::MainTransparentPass,
+                    Node3d::OitResolve,
+                    Node3d::EndMainPass,
+                ),
+            );
+    }
+}
+
+/// Component to blend the transparent meshes seen by a 3D camera in the order of the depth of
+/// their fragments, so that overlapping or intersecting transparent meshes, like the panes of a
+/// window, blend correctly whatever their order.
+///
+/// Instead of being blended into the view, the fragments of the meshes with the
+/// [`Blend`](bevy_render::alpha::AlphaMode::Blend),
+/// [`Premultiplied`](bevy_render::alpha::AlphaMode::Premultiplied) and
+/// [`Add`](bevy_render::alpha::AlphaMode::Add) alpha modes are stored in a list per pixel, in
+/// storage buffers. A resolve pass then sorts the fragments of each pixel by depth and blends
+/// them into the view, after the transparent pass. The other transparent meshes are still sorted
+/// per mesh, as are the meshes of custom shaders which don't store their fragments with
+/// `bevy_core_pipeline::oit::oit_draw`.
+///
+/// The storage buffers hold [`layer_count`](Self::layer_count) fragments per pixel of the
+/// viewport, so their memory grows with the resolution and the number of layers.
+///
+/// Requires storage buffers in fragment shaders, so it has no effect on WebGL 2.
+#[derive(Component, Reflect, Clone, Copy, Debug, PartialEq)]
+#[reflect(Component, Default)]
+pub struct OrderIndependentTransparencySettings {
+    /// The maximum number of transparent fragments stored per pixel. The fragments drawn after
+    /// it is reached are dropped.
+    ///
+    /// The default value is 8.
+    pub layer_count: u32,
+}
+
+impl Default for OrderIndependentTransparencySettings {
+    fn default() -> Self {
+        Self { layer_count: 8 }
+    }
+}
+
+/// Whether the GPU supports order independent transparency, which stores fragments in storage
+/// buffers from the fragment shaders of the meshes, on top of the storage buffers of their
+/// clustered lights and decals.
+pub fn oit_is_supported(render_device: &RenderDevice) -> bool {
+    matches!(
+        render_device.get_supported_read_only_binding_type(6),
+        BufferBindingType::Storage { .. }
+    )
+}
+
+/// Lets the resolve pass read the depth textures of the cameras with
+/// [`OrderIndependentTransparencySettings`].
+fn configure_oit_depth_textures(
+    mut cameras: Query<&mut Camera3d, With<OrderIndependentTransparencySettings>>,
+) {
+    for mut camera_3d in &mut cameras {
+        let usages = TextureUsages::from(camera_3d.depth_texture_usages);
+        if !usages.contains(TextureUsages::TEXTURE_BINDING) {
+            camera_3d.depth_texture_usages = (usages | TextureUsages::TEXTURE_BINDING).into();
+        }
+    }
+}
+
+/// Extracts the [`OrderIndependentTransparencySettings`] of the active cameras, with as many
+/// layers as fit in a storage buffer of the GPU.
+fn extract_oit_settings(
+    mut commands: Commands,
+    render_device: Res<RenderDevice>,
+    cameras: Extract<
+        Query<(Entity, &Camera, &OrderIndependentTransparencySettings), With<Camera3d>>,
+    >,
+) {
+    let max_binding_size = u64::from(render_device.limits().max_storage_buffer_binding_size);
+
+    for (entity, camera, settings) in &cameras {
+        let (true, Some(size)) = (camera.is_active, camera.physical_viewport_size()) else {
+            continue;
+        };
+
+        let pixel_count = u64::from(size.x) * u64::from(size.y);
+        let max_layer_count = max_binding_size / (pixel_count.max(1) * OIT_FRAGMENT_SIZE);
+        let layer_count = u64::from(settings.layer_count).min(max_layer_count) as u32;
+        if layer_count > 0 {
+            commands
+                .get_or_spawn(entity)
+                .insert(OrderIndependentTransparencySettings { layer_count });
+        }
+    }
+}
+
+/// The settings of the order independent transparency of a view, as seen by the shaders.
+#[derive(Clone, Copy, ShaderType)]
+pub struct OrderIndependentTransparencyUniform {
+    /// The number of fragments stored per pixel.
+    pub layer_count: u32,
+}
+
+/// The buffers storing the transparent fragments of a view, which persist across frames.
+#[derive(Component, Clone)]
+pub struct ViewOitBuffers {
+    /// The fragments of each pixel, by layer.
+    pub layers: Buffer,
+    /// The number of fragments stored for each pixel, which is cleared by the resolve pass.
+    pub layer_ids: Buffer,
+    /// The [`OrderIndependentTransparencyUniform`] of the view.
+    pub settings: Buffer,
+    /// The number of fragments stored per pixel.
+    pub layer_count: u32,
+    size: UVec2,
+}
+
+/// The [`ViewOitBuffers`] of each view, by the entity of the view.
+#[derive(Resource, Default)]
+pub struct OitBuffers(EntityHashMap<ViewOitBuffers>);
+
+fn prepare_oit_buffers(
+    mut commands: Commands,
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+    mut buffers: ResMut<OitBuffers>,
+    views: Query<(
+        Entity,
+        &ExtractedView,
+        &OrderIndependentTransparencySettings,
+    )>,
+) {
+    let mut previous_buffers = std::mem::take(&mut buffers.0);
+
+    for (entity, view, settings) in &views {
+        let size = UVec2::new(view.viewport.z, view.viewport.w);
+        let pixel_count = u64::from(size.x) * u64::from(size.y);
+        let layer_count = settings.layer_count;
+
+        let view_buffers = match previous_buffers.remove(&entity) {
+            Some(view_buffers)
+                if view_buffers.size == size && view_buffers.layer_count == layer_count =>
+            {
+                view_buffers
+            }
+            _ => {
+                let mut settings =
+                    UniformBuffer::from(OrderIndependentTransparencyUniform { layer_count });
+                settings.set_label(Some("oit_settings_buffer"));
+                settings.write_buffer(&render_device, &render_queue);
+
+                // New buffers are zeroed, so no fragment is stored in them.
+                ViewOitBuffers {
+                    layers: render_device.create_buffer(&BufferDescriptor {
+                        label: Some("oit_layers_buffer"),
+                        size: pixel_count * u64::from(layer_count) * OIT_FRAGMENT_SIZE,
+                        usage: BufferUsages::STORAGE,
+                        mapped_at_creation: false,
+                    }),
+                    layer_ids: render_device.create_buffer(&BufferDescriptor {
+                        label: Some("oit_layer_ids_buffer"),
+                        size: pixel_count * 4,
+                        usage: BufferUsages::STORAGE,
+                        mapped_at_creation: false,
+                    }),
+                    settings: settings.buffer().unwrap().clone(),
+                    layer_count,
+                    size,
+                }
+            }
+        };
+
+        commands.entity(entity).insert(view_buffers.clone());
+        buffers.0.insert(entity, view_buffers);
+    }
+}
+
+#[derive(Resource)]
+pub struct OitResolvePipeline {
+    layout: BindGroupLayout,
+    multisampled_layout: BindGroupLayout,
+}
+
+impl FromWorld for OitResolvePipeline {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+
+        let layout = |label, depth| {
+            render_device.create_bind_group_layout(
+                label,
+                &BindGroupLayoutEntries::sequential(
+                    ShaderStages::FRAGMENT,
+                    (
+                        uniform_buffer::<ViewUniform>(true),
+                        // Layers
+                        storage_buffer_sized(false, None),
+                        // Layer ids
+                        storage_buffer_sized(false, None),
+                        // Depth
+                        depth,
+                    ),
+                ),
+            )
+        };
+
+        OitResolvePipeline {
+            layout: layout("oit_resolve_bind_group_layout", texture_depth_2d()),
+            multisampled_layout: layout(
+                "oit_resolve_multisampled_bind_group_layout",
+                texture_depth_2d_multisampled(),
+            ),
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct ViewOitResolvePipeline(CachedRenderPipelineId);
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy)]
+pub struct OitResolvePipelineKey {
+    hdr: bool,
+    samples: u32,
+    layer_count: u32,
+}
+
+impl SpecializedRenderPipeline for OitResolvePipeline {
+    type Key = OitResolvePipelineKey;
+
+    fn specialize(&self, key: Self::Key) -> RenderPipelineDescriptor {
+        let mut shader_defs = vec![ShaderDefVal::UInt(
+            "OIT_LAYER_COUNT".into(),
+            key.layer_count,
+        )];
+
+        let layout = if key.samples > 1 {
+            shader_defs.push("MULTISAMPLED".into());
+            self.multisampled_layout.clone()
+        } else {
+            self.layout.clone()
+        };
+
+        RenderPipelineDescriptor {
+            label: Some("oit_resolve_pipeline".into()),
+            layout: vec![layout],
+            vertex: fullscreen_shader_vertex_state(),
+            fragment: Some(FragmentState {
+                shader: OIT_RESOLVE_SHADER_HANDLE,
+                shader_defs,
+                entry_point: "fragment".into(),
+                targets: vec![Some(ColorTargetState {
+                    format: if key.hdr {
+                        ViewTarget::TEXTURE_FORMAT_HDR
+                    } else {
+                        TextureFormat::bevy_default()
+                    },
+                    // The fragments are premultiplied by their alpha.
+                    blend: Some(BlendState::PREMULTIPLIED_ALPHA_BLENDING),
+                    write_mask: ColorWrites::ALL,
+                })],
+            }),
+            primitive: PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: MultisampleState {
+                count: key.samples,
+                ..MultisampleState::default()
+            },
+            push_constant_ranges: Vec::new(),
+        }
+    }
+}
+
+fn prepare_oit_resolve_pipelines(
+    mut commands: Commands,
+    pipeline_cache: Res<PipelineCache>,
+    mut pipelines: ResMut<SpecializedRenderPipelines<OitResolvePipeline>>,
+    oit_resolve_pipeline: Res<OitResolvePipeline>,
+    msaa: Res<Msaa>,
+    views: Query<(Entity, &ExtractedView, &ViewOitBuffers)>,
+) {
+    for (entity, view, buffers) in &views {
+        let pipeline_id = pipelines.specialize(
+            &pipeline_cache,
+            &oit_resolve_pipeline,
+            OitResolvePipelineKey {
+                hdr: view.hdr,
+                samples: msaa.samples(),
+                layer_count: buffers.layer_count,
+            },
+        );
+
+        commands
+            .entity(entity)
+            .insert(ViewOitResolvePipeline(pipeline_id));
+    }
+}
+
+/// Render [`bevy_render::render_graph::Node`] blending the transparent fragments stored for each
+/// pixel of a view, in the order of their depth.
+#[derive(Default)]
+pub struct OitResolveNode;
+
+impl ViewNode for OitResolveNode {
+    type ViewQuery = (
+        &'static ExtractedCamera,
+        &'static ViewTarget,
+        &'static ViewDepthTexture,
+        &'static ViewUniformOffset,
+        &'static ViewOitBuffers,
+        &'static ViewOitResolvePipeline,
+    );
+
+    fn run(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        (camera, target, depth, view_uniform_offset, buffers, pipeline_id): QueryItem<
+            Self::ViewQuery,
+        >,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        let pipeline_cache = world.resource::<PipelineCache>();
+        let oit_resolve_pipeline = world.resource::<OitResolvePipeline>();
+        let (Some(pipeline), Some(view_uniforms)) = (
+            pipeline_cache.get_render_pipeline(pipeline_id.0),
+            world.resource::<ViewUniforms>().uniforms.binding(),
+        ) else {
+            return Ok(());
+        };
+
+        let layout = if depth.texture.sample_count() > 1 {
+            &oit_resolve_pipeline.multisampled_layout
+        } else {
+            &oit_resolve_pipeline.layout
+        };
+        let bind_group = render_context.render_device().create_bind_group(
+            "oit_resolve_bind_group",
+            layout,
+            &BindGroupEntries::sequential((
+                view_uniforms,
+                buffers.layers.as_entire_binding(),
+                buffers.layer_ids.as_entire_binding(),
+                depth.view(),
+            )),
+        );
+
+        let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+            label: Some("oit_resolve_pass"),
+            color_attachments: &[Some(target.get_color_attachment())],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+
+        if let Some(viewport) = camera.viewport.as_ref() {
+            render_pass.set_camera_viewport(viewport);
+        }
+
+        render_pass.set_render_pipeline(pipeline);
+        render_pass.set_bind_group(0, &bind_group, &[view_uniform_offset.offset]);
+        render_pass.draw(0..3, 0..1);
+
+        Ok(())
+    }
+}
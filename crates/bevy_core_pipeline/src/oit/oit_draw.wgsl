@@ -0,0 +1,30 @@
+#define_import_path bevy_core_pipeline::oit
+
+#import bevy_pbr::{
+    mesh_view_bindings::{view, oit_layers, oit_layer_ids, oit_settings},
+    rgb9e5::vec3_to_rgb9e5_,
+}
+
+// Stores the transparent fragment at `position`, with a color premultiplied by its alpha, for the
+// resolve pass to blend the fragments of each pixel in the order of their depth.
+//
+// Each fragment is stored as its color, in the RGB9E5 format, and its depth, in 24 bits, followed
+// by its alpha, in 8 bits.
+fn oit_draw(position: vec4<f32>, color: vec4<f32>) {
+    let coords = vec2<u32>(position.xy - view.viewport.xy);
+    let screen_index = coords.x + coords.y * u32(view.viewport.z);
+
+    let layer_id = atomicAdd(&oit_layer_ids[screen_index], 1u);
+    if layer_id >= oit_settings.layer_count {
+        // The layers are full, so the fragment is dropped. The count is clamped to keep it from
+        // overflowing.
+        atomicMin(&oit_layer_ids[screen_index], oit_settings.layer_count);
+        return;
+    }
+
+    let pixel_count = u32(view.viewport.z) * u32(view.viewport.w);
+    let depth = u32(saturate(position.z) * f32(0xffffffu));
+    let alpha = u32(saturate(color.a) * 255.0 + 0.5);
+    oit_layers[screen_index + layer_id * pixel_count] =
+        vec2(vec3_to_rgb9e5_(color.rgb), (depth << 8u) | alpha);
+}
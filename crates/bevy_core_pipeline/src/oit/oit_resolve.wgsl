@@ -0,0 +1,61 @@
+// Blends the transparent fragments stored by `oit_draw` for each pixel, in the order of their
+// depth, and clears them for the next frame.
+
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+#import bevy_pbr::rgb9e5::rgb9e5_to_vec3_
+#import bevy_render::view::View
+
+@group(0) @binding(0) var<uniform> view: View;
+@group(0) @binding(1) var<storage, read_write> layers: array<vec2<u32>>;
+@group(0) @binding(2) var<storage, read_write> layer_ids: array<atomic<u32>>;
+#ifdef MULTISAMPLED
+@group(0) @binding(3) var depth_texture: texture_depth_multisampled_2d;
+#else
+@group(0) @binding(3) var depth_texture: texture_depth_2d;
+#endif
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let coords = vec2<u32>(in.position.xy - view.viewport.xy);
+    let screen_index = coords.x + coords.y * u32(view.viewport.z);
+    let pixel_count = u32(view.viewport.z) * u32(view.viewport.w);
+
+    let count = min(atomicLoad(&layer_ids[screen_index]), #{OIT_LAYER_COUNT}u);
+    atomicStore(&layer_ids[screen_index], 0u);
+    if count == 0u {
+        discard;
+    }
+
+    // The fragments behind the opaque meshes may have been stored, as the depth test may run
+    // after the fragment shader.
+    let opaque_depth = u32(saturate(textureLoad(depth_texture, vec2<i32>(in.position.xy), 0)) * f32(0xffffffu));
+
+    // Insertion sort of the fragments, from the farthest to the closest. The depth is reversed, so
+    // the farthest fragments have the lowest depth.
+    var fragments: array<vec2<u32>, #{OIT_LAYER_COUNT}u>;
+    var fragment_count = 0u;
+    for (var i = 0u; i < count; i += 1u) {
+        let fragment = layers[screen_index + i * pixel_count];
+        let depth = fragment.y >> 8u;
+        if depth < opaque_depth {
+            continue;
+        }
+
+        var j = fragment_count;
+        while j > 0u && (fragments[j - 1u].y >> 8u) > depth {
+            fragments[j] = fragments[j - 1u];
+            j -= 1u;
+        }
+        fragments[j] = fragment;
+        fragment_count += 1u;
+    }
+
+    // Blend the premultiplied fragments back to front.
+    var color = vec4(0.0);
+    for (var i = 0u; i < fragment_count; i += 1u) {
+        let fragment = fragments[i];
+        let fragment_color = vec4(rgb9e5_to_vec3_(fragment.x), f32(fragment.y & 0xffu) / 255.0);
+        color = fragment_color + color * (1.0 - fragment_color.a);
+    }
+    return color;
+}
@@ -0,0 +1,363 @@
+//! Custom fullscreen effects, from a fragment shader and a settings component.
+//!
+//! Implement [`PostProcess`] for the settings of an effect, and register it with
+//! [`PostProcessApp::add_post_process`]. The effect is applied to the cameras with the settings.
+
+use std::marker::PhantomData;
+
+use crate::{
+    core_2d::graph::{Core2d, Node2d},
+    core_3d::graph::{Core3d, Node3d},
+    fullscreen_vertex_shader::fullscreen_shader_vertex_state,
+};
+use bevy_app::{App, Plugin};
+use bevy_asset::{AssetServer, Handle};
+use bevy_ecs::{prelude::*, query::QueryItem};
+use bevy_render::{
+    extract_component::{
+        ComponentUniforms, DynamicUniformIndex, ExtractComponent, ExtractComponentPlugin,
+        UniformComponentPlugin,
+    },
+    render_graph::{
+        InternedRenderLabel, NodeRunError, RenderGraphApp, RenderGraphContext, RenderLabel,
+        ViewNode, ViewNodeRunner,
+    },
+    render_resource::{
+        binding_types::{sampler, texture_2d, uniform_buffer},
+        encase::private::WriteInto,
+        *,
+    },
+    renderer::{RenderContext, RenderDevice},
+    texture::BevyDefault,
+    view::{ExtractedView, ViewTarget},
+    Render, RenderApp, RenderSet,
+};
+
+/// A fullscreen effect applied to the cameras with this component, which holds its settings.
+///
+/// The fragment shader of the effect has a `fragment` entry point taking the
+/// `FullscreenVertexOutput` of `bevy_core_pipeline::fullscreen_vertex_shader`, with these
+/// bindings in group 0:
+/// - binding 0: the image of the camera, a `texture_2d<f32>`.
+/// - binding 1: a filtering `sampler` for the image.
+/// - binding 2: the settings, as a `var<uniform>` of the WGSL struct matching this component.
+///
+/// # Example
+///
+/// ```
+/// # use bevy_app::App;
+/// # use bevy_core_pipeline::post_process::{PostProcess, PostProcessApp};
+/// # use bevy_ecs::prelude::*;
+/// # use bevy_render::{
+/// #     extract_component::ExtractComponent,
+/// #     render_resource::{ShaderRef, ShaderType},
+/// # };
+/// #[derive(Component, ExtractComponent, ShaderType, Clone, Copy)]
+/// struct Vignette {
+///     intensity: f32,
+/// }
+///
+/// impl PostProcess for Vignette {
+///     fn fragment_shader() -> ShaderRef {
+///         "shaders/vignette.wgsl".into()
+///     }
+/// }
+///
+/// # fn add(app: &mut App) {
+/// app.add_post_process::<Vignette>();
+/// # }
+/// ```
+pub trait PostProcess:
+    Component + ExtractComponent<Out = Self> + ShaderType + WriteInto + Clone
+{
+    /// The fragment shader of the effect.
+    fn fragment_shader() -> ShaderRef;
+
+    /// Where the effect runs among the other post-processing effects.
+    fn order() -> PostProcessOrder {
+        PostProcessOrder::AfterTonemapping
+    }
+}
+
+/// Where a [`PostProcess`] effect runs among the other post-processing effects.
+///
+/// Effects with the same order run in the order they were added.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PostProcessOrder {
+    /// On the HDR colors, after bloom and depth of field, and before tonemapping.
+    BeforeTonemapping,
+    /// On the tonemapped colors, after color grading, and before FXAA and the other
+    /// anti-aliasing and sharpening effects.
+    #[default]
+    AfterTonemapping,
+}
+
+/// Adds [`PostProcess`] effects to an [`App`].
+pub trait PostProcessApp {
+    /// Adds a [`PostProcessPlugin`] for the effect `T`.
+    fn add_post_process<T: PostProcess>(&mut self) -> &mut Self;
+}
+
+impl PostProcessApp for App {
+    fn add_post_process<T: PostProcess>(&mut self) -> &mut Self {
+        self.add_plugins(PostProcessPlugin::<T>::default())
+    }
+}
+
+/// Adds support for the [`PostProcess`] effect `T`.
+pub struct PostProcessPlugin<T: PostProcess>(PhantomData<T>);
+
+impl<T: PostProcess> Default for PostProcessPlugin<T> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T: PostProcess> Plugin for PostProcessPlugin<T> {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            ExtractComponentPlugin::<T>::default(),
+            UniformComponentPlugin::<T>::default(),
+        ));
+
+        let Ok(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        render_app
+            .init_resource::<SpecializedRenderPipelines<PostProcessPipeline<T>>>()
+            .init_resource::<LastPostProcesses>()
+            .add_systems(
+                Render,
+                prepare_post_process_pipelines::<T>.in_set(RenderSet::Prepare),
+            );
+
+        let label = PostProcessLabel(std::any::type_name::<T>());
+        let (previous_3d, next_3d, previous_2d, next_2d) = match T::order() {
+            PostProcessOrder::BeforeTonemapping => (
+                Node3d::DepthOfField.intern(),
+                Node3d::Tonemapping.intern(),
+                Node2d::Bloom.intern(),
+                Node2d::Tonemapping.intern(),
+            ),
+            PostProcessOrder::AfterTonemapping => (
+                Node3d::ColorGradingLut.intern(),
+                Node3d::Fxaa.intern(),
+                Node2d::ColorGradingLut.intern(),
+                Node2d::Fxaa.intern(),
+            ),
+        };
+        render_app
+            .add_render_graph_node::<ViewNodeRunner<PostProcessNode<T>>>(Core3d, label.clone())
+            .add_render_graph_edges(Core3d, (previous_3d, label.intern(), next_3d))
+            .add_render_graph_node::<ViewNodeRunner<PostProcessNode<T>>>(Core2d, label.clone())
+            .add_render_graph_edges(Core2d, (previous_2d, label.intern(), next_2d));
+
+        // Effects with the same order run after the ones added before them.
+        let mut last_post_processes = render_app.world.resource_mut::<LastPostProcesses>();
+        let last = match T::order() {
+            PostProcessOrder::BeforeTonemapping => &mut last_post_processes.before_tonemapping,
+            PostProcessOrder::AfterTonemapping => &mut last_post_processes.after_tonemapping,
+        };
+        if let Some(last) = last.replace(label.intern()) {
+            render_app
+                .add_render_graph_edge(Core3d, last, label.clone())
+                .add_render_graph_edge(Core2d, last, label);
+        }
+    }
+
+    fn finish(&self, app: &mut App) {
+        let Ok(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        render_app.init_resource::<PostProcessPipeline<T>>();
+    }
+}
+
+/// The label of the node of a [`PostProcess`] effect, named after its type.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct PostProcessLabel(pub &'static str);
+
+/// The last [`PostProcess`] effect added in each [`PostProcessOrder`].
+#[derive(Resource, Default)]
+struct LastPostProcesses {
+    before_tonemapping: Option<InternedRenderLabel>,
+    after_tonemapping: Option<InternedRenderLabel>,
+}
+
+/// The pipeline of the [`PostProcess`] effect `T`.
+#[derive(Resource)]
+pub struct PostProcessPipeline<T: PostProcess> {
+    layout: BindGroupLayout,
+    sampler: Sampler,
+    shader: Handle<Shader>,
+    marker: PhantomData<T>,
+}
+
+impl<T: PostProcess> FromWorld for PostProcessPipeline<T> {
+    fn from_world(render_world: &mut World) -> Self {
+        let render_device = render_world.resource::<RenderDevice>();
+        let layout = render_device.create_bind_group_layout(
+            "post_process_bind_group_layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::FRAGMENT,
+                (
+                    texture_2d(TextureSampleType::Float { filterable: true }),
+                    sampler(SamplerBindingType::Filtering),
+                    uniform_buffer::<T>(true),
+                ),
+            ),
+        );
+
+        let sampler = render_device.create_sampler(&SamplerDescriptor::default());
+
+        let shader = match T::fragment_shader() {
+            ShaderRef::Default => panic!(
+                "The post-process effect {} has no fragment shader",
+                std::any::type_name::<T>()
+            ),
+            ShaderRef::Handle(handle) => handle,
+            ShaderRef::Path(path) => render_world.resource::<AssetServer>().load(path),
+        };
+
+        PostProcessPipeline {
+            layout,
+            sampler,
+            shader,
+            marker: PhantomData,
+        }
+    }
+}
+
+/// The key of a [`PostProcessPipeline`], the format of the target of the view.
+#[derive(PartialEq, Eq, Hash, Clone, Copy)]
+pub struct PostProcessPipelineKey {
+    texture_format: TextureFormat,
+}
+
+impl<T: PostProcess> SpecializedRenderPipeline for PostProcessPipeline<T> {
+    type Key = PostProcessPipelineKey;
+
+    fn specialize(&self, key: Self::Key) -> RenderPipelineDescriptor {
+        RenderPipelineDescriptor {
+            label: Some("post_process_pipeline".into()),
+            layout: vec![self.layout.clone()],
+            vertex: fullscreen_shader_vertex_state(),
+            fragment: Some(FragmentState {
+                shader: self.shader.clone(),
+                shader_defs: Vec::new(),
+                entry_point: "fragment".into(),
+                targets: vec![Some(ColorTargetState {
+                    format: key.texture_format,
+                    blend: None,
+                    write_mask: ColorWrites::ALL,
+                })],
+            }),
+            primitive: PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: MultisampleState::default(),
+            push_constant_ranges: Vec::new(),
+        }
+    }
+}
+
+/// The [`PostProcessPipeline`] of the effect `T` specialized for a view.
+#[derive(Component)]
+pub struct ViewPostProcessPipeline<T: PostProcess>(CachedRenderPipelineId, PhantomData<T>);
+
+pub fn prepare_post_process_pipelines<T: PostProcess>(
+    mut commands: Commands,
+    pipeline_cache: Res<PipelineCache>,
+    mut pipelines: ResMut<SpecializedRenderPipelines<PostProcessPipeline<T>>>,
+    post_process_pipeline: Res<PostProcessPipeline<T>>,
+    views: Query<(Entity, &ExtractedView), With<T>>,
+) {
+    for (entity, view) in &views {
+        let pipeline_id = pipelines.specialize(
+            &pipeline_cache,
+            &post_process_pipeline,
+            PostProcessPipelineKey {
+                texture_format: if view.hdr {
+                    ViewTarget::TEXTURE_FORMAT_HDR
+                } else {
+                    TextureFormat::bevy_default()
+                },
+            },
+        );
+
+        commands
+            .entity(entity)
+            .insert(ViewPostProcessPipeline::<T>(pipeline_id, PhantomData));
+    }
+}
+
+/// Render [`bevy_render::render_graph::Node`] applying the [`PostProcess`] effect `T` of a view.
+pub struct PostProcessNode<T: PostProcess>(PhantomData<T>);
+
+impl<T: PostProcess> Default for PostProcessNode<T> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T: PostProcess> ViewNode for PostProcessNode<T> {
+    type ViewQuery = (
+        &'static ViewTarget,
+        &'static ViewPostProcessPipeline<T>,
+        &'static DynamicUniformIndex<T>,
+    );
+
+    fn run(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        (target, pipeline_id, uniform_index): QueryItem<Self::ViewQuery>,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        let pipeline_cache = world.resource::<PipelineCache>();
+        let post_process_pipeline = world.resource::<PostProcessPipeline<T>>();
+        let (Some(pipeline), Some(settings)) = (
+            pipeline_cache.get_render_pipeline(pipeline_id.0),
+            world
+                .resource::<ComponentUniforms<T>>()
+                .uniforms()
+                .binding(),
+        ) else {
+            return Ok(());
+        };
+
+        let post_process = target.post_process_write();
+        let bind_group = render_context.render_device().create_bind_group(
+            "post_process_bind_group",
+            &post_process_pipeline.layout,
+            &BindGroupEntries::sequential((
+                post_process.source,
+                &post_process_pipeline.sampler,
+                settings,
+            )),
+        );
+
+        let pass_descriptor = RenderPassDescriptor {
+            label: Some("post_process_pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: post_process.destination,
+                resolve_target: None,
+                ops: Operations::default(),
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        };
+
+        let mut render_pass = render_context
+            .command_encoder()
+            .begin_render_pass(&pass_descriptor);
+
+        render_pass.set_pipeline(pipeline);
+        render_pass.set_bind_group(0, &bind_group, &[uniform_index.index()]);
+        render_pass.draw(0..3, 0..1);
+
+        Ok(())
+    }
+}
@@ -34,12 +34,16 @@ use bevy_ecs::prelude::*;
 use bevy_reflect::Reflect;
 use bevy_render::{
     mesh::Mesh,
-    render_phase::{CachedRenderPipelinePhaseItem, DrawFunctionId, PhaseItem},
-    render_resource::{CachedRenderPipelineId, Extent3d, TextureFormat, TextureView},
-    texture::ColorAttachment,
+    render_phase::{
+        sort_binned, BinnedPhaseItem, CachedRenderPipelinePhaseItem, DrawFunctionId, PhaseItem,
+    },
+    render_resource::{BindGroupId, CachedRenderPipelineId, Extent3d, TextureFormat, TextureView},
+    texture::{ColorAttachment, Image},
 };
 use bevy_utils::{nonmax::NonMaxU32, FloatOrd};
 
+use crate::core_3d::Opaque3dBinKey;
+
 pub const NORMAL_PREPASS_FORMAT: TextureFormat = TextureFormat::Rgb10a2Unorm;
 pub const MOTION_VECTOR_PREPASS_FORMAT: TextureFormat = TextureFormat::Rg16Float;
 
@@ -107,12 +111,14 @@ impl ViewPrepassTextures {
 
 /// Opaque phase of the 3D prepass.
 ///
-/// Sorted front-to-back by the z-distance in front of the camera.
+/// Binned by pipeline, mesh, material and lightmap, see [`BinnedPhaseItem`].
 ///
 /// Used to render all 3D meshes with materials that have no transparency.
 pub struct Opaque3dPrepass {
     pub entity: Entity,
     pub asset_id: AssetId<Mesh>,
+    pub material_bind_group_id: Option<BindGroupId>,
+    pub lightmap_image: Option<AssetId<Image>>,
     pub pipeline_id: CachedRenderPipelineId,
     pub draw_function: DrawFunctionId,
     pub batch_range: Range<u32>,
@@ -140,7 +146,7 @@ impl PhaseItem for Opaque3dPrepass {
 
     #[inline]
     fn sort(items: &mut [Self]) {
-        items.sort_unstable_by_key(Self::sort_key);
+        sort_binned(items);
     }
 
     #[inline]
@@ -164,6 +170,19 @@ impl PhaseItem for Opaque3dPrepass {
     }
 }
 
+impl BinnedPhaseItem for Opaque3dPrepass {
+    type BinKey = Opaque3dBinKey;
+
+    #[inline]
+    fn bin_key(&self) -> Self::BinKey {
+        Opaque3dBinKey {
+            asset_id: self.asset_id,
+            material_bind_group_id: self.material_bind_group_id,
+            lightmap_image: self.lightmap_image,
+        }
+    }
+}
+
 impl CachedRenderPipelinePhaseItem for Opaque3dPrepass {
     #[inline]
     fn cached_pipeline(&self) -> CachedRenderPipelineId {
@@ -69,6 +69,7 @@ impl Plugin for TemporalAntiAliasPlugin {
                 Core3d,
                 (
                     Node3d::EndMainPass,
+                    Node3d::MotionBlur,
                     Node3d::Taa,
                     Node3d::Bloom,
                     Node3d::Tonemapping,
@@ -1,3 +1,4 @@
+use crate::auto_exposure::{AutoExposureBuffers, AutoExposureUniform};
 use crate::fullscreen_vertex_shader::fullscreen_shader_vertex_state;
 use bevy_app::prelude::*;
 use bevy_asset::{load_internal_asset, Assets, Handle};
@@ -8,7 +9,7 @@ use bevy_render::extract_component::{ExtractComponent, ExtractComponentPlugin};
 use bevy_render::extract_resource::{ExtractResource, ExtractResourcePlugin};
 use bevy_render::render_asset::{RenderAssetUsages, RenderAssets};
 use bevy_render::render_resource::binding_types::{
-    sampler, texture_2d, texture_3d, uniform_buffer,
+    sampler, texture_2d, texture_3d, uniform_buffer, uniform_buffer_sized,
 };
 use bevy_render::renderer::RenderDevice;
 use bevy_render::texture::{CompressedImageFormats, Image, ImageSampler, ImageType};
@@ -114,6 +115,7 @@ impl Plugin for TonemappingPlugin {
 #[derive(Resource)]
 pub struct TonemappingPipeline {
     texture_bind_group: BindGroupLayout,
+    auto_exposure_bind_group: BindGroupLayout,
     sampler: Sampler,
 }
 
@@ -179,6 +181,7 @@ impl Tonemapping {
 pub struct TonemappingPipelineKey {
     deband_dither: DebandDither,
     tonemapping: Tonemapping,
+    auto_exposure: bool,
 }
 
 impl SpecializedRenderPipeline for TonemappingPipeline {
@@ -190,6 +193,12 @@ impl SpecializedRenderPipeline for TonemappingPipeline {
             shader_defs.push("DEBAND_DITHER".into());
         }
 
+        let mut layout = vec![self.texture_bind_group.clone()];
+        if key.auto_exposure {
+            shader_defs.push("AUTO_EXPOSURE".into());
+            layout.push(self.auto_exposure_bind_group.clone());
+        }
+
         match key.tonemapping {
             Tonemapping::None => shader_defs.push("TONEMAP_METHOD_NONE".into()),
             Tonemapping::Reinhard => shader_defs.push("TONEMAP_METHOD_REINHARD".into()),
@@ -230,7 +239,7 @@ impl SpecializedRenderPipeline for TonemappingPipeline {
         }
         RenderPipelineDescriptor {
             label: Some("tonemapping pipeline".into()),
-            layout: vec![self.texture_bind_group.clone()],
+            layout,
             vertex: fullscreen_shader_vertex_state(),
             fragment: Some(FragmentState {
                 shader: TONEMAPPING_SHADER_HANDLE,
@@ -271,10 +280,19 @@ impl FromWorld for TonemappingPipeline {
         let tonemap_texture_bind_group = render_device
             .create_bind_group_layout("tonemapping_hdr_texture_bind_group_layout", &entries);
 
+        let auto_exposure_bind_group = render_device.create_bind_group_layout(
+            "tonemapping_auto_exposure_bind_group_layout",
+            &BindGroupLayoutEntries::single(
+                ShaderStages::FRAGMENT,
+                uniform_buffer_sized(false, None),
+            ),
+        );
+
         let sampler = render_device.create_sampler(&SamplerDescriptor::default());
 
         TonemappingPipeline {
             texture_bind_group: tonemap_texture_bind_group,
+            auto_exposure_bind_group,
             sampler,
         }
     }
@@ -288,12 +306,22 @@ pub fn prepare_view_tonemapping_pipelines(
     pipeline_cache: Res<PipelineCache>,
     mut pipelines: ResMut<SpecializedRenderPipelines<TonemappingPipeline>>,
     upscaling_pipeline: Res<TonemappingPipeline>,
-    view_targets: Query<(Entity, Option<&Tonemapping>, Option<&DebandDither>), With<ViewTarget>>,
+    auto_exposure_buffers: Option<Res<AutoExposureBuffers>>,
+    view_targets: Query<
+        (
+            Entity,
+            Option<&Tonemapping>,
+            Option<&DebandDither>,
+            Has<AutoExposureUniform>,
+        ),
+        With<ViewTarget>,
+    >,
 ) {
-    for (entity, tonemapping, dither) in view_targets.iter() {
+    for (entity, tonemapping, dither, auto_exposure) in view_targets.iter() {
         let key = TonemappingPipelineKey {
             deband_dither: *dither.unwrap_or(&DebandDither::Disabled),
             tonemapping: *tonemapping.unwrap_or(&Tonemapping::None),
+            auto_exposure: auto_exposure && auto_exposure_buffers.is_some(),
         };
         let pipeline = pipelines.specialize(&pipeline_cache, &upscaling_pipeline, key);
 
@@ -1,6 +1,9 @@
 use std::sync::Mutex;
 
-use crate::tonemapping::{TonemappingLuts, TonemappingPipeline, ViewTonemappingPipeline};
+use crate::{
+    auto_exposure::ViewAutoExposureBuffers,
+    tonemapping::{TonemappingLuts, TonemappingPipeline, ViewTonemappingPipeline},
+};
 
 use bevy_ecs::{prelude::*, query::QueryItem};
 use bevy_render::{
@@ -29,15 +32,20 @@ impl ViewNode for TonemappingNode {
         &'static ViewTarget,
         &'static ViewTonemappingPipeline,
         &'static Tonemapping,
+        Option<&'static ViewAutoExposureBuffers>,
     );
 
     fn run(
         &self,
         _graph: &mut RenderGraphContext,
         render_context: &mut RenderContext,
-        (view_uniform_offset, target, view_tonemapping_pipeline, tonemapping): QueryItem<
-            Self::ViewQuery,
-        >,
+        (
+            view_uniform_offset,
+            target,
+            view_tonemapping_pipeline,
+            tonemapping,
+            auto_exposure_buffers,
+        ): QueryItem<Self::ViewQuery>,
         world: &World,
     ) -> Result<(), NodeRunError> {
         let pipeline_cache = world.resource::<PipelineCache>();
@@ -102,6 +110,14 @@ impl ViewNode for TonemappingNode {
             }
         };
 
+        let auto_exposure_bind_group = auto_exposure_buffers.map(|auto_exposure_buffers| {
+            render_context.render_device().create_bind_group(
+                "tonemapping_auto_exposure_bind_group",
+                &tonemapping_pipeline.auto_exposure_bind_group,
+                &BindGroupEntries::single(auto_exposure_buffers.state.as_entire_binding()),
+            )
+        });
+
         let pass_descriptor = RenderPassDescriptor {
             label: Some("tonemapping_pass"),
             color_attachments: &[Some(RenderPassColorAttachment {
@@ -123,6 +139,9 @@ impl ViewNode for TonemappingNode {
 
         render_pass.set_pipeline(pipeline);
         render_pass.set_bind_group(0, bind_group, &[view_uniform_offset.offset]);
+        if let Some(auto_exposure_bind_group) = &auto_exposure_bind_group {
+            render_pass.set_bind_group(1, auto_exposure_bind_group, &[]);
+        }
         render_pass.draw(0..3, 0..1);
 
         Ok(())
@@ -13,9 +13,22 @@
 @group(0) @binding(3) var dt_lut_texture: texture_3d<f32>;
 @group(0) @binding(4) var dt_lut_sampler: sampler;
 
+#ifdef AUTO_EXPOSURE
+struct AutoExposureState {
+    ev: f32,
+    initialized: u32,
+}
+
+@group(1) @binding(0) var<uniform> auto_exposure: AutoExposureState;
+#endif
+
 @fragment
 fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
-    let hdr_color = textureSample(hdr_texture, hdr_sampler, in.uv);
+    var hdr_color = textureSample(hdr_texture, hdr_sampler, in.uv);
+
+#ifdef AUTO_EXPOSURE
+    hdr_color = vec4(hdr_color.rgb * exp2(-auto_exposure.ev), hdr_color.a);
+#endif
 
     var output_rgb = tone_mapping(hdr_color, view.color_grading).rgb;
 
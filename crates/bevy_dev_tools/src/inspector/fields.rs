@@ -0,0 +1,325 @@
+//! Flattens reflected values into the fields displayed by the inspector, and edits them.
+
+use bevy_reflect::{
+    DynamicEnum, DynamicVariant, GetPath, Reflect, ReflectRef, TypeInfo, VariantInfo, VariantType,
+};
+
+/// The maximum nesting of the fields displayed for a value.
+const MAX_DEPTH: usize = 8;
+
+/// The maximum number of items displayed for a list or an array.
+const MAX_ITEMS: usize = 32;
+
+/// A field of a reflected value, displayed on a row of the inspector.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Field {
+    /// The reflection path of the field from the inspected value, like `.translation.x`.
+    pub path: String,
+    /// How deep the field is nested in the inspected value, from 0.
+    pub depth: usize,
+    /// The name of the field in its parent, like `x` or `[2]`.
+    pub label: String,
+    /// The value of the field, or a summary for the fields containing other fields.
+    pub value: String,
+    pub kind: FieldKind,
+}
+
+/// How a [`Field`] can be edited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FieldKind {
+    /// A field containing other fields, displayed on the following rows.
+    Container,
+    /// A boolean, inverted by clicking it.
+    Bool,
+    /// An enum whose variants have no fields, switched to the next variant by clicking it.
+    UnitEnum,
+    /// A number, string or character, edited as text.
+    Text,
+    /// A field which can't be edited.
+    ReadOnly,
+}
+
+/// Returns the fields of the `value`, depth first.
+pub(crate) fn collect_fields(value: &dyn Reflect) -> Vec<Field> {
+    let mut fields = Vec::new();
+    visit_children(value, "", 0, &mut fields);
+    fields
+}
+
+fn visit_children(value: &dyn Reflect, path: &str, depth: usize, fields: &mut Vec<Field>) {
+    if depth == MAX_DEPTH {
+        return;
+    }
+    match value.reflect_ref() {
+        ReflectRef::Struct(value) => {
+            for (index, field) in value.iter_fields().enumerate() {
+                let name = value.name_at(index).unwrap_or_default();
+                visit(field, format!("{path}.{name}"), name.into(), depth, fields);
+            }
+        }
+        ReflectRef::TupleStruct(value) => {
+            for (index, field) in value.iter_fields().enumerate() {
+                visit(
+                    field,
+                    format!("{path}.{index}"),
+                    index.to_string(),
+                    depth,
+                    fields,
+                );
+            }
+        }
+        ReflectRef::Tuple(value) => {
+            for (index, field) in value.iter_fields().enumerate() {
+                visit(
+                    field,
+                    format!("{path}.{index}"),
+                    index.to_string(),
+                    depth,
+                    fields,
+                );
+            }
+        }
+        ReflectRef::List(value) => {
+            for (index, item) in value.iter().enumerate().take(MAX_ITEMS) {
+                visit(
+                    item,
+                    format!("{path}[{index}]"),
+                    format!("[{index}]"),
+                    depth,
+                    fields,
+                );
+            }
+        }
+        ReflectRef::Array(value) => {
+            for (index, item) in value.iter().enumerate().take(MAX_ITEMS) {
+                visit(
+                    item,
+                    format!("{path}[{index}]"),
+                    format!("[{index}]"),
+                    depth,
+                    fields,
+                );
+            }
+        }
+        ReflectRef::Enum(value) => {
+            for (index, field) in value.iter_fields().enumerate() {
+                let (field_path, label) = match field.name() {
+                    Some(name) => (format!("{path}.{name}"), name.to_string()),
+                    None => (format!("{path}.{index}"), index.to_string()),
+                };
+                visit(field.value(), field_path, label, depth, fields);
+            }
+        }
+        ReflectRef::Map(_) | ReflectRef::Value(_) => {}
+    }
+}
+
+fn visit(value: &dyn Reflect, path: String, label: String, depth: usize, fields: &mut Vec<Field>) {
+    let (summary, kind) = match value.reflect_ref() {
+        ReflectRef::Struct(_) | ReflectRef::TupleStruct(_) | ReflectRef::Tuple(_) => {
+            (short_type_name(value), FieldKind::Container)
+        }
+        ReflectRef::List(list) => (format!("{} items", list.len()), FieldKind::Container),
+        ReflectRef::Array(array) => (format!("{} items", array.len()), FieldKind::Container),
+        ReflectRef::Enum(value_enum) => {
+            let kind = if value_enum.variant_type() == VariantType::Unit && has_unit_variants(value)
+            {
+                FieldKind::UnitEnum
+            } else if value_enum.field_len() > 0 {
+                FieldKind::Container
+            } else {
+                FieldKind::ReadOnly
+            };
+            (value_enum.variant_name().to_string(), kind)
+        }
+        ReflectRef::Value(_) if value.is::<bool>() => (format!("{value:?}"), FieldKind::Bool),
+        ReflectRef::Value(_) if is_text(value) => (format!("{value:?}"), FieldKind::Text),
+        ReflectRef::Map(_) | ReflectRef::Value(_) => (format!("{value:?}"), FieldKind::ReadOnly),
+    };
+    let is_container = kind == FieldKind::Container;
+    fields.push(Field {
+        path: path.clone(),
+        depth,
+        label,
+        value: summary,
+        kind,
+    });
+    if is_container {
+        visit_children(value, &path, depth + 1, fields);
+    }
+}
+
+fn short_type_name(value: &dyn Reflect) -> String {
+    value
+        .get_represented_type_info()
+        .map(|info| info.type_path_table().short_path().to_string())
+        .unwrap_or_default()
+}
+
+/// Whether all the variants of the enum `value` are unit variants.
+fn has_unit_variants(value: &dyn Reflect) -> bool {
+    match value.get_represented_type_info() {
+        Some(TypeInfo::Enum(info)) => info
+            .iter()
+            .all(|variant| matches!(variant, VariantInfo::Unit(_))),
+        _ => false,
+    }
+}
+
+macro_rules! text_types {
+    ($macro:ident) => {
+        $macro!(f32, f64, i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize)
+    };
+}
+
+fn is_text(value: &dyn Reflect) -> bool {
+    macro_rules! is_any {
+        ($($ty:ty),*) => { $(value.is::<$ty>())||* };
+    }
+    text_types!(is_any) || value.is::<String>() || value.is::<char>()
+}
+
+/// The text edited for the `value` of a [`FieldKind::Text`] field.
+pub(crate) fn edit_text(value: &dyn Reflect) -> String {
+    if let Some(value) = value.downcast_ref::<String>() {
+        value.clone()
+    } else if let Some(value) = value.downcast_ref::<char>() {
+        value.to_string()
+    } else {
+        format!("{value:?}")
+    }
+}
+
+/// Parses the `text` as a value of the type of the `current` value.
+fn parse(current: &dyn Reflect, text: &str) -> Option<Box<dyn Reflect>> {
+    macro_rules! parse_as {
+        ($($ty:ty),*) => {
+            $(
+                if current.is::<$ty>() {
+                    return text.trim().parse::<$ty>().ok().map(|value| Box::new(value) as _);
+                }
+            )*
+        };
+    }
+    text_types!(parse_as);
+    if current.is::<String>() {
+        return Some(Box::new(text.to_string()));
+    }
+    if current.is::<char>() {
+        let mut chars = text.chars();
+        return match (chars.next(), chars.next()) {
+            (Some(char), None) => Some(Box::new(char)),
+            _ => None,
+        };
+    }
+    None
+}
+
+/// Sets the [`FieldKind::Text`] field at the `path` of the `root` value to the value parsed from
+/// the `text`.
+pub(crate) fn set_field(root: &mut dyn Reflect, path: &str, text: &str) -> Result<(), String> {
+    let field = root.reflect_path_mut(path).map_err(|err| err.to_string())?;
+    let value = parse(field, text)
+        .ok_or_else(|| format!("`{text}` isn't a valid {}", field.reflect_short_type_path()))?;
+    field
+        .set(value)
+        .map_err(|_| "the field has another type".to_string())
+}
+
+/// Inverts the [`FieldKind::Bool`] field at the `path` of the `root` value, or switches the
+/// [`FieldKind::UnitEnum`] field to its next variant.
+pub(crate) fn toggle_field(root: &mut dyn Reflect, path: &str) -> Result<(), String> {
+    let field = root.reflect_path_mut(path).map_err(|err| err.to_string())?;
+    if let Some(value) = field.downcast_mut::<bool>() {
+        *value = !*value;
+        return Ok(());
+    }
+    let Some(TypeInfo::Enum(info)) = field.get_represented_type_info() else {
+        return Err("the field can't be toggled".to_string());
+    };
+    let ReflectRef::Enum(value) = field.reflect_ref() else {
+        return Err("the field can't be toggled".to_string());
+    };
+    let next = (value.variant_index() + 1) % info.variant_len();
+    let next_name = info.variant_names()[next];
+    field.apply(&DynamicEnum::new(next_name, DynamicVariant::Unit));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Reflect, Debug, PartialEq)]
+    enum Mode {
+        Fast,
+        Slow,
+    }
+
+    #[derive(Reflect)]
+    struct Inner {
+        speed: f32,
+        name: String,
+    }
+
+    #[derive(Reflect)]
+    struct Settings {
+        enabled: bool,
+        mode: Mode,
+        inner: Inner,
+        values: Vec<u8>,
+    }
+
+    fn settings() -> Settings {
+        Settings {
+            enabled: true,
+            mode: Mode::Fast,
+            inner: Inner {
+                speed: 1.5,
+                name: "player".to_string(),
+            },
+            values: vec![7],
+        }
+    }
+
+    #[test]
+    fn fields_are_flattened_depth_first() {
+        let fields = collect_fields(&settings());
+        let summary: Vec<_> = fields
+            .iter()
+            .map(|field| (field.path.as_str(), field.depth, field.kind))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (".enabled", 0, FieldKind::Bool),
+                (".mode", 0, FieldKind::UnitEnum),
+                (".inner", 0, FieldKind::Container),
+                (".inner.speed", 1, FieldKind::Text),
+                (".inner.name", 1, FieldKind::Text),
+                (".values", 0, FieldKind::Container),
+                (".values[0]", 1, FieldKind::Text),
+            ]
+        );
+        assert_eq!(fields[1].value, "Fast");
+        assert_eq!(fields[3].value, "1.5");
+    }
+
+    #[test]
+    fn fields_are_edited() {
+        let mut settings = settings();
+        set_field(&mut settings, ".inner.speed", " 2.25 ").unwrap();
+        set_field(&mut settings, ".inner.name", "enemy").unwrap();
+        set_field(&mut settings, ".values[0]", "9").unwrap();
+        assert!(set_field(&mut settings, ".values[0]", "300").is_err());
+        toggle_field(&mut settings, ".enabled").unwrap();
+        toggle_field(&mut settings, ".mode").unwrap();
+
+        assert_eq!(settings.inner.speed, 2.25);
+        assert_eq!(settings.inner.name, "enemy");
+        assert_eq!(settings.values, vec![9]);
+        assert!(!settings.enabled);
+        assert_eq!(settings.mode, Mode::Slow);
+        assert_eq!(edit_text(&settings.inner.name), "enemy");
+    }
+}
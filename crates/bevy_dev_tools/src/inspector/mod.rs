@@ -0,0 +1,963 @@
+//! An in-game inspector, browsing the hierarchy of the entities and the resources of the app,
+//! and editing the fields of their reflected components and resources.
+//!
+//! The components and resources are inspected through the [`AppTypeRegistry`], so only the types
+//! registered with their [`ReflectComponent`] or [`ReflectResource`] data can be edited.
+//!
+//! - Click on an entity or a resource to inspect it, and on `+` to list the children of an
+//!   entity.
+//! - Click on the search field and type to only list the entities and the resources whose name
+//!   contains the text.
+//! - Click on a boolean or an enum without fields to toggle it, or on a number or a string to
+//!   edit it. Type the new value, then press `Enter` to apply it or `Escape` to cancel.
+//! - Scroll with the mouse wheel over the list or the inspected fields.
+
+mod fields;
+
+use std::any::TypeId;
+
+use bevy_app::{App, Plugin, Startup, Update};
+use bevy_core::Name;
+use bevy_ecs::{
+    entity::EntityHashSet,
+    prelude::*,
+    reflect::{AppTypeRegistry, ReflectComponent, ReflectResource},
+    system::SystemState,
+};
+use bevy_hierarchy::{
+    BuildChildren, BuildWorldChildren, Children, DespawnRecursiveExt, Parent, WorldChildBuilder,
+};
+use bevy_input::{
+    keyboard::{Key, KeyCode, KeyboardInput},
+    mouse::{MouseScrollUnit, MouseWheel},
+    ButtonInput, ButtonState,
+};
+use bevy_reflect::{GetPath, Reflect};
+use bevy_render::{color::Color, view::Visibility};
+use bevy_text::TextStyle;
+use bevy_ui::{
+    node_bundles::{ButtonBundle, NodeBundle, TextBundle},
+    BackgroundColor, FlexDirection, Interaction, Overflow, PositionType, RelativeCursorPosition,
+    Style, UiRect, Val, ZIndex,
+};
+use bevy_utils::{default, get_short_name, Duration, Instant};
+use bevy_window::ReceivedCharacter;
+
+use fields::{collect_fields, edit_text, set_field, toggle_field, FieldKind};
+
+/// How often the inspected values are refreshed.
+const REFRESH_INTERVAL: Duration = Duration::from_millis(250);
+
+/// The maximum number of rows displayed by the list and by the inspected fields.
+const MAX_ROWS: usize = 60;
+
+/// The color of the names of the fields and of the values which can't be edited.
+const DIM_COLOR: Color = Color::rgb(0.6, 0.6, 0.6);
+
+/// The background of the selected rows and of the edited fields.
+const HIGHLIGHT_COLOR: Color = Color::rgba(0.3, 0.4, 0.7, 0.8);
+
+/// Adds an [`InspectorConfig`] and the inspector it configures, on the right side of the UI.
+///
+/// The inspector is hidden by default, and toggled with the [`InspectorConfig::toggle_key`].
+#[derive(Default)]
+pub struct InspectorPlugin {
+    /// The configuration of the inspector when the app starts.
+    pub config: InspectorConfig,
+}
+
+impl Plugin for InspectorPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.config.clone())
+            .init_resource::<InspectorState>()
+            .add_systems(Startup, setup_inspector)
+            .add_systems(
+                Update,
+                (
+                    toggle_inspector,
+                    show_inspector.run_if(resource_changed::<InspectorConfig>),
+                    update_inspector.run_if(inspector_enabled),
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// The configuration of the inspector added by the [`InspectorPlugin`].
+#[derive(Resource, Clone, Debug)]
+pub struct InspectorConfig {
+    /// Whether the inspector is displayed.
+    pub enabled: bool,
+    /// The key toggling the inspector, or `None` not to toggle it with the keyboard.
+    pub toggle_key: Option<KeyCode>,
+    /// The style of the text of the inspector.
+    pub text_style: TextStyle,
+}
+
+impl Default for InspectorConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            toggle_key: Some(KeyCode::F11),
+            text_style: TextStyle {
+                font_size: 14.0,
+                color: Color::WHITE,
+                ..default()
+            },
+        }
+    }
+}
+
+/// What the inspector lists.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum InspectorTab {
+    /// The hierarchy of the entities.
+    #[default]
+    Entities,
+    /// The resources with reflection data.
+    Resources,
+}
+
+/// The state of the inspector, which can also be changed by the app, e.g. to inspect an entity.
+#[derive(Resource, Debug, Default)]
+pub struct InspectorState {
+    /// What the inspector lists.
+    pub tab: InspectorTab,
+    /// The text the names of the listed entities and resources contain, ignoring the case.
+    ///
+    /// The entities are listed without their hierarchy while searching.
+    pub search: String,
+    /// The inspected entity, in the [`InspectorTab::Entities`] tab.
+    pub selected_entity: Option<Entity>,
+    /// The type of the inspected resource, in the [`InspectorTab::Resources`] tab.
+    pub selected_resource: Option<TypeId>,
+    /// The entities whose children are listed.
+    pub expanded: EntityHashSet,
+    focus: Focus,
+    list_scroll: usize,
+    details_scroll: usize,
+    /// The error of the last edit, displayed until the next one.
+    error: Option<String>,
+}
+
+/// Where the typed text goes.
+#[derive(Debug, Clone, Default, PartialEq)]
+enum Focus {
+    #[default]
+    None,
+    Search,
+    Field {
+        target: Target,
+        path: String,
+        text: String,
+    },
+}
+
+/// A reflected value edited by the inspector.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Target {
+    Component(Entity, TypeId),
+    Resource(TypeId),
+}
+
+/// The root node of the inspector.
+#[derive(Component)]
+pub struct InspectorPanel;
+
+/// Marks the nodes of the inspector, which aren't listed by it.
+#[derive(Component)]
+struct InspectorUi;
+
+/// The parts of the inspector whose rows are rebuilt when it's refreshed.
+#[derive(Component, Clone, Copy, PartialEq, Eq)]
+enum InspectorSection {
+    Header,
+    List,
+    Details,
+}
+
+/// What clicking on a button of the inspector does.
+#[derive(Component, Debug, Clone, PartialEq)]
+enum InspectorAction {
+    Tab(InspectorTab),
+    FocusSearch,
+    SelectEntity(Entity),
+    ToggleExpanded(Entity),
+    SelectResource(TypeId),
+    Field {
+        target: Target,
+        path: String,
+        kind: FieldKind,
+    },
+}
+
+/// A row of the inspector.
+struct Row {
+    indent: usize,
+    cells: Vec<Cell>,
+}
+
+/// A text on a [`Row`], which is a button if it has an action.
+struct Cell {
+    text: String,
+    color: Option<Color>,
+    highlighted: bool,
+    action: Option<InspectorAction>,
+}
+
+impl Cell {
+    fn text(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            color: None,
+            highlighted: false,
+            action: None,
+        }
+    }
+
+    fn dim(text: impl Into<String>) -> Self {
+        Self {
+            color: Some(DIM_COLOR),
+            ..Self::text(text)
+        }
+    }
+
+    fn button(text: impl Into<String>, action: InspectorAction, highlighted: bool) -> Self {
+        Self {
+            highlighted,
+            action: Some(action),
+            ..Self::text(text)
+        }
+    }
+}
+
+impl Row {
+    fn new(indent: usize, cells: Vec<Cell>) -> Self {
+        Self { indent, cells }
+    }
+}
+
+/// A key typed in a focused text.
+enum TypedKey {
+    Text(String),
+    Backspace,
+    Enter,
+    Escape,
+}
+
+fn inspector_enabled(config: Res<InspectorConfig>) -> bool {
+    config.enabled
+}
+
+fn setup_inspector(mut commands: Commands, config: Res<InspectorConfig>) {
+    let column = |width: f32| NodeBundle {
+        style: Style {
+            width: Val::Percent(width),
+            height: Val::Percent(100.0),
+            flex_direction: FlexDirection::Column,
+            overflow: Overflow::clip(),
+            ..default()
+        },
+        ..default()
+    };
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(0.0),
+                    right: Val::Px(0.0),
+                    width: Val::Px(720.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(6.0),
+                    padding: UiRect::all(Val::Px(6.0)),
+                    ..default()
+                },
+                background_color: Color::rgba(0.08, 0.08, 0.1, 0.92).into(),
+                z_index: ZIndex::Global(i32::MAX - 1),
+                visibility: inspector_visibility(&config),
+                ..default()
+            },
+            InspectorPanel,
+            InspectorUi,
+        ))
+        .with_children(|panel| {
+            panel.spawn((
+                NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        ..default()
+                    },
+                    ..default()
+                },
+                InspectorSection::Header,
+                InspectorUi,
+            ));
+            panel
+                .spawn((
+                    NodeBundle {
+                        style: Style {
+                            flex_grow: 1.0,
+                            column_gap: Val::Px(8.0),
+                            overflow: Overflow::clip(),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    InspectorUi,
+                ))
+                .with_children(|body| {
+                    body.spawn((
+                        column(40.0),
+                        RelativeCursorPosition::default(),
+                        InspectorSection::List,
+                        InspectorUi,
+                    ));
+                    body.spawn((
+                        column(60.0),
+                        RelativeCursorPosition::default(),
+                        InspectorSection::Details,
+                        InspectorUi,
+                    ));
+                });
+        });
+}
+
+fn inspector_visibility(config: &InspectorConfig) -> Visibility {
+    if config.enabled {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    }
+}
+
+fn toggle_inspector(
+    keyboard: Option<Res<ButtonInput<KeyCode>>>,
+    mut config: ResMut<InspectorConfig>,
+) {
+    let (Some(keyboard), Some(toggle_key)) = (keyboard, config.toggle_key) else {
+        return;
+    };
+    if keyboard.just_pressed(toggle_key) {
+        config.enabled = !config.enabled;
+    }
+}
+
+fn show_inspector(
+    config: Res<InspectorConfig>,
+    mut panels: Query<&mut Visibility, With<InspectorPanel>>,
+) {
+    for mut visibility in &mut panels {
+        *visibility = inspector_visibility(&config);
+    }
+}
+
+type InspectorInput<'w, 's> = (
+    Query<'w, 's, (&'static Interaction, &'static InspectorAction), Changed<Interaction>>,
+    Query<'w, 's, (&'static RelativeCursorPosition, &'static InspectorSection)>,
+    EventReader<'w, 's, ReceivedCharacter>,
+    EventReader<'w, 's, KeyboardInput>,
+    EventReader<'w, 's, MouseWheel>,
+);
+
+/// Applies the input to the inspector, and rebuilds its rows when they changed or when the
+/// inspected values need to be refreshed.
+fn update_inspector(
+    world: &mut World,
+    input: &mut SystemState<InspectorInput>,
+    mut last_refresh: Local<Option<Instant>>,
+) {
+    let (buttons, sections, mut characters, mut keyboard, mut wheel) = input.get_mut(world);
+    let actions: Vec<InspectorAction> = buttons
+        .iter()
+        .filter(|(interaction, _)| **interaction == Interaction::Pressed)
+        .map(|(_, action)| action.clone())
+        .collect();
+    let mut keys: Vec<TypedKey> = Vec::new();
+    for character in characters.read() {
+        if !character.char.chars().any(char::is_control) {
+            keys.push(TypedKey::Text(character.char.to_string()));
+        }
+    }
+    for event in keyboard.read() {
+        if event.state != ButtonState::Pressed {
+            continue;
+        }
+        match event.logical_key {
+            Key::Backspace => keys.push(TypedKey::Backspace),
+            Key::Enter => keys.push(TypedKey::Enter),
+            Key::Escape => keys.push(TypedKey::Escape),
+            _ => {}
+        }
+    }
+    let scroll: f32 = wheel
+        .read()
+        .map(|event| match event.unit {
+            MouseScrollUnit::Line => event.y,
+            MouseScrollUnit::Pixel => event.y / 20.0,
+        })
+        .sum();
+    let hovered_section = sections
+        .iter()
+        .find(|(cursor, _)| cursor.mouse_over())
+        .map(|(_, section)| *section);
+
+    let changed = !actions.is_empty() || !keys.is_empty() || scroll != 0.0;
+    world.resource_scope(|world, mut state: Mut<InspectorState>| {
+        for action in actions {
+            apply_action(world, &mut state, action);
+        }
+        for key in keys {
+            apply_key(world, &mut state, key);
+        }
+        let rows = -scroll.round() as isize;
+        match hovered_section {
+            Some(InspectorSection::List) => {
+                state.list_scroll = state.list_scroll.saturating_add_signed(rows);
+            }
+            Some(InspectorSection::Details) => {
+                state.details_scroll = state.details_scroll.saturating_add_signed(rows);
+            }
+            _ => {}
+        }
+    });
+
+    let now = Instant::now();
+    if !changed && last_refresh.is_some_and(|last| now - last < REFRESH_INTERVAL) {
+        return;
+    }
+    *last_refresh = Some(now);
+    rebuild(world);
+}
+
+fn apply_action(world: &mut World, state: &mut InspectorState, action: InspectorAction) {
+    match action {
+        InspectorAction::Tab(tab) => {
+            state.tab = tab;
+            state.list_scroll = 0;
+            state.details_scroll = 0;
+        }
+        InspectorAction::FocusSearch => state.focus = Focus::Search,
+        InspectorAction::SelectEntity(entity) => {
+            state.selected_entity = Some(entity);
+            state.details_scroll = 0;
+            state.focus = Focus::None;
+        }
+        InspectorAction::ToggleExpanded(entity) => {
+            if !state.expanded.remove(&entity) {
+                state.expanded.insert(entity);
+            }
+        }
+        InspectorAction::SelectResource(type_id) => {
+            state.selected_resource = Some(type_id);
+            state.details_scroll = 0;
+            state.focus = Focus::None;
+        }
+        InspectorAction::Field { target, path, kind } => match kind {
+            FieldKind::Bool | FieldKind::UnitEnum => {
+                state.focus = Focus::None;
+                state.error = edit_target(world, target, |value| toggle_field(value, &path)).err();
+            }
+            FieldKind::Text => {
+                let text = read_target(world, target, |value| {
+                    value
+                        .reflect_path(path.as_str())
+                        .map(edit_text)
+                        .map_err(|err| err.to_string())
+                });
+                match text {
+                    Ok(text) => state.focus = Focus::Field { target, path, text },
+                    Err(err) => state.error = Some(err),
+                }
+            }
+            FieldKind::Container | FieldKind::ReadOnly => {}
+        },
+    }
+}
+
+fn apply_key(world: &mut World, state: &mut InspectorState, key: TypedKey) {
+    let text = match &mut state.focus {
+        Focus::None => return,
+        Focus::Search => {
+            state.list_scroll = 0;
+            &mut state.search
+        }
+        Focus::Field { text, .. } => text,
+    };
+    match key {
+        TypedKey::Text(typed) => text.push_str(&typed),
+        TypedKey::Backspace => {
+            text.pop();
+        }
+        TypedKey::Escape => state.focus = Focus::None,
+        TypedKey::Enter => {
+            if let Focus::Field { target, path, text } = std::mem::take(&mut state.focus) {
+                state.error =
+                    edit_target(world, target, |value| set_field(value, &path, &text)).err();
+            }
+        }
+    }
+}
+
+/// Calls `f` with the reflected value of the `target`.
+fn read_target<T>(
+    world: &World,
+    target: Target,
+    f: impl FnOnce(&dyn Reflect) -> Result<T, String>,
+) -> Result<T, String> {
+    let registry = world.resource::<AppTypeRegistry>().read();
+    match target {
+        Target::Component(entity, type_id) => {
+            let value = registry
+                .get_type_data::<ReflectComponent>(type_id)
+                .zip(world.get_entity(entity))
+                .and_then(|(component, entity)| component.reflect(entity))
+                .ok_or("the component was removed")?;
+            f(value)
+        }
+        Target::Resource(type_id) => {
+            let value = registry
+                .get_type_data::<ReflectResource>(type_id)
+                .and_then(|resource| resource.reflect(world))
+                .ok_or("the resource was removed")?;
+            f(value)
+        }
+    }
+}
+
+/// Calls `f` with the reflected value of the `target`, marking it as changed.
+fn edit_target(
+    world: &mut World,
+    target: Target,
+    f: impl FnOnce(&mut dyn Reflect) -> Result<(), String>,
+) -> Result<(), String> {
+    let registry = world.resource::<AppTypeRegistry>().clone();
+    let registry = registry.read();
+    match target {
+        Target::Component(entity, type_id) => {
+            let component = registry
+                .get_type_data::<ReflectComponent>(type_id)
+                .ok_or("the component isn't reflected")?;
+            let mut entity = world
+                .get_entity_mut(entity)
+                .ok_or("the entity was despawned")?;
+            let mut value = component
+                .reflect_mut(&mut entity)
+                .ok_or("the component was removed")?;
+            f(&mut *value)
+        }
+        Target::Resource(type_id) => {
+            let resource = registry
+                .get_type_data::<ReflectResource>(type_id)
+                .ok_or("the resource isn't reflected")?;
+            let mut value = resource
+                .reflect_mut(world)
+                .ok_or("the resource was removed")?;
+            f(&mut *value)
+        }
+    }
+}
+
+/// Replaces the rows of the sections of the inspector.
+fn rebuild(world: &mut World) {
+    let text_style = world.resource::<InspectorConfig>().text_style.clone();
+    let rows = {
+        let state = world.resource::<InspectorState>();
+        [
+            (InspectorSection::Header, header_rows(state)),
+            (InspectorSection::List, list_rows(world, state)),
+            (InspectorSection::Details, details_rows(world, state)),
+        ]
+    };
+    let mut sections = world.query::<(Entity, &InspectorSection)>();
+    let sections: Vec<(Entity, InspectorSection)> = sections
+        .iter(world)
+        .map(|(entity, section)| (entity, *section))
+        .collect();
+
+    for (section_entity, section) in sections {
+        let Some((_, section_rows)) = rows
+            .iter()
+            .find(|(rows_section, _)| *rows_section == section)
+        else {
+            continue;
+        };
+        let mut state = world.resource_mut::<InspectorState>();
+        let scroll = match section {
+            InspectorSection::Header => &mut 0,
+            InspectorSection::List => &mut state.list_scroll,
+            InspectorSection::Details => &mut state.details_scroll,
+        };
+        *scroll = (*scroll).min(section_rows.len().saturating_sub(1));
+        let scroll = *scroll;
+
+        let mut section_entity = world.entity_mut(section_entity);
+        section_entity.despawn_descendants();
+        section_entity.with_children(|section| {
+            for row in section_rows.iter().skip(scroll).take(MAX_ROWS) {
+                spawn_row(section, row, &text_style);
+            }
+        });
+    }
+}
+
+fn spawn_row(section: &mut WorldChildBuilder, row: &Row, text_style: &TextStyle) {
+    section
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    padding: UiRect::left(Val::Px(row.indent as f32 * 12.0)),
+                    column_gap: Val::Px(6.0),
+                    ..default()
+                },
+                ..default()
+            },
+            InspectorUi,
+        ))
+        .with_children(|row_node| {
+            for cell in &row.cells {
+                let style = TextStyle {
+                    color: cell.color.unwrap_or(text_style.color),
+                    ..text_style.clone()
+                };
+                let text = TextBundle::from_section(cell.text.clone(), style);
+                let background = if cell.highlighted {
+                    HIGHLIGHT_COLOR
+                } else {
+                    Color::NONE
+                };
+                match &cell.action {
+                    Some(action) => {
+                        row_node
+                            .spawn((
+                                ButtonBundle {
+                                    background_color: BackgroundColor(background),
+                                    ..default()
+                                },
+                                action.clone(),
+                                InspectorUi,
+                            ))
+                            .with_children(|button| {
+                                button.spawn((text, InspectorUi));
+                            });
+                    }
+                    None => {
+                        row_node.spawn((text.with_background_color(background), InspectorUi));
+                    }
+                }
+            }
+        });
+}
+
+fn header_rows(state: &InspectorState) -> Vec<Row> {
+    let tab = |name: &str, tab: InspectorTab| {
+        Cell::button(name, InspectorAction::Tab(tab), state.tab == tab)
+    };
+    let search_focused = state.focus == Focus::Search;
+    let cursor = if search_focused { "_" } else { "" };
+    let mut rows = vec![
+        Row::new(
+            0,
+            vec![
+                tab("Entities", InspectorTab::Entities),
+                tab("Resources", InspectorTab::Resources),
+            ],
+        ),
+        Row::new(
+            0,
+            vec![
+                Cell::dim("Search:"),
+                Cell::button(
+                    format!("{}{cursor}", state.search),
+                    InspectorAction::FocusSearch,
+                    search_focused,
+                ),
+            ],
+        ),
+    ];
+    if let Some(error) = &state.error {
+        rows.push(Row::new(
+            0,
+            vec![Cell {
+                color: Some(Color::RED),
+                ..Cell::text(error.clone())
+            }],
+        ));
+    }
+    rows
+}
+
+fn list_rows(world: &World, state: &InspectorState) -> Vec<Row> {
+    match state.tab {
+        InspectorTab::Entities => entity_rows(world, state),
+        InspectorTab::Resources => resource_rows(world, state),
+    }
+}
+
+/// The name of the `entity` in the inspector.
+fn entity_label(world: &World, entity: Entity) -> String {
+    match world.get::<Name>(entity) {
+        Some(name) => format!("{name} ({entity:?})"),
+        None => format!("Entity ({entity:?})"),
+    }
+}
+
+fn matches_search(name: &str, search: &str) -> bool {
+    name.to_lowercase().contains(&search.to_lowercase())
+}
+
+fn entity_rows(world: &World, state: &InspectorState) -> Vec<Row> {
+    let mut entities: Vec<Entity> = world
+        .iter_entities()
+        .filter(|entity| !entity.contains::<InspectorUi>())
+        .filter(|entity| !state.search.is_empty() || !entity.contains::<Parent>())
+        .map(|entity| entity.id())
+        .collect();
+    entities.sort();
+
+    let entity_cell = |entity: Entity, label: String| {
+        Cell::button(
+            label,
+            InspectorAction::SelectEntity(entity),
+            state.selected_entity == Some(entity),
+        )
+    };
+
+    let mut rows = Vec::new();
+    if !state.search.is_empty() {
+        for entity in entities {
+            let label = entity_label(world, entity);
+            if matches_search(&label, &state.search) {
+                rows.push(Row::new(0, vec![entity_cell(entity, label)]));
+            }
+        }
+        return rows;
+    }
+
+    // Lists the hierarchy depth first, with the children of the expanded entities.
+    let mut stack: Vec<(Entity, usize)> = entities.into_iter().rev().map(|e| (e, 0)).collect();
+    while let Some((entity, depth)) = stack.pop() {
+        let children = world.get::<Children>(entity);
+        let expanded = state.expanded.contains(&entity);
+        let toggle = match children {
+            Some(_) if expanded => {
+                Cell::button("-", InspectorAction::ToggleExpanded(entity), false)
+            }
+            Some(_) => Cell::button("+", InspectorAction::ToggleExpanded(entity), false),
+            None => Cell::text(" "),
+        };
+        rows.push(Row::new(
+            depth,
+            vec![toggle, entity_cell(entity, entity_label(world, entity))],
+        ));
+        if let (Some(children), true) = (children, expanded) {
+            stack.extend(children.iter().rev().map(|child| (*child, depth + 1)));
+        }
+    }
+    rows
+}
+
+fn resource_rows(world: &World, state: &InspectorState) -> Vec<Row> {
+    let registry = world.resource::<AppTypeRegistry>().read();
+    let mut resources: Vec<(String, TypeId)> = registry
+        .iter()
+        .filter(|registration| {
+            registration
+                .data::<ReflectResource>()
+                .is_some_and(|resource| resource.reflect(world).is_some())
+        })
+        .map(|registration| {
+            let name = registration.type_info().type_path_table().short_path();
+            (name.to_string(), registration.type_id())
+        })
+        .filter(|(name, _)| matches_search(name, &state.search))
+        .collect();
+    resources.sort();
+    resources
+        .into_iter()
+        .map(|(name, type_id)| {
+            Row::new(
+                0,
+                vec![Cell::button(
+                    name,
+                    InspectorAction::SelectResource(type_id),
+                    state.selected_resource == Some(type_id),
+                )],
+            )
+        })
+        .collect()
+}
+
+fn details_rows(world: &World, state: &InspectorState) -> Vec<Row> {
+    let registry = world.resource::<AppTypeRegistry>().read();
+    let mut rows = Vec::new();
+    match state.tab {
+        InspectorTab::Entities => {
+            let Some(entity) = state.selected_entity else {
+                rows.push(Row::new(0, vec![Cell::dim("Select an entity")]));
+                return rows;
+            };
+            let Some(entity_ref) = world.get_entity(entity) else {
+                rows.push(Row::new(0, vec![Cell::dim("The entity was despawned")]));
+                return rows;
+            };
+            rows.push(Row::new(0, vec![Cell::text(entity_label(world, entity))]));
+            let mut components: Vec<(String, Option<TypeId>)> = world
+                .inspect_entity(entity)
+                .into_iter()
+                .map(|info| (get_short_name(info.name()), info.type_id()))
+                .collect();
+            components.sort();
+            for (name, type_id) in components {
+                rows.push(Row::new(1, vec![Cell::text(name)]));
+                let value = type_id.and_then(|type_id| {
+                    let component = registry.get_type_data::<ReflectComponent>(type_id)?;
+                    Some((type_id, component.reflect(entity_ref)?))
+                });
+                match value {
+                    Some((type_id, value)) => {
+                        field_rows(&mut rows, state, Target::Component(entity, type_id), value);
+                    }
+                    None => rows.push(Row::new(2, vec![Cell::dim("not reflected")])),
+                }
+            }
+        }
+        InspectorTab::Resources => {
+            let value = state.selected_resource.and_then(|type_id| {
+                let resource = registry.get_type_data::<ReflectResource>(type_id)?;
+                Some((type_id, resource.reflect(world)?))
+            });
+            let Some((type_id, value)) = value else {
+                rows.push(Row::new(0, vec![Cell::dim("Select a resource")]));
+                return rows;
+            };
+            rows.push(Row::new(
+                0,
+                vec![Cell::text(value.reflect_short_type_path())],
+            ));
+            field_rows(&mut rows, state, Target::Resource(type_id), value);
+        }
+    }
+    rows
+}
+
+fn field_rows(rows: &mut Vec<Row>, state: &InspectorState, target: Target, value: &dyn Reflect) {
+    for field in collect_fields(value) {
+        let label = Cell::dim(format!("{}:", field.label));
+        let edited = match &state.focus {
+            Focus::Field {
+                target: focused,
+                path,
+                text,
+            } if *focused == target && *path == field.path => Some(text),
+            _ => None,
+        };
+        let value = match (field.kind, edited) {
+            (_, Some(text)) => Cell {
+                highlighted: true,
+                ..Cell::text(format!("{text}_"))
+            },
+            (FieldKind::Container | FieldKind::ReadOnly, _) => Cell::dim(field.value),
+            (kind, _) => Cell::button(
+                field.value,
+                InspectorAction::Field {
+                    target,
+                    path: field.path,
+                    kind,
+                },
+                false,
+            ),
+        };
+        rows.push(Row::new(field.depth + 2, vec![label, value]));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Component, Reflect, Default)]
+    #[reflect(Component)]
+    struct Health {
+        current: f32,
+        regenerating: bool,
+    }
+
+    fn world() -> World {
+        let mut world = World::new();
+        let registry = AppTypeRegistry::default();
+        registry.write().register::<Health>();
+        world.insert_resource(registry);
+        world
+    }
+
+    #[test]
+    fn components_are_edited() {
+        let mut world = world();
+        let entity = world.spawn(Health::default()).id();
+        let target = Target::Component(entity, TypeId::of::<Health>());
+        let mut state = InspectorState::default();
+
+        apply_action(
+            &mut world,
+            &mut state,
+            InspectorAction::Field {
+                target,
+                path: ".current".to_string(),
+                kind: FieldKind::Text,
+            },
+        );
+        for key in [
+            TypedKey::Backspace,
+            TypedKey::Backspace,
+            TypedKey::Backspace,
+            TypedKey::Text("42".to_string()),
+            TypedKey::Enter,
+        ] {
+            apply_key(&mut world, &mut state, key);
+        }
+        apply_action(
+            &mut world,
+            &mut state,
+            InspectorAction::Field {
+                target,
+                path: ".regenerating".to_string(),
+                kind: FieldKind::Bool,
+            },
+        );
+
+        let health = world.get::<Health>(entity).unwrap();
+        assert_eq!(health.current, 42.0);
+        assert!(health.regenerating);
+        assert_eq!(state.focus, Focus::None);
+        assert_eq!(state.error, None);
+    }
+
+    #[test]
+    fn entities_are_listed_as_a_hierarchy_or_searched() {
+        let mut world = world();
+        let parent = world.spawn(Name::new("Player")).id();
+        let child = world.spawn(Name::new("Sword")).id();
+        world.entity_mut(parent).add_child(child);
+        world.spawn((Name::new("Hidden"), InspectorUi));
+
+        let mut state = InspectorState::default();
+        let labels = |state: &InspectorState| -> Vec<String> {
+            entity_rows(&world, state)
+                .into_iter()
+                .map(|row| row.cells.last().unwrap().text.clone())
+                .collect()
+        };
+        assert_eq!(labels(&state), vec![format!("Player ({parent:?})")]);
+
+        state.expanded.insert(parent);
+        assert_eq!(
+            labels(&state),
+            vec![format!("Player ({parent:?})"), format!("Sword ({child:?})")]
+        );
+
+        state.search = "sWo".to_string();
+        assert_eq!(labels(&state), vec![format!("Sword ({child:?})")]);
+    }
+}
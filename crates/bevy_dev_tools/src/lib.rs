@@ -0,0 +1,7 @@
+//! Tools to help developing apps with the [Bevy game engine](https://bevyengine.org/), like
+//! an in-game performance overlay and an entity and resource inspector.
+
+#![forbid(unsafe_code)]
+
+pub mod inspector;
+pub mod perf_overlay;
@@ -0,0 +1,360 @@
+//! A toggleable overlay displaying the performance of the app: its frame rate, a graph of its
+//! frame times, its GPU time, its number of entities and draw calls, its memory usage and its
+//! slowest systems.
+
+use std::collections::VecDeque;
+
+use bevy_app::{App, Plugin, Startup, Update};
+use bevy_diagnostic::{
+    DiagnosticPath, DiagnosticsStore, EntityCountDiagnosticsPlugin, FrameTimeDiagnosticsPlugin,
+    SystemInformationDiagnosticsPlugin, SystemTimeDiagnosticsPlugin,
+};
+use bevy_ecs::prelude::*;
+use bevy_hierarchy::{BuildChildren, Children};
+use bevy_input::{keyboard::KeyCode, ButtonInput};
+use bevy_render::{color::Color, diagnostic::RenderDiagnosticsPlugin, view::Visibility};
+use bevy_text::{Text, TextStyle};
+use bevy_ui::{
+    node_bundles::{NodeBundle, TextBundle},
+    AlignItems, BackgroundColor, FlexDirection, PositionType, Style, UiRect, Val, ZIndex,
+};
+use bevy_utils::{default, get_short_name};
+
+/// The number of frames displayed by the frame time graph.
+const GRAPH_FRAMES: usize = 100;
+
+/// Adds a [`PerfOverlayConfig`] and the performance overlay it configures, in the top left
+/// corner of the UI.
+///
+/// The overlay is fed from the [`DiagnosticsStore`], and adds the diagnostics plugins it displays
+/// if they weren't added yet.
+#[derive(Default)]
+pub struct PerfOverlayPlugin {
+    /// The configuration of the overlay when the app starts.
+    pub config: PerfOverlayConfig,
+}
+
+impl Plugin for PerfOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<FrameTimeDiagnosticsPlugin>() {
+            app.add_plugins(FrameTimeDiagnosticsPlugin);
+        }
+        if !app.is_plugin_added::<EntityCountDiagnosticsPlugin>() {
+            app.add_plugins(EntityCountDiagnosticsPlugin);
+        }
+        if !app.is_plugin_added::<RenderDiagnosticsPlugin>() {
+            app.add_plugins(RenderDiagnosticsPlugin);
+        }
+        if !app.is_plugin_added::<SystemInformationDiagnosticsPlugin>() {
+            app.add_plugins(SystemInformationDiagnosticsPlugin);
+        }
+        if !app.is_plugin_added::<SystemTimeDiagnosticsPlugin>() {
+            app.add_plugins(SystemTimeDiagnosticsPlugin);
+        }
+
+        app.insert_resource(self.config.clone())
+            .init_resource::<FrameTimeHistory>()
+            .add_systems(Startup, setup_overlay)
+            .add_systems(
+                Update,
+                (
+                    toggle_overlay,
+                    configure_overlay.run_if(resource_changed::<PerfOverlayConfig>),
+                    (update_text, update_graph).run_if(overlay_enabled),
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// The configuration of the performance overlay added by the [`PerfOverlayPlugin`].
+#[derive(Resource, Clone, Debug)]
+pub struct PerfOverlayConfig {
+    /// Whether the overlay is displayed.
+    pub enabled: bool,
+    /// The key toggling the overlay, or `None` not to toggle it with the keyboard.
+    pub toggle_key: Option<KeyCode>,
+    /// The style of the text of the overlay.
+    pub text_style: TextStyle,
+    /// The frame time at the top of the frame time graph, in milliseconds.
+    ///
+    /// The frames taking less than half of it are drawn in green, the ones taking less than it in
+    /// yellow, and the slower ones in red.
+    pub graph_max_frame_time: f64,
+    /// The number of systems listed by the overlay, from the one taking the most CPU time.
+    pub system_count: usize,
+}
+
+impl Default for PerfOverlayConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            toggle_key: Some(KeyCode::F12),
+            text_style: TextStyle {
+                font_size: 16.0,
+                color: Color::WHITE,
+                ..default()
+            },
+            graph_max_frame_time: 1000.0 / 30.0,
+            system_count: 5,
+        }
+    }
+}
+
+/// The root node of the performance overlay.
+#[derive(Component)]
+pub struct PerfOverlay;
+
+/// The text of the performance overlay.
+#[derive(Component)]
+struct PerfOverlayText;
+
+/// The frame time graph of the performance overlay, with a bar per frame.
+#[derive(Component)]
+struct PerfOverlayGraph;
+
+/// The frame times displayed by the graph, in milliseconds, from the oldest to the newest.
+#[derive(Resource, Default)]
+struct FrameTimeHistory(VecDeque<f64>);
+
+fn overlay_enabled(config: Res<PerfOverlayConfig>) -> bool {
+    config.enabled
+}
+
+fn setup_overlay(mut commands: Commands, config: Res<PerfOverlayConfig>) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(0.0),
+                    left: Val::Px(0.0),
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(4.0),
+                    padding: UiRect::all(Val::Px(6.0)),
+                    ..default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.6).into(),
+                z_index: ZIndex::Global(i32::MAX),
+                visibility: overlay_visibility(&config),
+                ..default()
+            },
+            PerfOverlay,
+        ))
+        .with_children(|overlay| {
+            overlay.spawn((
+                TextBundle::from_section("", config.text_style.clone()),
+                PerfOverlayText,
+            ));
+            overlay
+                .spawn((
+                    NodeBundle {
+                        style: Style {
+                            width: Val::Px(GRAPH_FRAMES as f32 * 2.0),
+                            height: Val::Px(40.0),
+                            align_items: AlignItems::FlexEnd,
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    PerfOverlayGraph,
+                ))
+                .with_children(|graph| {
+                    for _ in 0..GRAPH_FRAMES {
+                        graph.spawn(NodeBundle {
+                            style: Style {
+                                width: Val::Px(2.0),
+                                height: Val::Percent(0.0),
+                                ..default()
+                            },
+                            ..default()
+                        });
+                    }
+                });
+        });
+}
+
+fn overlay_visibility(config: &PerfOverlayConfig) -> Visibility {
+    if config.enabled {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    }
+}
+
+fn toggle_overlay(
+    keyboard: Option<Res<ButtonInput<KeyCode>>>,
+    mut config: ResMut<PerfOverlayConfig>,
+) {
+    let (Some(keyboard), Some(toggle_key)) = (keyboard, config.toggle_key) else {
+        return;
+    };
+    if keyboard.just_pressed(toggle_key) {
+        config.enabled = !config.enabled;
+    }
+}
+
+fn configure_overlay(
+    config: Res<PerfOverlayConfig>,
+    mut overlays: Query<&mut Visibility, With<PerfOverlay>>,
+    mut texts: Query<&mut Text, With<PerfOverlayText>>,
+) {
+    for mut visibility in &mut overlays {
+        *visibility = overlay_visibility(&config);
+    }
+    for mut text in &mut texts {
+        for section in &mut text.sections {
+            section.style = config.text_style.clone();
+        }
+    }
+}
+
+fn update_text(
+    diagnostics: Res<DiagnosticsStore>,
+    config: Res<PerfOverlayConfig>,
+    mut texts: Query<&mut Text, With<PerfOverlayText>>,
+) {
+    let value = overlay_text(&diagnostics, config.system_count);
+    for mut text in &mut texts {
+        if let Some(section) = text.sections.first_mut() {
+            section.value.clone_from(&value);
+        }
+    }
+}
+
+/// Formats the diagnostics displayed by the overlay, with a dash for the unavailable ones, and the
+/// `system_count` systems taking the most time.
+fn overlay_text(diagnostics: &DiagnosticsStore, system_count: usize) -> String {
+    let smoothed = |path: &DiagnosticPath| {
+        diagnostics
+            .get(path)
+            .filter(|diagnostic| diagnostic.is_enabled)
+            .and_then(|diagnostic| diagnostic.smoothed())
+    };
+    let format = |value: Option<f64>, precision: usize, suffix: &str| {
+        value.map_or_else(
+            || "-".to_string(),
+            |value| format!("{value:.precision$}{suffix}"),
+        )
+    };
+
+    let mut text = format!(
+        "FPS: {} ({})\nGPU: {}\nEntities: {}\nDraw calls: {}\nMemory: {}",
+        format(smoothed(&FrameTimeDiagnosticsPlugin::FPS), 0, ""),
+        format(smoothed(&FrameTimeDiagnosticsPlugin::FRAME_TIME), 2, " ms"),
+        format(smoothed(&RenderDiagnosticsPlugin::GPU_TIME), 2, " ms"),
+        format(smoothed(&EntityCountDiagnosticsPlugin::ENTITY_COUNT), 0, ""),
+        format(smoothed(&RenderDiagnosticsPlugin::DRAW_CALLS), 0, ""),
+        format(
+            smoothed(&SystemInformationDiagnosticsPlugin::MEM_USAGE),
+            1,
+            "%"
+        ),
+    );
+
+    let mut system_times: Vec<_> = diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.is_enabled)
+        .filter_map(|diagnostic| {
+            // The path is `system_time/<schedule>/<system>`.
+            let mut components = diagnostic.path().as_str().splitn(3, '/');
+            if components.next() != Some(SystemTimeDiagnosticsPlugin::SYSTEM_TIME.as_str()) {
+                return None;
+            }
+            let system = components.nth(1)?;
+            Some((get_short_name(system), diagnostic.smoothed()?))
+        })
+        .collect();
+    if system_count > 0 && !system_times.is_empty() {
+        system_times.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+        text.push_str("\nSystems:");
+        for (system, time) in system_times.iter().take(system_count) {
+            text.push_str(&format!("\n  {system}: {time:.2} ms"));
+        }
+    }
+    text
+}
+
+fn update_graph(
+    diagnostics: Res<DiagnosticsStore>,
+    config: Res<PerfOverlayConfig>,
+    mut history: ResMut<FrameTimeHistory>,
+    graphs: Query<&Children, With<PerfOverlayGraph>>,
+    mut bars: Query<(&mut Style, &mut BackgroundColor)>,
+) {
+    let Some(frame_time) = diagnostics
+        .get(&FrameTimeDiagnosticsPlugin::FRAME_TIME)
+        .and_then(|diagnostic| diagnostic.value())
+    else {
+        return;
+    };
+    if history.0.len() == GRAPH_FRAMES {
+        history.0.pop_front();
+    }
+    history.0.push_back(frame_time);
+
+    // The newest frame is on the right of the graph.
+    let offset = GRAPH_FRAMES - history.0.len();
+    for children in &graphs {
+        for (&frame_time, &bar) in history.0.iter().zip(children.iter().skip(offset)) {
+            let Ok((mut style, mut color)) = bars.get_mut(bar) else {
+                continue;
+            };
+            let ratio = frame_time / config.graph_max_frame_time;
+            style.height = Val::Percent((ratio.min(1.0) * 100.0) as f32);
+            color.0 = if ratio < 0.5 {
+                Color::GREEN
+            } else if ratio < 1.0 {
+                Color::YELLOW
+            } else {
+                Color::RED
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_diagnostic::{Diagnostic, DiagnosticMeasurement};
+    use bevy_utils::Instant;
+
+    #[test]
+    fn overlay_text_shows_available_diagnostics() {
+        let mut diagnostics = DiagnosticsStore::default();
+        let mut fps = Diagnostic::new(FrameTimeDiagnosticsPlugin::FPS);
+        fps.add_measurement(DiagnosticMeasurement {
+            time: Instant::now(),
+            value: 60.0,
+        });
+        diagnostics.add(fps);
+        diagnostics.add(Diagnostic::new(RenderDiagnosticsPlugin::DRAW_CALLS));
+
+        assert_eq!(
+            overlay_text(&diagnostics, 5),
+            "FPS: 60 (-)\nGPU: -\nEntities: -\nDraw calls: -\nMemory: -"
+        );
+    }
+
+    #[test]
+    fn overlay_text_lists_slowest_systems() {
+        let mut diagnostics = DiagnosticsStore::default();
+        for (system, time) in [
+            ("my_game::movement", 0.5),
+            ("my_game::ai<my_game::Enemy>", 2.0),
+            ("my_game::audio", 0.1),
+        ] {
+            let mut diagnostic =
+                Diagnostic::new(SystemTimeDiagnosticsPlugin::system_path(&Update, system));
+            diagnostic.add_measurement(DiagnosticMeasurement {
+                time: Instant::now(),
+                value: time,
+            });
+            diagnostics.add(diagnostic);
+        }
+
+        assert!(overlay_text(&diagnostics, 2)
+            .ends_with("Memory: -\nSystems:\n  ai<Enemy>: 2.00 ms\n  movement: 0.50 ms"));
+        assert!(overlay_text(&diagnostics, 0).ends_with("Memory: -"));
+    }
+}
@@ -10,6 +10,7 @@ mod entity_count_diagnostics_plugin;
 mod frame_time_diagnostics_plugin;
 mod log_diagnostics_plugin;
 mod system_information_diagnostics_plugin;
+mod system_time_diagnostics_plugin;
 
 use bevy_app::prelude::*;
 pub use diagnostic::*;
@@ -17,6 +18,7 @@ pub use entity_count_diagnostics_plugin::EntityCountDiagnosticsPlugin;
 pub use frame_time_diagnostics_plugin::FrameTimeDiagnosticsPlugin;
 pub use log_diagnostics_plugin::LogDiagnosticsPlugin;
 pub use system_information_diagnostics_plugin::SystemInformationDiagnosticsPlugin;
+pub use system_time_diagnostics_plugin::SystemTimeDiagnosticsPlugin;
 
 /// Adds core diagnostics resources to an App.
 #[derive(Default)]
@@ -0,0 +1,131 @@
+use bevy_app::prelude::*;
+use bevy_ecs::{
+    prelude::*,
+    schedule::{InternedScheduleLabel, NodeId, ScheduleLabel},
+};
+use bevy_utils::{HashMap, Instant};
+
+use crate::{Diagnostic, DiagnosticMeasurement, DiagnosticPath, DiagnosticsStore};
+
+/// Adds a "system time" diagnostic to an App for each system of its schedules, with the CPU time
+/// the system took to run.
+///
+/// The diagnostics are named `system_time/<schedule>/<system>`, and are added when the systems
+/// first run. The times of systems with the same name in a schedule are summed.
+///
+/// Schedules only record the times of their last run, so the systems of schedules running several
+/// times per frame, like `FixedUpdate`, are measured once per frame. Schedules that didn't run
+/// since the previous frame, like `OnEnter` schedules, aren't measured again: the recorded times
+/// are cleared with [`Schedule::clear_system_times`] once read. The systems of the [`First`]
+/// schedule aren't measured, as it is running while the diagnostics are updated.
+///
+/// # See also
+///
+/// [`LogDiagnosticsPlugin`](crate::LogDiagnosticsPlugin) to output diagnostics to the console.
+#[derive(Default)]
+pub struct SystemTimeDiagnosticsPlugin;
+
+impl Plugin for SystemTimeDiagnosticsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DiagnosticsStore>()
+            .add_systems(First, Self::diagnostic_system);
+    }
+}
+
+impl SystemTimeDiagnosticsPlugin {
+    /// The prefix of the paths of the system time diagnostics.
+    pub const SYSTEM_TIME: DiagnosticPath = DiagnosticPath::const_new("system_time");
+
+    /// The path of the diagnostic of the systems named `system_name` in the `schedule`.
+    pub fn system_path(schedule: &dyn ScheduleLabel, system_name: &str) -> DiagnosticPath {
+        DiagnosticPath::from_components([
+            Self::SYSTEM_TIME.as_str(),
+            &format!("{schedule:?}"),
+            system_name,
+        ])
+    }
+
+    pub fn diagnostic_system(
+        mut schedules: ResMut<Schedules>,
+        mut diagnostics: ResMut<DiagnosticsStore>,
+        mut paths: Local<HashMap<(InternedScheduleLabel, NodeId), DiagnosticPath>>,
+        mut times: Local<HashMap<DiagnosticPath, Option<f64>>>,
+    ) {
+        for time in times.values_mut() {
+            *time = None;
+        }
+
+        for (label, schedule) in schedules.iter_mut() {
+            schedule.set_record_system_times(true);
+            for (node_id, system, duration) in schedule.system_times() {
+                let path = paths
+                    .entry((schedule.label(), node_id))
+                    .or_insert_with(|| Self::system_path(label, &system.name()));
+                if !times.contains_key(path) {
+                    times.insert(path.clone(), None);
+                }
+                let time = times.get_mut(path).unwrap();
+                *time = Some(time.unwrap_or(0.0) + duration.as_secs_f64() * 1000.0);
+            }
+            schedule.clear_system_times();
+        }
+
+        let now = Instant::now();
+        for (path, time) in times.iter() {
+            let Some(time) = *time else {
+                continue;
+            };
+            if diagnostics.get(path).is_none() {
+                diagnostics.add(Diagnostic::new(path.clone()).with_suffix("ms"));
+            }
+            if let Some(diagnostic) = diagnostics
+                .get_mut(path)
+                .filter(|diagnostic| diagnostic.is_enabled)
+            {
+                diagnostic.add_measurement(DiagnosticMeasurement {
+                    time: now,
+                    value: time,
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_ecs::schedule::common_conditions::run_once;
+
+    #[derive(ScheduleLabel, Hash, PartialEq, Eq, Debug, Clone)]
+    struct RunsOnce;
+
+    fn system() {}
+
+    #[test]
+    fn schedules_are_only_measured_when_they_run() {
+        let mut app = App::new();
+        app.add_plugins(SystemTimeDiagnosticsPlugin)
+            .init_schedule(RunsOnce)
+            .add_systems(RunsOnce, system)
+            .add_systems(Update, system)
+            .add_systems(
+                Update,
+                (|world: &mut World| world.run_schedule(RunsOnce)).run_if(run_once()),
+            );
+        for _ in 0..4 {
+            app.update();
+        }
+
+        let diagnostics = app.world.resource::<DiagnosticsStore>();
+        let measurements = |schedule: &dyn ScheduleLabel| {
+            let path = SystemTimeDiagnosticsPlugin::system_path(
+                schedule,
+                "bevy_diagnostic::system_time_diagnostics_plugin::tests::system",
+            );
+            diagnostics.get(&path).unwrap().measurements().count()
+        };
+        assert_eq!(measurements(&RunsOnce), 1);
+        // `Update` runs every frame, and is measured in the next frame.
+        assert_eq!(measurements(&Update), 3);
+    }
+}
@@ -1,7 +1,13 @@
 use proc_macro::TokenStream;
 use proc_macro2::{Span, TokenStream as TokenStream2};
 use quote::quote;
-use syn::{parse_macro_input, parse_quote, DeriveInput, Ident, LitStr, Path, Result};
+use syn::{
+    parenthesized,
+    parse::{Parse, ParseStream},
+    parse_macro_input, parse_quote,
+    punctuated::Punctuated,
+    DeriveInput, Expr, ExprPath, Ident, LitStr, Path, Result, Token, Type,
+};
 
 pub fn derive_event(input: TokenStream) -> TokenStream {
     let mut ast = parse_macro_input!(input as DeriveInput);
@@ -50,6 +56,17 @@ pub fn derive_component(input: TokenStream) -> TokenStream {
 
     let storage = storage_path(&bevy_ecs_path, attrs.storage);
 
+    let on_add = hook_register_function_call(quote! {on_add}, attrs.on_add);
+    let on_insert = hook_register_function_call(quote! {on_insert}, attrs.on_insert);
+    let on_remove = hook_register_function_call(quote! {on_remove}, attrs.on_remove);
+    let requires = attrs.requires.iter().map(|require| {
+        let ty = &require.ty;
+        match &require.constructor {
+            Some(constructor) => quote! { required.register_with::<#ty>(#constructor); },
+            None => quote! { required.register::<#ty>(); },
+        }
+    });
+
     ast.generics
         .make_where_clause()
         .predicates
@@ -61,15 +78,53 @@ pub fn derive_component(input: TokenStream) -> TokenStream {
     TokenStream::from(quote! {
         impl #impl_generics #bevy_ecs_path::component::Component for #struct_name #type_generics #where_clause {
             type Storage = #storage;
+
+            #[allow(unused_variables)]
+            fn register_component_hooks(hooks: &mut #bevy_ecs_path::component::ComponentHooks) {
+                #on_add
+                #on_insert
+                #on_remove
+            }
+
+            #[allow(unused_variables)]
+            fn register_required_components(required: &mut #bevy_ecs_path::component::RequiredComponents) {
+                #(#requires)*
+            }
         }
     })
 }
 
 pub const COMPONENT: &str = "component";
 pub const STORAGE: &str = "storage";
+pub const ON_ADD: &str = "on_add";
+pub const ON_INSERT: &str = "on_insert";
+pub const ON_REMOVE: &str = "on_remove";
+pub const REQUIRE: &str = "require";
 
 struct Attrs {
     storage: StorageTy,
+    on_add: Option<ExprPath>,
+    on_insert: Option<ExprPath>,
+    on_remove: Option<ExprPath>,
+    requires: Vec<Require>,
+}
+
+/// A required component, with an optional constructor: `Type` or `Type = constructor`.
+struct Require {
+    ty: Type,
+    constructor: Option<Expr>,
+}
+
+impl Parse for Require {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let ty = input.parse()?;
+        let constructor = if input.parse::<Option<Token![=]>>()?.is_some() {
+            Some(input.parse()?)
+        } else {
+            None
+        };
+        Ok(Require { ty, constructor })
+    }
 }
 
 #[derive(Clone, Copy)]
@@ -85,6 +140,10 @@ const SPARSE_SET: &str = "SparseSet";
 fn parse_component_attr(ast: &DeriveInput) -> Result<Attrs> {
     let mut attrs = Attrs {
         storage: StorageTy::Table,
+        on_add: None,
+        on_insert: None,
+        on_remove: None,
+        requires: Vec::new(),
     };
 
     for meta in ast.attrs.iter().filter(|a| a.path().is_ident(COMPONENT)) {
@@ -100,6 +159,24 @@ fn parse_component_attr(ast: &DeriveInput) -> Result<Attrs> {
                     }
                 };
                 Ok(())
+            } else if nested.path.is_ident(ON_ADD) {
+                attrs.on_add = Some(nested.value()?.parse::<ExprPath>()?);
+                Ok(())
+            } else if nested.path.is_ident(ON_INSERT) {
+                attrs.on_insert = Some(nested.value()?.parse::<ExprPath>()?);
+                Ok(())
+            } else if nested.path.is_ident(ON_REMOVE) {
+                attrs.on_remove = Some(nested.value()?.parse::<ExprPath>()?);
+                Ok(())
+            } else if nested.path.is_ident(REQUIRE) {
+                let content;
+                parenthesized!(content in nested.input);
+                attrs
+                    .requires
+                    .extend(Punctuated::<Require, Token![,]>::parse_terminated(
+                        &content,
+                    )?);
+                Ok(())
             } else {
                 Err(nested.error("Unsupported attribute"))
             }
@@ -117,3 +194,10 @@ fn storage_path(bevy_ecs_path: &Path, ty: StorageTy) -> TokenStream2 {
 
     quote! { #bevy_ecs_path::component::#typename }
 }
+
+fn hook_register_function_call(
+    hook: TokenStream2,
+    function: Option<ExprPath>,
+) -> Option<TokenStream2> {
+    function.map(|meta| quote! { hooks. #hook (#meta); })
+}
@@ -21,8 +21,9 @@
 
 use crate::{
     bundle::BundleId,
-    component::{ComponentId, StorageType},
+    component::{ComponentId, Components, StorageType},
     entity::{Entity, EntityLocation},
+    observer::Observers,
     storage::{ImmutableSparseSet, SparseArray, SparseSet, SparseSetIndex, TableId, TableRow},
 };
 use std::{
@@ -298,6 +299,20 @@ struct ArchetypeComponentInfo {
     archetype_component_id: ArchetypeComponentId,
 }
 
+bitflags::bitflags! {
+    /// Flags used to keep track of the hooks and observers of the components of an
+    /// [`Archetype`], so that entities in archetypes without any can skip looking them up.
+    #[derive(Clone, Copy)]
+    pub(crate) struct ArchetypeFlags: u32 {
+        const ON_ADD_HOOK = 1 << 0;
+        const ON_INSERT_HOOK = 1 << 1;
+        const ON_REMOVE_HOOK = 1 << 2;
+        const ON_ADD_OBSERVER = 1 << 3;
+        const ON_INSERT_OBSERVER = 1 << 4;
+        const ON_REMOVE_OBSERVER = 1 << 5;
+    }
+}
+
 /// Metadata for a single archetype within a [`World`].
 ///
 /// For more information, see the *[module level documentation]*.
@@ -310,10 +325,13 @@ pub struct Archetype {
     edges: Edges,
     entities: Vec<ArchetypeEntity>,
     components: ImmutableSparseSet<ComponentId, ArchetypeComponentInfo>,
+    flags: ArchetypeFlags,
 }
 
 impl Archetype {
     pub(crate) fn new(
+        components: &Components,
+        observers: &Observers,
         id: ArchetypeId,
         table_id: TableId,
         table_components: impl Iterator<Item = (ComponentId, ArchetypeComponentId)>,
@@ -321,9 +339,14 @@ impl Archetype {
     ) -> Self {
         let (min_table, _) = table_components.size_hint();
         let (min_sparse, _) = sparse_set_components.size_hint();
-        let mut components = SparseSet::with_capacity(min_table + min_sparse);
+        let mut flags = ArchetypeFlags::empty();
+        let mut archetype_components = SparseSet::with_capacity(min_table + min_sparse);
         for (component_id, archetype_component_id) in table_components {
-            components.insert(
+            // SAFETY: We are creating an archetype that includes this component so it must exist
+            let info = unsafe { components.get_info_unchecked(component_id) };
+            info.update_archetype_flags(&mut flags);
+            observers.update_archetype_flags(component_id, &mut flags);
+            archetype_components.insert(
                 component_id,
                 ArchetypeComponentInfo {
                     storage_type: StorageType::Table,
@@ -333,7 +356,11 @@ impl Archetype {
         }
 
         for (component_id, archetype_component_id) in sparse_set_components {
-            components.insert(
+            // SAFETY: We are creating an archetype that includes this component so it must exist
+            let info = unsafe { components.get_info_unchecked(component_id) };
+            info.update_archetype_flags(&mut flags);
+            observers.update_archetype_flags(component_id, &mut flags);
+            archetype_components.insert(
                 component_id,
                 ArchetypeComponentInfo {
                     storage_type: StorageType::SparseSet,
@@ -345,8 +372,9 @@ impl Archetype {
             id,
             table_id,
             entities: Vec::new(),
-            components: components.into_immutable(),
+            components: archetype_components.into_immutable(),
             edges: Default::default(),
+            flags,
         }
     }
 
@@ -532,6 +560,57 @@ impl Archetype {
             .map(|info| info.archetype_component_id)
     }
 
+    /// Returns the flags telling which hooks and observers the components of this archetype have.
+    #[inline]
+    pub(crate) fn flags(&self) -> ArchetypeFlags {
+        self.flags
+    }
+
+    /// Sets flags on this archetype, after an observer of one of its components was registered.
+    #[inline]
+    pub(crate) fn insert_flags(&mut self, flags: ArchetypeFlags) {
+        self.flags.insert(flags);
+    }
+
+    /// Returns true if any of the components in this archetype have `on_add` hooks.
+    #[inline]
+    pub fn has_add_hook(&self) -> bool {
+        self.flags.contains(ArchetypeFlags::ON_ADD_HOOK)
+    }
+
+    /// Returns true if any of the components in this archetype have `on_insert` hooks.
+    #[inline]
+    pub fn has_insert_hook(&self) -> bool {
+        self.flags.contains(ArchetypeFlags::ON_INSERT_HOOK)
+    }
+
+    /// Returns true if any of the components in this archetype have `on_remove` hooks.
+    #[inline]
+    pub fn has_remove_hook(&self) -> bool {
+        self.flags.contains(ArchetypeFlags::ON_REMOVE_HOOK)
+    }
+
+    /// Returns true if any of the components in this archetype have at least one
+    /// [`OnAdd`](crate::world::OnAdd) observer.
+    #[inline]
+    pub fn has_add_observer(&self) -> bool {
+        self.flags.contains(ArchetypeFlags::ON_ADD_OBSERVER)
+    }
+
+    /// Returns true if any of the components in this archetype have at least one
+    /// [`OnInsert`](crate::world::OnInsert) observer.
+    #[inline]
+    pub fn has_insert_observer(&self) -> bool {
+        self.flags.contains(ArchetypeFlags::ON_INSERT_OBSERVER)
+    }
+
+    /// Returns true if any of the components in this archetype have at least one
+    /// [`OnRemove`](crate::world::OnRemove) observer.
+    #[inline]
+    pub fn has_remove_observer(&self) -> bool {
+        self.flags.contains(ArchetypeFlags::ON_REMOVE_OBSERVER)
+    }
+
     /// Clears all entities from the archetype.
     pub(crate) fn clear_entities(&mut self) {
         self.entities.clear();
@@ -618,7 +697,16 @@ impl Archetypes {
             by_components: Default::default(),
             archetype_component_count: 0,
         };
-        archetypes.get_id_or_insert(TableId::empty(), Vec::new(), Vec::new());
+        // SAFETY: Empty archetype has no components
+        unsafe {
+            archetypes.get_id_or_insert(
+                &Components::default(),
+                &Observers::default(),
+                TableId::empty(),
+                Vec::new(),
+                Vec::new(),
+            );
+        }
         archetypes
     }
 
@@ -711,8 +799,11 @@ impl Archetypes {
     ///
     /// # Safety
     /// [`TableId`] must exist in tables
-    pub(crate) fn get_id_or_insert(
+    /// `table_components` and `sparse_set_components` must exist in `components`
+    pub(crate) unsafe fn get_id_or_insert(
         &mut self,
+        components: &Components,
+        observers: &Observers,
         table_id: TableId,
         table_components: Vec<ComponentId>,
         sparse_set_components: Vec<ComponentId>,
@@ -738,6 +829,8 @@ impl Archetypes {
                 let sparse_set_archetype_components =
                     (sparse_start..*archetype_component_count).map(ArchetypeComponentId);
                 archetypes.push(Archetype::new(
+                    components,
+                    observers,
                     id,
                     table_id,
                     table_components.into_iter().zip(table_archetype_components),
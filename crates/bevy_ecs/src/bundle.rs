@@ -12,6 +12,7 @@ use crate::{
     },
     component::{Component, ComponentId, ComponentStorage, Components, StorageType, Tick},
     entity::{Entities, Entity, EntityLocation},
+    observer::Observers,
     query::DebugCheckedUnwrap,
     storage::{SparseSetIndex, SparseSets, Storages, Table, TableRow},
 };
@@ -346,17 +347,19 @@ impl BundleInfo {
         &self.component_ids
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn get_bundle_inserter<'a, 'b>(
         &'b self,
         entities: &'a mut Entities,
         archetypes: &'a mut Archetypes,
         components: &Components,
         storages: &'a mut Storages,
+        observers: &Observers,
         archetype_id: ArchetypeId,
         change_tick: Tick,
     ) -> BundleInserter<'a, 'b> {
         let new_archetype_id =
-            self.add_bundle_to_archetype(archetypes, storages, components, archetype_id);
+            self.add_bundle_to_archetype(archetypes, storages, components, observers, archetype_id);
         let archetypes_ptr = archetypes.archetypes.as_mut_ptr();
         if new_archetype_id == archetype_id {
             let archetype = &mut archetypes[archetype_id];
@@ -412,10 +415,16 @@ impl BundleInfo {
         archetypes: &'a mut Archetypes,
         components: &Components,
         storages: &'a mut Storages,
+        observers: &Observers,
         change_tick: Tick,
     ) -> BundleSpawner<'a, 'b> {
-        let new_archetype_id =
-            self.add_bundle_to_archetype(archetypes, storages, components, ArchetypeId::EMPTY);
+        let new_archetype_id = self.add_bundle_to_archetype(
+            archetypes,
+            storages,
+            components,
+            observers,
+            ArchetypeId::EMPTY,
+        );
         let archetype = &mut archetypes[new_archetype_id];
         let table = &mut storages.tables[archetype.table_id()];
         BundleSpawner {
@@ -498,6 +507,7 @@ impl BundleInfo {
         archetypes: &mut Archetypes,
         storages: &mut Storages,
         components: &Components,
+        observers: &Observers,
         archetype_id: ArchetypeId,
     ) -> ArchetypeId {
         if let Some(add_bundle_id) = archetypes[archetype_id].edges().get_add_bundle(self.id) {
@@ -561,8 +571,16 @@ impl BundleInfo {
                     new_sparse_set_components
                 };
             };
-            let new_archetype_id =
-                archetypes.get_id_or_insert(table_id, table_components, sparse_set_components);
+            // SAFETY: ids in self must be valid
+            let new_archetype_id = unsafe {
+                archetypes.get_id_or_insert(
+                    components,
+                    observers,
+                    table_id,
+                    table_components,
+                    sparse_set_components,
+                )
+            };
             // add an edge from the old archetype to the new archetype
             archetypes[archetype_id].edges_mut().insert_add_bundle(
                 self.id,
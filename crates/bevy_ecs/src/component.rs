@@ -2,10 +2,12 @@
 
 use crate::{
     self as bevy_ecs,
+    archetype::ArchetypeFlags,
     change_detection::MAX_CHANGE_AGE,
+    entity::Entity,
     storage::{SparseSetIndex, Storages},
     system::{Local, Resource, SystemParam},
-    world::{FromWorld, World},
+    world::{DeferredWorld, FromWorld, World},
 };
 pub use bevy_ecs_macros::Component;
 use bevy_ptr::{OwningPtr, UnsafeCellDeref};
@@ -17,8 +19,11 @@ use std::{
     alloc::Layout,
     any::{Any, TypeId},
     borrow::Cow,
+    fmt::Debug,
     marker::PhantomData,
-    mem::needs_drop,
+    mem::{needs_drop, MaybeUninit},
+    ptr::NonNull,
+    sync::Arc,
 };
 
 /// A data type that can be used to store data for an [entity].
@@ -148,10 +153,61 @@ use std::{
 ///
 /// [`SyncCell`]: bevy_utils::synccell::SyncCell
 /// [`Exclusive`]: https://doc.rust-lang.org/nightly/std/sync/struct.Exclusive.html
+///
+/// # Lifecycle hooks
+///
+/// Components can react to being added to, inserted on or removed from an entity with
+/// [`ComponentHooks`]. Hooks are registered once per component type, either by implementing
+/// [`Component::register_component_hooks`], with the `on_add`, `on_insert` and `on_remove`
+/// attributes of the derive, or at runtime with [`World::register_component_hooks`].
+///
+/// ```
+/// # use bevy_ecs::prelude::*;
+/// # use bevy_ecs::{component::ComponentId, world::DeferredWorld};
+/// #[derive(Component)]
+/// #[component(on_add = announce)]
+/// struct Player;
+///
+/// fn announce(world: DeferredWorld, entity: Entity, _component_id: ComponentId) {
+///     println!("{entity:?} joined the game");
+/// }
+/// ```
+///
+/// # Required components
+///
+/// A component can require other components, which are inserted with a default value when it's
+/// added to an entity that doesn't have them yet. Requirements are registered once per component
+/// type, either by implementing [`Component::register_required_components`], with the `require`
+/// attribute of the derive, or at runtime with [`World::register_required_components`].
+///
+/// ```
+/// # use bevy_ecs::prelude::*;
+/// #[derive(Component, Default)]
+/// struct Health(u32);
+///
+/// #[derive(Component)]
+/// struct Team(u8);
+///
+/// #[derive(Component)]
+/// #[component(require(Health, Team = || Team(1)))]
+/// struct Player;
+///
+/// let mut world = World::new();
+/// let player = world.spawn((Player, Health(100))).id();
+/// assert_eq!(world.get::<Health>(player).unwrap().0, 100);
+/// assert_eq!(world.get::<Team>(player).unwrap().0, 1);
+/// ```
 pub trait Component: Send + Sync + 'static {
     /// A marker type indicating the storage type used for this component.
     /// This must be either [`TableStorage`] or [`SparseStorage`].
     type Storage: ComponentStorage;
+
+    /// Called when registering this component, allowing mutable access to its [`ComponentHooks`].
+    fn register_component_hooks(_hooks: &mut ComponentHooks) {}
+
+    /// Called when registering this component, allowing mutable access to its
+    /// [`RequiredComponents`].
+    fn register_required_components(_required: &mut RequiredComponents) {}
 }
 
 /// Marker type for components stored in a [`Table`](crate::storage::Table).
@@ -203,11 +259,316 @@ pub enum StorageType {
     SparseSet,
 }
 
+/// The type used for [`Component`] lifecycle hooks such as `on_add`, `on_insert` or `on_remove`.
+pub type ComponentHook = for<'w> fn(DeferredWorld<'w>, Entity, ComponentId);
+
+/// Functions that run when a [`Component`] is added to, inserted on or removed from an entity.
+///
+/// Unlike systems reacting to [`Added`](crate::query::Added) or
+/// [`RemovedComponents`](crate::removal_detection::RemovedComponents), hooks run immediately, as
+/// part of the operation that changed the entity. They can be used to enforce invariants between
+/// components, or to keep an index of entities up to date.
+///
+/// Hooks receive a [`DeferredWorld`], which allows accessing components and resources, but defers
+/// structural changes such as spawning entities or inserting components through
+/// [`DeferredWorld::commands`]. These commands are applied right after the operation that
+/// triggered the hook.
+///
+/// Each component type can only have one hook of each kind, and hooks can only be registered
+/// before the component is first added to an entity.
+///
+/// ```
+/// use bevy_ecs::prelude::*;
+/// use bevy_utils::HashSet;
+///
+/// #[derive(Component)]
+/// struct MyTrackedComponent;
+///
+/// #[derive(Resource, Default)]
+/// struct TrackedEntities(HashSet<Entity>);
+///
+/// let mut world = World::new();
+/// world.init_resource::<TrackedEntities>();
+///
+/// world
+///     .register_component_hooks::<MyTrackedComponent>()
+///     .on_add(|mut world, entity, _| {
+///         world.resource_mut::<TrackedEntities>().0.insert(entity);
+///     })
+///     .on_remove(|mut world, entity, _| {
+///         world.resource_mut::<TrackedEntities>().0.remove(&entity);
+///     });
+///
+/// let entity = world.spawn(MyTrackedComponent).id();
+/// assert!(world.resource::<TrackedEntities>().0.contains(&entity));
+///
+/// world.despawn(entity);
+/// assert!(world.resource::<TrackedEntities>().0.is_empty());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ComponentHooks {
+    pub(crate) on_add: Option<ComponentHook>,
+    pub(crate) on_insert: Option<ComponentHook>,
+    pub(crate) on_remove: Option<ComponentHook>,
+}
+
+impl ComponentHooks {
+    /// Registers a [`ComponentHook`] that runs when this component is added to an entity.
+    ///
+    /// An `on_add` hook always runs before the `on_insert` hook of the same component.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the component already has an `on_add` hook.
+    pub fn on_add(&mut self, hook: ComponentHook) -> &mut Self {
+        self.try_on_add(hook)
+            .expect("Component already has an on_add hook")
+    }
+
+    /// Registers a [`ComponentHook`] that runs when this component is added to an entity, or when
+    /// an existing value of this component is replaced.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the component already has an `on_insert` hook.
+    pub fn on_insert(&mut self, hook: ComponentHook) -> &mut Self {
+        self.try_on_insert(hook)
+            .expect("Component already has an on_insert hook")
+    }
+
+    /// Registers a [`ComponentHook`] that runs when this component is removed from an entity,
+    /// including when the entity is despawned.
+    ///
+    /// The hook runs before the component is removed, so it can still be read.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the component already has an `on_remove` hook.
+    pub fn on_remove(&mut self, hook: ComponentHook) -> &mut Self {
+        self.try_on_remove(hook)
+            .expect("Component already has an on_remove hook")
+    }
+
+    /// Attempts to register a [`ComponentHook`] that runs when this component is added to an
+    /// entity.
+    ///
+    /// Returns `None` if the component already has an `on_add` hook.
+    pub fn try_on_add(&mut self, hook: ComponentHook) -> Option<&mut Self> {
+        if self.on_add.is_some() {
+            return None;
+        }
+        self.on_add = Some(hook);
+        Some(self)
+    }
+
+    /// Attempts to register a [`ComponentHook`] that runs when this component is added to an
+    /// entity, or when an existing value of this component is replaced.
+    ///
+    /// Returns `None` if the component already has an `on_insert` hook.
+    pub fn try_on_insert(&mut self, hook: ComponentHook) -> Option<&mut Self> {
+        if self.on_insert.is_some() {
+            return None;
+        }
+        self.on_insert = Some(hook);
+        Some(self)
+    }
+
+    /// Attempts to register a [`ComponentHook`] that runs when this component is removed from an
+    /// entity.
+    ///
+    /// Returns `None` if the component already has an `on_remove` hook.
+    pub fn try_on_remove(&mut self, hook: ComponentHook) -> Option<&mut Self> {
+        if self.on_remove.is_some() {
+            return None;
+        }
+        self.on_remove = Some(hook);
+        Some(self)
+    }
+}
+
+/// Adds a required component to a [`MissingRequiredComponents`], unless the entity already has
+/// it.
+type RequiredComponentConstructor =
+    Arc<dyn Fn(&mut World, &mut MissingRequiredComponents) + Send + Sync>;
+
+/// The components that must be on every entity with a [`Component`].
+///
+/// When the component is added to an entity, the required components that the entity doesn't
+/// have yet are inserted right after the operation that added it, like the commands of
+/// [`ComponentHooks`]. Components that the required components themselves require are inserted
+/// too, all of them at once so the entity only moves to a single new archetype. Removing a
+/// required component later on doesn't insert it again.
+///
+/// Requirements can only be registered before the component is first added to an entity.
+#[derive(Clone, Default)]
+pub struct RequiredComponents {
+    constructors: Vec<(TypeId, &'static str, RequiredComponentConstructor)>,
+}
+
+impl RequiredComponents {
+    /// Requires the component `R`, inserted with its [`Default`] value.
+    pub fn register<R: Component + Default>(&mut self) -> &mut Self {
+        self.register_with(R::default)
+    }
+
+    /// Requires the component `R`, inserted with the value returned by `constructor`.
+    ///
+    /// This replaces the previous constructor of `R` if it was already required.
+    pub fn register_with<R: Component>(
+        &mut self,
+        constructor: impl Fn() -> R + Send + Sync + 'static,
+    ) -> &mut Self {
+        let type_id = TypeId::of::<R>();
+        self.constructors.retain(|(id, _, _)| *id != type_id);
+        self.constructors.push((
+            type_id,
+            std::any::type_name::<R>(),
+            Arc::new(move |world, missing| {
+                let id = world.init_component::<R>();
+                if missing.contains(world, id) {
+                    return;
+                }
+                missing.push(id, constructor());
+                // SAFETY: `id` was just initialized.
+                let info = unsafe { world.components.get_info_unchecked(id) };
+                if !info.required_components.is_empty() {
+                    let required = info.required_components.clone();
+                    required.collect_missing(world, missing);
+                }
+            }),
+        ));
+        self
+    }
+
+    /// Returns `true` if the component `R` is required.
+    pub fn contains<R: Component>(&self) -> bool {
+        let type_id = TypeId::of::<R>();
+        self.constructors.iter().any(|(id, _, _)| *id == type_id)
+    }
+
+    /// Returns the number of required components.
+    pub fn len(&self) -> usize {
+        self.constructors.len()
+    }
+
+    /// Returns `true` if no component is required.
+    pub fn is_empty(&self) -> bool {
+        self.constructors.is_empty()
+    }
+
+    /// Adds the required components, and the components they require, that the entity of
+    /// `missing` doesn't have yet.
+    pub(crate) fn collect_missing(
+        &self,
+        world: &mut World,
+        missing: &mut MissingRequiredComponents,
+    ) {
+        for (_, _, constructor) in &self.constructors {
+            constructor(world, missing);
+        }
+    }
+}
+
+/// The required components that an entity doesn't have yet, inserted together as a single
+/// dynamic bundle.
+pub(crate) struct MissingRequiredComponents {
+    entity: Entity,
+    ids: Vec<ComponentId>,
+    values: Vec<BoxedComponent>,
+}
+
+/// A boxed component value, with the function freeing its box.
+struct BoxedComponent {
+    ptr: NonNull<u8>,
+    free: unsafe fn(NonNull<u8>, bool),
+}
+
+/// Frees a box created by [`MissingRequiredComponents::push`], dropping the value unless it was
+/// moved out of the box.
+///
+/// # Safety
+///
+/// `ptr` must come from a `Box<R>`, freed only once.
+unsafe fn free_boxed_component<R>(ptr: NonNull<u8>, moved: bool) {
+    if moved {
+        // SAFETY: The caller ensures `ptr` is a `Box<R>`, whose value was moved out.
+        drop(unsafe { Box::from_raw(ptr.as_ptr().cast::<MaybeUninit<R>>()) });
+    } else {
+        // SAFETY: The caller ensures `ptr` is a `Box<R>`.
+        drop(unsafe { Box::from_raw(ptr.as_ptr().cast::<R>()) });
+    }
+}
+
+impl MissingRequiredComponents {
+    /// Creates an empty set of components for `entity`, which must exist until they are
+    /// collected.
+    pub(crate) fn new(entity: Entity) -> Self {
+        Self {
+            entity,
+            ids: Vec::new(),
+            values: Vec::new(),
+        }
+    }
+
+    /// Returns `true` if the entity already has the component `id`, or if it was already added.
+    fn contains(&self, world: &World, id: ComponentId) -> bool {
+        self.ids.contains(&id) || world.entity(self.entity).contains_id(id)
+    }
+
+    fn push<R: Component>(&mut self, id: ComponentId, value: R) {
+        let ptr = NonNull::from(Box::leak(Box::new(value))).cast();
+        self.ids.push(id);
+        self.values.push(BoxedComponent {
+            ptr,
+            free: free_boxed_component::<R>,
+        });
+    }
+
+    /// Inserts the missing components into the entity.
+    pub(crate) fn insert(mut self, world: &mut World) {
+        if self.ids.is_empty() {
+            return;
+        }
+        let mut entity = world.entity_mut(self.entity);
+        let values = std::mem::take(&mut self.values);
+        // SAFETY: The ids were initialized in this world, and each value has the type of its id.
+        unsafe {
+            entity.insert_by_ids(
+                &self.ids,
+                values.iter().map(|value| OwningPtr::new(value.ptr)),
+            );
+        }
+        for value in values {
+            // SAFETY: The values were moved into the entity.
+            unsafe { (value.free)(value.ptr, true) };
+        }
+    }
+}
+
+impl Drop for MissingRequiredComponents {
+    fn drop(&mut self) {
+        for value in self.values.drain(..) {
+            // SAFETY: The values that weren't inserted are still in their box.
+            unsafe { (value.free)(value.ptr, false) };
+        }
+    }
+}
+
+impl Debug for RequiredComponents {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list()
+            .entries(self.constructors.iter().map(|(_, name, _)| name))
+            .finish()
+    }
+}
+
 /// Stores metadata for a type of component or resource stored in a specific [`World`].
 #[derive(Debug, Clone)]
 pub struct ComponentInfo {
     id: ComponentId,
     descriptor: ComponentDescriptor,
+    hooks: ComponentHooks,
+    required_components: RequiredComponents,
 }
 
 impl ComponentInfo {
@@ -263,7 +624,40 @@ impl ComponentInfo {
 
     /// Create a new [`ComponentInfo`].
     pub(crate) fn new(id: ComponentId, descriptor: ComponentDescriptor) -> Self {
-        ComponentInfo { id, descriptor }
+        ComponentInfo {
+            id,
+            descriptor,
+            hooks: ComponentHooks::default(),
+            required_components: RequiredComponents::default(),
+        }
+    }
+
+    /// Returns the [`ComponentHooks`] of this component.
+    #[inline]
+    pub fn hooks(&self) -> &ComponentHooks {
+        &self.hooks
+    }
+
+    /// Returns the [`RequiredComponents`] of this component.
+    #[inline]
+    pub fn required_components(&self) -> &RequiredComponents {
+        &self.required_components
+    }
+
+    /// Sets the flags of an archetype containing this component that tell which of its hooks exist.
+    ///
+    /// Required components are inserted along with the `on_add` hooks, so they set the same flag.
+    #[inline]
+    pub(crate) fn update_archetype_flags(&self, flags: &mut ArchetypeFlags) {
+        if self.hooks.on_add.is_some() || !self.required_components.is_empty() {
+            flags.insert(ArchetypeFlags::ON_ADD_HOOK);
+        }
+        if self.hooks.on_insert.is_some() {
+            flags.insert(ArchetypeFlags::ON_INSERT_HOOK);
+        }
+        if self.hooks.on_remove.is_some() {
+            flags.insert(ArchetypeFlags::ON_REMOVE_HOOK);
+        }
     }
 }
 
@@ -474,7 +868,15 @@ impl Components {
             ..
         } = self;
         *indices.entry(type_id).or_insert_with(|| {
-            Components::init_component_inner(components, storages, ComponentDescriptor::new::<T>())
+            let index = Components::init_component_inner(
+                components,
+                storages,
+                ComponentDescriptor::new::<T>(),
+            );
+            let info = &mut components[index.index()];
+            T::register_component_hooks(&mut info.hooks);
+            T::register_required_components(&mut info.required_components);
+            index
         })
     }
 
@@ -551,6 +953,25 @@ impl Components {
         unsafe { self.components.get_unchecked(id.0) }
     }
 
+    /// Returns a mutable reference to the [`ComponentHooks`] of the component with the given
+    /// [`ComponentId`], if it exists.
+    #[inline]
+    pub(crate) fn get_hooks_mut(&mut self, id: ComponentId) -> Option<&mut ComponentHooks> {
+        self.components.get_mut(id.0).map(|info| &mut info.hooks)
+    }
+
+    /// Returns a mutable reference to the [`RequiredComponents`] of the component with the given
+    /// [`ComponentId`], if it exists.
+    #[inline]
+    pub(crate) fn get_required_components_mut(
+        &mut self,
+        id: ComponentId,
+    ) -> Option<&mut RequiredComponents> {
+        self.components
+            .get_mut(id.0)
+            .map(|info| &mut info.required_components)
+    }
+
     /// Type-erased equivalent of [`Components::component_id()`].
     #[inline]
     pub fn get_id(&self, type_id: TypeId) -> Option<ComponentId> {
@@ -0,0 +1,60 @@
+//! Disabling entities without despawning them.
+//!
+//! An entity with the [`Disabled`] component is skipped by queries, unless the query explicitly
+//! mentions [`Disabled`], for example through `With<Disabled>`, `Has<Disabled>` or
+//! `Option<&Disabled>`. This makes it cheap to deactivate an entity and later reactivate it, by
+//! removing the component, without despawning and respawning it.
+//!
+//! ```
+//! # use bevy_ecs::prelude::*;
+//! #[derive(Component)]
+//! struct Enemy;
+//!
+//! let mut world = World::new();
+//! world.spawn(Enemy);
+//! let disabled = world.spawn((Enemy, Disabled)).id();
+//!
+//! let mut enemies = world.query_filtered::<Entity, With<Enemy>>();
+//! assert_eq!(enemies.iter(&world).count(), 1);
+//!
+//! // Mentioning `Disabled` in the query opts into disabled entities.
+//! let mut all_enemies = world.query_filtered::<(Entity, Has<Disabled>), With<Enemy>>();
+//! assert_eq!(all_enemies.iter(&world).count(), 2);
+//!
+//! world.entity_mut(disabled).remove::<Disabled>();
+//! assert_eq!(enemies.iter(&world).count(), 2);
+//! ```
+//!
+//! Disabling an entity doesn't disable its descendants. To deactivate a whole hierarchy, such as
+//! a prefab, use `DisableRecursiveExt::disable_recursive` from `bevy_hierarchy`, which adds
+//! [`Disabled`] to the entity and all its descendants.
+
+use crate as bevy_ecs;
+use crate::{
+    component::{Component, ComponentId},
+    query::FilteredAccess,
+};
+
+#[cfg(feature = "bevy_reflect")]
+use {crate::reflect::ReflectComponent, bevy_reflect::std_traits::ReflectDefault};
+
+/// A marker component for disabled entities, which queries skip unless they explicitly mention
+/// it. See the [module docs](crate::entity_disabling) for more information.
+#[derive(Component, Clone, Copy, Debug, Default)]
+#[cfg_attr(
+    feature = "bevy_reflect",
+    derive(bevy_reflect::Reflect),
+    reflect(Component, Default)
+)]
+pub struct Disabled;
+
+/// Adds a `Without<Disabled>` filter to `component_access`, unless it already mentions the
+/// [`Disabled`] component `disabled`.
+pub(crate) fn filter_disabled(
+    component_access: &mut FilteredAccess<ComponentId>,
+    disabled: ComponentId,
+) {
+    if !component_access.contains(disabled) {
+        component_access.and_without(disabled);
+    }
+}
@@ -0,0 +1,230 @@
+//! Indexes for looking up entities by the value of one of their components.
+//!
+//! Finding the entities whose component has a given value normally means iterating over all of
+//! them. A [`ComponentIndex<T>`] maps each value of the component `T` to the entities that have
+//! it instead, and the [`Index<T>`] system parameter keeps it in sync with the world through
+//! change detection, for constant time lookups.
+//!
+//! ```
+//! # use bevy_ecs::prelude::*;
+//! # use bevy_ecs::index::{ComponentIndex, Index};
+//! #[derive(Component, Clone, PartialEq, Eq, Hash)]
+//! struct GridPosition {
+//!     x: i32,
+//!     y: i32,
+//! }
+//!
+//! fn find_occupant(mut index: Index<GridPosition>) {
+//!     if let Some(occupant) = index.get_single(&GridPosition { x: 1, y: 2 }) {
+//!         // ...
+//!     }
+//! }
+//!
+//! let mut world = World::new();
+//! world.init_resource::<ComponentIndex<GridPosition>>();
+//! # let mut schedule = Schedule::default();
+//! # schedule.add_systems(find_occupant);
+//! # schedule.run(&mut world);
+//! ```
+
+use std::hash::Hash;
+
+use crate as bevy_ecs;
+use crate::{
+    component::{Component, Tick},
+    entity::{Entity, EntityHashMap, EntityHashSet},
+    entity_disabling::Disabled,
+    query::{Changed, Has},
+    removal_detection::RemovedComponents,
+    system::{Local, Query, ResMut, Resource, SystemChangeTick, SystemParam},
+};
+use bevy_utils::HashMap;
+
+/// Maps the values of the [`Component`] `T` to the entities that have them.
+///
+/// This is kept in sync with the world by the [`Index<T>`] system parameter, and by
+/// [`index_update_system`], which must run regularly so that no removal of `T` is missed.
+#[derive(Resource)]
+pub struct ComponentIndex<T: Component + Eq + Hash + Clone> {
+    entities_by_value: HashMap<T, EntityHashSet>,
+    values_by_entity: EntityHashMap<T>,
+}
+
+impl<T: Component + Eq + Hash + Clone> Default for ComponentIndex<T> {
+    fn default() -> Self {
+        Self {
+            entities_by_value: HashMap::default(),
+            values_by_entity: EntityHashMap::default(),
+        }
+    }
+}
+
+impl<T: Component + Eq + Hash + Clone> ComponentIndex<T> {
+    /// Returns the entities whose component `T` equals `value`, in no particular order.
+    pub fn get(&self, value: &T) -> impl Iterator<Item = Entity> + '_ {
+        self.entities_by_value
+            .get(value)
+            .into_iter()
+            .flat_map(|entities| entities.iter().copied())
+    }
+
+    /// Returns the entity whose component `T` equals `value`, if there is exactly one.
+    pub fn get_single(&self, value: &T) -> Option<Entity> {
+        let entities = self.entities_by_value.get(value)?;
+        if entities.len() == 1 {
+            entities.iter().next().copied()
+        } else {
+            None
+        }
+    }
+
+    /// Returns `true` if any entity has a component `T` equal to `value`.
+    pub fn contains(&self, value: &T) -> bool {
+        self.entities_by_value.contains_key(value)
+    }
+
+    /// Returns the indexed value of the component `T` of `entity`.
+    pub fn value(&self, entity: Entity) -> Option<&T> {
+        self.values_by_entity.get(&entity)
+    }
+
+    /// Returns the number of indexed entities.
+    pub fn len(&self) -> usize {
+        self.values_by_entity.len()
+    }
+
+    /// Returns `true` if no entity is indexed.
+    pub fn is_empty(&self) -> bool {
+        self.values_by_entity.is_empty()
+    }
+
+    /// Indexes `entity` under `value`, replacing its previous value.
+    fn insert(&mut self, entity: Entity, value: T) {
+        if self.values_by_entity.get(&entity) == Some(&value) {
+            return;
+        }
+        self.remove(entity);
+        self.entities_by_value
+            .entry(value.clone())
+            .or_default()
+            .insert(entity);
+        self.values_by_entity.insert(entity, value);
+    }
+
+    /// Removes `entity` from the index.
+    fn remove(&mut self, entity: Entity) {
+        let Some(value) = self.values_by_entity.remove(&entity) else {
+            return;
+        };
+        if let Some(entities) = self.entities_by_value.get_mut(&value) {
+            entities.remove(&entity);
+            if entities.is_empty() {
+                self.entities_by_value.remove(&value);
+            }
+        }
+    }
+}
+
+/// A [`SystemParam`] to look up entities by the value of their component `T`, through the
+/// [`ComponentIndex<T>`] resource.
+///
+/// The index is brought up to date with the changes made to `T` before the system ran on its
+/// first lookup in each run of the system, so changes the system itself makes to `T` are only
+/// seen by its next run. Disabled entities are indexed too.
+///
+/// # Panics
+///
+/// Panics if the [`ComponentIndex<T>`] resource doesn't exist.
+#[derive(SystemParam)]
+pub struct Index<'w, 's, T: Component + Eq + Hash + Clone> {
+    index: ResMut<'w, ComponentIndex<T>>,
+    // Mentioning `Disabled` includes disabled entities in the query.
+    changed: Query<'w, 's, (Entity, &'static T, Has<Disabled>), Changed<T>>,
+    removed: RemovedComponents<'w, 's, T>,
+    refreshed: Local<'s, Option<Tick>>,
+    ticks: SystemChangeTick,
+}
+
+impl<'w, 's, T: Component + Eq + Hash + Clone> Index<'w, 's, T> {
+    /// Applies the changes made to `T` since the last run of the system to the index, unless
+    /// that was already done during this run.
+    fn refresh(&mut self) -> &ComponentIndex<T> {
+        if *self.refreshed != Some(self.ticks.this_run()) {
+            *self.refreshed = Some(self.ticks.this_run());
+            // Removals are applied first, as a component removed and then inserted again is
+            // both in the removed components and in the changed ones.
+            for entity in self.removed.read() {
+                self.index.remove(entity);
+            }
+            for (entity, value, _) in &self.changed {
+                self.index.insert(entity, value.clone());
+            }
+        }
+        &self.index
+    }
+
+    /// Returns the entities whose component `T` equals `value`, in no particular order.
+    pub fn get(&mut self, value: &T) -> impl Iterator<Item = Entity> + '_ {
+        self.refresh().get(value)
+    }
+
+    /// Returns the entity whose component `T` equals `value`, if there is exactly one.
+    pub fn get_single(&mut self, value: &T) -> Option<Entity> {
+        self.refresh().get_single(value)
+    }
+
+    /// Returns `true` if any entity has a component `T` equal to `value`.
+    pub fn contains(&mut self, value: &T) -> bool {
+        self.refresh().contains(value)
+    }
+
+    /// Returns the up to date [`ComponentIndex<T>`].
+    pub fn index(&mut self) -> &ComponentIndex<T> {
+        self.refresh()
+    }
+}
+
+/// Keeps the [`ComponentIndex<T>`] in sync with the world, even when no system looks it up.
+///
+/// This must run at least once per frame, so that the removals of `T` aren't dropped before
+/// being applied to the index.
+pub fn index_update_system<T: Component + Eq + Hash + Clone>(mut index: Index<T>) {
+    index.refresh();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{schedule::Schedule, world::World};
+
+    #[derive(Component, Clone, PartialEq, Eq, Hash, Debug)]
+    struct GridPosition(i32, i32);
+
+    #[test]
+    fn index_tracks_component_values() {
+        let mut world = World::new();
+        world.init_resource::<ComponentIndex<GridPosition>>();
+        let mut schedule = Schedule::default();
+        schedule.add_systems(index_update_system::<GridPosition>);
+
+        let a = world.spawn(GridPosition(0, 0)).id();
+        let b = world.spawn(GridPosition(1, 0)).id();
+        schedule.run(&mut world);
+        let index = world.resource::<ComponentIndex<GridPosition>>();
+        assert_eq!(index.get_single(&GridPosition(0, 0)), Some(a));
+        assert_eq!(index.get_single(&GridPosition(1, 0)), Some(b));
+
+        world.get_mut::<GridPosition>(a).unwrap().0 = 1;
+        world.clear_trackers();
+        schedule.run(&mut world);
+        let index = world.resource::<ComponentIndex<GridPosition>>();
+        assert!(!index.contains(&GridPosition(0, 0)));
+        assert_eq!(index.get(&GridPosition(1, 0)).count(), 2);
+
+        world.entity_mut(b).remove::<GridPosition>();
+        world.despawn(a);
+        world.clear_trackers();
+        schedule.run(&mut world);
+        assert!(world.resource::<ComponentIndex<GridPosition>>().is_empty());
+    }
+}
@@ -0,0 +1,38 @@
+use crate::{
+    change_detection::DetectChangesMut,
+    component::{Component, ComponentHooks, SparseStorage},
+    entity::Entity,
+    observer::Observer,
+};
+
+/// Tracks the [`Observer`]s watching an entity, so that they're despawned along with it.
+#[derive(Default)]
+pub(crate) struct ObservedBy(pub(crate) Vec<Entity>);
+
+impl Component for ObservedBy {
+    type Storage = SparseStorage;
+
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        hooks.on_remove(|mut world, entity, _| {
+            let observed_by = {
+                let Some(mut component) = world.get_mut::<ObservedBy>(entity) else {
+                    return;
+                };
+                std::mem::take(&mut component.0)
+            };
+            for observer_entity in observed_by {
+                let Some(mut observer) = world.get_mut::<Observer>(observer_entity) else {
+                    continue;
+                };
+                let observer = observer.bypass_change_detection();
+                observer.despawned_watched_entities += 1;
+                // The observer only goes away with the last of the entities it watches.
+                if observer.despawned_watched_entities as usize
+                    >= observer.descriptor.entities.len()
+                {
+                    world.commands().entity(observer_entity).despawn();
+                }
+            }
+        });
+    }
+}
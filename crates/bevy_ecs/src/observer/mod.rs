@@ -0,0 +1,627 @@
+//! Types for creating and storing [`Observer`]s
+
+mod entity_observer;
+mod runner;
+mod trigger_event;
+
+pub(crate) use entity_observer::ObservedBy;
+pub use runner::*;
+pub use trigger_event::*;
+
+use crate::{
+    archetype::{ArchetypeFlags, Archetypes},
+    bundle::Bundle,
+    change_detection::DetectChangesMut,
+    component::ComponentId,
+    entity::{Entity, EntityHashMap},
+    event::Event,
+    system::IntoObserverSystem,
+    world::{DeferredWorld, EntityWorldMut, OnAdd, OnInsert, OnRemove, World},
+};
+use bevy_ptr::PtrMut;
+use bevy_utils::{HashMap, TypeIdMap};
+use std::{any::TypeId, fmt::Debug, marker::PhantomData};
+
+/// Type containing triggered [`Event`] information for a given run of an [`Observer`]. This contains the
+/// [`Event`] data itself. If it was triggered for a specific [`Entity`], it includes that as well.
+pub struct Trigger<'w, E, B: Bundle = ()> {
+    event: &'w mut E,
+    trigger: ObserverTrigger,
+    _marker: PhantomData<B>,
+}
+
+impl<'w, E, B: Bundle> Trigger<'w, E, B> {
+    /// Creates a new trigger for the given event and observer information.
+    pub fn new(event: &'w mut E, trigger: ObserverTrigger) -> Self {
+        Self {
+            event,
+            trigger,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the event type of this trigger.
+    pub fn event_type(&self) -> TypeId {
+        self.trigger.event_type
+    }
+
+    /// Returns a reference to the triggered event.
+    pub fn event(&self) -> &E {
+        self.event
+    }
+
+    /// Returns a mutable reference to the triggered event.
+    pub fn event_mut(&mut self) -> &mut E {
+        self.event
+    }
+
+    /// Returns a pointer to the triggered event.
+    pub fn event_ptr(&self) -> *const E {
+        self.event
+    }
+
+    /// Returns the entity that triggered the observer, or [`Entity::PLACEHOLDER`] if the
+    /// trigger didn't target an entity.
+    pub fn entity(&self) -> Entity {
+        self.trigger.entity
+    }
+
+    /// Returns the entity of the [`Observer`] that is running.
+    pub fn observer(&self) -> Entity {
+        self.trigger.observer
+    }
+}
+
+impl<'w, E: Debug, B: Bundle> Debug for Trigger<'w, E, B> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Trigger")
+            .field("event", &self.event)
+            .field("trigger", &self.trigger)
+            .finish()
+    }
+}
+
+/// A description of what an [`Observer`] observes.
+#[derive(Default, Clone, Debug)]
+pub struct ObserverDescriptor {
+    /// The event the observer is watching.
+    event: Option<TypeId>,
+
+    /// The components the observer is watching.
+    components: Vec<ComponentId>,
+
+    /// The entities the observer is watching.
+    entities: Vec<Entity>,
+}
+
+impl ObserverDescriptor {
+    /// Returns the type of the event the observer is watching, once it's set.
+    pub fn event(&self) -> Option<TypeId> {
+        self.event
+    }
+
+    /// Returns the components the observer is watching.
+    pub fn components(&self) -> &[ComponentId] {
+        &self.components
+    }
+
+    /// Returns the entities the observer is watching.
+    pub fn entities(&self) -> &[Entity] {
+        &self.entities
+    }
+}
+
+/// Event trigger metadata for a given [`Observer`].
+#[derive(Debug, Clone, Copy)]
+pub struct ObserverTrigger {
+    /// The [`Entity`] of the observer handling the trigger.
+    pub observer: Entity,
+
+    /// The [`Event`] the trigger targeted.
+    pub event_type: TypeId,
+
+    /// The entity the trigger targeted.
+    pub entity: Entity,
+}
+
+/// The observers of a single component, for a given event.
+#[derive(Default, Debug)]
+struct CachedComponentObservers {
+    /// Observers listening to events targeting this component on any entity.
+    map: Vec<Entity>,
+    /// Observers listening to events targeting this component on specific entities.
+    entity_map: EntityHashMap<Vec<Entity>>,
+}
+
+/// The observers of a given event.
+#[derive(Default, Debug)]
+struct CachedObservers {
+    /// Observers listening for any time this event is fired.
+    map: Vec<Entity>,
+    /// Observers listening for this event fired at a specific component.
+    component_observers: HashMap<ComponentId, CachedComponentObservers>,
+    /// Observers listening for this event fired at a specific entity.
+    entity_observers: EntityHashMap<Vec<Entity>>,
+}
+
+impl CachedObservers {
+    /// Returns true if there are no observers of the event.
+    fn is_empty(&self) -> bool {
+        self.map.is_empty()
+            && self.component_observers.is_empty()
+            && self.entity_observers.is_empty()
+    }
+}
+
+/// Metadata for observers. Stores a cache mapping event ids to the registered observers.
+#[derive(Default, Debug)]
+pub struct Observers {
+    // Cached observers for the lifecycle events, which are looked up on each structural change.
+    on_add: CachedObservers,
+    on_insert: CachedObservers,
+    on_remove: CachedObservers,
+    // Map from the type of the other events to the cached observers.
+    cache: TypeIdMap<CachedObservers>,
+}
+
+impl Observers {
+    fn get_observers(&mut self, event_type: TypeId) -> &mut CachedObservers {
+        if event_type == TypeId::of::<OnAdd>() {
+            &mut self.on_add
+        } else if event_type == TypeId::of::<OnInsert>() {
+            &mut self.on_insert
+        } else if event_type == TypeId::of::<OnRemove>() {
+            &mut self.on_remove
+        } else {
+            self.cache.entry(event_type).or_default()
+        }
+    }
+
+    fn try_get_observers(&self, event_type: TypeId) -> Option<&CachedObservers> {
+        if event_type == TypeId::of::<OnAdd>() {
+            Some(&self.on_add)
+        } else if event_type == TypeId::of::<OnInsert>() {
+            Some(&self.on_insert)
+        } else if event_type == TypeId::of::<OnRemove>() {
+            Some(&self.on_remove)
+        } else {
+            self.cache.get(&event_type)
+        }
+    }
+
+    /// Returns the archetype flag telling that an archetype has observers of `event_type`, if
+    /// it's a lifecycle event.
+    fn lifecycle_flag(event_type: TypeId) -> Option<ArchetypeFlags> {
+        if event_type == TypeId::of::<OnAdd>() {
+            Some(ArchetypeFlags::ON_ADD_OBSERVER)
+        } else if event_type == TypeId::of::<OnInsert>() {
+            Some(ArchetypeFlags::ON_INSERT_OBSERVER)
+        } else if event_type == TypeId::of::<OnRemove>() {
+            Some(ArchetypeFlags::ON_REMOVE_OBSERVER)
+        } else {
+            None
+        }
+    }
+
+    /// Runs the observers of `event_type` targeting `entity` or any of the `components`, along
+    /// with the global observers of the event.
+    ///
+    /// # Safety
+    /// `data` must point to a value of the type the observers of `event_type` expect, and the
+    /// `components` must exist in the world.
+    pub(crate) unsafe fn invoke(
+        mut world: DeferredWorld,
+        event_type: TypeId,
+        entity: Entity,
+        components: impl Iterator<Item = ComponentId>,
+        mut data: PtrMut,
+    ) {
+        // Collect the observers first, so that they can run with mutable access to the world.
+        let observers = {
+            let world = world.as_unsafe_world_cell();
+            let Some(observers) = world.observers().try_get_observers(event_type) else {
+                return;
+            };
+            let mut targets = observers.map.clone();
+            if entity != Entity::PLACEHOLDER {
+                if let Some(map) = observers.entity_observers.get(&entity) {
+                    targets.extend(map);
+                }
+            }
+            for component in components {
+                let Some(component_observers) = observers.component_observers.get(&component)
+                else {
+                    continue;
+                };
+                targets.extend(&component_observers.map);
+                if entity != Entity::PLACEHOLDER {
+                    if let Some(map) = component_observers.entity_map.get(&entity) {
+                        targets.extend(map);
+                    }
+                }
+            }
+            // An observer watching several of the components only runs once.
+            let mut seen = Vec::with_capacity(targets.len());
+            targets.retain(|observer| {
+                if seen.contains(observer) {
+                    false
+                } else {
+                    seen.push(*observer);
+                    true
+                }
+            });
+            targets
+        };
+
+        for observer in observers {
+            let trigger = ObserverTrigger {
+                observer,
+                event_type,
+                entity,
+            };
+            // SAFETY: Caller ensures that `data` matches the observers of `event_type`
+            unsafe { run_observer(world.reborrow(), trigger, data.reborrow()) };
+        }
+    }
+
+    /// Updates the `flags` of an archetype containing `component` with the lifecycle observers
+    /// it has.
+    pub(crate) fn update_archetype_flags(
+        &self,
+        component: ComponentId,
+        flags: &mut ArchetypeFlags,
+    ) {
+        let observes = |observers: &CachedObservers| {
+            !observers.map.is_empty()
+                || !observers.entity_observers.is_empty()
+                || observers.component_observers.contains_key(&component)
+        };
+        if observes(&self.on_add) {
+            flags.insert(ArchetypeFlags::ON_ADD_OBSERVER);
+        }
+        if observes(&self.on_insert) {
+            flags.insert(ArchetypeFlags::ON_INSERT_OBSERVER);
+        }
+        if observes(&self.on_remove) {
+            flags.insert(ArchetypeFlags::ON_REMOVE_OBSERVER);
+        }
+    }
+
+    /// Registers the `observer` with the given `descriptor`, and flags the archetypes it now
+    /// needs to be looked up for.
+    pub(crate) fn register(
+        &mut self,
+        archetypes: &mut Archetypes,
+        observer: Entity,
+        descriptor: &ObserverDescriptor,
+    ) {
+        let Some(event_type) = descriptor.event else {
+            return;
+        };
+        let observers = self.get_observers(event_type);
+        if descriptor.components.is_empty() {
+            if descriptor.entities.is_empty() {
+                observers.map.push(observer);
+            } else {
+                for &watched_entity in &descriptor.entities {
+                    observers
+                        .entity_observers
+                        .entry(watched_entity)
+                        .or_default()
+                        .push(observer);
+                }
+            }
+        } else {
+            for &component in &descriptor.components {
+                let observers = observers.component_observers.entry(component).or_default();
+                if descriptor.entities.is_empty() {
+                    observers.map.push(observer);
+                } else {
+                    for &watched_entity in &descriptor.entities {
+                        observers
+                            .entity_map
+                            .entry(watched_entity)
+                            .or_default()
+                            .push(observer);
+                    }
+                }
+            }
+        }
+
+        if let Some(flag) = Self::lifecycle_flag(event_type) {
+            for archetype in archetypes.archetypes.iter_mut() {
+                if descriptor.components.is_empty()
+                    || descriptor
+                        .components
+                        .iter()
+                        .any(|&component| archetype.contains(component))
+                {
+                    archetype.insert_flags(flag);
+                }
+            }
+        }
+    }
+
+    /// Unregisters the `observer` with the given `descriptor`.
+    ///
+    /// The flags of the archetypes are left as they are: looking up an event without observers
+    /// is only a little slower.
+    pub(crate) fn unregister(&mut self, observer: Entity, descriptor: &ObserverDescriptor) {
+        let Some(event_type) = descriptor.event else {
+            return;
+        };
+        let observers = self.get_observers(event_type);
+        let remove = |map: &mut Vec<Entity>| map.retain(|&entity| entity != observer);
+        if descriptor.components.is_empty() {
+            if descriptor.entities.is_empty() {
+                remove(&mut observers.map);
+            } else {
+                for watched_entity in &descriptor.entities {
+                    if let Some(map) = observers.entity_observers.get_mut(watched_entity) {
+                        remove(map);
+                        if map.is_empty() {
+                            observers.entity_observers.remove(watched_entity);
+                        }
+                    }
+                }
+            }
+        } else {
+            for component in &descriptor.components {
+                let Some(component_observers) = observers.component_observers.get_mut(component)
+                else {
+                    continue;
+                };
+                if descriptor.entities.is_empty() {
+                    remove(&mut component_observers.map);
+                } else {
+                    for watched_entity in &descriptor.entities {
+                        if let Some(map) = component_observers.entity_map.get_mut(watched_entity) {
+                            remove(map);
+                            if map.is_empty() {
+                                component_observers.entity_map.remove(watched_entity);
+                            }
+                        }
+                    }
+                }
+                if component_observers.map.is_empty() && component_observers.entity_map.is_empty() {
+                    observers.component_observers.remove(component);
+                }
+            }
+        }
+
+        if Self::lifecycle_flag(event_type).is_none() && observers.is_empty() {
+            self.cache.remove(&event_type);
+        }
+    }
+}
+
+impl World {
+    /// Spawns a "global" [`Observer`] and returns its [`Entity`].
+    ///
+    /// The observer runs each time its event is triggered, whichever entity it targets.
+    ///
+    /// ```
+    /// # use bevy_ecs::prelude::*;
+    /// #[derive(Component)]
+    /// struct Health(u32);
+    ///
+    /// #[derive(Resource, Default)]
+    /// struct Spawned(u32);
+    ///
+    /// let mut world = World::new();
+    /// world.init_resource::<Spawned>();
+    /// world.observe(|_trigger: Trigger<OnAdd, Health>, mut spawned: ResMut<Spawned>| {
+    ///     spawned.0 += 1;
+    /// });
+    ///
+    /// world.spawn(Health(10));
+    /// assert_eq!(world.resource::<Spawned>().0, 1);
+    /// ```
+    pub fn observe<E: Event, B: Bundle, M>(
+        &mut self,
+        system: impl IntoObserverSystem<E, B, M>,
+    ) -> EntityWorldMut<'_> {
+        self.spawn(Observer::new(system))
+    }
+
+    /// Triggers the given `event`, which runs its global [`Observer`]s.
+    pub fn trigger<E: Event>(&mut self, event: E) {
+        TriggerEvent { event, targets: () }.trigger(self);
+    }
+
+    /// Triggers the given `event` for each of the `targets`, which runs the [`Observer`]s of
+    /// the event watching these targets, along with its global observers.
+    pub fn trigger_targets<E: Event>(&mut self, event: E, targets: impl TriggerTargets) {
+        TriggerEvent { event, targets }.trigger(self);
+    }
+
+    /// Registers the [`Observer`] of `observer_entity`, after it was spawned.
+    pub(crate) fn register_observer(&mut self, observer_entity: Entity) {
+        let Some(mut observer) = self.get_mut::<Observer>(observer_entity) else {
+            return;
+        };
+        let Some(mut system) = observer.bypass_change_detection().system.take() else {
+            return;
+        };
+        let components = system.initialize(self);
+
+        let Some(mut observer) = self.get_mut::<Observer>(observer_entity) else {
+            return;
+        };
+        let observer = observer.bypass_change_detection();
+        observer.system = Some(system);
+        for component in components {
+            if !observer.descriptor.components.contains(&component) {
+                observer.descriptor.components.push(component);
+            }
+        }
+        let descriptor = observer.descriptor.clone();
+
+        self.observers
+            .register(&mut self.archetypes, observer_entity, &descriptor);
+
+        for &watched_entity in &descriptor.entities {
+            let Some(mut entity) = self.get_entity_mut(watched_entity) else {
+                continue;
+            };
+            if let Some(mut observed_by) = entity.get_mut::<ObservedBy>() {
+                observed_by.0.push(observer_entity);
+            } else {
+                entity.insert(ObservedBy(vec![observer_entity]));
+            }
+        }
+    }
+
+    /// Unregisters the [`Observer`] of `observer_entity`, after it was despawned.
+    pub(crate) fn unregister_observer(
+        &mut self,
+        observer_entity: Entity,
+        descriptor: ObserverDescriptor,
+    ) {
+        self.observers.unregister(observer_entity, &descriptor);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate as bevy_ecs;
+    use crate::prelude::*;
+
+    #[derive(Component)]
+    struct A;
+
+    #[derive(Component)]
+    struct B;
+
+    #[derive(Resource, Default)]
+    struct Order(Vec<&'static str>);
+
+    impl Order {
+        fn observed(&mut self, name: &'static str) {
+            self.0.push(name);
+        }
+    }
+
+    #[derive(Event)]
+    struct EventA;
+
+    #[derive(Event)]
+    struct Counted(u32);
+
+    #[test]
+    fn observer_order_spawn_despawn() {
+        let mut world = World::new();
+        world.init_resource::<Order>();
+
+        world.observe(|_: Trigger<OnAdd, A>, mut res: ResMut<Order>| res.observed("add"));
+        world.observe(|_: Trigger<OnInsert, A>, mut res: ResMut<Order>| res.observed("insert"));
+        world.observe(|_: Trigger<OnRemove, A>, mut res: ResMut<Order>| res.observed("remove"));
+
+        let entity = world.spawn(A).id();
+        world.despawn(entity);
+        assert_eq!(vec!["add", "insert", "remove"], world.resource::<Order>().0);
+    }
+
+    #[test]
+    fn observer_order_insert_remove() {
+        let mut world = World::new();
+        world.init_resource::<Order>();
+
+        world.observe(|_: Trigger<OnAdd, A>, mut res: ResMut<Order>| res.observed("add"));
+        world.observe(|_: Trigger<OnInsert, A>, mut res: ResMut<Order>| res.observed("insert"));
+        world.observe(|_: Trigger<OnRemove, A>, mut res: ResMut<Order>| res.observed("remove"));
+
+        let mut entity = world.spawn_empty();
+        entity.insert(A);
+        entity.insert(A);
+        entity.remove::<A>();
+        assert_eq!(
+            vec!["add", "insert", "insert", "remove"],
+            world.resource::<Order>().0
+        );
+    }
+
+    #[test]
+    fn observer_multiple_components() {
+        let mut world = World::new();
+        world.init_resource::<Order>();
+
+        world.observe(|_: Trigger<OnAdd, (A, B)>, mut res: ResMut<Order>| res.observed("add"));
+
+        world.spawn((A, B));
+        world.spawn(B);
+        assert_eq!(vec!["add", "add"], world.resource::<Order>().0);
+    }
+
+    #[test]
+    fn observer_commands_are_applied() {
+        let mut world = World::new();
+
+        world.observe(|trigger: Trigger<OnAdd, A>, mut commands: Commands| {
+            commands.entity(trigger.entity()).insert(B);
+        });
+
+        let entity = world.spawn(A).id();
+        assert!(world.entity(entity).contains::<B>());
+    }
+
+    #[test]
+    fn observer_entity_targets() {
+        let mut world = World::new();
+        world.init_resource::<Order>();
+
+        let target = world.spawn_empty().id();
+        let other = world.spawn_empty().id();
+        world
+            .entity_mut(target)
+            .observe(|_: Trigger<EventA>, mut res: ResMut<Order>| res.observed("target"));
+        world.observe(|_: Trigger<EventA>, mut res: ResMut<Order>| res.observed("global"));
+
+        world.trigger_targets(EventA, other);
+        world.trigger_targets(EventA, target);
+        assert_eq!(
+            vec!["global", "global", "target"],
+            world.resource::<Order>().0
+        );
+    }
+
+    #[test]
+    fn observer_mutates_event() {
+        let mut world = World::new();
+        world.observe(|mut trigger: Trigger<Counted>| trigger.event_mut().0 += 1);
+        world.observe(|mut trigger: Trigger<Counted>| trigger.event_mut().0 *= 10);
+
+        let mut event = Counted(1);
+        let mut world = crate::world::DeferredWorld::from(&mut world);
+        // SAFETY: `event` is the event the observers expect
+        unsafe {
+            world.trigger_observers_with_data(
+                std::any::TypeId::of::<Counted>(),
+                Entity::PLACEHOLDER,
+                std::iter::empty(),
+                &mut event,
+            );
+        }
+        assert_eq!(event.0, 20);
+    }
+
+    #[test]
+    fn observer_despawned_with_watched_entity() {
+        let mut world = World::new();
+        world.init_resource::<Order>();
+
+        let target = world.spawn_empty().id();
+        world
+            .entity_mut(target)
+            .observe(|_: Trigger<EventA>, mut res: ResMut<Order>| res.observed("target"));
+        let observer = world
+            .query_filtered::<Entity, With<Observer>>()
+            .single(&world);
+
+        world.despawn(target);
+        assert!(world.get_entity(observer).is_none());
+
+        world.trigger_targets(EventA, target);
+        assert!(world.resource::<Order>().0.is_empty());
+    }
+}
@@ -0,0 +1,204 @@
+use std::any::TypeId;
+
+use bevy_ptr::PtrMut;
+
+use crate::{
+    bundle::Bundle,
+    change_detection::DetectChangesMut,
+    component::{Component, ComponentHooks, ComponentId, SparseStorage},
+    entity::Entity,
+    event::Event,
+    observer::{ObserverDescriptor, ObserverTrigger, Trigger},
+    system::{BoxedObserverSystem, IntoObserverSystem},
+    world::{DeferredWorld, World},
+};
+
+/// An [`Observer`] system. Add this [`Component`] to an [`Entity`] to turn it into an "observer".
+///
+/// Observers listen for a "trigger" of a specific [`Event`]. Events are triggered by calling
+/// [`World::trigger`] or [`World::trigger_targets`], and the lifecycle events [`OnAdd`],
+/// [`OnInsert`] and [`OnRemove`] are triggered when components are added to or removed from
+/// entities.
+///
+/// Unlike [`EventReader`](crate::event::EventReader)s, observers run immediately when their
+/// event is triggered, and the mutations they make to the world through
+/// [`Commands`](crate::system::Commands) are applied as soon as they return.
+///
+/// An observer system is a system whose first parameter is a [`Trigger`], which tells which
+/// event it observes. The [`Bundle`] of the trigger restricts the lifecycle events observed to
+/// the components of the bundle:
+///
+/// ```
+/// # use bevy_ecs::prelude::*;
+/// #[derive(Component)]
+/// struct Enemy;
+///
+/// #[derive(Resource, Default)]
+/// struct EnemyCount(u32);
+///
+/// let mut world = World::new();
+/// world.init_resource::<EnemyCount>();
+///
+/// world.observe(|_: Trigger<OnAdd, Enemy>, mut count: ResMut<EnemyCount>| {
+///     count.0 += 1;
+/// });
+/// world.observe(|_: Trigger<OnRemove, Enemy>, mut count: ResMut<EnemyCount>| {
+///     count.0 -= 1;
+/// });
+///
+/// let enemy = world.spawn(Enemy).id();
+/// assert_eq!(world.resource::<EnemyCount>().0, 1);
+/// world.despawn(enemy);
+/// assert_eq!(world.resource::<EnemyCount>().0, 0);
+/// ```
+///
+/// Observers can also watch specific entities, with [`Observer::with_entity`] or
+/// [`EntityWorldMut::observe`](crate::world::EntityWorldMut::observe). Such an observer is
+/// despawned once all the entities it watches are despawned.
+///
+/// [`OnAdd`]: crate::world::OnAdd
+/// [`OnInsert`]: crate::world::OnInsert
+/// [`OnRemove`]: crate::world::OnRemove
+pub struct Observer {
+    pub(crate) system: Option<Box<dyn AnyObserverSystem>>,
+    pub(crate) descriptor: ObserverDescriptor,
+    /// The number of watched entities that were despawned, the observer being despawned
+    /// along with the last of them.
+    pub(crate) despawned_watched_entities: u32,
+}
+
+impl Observer {
+    /// Creates a new [`Observer`], which defaults to a "global" observer. This means it will run
+    /// whenever the event `E` is triggered.
+    pub fn new<E: Event, B: Bundle, M>(system: impl IntoObserverSystem<E, B, M>) -> Self {
+        Self {
+            system: Some(Box::new(
+                Box::new(IntoObserverSystem::into_system(system)) as BoxedObserverSystem<E, B>
+            )),
+            descriptor: ObserverDescriptor {
+                event: Some(TypeId::of::<E>()),
+                ..Default::default()
+            },
+            despawned_watched_entities: 0,
+        }
+    }
+
+    /// Observe the given `entity`. This will cause the [`Observer`] to run whenever the
+    /// [`Event`] is triggered for the `entity`.
+    pub fn with_entity(mut self, entity: Entity) -> Self {
+        self.descriptor.entities.push(entity);
+        self
+    }
+
+    /// Observe the given `entity`. This will cause the [`Observer`] to run whenever the
+    /// [`Event`] is triggered for the `entity`.
+    ///
+    /// Note that if this is called after the [`Observer`] is spawned, it will have no effect.
+    pub fn watch_entity(&mut self, entity: Entity) {
+        self.descriptor.entities.push(entity);
+    }
+
+    /// Observe the given `component`. This will cause the [`Observer`] to run whenever the
+    /// [`Event`] is triggered for the `component`, in addition to the components of its
+    /// [`Trigger`]'s [`Bundle`].
+    pub fn with_component(mut self, component: ComponentId) -> Self {
+        self.descriptor.components.push(component);
+        self
+    }
+
+    /// Returns the [`ObserverDescriptor`] of this [`Observer`].
+    pub fn descriptor(&self) -> &ObserverDescriptor {
+        &self.descriptor
+    }
+}
+
+impl Component for Observer {
+    type Storage = SparseStorage;
+
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        hooks.on_add(|mut world, entity, _| {
+            world.commands().add(move |world: &mut World| {
+                world.register_observer(entity);
+            });
+        });
+        hooks.on_remove(|mut world, entity, _| {
+            let Some(mut observer) = world.get_mut::<Observer>(entity) else {
+                return;
+            };
+            let descriptor = std::mem::take(&mut observer.bypass_change_detection().descriptor);
+            world.commands().add(move |world: &mut World| {
+                world.unregister_observer(entity, descriptor);
+            });
+        });
+    }
+}
+
+/// Type-erased [`ObserverSystem`](crate::system::ObserverSystem), so that observers of any
+/// event can be stored in the [`Observer`] component.
+pub(crate) trait AnyObserverSystem: Send + Sync + 'static {
+    /// Initializes the system, and returns the components of its [`Trigger`]'s [`Bundle`].
+    fn initialize(&mut self, world: &mut World) -> Vec<ComponentId>;
+
+    /// Runs the system for the given `trigger`, and queues its deferred mutations.
+    ///
+    /// # Safety
+    /// `event` must point to a value of the event type of the system.
+    unsafe fn run(&mut self, world: DeferredWorld, trigger: ObserverTrigger, event: PtrMut);
+}
+
+impl<E: Event, B: Bundle> AnyObserverSystem for BoxedObserverSystem<E, B> {
+    fn initialize(&mut self, world: &mut World) -> Vec<ComponentId> {
+        self.as_mut().initialize(world);
+        let mut components = Vec::new();
+        B::component_ids(&mut world.components, &mut world.storages, &mut |id| {
+            components.push(id);
+        });
+        components
+    }
+
+    unsafe fn run(&mut self, mut world: DeferredWorld, trigger: ObserverTrigger, event: PtrMut) {
+        // SAFETY: Caller ensures that `event` points to an `E`
+        let event = unsafe { event.deref_mut::<E>() };
+        let trigger: Trigger<E, B> = Trigger::new(event, trigger);
+        // SAFETY: The trigger is passed by value to the system, which is run to completion
+        // before this function returns, so it doesn't outlive the event it borrows.
+        let trigger: Trigger<'static, E, B> = unsafe { std::mem::transmute(trigger) };
+
+        let world_cell = world.as_unsafe_world_cell();
+        self.update_archetype_component_access(world_cell);
+        // SAFETY:
+        // - `update_archetype_component_access` was just called
+        // - the `DeferredWorld` has mutable access to the world, and no other borrows of world
+        //   data are live while observers run
+        unsafe {
+            self.run_unsafe(trigger, world_cell);
+        }
+        self.queue_deferred(world);
+    }
+}
+
+/// Runs the [`Observer`] of `trigger.observer` for the event behind `event`.
+///
+/// The observer's system is taken out of its component while it runs, so that it can access
+/// the observer's entity.
+///
+/// # Safety
+/// `event` must point to a value of the event type of the observer.
+pub(super) unsafe fn run_observer(
+    mut world: DeferredWorld,
+    trigger: ObserverTrigger,
+    event: PtrMut,
+) {
+    let Some(mut observer) = world.get_mut::<Observer>(trigger.observer) else {
+        return;
+    };
+    let Some(mut system) = observer.bypass_change_detection().system.take() else {
+        // The observer is already running, because it triggered its own event.
+        return;
+    };
+    // SAFETY: Caller ensures that `event` matches the observer
+    unsafe { system.run(world.reborrow(), trigger, event) };
+    if let Some(mut observer) = world.get_mut::<Observer>(trigger.observer) {
+        observer.bypass_change_detection().system = Some(system);
+    }
+}
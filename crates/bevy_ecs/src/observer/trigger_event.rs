@@ -0,0 +1,87 @@
+use std::any::TypeId;
+
+use crate::{
+    entity::Entity,
+    event::Event,
+    system::Command,
+    world::{DeferredWorld, World},
+};
+
+/// A [`Command`] that triggers the [`Observer`](crate::observer::Observer)s of an [`Event`]
+/// for each of its `targets`, or its global observers when there are no targets.
+pub struct TriggerEvent<E, Targets: TriggerTargets = ()> {
+    /// The event to trigger.
+    pub event: E,
+
+    /// The targets to trigger the event for.
+    pub targets: Targets,
+}
+
+impl<E: Event, Targets: TriggerTargets> TriggerEvent<E, Targets> {
+    pub(crate) fn trigger(mut self, world: &mut World) {
+        let event_type = TypeId::of::<E>();
+        let mut deferred = DeferredWorld::from(&mut *world);
+        let targets = self.targets.entities();
+        if targets.is_empty() {
+            // SAFETY: `self.event` is the event of type `event_type`
+            unsafe {
+                deferred.trigger_observers_with_data(
+                    event_type,
+                    Entity::PLACEHOLDER,
+                    std::iter::empty(),
+                    &mut self.event,
+                );
+            }
+        } else {
+            for &target in targets {
+                // SAFETY: `self.event` is the event of type `event_type`
+                unsafe {
+                    deferred.trigger_observers_with_data(
+                        event_type,
+                        target,
+                        std::iter::empty(),
+                        &mut self.event,
+                    );
+                }
+            }
+        }
+        world.flush();
+    }
+}
+
+impl<E: Event, Targets: TriggerTargets> Command for TriggerEvent<E, Targets> {
+    fn apply(self, world: &mut World) {
+        self.trigger(world);
+    }
+}
+
+/// Represents a collection of targets, which are [`Entity`]s, for a specific
+/// [`Trigger`](crate::observer::Trigger) of an [`Event`].
+pub trait TriggerTargets: Send + Sync + 'static {
+    /// The entities the trigger should target.
+    fn entities(&self) -> &[Entity];
+}
+
+impl TriggerTargets for () {
+    fn entities(&self) -> &[Entity] {
+        &[]
+    }
+}
+
+impl TriggerTargets for Entity {
+    fn entities(&self) -> &[Entity] {
+        std::slice::from_ref(self)
+    }
+}
+
+impl TriggerTargets for Vec<Entity> {
+    fn entities(&self) -> &[Entity] {
+        self.as_slice()
+    }
+}
+
+impl<const N: usize> TriggerTargets for [Entity; N] {
+    fn entities(&self) -> &[Entity] {
+        self.as_slice()
+    }
+}
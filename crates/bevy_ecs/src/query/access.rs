@@ -394,6 +394,20 @@ impl<T: SparseSetIndex> FilteredAccess<T> {
         self.required.insert(index);
     }
 
+    /// Returns `true` if this explicitly mentions the element given by `index`, through a read,
+    /// a write, an archetypal access, or a `With` or `Without` filter.
+    ///
+    /// Unlike [`Access::has_read`], this ignores access to all elements.
+    pub fn contains(&self, index: T) -> bool {
+        let index = index.sparse_set_index();
+        self.access.reads_and_writes.contains(index)
+            || self.access.archetypal.contains(index)
+            || self
+                .filter_sets
+                .iter()
+                .any(|filter| filter.with.contains(index) || filter.without.contains(index))
+    }
+
     /// Adds a `With` filter: corresponds to a conjunction (AND) operation.
     ///
     /// Suppose we begin with `Or<(With<A>, With<B>)>`, which is represented by an array of two `AccessFilter` instances.
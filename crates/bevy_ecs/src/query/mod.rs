@@ -804,4 +804,33 @@ mod tests {
         let values = world.query::<&B>().iter(&world).collect::<Vec<&B>>();
         assert_eq!(values, vec![&B(2)]);
     }
+
+    #[test]
+    fn query_skips_disabled_entities() {
+        use crate::entity_disabling::Disabled;
+
+        let mut world = World::new();
+        let enabled = world.spawn(A(1)).id();
+        let disabled = world.spawn((A(2), Disabled)).id();
+
+        let mut query = world.query::<Entity>();
+        assert_eq!(query.iter(&world).collect::<Vec<_>>(), vec![enabled]);
+        assert!(world.query::<&A>().get(&world, disabled).is_err());
+
+        let mut with_disabled = world.query_filtered::<Entity, With<Disabled>>();
+        assert_eq!(
+            with_disabled.iter(&world).collect::<Vec<_>>(),
+            vec![disabled]
+        );
+        let mut has_disabled = world.query::<(Entity, Has<Disabled>)>();
+        assert_eq!(
+            has_disabled.iter(&world).collect::<HashSet<_>>(),
+            HashSet::from([(enabled, false), (disabled, true)])
+        );
+        let mut optional_disabled = world.query::<(&A, Option<&Disabled>)>();
+        assert_eq!(optional_disabled.iter(&world).count(), 2);
+
+        world.entity_mut(disabled).remove::<Disabled>();
+        assert_eq!(query.iter(&world).count(), 2);
+    }
 }
@@ -2,6 +2,7 @@ use crate::{
     archetype::{Archetype, ArchetypeComponentId, ArchetypeGeneration, ArchetypeId},
     component::{ComponentId, Tick},
     entity::Entity,
+    entity_disabling::filter_disabled,
     prelude::FromWorld,
     query::{
         Access, BatchingStrategy, DebugCheckedUnwrap, FilteredAccess, QueryCombinationIter,
@@ -135,6 +136,9 @@ impl<D: QueryData, F: QueryFilter> QueryState<D, F> {
         // properly considered in a global "cross-query" context (both within systems and across systems).
         component_access.extend(&filter_component_access);
 
+        // Skip disabled entities, unless the query explicitly mentions them.
+        filter_disabled(&mut component_access, world.disabled_component_id());
+
         let mut state = Self {
             world_id: world.id(),
             archetype_generation: ArchetypeGeneration::initial(),
@@ -162,6 +166,11 @@ impl<D: QueryData, F: QueryFilter> QueryState<D, F> {
         let mut fetch_state = D::init_state(builder.world_mut());
         let filter_state = F::init_state(builder.world_mut());
         D::set_access(&mut fetch_state, builder.access());
+        let mut component_access = builder.access().clone();
+        filter_disabled(
+            &mut component_access,
+            builder.world().disabled_component_id(),
+        );
 
         let mut state = Self {
             world_id: builder.world().id(),
@@ -170,7 +179,7 @@ impl<D: QueryData, F: QueryFilter> QueryState<D, F> {
             matched_archetype_ids: Vec::new(),
             fetch_state,
             filter_state,
-            component_access: builder.access().clone(),
+            component_access,
             matched_tables: Default::default(),
             matched_archetypes: Default::default(),
             archetype_component_access: Default::default(),
@@ -6,6 +6,7 @@ pub use self::multi_threaded::{MainThreadExecutor, MultiThreadedExecutor};
 pub use self::simple::SimpleExecutor;
 pub use self::single_threaded::SingleThreadedExecutor;
 
+use bevy_utils::{Duration, Instant};
 use fixedbitset::FixedBitSet;
 
 use crate::{
@@ -73,6 +74,9 @@ pub struct SystemSchedule {
     pub(super) set_conditions: Vec<Vec<BoxedCondition>>,
     /// Indexed by system set node id.
     pub(super) systems_in_sets_with_conditions: Vec<FixedBitSet>,
+    /// Indexed by system node id. The time each system took to run the last time the schedule
+    /// ran, or zero if it didn't run. `None` when the times aren't recorded.
+    pub(super) system_times: Option<Vec<Duration>>,
 }
 
 impl SystemSchedule {
@@ -88,6 +92,22 @@ impl SystemSchedule {
             system_dependents: Vec::new(),
             sets_with_conditions_of_systems: Vec::new(),
             systems_in_sets_with_conditions: Vec::new(),
+            system_times: None,
+        }
+    }
+
+    /// Resets the recorded times of the systems to zero, before running the schedule.
+    fn reset_system_times(&mut self) {
+        if let Some(system_times) = &mut self.system_times {
+            system_times.clear();
+            system_times.resize(self.systems.len(), Duration::ZERO);
+        }
+    }
+
+    /// Records the time the system at `system_index` took to run, since `start`.
+    fn record_system_time(&mut self, system_index: usize, start: Option<Instant>) {
+        if let (Some(system_times), Some(start)) = (&mut self.system_times, start) {
+            system_times[system_index] = start.elapsed();
         }
     }
 }
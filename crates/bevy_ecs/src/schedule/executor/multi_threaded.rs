@@ -4,10 +4,10 @@ use std::{
 };
 
 use bevy_tasks::{block_on, poll_once, ComputeTaskPool, Scope, TaskPool, ThreadExecutor};
-use bevy_utils::default;
 use bevy_utils::syncunsafecell::SyncUnsafeCell;
 #[cfg(feature = "trace")]
 use bevy_utils::tracing::{info_span, Instrument, Span};
+use bevy_utils::{default, Duration, Instant};
 use std::panic::AssertUnwindSafe;
 
 use async_channel::{Receiver, Sender};
@@ -71,6 +71,8 @@ struct SystemTaskMetadata {
 struct SystemResult {
     system_index: usize,
     success: bool,
+    /// The time the system took to run, if recorded.
+    duration: Option<Duration>,
 }
 
 /// Runs the schedule using a thread pool. Non-conflicting systems can run in parallel.
@@ -115,6 +117,8 @@ pub struct MultiThreadedExecutor {
     panic_payload: Arc<Mutex<Option<Box<dyn Any + Send>>>>,
     /// When set, stops the executor from running any more systems.
     stop_spawning: bool,
+    /// The times of the systems, taken from the [`SystemSchedule`] while it runs.
+    system_times: Option<Vec<Duration>>,
 }
 
 impl Default for MultiThreadedExecutor {
@@ -203,6 +207,9 @@ impl SystemExecutor for MultiThreadedExecutor {
             }
         }
 
+        schedule.reset_system_times();
+        self.system_times = schedule.system_times.take();
+
         let thread_executor = world
             .get_resource::<MainThreadExecutor>()
             .map(|e| e.0.clone());
@@ -273,6 +280,8 @@ impl SystemExecutor for MultiThreadedExecutor {
             debug_assert!(self.unapplied_systems.is_clear());
         }
 
+        schedule.system_times = self.system_times.take();
+
         // check to see if there was a panic
         let mut payload = self.panic_payload.lock().unwrap();
         if let Some(payload) = payload.take() {
@@ -319,6 +328,7 @@ impl MultiThreadedExecutor {
             apply_final_deferred: true,
             panic_payload: Arc::new(Mutex::new(None)),
             stop_spawning: false,
+            system_times: None,
         }
     }
 
@@ -527,7 +537,9 @@ impl MultiThreadedExecutor {
         let system = unsafe { &mut *systems[system_index].get() };
         let sender = self.sender.clone();
         let panic_payload = self.panic_payload.clone();
+        let record_time = self.system_times.is_some();
         let task = async move {
+            let start = record_time.then(Instant::now);
             let res = std::panic::catch_unwind(AssertUnwindSafe(|| {
                 // SAFETY:
                 // - The caller ensures that we have permission to
@@ -540,6 +552,7 @@ impl MultiThreadedExecutor {
                 .try_send(SystemResult {
                     system_index,
                     success: res.is_ok(),
+                    duration: start.map(|start| start.elapsed()),
                 })
                 .unwrap_or_else(|error| unreachable!("{}", error));
             if let Err(payload) = res {
@@ -585,17 +598,20 @@ impl MultiThreadedExecutor {
 
         let sender = self.sender.clone();
         let panic_payload = self.panic_payload.clone();
+        let record_time = self.system_times.is_some();
         if is_apply_deferred(system) {
             // TODO: avoid allocation
             let unapplied_systems = self.unapplied_systems.clone();
             self.unapplied_systems.clear();
             let task = async move {
+                let start = record_time.then(Instant::now);
                 let res = apply_deferred(&unapplied_systems, systems, world);
                 // tell the executor that the system finished
                 sender
                     .try_send(SystemResult {
                         system_index,
                         success: res.is_ok(),
+                        duration: start.map(|start| start.elapsed()),
                     })
                     .unwrap_or_else(|error| unreachable!("{}", error));
                 if let Err(payload) = res {
@@ -614,6 +630,7 @@ impl MultiThreadedExecutor {
             scope.spawn_on_scope(task);
         } else {
             let task = async move {
+                let start = record_time.then(Instant::now);
                 let res = std::panic::catch_unwind(AssertUnwindSafe(|| {
                     system.run((), world);
                 }));
@@ -622,6 +639,7 @@ impl MultiThreadedExecutor {
                     .try_send(SystemResult {
                         system_index,
                         success: res.is_ok(),
+                        duration: start.map(|start| start.elapsed()),
                     })
                     .unwrap_or_else(|error| unreachable!("{}", error));
                 if let Err(payload) = res {
@@ -652,8 +670,13 @@ impl MultiThreadedExecutor {
         let SystemResult {
             system_index,
             success,
+            duration,
         } = result;
 
+        if let (Some(system_times), Some(duration)) = (&mut self.system_times, duration) {
+            system_times[system_index] = duration;
+        }
+
         if self.system_task_metadata[system_index].is_exclusive {
             self.exclusive_running = false;
         }
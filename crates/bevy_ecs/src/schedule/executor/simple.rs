@@ -1,5 +1,6 @@
 #[cfg(feature = "trace")]
 use bevy_utils::tracing::info_span;
+use bevy_utils::Instant;
 use fixedbitset::FixedBitSet;
 use std::panic::AssertUnwindSafe;
 
@@ -46,6 +47,7 @@ impl SystemExecutor for SimpleExecutor {
             self.completed_systems |= skipped_systems;
         }
 
+        schedule.reset_system_times();
         for system_index in 0..schedule.systems.len() {
             #[cfg(feature = "trace")]
             let name = schedule.systems[system_index].name();
@@ -87,6 +89,7 @@ impl SystemExecutor for SimpleExecutor {
                 continue;
             }
 
+            let start = schedule.system_times.is_some().then(Instant::now);
             let system = &mut schedule.systems[system_index];
             if is_apply_deferred(system) {
                 continue;
@@ -99,6 +102,7 @@ impl SystemExecutor for SimpleExecutor {
                 eprintln!("Encountered a panic in system `{}`!", &*system.name());
                 std::panic::resume_unwind(payload);
             }
+            schedule.record_system_time(system_index, start);
         }
 
         self.evaluated_sets.clear();
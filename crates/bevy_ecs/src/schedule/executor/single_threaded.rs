@@ -1,5 +1,6 @@
 #[cfg(feature = "trace")]
 use bevy_utils::tracing::info_span;
+use bevy_utils::Instant;
 use fixedbitset::FixedBitSet;
 use std::panic::AssertUnwindSafe;
 
@@ -52,6 +53,7 @@ impl SystemExecutor for SingleThreadedExecutor {
             self.completed_systems |= skipped_systems;
         }
 
+        schedule.reset_system_times();
         for system_index in 0..schedule.systems.len() {
             #[cfg(feature = "trace")]
             let name = schedule.systems[system_index].name();
@@ -93,9 +95,11 @@ impl SystemExecutor for SingleThreadedExecutor {
                 continue;
             }
 
+            let start = schedule.system_times.is_some().then(Instant::now);
             let system = &mut schedule.systems[system_index];
             if is_apply_deferred(system) {
                 self.apply_deferred(schedule, world);
+                schedule.record_system_time(system_index, start);
                 continue;
             }
 
@@ -115,6 +119,7 @@ impl SystemExecutor for SingleThreadedExecutor {
                 eprintln!("Encountered a panic in system `{}`!", &*system.name());
                 std::panic::resume_unwind(payload);
             }
+            schedule.record_system_time(system_index, start);
             self.unapplied_systems.insert(system_index);
         }
 
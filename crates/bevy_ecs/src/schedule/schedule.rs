@@ -11,7 +11,7 @@ use bevy_utils::{
     petgraph::{algo::TarjanScc, prelude::*},
     thiserror::Error,
     tracing::{error, warn},
-    HashMap, HashSet,
+    Duration, HashMap, HashSet,
 };
 
 use fixedbitset::FixedBitSet;
@@ -33,6 +33,7 @@ pub struct Schedules {
     inner: HashMap<InternedScheduleLabel, Schedule>,
     /// List of [`ComponentId`]s to ignore when reporting system order ambiguity conflicts
     pub ignored_scheduling_ambiguities: BTreeSet<ComponentId>,
+    deterministic: bool,
 }
 
 impl Schedules {
@@ -41,6 +42,7 @@ impl Schedules {
         Self {
             inner: HashMap::new(),
             ignored_scheduling_ambiguities: BTreeSet::new(),
+            deterministic: false,
         }
     }
 
@@ -48,7 +50,14 @@ impl Schedules {
     ///
     /// If the map already had an entry for `label`, `schedule` is inserted,
     /// and the old schedule is returned. Otherwise, `None` is returned.
-    pub fn insert(&mut self, schedule: Schedule) -> Option<Schedule> {
+    ///
+    /// If the schedules are [deterministic](Schedules::set_deterministic), so is `schedule`, unless
+    /// it was explicitly made non-deterministic with [`Schedule::set_deterministic`] or
+    /// [`Schedule::set_executor_kind`].
+    pub fn insert(&mut self, mut schedule: Schedule) -> Option<Schedule> {
+        if self.deterministic && !schedule.nondeterministic {
+            schedule.set_deterministic(true);
+        }
         self.inner.insert(schedule.label, schedule)
     }
 
@@ -117,6 +126,23 @@ impl Schedules {
         }
     }
 
+    /// Sets whether all schedules, including the ones inserted later, run deterministically.
+    ///
+    /// See [`Schedule::set_deterministic`] for more information.
+    pub fn set_deterministic(&mut self, deterministic: bool) {
+        self.deterministic = deterministic;
+        for (_, schedule) in &mut self.inner {
+            schedule.set_deterministic(deterministic);
+        }
+    }
+
+    /// Returns `true` if all schedules run deterministically.
+    ///
+    /// See [`Schedules::set_deterministic`] for more information.
+    pub fn is_deterministic(&self) -> bool {
+        self.deterministic
+    }
+
     /// Ignore system order ambiguities caused by conflicts on [`Component`]s of type `T`.
     pub fn allow_ambiguous_component<T: Component>(&mut self, world: &mut World) {
         self.ignored_scheduling_ambiguities
@@ -213,6 +239,12 @@ pub struct Schedule {
     executable: SystemSchedule,
     executor: Box<dyn SystemExecutor>,
     executor_initialized: bool,
+    deterministic: bool,
+    /// The executor kind to restore when the schedule stops being deterministic.
+    nondeterministic_executor_kind: ExecutorKind,
+    /// Whether the schedule was explicitly made non-deterministic, in which case it isn't made
+    /// deterministic again when inserted in deterministic [`Schedules`].
+    nondeterministic: bool,
 }
 
 #[derive(ScheduleLabel, Hash, PartialEq, Eq, Debug, Clone)]
@@ -237,6 +269,9 @@ impl Schedule {
             executable: SystemSchedule::new(),
             executor: make_executor(ExecutorKind::default()),
             executor_initialized: false,
+            deterministic: false,
+            nondeterministic_executor_kind: ExecutorKind::default(),
+            nondeterministic: false,
         }
     }
 
@@ -306,7 +341,14 @@ impl Schedule {
     }
 
     /// Sets the schedule's execution strategy.
+    ///
+    /// Any executor other than [`SingleThreaded`](ExecutorKind::SingleThreaded) makes a
+    /// [deterministic](Schedule::set_deterministic) schedule non-deterministic.
     pub fn set_executor_kind(&mut self, executor: ExecutorKind) -> &mut Self {
+        if executor != ExecutorKind::SingleThreaded {
+            self.deterministic = false;
+            self.nondeterministic = true;
+        }
         if executor != self.executor.kind() {
             self.executor = make_executor(executor);
             self.executor_initialized = false;
@@ -314,6 +356,81 @@ impl Schedule {
         self
     }
 
+    /// Sets whether the schedule runs deterministically, for simulations that must give
+    /// bit-identical results across runs, like lockstep networking.
+    ///
+    /// A deterministic schedule runs its systems one at a time with the
+    /// [`SingleThreaded`](ExecutorKind::SingleThreaded) executor, in the topological order of the
+    /// schedule graph, so that the order doesn't depend on thread timing. Deferred system buffers
+    /// (like [`Commands`](crate::system::Commands)) are then applied in that same order, and the
+    /// entities they spawn get the same ids on every run.
+    ///
+    /// The order of systems that aren't ordered relative to each other still depends on the order
+    /// they were added in. Use [`Schedule::check_determinism`] to find the systems whose relative
+    /// order matters.
+    ///
+    /// Disabling it switches back to the executor the schedule used before it was enabled, and
+    /// so does switching to another executor with [`Schedule::set_executor_kind`]. Either way, the
+    /// schedule stays non-deterministic when it's inserted in [deterministic](Schedules::set_deterministic)
+    /// [`Schedules`], until this is called again.
+    pub fn set_deterministic(&mut self, deterministic: bool) -> &mut Self {
+        self.nondeterministic = !deterministic;
+        if deterministic == self.deterministic {
+            return self;
+        }
+        self.deterministic = deterministic;
+        if deterministic {
+            self.nondeterministic_executor_kind = self.executor.kind();
+            self.set_executor_kind(ExecutorKind::SingleThreaded)
+        } else {
+            self.set_executor_kind(self.nondeterministic_executor_kind)
+        }
+    }
+
+    /// Returns `true` if the schedule was made deterministic with
+    /// [`Schedule::set_deterministic`].
+    pub fn is_deterministic(&self) -> bool {
+        self.deterministic
+    }
+
+    /// Initializes the schedule and returns an [`Ambiguity`](ScheduleBuildError::Ambiguity) error
+    /// listing the systems with conflicting data access and no order between them, regardless of
+    /// [`ScheduleBuildSettings::ambiguity_detection`].
+    ///
+    /// The result of such systems depends on the order they run in, which isn't guaranteed to
+    /// stay the same when systems are added in a different order, so they should be ordered
+    /// explicitly in deterministic simulations. This is meant to be used in tests.
+    ///
+    /// ```
+    /// # use bevy_ecs::prelude::*;
+    /// #[derive(Resource, Default)]
+    /// struct Counter(u32);
+    ///
+    /// fn double(mut counter: ResMut<Counter>) { counter.0 *= 2; }
+    /// fn increment(mut counter: ResMut<Counter>) { counter.0 += 1; }
+    ///
+    /// let mut world = World::new();
+    /// world.init_resource::<Counter>();
+    ///
+    /// let mut schedule = Schedule::default();
+    /// schedule.add_systems((double, increment));
+    /// assert!(schedule.check_determinism(&mut world).is_err());
+    ///
+    /// let mut schedule = Schedule::default();
+    /// schedule.add_systems((double, increment).chain());
+    /// assert!(schedule.check_determinism(&mut world).is_ok());
+    /// ```
+    pub fn check_determinism(&mut self, world: &mut World) -> Result<(), ScheduleBuildError> {
+        // Rebuild the schedule with ambiguities reported as errors, as the names of the systems
+        // are only available while they're in the graph.
+        let ambiguity_detection = self.graph.settings.ambiguity_detection.clone();
+        self.graph.settings.ambiguity_detection = LogLevel::Error;
+        self.graph.changed = true;
+        let result = self.initialize(world);
+        self.graph.settings.ambiguity_detection = ambiguity_detection;
+        result
+    }
+
     /// Set whether the schedule applies deferred system buffers on final time or not. This is a catch-all
     /// in case a system uses commands but was not explicitly ordered before an instance of
     /// [`apply_deferred`]. By default this
@@ -449,6 +566,45 @@ impl Schedule {
         Ok(iter)
     }
 
+    /// Sets whether the schedule records the time each of its systems takes to run, returned by
+    /// [`Schedule::system_times`].
+    ///
+    /// Recording is disabled by default, as reading the clock around each system has a small cost.
+    pub fn set_record_system_times(&mut self, record: bool) -> &mut Self {
+        if record != self.records_system_times() {
+            self.executable.system_times = record.then(Vec::new);
+        }
+        self
+    }
+
+    /// Returns `true` if the schedule records the time each of its systems takes to run.
+    pub fn records_system_times(&self) -> bool {
+        self.executable.system_times.is_some()
+    }
+
+    /// Returns an iterator over the systems of this schedule, with the time they took to run the
+    /// last time the schedule ran, or zero for the systems that were skipped.
+    ///
+    /// The iterator is empty until the schedule runs with
+    /// [`Schedule::set_record_system_times`] enabled.
+    pub fn system_times(&self) -> impl Iterator<Item = (NodeId, &BoxedSystem, Duration)> {
+        self.executable
+            .system_ids
+            .iter()
+            .zip(&self.executable.systems)
+            .zip(self.executable.system_times.iter().flatten())
+            .map(|((node_id, system), duration)| (*node_id, system, *duration))
+    }
+
+    /// Clears the times returned by [`Schedule::system_times`] until the schedule runs again, so
+    /// that they're only read once.
+    pub fn clear_system_times(&mut self) -> &mut Self {
+        if let Some(system_times) = &mut self.executable.system_times {
+            system_times.clear();
+        }
+        self
+    }
+
     /// Returns the number of systems in this schedule.
     pub fn systems_len(&self) -> usize {
         if !self.executor_initialized {
@@ -1442,6 +1598,7 @@ impl ScheduleGraph {
             system_dependents,
             sets_with_conditions_of_systems,
             systems_in_sets_with_conditions,
+            system_times: None,
         }
     }
 
@@ -1475,7 +1632,9 @@ impl ScheduleGraph {
             self.system_set_conditions[id.index()] = conditions;
         }
 
+        let record_system_times = schedule.system_times.is_some();
         *schedule = self.build_schedule(components, schedule_label, ignored_ambiguities)?;
+        schedule.system_times = record_system_times.then(Vec::new);
 
         // move systems into new schedule
         for &id in &schedule.system_ids {
@@ -2004,9 +2163,10 @@ pub struct ScheduleNotInitialized;
 mod tests {
     use crate::{
         self as bevy_ecs,
-        prelude::{Res, Resource},
+        prelude::{Res, ResMut, Resource},
         schedule::{
-            IntoSystemConfigs, IntoSystemSetConfigs, Schedule, ScheduleBuildSettings, SystemSet,
+            ExecutorKind, IntoSystemConfigs, IntoSystemSetConfigs, Schedule, ScheduleBuildSettings,
+            ScheduleLabel, Schedules, SystemSet,
         },
         system::Commands,
         world::World,
@@ -2018,6 +2178,50 @@ mod tests {
     #[derive(Resource)]
     struct Resource2;
 
+    #[test]
+    fn system_times_are_recorded_by_every_executor() {
+        use crate::schedule::{common_conditions::run_once, ExecutorKind};
+        use bevy_utils::Duration;
+
+        fn slow_system() {
+            std::thread::sleep(Duration::from_millis(2));
+        }
+
+        for executor in [
+            ExecutorKind::SingleThreaded,
+            ExecutorKind::Simple,
+            ExecutorKind::MultiThreaded,
+        ] {
+            let mut world = World::new();
+            let mut schedule = Schedule::default();
+            schedule
+                .set_executor_kind(executor)
+                .add_systems((slow_system, slow_system.run_if(run_once())));
+            schedule.run(&mut world);
+            assert_eq!(schedule.system_times().count(), 0);
+
+            schedule.set_record_system_times(true);
+            schedule.run(&mut world);
+            let times: Vec<_> = schedule
+                .system_times()
+                .map(|(_, _, duration)| duration)
+                .collect();
+            assert_eq!(times.len(), 2, "{executor:?}");
+            assert_eq!(
+                times
+                    .iter()
+                    .filter(|duration| **duration >= Duration::from_millis(2))
+                    .count(),
+                1,
+                "{executor:?}"
+            );
+            assert!(times.contains(&Duration::ZERO), "{executor:?}");
+
+            schedule.clear_system_times();
+            assert_eq!(schedule.system_times().count(), 0, "{executor:?}");
+        }
+    }
+
     // regression test for https://github.com/bevyengine/bevy/issues/9114
     #[test]
     fn ambiguous_with_not_breaking_run_conditions() {
@@ -2125,6 +2329,106 @@ mod tests {
         assert_eq!(schedule.executable.systems.len(), 2);
     }
 
+    #[test]
+    fn deterministic_schedules() {
+        let mut schedules = Schedules::new();
+        schedules.insert(Schedule::default());
+        schedules.set_deterministic(true);
+
+        #[derive(ScheduleLabel, Hash, PartialEq, Eq, Debug, Clone)]
+        struct Later;
+        schedules.insert(Schedule::new(Later));
+        assert!(schedules
+            .iter()
+            .all(|(_, schedule)| schedule.is_deterministic()));
+
+        let mut world = World::default();
+        world.insert_resource(Resource1);
+        let mut schedule = schedules.remove(Later).unwrap();
+        schedule.add_systems((|_: Res<Resource1>| {}, |_: Res<Resource1>| {}));
+        assert!(schedule.check_determinism(&mut world).is_ok());
+
+        schedule.add_systems(|_: ResMut<Resource1>| {});
+        assert!(schedule.check_determinism(&mut world).is_err());
+    }
+
+    #[test]
+    fn disabling_determinism_restores_executor_kind() {
+        let mut schedule = Schedule::default();
+        schedule.set_executor_kind(ExecutorKind::SingleThreaded);
+        assert!(!schedule.is_deterministic());
+
+        schedule.set_deterministic(true);
+        assert!(schedule.is_deterministic());
+        schedule.set_deterministic(false);
+        assert!(!schedule.is_deterministic());
+        assert_eq!(schedule.get_executor_kind(), ExecutorKind::SingleThreaded);
+
+        let mut schedule = Schedule::default();
+        schedule.set_executor_kind(ExecutorKind::Simple);
+        schedule.set_deterministic(true);
+        assert_eq!(schedule.get_executor_kind(), ExecutorKind::SingleThreaded);
+        schedule.set_deterministic(false);
+        assert_eq!(schedule.get_executor_kind(), ExecutorKind::Simple);
+    }
+
+    #[test]
+    fn changing_executor_kind_disables_determinism() {
+        let mut schedule = Schedule::default();
+        schedule.set_deterministic(true);
+        schedule.set_executor_kind(ExecutorKind::SingleThreaded);
+        assert!(schedule.is_deterministic());
+
+        schedule.set_executor_kind(ExecutorKind::Simple);
+        assert!(!schedule.is_deterministic());
+        assert_eq!(schedule.get_executor_kind(), ExecutorKind::Simple);
+
+        // The schedule can be made deterministic again.
+        schedule.set_deterministic(true);
+        assert!(schedule.is_deterministic());
+        assert_eq!(schedule.get_executor_kind(), ExecutorKind::SingleThreaded);
+        schedule.set_deterministic(false);
+        assert_eq!(schedule.get_executor_kind(), ExecutorKind::Simple);
+    }
+
+    #[test]
+    fn deterministic_schedules_keep_opt_outs() {
+        #[derive(ScheduleLabel, Hash, PartialEq, Eq, Debug, Clone)]
+        struct OptedOut;
+        #[derive(ScheduleLabel, Hash, PartialEq, Eq, Debug, Clone)]
+        struct MultiThreaded;
+
+        let mut world = World::default();
+        world.add_schedule(Schedule::new(OptedOut));
+        world.add_schedule(Schedule::new(MultiThreaded));
+        world.resource_mut::<Schedules>().set_deterministic(true);
+
+        let mut schedules = world.resource_mut::<Schedules>();
+        schedules
+            .get_mut(OptedOut)
+            .unwrap()
+            .set_deterministic(false);
+        schedules
+            .get_mut(MultiThreaded)
+            .unwrap()
+            .set_executor_kind(ExecutorKind::MultiThreaded);
+
+        // Running a schedule removes it from `Schedules` and inserts it back.
+        world.run_schedule(OptedOut);
+        world.run_schedule(MultiThreaded);
+
+        let schedules = world.resource::<Schedules>();
+        let opted_out = schedules.get(OptedOut).unwrap();
+        assert!(!opted_out.is_deterministic());
+        assert_eq!(opted_out.get_executor_kind(), ExecutorKind::default());
+        let multi_threaded = schedules.get(MultiThreaded).unwrap();
+        assert!(!multi_threaded.is_deterministic());
+        assert_eq!(
+            multi_threaded.get_executor_kind(),
+            ExecutorKind::MultiThreaded
+        );
+    }
+
     mod no_sync_edges {
         use super::*;
 
@@ -0,0 +1,368 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    task::{Context, Poll, Wake, Waker},
+};
+
+use async_channel::{Receiver, Sender};
+use bevy_utils::{synccell::SyncCell, HashSet};
+
+use crate::{
+    self as bevy_ecs,
+    entity::Entity,
+    system::{Local, Res, ResMut, Resource},
+    world::World,
+};
+
+/// A request for access to the [`World`] made by an async task, with the owner of the task.
+type WorldRequest = (Option<Entity>, Box<dyn FnOnce(&mut World) + Send>);
+
+/// A handle that async tasks use to access the [`World`].
+///
+/// The [`World`] can't be borrowed across an `.await`, so tasks instead send closures to run on
+/// it with [`AsyncWorld::run`]. The closures run the next time the tasks are run by
+/// [`run_async_tasks`], and the task resumes with their result the time after that, typically
+/// during the next frame.
+#[derive(Clone)]
+pub struct AsyncWorld {
+    requests: Sender<WorldRequest>,
+    owner: Option<Entity>,
+}
+
+impl AsyncWorld {
+    /// Runs `f` with exclusive access to the [`World`], and returns its result.
+    ///
+    /// The task yields back to the scheduler while waiting for world access, so the world may
+    /// have changed between two calls.
+    pub async fn run<R: Send + 'static>(
+        &self,
+        f: impl FnOnce(&mut World) -> R + Send + 'static,
+    ) -> R {
+        let (sender, receiver) = async_channel::bounded(1);
+        self.requests
+            .try_send((
+                self.owner,
+                Box::new(move |world| {
+                    // The task may have been cancelled in the meantime.
+                    let _ = sender.try_send(f(world));
+                }),
+            ))
+            .expect("the AsyncTasks resource was dropped");
+        receiver
+            .recv()
+            .await
+            .expect("the AsyncTasks resource was dropped")
+    }
+}
+
+/// Identifies a task spawned on [`AsyncTasks`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct AsyncTaskId(u64);
+
+struct AsyncTask {
+    id: AsyncTaskId,
+    owner: Option<Entity>,
+    future: SyncCell<Pin<Box<dyn Future<Output = ()> + Send>>>,
+    waker: Arc<TaskWaker>,
+}
+
+/// Marks a task as ready to be polled again, when the future it's waiting on makes progress.
+struct TaskWaker {
+    woken: AtomicBool,
+}
+
+impl Wake for TaskWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.woken.store(true, Ordering::Release);
+    }
+}
+
+/// Stores the async tasks that run alongside the systems of a [`World`].
+///
+/// Tasks are futures polled by [`run_async_tasks`], on the thread running it, with access to the
+/// [`World`] through an [`AsyncWorld`]. They can also `.await` any other future, such as a
+/// [`Task`](bevy_tasks::Task) of a task pool or the loading of an asset. A task is only polled
+/// again once the future it's waiting on wakes it.
+///
+/// The tasks are run by the [`run_async_tasks`] system, which apps add with the
+/// `AsyncTasksPlugin` of `bevy_app`, included in `DefaultPlugins` and `MinimalPlugins`.
+///
+/// A task can be owned by an entity, in which case it's cancelled when the entity is despawned.
+///
+/// ```
+/// # use bevy_ecs::prelude::*;
+/// # use bevy_ecs::system::{run_async_tasks, AsyncTasks, RunSystemOnce};
+/// #[derive(Component)]
+/// struct Score(u32);
+///
+/// let mut world = World::new();
+/// world.init_resource::<AsyncTasks>();
+/// let player = world.spawn(Score(0)).id();
+///
+/// world.resource_mut::<AsyncTasks>().spawn(Some(player), move |world| async move {
+///     for _ in 0..3 {
+///         world.run(move |world| world.get_mut::<Score>(player).unwrap().0 += 1).await;
+///     }
+/// });
+///
+/// for _ in 0..4 {
+///     world.run_system_once(run_async_tasks);
+/// }
+/// assert_eq!(world.get::<Score>(player).unwrap().0, 3);
+/// ```
+#[derive(Resource)]
+pub struct AsyncTasks {
+    tasks: Vec<AsyncTask>,
+    next_id: u64,
+    requests: Sender<WorldRequest>,
+    request_receiver: Receiver<WorldRequest>,
+}
+
+impl Default for AsyncTasks {
+    fn default() -> Self {
+        let (requests, request_receiver) = async_channel::unbounded();
+        Self {
+            tasks: Vec::new(),
+            next_id: 0,
+            requests,
+            request_receiver,
+        }
+    }
+}
+
+impl AsyncTasks {
+    /// Spawns the task returned by `f`, optionally owned by the entity `owner`.
+    pub fn spawn<F, Fut>(&mut self, owner: Option<Entity>, f: F) -> AsyncTaskId
+    where
+        F: FnOnce(AsyncWorld) -> Fut,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let id = AsyncTaskId(self.next_id);
+        self.next_id += 1;
+        let world = AsyncWorld {
+            requests: self.requests.clone(),
+            owner,
+        };
+        self.tasks.push(AsyncTask {
+            id,
+            owner,
+            future: SyncCell::new(Box::pin(f(world))),
+            waker: Arc::new(TaskWaker {
+                woken: AtomicBool::new(true),
+            }),
+        });
+        id
+    }
+
+    /// Returns `true` if the task `id` is still running.
+    pub fn contains(&self, id: AsyncTaskId) -> bool {
+        self.tasks.iter().any(|task| task.id == id)
+    }
+
+    /// Cancels the task `id`, dropping its future. Returns `true` if it was still running.
+    pub fn cancel(&mut self, id: AsyncTaskId) -> bool {
+        let len = self.tasks.len();
+        self.tasks.retain(|task| task.id != id);
+        self.tasks.len() != len
+    }
+
+    /// Returns the number of running tasks.
+    pub fn len(&self) -> usize {
+        self.tasks.len()
+    }
+
+    /// Returns `true` if no task is running.
+    pub fn is_empty(&self) -> bool {
+        self.tasks.is_empty()
+    }
+}
+
+/// Runs the tasks of the [`AsyncTasks`] resource, if it exists.
+///
+/// The tasks whose owner was despawned are cancelled, the ones woken since they were last polled
+/// are polled once, and then the [`World`] accesses they requested through their [`AsyncWorld`]
+/// are run, in order. The requests of a task whose owner was despawned by a previous request are
+/// dropped.
+///
+/// The tasks stay in the resource while they run, so the requests can spawn, cancel or inspect
+/// them.
+pub fn run_async_tasks(world: &mut World) {
+    let Some(async_tasks) = world.get_resource::<AsyncTasks>() else {
+        return;
+    };
+    let is_despawned = |world: &World, owner: Option<Entity>| {
+        owner.is_some_and(|owner| !world.entities().contains(owner))
+    };
+    let cancelled: HashSet<AsyncTaskId> = async_tasks
+        .tasks
+        .iter()
+        .filter(|task| is_despawned(world, task.owner))
+        .map(|task| task.id)
+        .collect();
+    let request_receiver = async_tasks.request_receiver.clone();
+
+    world.resource_mut::<AsyncTasks>().tasks.retain_mut(|task| {
+        if cancelled.contains(&task.id) {
+            return false;
+        }
+        if !task.waker.woken.swap(false, Ordering::AcqRel) {
+            return true;
+        }
+        let waker = Waker::from(task.waker.clone());
+        let mut context = Context::from_waker(&waker);
+        task.future.get().as_mut().poll(&mut context) == Poll::Pending
+    });
+
+    while let Ok((owner, request)) = request_receiver.try_recv() {
+        if !is_despawned(world, owner) {
+            request(world);
+        }
+    }
+}
+
+/// A run condition that is `true` if the [`AsyncTasks`] resource exists and has running tasks.
+pub fn any_async_tasks(async_tasks: Option<Res<AsyncTasks>>) -> bool {
+    async_tasks.is_some_and(|async_tasks| !async_tasks.is_empty())
+}
+
+/// Turns the async function `f` into a system that spawns it as a task on [`AsyncTasks`] whenever
+/// the previous task it spawned has finished.
+///
+/// ```
+/// # use bevy_ecs::prelude::*;
+/// # use bevy_ecs::system::{async_system, run_async_tasks, AsyncTasks, AsyncWorld};
+/// #[derive(Resource, Default)]
+/// struct Frames(u32);
+///
+/// async fn count_frames(world: AsyncWorld) {
+///     world.run(|world| world.resource_mut::<Frames>().0 += 1).await;
+/// }
+///
+/// let mut world = World::new();
+/// world.init_resource::<AsyncTasks>();
+/// world.init_resource::<Frames>();
+///
+/// let mut schedule = Schedule::default();
+/// schedule.add_systems((async_system(count_frames), run_async_tasks).chain());
+/// for _ in 0..4 {
+///     schedule.run(&mut world);
+/// }
+/// // Each task takes two frames: one to request the world access, one to resume.
+/// assert_eq!(world.resource::<Frames>().0, 2);
+/// ```
+///
+/// # Panics
+///
+/// The system panics if the [`AsyncTasks`] resource doesn't exist.
+pub fn async_system<F, Fut>(
+    f: F,
+) -> impl FnMut(ResMut<AsyncTasks>, Local<Option<AsyncTaskId>>) + Send + Sync + 'static
+where
+    F: Fn(AsyncWorld) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    move |mut tasks: ResMut<AsyncTasks>, mut running: Local<Option<AsyncTaskId>>| {
+        if !running.is_some_and(|id| tasks.contains(id)) {
+            *running = Some(tasks.spawn(None, &f));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        prelude::Component,
+        system::{CommandQueue, Commands, RunSystemOnce},
+    };
+
+    #[derive(Component)]
+    struct Counter(u32);
+
+    #[test]
+    fn tasks_are_cancelled_with_their_owner() {
+        let mut world = World::new();
+        let owner = world.spawn(Counter(0)).id();
+        let mut queue = CommandQueue::default();
+        let mut commands = Commands::new(&mut queue, &world);
+        commands.entity(owner).spawn_task(move |world| async move {
+            loop {
+                world
+                    .run(move |world| world.get_mut::<Counter>(owner).unwrap().0 += 1)
+                    .await;
+            }
+        });
+        queue.apply(&mut world);
+
+        world.run_system_once(run_async_tasks);
+        world.run_system_once(run_async_tasks);
+        assert_eq!(world.get::<Counter>(owner).unwrap().0, 2);
+        assert_eq!(world.resource::<AsyncTasks>().len(), 1);
+
+        world.despawn(owner);
+        world.run_system_once(run_async_tasks);
+        assert!(world.resource::<AsyncTasks>().is_empty());
+    }
+
+    #[test]
+    fn tasks_can_be_cancelled_from_their_requests() {
+        let mut world = World::new();
+        world.init_resource::<AsyncTasks>();
+        let counter = world.spawn(Counter(0)).id();
+        let mut tasks = world.resource_mut::<AsyncTasks>();
+        let id = tasks.spawn(None, move |world| async move {
+            loop {
+                world
+                    .run(move |world| {
+                        world.get_mut::<Counter>(counter).unwrap().0 += 1;
+                        let mut tasks = world.resource_mut::<AsyncTasks>();
+                        assert_eq!(tasks.len(), 1);
+                        assert!(tasks.cancel(AsyncTaskId(0)));
+                    })
+                    .await;
+            }
+        });
+        assert_eq!(id, AsyncTaskId(0));
+
+        world.run_system_once(run_async_tasks);
+        assert!(world.resource::<AsyncTasks>().is_empty());
+        world.run_system_once(run_async_tasks);
+        assert_eq!(world.get::<Counter>(counter).unwrap().0, 1);
+    }
+
+    #[test]
+    fn tasks_are_only_polled_when_woken() {
+        struct CountPolls(Arc<std::sync::atomic::AtomicU32>);
+
+        impl Future for CountPolls {
+            type Output = ();
+
+            fn poll(self: Pin<&mut Self>, _: &mut Context) -> Poll<()> {
+                self.0.fetch_add(1, Ordering::Relaxed);
+                Poll::Pending
+            }
+        }
+
+        let mut world = World::new();
+        world.init_resource::<AsyncTasks>();
+        let polls = Arc::new(std::sync::atomic::AtomicU32::new(0));
+        let future = CountPolls(polls.clone());
+        world
+            .resource_mut::<AsyncTasks>()
+            .spawn(None, move |_| future);
+
+        for _ in 0..3 {
+            world.run_system_once(run_async_tasks);
+        }
+        assert_eq!(polls.load(Ordering::Relaxed), 1);
+        assert_eq!(world.resource::<AsyncTasks>().len(), 1);
+    }
+}
@@ -0,0 +1,101 @@
+use crate::{
+    self as bevy_ecs,
+    entity::Entity,
+    event::{Event, Events},
+    system::Resource,
+    world::World,
+};
+use bevy_utils::tracing::warn;
+use thiserror::Error;
+
+/// An error that occurred while applying a command.
+///
+/// How these errors are handled is decided by the [`CommandErrorPolicy`].
+#[derive(Event, Error, Debug, Clone, PartialEq, Eq)]
+pub enum CommandError {
+    /// The entity targeted by an entity command doesn't exist, for example because another
+    /// command despawned it.
+    #[error("error[B0003]: Could not {action} entity {entity:?} because it doesn't exist in this World.")]
+    NoSuchEntity {
+        /// The entity that doesn't exist.
+        entity: Entity,
+        /// A description of what the command tried to do with the entity.
+        action: String,
+    },
+    /// The resource removed by [`Commands::remove_resource`](super::Commands::remove_resource)
+    /// doesn't exist.
+    #[error("Could not remove resource `{resource}` because it doesn't exist in this World.")]
+    NoSuchResource {
+        /// The type name of the resource that doesn't exist.
+        resource: &'static str,
+    },
+}
+
+/// How the [`CommandError`]s of fallible commands are handled.
+///
+/// A policy can be set for all commands by inserting it as a resource, and for the commands of a
+/// single entity with [`EntityCommands::on_error`](super::EntityCommands::on_error). Without
+/// either, each command keeps its own default: for example [`EntityCommands::insert`](super::EntityCommands::insert)
+/// panics, [`EntityCommands::despawn`](super::EntityCommands::despawn) only warns, and
+/// [`EntityCommands::remove`](super::EntityCommands::remove) and
+/// [`Commands::remove_resource`](super::Commands::remove_resource) do nothing. Inserting a
+/// resource can't fail, so [`Commands::insert_resource`](super::Commands::insert_resource) never
+/// reports an error.
+///
+/// ```
+/// # use bevy_ecs::prelude::*;
+/// # use bevy_ecs::system::CommandErrorPolicy;
+/// #[derive(Component)]
+/// struct Ghost;
+///
+/// let mut world = World::new();
+/// world.insert_resource(CommandErrorPolicy::Warn);
+///
+/// let entity = world.spawn_empty().id();
+/// let mut commands = world.commands();
+/// commands.entity(entity).despawn();
+/// // The entity is despawned by the time this command is applied, which logs a warning.
+/// commands.entity(entity).insert(Ghost);
+/// world.flush();
+/// ```
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum CommandErrorPolicy {
+    /// Panics with the error.
+    #[default]
+    Panic,
+    /// Logs the error as a warning.
+    Warn,
+    /// Silently ignores the error.
+    Ignore,
+    /// Sends the error as an event, to the [`Events<CommandError>`] resource. The error is logged
+    /// as a warning if that resource doesn't exist.
+    ///
+    /// In an `App`, the event is registered by the `CommandErrorPlugin` of `bevy_app`.
+    SendEvent,
+}
+
+impl CommandErrorPolicy {
+    /// Returns `policy` if set, else the [`CommandErrorPolicy`] resource of `world` if it exists,
+    /// else `default`.
+    pub(crate) fn resolve(world: &World, policy: Option<Self>, default: Self) -> Self {
+        policy
+            .or_else(|| world.get_resource::<Self>().copied())
+            .unwrap_or(default)
+    }
+
+    /// Handles `error` according to this policy.
+    pub fn handle(self, world: &mut World, error: CommandError) {
+        match self {
+            CommandErrorPolicy::Panic => panic!("{error}"),
+            CommandErrorPolicy::Warn => warn!("{error}"),
+            CommandErrorPolicy::Ignore => {}
+            CommandErrorPolicy::SendEvent => {
+                if let Some(mut events) = world.get_resource_mut::<Events<CommandError>>() {
+                    events.send(error);
+                } else {
+                    warn!("{error}");
+                }
+            }
+        }
+    }
+}
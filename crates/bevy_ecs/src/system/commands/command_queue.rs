@@ -117,9 +117,11 @@ impl CommandQueue {
     #[inline]
     pub fn apply(&mut self, world: &mut World) {
         // flush the previously queued entities
-        world.flush();
+        world.flush_entities();
 
         self.apply_or_drop_queued(Some(world));
+        // apply the commands queued by hooks and observers
+        world.flush_commands();
     }
 
     /// If `world` is [`Some`], this will apply the queued [commands](`Command`).
@@ -171,6 +173,12 @@ impl CommandQueue {
     pub fn append(&mut self, other: &mut CommandQueue) {
         self.bytes.append(&mut other.bytes);
     }
+
+    /// Returns false if there are any commands in the queue
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
 }
 
 impl Drop for CommandQueue {
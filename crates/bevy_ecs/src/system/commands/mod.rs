@@ -1,3 +1,4 @@
+mod command_error;
 mod command_queue;
 mod parallel_scope;
 
@@ -5,14 +6,17 @@ use crate::{
     self as bevy_ecs,
     bundle::Bundle,
     entity::{Entities, Entity},
-    system::{RunSystemWithInput, SystemId},
-    world::{EntityWorldMut, FromWorld, World},
+    event::Event,
+    observer::{Observer, TriggerEvent, TriggerTargets},
+    system::{AsyncTasks, AsyncWorld, IntoObserverSystem, RunSystemWithInput, SystemId},
+    world::{DeferredWorld, EntityWorldMut, FromWorld, World},
 };
 use bevy_ecs_macros::SystemParam;
 use bevy_utils::tracing::{error, info};
+pub use command_error::*;
 pub use command_queue::CommandQueue;
 pub use parallel_scope::*;
-use std::marker::PhantomData;
+use std::{future::Future, marker::PhantomData};
 
 use super::{Deferred, Resource, SystemBuffer, SystemMeta};
 
@@ -122,6 +126,13 @@ impl SystemBuffer for CommandQueue {
         let _span_guard = _system_meta.commands_span.enter();
         self.apply(world);
     }
+
+    #[inline]
+    fn queue(&mut self, _system_meta: &SystemMeta, mut world: DeferredWorld) {
+        #[cfg(feature = "trace")]
+        let _span_guard = _system_meta.commands_span.enter();
+        world.commands().append(self);
+    }
 }
 
 impl<'w, 's> Commands<'w, 's> {
@@ -216,6 +227,7 @@ impl<'w, 's> Commands<'w, 's> {
         EntityCommands {
             entity,
             commands: self.reborrow(),
+            error_policy: None,
         }
     }
 
@@ -240,6 +252,7 @@ impl<'w, 's> Commands<'w, 's> {
         EntityCommands {
             entity,
             commands: self.reborrow(),
+            error_policy: None,
         }
     }
 
@@ -388,6 +401,7 @@ impl<'w, 's> Commands<'w, 's> {
         self.entities.contains(entity).then_some(EntityCommands {
             entity,
             commands: self.reborrow(),
+            error_policy: None,
         })
     }
 
@@ -544,7 +558,7 @@ impl<'w, 's> Commands<'w, 's> {
     /// # bevy_ecs::system::assert_is_system(system);
     /// ```
     pub fn remove_resource<R: Resource>(&mut self) {
-        self.queue.push(remove_resource::<R>);
+        self.queue.push(remove_resource::<R>());
     }
 
     /// Runs the system corresponding to the given [`SystemId`].
@@ -574,6 +588,48 @@ impl<'w, 's> Commands<'w, 's> {
             .push(RunSystemWithInput::new_with_input(id, input));
     }
 
+    /// Spawns the async task returned by `f` on the [`AsyncTasks`] resource, which is inserted
+    /// if it doesn't exist.
+    ///
+    /// The task runs when [`run_async_tasks`](crate::system::run_async_tasks) does, which apps
+    /// schedule with the `AsyncTasksPlugin` of `bevy_app`, included in `DefaultPlugins` and
+    /// `MinimalPlugins`.
+    ///
+    /// The task isn't owned by any entity, see [`EntityCommands::spawn_task`] for tasks that are
+    /// cancelled when their entity is despawned.
+    pub fn spawn_task<F, Fut>(&mut self, f: F)
+    where
+        F: FnOnce(AsyncWorld) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.add(move |world: &mut World| {
+            world
+                .get_resource_or_insert_with(AsyncTasks::default)
+                .spawn(None, f);
+        });
+    }
+
+    /// Sends a "global" [`Trigger`](crate::observer::Trigger) without any targets. This will run
+    /// any [`Observer`] of the `event` that isn't scoped to specific targets.
+    pub fn trigger(&mut self, event: impl Event) {
+        self.add(TriggerEvent { event, targets: () });
+    }
+
+    /// Sends a [`Trigger`](crate::observer::Trigger) for the given targets. This will run any
+    /// [`Observer`] of the `event` that watches those targets.
+    pub fn trigger_targets(&mut self, event: impl Event, targets: impl TriggerTargets) {
+        self.add(TriggerEvent { event, targets });
+    }
+
+    /// Spawns an [`Observer`] and returns the [`EntityCommands`] associated with the entity that
+    /// stores the observer.
+    pub fn observe<E: Event, B: Bundle, M>(
+        &mut self,
+        observer: impl IntoObserverSystem<E, B, M>,
+    ) -> EntityCommands<'_> {
+        self.spawn(Observer::new(observer))
+    }
+
     /// Pushes a generic [`Command`] to the command queue.
     ///
     /// `command` can be a built-in command, custom struct that implements [`Command`] or a closure
@@ -702,6 +758,7 @@ where
 pub struct EntityCommands<'a> {
     pub(crate) entity: Entity,
     pub(crate) commands: Commands<'a, 'a>,
+    pub(crate) error_policy: Option<CommandErrorPolicy>,
 }
 
 impl EntityCommands<'_> {
@@ -729,6 +786,7 @@ impl EntityCommands<'_> {
         EntityCommands {
             entity: self.entity,
             commands: self.commands.reborrow(),
+            error_policy: self.error_policy,
         }
     }
 
@@ -738,7 +796,8 @@ impl EntityCommands<'_> {
     ///
     /// # Panics
     ///
-    /// The command will panic when applied if the associated entity does not exist.
+    /// The command will panic when applied if the associated entity does not exist, unless
+    /// another [`CommandErrorPolicy`] is set for the command.
     ///
     /// To avoid a panic in this case, use the command [`Self::try_insert`] instead.
     ///
@@ -784,7 +843,8 @@ impl EntityCommands<'_> {
     /// # bevy_ecs::system::assert_is_system(add_combat_stats_system);
     /// ```
     pub fn insert(&mut self, bundle: impl Bundle) -> &mut Self {
-        self.add(insert(bundle))
+        let error_policy = self.error_policy;
+        self.push(insert(bundle, error_policy))
     }
 
     /// Tries to add a [`Bundle`] of components to the entity.
@@ -836,7 +896,7 @@ impl EntityCommands<'_> {
     /// # bevy_ecs::system::assert_is_system(add_combat_stats_system);
     /// ```
     pub fn try_insert(&mut self, bundle: impl Bundle) -> &mut Self {
-        self.add(try_insert(bundle))
+        self.push(try_insert(bundle))
     }
 
     /// Removes a [`Bundle`] of components from the entity.
@@ -878,7 +938,8 @@ impl EntityCommands<'_> {
     where
         T: Bundle,
     {
-        self.add(remove::<T>)
+        let error_policy = self.error_policy;
+        self.push(remove::<T>(error_policy))
     }
 
     /// Despawns the entity.
@@ -890,9 +951,10 @@ impl EntityCommands<'_> {
     /// This won't clean up external references to the entity (such as parent-child relationships
     /// if you're using `bevy_hierarchy`), which may leave the world in an invalid state.
     ///
-    /// # Panics
+    /// # Errors
     ///
-    /// The command will panic when applied if the associated entity does not exist.
+    /// The command will log a warning when applied if the associated entity does not exist,
+    /// unless another [`CommandErrorPolicy`] is set for the command.
     ///
     /// # Example
     ///
@@ -912,7 +974,46 @@ impl EntityCommands<'_> {
     /// # bevy_ecs::system::assert_is_system(remove_character_system);
     /// ```
     pub fn despawn(&mut self) {
-        self.add(despawn);
+        let error_policy = self.error_policy;
+        self.push(despawn(error_policy));
+    }
+
+    /// Despawns the entity, if it exists.
+    ///
+    /// Unlike [`Self::despawn`], this will not log a warning if the associated entity does not
+    /// exist.
+    pub fn try_despawn(&mut self) {
+        self.push(despawn(Some(CommandErrorPolicy::Ignore)));
+    }
+
+    /// Sets how the errors of the commands subsequently queued for this entity, like
+    /// [`Self::insert`], [`Self::remove`], [`Self::despawn`] and [`Self::add`], are handled,
+    /// overriding the [`CommandErrorPolicy`] resource.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use bevy_ecs::prelude::*;
+    /// # use bevy_ecs::system::CommandErrorPolicy;
+    /// #[derive(Component)]
+    /// struct Target;
+    ///
+    /// fn mark_target(mut commands: Commands, enemy: Query<Entity, With<Name>>) {
+    ///     for enemy in &enemy {
+    ///         // Another system may have despawned the enemy in the meantime.
+    ///         commands
+    ///             .entity(enemy)
+    ///             .on_error(CommandErrorPolicy::Warn)
+    ///             .insert(Target);
+    ///     }
+    /// }
+    /// # #[derive(Component)]
+    /// # struct Name;
+    /// # bevy_ecs::system::assert_is_system(mark_target);
+    /// ```
+    pub fn on_error(&mut self, policy: CommandErrorPolicy) -> &mut Self {
+        self.error_policy = Some(policy);
+        self
     }
 
     /// Pushes an [`EntityCommand`] to the queue, which will get executed for the current [`Entity`].
@@ -931,7 +1032,32 @@ impl EntityCommands<'_> {
     /// # }
     /// # bevy_ecs::system::assert_is_system(my_system);
     /// ```
+    ///
+    /// # Errors
+    ///
+    /// If a [`CommandErrorPolicy`] is set with [`Self::on_error`], the command is only applied if
+    /// the entity exists, and the error is handled according to that policy otherwise.
     pub fn add<M: 'static>(&mut self, command: impl EntityCommand<M>) -> &mut Self {
+        let Some(error_policy) = self.error_policy else {
+            return self.push(command);
+        };
+        self.push(move |entity: Entity, world: &mut World| {
+            if world.get_entity(entity).is_some() {
+                command.apply(entity, world);
+            } else {
+                error_policy.handle(
+                    world,
+                    CommandError::NoSuchEntity {
+                        entity,
+                        action: "apply a command to".to_string(),
+                    },
+                );
+            }
+        })
+    }
+
+    /// Pushes an [`EntityCommand`] that handles its own errors to the queue.
+    fn push<M: 'static>(&mut self, command: impl EntityCommand<M>) -> &mut Self {
         self.commands.add(command.with_entity(self.entity));
         self
     }
@@ -977,22 +1103,52 @@ impl EntityCommands<'_> {
     where
         T: Bundle,
     {
-        self.add(retain::<T>)
+        let error_policy = self.error_policy;
+        self.push(retain::<T>(error_policy))
     }
 
     /// Logs the components of the entity at the info level.
     ///
     /// # Panics
     ///
-    /// The command will panic when applied if the associated entity does not exist.
+    /// The command will panic when applied if the associated entity does not exist, unless
+    /// another [`CommandErrorPolicy`] is set for the command.
     pub fn log_components(&mut self) {
-        self.add(log_components);
+        let error_policy = self.error_policy;
+        self.push(log_components(error_policy));
     }
 
     /// Returns the underlying [`Commands`].
     pub fn commands(&mut self) -> Commands {
         self.commands.reborrow()
     }
+
+    /// Spawns the async task returned by `f` on the [`AsyncTasks`] resource, which is inserted
+    /// if it doesn't exist. The task is cancelled when this entity is despawned.
+    ///
+    /// The task runs when [`run_async_tasks`](crate::system::run_async_tasks) does, which apps
+    /// schedule with the `AsyncTasksPlugin` of `bevy_app`, included in `DefaultPlugins` and
+    /// `MinimalPlugins`.
+    pub fn spawn_task<F, Fut>(&mut self, f: F) -> &mut Self
+    where
+        F: FnOnce(AsyncWorld) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.add(move |entity: Entity, world: &mut World| {
+            world
+                .get_resource_or_insert_with(AsyncTasks::default)
+                .spawn(Some(entity), f);
+        })
+    }
+
+    /// Creates an [`Observer`] listening for events of type `E` targeting this entity.
+    pub fn observe<E: Event, B: Bundle, M>(
+        &mut self,
+        system: impl IntoObserverSystem<E, B, M>,
+    ) -> &mut Self {
+        let error_policy = self.error_policy;
+        self.push(observe(system, error_policy))
+    }
 }
 
 impl<F> Command for F
@@ -1009,7 +1165,13 @@ where
     F: FnOnce(EntityWorldMut) + Send + 'static,
 {
     fn apply(self, id: Entity, world: &mut World) {
-        self(world.entity_mut(id));
+        if let Some(entity) = world.get_entity_mut(id) {
+            self(entity);
+        } else {
+            handle_no_such_entity(world, id, None, CommandErrorPolicy::Panic, || {
+                "apply a command to".to_string()
+            });
+        }
     }
 }
 
@@ -1056,23 +1218,47 @@ where
 }
 
 /// A [`Command`] that despawns a specific entity.
-/// This will emit a warning if the entity does not exist.
+/// By default, this will emit a warning if the entity does not exist.
 ///
 /// # Note
 ///
 /// This won't clean up external references to the entity (such as parent-child relationships
 /// if you're using `bevy_hierarchy`), which may leave the world in an invalid state.
-fn despawn(entity: Entity, world: &mut World) {
-    world.despawn(entity);
+fn despawn(error_policy: Option<CommandErrorPolicy>) -> impl EntityCommand {
+    move |entity: Entity, world: &mut World| {
+        if let Some(entity) = world.get_entity_mut(entity) {
+            entity.despawn();
+        } else {
+            handle_no_such_entity(
+                world,
+                entity,
+                error_policy,
+                CommandErrorPolicy::Warn,
+                || "despawn".to_string(),
+            );
+        }
+    }
 }
 
 /// An [`EntityCommand`] that adds the components in a [`Bundle`] to an entity.
-fn insert<T: Bundle>(bundle: T) -> impl EntityCommand {
+/// By default, this will panic if the entity does not exist.
+fn insert<T: Bundle>(bundle: T, error_policy: Option<CommandErrorPolicy>) -> impl EntityCommand {
     move |entity: Entity, world: &mut World| {
         if let Some(mut entity) = world.get_entity_mut(entity) {
             entity.insert(bundle);
         } else {
-            panic!("error[B0003]: Could not insert a bundle (of type `{}`) for entity {:?} because it doesn't exist in this World.", std::any::type_name::<T>(), entity);
+            handle_no_such_entity(
+                world,
+                entity,
+                error_policy,
+                CommandErrorPolicy::Panic,
+                || {
+                    format!(
+                        "insert a bundle (of type `{}`) into",
+                        std::any::type_name::<T>()
+                    )
+                },
+            );
         }
     }
 }
@@ -1086,21 +1272,53 @@ fn try_insert(bundle: impl Bundle) -> impl EntityCommand {
     }
 }
 
-/// A [`Command`] that removes components from an entity.
+/// An [`EntityCommand`] that removes components from an entity.
 /// For a [`Bundle`] type `T`, this will remove any components in the bundle.
 /// Any components in the bundle that aren't found on the entity will be ignored.
-fn remove<T: Bundle>(entity: Entity, world: &mut World) {
-    if let Some(mut entity_mut) = world.get_entity_mut(entity) {
-        entity_mut.remove::<T>();
+/// By default, this will do nothing if the entity does not exist.
+fn remove<T: Bundle>(error_policy: Option<CommandErrorPolicy>) -> impl EntityCommand {
+    move |entity: Entity, world: &mut World| {
+        if let Some(mut entity_mut) = world.get_entity_mut(entity) {
+            entity_mut.remove::<T>();
+        } else {
+            handle_no_such_entity(
+                world,
+                entity,
+                error_policy,
+                CommandErrorPolicy::Ignore,
+                || {
+                    format!(
+                        "remove a bundle (of type `{}`) from",
+                        std::any::type_name::<T>()
+                    )
+                },
+            );
+        }
     }
 }
 
-/// A [`Command`] that removes components from an entity.
+/// An [`EntityCommand`] that removes components from an entity.
 /// For a [`Bundle`] type `T`, this will remove all components except those in the bundle.
 /// Any components in the bundle that aren't found on the entity will be ignored.
-fn retain<T: Bundle>(entity: Entity, world: &mut World) {
-    if let Some(mut entity_mut) = world.get_entity_mut(entity) {
-        entity_mut.retain::<T>();
+/// By default, this will do nothing if the entity does not exist.
+fn retain<T: Bundle>(error_policy: Option<CommandErrorPolicy>) -> impl EntityCommand {
+    move |entity: Entity, world: &mut World| {
+        if let Some(mut entity_mut) = world.get_entity_mut(entity) {
+            entity_mut.retain::<T>();
+        } else {
+            handle_no_such_entity(
+                world,
+                entity,
+                error_policy,
+                CommandErrorPolicy::Ignore,
+                || {
+                    format!(
+                        "retain a bundle (of type `{}`) on",
+                        std::any::type_name::<T>()
+                    )
+                },
+            );
+        }
     }
 }
 
@@ -1111,8 +1329,18 @@ fn init_resource<R: Resource + FromWorld>(world: &mut World) {
 }
 
 /// A [`Command`] that removes the [resource](Resource) `R` from the world.
-fn remove_resource<R: Resource>(world: &mut World) {
-    world.remove_resource::<R>();
+/// By default, this will do nothing if the resource does not exist.
+fn remove_resource<R: Resource>() -> impl Command {
+    move |world: &mut World| {
+        if world.remove_resource::<R>().is_none() {
+            CommandErrorPolicy::resolve(world, None, CommandErrorPolicy::Ignore).handle(
+                world,
+                CommandError::NoSuchResource {
+                    resource: std::any::type_name::<R>(),
+                },
+            );
+        }
+    }
 }
 
 /// A [`Command`] that inserts a [`Resource`] into the world.
@@ -1122,14 +1350,66 @@ fn insert_resource<R: Resource>(resource: R) -> impl Command {
     }
 }
 
+/// An [`EntityCommand`] that creates an [`Observer`] listening for events targeting the entity.
+/// By default, this will do nothing if the entity does not exist.
+fn observe<E: Event, B: Bundle, M>(
+    observer: impl IntoObserverSystem<E, B, M>,
+    error_policy: Option<CommandErrorPolicy>,
+) -> impl EntityCommand {
+    move |entity, world: &mut World| {
+        if let Some(mut entity) = world.get_entity_mut(entity) {
+            entity.observe(observer);
+        } else {
+            handle_no_such_entity(
+                world,
+                entity,
+                error_policy,
+                CommandErrorPolicy::Ignore,
+                || "observe".to_string(),
+            );
+        }
+    }
+}
+
 /// [`EntityCommand`] to log the components of a given entity. See [`EntityCommands::log_components`].
-fn log_components(entity: Entity, world: &mut World) {
-    let debug_infos: Vec<_> = world
-        .inspect_entity(entity)
-        .into_iter()
-        .map(|component_info| component_info.name())
-        .collect();
-    info!("Entity {:?}: {:?}", entity, debug_infos);
+/// By default, this will panic if the entity does not exist.
+fn log_components(error_policy: Option<CommandErrorPolicy>) -> impl EntityCommand {
+    move |entity: Entity, world: &mut World| {
+        if world.get_entity(entity).is_none() {
+            handle_no_such_entity(
+                world,
+                entity,
+                error_policy,
+                CommandErrorPolicy::Panic,
+                || "log the components of".to_string(),
+            );
+            return;
+        }
+        let debug_infos: Vec<_> = world
+            .inspect_entity(entity)
+            .into_iter()
+            .map(|component_info| component_info.name())
+            .collect();
+        info!("Entity {:?}: {:?}", entity, debug_infos);
+    }
+}
+
+/// Handles the [`CommandError::NoSuchEntity`] of a command that failed to `action` the missing
+/// `entity`, with `error_policy` if set, else the [`CommandErrorPolicy`] resource, else `default`.
+fn handle_no_such_entity(
+    world: &mut World,
+    entity: Entity,
+    error_policy: Option<CommandErrorPolicy>,
+    default: CommandErrorPolicy,
+    action: impl FnOnce() -> String,
+) {
+    CommandErrorPolicy::resolve(world, error_policy, default).handle(
+        world,
+        CommandError::NoSuchEntity {
+            entity,
+            action: action(),
+        },
+    );
 }
 
 #[cfg(test)]
@@ -1138,8 +1418,10 @@ mod tests {
     use crate::{
         self as bevy_ecs,
         component::Component,
-        system::{CommandQueue, Commands, Resource},
-        world::World,
+        entity::Entity,
+        event::Events,
+        system::{CommandError, CommandErrorPolicy, CommandQueue, Commands, Resource},
+        world::{EntityWorldMut, World},
     };
     use std::sync::{
         atomic::{AtomicUsize, Ordering},
@@ -1223,6 +1505,130 @@ mod tests {
         assert_eq!(results3, vec![(42u32, 0u64), (0u32, 42u64)]);
     }
 
+    #[test]
+    fn command_error_policies() {
+        let mut world = World::default();
+        let mut command_queue = CommandQueue::default();
+
+        // Neither of these should panic.
+        let entity = world.spawn_empty().id();
+        {
+            let mut commands = Commands::new(&mut command_queue, &world);
+            commands.entity(entity).despawn();
+            commands
+                .entity(entity)
+                .on_error(CommandErrorPolicy::Warn)
+                .insert(W(1u32));
+            commands
+                .entity(entity)
+                .on_error(CommandErrorPolicy::Ignore)
+                .insert(W(1u32));
+            commands.entity(entity).try_despawn();
+        }
+        command_queue.apply(&mut world);
+
+        world.insert_resource(CommandErrorPolicy::SendEvent);
+        world.init_resource::<Events<CommandError>>();
+        let entity = world.spawn_empty().id();
+        {
+            let mut commands = Commands::new(&mut command_queue, &world);
+            commands.entity(entity).despawn();
+            commands.entity(entity).insert(W(1u32));
+            commands.entity(entity).despawn();
+            // The policy of the entity takes precedence over the resource.
+            commands
+                .entity(entity)
+                .on_error(CommandErrorPolicy::Ignore)
+                .despawn();
+        }
+        command_queue.apply(&mut world);
+        let errors: Vec<_> = world
+            .resource::<Events<CommandError>>()
+            .iter_current_update_events()
+            .cloned()
+            .collect();
+        assert_eq!(errors.len(), 2);
+        let CommandError::NoSuchEntity { entity: e, action } = &errors[0] else {
+            panic!("Unexpected error {:?}", errors[0]);
+        };
+        assert_eq!(*e, entity);
+        assert!(action.starts_with("insert a bundle"));
+        assert_eq!(
+            errors[1],
+            CommandError::NoSuchEntity {
+                entity,
+                action: "despawn".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn command_error_policies_cover_entity_and_resource_commands() {
+        let mut world = World::default();
+        let mut command_queue = CommandQueue::default();
+        world.insert_resource(CommandErrorPolicy::SendEvent);
+        world.init_resource::<Events<CommandError>>();
+
+        let entity = world.spawn_empty().id();
+        {
+            let mut commands = Commands::new(&mut command_queue, &world);
+            commands.entity(entity).despawn();
+            commands.entity(entity).remove::<W<u32>>();
+            commands.entity(entity).retain::<W<u32>>();
+            commands.entity(entity).add(|_: EntityWorldMut| {});
+            commands.remove_resource::<W<u32>>();
+            // Custom commands are only checked once a policy is set for the entity.
+            commands
+                .entity(entity)
+                .on_error(CommandErrorPolicy::SendEvent)
+                .add(|_: Entity, _: &mut World| panic!("The entity doesn't exist"));
+            commands
+                .entity(entity)
+                .on_error(CommandErrorPolicy::Ignore)
+                .remove::<W<u32>>();
+        }
+        command_queue.apply(&mut world);
+        let errors: Vec<_> = world
+            .resource::<Events<CommandError>>()
+            .iter_current_update_events()
+            .cloned()
+            .collect();
+        let no_such_entity = |action: &str| CommandError::NoSuchEntity {
+            entity,
+            action: action.to_string(),
+        };
+        assert_eq!(
+            errors,
+            vec![
+                no_such_entity(&format!(
+                    "remove a bundle (of type `{}`) from",
+                    std::any::type_name::<W<u32>>()
+                )),
+                no_such_entity(&format!(
+                    "retain a bundle (of type `{}`) on",
+                    std::any::type_name::<W<u32>>()
+                )),
+                no_such_entity("apply a command to"),
+                CommandError::NoSuchResource {
+                    resource: std::any::type_name::<W<u32>>(),
+                },
+                no_such_entity("apply a command to"),
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "error[B0003]")]
+    fn insert_panics_by_default() {
+        let mut world = World::default();
+        let mut command_queue = CommandQueue::default();
+        let entity = world.spawn_empty().id();
+        let mut commands = Commands::new(&mut command_queue, &world);
+        commands.entity(entity).despawn();
+        commands.entity(entity).insert(W(1u32));
+        command_queue.apply(&mut world);
+    }
+
     #[test]
     fn remove_components() {
         let mut world = World::default();
@@ -0,0 +1,28 @@
+//! Events triggered by the lifecycle of components, which can be observed with
+//! [`Observer`](crate::observer::Observer)s.
+
+use crate as bevy_ecs;
+use crate::event::Event;
+
+/// Trigger emitted when a component is added to an entity that didn't have it.
+///
+/// See [`ComponentHooks::on_add`](crate::component::ComponentHooks::on_add) for the equivalent
+/// hook, which runs before the observers.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct OnAdd;
+
+/// Trigger emitted when a component is inserted on an entity, whether or not it already had it.
+///
+/// See [`ComponentHooks::on_insert`](crate::component::ComponentHooks::on_insert) for the
+/// equivalent hook, which runs before the observers.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct OnInsert;
+
+/// Trigger emitted when a component is removed from an entity, including when the entity is
+/// despawned.
+///
+/// The observers run before the component is removed, so it can still be read.
+/// See [`ComponentHooks::on_remove`](crate::component::ComponentHooks::on_remove) for the
+/// equivalent hook, which runs after the observers.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct OnRemove;
@@ -0,0 +1,400 @@
+use std::{any::TypeId, ops::Deref};
+
+use bevy_ptr::PtrMut;
+
+use crate::{
+    archetype::Archetype,
+    change_detection::MutUntyped,
+    component::{Component, ComponentId, MissingRequiredComponents},
+    entity::Entity,
+    event::{Event, EventId, Events, SendBatchIds},
+    observer::{Observers, TriggerTargets},
+    system::{Commands, Resource},
+};
+
+use super::{
+    unsafe_world_cell::{UnsafeEntityCell, UnsafeWorldCell},
+    EntityMut, Mut, OnRemove, World,
+};
+
+/// A [`World`] reference that disallows structural ECS changes.
+///
+/// This includes spawning and despawning entities, inserting and removing components, and
+/// initializing resources. Those changes can be queued with [`DeferredWorld::commands`] instead,
+/// and are applied once the hook or observer that was given this world returns.
+pub struct DeferredWorld<'w> {
+    // SAFETY: Implementors must not use this reference to make structural changes
+    world: UnsafeWorldCell<'w>,
+}
+
+impl<'w> Deref for DeferredWorld<'w> {
+    type Target = World;
+
+    fn deref(&self) -> &Self::Target {
+        // SAFETY: Structural changes cannot be made through &World, and `&self` ensures that no
+        // mutable borrows of world data are live
+        unsafe { self.world.world() }
+    }
+}
+
+impl<'w> UnsafeWorldCell<'w> {
+    /// Turns this [`UnsafeWorldCell`] into a [`DeferredWorld`].
+    ///
+    /// # Safety
+    /// The [`UnsafeWorldCell`] must have mutable access to the whole world, and no other
+    /// borrows of world data may be live while the [`DeferredWorld`] is used.
+    #[inline]
+    pub unsafe fn into_deferred(self) -> DeferredWorld<'w> {
+        DeferredWorld { world: self }
+    }
+}
+
+impl<'w> From<&'w mut World> for DeferredWorld<'w> {
+    fn from(world: &'w mut World) -> DeferredWorld<'w> {
+        DeferredWorld {
+            world: world.as_unsafe_world_cell(),
+        }
+    }
+}
+
+impl<'w> DeferredWorld<'w> {
+    /// Reborrows this [`DeferredWorld`] with a shorter lifetime.
+    #[inline]
+    pub fn reborrow(&mut self) -> DeferredWorld<'_> {
+        DeferredWorld { world: self.world }
+    }
+
+    /// Creates a [`Commands`] instance that pushes to the world's command queue.
+    ///
+    /// The commands are applied once the current hook or observer returns.
+    #[inline]
+    pub fn commands(&mut self) -> Commands<'_, '_> {
+        // SAFETY: &mut self ensures that there are no outstanding accesses to the queue
+        let queue = unsafe { self.world.get_command_queue() };
+        Commands::new_from_entities(queue, self.world.entities())
+    }
+
+    /// Retrieves a mutable reference to the given `entity`'s [`Component`] of the given type.
+    /// Returns `None` if the `entity` does not have a [`Component`] of the given type.
+    #[inline]
+    pub fn get_mut<T: Component>(&mut self, entity: Entity) -> Option<Mut<'_, T>> {
+        // SAFETY: &mut self ensures that there are no outstanding accesses to the component
+        unsafe { self.world.get_entity(entity)?.get_mut() }
+    }
+
+    /// Retrieves an [`EntityMut`] that exposes read and write operations for the given `entity`.
+    /// Returns `None` if the `entity` does not exist.
+    #[inline]
+    pub fn get_entity_mut(&mut self, entity: Entity) -> Option<EntityMut<'_>> {
+        let location = self.entities.get(entity)?;
+        let cell = UnsafeEntityCell::new(self.world, entity, location);
+        // SAFETY: &mut self ensures that there are no outstanding accesses to the entity's
+        // components
+        Some(unsafe { EntityMut::new(cell) })
+    }
+
+    /// Retrieves an [`EntityMut`] that exposes read and write operations for the given `entity`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `entity` does not exist.
+    #[inline]
+    #[track_caller]
+    pub fn entity_mut(&mut self, entity: Entity) -> EntityMut<'_> {
+        #[inline(never)]
+        #[cold]
+        #[track_caller]
+        fn panic_no_entity(entity: Entity) -> ! {
+            panic!("Entity {entity:?} does not exist");
+        }
+
+        match self.get_entity_mut(entity) {
+            Some(entity) => entity,
+            None => panic_no_entity(entity),
+        }
+    }
+
+    /// Gets a mutable reference to the resource of the given type.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the resource does not exist.
+    /// Use [`get_resource_mut`](DeferredWorld::get_resource_mut) instead if you want to handle
+    /// this case.
+    #[inline]
+    #[track_caller]
+    pub fn resource_mut<R: Resource>(&mut self) -> Mut<'_, R> {
+        match self.get_resource_mut() {
+            Some(x) => x,
+            None => panic!(
+                "Requested resource {} does not exist in the `World`.
+                Did you forget to add it using `app.insert_resource` / `app.init_resource`?
+                Resources are also implicitly added via `app.add_event`,
+                and can be added by plugins.",
+                std::any::type_name::<R>()
+            ),
+        }
+    }
+
+    /// Gets a mutable reference to the resource of the given type if it exists.
+    #[inline]
+    pub fn get_resource_mut<R: Resource>(&mut self) -> Option<Mut<'_, R>> {
+        // SAFETY: &mut self ensures that there are no outstanding accesses to the resource
+        unsafe { self.world.get_resource_mut() }
+    }
+
+    /// Gets a mutable reference to the non-send resource of the given type.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the resource does not exist, or if this isn't called from the thread that
+    /// inserted it.
+    #[inline]
+    #[track_caller]
+    pub fn non_send_resource_mut<R: 'static>(&mut self) -> Mut<'_, R> {
+        match self.get_non_send_resource_mut() {
+            Some(x) => x,
+            None => panic!(
+                "Requested non-send resource {} does not exist in the `World`.
+                Did you forget to add it using `app.insert_non_send_resource` / `app.init_non_send_resource`?
+                Non-send resources can also be be added by plugins.",
+                std::any::type_name::<R>()
+            ),
+        }
+    }
+
+    /// Gets a mutable reference to the non-send resource of the given type, if it exists.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this isn't called from the thread that inserted the resource.
+    #[inline]
+    pub fn get_non_send_resource_mut<R: 'static>(&mut self) -> Option<Mut<'_, R>> {
+        // SAFETY: &mut self ensures that there are no outstanding accesses to the resource
+        unsafe { self.world.get_non_send_resource_mut() }
+    }
+
+    /// Sends an [`Event`].
+    /// This method returns the [ID](`EventId`) of the sent `event`,
+    /// or [`None`] if the `event` could not be sent.
+    #[inline]
+    pub fn send_event<E: Event>(&mut self, event: E) -> Option<EventId<E>> {
+        self.send_event_batch(std::iter::once(event))?.next()
+    }
+
+    /// Sends a batch of [`Event`]s from an iterator.
+    /// This method returns the [IDs](`EventId`) of the sent `events`,
+    /// or [`None`] if the `event` could not be sent.
+    #[inline]
+    pub fn send_event_batch<E: Event>(
+        &mut self,
+        events: impl IntoIterator<Item = E>,
+    ) -> Option<SendBatchIds<E>> {
+        let Some(mut events_resource) = self.get_resource_mut::<Events<E>>() else {
+            bevy_utils::tracing::error!(
+                "Unable to send event `{}`\n\tEvent must be added to the app with `add_event()`\n\thttps://docs.rs/bevy/*/bevy/app/struct.App.html#method.add_event ",
+                std::any::type_name::<E>()
+            );
+            return None;
+        };
+        Some(events_resource.send_batch(events))
+    }
+
+    /// Gets a pointer to the resource with the id [`ComponentId`] if it exists.
+    /// The returned pointer may be used to modify the resource, as long as the mutable borrow
+    /// of the [`DeferredWorld`] is still valid.
+    ///
+    /// **You should prefer to use the typed API [`DeferredWorld::get_resource_mut`] where possible and only
+    /// use this in cases where the actual types are not known at compile time.**
+    #[inline]
+    pub fn get_resource_mut_by_id(&mut self, component_id: ComponentId) -> Option<MutUntyped<'_>> {
+        // SAFETY: &mut self ensures that there are no outstanding accesses to the resource
+        unsafe { self.world.get_resource_mut_by_id(component_id) }
+    }
+
+    /// Retrieves a mutable untyped reference to the given `entity`'s [`Component`] of the given [`ComponentId`].
+    /// Returns `None` if the `entity` does not have a [`Component`] of the given type.
+    ///
+    /// **You should prefer to use the typed API [`DeferredWorld::get_mut`] where possible and only
+    /// use this in cases where the actual types are not known at compile time.**
+    #[inline]
+    pub fn get_mut_by_id(
+        &mut self,
+        entity: Entity,
+        component_id: ComponentId,
+    ) -> Option<MutUntyped<'_>> {
+        // SAFETY: &mut self ensures that there are no outstanding accesses to the component
+        unsafe { self.world.get_entity(entity)?.get_mut_by_id(component_id) }
+    }
+
+    /// Triggers all `on_add` hooks for [`ComponentId`] in target.
+    ///
+    /// # Safety
+    /// Caller must ensure [`ComponentId`] in target exist in self.
+    #[inline]
+    pub(crate) unsafe fn trigger_on_add(
+        &mut self,
+        entity: Entity,
+        targets: impl Iterator<Item = ComponentId>,
+    ) {
+        let mut required_components = Vec::new();
+        for component_id in targets {
+            // SAFETY: Caller ensures that these components exist
+            let info = unsafe { self.components().get_info_unchecked(component_id) };
+            if let Some(hook) = info.hooks().on_add {
+                hook(DeferredWorld { world: self.world }, entity, component_id);
+            }
+            if !info.required_components().is_empty() {
+                required_components.push(info.required_components().clone());
+            }
+        }
+        if !required_components.is_empty() {
+            // Inserting components is a structural change, so it's deferred like the commands of
+            // the hooks.
+            self.commands().add(move |world: &mut World| {
+                if !world.entities().contains(entity) {
+                    return;
+                }
+                let mut missing = MissingRequiredComponents::new(entity);
+                for required_components in &required_components {
+                    required_components.collect_missing(world, &mut missing);
+                }
+                missing.insert(world);
+            });
+        }
+    }
+
+    /// Triggers all `on_insert` hooks for [`ComponentId`] in target.
+    ///
+    /// # Safety
+    /// Caller must ensure [`ComponentId`] in target exist in self.
+    #[inline]
+    pub(crate) unsafe fn trigger_on_insert(
+        &mut self,
+        entity: Entity,
+        targets: impl Iterator<Item = ComponentId>,
+    ) {
+        for component_id in targets {
+            // SAFETY: Caller ensures that these components exist
+            let hooks = unsafe { self.components().get_info_unchecked(component_id) }.hooks();
+            if let Some(hook) = hooks.on_insert {
+                hook(DeferredWorld { world: self.world }, entity, component_id);
+            }
+        }
+    }
+
+    /// Triggers all `on_remove` hooks for [`ComponentId`] in target.
+    ///
+    /// # Safety
+    /// Caller must ensure [`ComponentId`] in target exist in self.
+    #[inline]
+    pub(crate) unsafe fn trigger_on_remove(
+        &mut self,
+        entity: Entity,
+        targets: impl Iterator<Item = ComponentId>,
+    ) {
+        for component_id in targets {
+            // SAFETY: Caller ensures that these components exist
+            let hooks = unsafe { self.components().get_info_unchecked(component_id) }.hooks();
+            if let Some(hook) = hooks.on_remove {
+                hook(DeferredWorld { world: self.world }, entity, component_id);
+            }
+        }
+    }
+
+    /// Triggers the [`OnRemove`] observers and then the `on_remove` hooks of the `components` of
+    /// `entity`, which is in `archetype`, before they're removed.
+    ///
+    /// # Safety
+    /// Caller must ensure that `entity` is in `archetype`, which contains the `components`.
+    #[inline]
+    pub(crate) unsafe fn trigger_on_remove_components(
+        &mut self,
+        archetype: &Archetype,
+        entity: Entity,
+        components: impl Iterator<Item = ComponentId> + Clone,
+    ) {
+        if archetype.has_remove_observer() {
+            // SAFETY: Caller ensures that the components exist
+            unsafe {
+                self.trigger_observers(TypeId::of::<OnRemove>(), entity, components.clone());
+            }
+        }
+        if archetype.has_remove_hook() {
+            // SAFETY: Caller ensures that the components exist
+            unsafe { self.trigger_on_remove(entity, components) };
+        }
+    }
+
+    /// Triggers all event observers for [`ComponentId`] in target.
+    ///
+    /// # Safety
+    /// Caller must ensure `event` has no data, or that it's a lifecycle event whose observers
+    /// expect a unit struct.
+    #[inline]
+    pub(crate) unsafe fn trigger_observers(
+        &mut self,
+        event: TypeId,
+        entity: Entity,
+        components: impl Iterator<Item = ComponentId>,
+    ) {
+        // Lifecycle events are unit structs, so any well-aligned non-null pointer is a valid
+        // pointer to them.
+        let mut data = ();
+        // SAFETY: Caller ensures that the observers of `event` expect a unit struct
+        unsafe {
+            Observers::invoke(
+                self.reborrow(),
+                event,
+                entity,
+                components,
+                PtrMut::from(&mut data),
+            );
+        }
+    }
+
+    /// Triggers all event observers for [`ComponentId`] in target, with `data`.
+    ///
+    /// # Safety
+    /// Caller must ensure `E` is accessible as the type represented by `event`
+    #[inline]
+    pub(crate) unsafe fn trigger_observers_with_data<E>(
+        &mut self,
+        event: TypeId,
+        entity: Entity,
+        components: impl Iterator<Item = ComponentId>,
+        data: &mut E,
+    ) {
+        // SAFETY: Caller ensures that `data` matches the observers of `event`
+        unsafe {
+            Observers::invoke(
+                self.reborrow(),
+                event,
+                entity,
+                components,
+                PtrMut::from(data),
+            );
+        }
+    }
+
+    /// Sends a "global" [`Trigger`](crate::observer::Trigger) without any targets.
+    ///
+    /// The trigger runs its observers once the current hook or observer returns.
+    pub fn trigger<E: Event>(&mut self, event: E) {
+        self.commands().trigger(event);
+    }
+
+    /// Sends a [`Trigger`](crate::observer::Trigger) with the given `targets`.
+    ///
+    /// The trigger runs its observers once the current hook or observer returns.
+    pub fn trigger_targets<E: Event>(&mut self, event: E, targets: impl TriggerTargets) {
+        self.commands().trigger_targets(event, targets);
+    }
+
+    /// Gets an [`UnsafeWorldCell`] containing the underlying world, which must only be used to
+    /// make non-structural ECS changes.
+    #[inline]
+    pub(crate) fn as_unsafe_world_cell(&mut self) -> UnsafeWorldCell<'_> {
+        self.world
+    }
+}
@@ -1,12 +1,15 @@
 use crate::{
     archetype::{Archetype, ArchetypeId, Archetypes},
-    bundle::{Bundle, BundleInfo, BundleInserter, DynamicBundle},
+    bundle::{Bundle, BundleId, BundleInfo, BundleInserter, DynamicBundle},
     change_detection::MutUntyped,
     component::{Component, ComponentId, ComponentTicks, Components, StorageType},
     entity::{Entities, Entity, EntityLocation},
+    event::Event,
+    observer::{Observer, Observers},
     query::{Access, DebugCheckedUnwrap},
     removal_detection::RemovedComponentEvents,
     storage::Storages,
+    system::IntoObserverSystem,
     world::{Mut, World},
 };
 use bevy_ptr::{OwningPtr, Ptr};
@@ -475,7 +478,19 @@ pub struct EntityWorldMut<'w> {
 }
 
 impl<'w> EntityWorldMut<'w> {
+    /// Panics if the entity was despawned, which can happen through hooks and observers run by
+    /// the structural changes made with this [`EntityWorldMut`].
+    #[track_caller]
+    fn assert_not_despawned(&self) {
+        if self.location.archetype_id == ArchetypeId::INVALID {
+            panic!(
+                "Entity {:?} does not exist, it was despawned by a hook or an observer",
+                self.entity
+            );
+        }
+    }
     fn as_unsafe_entity_cell_readonly(&self) -> UnsafeEntityCell<'_> {
+        self.assert_not_despawned();
         UnsafeEntityCell::new(
             self.world.as_unsafe_world_cell_readonly(),
             self.entity,
@@ -483,6 +498,7 @@ impl<'w> EntityWorldMut<'w> {
         )
     }
     fn as_unsafe_entity_cell(&mut self) -> UnsafeEntityCell<'_> {
+        self.assert_not_despawned();
         UnsafeEntityCell::new(
             self.world.as_unsafe_world_cell(),
             self.entity,
@@ -490,6 +506,7 @@ impl<'w> EntityWorldMut<'w> {
         )
     }
     fn into_unsafe_entity_cell(self) -> UnsafeEntityCell<'w> {
+        self.assert_not_despawned();
         UnsafeEntityCell::new(
             self.world.as_unsafe_world_cell(),
             self.entity,
@@ -535,6 +552,7 @@ impl<'w> EntityWorldMut<'w> {
     /// Returns the archetype that the current entity belongs to.
     #[inline]
     pub fn archetype(&self) -> &Archetype {
+        self.assert_not_despawned();
         &self.world.archetypes[self.location.archetype_id]
     }
 
@@ -652,24 +670,31 @@ impl<'w> EntityWorldMut<'w> {
     ///
     /// This will overwrite any previous value(s) of the same component type.
     pub fn insert<T: Bundle>(&mut self, bundle: T) -> &mut Self {
+        self.assert_not_despawned();
         let change_tick = self.world.change_tick();
+        let old_archetype_id = self.location.archetype_id;
         let bundle_info = self
             .world
             .bundles
             .init_info::<T>(&mut self.world.components, &mut self.world.storages);
+        let bundle_id = bundle_info.id();
         let mut bundle_inserter = bundle_info.get_bundle_inserter(
             &mut self.world.entities,
             &mut self.world.archetypes,
             &self.world.components,
             &mut self.world.storages,
-            self.location.archetype_id,
+            &self.world.observers,
+            old_archetype_id,
             change_tick,
         );
         // SAFETY: location matches current entity. `T` matches `bundle_info`
         unsafe {
             self.location = bundle_inserter.insert(self.entity, self.location, bundle);
         }
-
+        self.world
+            .trigger_on_insert_bundle(self.entity, old_archetype_id, bundle_id);
+        self.world.flush();
+        self.update_location();
         self
     }
 
@@ -688,18 +713,22 @@ impl<'w> EntityWorldMut<'w> {
         component_id: ComponentId,
         component: OwningPtr<'_>,
     ) -> &mut Self {
+        self.assert_not_despawned();
         let change_tick = self.world.change_tick();
+        let old_archetype_id = self.location.archetype_id;
 
         let bundles = &mut self.world.bundles;
         let components = &mut self.world.components;
 
         let (bundle_info, storage_type) = bundles.init_component_info(components, component_id);
+        let bundle_id = bundle_info.id();
         let bundle_inserter = bundle_info.get_bundle_inserter(
             &mut self.world.entities,
             &mut self.world.archetypes,
             &self.world.components,
             &mut self.world.storages,
-            self.location.archetype_id,
+            &self.world.observers,
+            old_archetype_id,
             change_tick,
         );
 
@@ -710,7 +739,10 @@ impl<'w> EntityWorldMut<'w> {
             Some(component).into_iter(),
             Some(storage_type).into_iter(),
         );
-
+        self.world
+            .trigger_on_insert_bundle(self.entity, old_archetype_id, bundle_id);
+        self.world.flush();
+        self.update_location();
         self
     }
 
@@ -731,18 +763,22 @@ impl<'w> EntityWorldMut<'w> {
         component_ids: &[ComponentId],
         iter_components: I,
     ) -> &mut Self {
+        self.assert_not_despawned();
         let change_tick = self.world.change_tick();
+        let old_archetype_id = self.location.archetype_id;
 
         let bundles = &mut self.world.bundles;
         let components = &mut self.world.components;
 
         let (bundle_info, storage_types) = bundles.init_dynamic_info(components, component_ids);
+        let bundle_id = bundle_info.id();
         let bundle_inserter = bundle_info.get_bundle_inserter(
             &mut self.world.entities,
             &mut self.world.archetypes,
             &self.world.components,
             &mut self.world.storages,
-            self.location.archetype_id,
+            &self.world.observers,
+            old_archetype_id,
             change_tick,
         );
 
@@ -753,7 +789,10 @@ impl<'w> EntityWorldMut<'w> {
             iter_components,
             storage_types.iter().cloned(),
         );
-
+        self.world
+            .trigger_on_insert_bundle(self.entity, old_archetype_id, bundle_id);
+        self.world.flush();
+        self.update_location();
         self
     }
 
@@ -764,21 +803,22 @@ impl<'w> EntityWorldMut<'w> {
     // TODO: BundleRemover?
     #[must_use]
     pub fn take<T: Bundle>(&mut self) -> Option<T> {
-        let archetypes = &mut self.world.archetypes;
-        let storages = &mut self.world.storages;
-        let components = &mut self.world.components;
-        let entities = &mut self.world.entities;
-        let removed_components = &mut self.world.removed_components;
+        self.assert_not_despawned();
+        let world = &mut *self.world;
+        let storages = &mut world.storages;
+        let components = &mut world.components;
 
-        let bundle_info = self.world.bundles.init_info::<T>(components, storages);
+        let bundle_info = world.bundles.init_info::<T>(components, storages);
+        let bundle_id = bundle_info.id();
         let old_location = self.location;
         // SAFETY: `archetype_id` exists because it is referenced in the old `EntityLocation` which is valid,
         // components exist in `bundle_info` because `Bundles::init_info` initializes a `BundleInfo` containing all components of the bundle type `T`
         let new_archetype_id = unsafe {
             remove_bundle_from_archetype(
-                archetypes,
+                &mut world.archetypes,
                 storages,
                 components,
+                &world.observers,
                 old_location.archetype_id,
                 bundle_info,
                 false,
@@ -789,6 +829,17 @@ impl<'w> EntityWorldMut<'w> {
             return None;
         }
 
+        self.world
+            .trigger_on_remove_bundle(self.entity, old_location, bundle_id);
+
+        let archetypes = &mut self.world.archetypes;
+        let storages = &mut self.world.storages;
+        let components = &mut self.world.components;
+        let entities = &mut self.world.entities;
+        let removed_components = &mut self.world.removed_components;
+        // SAFETY: the bundle was initialized above
+        let bundle_info = unsafe { self.world.bundles.get(bundle_id).debug_checked_unwrap() };
+
         let mut bundle_components = bundle_info.components().iter().cloned();
         let entity = self.entity;
         // SAFETY: bundle components are iterated in order, which guarantees that the component type
@@ -824,7 +875,8 @@ impl<'w> EntityWorldMut<'w> {
                 new_archetype_id,
             );
         }
-
+        self.world.flush();
+        self.update_location();
         Some(result)
     }
 
@@ -927,6 +979,7 @@ impl<'w> EntityWorldMut<'w> {
         archetypes: &mut Archetypes,
         storages: &mut Storages,
         components: &Components,
+        observers: &Observers,
         entities: &mut Entities,
         removed_components: &mut RemovedComponentEvents,
     ) {
@@ -937,6 +990,7 @@ impl<'w> EntityWorldMut<'w> {
                 archetypes,
                 storages,
                 components,
+                observers,
                 old_location.archetype_id,
                 bundle_info,
                 true,
@@ -981,36 +1035,48 @@ impl<'w> EntityWorldMut<'w> {
         }
     }
 
-    /// Removes any components in the [`Bundle`] from the entity.
-    ///
-    /// See [`EntityCommands::remove`](crate::system::EntityCommands::remove) for more details.
-    // TODO: BundleRemover?
-    pub fn remove<T: Bundle>(&mut self) -> &mut Self {
-        let archetypes = &mut self.world.archetypes;
-        let storages = &mut self.world.storages;
-        let components = &mut self.world.components;
-        let entities = &mut self.world.entities;
-        let removed_components = &mut self.world.removed_components;
-
-        let bundle_info = self.world.bundles.init_info::<T>(components, storages);
+    /// Removes the components of the bundle `bundle_id` that the entity has, after triggering
+    /// their `on_remove` hooks and [`OnRemove`](crate::world::OnRemove) observers.
+    fn remove_bundle(&mut self, bundle_id: BundleId) {
         let old_location = self.location;
+        self.world
+            .trigger_on_remove_bundle(self.entity, old_location, bundle_id);
 
-        // SAFETY: Components exist in `bundle_info` because `Bundles::init_info`
-        // initializes a `BundleInfo` containing all components of the bundle type `T`.
+        let world = &mut *self.world;
+        // SAFETY: the caller initialized the bundle
+        let bundle_info = unsafe { world.bundles.get(bundle_id).debug_checked_unwrap() };
+        // SAFETY: Components exist in `bundle_info` because the bundle was initialized, which
+        // initializes all of its components.
         unsafe {
             Self::remove_bundle_info(
                 self.entity,
                 &mut self.location,
                 old_location,
                 bundle_info,
-                archetypes,
-                storages,
-                components,
-                entities,
-                removed_components,
+                &mut world.archetypes,
+                &mut world.storages,
+                &world.components,
+                &world.observers,
+                &mut world.entities,
+                &mut world.removed_components,
             );
         }
+        self.world.flush();
+        self.update_location();
+    }
 
+    /// Removes any components in the [`Bundle`] from the entity.
+    ///
+    /// See [`EntityCommands::remove`](crate::system::EntityCommands::remove) for more details.
+    // TODO: BundleRemover?
+    pub fn remove<T: Bundle>(&mut self) -> &mut Self {
+        self.assert_not_despawned();
+        let bundle_id = self
+            .world
+            .bundles
+            .init_info::<T>(&mut self.world.components, &mut self.world.storages)
+            .id();
+        self.remove_bundle(bundle_id);
         self
     }
 
@@ -1018,42 +1084,25 @@ impl<'w> EntityWorldMut<'w> {
     ///
     /// See [`EntityCommands::retain`](crate::system::EntityCommands::retain) for more details.
     pub fn retain<T: Bundle>(&mut self) -> &mut Self {
+        self.assert_not_despawned();
         let archetypes = &mut self.world.archetypes;
         let storages = &mut self.world.storages;
         let components = &mut self.world.components;
-        let entities = &mut self.world.entities;
-        let removed_components = &mut self.world.removed_components;
 
         let retained_bundle_info = self.world.bundles.init_info::<T>(components, storages);
-        let old_location = self.location;
-        let old_archetype = &mut archetypes[old_location.archetype_id];
+        let old_archetype = &mut archetypes[self.location.archetype_id];
 
         let to_remove = &old_archetype
             .components()
             .filter(|c| !retained_bundle_info.components().contains(c))
             .collect::<Vec<_>>();
-        let remove_bundle_info = self
+        let remove_bundle_id = self
             .world
             .bundles
             .init_dynamic_info(components, to_remove)
-            .0;
-
-        // SAFETY: Components exist in `remove_bundle_info` because `Bundles::init_dynamic_info`
-        // initializes a `BundleInfo` containing all components in the to_remove Bundle.
-        unsafe {
-            Self::remove_bundle_info(
-                self.entity,
-                &mut self.location,
-                old_location,
-                remove_bundle_info,
-                archetypes,
-                storages,
-                components,
-                entities,
-                removed_components,
-            );
-        }
-
+            .0
+            .id();
+        self.remove_bundle(remove_bundle_id);
         self
     }
 
@@ -1061,9 +1110,11 @@ impl<'w> EntityWorldMut<'w> {
     ///
     /// See [`World::despawn`] for more details.
     pub fn despawn(self) {
+        self.assert_not_despawned();
         debug!("Despawning entity {:?}", self.entity);
         let world = self.world;
-        world.flush();
+        world.trigger_on_despawn(self.entity, self.location);
+        world.flush_entities();
         let location = world
             .entities
             .free(self.entity)
@@ -1123,6 +1174,7 @@ impl<'w> EntityWorldMut<'w> {
             world.archetypes[moved_location.archetype_id]
                 .set_entity_table_row(moved_location.archetype_row, table_row);
         }
+        world.flush();
     }
 
     /// Gets read-only access to the world that the current entity belongs to.
@@ -1198,8 +1250,29 @@ impl<'w> EntityWorldMut<'w> {
     ///
     /// This is *only* required when using the unsafe function [`EntityWorldMut::world_mut`],
     /// which enables the location to change.
+    ///
+    /// If the entity was despawned, any further use of this [`EntityWorldMut`] panics.
     pub fn update_location(&mut self) {
-        self.location = self.world.entities().get(self.entity).unwrap();
+        self.location = self
+            .world
+            .entities()
+            .get(self.entity)
+            .unwrap_or(EntityLocation::INVALID);
+    }
+
+    /// Creates an [`Observer`] listening for events of type `E` targeting this entity.
+    ///
+    /// The observer is despawned along with the entity.
+    pub fn observe<E: Event, B: Bundle, M>(
+        &mut self,
+        observer: impl IntoObserverSystem<E, B, M>,
+    ) -> &mut Self {
+        self.assert_not_despawned();
+        self.world
+            .spawn(Observer::new(observer).with_entity(self.entity));
+        self.world.flush();
+        self.update_location();
+        self
     }
 
     /// Gets an Entry into the world for this entity and component for in-place manipulation.
@@ -2116,6 +2189,7 @@ unsafe fn remove_bundle_from_archetype(
     archetypes: &mut Archetypes,
     storages: &mut Storages,
     components: &Components,
+    observers: &Observers,
     archetype_id: ArchetypeId,
     bundle_info: &BundleInfo,
     intersection: bool,
@@ -2184,11 +2258,16 @@ unsafe fn remove_bundle_from_archetype(
             };
         }
 
-        let new_archetype_id = archetypes.get_id_or_insert(
-            next_table_id,
-            next_table_components,
-            next_sparse_set_components,
-        );
+        // SAFETY: all components in the next archetype exist
+        let new_archetype_id = unsafe {
+            archetypes.get_id_or_insert(
+                components,
+                observers,
+                next_table_id,
+                next_table_components,
+                next_sparse_set_components,
+            )
+        };
         Some(new_archetype_id)
     };
     let current_archetype = &mut archetypes[archetype_id];
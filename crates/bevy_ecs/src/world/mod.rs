@@ -1,5 +1,7 @@
 //! Defines the [`World`] and APIs for accessing it directly.
 
+mod component_constants;
+mod deferred_world;
 mod entity_ref;
 pub mod error;
 mod spawn_batch;
@@ -7,6 +9,8 @@ pub mod unsafe_world_cell;
 mod world_cell;
 
 pub use crate::change_detection::{Mut, Ref, CHECK_TICK_THRESHOLD};
+pub use component_constants::*;
+pub use deferred_world::DeferredWorld;
 pub use entity_ref::{
     EntityMut, EntityRef, EntityWorldMut, Entry, FilteredEntityMut, FilteredEntityRef,
     OccupiedEntry, VacantEntry,
@@ -15,20 +19,25 @@ pub use spawn_batch::*;
 pub use world_cell::*;
 
 use crate::{
-    archetype::{ArchetypeComponentId, ArchetypeId, ArchetypeRow, Archetypes},
-    bundle::{Bundle, BundleInserter, BundleSpawner, Bundles},
+    archetype::{
+        ArchetypeComponentId, ArchetypeFlags, ArchetypeId, ArchetypeRow, Archetypes,
+        ComponentStatus,
+    },
+    bundle::{Bundle, BundleId, BundleInserter, BundleSpawner, Bundles},
     change_detection::{MutUntyped, TicksMut},
     component::{
-        Component, ComponentDescriptor, ComponentId, ComponentInfo, ComponentTicks, Components,
-        Tick,
+        Component, ComponentDescriptor, ComponentHooks, ComponentId, ComponentInfo, ComponentTicks,
+        Components, RequiredComponents, Tick,
     },
     entity::{AllocAtWithoutReplacement, Entities, Entity, EntityLocation},
+    entity_disabling::Disabled,
     event::{Event, EventId, Events, SendBatchIds},
+    observer::Observers,
     query::{DebugCheckedUnwrap, QueryData, QueryEntityError, QueryFilter, QueryState},
     removal_detection::RemovedComponentEvents,
     schedule::{Schedule, ScheduleLabel, Schedules},
     storage::{ResourceData, Storages},
-    system::{Res, Resource},
+    system::{CommandQueue, Commands, Res, Resource},
     world::error::TryRunScheduleError,
 };
 use bevy_ptr::{OwningPtr, Ptr};
@@ -77,16 +86,24 @@ pub struct World {
     pub(crate) change_tick: AtomicU32,
     pub(crate) last_change_tick: Tick,
     pub(crate) last_check_tick: Tick,
+    pub(crate) observers: Observers,
+    pub(crate) command_queue: CommandQueue,
+    /// The [`ComponentId`] of [`Disabled`], which is registered up front so that every query
+    /// can skip disabled entities.
+    disabled_component_id: ComponentId,
 }
 
 impl Default for World {
     fn default() -> Self {
+        let mut components = Components::default();
+        let mut storages = Storages::default();
+        let disabled_component_id = components.init_component::<Disabled>(&mut storages);
         Self {
             id: WorldId::new().expect("More `bevy` `World`s have been created than is supported"),
             entities: Entities::new(),
-            components: Default::default(),
+            components,
             archetypes: Archetypes::new(),
-            storages: Default::default(),
+            storages,
             bundles: Default::default(),
             removed_components: Default::default(),
             archetype_component_access: Default::default(),
@@ -95,6 +112,9 @@ impl Default for World {
             change_tick: AtomicU32::new(1),
             last_change_tick: Tick::new(0),
             last_check_tick: Tick::new(0),
+            observers: Observers::default(),
+            command_queue: CommandQueue::default(),
+            disabled_component_id,
         }
     }
 }
@@ -183,11 +203,66 @@ impl World {
         WorldCell::new(self)
     }
 
+    /// Returns the [`ComponentId`] of the [`Disabled`] component, which queries skip unless
+    /// they explicitly mention it.
+    #[inline]
+    pub fn disabled_component_id(&self) -> ComponentId {
+        self.disabled_component_id
+    }
+
     /// Initializes a new [`Component`] type and returns the [`ComponentId`] created for it.
     pub fn init_component<T: Component>(&mut self) -> ComponentId {
         self.components.init_component::<T>(&mut self.storages)
     }
 
+    /// Returns a mutable reference to the [`ComponentHooks`] for a [`Component`] type.
+    ///
+    /// Will panic if `T` exists in any archetypes.
+    pub fn register_component_hooks<T: Component>(&mut self) -> &mut ComponentHooks {
+        let index = self.init_component::<T>();
+        assert!(
+            !self.archetypes.archetypes.iter().any(|a| a.contains(index)),
+            "Components hooks cannot be modified if the component already exists in an archetype, use init_component if {} may already be in use",
+            std::any::type_name::<T>()
+        );
+        // SAFETY: We just created this component
+        unsafe { self.components.get_hooks_mut(index).debug_checked_unwrap() }
+    }
+
+    /// Returns a mutable reference to the [`RequiredComponents`] of a [`Component`] type.
+    ///
+    /// Will panic if `T` exists in any archetypes.
+    pub fn register_required_components<T: Component>(&mut self) -> &mut RequiredComponents {
+        let index = self.init_component::<T>();
+        assert!(
+            !self.archetypes.archetypes.iter().any(|a| a.contains(index)),
+            "Required components of {} cannot be registered once it has been added to an entity, register them before spawning it",
+            std::any::type_name::<T>()
+        );
+        // SAFETY: We just created this component
+        unsafe {
+            self.components
+                .get_required_components_mut(index)
+                .debug_checked_unwrap()
+        }
+    }
+
+    /// Returns a mutable reference to the [`ComponentHooks`] for a [`Component`] with the given id
+    /// if it exists.
+    ///
+    /// Will panic if `id` exists in any archetypes.
+    pub fn register_component_hooks_by_id(
+        &mut self,
+        id: ComponentId,
+    ) -> Option<&mut ComponentHooks> {
+        assert!(
+            !self.archetypes.archetypes.iter().any(|a| a.contains(id)),
+            "Components hooks cannot be modified if the component already exists in an archetype, use init_component if the component with id {:?} may already be in use",
+            id
+        );
+        self.components.get_hooks_mut(id)
+    }
+
     /// Initializes a new [`Component`] type and returns the [`ComponentId`] created for it.
     ///
     /// This method differs from [`World::init_component`] in that it uses a [`ComponentDescriptor`]
@@ -422,7 +497,7 @@ impl World {
     /// scheme worked out to share an ID space (which doesn't happen by default).
     #[inline]
     pub fn get_or_spawn(&mut self, entity: Entity) -> Option<EntityWorldMut> {
-        self.flush();
+        self.flush_entities();
         match self.entities.alloc_at_without_replacement(entity) {
             AllocAtWithoutReplacement::Exists(location) => {
                 // SAFETY: `entity` exists and `location` is that entity's location
@@ -676,7 +751,7 @@ impl World {
     /// assert_eq!(position.x, 0.0);
     /// ```
     pub fn spawn_empty(&mut self) -> EntityWorldMut {
-        self.flush();
+        self.flush_entities();
         let entity = self.entities.alloc();
         // SAFETY: entity was just allocated
         unsafe { self.spawn_at_empty_internal(entity) }
@@ -742,10 +817,10 @@ impl World {
     /// assert_eq!(position.x, 2.0);
     /// ```
     pub fn spawn<B: Bundle>(&mut self, bundle: B) -> EntityWorldMut {
-        self.flush();
+        self.flush_entities();
         let change_tick = self.change_tick();
         let entity = self.entities.alloc();
-        let entity_location = {
+        let bundle_id = {
             let bundle_info = self
                 .bundles
                 .init_info::<B>(&mut self.components, &mut self.storages);
@@ -754,14 +829,21 @@ impl World {
                 &mut self.archetypes,
                 &self.components,
                 &mut self.storages,
+                &self.observers,
                 change_tick,
             );
 
             // SAFETY: bundle's type matches `bundle_info`, entity is allocated but non-existent
-            unsafe { spawner.spawn_non_existent(entity, bundle) }
+            unsafe { spawner.spawn_non_existent(entity, bundle) };
+            bundle_info.id()
         };
+        self.trigger_on_insert_bundle(entity, ArchetypeId::EMPTY, bundle_id);
+        self.flush();
 
-        // SAFETY: entity and location are valid, as they were just created above
+        // Hooks and observers may have moved or despawned the entity
+        let entity_location = self.entities.get(entity).unwrap_or(EntityLocation::INVALID);
+        // SAFETY: entity and location are valid, or the location is invalid and the
+        // `EntityWorldMut` panics on use
         unsafe { EntityWorldMut::new(self, entity, entity_location) }
     }
 
@@ -1534,7 +1616,7 @@ impl World {
         I::IntoIter: Iterator<Item = (Entity, B)>,
         B: Bundle,
     {
-        self.flush();
+        self.flush_entities();
 
         let change_tick = self.change_tick();
 
@@ -1559,9 +1641,14 @@ impl World {
             &mut self.archetypes,
             &self.components,
             &mut self.storages,
+            &self.observers,
             change_tick,
         ));
 
+        let bundle_id = bundle_info.id();
+        // The entities that got the bundle, along with the archetype they were in before, to
+        // trigger their hooks and observers once all of them are inserted or spawned
+        let mut inserted = Vec::new();
         let mut invalid_entities = Vec::new();
         for (entity, bundle) in iter {
             match spawn_or_insert
@@ -1569,6 +1656,7 @@ impl World {
                 .alloc_at_without_replacement(entity)
             {
                 AllocAtWithoutReplacement::Exists(location) => {
+                    inserted.push((entity, location.archetype_id));
                     match spawn_or_insert {
                         SpawnOrInsert::Insert(ref mut inserter, archetype)
                             if location.archetype_id == archetype =>
@@ -1582,6 +1670,7 @@ impl World {
                                 &mut self.archetypes,
                                 &self.components,
                                 &mut self.storages,
+                                &self.observers,
                                 location.archetype_id,
                                 change_tick,
                             );
@@ -1593,6 +1682,7 @@ impl World {
                     };
                 }
                 AllocAtWithoutReplacement::DidNotExist => {
+                    inserted.push((entity, ArchetypeId::EMPTY));
                     if let SpawnOrInsert::Spawn(ref mut spawner) = spawn_or_insert {
                         // SAFETY: `entity` is allocated (but non existent), bundle matches inserter
                         unsafe { spawner.spawn_non_existent(entity, bundle) };
@@ -1602,6 +1692,7 @@ impl World {
                             &mut self.archetypes,
                             &self.components,
                             &mut self.storages,
+                            &self.observers,
                             change_tick,
                         );
                         // SAFETY: `entity` is valid, `location` matches entity, bundle matches inserter
@@ -1615,6 +1706,11 @@ impl World {
             }
         }
 
+        for (entity, old_archetype_id) in inserted {
+            self.trigger_on_insert_bundle(entity, old_archetype_id, bundle_id);
+        }
+        self.flush();
+
         if invalid_entities.is_empty() {
             Ok(())
         } else {
@@ -1824,7 +1920,7 @@ impl World {
     /// Empties queued entities and adds them to the empty [`Archetype`](crate::archetype::Archetype).
     /// This should be called before doing operations that might operate on queued entities,
     /// such as inserting a [`Component`].
-    pub(crate) fn flush(&mut self) {
+    pub(crate) fn flush_entities(&mut self) {
         let empty_archetype = self.archetypes.empty_mut();
         let table = &mut self.storages.tables[empty_archetype.table_id()];
         // PERF: consider pre-allocating space for flushed entities
@@ -1838,6 +1934,154 @@ impl World {
         }
     }
 
+    /// Applies any commands in the world's internal [`CommandQueue`].
+    /// This does not apply commands from any systems, only those stored in the world.
+    ///
+    /// These are the commands queued by component hooks and observers, which are applied as
+    /// soon as the operation that triggered them completes.
+    pub fn flush_commands(&mut self) {
+        while !self.command_queue.is_empty() {
+            // Take the queue out of the world, so that the commands can queue more commands,
+            // which are applied by the next iteration.
+            let mut commands = std::mem::take(&mut self.command_queue);
+            commands.apply(self);
+            // Give the buffer back to the world to reuse its allocation.
+            if self.command_queue.is_empty() {
+                self.command_queue = commands;
+            }
+        }
+    }
+
+    /// Creates a new [`Commands`] instance that writes to the world's command queue.
+    ///
+    /// The commands are applied on the next call to [`World::flush`], which happens after
+    /// each structural change to the world.
+    #[inline]
+    pub fn commands(&mut self) -> Commands<'_, '_> {
+        Commands::new_from_entities(&mut self.command_queue, &self.entities)
+    }
+
+    /// Flushes queued entities and applies the commands queued by hooks and observers.
+    ///
+    /// This is called automatically after each structural change to the world, so it's only
+    /// needed after queueing commands with [`World::commands`].
+    #[inline]
+    pub fn flush(&mut self) {
+        self.flush_entities();
+        self.flush_commands();
+    }
+
+    /// Triggers the `on_add` hooks and [`OnAdd`] observers of the components of `bundle_id`
+    /// that `entity` didn't have in `old_archetype_id`, then the `on_insert` hooks and
+    /// [`OnInsert`] observers of all the components of `bundle_id`.
+    ///
+    /// This must be called right after the bundle is inserted, and followed by a
+    /// [`World::flush`] to apply the commands they queue.
+    pub(crate) fn trigger_on_insert_bundle(
+        &mut self,
+        entity: Entity,
+        old_archetype_id: ArchetypeId,
+        bundle_id: BundleId,
+    ) {
+        let world = self.as_unsafe_world_cell();
+        let Some(location) = world.entities().get(entity) else {
+            return;
+        };
+        let archetype = &world.archetypes()[location.archetype_id];
+        let flags = archetype.flags();
+        if !flags.intersects(
+            ArchetypeFlags::ON_ADD_HOOK
+                | ArchetypeFlags::ON_ADD_OBSERVER
+                | ArchetypeFlags::ON_INSERT_HOOK
+                | ArchetypeFlags::ON_INSERT_OBSERVER,
+        ) {
+            return;
+        }
+        // SAFETY: the bundle was just inserted, so it's registered
+        let bundle_info = unsafe { world.bundles().get(bundle_id).debug_checked_unwrap() };
+        let components = bundle_info.components();
+        let add_bundle = world.archetypes()[old_archetype_id]
+            .edges()
+            .get_add_bundle_internal(bundle_id);
+        let added = components.iter().enumerate().filter_map(move |(i, &id)| {
+            match add_bundle.map(|add_bundle| add_bundle.bundle_status[i]) {
+                Some(ComponentStatus::Mutated) => None,
+                _ => Some(id),
+            }
+        });
+        // SAFETY: hooks and observers can only make non-structural changes through the
+        // `DeferredWorld`, so the archetypes and bundles borrowed above stay valid
+        let mut deferred = unsafe { world.into_deferred() };
+        // SAFETY: the components of the bundle are registered in the world
+        unsafe {
+            if archetype.has_add_hook() {
+                deferred.trigger_on_add(entity, added.clone());
+            }
+            if archetype.has_add_observer() {
+                deferred.trigger_observers(TypeId::of::<OnAdd>(), entity, added);
+            }
+            if archetype.has_insert_hook() {
+                deferred.trigger_on_insert(entity, components.iter().copied());
+            }
+            if archetype.has_insert_observer() {
+                deferred.trigger_observers(
+                    TypeId::of::<OnInsert>(),
+                    entity,
+                    components.iter().copied(),
+                );
+            }
+        }
+    }
+
+    /// Triggers the [`OnRemove`] observers and `on_remove` hooks of the components of
+    /// `bundle_id` that `entity`, which is at `location`, has.
+    ///
+    /// This must be called right before the bundle is removed.
+    pub(crate) fn trigger_on_remove_bundle(
+        &mut self,
+        entity: Entity,
+        location: EntityLocation,
+        bundle_id: BundleId,
+    ) {
+        let world = self.as_unsafe_world_cell();
+        let archetype = &world.archetypes()[location.archetype_id];
+        if !archetype.has_remove_hook() && !archetype.has_remove_observer() {
+            return;
+        }
+        // SAFETY: the bundle is about to be removed, so it's registered
+        let bundle_info = unsafe { world.bundles().get(bundle_id).debug_checked_unwrap() };
+        let components = bundle_info
+            .components()
+            .iter()
+            .copied()
+            .filter(|&id| archetype.contains(id));
+        // SAFETY: hooks and observers can only make non-structural changes through the
+        // `DeferredWorld`, so the archetypes and bundles borrowed above stay valid
+        let mut deferred = unsafe { world.into_deferred() };
+        // SAFETY: `entity` is in `archetype`, which contains the `components`
+        unsafe { deferred.trigger_on_remove_components(archetype, entity, components) };
+    }
+
+    /// Triggers the [`OnRemove`] observers and `on_remove` hooks of all the components of
+    /// `entity`, which is at `location`.
+    ///
+    /// This must be called right before the entity is despawned.
+    pub(crate) fn trigger_on_despawn(&mut self, entity: Entity, location: EntityLocation) {
+        let world = self.as_unsafe_world_cell();
+        let archetype = &world.archetypes()[location.archetype_id];
+        if !archetype.has_remove_hook() && !archetype.has_remove_observer() {
+            return;
+        }
+        let components = archetype.components().collect::<Vec<_>>();
+        // SAFETY: hooks and observers can only make non-structural changes through the
+        // `DeferredWorld`, so the archetype borrowed above stays valid
+        let mut deferred = unsafe { world.into_deferred() };
+        // SAFETY: `entity` is in `archetype`, which contains the `components`
+        unsafe {
+            deferred.trigger_on_remove_components(archetype, entity, components.into_iter());
+        }
+    }
+
     /// Increments the world's current change tick and returns the old value.
     #[inline]
     pub fn increment_change_tick(&self) -> Tick {
@@ -2363,10 +2607,11 @@ impl<T: Default> FromWorld for T {
 
 #[cfg(test)]
 mod tests {
-    use super::{FromWorld, World};
+    use super::{DeferredWorld, FromWorld, World};
     use crate::{
         change_detection::DetectChangesMut,
-        component::{ComponentDescriptor, ComponentInfo, StorageType},
+        component::{ComponentDescriptor, ComponentId, ComponentInfo, StorageType},
+        entity::Entity,
         ptr::OwningPtr,
         system::Resource,
     };
@@ -2802,4 +3047,127 @@ mod tests {
         let mut world = World::new();
         world.spawn(());
     }
+
+    #[derive(Resource, Default)]
+    struct HookOrder(Vec<&'static str>);
+
+    #[derive(Component)]
+    struct Hooked;
+
+    #[test]
+    fn component_hooks_order() {
+        let mut world = World::new();
+        world.init_resource::<HookOrder>();
+        world
+            .register_component_hooks::<Hooked>()
+            .on_add(|mut world, _, _| world.resource_mut::<HookOrder>().0.push("add"))
+            .on_insert(|mut world, _, _| world.resource_mut::<HookOrder>().0.push("insert"))
+            .on_remove(|mut world, _, _| world.resource_mut::<HookOrder>().0.push("remove"));
+
+        let entity = world.spawn(Hooked).id();
+        world.entity_mut(entity).insert(Hooked);
+        world.entity_mut(entity).remove::<Hooked>();
+        world.entity_mut(entity).insert(Hooked);
+        world.despawn(entity);
+        assert_eq!(
+            world.resource::<HookOrder>().0,
+            vec!["add", "insert", "insert", "remove", "add", "insert", "remove"]
+        );
+    }
+
+    #[test]
+    fn component_hooks_from_derive() {
+        #[derive(Component)]
+        #[component(on_add = on_add_despawn)]
+        struct DespawnOnAdd;
+
+        fn on_add_despawn(mut world: DeferredWorld, entity: Entity, _: ComponentId) {
+            world.commands().entity(entity).despawn();
+        }
+
+        let mut world = World::new();
+        let entity = world.spawn_empty().insert(DespawnOnAdd).id();
+        // The hook's commands are applied before `insert` returns
+        assert!(world.get_entity(entity).is_none());
+    }
+
+    #[test]
+    fn required_components() {
+        #[derive(Component, Default, PartialEq, Debug)]
+        struct Velocity(u32);
+
+        #[derive(Component, PartialEq, Debug)]
+        #[component(require(Velocity))]
+        struct Mass(u32);
+
+        #[derive(Component)]
+        #[component(require(Mass = || Mass(10)))]
+        struct Body;
+
+        let mut world = World::new();
+        let body = world.spawn(Body).id();
+        // Requirements of the required components are inserted too
+        assert_eq!(world.get::<Mass>(body), Some(&Mass(10)));
+        assert_eq!(world.get::<Velocity>(body), Some(&Velocity(0)));
+
+        // Components already on the entity are kept
+        let body = world.spawn((Body, Mass(1), Velocity(2))).id();
+        assert_eq!(world.get::<Mass>(body), Some(&Mass(1)));
+        assert_eq!(world.get::<Velocity>(body), Some(&Velocity(2)));
+
+        #[derive(Component)]
+        struct Wheel;
+        world
+            .register_required_components::<Wheel>()
+            .register::<Velocity>();
+        let wheel = world.spawn_empty().insert(Wheel).id();
+        assert_eq!(world.get::<Velocity>(wheel), Some(&Velocity(0)));
+    }
+
+    #[test]
+    fn required_components_are_inserted_together() {
+        #[derive(Component, Default)]
+        struct A;
+
+        #[derive(Component, Default)]
+        #[component(require(A))]
+        struct B;
+
+        #[derive(Component, Default)]
+        struct C;
+
+        #[derive(Component)]
+        #[component(require(B, C))]
+        struct D;
+
+        let mut world = World::new();
+        let archetypes = world.archetypes().len();
+        let entity = world.spawn(D).id();
+        assert!(world.entity(entity).contains::<A>());
+        assert!(world.entity(entity).contains::<B>());
+        assert!(world.entity(entity).contains::<C>());
+        // The archetypes of `D` and of `D` with all its required components.
+        assert_eq!(world.archetypes().len(), archetypes + 2);
+    }
+
+    #[test]
+    #[should_panic]
+    fn required_components_registered_after_use() {
+        #[derive(Component)]
+        struct A;
+
+        let mut world = World::new();
+        world.spawn(A);
+        world.register_required_components::<A>();
+    }
+
+    #[test]
+    #[should_panic]
+    fn component_hooks_registered_after_use() {
+        let mut world = World::new();
+        world.spawn(Hooked);
+        world
+            .register_component_hooks::<Hooked>()
+            .on_add(|_, _, _| {});
+    }
 }
@@ -1,4 +1,5 @@
 use crate::{
+    archetype::ArchetypeFlags,
     bundle::{Bundle, BundleSpawner},
     entity::Entity,
     world::World,
@@ -15,7 +16,16 @@ where
     I::Item: Bundle,
 {
     inner: I,
-    spawner: BundleSpawner<'w, 'w>,
+    spawner: SpawnBatchSpawner<'w>,
+}
+
+/// How a [`SpawnBatchIter`] spawns its entities.
+enum SpawnBatchSpawner<'w> {
+    /// Spawns all the entities directly into their archetype.
+    Spawner(BundleSpawner<'w, 'w>),
+    /// Spawns the entities one at a time through the [`World`], because their components have
+    /// hooks or observers that must run after each of them is spawned.
+    World(&'w mut World),
 }
 
 impl<'w, I> SpawnBatchIter<'w, I>
@@ -27,13 +37,39 @@ where
     pub(crate) fn new(world: &'w mut World, iter: I) -> Self {
         // Ensure all entity allocations are accounted for so `self.entities` can realloc if
         // necessary
-        world.flush();
+        world.flush_entities();
 
         let change_tick = world.change_tick();
 
         let (lower, upper) = iter.size_hint();
         let length = upper.unwrap_or(lower);
 
+        let bundle_info = world
+            .bundles
+            .init_info::<I::Item>(&mut world.components, &mut world.storages);
+        let has_hooks_or_observers = {
+            let spawner = bundle_info.get_bundle_spawner(
+                &mut world.entities,
+                &mut world.archetypes,
+                &world.components,
+                &mut world.storages,
+                &world.observers,
+                change_tick,
+            );
+            spawner.archetype.flags().intersects(
+                ArchetypeFlags::ON_ADD_HOOK
+                    | ArchetypeFlags::ON_ADD_OBSERVER
+                    | ArchetypeFlags::ON_INSERT_HOOK
+                    | ArchetypeFlags::ON_INSERT_OBSERVER,
+            )
+        };
+        if has_hooks_or_observers {
+            return Self {
+                inner: iter,
+                spawner: SpawnBatchSpawner::World(world),
+            };
+        }
+
         let bundle_info = world
             .bundles
             .init_info::<I::Item>(&mut world.components, &mut world.storages);
@@ -43,13 +79,14 @@ where
             &mut world.archetypes,
             &world.components,
             &mut world.storages,
+            &world.observers,
             change_tick,
         );
         spawner.reserve_storage(length);
 
         Self {
             inner: iter,
-            spawner,
+            spawner: SpawnBatchSpawner::Spawner(spawner),
         }
     }
 }
@@ -73,8 +110,11 @@ where
 
     fn next(&mut self) -> Option<Entity> {
         let bundle = self.inner.next()?;
-        // SAFETY: bundle matches spawner type
-        unsafe { Some(self.spawner.spawn(bundle)) }
+        match &mut self.spawner {
+            // SAFETY: bundle matches spawner type
+            SpawnBatchSpawner::Spawner(spawner) => unsafe { Some(spawner.spawn(bundle)) },
+            SpawnBatchSpawner::World(world) => Some(world.spawn(bundle).id()),
+        }
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
@@ -11,10 +11,11 @@ use crate::{
         ComponentId, ComponentStorage, ComponentTicks, Components, StorageType, Tick, TickCells,
     },
     entity::{Entities, Entity, EntityLocation},
+    observer::Observers,
     prelude::Component,
     removal_detection::RemovedComponentEvents,
     storage::{Column, ComponentSparseSet, Storages},
-    system::{Res, Resource},
+    system::{CommandQueue, Res, Resource},
 };
 use bevy_ptr::Ptr;
 use std::{any::TypeId, cell::UnsafeCell, fmt::Debug, marker::PhantomData, ptr};
@@ -240,6 +241,28 @@ impl<'w> UnsafeWorldCell<'w> {
         &unsafe { self.world_metadata() }.bundles
     }
 
+    /// Retrieves this world's [`Observers`] collection.
+    #[inline]
+    pub(crate) fn observers(self) -> &'w Observers {
+        // SAFETY:
+        // - we only access world metadata
+        &unsafe { self.world_metadata() }.observers
+    }
+
+    /// Retrieves this world's command queue, which hooks and observers push their
+    /// commands to.
+    ///
+    /// # Safety
+    /// - the [`UnsafeWorldCell`] has permission to access the queue mutably
+    /// - no other references to the queue exist at the same time
+    #[inline]
+    pub(crate) unsafe fn get_command_queue(self) -> &'w mut CommandQueue {
+        // SAFETY:
+        // - caller ensures there are no existing references to the queue
+        // - caller ensures that we have permission to access the queue
+        unsafe { &mut *ptr::addr_of_mut!((*self.0).command_queue) }
+    }
+
     /// Gets the current change tick of this world.
     #[inline]
     pub fn change_tick(self) -> Tick {
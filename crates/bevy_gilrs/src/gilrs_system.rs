@@ -5,19 +5,33 @@ use bevy_input::gamepad::{
     GamepadAxisChangedEvent, GamepadButtonChangedEvent, GamepadConnection, GamepadConnectionEvent,
     GamepadSettings,
 };
-use bevy_input::gamepad::{GamepadEvent, GamepadInfo};
+use bevy_input::gamepad::{GamepadEvent, GamepadFeatures, GamepadInfo};
 use bevy_input::prelude::{GamepadAxis, GamepadButton};
 use bevy_input::Axis;
-use gilrs::{ev::filter::axis_dpad_to_button, EventType, Filter, Gilrs};
+use gilrs::{ev::filter::axis_dpad_to_button, EventType, Filter, GamepadId, Gilrs};
+
+/// The metadata of a gamepad.
+fn gamepad_info(gilrs: &Gilrs, id: GamepadId) -> GamepadInfo {
+    let gamepad = gilrs.gamepad(id);
+    // Gilrs can't rumble the triggers of gamepads, but the Gamepad API of browsers can.
+    #[cfg(target_arch = "wasm32")]
+    let trigger_rumble = crate::rumble::web::gamepad_supports_trigger_rumble(gilrs, id);
+    #[cfg(not(target_arch = "wasm32"))]
+    let trigger_rumble = false;
+    let features = GamepadFeatures {
+        // On the web, gamepads rumble through the Gamepad API instead.
+        rumble: gamepad.is_ff_supported() || cfg!(target_arch = "wasm32"),
+        trigger_rumble,
+    };
+    GamepadInfo::new(gamepad.name(), features)
+}
 
 pub fn gilrs_event_startup_system(
     gilrs: NonSend<Gilrs>,
     mut connection_events: EventWriter<GamepadConnectionEvent>,
 ) {
-    for (id, gamepad) in gilrs.gamepads() {
-        let info = GamepadInfo {
-            name: gamepad.name().into(),
-        };
+    for (id, _) in gilrs.gamepads() {
+        let info = gamepad_info(&gilrs, id);
 
         connection_events.send(GamepadConnectionEvent {
             gamepad: convert_gamepad_id(id),
@@ -42,10 +56,7 @@ pub fn gilrs_event_system(
         let gamepad = convert_gamepad_id(gilrs_event.id);
         match gilrs_event.event {
             EventType::Connected => {
-                let pad = gilrs.gamepad(gilrs_event.id);
-                let info = GamepadInfo {
-                    name: pad.name().into(),
-                };
+                let info = gamepad_info(&gilrs, gilrs_event.id);
 
                 events.send(
                     GamepadConnectionEvent::new(gamepad, GamepadConnection::Connected(info)).into(),
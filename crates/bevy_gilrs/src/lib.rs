@@ -9,7 +9,7 @@ mod rumble;
 
 use bevy_app::{App, Plugin, PostUpdate, PreStartup, PreUpdate};
 use bevy_ecs::prelude::*;
-use bevy_input::InputSystem;
+use bevy_input::{recording::InputPlaybackSystem, InputSystem};
 use bevy_utils::tracing::error;
 use gilrs::GilrsBuilder;
 use gilrs_system::{gilrs_event_startup_system, gilrs_event_system};
@@ -34,7 +34,12 @@ impl Plugin for GilrsPlugin {
                 app.insert_non_send_resource(gilrs)
                     .init_non_send_resource::<RunningRumbleEffects>()
                     .add_systems(PreStartup, gilrs_event_startup_system)
-                    .add_systems(PreUpdate, gilrs_event_system.before(InputSystem))
+                    .add_systems(
+                        PreUpdate,
+                        gilrs_event_system
+                            .before(InputSystem)
+                            .before(InputPlaybackSystem),
+                    )
                     .add_systems(PostUpdate, play_gilrs_rumble.in_set(RumbleSystem));
             }
             Err(err) => error!("Failed to start Gilrs. {}", err),
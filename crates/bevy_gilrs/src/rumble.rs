@@ -3,10 +3,10 @@ use bevy_ecs::{
     prelude::{EventReader, Res},
     system::NonSendMut,
 };
-use bevy_input::gamepad::{GamepadRumbleIntensity, GamepadRumbleRequest};
+use bevy_input::gamepad::{GamepadRumbleIntensity, GamepadRumbleRequest, GamepadRumbleStacking};
 use bevy_log::{debug, warn};
 use bevy_time::{Real, Time};
-use bevy_utils::{Duration, HashMap};
+use bevy_utils::{warn_once, Duration, HashMap, HashSet};
 use gilrs::{
     ff::{self, BaseEffect, BaseEffectType, Repeat, Replay},
     GamepadId, Gilrs,
@@ -15,15 +15,23 @@ use thiserror::Error;
 
 use crate::converter::convert_gamepad_id;
 
-/// A rumble effect that is currently in effect.
+/// A rumble that is currently running.
+#[derive(Debug, Clone, Copy)]
 struct RunningRumble {
-    /// Duration from app startup when this effect will be finished
+    /// Duration from app startup when this rumble will be finished
     deadline: Duration,
-    /// A ref-counted handle to the specific force-feedback effect
+    intensity: GamepadRumbleIntensity,
+    stacking: GamepadRumbleStacking,
+}
+
+/// The rumbles running on a gamepad, played as a single effect.
+#[derive(Default)]
+struct GamepadRumbles {
+    rumbles: Vec<RunningRumble>,
+    /// A ref-counted handle to the force-feedback effect playing the rumbles
     ///
     /// Dropping it will cause the effect to stop
-    #[allow(dead_code)]
-    effect: ff::Effect,
+    effect: Option<ff::Effect>,
 }
 
 #[derive(Error, Debug)]
@@ -34,12 +42,12 @@ enum RumbleError {
     GilrsError(#[from] ff::Error),
 }
 
-/// Contains the gilrs rumble effects that are currently running for each gamepad
+/// Contains the rumbles that are currently running for each gamepad
 #[derive(Default)]
 pub(crate) struct RunningRumbleEffects {
-    /// If multiple rumbles are running at the same time, their resulting rumble
-    /// will be the saturated sum of their strengths up until [`u16::MAX`]
-    rumbles: HashMap<GamepadId, Vec<RunningRumble>>,
+    /// The running rumbles are combined according to their [`GamepadRumbleStacking`], and the
+    /// effect playing them is replaced whenever a rumble starts or finishes
+    rumbles: HashMap<GamepadId, GamepadRumbles>,
 }
 
 /// gilrs uses magnitudes from 0 to [`u16::MAX`], while ours go from `0.0` to `1.0` ([`f32`])
@@ -47,10 +55,29 @@ fn to_gilrs_magnitude(ratio: f32) -> u16 {
     (ratio * u16::MAX as f32) as u16
 }
 
+/// The intensity of the running `rumbles` combined: the sum of the added rumbles, or the
+/// strongest rumble if it's stronger.
+fn combined_intensity(rumbles: &[RunningRumble]) -> GamepadRumbleIntensity {
+    let (added, max) = rumbles.iter().fold(
+        (
+            GamepadRumbleIntensity::default(),
+            GamepadRumbleIntensity::default(),
+        ),
+        |(added, max), rumble| match rumble.stacking {
+            GamepadRumbleStacking::Add | GamepadRumbleStacking::Replace => {
+                (added.saturating_add(rumble.intensity), max)
+            }
+            GamepadRumbleStacking::Max => (added, max.max(rumble.intensity)),
+        },
+    );
+    added.max(max)
+}
+
 fn get_base_effects(
     GamepadRumbleIntensity {
         weak_motor,
         strong_motor,
+        ..
     }: GamepadRumbleIntensity,
     duration: Duration,
 ) -> Vec<BaseEffect> {
@@ -69,9 +96,13 @@ fn get_base_effects(
     }
     if weak_motor > 0. {
         effects.push(BaseEffect {
-            kind: BaseEffectType::Strong {
+            kind: BaseEffectType::Weak {
                 magnitude: to_gilrs_magnitude(weak_motor),
             },
+            scheduling: Replay {
+                play_for: duration.into(),
+                ..Default::default()
+            },
             ..Default::default()
         });
     }
@@ -80,10 +111,10 @@ fn get_base_effects(
 
 fn handle_rumble_request(
     running_rumbles: &mut RunningRumbleEffects,
-    gilrs: &mut Gilrs,
+    gilrs: &Gilrs,
     rumble: GamepadRumbleRequest,
     current_time: Duration,
-) -> Result<(), RumbleError> {
+) -> Result<GamepadId, RumbleError> {
     let gamepad = rumble.gamepad();
 
     let (gamepad_id, _) = gilrs
@@ -91,34 +122,101 @@ fn handle_rumble_request(
         .find(|(pad_id, _)| convert_gamepad_id(*pad_id) == gamepad)
         .ok_or(RumbleError::GamepadNotFound)?;
 
+    let rumbles = &mut running_rumbles
+        .rumbles
+        .entry(gamepad_id)
+        .or_default()
+        .rumbles;
     match rumble {
-        GamepadRumbleRequest::Stop { .. } => {
-            // `ff::Effect` uses RAII, dropping = deactivating
-            running_rumbles.rumbles.remove(&gamepad_id);
-        }
+        GamepadRumbleRequest::Stop { .. } => rumbles.clear(),
         GamepadRumbleRequest::Add {
             duration,
             intensity,
+            stacking,
             ..
         } => {
-            let mut effect_builder = ff::EffectBuilder::new();
-
-            for effect in get_base_effects(intensity, duration) {
-                effect_builder.add_effect(effect);
-                effect_builder.repeat(Repeat::For(duration.into()));
+            if stacking == GamepadRumbleStacking::Replace {
+                rumbles.clear();
             }
+            rumbles.push(RunningRumble {
+                deadline: current_time + duration,
+                intensity,
+                stacking,
+            });
+        }
+    }
 
-            let effect = effect_builder.gamepads(&[gamepad_id]).finish(gilrs)?;
-            effect.play()?;
+    Ok(gamepad_id)
+}
 
-            let gamepad_rumbles = running_rumbles.rumbles.entry(gamepad_id).or_default();
-            let deadline = current_time + duration;
-            gamepad_rumbles.push(RunningRumble { deadline, effect });
-        }
+/// Plays the combined intensity of the running rumbles of a gamepad until the last of them
+/// finishes, replacing the effect playing them, or stops the gamepad if there are none.
+fn play_rumbles(
+    gilrs: &mut Gilrs,
+    gamepad_id: GamepadId,
+    gamepad_rumbles: &mut GamepadRumbles,
+    current_time: Duration,
+) -> Result<(), RumbleError> {
+    // `ff::Effect` uses RAII, dropping = deactivating
+    gamepad_rumbles.effect = None;
+    let Some(deadline) = gamepad_rumbles
+        .rumbles
+        .iter()
+        .map(|rumble| rumble.deadline)
+        .max()
+    else {
+        #[cfg(target_arch = "wasm32")]
+        web::play(
+            gilrs,
+            gamepad_id,
+            GamepadRumbleIntensity::default(),
+            Duration::ZERO,
+        );
+        return Ok(());
+    };
+    let intensity = combined_intensity(&gamepad_rumbles.rumbles);
+    let duration = deadline - current_time;
+
+    // gilrs can't rumble gamepads on the web, so ask the browser instead.
+    #[cfg(target_arch = "wasm32")]
+    if !gilrs.gamepad(gamepad_id).is_ff_supported() {
+        web::play(gilrs, gamepad_id, intensity, duration);
+        return Ok(());
+    }
+
+    if intensity.left_trigger > 0. || intensity.right_trigger > 0. {
+        warn_once!("Gilrs doesn't support rumbling the triggers of gamepads");
+    }
+
+    let mut effect_builder = ff::EffectBuilder::new();
+    for effect in get_base_effects(intensity, duration) {
+        effect_builder.add_effect(effect);
+        effect_builder.repeat(Repeat::For(duration.into()));
     }
+    let effect = effect_builder.gamepads(&[gamepad_id]).finish(gilrs)?;
+    effect.play()?;
+    gamepad_rumbles.effect = Some(effect);
 
     Ok(())
 }
+
+fn log_rumble_error(gamepad: impl std::fmt::Debug, err: RumbleError) {
+    match err {
+        RumbleError::GilrsError(err) => {
+            if let ff::Error::FfNotSupported(_) = err {
+                debug!("Tried to rumble {gamepad:?}, but it doesn't support force feedback");
+            } else {
+                warn!(
+                    "Tried to handle rumble request for {gamepad:?} but an error occurred: {err}"
+                );
+            }
+        }
+        RumbleError::GamepadNotFound => {
+            warn!("Tried to handle rumble request {gamepad:?} but it doesn't exist!");
+        }
+    }
+}
+
 pub(crate) fn play_gilrs_rumble(
     time: Res<Time<Real>>,
     mut gilrs: NonSendMut<Gilrs>,
@@ -126,39 +224,150 @@ pub(crate) fn play_gilrs_rumble(
     mut running_rumbles: NonSendMut<RunningRumbleEffects>,
 ) {
     let current_time = time.elapsed();
-    // Remove outdated rumble effects.
-    for rumbles in running_rumbles.rumbles.values_mut() {
-        // `ff::Effect` uses RAII, dropping = deactivating
-        rumbles.retain(|RunningRumble { deadline, .. }| *deadline >= current_time);
+    let mut changed_gamepads = HashSet::new();
+
+    // Remove outdated rumbles.
+    for (gamepad_id, gamepad_rumbles) in &mut running_rumbles.rumbles {
+        let count = gamepad_rumbles.rumbles.len();
+        gamepad_rumbles
+            .rumbles
+            .retain(|RunningRumble { deadline, .. }| *deadline >= current_time);
+        if gamepad_rumbles.rumbles.len() != count {
+            changed_gamepads.insert(*gamepad_id);
+        }
     }
-    running_rumbles
-        .rumbles
-        .retain(|_gamepad, rumbles| !rumbles.is_empty());
 
-    // Add new effects.
+    // Add new rumbles.
     for rumble in requests.read().cloned() {
         let gamepad = rumble.gamepad();
-        match handle_rumble_request(&mut running_rumbles, &mut gilrs, rumble, current_time) {
-            Ok(()) => {}
-            Err(RumbleError::GilrsError(err)) => {
-                if let ff::Error::FfNotSupported(_) = err {
-                    debug!("Tried to rumble {gamepad:?}, but it doesn't support force feedback");
-                } else {
-                    warn!(
-                    "Tried to handle rumble request for {gamepad:?} but an error occurred: {err}"
-                    );
-                }
-            }
-            Err(RumbleError::GamepadNotFound) => {
-                warn!("Tried to handle rumble request {gamepad:?} but it doesn't exist!");
+        match handle_rumble_request(&mut running_rumbles, &gilrs, rumble, current_time) {
+            Ok(gamepad_id) => {
+                changed_gamepads.insert(gamepad_id);
             }
+            Err(err) => log_rumble_error(gamepad, err),
+        };
+    }
+
+    // Play the rumbles of the gamepads whose rumbles changed.
+    for gamepad_id in changed_gamepads {
+        let Some(gamepad_rumbles) = running_rumbles.rumbles.get_mut(&gamepad_id) else {
+            continue;
+        };
+        if let Err(err) = play_rumbles(&mut gilrs, gamepad_id, gamepad_rumbles, current_time) {
+            log_rumble_error(convert_gamepad_id(gamepad_id), err);
+        }
+    }
+    running_rumbles
+        .rumbles
+        .retain(|_gamepad, gamepad_rumbles| !gamepad_rumbles.rumbles.is_empty());
+}
+
+/// Rumble through the [Gamepad API](https://developer.mozilla.org/en-US/docs/Web/API/Gamepad_API)
+/// of the browser.
+#[cfg(target_arch = "wasm32")]
+pub(crate) mod web {
+    use bevy_input::gamepad::GamepadRumbleIntensity;
+    use bevy_utils::Duration;
+    use gilrs::{GamepadId, Gilrs};
+    use js_sys::{Function, Object, Reflect};
+    use wasm_bindgen::{JsCast, JsValue};
+
+    /// Plays `intensity` on the gamepad for `duration`, replacing the current effect, or stops
+    /// the gamepad if `duration` is zero.
+    ///
+    /// The triggers rumble with the `trigger-rumble` effect of the browsers supporting it.
+    pub(super) fn play(
+        gilrs: &Gilrs,
+        gamepad_id: GamepadId,
+        intensity: GamepadRumbleIntensity,
+        duration: Duration,
+    ) {
+        let Some(actuator) = vibration_actuator(gilrs, gamepad_id) else {
+            return;
         };
+        if duration.is_zero() {
+            call(&actuator, "reset", &[]);
+            return;
+        }
+        let params = Object::new();
+        for (key, value) in [
+            ("duration", duration.as_secs_f64() * 1000.0),
+            (
+                "strongMagnitude",
+                intensity.strong_motor.clamp(0.0, 1.0) as f64,
+            ),
+            ("weakMagnitude", intensity.weak_motor.clamp(0.0, 1.0) as f64),
+            ("leftTrigger", intensity.left_trigger.clamp(0.0, 1.0) as f64),
+            (
+                "rightTrigger",
+                intensity.right_trigger.clamp(0.0, 1.0) as f64,
+            ),
+        ] {
+            let _ = Reflect::set(&params, &key.into(), &value.into());
+        }
+        let triggers = intensity.left_trigger > 0.0 || intensity.right_trigger > 0.0;
+        let effect = if triggers && supports_trigger_rumble(&actuator) {
+            "trigger-rumble"
+        } else {
+            "dual-rumble"
+        };
+        call(&actuator, "playEffect", &[effect.into(), params.into()]);
+    }
+
+    /// Returns `true` if the browser can rumble the triggers of the gamepad.
+    pub(crate) fn gamepad_supports_trigger_rumble(gilrs: &Gilrs, gamepad_id: GamepadId) -> bool {
+        vibration_actuator(gilrs, gamepad_id)
+            .is_some_and(|actuator| supports_trigger_rumble(&actuator))
+    }
+
+    /// Returns `true` if the `effects` of `actuator` include `trigger-rumble`.
+    fn supports_trigger_rumble(actuator: &JsValue) -> bool {
+        Reflect::get(actuator, &"effects".into())
+            .ok()
+            .and_then(|effects| effects.dyn_into::<js_sys::Array>().ok())
+            .is_some_and(|effects| effects.includes(&"trigger-rumble".into(), 0))
+    }
+
+    /// Finds the vibration actuator of the gamepad, matching the gamepads of the browser by
+    /// name, as gilrs names them after their `id`.
+    fn vibration_actuator(gilrs: &Gilrs, gamepad_id: GamepadId) -> Option<JsValue> {
+        let name = gilrs.gamepad(gamepad_id).name();
+        let nth = gilrs
+            .gamepads()
+            .take_while(|(id, _)| *id != gamepad_id)
+            .filter(|(_, gamepad)| gamepad.name() == name)
+            .count();
+        let gamepads = web_sys::window()?.navigator().get_gamepads().ok()?;
+        let gamepad = gamepads
+            .iter()
+            .filter(|gamepad| {
+                Reflect::get(gamepad, &"id".into())
+                    .ok()
+                    .and_then(|id| id.as_string())
+                    .is_some_and(|id| id == name)
+            })
+            .nth(nth)?;
+        let actuator = Reflect::get(&gamepad, &"vibrationActuator".into()).ok()?;
+        (!actuator.is_null() && !actuator.is_undefined()).then_some(actuator)
+    }
+
+    /// Calls the method `name` of `actuator`, which browsers may not support.
+    fn call(actuator: &JsValue, name: &str, args: &[JsValue]) {
+        let method = Reflect::get(actuator, &name.into())
+            .ok()
+            .and_then(|method| method.dyn_into::<Function>().ok());
+        if let Some(method) = method {
+            let args: js_sys::Array = args.iter().collect();
+            let _ = method.apply(actuator, &args);
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::to_gilrs_magnitude;
+    use super::{combined_intensity, to_gilrs_magnitude, RunningRumble};
+    use bevy_input::gamepad::{GamepadRumbleIntensity, GamepadRumbleStacking};
+    use bevy_utils::Duration;
 
     #[test]
     fn magnitude_conversion() {
@@ -174,4 +383,33 @@ mod tests {
         assert_eq!(to_gilrs_magnitude(-1.0), 0);
         assert_eq!(to_gilrs_magnitude(-0.1), 0);
     }
+
+    #[test]
+    fn rumbles_combine_by_stacking() {
+        let rumble = |stacking, strong_motor| RunningRumble {
+            deadline: Duration::ZERO,
+            intensity: GamepadRumbleIntensity::strong_motor(strong_motor),
+            stacking,
+        };
+        let strong_motor = |rumbles: &[RunningRumble]| combined_intensity(rumbles).strong_motor;
+
+        let added = [
+            rumble(GamepadRumbleStacking::Add, 0.25),
+            rumble(GamepadRumbleStacking::Add, 0.5),
+        ];
+        assert_eq!(strong_motor(&added), 0.75);
+        assert_eq!(
+            strong_motor(&[added[0], added[1], rumble(GamepadRumbleStacking::Add, 0.5)]),
+            1.0
+        );
+
+        // Repeated rumbles with `Max` don't get stronger.
+        let max = [
+            rumble(GamepadRumbleStacking::Max, 0.5),
+            rumble(GamepadRumbleStacking::Max, 0.5),
+        ];
+        assert_eq!(strong_motor(&max), 0.5);
+        assert_eq!(strong_motor(&[max[0], added[1], added[0]]), 0.75);
+        assert_eq!(strong_motor(&[max[0], added[0]]), 0.5);
+    }
 }
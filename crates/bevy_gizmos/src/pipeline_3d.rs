@@ -6,6 +6,7 @@ use bevy_app::{App, Plugin};
 use bevy_asset::Handle;
 use bevy_core_pipeline::{
     core_3d::{Transparent3d, CORE_3D_DEPTH_FORMAT},
+    oit::OrderIndependentTransparencySettings,
     prepass::{DeferredPrepass, DepthPrepass, MotionVectorPrepass, NormalPrepass},
 };
 
@@ -171,6 +172,7 @@ fn queue_line_gizmos_3d(
             Has<MotionVectorPrepass>,
             Has<DeferredPrepass>,
         ),
+        Has<OrderIndependentTransparencySettings>,
     )>,
 ) {
     let draw_function = draw_functions.read().get_id::<DrawLineGizmo3d>().unwrap();
@@ -180,6 +182,7 @@ fn queue_line_gizmos_3d(
         mut transparent_phase,
         render_layers,
         (normal_prepass, depth_prepass, motion_vector_prepass, deferred_prepass),
+        oit,
     ) in &mut views
     {
         let render_layers = render_layers.copied().unwrap_or_default();
@@ -203,6 +206,10 @@ fn queue_line_gizmos_3d(
             view_key |= MeshPipelineKey::DEFERRED_PREPASS;
         }
 
+        if oit {
+            view_key |= MeshPipelineKey::OIT_ENABLED;
+        }
+
         for (entity, handle, config) in &line_gizmos {
             if !config.render_layers.intersects(&render_layers) {
                 continue;
@@ -0,0 +1,644 @@
+use bevy_asset::{AssetId, Assets, Handle};
+use bevy_core::Name;
+use bevy_ecs::{entity::Entity, world::World};
+use bevy_hierarchy::Children;
+use bevy_log::warn;
+use bevy_math::{Quat, Vec3};
+use bevy_pbr::{DirectionalLight, PointLight, SpotLight, StandardMaterial};
+use bevy_render::{
+    alpha::AlphaMode,
+    camera::{Camera, OrthographicProjection, PerspectiveProjection, Projection},
+    mesh::{Indices, Mesh, MeshVertexAttribute, PrimitiveTopology, VertexAttributeValues},
+};
+use bevy_transform::components::Transform;
+use bevy_utils::HashMap;
+use gltf::json::{
+    self,
+    accessor::{ComponentType, GenericComponentType, Type},
+    buffer::Target,
+    extensions::scene::khr_lights_punctual,
+    mesh::{Mode, Semantic},
+    validation::{Checked, USize64},
+};
+use thiserror::Error;
+
+/// An error that occurs when exporting a glTF file.
+#[derive(Error, Debug)]
+pub enum GltfExportError {
+    /// Failed to serialize the JSON document.
+    #[error("failed to serialize the glTF document: {0}")]
+    Json(#[from] serde_json::Error),
+    /// Failed to write the binary glTF file.
+    #[error("failed to write the binary glTF file: {0}")]
+    Glb(#[from] gltf::Error),
+}
+
+/// Exports hierarchies of entities to a glTF 2.0 file, which can be opened by DCC tools or loaded
+/// back with the [`GltfLoader`](crate::GltfLoader).
+///
+/// Every entity becomes a node with its [`Transform`] and [`Name`], and the following components
+/// are exported with it:
+/// - a [`Handle<Mesh>`] and its [`Handle<StandardMaterial>`], as a mesh with a single primitive,
+/// - a [`Camera`] with a [`Projection`] or an [`OrthographicProjection`], as a camera,
+/// - a [`PointLight`], [`SpotLight`] or [`DirectionalLight`], with the `KHR_lights_punctual`
+///   extension.
+///
+/// Meshes and materials are read from the [`Assets`] given to the exporter, so entities can be
+/// exported from the app's [`World`] as well as from the world of a [`Scene`](bevy_scene::Scene).
+/// A [`DynamicScene`](bevy_scene::DynamicScene) can be exported by first converting it to a
+/// [`Scene`](bevy_scene::Scene). The textures of the materials are not exported.
+///
+/// ```
+/// # use bevy_ecs::prelude::*;
+/// # use bevy_gltf::GltfExporter;
+/// fn export_level(world: &mut World, level: Entity) -> Vec<u8> {
+///     let mut exporter = GltfExporter::from_world(world);
+///     exporter.add_scene(Some("Level"), world, [level]);
+///     exporter.to_glb().unwrap()
+/// }
+/// ```
+pub struct GltfExporter<'a> {
+    meshes: &'a Assets<Mesh>,
+    materials: &'a Assets<StandardMaterial>,
+    root: json::Root,
+    buffer: Vec<u8>,
+    exported_meshes: HashMap<
+        (AssetId<Mesh>, Option<AssetId<StandardMaterial>>),
+        Option<json::Index<json::Mesh>>,
+    >,
+    exported_materials: HashMap<AssetId<StandardMaterial>, json::Index<json::Material>>,
+}
+
+impl<'a> GltfExporter<'a> {
+    /// Creates an exporter reading meshes and materials from `meshes` and `materials`.
+    pub fn new(meshes: &'a Assets<Mesh>, materials: &'a Assets<StandardMaterial>) -> Self {
+        Self {
+            meshes,
+            materials,
+            root: json::Root {
+                asset: json::Asset {
+                    generator: Some("Bevy".to_string()),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            buffer: Vec::new(),
+            exported_meshes: HashMap::default(),
+            exported_materials: HashMap::default(),
+        }
+    }
+
+    /// Creates an exporter reading meshes and materials from the [`Assets`] resources of `world`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `world` doesn't have the [`Assets<Mesh>`] or [`Assets<StandardMaterial>`]
+    /// resources.
+    pub fn from_world(world: &'a World) -> Self {
+        Self::new(
+            world.resource::<Assets<Mesh>>(),
+            world.resource::<Assets<StandardMaterial>>(),
+        )
+    }
+
+    /// Adds a scene made of the entities of `world` under `roots`, with their descendants.
+    ///
+    /// The first scene added is the default scene of the file. Returns the index of the scene.
+    pub fn add_scene(
+        &mut self,
+        name: Option<&str>,
+        world: &World,
+        roots: impl IntoIterator<Item = Entity>,
+    ) -> usize {
+        let nodes = roots
+            .into_iter()
+            .filter_map(|root| self.export_node(world, root))
+            .collect();
+        let scene = self.root.push(json::Scene {
+            extensions: Default::default(),
+            extras: Default::default(),
+            name: name.map(ToString::to_string),
+            nodes,
+        });
+        if self.root.scene.is_none() {
+            self.root.scene = Some(scene);
+        }
+        scene.value()
+    }
+
+    /// Writes the exported scenes to a binary glTF (`.glb`) file.
+    pub fn to_glb(&self) -> Result<Vec<u8>, GltfExportError> {
+        let root = self.finish(None);
+        let json = json::serialize::to_vec(&root)?;
+        let glb = gltf::binary::Glb {
+            header: gltf::binary::Header {
+                magic: *b"glTF",
+                version: 2,
+                // Computed when writing the file.
+                length: 0,
+            },
+            json: json.into(),
+            bin: (!self.buffer.is_empty()).then(|| self.buffer.as_slice().into()),
+        };
+        Ok(glb.to_vec()?)
+    }
+
+    /// Writes the exported scenes to a JSON glTF (`.gltf`) file, with its binary data embedded
+    /// in a base64 data URI.
+    pub fn to_gltf(&self) -> Result<Vec<u8>, GltfExportError> {
+        let uri = format!(
+            "data:application/octet-stream;base64,{}",
+            base64::Engine::encode(&base64::engine::general_purpose::STANDARD, &self.buffer)
+        );
+        let root = self.finish(Some(uri));
+        Ok(json::serialize::to_vec_pretty(&root)?)
+    }
+
+    /// Returns the document with its buffer, which is stored at `uri` or in the binary chunk of
+    /// a `.glb` file.
+    fn finish(&self, uri: Option<String>) -> json::Root {
+        let mut root = self.root.clone();
+        if !self.buffer.is_empty() {
+            root.push(json::Buffer {
+                byte_length: USize64::from(self.buffer.len()),
+                name: None,
+                uri,
+                extensions: Default::default(),
+                extras: Default::default(),
+            });
+        }
+        root
+    }
+
+    fn export_node(&mut self, world: &World, entity: Entity) -> Option<json::Index<json::Node>> {
+        let entity_ref = world.get_entity(entity)?;
+        let transform = entity_ref.get::<Transform>().copied().unwrap_or_default();
+        let mut node = json::Node {
+            name: entity_ref
+                .get::<Name>()
+                .map(|name| name.as_str().to_string()),
+            translation: (transform.translation != Vec3::ZERO)
+                .then(|| transform.translation.to_array()),
+            rotation: (transform.rotation != Quat::IDENTITY)
+                .then(|| json::scene::UnitQuaternion(transform.rotation.to_array())),
+            scale: (transform.scale != Vec3::ONE).then(|| transform.scale.to_array()),
+            ..Default::default()
+        };
+
+        if let Some(mesh) = entity_ref.get::<Handle<Mesh>>() {
+            let material = entity_ref.get::<Handle<StandardMaterial>>().map(Handle::id);
+            node.mesh = self.export_mesh(mesh.id(), material);
+        }
+
+        if entity_ref.contains::<Camera>() {
+            let camera = match entity_ref.get::<Projection>() {
+                Some(Projection::Perspective(projection)) => Some(export_perspective(projection)),
+                Some(Projection::Orthographic(projection)) => Some(export_orthographic(projection)),
+                None => entity_ref
+                    .get::<OrthographicProjection>()
+                    .map(export_orthographic),
+            };
+            node.camera = camera.map(|camera| self.root.push(camera));
+        }
+
+        let light = if let Some(light) = entity_ref.get::<DirectionalLight>() {
+            Some(export_light(
+                khr_lights_punctual::Type::Directional,
+                light.color.as_rgba_f32(),
+                // NOTE: KHR_punctual_lights defines the intensity units for directional lights in
+                // lux (lm/m^2) which is what we have.
+                light.illuminance,
+                None,
+                None,
+            ))
+        } else if let Some(light) = entity_ref.get::<PointLight>() {
+            Some(export_light(
+                khr_lights_punctual::Type::Point,
+                light.color.as_rgba_f32(),
+                // NOTE: KHR_punctual_lights defines the intensity units for point lights in
+                // candela (lm/sr), and we have the luminous power, which is 4 * pi * luminous
+                // intensity.
+                light.intensity / (std::f32::consts::PI * 4.0),
+                Some(light.range),
+                None,
+            ))
+        } else {
+            entity_ref.get::<SpotLight>().map(|light| {
+                export_light(
+                    khr_lights_punctual::Type::Spot,
+                    light.color.as_rgba_f32(),
+                    // NOTE: See the point light, spot lights use the same mapping.
+                    light.intensity / (std::f32::consts::PI * 4.0),
+                    Some(light.range),
+                    Some(khr_lights_punctual::Spot {
+                        inner_cone_angle: light.inner_angle,
+                        outer_cone_angle: light.outer_angle,
+                    }),
+                )
+            })
+        };
+        if let Some(light) = light {
+            node.extensions = Some(json::extensions::scene::Node {
+                khr_lights_punctual: Some(khr_lights_punctual::KhrLightsPunctual {
+                    light: self.push_light(light),
+                }),
+                ..Default::default()
+            });
+        }
+
+        if let Some(children) = entity_ref.get::<Children>() {
+            let children = children
+                .iter()
+                .filter_map(|child| self.export_node(world, *child))
+                .collect::<Vec<_>>();
+            if !children.is_empty() {
+                node.children = Some(children);
+            }
+        }
+
+        Some(self.root.push(node))
+    }
+
+    fn export_mesh(
+        &mut self,
+        id: AssetId<Mesh>,
+        material: Option<AssetId<StandardMaterial>>,
+    ) -> Option<json::Index<json::Mesh>> {
+        if let Some(index) = self.exported_meshes.get(&(id, material)) {
+            return *index;
+        }
+        let index = self.meshes.get(id).and_then(|mesh| {
+            let primitive = self.export_primitive(mesh, material)?;
+            Some(self.root.push(json::Mesh {
+                extensions: Default::default(),
+                extras: Default::default(),
+                name: None,
+                primitives: vec![primitive],
+                weights: None,
+            }))
+        });
+        self.exported_meshes.insert((id, material), index);
+        index
+    }
+
+    fn export_primitive(
+        &mut self,
+        mesh: &Mesh,
+        material: Option<AssetId<StandardMaterial>>,
+    ) -> Option<json::mesh::Primitive> {
+        let mode = match mesh.primitive_topology() {
+            PrimitiveTopology::PointList => Mode::Points,
+            PrimitiveTopology::LineList => Mode::Lines,
+            PrimitiveTopology::LineStrip => Mode::LineStrip,
+            PrimitiveTopology::TriangleList => Mode::Triangles,
+            PrimitiveTopology::TriangleStrip => Mode::TriangleStrip,
+        };
+
+        let Some(VertexAttributeValues::Float32x3(positions)) =
+            mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+        else {
+            warn!("Mesh ignored by the glTF exporter: it doesn't have Float32x3 positions");
+            return None;
+        };
+        let (min, max) = positions.iter().fold(
+            (Vec3::splat(f32::MAX), Vec3::splat(f32::MIN)),
+            |(min, max), position| {
+                let position = Vec3::from_array(*position);
+                (min.min(position), max.max(position))
+            },
+        );
+
+        let mut attributes = std::collections::BTreeMap::new();
+        for (attribute, semantic) in [
+            (Mesh::ATTRIBUTE_POSITION, Semantic::Positions),
+            (Mesh::ATTRIBUTE_NORMAL, Semantic::Normals),
+            (Mesh::ATTRIBUTE_TANGENT, Semantic::Tangents),
+            (Mesh::ATTRIBUTE_UV_0, Semantic::TexCoords(0)),
+            (Mesh::ATTRIBUTE_UV_1, Semantic::TexCoords(1)),
+            (Mesh::ATTRIBUTE_COLOR, Semantic::Colors(0)),
+        ] {
+            if let Some(accessor) = self.export_attribute(mesh, attribute) {
+                attributes.insert(Checked::Valid(semantic), accessor);
+            }
+        }
+        if let Some(accessor) = attributes.get_mut(&Checked::Valid(Semantic::Positions)) {
+            let accessor = &mut self.root.accessors[accessor.value()];
+            accessor.min = Some(json::Value::from(min.to_array().to_vec()));
+            accessor.max = Some(json::Value::from(max.to_array().to_vec()));
+        }
+
+        let indices = mesh.indices().map(|indices| {
+            let (component_type, bytes): (_, Vec<u8>) = match indices {
+                Indices::U16(indices) => (
+                    ComponentType::U16,
+                    indices
+                        .iter()
+                        .flat_map(|index| index.to_le_bytes())
+                        .collect(),
+                ),
+                Indices::U32(indices) => (
+                    ComponentType::U32,
+                    indices
+                        .iter()
+                        .flat_map(|index| index.to_le_bytes())
+                        .collect(),
+                ),
+            };
+            self.push_accessor(
+                &bytes,
+                indices.len(),
+                component_type,
+                Type::Scalar,
+                Target::ElementArrayBuffer,
+            )
+        });
+
+        Some(json::mesh::Primitive {
+            attributes,
+            extensions: Default::default(),
+            extras: Default::default(),
+            indices,
+            material: material.and_then(|material| self.export_material(material)),
+            mode: Checked::Valid(mode),
+            targets: None,
+        })
+    }
+
+    fn export_attribute(
+        &mut self,
+        mesh: &Mesh,
+        attribute: MeshVertexAttribute,
+    ) -> Option<json::Index<json::Accessor>> {
+        let values = mesh.attribute(attribute.id)?;
+        let type_ = match values {
+            VertexAttributeValues::Float32x2(_) => Type::Vec2,
+            VertexAttributeValues::Float32x3(_) => Type::Vec3,
+            VertexAttributeValues::Float32x4(_) => Type::Vec4,
+            _ => {
+                warn!(
+                    "Vertex attribute {} ignored by the glTF exporter: its format isn't supported",
+                    attribute.name
+                );
+                return None;
+            }
+        };
+        Some(self.push_accessor(
+            values.get_bytes(),
+            values.len(),
+            ComponentType::F32,
+            type_,
+            Target::ArrayBuffer,
+        ))
+    }
+
+    fn export_material(
+        &mut self,
+        id: AssetId<StandardMaterial>,
+    ) -> Option<json::Index<json::Material>> {
+        if let Some(index) = self.exported_materials.get(&id) {
+            return Some(*index);
+        }
+        let material = self.materials.get(id)?;
+
+        let (alpha_mode, alpha_cutoff) = match material.alpha_mode {
+            AlphaMode::Opaque => (json::material::AlphaMode::Opaque, None),
+            AlphaMode::Mask(cutoff) => (
+                json::material::AlphaMode::Mask,
+                Some(json::material::AlphaCutoff(cutoff)),
+            ),
+            _ => (json::material::AlphaMode::Blend, None),
+        };
+        // The emissive factor is limited to 1, so brighter colors use the
+        // `KHR_materials_emissive_strength` extension.
+        let [red, green, blue, _] = material.emissive.as_linear_rgba_f32();
+        let emissive_strength = red.max(green).max(blue).max(1.0);
+        let mut extensions = json::extensions::material::Material::default();
+        if emissive_strength > 1.0 {
+            self.use_extension("KHR_materials_emissive_strength");
+            extensions.emissive_strength = Some(json::extensions::material::EmissiveStrength {
+                emissive_strength: json::extensions::material::EmissiveStrengthFactor(
+                    emissive_strength,
+                ),
+            });
+        }
+        if material.unlit {
+            self.use_extension("KHR_materials_unlit");
+            extensions.unlit = Some(json::extensions::material::Unlit {});
+        }
+
+        let index = self.root.push(json::Material {
+            alpha_cutoff,
+            alpha_mode: Checked::Valid(alpha_mode),
+            double_sided: material.double_sided,
+            pbr_metallic_roughness: json::material::PbrMetallicRoughness {
+                base_color_factor: json::material::PbrBaseColorFactor(
+                    material.base_color.as_linear_rgba_f32(),
+                ),
+                metallic_factor: json::material::StrengthFactor(material.metallic),
+                roughness_factor: json::material::StrengthFactor(material.perceptual_roughness),
+                ..Default::default()
+            },
+            emissive_factor: json::material::EmissiveFactor([
+                red / emissive_strength,
+                green / emissive_strength,
+                blue / emissive_strength,
+            ]),
+            extensions: Some(extensions),
+            ..Default::default()
+        });
+        self.exported_materials.insert(id, index);
+        Some(index)
+    }
+
+    fn push_light(
+        &mut self,
+        light: khr_lights_punctual::Light,
+    ) -> json::Index<khr_lights_punctual::Light> {
+        self.use_extension("KHR_lights_punctual");
+        let lights = &mut self
+            .root
+            .extensions
+            .get_or_insert_with(Default::default)
+            .khr_lights_punctual
+            .get_or_insert_with(Default::default)
+            .lights;
+        json::Index::push(lights, light)
+    }
+
+    /// Appends `bytes` to the buffer, and returns an accessor to them.
+    fn push_accessor(
+        &mut self,
+        bytes: &[u8],
+        count: usize,
+        component_type: ComponentType,
+        type_: Type,
+        target: Target,
+    ) -> json::Index<json::Accessor> {
+        // Accessors must be aligned to the size of their components.
+        self.buffer.resize(self.buffer.len().next_multiple_of(4), 0);
+        let view = self.root.push(json::buffer::View {
+            buffer: json::Index::new(0),
+            byte_length: USize64::from(bytes.len()),
+            byte_offset: Some(USize64::from(self.buffer.len())),
+            byte_stride: None,
+            name: None,
+            target: Some(Checked::Valid(target)),
+            extensions: Default::default(),
+            extras: Default::default(),
+        });
+        self.buffer.extend_from_slice(bytes);
+        self.root.push(json::Accessor {
+            buffer_view: Some(view),
+            byte_offset: None,
+            count: USize64::from(count),
+            component_type: Checked::Valid(GenericComponentType(component_type)),
+            extensions: Default::default(),
+            extras: Default::default(),
+            type_: Checked::Valid(type_),
+            min: None,
+            max: None,
+            name: None,
+            normalized: false,
+            sparse: None,
+        })
+    }
+
+    fn use_extension(&mut self, name: &str) {
+        if !self.root.extensions_used.iter().any(|used| used == name) {
+            self.root.extensions_used.push(name.to_string());
+        }
+    }
+}
+
+fn export_perspective(projection: &PerspectiveProjection) -> json::Camera {
+    json::Camera {
+        name: None,
+        orthographic: None,
+        perspective: Some(json::camera::Perspective {
+            aspect_ratio: Some(projection.aspect_ratio),
+            yfov: projection.fov,
+            zfar: Some(projection.far),
+            znear: projection.near,
+            extensions: Default::default(),
+            extras: Default::default(),
+        }),
+        type_: Checked::Valid(json::camera::Type::Perspective),
+        extensions: Default::default(),
+        extras: Default::default(),
+    }
+}
+
+fn export_orthographic(projection: &OrthographicProjection) -> json::Camera {
+    json::Camera {
+        name: None,
+        orthographic: Some(json::camera::Orthographic {
+            xmag: projection.area.width() / 2.0,
+            ymag: projection.area.height() / 2.0,
+            zfar: projection.far,
+            znear: projection.near,
+            extensions: Default::default(),
+            extras: Default::default(),
+        }),
+        perspective: None,
+        type_: Checked::Valid(json::camera::Type::Orthographic),
+        extensions: Default::default(),
+        extras: Default::default(),
+    }
+}
+
+fn export_light(
+    type_: khr_lights_punctual::Type,
+    [red, green, blue, _]: [f32; 4],
+    intensity: f32,
+    range: Option<f32>,
+    spot: Option<khr_lights_punctual::Spot>,
+) -> khr_lights_punctual::Light {
+    khr_lights_punctual::Light {
+        color: [red, green, blue],
+        extensions: None,
+        extras: Default::default(),
+        intensity,
+        name: None,
+        range,
+        spot,
+        type_: Checked::Valid(type_),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GltfExporter;
+    use bevy_asset::Assets;
+    use bevy_core::Name;
+    use bevy_ecs::world::World;
+    use bevy_hierarchy::BuildWorldChildren;
+    use bevy_pbr::{PointLight, StandardMaterial};
+    use bevy_render::{
+        camera::{Camera, Projection},
+        color::Color,
+        mesh::{Indices, Mesh, PrimitiveTopology},
+        render_asset::RenderAssetUsages,
+    };
+    use bevy_transform::components::Transform;
+
+    #[test]
+    fn export_hierarchy() {
+        let mut meshes = Assets::<Mesh>::default();
+        let mut materials = Assets::<StandardMaterial>::default();
+        let mesh = meshes.add(
+            Mesh::new(
+                PrimitiveTopology::TriangleList,
+                RenderAssetUsages::default(),
+            )
+            .with_inserted_attribute(
+                Mesh::ATTRIBUTE_POSITION,
+                vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 2.0, 0.0]],
+            )
+            .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, vec![[0.0, 0.0, 1.0]; 3])
+            .with_inserted_indices(Indices::U16(vec![0, 1, 2])),
+        );
+        let material = materials.add(StandardMaterial {
+            base_color: Color::RED,
+            unlit: true,
+            ..Default::default()
+        });
+
+        let mut world = World::new();
+        let triangles = [
+            world.spawn((mesh.clone(), material.clone())).id(),
+            world
+                .spawn((mesh, material, Transform::from_xyz(1.0, 0.0, 0.0)))
+                .id(),
+        ];
+        let light = world.spawn(PointLight::default()).id();
+        let camera = world.spawn((Camera::default(), Projection::default())).id();
+        let root = world
+            .spawn(Name::new("Root"))
+            .push_children(&triangles)
+            .push_children(&[light, camera])
+            .id();
+
+        let mut exporter = GltfExporter::new(&meshes, &materials);
+        assert_eq!(exporter.add_scene(Some("Level"), &world, [root]), 0);
+        let glb = exporter.to_glb().unwrap();
+
+        let gltf = gltf::Gltf::from_slice(&glb).unwrap();
+        assert_eq!(gltf.scenes().len(), 1);
+        assert_eq!(gltf.nodes().len(), 5);
+        assert_eq!(gltf.meshes().len(), 1);
+        assert_eq!(gltf.materials().len(), 1);
+        assert_eq!(gltf.cameras().len(), 1);
+        assert_eq!(gltf.lights().unwrap().len(), 1);
+        assert!(gltf.materials().next().unwrap().unlit());
+
+        let root_node = gltf.default_scene().unwrap().nodes().next().unwrap();
+        assert_eq!(root_node.name(), Some("Root"));
+        assert_eq!(root_node.children().len(), 4);
+
+        let primitive = gltf.meshes().next().unwrap().primitives().next().unwrap();
+        let positions = primitive.get(&gltf::Semantic::Positions).unwrap();
+        assert_eq!(positions.count(), 3);
+        assert_eq!(positions.max(), Some(serde_json::json!([1.0, 2.0, 0.0])));
+        assert_eq!(primitive.indices().unwrap().count(), 3);
+    }
+}
@@ -7,13 +7,20 @@
 use bevy_animation::AnimationClip;
 use bevy_utils::HashMap;
 
+mod export;
 mod loader;
 mod vertex_attributes;
+pub use export::*;
 pub use loader::*;
 
 use bevy_app::prelude::*;
 use bevy_asset::{Asset, AssetApp, Handle};
-use bevy_ecs::{prelude::Component, reflect::ReflectComponent};
+use bevy_ecs::{
+    prelude::{Component, Entity, World},
+    reflect::ReflectComponent,
+    system::Command,
+};
+use bevy_hierarchy::Children;
 use bevy_pbr::StandardMaterial;
 use bevy_reflect::{Reflect, TypePath};
 use bevy_render::{
@@ -49,6 +56,7 @@ impl GltfPlugin {
 impl Plugin for GltfPlugin {
     fn build(&self, app: &mut App) {
         app.register_type::<GltfExtras>()
+            .register_type::<GltfMaterialVariants>()
             .init_asset::<Gltf>()
             .init_asset::<GltfNode>()
             .init_asset::<GltfPrimitive>()
@@ -88,6 +96,9 @@ pub struct Gltf {
     pub nodes: Vec<Handle<GltfNode>>,
     /// Named nodes loaded from the glTF file.
     pub named_nodes: HashMap<String, Handle<GltfNode>>,
+    /// Names of the material variants defined by the `KHR_materials_variants` extension, in the
+    /// order of their indices. See [`SelectMaterialVariant`].
+    pub variants: Vec<String>,
     /// Default scene to be displayed.
     pub default_scene: Option<Handle<Scene>>,
     /// All animations loaded from the glTF file.
@@ -143,6 +154,90 @@ pub struct GltfPrimitive {
     pub material_extras: Option<GltfExtras>,
 }
 
+/// The materials a glTF primitive can use for each of the variants defined by the
+/// `KHR_materials_variants` extension.
+///
+/// This component is added to the entities of the primitives that have material variants when
+/// a glTF scene is spawned. Use [`SelectMaterialVariant`] to switch between them.
+///
+/// See [the extension specification](https://github.com/KhronosGroup/glTF/blob/main/extensions/2.0/Khronos/KHR_materials_variants/README.md).
+#[derive(Clone, Debug, Reflect, Default, Component)]
+#[reflect(Component)]
+pub struct GltfMaterialVariants {
+    /// Material used when no variant is selected.
+    pub default: Handle<StandardMaterial>,
+    /// Material used for each variant, by name.
+    pub variants: HashMap<String, Handle<StandardMaterial>>,
+}
+
+impl GltfMaterialVariants {
+    /// Returns the material used for `variant`, or the default material if the primitive has no
+    /// material for it or if `variant` is `None`.
+    pub fn material(&self, variant: Option<&str>) -> &Handle<StandardMaterial> {
+        variant
+            .and_then(|variant| self.variants.get(variant))
+            .unwrap_or(&self.default)
+    }
+}
+
+/// A [`Command`] switching the materials of the primitives of a spawned glTF scene to a
+/// variant defined by the `KHR_materials_variants` extension.
+///
+/// Every descendant of `root` with a [`GltfMaterialVariants`] component uses the material of
+/// `variant`, or its default material if it has none for it. A `variant` of `None` restores the
+/// default materials. The names of the variants of a file are listed in [`Gltf::variants`].
+///
+/// ```
+/// # use bevy_ecs::prelude::*;
+/// # use bevy_gltf::SelectMaterialVariant;
+/// fn select_variant(mut commands: Commands, scene_root: Query<Entity, With<Handle<Scene>>>) {
+///     for root in &scene_root {
+///         commands.add(SelectMaterialVariant::new(root, Some("Midnight")));
+///     }
+/// }
+/// # use bevy_asset::Handle;
+/// # use bevy_scene::Scene;
+/// # bevy_ecs::system::assert_is_system(select_variant);
+/// ```
+#[derive(Clone, Debug)]
+pub struct SelectMaterialVariant {
+    /// The root entity of the spawned scene.
+    pub root: Entity,
+    /// The name of the selected variant, or `None` for the default materials.
+    pub variant: Option<String>,
+}
+
+impl SelectMaterialVariant {
+    /// Creates a command selecting `variant` for the scene spawned under `root`.
+    pub fn new(root: Entity, variant: Option<impl Into<String>>) -> Self {
+        Self {
+            root,
+            variant: variant.map(Into::into),
+        }
+    }
+}
+
+impl Command for SelectMaterialVariant {
+    fn apply(self, world: &mut World) {
+        let mut stack = vec![self.root];
+        while let Some(entity) = stack.pop() {
+            let Some(mut entity_mut) = world.get_entity_mut(entity) else {
+                continue;
+            };
+            if let Some(children) = entity_mut.get::<Children>() {
+                stack.extend(children.iter().copied());
+            }
+            let Some(material) = entity_mut
+                .get::<GltfMaterialVariants>()
+                .map(|variants| variants.material(self.variant.as_deref()).clone())
+            else {
+                continue;
+            };
+            entity_mut.insert(material);
+        }
+    }
+}
+
 /// Additional untyped data that can be present on most glTF types.
 ///
 /// See [the relevant glTF specification section](https://registry.khronos.org/glTF/specs/2.0/glTF-2.0.html#reference-extras).
@@ -152,3 +247,48 @@ pub struct GltfExtras {
     /// Content of the extra data.
     pub value: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{GltfMaterialVariants, SelectMaterialVariant};
+    use bevy_asset::Handle;
+    use bevy_ecs::{system::Command, world::World};
+    use bevy_hierarchy::BuildWorldChildren;
+    use bevy_pbr::StandardMaterial;
+
+    #[test]
+    fn select_material_variant() {
+        let default = Handle::<StandardMaterial>::weak_from_u128(1);
+        let midnight = Handle::<StandardMaterial>::weak_from_u128(2);
+        let mut world = World::new();
+        let primitive = world
+            .spawn((
+                default.clone(),
+                GltfMaterialVariants {
+                    default: default.clone(),
+                    variants: [("Midnight".to_string(), midnight.clone())].into(),
+                },
+            ))
+            .id();
+        let root = world.spawn_empty().push_children(&[primitive]).id();
+
+        SelectMaterialVariant::new(root, Some("Midnight")).apply(&mut world);
+        assert_eq!(
+            world.get::<Handle<StandardMaterial>>(primitive),
+            Some(&midnight)
+        );
+
+        SelectMaterialVariant::new(root, Some("Daylight")).apply(&mut world);
+        assert_eq!(
+            world.get::<Handle<StandardMaterial>>(primitive),
+            Some(&default)
+        );
+
+        SelectMaterialVariant::new(root, Some("Midnight")).apply(&mut world);
+        SelectMaterialVariant::new(root, None::<String>).apply(&mut world);
+        assert_eq!(
+            world.get::<Handle<StandardMaterial>>(primitive),
+            Some(&default)
+        );
+    }
+}
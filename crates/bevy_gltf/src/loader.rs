@@ -1,4 +1,6 @@
-use crate::{vertex_attributes::convert_attribute, Gltf, GltfExtras, GltfNode};
+use crate::{
+    vertex_attributes::convert_attribute, Gltf, GltfExtras, GltfMaterialVariants, GltfNode,
+};
 use bevy_animation::{AnimationTarget, AnimationTargetId};
 use bevy_asset::{
     io::Reader, AssetLoadError, AssetLoader, AsyncReadExt, Handle, LoadContext, ReadAssetBytesError,
@@ -102,6 +104,12 @@ pub enum GltfError {
 }
 
 /// Loads glTF files with all of their data as their corresponding bevy representations.
+///
+/// The sub-assets of a file can be loaded with their label, such as `model.glb#Animation0` for
+/// the first animation. Named animations, materials, meshes and nodes can also be loaded by name
+/// with the `Animation(Name)`, `Material(Name)`, `Mesh(Name)` and `Node(Name)` labels, such as
+/// `model.glb#Animation(Run)`. Those labels refer to a copy of the sub-asset, so their handles
+/// are different from the ones of the index-based labels.
 pub struct GltfLoader {
     /// List of compressed image formats handled by the loader.
     pub supported_compressed_formats: CompressedImageFormats,
@@ -140,6 +148,12 @@ pub struct GltfLoaderSettings {
     pub load_lights: bool,
     /// If true, the loader will include the root of the gltf root node.
     pub include_source: bool,
+    /// If set, the loaded animation clips are compressed with these settings.
+    ///
+    /// See [`AnimationClip::compress`](bevy_animation::AnimationClip::compress).
+    #[cfg(feature = "bevy_animation")]
+    #[serde(default)]
+    pub animation_compression: Option<bevy_animation::compression::AnimationCompressionSettings>,
 }
 
 impl Default for GltfLoaderSettings {
@@ -149,6 +163,8 @@ impl Default for GltfLoaderSettings {
             load_cameras: true,
             load_lights: true,
             include_source: false,
+            #[cfg(feature = "bevy_animation")]
+            animation_compression: None,
         }
     }
 }
@@ -290,9 +306,18 @@ async fn load_gltf<'a, 'b, 'c>(
                     );
                 }
             }
-            let handle = load_context
-                .add_labeled_asset(format!("Animation{}", animation.index()), animation_clip);
-            if let Some(name) = animation.name() {
+            if let Some(compression) = &settings.animation_compression {
+                animation_clip.compress(compression);
+            }
+            let label = format!("Animation{}", animation.index());
+            let name = animation
+                .name()
+                .filter(|name| is_unique_name(&named_animations, "animation", name));
+            if let Some(name) = name {
+                load_context.add_label_alias(named_label("Animation", name), label.clone());
+            }
+            let handle = load_context.add_labeled_asset(label, animation_clip);
+            if let Some(name) = name {
                 named_animations.insert(name.to_string(), handle.clone());
             }
             animations.push(handle);
@@ -381,8 +406,17 @@ async fn load_gltf<'a, 'b, 'c>(
     let mut named_materials = HashMap::default();
     // NOTE: materials must be loaded after textures because image load() calls will happen before load_with_settings, preventing is_srgb from being set properly
     for material in gltf.materials() {
+        let name = material
+            .name()
+            .filter(|name| is_unique_name(&named_materials, "material", name));
+        if let Some(name) = name {
+            load_context.add_label_alias(
+                named_label("Material", name),
+                material_label(&material, false),
+            );
+        }
         let handle = load_material(&material, load_context, false);
-        if let Some(name) = material.name() {
+        if let Some(name) = name {
             named_materials.insert(name.to_string(), handle.clone());
         }
         materials.push(handle);
@@ -518,14 +552,18 @@ async fn load_gltf<'a, 'b, 'c>(
             });
         }
 
-        let handle = load_context.add_labeled_asset(
-            mesh_label(&gltf_mesh),
-            super::GltfMesh {
-                primitives,
-                extras: get_gltf_extras(gltf_mesh.extras()),
-            },
-        );
-        if let Some(name) = gltf_mesh.name() {
+        let gltf_mesh_asset = super::GltfMesh {
+            primitives,
+            extras: get_gltf_extras(gltf_mesh.extras()),
+        };
+        let name = gltf_mesh
+            .name()
+            .filter(|name| is_unique_name(&named_meshes, "mesh", name));
+        if let Some(name) = name {
+            load_context.add_label_alias(named_label("Mesh", name), mesh_label(&gltf_mesh));
+        }
+        let handle = load_context.add_labeled_asset(mesh_label(&gltf_mesh), gltf_mesh_asset);
+        if let Some(name) = name {
             named_meshes.insert(name.to_string(), handle.clone());
         }
         meshes.push(handle);
@@ -533,8 +571,15 @@ async fn load_gltf<'a, 'b, 'c>(
 
     let mut nodes_intermediate = vec![];
     let mut named_nodes_intermediate = HashMap::default();
+    let mut node_names = HashMap::default();
     for node in gltf.nodes() {
         let node_label = node_label(&node);
+        let name = node
+            .name()
+            .filter(|name| is_unique_name(&named_nodes_intermediate, "node", name));
+        if let Some(name) = name {
+            node_names.insert(node_label.clone(), name.to_string());
+        }
         nodes_intermediate.push((
             node_label,
             GltfNode {
@@ -550,13 +595,18 @@ async fn load_gltf<'a, 'b, 'c>(
                 .map(|child| child.index())
                 .collect::<Vec<_>>(),
         ));
-        if let Some(name) = node.name() {
+        if let Some(name) = name {
             named_nodes_intermediate.insert(name, node.index());
         }
     }
     let nodes = resolve_node_hierarchy(nodes_intermediate, load_context.path())
         .into_iter()
-        .map(|(label, node)| load_context.add_labeled_asset(label, node))
+        .map(|(label, node)| {
+            if let Some(name) = node_names.get(&label) {
+                load_context.add_label_alias(named_label("Node", name), label.clone());
+            }
+            load_context.add_labeled_asset(label, node)
+        })
         .collect::<Vec<Handle<GltfNode>>>();
     let named_nodes = named_nodes_intermediate
         .into_iter()
@@ -586,6 +636,15 @@ async fn load_gltf<'a, 'b, 'c>(
 
     let mut scenes = vec![];
     let mut named_scenes = HashMap::default();
+    let variants = gltf
+        .variants()
+        .map(|variants| {
+            variants
+                .map(|variant| variant.name().to_string())
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+
     let mut active_camera_found = false;
     for scene in gltf.scenes() {
         let mut err = None;
@@ -608,6 +667,7 @@ async fn load_gltf<'a, 'b, 'c>(
                         &mut active_camera_found,
                         &Transform::default(),
                         &animation_roots,
+                        &variants,
                         None,
                     );
                     if result.is_err() {
@@ -679,6 +739,7 @@ async fn load_gltf<'a, 'b, 'c>(
         named_materials,
         nodes,
         named_nodes,
+        variants,
         #[cfg(feature = "bevy_animation")]
         animations,
         #[cfg(feature = "bevy_animation")]
@@ -810,6 +871,34 @@ async fn load_image<'a, 'b>(
     }
 }
 
+/// Loads the materials of the variants of `primitive` defined by the `KHR_materials_variants`
+/// extension, and returns them by variant name.
+fn load_variant_materials(
+    primitive: &Primitive,
+    variants: &[String],
+    root_load_context: &LoadContext,
+    load_context: &mut LoadContext,
+    is_scale_inverted: bool,
+) -> HashMap<String, Handle<StandardMaterial>> {
+    let mut variant_materials = HashMap::default();
+    for mapping in primitive.mappings() {
+        let material = mapping.material();
+        let material_label = material_label(&material, is_scale_inverted);
+        if !root_load_context.has_labeled_asset(&material_label)
+            && !load_context.has_labeled_asset(&material_label)
+        {
+            load_material(&material, load_context, is_scale_inverted);
+        }
+        let handle = load_context.get_label_handle(&material_label);
+        for variant in mapping.variants() {
+            if let Some(name) = variants.get(*variant as usize) {
+                variant_materials.insert(name.clone(), handle.clone());
+            }
+        }
+    }
+    variant_materials
+}
+
 /// Loads a glTF material as a bevy [`StandardMaterial`] and returns it.
 fn load_material(
     material: &Material,
@@ -1009,6 +1098,7 @@ fn load_node(
     active_camera_found: &mut bool,
     parent_transform: &Transform,
     animation_roots: &HashSet<usize>,
+    variants: &[String],
     mut animation_context: Option<AnimationContext>,
 ) -> Result<(), GltfError> {
     let mut gltf_error = None;
@@ -1152,6 +1242,20 @@ fn load_node(
                         Vec3::from_slice(&bounds.max),
                     ));
 
+                    let variant_materials = load_variant_materials(
+                        &primitive,
+                        variants,
+                        root_load_context,
+                        load_context,
+                        is_scale_inverted,
+                    );
+                    if !variant_materials.is_empty() {
+                        mesh_entity.insert(GltfMaterialVariants {
+                            default: load_context.get_label_handle(&material_label),
+                            variants: variant_materials,
+                        });
+                    }
+
                     if let Some(extras) = primitive.extras() {
                         mesh_entity.insert(GltfExtras {
                             value: extras.get().to_string(),
@@ -1258,6 +1362,7 @@ fn load_node(
                 active_camera_found,
                 &world_transform,
                 animation_roots,
+                variants,
                 animation_context.clone(),
             ) {
                 gltf_error = Some(err);
@@ -1354,6 +1459,29 @@ fn node_label(node: &Node) -> String {
     format!("Node{}", node.index())
 }
 
+/// Returns the label of a named sub-asset, such as `Animation(Run)`, which is an alias of its
+/// index-based label.
+fn named_label(kind: &str, name: &str) -> String {
+    format!("{kind}({name})")
+}
+
+/// Returns `true` if no other sub-asset of this `kind` is already named `name`, and warns
+/// otherwise. Only the first sub-asset with a name can be requested by it.
+fn is_unique_name<K: std::borrow::Borrow<str> + Eq + std::hash::Hash, V>(
+    named: &HashMap<K, V>,
+    kind: &str,
+    name: &str,
+) -> bool {
+    if named.contains_key(name) {
+        warn!(
+            "Several glTF {kind} sub-assets are named {name:?}, only the first one can be \
+            requested by name"
+        );
+        return false;
+    }
+    true
+}
+
 /// Returns the label for the `scene`.
 fn scene_label(scene: &gltf::Scene) -> String {
     format!("Scene{}", scene.index())
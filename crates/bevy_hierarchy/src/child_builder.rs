@@ -689,9 +689,153 @@ impl<'w> BuildWorldChildren for EntityWorldMut<'w> {
     }
 }
 
+/// Command that spawns a batch of parents, each with its own batch of children.
+///
+/// See [`BatchSpawnChildren::spawn_batch_with_children`].
+pub struct SpawnBatchWithChildren<I> {
+    /// The bundles of the parents, each with the bundles of its children.
+    pub bundles: I,
+}
+
+impl<I, P, C> Command for SpawnBatchWithChildren<I>
+where
+    I: IntoIterator<Item = (P, C)> + Send + 'static,
+    P: Bundle,
+    C: IntoIterator,
+    C::Item: Bundle,
+{
+    fn apply(self, world: &mut World) {
+        world.spawn_batch_with_children(self.bundles);
+    }
+}
+
+/// Trait that defines spawning batches of parents with their children through [`Commands`].
+pub trait BatchSpawnChildren {
+    /// Spawns a parent for each item of `bundles`, with a child for each of the bundles paired
+    /// with it, in one command.
+    ///
+    /// This is faster than spawning each entity and adding it as a child separately, see
+    /// [`BatchSpawnWorldChildren::spawn_batch_with_children`].
+    ///
+    /// ```
+    /// # use bevy_ecs::prelude::*;
+    /// # use bevy_hierarchy::BatchSpawnChildren;
+    /// # #[derive(Component)]
+    /// # struct Car;
+    /// # #[derive(Component)]
+    /// # struct Wheel;
+    /// # fn system(mut commands: Commands) {
+    /// commands.spawn_batch_with_children((0..100).map(|_| (Car, [Wheel, Wheel, Wheel, Wheel])));
+    /// # }
+    /// # bevy_ecs::system::assert_is_system(system);
+    /// ```
+    fn spawn_batch_with_children<I, P, C>(&mut self, bundles: I)
+    where
+        I: IntoIterator<Item = (P, C)> + Send + 'static,
+        P: Bundle,
+        C: IntoIterator,
+        C::Item: Bundle;
+}
+
+impl BatchSpawnChildren for Commands<'_, '_> {
+    fn spawn_batch_with_children<I, P, C>(&mut self, bundles: I)
+    where
+        I: IntoIterator<Item = (P, C)> + Send + 'static,
+        P: Bundle,
+        C: IntoIterator,
+        C::Item: Bundle,
+    {
+        self.add(SpawnBatchWithChildren { bundles });
+    }
+}
+
+/// Trait that defines spawning batches of parents with their children directly through the [`World`].
+pub trait BatchSpawnWorldChildren {
+    /// Spawns a parent for each item of `bundles`, with a child for each of the bundles paired
+    /// with it, and returns the parents.
+    ///
+    /// All the entities are allocated up front, and each of them is spawned with its [`Parent`]
+    /// or [`Children`] component in a single batch per bundle type, which is faster than spawning
+    /// each entity and adding it as a child separately. A [`HierarchyEvent::ChildAdded`] event is
+    /// sent for each child.
+    ///
+    /// ```
+    /// # use bevy_ecs::prelude::*;
+    /// # use bevy_hierarchy::{BatchSpawnWorldChildren, Children};
+    /// # #[derive(Component)]
+    /// # struct Car;
+    /// # #[derive(Component)]
+    /// # struct Wheel;
+    /// let mut world = World::new();
+    /// let cars =
+    ///     world.spawn_batch_with_children((0..100).map(|_| (Car, [Wheel, Wheel, Wheel, Wheel])));
+    /// assert_eq!(world.get::<Children>(cars[0]).unwrap().len(), 4);
+    /// ```
+    fn spawn_batch_with_children<I, P, C>(&mut self, bundles: I) -> Vec<Entity>
+    where
+        I: IntoIterator<Item = (P, C)>,
+        P: Bundle,
+        C: IntoIterator,
+        C::Item: Bundle;
+}
+
+impl BatchSpawnWorldChildren for World {
+    fn spawn_batch_with_children<I, P, C>(&mut self, bundles: I) -> Vec<Entity>
+    where
+        I: IntoIterator<Item = (P, C)>,
+        P: Bundle,
+        C: IntoIterator,
+        C::Item: Bundle,
+    {
+        let trees: Vec<(P, Vec<C::Item>)> = bundles
+            .into_iter()
+            .map(|(parent, children)| (parent, children.into_iter().collect()))
+            .collect();
+        let len = trees.len()
+            + trees
+                .iter()
+                .map(|(_, children)| children.len())
+                .sum::<usize>();
+        let mut entities = self
+            .entities()
+            .reserve_entities(len as u32)
+            .collect::<Vec<_>>()
+            .into_iter();
+        let parents: Vec<Entity> = entities.by_ref().take(trees.len()).collect();
+
+        let mut parent_batch = Vec::new();
+        let mut childless_parent_batch = Vec::new();
+        let mut child_batch = Vec::with_capacity(entities.len());
+        let mut events = Vec::with_capacity(entities.len());
+        for (&parent, (parent_bundle, child_bundles)) in parents.iter().zip(trees) {
+            if child_bundles.is_empty() {
+                childless_parent_batch.push((parent, parent_bundle));
+                continue;
+            }
+            let children: SmallVec<[Entity; 8]> =
+                entities.by_ref().take(child_bundles.len()).collect();
+            for (&child, child_bundle) in children.iter().zip(child_bundles) {
+                child_batch.push((child, (child_bundle, Parent(parent))));
+                events.push(HierarchyEvent::ChildAdded { child, parent });
+            }
+            parent_batch.push((parent, (parent_bundle, Children(children))));
+        }
+
+        // The entities were just reserved, so none of them can have an invalid generation.
+        self.insert_or_spawn_batch(parent_batch)
+            .expect("reserved entities can always be spawned");
+        self.insert_or_spawn_batch(childless_parent_batch)
+            .expect("reserved entities can always be spawned");
+        self.insert_or_spawn_batch(child_batch)
+            .expect("reserved entities can always be spawned");
+        push_events(self, events);
+        parents
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{BuildChildren, BuildWorldChildren};
+    use super::{BatchSpawnChildren, BatchSpawnWorldChildren, BuildChildren, BuildWorldChildren};
     use crate::{
         components::{Children, Parent},
         HierarchyEvent::{self, ChildAdded, ChildMoved, ChildRemoved},
@@ -882,6 +1026,41 @@ mod tests {
         assert_eq!(*world.get::<Parent>(children[1]).unwrap(), Parent(parent));
     }
 
+    #[test]
+    fn spawn_batch_with_children() {
+        let mut world = World::default();
+        world.init_resource::<Events<HierarchyEvent>>();
+
+        let parents = world.spawn_batch_with_children([
+            (C(1), vec![C(2), C(3)]),
+            (C(4), vec![]),
+            (C(5), vec![C(6)]),
+        ]);
+        assert_eq!(parents.len(), 3);
+        assert_children(&world, parents[1], None);
+        for &parent in [parents[0], parents[2]].iter() {
+            let children = world.get::<Children>(parent).unwrap().to_vec();
+            for &child in &children {
+                assert_parent(&world, child, Some(parent));
+            }
+        }
+        let children = world.get::<Children>(parents[0]).unwrap().to_vec();
+        assert_eq!(world.get::<C>(children[1]).unwrap().0, 3);
+        assert_eq!(
+            world
+                .resource::<Events<HierarchyEvent>>()
+                .iter_current_update_events()
+                .count(),
+            3
+        );
+
+        let mut queue = CommandQueue::default();
+        let mut commands = Commands::new(&mut queue, &world);
+        commands.spawn_batch_with_children((0..10).map(|i| (C(i), [C(i), C(i)])));
+        queue.apply(&mut world);
+        assert_eq!(world.query::<&Parent>().iter(&world).count(), 23);
+    }
+
     #[test]
     fn push_and_insert_and_remove_children_commands() {
         let mut world = World::default();
@@ -0,0 +1,155 @@
+use crate::components::Children;
+use bevy_ecs::{
+    entity::Entity,
+    entity_disabling::Disabled,
+    system::{Command, EntityCommands},
+    world::{EntityWorldMut, World},
+};
+
+/// Disables the given entity and all its descendants
+#[derive(Debug)]
+pub struct DisableRecursive {
+    /// Target entity
+    pub entity: Entity,
+}
+
+/// Enables the given entity and all its descendants
+#[derive(Debug)]
+pub struct EnableRecursive {
+    /// Target entity
+    pub entity: Entity,
+}
+
+/// Function for disabling an entity and all its descendants, by adding [`Disabled`] to them
+pub fn disable_with_children_recursive(world: &mut World, entity: Entity) {
+    set_disabled_recursive(world, entity, true);
+}
+
+/// Function for enabling an entity and all its descendants, by removing [`Disabled`] from them
+pub fn enable_with_children_recursive(world: &mut World, entity: Entity) {
+    set_disabled_recursive(world, entity, false);
+}
+
+fn set_disabled_recursive(world: &mut World, entity: Entity, disabled: bool) {
+    let Some(mut entity_mut) = world.get_entity_mut(entity) else {
+        return;
+    };
+    if disabled {
+        entity_mut.insert(Disabled);
+    } else {
+        entity_mut.remove::<Disabled>();
+    }
+    let children = world
+        .get::<Children>(entity)
+        .map(|children| children.to_vec())
+        .unwrap_or_default();
+    for child in children {
+        set_disabled_recursive(world, child, disabled);
+    }
+}
+
+impl Command for DisableRecursive {
+    fn apply(self, world: &mut World) {
+        #[cfg(feature = "trace")]
+        let _span = bevy_utils::tracing::info_span!(
+            "command",
+            name = "DisableRecursive",
+            entity = bevy_utils::tracing::field::debug(self.entity)
+        )
+        .entered();
+        disable_with_children_recursive(world, self.entity);
+    }
+}
+
+impl Command for EnableRecursive {
+    fn apply(self, world: &mut World) {
+        #[cfg(feature = "trace")]
+        let _span = bevy_utils::tracing::info_span!(
+            "command",
+            name = "EnableRecursive",
+            entity = bevy_utils::tracing::field::debug(self.entity)
+        )
+        .entered();
+        enable_with_children_recursive(world, self.entity);
+    }
+}
+
+/// Trait that holds functions for disabling and enabling entities recursively down the hierarchy,
+/// for example to deactivate a whole prefab without despawning it.
+///
+/// Enabling an entity enables all its descendants, including those that were disabled on their
+/// own before.
+pub trait DisableRecursiveExt {
+    /// Disables the provided entity alongside all descendants.
+    fn disable_recursive(&mut self) -> &mut Self;
+
+    /// Enables the provided entity alongside all descendants.
+    fn enable_recursive(&mut self) -> &mut Self;
+}
+
+impl DisableRecursiveExt for EntityCommands<'_> {
+    fn disable_recursive(&mut self) -> &mut Self {
+        let entity = self.id();
+        self.commands().add(DisableRecursive { entity });
+        self
+    }
+
+    fn enable_recursive(&mut self) -> &mut Self {
+        let entity = self.id();
+        self.commands().add(EnableRecursive { entity });
+        self
+    }
+}
+
+impl<'w> DisableRecursiveExt for EntityWorldMut<'w> {
+    fn disable_recursive(&mut self) -> &mut Self {
+        let entity = self.id();
+        self.world_scope(|world| disable_with_children_recursive(world, entity));
+        self
+    }
+
+    fn enable_recursive(&mut self) -> &mut Self {
+        let entity = self.id();
+        self.world_scope(|world| enable_with_children_recursive(world, entity));
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_ecs::{
+        entity::Entity,
+        entity_disabling::Disabled,
+        system::{CommandQueue, Commands},
+        world::World,
+    };
+
+    use super::DisableRecursiveExt;
+    use crate::child_builder::BuildWorldChildren;
+
+    #[test]
+    fn disable_and_enable_recursive() {
+        let mut world = World::default();
+        let bystander = world.spawn_empty().id();
+        let mut grandchild = Entity::PLACEHOLDER;
+        let mut parent = world.spawn_empty();
+        parent.with_children(|parent| {
+            parent.spawn_empty().with_children(|child| {
+                grandchild = child.spawn_empty().id();
+            });
+        });
+        let parent = parent.id();
+
+        let mut queue = CommandQueue::default();
+        Commands::new(&mut queue, &world)
+            .entity(parent)
+            .disable_recursive();
+        queue.apply(&mut world);
+        let mut enabled = world.query::<Entity>();
+        assert_eq!(enabled.iter(&world).collect::<Vec<_>>(), [bystander]);
+        assert!(world.get::<Disabled>(grandchild).is_some());
+
+        world.entity_mut(parent).enable_recursive();
+        assert_eq!(enabled.iter(&world).count(), 4);
+    }
+}
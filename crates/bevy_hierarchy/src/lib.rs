@@ -37,10 +37,18 @@
 //! In most cases, these operations will invalidate the hierarchy.
 //! Instead, you should use the provided [hierarchical despawn extension methods].
 //!
+//! ## Disabling entities
+//!
+//! Adding the [`Disabled`](bevy_ecs::entity_disabling::Disabled) component to an entity
+//! doesn't disable its descendants.
+//! To deactivate a whole hierarchy without despawning it,
+//! use the provided [hierarchical disable extension methods].
+//!
 //! [command]: BuildChildren
 //! [diagnostic plugin]: ValidParentCheckPlugin
 //! [events]: HierarchyEvent
 //! [hierarchical despawn extension methods]: DespawnRecursiveExt
+//! [hierarchical disable extension methods]: DisableRecursiveExt
 //! [plugin]: HierarchyPlugin
 //! [query extension methods]: HierarchyQueryExt
 //! [world]: BuildWorldChildren
@@ -51,6 +59,9 @@ pub use components::*;
 mod hierarchy;
 pub use hierarchy::*;
 
+mod disabling;
+pub use disabling::*;
+
 mod child_builder;
 pub use child_builder::*;
 
@@ -66,7 +77,9 @@ pub use query_extension::*;
 #[doc(hidden)]
 pub mod prelude {
     #[doc(hidden)]
-    pub use crate::{child_builder::*, components::*, hierarchy::*, query_extension::*};
+    pub use crate::{
+        child_builder::*, components::*, disabling::*, hierarchy::*, query_extension::*,
+    };
 
     #[doc(hidden)]
     #[cfg(feature = "bevy_app")]
@@ -0,0 +1,725 @@
+//! Mapping of inputs to the actions of a game.
+//!
+//! Instead of reading keys and buttons directly, systems can read the [`ActionState`] of the
+//! actions of the game, whose bindings are stored in an [`InputMap`] that can be changed at
+//! runtime, e.g. from a settings menu, and saved with the `serialize` feature.
+//!
+//! Actions can be put in an [`InputContext`], like the actions of a menu or of the gameplay, to
+//! ignore their inputs while the context is disabled in [`InputContexts`].
+//!
+//! ```
+//! # use bevy_app::prelude::*;
+//! # use bevy_ecs::prelude::*;
+//! # use bevy_input::{action::*, gamepad::*, keyboard::KeyCode};
+//! #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+//! enum Action {
+//!     Jump,
+//!     MoveX,
+//! }
+//!
+//! fn main() {
+//!     App::new()
+//!         .add_plugins(InputActionPlugin::<Action>::default())
+//!         .insert_resource(
+//!             InputMap::default()
+//!                 .with(Action::Jump, KeyCode::Space)
+//!                 .with(Action::Jump, GamepadButtonType::South)
+//!                 .with(
+//!                     Action::MoveX,
+//!                     InputBinding::KeyAxis {
+//!                         negative: KeyCode::ArrowLeft,
+//!                         positive: KeyCode::ArrowRight,
+//!                     },
+//!                 )
+//!                 .with(Action::MoveX, InputBinding::gamepad_axis(GamepadAxisType::LeftStickX)),
+//!         )
+//!         .add_systems(Update, move_player);
+//! }
+//!
+//! fn move_player(actions: Res<ActionState<Action>>) {
+//!     if actions.just_pressed(&Action::Jump) {
+//!         // Jump...
+//!     }
+//!     let speed = actions.value(&Action::MoveX);
+//!     // Move...
+//! }
+//! ```
+
+use std::borrow::Cow;
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+use bevy_app::prelude::*;
+use bevy_ecs::{prelude::*, system::SystemParam};
+use bevy_math::Vec2;
+use bevy_reflect::Reflect;
+use bevy_utils::{HashMap, HashSet};
+
+use crate::{
+    gamepad::{Gamepad, GamepadAxis, GamepadAxisType, GamepadButton, GamepadButtonType, Gamepads},
+    keyboard::KeyCode,
+    mouse::MouseButton,
+    Axis, ButtonInput, InputSystem,
+};
+
+#[cfg(feature = "serialize")]
+use bevy_reflect::{ReflectDeserialize, ReflectSerialize};
+
+/// An action of a game, usually a variant of an enum.
+///
+/// This is implemented for every type with the required bounds.
+pub trait InputAction: Clone + Eq + Hash + Send + Sync + 'static {}
+
+impl<T: Clone + Eq + Hash + Send + Sync + 'static> InputAction for T {}
+
+/// Adds an [`InputMap`] and an [`ActionState`] for the actions of type `A`, updated from the
+/// inputs every frame.
+pub struct InputActionPlugin<A: InputAction>(PhantomData<A>);
+
+impl<A: InputAction> Default for InputActionPlugin<A> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<A: InputAction> Plugin for InputActionPlugin<A> {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<InputMap<A>>()
+            .init_resource::<ActionState<A>>()
+            .init_resource::<InputContexts>()
+            .add_systems(PreUpdate, update_action_state::<A>.after(InputSystem));
+    }
+}
+
+/// A set of actions enabled and disabled together with [`InputContexts`], like the actions of a
+/// menu or of the gameplay.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Reflect)]
+#[reflect(Debug, PartialEq, Hash)]
+#[cfg_attr(
+    feature = "serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    reflect(Serialize, Deserialize)
+)]
+pub struct InputContext(Cow<'static, str>);
+
+impl InputContext {
+    /// Creates a context named `name`.
+    pub fn new(name: impl Into<Cow<'static, str>>) -> Self {
+        Self(name.into())
+    }
+
+    /// The name of the context.
+    pub fn name(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<&'static str> for InputContext {
+    fn from(name: &'static str) -> Self {
+        Self::new(name)
+    }
+}
+
+impl From<String> for InputContext {
+    fn from(name: String) -> Self {
+        Self::new(name)
+    }
+}
+
+/// The [`InputContext`]s whose actions are ignored, shared by the actions of every type.
+///
+/// Contexts are enabled unless they are disabled here. The actions of a disabled context are
+/// released, and aren't pressed again until the context is enabled.
+///
+/// ```
+/// # use bevy_ecs::prelude::*;
+/// # use bevy_input::action::InputContexts;
+/// fn open_menu(mut contexts: ResMut<InputContexts>) {
+///     contexts.disable("gameplay");
+///     contexts.enable("menu");
+/// }
+/// ```
+#[derive(Debug, Clone, Default, Resource)]
+pub struct InputContexts {
+    disabled: HashSet<InputContext>,
+}
+
+impl InputContexts {
+    /// Enables the actions of `context`.
+    pub fn enable(&mut self, context: impl Into<InputContext>) {
+        self.disabled.remove(&context.into());
+    }
+
+    /// Disables the actions of `context`.
+    pub fn disable(&mut self, context: impl Into<InputContext>) {
+        self.disabled.insert(context.into());
+    }
+
+    /// Enables or disables the actions of `context`.
+    pub fn set_enabled(&mut self, context: impl Into<InputContext>, enabled: bool) {
+        if enabled {
+            self.enable(context);
+        } else {
+            self.disable(context);
+        }
+    }
+
+    /// Returns `true` if the actions of `context` are enabled.
+    pub fn is_enabled(&self, context: &InputContext) -> bool {
+        !self.disabled.contains(context)
+    }
+}
+
+/// An input bound to an action.
+///
+/// Buttons give a value of `1.0` when pressed, and axes a value between `-scale` and `scale`.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    reflect(Serialize, Deserialize)
+)]
+pub enum InputBinding {
+    /// A key of the keyboard.
+    Key(KeyCode),
+    /// A button of the mouse.
+    Mouse(MouseButton),
+    /// A button of the gamepad.
+    GamepadButton(GamepadButtonType),
+    /// An axis of the gamepad.
+    GamepadAxis {
+        /// The axis.
+        axis: GamepadAxisType,
+        /// The value under which the axis is considered at rest, between `0.0` and `1.0`.
+        ///
+        /// Values over the deadzone are rescaled to start from `0.0`.
+        deadzone: f32,
+        /// The factor applied to the value of the axis, negative to invert it.
+        scale: f32,
+    },
+    /// Two keys acting as an axis, giving `-1.0` when `negative` is pressed and `1.0` when
+    /// `positive` is pressed.
+    KeyAxis {
+        /// The key for negative values.
+        negative: KeyCode,
+        /// The key for positive values.
+        positive: KeyCode,
+    },
+}
+
+impl InputBinding {
+    /// The default deadzone of [`InputBinding::GamepadAxis`].
+    pub const DEFAULT_DEADZONE: f32 = 0.1;
+
+    /// Binds the gamepad axis `axis`, with the default deadzone and no scaling.
+    pub const fn gamepad_axis(axis: GamepadAxisType) -> Self {
+        Self::GamepadAxis {
+            axis,
+            deadzone: Self::DEFAULT_DEADZONE,
+            scale: 1.0,
+        }
+    }
+
+    /// Gets the value of the binding, from the gamepad `gamepad` or from any gamepad if `None`.
+    fn value(&self, inputs: &ActionInputs, gamepad: Option<Gamepad>) -> f32 {
+        let pressed = |pressed: bool| if pressed { 1.0 } else { 0.0 };
+        match *self {
+            InputBinding::Key(key) => pressed(inputs.keys.pressed(key)),
+            InputBinding::Mouse(button) => pressed(inputs.mouse_buttons.pressed(button)),
+            InputBinding::GamepadButton(button) => {
+                pressed(inputs.gamepads(gamepad).any(|gamepad| {
+                    inputs
+                        .gamepad_buttons
+                        .pressed(GamepadButton::new(gamepad, button))
+                }))
+            }
+            InputBinding::GamepadAxis {
+                axis,
+                deadzone,
+                scale,
+            } => {
+                let deadzone = deadzone.clamp(0.0, 0.99);
+                let value = inputs
+                    .gamepads(gamepad)
+                    .filter_map(|gamepad| inputs.gamepad_axes.get(GamepadAxis::new(gamepad, axis)))
+                    .fold(
+                        0.0f32,
+                        |max, value| if value.abs() > max.abs() { value } else { max },
+                    );
+                if value.abs() <= deadzone {
+                    0.0
+                } else {
+                    value.signum() * (value.abs() - deadzone) / (1.0 - deadzone) * scale
+                }
+            }
+            InputBinding::KeyAxis { negative, positive } => {
+                pressed(inputs.keys.pressed(positive)) - pressed(inputs.keys.pressed(negative))
+            }
+        }
+    }
+}
+
+impl From<KeyCode> for InputBinding {
+    fn from(key: KeyCode) -> Self {
+        InputBinding::Key(key)
+    }
+}
+
+impl From<MouseButton> for InputBinding {
+    fn from(button: MouseButton) -> Self {
+        InputBinding::Mouse(button)
+    }
+}
+
+impl From<GamepadButtonType> for InputBinding {
+    fn from(button: GamepadButtonType) -> Self {
+        InputBinding::GamepadButton(button)
+    }
+}
+
+impl From<GamepadAxisType> for InputBinding {
+    fn from(axis: GamepadAxisType) -> Self {
+        InputBinding::gamepad_axis(axis)
+    }
+}
+
+/// The bindings of the actions of type `A`.
+///
+/// Bindings can be changed at any time, e.g. to let players rebind the controls, and are
+/// applied to the [`ActionState`] on the next frame.
+#[derive(Debug, Clone, Resource)]
+#[cfg_attr(
+    feature = "serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound(
+        serialize = "A: serde::Serialize",
+        deserialize = "A: serde::Deserialize<'de>"
+    ))
+)]
+pub struct InputMap<A: InputAction> {
+    bindings: HashMap<A, Vec<InputBinding>>,
+    #[cfg_attr(feature = "serialize", serde(default))]
+    contexts: HashMap<A, InputContext>,
+    /// The gamepad whose inputs are used, or `None` to use every gamepad.
+    pub gamepad: Option<Gamepad>,
+}
+
+impl<A: InputAction> Default for InputMap<A> {
+    fn default() -> Self {
+        Self {
+            bindings: HashMap::default(),
+            contexts: HashMap::default(),
+            gamepad: None,
+        }
+    }
+}
+
+impl<A: InputAction> InputMap<A> {
+    /// Binds `binding` to `action`, and returns the map.
+    pub fn with(mut self, action: A, binding: impl Into<InputBinding>) -> Self {
+        self.bind(action, binding);
+        self
+    }
+
+    /// Puts `action` in `context`, and returns the map.
+    pub fn with_context(mut self, action: A, context: impl Into<InputContext>) -> Self {
+        self.set_context(action, context);
+        self
+    }
+
+    /// Helper to only use the inputs of `gamepad`.
+    pub fn with_gamepad(mut self, gamepad: Gamepad) -> Self {
+        self.gamepad = Some(gamepad);
+        self
+    }
+
+    /// Binds `binding` to `action`, in addition to its other bindings.
+    pub fn bind(&mut self, action: A, binding: impl Into<InputBinding>) {
+        let binding = binding.into();
+        let bindings = self.bindings.entry(action).or_default();
+        if !bindings.contains(&binding) {
+            bindings.push(binding);
+        }
+    }
+
+    /// Removes `binding` from the bindings of `action`.
+    pub fn unbind(&mut self, action: &A, binding: impl Into<InputBinding>) {
+        let binding = binding.into();
+        if let Some(bindings) = self.bindings.get_mut(action) {
+            bindings.retain(|other| *other != binding);
+        }
+    }
+
+    /// Replaces the bindings of `action` with `bindings`.
+    pub fn rebind<B: Into<InputBinding>>(
+        &mut self,
+        action: A,
+        bindings: impl IntoIterator<Item = B>,
+    ) {
+        self.bindings
+            .insert(action, bindings.into_iter().map(Into::into).collect());
+    }
+
+    /// Removes the bindings of `action`.
+    pub fn clear(&mut self, action: &A) {
+        self.bindings.remove(action);
+    }
+
+    /// Puts `action` in `context`, ignoring its inputs while `context` is disabled in
+    /// [`InputContexts`]. Actions are in no context by default, and are always enabled.
+    pub fn set_context(&mut self, action: A, context: impl Into<InputContext>) {
+        self.contexts.insert(action, context.into());
+    }
+
+    /// Removes `action` from its context, so that it's always enabled.
+    pub fn remove_context(&mut self, action: &A) {
+        self.contexts.remove(action);
+    }
+
+    /// The context of `action`, if any.
+    pub fn context(&self, action: &A) -> Option<&InputContext> {
+        self.contexts.get(action)
+    }
+
+    /// Returns `true` if `action` isn't in a context disabled in `contexts`.
+    pub fn is_enabled(&self, action: &A, contexts: &InputContexts) -> bool {
+        match self.context(action) {
+            Some(context) => contexts.is_enabled(context),
+            None => true,
+        }
+    }
+
+    /// The bindings of `action`.
+    pub fn bindings(&self, action: &A) -> &[InputBinding] {
+        self.bindings.get(action).map_or(&[], Vec::as_slice)
+    }
+
+    /// The actions with bindings, and their bindings.
+    pub fn iter(&self) -> impl Iterator<Item = (&A, &[InputBinding])> {
+        self.bindings
+            .iter()
+            .map(|(action, bindings)| (action, bindings.as_slice()))
+    }
+
+    /// Gets the value of `action`: the value of its binding furthest from zero.
+    fn value(&self, action: &A, inputs: &ActionInputs) -> f32 {
+        self.bindings(action)
+            .iter()
+            .map(|binding| binding.value(inputs, self.gamepad))
+            .fold(
+                0.0,
+                |max, value| {
+                    if value.abs() > max.abs() {
+                        value
+                    } else {
+                        max
+                    }
+                },
+            )
+    }
+}
+
+/// The state of an action during the current frame.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ActionData {
+    /// The value of the action, `1.0` for pressed buttons, and between `-1.0` and `1.0` for
+    /// unscaled axes.
+    pub value: f32,
+    /// Whether the action is pressed, i.e. its value isn't zero.
+    pub pressed: bool,
+    /// Whether the action has been pressed during this frame.
+    pub just_pressed: bool,
+    /// Whether the action has been released during this frame.
+    pub just_released: bool,
+}
+
+/// The state of the actions of type `A`, updated from the [`InputMap`] every frame in
+/// [`PreUpdate`].
+#[derive(Debug, Clone, Resource)]
+pub struct ActionState<A: InputAction> {
+    actions: HashMap<A, ActionData>,
+}
+
+impl<A: InputAction> Default for ActionState<A> {
+    fn default() -> Self {
+        Self {
+            actions: HashMap::default(),
+        }
+    }
+}
+
+impl<A: InputAction> ActionState<A> {
+    /// The state of `action`.
+    pub fn get(&self, action: &A) -> ActionData {
+        self.actions.get(action).copied().unwrap_or_default()
+    }
+
+    /// Returns `true` if `action` is pressed.
+    pub fn pressed(&self, action: &A) -> bool {
+        self.get(action).pressed
+    }
+
+    /// Returns `true` if `action` has been pressed during this frame.
+    pub fn just_pressed(&self, action: &A) -> bool {
+        self.get(action).just_pressed
+    }
+
+    /// Returns `true` if `action` has been released during this frame.
+    pub fn just_released(&self, action: &A) -> bool {
+        self.get(action).just_released
+    }
+
+    /// The value of `action`, `0.0` when it isn't pressed.
+    pub fn value(&self, action: &A) -> f32 {
+        self.get(action).value
+    }
+
+    /// The values of the actions `x` and `y`, e.g. for movement on two axes.
+    pub fn axis_pair(&self, x: &A, y: &A) -> Vec2 {
+        Vec2::new(self.value(x), self.value(y))
+    }
+
+    /// Sets the value of `action` for this frame, updating whether it's pressed.
+    ///
+    /// This is done from the [`InputMap`] every frame, but can be used to simulate inputs.
+    pub fn set(&mut self, action: A, value: f32) {
+        let data = self.actions.entry(action).or_default();
+        let pressed = value != 0.0;
+        *data = ActionData {
+            value,
+            pressed,
+            just_pressed: pressed && !data.pressed,
+            just_released: !pressed && data.pressed,
+        };
+    }
+}
+
+/// The inputs that can be bound to actions.
+#[derive(SystemParam)]
+pub struct ActionInputs<'w> {
+    keys: Res<'w, ButtonInput<KeyCode>>,
+    mouse_buttons: Res<'w, ButtonInput<MouseButton>>,
+    gamepad_buttons: Res<'w, ButtonInput<GamepadButton>>,
+    gamepad_axes: Res<'w, Axis<GamepadAxis>>,
+    gamepads: Res<'w, Gamepads>,
+}
+
+impl<'w> ActionInputs<'w> {
+    /// The gamepad `gamepad`, or every gamepad if `None`.
+    fn gamepads(&self, gamepad: Option<Gamepad>) -> impl Iterator<Item = Gamepad> + '_ {
+        self.gamepads
+            .iter()
+            .filter(move |other| gamepad.is_none() || gamepad == Some(*other))
+    }
+}
+
+/// Updates the [`ActionState`] of the actions of type `A` from their bindings.
+///
+/// The actions of the [`InputContext`]s disabled in [`InputContexts`] are released.
+pub fn update_action_state<A: InputAction>(
+    map: Res<InputMap<A>>,
+    contexts: Res<InputContexts>,
+    inputs: ActionInputs,
+    mut state: ResMut<ActionState<A>>,
+) {
+    // Released actions whose bindings have been removed.
+    let unbound: Vec<_> = state
+        .actions
+        .keys()
+        .filter(|action| !map.bindings.contains_key(*action))
+        .cloned()
+        .collect();
+    for action in unbound {
+        state.set(action, 0.0);
+    }
+    for action in map.bindings.keys() {
+        let value = if map.is_enabled(action, &contexts) {
+            map.value(action, &inputs)
+        } else {
+            0.0
+        };
+        state.set(action.clone(), value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gamepad::{
+        gamepad_connection_system, GamepadConnection, GamepadConnectionEvent, GamepadFeatures,
+        GamepadInfo,
+    };
+    use bevy_ecs::{event::Events, system::RunSystemOnce};
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    enum Action {
+        Jump,
+        Move,
+    }
+
+    fn setup() -> World {
+        let mut world = World::new();
+        world.init_resource::<ButtonInput<KeyCode>>();
+        world.init_resource::<ButtonInput<MouseButton>>();
+        world.init_resource::<ButtonInput<GamepadButton>>();
+        world.init_resource::<Axis<GamepadAxis>>();
+        world.init_resource::<Gamepads>();
+        world.init_resource::<ActionState<Action>>();
+        world.init_resource::<InputContexts>();
+        world.insert_resource(
+            InputMap::default()
+                .with(Action::Jump, KeyCode::Space)
+                .with(Action::Jump, MouseButton::Left)
+                .with(
+                    Action::Move,
+                    InputBinding::KeyAxis {
+                        negative: KeyCode::KeyA,
+                        positive: KeyCode::KeyD,
+                    },
+                )
+                .with(
+                    Action::Move,
+                    InputBinding::GamepadAxis {
+                        axis: GamepadAxisType::LeftStickX,
+                        deadzone: 0.5,
+                        scale: -2.0,
+                    },
+                ),
+        );
+        world
+    }
+
+    #[test]
+    fn buttons_press_and_release_actions() {
+        let mut world = setup();
+        world
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .press(KeyCode::Space);
+        world.run_system_once(update_action_state::<Action>);
+        let state = world.resource::<ActionState<Action>>();
+        assert!(state.pressed(&Action::Jump));
+        assert!(state.just_pressed(&Action::Jump));
+        assert_eq!(state.value(&Action::Jump), 1.0);
+
+        world.run_system_once(update_action_state::<Action>);
+        assert!(!world
+            .resource::<ActionState<Action>>()
+            .just_pressed(&Action::Jump));
+
+        world
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .release(KeyCode::Space);
+        world.run_system_once(update_action_state::<Action>);
+        let state = world.resource::<ActionState<Action>>();
+        assert!(!state.pressed(&Action::Jump));
+        assert!(state.just_released(&Action::Jump));
+    }
+
+    #[test]
+    fn axes_apply_deadzone_and_scale() {
+        let mut world = setup();
+        let gamepad = Gamepad::new(0);
+        world.init_resource::<Axis<GamepadButton>>();
+        world.init_resource::<Events<GamepadConnectionEvent>>();
+        world.send_event(GamepadConnectionEvent::new(
+            gamepad,
+            GamepadConnection::Connected(GamepadInfo::new("test", GamepadFeatures::default())),
+        ));
+        world.run_system_once(gamepad_connection_system);
+        let axis = GamepadAxis::new(gamepad, GamepadAxisType::LeftStickX);
+        world.resource_mut::<Axis<GamepadAxis>>().set(axis, 0.4);
+        world.run_system_once(update_action_state::<Action>);
+        assert_eq!(
+            world.resource::<ActionState<Action>>().value(&Action::Move),
+            0.0
+        );
+
+        world.resource_mut::<Axis<GamepadAxis>>().set(axis, 0.75);
+        world.run_system_once(update_action_state::<Action>);
+        assert_eq!(
+            world.resource::<ActionState<Action>>().value(&Action::Move),
+            -1.0
+        );
+
+        // The binding furthest from zero wins.
+        world.resource_mut::<Axis<GamepadAxis>>().set(axis, 0.6);
+        world
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .press(KeyCode::KeyD);
+        world.run_system_once(update_action_state::<Action>);
+        assert_eq!(
+            world.resource::<ActionState<Action>>().value(&Action::Move),
+            1.0
+        );
+    }
+
+    #[test]
+    fn rebinding_replaces_bindings() {
+        let mut world = setup();
+        world
+            .resource_mut::<InputMap<Action>>()
+            .rebind(Action::Jump, [KeyCode::Enter]);
+        world
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .press(KeyCode::Space);
+        world.run_system_once(update_action_state::<Action>);
+        assert!(!world
+            .resource::<ActionState<Action>>()
+            .pressed(&Action::Jump));
+
+        world
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .press(KeyCode::Enter);
+        world.run_system_once(update_action_state::<Action>);
+        assert!(world
+            .resource::<ActionState<Action>>()
+            .pressed(&Action::Jump));
+
+        world
+            .resource_mut::<InputMap<Action>>()
+            .clear(&Action::Jump);
+        world.run_system_once(update_action_state::<Action>);
+        assert!(world
+            .resource::<ActionState<Action>>()
+            .just_released(&Action::Jump));
+        assert_eq!(
+            world
+                .resource::<InputMap<Action>>()
+                .bindings(&Action::Move)
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn disabled_contexts_release_their_actions() {
+        let mut world = setup();
+        world
+            .resource_mut::<InputMap<Action>>()
+            .set_context(Action::Jump, "gameplay");
+        world
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .press(KeyCode::Space);
+        world.run_system_once(update_action_state::<Action>);
+        assert!(world
+            .resource::<ActionState<Action>>()
+            .pressed(&Action::Jump));
+
+        world.resource_mut::<InputContexts>().disable("gameplay");
+        world
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .press(KeyCode::KeyD);
+        world.run_system_once(update_action_state::<Action>);
+        let state = world.resource::<ActionState<Action>>();
+        assert!(state.just_released(&Action::Jump));
+        // Actions without a context are always enabled.
+        assert_eq!(state.value(&Action::Move), 1.0);
+
+        world.resource_mut::<InputContexts>().enable("gameplay");
+        world.run_system_once(update_action_state::<Action>);
+        assert!(world
+            .resource::<ActionState<Action>>()
+            .just_pressed(&Action::Jump));
+    }
+}
@@ -100,6 +100,8 @@ impl Gamepad {
 }
 
 /// Metadata associated with a [`Gamepad`].
+///
+/// Input backends create it with [`GamepadInfo::new`].
 #[derive(Debug, Clone, PartialEq, Eq, Reflect)]
 #[reflect(Debug, PartialEq)]
 #[cfg_attr(
@@ -107,6 +109,7 @@ impl Gamepad {
     derive(serde::Serialize, serde::Deserialize),
     reflect(Serialize, Deserialize)
 )]
+#[non_exhaustive]
 pub struct GamepadInfo {
     /// The name of the gamepad.
     ///
@@ -114,6 +117,35 @@ pub struct GamepadInfo {
     ///
     /// For example on Windows the name may be "HID-compliant game controller".
     pub name: String,
+    /// The features of the gamepad supported by the input backend.
+    pub features: GamepadFeatures,
+}
+
+impl GamepadInfo {
+    /// Creates the metadata of a gamepad named `name`, with the given `features`.
+    pub fn new(name: impl Into<String>, features: GamepadFeatures) -> Self {
+        Self {
+            name: name.into(),
+            features,
+        }
+    }
+}
+
+/// The features of a [`Gamepad`], beyond its buttons and axes, supported by the input backend.
+///
+/// Requests for features a gamepad doesn't support are ignored.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+#[reflect(Debug, Default, PartialEq, Hash)]
+#[cfg_attr(
+    feature = "serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    reflect(Serialize, Deserialize)
+)]
+pub struct GamepadFeatures {
+    /// The gamepad can rumble, see [`GamepadRumbleRequest`].
+    pub rumble: bool,
+    /// The triggers of the gamepad can rumble, see [`GamepadRumbleIntensity::left_trigger`].
+    pub trigger_rumble: bool,
 }
 
 /// A collection of connected [`Gamepad`]s.
@@ -148,6 +180,11 @@ impl Gamepads {
         self.gamepads.get(&gamepad).map(|g| g.name.as_str())
     }
 
+    /// The features of the gamepad if this one is connected.
+    pub fn features(&self, gamepad: Gamepad) -> Option<GamepadFeatures> {
+        self.gamepads.get(&gamepad).map(|g| g.features)
+    }
+
     /// Registers the `gamepad`, marking it as connected.
     fn register(&mut self, gamepad: Gamepad, info: GamepadInfo) {
         self.gamepads.insert(gamepad, info);
@@ -1307,7 +1344,7 @@ const ALL_AXIS_TYPES: [GamepadAxisType; 6] = [
 ];
 
 /// The intensity at which a gamepad's force-feedback motors may rumble.
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub struct GamepadRumbleIntensity {
     /// The rumble intensity of the strong gamepad motor.
     ///
@@ -1323,6 +1360,17 @@ pub struct GamepadRumbleIntensity {
     /// By convention, this is usually a high-frequency motor on the right-hand
     /// side of the gamepad, though it may vary across platforms and hardware.
     pub weak_motor: f32,
+    /// The rumble intensity of the motor in the left trigger, like the impulse triggers of Xbox
+    /// controllers.
+    ///
+    /// Ranges from `0.0` to `1.0`. Ignored unless the [`GamepadFeatures::trigger_rumble`] of the
+    /// gamepad is supported.
+    pub left_trigger: f32,
+    /// The rumble intensity of the motor in the right trigger.
+    ///
+    /// Ranges from `0.0` to `1.0`. Ignored unless the [`GamepadFeatures::trigger_rumble`] of the
+    /// gamepad is supported.
+    pub right_trigger: f32,
 }
 
 impl GamepadRumbleIntensity {
@@ -1330,18 +1378,32 @@ impl GamepadRumbleIntensity {
     pub const MAX: Self = GamepadRumbleIntensity {
         strong_motor: 1.0,
         weak_motor: 1.0,
+        left_trigger: 0.0,
+        right_trigger: 0.0,
     };
 
     /// Rumble the weak motor at maximum intensity.
     pub const WEAK_MAX: Self = GamepadRumbleIntensity {
         strong_motor: 0.0,
         weak_motor: 1.0,
+        left_trigger: 0.0,
+        right_trigger: 0.0,
     };
 
     /// Rumble the strong motor at maximum intensity.
     pub const STRONG_MAX: Self = GamepadRumbleIntensity {
         strong_motor: 1.0,
         weak_motor: 0.0,
+        left_trigger: 0.0,
+        right_trigger: 0.0,
+    };
+
+    /// Rumble the motors of both triggers at maximum intensity.
+    pub const TRIGGERS_MAX: Self = GamepadRumbleIntensity {
+        strong_motor: 0.0,
+        weak_motor: 0.0,
+        left_trigger: 1.0,
+        right_trigger: 1.0,
     };
 
     /// Creates a new rumble intensity with weak motor intensity set to the given value.
@@ -1351,6 +1413,8 @@ impl GamepadRumbleIntensity {
         Self {
             weak_motor: intensity,
             strong_motor: 0.0,
+            left_trigger: 0.0,
+            right_trigger: 0.0,
         }
     }
 
@@ -1361,20 +1425,70 @@ impl GamepadRumbleIntensity {
         Self {
             strong_motor: intensity,
             weak_motor: 0.0,
+            left_trigger: 0.0,
+            right_trigger: 0.0,
+        }
+    }
+
+    /// Creates a new rumble intensity with the intensities of the left and right trigger motors
+    /// set to the given values.
+    pub const fn triggers(left: f32, right: f32) -> Self {
+        Self {
+            strong_motor: 0.0,
+            weak_motor: 0.0,
+            left_trigger: left,
+            right_trigger: right,
         }
     }
+
+    /// Returns the sum of the intensities of `self` and `other`, clamped to `1.0`.
+    pub fn saturating_add(self, other: Self) -> Self {
+        Self {
+            strong_motor: (self.strong_motor + other.strong_motor).min(1.0),
+            weak_motor: (self.weak_motor + other.weak_motor).min(1.0),
+            left_trigger: (self.left_trigger + other.left_trigger).min(1.0),
+            right_trigger: (self.right_trigger + other.right_trigger).min(1.0),
+        }
+    }
+
+    /// Returns the highest intensity of each motor of `self` and `other`.
+    pub fn max(self, other: Self) -> Self {
+        Self {
+            strong_motor: self.strong_motor.max(other.strong_motor),
+            weak_motor: self.weak_motor.max(other.weak_motor),
+            left_trigger: self.left_trigger.max(other.left_trigger),
+            right_trigger: self.right_trigger.max(other.right_trigger),
+        }
+    }
+}
+
+/// How a rumble added with [`GamepadRumbleRequest::Add`] combines with the rumbles already
+/// running on the gamepad.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum GamepadRumbleStacking {
+    /// The intensities of the rumbles add up, up to the maximum intensity.
+    #[default]
+    Add,
+    /// Each motor rumbles at the highest intensity of the running rumbles, so repeated rumbles,
+    /// like the hits of a weapon, don't get stronger.
+    Max,
+    /// The running rumbles are stopped, and replaced by the new one.
+    Replace,
 }
 
 /// An event that controls force-feedback rumbling of a [`Gamepad`].
 ///
 /// # Notes
 ///
-/// Does nothing if the gamepad or platform does not support rumble.
+/// Does nothing if the gamepad or platform does not support rumble. On the web, gamepads rumble
+/// through the Gamepad API of browsers supporting vibration.
 ///
 /// # Example
 ///
 /// ```
-/// # use bevy_input::gamepad::{Gamepad, Gamepads, GamepadRumbleRequest, GamepadRumbleIntensity};
+/// # use bevy_input::gamepad::{
+/// #     Gamepad, Gamepads, GamepadRumbleRequest, GamepadRumbleIntensity, GamepadRumbleStacking,
+/// # };
 /// # use bevy_ecs::prelude::{EventWriter, Res};
 /// # use bevy_utils::Duration;
 /// fn rumble_gamepad_system(
@@ -1386,6 +1500,7 @@ impl GamepadRumbleIntensity {
 ///             gamepad,
 ///             intensity: GamepadRumbleIntensity::MAX,
 ///             duration: Duration::from_secs_f32(0.5),
+///             stacking: GamepadRumbleStacking::Add,
 ///         });
 ///     }
 /// }
@@ -1398,14 +1513,13 @@ impl GamepadRumbleIntensity {
 pub enum GamepadRumbleRequest {
     /// Add a rumble to the given gamepad.
     ///
-    /// Simultaneous rumble effects add up to the sum of their strengths.
+    /// With [`GamepadRumbleStacking::Add`], simultaneous rumble effects add up to the sum of
+    /// their strengths.
     ///
     /// Consequently, if two rumbles at half intensity are added at the same
     /// time, their intensities will be added up, and the controller will rumble
     /// at full intensity until one of the rumbles finishes, then the rumble
     /// will continue at the intensity of the remaining event.
-    ///
-    /// To replace an existing rumble, send a [`GamepadRumbleRequest::Stop`] event first.
     Add {
         /// How long the gamepad should rumble.
         duration: Duration,
@@ -1413,6 +1527,8 @@ pub enum GamepadRumbleRequest {
         intensity: GamepadRumbleIntensity,
         /// The gamepad to rumble.
         gamepad: Gamepad,
+        /// How the rumble combines with the rumbles already running on the gamepad.
+        stacking: GamepadRumbleStacking,
     },
     /// Stop all running rumbles on the given [`Gamepad`].
     Stop {
@@ -0,0 +1,552 @@
+//! The recognition of gestures, like pinches and taps, from touches and touchpads.
+
+use bevy_ecs::{
+    event::{Event, EventReader, EventWriter},
+    system::{Local, Res, Resource, SystemParam},
+};
+use bevy_math::Vec2;
+use bevy_reflect::Reflect;
+use bevy_utils::{Duration, HashMap, Instant};
+
+use crate::{
+    pointer::{PointerId, Pointers},
+    touch::{Touch, Touches},
+    touchpad::{TouchpadMagnify, TouchpadRotate, TouchpadSmartMagnify},
+};
+
+#[cfg(feature = "serialize")]
+use bevy_reflect::{ReflectDeserialize, ReflectSerialize};
+
+/// Two or more fingers moving apart or closer, or a pinch on a touchpad.
+///
+/// Positions are in the window of the touches, or of the mouse cursor for touchpads.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    reflect(Serialize, Deserialize)
+)]
+pub struct PinchGesture {
+    /// The factor by which the distance between the fingers changed since the last event, over
+    /// `1.0` when zooming in.
+    pub scale: f32,
+    /// The center of the fingers.
+    pub center: Vec2,
+}
+
+/// Two or more fingers rotating around their center, or a rotation on a touchpad.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    reflect(Serialize, Deserialize)
+)]
+pub struct RotateGesture {
+    /// The angle of the rotation since the last event in radians, positive counterclockwise on
+    /// the screen.
+    pub angle: f32,
+    /// The center of the fingers.
+    pub center: Vec2,
+}
+
+/// One or more fingers moving together.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    reflect(Serialize, Deserialize)
+)]
+pub struct PanGesture {
+    /// The movement of the center of the fingers since the last event.
+    pub delta: Vec2,
+    /// The center of the fingers.
+    pub center: Vec2,
+    /// The number of fingers.
+    pub touches: usize,
+}
+
+/// A finger quickly pressed and lifted without moving.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    reflect(Serialize, Deserialize)
+)]
+pub struct TapGesture {
+    /// The position of the tap.
+    pub position: Vec2,
+}
+
+/// Two taps in quick succession, sent after the [`TapGesture`] of the second tap, or a double
+/// tap on a touchpad.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    reflect(Serialize, Deserialize)
+)]
+pub struct DoubleTapGesture {
+    /// The position of the second tap.
+    pub position: Vec2,
+}
+
+/// A finger held down without moving, sent once while it's still pressed.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    reflect(Serialize, Deserialize)
+)]
+pub struct LongPressGesture {
+    /// The position of the finger.
+    pub position: Vec2,
+}
+
+/// A finger quickly moved and lifted, like to turn a page.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    reflect(Serialize, Deserialize)
+)]
+pub struct SwipeGesture {
+    /// The position where the finger was pressed.
+    pub start: Vec2,
+    /// The position where the finger was lifted.
+    pub end: Vec2,
+    /// The average velocity of the finger, in logical pixels per second.
+    pub velocity: Vec2,
+}
+
+/// The thresholds used to recognize gestures.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Debug, PartialEq)]
+pub struct GestureSettings {
+    /// The distance a finger can move during a tap or a long press, in logical pixels. Fingers
+    /// moving further start pinches, rotations, pans and swipes.
+    pub tap_max_distance: f32,
+    /// The longest duration of a tap.
+    pub tap_max_duration: Duration,
+    /// The longest duration between the two taps of a double tap.
+    pub double_tap_interval: Duration,
+    /// The largest distance between the two taps of a double tap, in logical pixels.
+    pub double_tap_max_distance: f32,
+    /// The duration after which a pressed finger is a long press.
+    pub long_press_duration: Duration,
+    /// The lowest average velocity of a swipe, in logical pixels per second.
+    pub swipe_min_velocity: f32,
+}
+
+impl Default for GestureSettings {
+    fn default() -> Self {
+        Self {
+            tap_max_distance: 10.0,
+            tap_max_duration: Duration::from_millis(300),
+            double_tap_interval: Duration::from_millis(300),
+            double_tap_max_distance: 30.0,
+            long_press_duration: Duration::from_millis(500),
+            swipe_min_velocity: 500.0,
+        }
+    }
+}
+
+/// The events sent by [`gesture_system`].
+#[derive(SystemParam)]
+pub struct GestureEvents<'w> {
+    pinch: EventWriter<'w, PinchGesture>,
+    rotate: EventWriter<'w, RotateGesture>,
+    pan: EventWriter<'w, PanGesture>,
+    tap: EventWriter<'w, TapGesture>,
+    double_tap: EventWriter<'w, DoubleTapGesture>,
+    long_press: EventWriter<'w, LongPressGesture>,
+    swipe: EventWriter<'w, SwipeGesture>,
+}
+
+/// A gesture recognized from touches.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Gesture {
+    Pinch(PinchGesture),
+    Rotate(RotateGesture),
+    Pan(PanGesture),
+    Tap(TapGesture),
+    DoubleTap(DoubleTapGesture),
+    LongPress(LongPressGesture),
+    Swipe(SwipeGesture),
+}
+
+/// The touches from the first finger pressed to the last finger lifted.
+#[derive(Debug, Clone, Copy)]
+struct TouchSequence {
+    start: Instant,
+    max_touches: usize,
+    /// Whether a finger moved further than a tap.
+    moved: bool,
+    long_pressed: bool,
+}
+
+/// Recognizes gestures from the [`Touches`], frame after frame, in [`gesture_system`].
+#[derive(Debug, Default)]
+pub struct GestureRecognizer {
+    sequence: Option<TouchSequence>,
+    /// The positions of the pressed touches during the last frame.
+    positions: HashMap<u64, Vec2>,
+    /// The time and position of the last tap, if it can start a double tap.
+    last_tap: Option<(Instant, Vec2)>,
+}
+
+impl GestureRecognizer {
+    fn update(
+        &mut self,
+        touches: &Touches,
+        now: Instant,
+        settings: &GestureSettings,
+        gestures: &mut Vec<Gesture>,
+    ) {
+        let mut pressed: Vec<&Touch> = touches.iter().collect();
+        pressed.sort_by_key(|touch| touch.id());
+        let released: Vec<&Touch> = touches.iter_just_released().collect();
+        let previous_positions = std::mem::take(&mut self.positions);
+        self.positions = pressed
+            .iter()
+            .map(|touch| (touch.id(), touch.position()))
+            .collect();
+
+        if touches.any_just_canceled() {
+            self.sequence = None;
+            return;
+        }
+        if pressed.is_empty() && released.is_empty() {
+            return;
+        }
+        let sequence = self.sequence.get_or_insert(TouchSequence {
+            start: now,
+            max_touches: 0,
+            moved: false,
+            long_pressed: false,
+        });
+        sequence.max_touches = sequence.max_touches.max(pressed.len() + released.len());
+        sequence.moved |= pressed
+            .iter()
+            .chain(&released)
+            .any(|touch| touch.distance().length() > settings.tap_max_distance);
+
+        // The touches pressed during the last frame, with their last and current positions.
+        let moving: Vec<(Vec2, Vec2)> = pressed
+            .iter()
+            .filter_map(|touch| Some((previous_positions.get(&touch.id())?, touch.position())))
+            .map(|(previous, position)| (*previous, position))
+            .collect();
+        if sequence.moved && !moving.is_empty() {
+            let count = moving.len() as f32;
+            let previous_center =
+                moving.iter().map(|(previous, _)| *previous).sum::<Vec2>() / count;
+            let center = moving.iter().map(|(_, position)| *position).sum::<Vec2>() / count;
+            if center != previous_center {
+                gestures.push(Gesture::Pan(PanGesture {
+                    delta: center - previous_center,
+                    center,
+                    touches: moving.len(),
+                }));
+            }
+            if moving.len() >= 2 {
+                let spread = |center: Vec2, positions: &mut dyn Iterator<Item = Vec2>| {
+                    positions
+                        .map(|position| position.distance(center))
+                        .sum::<f32>()
+                        / count
+                };
+                let previous_spread = spread(
+                    previous_center,
+                    &mut moving.iter().map(|(previous, _)| *previous),
+                );
+                let current_spread =
+                    spread(center, &mut moving.iter().map(|(_, position)| *position));
+                if previous_spread > 0.0 && current_spread != previous_spread {
+                    gestures.push(Gesture::Pinch(PinchGesture {
+                        scale: current_spread / previous_spread,
+                        center,
+                    }));
+                }
+
+                // Window coordinates point down, so the angle is negated to be counterclockwise.
+                let previous_direction = moving[1].0 - moving[0].0;
+                let direction = moving[1].1 - moving[0].1;
+                let angle = -previous_direction.angle_between(direction);
+                if angle != 0.0 && angle.is_finite() {
+                    gestures.push(Gesture::Rotate(RotateGesture { angle, center }));
+                }
+            }
+        }
+
+        if let [touch] = pressed[..] {
+            if !sequence.long_pressed
+                && !sequence.moved
+                && sequence.max_touches == 1
+                && now.duration_since(sequence.start) >= settings.long_press_duration
+            {
+                sequence.long_pressed = true;
+                gestures.push(Gesture::LongPress(LongPressGesture {
+                    position: touch.position(),
+                }));
+            }
+        }
+
+        if !pressed.is_empty() {
+            return;
+        }
+        // The last finger has been lifted.
+        let sequence = self.sequence.take().unwrap();
+        let duration = now.duration_since(sequence.start);
+        let [touch] = released[..] else {
+            return;
+        };
+        if sequence.max_touches != 1 || sequence.long_pressed {
+            return;
+        }
+        if !sequence.moved && duration <= settings.tap_max_duration {
+            let position = touch.position();
+            gestures.push(Gesture::Tap(TapGesture { position }));
+            match self.last_tap {
+                Some((time, last_position))
+                    if now.duration_since(time) <= settings.double_tap_interval
+                        && last_position.distance(position) <= settings.double_tap_max_distance =>
+                {
+                    gestures.push(Gesture::DoubleTap(DoubleTapGesture { position }));
+                    self.last_tap = None;
+                }
+                _ => self.last_tap = Some((now, position)),
+            }
+        } else if sequence.moved {
+            let velocity = touch.distance() / duration.as_secs_f32().max(f32::EPSILON);
+            if velocity.length() >= settings.swipe_min_velocity {
+                gestures.push(Gesture::Swipe(SwipeGesture {
+                    start: touch.start_position(),
+                    end: touch.position(),
+                    velocity,
+                }));
+            }
+        }
+    }
+}
+
+/// Sends the gesture events recognized from the [`Touches`] and the touchpad events.
+///
+/// Touchpad gestures are centered on the mouse cursor.
+#[allow(clippy::too_many_arguments)]
+pub fn gesture_system(
+    mut recognizer: Local<GestureRecognizer>,
+    settings: Res<GestureSettings>,
+    touches: Res<Touches>,
+    pointers: Res<Pointers>,
+    mut magnify_events: EventReader<TouchpadMagnify>,
+    mut rotate_events: EventReader<TouchpadRotate>,
+    mut smart_magnify_events: EventReader<TouchpadSmartMagnify>,
+    mut events: GestureEvents,
+) {
+    let mut gestures = Vec::new();
+    recognizer.update(&touches, Instant::now(), &settings, &mut gestures);
+
+    let cursor = pointers
+        .get(PointerId::Mouse)
+        .map_or(Vec2::ZERO, |pointer| pointer.position);
+    gestures.extend(magnify_events.read().map(|event| {
+        Gesture::Pinch(PinchGesture {
+            scale: 1.0 + event.0,
+            center: cursor,
+        })
+    }));
+    gestures.extend(rotate_events.read().map(|event| {
+        Gesture::Rotate(RotateGesture {
+            angle: event.0.to_radians(),
+            center: cursor,
+        })
+    }));
+    gestures.extend(
+        smart_magnify_events
+            .read()
+            .map(|_| Gesture::DoubleTap(DoubleTapGesture { position: cursor })),
+    );
+
+    for gesture in gestures {
+        match gesture {
+            Gesture::Pinch(event) => {
+                events.pinch.send(event);
+            }
+            Gesture::Rotate(event) => {
+                events.rotate.send(event);
+            }
+            Gesture::Pan(event) => {
+                events.pan.send(event);
+            }
+            Gesture::Tap(event) => {
+                events.tap.send(event);
+            }
+            Gesture::DoubleTap(event) => {
+                events.double_tap.send(event);
+            }
+            Gesture::LongPress(event) => {
+                events.long_press.send(event);
+            }
+            Gesture::Swipe(event) => {
+                events.swipe.send(event);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::touch::{TouchInput, TouchPhase};
+    use bevy_ecs::entity::Entity;
+
+    struct Test {
+        recognizer: GestureRecognizer,
+        touches: Touches,
+        start: Instant,
+        settings: GestureSettings,
+    }
+
+    impl Test {
+        fn new() -> Self {
+            Self {
+                recognizer: GestureRecognizer::default(),
+                touches: Touches::default(),
+                start: Instant::now(),
+                settings: GestureSettings::default(),
+            }
+        }
+
+        /// Runs a frame at `millis` with the touch events `(phase, id, position)`.
+        fn frame(&mut self, millis: u64, events: &[(TouchPhase, u64, Vec2)]) -> Vec<Gesture> {
+            self.touches.clear();
+            for (phase, id, position) in events {
+                self.touches.process_touch_event(&TouchInput {
+                    phase: *phase,
+                    position: *position,
+                    window: Entity::PLACEHOLDER,
+                    force: None,
+                    id: *id,
+                });
+            }
+            let mut gestures = Vec::new();
+            self.recognizer.update(
+                &self.touches,
+                self.start + Duration::from_millis(millis),
+                &self.settings,
+                &mut gestures,
+            );
+            gestures
+        }
+    }
+
+    #[test]
+    fn taps_and_double_taps() {
+        let mut test = Test::new();
+        let position = Vec2::new(100.0, 100.0);
+        assert!(test
+            .frame(0, &[(TouchPhase::Started, 0, position)])
+            .is_empty());
+        assert_eq!(
+            test.frame(100, &[(TouchPhase::Ended, 0, position)]),
+            [Gesture::Tap(TapGesture { position })]
+        );
+        // Pressed and lifted during the same frame.
+        assert_eq!(
+            test.frame(
+                300,
+                &[
+                    (TouchPhase::Started, 1, position),
+                    (TouchPhase::Ended, 1, position)
+                ]
+            ),
+            [
+                Gesture::Tap(TapGesture { position }),
+                Gesture::DoubleTap(DoubleTapGesture { position })
+            ]
+        );
+
+        // Held down too long for a tap.
+        test.frame(1000, &[(TouchPhase::Started, 2, position)]);
+        assert!(test.frame(1400, &[]).is_empty());
+        assert_eq!(
+            test.frame(1600, &[]),
+            [Gesture::LongPress(LongPressGesture { position })]
+        );
+        assert!(test
+            .frame(1700, &[(TouchPhase::Ended, 2, position)])
+            .is_empty());
+    }
+
+    #[test]
+    fn two_fingers_pinch_and_rotate() {
+        let mut test = Test::new();
+        test.frame(
+            0,
+            &[
+                (TouchPhase::Started, 0, Vec2::new(-50.0, 0.0)),
+                (TouchPhase::Started, 1, Vec2::new(50.0, 0.0)),
+            ],
+        );
+        // The fingers move apart.
+        let gestures = test.frame(
+            16,
+            &[
+                (TouchPhase::Moved, 0, Vec2::new(-100.0, 0.0)),
+                (TouchPhase::Moved, 1, Vec2::new(100.0, 0.0)),
+            ],
+        );
+        assert_eq!(
+            gestures,
+            [Gesture::Pinch(PinchGesture {
+                scale: 2.0,
+                center: Vec2::ZERO
+            })]
+        );
+        // Nothing is sent while the fingers don't move.
+        assert!(test.frame(32, &[]).is_empty());
+
+        // The fingers rotate counterclockwise on the screen, whose y axis points down.
+        let gestures = test.frame(
+            48,
+            &[
+                (TouchPhase::Moved, 0, Vec2::new(0.0, 100.0)),
+                (TouchPhase::Moved, 1, Vec2::new(0.0, -100.0)),
+            ],
+        );
+        let [Gesture::Rotate(rotate)] = gestures[..] else {
+            panic!("expected a rotation, got {gestures:?}");
+        };
+        assert!((rotate.angle - std::f32::consts::FRAC_PI_2).abs() < 1e-5);
+    }
+
+    #[test]
+    fn fast_drags_are_swipes() {
+        let mut test = Test::new();
+        test.frame(0, &[(TouchPhase::Started, 0, Vec2::ZERO)]);
+        assert_eq!(
+            test.frame(50, &[(TouchPhase::Moved, 0, Vec2::new(50.0, 0.0))]),
+            [Gesture::Pan(PanGesture {
+                delta: Vec2::new(50.0, 0.0),
+                center: Vec2::new(50.0, 0.0),
+                touches: 1,
+            })]
+        );
+        let gestures = test.frame(100, &[(TouchPhase::Ended, 0, Vec2::new(50.0, 0.0))]);
+        assert_eq!(
+            gestures,
+            [Gesture::Swipe(SwipeGesture {
+                start: Vec2::ZERO,
+                end: Vec2::new(50.0, 0.0),
+                velocity: Vec2::new(500.0, 0.0),
+            })]
+        );
+    }
+}
@@ -70,9 +70,10 @@ use bevy_ecs::entity::Entity;
 use bevy_ecs::{
     change_detection::DetectChangesMut,
     event::{Event, EventReader},
-    system::ResMut,
+    system::{Local, ResMut},
 };
 use bevy_reflect::Reflect;
+use bevy_utils::HashMap;
 use smol_str::SmolStr;
 
 #[cfg(feature = "serialize")]
@@ -83,10 +84,15 @@ use bevy_reflect::{ReflectDeserialize, ReflectSerialize};
 /// This event is the translated version of the `WindowEvent::KeyboardInput` from the `winit` crate.
 /// It is available to the end user and can be used for game logic.
 ///
+/// Every event carries both the physical [`KeyCode`] of the key, which doesn't depend on the
+/// keyboard layout and suits WASD-style controls, and the logical [`Key`] it produces in the
+/// current layout, which suits text and menu shortcuts.
+///
 /// ## Usage
 ///
 /// The event is consumed inside of the [`keyboard_input_system`]
-/// to update the [`Input<KeyCode>`](ButtonInput<KeyCode>) resource.
+/// to update the [`Input<KeyCode>`](ButtonInput<KeyCode>) and
+/// [`Input<Key>`](ButtonInput<Key>) resources.
 #[derive(Event, Debug, Clone, PartialEq, Eq, Reflect)]
 #[reflect(Debug, PartialEq)]
 #[cfg_attr(
@@ -105,25 +111,48 @@ pub struct KeyboardInput {
     pub window: Entity,
 }
 
-/// Updates the [`ButtonInput<KeyCode>`] resource with the latest [`KeyboardInput`] events.
+/// Updates the [`ButtonInput<KeyCode>`] and [`ButtonInput<Key>`] resources with the latest
+/// [`KeyboardInput`] events.
+///
+/// A key is released as the logical key it was pressed as, even if the modifiers changed the
+/// logical key in the meantime, e.g. a key pressed as `A` with `Shift` and released as `a`.
 ///
 /// ## Differences
 ///
-/// The main difference between the [`KeyboardInput`] event and the [`ButtonInput<KeyCode>`] resources is that
+/// The main difference between the [`KeyboardInput`] event and the [`ButtonInput`] resources is that
 /// the latter have convenient functions such as [`ButtonInput::pressed`], [`ButtonInput::just_pressed`] and [`ButtonInput::just_released`].
 pub fn keyboard_input_system(
     mut key_input: ResMut<ButtonInput<KeyCode>>,
+    mut logical_key_input: ResMut<ButtonInput<Key>>,
     mut keyboard_input_events: EventReader<KeyboardInput>,
+    mut pressed_logical_keys: Local<HashMap<KeyCode, Key>>,
 ) {
     // Avoid clearing if it's not empty to ensure change detection is not triggered.
     key_input.bypass_change_detection().clear();
+    logical_key_input.bypass_change_detection().clear();
     for event in keyboard_input_events.read() {
         let KeyboardInput {
-            key_code, state, ..
+            key_code,
+            logical_key,
+            state,
+            ..
         } = event;
         match state {
-            ButtonState::Pressed => key_input.press(*key_code),
-            ButtonState::Released => key_input.release(*key_code),
+            ButtonState::Pressed => {
+                key_input.press(*key_code);
+                // Repeated presses keep the logical key of the first one.
+                if !pressed_logical_keys.contains_key(key_code) {
+                    pressed_logical_keys.insert(*key_code, logical_key.clone());
+                    logical_key_input.press(logical_key.clone());
+                }
+            }
+            ButtonState::Released => {
+                key_input.release(*key_code);
+                let logical_key = pressed_logical_keys
+                    .remove(key_code)
+                    .unwrap_or_else(|| logical_key.clone());
+                logical_key_input.release(logical_key);
+            }
         }
     }
 }
@@ -1441,3 +1470,43 @@ pub enum Key {
     /// General-purpose function key.
     F35,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_app::{App, Update};
+
+    #[test]
+    fn logical_keys_are_released_as_pressed() {
+        let mut app = App::new();
+        app.add_event::<KeyboardInput>()
+            .init_resource::<ButtonInput<KeyCode>>()
+            .init_resource::<ButtonInput<Key>>()
+            .add_systems(Update, keyboard_input_system);
+
+        let send = |app: &mut App, logical_key: &str, state| {
+            app.world.send_event(KeyboardInput {
+                key_code: KeyCode::KeyQ,
+                logical_key: Key::Character(logical_key.into()),
+                state,
+                window: Entity::PLACEHOLDER,
+            });
+            app.update();
+        };
+
+        // The key at the position of `Q` on a QWERTY layout produces `A` on an AZERTY layout.
+        send(&mut app, "A", ButtonState::Pressed);
+        let keys = app.world.resource::<ButtonInput<Key>>();
+        assert!(keys.just_pressed(Key::Character("A".into())));
+        assert!(app
+            .world
+            .resource::<ButtonInput<KeyCode>>()
+            .pressed(KeyCode::KeyQ));
+
+        // Releasing Shift before the key changes its logical key.
+        send(&mut app, "a", ButtonState::Released);
+        let keys = app.world.resource::<ButtonInput<Key>>();
+        assert!(keys.just_released(Key::Character("A".into())));
+        assert_eq!(keys.get_pressed().len(), 0);
+    }
+}
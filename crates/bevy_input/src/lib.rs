@@ -2,15 +2,31 @@
 //!
 //! # Supported input devices
 //!
-//! `bevy` currently supports keyboard, mouse, gamepad, and touch inputs.
+//! `bevy` currently supports keyboard, mouse, gamepad, touch, and pen inputs,
+//! as well as pointers unifying the mouse, touches and pens.
+//!
+//! # Actions
+//!
+//! Inputs can be bound to the actions of a game with an [`action::InputMap`], and read through
+//! an [`action::ActionState`] rather than directly.
+//!
+//! # Gestures
+//!
+//! Pinches, rotations, pans, taps, long presses and swipes are recognized from the touches and
+//! the touchpad, and sent as events like [`gestures::PinchGesture`] and [`gestures::TapGesture`].
 
+pub mod action;
 mod axis;
 mod button_input;
 /// Common run conditions
 pub mod common_conditions;
 pub mod gamepad;
+pub mod gestures;
 pub mod keyboard;
 pub mod mouse;
+pub mod pen;
+pub mod pointer;
+pub mod recording;
 pub mod touch;
 pub mod touchpad;
 
@@ -21,33 +37,45 @@ pub use button_input::*;
 pub mod prelude {
     #[doc(hidden)]
     pub use crate::{
+        action::{ActionState, InputActionPlugin, InputBinding, InputContexts, InputMap},
         gamepad::{
             Gamepad, GamepadAxis, GamepadAxisType, GamepadButton, GamepadButtonType, Gamepads,
         },
         keyboard::KeyCode,
         mouse::MouseButton,
+        pen::{PenInput, Pens},
+        pointer::{PointerButton, PointerId, PointerInput, Pointers},
         touch::{TouchInput, Touches},
         Axis, ButtonInput,
     };
 }
 
+use action::{InputBinding, InputContext};
 use bevy_app::prelude::*;
 use bevy_ecs::prelude::*;
 use bevy_reflect::Reflect;
+use gestures::{
+    gesture_system, DoubleTapGesture, GestureSettings, LongPressGesture, PanGesture, PinchGesture,
+    RotateGesture, SwipeGesture, TapGesture,
+};
 use keyboard::{keyboard_input_system, Key, KeyCode, KeyboardInput, NativeKey, NativeKeyCode};
 use mouse::{
     mouse_button_input_system, MouseButton, MouseButtonInput, MouseMotion, MouseScrollUnit,
-    MouseWheel,
+    MouseWheel, RawMouseMotion, RawMouseSettings,
+};
+use pen::{pen_input_system, PenButtons, PenInput, PenPhase, PenTilt, Pens};
+use pointer::{
+    pointer_input_system, PointerAction, PointerButton, PointerId, PointerInput, Pointers,
 };
 use touch::{touch_screen_input_system, ForceTouch, TouchInput, TouchPhase, Touches};
-use touchpad::{TouchpadMagnify, TouchpadRotate};
+use touchpad::{TouchpadMagnify, TouchpadRotate, TouchpadSmartMagnify};
 
 use gamepad::{
     gamepad_axis_event_system, gamepad_button_event_system, gamepad_connection_system,
     gamepad_event_system, AxisSettings, ButtonAxisSettings, ButtonSettings, Gamepad, GamepadAxis,
     GamepadAxisChangedEvent, GamepadAxisType, GamepadButton, GamepadButtonChangedEvent,
     GamepadButtonInput, GamepadButtonType, GamepadConnection, GamepadConnectionEvent, GamepadEvent,
-    GamepadRumbleRequest, GamepadSettings, Gamepads,
+    GamepadFeatures, GamepadRumbleRequest, GamepadSettings, Gamepads,
 };
 
 #[cfg(feature = "serialize")]
@@ -67,15 +95,19 @@ impl Plugin for InputPlugin {
             // keyboard
             .add_event::<KeyboardInput>()
             .init_resource::<ButtonInput<KeyCode>>()
+            .init_resource::<ButtonInput<Key>>()
             .add_systems(PreUpdate, keyboard_input_system.in_set(InputSystem))
             // mouse
             .add_event::<MouseButtonInput>()
             .add_event::<MouseMotion>()
             .add_event::<MouseWheel>()
+            .add_event::<RawMouseMotion>()
+            .init_resource::<RawMouseSettings>()
             .init_resource::<ButtonInput<MouseButton>>()
             .add_systems(PreUpdate, mouse_button_input_system.in_set(InputSystem))
             .add_event::<TouchpadMagnify>()
             .add_event::<TouchpadRotate>()
+            .add_event::<TouchpadSmartMagnify>()
             // gamepad
             .add_event::<GamepadConnectionEvent>()
             .add_event::<GamepadButtonChangedEvent>()
@@ -105,10 +137,36 @@ impl Plugin for InputPlugin {
             // touch
             .add_event::<TouchInput>()
             .init_resource::<Touches>()
-            .add_systems(PreUpdate, touch_screen_input_system.in_set(InputSystem));
+            .add_systems(PreUpdate, touch_screen_input_system.in_set(InputSystem))
+            // pen
+            .add_event::<PenInput>()
+            .init_resource::<Pens>()
+            .add_systems(PreUpdate, pen_input_system.in_set(InputSystem))
+            // pointer
+            .add_event::<PointerInput>()
+            .init_resource::<Pointers>()
+            .add_systems(PreUpdate, pointer_input_system.in_set(InputSystem))
+            // gestures
+            .add_event::<PinchGesture>()
+            .add_event::<RotateGesture>()
+            .add_event::<PanGesture>()
+            .add_event::<TapGesture>()
+            .add_event::<DoubleTapGesture>()
+            .add_event::<LongPressGesture>()
+            .add_event::<SwipeGesture>()
+            .init_resource::<GestureSettings>()
+            .add_systems(
+                PreUpdate,
+                gesture_system
+                    .after(touch_screen_input_system)
+                    .after(pointer_input_system)
+                    .in_set(InputSystem),
+            );
 
         // Register common types
-        app.register_type::<ButtonState>();
+        app.register_type::<ButtonState>()
+            .register_type::<InputBinding>()
+            .register_type::<InputContext>();
 
         // Register keyboard types
         app.register_type::<KeyboardInput>()
@@ -122,17 +180,42 @@ impl Plugin for InputPlugin {
             .register_type::<MouseButton>()
             .register_type::<MouseMotion>()
             .register_type::<MouseScrollUnit>()
-            .register_type::<MouseWheel>();
+            .register_type::<MouseWheel>()
+            .register_type::<RawMouseMotion>()
+            .register_type::<RawMouseSettings>();
 
         // Register touchpad types
         app.register_type::<TouchpadMagnify>()
-            .register_type::<TouchpadRotate>();
+            .register_type::<TouchpadRotate>()
+            .register_type::<TouchpadSmartMagnify>();
+
+        // Register gesture types
+        app.register_type::<PinchGesture>()
+            .register_type::<RotateGesture>()
+            .register_type::<PanGesture>()
+            .register_type::<TapGesture>()
+            .register_type::<DoubleTapGesture>()
+            .register_type::<LongPressGesture>()
+            .register_type::<SwipeGesture>()
+            .register_type::<GestureSettings>();
 
         // Register touch types
         app.register_type::<TouchInput>()
             .register_type::<ForceTouch>()
             .register_type::<TouchPhase>();
 
+        // Register pen types
+        app.register_type::<PenInput>()
+            .register_type::<PenPhase>()
+            .register_type::<PenTilt>()
+            .register_type::<PenButtons>();
+
+        // Register pointer types
+        app.register_type::<PointerInput>()
+            .register_type::<PointerId>()
+            .register_type::<PointerButton>()
+            .register_type::<PointerAction>();
+
         // Register gamepad types
         app.register_type::<Gamepad>()
             .register_type::<GamepadConnection>()
@@ -144,7 +227,8 @@ impl Plugin for InputPlugin {
             .register_type::<GamepadSettings>()
             .register_type::<ButtonSettings>()
             .register_type::<AxisSettings>()
-            .register_type::<ButtonAxisSettings>();
+            .register_type::<ButtonAxisSettings>()
+            .register_type::<GamepadFeatures>();
     }
 }
 
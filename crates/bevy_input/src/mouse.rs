@@ -5,10 +5,11 @@ use bevy_ecs::entity::Entity;
 use bevy_ecs::{
     change_detection::DetectChangesMut,
     event::{Event, EventReader},
-    system::ResMut,
+    system::{ResMut, Resource},
 };
 use bevy_math::Vec2;
-use bevy_reflect::Reflect;
+use bevy_reflect::{std_traits::ReflectDefault, Reflect};
+use bevy_utils::Instant;
 
 #[cfg(feature = "serialize")]
 use bevy_reflect::{ReflectDeserialize, ReflectSerialize};
@@ -90,6 +91,71 @@ pub struct MouseMotion {
     pub delta: Vec2,
 }
 
+/// An event reporting the raw motion of a mouse, sent for every motion reported by the device.
+///
+/// Unlike [`MouseMotion`], these events are only sent when enabled by [`RawMouseSettings`], and
+/// they identify the device they come from and can carry the time they were received at. They are
+/// never accumulated or merged, so a mouse with a high polling rate sends many of them per frame.
+///
+/// Like [`MouseMotion`], the deltas aren't affected by the acceleration or sensitivity the
+/// operating system applies to the cursor, so they are suitable for camera control.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    reflect(Serialize, Deserialize)
+)]
+pub struct RawMouseMotion {
+    /// The identifier of the device, unique among the devices connected to the machine.
+    pub device: u64,
+    /// The change in the position of the device since its previous motion.
+    pub delta: Vec2,
+    /// The time the motion was received at, if [`RawMouseSettings::timestamps`] is enabled.
+    ///
+    /// This is the time the event loop handled the motion, not a timestamp of the device or of the
+    /// operating system, so it includes the latency of delivering the motion to the application.
+    /// Motions handled during the same frame have different timestamps, which can be compared
+    /// with the start of the frame to know in which order and how far apart they arrived, but
+    /// motions delivered in a batch can have nearly identical timestamps.
+    #[cfg_attr(feature = "serialize", serde(skip))]
+    pub timestamp: Option<Instant>,
+}
+
+/// Settings for the raw mouse input, reported by [`RawMouseMotion`] events.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Debug, Default, PartialEq)]
+pub struct RawMouseSettings {
+    /// Whether [`RawMouseMotion`] events are sent.
+    pub enabled: bool,
+    /// Whether [`RawMouseMotion`] events carry the time they were received at, see
+    /// [`RawMouseMotion::timestamp`].
+    pub timestamps: bool,
+}
+
+impl RawMouseSettings {
+    /// Settings sending [`RawMouseMotion`] events with their timestamps.
+    pub const ENABLED: Self = Self {
+        enabled: true,
+        timestamps: true,
+    };
+
+    /// Returns the [`RawMouseMotion`] event to send for a motion of `device` by `delta`, handled
+    /// by the event loop at `received`, or `None` if raw mouse motions are disabled.
+    pub fn raw_motion(
+        &self,
+        device: u64,
+        delta: Vec2,
+        received: Instant,
+    ) -> Option<RawMouseMotion> {
+        self.enabled.then(|| RawMouseMotion {
+            device,
+            delta,
+            timestamp: self.timestamps.then_some(received),
+        })
+    }
+}
+
 /// The scroll unit.
 ///
 /// Describes how a value of a [`MouseWheel`] event has to be interpreted.
@@ -155,3 +221,36 @@ pub fn mouse_button_input_system(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn raw_motion_follows_settings() {
+        let delta = Vec2::new(1.0, -2.0);
+        let received = Instant::now();
+
+        assert_eq!(
+            RawMouseSettings::default().raw_motion(7, delta, received),
+            None
+        );
+
+        let motion = RawMouseSettings {
+            enabled: true,
+            timestamps: false,
+        }
+        .raw_motion(7, delta, received);
+        assert_eq!(
+            motion,
+            Some(RawMouseMotion {
+                device: 7,
+                delta,
+                timestamp: None,
+            })
+        );
+
+        let motion = RawMouseSettings::ENABLED.raw_motion(7, delta, received);
+        assert_eq!(motion.and_then(|motion| motion.timestamp), Some(received));
+    }
+}
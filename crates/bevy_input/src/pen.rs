@@ -0,0 +1,240 @@
+//! The pen and stylus input functionality.
+
+use bevy_ecs::entity::Entity;
+use bevy_ecs::event::{Event, EventReader};
+use bevy_ecs::system::{ResMut, Resource};
+use bevy_math::Vec2;
+use bevy_reflect::{std_traits::ReflectDefault, Reflect};
+use bevy_utils::HashMap;
+
+#[cfg(feature = "serialize")]
+use bevy_reflect::{ReflectDeserialize, ReflectSerialize};
+
+/// A pen or stylus input event, e.g. from a drawing tablet or an Apple Pencil.
+///
+/// ## Logic
+///
+/// Pens that can be detected above the surface send [`PenPhase::Hovered`] events while they
+/// approach it, and a [`PenPhase::Left`] event when they leave the detection range.
+/// Touching the surface sends a [`PenPhase::Started`] event, followed by [`PenPhase::Moved`]
+/// events when the pen moves or its pressure or tilt changes, and a [`PenPhase::Ended`] event
+/// when the pen is lifted.
+///
+/// ## Note
+///
+/// Pens may also be reported as [`TouchInput`](crate::touch::TouchInput)s or as the mouse,
+/// depending on the platform. The fields a platform doesn't report keep their default values:
+/// a pressure of `1.0` while touching, no tilt and no buttons.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    reflect(Serialize, Deserialize)
+)]
+pub struct PenInput {
+    /// The phase of the pen input.
+    pub phase: PenPhase,
+    /// The position of the tip of the pen in the window.
+    pub position: Vec2,
+    /// The window entity registering the pen.
+    pub window: Entity,
+    /// The pressure of the pen, from `0.0` to `1.0`, and `0.0` while hovering.
+    pub pressure: f32,
+    /// The tilt of the pen, if reported.
+    pub tilt: Option<PenTilt>,
+    /// The buttons of the pen being pressed.
+    pub buttons: PenButtons,
+    /// The unique identifier of the pen.
+    pub id: u64,
+}
+
+/// The phase of a [`PenInput`].
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Copy, Reflect)]
+#[reflect(Debug, Hash, PartialEq)]
+#[cfg_attr(
+    feature = "serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    reflect(Serialize, Deserialize)
+)]
+pub enum PenPhase {
+    /// The pen moved above the surface, without touching it.
+    Hovered,
+    /// The pen left the detection range of the surface.
+    Left,
+    /// The pen touched the surface.
+    Started,
+    /// The pen moved, or its pressure or tilt changed, while touching the surface.
+    Moved,
+    /// The pen was lifted from the surface.
+    Ended,
+    /// The system canceled tracking the pen.
+    Canceled,
+}
+
+/// The tilt of a pen.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    reflect(Serialize, Deserialize)
+)]
+pub struct PenTilt {
+    /// The angle between the pen and the surface, in radians: `0.0` when the pen lies on the
+    /// surface, and `π/2` when it's perpendicular to it.
+    pub altitude: f32,
+    /// The direction the pen points to on the surface, in radians, clockwise from the right of
+    /// the window, if reported.
+    pub azimuth: Option<f32>,
+}
+
+impl PenTilt {
+    /// Creates a tilt from the angles between the pen and the normal of the surface, in the
+    /// planes of the horizontal and vertical axes of the window, in radians.
+    pub fn from_tilt_angles(tilt_x: f32, tilt_y: f32) -> Self {
+        let (x, y) = (tilt_x.tan(), tilt_y.tan());
+        let length = x.hypot(y);
+        if length == 0.0 {
+            Self {
+                altitude: std::f32::consts::FRAC_PI_2,
+                azimuth: None,
+            }
+        } else {
+            Self {
+                altitude: length.recip().atan(),
+                azimuth: Some(y.atan2(x)),
+            }
+        }
+    }
+}
+
+/// The buttons of a pen being pressed.
+#[derive(Debug, Default, Hash, PartialEq, Eq, Clone, Copy, Reflect)]
+#[reflect(Debug, Default, Hash, PartialEq)]
+#[cfg_attr(
+    feature = "serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    reflect(Serialize, Deserialize)
+)]
+pub struct PenButtons {
+    /// The button on the barrel of the pen.
+    pub barrel: bool,
+    /// The eraser at the back of the pen, or the eraser button.
+    pub eraser: bool,
+}
+
+/// The state of a pen, as tracked by [`Pens`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pen {
+    /// The position of the tip of the pen.
+    pub position: Vec2,
+    /// The window of the pen.
+    pub window: Entity,
+    /// The pressure of the pen, `0.0` while hovering.
+    pub pressure: f32,
+    /// The tilt of the pen, if reported.
+    pub tilt: Option<PenTilt>,
+    /// The buttons of the pen being pressed.
+    pub buttons: PenButtons,
+    /// Whether the pen touches the surface.
+    pub touching: bool,
+}
+
+/// The pens detected by the system, updated from the [`PenInput`] events by
+/// [`pen_input_system`].
+#[derive(Debug, Clone, Default, Resource)]
+pub struct Pens {
+    pens: HashMap<u64, Pen>,
+}
+
+impl Pens {
+    /// Gets the state of the pen with the identifier `id`.
+    pub fn get(&self, id: u64) -> Option<&Pen> {
+        self.pens.get(&id)
+    }
+
+    /// An iterator over the pens and their identifiers.
+    pub fn iter(&self) -> impl Iterator<Item = (u64, &Pen)> {
+        self.pens.iter().map(|(id, pen)| (*id, pen))
+    }
+
+    /// An iterator over the pens touching the surface.
+    pub fn iter_touching(&self) -> impl Iterator<Item = &Pen> {
+        self.pens.values().filter(|pen| pen.touching)
+    }
+
+    fn process_event(&mut self, event: &PenInput) {
+        match event.phase {
+            PenPhase::Left | PenPhase::Canceled => {
+                self.pens.remove(&event.id);
+            }
+            phase => {
+                self.pens.insert(
+                    event.id,
+                    Pen {
+                        position: event.position,
+                        window: event.window,
+                        pressure: event.pressure,
+                        tilt: event.tilt,
+                        buttons: event.buttons,
+                        touching: matches!(phase, PenPhase::Started | PenPhase::Moved),
+                    },
+                );
+            }
+        }
+    }
+}
+
+/// Updates the [`Pens`] resource with the latest [`PenInput`] events.
+pub fn pen_input_system(mut pens: ResMut<Pens>, mut pen_input_events: EventReader<PenInput>) {
+    for event in pen_input_events.read() {
+        pens.process_event(event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pens_track_hover_and_touch() {
+        let mut pens = Pens::default();
+        let mut event = PenInput {
+            phase: PenPhase::Hovered,
+            position: Vec2::new(4.0, 2.0),
+            window: Entity::PLACEHOLDER,
+            pressure: 0.0,
+            tilt: None,
+            buttons: PenButtons::default(),
+            id: 1,
+        };
+        pens.process_event(&event);
+        assert_eq!(pens.iter_touching().count(), 0);
+
+        event.phase = PenPhase::Started;
+        event.pressure = 0.5;
+        pens.process_event(&event);
+        assert_eq!(pens.get(1).unwrap().pressure, 0.5);
+        assert_eq!(pens.iter_touching().count(), 1);
+
+        event.phase = PenPhase::Ended;
+        pens.process_event(&event);
+        assert!(!pens.get(1).unwrap().touching);
+
+        event.phase = PenPhase::Left;
+        pens.process_event(&event);
+        assert!(pens.get(1).is_none());
+    }
+
+    #[test]
+    fn tilt_angles_convert_to_altitude_and_azimuth() {
+        let upright = PenTilt::from_tilt_angles(0.0, 0.0);
+        assert_eq!(upright.altitude, std::f32::consts::FRAC_PI_2);
+        assert_eq!(upright.azimuth, None);
+
+        let tilted = PenTilt::from_tilt_angles(std::f32::consts::FRAC_PI_4, 0.0);
+        assert!((tilted.altitude - std::f32::consts::FRAC_PI_4).abs() < 1e-6);
+        assert_eq!(tilted.azimuth, Some(0.0));
+    }
+}
@@ -0,0 +1,270 @@
+//! The pointer input functionality, unifying the mouse, touches and pens.
+
+use crate::ButtonInput;
+use bevy_ecs::entity::Entity;
+use bevy_ecs::{
+    change_detection::DetectChangesMut,
+    event::{Event, EventReader},
+    system::{ResMut, Resource},
+};
+use bevy_math::Vec2;
+use bevy_reflect::Reflect;
+use bevy_utils::HashMap;
+
+#[cfg(feature = "serialize")]
+use bevy_reflect::{ReflectDeserialize, ReflectSerialize};
+
+/// The identifier of a pointer.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Copy, Reflect)]
+#[reflect(Debug, Hash, PartialEq)]
+#[cfg_attr(
+    feature = "serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    reflect(Serialize, Deserialize)
+)]
+pub enum PointerId {
+    /// The mouse.
+    Mouse,
+    /// A touch, with the identifier of its [`TouchInput`](crate::touch::TouchInput)s.
+    Touch(u64),
+    /// A pen, with the identifier of its [`PenInput`](crate::pen::PenInput)s.
+    Pen(u64),
+}
+
+/// A button of a pointer.
+///
+/// Touches and the tips of pens press the [`PointerButton::Primary`] button.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Copy, Reflect)]
+#[reflect(Debug, Hash, PartialEq)]
+#[cfg_attr(
+    feature = "serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    reflect(Serialize, Deserialize)
+)]
+pub enum PointerButton {
+    /// The left mouse button, a touch or the tip of a pen.
+    Primary,
+    /// The right mouse button or the barrel button of a pen.
+    Secondary,
+    /// The middle mouse button.
+    Middle,
+}
+
+/// The action of a [`PointerInput`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Reflect)]
+#[reflect(Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    reflect(Serialize, Deserialize)
+)]
+pub enum PointerAction {
+    /// The pointer moved, or entered the window.
+    Moved,
+    /// A button of the pointer was pressed.
+    Pressed(PointerButton),
+    /// A button of the pointer was released.
+    Released(PointerButton),
+    /// The pointer left the window, or a touch was lifted.
+    Left,
+    /// The system canceled tracking the pointer.
+    Canceled,
+}
+
+/// A pointer input event, sent for the mouse, touches and pens alike.
+///
+/// Systems reacting to pointers instead of a specific device work with all of them, e.g. a button
+/// can be clicked with the mouse, tapped with a finger or with a pen.
+///
+/// ## Usage
+///
+/// The event is read inside of the [`pointer_input_system`] to update the [`Pointers`] resource.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    reflect(Serialize, Deserialize)
+)]
+pub struct PointerInput {
+    /// The pointer.
+    pub id: PointerId,
+    /// The action of the pointer.
+    pub action: PointerAction,
+    /// The window of the pointer.
+    pub window: Entity,
+    /// The position of the pointer in the window.
+    pub position: Vec2,
+    /// The pressure of the pointer, from `0.0` to `1.0`: `0.5` while pressing a button without
+    /// pressure sensitivity, and `0.0` while no button is pressed.
+    pub pressure: f32,
+}
+
+/// The state of a pointer, as tracked by [`Pointers`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pointer {
+    /// The window of the pointer.
+    pub window: Entity,
+    /// The position of the pointer in the window.
+    pub position: Vec2,
+    /// The pressure of the pointer.
+    pub pressure: f32,
+}
+
+/// The pointers in the windows and their pressed buttons, updated from the [`PointerInput`]
+/// events by [`pointer_input_system`].
+#[derive(Debug, Clone, Default, Resource)]
+pub struct Pointers {
+    pointers: HashMap<PointerId, Pointer>,
+    buttons: ButtonInput<(PointerId, PointerButton)>,
+}
+
+impl Pointers {
+    /// Gets the state of the pointer `id`, if it's in a window.
+    pub fn get(&self, id: PointerId) -> Option<&Pointer> {
+        self.pointers.get(&id)
+    }
+
+    /// An iterator over the pointers in the windows and their identifiers.
+    pub fn iter(&self) -> impl Iterator<Item = (PointerId, &Pointer)> {
+        self.pointers.iter().map(|(id, pointer)| (*id, pointer))
+    }
+
+    /// Returns `true` if the `button` of the pointer `id` is pressed.
+    pub fn pressed(&self, id: PointerId, button: PointerButton) -> bool {
+        self.buttons.pressed((id, button))
+    }
+
+    /// Returns `true` if the `button` of the pointer `id` has just been pressed.
+    pub fn just_pressed(&self, id: PointerId, button: PointerButton) -> bool {
+        self.buttons.just_pressed((id, button))
+    }
+
+    /// Returns `true` if the `button` of the pointer `id` has just been released.
+    pub fn just_released(&self, id: PointerId, button: PointerButton) -> bool {
+        self.buttons.just_released((id, button))
+    }
+
+    /// Returns `true` if the `button` of any pointer is pressed.
+    pub fn any_pressed(&self, button: PointerButton) -> bool {
+        self.buttons
+            .get_pressed()
+            .any(|(_, pressed)| *pressed == button)
+    }
+
+    /// Returns `true` if the `button` of any pointer has just been pressed.
+    pub fn any_just_pressed(&self, button: PointerButton) -> bool {
+        self.buttons
+            .get_just_pressed()
+            .any(|(_, pressed)| *pressed == button)
+    }
+
+    /// Returns `true` if the `button` of any pointer has just been released.
+    pub fn any_just_released(&self, button: PointerButton) -> bool {
+        self.buttons
+            .get_just_released()
+            .any(|(_, released)| *released == button)
+    }
+
+    /// The position of the pointer interacting with the `window`: a pointer pressing a button
+    /// if any, or else a hovering pointer, like the mouse cursor.
+    pub fn position(&self, window: Entity) -> Option<Vec2> {
+        let mut in_window = self
+            .pointers
+            .iter()
+            .filter(|(_, pointer)| pointer.window == window);
+        let pressing = in_window.clone().find(|(id, _)| {
+            self.buttons
+                .get_pressed()
+                .any(|(pressed_id, _)| pressed_id == *id)
+        });
+        pressing
+            .or_else(|| in_window.next())
+            .map(|(_, pointer)| pointer.position)
+    }
+
+    fn process_event(&mut self, event: &PointerInput) {
+        match event.action {
+            PointerAction::Left | PointerAction::Canceled => {
+                self.pointers.remove(&event.id);
+                for button in [
+                    PointerButton::Primary,
+                    PointerButton::Secondary,
+                    PointerButton::Middle,
+                ] {
+                    self.buttons.release((event.id, button));
+                }
+            }
+            action => {
+                self.pointers.insert(
+                    event.id,
+                    Pointer {
+                        window: event.window,
+                        position: event.position,
+                        pressure: event.pressure,
+                    },
+                );
+                match action {
+                    PointerAction::Pressed(button) => self.buttons.press((event.id, button)),
+                    PointerAction::Released(button) => self.buttons.release((event.id, button)),
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+/// Updates the [`Pointers`] resource with the latest [`PointerInput`] events.
+pub fn pointer_input_system(
+    mut pointers: ResMut<Pointers>,
+    mut pointer_input_events: EventReader<PointerInput>,
+) {
+    // Avoid clearing if it's not empty to ensure change detection is not triggered.
+    pointers.bypass_change_detection().buttons.clear();
+    for event in pointer_input_events.read() {
+        pointers.process_event(event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(id: PointerId, action: PointerAction, position: Vec2) -> PointerInput {
+        PointerInput {
+            id,
+            action,
+            window: Entity::PLACEHOLDER,
+            position,
+            pressure: 0.0,
+        }
+    }
+
+    #[test]
+    fn pointers_track_buttons_of_all_devices() {
+        let mut pointers = Pointers::default();
+        pointers.process_event(&event(PointerId::Mouse, PointerAction::Moved, Vec2::ONE));
+        pointers.process_event(&event(
+            PointerId::Touch(3),
+            PointerAction::Pressed(PointerButton::Primary),
+            Vec2::new(5.0, 6.0),
+        ));
+        assert!(pointers.any_just_pressed(PointerButton::Primary));
+        assert!(!pointers.pressed(PointerId::Mouse, PointerButton::Primary));
+        // The touch pressing a button takes precedence over the hovering mouse.
+        assert_eq!(
+            pointers.position(Entity::PLACEHOLDER),
+            Some(Vec2::new(5.0, 6.0))
+        );
+
+        pointers.buttons.clear();
+        pointers.process_event(&event(
+            PointerId::Touch(3),
+            PointerAction::Left,
+            Vec2::new(5.0, 6.0),
+        ));
+        assert!(pointers.just_released(PointerId::Touch(3), PointerButton::Primary));
+        assert!(pointers.get(PointerId::Touch(3)).is_none());
+        assert_eq!(pointers.position(Entity::PLACEHOLDER), Some(Vec2::ONE));
+    }
+}
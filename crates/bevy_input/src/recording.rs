@@ -0,0 +1,336 @@
+//! Recording of the input events and their playback, for integration tests and demos.
+
+use crate::{
+    gamepad::GamepadEvent,
+    keyboard::KeyboardInput,
+    mouse::{MouseButtonInput, MouseMotion, MouseWheel},
+    pen::PenInput,
+    pointer::PointerInput,
+    touch::TouchInput,
+    touchpad::{TouchpadMagnify, TouchpadRotate},
+    InputSystem,
+};
+use bevy_app::{App, Plugin, PreUpdate};
+use bevy_ecs::{
+    event::{EventReader, Events},
+    schedule::{IntoSystemConfigs, IntoSystemSetConfigs, SystemSet},
+    system::{ResMut, Resource, SystemParam},
+};
+use bevy_utils::{Duration, Instant};
+
+/// Adds the [`InputRecorder`] resource, to record the input events of an app and play them back.
+///
+/// The events recorded are the ones sent by the input backends: [`KeyboardInput`],
+/// [`MouseButtonInput`], [`MouseMotion`], [`MouseWheel`], [`TouchpadMagnify`],
+/// [`TouchpadRotate`], [`TouchInput`], [`PenInput`], [`PointerInput`] and [`GamepadEvent`]. The
+/// input resources, like
+/// [`ButtonInput<KeyCode>`](crate::ButtonInput), are updated from them during the playback.
+#[derive(Default)]
+pub struct InputRecordingPlugin;
+
+/// Label for the system playing back the recorded input events, before the [`InputSystem`].
+///
+/// Input backends sending their events during [`PreUpdate`] should run before it, so their
+/// events are replaced by the recorded ones during the playback.
+#[derive(Debug, PartialEq, Eq, Clone, Hash, SystemSet)]
+pub struct InputPlaybackSystem;
+
+impl Plugin for InputRecordingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<InputRecorder>()
+            .configure_sets(PreUpdate, InputPlaybackSystem.before(InputSystem))
+            .add_systems(
+                PreUpdate,
+                (
+                    play_inputs.in_set(InputPlaybackSystem),
+                    record_inputs.after(InputSystem),
+                ),
+            );
+    }
+}
+
+/// The input events of a frame of an [`InputRecording`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct RecordedFrame {
+    /// The index of the frame, from the start of the recording.
+    pub frame: u64,
+    /// The time elapsed between the start of the recording and the frame.
+    pub elapsed: Duration,
+    /// The input events of the frame, in the order they were sent for each type of event.
+    pub inputs: Vec<RecordedInput>,
+}
+
+/// Input events recorded by an [`InputRecorder`], which can be played back.
+///
+/// With the `serialize` feature, recordings can be saved to be played back later, e.g. by an
+/// integration test running a headless app.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct InputRecording {
+    /// The frames with input events.
+    pub frames: Vec<RecordedFrame>,
+    /// The number of frames recorded, including the ones without input events.
+    pub frame_count: u64,
+}
+
+#[derive(Debug, Default)]
+enum RecorderMode {
+    #[default]
+    Idle,
+    Recording {
+        recording: InputRecording,
+        start: Instant,
+    },
+    Playing {
+        recording: InputRecording,
+        frame: u64,
+        next: usize,
+    },
+}
+
+/// Records the input events of the app and plays them back, frame by frame.
+///
+/// Playing the events back frame by frame makes the playback deterministic as long as the app is,
+/// e.g. when it uses a fixed time step. Live input events are discarded during the playback.
+///
+/// ```
+/// # use bevy_ecs::prelude::*;
+/// # use bevy_input::{recording::InputRecorder, keyboard::KeyCode, ButtonInput};
+/// fn toggle_recording(keys: Res<ButtonInput<KeyCode>>, mut recorder: ResMut<InputRecorder>) {
+///     if keys.just_pressed(KeyCode::F9) {
+///         if let Some(recording) = recorder.stop_recording() {
+///             recorder.play(recording);
+///         } else {
+///             recorder.start_recording();
+///         }
+///     }
+/// }
+/// ```
+#[derive(Resource, Debug, Default)]
+pub struct InputRecorder {
+    mode: RecorderMode,
+}
+
+impl InputRecorder {
+    /// Starts recording the input events, from the next frame.
+    ///
+    /// Stops the playback of a recording, if any.
+    pub fn start_recording(&mut self) {
+        self.mode = RecorderMode::Recording {
+            recording: InputRecording::default(),
+            start: Instant::now(),
+        };
+    }
+
+    /// Stops recording the input events and returns the recording, if recording.
+    pub fn stop_recording(&mut self) -> Option<InputRecording> {
+        match std::mem::take(&mut self.mode) {
+            RecorderMode::Recording { recording, .. } => Some(recording),
+            mode => {
+                self.mode = mode;
+                None
+            }
+        }
+    }
+
+    /// Plays back the `recording`, from the next frame.
+    ///
+    /// Stops recording, if recording.
+    pub fn play(&mut self, recording: InputRecording) {
+        self.mode = RecorderMode::Playing {
+            recording,
+            frame: 0,
+            next: 0,
+        };
+    }
+
+    /// Stops the playback of a recording, if any.
+    pub fn stop_playback(&mut self) {
+        if self.is_playing() {
+            self.mode = RecorderMode::Idle;
+        }
+    }
+
+    /// Returns `true` if recording the input events.
+    pub fn is_recording(&self) -> bool {
+        matches!(self.mode, RecorderMode::Recording { .. })
+    }
+
+    /// Returns `true` if playing back a recording. The playback stops after the last frame of the
+    /// recording.
+    pub fn is_playing(&self) -> bool {
+        matches!(self.mode, RecorderMode::Playing { .. })
+    }
+}
+
+macro_rules! recorded_inputs {
+    ($($variant:ident($event:ty) => $field:ident,)*) => {
+        /// An input event recorded in an [`InputRecording`].
+        #[derive(Debug, Clone, PartialEq)]
+        #[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+        pub enum RecordedInput {
+            $(
+                #[doc = concat!("A [`", stringify!($event), "`] event.")]
+                $variant($event),
+            )*
+        }
+
+        #[derive(SystemParam)]
+        struct InputEventReaders<'w, 's> {
+            $($field: EventReader<'w, 's, $event>,)*
+        }
+
+        impl InputEventReaders<'_, '_> {
+            fn read(&mut self, inputs: &mut Vec<RecordedInput>) {
+                $(inputs.extend(self.$field.read().cloned().map(RecordedInput::$variant));)*
+            }
+        }
+
+        #[derive(SystemParam)]
+        struct InputEvents<'w> {
+            $($field: ResMut<'w, Events<$event>>,)*
+        }
+
+        impl InputEvents<'_> {
+            fn clear(&mut self) {
+                $(self.$field.clear();)*
+            }
+
+            fn send(&mut self, input: RecordedInput) {
+                match input {
+                    $(RecordedInput::$variant(event) => {
+                        self.$field.send(event);
+                    })*
+                }
+            }
+        }
+    };
+}
+
+recorded_inputs! {
+    Keyboard(KeyboardInput) => keyboard,
+    MouseButton(MouseButtonInput) => mouse_button,
+    MouseMotion(MouseMotion) => mouse_motion,
+    MouseWheel(MouseWheel) => mouse_wheel,
+    TouchpadMagnify(TouchpadMagnify) => touchpad_magnify,
+    TouchpadRotate(TouchpadRotate) => touchpad_rotate,
+    Touch(TouchInput) => touch,
+    Pen(PenInput) => pen,
+    Pointer(PointerInput) => pointer,
+    Gamepad(GamepadEvent) => gamepad,
+}
+
+/// Records the input events of the frame, if recording.
+fn record_inputs(mut recorder: ResMut<InputRecorder>, mut readers: InputEventReaders) {
+    let RecorderMode::Recording { recording, start } = &mut recorder.mode else {
+        // Skip the events of the frames not being recorded.
+        readers.read(&mut Vec::new());
+        return;
+    };
+    let mut inputs = Vec::new();
+    readers.read(&mut inputs);
+    if !inputs.is_empty() {
+        recording.frames.push(RecordedFrame {
+            frame: recording.frame_count,
+            elapsed: start.elapsed(),
+            inputs,
+        });
+    }
+    recording.frame_count += 1;
+}
+
+/// Replaces the input events of the frame with the recorded ones, if playing back a recording.
+fn play_inputs(mut recorder: ResMut<InputRecorder>, mut events: InputEvents) {
+    let RecorderMode::Playing {
+        recording,
+        frame,
+        next,
+    } = &mut recorder.mode
+    else {
+        return;
+    };
+    if *frame >= recording.frame_count {
+        recorder.mode = RecorderMode::Idle;
+        return;
+    }
+    events.clear();
+    if let Some(recorded) = recording
+        .frames
+        .get(*next)
+        .filter(|recorded| recorded.frame == *frame)
+    {
+        for input in recorded.inputs.iter().cloned() {
+            events.send(input);
+        }
+        *next += 1;
+    }
+    *frame += 1;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{keyboard::Key, keyboard::KeyCode, ButtonInput, ButtonState, InputPlugin};
+    use bevy_ecs::entity::Entity;
+
+    fn key_event(state: ButtonState) -> KeyboardInput {
+        KeyboardInput {
+            key_code: KeyCode::Space,
+            logical_key: Key::Space,
+            state,
+            window: Entity::PLACEHOLDER,
+        }
+    }
+
+    #[test]
+    fn recorded_inputs_play_back_frame_by_frame() {
+        let mut app = App::new();
+        app.add_plugins((InputPlugin, InputRecordingPlugin));
+        app.world.resource_mut::<InputRecorder>().start_recording();
+
+        app.world.send_event(key_event(ButtonState::Pressed));
+        app.update();
+        app.update();
+        app.world.send_event(key_event(ButtonState::Released));
+        app.update();
+
+        let recording = app
+            .world
+            .resource_mut::<InputRecorder>()
+            .stop_recording()
+            .unwrap();
+        assert_eq!(recording.frame_count, 3);
+        assert_eq!(
+            recording
+                .frames
+                .iter()
+                .map(|frame| frame.frame)
+                .collect::<Vec<_>>(),
+            [0, 2]
+        );
+
+        app.world.resource_mut::<InputRecorder>().play(recording);
+        app.update();
+        assert!(app
+            .world
+            .resource::<ButtonInput<KeyCode>>()
+            .just_pressed(KeyCode::Space));
+
+        // Live input is discarded during the playback.
+        app.world.send_event(key_event(ButtonState::Released));
+        app.update();
+        assert!(app
+            .world
+            .resource::<ButtonInput<KeyCode>>()
+            .pressed(KeyCode::Space));
+
+        app.update();
+        assert!(app
+            .world
+            .resource::<ButtonInput<KeyCode>>()
+            .just_released(KeyCode::Space));
+        app.update();
+        assert!(!app.world.resource::<InputRecorder>().is_playing());
+    }
+}
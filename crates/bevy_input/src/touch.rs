@@ -365,7 +365,7 @@ impl Touches {
 
     /// Processes a [`TouchInput`] event by updating the `pressed`, `just_pressed`,
     /// `just_released`, and `just_canceled` collections.
-    fn process_touch_event(&mut self, event: &TouchInput) {
+    pub(crate) fn process_touch_event(&mut self, event: &TouchInput) {
         match event.phase {
             TouchPhase::Started => {
                 self.pressed.insert(event.id, event.into());
@@ -39,3 +39,17 @@ pub struct TouchpadMagnify(pub f32);
     reflect(Serialize, Deserialize)
 )]
 pub struct TouchpadRotate(pub f32);
+
+/// Touchpad smart magnification event, sent on a two-finger double tap.
+///
+/// ## Platform-specific
+///
+/// - Only available on **`macOS`**.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    reflect(Serialize, Deserialize)
+)]
+pub struct TouchpadSmartMagnify;
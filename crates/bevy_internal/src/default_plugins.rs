@@ -2,6 +2,8 @@ use bevy_app::{Plugin, PluginGroup, PluginGroupBuilder};
 
 /// This plugin group will add all the default plugins for a *Bevy* application:
 /// * [`LogPlugin`](crate::log::LogPlugin)
+/// * [`CommandErrorPlugin`](crate::app::CommandErrorPlugin)
+/// * [`AsyncTasksPlugin`](crate::app::AsyncTasksPlugin)
 /// * [`TaskPoolPlugin`](crate::core::TaskPoolPlugin)
 /// * [`TypeRegistrationPlugin`](crate::core::TypeRegistrationPlugin)
 /// * [`FrameCountPlugin`](crate::core::FrameCountPlugin)
@@ -42,6 +44,8 @@ impl PluginGroup for DefaultPlugins {
         let mut group = PluginGroupBuilder::start::<Self>();
         group = group
             .add(bevy_log::LogPlugin::default())
+            .add(bevy_app::CommandErrorPlugin)
+            .add(bevy_app::AsyncTasksPlugin)
             .add(bevy_core::TaskPoolPlugin::default())
             .add(bevy_core::TypeRegistrationPlugin)
             .add(bevy_core::FrameCountPlugin)
@@ -134,6 +138,16 @@ impl PluginGroup for DefaultPlugins {
             group = group.add(bevy_gizmos::GizmoPlugin);
         }
 
+        #[cfg(feature = "bevy_particles")]
+        {
+            group = group.add(bevy_particles::ParticlePlugin);
+        }
+
+        #[cfg(feature = "bevy_picking")]
+        {
+            group = group.add(bevy_picking::PickingPlugin);
+        }
+
         group = group.add(IgnoreAmbiguitiesPlugin);
 
         group
@@ -181,6 +195,8 @@ impl Plugin for IgnoreAmbiguitiesPlugin {
 }
 
 /// This plugin group will add the minimal plugins for a *Bevy* application:
+/// * [`CommandErrorPlugin`](crate::app::CommandErrorPlugin)
+/// * [`AsyncTasksPlugin`](crate::app::AsyncTasksPlugin)
 /// * [`TaskPoolPlugin`](crate::core::TaskPoolPlugin)
 /// * [`TypeRegistrationPlugin`](crate::core::TypeRegistrationPlugin)
 /// * [`FrameCountPlugin`](crate::core::FrameCountPlugin)
@@ -200,6 +216,8 @@ pub struct MinimalPlugins;
 impl PluginGroup for MinimalPlugins {
     fn build(self) -> PluginGroupBuilder {
         PluginGroupBuilder::start::<Self>()
+            .add(bevy_app::CommandErrorPlugin)
+            .add(bevy_app::AsyncTasksPlugin)
             .add(bevy_core::TaskPoolPlugin::default())
             .add(bevy_core::TypeRegistrationPlugin)
             .add(bevy_core::FrameCountPlugin)
@@ -116,6 +116,12 @@ pub mod core_pipeline {
     pub use bevy_core_pipeline::*;
 }
 
+#[cfg(feature = "bevy_dev_tools")]
+pub mod dev_tools {
+    //! Tools to help developing apps, like a performance overlay.
+    pub use bevy_dev_tools::*;
+}
+
 #[cfg(feature = "bevy_gilrs")]
 pub mod gilrs {
     //! Bevy interface with `GilRs` - "Game Input Library for Rust" - to handle gamepad inputs.
@@ -134,6 +140,18 @@ pub mod pbr {
     pub use bevy_pbr::*;
 }
 
+#[cfg(feature = "bevy_particles")]
+pub mod particles {
+    //! Particle effects, simulated on the GPU and drawn by 3D cameras.
+    pub use bevy_particles::*;
+}
+
+#[cfg(feature = "bevy_picking")]
+pub mod picking {
+    //! Finds the meshes, sprites and UI nodes under the pointers, and sends them pointer events.
+    pub use bevy_picking::*;
+}
+
 #[cfg(feature = "bevy_render")]
 pub mod render {
     //! Cameras, meshes, textures, shaders, and pipelines.
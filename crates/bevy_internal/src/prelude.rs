@@ -27,6 +27,14 @@ pub use crate::core_pipeline::prelude::*;
 #[cfg(feature = "bevy_pbr")]
 pub use crate::pbr::prelude::*;
 
+#[doc(hidden)]
+#[cfg(feature = "bevy_particles")]
+pub use crate::particles::prelude::*;
+
+#[doc(hidden)]
+#[cfg(feature = "bevy_picking")]
+pub use crate::picking::prelude::*;
+
 #[doc(hidden)]
 #[cfg(feature = "bevy_render")]
 pub use crate::render::prelude::*;
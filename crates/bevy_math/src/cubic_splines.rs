@@ -296,6 +296,7 @@ pub trait CubicGenerator<P: Point> {
 ///
 /// Segments can be chained together to form a longer compound curve.
 #[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct CubicSegment<P: Point> {
     coeff: [P; 4],
 }
@@ -435,6 +436,7 @@ impl CubicSegment<Vec2> {
 /// Use any struct that implements the [`CubicGenerator`] trait to create a new curve, such as
 /// [`CubicBezier`].
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct CubicCurve<P: Point> {
     segments: Vec<CubicSegment<P>>,
 }
@@ -0,0 +1,711 @@
+//! Provides the [`Curve`] trait, for values that vary along a parameter such as time, along with
+//! keyframed curves, easing functions and combinators to build new curves from existing ones.
+//!
+//! Curves are shared by animation, UI transitions and gameplay code. The curve types that don't
+//! store closures can be serialized with the `serialize` feature, so that they can be stored in
+//! assets.
+
+use std::{f32::consts::PI, marker::PhantomData};
+
+use glam::{Quat, Vec2, Vec3, Vec3A, Vec4};
+
+use crate::cubic_splines::{CubicCurve, CubicSegment, Point};
+
+/// A value that can be interpolated between two instances of itself.
+pub trait Interpolate: Clone {
+    /// Interpolates between `self` and `other`, where `t = 0` gives `self` and `t = 1` gives
+    /// `other`.
+    ///
+    /// `t` may be outside of `0..=1` with easing functions that overshoot, such as
+    /// [`EaseFunction::BackOut`].
+    fn interpolate(&self, other: &Self, t: f32) -> Self;
+}
+
+macro_rules! impl_interpolate_lerp {
+    ($($ty:ty),*) => {
+        $(
+            impl Interpolate for $ty {
+                #[inline]
+                fn interpolate(&self, other: &Self, t: f32) -> Self {
+                    *self + (*other - *self) * t
+                }
+            }
+        )*
+    };
+}
+
+impl_interpolate_lerp!(f32, Vec2, Vec3, Vec3A, Vec4);
+
+impl Interpolate for Quat {
+    /// Spherical linear interpolation, along the shortest path.
+    #[inline]
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        self.slerp(*other, t)
+    }
+}
+
+/// A value of type `T` that varies along a parameter `t`, usually time.
+///
+/// The curve is defined for `t` in `0..=duration`. Sampling outside of this range gives the value
+/// at the nearest end of the curve.
+pub trait Curve<T> {
+    /// The length of the domain of the curve, which starts at 0.
+    fn duration(&self) -> f32;
+
+    /// Samples the curve at `t`, which is clamped to `0..=duration`.
+    fn sample(&self, t: f32) -> T;
+
+    /// Iterates over `subdivisions + 1` samples evenly spaced over the whole curve.
+    fn samples(&self, subdivisions: usize) -> impl Iterator<Item = T> + '_
+    where
+        Self: Sized,
+    {
+        let step = self.duration() / subdivisions.max(1) as f32;
+        (0..=subdivisions).map(move |i| self.sample(i as f32 * step))
+    }
+
+    /// Creates a curve whose values are those of this curve transformed by `f`.
+    fn map<U, F: Fn(T) -> U>(self, f: F) -> MapCurve<Self, F, T>
+    where
+        Self: Sized,
+    {
+        MapCurve {
+            curve: self,
+            f,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Creates a curve that follows this curve, then `next`.
+    ///
+    /// The duration of the resulting curve is the sum of the durations of both curves.
+    fn chain<C: Curve<T>>(self, next: C) -> ChainCurve<Self, C>
+    where
+        Self: Sized,
+    {
+        ChainCurve { first: self, next }
+    }
+
+    /// Creates a curve that follows this curve backwards.
+    fn reverse(self) -> ReverseCurve<Self>
+    where
+        Self: Sized,
+    {
+        ReverseCurve { curve: self }
+    }
+
+    /// Creates a curve that follows this curve, stretched or compressed to last `duration`.
+    fn with_duration(self, duration: f32) -> ScaledCurve<Self>
+    where
+        Self: Sized,
+    {
+        ScaledCurve {
+            curve: self,
+            duration,
+        }
+    }
+}
+
+impl<T, C: Curve<T> + ?Sized> Curve<T> for &C {
+    #[inline]
+    fn duration(&self) -> f32 {
+        (**self).duration()
+    }
+
+    #[inline]
+    fn sample(&self, t: f32) -> T {
+        (**self).sample(t)
+    }
+}
+
+impl<T, C: Curve<T> + ?Sized> Curve<T> for Box<C> {
+    #[inline]
+    fn duration(&self) -> f32 {
+        (**self).duration()
+    }
+
+    #[inline]
+    fn sample(&self, t: f32) -> T {
+        (**self).sample(t)
+    }
+}
+
+/// A curve given by a function of `t`.
+///
+/// See [`function_curve`].
+#[derive(Clone, Debug)]
+pub struct FunctionCurve<F> {
+    duration: f32,
+    f: F,
+}
+
+/// Creates a curve lasting `duration` whose value at `t` is `f(t)`.
+pub fn function_curve<T, F: Fn(f32) -> T>(duration: f32, f: F) -> FunctionCurve<F> {
+    FunctionCurve { duration, f }
+}
+
+impl<T, F: Fn(f32) -> T> Curve<T> for FunctionCurve<F> {
+    #[inline]
+    fn duration(&self) -> f32 {
+        self.duration
+    }
+
+    #[inline]
+    fn sample(&self, t: f32) -> T {
+        (self.f)(t.clamp(0.0, self.duration))
+    }
+}
+
+/// A curve that always has the same value.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct ConstantCurve<T> {
+    /// The duration of the curve.
+    pub duration: f32,
+    /// The value of the curve.
+    pub value: T,
+}
+
+impl<T: Clone> Curve<T> for ConstantCurve<T> {
+    #[inline]
+    fn duration(&self) -> f32 {
+        self.duration
+    }
+
+    #[inline]
+    fn sample(&self, _t: f32) -> T {
+        self.value.clone()
+    }
+}
+
+/// How a [`KeyframeCurve`] interpolates between its keyframes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub enum KeyframeInterpolation {
+    /// The value of a keyframe is held until the next keyframe.
+    Step,
+    /// The value is interpolated linearly between keyframes.
+    #[default]
+    Linear,
+}
+
+/// A curve defined by values at given times, called keyframes.
+///
+/// The curve holds the value of its first keyframe before it, and ends at its last keyframe.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct KeyframeCurve<T> {
+    times: Vec<f32>,
+    values: Vec<T>,
+    interpolation: KeyframeInterpolation,
+}
+
+impl<T> KeyframeCurve<T> {
+    /// Creates a curve from `(time, value)` keyframes, which don't need to be sorted.
+    ///
+    /// Returns [`None`] if there are no keyframes, or if a time is negative or NaN.
+    pub fn new(
+        keyframes: impl IntoIterator<Item = (f32, T)>,
+        interpolation: KeyframeInterpolation,
+    ) -> Option<Self> {
+        let mut keyframes: Vec<(f32, T)> = keyframes.into_iter().collect();
+        if keyframes.is_empty()
+            || keyframes
+                .iter()
+                .any(|(time, _)| time.is_nan() || *time < 0.0)
+        {
+            return None;
+        }
+        keyframes.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+        let (times, values) = keyframes.into_iter().unzip();
+        Some(Self {
+            times,
+            values,
+            interpolation,
+        })
+    }
+
+    /// The times of the keyframes, in increasing order.
+    pub fn times(&self) -> &[f32] {
+        &self.times
+    }
+
+    /// The values of the keyframes, in the same order as [`KeyframeCurve::times`].
+    pub fn values(&self) -> &[T] {
+        &self.values
+    }
+
+    /// How the curve interpolates between keyframes.
+    pub fn interpolation(&self) -> KeyframeInterpolation {
+        self.interpolation
+    }
+}
+
+impl<T: Interpolate> Curve<T> for KeyframeCurve<T> {
+    #[inline]
+    fn duration(&self) -> f32 {
+        // `new` ensures that there is at least one keyframe.
+        self.times[self.times.len() - 1]
+    }
+
+    fn sample(&self, t: f32) -> T {
+        // Index of the first keyframe after `t`.
+        let next = self.times.partition_point(|&time| time <= t);
+        if next == 0 {
+            return self.values[0].clone();
+        }
+        if next == self.times.len() {
+            return self.values[next - 1].clone();
+        }
+        let previous = next - 1;
+        match self.interpolation {
+            KeyframeInterpolation::Step => self.values[previous].clone(),
+            KeyframeInterpolation::Linear => {
+                let (start, end) = (self.times[previous], self.times[next]);
+                let s = (t - start) / (end - start);
+                self.values[previous].interpolate(&self.values[next], s)
+            }
+        }
+    }
+}
+
+/// Easing functions, which map a time in `0..=1` to a progress that starts at 0 and ends at 1.
+///
+/// See <https://easings.net> for illustrations of most of these functions.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub enum EaseFunction {
+    /// `f(t) = t`
+    #[default]
+    Linear,
+    /// `f(t) = t²`
+    QuadraticIn,
+    /// `f(t) = -(t * (t - 2.0))`
+    QuadraticOut,
+    /// Behaves as [`EaseFunction::QuadraticIn`] for t < 0.5 and as
+    /// [`EaseFunction::QuadraticOut`] for t >= 0.5.
+    QuadraticInOut,
+    /// `f(t) = t³`
+    CubicIn,
+    /// `f(t) = (t - 1.0)³ + 1.0`
+    CubicOut,
+    /// Behaves as [`EaseFunction::CubicIn`] for t < 0.5 and as [`EaseFunction::CubicOut`] for
+    /// t >= 0.5.
+    CubicInOut,
+    /// `f(t) = 1.0 - cos(t * π / 2.0)`
+    SineIn,
+    /// `f(t) = sin(t * π / 2.0)`
+    SineOut,
+    /// Behaves as [`EaseFunction::SineIn`] for t < 0.5 and as [`EaseFunction::SineOut`] for
+    /// t >= 0.5.
+    SineInOut,
+    /// `f(t) = 2.0^(10.0 * (t - 1.0))`
+    ExponentialIn,
+    /// `f(t) = 1.0 - 2.0^(-10.0 * t)`
+    ExponentialOut,
+    /// Behaves as [`EaseFunction::ExponentialIn`] for t < 0.5 and as
+    /// [`EaseFunction::ExponentialOut`] for t >= 0.5.
+    ExponentialInOut,
+    /// `f(t) = 1.0 - sqrt(1.0 - t²)`
+    CircularIn,
+    /// `f(t) = sqrt((2.0 - t) * t)`
+    CircularOut,
+    /// Behaves as [`EaseFunction::CircularIn`] for t < 0.5 and as
+    /// [`EaseFunction::CircularOut`] for t >= 0.5.
+    CircularInOut,
+    /// `f(t) = 2.70158 * t³ - 1.70158 * t²`, which goes below 0 at the start.
+    BackIn,
+    /// The reverse of [`EaseFunction::BackIn`], which goes over 1 at the end.
+    BackOut,
+    /// Behaves as [`EaseFunction::BackIn`] for t < 0.5 and as [`EaseFunction::BackOut`] for
+    /// t >= 0.5.
+    BackInOut,
+    /// An oscillation of growing amplitude, like a spring being released.
+    ElasticIn,
+    /// An oscillation of decreasing amplitude, like a spring settling down.
+    ElasticOut,
+    /// Behaves as [`EaseFunction::ElasticIn`] for t < 0.5 and as
+    /// [`EaseFunction::ElasticOut`] for t >= 0.5.
+    ElasticInOut,
+    /// The reverse of [`EaseFunction::BounceOut`].
+    BounceIn,
+    /// Bounces like a ball dropped on the floor.
+    BounceOut,
+    /// Behaves as [`EaseFunction::BounceIn`] for t < 0.5 and as [`EaseFunction::BounceOut`]
+    /// for t >= 0.5.
+    BounceInOut,
+    /// Jumps from 0 to 1 in the given number of equal steps.
+    Steps(usize),
+    /// A cubic Bezier with control points `(0, 0)`, the two given points, and `(1, 1)`, as with
+    /// CSS `cubic-bezier()`.
+    ///
+    /// See [`CubicSegment::new_bezier`].
+    CubicBezier(Vec2, Vec2),
+}
+
+impl EaseFunction {
+    /// Computes the progress at time `t`, which is clamped to `0..=1`.
+    ///
+    /// The result is 0 at `t = 0` and 1 at `t = 1`, but may be outside of `0..=1` in between for
+    /// functions that overshoot.
+    pub fn ease(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            EaseFunction::Linear => t,
+            EaseFunction::QuadraticIn => t * t,
+            EaseFunction::QuadraticOut => -(t * (t - 2.0)),
+            EaseFunction::QuadraticInOut => in_out(t, EaseFunction::QuadraticIn),
+            EaseFunction::CubicIn => t * t * t,
+            EaseFunction::CubicOut => (t - 1.0).powi(3) + 1.0,
+            EaseFunction::CubicInOut => in_out(t, EaseFunction::CubicIn),
+            EaseFunction::SineIn => 1.0 - (t * PI / 2.0).cos(),
+            EaseFunction::SineOut => (t * PI / 2.0).sin(),
+            EaseFunction::SineInOut => in_out(t, EaseFunction::SineIn),
+            EaseFunction::ExponentialIn => {
+                if t <= 0.0 {
+                    0.0
+                } else {
+                    2.0f32.powf(10.0 * (t - 1.0))
+                }
+            }
+            EaseFunction::ExponentialOut => 1.0 - EaseFunction::ExponentialIn.ease(1.0 - t),
+            EaseFunction::ExponentialInOut => in_out(t, EaseFunction::ExponentialIn),
+            EaseFunction::CircularIn => 1.0 - (1.0 - t * t).sqrt(),
+            EaseFunction::CircularOut => ((2.0 - t) * t).sqrt(),
+            EaseFunction::CircularInOut => in_out(t, EaseFunction::CircularIn),
+            EaseFunction::BackIn => {
+                const C1: f32 = 1.70158;
+                (C1 + 1.0) * t * t * t - C1 * t * t
+            }
+            EaseFunction::BackOut => 1.0 - EaseFunction::BackIn.ease(1.0 - t),
+            EaseFunction::BackInOut => in_out(t, EaseFunction::BackIn),
+            EaseFunction::ElasticIn => {
+                if t <= 0.0 || t >= 1.0 {
+                    t
+                } else {
+                    -(2.0f32.powf(10.0 * t - 10.0)) * ((t * 10.0 - 10.75) * 2.0 * PI / 3.0).sin()
+                }
+            }
+            EaseFunction::ElasticOut => 1.0 - EaseFunction::ElasticIn.ease(1.0 - t),
+            EaseFunction::ElasticInOut => in_out(t, EaseFunction::ElasticIn),
+            EaseFunction::BounceIn => 1.0 - EaseFunction::BounceOut.ease(1.0 - t),
+            EaseFunction::BounceOut => {
+                const N: f32 = 7.5625;
+                const D: f32 = 2.75;
+                if t < 1.0 / D {
+                    N * t * t
+                } else if t < 2.0 / D {
+                    let t = t - 1.5 / D;
+                    N * t * t + 0.75
+                } else if t < 2.5 / D {
+                    let t = t - 2.25 / D;
+                    N * t * t + 0.9375
+                } else {
+                    let t = t - 2.625 / D;
+                    N * t * t + 0.984375
+                }
+            }
+            EaseFunction::BounceInOut => in_out(t, EaseFunction::BounceIn),
+            EaseFunction::Steps(steps) => {
+                let steps = steps.max(1) as f32;
+                (t * steps).floor() / steps
+            }
+            EaseFunction::CubicBezier(p1, p2) => CubicSegment::new_bezier(p1, p2).ease(t),
+        }
+    }
+}
+
+/// Builds an "in-out" easing function from an "in" one, by following it for the first half of
+/// `t` and following it in reverse for the second half.
+#[inline]
+fn in_out(t: f32, ease_in: EaseFunction) -> f32 {
+    if t < 0.5 {
+        ease_in.ease(2.0 * t) / 2.0
+    } else {
+        1.0 - ease_in.ease(2.0 - 2.0 * t) / 2.0
+    }
+}
+
+impl Curve<f32> for EaseFunction {
+    #[inline]
+    fn duration(&self) -> f32 {
+        1.0
+    }
+
+    #[inline]
+    fn sample(&self, t: f32) -> f32 {
+        self.ease(t)
+    }
+}
+
+/// A curve that goes from `start` to `end` in one unit of time, following an [`EaseFunction`].
+///
+/// Use [`Curve::with_duration`] to change how long the transition lasts.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct EasingCurve<T> {
+    /// The value at the start of the curve.
+    pub start: T,
+    /// The value at the end of the curve.
+    pub end: T,
+    /// How the value progresses from `start` to `end`.
+    pub ease: EaseFunction,
+}
+
+impl<T> EasingCurve<T> {
+    /// Creates a curve going from `start` to `end` following `ease`.
+    pub fn new(start: T, end: T, ease: EaseFunction) -> Self {
+        Self { start, end, ease }
+    }
+}
+
+impl<T: Interpolate> Curve<T> for EasingCurve<T> {
+    #[inline]
+    fn duration(&self) -> f32 {
+        1.0
+    }
+
+    #[inline]
+    fn sample(&self, t: f32) -> T {
+        self.start.interpolate(&self.end, self.ease.ease(t))
+    }
+}
+
+impl<P: Point> Curve<P> for CubicSegment<P> {
+    #[inline]
+    fn duration(&self) -> f32 {
+        1.0
+    }
+
+    #[inline]
+    fn sample(&self, t: f32) -> P {
+        self.position(t.clamp(0.0, 1.0))
+    }
+}
+
+impl<P: Point> Curve<P> for CubicCurve<P> {
+    #[inline]
+    fn duration(&self) -> f32 {
+        self.segments().len() as f32
+    }
+
+    #[inline]
+    fn sample(&self, t: f32) -> P {
+        self.position(t.clamp(0.0, self.duration()))
+    }
+}
+
+/// A curve whose values are transformed by a function.
+///
+/// See [`Curve::map`].
+#[derive(Clone, Debug)]
+pub struct MapCurve<C, F, T> {
+    curve: C,
+    f: F,
+    _phantom: PhantomData<fn(T)>,
+}
+
+impl<T, U, C: Curve<T>, F: Fn(T) -> U> Curve<U> for MapCurve<C, F, T> {
+    #[inline]
+    fn duration(&self) -> f32 {
+        self.curve.duration()
+    }
+
+    #[inline]
+    fn sample(&self, t: f32) -> U {
+        (self.f)(self.curve.sample(t))
+    }
+}
+
+/// A curve that follows a curve, then another one.
+///
+/// See [`Curve::chain`].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChainCurve<A, B> {
+    first: A,
+    next: B,
+}
+
+impl<T, A: Curve<T>, B: Curve<T>> Curve<T> for ChainCurve<A, B> {
+    #[inline]
+    fn duration(&self) -> f32 {
+        self.first.duration() + self.next.duration()
+    }
+
+    #[inline]
+    fn sample(&self, t: f32) -> T {
+        let first_duration = self.first.duration();
+        if t < first_duration {
+            self.first.sample(t)
+        } else {
+            self.next.sample(t - first_duration)
+        }
+    }
+}
+
+/// A curve that follows a curve backwards.
+///
+/// See [`Curve::reverse`].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReverseCurve<C> {
+    curve: C,
+}
+
+impl<T, C: Curve<T>> Curve<T> for ReverseCurve<C> {
+    #[inline]
+    fn duration(&self) -> f32 {
+        self.curve.duration()
+    }
+
+    #[inline]
+    fn sample(&self, t: f32) -> T {
+        let duration = self.curve.duration();
+        self.curve.sample(duration - t.clamp(0.0, duration))
+    }
+}
+
+/// A curve that follows a curve at a different speed.
+///
+/// See [`Curve::with_duration`].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct ScaledCurve<C> {
+    curve: C,
+    duration: f32,
+}
+
+impl<T, C: Curve<T>> Curve<T> for ScaledCurve<C> {
+    #[inline]
+    fn duration(&self) -> f32 {
+        self.duration
+    }
+
+    #[inline]
+    fn sample(&self, t: f32) -> T {
+        if self.duration <= 0.0 {
+            return self.curve.sample(self.curve.duration());
+        }
+        let t = t.clamp(0.0, self.duration) / self.duration;
+        self.curve.sample(t * self.curve.duration())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::{vec2, Vec3};
+
+    use super::*;
+    use crate::cubic_splines::{CubicBezier, CubicGenerator};
+
+    /// How close two floats can be and still be considered equal
+    const FLOAT_EQ: f32 = 1e-5;
+
+    #[test]
+    fn keyframe_curve() {
+        let curve = KeyframeCurve::new(
+            [(2.0, 4.0), (0.0, 0.0), (1.0, 1.0)],
+            KeyframeInterpolation::Linear,
+        )
+        .unwrap();
+        assert_eq!(curve.times(), &[0.0, 1.0, 2.0]);
+        assert_eq!(curve.duration(), 2.0);
+        assert_eq!(curve.sample(-1.0), 0.0);
+        assert_eq!(curve.sample(0.5), 0.5);
+        assert_eq!(curve.sample(1.5), 2.5);
+        assert_eq!(curve.sample(3.0), 4.0);
+
+        let step = KeyframeCurve::new(
+            [(0.0, Vec3::ZERO), (1.0, Vec3::ONE)],
+            KeyframeInterpolation::Step,
+        )
+        .unwrap();
+        assert_eq!(step.sample(0.25), Vec3::ZERO);
+        assert_eq!(step.sample(1.0), Vec3::ONE);
+
+        assert!(KeyframeCurve::<f32>::new([], KeyframeInterpolation::Step).is_none());
+        assert!(KeyframeCurve::new([(f32::NAN, 0.0)], KeyframeInterpolation::Step).is_none());
+    }
+
+    #[test]
+    fn ease_functions_start_at_zero_and_end_at_one() {
+        let functions = [
+            EaseFunction::Linear,
+            EaseFunction::QuadraticIn,
+            EaseFunction::QuadraticOut,
+            EaseFunction::QuadraticInOut,
+            EaseFunction::CubicIn,
+            EaseFunction::CubicOut,
+            EaseFunction::CubicInOut,
+            EaseFunction::SineIn,
+            EaseFunction::SineOut,
+            EaseFunction::SineInOut,
+            EaseFunction::ExponentialIn,
+            EaseFunction::ExponentialOut,
+            EaseFunction::ExponentialInOut,
+            EaseFunction::CircularIn,
+            EaseFunction::CircularOut,
+            EaseFunction::CircularInOut,
+            EaseFunction::BackIn,
+            EaseFunction::BackOut,
+            EaseFunction::BackInOut,
+            EaseFunction::ElasticIn,
+            EaseFunction::ElasticOut,
+            EaseFunction::ElasticInOut,
+            EaseFunction::BounceIn,
+            EaseFunction::BounceOut,
+            EaseFunction::BounceInOut,
+            EaseFunction::Steps(4),
+            EaseFunction::CubicBezier(vec2(0.25, 0.1), vec2(0.25, 1.0)),
+        ];
+        for function in functions {
+            assert!(function.ease(0.0).abs() < 1e-3, "{function:?}");
+            assert!((function.ease(1.0) - 1.0).abs() < 1e-3, "{function:?}");
+            assert!(function.ease(0.5).is_finite(), "{function:?}");
+        }
+        assert!((EaseFunction::QuadraticInOut.ease(0.5) - 0.5).abs() < FLOAT_EQ);
+        assert_eq!(EaseFunction::Steps(4).ease(0.3), 0.25);
+    }
+
+    #[test]
+    fn combinators() {
+        let curve = EasingCurve::new(0.0, 1.0, EaseFunction::Linear)
+            .with_duration(2.0)
+            .chain(ConstantCurve {
+                duration: 1.0,
+                value: 1.0,
+            })
+            .map(|value| value * 10.0);
+        assert_eq!(curve.duration(), 3.0);
+        assert!((curve.sample(1.0) - 5.0).abs() < FLOAT_EQ);
+        assert!((curve.sample(2.5) - 10.0).abs() < FLOAT_EQ);
+
+        let reversed = EasingCurve::new(Vec3::ZERO, Vec3::X, EaseFunction::Linear).reverse();
+        assert!(reversed
+            .sample(0.25)
+            .abs_diff_eq(Vec3::new(0.75, 0.0, 0.0), FLOAT_EQ));
+
+        let samples: Vec<f32> = function_curve(1.0, |t| t * 2.0).samples(2).collect();
+        assert_eq!(samples, vec![0.0, 1.0, 2.0]);
+
+        let boxed: Box<dyn Curve<f32>> = Box::new(EaseFunction::QuadraticIn);
+        assert!((boxed.sample(0.5) - 0.25).abs() < FLOAT_EQ);
+    }
+
+    #[test]
+    fn cubic_curves_are_curves() {
+        let bezier = CubicBezier::new([[
+            vec2(0.0, 0.0),
+            vec2(1.0, 1.0),
+            vec2(2.0, 1.0),
+            vec2(3.0, 0.0),
+        ]])
+        .to_curve();
+        assert_eq!(bezier.duration(), 1.0);
+        assert!(bezier.sample(2.0).abs_diff_eq(vec2(3.0, 0.0), FLOAT_EQ));
+        assert!(bezier
+            .sample(0.5)
+            .abs_diff_eq(bezier.position(0.5), FLOAT_EQ));
+    }
+}
@@ -0,0 +1,291 @@
+use std::ops::RangeInclusive;
+
+use bevy_asset::{Asset, Handle};
+use bevy_math::{Mat4, Vec2, Vec3, Vec4};
+use bevy_reflect::{std_traits::ReflectDefault, Reflect};
+use bevy_render::{color::Color, mesh::Mesh, render_resource::ShaderType};
+
+/// The number of samples each curve of a [`ParticleEffect`] is baked to, for the GPU.
+pub const CURVE_SAMPLES: usize = 16;
+
+/// An asset describing how the particles of an emitter spawn, move and look.
+///
+/// Spawn an effect with a [`ParticleEffectBundle`](crate::ParticleEffectBundle): its particles
+/// spawn at the [`GlobalTransform`](bevy_transform::components::GlobalTransform) of the emitter,
+/// then move in world space.
+#[derive(Asset, Reflect, Clone, Debug)]
+#[reflect(Default)]
+pub struct ParticleEffect {
+    /// The maximum number of particles of an emitter alive at once. Once reached, new particles
+    /// replace the oldest ones.
+    pub capacity: u32,
+    /// The number of particles spawned per second.
+    pub spawn_rate: f32,
+    /// The range of lifetimes of the particles, in seconds.
+    pub lifetime: RangeInclusive<f32>,
+    /// The shape the particles spawn in, in the space of the emitter.
+    pub shape: EmitterShape,
+    /// The range of initial speeds of the particles, in the direction given by the
+    /// [`shape`](Self::shape).
+    pub speed: RangeInclusive<f32>,
+    /// The acceleration of the particles in world space, such as gravity.
+    pub acceleration: Vec3,
+    /// How quickly the particles lose their velocity, per second.
+    pub drag: f32,
+    /// The factor of the velocity of the particles over their life.
+    pub speed_over_life: ParticleCurve,
+    /// The size of the particles over their life, in world units.
+    pub size_over_life: ParticleCurve,
+    /// The color of the particles over their life.
+    ///
+    /// Colors brighter than 1.0 are kept with HDR cameras, and glow with bloom.
+    pub color_over_life: ParticleGradient,
+    /// How the particles blend with what is behind them.
+    pub blend_mode: ParticleBlendMode,
+    /// What each particle is drawn as.
+    pub render_mode: ParticleRenderMode,
+}
+
+impl Default for ParticleEffect {
+    fn default() -> Self {
+        Self {
+            capacity: 1024,
+            spawn_rate: 64.0,
+            lifetime: 1.0..=2.0,
+            shape: EmitterShape::default(),
+            speed: 1.0..=2.0,
+            acceleration: Vec3::ZERO,
+            drag: 0.0,
+            speed_over_life: ParticleCurve::constant(1.0),
+            size_over_life: ParticleCurve::constant(0.1),
+            color_over_life: ParticleGradient::constant(Color::WHITE),
+            blend_mode: ParticleBlendMode::default(),
+            render_mode: ParticleRenderMode::default(),
+        }
+    }
+}
+
+/// The shape the particles of a [`ParticleEffect`] spawn in, and the direction of their initial
+/// velocity.
+#[derive(Reflect, Clone, Copy, Debug, Default, PartialEq)]
+#[reflect(Default)]
+pub enum EmitterShape {
+    /// Particles spawn at the emitter, moving in random directions.
+    #[default]
+    Point,
+    /// Particles spawn in a sphere, moving away from its center.
+    Sphere {
+        /// The radius of the sphere.
+        radius: f32,
+    },
+    /// Particles spawn in a box, moving along its Y axis.
+    Cuboid {
+        /// Half the size of the box along each axis.
+        half_size: Vec3,
+    },
+    /// Particles spawn in a disk on the XZ plane, moving within a cone around the Y axis.
+    Cone {
+        /// The radius of the disk.
+        radius: f32,
+        /// The angle between the Y axis and the side of the cone, in radians.
+        angle: f32,
+    },
+}
+
+impl EmitterShape {
+    /// The index of the shape and its dimensions, as read by the shaders.
+    fn shader_data(self) -> (u32, Vec3) {
+        match self {
+            EmitterShape::Point => (0, Vec3::ZERO),
+            EmitterShape::Sphere { radius } => (1, Vec3::splat(radius)),
+            EmitterShape::Cuboid { half_size } => (2, half_size),
+            EmitterShape::Cone { radius, angle } => (3, Vec3::new(radius, angle, 0.0)),
+        }
+    }
+}
+
+/// A value over the life of the particles, linearly interpolated between keys.
+///
+/// The time of each key goes from 0.0, when particles spawn, to 1.0, when they die. Keys must be
+/// sorted by time.
+#[derive(Reflect, Clone, Debug, PartialEq)]
+#[reflect(Default)]
+pub struct ParticleCurve {
+    /// The times and values of the keys.
+    pub keys: Vec<(f32, f32)>,
+}
+
+impl Default for ParticleCurve {
+    fn default() -> Self {
+        Self::constant(1.0)
+    }
+}
+
+impl ParticleCurve {
+    /// Creates a curve from its keys, sorted by time.
+    pub fn new(keys: impl Into<Vec<(f32, f32)>>) -> Self {
+        Self { keys: keys.into() }
+    }
+
+    /// Creates a curve with the same value over the whole life of the particles.
+    pub fn constant(value: f32) -> Self {
+        Self::new([(0.0, value)])
+    }
+
+    /// Returns the value of the curve at a time from 0.0 to 1.0.
+    pub fn sample(&self, time: f32) -> f32 {
+        sample_keys(&self.keys, time, |a, b, t| a + (b - a) * t).unwrap_or(0.0)
+    }
+
+    fn bake(&self) -> [Vec4; CURVE_SAMPLES / 4] {
+        let samples = baked_times().map(|time| self.sample(time));
+        std::array::from_fn(|i| Vec4::from_slice(&samples[i * 4..]))
+    }
+}
+
+/// A color over the life of the particles, linearly interpolated between keys in linear space.
+///
+/// The time of each key goes from 0.0, when particles spawn, to 1.0, when they die. Keys must be
+/// sorted by time.
+#[derive(Reflect, Clone, Debug, PartialEq)]
+#[reflect(Default)]
+pub struct ParticleGradient {
+    /// The times and colors of the keys.
+    pub keys: Vec<(f32, Color)>,
+}
+
+impl Default for ParticleGradient {
+    fn default() -> Self {
+        Self::constant(Color::WHITE)
+    }
+}
+
+impl ParticleGradient {
+    /// Creates a gradient from its keys, sorted by time.
+    pub fn new(keys: impl Into<Vec<(f32, Color)>>) -> Self {
+        Self { keys: keys.into() }
+    }
+
+    /// Creates a gradient with the same color over the whole life of the particles.
+    pub fn constant(color: Color) -> Self {
+        Self::new([(0.0, color)])
+    }
+
+    /// Returns the linear RGBA color of the gradient at a time from 0.0 to 1.0.
+    pub fn sample(&self, time: f32) -> Vec4 {
+        let keys: Vec<_> = self
+            .keys
+            .iter()
+            .map(|&(time, color)| (time, Vec4::from(color.as_linear_rgba_f32())))
+            .collect();
+        sample_keys(&keys, time, Vec4::lerp).unwrap_or(Vec4::ZERO)
+    }
+
+    fn bake(&self) -> [Vec4; CURVE_SAMPLES] {
+        baked_times().map(|time| self.sample(time))
+    }
+}
+
+/// The times at which the curves are baked.
+fn baked_times() -> [f32; CURVE_SAMPLES] {
+    std::array::from_fn(|i| i as f32 / (CURVE_SAMPLES - 1) as f32)
+}
+
+fn sample_keys<T: Copy>(keys: &[(f32, T)], time: f32, lerp: impl Fn(T, T, f32) -> T) -> Option<T> {
+    let next = keys.partition_point(|&(key_time, _)| key_time <= time);
+    match (keys.get(next.wrapping_sub(1)), keys.get(next)) {
+        (Some(&(start_time, start)), Some(&(end_time, end))) => {
+            let t = (time - start_time) / (end_time - start_time);
+            Some(lerp(start, end, t))
+        }
+        (Some(&(_, value)), None) | (None, Some(&(_, value))) => Some(value),
+        (None, None) => None,
+    }
+}
+
+/// How the particles of a [`ParticleEffect`] blend with what is behind them.
+#[derive(Reflect, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[reflect(Default)]
+pub enum ParticleBlendMode {
+    /// Particles are blended by their alpha.
+    #[default]
+    Blend,
+    /// Particles add their color, scaled by their alpha, to what is behind them. Suited to fire,
+    /// sparks and magic.
+    Add,
+}
+
+/// What each particle of a [`ParticleEffect`] is drawn as.
+#[derive(Reflect, Clone, Debug, Default, PartialEq)]
+#[reflect(Default)]
+pub enum ParticleRenderMode {
+    /// Round soft quads facing the camera.
+    #[default]
+    Billboard,
+    /// Instances of a mesh, scaled by the size of the particles and rotated along their velocity.
+    Mesh(Handle<Mesh>),
+}
+
+/// The data of an emitter and of its [`ParticleEffect`] read by the shaders, which must match
+/// `particles.wgsl`.
+#[derive(ShaderType, Clone, Default)]
+pub(crate) struct ParticleEffectUniform {
+    pub(crate) emitter: Mat4,
+    pub(crate) acceleration: Vec3,
+    pub(crate) drag: f32,
+    pub(crate) shape_size: Vec3,
+    pub(crate) shape: u32,
+    pub(crate) lifetime: Vec2,
+    pub(crate) speed: Vec2,
+    pub(crate) speed_over_life: [Vec4; CURVE_SAMPLES / 4],
+    pub(crate) size_over_life: [Vec4; CURVE_SAMPLES / 4],
+    pub(crate) color_over_life: [Vec4; CURVE_SAMPLES],
+    pub(crate) delta_time: f32,
+    pub(crate) seed: u32,
+    pub(crate) spawn_start: u32,
+    pub(crate) spawn_count: u32,
+    pub(crate) capacity: u32,
+}
+
+impl ParticleEffectUniform {
+    /// Bakes an effect for an emitter, leaving the data of the frame to the simulation.
+    pub(crate) fn new(effect: &ParticleEffect, emitter: Mat4) -> Self {
+        let (shape, shape_size) = effect.shape.shader_data();
+        Self {
+            emitter,
+            acceleration: effect.acceleration,
+            drag: effect.drag,
+            shape_size,
+            shape,
+            lifetime: Vec2::new(*effect.lifetime.start(), *effect.lifetime.end()),
+            speed: Vec2::new(*effect.speed.start(), *effect.speed.end()),
+            speed_over_life: effect.speed_over_life.bake(),
+            size_over_life: effect.size_over_life.bake(),
+            color_over_life: effect.color_over_life.bake(),
+            capacity: effect.capacity,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ParticleCurve, ParticleGradient};
+    use bevy_math::Vec4;
+    use bevy_render::color::Color;
+
+    #[test]
+    fn sample_curve() {
+        let curve = ParticleCurve::new([(0.25, 1.0), (0.75, 3.0)]);
+        assert_eq!(curve.sample(0.0), 1.0);
+        assert_eq!(curve.sample(0.5), 2.0);
+        assert_eq!(curve.sample(1.0), 3.0);
+        assert_eq!(ParticleCurve::new([]).sample(0.5), 0.0);
+    }
+
+    #[test]
+    fn sample_gradient() {
+        let gradient = ParticleGradient::new([(0.0, Color::BLACK), (1.0, Color::WHITE)]);
+        assert_eq!(gradient.sample(0.5), Vec4::new(0.5, 0.5, 0.5, 1.0));
+    }
+}
@@ -0,0 +1,165 @@
+//! This crate provides particle effects: emitters spawning many small particles which move and
+//! change over their life, such as fire, smoke, sparks or magic.
+//!
+//! A [`ParticleEffect`] asset describes how particles spawn, move and look, and is spawned with a
+//! [`ParticleEffectBundle`]. The particles are simulated with compute shaders, or on the CPU where
+//! they are not supported, and drawn in the transparent pass of the 3D cameras.
+//!
+//! # Example
+//!
+//! ```
+//! # use bevy_asset::Assets;
+//! # use bevy_ecs::prelude::*;
+//! # use bevy_math::Vec3;
+//! # use bevy_particles::prelude::*;
+//! # use bevy_render::color::Color;
+//! fn spawn_sparks(mut commands: Commands, mut effects: ResMut<Assets<ParticleEffect>>) {
+//!     let effect = effects.add(ParticleEffect {
+//!         shape: EmitterShape::Cone { radius: 0.1, angle: 0.3 },
+//!         speed: 2.0..=4.0,
+//!         acceleration: Vec3::new(0.0, -9.8, 0.0),
+//!         color_over_life: ParticleGradient::new([
+//!             (0.0, Color::rgb(4.0, 2.0, 0.5)),
+//!             (1.0, Color::rgba(1.0, 0.2, 0.0, 0.0)),
+//!         ]),
+//!         blend_mode: ParticleBlendMode::Add,
+//!         ..Default::default()
+//!     });
+//!     commands.spawn(ParticleEffectBundle {
+//!         effect,
+//!         ..Default::default()
+//!     });
+//! }
+//! # bevy_ecs::system::assert_is_system(spawn_sparks);
+//! ```
+
+mod effect;
+mod render;
+mod simulation;
+
+pub use effect::*;
+pub use simulation::{particle_simulation_is_supported, ParticleSimulationLabel};
+
+/// The `bevy_particles` prelude.
+pub mod prelude {
+    #[doc(hidden)]
+    pub use crate::{
+        EmitterShape, ParticleBlendMode, ParticleCurve, ParticleEffect, ParticleEffectBundle,
+        ParticleGradient, ParticlePlugin, ParticleRenderMode,
+    };
+}
+
+use bevy_app::{App, Plugin};
+use bevy_asset::{load_internal_asset, AssetApp, Handle};
+use bevy_core_pipeline::core_3d::Transparent3d;
+use bevy_ecs::prelude::*;
+use bevy_render::{
+    graph::CameraDriverLabel,
+    mesh::Mesh,
+    render_asset::prepare_assets,
+    render_graph::RenderGraph,
+    render_phase::AddRenderCommand,
+    render_resource::{Shader, SpecializedRenderPipelines},
+    renderer::RenderDevice,
+    view::{InheritedVisibility, ViewVisibility, Visibility},
+    ExtractSchedule, Render, RenderApp, RenderSet,
+};
+use bevy_transform::components::{GlobalTransform, Transform};
+use render::{
+    prepare_particle_bind_groups, queue_particles, DrawParticles, ParticleRenderPipeline,
+    ParticleViewBindGroup,
+};
+use simulation::{
+    extract_particle_emitters, prepare_particle_emitters, prepare_particle_simulation_bind_groups,
+    ExtractedParticleEmitters, ParticleEmitters, ParticleSimulationNode,
+    ParticleSimulationPipeline,
+};
+
+const PARTICLES_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(6482544168567612059);
+const SIMULATE_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(12948708915562271027);
+const RENDER_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(17571673517410908878);
+
+/// Adds support for [`ParticleEffect`]s.
+pub struct ParticlePlugin;
+
+impl Plugin for ParticlePlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(
+            app,
+            PARTICLES_SHADER_HANDLE,
+            "particles.wgsl",
+            Shader::from_wgsl
+        );
+        load_internal_asset!(
+            app,
+            SIMULATE_SHADER_HANDLE,
+            "simulate.wgsl",
+            Shader::from_wgsl
+        );
+        load_internal_asset!(app, RENDER_SHADER_HANDLE, "render.wgsl", Shader::from_wgsl);
+
+        app.init_asset::<ParticleEffect>()
+            .register_asset_reflect::<ParticleEffect>();
+
+        let Ok(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        render_app
+            .init_resource::<ExtractedParticleEmitters>()
+            .init_resource::<ParticleEmitters>()
+            .init_resource::<ParticleViewBindGroup>()
+            .init_resource::<SpecializedRenderPipelines<ParticleRenderPipeline>>()
+            .add_render_command::<Transparent3d, DrawParticles>()
+            .add_systems(ExtractSchedule, extract_particle_emitters)
+            .add_systems(
+                Render,
+                (
+                    queue_particles
+                        .in_set(RenderSet::Queue)
+                        .after(prepare_assets::<Mesh>),
+                    prepare_particle_emitters.in_set(RenderSet::PrepareResources),
+                    prepare_particle_bind_groups.in_set(RenderSet::PrepareBindGroups),
+                ),
+            );
+
+        let mut render_graph = render_app.world.resource_mut::<RenderGraph>();
+        render_graph.add_node(ParticleSimulationLabel, ParticleSimulationNode);
+        render_graph.add_node_edge(ParticleSimulationLabel, CameraDriverLabel);
+    }
+
+    fn finish(&self, app: &mut App) {
+        let Ok(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        render_app.init_resource::<ParticleRenderPipeline>();
+
+        // Without compute shaders, the particles are simulated on the CPU.
+        if particle_simulation_is_supported(render_app.world.resource::<RenderDevice>()) {
+            render_app
+                .init_resource::<ParticleSimulationPipeline>()
+                .add_systems(
+                    Render,
+                    prepare_particle_simulation_bind_groups.in_set(RenderSet::PrepareBindGroups),
+                );
+        }
+    }
+}
+
+/// A component bundle for the emitters of [`ParticleEffect`]s.
+#[derive(Bundle, Clone, Default)]
+pub struct ParticleEffectBundle {
+    /// The effect spawned by the emitter.
+    pub effect: Handle<ParticleEffect>,
+    /// The transform of the emitter, which particles spawn relative to.
+    pub transform: Transform,
+    /// The global transform of the emitter.
+    pub global_transform: GlobalTransform,
+    /// User indication of whether an entity is visible
+    pub visibility: Visibility,
+    /// Inherited visibility of an entity.
+    pub inherited_visibility: InheritedVisibility,
+    /// Algorithmically-computed indication of whether an entity is visible and should be extracted for rendering
+    pub view_visibility: ViewVisibility,
+}
@@ -0,0 +1,58 @@
+#define_import_path bevy_particles::particles
+
+// The number of samples of the curves, 4 per vector.
+const CURVE_SAMPLES: u32 = 16u;
+
+// The shapes particles spawn in.
+const SHAPE_SPHERE: u32 = 1u;
+const SHAPE_CUBOID: u32 = 2u;
+const SHAPE_CONE: u32 = 3u;
+
+struct Particle {
+    position: vec3<f32>,
+    age: f32,
+    velocity: vec3<f32>,
+    lifetime: f32,
+}
+
+struct ParticleEffect {
+    emitter: mat4x4<f32>,
+    acceleration: vec3<f32>,
+    drag: f32,
+    shape_size: vec3<f32>,
+    shape: u32,
+    lifetime: vec2<f32>,
+    speed: vec2<f32>,
+    speed_over_life: array<vec4<f32>, 4>,
+    size_over_life: array<vec4<f32>, 4>,
+    color_over_life: array<vec4<f32>, 16>,
+    delta_time: f32,
+    seed: u32,
+    spawn_start: u32,
+    spawn_count: u32,
+    capacity: u32,
+}
+
+// The two samples of a curve surrounding a time, and how much of the second to blend in.
+struct CurveSamples {
+    first: u32,
+    second: u32,
+    blend: f32,
+}
+
+fn curve_samples(time: f32) -> CurveSamples {
+    let x = saturate(time) * f32(CURVE_SAMPLES - 1u);
+    let first = min(u32(x), CURVE_SAMPLES - 2u);
+    return CurveSamples(first, first + 1u, x - f32(first));
+}
+
+fn is_alive(particle: Particle) -> bool {
+    return particle.age < particle.lifetime;
+}
+
+// The PCG hash.
+fn hash(value: u32) -> u32 {
+    let state = value * 747796405u + 2891336453u;
+    let word = ((state >> ((state >> 28u) + 4u)) ^ state) * 277803737u;
+    return (word >> 22u) ^ word;
+}
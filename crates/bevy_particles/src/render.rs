@@ -0,0 +1,359 @@
+use bevy_core_pipeline::core_3d::{Transparent3d, CORE_3D_DEPTH_FORMAT};
+use bevy_ecs::{
+    prelude::*,
+    system::{lifetimeless::*, SystemParamItem},
+};
+use bevy_render::{
+    mesh::{GpuBufferInfo, Mesh},
+    render_asset::RenderAssets,
+    render_phase::{
+        DrawFunctions, PhaseItem, RenderCommand, RenderCommandResult, RenderPhase, SetItemPipeline,
+        TrackedRenderPass,
+    },
+    render_resource::{binding_types::uniform_buffer, *},
+    renderer::RenderDevice,
+    texture::BevyDefault,
+    view::{
+        ExtractedView, Msaa, ViewTarget, ViewUniform, ViewUniformOffset, ViewUniforms,
+        VisibleEntities,
+    },
+};
+use bevy_utils::tracing::warn;
+
+use crate::{
+    effect::{ParticleBlendMode, ParticleEffectUniform},
+    simulation::ParticleEmitters,
+    RENDER_SHADER_HANDLE,
+};
+
+/// The number of vertices of a billboard, drawn as two triangles.
+const BILLBOARD_VERTEX_COUNT: u32 = 6;
+
+#[derive(Resource)]
+pub(crate) struct ParticleRenderPipeline {
+    view_layout: BindGroupLayout,
+    effect_layout: BindGroupLayout,
+}
+
+impl FromWorld for ParticleRenderPipeline {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+
+        let view_layout = render_device.create_bind_group_layout(
+            "particle_view_layout",
+            &BindGroupLayoutEntries::single(
+                ShaderStages::VERTEX_FRAGMENT,
+                uniform_buffer::<ViewUniform>(true),
+            ),
+        );
+        let effect_layout = render_device.create_bind_group_layout(
+            "particle_effect_layout",
+            &BindGroupLayoutEntries::single(
+                ShaderStages::VERTEX_FRAGMENT,
+                uniform_buffer::<ParticleEffectUniform>(false),
+            ),
+        );
+
+        Self {
+            view_layout,
+            effect_layout,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub(crate) struct ParticlePipelineKey {
+    hdr: bool,
+    msaa_samples: u32,
+    blend_mode: ParticleBlendMode,
+    /// The layout and topology of the mesh drawn for each particle, if any.
+    mesh: Option<(VertexBufferLayout, PrimitiveTopology)>,
+}
+
+impl SpecializedRenderPipeline for ParticleRenderPipeline {
+    type Key = ParticlePipelineKey;
+
+    fn specialize(&self, key: Self::Key) -> RenderPipelineDescriptor {
+        let mut shader_defs = Vec::new();
+        let mut buffers = Vec::new();
+        let mut primitive = PrimitiveState::default();
+        if let Some((mesh_layout, topology)) = key.mesh {
+            shader_defs.push("MESH".into());
+            buffers.push(mesh_layout);
+            primitive.topology = topology;
+            primitive.cull_mode = Some(Face::Back);
+        }
+        buffers.push(VertexBufferLayout::from_vertex_formats(
+            VertexStepMode::Instance,
+            [VertexFormat::Float32x4, VertexFormat::Float32x4],
+        ));
+        // The mesh, if any, uses the first location.
+        for attribute in &mut buffers.last_mut().unwrap().attributes {
+            attribute.shader_location += 1;
+        }
+
+        let blend = match key.blend_mode {
+            ParticleBlendMode::Blend => BlendState::ALPHA_BLENDING,
+            ParticleBlendMode::Add => BlendState {
+                color: BlendComponent {
+                    src_factor: BlendFactor::SrcAlpha,
+                    dst_factor: BlendFactor::One,
+                    operation: BlendOperation::Add,
+                },
+                alpha: BlendComponent {
+                    src_factor: BlendFactor::Zero,
+                    dst_factor: BlendFactor::One,
+                    operation: BlendOperation::Add,
+                },
+            },
+        };
+
+        RenderPipelineDescriptor {
+            label: Some("particle_render_pipeline".into()),
+            layout: vec![self.view_layout.clone(), self.effect_layout.clone()],
+            push_constant_ranges: vec![],
+            vertex: VertexState {
+                shader: RENDER_SHADER_HANDLE,
+                shader_defs: shader_defs.clone(),
+                entry_point: "vertex".into(),
+                buffers,
+            },
+            fragment: Some(FragmentState {
+                shader: RENDER_SHADER_HANDLE,
+                shader_defs,
+                entry_point: "fragment".into(),
+                targets: vec![Some(ColorTargetState {
+                    format: if key.hdr {
+                        ViewTarget::TEXTURE_FORMAT_HDR
+                    } else {
+                        TextureFormat::bevy_default()
+                    },
+                    blend: Some(blend),
+                    write_mask: ColorWrites::ALL,
+                })],
+            }),
+            primitive,
+            // Particles are tested against the depth of the opaque meshes, without hiding each
+            // other.
+            depth_stencil: Some(DepthStencilState {
+                format: CORE_3D_DEPTH_FORMAT,
+                depth_write_enabled: false,
+                depth_compare: CompareFunction::GreaterEqual,
+                stencil: StencilState::default(),
+                bias: DepthBiasState::default(),
+            }),
+            multisample: MultisampleState {
+                count: key.msaa_samples,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+        }
+    }
+}
+
+/// Adds the visible emitters to the transparent phase of the views, sorted by the distance of
+/// their emitter. The particles of an emitter are not sorted.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn queue_particles(
+    draw_functions: Res<DrawFunctions<Transparent3d>>,
+    pipeline: Res<ParticleRenderPipeline>,
+    mut pipelines: ResMut<SpecializedRenderPipelines<ParticleRenderPipeline>>,
+    pipeline_cache: Res<PipelineCache>,
+    msaa: Res<Msaa>,
+    emitters: Res<ParticleEmitters>,
+    meshes: Res<RenderAssets<Mesh>>,
+    mut views: Query<(
+        &ExtractedView,
+        &VisibleEntities,
+        &mut RenderPhase<Transparent3d>,
+    )>,
+) {
+    if emitters.0.is_empty() {
+        return;
+    }
+
+    let draw_function = draw_functions.read().id::<DrawParticles>();
+    for (view, visible_entities, mut transparent_phase) in &mut views {
+        let rangefinder = view.rangefinder3d();
+        for entity in visible_entities.iter() {
+            let Some(emitter) = emitters.0.get(entity) else {
+                continue;
+            };
+
+            let mesh = match emitter.mesh {
+                None => None,
+                Some(mesh_id) => {
+                    let Some(mesh) = meshes.get(mesh_id) else {
+                        continue;
+                    };
+                    let layout = match mesh
+                        .layout
+                        .get_layout(&[Mesh::ATTRIBUTE_POSITION.at_shader_location(0)])
+                    {
+                        Ok(layout) => layout,
+                        Err(err) => {
+                            warn!("Cannot draw particles as mesh {mesh_id:?}: {err}");
+                            continue;
+                        }
+                    };
+                    Some((layout, mesh.primitive_topology))
+                }
+            };
+
+            let pipeline = pipelines.specialize(
+                &pipeline_cache,
+                &pipeline,
+                ParticlePipelineKey {
+                    hdr: view.hdr,
+                    msaa_samples: msaa.samples(),
+                    blend_mode: emitter.blend_mode,
+                    mesh,
+                },
+            );
+
+            transparent_phase.add(Transparent3d {
+                entity: *entity,
+                draw_function,
+                pipeline,
+                distance: rangefinder.distance_translation(&emitter.translation),
+                batch_range: 0..1,
+                dynamic_offset: None,
+            });
+        }
+    }
+}
+
+/// The bind group of the view uniforms of all views, bound at their offset.
+#[derive(Resource, Default)]
+pub(crate) struct ParticleViewBindGroup(Option<BindGroup>);
+
+pub(crate) fn prepare_particle_bind_groups(
+    mut view_bind_group: ResMut<ParticleViewBindGroup>,
+    mut emitters: ResMut<ParticleEmitters>,
+    pipeline: Res<ParticleRenderPipeline>,
+    view_uniforms: Res<ViewUniforms>,
+    render_device: Res<RenderDevice>,
+) {
+    view_bind_group.0 = view_uniforms.uniforms.binding().map(|view_uniforms| {
+        render_device.create_bind_group(
+            "particle_view_bind_group",
+            &pipeline.view_layout,
+            &BindGroupEntries::single(view_uniforms),
+        )
+    });
+
+    for emitter in emitters.0.values_mut() {
+        emitter.render_bind_group = emitter.uniform.binding().map(|uniform| {
+            render_device.create_bind_group(
+                "particle_effect_bind_group",
+                &pipeline.effect_layout,
+                &BindGroupEntries::single(uniform),
+            )
+        });
+    }
+}
+
+pub(crate) type DrawParticles = (
+    SetItemPipeline,
+    SetParticleViewBindGroup<0>,
+    SetParticleEffectBindGroup<1>,
+    DrawParticleInstances,
+);
+
+pub(crate) struct SetParticleViewBindGroup<const I: usize>;
+
+impl<P: PhaseItem, const I: usize> RenderCommand<P> for SetParticleViewBindGroup<I> {
+    type Param = SRes<ParticleViewBindGroup>;
+    type ViewQuery = Read<ViewUniformOffset>;
+    type ItemQuery = ();
+
+    fn render<'w>(
+        _item: &P,
+        view_uniform: &'w ViewUniformOffset,
+        _entity: Option<()>,
+        bind_group: SystemParamItem<'w, '_, Self::Param>,
+        pass: &mut TrackedRenderPass<'w>,
+    ) -> RenderCommandResult {
+        let Some(bind_group) = &bind_group.into_inner().0 else {
+            return RenderCommandResult::Failure;
+        };
+        pass.set_bind_group(I, bind_group, &[view_uniform.offset]);
+        RenderCommandResult::Success
+    }
+}
+
+pub(crate) struct SetParticleEffectBindGroup<const I: usize>;
+
+impl<P: PhaseItem, const I: usize> RenderCommand<P> for SetParticleEffectBindGroup<I> {
+    type Param = SRes<ParticleEmitters>;
+    type ViewQuery = ();
+    type ItemQuery = ();
+
+    fn render<'w>(
+        item: &P,
+        _view: (),
+        _entity: Option<()>,
+        emitters: SystemParamItem<'w, '_, Self::Param>,
+        pass: &mut TrackedRenderPass<'w>,
+    ) -> RenderCommandResult {
+        let Some(bind_group) = emitters
+            .into_inner()
+            .0
+            .get(&item.entity())
+            .and_then(|emitter| emitter.render_bind_group.as_ref())
+        else {
+            return RenderCommandResult::Failure;
+        };
+        pass.set_bind_group(I, bind_group, &[]);
+        RenderCommandResult::Success
+    }
+}
+
+/// Draws a billboard or a mesh for each particle of an emitter. The dead particles are moved out
+/// of the view by the vertex shader.
+pub(crate) struct DrawParticleInstances;
+
+impl<P: PhaseItem> RenderCommand<P> for DrawParticleInstances {
+    type Param = (SRes<ParticleEmitters>, SRes<RenderAssets<Mesh>>);
+    type ViewQuery = ();
+    type ItemQuery = ();
+
+    fn render<'w>(
+        item: &P,
+        _view: (),
+        _entity: Option<()>,
+        (emitters, meshes): SystemParamItem<'w, '_, Self::Param>,
+        pass: &mut TrackedRenderPass<'w>,
+    ) -> RenderCommandResult {
+        let Some(emitter) = emitters.into_inner().0.get(&item.entity()) else {
+            return RenderCommandResult::Failure;
+        };
+        let instances = 0..emitter.capacity;
+
+        let Some(mesh_id) = emitter.mesh else {
+            pass.set_vertex_buffer(0, emitter.particles.slice(..));
+            pass.draw(0..BILLBOARD_VERTEX_COUNT, instances);
+            return RenderCommandResult::Success;
+        };
+
+        let Some(mesh) = meshes.into_inner().get(mesh_id) else {
+            return RenderCommandResult::Failure;
+        };
+        pass.set_vertex_buffer(0, mesh.vertex_buffer.slice(..));
+        pass.set_vertex_buffer(1, emitter.particles.slice(..));
+        match &mesh.buffer_info {
+            GpuBufferInfo::Indexed {
+                buffer,
+                index_format,
+                count,
+            } => {
+                pass.set_index_buffer(buffer.slice(..), 0, *index_format);
+                pass.draw_indexed(0..*count, 0, instances);
+            }
+            GpuBufferInfo::NonIndexed => {
+                pass.draw(0..mesh.vertex_count, instances);
+            }
+        }
+        RenderCommandResult::Success
+    }
+}
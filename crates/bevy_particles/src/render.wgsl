@@ -0,0 +1,101 @@
+// Draws the particles of an emitter as billboards, or as instances of a mesh.
+
+#import bevy_render::view::View
+#import bevy_particles::particles::{ParticleEffect, Particle, curve_samples, is_alive}
+
+@group(0) @binding(0) var<uniform> view: View;
+@group(1) @binding(0) var<uniform> effect: ParticleEffect;
+
+struct Vertex {
+#ifdef MESH
+    @location(0) position: vec3<f32>,
+#else
+    @builtin(vertex_index) index: u32,
+#endif
+    @location(1) position_age: vec4<f32>,
+    @location(2) velocity_lifetime: vec4<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+    // The position on the billboard, from -1.0 to 1.0.
+    @location(1) uv: vec2<f32>,
+}
+
+fn size_over_life(time: f32) -> f32 {
+    let samples = curve_samples(time);
+    let first = effect.size_over_life[samples.first / 4u][samples.first % 4u];
+    let second = effect.size_over_life[samples.second / 4u][samples.second % 4u];
+    return mix(first, second, samples.blend);
+}
+
+fn color_over_life(time: f32) -> vec4<f32> {
+    let samples = curve_samples(time);
+    let first = effect.color_over_life[samples.first];
+    let second = effect.color_over_life[samples.second];
+    return mix(first, second, samples.blend);
+}
+
+// A rotation turning the Y axis towards a direction.
+fn rotation_towards(direction: vec3<f32>) -> mat3x3<f32> {
+    if all(direction == vec3(0.0)) {
+        return mat3x3(vec3(1.0, 0.0, 0.0), vec3(0.0, 1.0, 0.0), vec3(0.0, 0.0, 1.0));
+    }
+    let y = normalize(direction);
+    let reference = select(vec3(0.0, 1.0, 0.0), vec3(1.0, 0.0, 0.0), abs(y.y) > 0.99);
+    let x = normalize(cross(reference, y));
+    return mat3x3(x, y, cross(x, y));
+}
+
+@vertex
+fn vertex(vertex: Vertex) -> VertexOutput {
+    var particle: Particle;
+    particle.position = vertex.position_age.xyz;
+    particle.age = vertex.position_age.w;
+    particle.velocity = vertex.velocity_lifetime.xyz;
+    particle.lifetime = vertex.velocity_lifetime.w;
+
+    var out: VertexOutput;
+    // Dead particles are moved out of the view, so that their triangles are clipped.
+    if !is_alive(particle) {
+        out.position = vec4(2.0, 2.0, 2.0, 1.0);
+        return out;
+    }
+
+    let time = particle.age / particle.lifetime;
+    let size = size_over_life(time);
+    out.color = color_over_life(time);
+
+#ifdef MESH
+    let offset = rotation_towards(particle.velocity) * vertex.position * size;
+    out.uv = vec2(0.0);
+#else
+    // The corners of the two triangles of the billboard.
+    var corners = array(
+        vec2(-1.0, -1.0),
+        vec2(1.0, -1.0),
+        vec2(1.0, 1.0),
+        vec2(-1.0, -1.0),
+        vec2(1.0, 1.0),
+        vec2(-1.0, 1.0),
+    );
+    let corner = corners[vertex.index];
+    // The columns of the view matrix are the axes of the camera in world space.
+    let offset = (view.view[0].xyz * corner.x + view.view[1].xyz * corner.y) * size * 0.5;
+    out.uv = corner;
+#endif
+
+    out.position = view.view_proj * vec4(particle.position + offset, 1.0);
+    return out;
+}
+
+@fragment
+fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    var color = in.color;
+#ifndef MESH
+    // Billboards are round, and fade towards their edge.
+    color.a *= 1.0 - smoothstep(0.5, 1.0, length(in.uv));
+#endif
+    return color;
+}
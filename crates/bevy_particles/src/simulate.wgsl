@@ -0,0 +1,99 @@
+// Spawns and moves the particles of an emitter. The CPU simulation of `simulation.rs` must match.
+
+#import bevy_particles::particles::{
+    ParticleEffect, Particle, curve_samples, hash, is_alive, SHAPE_SPHERE, SHAPE_CUBOID,
+    SHAPE_CONE,
+}
+
+const TAU: f32 = 6.28318530718;
+
+@group(0) @binding(0) var<uniform> effect: ParticleEffect;
+@group(0) @binding(1) var<storage, read_write> particles: array<Particle>;
+
+var<private> rng_state: u32;
+
+// A random number from 0.0 to 1.0.
+fn random() -> f32 {
+    rng_state = hash(rng_state);
+    return f32(rng_state >> 8u) / 16777216.0;
+}
+
+fn random_direction() -> vec3<f32> {
+    let z = random() * 2.0 - 1.0;
+    let phi = random() * TAU;
+    let r = sqrt(max(1.0 - z * z, 0.0));
+    return vec3(r * cos(phi), r * sin(phi), z);
+}
+
+fn speed_over_life(time: f32) -> f32 {
+    let samples = curve_samples(time);
+    let first = effect.speed_over_life[samples.first / 4u][samples.first % 4u];
+    let second = effect.speed_over_life[samples.second / 4u][samples.second % 4u];
+    return mix(first, second, samples.blend);
+}
+
+fn spawn_particle(index: u32) -> Particle {
+    rng_state = index ^ hash(effect.seed);
+    let lifetime = mix(effect.lifetime.x, effect.lifetime.y, random());
+    let speed = mix(effect.speed.x, effect.speed.y, random());
+
+    let size = effect.shape_size;
+    var position = vec3(0.0);
+    var direction = vec3(0.0, 1.0, 0.0);
+    if effect.shape == SHAPE_SPHERE {
+        direction = random_direction();
+        position = direction * size.x * pow(random(), 1.0 / 3.0);
+    } else if effect.shape == SHAPE_CUBOID {
+        let x = random();
+        let y = random();
+        let z = random();
+        position = (vec3(x, y, z) * 2.0 - 1.0) * size;
+    } else if effect.shape == SHAPE_CONE {
+        let radius = size.x * sqrt(random());
+        let phi = random() * TAU;
+        let cos_theta = mix(1.0, cos(size.y), random());
+        let sin_theta = sqrt(max(1.0 - cos_theta * cos_theta, 0.0));
+        let direction_phi = random() * TAU;
+        position = vec3(radius * cos(phi), 0.0, radius * sin(phi));
+        direction = vec3(sin_theta * cos(direction_phi), cos_theta, sin_theta * sin(direction_phi));
+    } else {
+        direction = random_direction();
+    }
+
+    let world_direction = (effect.emitter * vec4(direction, 0.0)).xyz;
+    let is_zero = all(world_direction == vec3(0.0));
+    var particle: Particle;
+    particle.position = (effect.emitter * vec4(position, 1.0)).xyz;
+    particle.age = 0.0;
+    particle.velocity = select(normalize(world_direction), vec3(0.0), is_zero) * speed;
+    particle.lifetime = lifetime;
+    return particle;
+}
+
+@compute @workgroup_size(64, 1, 1)
+fn simulate(@builtin(global_invocation_id) id: vec3<u32>) {
+    let index = id.x;
+    if index >= effect.capacity {
+        return;
+    }
+
+    // Particles spawn in a ring, replacing the oldest ones.
+    let spawn_offset = (index + effect.capacity - effect.spawn_start) % effect.capacity;
+    if spawn_offset < effect.spawn_count {
+        particles[index] = spawn_particle(index);
+        return;
+    }
+
+    var particle = particles[index];
+    if !is_alive(particle) {
+        return;
+    }
+
+    let delta_time = effect.delta_time;
+    let speed_factor = speed_over_life(particle.age / particle.lifetime);
+    particle.velocity += effect.acceleration * delta_time;
+    particle.velocity /= 1.0 + effect.drag * delta_time;
+    particle.position += particle.velocity * speed_factor * delta_time;
+    particle.age += delta_time;
+    particles[index] = particle;
+}
@@ -0,0 +1,466 @@
+use std::f32::consts::TAU;
+
+use bevy_asset::{AssetId, Assets, Handle};
+use bevy_ecs::{entity::EntityHashMap, prelude::*};
+use bevy_math::{Vec3, Vec4};
+use bevy_render::{
+    mesh::Mesh,
+    render_graph::{Node, NodeRunError, RenderGraphContext, RenderLabel},
+    render_resource::{binding_types::*, *},
+    renderer::{RenderContext, RenderDevice, RenderQueue},
+    view::InheritedVisibility,
+    Extract,
+};
+use bevy_time::Time;
+use bevy_transform::components::GlobalTransform;
+use bytemuck::{Pod, Zeroable};
+
+use crate::{
+    effect::{
+        ParticleBlendMode, ParticleEffect, ParticleEffectUniform, ParticleRenderMode, CURVE_SAMPLES,
+    },
+    SIMULATE_SHADER_HANDLE,
+};
+
+/// The size of the workgroups of `simulate.wgsl`.
+const WORKGROUP_SIZE: u32 = 64;
+
+/// A particle, as stored in the buffers of the emitters, which must match `particles.wgsl`.
+///
+/// Particles whose age reached their lifetime are dead, and are not drawn.
+#[derive(Clone, Copy, Default, Pod, Zeroable)]
+#[repr(C)]
+pub(crate) struct Particle {
+    position: Vec3,
+    age: f32,
+    velocity: Vec3,
+    lifetime: f32,
+}
+
+/// Whether the particles can be simulated with compute shaders. Otherwise, they are simulated on
+/// the CPU and copied to the GPU each frame.
+pub fn particle_simulation_is_supported(render_device: &RenderDevice) -> bool {
+    let limits = render_device.limits();
+    limits.max_storage_buffers_per_shader_stage >= 1
+        && limits.max_compute_workgroup_size_x >= WORKGROUP_SIZE
+}
+
+/// The emitters of the frame, with the effect they spawn.
+#[derive(Resource, Default)]
+pub(crate) struct ExtractedParticleEmitters(EntityHashMap<ExtractedParticleEmitter>);
+
+struct ExtractedParticleEmitter {
+    uniform: ParticleEffectUniform,
+    spawn_rate: f32,
+    translation: Vec3,
+    blend_mode: ParticleBlendMode,
+    mesh: Option<AssetId<Mesh>>,
+}
+
+/// The particles of each emitter, kept from frame to frame.
+#[derive(Resource, Default)]
+pub(crate) struct ParticleEmitters(pub(crate) EntityHashMap<ParticleEmitter>);
+
+pub(crate) struct ParticleEmitter {
+    /// The particles, simulated in place and drawn as instances.
+    pub(crate) particles: Buffer,
+    pub(crate) uniform: UniformBuffer<ParticleEffectUniform>,
+    pub(crate) capacity: u32,
+    pub(crate) translation: Vec3,
+    pub(crate) blend_mode: ParticleBlendMode,
+    pub(crate) mesh: Option<AssetId<Mesh>>,
+    pub(crate) render_bind_group: Option<BindGroup>,
+    simulation_bind_group: Option<BindGroup>,
+    /// The fraction of a particle left to spawn from the previous frames.
+    spawn_accumulator: f32,
+    /// The index of the next particle to spawn, as the particles are spawned in a ring.
+    next_spawn: u32,
+    seed: u32,
+    /// The particles simulated on the CPU, when compute shaders are not supported.
+    cpu_particles: Vec<Particle>,
+}
+
+pub(crate) fn extract_particle_emitters(
+    mut extracted_emitters: ResMut<ExtractedParticleEmitters>,
+    effects: Extract<Res<Assets<ParticleEffect>>>,
+    emitters: Extract<
+        Query<(
+            Entity,
+            &Handle<ParticleEffect>,
+            &GlobalTransform,
+            &InheritedVisibility,
+        )>,
+    >,
+) {
+    extracted_emitters.0.clear();
+    for (entity, handle, transform, visibility) in &emitters {
+        if !visibility.get() {
+            continue;
+        }
+        let Some(effect) = effects.get(handle) else {
+            continue;
+        };
+        if effect.capacity == 0 {
+            continue;
+        }
+
+        extracted_emitters.0.insert(
+            entity,
+            ExtractedParticleEmitter {
+                uniform: ParticleEffectUniform::new(effect, transform.compute_matrix()),
+                spawn_rate: effect.spawn_rate,
+                translation: transform.translation(),
+                blend_mode: effect.blend_mode,
+                mesh: match &effect.render_mode {
+                    ParticleRenderMode::Billboard => None,
+                    ParticleRenderMode::Mesh(mesh) => Some(mesh.id()),
+                },
+            },
+        );
+    }
+}
+
+/// Spawns the particles of the frame, and simulates them on the CPU if compute shaders are not
+/// supported.
+pub(crate) fn prepare_particle_emitters(
+    extracted_emitters: Res<ExtractedParticleEmitters>,
+    mut emitters: ResMut<ParticleEmitters>,
+    simulation_pipeline: Option<Res<ParticleSimulationPipeline>>,
+    time: Res<Time>,
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+) {
+    let emitters = &mut emitters.0;
+    emitters.retain(|entity, _| extracted_emitters.0.contains_key(entity));
+
+    let delta_time = time.delta_seconds();
+    for (&entity, extracted_emitter) in &extracted_emitters.0 {
+        let capacity = extracted_emitter.uniform.capacity;
+        if !matches!(emitters.get(&entity), Some(emitter) if emitter.capacity == capacity) {
+            let mut usage = BufferUsages::VERTEX | BufferUsages::COPY_DST;
+            if simulation_pipeline.is_some() {
+                usage |= BufferUsages::STORAGE;
+            }
+            let cpu_particles = vec![Particle::default(); capacity as usize];
+            let particles = render_device.create_buffer_with_data(&BufferInitDescriptor {
+                label: Some("particles"),
+                contents: bytemuck::cast_slice(&cpu_particles),
+                usage,
+            });
+
+            emitters.insert(
+                entity,
+                ParticleEmitter {
+                    particles,
+                    uniform: UniformBuffer::default(),
+                    capacity,
+                    translation: Vec3::ZERO,
+                    blend_mode: ParticleBlendMode::default(),
+                    mesh: None,
+                    render_bind_group: None,
+                    simulation_bind_group: None,
+                    spawn_accumulator: 0.0,
+                    next_spawn: 0,
+                    seed: hash(entity.index()),
+                    cpu_particles: if simulation_pipeline.is_some() {
+                        Vec::new()
+                    } else {
+                        cpu_particles
+                    },
+                },
+            );
+        }
+        let emitter = emitters.get_mut(&entity).unwrap();
+        emitter.translation = extracted_emitter.translation;
+        emitter.blend_mode = extracted_emitter.blend_mode;
+        emitter.mesh = extracted_emitter.mesh;
+
+        emitter.spawn_accumulator += extracted_emitter.spawn_rate * delta_time;
+        let spawn_count = emitter.spawn_accumulator.floor();
+        emitter.spawn_accumulator -= spawn_count;
+        let spawn_count = (spawn_count as u32).min(capacity);
+
+        let uniform = ParticleEffectUniform {
+            delta_time,
+            seed: emitter.seed,
+            spawn_start: emitter.next_spawn,
+            spawn_count,
+            ..extracted_emitter.uniform.clone()
+        };
+        emitter.next_spawn = (emitter.next_spawn + spawn_count) % capacity;
+        emitter.seed = emitter.seed.wrapping_add(1);
+
+        if simulation_pipeline.is_none() {
+            for (index, particle) in emitter.cpu_particles.iter_mut().enumerate() {
+                simulate_particle(particle, index as u32, &uniform);
+            }
+            render_queue.write_buffer(
+                &emitter.particles,
+                0,
+                bytemuck::cast_slice(&emitter.cpu_particles),
+            );
+        }
+
+        emitter.uniform.set(uniform);
+        emitter.uniform.write_buffer(&render_device, &render_queue);
+    }
+}
+
+pub(crate) fn prepare_particle_simulation_bind_groups(
+    mut emitters: ResMut<ParticleEmitters>,
+    pipeline: Res<ParticleSimulationPipeline>,
+    render_device: Res<RenderDevice>,
+) {
+    for emitter in emitters.0.values_mut() {
+        let Some(uniform) = emitter.uniform.binding() else {
+            continue;
+        };
+        emitter.simulation_bind_group = Some(render_device.create_bind_group(
+            "particle_simulation_bind_group",
+            &pipeline.layout,
+            &BindGroupEntries::sequential((uniform, emitter.particles.as_entire_binding())),
+        ));
+    }
+}
+
+#[derive(Resource)]
+pub(crate) struct ParticleSimulationPipeline {
+    layout: BindGroupLayout,
+    pipeline_id: CachedComputePipelineId,
+}
+
+impl FromWorld for ParticleSimulationPipeline {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+
+        let layout = render_device.create_bind_group_layout(
+            "particle_simulation_bind_group_layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::COMPUTE,
+                (
+                    uniform_buffer::<ParticleEffectUniform>(false),
+                    storage_buffer_sized(false, None),
+                ),
+            ),
+        );
+
+        let pipeline_id =
+            world
+                .resource::<PipelineCache>()
+                .queue_compute_pipeline(ComputePipelineDescriptor {
+                    label: Some("particle_simulation_pipeline".into()),
+                    layout: vec![layout.clone()],
+                    push_constant_ranges: vec![],
+                    shader: SIMULATE_SHADER_HANDLE,
+                    shader_defs: vec![],
+                    entry_point: "simulate".into(),
+                });
+
+        Self {
+            layout,
+            pipeline_id,
+        }
+    }
+}
+
+/// The label of the node simulating the particles with compute shaders, which runs before the
+/// cameras are rendered.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct ParticleSimulationLabel;
+
+#[derive(Default)]
+pub(crate) struct ParticleSimulationNode;
+
+impl Node for ParticleSimulationNode {
+    fn run(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        let Some(pipeline) = world.get_resource::<ParticleSimulationPipeline>() else {
+            return Ok(());
+        };
+        let Some(compute_pipeline) = world
+            .resource::<PipelineCache>()
+            .get_compute_pipeline(pipeline.pipeline_id)
+        else {
+            return Ok(());
+        };
+
+        let emitters = world.resource::<ParticleEmitters>();
+        if emitters.0.is_empty() {
+            return Ok(());
+        }
+
+        let mut pass =
+            render_context
+                .command_encoder()
+                .begin_compute_pass(&ComputePassDescriptor {
+                    label: Some("particle_simulation"),
+                    timestamp_writes: None,
+                });
+        pass.set_pipeline(compute_pipeline);
+        for emitter in emitters.0.values() {
+            let Some(bind_group) = &emitter.simulation_bind_group else {
+                continue;
+            };
+            pass.set_bind_group(0, bind_group, &[]);
+            pass.dispatch_workgroups(emitter.capacity.div_ceil(WORKGROUP_SIZE), 1, 1);
+        }
+
+        Ok(())
+    }
+}
+
+/// The PCG hash, which must match `particles.wgsl`.
+fn hash(value: u32) -> u32 {
+    let state = value.wrapping_mul(747796405).wrapping_add(2891336453);
+    let word = ((state >> ((state >> 28) + 4)) ^ state).wrapping_mul(277803737);
+    (word >> 22) ^ word
+}
+
+/// The random numbers of a particle, which must match `particles.wgsl`.
+struct ParticleRng(u32);
+
+impl ParticleRng {
+    fn new(index: u32, seed: u32) -> Self {
+        Self(index ^ hash(seed))
+    }
+
+    /// A random number from 0.0 to 1.0.
+    fn next(&mut self) -> f32 {
+        self.0 = hash(self.0);
+        (self.0 >> 8) as f32 / 16777216.0
+    }
+
+    fn direction(&mut self) -> Vec3 {
+        let z = self.next() * 2.0 - 1.0;
+        let phi = self.next() * TAU;
+        let r = (1.0 - z * z).max(0.0).sqrt();
+        Vec3::new(r * phi.cos(), r * phi.sin(), z)
+    }
+}
+
+/// Samples a curve baked by [`ParticleEffectUniform::new`], which must match `particles.wgsl`.
+fn sample_baked_curve(samples: &[Vec4; CURVE_SAMPLES / 4], time: f32) -> f32 {
+    let x = time.clamp(0.0, 1.0) * (CURVE_SAMPLES - 1) as f32;
+    let first = (x as usize).min(CURVE_SAMPLES - 2);
+    let sample = |index: usize| samples[index / 4][index % 4];
+    let (start, end) = (sample(first), sample(first + 1));
+    start + (end - start) * (x - first as f32)
+}
+
+/// Spawns or moves a particle, which must match `simulate.wgsl`.
+fn simulate_particle(particle: &mut Particle, index: u32, effect: &ParticleEffectUniform) {
+    let spawn_offset = (index + effect.capacity - effect.spawn_start) % effect.capacity;
+    if spawn_offset < effect.spawn_count {
+        *particle = spawn_particle(index, effect);
+        return;
+    }
+
+    if particle.age >= particle.lifetime {
+        return;
+    }
+
+    let delta_time = effect.delta_time;
+    let speed_factor =
+        sample_baked_curve(&effect.speed_over_life, particle.age / particle.lifetime);
+    particle.velocity =
+        (particle.velocity + effect.acceleration * delta_time) / (1.0 + effect.drag * delta_time);
+    particle.position += particle.velocity * speed_factor * delta_time;
+    particle.age += delta_time;
+}
+
+fn spawn_particle(index: u32, effect: &ParticleEffectUniform) -> Particle {
+    let mut rng = ParticleRng::new(index, effect.seed);
+    let lifetime = effect.lifetime.x + (effect.lifetime.y - effect.lifetime.x) * rng.next();
+    let speed = effect.speed.x + (effect.speed.y - effect.speed.x) * rng.next();
+
+    let size = effect.shape_size;
+    let (position, direction) = match effect.shape {
+        // Sphere
+        1 => {
+            let direction = rng.direction();
+            (direction * size.x * rng.next().cbrt(), direction)
+        }
+        // Cuboid
+        2 => {
+            let x = rng.next();
+            let y = rng.next();
+            let z = rng.next();
+            ((Vec3::new(x, y, z) * 2.0 - 1.0) * size, Vec3::Y)
+        }
+        // Cone
+        3 => {
+            let radius = size.x * rng.next().sqrt();
+            let phi = rng.next() * TAU;
+            let cos_theta = 1.0 + (size.y.cos() - 1.0) * rng.next();
+            let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+            let direction_phi = rng.next() * TAU;
+            (
+                Vec3::new(radius * phi.cos(), 0.0, radius * phi.sin()),
+                Vec3::new(
+                    sin_theta * direction_phi.cos(),
+                    cos_theta,
+                    sin_theta * direction_phi.sin(),
+                ),
+            )
+        }
+        // Point
+        _ => (Vec3::ZERO, rng.direction()),
+    };
+
+    Particle {
+        position: effect.emitter.transform_point3(position),
+        age: 0.0,
+        velocity: effect
+            .emitter
+            .transform_vector3(direction)
+            .normalize_or_zero()
+            * speed,
+        lifetime,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{simulate_particle, Particle};
+    use crate::effect::{ParticleEffect, ParticleEffectUniform};
+    use bevy_math::{Mat4, Vec3};
+
+    #[test]
+    fn spawn_in_ring() {
+        let effect = ParticleEffect {
+            capacity: 4,
+            lifetime: 1.0..=1.0,
+            acceleration: Vec3::NEG_Y,
+            ..Default::default()
+        };
+        let mut particles = [Particle::default(); 4];
+        let simulate = |particles: &mut [Particle; 4], spawn_start, spawn_count| {
+            let uniform = ParticleEffectUniform {
+                delta_time: 0.5,
+                spawn_start,
+                spawn_count,
+                ..ParticleEffectUniform::new(&effect, Mat4::from_translation(Vec3::X))
+            };
+            for (index, particle) in particles.iter_mut().enumerate() {
+                simulate_particle(particle, index as u32, &uniform);
+            }
+        };
+
+        // Spawning wraps around the end of the particles.
+        simulate(&mut particles, 3, 2);
+        let alive = particles.map(|particle| particle.age < particle.lifetime);
+        assert_eq!(alive, [true, false, false, true]);
+        assert_eq!(particles[0].position, Vec3::X);
+
+        // Particles move, then die at the end of their lifetime.
+        simulate(&mut particles, 1, 0);
+        assert_eq!(particles[0].age, 0.5);
+        assert_ne!(particles[0].position, Vec3::X);
+        simulate(&mut particles, 1, 0);
+        assert!(particles[0].age >= particles[0].lifetime);
+    }
+}
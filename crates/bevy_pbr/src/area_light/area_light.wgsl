@@ -0,0 +1,346 @@
+#define_import_path bevy_pbr::area_light
+
+// Clustered area lights, shaded with linearly transformed cosines (LTCs).
+//
+// The GGX lobe of the surface is approximated by a cosine distribution transformed by a matrix `M`,
+// which comes from the LTC lookup table. The integral of the lobe over a light is the integral of
+// a cosine over the light transformed by `M⁻¹`. For a rectangle, it's the sum of the integrals
+// over its edges, from Eric Heitz, Jonathan Dupuy, Stephen Hill and David Neubelt, "Real-Time
+// Polygonal-Light Shading with Linearly Transformed Cosines". For a disk, which `M⁻¹` transforms
+// into an ellipse, it's the one of the equivalent sphere, from Eric Heitz and Stephen Hill,
+// "Real-Time Line- and Disk-Light Shading with Linearly Transformed Cosines". In both cases, the
+// clipping of the light to the horizon is approximated by the one of a sphere, which is tabulated.
+
+#import bevy_pbr::{
+    mesh_view_bindings as bindings,
+    mesh_view_types::{AREA_LIGHT_FLAGS_DISK_BIT, AREA_LIGHT_FLAGS_TWO_SIDED_BIT},
+    utils::PI,
+}
+
+#ifdef CLUSTERED_AREA_LIGHTS_ARE_USABLE
+
+// Must match `LTC_LUT_SIZE` in `area_light/mod.rs`.
+const LTC_LUT_SIZE: f32 = 64.0;
+const LTC_LUT_SCALE: f32 = (LTC_LUT_SIZE - 1.0) / LTC_LUT_SIZE;
+const LTC_LUT_BIAS: f32 = 0.5 / LTC_LUT_SIZE;
+
+// The integral of a cosine over the light, clipped to the upper hemisphere, given its integral
+// without clipping and the Z coordinate of its average direction.
+fn clip_to_horizon(form_factor: f32, z: f32) -> f32 {
+    let uv = vec2(z * 0.5 + 0.5, form_factor) * LTC_LUT_SCALE + LTC_LUT_BIAS;
+    return form_factor * textureSampleLevel(
+        bindings::area_light_ltc_lut,
+        bindings::area_light_sampler,
+        uv,
+        1,
+        0.0
+    ).w;
+}
+
+// The vector form factor of the arc of great circle between the unit vectors `v1` and `v2`, with
+// a rational fit of `θ / sin(θ)`.
+fn integrate_edge(v1: vec3<f32>, v2: vec3<f32>) -> vec3<f32> {
+    let x = dot(v1, v2);
+    let y = abs(x);
+    let a = 0.8543985 + (0.4965155 + 0.0145206 * y) * y;
+    let b = 3.4175940 + (4.1616724 + y) * y;
+    let v = a / b;
+    var theta_sintheta = v;
+    if x <= 0.0 {
+        theta_sintheta = 0.5 * inverseSqrt(max(1.0 - x * x, 1e-7)) - v;
+    }
+    return cross(v1, v2) * theta_sintheta;
+}
+
+// Returns the average direction of a rectangle light in the space of the cosine distribution in
+// `xyz`, and the integral of the cosine over the light in `w`.
+//
+// `center`, `x_axis` and `y_axis` are the center and the half axes of the light, relative to the
+// shaded point, and `m_inv` transforms them into the space of the cosine distribution.
+fn ltc_rectangle(
+    m_inv: mat3x3<f32>,
+    center: vec3<f32>,
+    x_axis: vec3<f32>,
+    y_axis: vec3<f32>,
+    two_sided: bool,
+) -> vec4<f32> {
+    let in_front = dot(center, cross(x_axis, y_axis)) > 0.0;
+    if !in_front && !two_sided {
+        return vec4(0.0);
+    }
+
+    let l0 = normalize(m_inv * (center - x_axis - y_axis));
+    let l1 = normalize(m_inv * (center - x_axis + y_axis));
+    let l2 = normalize(m_inv * (center + x_axis + y_axis));
+    let l3 = normalize(m_inv * (center + x_axis - y_axis));
+    var f = integrate_edge(l0, l1) + integrate_edge(l1, l2) + integrate_edge(l2, l3)
+        + integrate_edge(l3, l0);
+    if in_front {
+        f = -f;
+    }
+
+    let form_factor = length(f);
+    if form_factor <= 0.0 {
+        return vec4(0.0);
+    }
+    let direction = f / form_factor;
+    return vec4(direction, clip_to_horizon(form_factor, direction.z));
+}
+
+// Returns the roots of `x³ + c2 x² + c1 x + c0`, which must all be real, with the middle one in
+// `y`, after James F. Blinn, "How to Solve a Cubic Equation".
+fn solve_cubic(c0: f32, c1: f32, c2: f32) -> vec3<f32> {
+    let b = c2 / 3.0;
+    let c = c1 / 3.0;
+    let d = c0;
+    let delta = vec3(-b * b + c, -c * b + d, b * d - c * c);
+    let discriminant = max(4.0 * delta.x * delta.z - delta.y * delta.y, 0.0);
+
+    // The largest root, as a fraction.
+    let d_a = -2.0 * b * delta.x + delta.y;
+    let theta_a = atan2(sqrt(discriminant), -d_a) / 3.0;
+    let scale_a = 2.0 * sqrt(max(-delta.x, 0.0));
+    let x_1a = scale_a * cos(theta_a);
+    let x_3a = scale_a * cos(theta_a + 2.0 / 3.0 * PI);
+    let xl = select(x_3a, x_1a, x_1a + x_3a > 2.0 * b);
+    let xlc = vec2(xl - b, 1.0);
+
+    // The smallest root, as a fraction.
+    let d_d = -d * delta.y + 2.0 * c * delta.z;
+    let theta_d = atan2(d * sqrt(discriminant), -d_d) / 3.0;
+    let scale_d = 2.0 * sqrt(max(-delta.z, 0.0));
+    let x_1d = scale_d * cos(theta_d);
+    let x_3d = scale_d * cos(theta_d + 2.0 / 3.0 * PI);
+    let xs = select(x_3d, x_1d, x_1d + x_3d < 2.0 * c);
+    let xsc = vec2(-d, xs + c);
+
+    // The middle root, from the other two.
+    let e = xlc.y * xsc.y;
+    let f = -xlc.x * xsc.y - xlc.y * xsc.x;
+    let g = xlc.x * xsc.x;
+    let xmc = vec2(c * f - b * g, -b * f + c * e);
+
+    var roots = vec3(xsc.x / xsc.y, xmc.x / xmc.y, xlc.x / xlc.y);
+    if roots.x < roots.y && roots.x < roots.z {
+        roots = roots.yxz;
+    } else if roots.z < roots.x && roots.z < roots.y {
+        roots = roots.xzy;
+    }
+    return roots;
+}
+
+// Like `ltc_rectangle`, for a disk light, whose half axes are its radii along X and Y.
+fn ltc_disk(
+    m_inv: mat3x3<f32>,
+    center: vec3<f32>,
+    x_axis: vec3<f32>,
+    y_axis: vec3<f32>,
+    two_sided: bool,
+) -> vec4<f32> {
+    let c = m_inv * center;
+    var v1 = m_inv * x_axis;
+    var v2 = m_inv * y_axis;
+    if !two_sided && dot(c, cross(v1, v2)) <= 0.0 {
+        return vec4(0.0);
+    }
+
+    // Orthogonalize the axes of the ellipse, and scale them by the inverse of its radii.
+    var a: f32;
+    var b: f32;
+    let d11 = dot(v1, v1);
+    let d22 = dot(v2, v2);
+    let d12 = dot(v1, v2);
+    if abs(d12) / sqrt(d11 * d22) > 0.0001 {
+        let tr = d11 + d22;
+        let det = sqrt(max(d11 * d22 - d12 * d12, 0.0));
+        let u = 0.5 * sqrt(max(tr - 2.0 * det, 0.0));
+        let v = 0.5 * sqrt(tr + 2.0 * det);
+        let e_max = (u + v) * (u + v);
+        let e_min = (u - v) * (u - v);
+        var v1_: vec3<f32>;
+        var v2_: vec3<f32>;
+        if d11 > d22 {
+            v1_ = d12 * v1 + (e_max - d11) * v2;
+            v2_ = d12 * v1 + (e_min - d11) * v2;
+        } else {
+            v1_ = d12 * v2 + (e_max - d22) * v1;
+            v2_ = d12 * v2 + (e_min - d22) * v1;
+        }
+        a = 1.0 / e_max;
+        b = 1.0 / e_min;
+        v1 = normalize(v1_);
+        v2 = normalize(v2_);
+    } else {
+        a = 1.0 / d11;
+        b = 1.0 / d22;
+        v1 *= sqrt(a);
+        v2 *= sqrt(b);
+    }
+
+    var v3 = cross(v1, v2);
+    if dot(c, v3) < 0.0 {
+        v3 = -v3;
+    }
+    let l = dot(v3, c);
+    if l <= 0.0 {
+        return vec4(0.0);
+    }
+    let x0 = dot(v1, c) / l;
+    let y0 = dot(v2, c) / l;
+    a *= l * l;
+    b *= l * l;
+
+    // The eigenvalues of the matrix of the cone from the shaded point to the ellipse.
+    let c0 = a * b;
+    let c1 = a * b * (1.0 + x0 * x0 + y0 * y0) - a - b;
+    let c2 = 1.0 - a * (1.0 + x0 * x0) - b * (1.0 + y0 * y0);
+    let roots = solve_cubic(c0, c1, c2);
+    let e1 = roots.x;
+    let e2 = roots.y;
+    let e3 = roots.z;
+
+    // Keep the denominators away from zero, with their sign.
+    let a_e2 = select(-1.0, 1.0, a - e2 >= 0.0) * max(abs(a - e2), 1e-7 * a);
+    let b_e2 = select(-1.0, 1.0, b - e2 >= 0.0) * max(abs(b - e2), 1e-7 * b);
+    let direction = normalize(v1 * a * x0 / a_e2 + v2 * b * y0 / b_e2 + v3);
+
+    // The form factor of the sphere that subtends the same solid angle.
+    let l1 = sqrt(max(-e2 / e3, 0.0));
+    let l2 = sqrt(max(-e2 / e1, 0.0));
+    let form_factor = l1 * l2 * inverseSqrt((1.0 + l1 * l1) * (1.0 + l2 * l2));
+    return vec4(direction, clip_to_horizon(form_factor, direction.z));
+}
+
+// Samples the texture of a light where the average direction of the distribution hits it, at a
+// mip level that matches the spread of the distribution over the light.
+fn sample_area_light_texture(
+    m_inv: mat3x3<f32>,
+    center: vec3<f32>,
+    x_axis: vec3<f32>,
+    y_axis: vec3<f32>,
+    direction: vec3<f32>,
+    is_disk: bool,
+    texture_index: i32,
+) -> vec3<f32> {
+    let origin = m_inv * center;
+    let a = m_inv * x_axis;
+    let b = m_inv * y_axis;
+    let n = cross(a, b);
+    let n_dot_n = dot(n, n);
+
+    // Intersect the plane of the light, and find the coordinates along its half axes.
+    let n_dot_direction = dot(n, direction);
+    let t = dot(origin, n) / select(n_dot_direction, 1e-6, abs(n_dot_direction) < 1e-6);
+    let p = direction * t - origin;
+    let u = dot(cross(p, b), n) / n_dot_n;
+    let v = dot(cross(a, p), n) / n_dot_n;
+
+    // The texture is seen unmirrored from the side the light emits toward, along -`n`.
+    let uv = vec2(0.5 - u * 0.5, 0.5 - v * 0.5);
+
+    // The spread of the distribution grows with the distance to the light relative to its size.
+    let distance_squared = dot(origin, n) * dot(origin, n) / n_dot_n;
+    let area = select(4.0, PI, is_disk) * sqrt(n_dot_n);
+    let size = vec2<f32>(textureDimensions(bindings::area_light_textures[texture_index]));
+    let lod = 0.5 * log2(PI * distance_squared * size.x * size.y / max(area, 1e-12));
+
+    return textureSampleLevel(
+        bindings::area_light_textures[texture_index],
+        bindings::area_light_sampler,
+        uv,
+        max(lod, 0.0)
+    ).rgb;
+}
+
+// Returns the light that the area light at `light_index` in the buffer of area lights reflects
+// toward the view.
+fn area_light(
+    light_index: u32,
+    world_position: vec3<f32>,
+    perceptual_roughness: f32,
+    NdotV: f32,
+    N: vec3<f32>,
+    V: vec3<f32>,
+    F0: vec3<f32>,
+    f_ab: vec2<f32>,
+    diffuse_color: vec3<f32>,
+) -> vec3<f32> {
+    let light = &bindings::clustered_area_lights.data[light_index];
+    let center = (*light).position - world_position;
+    let x_axis = (*light).x_axis;
+    let y_axis = (*light).y_axis;
+
+    // The same window as the one of point lights, on the distance to the center of the light.
+    let factor = dot(center, center) * (*light).inverse_range_squared;
+    let smooth_factor = saturate(1.0 - factor * factor);
+    let attenuation = smooth_factor * smooth_factor;
+    if attenuation <= 0.0 {
+        return vec3(0.0);
+    }
+
+    // The frame of the LTC lookup table: the view vector lies in its XZ plane, and the normal is
+    // its Z axis. Any tangent works when the view is along the normal.
+    var t1 = V - N * dot(N, V);
+    if dot(t1, t1) < 1e-8 {
+        let up = select(vec3(1.0, 0.0, 0.0), vec3(0.0, 1.0, 0.0), abs(N.x) > 0.9);
+        t1 = up - N * dot(N, up);
+    }
+    t1 = normalize(t1);
+    let t2 = cross(N, t1);
+    let world_to_tangent = transpose(mat3x3(t1, t2, N));
+
+    let lut_uv = vec2(perceptual_roughness, sqrt(1.0 - NdotV)) * LTC_LUT_SCALE + LTC_LUT_BIAS;
+    let lut_0 = textureSampleLevel(
+        bindings::area_light_ltc_lut,
+        bindings::area_light_sampler,
+        lut_uv,
+        0,
+        0.0
+    );
+    let lut_1 = textureSampleLevel(
+        bindings::area_light_ltc_lut,
+        bindings::area_light_sampler,
+        lut_uv,
+        1,
+        0.0
+    );
+    let m_inv = mat3x3(
+        vec3(lut_0.x, 0.0, lut_0.y),
+        vec3(0.0, 1.0, 0.0),
+        vec3(lut_0.z, 0.0, lut_0.w)
+    ) * world_to_tangent;
+
+    let is_disk = ((*light).flags & AREA_LIGHT_FLAGS_DISK_BIT) != 0u;
+    let two_sided = ((*light).flags & AREA_LIGHT_FLAGS_TWO_SIDED_BIT) != 0u;
+    var specular: vec4<f32>;
+    var diffuse: vec4<f32>;
+    if is_disk {
+        specular = ltc_disk(m_inv, center, x_axis, y_axis, two_sided);
+        diffuse = ltc_disk(world_to_tangent, center, x_axis, y_axis, two_sided);
+    } else {
+        specular = ltc_rectangle(m_inv, center, x_axis, y_axis, two_sided);
+        diffuse = ltc_rectangle(world_to_tangent, center, x_axis, y_axis, two_sided);
+    }
+
+    var specular_radiance = (*light).radiance;
+    var diffuse_radiance = (*light).radiance;
+    let texture_index = (*light).texture_index;
+    if texture_index >= 0 {
+        specular_radiance *= sample_area_light_texture(
+            m_inv, center, x_axis, y_axis, specular.xyz, is_disk, texture_index
+        );
+        diffuse_radiance *= sample_area_light_texture(
+            world_to_tangent, center, x_axis, y_axis, diffuse.xyz, is_disk, texture_index
+        );
+    }
+
+    // The magnitude and the Fresnel term of the lobe, with the same multiple scattering
+    // compensation as punctual lights.
+    let f90 = saturate(dot(F0, vec3(50.0 * 0.33)));
+    let specular_color = (F0 * lut_1.x + (f90 - F0) * lut_1.y) * (1.0 + F0 * (1.0 / f_ab.x - 1.0));
+
+    return attenuation * (specular_radiance * specular_color * specular.w
+        + diffuse_radiance * diffuse_color * diffuse.w);
+}
+
+#endif  // CLUSTERED_AREA_LIGHTS_ARE_USABLE
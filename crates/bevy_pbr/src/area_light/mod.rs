@@ -0,0 +1,550 @@
+//! Area lights: rectangles and disks that emit light from their whole surface.
+//!
+//! An [`AreaLight`] lies on the XY plane of its entity, centered on its origin,
+//! and emits light along its −Z axis, or along both sides if it's
+//! [`AreaLight::two_sided`]. Unlike the highlights of point and spot lights,
+//! which are infinitely small, the highlights of area lights have the shape of
+//! the light on glossy surfaces.
+//!
+//! Area lights are shaded with *linearly transformed cosines* (LTCs), after Eric
+//! Heitz, Jonathan Dupuy, Stephen Hill and David Neubelt, "Real-Time
+//! Polygonal-Light Shading with Linearly Transformed Cosines", and Eric Heitz
+//! and Stephen Hill, "Real-Time Line- and Disk-Light Shading with Linearly
+//! Transformed Cosines". The GGX specular lobe of a surface is approximated by a
+//! cosine distribution transformed by a 3×3 matrix, which comes from a lookup
+//! table indexed by the roughness of the surface and the view angle. The
+//! integral of the lobe over the light is then the integral of a cosine over
+//! the light transformed by the inverse of that matrix, which has a closed form
+//! for rectangles and an accurate approximation for disks.
+//!
+//! Area lights are assigned to the clusters of each view after the point
+//! lights, spot lights and decals. Like [`crate::Decal`]s, they use binding
+//! arrays (also known as bindless textures) and storage buffers, and
+//! consequently aren't supported on WebGL2 or WebGPU, or if GLSL is in use. On
+//! those platforms, area lights are ignored. They don't cast shadows.
+
+use std::{num::NonZeroU32, ops::Deref};
+
+use bevy_app::{App, Plugin};
+use bevy_asset::{load_internal_asset, AssetId, Handle};
+use bevy_ecs::{
+    entity::{Entity, EntityHashMap},
+    prelude::*,
+};
+use bevy_math::{Vec3, Vec3A};
+use bevy_reflect::{std_traits::ReflectDefault, Reflect};
+use bevy_render::{
+    color::Color,
+    prelude::SpatialBundle,
+    render_asset::RenderAssets,
+    render_resource::{
+        binding_types, AddressMode, BindGroupLayoutEntryBuilder, BindingResource,
+        BufferBindingType, Extent3d, FilterMode, Sampler, SamplerBindingType, SamplerDescriptor,
+        Shader, ShaderType, StorageBuffer, TextureDataOrder, TextureDescriptor, TextureDimension,
+        TextureFormat, TextureSampleType, TextureUsages, TextureView, TextureViewDescriptor,
+        TextureViewDimension,
+    },
+    renderer::{RenderDevice, RenderQueue},
+    texture::{FallbackImage, Image},
+    view::ViewVisibility,
+    Extract, ExtractSchedule, Render, RenderApp, RenderSet,
+};
+use bevy_transform::prelude::GlobalTransform;
+use bevy_utils::{tracing::warn, HashMap};
+
+use crate::{
+    clustered_decals_are_usable, prepare_clusters, CLUSTERED_FORWARD_STORAGE_BUFFER_COUNT,
+    MAX_DECAL_TEXTURES, MAX_VIEW_LIGHT_PROBES,
+    STANDARD_MATERIAL_FRAGMENT_SHADER_MIN_TEXTURE_BINDINGS,
+};
+
+/// A handle to the area light shader.
+pub const AREA_LIGHT_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(4180617384729021563);
+
+/// The maximum number of different textures that the area lights of the scene
+/// can use.
+///
+/// This is the size of the binding array of area light textures.
+pub const MAX_AREA_LIGHT_TEXTURES: usize = 8;
+
+/// The size of each layer of the LTC lookup table, along both axes.
+const LTC_LUT_SIZE: u32 = 64;
+
+/// Adds support for [`AreaLight`]s.
+pub struct AreaLightPlugin;
+
+/// A light that emits from the whole surface of a rectangle or a disk.
+///
+/// The light lies on the XY plane of its entity, centered on its origin, and
+/// emits light along its −Z axis, or along both sides if
+/// [`AreaLight::two_sided`] is set. Its size is given by [`AreaLight::shape`],
+/// which the transform of the entity scales.
+///
+/// Area lights don't cast shadows, and aren't supported on WebGL2 or WebGPU.
+#[derive(Component, Clone, Debug, Reflect)]
+#[reflect(Component, Default)]
+pub struct AreaLight {
+    /// The color of the light.
+    ///
+    /// Defaults to [`Color::WHITE`].
+    pub color: Color,
+
+    /// Luminous power in lumens, emitted over the whole surface of the light,
+    /// and split between both sides if it's [`AreaLight::two_sided`].
+    ///
+    /// Defaults to 1,000,000 lumens, like [`crate::PointLight`].
+    pub intensity: f32,
+
+    /// The distance from the center of the light beyond which it has no
+    /// effect.
+    ///
+    /// Defaults to 20 meters.
+    pub range: f32,
+
+    /// The shape and the size of the light, before the transform of the entity
+    /// is applied.
+    pub shape: AreaLightShape,
+
+    /// Whether the light emits along both sides of its plane, rather than only
+    /// along −Z.
+    ///
+    /// Defaults to false.
+    pub two_sided: bool,
+
+    /// A texture that modulates the color of the light across its surface, like
+    /// a screen or a stained glass window.
+    ///
+    /// The texture is seen unmirrored from the side that the light emits
+    /// toward, with its top toward +Y. It's sampled at coarser mip levels on
+    /// rougher surfaces, so it should have a full chain of mipmaps.
+    pub texture: Option<Handle<Image>>,
+}
+
+/// The shape of an [`AreaLight`], on the XY plane of its entity.
+#[derive(Clone, Copy, Debug, Reflect)]
+pub enum AreaLightShape {
+    /// A rectangle, with its width along X and its height along Y.
+    Rectangle { width: f32, height: f32 },
+    /// A disk, which the transform of the entity can stretch into an ellipse.
+    Disk { radius: f32 },
+}
+
+impl Default for AreaLight {
+    fn default() -> Self {
+        Self {
+            color: Color::WHITE,
+            intensity: 1_000_000.0,
+            range: 20.0,
+            shape: AreaLightShape::default(),
+            two_sided: false,
+            texture: None,
+        }
+    }
+}
+
+impl Default for AreaLightShape {
+    fn default() -> Self {
+        Self::Rectangle {
+            width: 1.0,
+            height: 1.0,
+        }
+    }
+}
+
+/// A bundle that contains everything needed to add an [`AreaLight`].
+#[derive(Bundle, Default)]
+pub struct AreaLightBundle {
+    /// The area light.
+    pub area_light: AreaLight,
+    /// Contains the transform of the light.
+    pub spatial: SpatialBundle,
+}
+
+bitflags::bitflags! {
+    #[repr(transparent)]
+    struct AreaLightFlags: u32 {
+        const DISK                       = 1 << 0;
+        const TWO_SIDED                  = 1 << 1;
+        const NONE                       = 0;
+    }
+}
+
+/// An area light extracted to the render world.
+struct ExtractedAreaLight {
+    entity: Entity,
+    position: Vec3,
+    x_axis: Vec3,
+    y_axis: Vec3,
+    radiance: Vec3,
+    inverse_range_squared: f32,
+    flags: AreaLightFlags,
+    texture: Option<AssetId<Image>>,
+}
+
+/// A GPU type that stores information about an area light.
+#[derive(Clone, Copy, ShaderType, Default)]
+struct GpuAreaLight {
+    /// The center of the light, in world space.
+    position: Vec3,
+
+    inverse_range_squared: f32,
+
+    /// Half the width of the light along its local X axis, and half its height
+    /// along its local Y axis, in world space.
+    x_axis: Vec3,
+
+    /// A combination of [`AreaLightFlags`].
+    flags: u32,
+
+    y_axis: Vec3,
+
+    /// The index of the texture of the light in the binding array of area light
+    /// textures, or -1 if the light doesn't have a texture.
+    texture_index: i32,
+
+    /// The radiance that the light emits, in linear space.
+    radiance: Vec3,
+}
+
+/// The GPU buffer of all the area lights that the clusters of the views refer
+/// to.
+#[derive(ShaderType, Default)]
+struct GpuAreaLights {
+    #[size(runtime)]
+    data: Vec<GpuAreaLight>,
+}
+
+/// The area lights of the scene, ready to be bound to the PBR shaders.
+#[derive(Resource)]
+pub struct RenderAreaLights {
+    /// The area lights extracted from the main world this frame.
+    lights: Vec<ExtractedAreaLight>,
+
+    /// The index of each area light entity in the GPU buffer of area lights.
+    ///
+    /// The cluster index lists refer to area lights by these indices.
+    pub(crate) entity_to_index: EntityHashMap<usize>,
+
+    /// The textures of the area lights, in the order of the binding array.
+    binding_index_to_textures: Vec<AssetId<Image>>,
+
+    /// The reverse of `binding_index_to_textures`.
+    texture_to_binding_index: HashMap<AssetId<Image>, i32>,
+
+    buffer: StorageBuffer<GpuAreaLights>,
+
+    /// The LTC lookup table, with two layers.
+    ///
+    /// Its X axis is the perceptual roughness of the surface, and its Y axis
+    /// `sqrt(1 - N·V)`. The first layer stores the four non-trivial elements of
+    /// the inverse LTC matrix, and the second layer stores the magnitude and
+    /// the Fresnel term of the specular lobe in its red and green channels. The
+    /// alpha channel of the second layer is a separate table, of the integral
+    /// of a cosine over a sphere clipped to the horizon, which approximates the
+    /// horizon clipping of the lights.
+    ltc_lut: TextureView,
+
+    /// The sampler shared by the LTC lookup table and the area light textures.
+    sampler: Sampler,
+}
+
+/// All the bind group entries necessary for PBR shaders to access the area
+/// lights.
+pub(crate) struct RenderAreaLightsBindGroupEntries<'a> {
+    /// The GPU buffer of area lights.
+    pub(crate) area_lights: BindingResource<'a>,
+
+    /// A texture view of each area light texture, padded to
+    /// [`MAX_AREA_LIGHT_TEXTURES`] with fallback textures.
+    ///
+    /// This is a vector of `wgpu::TextureView`s. But we don't want to import
+    /// `wgpu` in this crate, so we refer to it indirectly like this.
+    pub(crate) texture_views: Vec<&'a <TextureView as Deref>::Target>,
+
+    /// The LTC lookup table.
+    pub(crate) ltc_lut: &'a TextureView,
+
+    /// The sampler used to sample the LTC lookup table and the area light
+    /// textures.
+    pub(crate) sampler: &'a Sampler,
+}
+
+impl Plugin for AreaLightPlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(
+            app,
+            AREA_LIGHT_SHADER_HANDLE,
+            "area_light.wgsl",
+            Shader::from_wgsl
+        );
+
+        app.register_type::<AreaLight>();
+    }
+
+    fn finish(&self, app: &mut App) {
+        let Ok(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        render_app
+            .init_resource::<RenderAreaLights>()
+            .add_systems(ExtractSchedule, extract_area_lights)
+            .add_systems(
+                Render,
+                prepare_area_lights
+                    .in_set(RenderSet::PrepareResources)
+                    .before(prepare_clusters),
+            );
+    }
+}
+
+impl FromWorld for RenderAreaLights {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+        let render_queue = world.resource::<RenderQueue>();
+
+        let mut buffer = StorageBuffer::default();
+        buffer.set_label(Some("area_lights_buffer"));
+
+        // The table was fitted offline for the GGX distribution, and is stored
+        // as little-endian half floats, layer after layer.
+        let ltc_lut = render_device
+            .create_texture_with_data(
+                render_queue,
+                &(TextureDescriptor {
+                    label: Some("area_light_ltc_lut"),
+                    size: Extent3d {
+                        width: LTC_LUT_SIZE,
+                        height: LTC_LUT_SIZE,
+                        depth_or_array_layers: 2,
+                    },
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: TextureDimension::D2,
+                    format: TextureFormat::Rgba16Float,
+                    usage: TextureUsages::TEXTURE_BINDING,
+                    view_formats: &[],
+                }),
+                TextureDataOrder::default(),
+                include_bytes!("ltc_lut.bin"),
+            )
+            .create_view(&TextureViewDescriptor {
+                label: Some("area_light_ltc_lut_view"),
+                dimension: Some(TextureViewDimension::D2Array),
+                ..TextureViewDescriptor::default()
+            });
+
+        Self {
+            lights: Vec::new(),
+            entity_to_index: EntityHashMap::default(),
+            binding_index_to_textures: Vec::new(),
+            texture_to_binding_index: HashMap::new(),
+            buffer,
+            ltc_lut,
+            sampler: render_device.create_sampler(&SamplerDescriptor {
+                label: Some("area_light_sampler"),
+                address_mode_u: AddressMode::ClampToEdge,
+                address_mode_v: AddressMode::ClampToEdge,
+                mag_filter: FilterMode::Linear,
+                min_filter: FilterMode::Linear,
+                mipmap_filter: FilterMode::Linear,
+                ..SamplerDescriptor::default()
+            }),
+        }
+    }
+}
+
+/// Many things can prevent area lights from being shaded. This function checks
+/// that:
+///
+/// 1. Clustered decals are usable, see [`clustered_decals_are_usable`], since
+///    area lights follow the decals in the clusters.
+///
+/// 2. Storage buffers are available for the area lights in addition to the
+///    decals and the clustered forward buffers.
+///
+/// 3. There are enough texture bindings available in the fragment shader for
+///    the binding array of area light textures and the LTC lookup table, on
+///    top of the light probes and the decals.
+pub(crate) fn clustered_area_lights_are_usable(render_device: &RenderDevice) -> bool {
+    clustered_decals_are_usable(render_device)
+        && matches!(
+            render_device
+                .get_supported_read_only_binding_type(CLUSTERED_FORWARD_STORAGE_BUFFER_COUNT + 2),
+            BufferBindingType::Storage { .. }
+        )
+        && render_device.limits().max_sampled_textures_per_shader_stage
+            >= (STANDARD_MATERIAL_FRAGMENT_SHADER_MIN_TEXTURE_BINDINGS
+                + MAX_VIEW_LIGHT_PROBES * 3
+                + MAX_DECAL_TEXTURES
+                + MAX_AREA_LIGHT_TEXTURES
+                + 1) as u32
+}
+
+/// Returns the bind group layout entries for the area light buffer, the
+/// binding array of area light textures, the LTC lookup table, and their
+/// sampler respectively.
+pub(crate) fn get_area_light_bind_group_layout_entries() -> [BindGroupLayoutEntryBuilder; 4] {
+    [
+        binding_types::storage_buffer_read_only::<GpuAreaLights>(false),
+        binding_types::texture_2d(TextureSampleType::Float { filterable: true })
+            .count(NonZeroU32::new(MAX_AREA_LIGHT_TEXTURES as _).unwrap()),
+        binding_types::texture_2d_array(TextureSampleType::Float { filterable: true }),
+        binding_types::sampler(SamplerBindingType::Filtering),
+    ]
+}
+
+/// Extracts the visible area lights of the scene.
+fn extract_area_lights(
+    mut render_area_lights: ResMut<RenderAreaLights>,
+    area_lights: Extract<Query<(Entity, &AreaLight, &GlobalTransform, &ViewVisibility)>>,
+) {
+    render_area_lights.lights.clear();
+    render_area_lights.lights.extend(
+        area_lights
+            .iter()
+            .filter(|(.., view_visibility)| view_visibility.get())
+            .map(|(entity, area_light, transform, _)| {
+                let affine = transform.affine();
+                let (half_width, half_height, flags, area_factor) = match area_light.shape {
+                    AreaLightShape::Rectangle { width, height } => {
+                        (width * 0.5, height * 0.5, AreaLightFlags::NONE, 4.0)
+                    }
+                    AreaLightShape::Disk { radius } => {
+                        (radius, radius, AreaLightFlags::DISK, std::f32::consts::PI)
+                    }
+                };
+                let x_axis = Vec3::from(affine.matrix3 * Vec3A::new(half_width, 0.0, 0.0));
+                let y_axis = Vec3::from(affine.matrix3 * Vec3A::new(0.0, half_height, 0.0));
+
+                // The light is a Lambertian emitter, whose radiance is its power
+                // divided by π and by the area of each side that emits.
+                let (flags, sides) = if area_light.two_sided {
+                    (flags | AreaLightFlags::TWO_SIDED, 2.0)
+                } else {
+                    (flags, 1.0)
+                };
+                let area = area_factor * x_axis.cross(y_axis).length();
+                let power = area_light.intensity / (std::f32::consts::PI * area * sides);
+                let color = Vec3::from_slice(&area_light.color.as_linear_rgba_f32());
+
+                ExtractedAreaLight {
+                    entity,
+                    position: transform.translation(),
+                    x_axis,
+                    y_axis,
+                    radiance: if area > 0.0 {
+                        color * power
+                    } else {
+                        Vec3::ZERO
+                    },
+                    inverse_range_squared: 1.0 / (area_light.range * area_light.range),
+                    flags,
+                    texture: area_light.texture.as_ref().map(Handle::id),
+                }
+            }),
+    );
+}
+
+/// Uploads the extracted area lights to the GPU, and collects their textures
+/// into the binding array.
+fn prepare_area_lights(
+    render_area_lights: ResMut<RenderAreaLights>,
+    images: Res<RenderAssets<Image>>,
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+    mut max_area_light_textures_warning_emitted: Local<bool>,
+) {
+    let RenderAreaLights {
+        lights,
+        entity_to_index,
+        binding_index_to_textures,
+        texture_to_binding_index,
+        buffer,
+        ..
+    } = render_area_lights.into_inner();
+
+    entity_to_index.clear();
+    binding_index_to_textures.clear();
+    texture_to_binding_index.clear();
+    let gpu_area_lights = &mut buffer.get_mut().data;
+    gpu_area_lights.clear();
+
+    for light in lights.iter() {
+        // Every extracted light gets an index, since the clusters may refer to
+        // it, even if it doesn't emit.
+        entity_to_index.insert(light.entity, gpu_area_lights.len());
+
+        let mut texture_index = -1;
+        let mut texture_ready = true;
+        if let Some(texture) = light.texture {
+            let next_binding_index = binding_index_to_textures.len();
+            if images.get(texture).is_none() {
+                texture_ready = false;
+            } else if next_binding_index >= MAX_AREA_LIGHT_TEXTURES
+                && !texture_to_binding_index.contains_key(&texture)
+            {
+                if !*max_area_light_textures_warning_emitted {
+                    warn!(
+                        "MAX_AREA_LIGHT_TEXTURES ({}) exceeded",
+                        MAX_AREA_LIGHT_TEXTURES
+                    );
+                    *max_area_light_textures_warning_emitted = true;
+                }
+                texture_ready = false;
+            } else {
+                texture_index = *texture_to_binding_index.entry(texture).or_insert_with(|| {
+                    binding_index_to_textures.push(texture);
+                    next_binding_index as i32
+                });
+            }
+        }
+
+        // A light whose texture isn't loaded yet doesn't emit.
+        gpu_area_lights.push(GpuAreaLight {
+            position: light.position,
+            inverse_range_squared: light.inverse_range_squared,
+            x_axis: light.x_axis,
+            flags: light.flags.bits(),
+            y_axis: light.y_axis,
+            texture_index,
+            radiance: if texture_ready {
+                light.radiance
+            } else {
+                Vec3::ZERO
+            },
+        });
+    }
+
+    buffer.write_buffer(&render_device, &render_queue);
+}
+
+impl RenderAreaLights {
+    /// Returns the bindings of the area light buffer, the binding array of area
+    /// light textures, the LTC lookup table, and their sampler.
+    pub(crate) fn bind_group_entries<'a>(
+        &'a self,
+        images: &'a RenderAssets<Image>,
+        fallback_image: &'a FallbackImage,
+    ) -> Option<RenderAreaLightsBindGroupEntries<'a>> {
+        let mut texture_views: Vec<_> = self
+            .binding_index_to_textures
+            .iter()
+            .map(|&texture| match images.get(texture) {
+                Some(image) => &*image.texture_view,
+                None => &*fallback_image.d2.texture_view,
+            })
+            .collect();
+
+        // Pad out the bindings to the size of the binding array using fallback
+        // textures. This is necessary on D3D12 and Metal.
+        texture_views.resize(MAX_AREA_LIGHT_TEXTURES, &*fallback_image.d2.texture_view);
+
+        Some(RenderAreaLightsBindGroupEntries {
+            area_lights: self.buffer.binding()?,
+            texture_views,
+            ltc_lut: &self.ltc_lut,
+            sampler: &self.sampler,
+        })
+    }
+}
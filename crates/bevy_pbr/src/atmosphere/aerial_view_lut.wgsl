@@ -0,0 +1,74 @@
+// Fills the aerial view lookup texture, a froxel volume aligned with the frustum of the view,
+// whose slices are spread evenly up to the aerial perspective distance. Each texel holds the light
+// scattered toward the camera between the camera and the end of its slice, before the exposure of
+// the view, and the mean transmittance over that distance.
+
+#import bevy_render::view::View
+#import bevy_pbr::atmosphere::{
+    types::{Atmosphere, AtmosphereLights},
+    functions::{
+        AERIAL_VIEW_LUT_SIZE, sample_medium, ray_length, camera_radius, inscattering,
+        integrate_step,
+    },
+}
+
+@group(0) @binding(0) var<uniform> atmosphere: Atmosphere;
+@group(0) @binding(1) var<uniform> lights: AtmosphereLights;
+@group(0) @binding(2) var<uniform> view: View;
+@group(0) @binding(3) var transmittance_lut: texture_2d<f32>;
+@group(0) @binding(4) var multiscattering_lut: texture_2d<f32>;
+@group(0) @binding(5) var lut_sampler: sampler;
+@group(0) @binding(6) var aerial_view_lut_out: texture_storage_3d<rgba16float, write>;
+
+@compute
+@workgroup_size(16, 16, 1)
+fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    if any(global_id.xy >= AERIAL_VIEW_LUT_SIZE.xy) {
+        return;
+    }
+
+    let uv = (vec2<f32>(global_id.xy) + 0.5) / vec2<f32>(AERIAL_VIEW_LUT_SIZE.xy);
+    let ndc = vec4(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 1.0, 1.0);
+    let world_near = view.inverse_view_proj * ndc;
+    let direction = normalize(world_near.xyz / world_near.w - view.world_position);
+
+    let r = camera_radius(atmosphere, view.world_position.y);
+    let position = vec3(0.0, r, 0.0);
+    let max_distance = ray_length(atmosphere, r, direction.y);
+    let slice_length = atmosphere.aerial_perspective_distance / f32(AERIAL_VIEW_LUT_SIZE.z);
+
+    var luminance = vec3(0.0);
+    var throughput = vec3(1.0);
+    for (var slice = 0u; slice < AERIAL_VIEW_LUT_SIZE.z; slice += 1u) {
+        // Past the end of the atmosphere, the slices hold the light scattered up to the end.
+        let t_start = f32(slice) * slice_length;
+        let step_length = clamp(max_distance - t_start, 0.0, slice_length);
+        if step_length > 0.0 {
+            let t = t_start + 0.5 * step_length;
+            let sample_position = position + direction * t;
+            let medium = sample_medium(atmosphere, length(sample_position));
+            let step_transmittance = exp(-medium.extinction * step_length);
+
+            let scattered = inscattering(
+                atmosphere,
+                lights,
+                transmittance_lut,
+                multiscattering_lut,
+                lut_sampler,
+                sample_position,
+                direction,
+                medium,
+            );
+            luminance += throughput
+                * integrate_step(scattered, medium.extinction, step_transmittance);
+            throughput *= step_transmittance;
+        }
+
+        let transmittance = dot(throughput, vec3(1.0 / 3.0));
+        textureStore(
+            aerial_view_lut_out,
+            vec3(global_id.xy, slice),
+            vec4(luminance, transmittance)
+        );
+    }
+}
@@ -0,0 +1,238 @@
+#define_import_path bevy_pbr::atmosphere::functions
+
+// The scattering of light in the atmosphere, after Sébastien Hillaire's "A Scalable and Production
+// Ready Sky and Atmosphere Rendering Technique".
+//
+// Positions are relative to the center of the planet, with +Y up at the camera. `r` is the
+// distance of a point to the center, and `mu` the cosine of the angle between a direction and the
+// vertical at that point.
+
+#import bevy_pbr::atmosphere::types::{Atmosphere, AtmosphereLights}
+
+const PI: f32 = 3.141592653589793;
+
+// The sizes of the lookup textures, which must match `atmosphere/mod.rs`.
+const TRANSMITTANCE_LUT_SIZE: vec2<u32> = vec2<u32>(256u, 128u);
+const MULTISCATTERING_LUT_SIZE: vec2<u32> = vec2<u32>(32u, 32u);
+const SKY_VIEW_LUT_SIZE: vec2<u32> = vec2<u32>(192u, 108u);
+const AERIAL_VIEW_LUT_SIZE: vec3<u32> = vec3<u32>(32u, 32u, 32u);
+
+// The scattering and extinction coefficients of the atmosphere at a point.
+struct Medium {
+    rayleigh_scattering: vec3<f32>,
+    mie_scattering: f32,
+    scattering: vec3<f32>,
+    extinction: vec3<f32>,
+}
+
+fn sample_medium(atmosphere: Atmosphere, r: f32) -> Medium {
+    let altitude = max(r - atmosphere.bottom_radius, 0.0);
+    let rayleigh_density = exp(-altitude * atmosphere.rayleigh_density_exp_scale);
+    let mie_density = exp(-altitude * atmosphere.mie_density_exp_scale);
+    let ozone_density = max(
+        0.0,
+        1.0 - abs(altitude - atmosphere.ozone_layer_altitude) / (atmosphere.ozone_layer_width * 0.5)
+    );
+
+    var medium: Medium;
+    medium.rayleigh_scattering = atmosphere.rayleigh_scattering * rayleigh_density;
+    medium.mie_scattering = atmosphere.mie_scattering * mie_density;
+    medium.scattering = medium.rayleigh_scattering + medium.mie_scattering;
+    medium.extinction = medium.scattering + atmosphere.mie_absorption * mie_density
+        + atmosphere.ozone_absorption * ozone_density;
+    return medium;
+}
+
+fn rayleigh_phase(cos_theta: f32) -> f32 {
+    return 3.0 / (16.0 * PI) * (1.0 + cos_theta * cos_theta);
+}
+
+// The Cornette-Shanks phase function.
+fn mie_phase(cos_theta: f32, g: f32) -> f32 {
+    let g2 = g * g;
+    let k = 3.0 / (8.0 * PI) * (1.0 - g2) / (2.0 + g2);
+    return k * (1.0 + cos_theta * cos_theta) / pow(1.0 + g2 - 2.0 * g * cos_theta, 1.5);
+}
+
+fn distance_to_top_boundary(atmosphere: Atmosphere, r: f32, mu: f32) -> f32 {
+    let discriminant = r * r * (mu * mu - 1.0) + atmosphere.top_radius * atmosphere.top_radius;
+    return max(-r * mu + sqrt(max(discriminant, 0.0)), 0.0);
+}
+
+fn distance_to_bottom_boundary(atmosphere: Atmosphere, r: f32, mu: f32) -> f32 {
+    let discriminant = r * r * (mu * mu - 1.0)
+        + atmosphere.bottom_radius * atmosphere.bottom_radius;
+    return max(-r * mu - sqrt(max(discriminant, 0.0)), 0.0);
+}
+
+fn ray_intersects_ground(atmosphere: Atmosphere, r: f32, mu: f32) -> bool {
+    return mu < 0.0
+        && r * r * (mu * mu - 1.0) + atmosphere.bottom_radius * atmosphere.bottom_radius >= 0.0;
+}
+
+// The distance a ray travels through the atmosphere, up to the ground or to space.
+fn ray_length(atmosphere: Atmosphere, r: f32, mu: f32) -> f32 {
+    if ray_intersects_ground(atmosphere, r, mu) {
+        return distance_to_bottom_boundary(atmosphere, r, mu);
+    }
+    return distance_to_top_boundary(atmosphere, r, mu);
+}
+
+// The distance of the camera to the center of the planet, kept within the atmosphere.
+fn camera_radius(atmosphere: Atmosphere, camera_altitude: f32) -> f32 {
+    return clamp(
+        atmosphere.bottom_radius + camera_altitude,
+        atmosphere.bottom_radius + 1.0,
+        atmosphere.top_radius - 1.0
+    );
+}
+
+// The mapping of the transmittance lookup texture, from Eric Bruneton's "Precomputed Atmospheric
+// Scattering", which gives more precision toward the horizon.
+fn transmittance_lut_r_mu_to_uv(atmosphere: Atmosphere, r: f32, mu: f32) -> vec2<f32> {
+    let h = sqrt(
+        atmosphere.top_radius * atmosphere.top_radius
+            - atmosphere.bottom_radius * atmosphere.bottom_radius
+    );
+    let rho = sqrt(max(r * r - atmosphere.bottom_radius * atmosphere.bottom_radius, 0.0));
+    let d = distance_to_top_boundary(atmosphere, r, mu);
+    let d_min = atmosphere.top_radius - r;
+    let d_max = rho + h;
+    return vec2((d - d_min) / (d_max - d_min), rho / h);
+}
+
+fn transmittance_lut_uv_to_r_mu(atmosphere: Atmosphere, uv: vec2<f32>) -> vec2<f32> {
+    let h = sqrt(
+        atmosphere.top_radius * atmosphere.top_radius
+            - atmosphere.bottom_radius * atmosphere.bottom_radius
+    );
+    let rho = h * uv.y;
+    let r = sqrt(rho * rho + atmosphere.bottom_radius * atmosphere.bottom_radius);
+    let d_min = atmosphere.top_radius - r;
+    let d_max = rho + h;
+    let d = d_min + uv.x * (d_max - d_min);
+    var mu = 1.0;
+    if d > 0.0 {
+        mu = clamp((h * h - rho * rho - d * d) / (2.0 * r * d), -1.0, 1.0);
+    }
+    return vec2(r, mu);
+}
+
+// The transmittance from a point to the top of the atmosphere.
+fn sample_transmittance_lut(
+    atmosphere: Atmosphere,
+    transmittance_lut: texture_2d<f32>,
+    lut_sampler: sampler,
+    r: f32,
+    mu: f32,
+) -> vec3<f32> {
+    let uv = transmittance_lut_r_mu_to_uv(atmosphere, r, mu);
+    return textureSampleLevel(transmittance_lut, lut_sampler, uv, 0.0).rgb;
+}
+
+// The transmittance from a point to a light, which is zero if the planet hides the light.
+fn sample_light_transmittance(
+    atmosphere: Atmosphere,
+    transmittance_lut: texture_2d<f32>,
+    lut_sampler: sampler,
+    r: f32,
+    mu_light: f32,
+) -> vec3<f32> {
+    if ray_intersects_ground(atmosphere, r, mu_light) {
+        return vec3(0.0);
+    }
+    return sample_transmittance_lut(atmosphere, transmittance_lut, lut_sampler, r, mu_light);
+}
+
+fn multiscattering_lut_r_mu_to_uv(atmosphere: Atmosphere, r: f32, mu: f32) -> vec2<f32> {
+    return vec2(
+        mu * 0.5 + 0.5,
+        (r - atmosphere.bottom_radius) / (atmosphere.top_radius - atmosphere.bottom_radius)
+    );
+}
+
+fn multiscattering_lut_uv_to_r_mu(atmosphere: Atmosphere, uv: vec2<f32>) -> vec2<f32> {
+    return vec2(mix(atmosphere.bottom_radius, atmosphere.top_radius, uv.y), uv.x * 2.0 - 1.0);
+}
+
+// The light scattered more than once toward any direction at a point, per unit of illuminance of
+// a light and per unit of scattering coefficient.
+fn sample_multiscattering_lut(
+    atmosphere: Atmosphere,
+    multiscattering_lut: texture_2d<f32>,
+    lut_sampler: sampler,
+    r: f32,
+    mu_light: f32,
+) -> vec3<f32> {
+    let uv = multiscattering_lut_r_mu_to_uv(atmosphere, r, mu_light);
+    return textureSampleLevel(multiscattering_lut, lut_sampler, uv, 0.0).rgb;
+}
+
+// The sky view lookup texture covers all the directions around the camera in world space: its X
+// axis is the azimuth, and its Y axis the elevation, with more precision toward the horizon.
+fn sky_view_lut_direction_to_uv(direction: vec3<f32>) -> vec2<f32> {
+    let azimuth = atan2(direction.z, direction.x);
+    let elevation = asin(clamp(direction.y, -1.0, 1.0));
+    return vec2(
+        azimuth / (2.0 * PI) + 0.5,
+        0.5 - 0.5 * sign(elevation) * sqrt(abs(elevation) / (0.5 * PI))
+    );
+}
+
+fn sky_view_lut_uv_to_direction(uv: vec2<f32>) -> vec3<f32> {
+    let azimuth = (uv.x - 0.5) * 2.0 * PI;
+    let t = 1.0 - uv.y * 2.0;
+    let elevation = sign(t) * t * t * 0.5 * PI;
+    return vec3(
+        cos(elevation) * cos(azimuth),
+        sin(elevation),
+        cos(elevation) * sin(azimuth)
+    );
+}
+
+// The light scattered toward `-direction` at a point, per unit of length, from the lights and
+// from the light scattered more than once.
+fn inscattering(
+    atmosphere: Atmosphere,
+    lights: AtmosphereLights,
+    transmittance_lut: texture_2d<f32>,
+    multiscattering_lut: texture_2d<f32>,
+    lut_sampler: sampler,
+    position: vec3<f32>,
+    direction: vec3<f32>,
+    medium: Medium,
+) -> vec3<f32> {
+    let r = length(position);
+    let up = position / r;
+
+    // Arguments can't be indexed dynamically.
+    var lights_array = lights.lights;
+
+    var inscattering = vec3(0.0);
+    for (var i = 0u; i < lights.count; i += 1u) {
+        let light = lights_array[i];
+        let mu_light = dot(up, light.direction_to_light);
+        let cos_theta = dot(direction, light.direction_to_light);
+
+        let light_transmittance =
+            sample_light_transmittance(atmosphere, transmittance_lut, lut_sampler, r, mu_light);
+        let single_scattering = medium.rayleigh_scattering * rayleigh_phase(cos_theta)
+            + medium.mie_scattering * mie_phase(cos_theta, atmosphere.mie_asymmetry);
+        let multiscattering =
+            sample_multiscattering_lut(atmosphere, multiscattering_lut, lut_sampler, r, mu_light);
+
+        inscattering += light.illuminance
+            * (light_transmittance * single_scattering + multiscattering * medium.scattering);
+    }
+    return inscattering;
+}
+
+// Integrates a constant inscattering over a step with the given transmittance, following
+// Hillaire's energy conserving integration.
+fn integrate_step(
+    inscattering: vec3<f32>,
+    extinction: vec3<f32>,
+    step_transmittance: vec3<f32>,
+) -> vec3<f32> {
+    return (inscattering - inscattering * step_transmittance) / max(extinction, vec3(1e-12));
+}
@@ -0,0 +1,895 @@
+//! Procedural sky and atmosphere, with physically-based scattering.
+//!
+//! An [`Atmosphere`] on a 3D camera replaces its background with the sky of a
+//! planet lit by the [`DirectionalLight`](crate::DirectionalLight)s of the
+//! scene, including the disks of the suns, and adds the haze of the atmosphere
+//! between the camera and the opaque surfaces, known as aerial perspective.
+//!
+//! The scattering follows Sébastien Hillaire's "A Scalable and Production
+//! Ready Sky and Atmosphere Rendering Technique": every frame, compute shaders
+//! fill lookup textures with the transmittance of the atmosphere, the light
+//! scattered more than once, the light scattered toward the camera from every
+//! direction, and the light scattered toward the camera within the frustum.
+//! The sky and the aerial perspective are then drawn from these textures after
+//! the opaque pass.
+//!
+//! The planet is centered below the origin of the world, whose +Y axis points
+//! up, so that the ground of the scene is at an altitude of 0 at the origin.
+//! Distances are in meters.
+//!
+//! The atmosphere needs compute shaders, and isn't supported on WebGL 2.
+
+use bevy_app::{App, Plugin, Update};
+use bevy_asset::{load_internal_asset, Handle};
+use bevy_core_pipeline::{
+    core_3d::{
+        graph::{Core3d, Node3d},
+        Camera3d,
+    },
+    fullscreen_vertex_shader::fullscreen_shader_vertex_state,
+};
+use bevy_ecs::{prelude::*, query::QueryItem};
+use bevy_math::{UVec2, UVec3, Vec3};
+use bevy_reflect::{std_traits::ReflectDefault, Reflect};
+use bevy_render::{
+    extract_component::{
+        ComponentUniforms, DynamicUniformIndex, ExtractComponent, ExtractComponentPlugin,
+        UniformComponentPlugin,
+    },
+    render_graph::{NodeRunError, RenderGraphApp, RenderGraphContext, ViewNode, ViewNodeRunner},
+    render_resource::{
+        binding_types::{
+            sampler, texture_2d, texture_3d, texture_depth_2d, texture_depth_2d_multisampled,
+            texture_storage_2d, texture_storage_3d, uniform_buffer,
+        },
+        *,
+    },
+    renderer::{RenderContext, RenderDevice, RenderQueue},
+    texture::{BevyDefault, CachedTexture, TextureCache},
+    view::{
+        ExtractedView, Msaa, ViewDepthTexture, ViewTarget, ViewUniform, ViewUniformOffset,
+        ViewUniforms,
+    },
+    Render, RenderApp, RenderSet,
+};
+use bevy_utils::tracing::warn;
+
+use crate::{graph::NodePbr, ExtractedDirectionalLight};
+
+const ATMOSPHERE_TYPES_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(4820175469130318451);
+const ATMOSPHERE_FUNCTIONS_SHADER_HANDLE: Handle<Shader> =
+    Handle::weak_from_u128(11932061357820396148);
+const TRANSMITTANCE_LUT_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(7356228931870522614);
+const MULTISCATTERING_LUT_SHADER_HANDLE: Handle<Shader> =
+    Handle::weak_from_u128(15794018337286920731);
+const SKY_VIEW_LUT_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(2613785910045326837);
+const AERIAL_VIEW_LUT_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(9043152287464812260);
+const RENDER_SKY_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(16670835091623487522);
+
+/// The sizes of the lookup textures, which must match `functions.wgsl`.
+const TRANSMITTANCE_LUT_SIZE: UVec2 = UVec2::new(256, 128);
+const MULTISCATTERING_LUT_SIZE: UVec2 = UVec2::new(32, 32);
+const SKY_VIEW_LUT_SIZE: UVec2 = UVec2::new(192, 108);
+const AERIAL_VIEW_LUT_SIZE: UVec3 = UVec3::new(32, 32, 32);
+
+/// The format of the lookup textures.
+const LUT_TEXTURE_FORMAT: TextureFormat = TextureFormat::Rgba16Float;
+
+/// The maximum number of directional lights lighting the atmosphere, which must
+/// match `types.wgsl`.
+///
+/// The brightest lights of the scene are used.
+const MAX_ATMOSPHERE_LIGHTS: usize = 4;
+
+/// The size of the workgroups of the compute shaders, along X and Y.
+const WORKGROUP_SIZE: u32 = 16;
+
+/// Adds support for [`Atmosphere`].
+pub struct AtmospherePlugin;
+
+/// Renders the sky of a planet with a physically-based atmosphere behind the
+/// opaque surfaces of a 3D camera, and the aerial perspective in front of them.
+///
+/// The sky is lit by the [`DirectionalLight`](crate::DirectionalLight)s of the
+/// scene, whose direction gives the time of day. Their illuminance is the one
+/// above the atmosphere: the light reaching the ground is dimmed and reddened
+/// by the atmosphere, toward the horizon especially, but the lighting of the
+/// surfaces of the scene is unchanged.
+///
+/// The sky replaces any [`Skybox`](bevy_core_pipeline::Skybox) of the camera.
+/// The aerial perspective covers the opaque surfaces only: the transparent
+/// surfaces and the ones rendered after the opaque pass don't receive it.
+///
+/// The defaults describe the atmosphere of the Earth.
+///
+/// The atmosphere isn't supported on WebGL 2.
+#[derive(Clone, Copy, Debug, Component, Reflect, ShaderType, ExtractComponent)]
+#[extract_component_filter(With<Camera3d>)]
+#[reflect(Component, Default)]
+pub struct Atmosphere {
+    /// The radius of the planet, at the ground, in meters.
+    ///
+    /// Defaults to 6,360 km.
+    pub bottom_radius: f32,
+
+    /// The radius of the top of the atmosphere, in meters.
+    ///
+    /// Defaults to 6,460 km.
+    pub top_radius: f32,
+
+    /// The albedo of the ground of the planet, which reflects light into the
+    /// atmosphere.
+    ///
+    /// Defaults to 0.3.
+    pub ground_albedo: Vec3,
+
+    /// The inverse of the altitude over which the density of the molecules of
+    /// the atmosphere, which cause Rayleigh scattering, decreases by a factor
+    /// of e, in 1/meters.
+    ///
+    /// Defaults to 1/8 km.
+    pub rayleigh_density_exp_scale: f32,
+
+    /// The scattering coefficients of the molecules of the atmosphere at the
+    /// ground, for red, green, and blue light, in 1/meters.
+    ///
+    /// Rayleigh scattering scatters blue light the most, which makes the sky
+    /// blue and the sunsets red.
+    pub rayleigh_scattering: Vec3,
+
+    /// The inverse of the altitude over which the density of the aerosols of
+    /// the atmosphere, which cause Mie scattering, decreases by a factor of e,
+    /// in 1/meters.
+    ///
+    /// Defaults to 1/1.2 km.
+    pub mie_density_exp_scale: f32,
+
+    /// The scattering coefficient of the aerosols of the atmosphere at the
+    /// ground, in 1/meters.
+    ///
+    /// Mie scattering gives the haze of the atmosphere, and the glow around the
+    /// sun.
+    pub mie_scattering: f32,
+
+    /// The absorption coefficient of the aerosols of the atmosphere at the
+    /// ground, in 1/meters.
+    pub mie_absorption: f32,
+
+    /// How much the aerosols scatter light forward, from -1.0 to 1.0.
+    ///
+    /// Defaults to 0.8.
+    pub mie_asymmetry: f32,
+
+    /// The altitude of the center of the ozone layer, in meters.
+    ///
+    /// Defaults to 25 km.
+    pub ozone_layer_altitude: f32,
+
+    /// The absorption coefficients of the ozone at the center of its layer,
+    /// for red, green, and blue light, in 1/meters.
+    ///
+    /// Ozone absorbs orange light, which keeps the sky blue at twilight.
+    pub ozone_absorption: Vec3,
+
+    /// The thickness of the ozone layer, in meters, over which its density
+    /// decreases linearly from its center.
+    ///
+    /// Defaults to 30 km.
+    pub ozone_layer_width: f32,
+
+    /// The distance from the camera up to which the aerial perspective is
+    /// computed, in meters. Farther surfaces receive the aerial perspective at
+    /// this distance.
+    ///
+    /// Defaults to 32 km.
+    pub aerial_perspective_distance: f32,
+
+    /// The angle the disks of the suns span in the sky, in radians.
+    ///
+    /// Defaults to 0.0095, the angular diameter of the sun seen from the Earth.
+    pub sun_angular_diameter: f32,
+}
+
+impl Atmosphere {
+    /// The atmosphere of the Earth.
+    pub const EARTH: Atmosphere = Atmosphere {
+        bottom_radius: 6_360_000.0,
+        top_radius: 6_460_000.0,
+        ground_albedo: Vec3::splat(0.3),
+        rayleigh_density_exp_scale: 1.0 / 8_000.0,
+        rayleigh_scattering: Vec3::new(5.802e-6, 13.558e-6, 33.1e-6),
+        mie_density_exp_scale: 1.0 / 1_200.0,
+        mie_scattering: 3.996e-6,
+        mie_absorption: 0.444e-6,
+        mie_asymmetry: 0.8,
+        ozone_layer_altitude: 25_000.0,
+        ozone_absorption: Vec3::new(0.650e-6, 1.881e-6, 0.085e-6),
+        ozone_layer_width: 30_000.0,
+        aerial_perspective_distance: 32_000.0,
+        sun_angular_diameter: 0.0095,
+    };
+}
+
+impl Default for Atmosphere {
+    fn default() -> Self {
+        Self::EARTH
+    }
+}
+
+/// A directional light lighting the atmosphere, which must match `types.wgsl`.
+#[derive(Clone, Copy, Default, ShaderType)]
+struct GpuAtmosphereLight {
+    direction_to_light: Vec3,
+    /// The illuminance of the light, times its linear color.
+    illuminance: Vec3,
+}
+
+/// The directional lights lighting the atmosphere, which must match
+/// `types.wgsl`.
+#[derive(Clone, Copy, Default, ShaderType)]
+struct GpuAtmosphereLights {
+    lights: [GpuAtmosphereLight; MAX_ATMOSPHERE_LIGHTS],
+    count: u32,
+}
+
+#[derive(Resource, Default)]
+struct AtmosphereLightsBuffer(UniformBuffer<GpuAtmosphereLights>);
+
+#[derive(Resource)]
+struct AtmospherePipelines {
+    transmittance_lut_layout: BindGroupLayout,
+    multiscattering_lut_layout: BindGroupLayout,
+    sky_view_lut_layout: BindGroupLayout,
+    aerial_view_lut_layout: BindGroupLayout,
+    render_sky_layout: BindGroupLayout,
+    render_sky_layout_multisampled: BindGroupLayout,
+    /// Samples the lookup textures, clamping to their edges.
+    lut_sampler: Sampler,
+    /// Samples the sky view lookup texture, whose X axis wraps around.
+    sky_view_sampler: Sampler,
+    transmittance_lut_pipeline: CachedComputePipelineId,
+    multiscattering_lut_pipeline: CachedComputePipelineId,
+    sky_view_lut_pipeline: CachedComputePipelineId,
+    aerial_view_lut_pipeline: CachedComputePipelineId,
+}
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy)]
+struct RenderSkyPipelineKey {
+    hdr: bool,
+    samples: u32,
+}
+
+/// The pipeline drawing the sky and the aerial perspective of a view.
+#[derive(Component)]
+struct ViewRenderSkyPipeline(CachedRenderPipelineId);
+
+/// The lookup textures of a view.
+#[derive(Component)]
+struct AtmosphereTextures {
+    transmittance_lut: CachedTexture,
+    multiscattering_lut: CachedTexture,
+    sky_view_lut: CachedTexture,
+    aerial_view_lut: CachedTexture,
+}
+
+/// The bind groups of the passes of a view.
+#[derive(Component)]
+struct AtmosphereBindGroups {
+    transmittance_lut: BindGroup,
+    multiscattering_lut: BindGroup,
+    sky_view_lut: BindGroup,
+    aerial_view_lut: BindGroup,
+    render_sky: BindGroup,
+}
+
+/// Fills the lookup textures of a view, then draws its sky and its aerial
+/// perspective.
+#[derive(Default)]
+struct AtmosphereNode;
+
+impl Plugin for AtmospherePlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(
+            app,
+            ATMOSPHERE_TYPES_SHADER_HANDLE,
+            "types.wgsl",
+            Shader::from_wgsl
+        );
+        load_internal_asset!(
+            app,
+            ATMOSPHERE_FUNCTIONS_SHADER_HANDLE,
+            "functions.wgsl",
+            Shader::from_wgsl
+        );
+        load_internal_asset!(
+            app,
+            TRANSMITTANCE_LUT_SHADER_HANDLE,
+            "transmittance_lut.wgsl",
+            Shader::from_wgsl
+        );
+        load_internal_asset!(
+            app,
+            MULTISCATTERING_LUT_SHADER_HANDLE,
+            "multiscattering_lut.wgsl",
+            Shader::from_wgsl
+        );
+        load_internal_asset!(
+            app,
+            SKY_VIEW_LUT_SHADER_HANDLE,
+            "sky_view_lut.wgsl",
+            Shader::from_wgsl
+        );
+        load_internal_asset!(
+            app,
+            AERIAL_VIEW_LUT_SHADER_HANDLE,
+            "aerial_view_lut.wgsl",
+            Shader::from_wgsl
+        );
+        load_internal_asset!(
+            app,
+            RENDER_SKY_SHADER_HANDLE,
+            "render_sky.wgsl",
+            Shader::from_wgsl
+        );
+
+        app.register_type::<Atmosphere>()
+            .add_plugins((
+                ExtractComponentPlugin::<Atmosphere>::default(),
+                UniformComponentPlugin::<Atmosphere>::default(),
+            ))
+            .add_systems(Update, configure_atmosphere_depth_textures);
+    }
+
+    fn finish(&self, app: &mut App) {
+        let Ok(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        if render_app
+            .world
+            .resource::<RenderDevice>()
+            .limits()
+            .max_storage_textures_per_shader_stage
+            == 0
+        {
+            warn!("AtmospherePlugin not loaded. GPU lacks support for storage textures.");
+            return;
+        }
+
+        render_app
+            .init_resource::<AtmospherePipelines>()
+            .init_resource::<SpecializedRenderPipelines<AtmospherePipelines>>()
+            .init_resource::<AtmosphereLightsBuffer>()
+            .add_systems(
+                Render,
+                (
+                    prepare_atmosphere_pipelines.in_set(RenderSet::Prepare),
+                    (prepare_atmosphere_lights, prepare_atmosphere_textures)
+                        .in_set(RenderSet::PrepareResources),
+                    prepare_atmosphere_bind_groups.in_set(RenderSet::PrepareBindGroups),
+                ),
+            )
+            .add_render_graph_node::<ViewNodeRunner<AtmosphereNode>>(Core3d, NodePbr::Atmosphere)
+            .add_render_graph_edges(
+                Core3d,
+                (
+                    // MAIN_OPAQUE_PASS -> ATMOSPHERE -> MAIN_TRANSMISSIVE_PASS
+                    Node3d::MainOpaquePass,
+                    NodePbr::Atmosphere,
+                    Node3d::MainTransmissivePass,
+                ),
+            );
+    }
+}
+
+/// Lets the sky pass read the depth textures of the cameras with an
+/// [`Atmosphere`].
+fn configure_atmosphere_depth_textures(mut cameras: Query<&mut Camera3d, With<Atmosphere>>) {
+    for mut camera_3d in &mut cameras {
+        let usages = TextureUsages::from(camera_3d.depth_texture_usages);
+        if !usages.contains(TextureUsages::TEXTURE_BINDING) {
+            camera_3d.depth_texture_usages = (usages | TextureUsages::TEXTURE_BINDING).into();
+        }
+    }
+}
+
+impl FromWorld for AtmospherePipelines {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+
+        let lut_storage = |dimension| match dimension {
+            TextureViewDimension::D3 => {
+                texture_storage_3d(LUT_TEXTURE_FORMAT, StorageTextureAccess::WriteOnly)
+            }
+            _ => texture_storage_2d(LUT_TEXTURE_FORMAT, StorageTextureAccess::WriteOnly),
+        };
+        let lut = texture_2d(TextureSampleType::Float { filterable: true });
+
+        let transmittance_lut_layout = render_device.create_bind_group_layout(
+            "transmittance_lut_bind_group_layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::COMPUTE,
+                (
+                    uniform_buffer::<Atmosphere>(true),
+                    lut_storage(TextureViewDimension::D2),
+                ),
+            ),
+        );
+        let multiscattering_lut_layout = render_device.create_bind_group_layout(
+            "multiscattering_lut_bind_group_layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::COMPUTE,
+                (
+                    uniform_buffer::<Atmosphere>(true),
+                    lut,
+                    sampler(SamplerBindingType::Filtering),
+                    lut_storage(TextureViewDimension::D2),
+                ),
+            ),
+        );
+        let scattering_lut_layout = |label, dimension| {
+            render_device.create_bind_group_layout(
+                label,
+                &BindGroupLayoutEntries::sequential(
+                    ShaderStages::COMPUTE,
+                    (
+                        uniform_buffer::<Atmosphere>(true),
+                        uniform_buffer::<GpuAtmosphereLights>(false),
+                        uniform_buffer::<ViewUniform>(true),
+                        lut,
+                        lut,
+                        sampler(SamplerBindingType::Filtering),
+                        lut_storage(dimension),
+                    ),
+                ),
+            )
+        };
+        let sky_view_lut_layout =
+            scattering_lut_layout("sky_view_lut_bind_group_layout", TextureViewDimension::D2);
+        let aerial_view_lut_layout = scattering_lut_layout(
+            "aerial_view_lut_bind_group_layout",
+            TextureViewDimension::D3,
+        );
+        let render_sky_layout = |label, depth| {
+            render_device.create_bind_group_layout(
+                label,
+                &BindGroupLayoutEntries::sequential(
+                    ShaderStages::FRAGMENT,
+                    (
+                        uniform_buffer::<Atmosphere>(true),
+                        uniform_buffer::<GpuAtmosphereLights>(false),
+                        uniform_buffer::<ViewUniform>(true),
+                        lut,
+                        lut,
+                        texture_3d(TextureSampleType::Float { filterable: true }),
+                        sampler(SamplerBindingType::Filtering),
+                        sampler(SamplerBindingType::Filtering),
+                        depth,
+                    ),
+                ),
+            )
+        };
+        let render_sky_layout_multisampled = render_sky_layout(
+            "render_sky_multisampled_bind_group_layout",
+            texture_depth_2d_multisampled(),
+        );
+        let render_sky_layout =
+            render_sky_layout("render_sky_bind_group_layout", texture_depth_2d());
+
+        let lut_sampler = render_device.create_sampler(&SamplerDescriptor {
+            label: Some("atmosphere_lut_sampler"),
+            mag_filter: FilterMode::Linear,
+            min_filter: FilterMode::Linear,
+            ..SamplerDescriptor::default()
+        });
+        let sky_view_sampler = render_device.create_sampler(&SamplerDescriptor {
+            label: Some("atmosphere_sky_view_sampler"),
+            address_mode_u: AddressMode::Repeat,
+            mag_filter: FilterMode::Linear,
+            min_filter: FilterMode::Linear,
+            ..SamplerDescriptor::default()
+        });
+
+        let pipeline_cache = world.resource::<PipelineCache>();
+        let queue_pipeline = |label: &'static str, layout: &BindGroupLayout, shader| {
+            pipeline_cache.queue_compute_pipeline(ComputePipelineDescriptor {
+                label: Some(label.into()),
+                layout: vec![layout.clone()],
+                push_constant_ranges: Vec::new(),
+                shader,
+                shader_defs: Vec::new(),
+                entry_point: "main".into(),
+            })
+        };
+        let transmittance_lut_pipeline = queue_pipeline(
+            "transmittance_lut_pipeline",
+            &transmittance_lut_layout,
+            TRANSMITTANCE_LUT_SHADER_HANDLE,
+        );
+        let multiscattering_lut_pipeline = queue_pipeline(
+            "multiscattering_lut_pipeline",
+            &multiscattering_lut_layout,
+            MULTISCATTERING_LUT_SHADER_HANDLE,
+        );
+        let sky_view_lut_pipeline = queue_pipeline(
+            "sky_view_lut_pipeline",
+            &sky_view_lut_layout,
+            SKY_VIEW_LUT_SHADER_HANDLE,
+        );
+        let aerial_view_lut_pipeline = queue_pipeline(
+            "aerial_view_lut_pipeline",
+            &aerial_view_lut_layout,
+            AERIAL_VIEW_LUT_SHADER_HANDLE,
+        );
+
+        Self {
+            transmittance_lut_layout,
+            multiscattering_lut_layout,
+            sky_view_lut_layout,
+            aerial_view_lut_layout,
+            render_sky_layout,
+            render_sky_layout_multisampled,
+            lut_sampler,
+            sky_view_sampler,
+            transmittance_lut_pipeline,
+            multiscattering_lut_pipeline,
+            sky_view_lut_pipeline,
+            aerial_view_lut_pipeline,
+        }
+    }
+}
+
+impl SpecializedRenderPipeline for AtmospherePipelines {
+    type Key = RenderSkyPipelineKey;
+
+    fn specialize(&self, key: Self::Key) -> RenderPipelineDescriptor {
+        let mut shader_defs = Vec::new();
+        let layout = if key.samples > 1 {
+            shader_defs.push("MULTISAMPLED".into());
+            self.render_sky_layout_multisampled.clone()
+        } else {
+            self.render_sky_layout.clone()
+        };
+
+        RenderPipelineDescriptor {
+            label: Some("render_sky_pipeline".into()),
+            layout: vec![layout],
+            vertex: fullscreen_shader_vertex_state(),
+            fragment: Some(FragmentState {
+                shader: RENDER_SKY_SHADER_HANDLE,
+                shader_defs,
+                entry_point: "fragment".into(),
+                targets: vec![Some(ColorTargetState {
+                    format: if key.hdr {
+                        ViewTarget::TEXTURE_FORMAT_HDR
+                    } else {
+                        TextureFormat::bevy_default()
+                    },
+                    // The sky and the light scattered toward the camera are
+                    // added to the surfaces, dimmed by the transmittance of the
+                    // atmosphere in the alpha channel.
+                    blend: Some(BlendState {
+                        color: BlendComponent {
+                            src_factor: BlendFactor::One,
+                            dst_factor: BlendFactor::OneMinusSrcAlpha,
+                            operation: BlendOperation::Add,
+                        },
+                        alpha: BlendComponent {
+                            src_factor: BlendFactor::Zero,
+                            dst_factor: BlendFactor::One,
+                            operation: BlendOperation::Add,
+                        },
+                    }),
+                    write_mask: ColorWrites::ALL,
+                })],
+            }),
+            primitive: PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: MultisampleState {
+                count: key.samples,
+                ..MultisampleState::default()
+            },
+            push_constant_ranges: Vec::new(),
+        }
+    }
+}
+
+fn prepare_atmosphere_pipelines(
+    mut commands: Commands,
+    pipeline_cache: Res<PipelineCache>,
+    mut pipelines: ResMut<SpecializedRenderPipelines<AtmospherePipelines>>,
+    atmosphere_pipelines: Res<AtmospherePipelines>,
+    msaa: Res<Msaa>,
+    views: Query<(Entity, &ExtractedView), With<Atmosphere>>,
+) {
+    for (entity, view) in &views {
+        let pipeline_id = pipelines.specialize(
+            &pipeline_cache,
+            &atmosphere_pipelines,
+            RenderSkyPipelineKey {
+                hdr: view.hdr,
+                samples: msaa.samples(),
+            },
+        );
+
+        commands
+            .entity(entity)
+            .insert(ViewRenderSkyPipeline(pipeline_id));
+    }
+}
+
+/// Uploads the brightest directional lights of the scene.
+fn prepare_atmosphere_lights(
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+    mut lights_buffer: ResMut<AtmosphereLightsBuffer>,
+    directional_lights: Query<&ExtractedDirectionalLight>,
+    views: Query<(), With<Atmosphere>>,
+) {
+    if views.is_empty() {
+        return;
+    }
+
+    let mut directional_lights: Vec<_> = directional_lights.iter().collect();
+    directional_lights.sort_by(|a, b| b.illuminance.total_cmp(&a.illuminance));
+
+    let mut lights = GpuAtmosphereLights::default();
+    for (gpu_light, light) in lights.lights.iter_mut().zip(&directional_lights) {
+        *gpu_light = GpuAtmosphereLight {
+            direction_to_light: light.transform.back(),
+            illuminance: Vec3::from_slice(&light.color.as_linear_rgba_f32()) * light.illuminance,
+        };
+    }
+    lights.count = directional_lights.len().min(MAX_ATMOSPHERE_LIGHTS) as u32;
+
+    lights_buffer.0.set(lights);
+    lights_buffer.0.write_buffer(&render_device, &render_queue);
+}
+
+fn prepare_atmosphere_textures(
+    mut commands: Commands,
+    mut texture_cache: ResMut<TextureCache>,
+    render_device: Res<RenderDevice>,
+    views: Query<Entity, (With<Atmosphere>, With<ExtractedView>)>,
+) {
+    let mut lut = |label, size: UVec3, dimension| {
+        texture_cache.get(
+            &render_device,
+            TextureDescriptor {
+                label: Some(label),
+                size: Extent3d {
+                    width: size.x,
+                    height: size.y,
+                    depth_or_array_layers: size.z,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension,
+                format: LUT_TEXTURE_FORMAT,
+                usage: TextureUsages::STORAGE_BINDING | TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            },
+        )
+    };
+
+    for entity in &views {
+        commands.entity(entity).insert(AtmosphereTextures {
+            transmittance_lut: lut(
+                "transmittance_lut",
+                TRANSMITTANCE_LUT_SIZE.extend(1),
+                TextureDimension::D2,
+            ),
+            multiscattering_lut: lut(
+                "multiscattering_lut",
+                MULTISCATTERING_LUT_SIZE.extend(1),
+                TextureDimension::D2,
+            ),
+            sky_view_lut: lut(
+                "sky_view_lut",
+                SKY_VIEW_LUT_SIZE.extend(1),
+                TextureDimension::D2,
+            ),
+            aerial_view_lut: lut(
+                "aerial_view_lut",
+                AERIAL_VIEW_LUT_SIZE,
+                TextureDimension::D3,
+            ),
+        });
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn prepare_atmosphere_bind_groups(
+    mut commands: Commands,
+    render_device: Res<RenderDevice>,
+    pipelines: Res<AtmospherePipelines>,
+    atmosphere_uniforms: Res<ComponentUniforms<Atmosphere>>,
+    lights_buffer: Res<AtmosphereLightsBuffer>,
+    view_uniforms: Res<ViewUniforms>,
+    msaa: Res<Msaa>,
+    views: Query<(Entity, &AtmosphereTextures, &ViewDepthTexture)>,
+) {
+    let (Some(atmosphere), Some(lights), Some(view)) = (
+        atmosphere_uniforms.binding(),
+        lights_buffer.0.binding(),
+        view_uniforms.uniforms.binding(),
+    ) else {
+        return;
+    };
+
+    for (entity, textures, depth) in &views {
+        let transmittance_lut = &textures.transmittance_lut.default_view;
+        let multiscattering_lut = &textures.multiscattering_lut.default_view;
+        let sky_view_lut = &textures.sky_view_lut.default_view;
+        let aerial_view_lut = &textures.aerial_view_lut.default_view;
+        let lut_sampler = &pipelines.lut_sampler;
+
+        let scattering_lut_bind_group = |label, layout, output| {
+            render_device.create_bind_group(
+                label,
+                layout,
+                &BindGroupEntries::sequential((
+                    atmosphere.clone(),
+                    lights.clone(),
+                    view.clone(),
+                    transmittance_lut,
+                    multiscattering_lut,
+                    lut_sampler,
+                    output,
+                )),
+            )
+        };
+
+        let render_sky_layout = if msaa.samples() > 1 {
+            &pipelines.render_sky_layout_multisampled
+        } else {
+            &pipelines.render_sky_layout
+        };
+
+        commands.entity(entity).insert(AtmosphereBindGroups {
+            transmittance_lut: render_device.create_bind_group(
+                "transmittance_lut_bind_group",
+                &pipelines.transmittance_lut_layout,
+                &BindGroupEntries::sequential((atmosphere.clone(), transmittance_lut)),
+            ),
+            multiscattering_lut: render_device.create_bind_group(
+                "multiscattering_lut_bind_group",
+                &pipelines.multiscattering_lut_layout,
+                &BindGroupEntries::sequential((
+                    atmosphere.clone(),
+                    transmittance_lut,
+                    lut_sampler,
+                    multiscattering_lut,
+                )),
+            ),
+            sky_view_lut: scattering_lut_bind_group(
+                "sky_view_lut_bind_group",
+                &pipelines.sky_view_lut_layout,
+                sky_view_lut,
+            ),
+            aerial_view_lut: scattering_lut_bind_group(
+                "aerial_view_lut_bind_group",
+                &pipelines.aerial_view_lut_layout,
+                aerial_view_lut,
+            ),
+            render_sky: render_device.create_bind_group(
+                "render_sky_bind_group",
+                render_sky_layout,
+                &BindGroupEntries::sequential((
+                    atmosphere.clone(),
+                    lights.clone(),
+                    view.clone(),
+                    transmittance_lut,
+                    sky_view_lut,
+                    aerial_view_lut,
+                    lut_sampler,
+                    &pipelines.sky_view_sampler,
+                    depth.view(),
+                )),
+            ),
+        });
+    }
+}
+
+impl ViewNode for AtmosphereNode {
+    type ViewQuery = (
+        &'static ViewTarget,
+        &'static ViewUniformOffset,
+        &'static DynamicUniformIndex<Atmosphere>,
+        &'static ViewRenderSkyPipeline,
+        &'static AtmosphereBindGroups,
+    );
+
+    fn run(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        (view_target, view_uniform_offset, atmosphere_index, view_pipeline, bind_groups): QueryItem<
+            Self::ViewQuery,
+        >,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        let pipelines = world.resource::<AtmospherePipelines>();
+        let pipeline_cache = world.resource::<PipelineCache>();
+        let (
+            Some(transmittance_lut_pipeline),
+            Some(multiscattering_lut_pipeline),
+            Some(sky_view_lut_pipeline),
+            Some(aerial_view_lut_pipeline),
+            Some(render_sky_pipeline),
+        ) = (
+            pipeline_cache.get_compute_pipeline(pipelines.transmittance_lut_pipeline),
+            pipeline_cache.get_compute_pipeline(pipelines.multiscattering_lut_pipeline),
+            pipeline_cache.get_compute_pipeline(pipelines.sky_view_lut_pipeline),
+            pipeline_cache.get_compute_pipeline(pipelines.aerial_view_lut_pipeline),
+            pipeline_cache.get_render_pipeline(view_pipeline.0),
+        )
+        else {
+            return Ok(());
+        };
+
+        let atmosphere_offset = atmosphere_index.index();
+        let view_offset = view_uniform_offset.offset;
+
+        {
+            let mut compute_pass =
+                render_context
+                    .command_encoder()
+                    .begin_compute_pass(&ComputePassDescriptor {
+                        label: Some("atmosphere_luts_pass"),
+                        timestamp_writes: None,
+                    });
+
+            compute_pass.set_pipeline(transmittance_lut_pipeline);
+            compute_pass.set_bind_group(0, &bind_groups.transmittance_lut, &[atmosphere_offset]);
+            dispatch_2d(&mut compute_pass, TRANSMITTANCE_LUT_SIZE);
+
+            compute_pass.set_pipeline(multiscattering_lut_pipeline);
+            compute_pass.set_bind_group(0, &bind_groups.multiscattering_lut, &[atmosphere_offset]);
+            dispatch_2d(&mut compute_pass, MULTISCATTERING_LUT_SIZE);
+
+            compute_pass.set_pipeline(sky_view_lut_pipeline);
+            compute_pass.set_bind_group(
+                0,
+                &bind_groups.sky_view_lut,
+                &[atmosphere_offset, view_offset],
+            );
+            dispatch_2d(&mut compute_pass, SKY_VIEW_LUT_SIZE);
+
+            // Each invocation marches through all the slices of the aerial view
+            // lookup texture.
+            compute_pass.set_pipeline(aerial_view_lut_pipeline);
+            compute_pass.set_bind_group(
+                0,
+                &bind_groups.aerial_view_lut,
+                &[atmosphere_offset, view_offset],
+            );
+            dispatch_2d(&mut compute_pass, AERIAL_VIEW_LUT_SIZE.truncate());
+        }
+
+        let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+            label: Some("render_sky_pass"),
+            color_attachments: &[Some(view_target.get_color_attachment())],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        render_pass.set_render_pipeline(render_sky_pipeline);
+        render_pass.set_bind_group(
+            0,
+            &bind_groups.render_sky,
+            &[atmosphere_offset, view_offset],
+        );
+        render_pass.draw(0..3, 0..1);
+
+        Ok(())
+    }
+}
+
+fn dispatch_2d(compute_pass: &mut ComputePass, size: UVec2) {
+    compute_pass.dispatch_workgroups(
+        size.x.div_ceil(WORKGROUP_SIZE),
+        size.y.div_ceil(WORKGROUP_SIZE),
+        1,
+    );
+}
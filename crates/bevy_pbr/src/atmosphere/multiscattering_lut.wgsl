@@ -0,0 +1,105 @@
+// Fills the multiple scattering lookup texture, with the light scattered more than once toward any
+// direction at a point of the atmosphere, per unit of illuminance of a light and per unit of
+// scattering coefficient, for a given altitude and direction of the light.
+//
+// The light scattered twice is integrated over the sphere of directions around the point, and
+// scattering of higher orders is approximated by a geometric series, assuming an isotropic phase
+// function.
+
+#import bevy_pbr::atmosphere::{
+    types::Atmosphere,
+    functions::{
+        PI, MULTISCATTERING_LUT_SIZE, sample_medium, ray_length, ray_intersects_ground,
+        sample_light_transmittance, multiscattering_lut_uv_to_r_mu, integrate_step,
+    },
+}
+
+@group(0) @binding(0) var<uniform> atmosphere: Atmosphere;
+@group(0) @binding(1) var transmittance_lut: texture_2d<f32>;
+@group(0) @binding(2) var lut_sampler: sampler;
+@group(0) @binding(3) var multiscattering_lut_out: texture_storage_2d<rgba16float, write>;
+
+const DIRECTION_COUNT: u32 = 64u;
+const SAMPLE_COUNT: u32 = 20u;
+
+// Spreads the directions evenly over the sphere, along a Fibonacci spiral.
+fn fibonacci_sphere(i: u32) -> vec3<f32> {
+    let golden_angle = PI * (3.0 - sqrt(5.0));
+    let y = 1.0 - 2.0 * (f32(i) + 0.5) / f32(DIRECTION_COUNT);
+    let radius = sqrt(1.0 - y * y);
+    let theta = golden_angle * f32(i);
+    return vec3(cos(theta) * radius, y, sin(theta) * radius);
+}
+
+@compute
+@workgroup_size(16, 16, 1)
+fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    if any(global_id.xy >= MULTISCATTERING_LUT_SIZE) {
+        return;
+    }
+
+    let uv = (vec2<f32>(global_id.xy) + 0.5) / vec2<f32>(MULTISCATTERING_LUT_SIZE);
+    let r_mu = multiscattering_lut_uv_to_r_mu(atmosphere, uv);
+    let r = r_mu.x;
+    let position = vec3(0.0, r, 0.0);
+    let direction_to_light = vec3(sqrt(1.0 - r_mu.y * r_mu.y), r_mu.y, 0.0);
+
+    // The light scattered twice toward the point, and the fraction of the light scattered once
+    // more toward the point.
+    var second_order = vec3(0.0);
+    var transfer = vec3(0.0);
+    for (var i = 0u; i < DIRECTION_COUNT; i += 1u) {
+        let direction = fibonacci_sphere(i);
+        let mu = direction.y;
+        let step_length = ray_length(atmosphere, r, mu) / f32(SAMPLE_COUNT);
+
+        var throughput = vec3(1.0);
+        for (var j = 0u; j < SAMPLE_COUNT; j += 1u) {
+            let t = (f32(j) + 0.5) * step_length;
+            let sample_position = position + direction * t;
+            let sample_r = length(sample_position);
+            let medium = sample_medium(atmosphere, sample_r);
+            let step_transmittance = exp(-medium.extinction * step_length);
+
+            let mu_light = dot(sample_position / sample_r, direction_to_light);
+            let light_transmittance = sample_light_transmittance(
+                atmosphere,
+                transmittance_lut,
+                lut_sampler,
+                sample_r,
+                mu_light,
+            );
+
+            let scattered = light_transmittance * medium.scattering / (4.0 * PI);
+            second_order += throughput
+                * integrate_step(scattered, medium.extinction, step_transmittance);
+            transfer += throughput
+                * integrate_step(medium.scattering, medium.extinction, step_transmittance);
+
+            throughput *= step_transmittance;
+        }
+
+        // The light reflected by the ground.
+        if ray_intersects_ground(atmosphere, r, mu) {
+            let ground_position = position + direction * step_length * f32(SAMPLE_COUNT);
+            let ground_normal = normalize(ground_position);
+            let mu_light = dot(ground_normal, direction_to_light);
+            let light_transmittance = sample_light_transmittance(
+                atmosphere,
+                transmittance_lut,
+                lut_sampler,
+                atmosphere.bottom_radius,
+                mu_light,
+            );
+            second_order += throughput * light_transmittance * saturate(mu_light)
+                * atmosphere.ground_albedo / PI;
+        }
+    }
+
+    // Both are integrated over the sphere with an isotropic phase function.
+    second_order /= f32(DIRECTION_COUNT);
+    transfer /= f32(DIRECTION_COUNT);
+
+    let multiscattering = second_order / (1.0 - transfer);
+    textureStore(multiscattering_lut_out, global_id.xy, vec4(multiscattering, 1.0));
+}
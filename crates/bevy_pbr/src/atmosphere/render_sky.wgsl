@@ -0,0 +1,79 @@
+// Draws the sky behind the opaque surfaces of a view, and the aerial perspective in front of them,
+// from the lookup textures.
+
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+#import bevy_render::view::View
+#import bevy_pbr::atmosphere::{
+    types::{Atmosphere, AtmosphereLights},
+    functions::{
+        PI, AERIAL_VIEW_LUT_SIZE, camera_radius, ray_intersects_ground, sample_transmittance_lut,
+        sky_view_lut_direction_to_uv,
+    },
+}
+
+@group(0) @binding(0) var<uniform> atmosphere: Atmosphere;
+@group(0) @binding(1) var<uniform> lights: AtmosphereLights;
+@group(0) @binding(2) var<uniform> view: View;
+@group(0) @binding(3) var transmittance_lut: texture_2d<f32>;
+@group(0) @binding(4) var sky_view_lut: texture_2d<f32>;
+@group(0) @binding(5) var aerial_view_lut: texture_3d<f32>;
+@group(0) @binding(6) var lut_sampler: sampler;
+@group(0) @binding(7) var sky_view_sampler: sampler;
+#ifdef MULTISAMPLED
+@group(0) @binding(8) var depth_texture: texture_depth_multisampled_2d;
+#else
+@group(0) @binding(8) var depth_texture: texture_depth_2d;
+#endif
+
+fn world_position(uv: vec2<f32>, depth: f32) -> vec3<f32> {
+    let ndc = vec4(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, depth, 1.0);
+    let world_position = view.inverse_view_proj * ndc;
+    return world_position.xyz / world_position.w;
+}
+
+// The disks of the lights seen in the direction of the view, dimmed by the atmosphere.
+fn sun_disks(r: f32, direction: vec3<f32>) -> vec3<f32> {
+    if ray_intersects_ground(atmosphere, r, direction.y) {
+        return vec3(0.0);
+    }
+
+    let cos_half_angle = cos(0.5 * atmosphere.sun_angular_diameter);
+    let solid_angle = 2.0 * PI * (1.0 - cos_half_angle);
+    let transmittance =
+        sample_transmittance_lut(atmosphere, transmittance_lut, lut_sampler, r, direction.y);
+
+    var luminance = vec3(0.0);
+    for (var i = 0u; i < lights.count; i += 1u) {
+        let light = lights.lights[i];
+        if dot(direction, light.direction_to_light) > cos_half_angle {
+            luminance += light.illuminance / solid_angle * transmittance;
+        }
+    }
+    return luminance;
+}
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let depth = textureLoad(depth_texture, vec2<i32>(in.position.xy), 0);
+
+    if depth == 0.0 {
+        let direction = normalize(world_position(in.uv, 1.0) - view.world_position);
+        let r = camera_radius(atmosphere, view.world_position.y);
+        let sky_view_uv = sky_view_lut_direction_to_uv(direction);
+        let sky = textureSampleLevel(sky_view_lut, sky_view_sampler, sky_view_uv, 0.0).rgb;
+        return vec4((sky + sun_disks(r, direction)) * view.exposure, 1.0);
+    }
+
+    // The slices of the aerial view lookup texture hold the light scattered up to their end, so
+    // the aerial perspective fades in over the first slice.
+    let distance = length(world_position(in.uv, depth) - view.world_position);
+    let slice_count = f32(AERIAL_VIEW_LUT_SIZE.z);
+    let slice = distance / atmosphere.aerial_perspective_distance * slice_count - 1.0;
+    let weight = saturate(slice + 1.0);
+    let w = (max(slice, 0.0) + 0.5) / slice_count;
+
+    let aerial_view = textureSampleLevel(aerial_view_lut, lut_sampler, vec3(in.uv, w), 0.0);
+    let inscattering = aerial_view.rgb * weight * view.exposure;
+    let transmittance = mix(1.0, aerial_view.a, weight);
+    return vec4(inscattering, 1.0 - transmittance);
+}
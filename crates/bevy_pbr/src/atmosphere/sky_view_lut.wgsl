@@ -0,0 +1,78 @@
+// Fills the sky view lookup texture, with the light reaching the camera from every direction
+// around it, through the whole atmosphere, before the exposure of the view.
+
+#import bevy_render::view::View
+#import bevy_pbr::atmosphere::{
+    types::{Atmosphere, AtmosphereLights},
+    functions::{
+        PI, SKY_VIEW_LUT_SIZE, sample_medium, ray_length, ray_intersects_ground, camera_radius,
+        sample_light_transmittance, sky_view_lut_uv_to_direction, inscattering, integrate_step,
+    },
+}
+
+@group(0) @binding(0) var<uniform> atmosphere: Atmosphere;
+@group(0) @binding(1) var<uniform> lights: AtmosphereLights;
+@group(0) @binding(2) var<uniform> view: View;
+@group(0) @binding(3) var transmittance_lut: texture_2d<f32>;
+@group(0) @binding(4) var multiscattering_lut: texture_2d<f32>;
+@group(0) @binding(5) var lut_sampler: sampler;
+@group(0) @binding(6) var sky_view_lut_out: texture_storage_2d<rgba16float, write>;
+
+const SAMPLE_COUNT: u32 = 32u;
+
+@compute
+@workgroup_size(16, 16, 1)
+fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    if any(global_id.xy >= SKY_VIEW_LUT_SIZE) {
+        return;
+    }
+
+    let uv = (vec2<f32>(global_id.xy) + 0.5) / vec2<f32>(SKY_VIEW_LUT_SIZE);
+    let direction = sky_view_lut_uv_to_direction(uv);
+    let r = camera_radius(atmosphere, view.world_position.y);
+    let position = vec3(0.0, r, 0.0);
+    let mu = direction.y;
+    let step_length = ray_length(atmosphere, r, mu) / f32(SAMPLE_COUNT);
+
+    var luminance = vec3(0.0);
+    var throughput = vec3(1.0);
+    for (var i = 0u; i < SAMPLE_COUNT; i += 1u) {
+        let t = (f32(i) + 0.5) * step_length;
+        let sample_position = position + direction * t;
+        let medium = sample_medium(atmosphere, length(sample_position));
+        let step_transmittance = exp(-medium.extinction * step_length);
+
+        let scattered = inscattering(
+            atmosphere,
+            lights,
+            transmittance_lut,
+            multiscattering_lut,
+            lut_sampler,
+            sample_position,
+            direction,
+            medium,
+        );
+        luminance += throughput * integrate_step(scattered, medium.extinction, step_transmittance);
+        throughput *= step_transmittance;
+    }
+
+    // The light reflected by the ground, seen through the atmosphere.
+    if ray_intersects_ground(atmosphere, r, mu) {
+        let ground_normal = normalize(position + direction * step_length * f32(SAMPLE_COUNT));
+        for (var i = 0u; i < lights.count; i += 1u) {
+            let light = lights.lights[i];
+            let mu_light = dot(ground_normal, light.direction_to_light);
+            let light_transmittance = sample_light_transmittance(
+                atmosphere,
+                transmittance_lut,
+                lut_sampler,
+                atmosphere.bottom_radius,
+                mu_light,
+            );
+            luminance += throughput * light.illuminance * light_transmittance * saturate(mu_light)
+                * atmosphere.ground_albedo / PI;
+        }
+    }
+
+    textureStore(sky_view_lut_out, global_id.xy, vec4(luminance, 1.0));
+}
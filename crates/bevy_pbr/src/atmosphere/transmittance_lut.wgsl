@@ -0,0 +1,38 @@
+// Fills the transmittance lookup texture, with the transmittance from a point of the atmosphere to
+// the top of the atmosphere along a direction.
+
+#import bevy_pbr::atmosphere::{
+    types::Atmosphere,
+    functions::{
+        TRANSMITTANCE_LUT_SIZE, sample_medium, distance_to_top_boundary,
+        transmittance_lut_uv_to_r_mu,
+    },
+}
+
+@group(0) @binding(0) var<uniform> atmosphere: Atmosphere;
+@group(0) @binding(1) var transmittance_lut_out: texture_storage_2d<rgba16float, write>;
+
+const SAMPLE_COUNT: u32 = 40u;
+
+@compute
+@workgroup_size(16, 16, 1)
+fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    if any(global_id.xy >= TRANSMITTANCE_LUT_SIZE) {
+        return;
+    }
+
+    let uv = (vec2<f32>(global_id.xy) + 0.5) / vec2<f32>(TRANSMITTANCE_LUT_SIZE);
+    let r_mu = transmittance_lut_uv_to_r_mu(atmosphere, uv);
+    let r = r_mu.x;
+    let mu = r_mu.y;
+
+    let step_length = distance_to_top_boundary(atmosphere, r, mu) / f32(SAMPLE_COUNT);
+    var optical_depth = vec3(0.0);
+    for (var i = 0u; i < SAMPLE_COUNT; i += 1u) {
+        let t = (f32(i) + 0.5) * step_length;
+        let sample_r = sqrt(r * r + t * t + 2.0 * r * mu * t);
+        optical_depth += sample_medium(atmosphere, sample_r).extinction * step_length;
+    }
+
+    textureStore(transmittance_lut_out, global_id.xy, vec4(exp(-optical_depth), 1.0));
+}
@@ -0,0 +1,33 @@
+#define_import_path bevy_pbr::atmosphere::types
+
+// Must match `Atmosphere` in `atmosphere/mod.rs`.
+struct Atmosphere {
+    bottom_radius: f32,
+    top_radius: f32,
+    ground_albedo: vec3<f32>,
+    rayleigh_density_exp_scale: f32,
+    rayleigh_scattering: vec3<f32>,
+    mie_density_exp_scale: f32,
+    mie_scattering: f32,
+    mie_absorption: f32,
+    mie_asymmetry: f32,
+    ozone_layer_altitude: f32,
+    ozone_absorption: vec3<f32>,
+    ozone_layer_width: f32,
+    aerial_perspective_distance: f32,
+    sun_angular_diameter: f32,
+}
+
+struct AtmosphereLight {
+    direction_to_light: vec3<f32>,
+    // The illuminance of the light, times its linear color.
+    illuminance: vec3<f32>,
+}
+
+// Must match `MAX_ATMOSPHERE_LIGHTS` in `atmosphere/mod.rs`.
+const MAX_ATMOSPHERE_LIGHTS: u32 = 4u;
+
+struct AtmosphereLights {
+    lights: array<AtmosphereLight, MAX_ATMOSPHERE_LIGHTS>,
+    count: u32,
+}
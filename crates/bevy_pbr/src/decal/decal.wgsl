@@ -0,0 +1,128 @@
+#define_import_path bevy_pbr::decal
+
+// Clustered decals.
+//
+// The decals of the cluster of the fragment are blended over its material before lighting, in
+// increasing sort order. Each decal projects its textures along the -Z axis of its projection box,
+// a 1×1×1 cube centered on the origin in decal space.
+
+#import bevy_pbr::{
+    clustered_forward,
+    mesh_view_bindings as bindings,
+    pbr_types::PbrInput,
+}
+
+#ifdef CLUSTERED_DECALS_ARE_USABLE
+
+// Returns the `PbrInput` with the decals that cover the fragment applied to it.
+fn apply_decals(in: PbrInput) -> PbrInput {
+    var pbr_input = in;
+
+    // The derivatives must be computed in uniform control flow, before the loop over the decals.
+    let world_position = pbr_input.world_position.xyz;
+    let world_position_dx = dpdx(world_position);
+    let world_position_dy = dpdy(world_position);
+
+    let view_z = dot(vec4<f32>(
+        bindings::view.inverse_view[0].z,
+        bindings::view.inverse_view[1].z,
+        bindings::view.inverse_view[2].z,
+        bindings::view.inverse_view[3].z
+    ), pbr_input.world_position);
+    let cluster_index = clustered_forward::fragment_cluster_index(
+        pbr_input.frag_coord.xy,
+        view_z,
+        pbr_input.is_orthographic
+    );
+    let offset_and_count = clustered_forward::unpack_decal_offset_and_count(cluster_index);
+
+    for (var i = offset_and_count[0]; i < offset_and_count[0] + offset_and_count[1]; i += 1u) {
+        let decal = bindings::clustered_decals.data[clustered_forward::get_light_id(i)];
+
+        // The rows of the transform from world space to decal space.
+        let row_x = decal.inverse_transpose_transform[0];
+        let row_y = decal.inverse_transpose_transform[1];
+        let row_z = decal.inverse_transpose_transform[2];
+
+        let local_position = vec3(
+            dot(row_x, vec4(world_position, 1.0)),
+            dot(row_y, vec4(world_position, 1.0)),
+            dot(row_z, vec4(world_position, 1.0)),
+        );
+        if any(abs(local_position) > vec3(0.5)) {
+            continue;
+        }
+
+        // The decal fades out on the surfaces turned away from its projection, and toward the
+        // front and the back of its projection box.
+        let projection_direction = normalize(row_z.xyz);
+        let angle_fade = saturate(dot(pbr_input.N, projection_direction) / max(decal.angle_fade, 1e-4));
+        let depth_fade = saturate((0.5 - abs(local_position.z)) / max(decal.depth_fade, 1e-4));
+
+        let uv = vec2(local_position.x + 0.5, 0.5 - local_position.y);
+        let uv_dx = vec2(dot(row_x.xyz, world_position_dx), -dot(row_y.xyz, world_position_dx));
+        let uv_dy = vec2(dot(row_x.xyz, world_position_dy), -dot(row_y.xyz, world_position_dy));
+
+        var base_color = decal.base_color;
+        if decal.base_color_texture_index >= 0 {
+            base_color *= textureSampleGrad(
+                bindings::clustered_decal_textures[decal.base_color_texture_index],
+                bindings::clustered_decal_sampler,
+                uv,
+                uv_dx,
+                uv_dy
+            );
+        }
+
+        let alpha = base_color.a * angle_fade * depth_fade;
+        if alpha <= 0.0 {
+            continue;
+        }
+
+        pbr_input.material.base_color = vec4(
+            mix(pbr_input.material.base_color.rgb, base_color.rgb, alpha),
+            pbr_input.material.base_color.a
+        );
+
+        if decal.normal_map_texture_index >= 0 {
+            let normal_map = textureSampleGrad(
+                bindings::clustered_decal_textures[decal.normal_map_texture_index],
+                bindings::clustered_decal_sampler,
+                uv,
+                uv_dx,
+                uv_dy
+            ).rgb * 2.0 - 1.0;
+            // The tangent space of the decal follows the X and Y axes of its projection box.
+            let N = pbr_input.N;
+            let T = normalize(row_x.xyz - N * dot(N, row_x.xyz));
+            let B = cross(N, T);
+            let decal_normal = normalize(T * normal_map.x + B * normal_map.y + N * normal_map.z);
+            pbr_input.N = normalize(mix(N, decal_normal, alpha));
+        }
+
+        if decal.occlusion_roughness_metallic_texture_index >= 0 {
+            let occlusion_roughness_metallic = textureSampleGrad(
+                bindings::clustered_decal_textures[decal.occlusion_roughness_metallic_texture_index],
+                bindings::clustered_decal_sampler,
+                uv,
+                uv_dx,
+                uv_dy
+            ).rgb;
+            pbr_input.diffuse_occlusion *= mix(1.0, occlusion_roughness_metallic.r, alpha);
+            pbr_input.material.perceptual_roughness = mix(
+                pbr_input.material.perceptual_roughness,
+                occlusion_roughness_metallic.g,
+                alpha
+            );
+            pbr_input.material.metallic = mix(
+                pbr_input.material.metallic,
+                occlusion_roughness_metallic.b,
+                alpha
+            );
+        }
+    }
+
+    return pbr_input;
+}
+
+#endif  // CLUSTERED_DECALS_ARE_USABLE
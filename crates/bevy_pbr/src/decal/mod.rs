@@ -0,0 +1,475 @@
+//! Clustered decals: textures projected onto the surfaces of the scene.
+//!
+//! A [`Decal`] projects its textures along the −Z axis of its entity, onto every
+//! surface inside its *projection box*. The projection box is conceptually a
+//! unit cube (1×1×1) centered on the origin, which the
+//! [`bevy_transform::prelude::Transform`] of the entity scales, rotates, and
+//! translates. The textures cover the face of the box on the XY plane, with
+//! their top toward +Y, and are stretched through its depth.
+//!
+//! Decals are assigned to the clusters of each view alongside the point and
+//! spot lights, so that the PBR fragment shader only considers the decals
+//! around each fragment. They are blended over the material of the surface
+//! before lighting, both in the forward and in the deferred renderer, in
+//! increasing [`Decal::sort_order`].
+//!
+//! Decals use binding arrays (also known as bindless textures) and storage
+//! buffers and consequently aren't supported on WebGL2 or WebGPU. They're also
+//! unsupported if GLSL is in use, due to `naga` limitations. On those
+//! platforms, decals are ignored.
+
+use std::{num::NonZeroU32, ops::Deref};
+
+use bevy_app::{App, Plugin};
+use bevy_asset::{load_internal_asset, AssetId, Handle};
+use bevy_ecs::{
+    entity::{Entity, EntityHashMap},
+    prelude::*,
+};
+use bevy_math::{Affine3A, Mat4, Vec4};
+use bevy_reflect::{std_traits::ReflectDefault, Reflect};
+use bevy_render::{
+    color::Color,
+    prelude::SpatialBundle,
+    render_asset::RenderAssets,
+    render_resource::{
+        binding_types, BindGroupLayoutEntryBuilder, BindingResource, BufferBindingType, FilterMode,
+        Sampler, SamplerBindingType, SamplerDescriptor, Shader, ShaderType, StorageBuffer,
+        TextureSampleType, TextureView,
+    },
+    renderer::{RenderDevice, RenderQueue},
+    texture::{FallbackImage, Image},
+    view::ViewVisibility,
+    Extract, ExtractSchedule, Render, RenderApp, RenderSet,
+};
+use bevy_transform::prelude::GlobalTransform;
+use bevy_utils::{tracing::warn, HashMap};
+
+use crate::{
+    binding_arrays_are_usable, prepare_clusters, CLUSTERED_FORWARD_STORAGE_BUFFER_COUNT,
+    MAX_VIEW_LIGHT_PROBES, STANDARD_MATERIAL_FRAGMENT_SHADER_MIN_TEXTURE_BINDINGS,
+};
+
+/// A handle to the decal shader.
+pub const DECAL_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(7358291645017426093);
+
+/// The maximum number of different textures that the decals of the scene can use.
+///
+/// This is the size of the binding array of decal textures.
+pub const MAX_DECAL_TEXTURES: usize = 16;
+
+/// Adds support for [`Decal`]s.
+pub struct DecalPlugin;
+
+/// A texture projected onto the surfaces inside its projection box.
+///
+/// The decal projects its textures along the −Z axis of its entity, onto the
+/// surfaces inside its projection box: a unit cube centered on the origin,
+/// which the transform of the entity scales, rotates, and translates. The
+/// textures use the same conventions as the ones of
+/// [`crate::StandardMaterial`].
+///
+/// Decals aren't supported on WebGL2 or WebGPU.
+#[derive(Component, Clone, Debug, Reflect)]
+#[reflect(Component, Default)]
+pub struct Decal {
+    /// The color of the decal, multiplied by the color of
+    /// [`Decal::base_color_texture`].
+    ///
+    /// The alpha channel controls the opacity of the whole decal, including
+    /// its normal map and its occlusion, roughness and metallic texture.
+    ///
+    /// Defaults to [`Color::WHITE`].
+    pub base_color: Color,
+
+    /// The color texture of the decal.
+    ///
+    /// Its alpha channel is multiplied with the alpha of
+    /// [`Decal::base_color`].
+    pub base_color_texture: Option<Handle<Image>>,
+
+    /// The normal map of the decal, in the tangent space of the projection box,
+    /// with green pointing toward +Y.
+    ///
+    /// Like the normal maps of [`crate::StandardMaterial`], it must be loaded
+    /// in a linear color space.
+    pub normal_map_texture: Option<Handle<Image>>,
+
+    /// A texture that stores the ambient occlusion of the decal in its red
+    /// channel, its perceptual roughness in its green channel, and its metallic
+    /// factor in its blue channel.
+    ///
+    /// It replaces the roughness and the metallic factor of the surface, and
+    /// darkens its ambient occlusion.
+    pub occlusion_roughness_metallic_texture: Option<Handle<Image>>,
+
+    /// The order in which overlapping decals are applied: decals with a higher
+    /// sort order are drawn on top of the ones with a lower sort order.
+    ///
+    /// Defaults to 0.
+    pub sort_order: i32,
+
+    /// The fraction of the depth of the projection box, at its front and at
+    /// its back, over which the decal fades out.
+    ///
+    /// A value of 0.0 cuts the decal abruptly at the faces of the box.
+    ///
+    /// Defaults to 0.1.
+    pub depth_fade: f32,
+
+    /// How the decal fades out on the surfaces turned away from it.
+    ///
+    /// The decal is fully applied where the cosine of the angle between the
+    /// normal of the surface and the +Z axis of the projection box is above
+    /// this value, and fades out as the cosine goes down to 0.0. The surfaces
+    /// facing away from the projection never receive the decal.
+    ///
+    /// Defaults to 0.25.
+    pub angle_fade: f32,
+}
+
+impl Default for Decal {
+    fn default() -> Self {
+        Self {
+            base_color: Color::WHITE,
+            base_color_texture: None,
+            normal_map_texture: None,
+            occlusion_roughness_metallic_texture: None,
+            sort_order: 0,
+            depth_fade: 0.1,
+            angle_fade: 0.25,
+        }
+    }
+}
+
+/// A bundle that contains everything needed to project a [`Decal`].
+#[derive(Bundle, Default)]
+pub struct DecalBundle {
+    /// The decal and its textures.
+    pub decal: Decal,
+    /// Contains the transform of the projection box of the decal.
+    pub spatial: SpatialBundle,
+}
+
+/// A decal extracted to the render world.
+struct ExtractedDecal {
+    entity: Entity,
+    inverse_transform: Mat4,
+    base_color: Vec4,
+    base_color_texture: Option<AssetId<Image>>,
+    normal_map_texture: Option<AssetId<Image>>,
+    occlusion_roughness_metallic_texture: Option<AssetId<Image>>,
+    depth_fade: f32,
+    angle_fade: f32,
+}
+
+/// A GPU type that stores information about a decal.
+#[derive(Clone, Copy, ShaderType, Default)]
+struct GpuDecal {
+    /// The transform from world space to the space of the projection box.
+    ///
+    /// Like the one of light probes, it's transposed to fit in three `Vec4`s.
+    inverse_transpose_transform: [Vec4; 3],
+
+    /// The color of the decal, in linear space.
+    base_color: Vec4,
+
+    /// The index of each texture of the decal in the binding array of decal
+    /// textures, or -1 if the decal doesn't have that texture.
+    base_color_texture_index: i32,
+    normal_map_texture_index: i32,
+    occlusion_roughness_metallic_texture_index: i32,
+
+    depth_fade: f32,
+    angle_fade: f32,
+}
+
+/// The GPU buffer of all the decals that the clusters of the views refer to.
+#[derive(ShaderType, Default)]
+struct GpuDecals {
+    #[size(runtime)]
+    data: Vec<GpuDecal>,
+}
+
+/// The decals of the scene, ready to be bound to the PBR shaders.
+#[derive(Resource)]
+pub struct RenderDecals {
+    /// The decals extracted from the main world this frame.
+    decals: Vec<ExtractedDecal>,
+
+    /// The index of each decal entity in the GPU buffer of decals.
+    ///
+    /// The cluster index lists refer to decals by these indices.
+    pub(crate) entity_to_index: EntityHashMap<usize>,
+
+    /// The textures of the decals, in the order of the binding array.
+    binding_index_to_textures: Vec<AssetId<Image>>,
+
+    /// The reverse of `binding_index_to_textures`.
+    texture_to_binding_index: HashMap<AssetId<Image>, i32>,
+
+    buffer: StorageBuffer<GpuDecals>,
+
+    /// The sampler shared by all the decal textures.
+    sampler: Sampler,
+}
+
+/// All the bind group entries necessary for PBR shaders to access the decals.
+pub(crate) struct RenderDecalsBindGroupEntries<'a> {
+    /// The GPU buffer of decals.
+    pub(crate) decals: BindingResource<'a>,
+
+    /// A texture view of each decal texture, padded to [`MAX_DECAL_TEXTURES`]
+    /// with fallback textures.
+    ///
+    /// This is a vector of `wgpu::TextureView`s. But we don't want to import
+    /// `wgpu` in this crate, so we refer to it indirectly like this.
+    pub(crate) texture_views: Vec<&'a <TextureView as Deref>::Target>,
+
+    /// The sampler used to sample all the decal textures.
+    pub(crate) sampler: &'a Sampler,
+}
+
+impl Plugin for DecalPlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(app, DECAL_SHADER_HANDLE, "decal.wgsl", Shader::from_wgsl);
+
+        app.register_type::<Decal>();
+    }
+
+    fn finish(&self, app: &mut App) {
+        let Ok(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        render_app
+            .init_resource::<RenderDecals>()
+            .add_systems(ExtractSchedule, extract_decals)
+            .add_systems(
+                Render,
+                prepare_decals
+                    .in_set(RenderSet::PrepareResources)
+                    .before(prepare_clusters),
+            );
+    }
+}
+
+impl FromWorld for RenderDecals {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+
+        let mut buffer = StorageBuffer::default();
+        buffer.set_label(Some("decals_buffer"));
+
+        Self {
+            decals: Vec::new(),
+            entity_to_index: EntityHashMap::default(),
+            binding_index_to_textures: Vec::new(),
+            texture_to_binding_index: HashMap::new(),
+            buffer,
+            sampler: render_device.create_sampler(&SamplerDescriptor {
+                label: Some("decal_sampler"),
+                mag_filter: FilterMode::Linear,
+                min_filter: FilterMode::Linear,
+                mipmap_filter: FilterMode::Linear,
+                ..SamplerDescriptor::default()
+            }),
+        }
+    }
+}
+
+/// Returns the radius of the sphere centered on the decal that bounds its
+/// projection box, for the assignment of the decal to clusters.
+pub(crate) fn decal_bounding_radius(transform: &GlobalTransform) -> f32 {
+    let Affine3A {
+        matrix3: axes,
+        translation: _,
+    } = transform.affine();
+    // The farthest corner of the unit cube from its center, for any transform
+    // including shears.
+    [
+        axes.x_axis + axes.y_axis + axes.z_axis,
+        axes.x_axis + axes.y_axis - axes.z_axis,
+        axes.x_axis - axes.y_axis + axes.z_axis,
+        axes.x_axis - axes.y_axis - axes.z_axis,
+    ]
+    .iter()
+    .map(|corner| corner.length())
+    .fold(0.0, f32::max)
+        * 0.5
+}
+
+/// Many things can prevent decals from being applied. This function checks
+/// that:
+///
+/// 1. Binding arrays are usable, see [`binding_arrays_are_usable`].
+///
+/// 2. Storage buffers are available for the decals in addition to the
+///    clustered forward buffers, since decals are only assigned to clusters
+///    when the cluster buffers are storage buffers.
+///
+/// 3. There are enough texture bindings available in the fragment shader for
+///    the binding array of decal textures, on top of the light probes.
+pub(crate) fn clustered_decals_are_usable(render_device: &RenderDevice) -> bool {
+    binding_arrays_are_usable(render_device)
+        && matches!(
+            render_device
+                .get_supported_read_only_binding_type(CLUSTERED_FORWARD_STORAGE_BUFFER_COUNT + 1),
+            BufferBindingType::Storage { .. }
+        )
+        && render_device.limits().max_sampled_textures_per_shader_stage
+            >= (STANDARD_MATERIAL_FRAGMENT_SHADER_MIN_TEXTURE_BINDINGS
+                + MAX_VIEW_LIGHT_PROBES * 3
+                + MAX_DECAL_TEXTURES) as u32
+}
+
+/// Returns the bind group layout entries for the decal buffer, the binding
+/// array of decal textures, and their sampler respectively.
+pub(crate) fn get_decal_bind_group_layout_entries() -> [BindGroupLayoutEntryBuilder; 3] {
+    [
+        binding_types::storage_buffer_read_only::<GpuDecals>(false),
+        binding_types::texture_2d(TextureSampleType::Float { filterable: true })
+            .count(NonZeroU32::new(MAX_DECAL_TEXTURES as _).unwrap()),
+        binding_types::sampler(SamplerBindingType::Filtering),
+    ]
+}
+
+/// Extracts the visible decals of the scene.
+fn extract_decals(
+    mut render_decals: ResMut<RenderDecals>,
+    decals: Extract<Query<(Entity, &Decal, &GlobalTransform, &ViewVisibility)>>,
+) {
+    render_decals.decals.clear();
+    render_decals.decals.extend(
+        decals
+            .iter()
+            .filter(|(.., view_visibility)| view_visibility.get())
+            .map(|(entity, decal, transform, _)| ExtractedDecal {
+                entity,
+                inverse_transform: transform.compute_matrix().inverse(),
+                base_color: decal.base_color.as_linear_rgba_f32().into(),
+                base_color_texture: decal.base_color_texture.as_ref().map(Handle::id),
+                normal_map_texture: decal.normal_map_texture.as_ref().map(Handle::id),
+                occlusion_roughness_metallic_texture: decal
+                    .occlusion_roughness_metallic_texture
+                    .as_ref()
+                    .map(Handle::id),
+                depth_fade: decal.depth_fade,
+                angle_fade: decal.angle_fade,
+            }),
+    );
+}
+
+/// Uploads the extracted decals to the GPU, and collects their textures into
+/// the binding array.
+fn prepare_decals(
+    render_decals: ResMut<RenderDecals>,
+    images: Res<RenderAssets<Image>>,
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+    mut max_decal_textures_warning_emitted: Local<bool>,
+) {
+    let RenderDecals {
+        decals,
+        entity_to_index,
+        binding_index_to_textures,
+        texture_to_binding_index,
+        buffer,
+        ..
+    } = render_decals.into_inner();
+
+    entity_to_index.clear();
+    binding_index_to_textures.clear();
+    texture_to_binding_index.clear();
+    let gpu_decals = &mut buffer.get_mut().data;
+    gpu_decals.clear();
+
+    for decal in decals.iter() {
+        // Every extracted decal gets an index, since the clusters may refer to
+        // it, even if it isn't applied.
+        entity_to_index.insert(decal.entity, gpu_decals.len());
+
+        let textures = [
+            decal.base_color_texture,
+            decal.normal_map_texture,
+            decal.occlusion_roughness_metallic_texture,
+        ];
+        let mut texture_indices = [-1; 3];
+        let mut textures_ready = true;
+        for (texture, texture_index) in textures.iter().zip(&mut texture_indices) {
+            let Some(texture) = *texture else {
+                continue;
+            };
+            if images.get(texture).is_none() {
+                textures_ready = false;
+                continue;
+            }
+            let next_binding_index = binding_index_to_textures.len();
+            if next_binding_index >= MAX_DECAL_TEXTURES
+                && !texture_to_binding_index.contains_key(&texture)
+            {
+                if !*max_decal_textures_warning_emitted {
+                    warn!("MAX_DECAL_TEXTURES ({}) exceeded", MAX_DECAL_TEXTURES);
+                    *max_decal_textures_warning_emitted = true;
+                }
+                textures_ready = false;
+                continue;
+            }
+            *texture_index = *texture_to_binding_index.entry(texture).or_insert_with(|| {
+                binding_index_to_textures.push(texture);
+                next_binding_index as i32
+            });
+        }
+
+        // A decal whose textures aren't loaded yet is fully transparent.
+        let inverse_transpose_transform = decal.inverse_transform.transpose();
+        gpu_decals.push(GpuDecal {
+            inverse_transpose_transform: [
+                inverse_transpose_transform.x_axis,
+                inverse_transpose_transform.y_axis,
+                inverse_transpose_transform.z_axis,
+            ],
+            base_color: if textures_ready {
+                decal.base_color
+            } else {
+                Vec4::ZERO
+            },
+            base_color_texture_index: texture_indices[0],
+            normal_map_texture_index: texture_indices[1],
+            occlusion_roughness_metallic_texture_index: texture_indices[2],
+            depth_fade: decal.depth_fade,
+            angle_fade: decal.angle_fade,
+        });
+    }
+
+    buffer.write_buffer(&render_device, &render_queue);
+}
+
+impl RenderDecals {
+    /// Returns the bindings of the decal buffer, the binding array of decal
+    /// textures, and their sampler.
+    pub(crate) fn bind_group_entries<'a>(
+        &'a self,
+        images: &'a RenderAssets<Image>,
+        fallback_image: &'a FallbackImage,
+    ) -> Option<RenderDecalsBindGroupEntries<'a>> {
+        let mut texture_views: Vec<_> = self
+            .binding_index_to_textures
+            .iter()
+            .map(|&texture| match images.get(texture) {
+                Some(image) => &*image.texture_view,
+                None => &*fallback_image.d2.texture_view,
+            })
+            .collect();
+
+        // Pad out the bindings to the size of the binding array using fallback
+        // textures. This is necessary on D3D12 and Metal.
+        texture_views.resize(MAX_DECAL_TEXTURES, &*fallback_image.d2.texture_view);
+
+        Some(RenderDecalsBindGroupEntries {
+            decals: self.buffer.binding()?,
+            texture_views,
+            sampler: &self.sampler,
+        })
+    }
+}
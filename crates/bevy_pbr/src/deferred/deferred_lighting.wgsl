@@ -8,6 +8,10 @@
     mesh_view_bindings::deferred_prepass_texture,
 }
 
+#ifdef CLUSTERED_DECALS_ARE_USABLE
+#import bevy_pbr::decal::apply_decals
+#endif
+
 #ifdef SCREEN_SPACE_AMBIENT_OCCLUSION
 #import bevy_pbr::mesh_view_bindings::screen_space_ambient_occlusion_texture
 #import bevy_pbr::gtao_utils::gtao_multibounce
@@ -57,6 +61,9 @@ fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
 #endif
 
     var pbr_input = pbr_input_from_deferred_gbuffer(frag_coord, deferred_data);
+#ifdef CLUSTERED_DECALS_ARE_USABLE
+    pbr_input = apply_decals(pbr_input);
+#endif
     var output_color = vec4(0.0);
 
     // NOTE: Unlit bit not set means == 0 is true, so the true case is if lit
@@ -10,6 +10,7 @@ use bevy_core_pipeline::{
     deferred::{
         copy_lighting_id::DeferredLightingIdDepthTexture, DEFERRED_LIGHTING_PASS_ID_DEPTH_FORMAT,
     },
+    oit::OrderIndependentTransparencySettings,
     prepass::{DeferredPrepass, DepthPrepass, MotionVectorPrepass, NormalPrepass},
     tonemapping::{DebandDither, Tonemapping},
 };
@@ -289,6 +290,14 @@ impl SpecializedRenderPipeline for DeferredLightingLayout {
             shader_defs.push("IRRADIANCE_VOLUME".into());
         }
 
+        if self.mesh_pipeline.clustered_decals_are_usable {
+            shader_defs.push("CLUSTERED_DECALS_ARE_USABLE".into());
+        }
+
+        if self.mesh_pipeline.clustered_area_lights_are_usable {
+            shader_defs.push("CLUSTERED_AREA_LIGHTS_ARE_USABLE".into());
+        }
+
         if key.contains(MeshPipelineKey::NORMAL_PREPASS) {
             shader_defs.push("NORMAL_PREPASS".into());
         }
@@ -414,6 +423,7 @@ pub fn prepare_deferred_lighting_pipelines(
             ),
             Has<RenderViewLightProbes<EnvironmentMapLight>>,
             Has<RenderViewLightProbes<IrradianceVolume>>,
+            Has<OrderIndependentTransparencySettings>,
         ),
         With<DeferredPrepass>,
     >,
@@ -428,6 +438,7 @@ pub fn prepare_deferred_lighting_pipelines(
         (normal_prepass, depth_prepass, motion_vector_prepass),
         has_environment_maps,
         has_irradiance_volumes,
+        oit,
     ) in &views
     {
         let mut view_key = MeshPipelineKey::from_hdr(view.hdr);
@@ -485,6 +496,10 @@ pub fn prepare_deferred_lighting_pipelines(
             view_key |= MeshPipelineKey::IRRADIANCE_VOLUME;
         }
 
+        if oit {
+            view_key |= MeshPipelineKey::OIT_ENABLED;
+        }
+
         match shadow_filter_method.unwrap_or(&ShadowFilteringMethod::default()) {
             ShadowFilteringMethod::Hardware2x2 => {
                 view_key |= MeshPipelineKey::SHADOW_FILTER_METHOD_HARDWARE_2X2;
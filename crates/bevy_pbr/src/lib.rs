@@ -3,7 +3,10 @@
 
 pub mod wireframe;
 
+mod area_light;
+mod atmosphere;
 mod bundle;
+mod decal;
 pub mod deferred;
 mod extended_material;
 mod fog;
@@ -13,11 +16,16 @@ mod lightmap;
 mod material;
 mod parallax;
 mod pbr_material;
+mod planar_reflection;
 mod prepass;
 mod render;
 mod ssao;
+mod ssr;
 
+pub use area_light::*;
+pub use atmosphere::*;
 pub use bundle::*;
+pub use decal::*;
 pub use extended_material::*;
 pub use fog::*;
 pub use light::*;
@@ -26,27 +34,35 @@ pub use lightmap::*;
 pub use material::*;
 pub use parallax::*;
 pub use pbr_material::*;
+pub use planar_reflection::*;
 pub use prepass::*;
 pub use render::*;
 pub use ssao::*;
+pub use ssr::*;
 
 pub mod prelude {
     #[doc(hidden)]
     pub use crate::{
+        area_light::{AreaLight, AreaLightBundle, AreaLightShape},
+        atmosphere::Atmosphere,
         bundle::{
             DirectionalLightBundle, MaterialMeshBundle, PbrBundle, PointLightBundle,
             SpotLightBundle,
         },
+        decal::{Decal, DecalBundle},
         fog::{FogFalloff, FogSettings},
         light::{light_consts, AmbientLight, DirectionalLight, PointLight, SpotLight},
         light_probe::{
-            environment_map::{EnvironmentMapLight, ReflectionProbeBundle},
+            capture::{IrradianceVolumeCapture, ReflectionProbeCapture},
+            environment_map::{EnvironmentMapLight, ReflectionProbe, ReflectionProbeBundle},
             LightProbe,
         },
         material::{Material, MaterialPlugin},
         parallax::ParallaxMappingMethod,
         pbr_material::StandardMaterial,
+        planar_reflection::{MirrorMaterial, PlanarReflection},
         ssao::ScreenSpaceAmbientOcclusionPlugin,
+        ssr::{ScreenSpaceReflectionsBundle, ScreenSpaceReflectionsSettings},
     };
 }
 
@@ -60,6 +76,14 @@ pub mod graph {
         /// Label for the screen space ambient occlusion render node.
         ScreenSpaceAmbientOcclusion,
         DeferredLightingPass,
+        /// Label for the screen space reflections pass.
+        ScreenSpaceReflections,
+        /// Label for the node that filters the captures of light probes, in the
+        /// main render graph.
+        LightProbeCapture,
+        /// Label for the node that renders the sky and the aerial perspective of
+        /// an [`Atmosphere`](crate::Atmosphere).
+        Atmosphere,
     }
 }
 
@@ -90,6 +114,8 @@ pub const UTILS_HANDLE: Handle<Shader> = Handle::weak_from_u128(1900548483293416
 pub const CLUSTERED_FORWARD_HANDLE: Handle<Shader> = Handle::weak_from_u128(166852093121196815);
 pub const PBR_LIGHTING_HANDLE: Handle<Shader> = Handle::weak_from_u128(14170772752254856967);
 pub const PBR_TRANSMISSION_HANDLE: Handle<Shader> = Handle::weak_from_u128(77319684653223658032);
+pub const PBR_SUBSURFACE_SCATTERING_HANDLE: Handle<Shader> =
+    Handle::weak_from_u128(13296185361712486209);
 pub const SHADOWS_HANDLE: Handle<Shader> = Handle::weak_from_u128(11350275143789590502);
 pub const SHADOW_SAMPLING_HANDLE: Handle<Shader> = Handle::weak_from_u128(3145627513789590502);
 pub const PBR_FRAGMENT_HANDLE: Handle<Shader> = Handle::weak_from_u128(2295049283805286543);
@@ -158,6 +184,12 @@ impl Plugin for PbrPlugin {
             "render/pbr_transmission.wgsl",
             Shader::from_wgsl
         );
+        load_internal_asset!(
+            app,
+            PBR_SUBSURFACE_SCATTERING_HANDLE,
+            "render/pbr_subsurface_scattering.wgsl",
+            Shader::from_wgsl
+        );
         load_internal_asset!(
             app,
             SHADOWS_HANDLE,
@@ -267,12 +299,17 @@ impl Plugin for PbrPlugin {
                     ..Default::default()
                 },
                 ScreenSpaceAmbientOcclusionPlugin,
+                ScreenSpaceReflectionsPlugin,
                 ExtractResourcePlugin::<AmbientLight>::default(),
                 FogPlugin,
                 ExtractResourcePlugin::<DefaultOpaqueRendererMethod>::default(),
                 ExtractComponentPlugin::<ShadowFilteringMethod>::default(),
                 LightmapPlugin,
                 LightProbePlugin,
+                DecalPlugin,
+                AreaLightPlugin,
+                PlanarReflectionPlugin,
+                AtmospherePlugin,
             ))
             .configure_sets(
                 PostUpdate,
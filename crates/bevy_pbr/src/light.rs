@@ -104,6 +104,14 @@ pub struct PointLight {
     pub range: f32,
     pub radius: f32,
     pub shadows_enabled: bool,
+    /// Whether this light casts soft shadows, whose penumbras widen with the distance from the
+    /// objects that cast them, like the shadows of real lights of size [`radius`](Self::radius).
+    ///
+    /// Soft shadows search the shadow map for the objects that cast them before filtering it, so
+    /// they are more expensive than hard shadows. They replace the
+    /// [`ShadowFilteringMethod`](crate::ShadowFilteringMethod) of the camera, and their noise is
+    /// smoothed by temporal anti-aliasing.
+    pub soft_shadows_enabled: bool,
     pub shadow_depth_bias: f32,
     /// A bias applied along the direction of the fragment's surface normal. It is scaled to the
     /// shadow map's texel size so that it can be small close to the camera and gets larger further
@@ -122,6 +130,7 @@ impl Default for PointLight {
             range: 20.0,
             radius: 0.0,
             shadows_enabled: false,
+            soft_shadows_enabled: false,
             shadow_depth_bias: Self::DEFAULT_SHADOW_DEPTH_BIAS,
             shadow_normal_bias: Self::DEFAULT_SHADOW_NORMAL_BIAS,
         }
@@ -158,6 +167,14 @@ pub struct SpotLight {
     pub range: f32,
     pub radius: f32,
     pub shadows_enabled: bool,
+    /// Whether this light casts soft shadows, whose penumbras widen with the distance from the
+    /// objects that cast them, like the shadows of real lights of size [`radius`](Self::radius).
+    ///
+    /// Soft shadows search the shadow map for the objects that cast them before filtering it, so
+    /// they are more expensive than hard shadows. They replace the
+    /// [`ShadowFilteringMethod`](crate::ShadowFilteringMethod) of the camera, and their noise is
+    /// smoothed by temporal anti-aliasing.
+    pub soft_shadows_enabled: bool,
     pub shadow_depth_bias: f32,
     /// A bias applied along the direction of the fragment's surface normal. It is scaled to the
     /// shadow map's texel size so that it can be small close to the camera and gets larger further
@@ -193,6 +210,7 @@ impl Default for SpotLight {
             range: 20.0,
             radius: 0.0,
             shadows_enabled: false,
+            soft_shadows_enabled: false,
             shadow_depth_bias: Self::DEFAULT_SHADOW_DEPTH_BIAS,
             shadow_normal_bias: Self::DEFAULT_SHADOW_NORMAL_BIAS,
             inner_angle: 0.0,
@@ -260,6 +278,16 @@ pub struct DirectionalLight {
     /// area.
     pub illuminance: f32,
     pub shadows_enabled: bool,
+    /// The angular diameter of the light in radians, for it to cast soft shadows, or `None` for
+    /// hard shadows.
+    ///
+    /// The penumbras of soft shadows widen with the distance from the objects that cast them, like
+    /// the shadows of the sun, whose angular diameter is about 0.0093 radians. Soft shadows search
+    /// the shadow map for the objects that cast them before filtering it, so they are more
+    /// expensive than hard shadows. They replace the
+    /// [`ShadowFilteringMethod`](crate::ShadowFilteringMethod) of the camera, and their noise is
+    /// smoothed by temporal anti-aliasing.
+    pub soft_shadow_size: Option<f32>,
     pub shadow_depth_bias: f32,
     /// A bias applied along the direction of the fragment's surface normal. It is scaled to the
     /// shadow map's texel size so that it is automatically adjusted to the orthographic projection.
@@ -272,6 +300,7 @@ impl Default for DirectionalLight {
             color: Color::rgb(1.0, 1.0, 1.0),
             illuminance: light_consts::lux::AMBIENT_DAYLIGHT,
             shadows_enabled: false,
+            soft_shadow_size: None,
             shadow_depth_bias: Self::DEFAULT_SHADOW_DEPTH_BIAS,
             shadow_normal_bias: Self::DEFAULT_SHADOW_NORMAL_BIAS,
         }
@@ -943,6 +972,12 @@ pub struct VisiblePointLights {
     pub(crate) entities: Vec<Entity>,
     pub point_light_count: usize,
     pub spot_light_count: usize,
+    /// The number of [`crate::Decal`]s, which follow the point and spot lights
+    /// in the entities.
+    pub decal_count: usize,
+    /// The number of [`crate::AreaLight`]s, which follow the decals in the
+    /// entities.
+    pub area_light_count: usize,
 }
 
 impl VisiblePointLights {
@@ -1235,6 +1270,10 @@ pub(crate) struct PointLightAssignmentData {
     range: f32,
     shadows_enabled: bool,
     spot_light_angle: Option<f32>,
+    /// The sort order of the decal, if this is a decal rather than a light.
+    decal_sort_order: Option<i32>,
+    /// Whether this is an area light rather than a point or spot light.
+    area_light: bool,
     render_layers: RenderLayers,
 }
 
@@ -1293,6 +1332,20 @@ pub(crate) fn assign_lights_to_clusters(
         Option<&RenderLayers>,
         &ViewVisibility,
     )>,
+    decals_query: Query<(
+        Entity,
+        &GlobalTransform,
+        &Decal,
+        Option<&RenderLayers>,
+        &ViewVisibility,
+    )>,
+    area_lights_query: Query<(
+        Entity,
+        &GlobalTransform,
+        &AreaLight,
+        Option<&RenderLayers>,
+        &ViewVisibility,
+    )>,
     mut lights: Local<Vec<PointLightAssignmentData>>,
     mut cluster_aabb_spheres: Local<Vec<Option<Sphere>>>,
     mut max_point_lights_warning_emitted: Local<bool>,
@@ -1317,6 +1370,8 @@ pub(crate) fn assign_lights_to_clusters(
                         shadows_enabled: point_light.shadows_enabled,
                         range: point_light.range,
                         spot_light_angle: None,
+                        decal_sort_order: None,
+                        area_light: false,
                         render_layers: maybe_layers.copied().unwrap_or_default(),
                     }
                 },
@@ -1334,12 +1389,61 @@ pub(crate) fn assign_lights_to_clusters(
                         shadows_enabled: spot_light.shadows_enabled,
                         range: spot_light.range,
                         spot_light_angle: Some(spot_light.outer_angle),
+                        decal_sort_order: None,
+                        area_light: false,
                         render_layers: maybe_layers.copied().unwrap_or_default(),
                     }
                 },
             ),
     );
 
+    // Decals follow the lights in the clusters, in increasing sort order. They're
+    // only assigned when the shaders can apply them.
+    if clustered_decals_are_usable(&render_device) {
+        let decals_start = lights.len();
+        lights.extend(
+            decals_query
+                .iter()
+                .filter(|(.., visibility)| visibility.get())
+                .map(|(entity, transform, decal, maybe_layers, _visibility)| {
+                    PointLightAssignmentData {
+                        entity,
+                        transform: *transform,
+                        shadows_enabled: false,
+                        range: decal_bounding_radius(transform),
+                        spot_light_angle: None,
+                        decal_sort_order: Some(decal.sort_order),
+                        area_light: false,
+                        render_layers: maybe_layers.copied().unwrap_or_default(),
+                    }
+                }),
+        );
+        lights[decals_start..].sort_by_key(|decal| (decal.decal_sort_order, decal.entity));
+    }
+
+    // Area lights follow the decals in the clusters.
+    if clustered_area_lights_are_usable(&render_device) {
+        lights.extend(
+            area_lights_query
+                .iter()
+                .filter(|(.., visibility)| visibility.get())
+                .map(
+                    |(entity, transform, area_light, maybe_layers, _visibility)| {
+                        PointLightAssignmentData {
+                            entity,
+                            transform: GlobalTransform::from_translation(transform.translation()),
+                            shadows_enabled: false,
+                            range: area_light.range,
+                            spot_light_angle: None,
+                            decal_sort_order: None,
+                            area_light: true,
+                            render_layers: maybe_layers.copied().unwrap_or_default(),
+                        }
+                    },
+                ),
+        );
+    }
+
     let clustered_forward_buffer_binding_type =
         render_device.get_supported_read_only_binding_type(CLUSTERED_FORWARD_STORAGE_BUFFER_COUNT);
     let supports_storage_buffers = matches!(
@@ -1560,6 +1664,8 @@ pub(crate) fn assign_lights_to_clusters(
             lights.entities.clear();
             lights.point_light_count = 0;
             lights.spot_light_count = 0;
+            lights.decal_count = 0;
+            lights.area_light_count = 0;
         }
         let cluster_count =
             (clusters.dimensions.x * clusters.dimensions.y * clusters.dimensions.z) as usize;
@@ -1643,8 +1749,12 @@ pub(crate) fn assign_lights_to_clusters(
                 }
 
                 // NOTE: The light intersects the frustum so it must be visible and part of the global set
-                global_lights.entities.insert(light.entity);
-                visible_lights.push(light.entity);
+                // Decals and area lights are only clustered, and have neither shadows nor light
+                // data.
+                if light.decal_sort_order.is_none() && !light.area_light {
+                    global_lights.entities.insert(light.entity);
+                    visible_lights.push(light.entity);
+                }
 
                 // note: caching seems to be slower than calling twice for this aabb calculation
                 let (light_aabb_xy_ndc_z_view_min, light_aabb_xy_ndc_z_view_max) =
@@ -1842,9 +1952,16 @@ pub(crate) fn assign_lights_to_clusters(
                             }
                         } else {
                             for _ in min_x..=max_x {
-                                // all clusters within range are affected by point lights
+                                // all clusters within range are affected by point lights, decals
+                                // and area lights
                                 clusters.lights[cluster_index].entities.push(light.entity);
-                                clusters.lights[cluster_index].point_light_count += 1;
+                                if light.decal_sort_order.is_some() {
+                                    clusters.lights[cluster_index].decal_count += 1;
+                                } else if light.area_light {
+                                    clusters.lights[cluster_index].area_light_count += 1;
+                                } else {
+                                    clusters.lights[cluster_index].point_light_count += 1;
+                                }
                                 cluster_index += clusters.dimensions.z as usize;
                             }
                         }
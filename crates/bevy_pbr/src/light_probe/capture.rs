@@ -0,0 +1,1136 @@
+//! Runtime capture of light probes.
+//!
+//! A [`ReflectionProbeCapture`] renders the surroundings of a reflection probe
+//! from its center into the six faces of a cubemap, with six cameras, and
+//! pre-filters the result on the GPU into the diffuse and specular cubemaps of
+//! the [`EnvironmentMapLight`] of the probe. This replaces the offline
+//! pre-filtering described in [`crate::environment_map`] for scenes whose
+//! reflections are only known at runtime.
+//!
+//! An [`IrradianceVolumeCapture`] moves the same six cameras through the voxels
+//! of an irradiance volume, one voxel per frame, and reduces each capture into
+//! the ambient cube of the voxel in the [`IrradianceVolume`] of the entity.
+//!
+//! A reflection probe capture can either run every frame, which keeps the
+//! reflections up to date with moving objects at the cost of rendering the
+//! scene six more times per probe, or run for a number of frames and then
+//! stop, which bakes the probe. Irradiance volumes are always baked. In both
+//! cases, the baked textures are ordinary [`Image`] assets, which a tool step
+//! can read back and save.
+
+use std::f32::consts::FRAC_PI_2;
+
+use bevy_app::{App, Plugin, PostUpdate};
+use bevy_asset::{load_internal_asset, AssetId, Assets, Handle};
+use bevy_core_pipeline::{
+    core_3d::Camera3dBundle,
+    tonemapping::{DebandDither, Tonemapping},
+};
+use bevy_ecs::{
+    component::Component,
+    entity::Entity,
+    query::{With, Without},
+    reflect::ReflectComponent,
+    schedule::IntoSystemConfigs,
+    system::{Commands, Query, Res, ResMut, Resource},
+    world::{FromWorld, World},
+};
+use bevy_math::{UVec3, Vec3};
+use bevy_reflect::{std_traits::ReflectDefault, Reflect};
+use bevy_render::{
+    camera::{Camera, Exposure, PerspectiveProjection, Projection, RenderTarget},
+    render_asset::{RenderAssetUsages, RenderAssets},
+    render_graph::{Node, NodeRunError, RenderGraph, RenderGraphContext},
+    render_resource::{
+        binding_types::{
+            sampler, texture_2d_array, texture_cube, texture_storage_2d_array, texture_storage_3d,
+            uniform_buffer,
+        },
+        *,
+    },
+    renderer::{RenderContext, RenderDevice, RenderQueue},
+    texture::{CachedTexture, Image, ImageSampler, TextureCache, TextureFormatPixelInfo, Volume},
+    view::VisibilitySystems,
+    Extract, ExtractSchedule, Render, RenderApp, RenderSet,
+};
+use bevy_transform::{
+    components::{GlobalTransform, Transform},
+    TransformSystem,
+};
+use bevy_utils::prelude::default;
+
+use crate::{
+    binding_arrays_are_usable, environment_map::EnvironmentMapLight, graph::NodePbr,
+    irradiance_volume::IrradianceVolume,
+};
+
+/// A handle to the shader that filters the captures of light probes.
+pub const LIGHT_PROBE_CAPTURE_SHADER_HANDLE: Handle<Shader> =
+    Handle::weak_from_u128(297521453216340781);
+
+/// The width and height of each face of the diffuse cubemap of a reflection
+/// probe capture.
+const DIFFUSE_MAP_RESOLUTION: u32 = 32;
+
+/// The format of the faces, cubemaps, and voxels of a capture.
+const CAPTURE_TEXTURE_FORMAT: TextureFormat = TextureFormat::Rgba16Float;
+
+/// The directions and up vectors of the cameras that render the six faces of
+/// a capture, in the layer order of cubemaps.
+const FACE_ORIENTATIONS: [(Vec3, Vec3); 6] = [
+    (Vec3::X, Vec3::Y),
+    (Vec3::NEG_X, Vec3::Y),
+    (Vec3::Y, Vec3::Z),
+    (Vec3::NEG_Y, Vec3::NEG_Z),
+    (Vec3::NEG_Z, Vec3::Y),
+    (Vec3::Z, Vec3::Y),
+];
+
+/// Adds support for [`ReflectionProbeCapture`] and [`IrradianceVolumeCapture`].
+pub struct LightProbeCapturePlugin;
+
+/// Captures the surroundings of a reflection probe into its
+/// [`EnvironmentMapLight`] at runtime.
+///
+/// Add this component to an entity with a [`crate::LightProbe`], such as one
+/// spawned with a [`crate::environment_map::ReflectionProbeBundle`]. The
+/// capture inserts a new [`EnvironmentMapLight`] on the entity, which replaces
+/// any existing one, and renders the scene from the translation of the entity
+/// into it. The capture cameras are ordinary top-level [`Camera3dBundle`]
+/// entities, lit by the lights and light probes of the scene, including the
+/// probe itself, so that light bounces between probes over the frames of the
+/// capture.
+///
+/// Reflection probes are unsupported on WebGL2 and WebGPU, and so are their
+/// captures.
+#[derive(Clone, Copy, Debug, Component, Reflect)]
+#[reflect(Component, Default)]
+pub struct ReflectionProbeCapture {
+    /// The width and height of each face of the specular cubemap, in pixels.
+    ///
+    /// Defaults to 256.
+    pub resolution: u32,
+
+    /// The distance from the center of the probe to the near plane of the
+    /// capture cameras, in meters.
+    ///
+    /// Defaults to 0.1.
+    pub near: f32,
+
+    /// The number of frames left to capture, or `None` to capture every frame.
+    ///
+    /// When this reaches zero, the component removes itself and the probe
+    /// keeps the last capture. Capturing for several frames gives the
+    /// pipelines of the scene time to compile, and the light time to bounce
+    /// between probes.
+    ///
+    /// Defaults to 60 frames.
+    pub frames: Option<u32>,
+}
+
+/// Bakes the diffuse indirect light of the scene into the [`IrradianceVolume`]
+/// of a light probe at runtime.
+///
+/// Add this component to an entity with a [`crate::LightProbe`]. The capture
+/// inserts a new [`IrradianceVolume`] on the entity, which replaces any
+/// existing one, and renders a cubemap from the center of each of its voxels in
+/// turn, one voxel per frame. Each cubemap is reduced into the ambient cube of
+/// its voxel, as described in [`crate::irradiance_volume`].
+///
+/// As with [`ReflectionProbeCapture`], the capture cameras see the lights and
+/// light probes of the scene, including the irradiance volume itself, so each
+/// pass over the voxels adds a bounce of indirect light.
+///
+/// The captures need compute shaders and are unsupported on WebGL2 and WebGPU.
+#[derive(Clone, Copy, Debug, Component, Reflect)]
+#[reflect(Component, Default)]
+pub struct IrradianceVolumeCapture {
+    /// The number of voxels of the irradiance volume along each axis.
+    ///
+    /// Defaults to 8×8×8.
+    pub resolution: UVec3,
+
+    /// The width and height of each face of the cubemap captured at each
+    /// voxel, in pixels.
+    ///
+    /// Diffuse light varies slowly with direction, so this can stay small.
+    /// Defaults to 32.
+    pub cubemap_resolution: u32,
+
+    /// The distance from the center of each voxel to the near plane of the
+    /// capture cameras, in meters.
+    ///
+    /// Defaults to 0.1.
+    pub near: f32,
+
+    /// The number of passes over all the voxels left to capture.
+    ///
+    /// When this reaches zero, the component removes itself and the
+    /// irradiance volume keeps the last capture. The first pass only captures
+    /// the direct light, and the voxels captured before the pipelines of the
+    /// scene are compiled.
+    ///
+    /// Defaults to 2.
+    pub passes: u32,
+}
+
+impl Default for ReflectionProbeCapture {
+    fn default() -> Self {
+        Self {
+            resolution: 256,
+            near: 0.1,
+            frames: Some(60),
+        }
+    }
+}
+
+impl Default for IrradianceVolumeCapture {
+    fn default() -> Self {
+        Self {
+            resolution: UVec3::splat(8),
+            cubemap_resolution: 32,
+            near: 0.1,
+            passes: 2,
+        }
+    }
+}
+
+/// The six cameras of a capture and the images they render to.
+struct CaptureFaces {
+    resolution: u32,
+    cameras: [Entity; 6],
+    images: [Handle<Image>; 6],
+}
+
+/// Marks one of the six cameras of a capture.
+#[derive(Component)]
+struct CaptureCamera {
+    /// The light probe that owns the camera.
+    light_probe: Entity,
+}
+
+/// The images and cameras of an active [`ReflectionProbeCapture`].
+#[derive(Component)]
+struct ReflectionProbeCaptureTargets {
+    faces: CaptureFaces,
+    diffuse_map: Handle<Image>,
+    specular_map: Handle<Image>,
+}
+
+/// The images and cameras of an active [`IrradianceVolumeCapture`].
+#[derive(Component)]
+struct IrradianceVolumeCaptureTargets {
+    faces: CaptureFaces,
+    resolution: UVec3,
+    voxels: Handle<Image>,
+    /// The index of the voxel captured this frame, in X, then Y, then Z order.
+    voxel_index: u32,
+}
+
+/// The active captures, extracted to the render world.
+#[derive(Resource, Default)]
+struct ExtractedLightProbeCaptures {
+    reflection_probes: Vec<ExtractedReflectionProbeCapture>,
+    irradiance_volumes: Vec<ExtractedIrradianceVolumeCapture>,
+}
+
+struct ExtractedReflectionProbeCapture {
+    faces: [AssetId<Image>; 6],
+    diffuse_map: AssetId<Image>,
+    specular_map: AssetId<Image>,
+}
+
+struct ExtractedIrradianceVolumeCapture {
+    faces: [AssetId<Image>; 6],
+    voxels: AssetId<Image>,
+    voxel: UVec3,
+}
+
+/// The GPU resources of the active captures, ready for [`LightProbeCaptureNode`].
+#[derive(Resource, Default)]
+struct PreparedLightProbeCaptures {
+    reflection_probes: Vec<PreparedReflectionProbeCapture>,
+    irradiance_volumes: Vec<PreparedIrradianceVolumeCapture>,
+}
+
+struct PreparedReflectionProbeCapture {
+    resolution: u32,
+    faces: [Texture; 6],
+    radiance_cubemap: CachedTexture,
+    specular_map: Texture,
+    /// One bind group per mip level of the radiance cubemap, except the first.
+    downsample_bind_groups: Vec<BindGroup>,
+    /// One bind group per mip level of the specular map, except the first.
+    specular_bind_groups: Vec<BindGroup>,
+    diffuse_bind_group: BindGroup,
+}
+
+struct PreparedIrradianceVolumeCapture {
+    resolution: u32,
+    faces: [Texture; 6],
+    radiance_cubemap: CachedTexture,
+    bind_group: BindGroup,
+}
+
+#[derive(Resource)]
+struct LightProbeCapturePipelines {
+    downsample_bind_group_layout: BindGroupLayout,
+    filter_bind_group_layout: BindGroupLayout,
+    irradiance_volume_bind_group_layout: BindGroupLayout,
+    sampler: Sampler,
+    downsample_pipeline: CachedComputePipelineId,
+    specular_pipeline: CachedComputePipelineId,
+    diffuse_pipeline: CachedComputePipelineId,
+    irradiance_volume_pipeline: CachedComputePipelineId,
+}
+
+#[derive(Clone, Copy, ShaderType)]
+struct FilterSettings {
+    roughness: f32,
+}
+
+/// Copies the faces of the captures into cubemaps and filters them.
+struct LightProbeCaptureNode;
+
+impl Plugin for LightProbeCapturePlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(
+            app,
+            LIGHT_PROBE_CAPTURE_SHADER_HANDLE,
+            "capture.wgsl",
+            Shader::from_wgsl
+        );
+
+        app.register_type::<ReflectionProbeCapture>()
+            .register_type::<IrradianceVolumeCapture>()
+            .add_systems(
+                PostUpdate,
+                (
+                    despawn_stale_capture_cameras,
+                    update_reflection_probe_captures,
+                    update_irradiance_volume_captures,
+                )
+                    .chain()
+                    .after(TransformSystem::TransformPropagate)
+                    .before(VisibilitySystems::UpdateProjectionFrusta),
+            );
+    }
+
+    fn finish(&self, app: &mut App) {
+        let Ok(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        if !binding_arrays_are_usable(render_app.world.resource::<RenderDevice>()) {
+            return;
+        }
+
+        render_app
+            .init_resource::<LightProbeCapturePipelines>()
+            .init_resource::<ExtractedLightProbeCaptures>()
+            .init_resource::<PreparedLightProbeCaptures>()
+            .add_systems(ExtractSchedule, extract_light_probe_captures)
+            .add_systems(
+                Render,
+                prepare_light_probe_captures.in_set(RenderSet::PrepareBindGroups),
+            );
+
+        // The captures are filtered after all the cameras, including the
+        // capture cameras, have rendered. The main cameras therefore see the
+        // capture of the previous frame.
+        let mut render_graph = render_app.world.resource_mut::<RenderGraph>();
+        render_graph.add_node(NodePbr::LightProbeCapture, LightProbeCaptureNode);
+        render_graph.add_node_edge(
+            bevy_render::graph::CameraDriverLabel,
+            NodePbr::LightProbeCapture,
+        );
+    }
+}
+
+impl CaptureFaces {
+    /// Spawns the six cameras of a capture of `light_probe` at `origin`.
+    fn spawn(
+        commands: &mut Commands,
+        images: &mut Assets<Image>,
+        light_probe: Entity,
+        resolution: u32,
+        near: f32,
+        origin: Vec3,
+    ) -> Self {
+        let images: [Handle<Image>; 6] = std::array::from_fn(|_| {
+            images.add(new_capture_image(
+                resolution,
+                1,
+                1,
+                TextureUsages::RENDER_ATTACHMENT | TextureUsages::COPY_SRC,
+            ))
+        });
+        let cameras = std::array::from_fn(|face| {
+            let transform = face_transform(origin, face);
+            commands
+                .spawn((
+                    Camera3dBundle {
+                        camera: Camera {
+                            target: RenderTarget::Image(images[face].clone()),
+                            order: -1,
+                            hdr: true,
+                            ..default()
+                        },
+                        projection: Projection::Perspective(PerspectiveProjection {
+                            fov: FRAC_PI_2,
+                            aspect_ratio: 1.0,
+                            near,
+                            ..default()
+                        }),
+                        tonemapping: Tonemapping::None,
+                        deband_dither: DebandDither::Disabled,
+                        transform,
+                        global_transform: GlobalTransform::from(transform),
+                        ..default()
+                    },
+                    CaptureCamera { light_probe },
+                ))
+                .id()
+        });
+
+        Self {
+            resolution,
+            cameras,
+            images,
+        }
+    }
+
+    /// Moves the cameras to `origin`.
+    fn move_to(
+        &self,
+        cameras: &mut Query<(&mut Transform, &mut GlobalTransform), With<CaptureCamera>>,
+        origin: Vec3,
+    ) {
+        for (face, camera) in self.cameras.iter().enumerate() {
+            if let Ok((mut transform, mut global_transform)) = cameras.get_mut(*camera) {
+                *transform = face_transform(origin, face);
+                *global_transform = GlobalTransform::from(*transform);
+            }
+        }
+    }
+
+    fn ids(&self) -> [AssetId<Image>; 6] {
+        std::array::from_fn(|face| self.images[face].id())
+    }
+}
+
+/// Despawns the cameras of the captures that were removed or replaced, and
+/// the targets of the captures that were removed.
+fn despawn_stale_capture_cameras(
+    mut commands: Commands,
+    cameras: Query<(Entity, &CaptureCamera)>,
+    reflection_probes: Query<&ReflectionProbeCaptureTargets>,
+    irradiance_volumes: Query<&IrradianceVolumeCaptureTargets>,
+    stale_reflection_probes: Query<
+        Entity,
+        (
+            With<ReflectionProbeCaptureTargets>,
+            Without<ReflectionProbeCapture>,
+        ),
+    >,
+    stale_irradiance_volumes: Query<
+        Entity,
+        (
+            With<IrradianceVolumeCaptureTargets>,
+            Without<IrradianceVolumeCapture>,
+        ),
+    >,
+) {
+    for (entity, camera) in &cameras {
+        let owned = reflection_probes
+            .get(camera.light_probe)
+            .is_ok_and(|targets| targets.faces.cameras.contains(&entity))
+            || irradiance_volumes
+                .get(camera.light_probe)
+                .is_ok_and(|targets| targets.faces.cameras.contains(&entity));
+        if !owned {
+            commands.entity(entity).despawn();
+        }
+    }
+
+    for entity in &stale_reflection_probes {
+        commands
+            .entity(entity)
+            .remove::<ReflectionProbeCaptureTargets>();
+    }
+    for entity in &stale_irradiance_volumes {
+        commands
+            .entity(entity)
+            .remove::<IrradianceVolumeCaptureTargets>();
+    }
+}
+
+/// Creates the images and cameras of new reflection probe captures, moves the
+/// cameras along with their probes, and ends the finished captures.
+fn update_reflection_probe_captures(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    mut probes: Query<
+        (
+            Entity,
+            &GlobalTransform,
+            &mut ReflectionProbeCapture,
+            Option<&ReflectionProbeCaptureTargets>,
+        ),
+        Without<CaptureCamera>,
+    >,
+    mut cameras: Query<(&mut Transform, &mut GlobalTransform), With<CaptureCamera>>,
+) {
+    for (entity, probe_transform, mut capture, targets) in &mut probes {
+        if capture.frames == Some(0) {
+            commands
+                .entity(entity)
+                .remove::<(ReflectionProbeCapture, ReflectionProbeCaptureTargets)>();
+            continue;
+        }
+
+        // The cameras only follow the translation of the probe, since the
+        // cubemap is sampled in world space.
+        let origin = probe_transform.translation();
+        let resolution = capture.resolution.max(1);
+        match targets {
+            Some(targets) if targets.faces.resolution == resolution => {
+                targets.faces.move_to(&mut cameras, origin);
+            }
+            _ => {
+                let faces = CaptureFaces::spawn(
+                    &mut commands,
+                    &mut images,
+                    entity,
+                    resolution,
+                    capture.near,
+                    origin,
+                );
+                let diffuse_map = images.add(new_capture_image(
+                    DIFFUSE_MAP_RESOLUTION,
+                    6,
+                    1,
+                    TextureUsages::STORAGE_BINDING,
+                ));
+                let specular_map = images.add(new_capture_image(
+                    resolution,
+                    6,
+                    mip_level_count(resolution),
+                    TextureUsages::STORAGE_BINDING | TextureUsages::COPY_DST,
+                ));
+
+                commands.entity(entity).insert((
+                    EnvironmentMapLight {
+                        diffuse_map: diffuse_map.clone(),
+                        specular_map: specular_map.clone(),
+                        intensity: capture_intensity(),
+                    },
+                    ReflectionProbeCaptureTargets {
+                        faces,
+                        diffuse_map,
+                        specular_map,
+                    },
+                ));
+            }
+        }
+
+        if let Some(frames) = &mut capture.frames {
+            *frames -= 1;
+        }
+    }
+}
+
+/// Creates the images and cameras of new irradiance volume captures, moves
+/// the cameras to the next voxel, and ends the finished captures.
+fn update_irradiance_volume_captures(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    mut volumes: Query<
+        (
+            Entity,
+            &GlobalTransform,
+            &mut IrradianceVolumeCapture,
+            Option<&mut IrradianceVolumeCaptureTargets>,
+        ),
+        Without<CaptureCamera>,
+    >,
+    mut cameras: Query<(&mut Transform, &mut GlobalTransform), With<CaptureCamera>>,
+) {
+    for (entity, volume_transform, mut capture, targets) in &mut volumes {
+        let resolution = capture.resolution.max(UVec3::ONE);
+        let cubemap_resolution = capture.cubemap_resolution.max(1);
+
+        if let Some(mut targets) = targets.filter(|targets| {
+            targets.resolution == resolution && targets.faces.resolution == cubemap_resolution
+        }) {
+            targets.voxel_index += 1;
+            if targets.voxel_index == resolution.x * resolution.y * resolution.z {
+                targets.voxel_index = 0;
+                capture.passes = capture.passes.saturating_sub(1);
+            }
+            if capture.passes == 0 {
+                commands
+                    .entity(entity)
+                    .remove::<(IrradianceVolumeCapture, IrradianceVolumeCaptureTargets)>();
+                continue;
+            }
+
+            targets.faces.move_to(
+                &mut cameras,
+                voxel_center(volume_transform, resolution, targets.voxel_index),
+            );
+            continue;
+        }
+
+        if capture.passes == 0 {
+            commands.entity(entity).remove::<IrradianceVolumeCapture>();
+            continue;
+        }
+
+        let faces = CaptureFaces::spawn(
+            &mut commands,
+            &mut images,
+            entity,
+            cubemap_resolution,
+            capture.near,
+            voxel_center(volume_transform, resolution, 0),
+        );
+        let mut voxels = Image::new_fill(
+            Extent3d {
+                width: resolution.x,
+                height: resolution.y * 2,
+                depth_or_array_layers: resolution.z * 3,
+            },
+            TextureDimension::D3,
+            &[0; 8],
+            CAPTURE_TEXTURE_FORMAT,
+            RenderAssetUsages::default(),
+        );
+        voxels.texture_descriptor.usage |= TextureUsages::STORAGE_BINDING;
+        voxels.sampler = ImageSampler::linear();
+        let voxels = images.add(voxels);
+
+        commands.entity(entity).insert((
+            IrradianceVolume {
+                voxels: voxels.clone(),
+                intensity: capture_intensity(),
+            },
+            IrradianceVolumeCaptureTargets {
+                faces,
+                resolution,
+                voxels,
+                voxel_index: 0,
+            },
+        ));
+    }
+}
+
+/// The intensity of the light probes that captures create.
+///
+/// The capture cameras expose the scene, so the captured radiance is undone
+/// from their exposure.
+fn capture_intensity() -> f32 {
+    1.0 / Exposure::default().exposure()
+}
+
+/// The transform of the camera that renders the face `face` of a capture at
+/// `origin`.
+fn face_transform(origin: Vec3, face: usize) -> Transform {
+    let (direction, up) = FACE_ORIENTATIONS[face];
+    Transform::from_translation(origin).looking_to(direction, up)
+}
+
+/// The coordinates of the voxel `voxel_index` of a grid of size `resolution`.
+fn voxel_coordinates(resolution: UVec3, voxel_index: u32) -> UVec3 {
+    UVec3::new(
+        voxel_index % resolution.x,
+        voxel_index / resolution.x % resolution.y,
+        voxel_index / (resolution.x * resolution.y),
+    )
+}
+
+/// The world space center of the voxel `voxel_index` of an irradiance volume,
+/// which spans the unit cube centered on the origin of its transform.
+fn voxel_center(volume_transform: &GlobalTransform, resolution: UVec3, voxel_index: u32) -> Vec3 {
+    let coordinates = voxel_coordinates(resolution, voxel_index).as_vec3();
+    volume_transform.transform_point((coordinates + 0.5) / resolution.as_vec3() - 0.5)
+}
+
+fn mip_level_count(resolution: u32) -> u32 {
+    resolution.ilog2() + 1
+}
+
+/// Creates a zeroed image for a capture, which is a cubemap if it has six
+/// layers.
+fn new_capture_image(
+    resolution: u32,
+    layers: u32,
+    mip_level_count: u32,
+    usage: TextureUsages,
+) -> Image {
+    let size = Extent3d {
+        width: resolution,
+        height: resolution,
+        depth_or_array_layers: layers,
+    };
+
+    let mut image = Image::new_fill(
+        size,
+        TextureDimension::D2,
+        &[0; 8],
+        CAPTURE_TEXTURE_FORMAT,
+        RenderAssetUsages::default(),
+    );
+    // The render world uploads the data of every mip level.
+    image.data = vec![
+        0;
+        (0..mip_level_count)
+            .map(|level| size.mip_level_size(level, TextureDimension::D2).volume())
+            .sum::<usize>()
+            * CAPTURE_TEXTURE_FORMAT.pixel_size()
+    ];
+    image.texture_descriptor.mip_level_count = mip_level_count;
+    image.texture_descriptor.usage |= usage;
+    image.sampler = ImageSampler::linear();
+    if layers == 6 {
+        image.texture_view_descriptor = Some(TextureViewDescriptor {
+            dimension: Some(TextureViewDimension::Cube),
+            ..default()
+        });
+    }
+    image
+}
+
+fn extract_light_probe_captures(
+    mut extracted_captures: ResMut<ExtractedLightProbeCaptures>,
+    reflection_probes: Extract<Query<&ReflectionProbeCaptureTargets>>,
+    irradiance_volumes: Extract<Query<&IrradianceVolumeCaptureTargets>>,
+) {
+    let extracted_captures = &mut *extracted_captures;
+
+    extracted_captures.reflection_probes.clear();
+    extracted_captures
+        .reflection_probes
+        .extend(
+            reflection_probes
+                .iter()
+                .map(|targets| ExtractedReflectionProbeCapture {
+                    faces: targets.faces.ids(),
+                    diffuse_map: targets.diffuse_map.id(),
+                    specular_map: targets.specular_map.id(),
+                }),
+        );
+
+    extracted_captures.irradiance_volumes.clear();
+    extracted_captures
+        .irradiance_volumes
+        .extend(
+            irradiance_volumes
+                .iter()
+                .map(|targets| ExtractedIrradianceVolumeCapture {
+                    faces: targets.faces.ids(),
+                    voxels: targets.voxels.id(),
+                    voxel: voxel_coordinates(targets.resolution, targets.voxel_index),
+                }),
+        );
+}
+
+impl FromWorld for LightProbeCapturePipelines {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+        let pipeline_cache = world.resource::<PipelineCache>();
+
+        let downsample_bind_group_layout = render_device.create_bind_group_layout(
+            "light_probe_capture_downsample_bind_group_layout",
+            &BindGroupLayoutEntries::with_indices(
+                ShaderStages::COMPUTE,
+                (
+                    (
+                        2,
+                        texture_storage_2d_array(
+                            CAPTURE_TEXTURE_FORMAT,
+                            StorageTextureAccess::WriteOnly,
+                        ),
+                    ),
+                    (
+                        4,
+                        texture_2d_array(TextureSampleType::Float { filterable: false }),
+                    ),
+                ),
+            ),
+        );
+
+        let filter_bind_group_layout = render_device.create_bind_group_layout(
+            "light_probe_capture_filter_bind_group_layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::COMPUTE,
+                (
+                    texture_cube(TextureSampleType::Float { filterable: true }),
+                    sampler(SamplerBindingType::Filtering),
+                    texture_storage_2d_array(
+                        CAPTURE_TEXTURE_FORMAT,
+                        StorageTextureAccess::WriteOnly,
+                    ),
+                    uniform_buffer::<FilterSettings>(false),
+                ),
+            ),
+        );
+
+        let irradiance_volume_bind_group_layout = render_device.create_bind_group_layout(
+            "light_probe_capture_irradiance_volume_bind_group_layout",
+            &BindGroupLayoutEntries::with_indices(
+                ShaderStages::COMPUTE,
+                (
+                    (
+                        4,
+                        texture_2d_array(TextureSampleType::Float { filterable: false }),
+                    ),
+                    (
+                        5,
+                        texture_storage_3d(CAPTURE_TEXTURE_FORMAT, StorageTextureAccess::WriteOnly),
+                    ),
+                    (6, uniform_buffer::<UVec3>(false)),
+                ),
+            ),
+        );
+
+        let sampler = render_device.create_sampler(&SamplerDescriptor {
+            label: Some("light_probe_capture_sampler"),
+            mag_filter: FilterMode::Linear,
+            min_filter: FilterMode::Linear,
+            mipmap_filter: FilterMode::Linear,
+            ..default()
+        });
+
+        let queue_pipeline = |label: &'static str, layout: &BindGroupLayout, entry_point| {
+            pipeline_cache.queue_compute_pipeline(ComputePipelineDescriptor {
+                label: Some(label.into()),
+                layout: vec![layout.clone()],
+                push_constant_ranges: vec![],
+                shader: LIGHT_PROBE_CAPTURE_SHADER_HANDLE,
+                shader_defs: vec![],
+                entry_point,
+            })
+        };
+        let downsample_pipeline = queue_pipeline(
+            "light_probe_capture_downsample_pipeline",
+            &downsample_bind_group_layout,
+            "downsample".into(),
+        );
+        let specular_pipeline = queue_pipeline(
+            "light_probe_capture_specular_pipeline",
+            &filter_bind_group_layout,
+            "filter_specular".into(),
+        );
+        let diffuse_pipeline = queue_pipeline(
+            "light_probe_capture_diffuse_pipeline",
+            &filter_bind_group_layout,
+            "filter_diffuse".into(),
+        );
+        let irradiance_volume_pipeline = queue_pipeline(
+            "light_probe_capture_irradiance_volume_pipeline",
+            &irradiance_volume_bind_group_layout,
+            "bake_irradiance_volume".into(),
+        );
+
+        Self {
+            downsample_bind_group_layout,
+            filter_bind_group_layout,
+            irradiance_volume_bind_group_layout,
+            sampler,
+            downsample_pipeline,
+            specular_pipeline,
+            diffuse_pipeline,
+            irradiance_volume_pipeline,
+        }
+    }
+}
+
+/// Creates the radiance cubemaps and the bind groups of the captures whose
+/// images are ready.
+#[allow(clippy::too_many_arguments)]
+fn prepare_light_probe_captures(
+    extracted_captures: Res<ExtractedLightProbeCaptures>,
+    mut prepared_captures: ResMut<PreparedLightProbeCaptures>,
+    pipelines: Res<LightProbeCapturePipelines>,
+    images: Res<RenderAssets<Image>>,
+    mut texture_cache: ResMut<TextureCache>,
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+) {
+    prepared_captures.reflection_probes.clear();
+    prepared_captures.irradiance_volumes.clear();
+
+    let get_faces = |faces: &[AssetId<Image>; 6]| {
+        let faces = faces
+            .iter()
+            .map(|face| images.get(*face).map(|image| image.texture.clone()))
+            .collect::<Option<Vec<_>>>()?;
+        faces.try_into().ok()
+    };
+    let mut radiance_cubemap = |label, resolution, mip_level_count, usage| {
+        texture_cache.get(
+            &render_device,
+            TextureDescriptor {
+                label: Some(label),
+                size: Extent3d {
+                    width: resolution,
+                    height: resolution,
+                    depth_or_array_layers: 6,
+                },
+                mip_level_count,
+                sample_count: 1,
+                dimension: TextureDimension::D2,
+                format: CAPTURE_TEXTURE_FORMAT,
+                usage: TextureUsages::COPY_DST | TextureUsages::TEXTURE_BINDING | usage,
+                view_formats: &[],
+            },
+        )
+    };
+    let mip_view = |texture: &Texture, mip_level| {
+        texture.create_view(&TextureViewDescriptor {
+            dimension: Some(TextureViewDimension::D2Array),
+            base_mip_level: mip_level,
+            mip_level_count: Some(1),
+            ..default()
+        })
+    };
+
+    for capture in &extracted_captures.reflection_probes {
+        let (Some(faces), Some(diffuse_map), Some(specular_map)) = (
+            get_faces(&capture.faces),
+            images.get(capture.diffuse_map),
+            images.get(capture.specular_map),
+        ) else {
+            continue;
+        };
+
+        let resolution = specular_map.size.x as u32;
+        let mip_level_count = specular_map.mip_level_count;
+        let radiance_cubemap = radiance_cubemap(
+            "reflection_probe_capture_radiance_cubemap",
+            resolution,
+            mip_level_count,
+            TextureUsages::STORAGE_BINDING,
+        );
+        let radiance_cubemap_view = radiance_cubemap
+            .texture
+            .create_view(&TextureViewDescriptor {
+                dimension: Some(TextureViewDimension::Cube),
+                ..default()
+            });
+
+        let downsample_bind_groups = (1..mip_level_count)
+            .map(|mip_level| {
+                render_device.create_bind_group(
+                    "reflection_probe_capture_downsample_bind_group",
+                    &pipelines.downsample_bind_group_layout,
+                    &BindGroupEntries::with_indices((
+                        (2, &mip_view(&radiance_cubemap.texture, mip_level)),
+                        (4, &mip_view(&radiance_cubemap.texture, mip_level - 1)),
+                    )),
+                )
+            })
+            .collect();
+
+        let filter_bind_group = |destination: &TextureView, roughness| {
+            let mut settings = UniformBuffer::from(FilterSettings { roughness });
+            settings.write_buffer(&render_device, &render_queue);
+            render_device.create_bind_group(
+                "reflection_probe_capture_filter_bind_group",
+                &pipelines.filter_bind_group_layout,
+                &BindGroupEntries::sequential((
+                    &radiance_cubemap_view,
+                    &pipelines.sampler,
+                    destination,
+                    &settings,
+                )),
+            )
+        };
+
+        // The environment map light samples the mip level of the specular map
+        // at the perceptual roughness times the number of levels minus one.
+        let specular_bind_groups = (1..mip_level_count)
+            .map(|mip_level| {
+                filter_bind_group(
+                    &mip_view(&specular_map.texture, mip_level),
+                    mip_level as f32 / (mip_level_count - 1) as f32,
+                )
+            })
+            .collect();
+        let diffuse_bind_group = filter_bind_group(&mip_view(&diffuse_map.texture, 0), 1.0);
+
+        prepared_captures
+            .reflection_probes
+            .push(PreparedReflectionProbeCapture {
+                resolution,
+                faces,
+                radiance_cubemap,
+                specular_map: specular_map.texture.clone(),
+                downsample_bind_groups,
+                specular_bind_groups,
+                diffuse_bind_group,
+            });
+    }
+
+    for capture in &extracted_captures.irradiance_volumes {
+        let (Some(faces), Some(voxels)) = (get_faces(&capture.faces), images.get(capture.voxels))
+        else {
+            continue;
+        };
+        let Some(resolution) = images.get(capture.faces[0]).map(|face| face.size.x as u32) else {
+            continue;
+        };
+
+        let radiance_cubemap = radiance_cubemap(
+            "irradiance_volume_capture_radiance_cubemap",
+            resolution,
+            1,
+            TextureUsages::empty(),
+        );
+
+        let mut voxel = UniformBuffer::from(capture.voxel);
+        voxel.write_buffer(&render_device, &render_queue);
+        let bind_group = render_device.create_bind_group(
+            "irradiance_volume_capture_bind_group",
+            &pipelines.irradiance_volume_bind_group_layout,
+            &BindGroupEntries::with_indices((
+                (4, &mip_view(&radiance_cubemap.texture, 0)),
+                (5, &voxels.texture_view),
+                (6, &voxel),
+            )),
+        );
+
+        prepared_captures
+            .irradiance_volumes
+            .push(PreparedIrradianceVolumeCapture {
+                resolution,
+                faces,
+                radiance_cubemap,
+                bind_group,
+            });
+    }
+}
+
+/// Records the copies of the six faces of a capture into the first mip level of
+/// each texture of `destinations`.
+fn copy_faces(
+    render_context: &mut RenderContext,
+    faces: &[Texture; 6],
+    resolution: u32,
+    destinations: &[&Texture],
+) {
+    let command_encoder = render_context.command_encoder();
+    for (layer, face) in faces.iter().enumerate() {
+        for destination in destinations {
+            command_encoder.copy_texture_to_texture(
+                face.as_image_copy(),
+                ImageCopyTexture {
+                    texture: destination,
+                    mip_level: 0,
+                    origin: Origin3d {
+                        x: 0,
+                        y: 0,
+                        z: layer as u32,
+                    },
+                    aspect: TextureAspect::All,
+                },
+                Extent3d {
+                    width: resolution,
+                    height: resolution,
+                    depth_or_array_layers: 1,
+                },
+            );
+        }
+    }
+}
+
+impl Node for LightProbeCaptureNode {
+    fn run<'w>(
+        &self,
+        _: &mut RenderGraphContext,
+        render_context: &mut RenderContext<'w>,
+        world: &'w World,
+    ) -> Result<(), NodeRunError> {
+        let prepared_captures = world.resource::<PreparedLightProbeCaptures>();
+        let pipelines = world.resource::<LightProbeCapturePipelines>();
+        let pipeline_cache = world.resource::<PipelineCache>();
+
+        let (
+            Some(downsample_pipeline),
+            Some(specular_pipeline),
+            Some(diffuse_pipeline),
+            Some(irradiance_volume_pipeline),
+        ) = (
+            pipeline_cache.get_compute_pipeline(pipelines.downsample_pipeline),
+            pipeline_cache.get_compute_pipeline(pipelines.specular_pipeline),
+            pipeline_cache.get_compute_pipeline(pipelines.diffuse_pipeline),
+            pipeline_cache.get_compute_pipeline(pipelines.irradiance_volume_pipeline),
+        )
+        else {
+            return Ok(());
+        };
+
+        for capture in &prepared_captures.reflection_probes {
+            // The unfiltered faces make up the first mip level of both the
+            // radiance cubemap and the specular map, which is perfectly smooth.
+            copy_faces(
+                render_context,
+                &capture.faces,
+                capture.resolution,
+                &[&capture.radiance_cubemap.texture, &capture.specular_map],
+            );
+
+            let mut pass =
+                render_context
+                    .command_encoder()
+                    .begin_compute_pass(&ComputePassDescriptor {
+                        label: Some("reflection_probe_capture_pass"),
+                        timestamp_writes: None,
+                    });
+
+            pass.set_pipeline(downsample_pipeline);
+            for (mip_level, bind_group) in (1..).zip(&capture.downsample_bind_groups) {
+                let workgroups = (capture.resolution >> mip_level).max(1).div_ceil(8);
+                pass.set_bind_group(0, bind_group, &[]);
+                pass.dispatch_workgroups(workgroups, workgroups, 6);
+            }
+
+            pass.set_pipeline(specular_pipeline);
+            for (mip_level, bind_group) in (1..).zip(&capture.specular_bind_groups) {
+                let workgroups = (capture.resolution >> mip_level).max(1).div_ceil(8);
+                pass.set_bind_group(0, bind_group, &[]);
+                pass.dispatch_workgroups(workgroups, workgroups, 6);
+            }
+
+            pass.set_pipeline(diffuse_pipeline);
+            pass.set_bind_group(0, &capture.diffuse_bind_group, &[]);
+            let workgroups = DIFFUSE_MAP_RESOLUTION.div_ceil(8);
+            pass.dispatch_workgroups(workgroups, workgroups, 6);
+        }
+
+        for capture in &prepared_captures.irradiance_volumes {
+            copy_faces(
+                render_context,
+                &capture.faces,
+                capture.resolution,
+                &[&capture.radiance_cubemap.texture],
+            );
+
+            let mut pass =
+                render_context
+                    .command_encoder()
+                    .begin_compute_pass(&ComputePassDescriptor {
+                        label: Some("irradiance_volume_capture_pass"),
+                        timestamp_writes: None,
+                    });
+
+            // One workgroup per side of the ambient cube of the voxel.
+            pass.set_pipeline(irradiance_volume_pipeline);
+            pass.set_bind_group(0, &capture.bind_group, &[]);
+            pass.dispatch_workgroups(6, 1, 1);
+        }
+
+        Ok(())
+    }
+}
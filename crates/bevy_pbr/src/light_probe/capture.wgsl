@@ -0,0 +1,202 @@
+// Filtering of the cubemaps captured by reflection probes and irradiance volumes.
+//
+// The six faces of the capture are copied into the first mip level of a radiance cubemap, which
+// `downsample` reduces into a full mip chain. `filter_specular` and `filter_diffuse` then
+// importance sample the radiance cubemap into the specular and diffuse cubemaps of the
+// environment map light, according to the GGX and Lambertian distributions respectively. Both
+// read the mip level of the radiance cubemap whose texels cover the solid angle of each sample,
+// which removes most of the noise of the few samples taken ("filtered importance sampling").
+//
+// The filters are isotropic, so they work in the coordinate space of the cubemap directly.
+//
+// `bake_irradiance_volume` reduces the six faces captured at a voxel of an irradiance volume into
+// the six sides of its ambient cube.
+
+#import bevy_pbr::utils::PI
+
+struct FilterSettings {
+    roughness: f32,
+}
+
+@group(0) @binding(0) var radiance_cubemap: texture_cube<f32>;
+@group(0) @binding(1) var radiance_sampler: sampler;
+@group(0) @binding(2) var destination: texture_storage_2d_array<rgba16float, write>;
+@group(0) @binding(3) var<uniform> settings: FilterSettings;
+@group(0) @binding(4) var downsample_source: texture_2d_array<f32>;
+@group(0) @binding(5) var irradiance_volume: texture_storage_3d<rgba16float, write>;
+@group(0) @binding(6) var<uniform> voxel: vec3<u32>;
+
+const SPECULAR_SAMPLE_COUNT: u32 = 64u;
+const DIFFUSE_SAMPLE_COUNT: u32 = 128u;
+const IRRADIANCE_VOLUME_WORKGROUP_SIZE: u32 = 64u;
+
+var<workgroup> irradiance_volume_sums: array<vec3<f32>, IRRADIANCE_VOLUME_WORKGROUP_SIZE>;
+
+// The direction of the center of the texel `texel` of the face `face` of a cubemap of size `size`.
+fn cubemap_direction(texel: vec2<u32>, face: u32, size: vec2<u32>) -> vec3<f32> {
+    let st = (vec2<f32>(texel) + 0.5) / vec2<f32>(size) * 2.0 - 1.0;
+    var direction: vec3<f32>;
+    switch face {
+        case 0u: { direction = vec3(1.0, -st.y, -st.x); }
+        case 1u: { direction = vec3(-1.0, -st.y, st.x); }
+        case 2u: { direction = vec3(st.x, 1.0, st.y); }
+        case 3u: { direction = vec3(st.x, -1.0, -st.y); }
+        case 4u: { direction = vec3(st.x, -st.y, 1.0); }
+        default: { direction = vec3(-st.x, -st.y, -1.0); }
+    }
+    return normalize(direction);
+}
+
+fn hammersley(i: u32, count: u32) -> vec2<f32> {
+    return vec2(f32(i) / f32(count), f32(reverseBits(i)) * 2.3283064365386963e-10);
+}
+
+// Rotates `v` from the tangent space around `N`, where `N` is the Z axis, to the cubemap space.
+fn tangent_to_cubemap(v: vec3<f32>, N: vec3<f32>) -> vec3<f32> {
+    var up = vec3(1.0, 0.0, 0.0);
+    if abs(N.z) < 0.999 {
+        up = vec3(0.0, 0.0, 1.0);
+    }
+    let T = normalize(cross(up, N));
+    let B = cross(N, T);
+    return T * v.x + B * v.y + N * v.z;
+}
+
+// The mip level of the radiance cubemap whose texels cover the solid angle of a sample of
+// probability density `pdf` out of `sample_count`.
+fn source_mip_level(pdf: f32, sample_count: u32) -> f32 {
+    let size = f32(textureDimensions(radiance_cubemap).x);
+    let sample_solid_angle = 1.0 / (f32(sample_count) * pdf + 1e-6);
+    let texel_solid_angle = 4.0 * PI / (6.0 * size * size);
+    return max(0.5 * log2(sample_solid_angle / texel_solid_angle) + 1.0, 0.0);
+}
+
+@compute @workgroup_size(8, 8, 1)
+fn downsample(@builtin(global_invocation_id) id: vec3<u32>) {
+    let size = textureDimensions(destination);
+    if any(id.xy >= size) {
+        return;
+    }
+
+    let source_size = vec2<i32>(textureDimensions(downsample_source));
+    let texel = vec2<i32>(id.xy) * 2;
+    var color = vec4(0.0);
+    for (var y = 0; y < 2; y += 1) {
+        for (var x = 0; x < 2; x += 1) {
+            let source_texel = min(texel + vec2(x, y), source_size - 1);
+            color += textureLoad(downsample_source, source_texel, id.z, 0);
+        }
+    }
+    textureStore(destination, id.xy, id.z, color * 0.25);
+}
+
+@compute @workgroup_size(8, 8, 1)
+fn filter_specular(@builtin(global_invocation_id) id: vec3<u32>) {
+    let size = textureDimensions(destination);
+    if any(id.xy >= size) {
+        return;
+    }
+
+    // The split-sum approximation assumes that the view direction is the normal.
+    let N = cubemap_direction(id.xy, id.z, size);
+    let a = settings.roughness * settings.roughness;
+    let a2 = a * a;
+
+    var color = vec3(0.0);
+    var total_weight = 0.0;
+    for (var i = 0u; i < SPECULAR_SAMPLE_COUNT; i += 1u) {
+        let xi = hammersley(i, SPECULAR_SAMPLE_COUNT);
+        let phi = 2.0 * PI * xi.x;
+        let cos_theta = sqrt((1.0 - xi.y) / (1.0 + (a2 - 1.0) * xi.y));
+        let sin_theta = sqrt(1.0 - cos_theta * cos_theta);
+        let H = tangent_to_cubemap(vec3(sin_theta * cos(phi), sin_theta * sin(phi), cos_theta), N);
+        let L = 2.0 * dot(N, H) * H - N;
+
+        let NdotL = dot(N, L);
+        if NdotL <= 0.0 {
+            continue;
+        }
+
+        // With the view direction along the normal, the density of `L` is D(H) / 4.
+        let d = cos_theta * cos_theta * (a2 - 1.0) + 1.0;
+        let pdf = a2 / (PI * d * d) / 4.0;
+        let level = source_mip_level(pdf, SPECULAR_SAMPLE_COUNT);
+
+        color += textureSampleLevel(radiance_cubemap, radiance_sampler, L, level).rgb * NdotL;
+        total_weight += NdotL;
+    }
+
+    textureStore(destination, id.xy, id.z, vec4(color / max(total_weight, 1e-6), 1.0));
+}
+
+@compute @workgroup_size(8, 8, 1)
+fn filter_diffuse(@builtin(global_invocation_id) id: vec3<u32>) {
+    let size = textureDimensions(destination);
+    if any(id.xy >= size) {
+        return;
+    }
+
+    let N = cubemap_direction(id.xy, id.z, size);
+
+    // The cosine-weighted mean of the radiance over the hemisphere, which is the irradiance
+    // divided by π.
+    var color = vec3(0.0);
+    for (var i = 0u; i < DIFFUSE_SAMPLE_COUNT; i += 1u) {
+        let xi = hammersley(i, DIFFUSE_SAMPLE_COUNT);
+        let phi = 2.0 * PI * xi.x;
+        let cos_theta = sqrt(1.0 - xi.y);
+        let sin_theta = sqrt(xi.y);
+        let L = tangent_to_cubemap(vec3(sin_theta * cos(phi), sin_theta * sin(phi), cos_theta), N);
+
+        let level = source_mip_level(cos_theta / PI, DIFFUSE_SAMPLE_COUNT);
+        color += textureSampleLevel(radiance_cubemap, radiance_sampler, L, level).rgb;
+    }
+
+    textureStore(destination, id.xy, id.z, vec4(color / f32(DIFFUSE_SAMPLE_COUNT), 1.0));
+}
+
+// Each workgroup integrates the side `workgroup_id.x` of the ambient cube, in the order +X, -X, +Y,
+// -Y, +Z, -Z, over all the texels of the faces in `downsample_source`.
+@compute @workgroup_size(64, 1, 1)
+fn bake_irradiance_volume(
+    @builtin(workgroup_id) workgroup_id: vec3<u32>,
+    @builtin(local_invocation_index) local_index: u32,
+) {
+    let side = workgroup_id.x;
+    var side_direction = vec3(0.0);
+    side_direction[side / 2u] = select(1.0, -1.0, side % 2u == 1u);
+
+    let size = textureDimensions(downsample_source);
+    let texel_count = size.x * size.y * 6u;
+    var sum = vec3(0.0);
+    for (var i = local_index; i < texel_count; i += IRRADIANCE_VOLUME_WORKGROUP_SIZE) {
+        let face = i / (size.x * size.y);
+        let texel = vec2(i % size.x, (i / size.x) % size.y);
+
+        // The cubemap space is the world space with Z flipped.
+        let st = (vec2<f32>(texel) + 0.5) / vec2<f32>(size) * 2.0 - 1.0;
+        let cubemap_L = cubemap_direction(texel, face, size);
+        let L = vec3(cubemap_L.xy, -cubemap_L.z);
+
+        let solid_angle = 4.0 / f32(size.x * size.y) / pow(1.0 + dot(st, st), 1.5);
+        let radiance = textureLoad(downsample_source, texel, face, 0).rgb;
+        sum += radiance * max(dot(L, side_direction), 0.0) * solid_angle;
+    }
+    irradiance_volume_sums[local_index] = sum;
+
+    for (var stride = IRRADIANCE_VOLUME_WORKGROUP_SIZE / 2u; stride > 0u; stride /= 2u) {
+        workgroupBarrier();
+        if local_index < stride {
+            irradiance_volume_sums[local_index] += irradiance_volume_sums[local_index + stride];
+        }
+    }
+
+    if local_index == 0u {
+        // The irradiance divided by π, like the diffuse maps of environment maps. The negative
+        // sides of each axis follow the positive ones along Y, and the axes follow each other
+        // along Z.
+        let resolution = textureDimensions(irradiance_volume) / vec3(1u, 2u, 3u);
+        let texel = voxel + vec3(0u, (side % 2u) * resolution.y, (side / 2u) * resolution.z);
+        textureStore(irradiance_volume, texel, vec4(irradiance_volume_sums[0] / PI, 1.0));
+    }
+}
@@ -32,6 +32,17 @@
 //! The Khronos Group has [several pre-filtered environment maps] available for
 //! you to use.
 //!
+//! Reflection probes can also be captured from the scene at runtime, by adding a
+//! [`crate::capture::ReflectionProbeCapture`] to them. The capture renders and
+//! pre-filters the cubemaps on the GPU, either every frame or once, to bake
+//! the probe.
+//!
+//! Where reflection probes overlap, the one nearest to the camera takes
+//! precedence, and fades out into the others and the view environment map
+//! according to its [`ReflectionProbe::blend_fraction`]. Reflection probes can
+//! also be parallax corrected, which suits probes that match the walls of a
+//! room. See [`ReflectionProbe`] for details.
+//!
 //! Currently, reflection probes (i.e. environment maps attached to light
 //! probes) use binding arrays (also known as bindless textures) and
 //! consequently aren't supported on WebGL2 or WebGPU. Reflection probes are
@@ -48,9 +59,10 @@
 
 use bevy_asset::{AssetId, Handle};
 use bevy_ecs::{
-    bundle::Bundle, component::Component, query::QueryItem, system::lifetimeless::Read,
+    bundle::Bundle, component::Component, query::QueryItem, reflect::ReflectComponent,
+    system::lifetimeless::Read,
 };
-use bevy_reflect::Reflect;
+use bevy_reflect::{std_traits::ReflectDefault, Reflect};
 use bevy_render::{
     extract_instances::ExtractInstance,
     prelude::SpatialBundle,
@@ -124,6 +136,45 @@ pub struct ReflectionProbeBundle {
     pub light_probe: LightProbe,
     /// The cubemaps that make up this environment map.
     pub environment_map: EnvironmentMapLight,
+    /// How this reflection probe blends with its surroundings and projects its
+    /// reflections.
+    pub reflection_probe: ReflectionProbe,
+}
+
+/// Options of a reflection probe: an entity with a [`LightProbe`] and an
+/// [`EnvironmentMapLight`].
+///
+/// The extent of the reflection probe is the box of the [`LightProbe`]: a unit
+/// cube centered on the origin, which the transform of the entity scales,
+/// rotates, and translates. Reflection probes without this component behave
+/// like its default.
+#[derive(Clone, Copy, Debug, Component, Reflect)]
+#[reflect(Component, Default)]
+pub struct ReflectionProbe {
+    /// Whether the reflections are parallax corrected, also known as box
+    /// projected.
+    ///
+    /// Without parallax correction, the cubemap is sampled as if the reflected
+    /// surroundings were infinitely far away, which suits outdoor scenes. With
+    /// parallax correction, the reflected rays are intersected with the box of
+    /// the probe, and the cubemap is sampled toward the intersection from the
+    /// center of the probe, where the cubemap was captured. This makes the
+    /// reflections of rooms line up with their walls, when the box of the
+    /// probe matches the room.
+    ///
+    /// Defaults to false.
+    pub parallax_correction: bool,
+
+    /// The fraction of the distance from the faces of the box to its center
+    /// over which the reflection probe fades out.
+    ///
+    /// Where the probe fades out, the remaining light comes from the
+    /// overlapping reflection probes farther from the camera, and then from
+    /// the view environment map. A value of 0.0 cuts the probe abruptly at
+    /// the faces of its box, and 1.0 fades it out all the way to its center.
+    ///
+    /// Defaults to 0.0.
+    pub blend_fraction: f32,
 }
 
 /// All the bind group entries necessary for PBR shaders to access the
@@ -335,6 +386,15 @@ impl LightProbeComponent for EnvironmentMapLight {
     }
 }
 
+impl Default for ReflectionProbe {
+    fn default() -> Self {
+        Self {
+            parallax_correction: false,
+            blend_fraction: 0.0,
+        }
+    }
+}
+
 impl Default for EnvironmentMapViewLightProbeInfo {
     fn default() -> Self {
         Self {
@@ -1,8 +1,9 @@
 #define_import_path bevy_pbr::environment_map
 
-#import bevy_pbr::light_probe::query_light_probe
+#import bevy_pbr::light_probe::transpose_affine_matrix
 #import bevy_pbr::mesh_view_bindings as bindings
 #import bevy_pbr::mesh_view_bindings::light_probes
+#import bevy_pbr::mesh_view_types::REFLECTION_PROBE_FLAGS_PARALLAX_CORRECTION_BIT
 
 struct EnvironmentMapLight {
     diffuse: vec3<f32>,
@@ -20,48 +21,140 @@ struct EnvironmentMapRadiances {
 
 #ifdef MULTIPLE_LIGHT_PROBES_IN_ARRAY
 
-fn compute_radiances(
+// Samples the diffuse and specular cubemaps at the given index in the binding arrays.
+fn sample_environment_map(
+    texture_index: i32,
+    intensity: f32,
     perceptual_roughness: f32,
     N: vec3<f32>,
     R: vec3<f32>,
-    world_position: vec3<f32>,
     found_diffuse_indirect: bool,
 ) -> EnvironmentMapRadiances {
     var radiances: EnvironmentMapRadiances;
-
-    // Search for a reflection probe that contains the fragment.
-    var query_result = query_light_probe(world_position, /*is_irradiance_volume=*/ false);
-
-    // If we didn't find a reflection probe, use the view environment map if applicable.
-    if (query_result.texture_index < 0) {
-        query_result.texture_index = light_probes.view_cubemap_index;
-        query_result.intensity = light_probes.intensity_for_view;
-    }
-
-    // If there's no cubemap, bail out.
-    if (query_result.texture_index < 0) {
-        radiances.irradiance = vec3(0.0);
-        radiances.radiance = vec3(0.0);
-        return radiances;
-    }
+    radiances.irradiance = vec3(0.0);
 
     // Split-sum approximation for image based lighting: https://cdn2.unrealengine.com/Resources/files/2013SiggraphPresentationsNotes-26915738.pdf
     let radiance_level = perceptual_roughness * f32(textureNumLevels(
-        bindings::specular_environment_maps[query_result.texture_index]) - 1u);
+        bindings::specular_environment_maps[texture_index]) - 1u);
 
     if (!found_diffuse_indirect) {
         radiances.irradiance = textureSampleLevel(
-            bindings::diffuse_environment_maps[query_result.texture_index],
+            bindings::diffuse_environment_maps[texture_index],
             bindings::environment_map_sampler,
             vec3(N.xy, -N.z),
-            0.0).rgb * query_result.intensity;
+            0.0).rgb * intensity;
     }
 
     radiances.radiance = textureSampleLevel(
-        bindings::specular_environment_maps[query_result.texture_index],
+        bindings::specular_environment_maps[texture_index],
         bindings::environment_map_sampler,
         vec3(R.xy, -R.z),
-        radiance_level).rgb * query_result.intensity;
+        radiance_level).rgb * intensity;
+
+    return radiances;
+}
+
+// How much the reflection probe contributes to the fragment at `probe_space_pos`: 1 inside the
+// box, fading out to 0 at its faces over `blend_fraction` of the distance to its center.
+fn reflection_probe_weight(probe_space_pos: vec3<f32>, blend_fraction: f32) -> f32 {
+    let abs_pos = abs(probe_space_pos);
+    let distance_to_faces = 0.5 - max(abs_pos.x, max(abs_pos.y, abs_pos.z));
+    if (distance_to_faces < 0.0) {
+        return 0.0;
+    }
+    if (blend_fraction <= 0.0) {
+        return 1.0;
+    }
+    return saturate(distance_to_faces / (0.5 * blend_fraction));
+}
+
+// Box projection: intersects the reflected ray with the box of the reflection probe, and returns
+// the direction from the center of the probe, where its cubemap was captured, to the intersection.
+//
+// The `inverse_transpose_transform` rows transform world space to probe space.
+fn parallax_corrected_direction(
+    inverse_transpose_transform: mat3x4<f32>,
+    probe_space_pos: vec3<f32>,
+    R: vec3<f32>,
+) -> vec3<f32> {
+    let row_x = inverse_transpose_transform[0].xyz;
+    let row_y = inverse_transpose_transform[1].xyz;
+    let row_z = inverse_transpose_transform[2].xyz;
+
+    var probe_space_R = vec3(dot(row_x, R), dot(row_y, R), dot(row_z, R));
+    probe_space_R = select(probe_space_R, vec3(1e-6), abs(probe_space_R) < vec3(1e-6));
+
+    // The ray leaves the box through the nearest of the faces it's heading toward.
+    let face_distances = (sign(probe_space_R) * 0.5 - probe_space_pos) / probe_space_R;
+    let hit_distance = min(face_distances.x, min(face_distances.y, face_distances.z));
+    let probe_space_hit = probe_space_pos + probe_space_R * hit_distance;
+
+    // Transform the direction back to world space, with the inverse of the linear part of the
+    // probe space transform.
+    let cofactor_x = cross(row_y, row_z);
+    let cofactor_y = cross(row_z, row_x);
+    let cofactor_z = cross(row_x, row_y);
+    return (cofactor_x * probe_space_hit.x + cofactor_y * probe_space_hit.y +
+        cofactor_z * probe_space_hit.z) / dot(row_x, cofactor_x);
+}
+
+fn compute_radiances(
+    perceptual_roughness: f32,
+    N: vec3<f32>,
+    R: vec3<f32>,
+    world_position: vec3<f32>,
+    found_diffuse_indirect: bool,
+) -> EnvironmentMapRadiances {
+    var radiances: EnvironmentMapRadiances;
+    radiances.irradiance = vec3(0.0);
+    radiances.radiance = vec3(0.0);
+
+    // Blend the reflection probes that contain the fragment, nearest to the camera first. Each
+    // probe only contributes the fraction of the light that the previous ones left.
+    var remaining_weight = 1.0;
+    for (var light_probe_index: i32 = 0;
+            light_probe_index < light_probes.reflection_probe_count && remaining_weight > 0.0;
+            light_probe_index += 1) {
+        let light_probe = light_probes.reflection_probes[light_probe_index];
+
+        let inverse_transform = transpose_affine_matrix(light_probe.inverse_transpose_transform);
+        let probe_space_pos = (inverse_transform * vec4<f32>(world_position, 1.0f)).xyz;
+        let weight = reflection_probe_weight(probe_space_pos, light_probe.blend_fraction);
+
+        if (weight > 0.0) {
+            var probe_R = R;
+            if ((light_probe.flags & REFLECTION_PROBE_FLAGS_PARALLAX_CORRECTION_BIT) != 0u) {
+                probe_R = parallax_corrected_direction(
+                    light_probe.inverse_transpose_transform,
+                    probe_space_pos,
+                    R);
+            }
+
+            let probe_radiances = sample_environment_map(
+                light_probe.cubemap_index,
+                light_probe.intensity,
+                perceptual_roughness,
+                N,
+                probe_R,
+                found_diffuse_indirect);
+            radiances.irradiance += probe_radiances.irradiance * weight * remaining_weight;
+            radiances.radiance += probe_radiances.radiance * weight * remaining_weight;
+            remaining_weight *= 1.0 - weight;
+        }
+    }
+
+    // Fill in the rest with the view environment map if applicable.
+    if (remaining_weight > 0.0 && light_probes.view_cubemap_index >= 0) {
+        let view_radiances = sample_environment_map(
+            light_probes.view_cubemap_index,
+            light_probes.intensity_for_view,
+            perceptual_roughness,
+            N,
+            R,
+            found_diffuse_indirect);
+        radiances.irradiance += view_radiances.irradiance * remaining_weight;
+        radiances.radiance += view_radiances.radiance * remaining_weight;
+    }
 
     return radiances;
 }
@@ -109,6 +202,25 @@ fn compute_radiances(
 
 #endif  // MULTIPLE_LIGHT_PROBES_IN_ARRAY
 
+// The fraction of the radiance reflected by the single scattering specular lobe, which multiplies
+// the specular radiance of the environment.
+fn single_scattering_specular(
+    roughness: f32,
+    NdotV: f32,
+    f_ab: vec2<f32>,
+    F0: vec3<f32>,
+) -> vec3<f32> {
+    // No real world material has specular values under 0.02, so we use this range as a
+    // "pre-baked specular occlusion" that extinguishes the fresnel term, for artistic control.
+    // See: https://google.github.io/filament/Filament.html#specularocclusion
+    let specular_occlusion = saturate(dot(F0, vec3(50.0 * 0.33)));
+
+    let Fr = max(vec3(1.0 - roughness), F0) - F0;
+    let kS = F0 + Fr * pow(1.0 - NdotV, 5.0);
+    let Ess = f_ab.x + f_ab.y;
+    return kS * Ess * specular_occlusion;
+}
+
 fn environment_map_light(
     perceptual_roughness: f32,
     roughness: f32,
@@ -135,17 +247,10 @@ fn environment_map_light(
         return out;
     }
 
-    // No real world material has specular values under 0.02, so we use this range as a
-    // "pre-baked specular occlusion" that extinguishes the fresnel term, for artistic control.
-    // See: https://google.github.io/filament/Filament.html#specularocclusion
-    let specular_occlusion = saturate(dot(F0, vec3(50.0 * 0.33)));
-
     // Multiscattering approximation: https://www.jcgt.org/published/0008/01/03/paper.pdf
     // Useful reference: https://bruop.github.io/ibl
-    let Fr = max(vec3(1.0 - roughness), F0) - F0;
-    let kS = F0 + Fr * pow(1.0 - NdotV, 5.0);
+    let FssEss = single_scattering_specular(roughness, NdotV, f_ab, F0);
     let Ess = f_ab.x + f_ab.y;
-    let FssEss = kS * Ess * specular_occlusion;
     let Ems = 1.0 - Ess;
     let Favg = F0 + (1.0 - F0) / 21.0;
     let Fms = FssEss * Favg / (1.0 - Ems * Favg);
@@ -32,9 +32,10 @@ use std::hash::Hash;
 use std::ops::Deref;
 
 use crate::{
+    capture::LightProbeCapturePlugin,
     irradiance_volume::IRRADIANCE_VOLUME_SHADER_HANDLE,
     light_probe::environment_map::{
-        EnvironmentMapIds, EnvironmentMapLight, ENVIRONMENT_MAP_SHADER_HANDLE,
+        EnvironmentMapIds, EnvironmentMapLight, ReflectionProbe, ENVIRONMENT_MAP_SHADER_HANDLE,
     },
 };
 
@@ -42,6 +43,7 @@ use self::irradiance_volume::IrradianceVolume;
 
 pub const LIGHT_PROBE_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(8954249792581071582);
 
+pub mod capture;
 pub mod environment_map;
 pub mod irradiance_volume;
 
@@ -53,7 +55,7 @@ pub const MAX_VIEW_LIGHT_PROBES: usize = 8;
 
 /// How many texture bindings are used in the fragment shader, *not* counting
 /// environment maps or irradiance volumes.
-const STANDARD_MATERIAL_FRAGMENT_SHADER_MIN_TEXTURE_BINDINGS: usize = 16;
+pub(crate) const STANDARD_MATERIAL_FRAGMENT_SHADER_MIN_TEXTURE_BINDINGS: usize = 16;
 
 /// Adds support for light probes: cuboid bounding regions that apply global
 /// illumination to objects within them.
@@ -124,6 +126,25 @@ struct RenderLightProbe {
     ///
     /// See the comment in [`EnvironmentMapLight`] for details.
     intensity: f32,
+
+    /// The fraction of the distance from the faces of the box to its center
+    /// over which a reflection probe fades out.
+    ///
+    /// See [`ReflectionProbe::blend_fraction`].
+    blend_fraction: f32,
+
+    /// Bitflags that describe options of a reflection probe.
+    flags: u32,
+}
+
+bitflags::bitflags! {
+    /// Bitflags that describe options of a reflection probe in the shader.
+    #[repr(transparent)]
+    struct RenderLightProbeFlags: u32 {
+        /// The reflections are box projected, see
+        /// [`ReflectionProbe::parallax_correction`].
+        const PARALLAX_CORRECTION = 0x1;
+    }
 }
 
 /// A per-view shader uniform that specifies all the light probes that the view
@@ -190,6 +211,9 @@ where
     // See the comment in [`EnvironmentMapLight`] for details.
     intensity: f32,
 
+    // The blending and parallax correction options of a reflection probe.
+    reflection_probe: ReflectionProbe,
+
     // The IDs of all assets associated with this light probe.
     //
     // Because each type of light probe component may reference different types
@@ -317,8 +341,10 @@ impl Plugin for LightProbePlugin {
             Shader::from_wgsl
         );
 
-        app.register_type::<LightProbe>()
+        app.add_plugins(LightProbeCapturePlugin)
+            .register_type::<LightProbe>()
             .register_type::<EnvironmentMapLight>()
+            .register_type::<ReflectionProbe>()
             .register_type::<IrradianceVolume>();
     }
 
@@ -343,7 +369,9 @@ impl Plugin for LightProbePlugin {
 /// to views, performing frustum culling and distance sorting in the process.
 fn gather_light_probes<C>(
     image_assets: Res<RenderAssets<Image>>,
-    light_probe_query: Extract<Query<(&GlobalTransform, &C), With<LightProbe>>>,
+    light_probe_query: Extract<
+        Query<(&GlobalTransform, &C, Option<&ReflectionProbe>), With<LightProbe>>,
+    >,
     view_query: Extract<Query<(Entity, &GlobalTransform, &Frustum, Option<&C>), With<Camera3d>>>,
     mut reflection_probes: Local<Vec<LightProbeInfo<C>>>,
     mut view_reflection_probes: Local<Vec<LightProbeInfo<C>>>,
@@ -504,7 +532,11 @@ where
     /// [`LightProbeInfo`]. This is done for every light probe in the scene
     /// every frame.
     fn new(
-        (light_probe_transform, environment_map): (&GlobalTransform, &C),
+        (light_probe_transform, environment_map, reflection_probe): (
+            &GlobalTransform,
+            &C,
+            Option<&ReflectionProbe>,
+        ),
         image_assets: &RenderAssets<Image>,
     ) -> Option<LightProbeInfo<C>> {
         environment_map.id(image_assets).map(|id| LightProbeInfo {
@@ -512,6 +544,7 @@ where
             inverse_transform: light_probe_transform.compute_matrix().inverse(),
             asset_id: id,
             intensity: environment_map.intensity(),
+            reflection_probe: reflection_probe.copied().unwrap_or_default(),
         })
     }
 
@@ -600,6 +633,12 @@ where
             // to recover the original inverse transform.
             let inverse_transpose_transform = light_probe.inverse_transform.transpose();
 
+            let mut flags = RenderLightProbeFlags::empty();
+            flags.set(
+                RenderLightProbeFlags::PARALLAX_CORRECTION,
+                light_probe.reflection_probe.parallax_correction,
+            );
+
             // Write in the light probe data.
             self.render_light_probes.push(RenderLightProbe {
                 inverse_transpose_transform: [
@@ -609,6 +648,8 @@ where
                 ],
                 texture_index: cubemap_index as i32,
                 intensity: light_probe.intensity,
+                blend_fraction: light_probe.reflection_probe.blend_fraction,
+                flags: flags.bits(),
             });
         }
     }
@@ -623,6 +664,7 @@ where
             inverse_transform: self.inverse_transform,
             affine_transform: self.affine_transform,
             intensity: self.intensity,
+            reflection_probe: self.reflection_probe,
             asset_id: self.asset_id.clone(),
         }
     }
@@ -1,11 +1,12 @@
 use crate::*;
-use bevy_app::{App, Plugin};
-use bevy_asset::{Asset, AssetApp, AssetEvent, AssetId, AssetServer, Assets, Handle};
+use bevy_app::{App, Plugin, PostUpdate};
+use bevy_asset::{Asset, AssetApp, AssetBudget, AssetEvent, AssetId, AssetServer, Assets, Handle};
 use bevy_core_pipeline::{
     core_3d::{
         AlphaMask3d, Camera3d, Opaque3d, ScreenSpaceTransmissionQuality, Transmissive3d,
         Transparent3d,
     },
+    oit::OrderIndependentTransparencySettings,
     prepass::{DeferredPrepass, DepthPrepass, MotionVectorPrepass, NormalPrepass},
     tonemapping::{DebandDither, Tonemapping},
 };
@@ -27,9 +28,14 @@ use bevy_render::{
     render_resource::*,
     renderer::RenderDevice,
     texture::FallbackImage,
-    view::{ExtractedView, Msaa, VisibleEntities},
+    view::{ExtractedView, Msaa, ViewVisibility, VisibilitySystems, VisibleEntities},
     Extract, ExtractSchedule, Render, RenderApp, RenderSet,
 };
+#[cfg(feature = "trace")]
+use bevy_utils::{
+    get_short_name,
+    tracing::{field, info_span},
+};
 use bevy_utils::{tracing::error, HashMap, HashSet};
 use std::marker::PhantomData;
 use std::sync::atomic::{AtomicU32, Ordering};
@@ -139,6 +145,16 @@ pub trait Material: Asset + AsBindGroup + Clone + Sized {
         false
     }
 
+    #[inline]
+    /// Returns whether light scatters underneath the surface of the material.
+    ///
+    /// Forward rendered opaque meshes with such materials are drawn a second time in the [`Transmissive3d`] pass, with the
+    /// `SUBSURFACE_SCATTERING_PASS` shader def, to diffuse the light the [`Opaque3d`] pass left on their surface. In the
+    /// [`Opaque3d`] pass, the `SCREEN_SPACE_SUBSURFACE_SCATTERING` shader def is set when that second draw will happen.
+    fn scatters_subsurface_light(&self) -> bool {
+        false
+    }
+
     /// Returns this material's prepass vertex shader. If [`ShaderRef::Default`] is returned, the default prepass vertex shader
     /// will be used.
     ///
@@ -212,7 +228,13 @@ where
 {
     fn build(&self, app: &mut App) {
         app.init_asset::<M>()
-            .add_plugins(ExtractInstancesPlugin::<AssetId<M>>::extract_visible());
+            .add_plugins(ExtractInstancesPlugin::<AssetId<M>>::extract_visible())
+            .add_systems(
+                PostUpdate,
+                touch_visible_material_images::<M>
+                    .run_if(resource_exists::<AssetBudget<Image>>)
+                    .after(VisibilitySystems::CheckVisibility),
+            );
 
         if let Ok(render_app) = app.get_sub_app_mut(RenderApp) {
             render_app
@@ -257,6 +279,28 @@ where
     }
 }
 
+/// Marks the images used by the materials of the visible entities as used in the
+/// [`AssetBudget`] of the images, so that they're not evicted, or reloaded if they were.
+pub fn touch_visible_material_images<M: Material>(
+    mut budget: ResMut<AssetBudget<Image>>,
+    materials: Res<Assets<M>>,
+    query: Query<(&Handle<M>, &ViewVisibility)>,
+) {
+    for (handle, view_visibility) in &query {
+        if !view_visibility.get() {
+            continue;
+        }
+        let Some(material) = materials.get(handle) else {
+            continue;
+        };
+        material.visit_dependencies(&mut |id| {
+            if let Ok(id) = id.try_typed::<Image>() {
+                budget.touch(id);
+            }
+        });
+    }
+}
+
 /// A key uniquely identifying a specialized [`MaterialPipeline`].
 pub struct MaterialPipelineKey<M: Material> {
     pub mesh_key: MeshPipelineKey,
@@ -491,11 +535,23 @@ pub fn queue_material_meshes<M: Material>(
         (
             Has<RenderViewLightProbes<EnvironmentMapLight>>,
             Has<RenderViewLightProbes<IrradianceVolume>>,
+            Has<OrderIndependentTransparencySettings>,
         ),
     )>,
 ) where
     M::Data: PartialEq + Eq + Hash + Clone,
 {
+    #[cfg(feature = "trace")]
+    let span = info_span!(
+        "queue_material_meshes",
+        material = get_short_name(std::any::type_name::<M>()),
+        views = views.iter().len(),
+        meshes = field::Empty,
+    )
+    .entered();
+    #[cfg(feature = "trace")]
+    let mut meshes = 0;
+
     for (
         view,
         visible_entities,
@@ -511,7 +567,7 @@ pub fn queue_material_meshes<M: Material>(
         mut alpha_mask_phase,
         mut transmissive_phase,
         mut transparent_phase,
-        (has_environment_maps, has_irradiance_volumes),
+        (has_environment_maps, has_irradiance_volumes, has_oit),
     ) in &mut views
     {
         let draw_opaque_pbr = opaque_draw_functions.read().id::<DrawMaterial<M>>();
@@ -550,6 +606,10 @@ pub fn queue_material_meshes<M: Material>(
             view_key |= MeshPipelineKey::IRRADIANCE_VOLUME;
         }
 
+        if has_oit {
+            view_key |= MeshPipelineKey::OIT_ENABLED;
+        }
+
         if let Some(projection) = projection {
             view_key |= match projection {
                 Projection::Perspective(_) => MeshPipelineKey::VIEW_PROJECTION_PERSPECTIVE,
@@ -586,6 +646,11 @@ pub fn queue_material_meshes<M: Material>(
                 camera_3d.screen_space_specular_transmission_quality,
             );
         }
+        // Subsurface scattering reads the opaque pass output from the view transmission texture,
+        // and stores the depth of the surfaces in its alpha channel, which needs a float format.
+        let screen_space_subsurface_scattering = view.hdr
+            && camera_3d
+                .is_some_and(|camera_3d| camera_3d.screen_space_specular_transmission_steps > 0);
         let rangefinder = view.rangefinder3d();
         for visible_entity in &visible_entities.entities {
             let Some(material_asset_id) = render_material_instances.get(visible_entity) else {
@@ -621,6 +686,18 @@ pub fn queue_material_meshes<M: Material>(
 
             mesh_key |= alpha_mode_pipeline_key(material.properties.alpha_mode);
 
+            let scatters_subsurface_light = screen_space_subsurface_scattering
+                && forward
+                && material.properties.scatters_subsurface_light
+                && !material.properties.reads_view_transmission_texture
+                && matches!(
+                    material.properties.alpha_mode,
+                    AlphaMode::Opaque | AlphaMode::Mask(_)
+                );
+            if scatters_subsurface_light {
+                mesh_key |= MeshPipelineKey::SCREEN_SPACE_SUBSURFACE_SCATTERING;
+            }
+
             if render_lightmaps
                 .render_lightmaps
                 .contains_key(visible_entity)
@@ -649,6 +726,39 @@ pub fn queue_material_meshes<M: Material>(
                 .material_bind_group_id
                 .set(material.get_bind_group_id());
 
+            if scatters_subsurface_light {
+                let subsurface_scattering_pipeline_id = pipelines.specialize(
+                    &pipeline_cache,
+                    &material_pipeline,
+                    MaterialPipelineKey {
+                        mesh_key: mesh_key | MeshPipelineKey::SUBSURFACE_SCATTERING_PASS,
+                        bind_group_data: material.key.clone(),
+                    },
+                    &mesh.layout,
+                );
+                match subsurface_scattering_pipeline_id {
+                    Ok(pipeline) => {
+                        let distance = rangefinder
+                            .distance_translation(&mesh_instance.transforms.transform.translation)
+                            + material.properties.depth_bias;
+                        transmissive_phase.add(Transmissive3d {
+                            entity: *visible_entity,
+                            draw_function: draw_transmissive_pbr,
+                            pipeline,
+                            distance,
+                            batch_range: 0..1,
+                            dynamic_offset: None,
+                        });
+                    }
+                    Err(err) => error!("{}", err),
+                }
+            }
+
+            #[cfg(feature = "trace")]
+            {
+                meshes += 1;
+            }
+
             match material.properties.alpha_mode {
                 AlphaMode::Opaque => {
                     if material.properties.reads_view_transmission_texture {
@@ -669,6 +779,11 @@ pub fn queue_material_meshes<M: Material>(
                             draw_function: draw_opaque_pbr,
                             pipeline: pipeline_id,
                             asset_id: mesh_instance.mesh_asset_id,
+                            material_bind_group_id: material.get_bind_group_id().0,
+                            lightmap_image: render_lightmaps
+                                .render_lightmaps
+                                .get(visible_entity)
+                                .map(|lightmap| lightmap.image),
                             batch_range: 0..1,
                             dynamic_offset: None,
                         });
@@ -717,6 +832,9 @@ pub fn queue_material_meshes<M: Material>(
             }
         }
     }
+
+    #[cfg(feature = "trace")]
+    span.record("meshes", meshes);
 }
 
 /// Default render method used for opaque materials.
@@ -783,6 +901,11 @@ pub struct MaterialProperties {
     /// This allows taking color output from the [`Opaque3d`] pass as an input, (for screen-space transmission) but requires
     /// rendering to take place in a separate [`Transmissive3d`] pass.
     pub reads_view_transmission_texture: bool,
+    /// Whether light scatters underneath the surface of the material.
+    ///
+    /// Such materials are drawn a second time, in the [`Transmissive3d`] pass, to diffuse the light the [`Opaque3d`]
+    /// pass left on their surface.
+    pub scatters_subsurface_light: bool,
 }
 
 /// Data prepared for a [`Material`] instance.
@@ -1001,6 +1124,7 @@ fn prepare_material<M: Material>(
             alpha_mode: material.alpha_mode(),
             depth_bias: material.depth_bias(),
             reads_view_transmission_texture: material.reads_view_transmission_texture(),
+            scatters_subsurface_light: material.scatters_subsurface_light(),
             render_method: method,
         },
     })
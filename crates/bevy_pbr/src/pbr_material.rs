@@ -1,5 +1,5 @@
 use bevy_asset::{Asset, Handle};
-use bevy_math::{Affine2, Vec2, Vec4};
+use bevy_math::{Affine2, Vec2, Vec3, Vec4};
 use bevy_reflect::{std_traits::ReflectDefault, Reflect};
 use bevy_render::{
     color::Color, mesh::MeshVertexBufferLayout, render_asset::RenderAssets, render_resource::*,
@@ -284,6 +284,54 @@ pub struct StandardMaterial {
     #[doc(alias = "extinction_color")]
     pub attenuation_color: Color,
 
+    /// How much of the light reaching the surface scatters underneath it before leaving it again,
+    /// from `0.0` to `1.0`.
+    ///
+    /// This is what keeps skin, wax, marble and leaves from looking like plastic: light entering
+    /// them bleeds into the shadows and softens the details of the surface. It is approximated in
+    /// screen space, by diffusing the light of the surface over the surface in a second draw.
+    ///
+    /// Defaults to `0.0`, i.e. no subsurface scattering.
+    ///
+    /// **Important:** Subsurface scattering requires an HDR camera, and a [`Camera3d`] with
+    /// [`Camera3d::screen_space_specular_transmission_steps`] greater than `0`, as it reads the
+    /// output of the opaque pass from the view transmission texture. Otherwise, it has no effect.
+    ///
+    /// **Note:** The scattering diffuses all of the light of the surface, including its specular
+    /// reflections, so strengths below `1.0` tend to look more natural. Materials reading the view
+    /// transmission texture, i.e. with [`StandardMaterial::specular_transmission`], don't scatter
+    /// light underneath their surface.
+    ///
+    /// [`Camera3d`]: bevy_core_pipeline::core_3d::Camera3d
+    /// [`Camera3d::screen_space_specular_transmission_steps`]: bevy_core_pipeline::core_3d::Camera3d::screen_space_specular_transmission_steps
+    #[doc(alias = "sss")]
+    pub subsurface_scattering: f32,
+
+    /// The color the light takes after scattering underneath the surface.
+    ///
+    /// Defaults to [`Color::WHITE`], i.e. no change.
+    ///
+    /// **Note:** To have any effect, must be used in conjunction with
+    /// [`StandardMaterial::subsurface_scattering`].
+    pub subsurface_color: Color,
+
+    /// How far, in world units, light travels underneath the surface before leaving it again.
+    ///
+    /// Defaults to `0.01`, i.e. one centimeter.
+    ///
+    /// **Note:** To have any effect, must be used in conjunction with
+    /// [`StandardMaterial::subsurface_scattering`].
+    pub subsurface_radius: f32,
+
+    /// The diffusion profile of the material: how far light of each color channel travels
+    /// underneath the surface, relative to [`StandardMaterial::subsurface_radius`].
+    ///
+    /// Red light travels farther than green and blue light in skin, for instance, which
+    /// `Vec3::new(1.0, 0.4, 0.25)` approximates.
+    ///
+    /// Defaults to [`Vec3::ONE`], i.e. all channels travel equally far.
+    pub subsurface_profile: Vec3,
+
     /// Used to fake the lighting of bumps and dents on a material.
     ///
     /// A typical usage would be faking cobblestones on a flat plane mesh in 3D.
@@ -507,6 +555,10 @@ impl Default for StandardMaterial {
             ior: 1.5,
             attenuation_color: Color::WHITE,
             attenuation_distance: f32::INFINITY,
+            subsurface_scattering: 0.0,
+            subsurface_color: Color::WHITE,
+            subsurface_radius: 0.01,
+            subsurface_profile: Vec3::ONE,
             occlusion_texture: None,
             normal_map_texture: None,
             flip_normal_map_y: false,
@@ -599,6 +651,12 @@ pub struct StandardMaterialUniform {
     pub emissive: Vec4,
     /// Color white light takes after travelling through the attenuation distance underneath the material surface
     pub attenuation_color: Vec4,
+    /// Color the light takes after scattering underneath the material surface
+    pub subsurface_color: Vec4,
+    /// How far light of each color channel travels underneath the material surface
+    pub subsurface_radius: Vec3,
+    /// Amount of light scattered underneath the material surface
+    pub subsurface_scattering: f32,
     /// The x-axis of the mat2 of the transform applied to the UVs corresponding to ATTRIBUTE_UV_0 on the mesh before sampling. Default is [1, 0].
     pub uv_transform_x_axis: Vec2,
     /// The y-axis of the mat2 of the transform applied to the UVs corresponding to ATTRIBUTE_UV_0 on the mesh before sampling. Default is [0, 1].
@@ -732,6 +790,9 @@ impl AsBindGroupShaderType<StandardMaterialUniform> for StandardMaterial {
             ior: self.ior,
             attenuation_distance: self.attenuation_distance,
             attenuation_color: self.attenuation_color.as_linear_rgba_f32().into(),
+            subsurface_color: self.subsurface_color.as_linear_rgba_f32().into(),
+            subsurface_radius: self.subsurface_radius * self.subsurface_profile,
+            subsurface_scattering: self.subsurface_scattering,
             flags: flags.bits(),
             alpha_cutoff,
             parallax_depth_scale: self.parallax_depth_scale,
@@ -793,7 +854,9 @@ impl Material for StandardMaterial {
             //
             // If the developer explicitly sets the `OpaqueRendererMethod` to `Deferred`, we assume
             // they know what they're doing and don't override it.
-            OpaqueRendererMethod::Auto if self.diffuse_transmission > 0.0 => {
+            OpaqueRendererMethod::Auto
+                if self.diffuse_transmission > 0.0 || self.subsurface_scattering > 0.0 =>
+            {
                 OpaqueRendererMethod::Forward
             }
             other => other,
@@ -810,6 +873,11 @@ impl Material for StandardMaterial {
         self.specular_transmission > 0.0
     }
 
+    #[inline]
+    fn scatters_subsurface_light(&self) -> bool {
+        self.subsurface_scattering > 0.0
+    }
+
     fn prepass_fragment_shader() -> ShaderRef {
         PBR_PREPASS_SHADER_HANDLE.into()
     }
@@ -0,0 +1,21 @@
+#import bevy_pbr::{
+    forward_io::VertexOutput,
+    mesh_view_bindings::view,
+}
+
+struct MirrorMaterial {
+    color: vec4<f32>,
+};
+
+@group(2) @binding(0) var<uniform> material: MirrorMaterial;
+@group(2) @binding(1) var reflection_texture: texture_2d<f32>;
+@group(2) @binding(2) var reflection_sampler: sampler;
+
+@fragment
+fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    // The reflection is sampled at the screen coordinates of the fragment, flipped horizontally
+    // like the reflection camera.
+    let uv = (in.position.xy - view.viewport.xy) / view.viewport.zw;
+    let reflection = textureSample(reflection_texture, reflection_sampler, vec2(1.0 - uv.x, uv.y));
+    return vec4(reflection.rgb * material.color.rgb, material.color.a);
+}
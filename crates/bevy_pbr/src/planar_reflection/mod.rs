@@ -0,0 +1,491 @@
+//! Planar reflections: mirrors and water planes reflecting the scene.
+//!
+//! A [`PlanarReflection`] on a mesh entity spawns a reflection camera, which
+//! renders the scene mirrored across the local XZ plane of the entity into an
+//! image. The reflection camera follows the camera whose view is reflected,
+//! and clips everything behind the plane with an oblique near plane, so that
+//! objects under a water plane don't appear in its reflection.
+//!
+//! The image is rendered flipped horizontally, so that the reflection camera
+//! keeps a right-handed transform, and is meant to be sampled in screen space
+//! by the reflecting surface. A [`MirrorMaterial`] on the entity receives the
+//! image automatically. Custom materials, such as water, can read it from the
+//! [`PlanarReflectionImage`] of the entity.
+
+use bevy_app::{App, Plugin, PostUpdate};
+use bevy_asset::{load_internal_asset, Asset, Assets, Handle};
+use bevy_core_pipeline::{
+    core_3d::{Camera3d, Camera3dBundle},
+    tonemapping::{DebandDither, Tonemapping},
+};
+use bevy_ecs::{
+    component::Component,
+    entity::{Entity, EntityHashMap},
+    query::{With, Without},
+    reflect::ReflectComponent,
+    schedule::IntoSystemConfigs,
+    system::{Commands, Query, ResMut},
+};
+use bevy_math::{Mat4, UVec2, Vec3, Vec3A, Vec4};
+use bevy_reflect::{std_traits::ReflectDefault, Reflect};
+use bevy_render::{
+    alpha::AlphaMode,
+    camera::{
+        camera_system, Camera, CameraProjection, CameraProjectionPlugin, CameraUpdateSystem,
+        Exposure, Projection, RenderTarget,
+    },
+    color::Color,
+    render_asset::RenderAssetUsages,
+    render_resource::{
+        AsBindGroup, Extent3d, Shader, ShaderRef, TextureDimension, TextureFormat, TextureUsages,
+    },
+    texture::Image,
+    view::{update_frusta, VisibilitySystems},
+};
+use bevy_transform::{
+    components::{GlobalTransform, Transform},
+    TransformSystem,
+};
+use bevy_utils::prelude::default;
+
+use crate::{
+    build_directional_light_cascades, clear_directional_light_cascades, Material, MaterialPlugin,
+    OpaqueRendererMethod, SimulationLightSystems,
+};
+
+/// A handle to the shader of [`MirrorMaterial`].
+pub const MIRROR_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(8519736420197734118);
+
+/// The format of the images of planar reflections.
+const PLANAR_REFLECTION_TEXTURE_FORMAT: TextureFormat = TextureFormat::Rgba16Float;
+
+/// Adds support for [`PlanarReflection`]s and [`MirrorMaterial`].
+pub struct PlanarReflectionPlugin;
+
+/// Reflects the scene across the local XZ plane of the entity, whose normal is
+/// its local +Y axis, such as a [`bevy_math::primitives::Plane3d`] mesh.
+///
+/// The reflection is rendered by a camera managed by this component into the
+/// [`PlanarReflectionImage`] inserted on the entity, and given to its
+/// [`MirrorMaterial`] if it has one. The reflection camera renders the whole
+/// scene once more, before the other cameras, with the lights, shadows, and
+/// exposure of the reflected camera.
+#[derive(Clone, Copy, Debug, Component, Reflect)]
+#[reflect(Component, Default)]
+pub struct PlanarReflection {
+    /// The 3D camera whose view is reflected.
+    ///
+    /// Defaults to `None`, which reflects the view of the first active 3D
+    /// camera rendering to a window, by [`Camera::order`].
+    pub camera: Option<Entity>,
+
+    /// The width and height of the reflection image, in pixels.
+    ///
+    /// The image is stretched over the viewport of the reflected camera, so a
+    /// resolution with the same aspect ratio gives the sharpest reflections.
+    ///
+    /// Defaults to 1024×1024.
+    pub resolution: UVec2,
+}
+
+impl Default for PlanarReflection {
+    fn default() -> Self {
+        Self {
+            camera: None,
+            resolution: UVec2::splat(1024),
+        }
+    }
+}
+
+/// The image a [`PlanarReflection`] is rendered into, inserted on its entity.
+///
+/// The image is in linear HDR colors, and flipped horizontally: the reflection
+/// seen at the screen coordinates `uv` of the reflected camera is at
+/// `(1.0 - uv.x, uv.y)` in the image.
+#[derive(Clone, Debug, Component)]
+pub struct PlanarReflectionImage(pub Handle<Image>);
+
+/// A material showing the [`PlanarReflection`] of its entity, tinted by a
+/// color.
+///
+/// The material is unlit: it shows the reflection as is, which suits mirrors.
+#[derive(Asset, AsBindGroup, Reflect, Debug, Clone)]
+#[reflect(Default, Debug)]
+pub struct MirrorMaterial {
+    /// The color the reflection is multiplied by.
+    ///
+    /// Its alpha controls the opacity of the mirror, which is blended with what
+    /// is behind it when below 1.0.
+    ///
+    /// Defaults to [`Color::WHITE`].
+    #[uniform(0)]
+    pub color: Color,
+
+    /// The image of the reflection, set automatically from the
+    /// [`PlanarReflectionImage`] of the entity.
+    #[texture(1)]
+    #[sampler(2)]
+    pub reflection: Option<Handle<Image>>,
+}
+
+impl Default for MirrorMaterial {
+    fn default() -> Self {
+        Self {
+            color: Color::WHITE,
+            reflection: None,
+        }
+    }
+}
+
+impl Material for MirrorMaterial {
+    fn fragment_shader() -> ShaderRef {
+        MIRROR_SHADER_HANDLE.into()
+    }
+
+    fn alpha_mode(&self) -> AlphaMode {
+        if self.color.a() < 1.0 {
+            AlphaMode::Blend
+        } else {
+            AlphaMode::Opaque
+        }
+    }
+
+    fn opaque_render_method(&self) -> OpaqueRendererMethod {
+        OpaqueRendererMethod::Forward
+    }
+}
+
+/// Marks the reflection camera of a [`PlanarReflection`].
+#[derive(Component)]
+struct PlanarReflectionCamera {
+    /// The entity of the reflection.
+    mirror: Entity,
+}
+
+/// The projection of a reflection camera: the projection of the reflected
+/// camera, with its near plane replaced by the plane of the reflection.
+#[derive(Clone, Debug, Default, Component, Reflect)]
+#[reflect(Component, Default)]
+struct PlanarReflectionProjection {
+    /// The projection of the reflected camera.
+    projection: Projection,
+    /// The plane of the reflection in view space, facing the visible side.
+    clip_plane: Vec4,
+}
+
+impl CameraProjection for PlanarReflectionProjection {
+    fn get_projection_matrix(&self) -> Mat4 {
+        oblique_projection(self.projection.get_projection_matrix(), self.clip_plane)
+    }
+
+    // The projection follows the reflected camera, not the size of the image.
+    fn update(&mut self, _width: f32, _height: f32) {}
+
+    fn far(&self) -> f32 {
+        self.projection.far()
+    }
+
+    fn get_frustum_corners(&self, z_near: f32, z_far: f32) -> [Vec3A; 8] {
+        self.projection.get_frustum_corners(z_near, z_far)
+    }
+}
+
+impl Plugin for PlanarReflectionPlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(app, MIRROR_SHADER_HANDLE, "mirror.wgsl", Shader::from_wgsl);
+
+        app.register_type::<PlanarReflection>()
+            .add_plugins((
+                MaterialPlugin::<MirrorMaterial>::default(),
+                CameraProjectionPlugin::<PlanarReflectionProjection>::default(),
+            ))
+            .add_systems(
+                PostUpdate,
+                (
+                    update_planar_reflections
+                        .after(TransformSystem::TransformPropagate)
+                        .after(camera_system::<Projection>)
+                        .before(camera_system::<PlanarReflectionProjection>),
+                    update_frusta::<PlanarReflectionProjection>
+                        .in_set(VisibilitySystems::UpdateProjectionFrusta)
+                        .after(camera_system::<PlanarReflectionProjection>)
+                        .after(TransformSystem::TransformPropagate),
+                    build_directional_light_cascades::<PlanarReflectionProjection>
+                        .in_set(SimulationLightSystems::UpdateDirectionalLightCascades)
+                        .after(clear_directional_light_cascades)
+                        .after(TransformSystem::TransformPropagate)
+                        .after(CameraUpdateSystem),
+                ),
+            );
+    }
+}
+
+/// Spawns and despawns the reflection cameras, moves them to the mirrored
+/// transforms of the reflected cameras, and hands the images to the mirror
+/// materials.
+#[allow(clippy::type_complexity)]
+fn update_planar_reflections(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    mut mirror_materials: ResMut<Assets<MirrorMaterial>>,
+    mirrors: Query<
+        (
+            Entity,
+            &PlanarReflection,
+            &GlobalTransform,
+            Option<&PlanarReflectionImage>,
+            Option<&Handle<MirrorMaterial>>,
+        ),
+        Without<PlanarReflectionCamera>,
+    >,
+    stale_mirrors: Query<Entity, (With<PlanarReflectionImage>, Without<PlanarReflection>)>,
+    reflected_cameras: Query<
+        (
+            Entity,
+            &Camera,
+            &GlobalTransform,
+            &Projection,
+            Option<&Exposure>,
+        ),
+        (With<Camera3d>, Without<PlanarReflectionCamera>),
+    >,
+    mut reflection_cameras: Query<(
+        Entity,
+        &PlanarReflectionCamera,
+        &mut Camera,
+        &mut Transform,
+        &mut GlobalTransform,
+        &mut PlanarReflectionProjection,
+        &mut Exposure,
+    )>,
+) {
+    for entity in &stale_mirrors {
+        commands.entity(entity).remove::<PlanarReflectionImage>();
+    }
+
+    let mut cameras_of_mirrors = EntityHashMap::default();
+    for (entity, reflection_camera, ..) in &reflection_cameras {
+        if mirrors.contains(reflection_camera.mirror) {
+            cameras_of_mirrors.insert(reflection_camera.mirror, entity);
+        } else {
+            commands.entity(entity).despawn();
+        }
+    }
+
+    let default_camera = reflected_cameras
+        .iter()
+        .filter(|(_, camera, ..)| {
+            camera.is_active && matches!(camera.target, RenderTarget::Window(_))
+        })
+        .min_by_key(|(_, camera, ..)| camera.order)
+        .map(|(entity, ..)| entity);
+
+    for (entity, reflection, mirror_transform, image, material) in &mirrors {
+        let resolution = reflection.resolution.max(UVec2::ONE);
+        let image = match image {
+            Some(PlanarReflectionImage(image)) => {
+                if images
+                    .get(image)
+                    .is_some_and(|image| image.size() != resolution)
+                {
+                    if let Some(image) = images.get_mut(image) {
+                        image.resize(extent(resolution));
+                    }
+                }
+                image.clone()
+            }
+            None => {
+                let image = images.add(new_reflection_image(resolution));
+                commands
+                    .entity(entity)
+                    .insert(PlanarReflectionImage(image.clone()));
+                image
+            }
+        };
+
+        if let Some(material) = material {
+            if mirror_materials
+                .get(material)
+                .is_some_and(|material| material.reflection.as_ref() != Some(&image))
+            {
+                if let Some(material) = mirror_materials.get_mut(material) {
+                    material.reflection = Some(image.clone());
+                }
+            }
+        }
+
+        let Some(camera) = cameras_of_mirrors.get(&entity) else {
+            commands
+                .spawn((
+                    Camera3dBundle {
+                        camera: Camera {
+                            target: RenderTarget::Image(image),
+                            order: -1,
+                            hdr: true,
+                            ..default()
+                        },
+                        tonemapping: Tonemapping::None,
+                        deband_dither: DebandDither::Disabled,
+                        ..default()
+                    },
+                    PlanarReflectionProjection::default(),
+                    PlanarReflectionCamera { mirror: entity },
+                ))
+                .remove::<Projection>();
+            continue;
+        };
+
+        let Ok((
+            _,
+            _,
+            mut camera,
+            mut transform,
+            mut global_transform,
+            mut projection,
+            mut exposure,
+        )) = reflection_cameras.get_mut(*camera)
+        else {
+            continue;
+        };
+
+        let Some((
+            _,
+            reflected_camera,
+            reflected_transform,
+            reflected_projection,
+            reflected_exposure,
+        )) = reflection
+            .camera
+            .or(default_camera)
+            .and_then(|entity| reflected_cameras.get(entity).ok())
+        else {
+            camera.is_active = false;
+            continue;
+        };
+
+        let normal = mirror_transform.up();
+        let origin = mirror_transform.translation();
+        let reflect = |direction: Vec3| direction - 2.0 * normal * normal.dot(direction);
+
+        // Mirroring the camera would make its transform left-handed, so the
+        // reflection camera keeps a right-handed transform and renders the
+        // reflection flipped horizontally.
+        *transform = Transform::from_translation(
+            origin + reflect(reflected_transform.translation() - origin),
+        )
+        .looking_to(
+            reflect(reflected_transform.forward()),
+            reflect(reflected_transform.up()),
+        );
+        *global_transform = GlobalTransform::from(*transform);
+
+        // The visible side of the plane is the side of the reflected camera.
+        let side = if normal.dot(reflected_transform.translation() - origin) < 0.0 {
+            -1.0
+        } else {
+            1.0
+        };
+        let world_clip_plane = side * normal.extend(-normal.dot(origin));
+
+        if camera.is_active != reflected_camera.is_active {
+            camera.is_active = reflected_camera.is_active;
+        }
+        *projection = PlanarReflectionProjection {
+            projection: reflected_projection.clone(),
+            clip_plane: global_transform.compute_matrix().transpose() * world_clip_plane,
+        };
+        let reflected_exposure = reflected_exposure.copied().unwrap_or_default();
+        if exposure.ev100 != reflected_exposure.ev100 {
+            *exposure = reflected_exposure;
+        }
+    }
+}
+
+fn extent(resolution: UVec2) -> Extent3d {
+    Extent3d {
+        width: resolution.x,
+        height: resolution.y,
+        depth_or_array_layers: 1,
+    }
+}
+
+/// Creates a zeroed image for a reflection.
+fn new_reflection_image(resolution: UVec2) -> Image {
+    let mut image = Image::new_fill(
+        extent(resolution),
+        TextureDimension::D2,
+        &[0; 8],
+        PLANAR_REFLECTION_TEXTURE_FORMAT,
+        RenderAssetUsages::default(),
+    );
+    image.texture_descriptor.usage =
+        TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST | TextureUsages::RENDER_ATTACHMENT;
+    image
+}
+
+/// Replaces the near plane of `projection` with `clip_plane`, a plane in view
+/// space whose positive side is visible, while keeping the depth of the
+/// visible points between 0.0 and 1.0.
+///
+/// This is the technique of Eric Lengyel's "Oblique View Frustum Depth
+/// Projection and Clipping", adapted to reversed depth: the plane is mapped
+/// to a depth of 1.0, and the far plane is tilted to go through the farthest
+/// corner of the frustum. If the plane faces away from that corner, the
+/// projection is returned unchanged.
+fn oblique_projection(projection: Mat4, clip_plane: Vec4) -> Mat4 {
+    let inverse_projection = projection.inverse();
+    let clip_space_plane = inverse_projection.transpose() * clip_plane;
+    let far_corner = inverse_projection
+        * Vec4::new(
+            clip_space_plane.x.signum(),
+            clip_space_plane.y.signum(),
+            0.0,
+            1.0,
+        );
+
+    let w_row = projection.row(3);
+    let scale = w_row.dot(far_corner) / clip_plane.dot(far_corner);
+    if !scale.is_finite() || scale <= 0.0 {
+        return projection;
+    }
+
+    // The depth of a point is `1.0 - scale * clip_plane.dot(point) / w`.
+    let z_row = w_row - clip_plane * scale;
+    let mut oblique = projection;
+    oblique.x_axis.z = z_row.x;
+    oblique.y_axis.z = z_row.y;
+    oblique.z_axis.z = z_row.z;
+    oblique.w_axis.z = z_row.w;
+    oblique
+}
+
+#[cfg(test)]
+mod tests {
+    use super::oblique_projection;
+    use bevy_math::{Vec3, Vec4};
+    use bevy_render::camera::{CameraProjection, PerspectiveProjection};
+
+    #[test]
+    fn oblique_projection_clips_behind_plane() {
+        let projection = PerspectiveProjection::default().get_projection_matrix();
+        // A tilted plane in front of the camera, facing away from it.
+        let normal = Vec3::new(0.0, 0.5, -1.0).normalize();
+        let point_on_plane = Vec3::new(0.0, 0.0, -5.0);
+        let clip_plane = normal.extend(-normal.dot(point_on_plane));
+        let oblique = oblique_projection(projection, clip_plane);
+
+        let depth = |point: Vec3| oblique.project_point3(point).z;
+        assert!((depth(point_on_plane) - 1.0).abs() < 1e-4);
+        assert!((depth(Vec3::new(1.0, -2.0, -5.0 - 1.0)) - 1.0).abs() < 1e-4);
+        // Beyond the plane, the points are visible.
+        assert!((0.0..1.0).contains(&depth(Vec3::new(0.0, 0.0, -20.0))));
+        assert!((0.0..1.0).contains(&depth(Vec3::new(0.5, 0.5, -1000.0))));
+        // Between the camera and the plane, they are clipped.
+        assert!(depth(Vec3::new(0.0, 0.0, -2.0)) > 1.0);
+
+        // The other coordinates are unchanged.
+        let point = Vec4::new(1.0, 2.0, -10.0, 1.0);
+        assert_eq!((oblique * point).x, (projection * point).x);
+        assert_eq!((oblique * point).w, (projection * point).w);
+    }
+}
@@ -846,6 +846,11 @@ pub fn queue_prepass_material_meshes<M: Material>(
                                 draw_function: opaque_draw_deferred,
                                 pipeline_id,
                                 asset_id: mesh_instance.mesh_asset_id,
+                                material_bind_group_id: material.get_bind_group_id().0,
+                                lightmap_image: render_lightmaps
+                                    .render_lightmaps
+                                    .get(visible_entity)
+                                    .map(|lightmap| lightmap.image),
                                 batch_range: 0..1,
                                 dynamic_offset: None,
                             });
@@ -855,6 +860,11 @@ pub fn queue_prepass_material_meshes<M: Material>(
                             draw_function: opaque_draw_prepass,
                             pipeline_id,
                             asset_id: mesh_instance.mesh_asset_id,
+                            material_bind_group_id: material.get_bind_group_id().0,
+                            lightmap_image: render_lightmaps
+                                .render_lightmaps
+                                .get(visible_entity)
+                                .map(|lightmap| lightmap.image),
                             batch_range: 0..1,
                             dynamic_offset: None,
                         });
@@ -48,6 +48,33 @@ fn unpack_offset_and_counts(cluster_index: u32) -> vec3<u32> {
 #endif
 }
 
+// Returns the offset of the decals of the cluster in the cluster light index lists, where they
+// follow the point and spot lights, and their count. Decals are only assigned to clusters when the
+// cluster buffers are storage buffers.
+fn unpack_decal_offset_and_count(cluster_index: u32) -> vec2<u32> {
+#if AVAILABLE_STORAGE_BUFFER_BINDINGS >= 3
+    let offset_and_counts = bindings::cluster_offsets_and_counts.data[cluster_index];
+    //  [ 31      ..      16 | 15   ..    0 ]
+    //  [   area light count | decal count ]
+    return vec2<u32>(offset_and_counts.x + offset_and_counts.y + offset_and_counts.z, offset_and_counts.w & 0xffffu);
+#else
+    return vec2<u32>(0u);
+#endif
+}
+
+// Returns the offset of the area lights of the cluster in the cluster light index lists, where
+// they follow the decals, and their count. Like decals, area lights are only assigned to clusters
+// when the cluster buffers are storage buffers.
+fn unpack_area_light_offset_and_count(cluster_index: u32) -> vec2<u32> {
+#if AVAILABLE_STORAGE_BUFFER_BINDINGS >= 3
+    let offset_and_counts = bindings::cluster_offsets_and_counts.data[cluster_index];
+    let decal_count = offset_and_counts.w & 0xffffu;
+    return vec2<u32>(offset_and_counts.x + offset_and_counts.y + offset_and_counts.z + decal_count, offset_and_counts.w >> 16u);
+#else
+    return vec2<u32>(0u);
+#endif
+}
+
 fn get_light_id(index: u32) -> u32 {
 #if AVAILABLE_STORAGE_BUFFER_BINDINGS >= 3
     return bindings::cluster_light_index_lists.data[index];
@@ -1,4 +1,5 @@
-use bevy_core_pipeline::core_3d::{Transparent3d, CORE_3D_DEPTH_FORMAT};
+use bevy_asset::AssetId;
+use bevy_core_pipeline::core_3d::{Opaque3dBinKey, Transparent3d, CORE_3D_DEPTH_FORMAT};
 use bevy_ecs::entity::EntityHashMap;
 use bevy_ecs::prelude::*;
 use bevy_math::{Mat4, UVec3, UVec4, Vec2, Vec3, Vec3Swizzles, Vec4, Vec4Swizzles};
@@ -36,6 +37,7 @@ pub struct ExtractedPointLight {
     pub radius: f32,
     pub transform: GlobalTransform,
     pub shadows_enabled: bool,
+    pub soft_shadows_enabled: bool,
     pub shadow_depth_bias: f32,
     pub shadow_normal_bias: f32,
     pub spot_light_angles: Option<(f32, f32)>,
@@ -47,6 +49,7 @@ pub struct ExtractedDirectionalLight {
     pub illuminance: f32,
     pub transform: GlobalTransform,
     pub shadows_enabled: bool,
+    pub soft_shadow_size: Option<f32>,
     pub shadow_depth_bias: f32,
     pub shadow_normal_bias: f32,
     pub cascade_shadow_config: CascadeShadowConfig,
@@ -151,6 +154,7 @@ bitflags::bitflags! {
     struct PointLightFlags: u32 {
         const SHADOWS_ENABLED            = 1 << 0;
         const SPOT_LIGHT_Y_NEGATIVE      = 1 << 1;
+        const SOFT_SHADOWS_ENABLED       = 1 << 2;
         const NONE                       = 0;
         const UNINITIALIZED              = 0xFFFF;
     }
@@ -175,6 +179,7 @@ pub struct GpuDirectionalLight {
     cascades_overlap_proportion: f32,
     depth_texture_base_index: u32,
     render_layers: u32,
+    soft_shadow_size: f32,
 }
 
 // NOTE: These must match the bit flags in bevy_pbr/src/render/mesh_view_types.wgsl!
@@ -228,6 +233,11 @@ pub const MAX_CASCADES_PER_LIGHT: usize = 1;
 pub struct ShadowSamplers {
     pub point_light_sampler: Sampler,
     pub directional_light_sampler: Sampler,
+    /// Reads the depths of the point light shadow maps, for the blocker search of soft shadows.
+    pub point_light_depth_sampler: Sampler,
+    /// Reads the depths of the directional light shadow maps, for the blocker search of soft
+    /// shadows.
+    pub directional_light_depth_sampler: Sampler,
 }
 
 // TODO: this pattern for initializing the shaders / pipeline isn't ideal. this should be handled by the asset system
@@ -256,6 +266,18 @@ impl FromWorld for ShadowSamplers {
                 compare: Some(CompareFunction::GreaterEqual),
                 ..Default::default()
             }),
+            point_light_depth_sampler: render_device.create_sampler(&SamplerDescriptor {
+                address_mode_u: AddressMode::ClampToEdge,
+                address_mode_v: AddressMode::ClampToEdge,
+                address_mode_w: AddressMode::ClampToEdge,
+                ..Default::default()
+            }),
+            directional_light_depth_sampler: render_device.create_sampler(&SamplerDescriptor {
+                address_mode_u: AddressMode::ClampToEdge,
+                address_mode_v: AddressMode::ClampToEdge,
+                address_mode_w: AddressMode::ClampToEdge,
+                ..Default::default()
+            }),
         }
     }
 }
@@ -270,8 +292,10 @@ pub struct ExtractedClusterConfig {
 }
 
 enum ExtractedClustersPointLightsElement {
-    ClusterHeader(u32, u32),
+    ClusterHeader(u32, u32, u32, u32),
     LightEntity(Entity),
+    DecalEntity(Entity),
+    AreaLightEntity(Entity),
 }
 
 #[derive(Component)]
@@ -294,9 +318,19 @@ pub fn extract_clusters(
             data.push(ExtractedClustersPointLightsElement::ClusterHeader(
                 cluster_lights.point_light_count as u32,
                 cluster_lights.spot_light_count as u32,
+                cluster_lights.decal_count as u32,
+                cluster_lights.area_light_count as u32,
             ));
-            for l in &cluster_lights.entities {
-                data.push(ExtractedClustersPointLightsElement::LightEntity(*l));
+            let light_count = cluster_lights.point_light_count + cluster_lights.spot_light_count;
+            let decals_end = light_count + cluster_lights.decal_count;
+            for (i, l) in cluster_lights.entities.iter().enumerate() {
+                data.push(if i < light_count {
+                    ExtractedClustersPointLightsElement::LightEntity(*l)
+                } else if i < decals_end {
+                    ExtractedClustersPointLightsElement::DecalEntity(*l)
+                } else {
+                    ExtractedClustersPointLightsElement::AreaLightEntity(*l)
+                });
             }
         }
 
@@ -394,6 +428,7 @@ pub fn extract_lights(
             radius: point_light.radius,
             transform: *transform,
             shadows_enabled: point_light.shadows_enabled,
+            soft_shadows_enabled: point_light.soft_shadows_enabled,
             shadow_depth_bias: point_light.shadow_depth_bias,
             // The factor of SQRT_2 is for the worst-case diagonal offset
             shadow_normal_bias: point_light.shadow_normal_bias
@@ -443,6 +478,7 @@ pub fn extract_lights(
                         radius: spot_light.radius,
                         transform: *transform,
                         shadows_enabled: spot_light.shadows_enabled,
+                        soft_shadows_enabled: spot_light.soft_shadows_enabled,
                         shadow_depth_bias: spot_light.shadow_depth_bias,
                         // The factor of SQRT_2 is for the worst-case diagonal offset
                         shadow_normal_bias: spot_light.shadow_normal_bias
@@ -483,6 +519,7 @@ pub fn extract_lights(
                 illuminance: directional_light.illuminance,
                 transform: *transform,
                 shadows_enabled: directional_light.shadows_enabled,
+                soft_shadow_size: directional_light.soft_shadow_size,
                 shadow_depth_bias: directional_light.shadow_depth_bias,
                 // The factor of SQRT_2 is for the worst-case diagonal offset
                 shadow_normal_bias: directional_light.shadow_normal_bias * std::f32::consts::SQRT_2,
@@ -834,6 +871,9 @@ pub fn prepare_lights(
                     && index - point_light_count < spot_light_shadow_maps_count))
         {
             flags |= PointLightFlags::SHADOWS_ENABLED;
+            if light.soft_shadows_enabled && light.radius > 0.0 {
+                flags |= PointLightFlags::SOFT_SHADOWS_ENABLED;
+            }
         }
 
         let (light_custom_data, spot_light_tan_angle) = match light.spot_light_angles {
@@ -919,6 +959,7 @@ pub fn prepare_lights(
             cascades_overlap_proportion: light.cascade_shadow_config.overlap_proportion,
             depth_texture_base_index: num_directional_cascades_enabled as u32,
             render_layers: light.render_layers.bits(),
+            soft_shadow_size: light.soft_shadow_size.unwrap_or(0.0).max(0.0),
         };
         if index < directional_shadow_enabled_count {
             num_directional_cascades_enabled += num_cascades;
@@ -1414,7 +1455,19 @@ impl ViewClusterBindings {
         }
     }
 
-    pub fn push_offset_and_counts(&mut self, offset: usize, point_count: usize, spot_count: usize) {
+    /// Pushes the offset of the indices of a cluster, and its light counts.
+    ///
+    /// The decal and area light counts are only stored in storage buffers, since
+    /// decals and area lights aren't supported otherwise. They share the last
+    /// component, with the decal count in its low 16 bits.
+    pub fn push_offset_and_counts(
+        &mut self,
+        offset: usize,
+        point_count: usize,
+        spot_count: usize,
+        decal_count: usize,
+        area_light_count: usize,
+    ) {
         match &mut self.buffers {
             ViewClusterBuffers::Uniform {
                 cluster_offsets_and_counts,
@@ -1438,7 +1491,7 @@ impl ViewClusterBindings {
                     offset as u32,
                     point_count as u32,
                     spot_count as u32,
-                    0,
+                    (decal_count as u32 & 0xffff) | ((area_light_count as u32) << 16),
                 ));
             }
         }
@@ -1539,12 +1592,15 @@ impl ViewClusterBindings {
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn prepare_clusters(
     mut commands: Commands,
     render_device: Res<RenderDevice>,
     render_queue: Res<RenderQueue>,
     mesh_pipeline: Res<MeshPipeline>,
     global_light_meta: Res<GlobalLightMeta>,
+    render_decals: Res<RenderDecals>,
+    render_area_lights: Res<RenderAreaLights>,
     views: Query<(Entity, &ExtractedClustersPointLights), With<RenderPhase<Transparent3d>>>,
 ) {
     let render_device = render_device.into_inner();
@@ -1562,12 +1618,16 @@ pub fn prepare_clusters(
                 ExtractedClustersPointLightsElement::ClusterHeader(
                     point_light_count,
                     spot_light_count,
+                    decal_count,
+                    area_light_count,
                 ) => {
                     let offset = view_clusters_bindings.n_indices();
                     view_clusters_bindings.push_offset_and_counts(
                         offset,
                         *point_light_count as usize,
                         *spot_light_count as usize,
+                        *decal_count as usize,
+                        *area_light_count as usize,
                     );
                 }
                 ExtractedClustersPointLightsElement::LightEntity(entity) => {
@@ -1581,6 +1641,19 @@ pub fn prepare_clusters(
                         view_clusters_bindings.push_index(*light_index);
                     }
                 }
+                ExtractedClustersPointLightsElement::DecalEntity(entity) => {
+                    // Decals are only clustered with storage buffers, which
+                    // have no limit on the number of indices.
+                    if let Some(decal_index) = render_decals.entity_to_index.get(entity) {
+                        view_clusters_bindings.push_index(*decal_index);
+                    }
+                }
+                ExtractedClustersPointLightsElement::AreaLightEntity(entity) => {
+                    // Likewise, area lights are only clustered with storage buffers.
+                    if let Some(area_light_index) = render_area_lights.entity_to_index.get(entity) {
+                        view_clusters_bindings.push_index(*area_light_index);
+                    }
+                }
             }
         }
 
@@ -1710,6 +1783,12 @@ pub fn queue_shadows<M: Material>(
                     pipeline: pipeline_id,
                     entity,
                     distance: 0.0, // TODO: sort front-to-back
+                    asset_id: mesh_instance.mesh_asset_id,
+                    material_bind_group_id: material.get_bind_group_id().0,
+                    lightmap_image: render_lightmaps
+                        .render_lightmaps
+                        .get(&entity)
+                        .map(|lightmap| lightmap.image),
                     batch_range: 0..1,
                     dynamic_offset: None,
                 });
@@ -1721,6 +1800,9 @@ pub fn queue_shadows<M: Material>(
 pub struct Shadow {
     pub distance: f32,
     pub entity: Entity,
+    pub asset_id: AssetId<Mesh>,
+    pub material_bind_group_id: Option<BindGroupId>,
+    pub lightmap_image: Option<AssetId<Image>>,
     pub pipeline: CachedRenderPipelineId,
     pub draw_function: DrawFunctionId,
     pub batch_range: Range<u32>,
@@ -1750,7 +1832,7 @@ impl PhaseItem for Shadow {
         // The shadow phase is sorted by pipeline id for performance reasons.
         // Grouping all draw commands using the same pipeline together performs
         // better than rebinding everything at a high rate.
-        radsort::sort_by_key(items, |item| item.sort_key());
+        sort_binned(items);
     }
 
     #[inline]
@@ -1774,6 +1856,19 @@ impl PhaseItem for Shadow {
     }
 }
 
+impl BinnedPhaseItem for Shadow {
+    type BinKey = Opaque3dBinKey;
+
+    #[inline]
+    fn bin_key(&self) -> Self::BinKey {
+        Opaque3dBinKey {
+            asset_id: self.asset_id,
+            material_bind_group_id: self.material_bind_group_id,
+            lightmap_image: self.lightmap_image,
+        }
+    }
+}
+
 impl CachedRenderPipelinePhaseItem for Shadow {
     #[inline]
     fn cached_pipeline(&self) -> CachedRenderPipelineId {
@@ -9,6 +9,7 @@ use bevy_asset::{load_internal_asset, AssetId, Handle};
 use bevy_core_pipeline::{
     core_3d::{AlphaMask3d, Opaque3d, Transmissive3d, Transparent3d, CORE_3D_DEPTH_FORMAT},
     deferred::{AlphaMask3dDeferred, Opaque3dDeferred},
+    prepass::{DeferredPrepass, DepthPrepass},
 };
 use bevy_derive::{Deref, DerefMut};
 use bevy_ecs::entity::EntityHashMap;
@@ -24,8 +25,15 @@ use bevy_render::{
         NoAutomaticBatching,
     },
     mesh::*,
+    occlusion_culling::{
+        prepare_occlusion_culling_views, OcclusionCulling, OcclusionCullingAabbs,
+        OcclusionCullingDraw, OcclusionCullingViews,
+    },
     render_asset::RenderAssets,
-    render_phase::{PhaseItem, RenderCommand, RenderCommandResult, TrackedRenderPass},
+    render_phase::{
+        CachedRenderPipelinePhaseItem, PhaseItem, RenderCommand, RenderCommandResult, RenderPhase,
+        TrackedRenderPass,
+    },
     render_resource::*,
     renderer::{RenderDevice, RenderQueue},
     texture::{
@@ -146,6 +154,14 @@ impl Plugin for MeshRenderPlugin {
                             batch_and_prepare_render_phase::<AlphaMask3dDeferred, MeshPipeline>,
                         )
                             .in_set(RenderSet::PrepareResources),
+                        (
+                            prepare_mesh_occlusion_culling_draws::<Opaque3d>
+                                .after(batch_and_prepare_render_phase::<Opaque3d, MeshPipeline>),
+                            prepare_mesh_occlusion_culling_draws::<AlphaMask3d>
+                                .after(batch_and_prepare_render_phase::<AlphaMask3d, MeshPipeline>),
+                        )
+                            .after(prepare_occlusion_culling_views)
+                            .in_set(RenderSet::PrepareResources),
                         write_batched_instance_buffer::<MeshPipeline>
                             .in_set(RenderSet::PrepareResourcesFlush),
                         prepare_skins.in_set(RenderSet::PrepareResources),
@@ -363,6 +379,12 @@ pub struct MeshPipeline {
     ///
     /// This affects whether reflection probes can be used.
     pub binding_arrays_are_usable: bool,
+
+    /// Whether clustered decals are usable on the current render device.
+    pub clustered_decals_are_usable: bool,
+
+    /// Whether clustered area lights are usable on the current render device.
+    pub clustered_area_lights_are_usable: bool,
 }
 
 impl FromWorld for MeshPipeline {
@@ -420,6 +442,8 @@ impl FromWorld for MeshPipeline {
             mesh_layouts: MeshLayouts::new(&render_device),
             per_object_buffer_batch_size: GpuArrayBuffer::<MeshUniform>::batch_size(&render_device),
             binding_arrays_are_usable: binding_arrays_are_usable(&render_device),
+            clustered_decals_are_usable: clustered_decals_are_usable(&render_device),
+            clustered_area_lights_are_usable: clustered_area_lights_are_usable(&render_device),
         }
     }
 }
@@ -489,7 +513,7 @@ bitflags::bitflags! {
     #[repr(transparent)]
     // NOTE: Apparently quadro drivers support up to 64x MSAA.
     /// MSAA uses the highest 3 bits for the MSAA log2(sample count) to support up to 128x MSAA.
-    pub struct MeshPipelineKey: u32 {
+    pub struct MeshPipelineKey: u64 {
         const NONE                              = 0;
         const HDR                               = 1 << 0;
         const TONEMAP_IN_SHADER                 = 1 << 1;
@@ -508,6 +532,9 @@ bitflags::bitflags! {
         const READS_VIEW_TRANSMISSION_TEXTURE   = 1 << 13;
         const LIGHTMAPPED                       = 1 << 14;
         const IRRADIANCE_VOLUME                 = 1 << 15;
+        const OIT_ENABLED                       = 1 << 16;
+        const SCREEN_SPACE_SUBSURFACE_SCATTERING = 1 << 17;
+        const SUBSURFACE_SCATTERING_PASS        = 1 << 18;
         const BLEND_RESERVED_BITS               = Self::BLEND_MASK_BITS << Self::BLEND_SHIFT_BITS; // ← Bitmask reserving bits for the blend state
         const BLEND_OPAQUE                      = 0 << Self::BLEND_SHIFT_BITS;                   // ← Values are just sequential within the mask, and can range from 0 to 3
         const BLEND_PREMULTIPLIED_ALPHA         = 1 << Self::BLEND_SHIFT_BITS;                   //
@@ -542,36 +569,36 @@ bitflags::bitflags! {
 }
 
 impl MeshPipelineKey {
-    const MSAA_MASK_BITS: u32 = 0b111;
-    const MSAA_SHIFT_BITS: u32 = 32 - Self::MSAA_MASK_BITS.count_ones();
+    const MSAA_MASK_BITS: u64 = 0b111;
+    const MSAA_SHIFT_BITS: u32 = 64 - Self::MSAA_MASK_BITS.count_ones();
 
-    const PRIMITIVE_TOPOLOGY_MASK_BITS: u32 = 0b111;
+    const PRIMITIVE_TOPOLOGY_MASK_BITS: u64 = 0b111;
     const PRIMITIVE_TOPOLOGY_SHIFT_BITS: u32 =
         Self::MSAA_SHIFT_BITS - Self::PRIMITIVE_TOPOLOGY_MASK_BITS.count_ones();
 
-    const BLEND_MASK_BITS: u32 = 0b11;
+    const BLEND_MASK_BITS: u64 = 0b11;
     const BLEND_SHIFT_BITS: u32 =
         Self::PRIMITIVE_TOPOLOGY_SHIFT_BITS - Self::BLEND_MASK_BITS.count_ones();
 
-    const TONEMAP_METHOD_MASK_BITS: u32 = 0b111;
+    const TONEMAP_METHOD_MASK_BITS: u64 = 0b111;
     const TONEMAP_METHOD_SHIFT_BITS: u32 =
         Self::BLEND_SHIFT_BITS - Self::TONEMAP_METHOD_MASK_BITS.count_ones();
 
-    const SHADOW_FILTER_METHOD_MASK_BITS: u32 = 0b11;
+    const SHADOW_FILTER_METHOD_MASK_BITS: u64 = 0b11;
     const SHADOW_FILTER_METHOD_SHIFT_BITS: u32 =
         Self::TONEMAP_METHOD_SHIFT_BITS - Self::SHADOW_FILTER_METHOD_MASK_BITS.count_ones();
 
-    const VIEW_PROJECTION_MASK_BITS: u32 = 0b11;
+    const VIEW_PROJECTION_MASK_BITS: u64 = 0b11;
     const VIEW_PROJECTION_SHIFT_BITS: u32 =
         Self::SHADOW_FILTER_METHOD_SHIFT_BITS - Self::VIEW_PROJECTION_MASK_BITS.count_ones();
 
-    const SCREEN_SPACE_SPECULAR_TRANSMISSION_MASK_BITS: u32 = 0b11;
+    const SCREEN_SPACE_SPECULAR_TRANSMISSION_MASK_BITS: u64 = 0b11;
     const SCREEN_SPACE_SPECULAR_TRANSMISSION_SHIFT_BITS: u32 = Self::VIEW_PROJECTION_SHIFT_BITS
         - Self::SCREEN_SPACE_SPECULAR_TRANSMISSION_MASK_BITS.count_ones();
 
     pub fn from_msaa_samples(msaa_samples: u32) -> Self {
         let msaa_bits =
-            (msaa_samples.trailing_zeros() & Self::MSAA_MASK_BITS) << Self::MSAA_SHIFT_BITS;
+            (msaa_samples.trailing_zeros() as u64 & Self::MSAA_MASK_BITS) << Self::MSAA_SHIFT_BITS;
         Self::from_bits_retain(msaa_bits)
     }
 
@@ -588,7 +615,7 @@ impl MeshPipelineKey {
     }
 
     pub fn from_primitive_topology(primitive_topology: PrimitiveTopology) -> Self {
-        let primitive_topology_bits = ((primitive_topology as u32)
+        let primitive_topology_bits = ((primitive_topology as u64)
             & Self::PRIMITIVE_TOPOLOGY_MASK_BITS)
             << Self::PRIMITIVE_TOPOLOGY_SHIFT_BITS;
         Self::from_bits_retain(primitive_topology_bits)
@@ -598,11 +625,11 @@ impl MeshPipelineKey {
         let primitive_topology_bits = (self.bits() >> Self::PRIMITIVE_TOPOLOGY_SHIFT_BITS)
             & Self::PRIMITIVE_TOPOLOGY_MASK_BITS;
         match primitive_topology_bits {
-            x if x == PrimitiveTopology::PointList as u32 => PrimitiveTopology::PointList,
-            x if x == PrimitiveTopology::LineList as u32 => PrimitiveTopology::LineList,
-            x if x == PrimitiveTopology::LineStrip as u32 => PrimitiveTopology::LineStrip,
-            x if x == PrimitiveTopology::TriangleList as u32 => PrimitiveTopology::TriangleList,
-            x if x == PrimitiveTopology::TriangleStrip as u32 => PrimitiveTopology::TriangleStrip,
+            x if x == PrimitiveTopology::PointList as u64 => PrimitiveTopology::PointList,
+            x if x == PrimitiveTopology::LineList as u64 => PrimitiveTopology::LineList,
+            x if x == PrimitiveTopology::LineStrip as u64 => PrimitiveTopology::LineStrip,
+            x if x == PrimitiveTopology::TriangleList as u64 => PrimitiveTopology::TriangleList,
+            x if x == PrimitiveTopology::TriangleStrip as u64 => PrimitiveTopology::TriangleStrip,
             _ => PrimitiveTopology::default(),
         }
     }
@@ -714,12 +741,27 @@ impl SpecializedMeshPipeline for MeshPipeline {
             shader_defs.push("SCREEN_SPACE_AMBIENT_OCCLUSION".into());
         }
 
+        if key.contains(MeshPipelineKey::OIT_ENABLED) {
+            shader_defs.push("OIT_ENABLED".into());
+        }
+
+        if key.contains(MeshPipelineKey::SCREEN_SPACE_SUBSURFACE_SCATTERING) {
+            shader_defs.push("SCREEN_SPACE_SUBSURFACE_SCATTERING".into());
+        }
+
         let vertex_buffer_layout = layout.get_layout(&vertex_attributes)?;
 
         let (label, blend, depth_write_enabled);
         let pass = key.intersection(MeshPipelineKey::BLEND_RESERVED_BITS);
         let mut is_opaque = false;
-        if pass == MeshPipelineKey::BLEND_ALPHA {
+        if key.contains(MeshPipelineKey::SUBSURFACE_SCATTERING_PASS) {
+            label = "subsurface_scattering_mesh_pipeline".into();
+            blend = None;
+            shader_defs.push("SUBSURFACE_SCATTERING_PASS".into());
+            // The subsurface scattering pass draws over the fragments the opaque pass wrote, at the
+            // same depth, so it doesn't need to write it again
+            depth_write_enabled = false;
+        } else if pass == MeshPipelineKey::BLEND_ALPHA {
             label = "alpha_blend_mesh_pipeline".into();
             blend = Some(BlendState::ALPHA_BLENDING);
             // For the transparent pass, fragments that are closer will be alpha blended
@@ -872,6 +914,14 @@ impl SpecializedMeshPipeline for MeshPipeline {
             shader_defs.push("IRRADIANCE_VOLUMES_ARE_USABLE".into());
         }
 
+        if self.clustered_decals_are_usable {
+            shader_defs.push("CLUSTERED_DECALS_ARE_USABLE".into());
+        }
+
+        if self.clustered_area_lights_are_usable {
+            shader_defs.push("CLUSTERED_AREA_LIGHTS_ARE_USABLE".into());
+        }
+
         let format = if key.contains(MeshPipelineKey::HDR) {
             ViewTarget::TEXTURE_FORMAT_HDR
         } else {
@@ -1150,19 +1200,24 @@ impl<P: PhaseItem, const I: usize> RenderCommand<P> for SetMeshBindGroup<I> {
 
 pub struct DrawMesh;
 impl<P: PhaseItem> RenderCommand<P> for DrawMesh {
-    type Param = (SRes<RenderAssets<Mesh>>, SRes<RenderMeshInstances>);
-    type ViewQuery = ();
+    type Param = (
+        SRes<RenderAssets<Mesh>>,
+        SRes<RenderMeshInstances>,
+        SRes<OcclusionCullingViews>,
+    );
+    type ViewQuery = Entity;
     type ItemQuery = ();
     #[inline]
     fn render<'w>(
         item: &P,
-        _view: (),
+        view: Entity,
         _item_query: Option<()>,
-        (meshes, mesh_instances): SystemParamItem<'w, '_, Self::Param>,
+        (meshes, mesh_instances, occlusion_culling_views): SystemParamItem<'w, '_, Self::Param>,
         pass: &mut TrackedRenderPass<'w>,
     ) -> RenderCommandResult {
         let meshes = meshes.into_inner();
         let mesh_instances = mesh_instances.into_inner();
+        let occlusion_culling_views = occlusion_culling_views.into_inner();
 
         let Some(mesh_instance) = mesh_instances.get(&item.entity()) else {
             return RenderCommandResult::Failure;
@@ -1180,6 +1235,11 @@ impl<P: PhaseItem> RenderCommand<P> for DrawMesh {
             0,
             &(batch_range.start as i32).to_le_bytes(),
         );
+        let indirect_draw = occlusion_culling_views
+            .get(&view)
+            .and_then(|view_occlusion_culling| {
+                view_occlusion_culling.indirect_draw(batch_range.start)
+            });
         match &gpu_mesh.buffer_info {
             GpuBufferInfo::Indexed {
                 buffer,
@@ -1187,16 +1247,79 @@ impl<P: PhaseItem> RenderCommand<P> for DrawMesh {
                 count,
             } => {
                 pass.set_index_buffer(buffer.slice(..), 0, *index_format);
-                pass.draw_indexed(0..*count, 0, batch_range.clone());
-            }
-            GpuBufferInfo::NonIndexed => {
-                pass.draw(0..gpu_mesh.vertex_count, batch_range.clone());
+                match indirect_draw {
+                    Some((indirect_buffer, offset)) => {
+                        pass.draw_indexed_indirect(indirect_buffer, offset);
+                    }
+                    None => pass.draw_indexed(0..*count, 0, batch_range.clone()),
+                }
             }
+            GpuBufferInfo::NonIndexed => match indirect_draw {
+                Some((indirect_buffer, offset)) => pass.draw_indirect(indirect_buffer, offset),
+                None => pass.draw(0..gpu_mesh.vertex_count, batch_range.clone()),
+            },
         }
         RenderCommandResult::Success
     }
 }
 
+/// Adds the batches of meshes of a render phase of the views with [`OcclusionCulling`] to their
+/// indirect draws, culled on the GPU after the depth prepass.
+///
+/// The views without a depth prepass are skipped, as their depth is only drawn by the main
+/// passes.
+pub fn prepare_mesh_occlusion_culling_draws<I: CachedRenderPipelinePhaseItem>(
+    views: Query<
+        (Entity, &RenderPhase<I>),
+        (
+            With<OcclusionCulling>,
+            Or<(With<DepthPrepass>, With<DeferredPrepass>)>,
+        ),
+    >,
+    mut occlusion_culling_views: ResMut<OcclusionCullingViews>,
+    aabbs: Res<OcclusionCullingAabbs>,
+    mesh_instances: Res<RenderMeshInstances>,
+    meshes: Res<RenderAssets<Mesh>>,
+) {
+    for (view, phase) in &views {
+        let Some(view_occlusion_culling) = occlusion_culling_views.get_mut(&view) else {
+            continue;
+        };
+
+        let mut index = 0;
+        while index < phase.items.len() {
+            let batch_range = phase.items[index].batch_range().clone();
+            let batch = &phase.items[index..(index + batch_range.len()).min(phase.items.len())];
+            index += batch_range.len().max(1);
+
+            let Some(gpu_mesh) = batch
+                .first()
+                .and_then(|item| meshes.get(mesh_instances.get(&item.entity())?.mesh_asset_id))
+            else {
+                continue;
+            };
+            // Meshes without bounding boxes are never culled.
+            let Some(batch_aabbs) = batch
+                .iter()
+                .map(|item| aabbs.get(&item.entity()).copied())
+                .collect::<Option<Vec<_>>>()
+            else {
+                continue;
+            };
+
+            let draw = match gpu_mesh.buffer_info {
+                GpuBufferInfo::Indexed { count, .. } => {
+                    OcclusionCullingDraw::Indexed { index_count: count }
+                }
+                GpuBufferInfo::NonIndexed => OcclusionCullingDraw::NonIndexed {
+                    vertex_count: gpu_mesh.vertex_count,
+                },
+            };
+            view_occlusion_culling.push_draw(draw, batch_range, batch_aabbs);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::MeshPipelineKey;
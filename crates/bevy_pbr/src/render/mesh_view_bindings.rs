@@ -2,6 +2,7 @@ use std::{array, num::NonZeroU64};
 
 use bevy_core_pipeline::{
     core_3d::ViewTransmissionTexture,
+    oit::{oit_is_supported, OrderIndependentTransparencyUniform, ViewOitBuffers},
     prepass::ViewPrepassTextures,
     tonemapping::{
         get_lut_bind_group_layout_entries, get_lut_bindings, Tonemapping, TonemappingLuts,
@@ -32,6 +33,8 @@ use bevy_render::render_resource::binding_types::{texture_2d_array, texture_cube
 use environment_map::EnvironmentMapLight;
 
 use crate::{
+    area_light::{self, RenderAreaLights, RenderAreaLightsBindGroupEntries},
+    decal::{self, RenderDecals, RenderDecalsBindGroupEntries},
     environment_map::{self, RenderViewEnvironmentMapBindGroupEntries},
     irradiance_volume::{
         self, IrradianceVolume, RenderViewIrradianceVolumeBindGroupEntries,
@@ -64,6 +67,7 @@ bitflags::bitflags! {
         const NORMAL_PREPASS              = 1 << 2;
         const MOTION_VECTOR_PREPASS       = 1 << 3;
         const DEFERRED_PREPASS            = 1 << 4;
+        const OIT_ENABLED                 = 1 << 5;
     }
 }
 
@@ -76,7 +80,7 @@ impl MeshPipelineViewLayoutKey {
         use MeshPipelineViewLayoutKey as Key;
 
         format!(
-            "mesh_view_layout{}{}{}{}{}",
+            "mesh_view_layout{}{}{}{}{}{}",
             self.contains(Key::MULTISAMPLED)
                 .then_some("_multisampled")
                 .unwrap_or_default(),
@@ -92,6 +96,9 @@ impl MeshPipelineViewLayoutKey {
             self.contains(Key::DEFERRED_PREPASS)
                 .then_some("_deferred")
                 .unwrap_or_default(),
+            self.contains(Key::OIT_ENABLED)
+                .then_some("_oit")
+                .unwrap_or_default(),
         )
     }
 }
@@ -115,6 +122,9 @@ impl From<MeshPipelineKey> for MeshPipelineViewLayoutKey {
         if value.contains(MeshPipelineKey::DEFERRED_PREPASS) {
             result |= MeshPipelineViewLayoutKey::DEFERRED_PREPASS;
         }
+        if value.contains(MeshPipelineKey::OIT_ENABLED) {
+            result |= MeshPipelineViewLayoutKey::OIT_ENABLED;
+        }
 
         result
     }
@@ -321,6 +331,49 @@ fn layout_entries(
         (25, sampler(SamplerBindingType::Filtering)),
     ));
 
+    // Clustered decals
+    if decal::clustered_decals_are_usable(render_device) {
+        let decal_entries = decal::get_decal_bind_group_layout_entries();
+        entries = entries.extend_with_indices((
+            (26, decal_entries[0]),
+            (27, decal_entries[1]),
+            (28, decal_entries[2]),
+        ));
+    }
+
+    // Shadow map depth samplers, for soft shadows
+    entries = entries.extend_with_indices((
+        (29, sampler(SamplerBindingType::NonFiltering)),
+        (30, sampler(SamplerBindingType::NonFiltering)),
+    ));
+
+    // Order independent transparency
+    if layout_key.contains(MeshPipelineViewLayoutKey::OIT_ENABLED)
+        && oit_is_supported(render_device)
+    {
+        entries = entries.extend_with_indices((
+            // Layers
+            (31, storage_buffer_sized(false, None)),
+            // Layer ids
+            (32, storage_buffer_sized(false, None)),
+            (
+                33,
+                uniform_buffer::<OrderIndependentTransparencyUniform>(false),
+            ),
+        ));
+    }
+
+    // Clustered area lights
+    if area_light::clustered_area_lights_are_usable(render_device) {
+        let area_light_entries = area_light::get_area_light_bind_group_layout_entries();
+        entries = entries.extend_with_indices((
+            (34, area_light_entries[0]),
+            (35, area_light_entries[1]),
+            (36, area_light_entries[2]),
+            (37, area_light_entries[3]),
+        ));
+    }
+
     entries.to_vec()
 }
 
@@ -374,6 +427,7 @@ pub fn prepare_mesh_view_bind_groups(
         &Tonemapping,
         Option<&RenderViewLightProbes<EnvironmentMapLight>>,
         Option<&RenderViewLightProbes<IrradianceVolume>>,
+        Option<&ViewOitBuffers>,
     )>,
     (images, mut fallback_images, fallback_image, fallback_image_zero): (
         Res<RenderAssets<Image>>,
@@ -385,6 +439,8 @@ pub fn prepare_mesh_view_bind_groups(
     globals_buffer: Res<GlobalsBuffer>,
     tonemapping_luts: Res<TonemappingLuts>,
     light_probes_buffer: Res<LightProbesBuffer>,
+    render_decals: Res<RenderDecals>,
+    render_area_lights: Res<RenderAreaLights>,
 ) {
     if let (
         Some(view_binding),
@@ -411,6 +467,7 @@ pub fn prepare_mesh_view_bind_groups(
             tonemapping,
             render_view_environment_maps,
             render_view_irradiance_volumes,
+            oit_buffers,
         ) in &views
         {
             let fallback_ssao = fallback_images
@@ -421,10 +478,12 @@ pub fn prepare_mesh_view_bind_groups(
                 .map(|t| &t.screen_space_ambient_occlusion_texture.default_view)
                 .unwrap_or(&fallback_ssao);
 
-            let layout = &mesh_pipeline.get_view_layout(
-                MeshPipelineViewLayoutKey::from(*msaa)
-                    | MeshPipelineViewLayoutKey::from(prepass_textures),
-            );
+            let mut layout_key = MeshPipelineViewLayoutKey::from(*msaa)
+                | MeshPipelineViewLayoutKey::from(prepass_textures);
+            if oit_buffers.is_some() {
+                layout_key |= MeshPipelineViewLayoutKey::OIT_ENABLED;
+            }
+            let layout = &mesh_pipeline.get_view_layout(layout_key);
 
             let mut entries = DynamicBindGroupEntries::new_with_indices((
                 (0, view_binding.clone()),
@@ -531,6 +590,57 @@ pub fn prepare_mesh_view_bind_groups(
             entries =
                 entries.extend_with_indices(((24, transmission_view), (25, transmission_sampler)));
 
+            let decal_bind_group_entries = if mesh_pipeline.clustered_decals_are_usable {
+                render_decals.bind_group_entries(&images, &fallback_image)
+            } else {
+                None
+            };
+            if let Some(RenderDecalsBindGroupEntries {
+                decals,
+                ref texture_views,
+                sampler,
+            }) = decal_bind_group_entries
+            {
+                entries = entries.extend_with_indices((
+                    (26, decals),
+                    (27, texture_views.as_slice()),
+                    (28, sampler),
+                ));
+            }
+
+            entries = entries.extend_with_indices((
+                (29, &shadow_samplers.point_light_depth_sampler),
+                (30, &shadow_samplers.directional_light_depth_sampler),
+            ));
+
+            if let Some(oit_buffers) = oit_buffers {
+                entries = entries.extend_with_indices((
+                    (31, oit_buffers.layers.as_entire_binding()),
+                    (32, oit_buffers.layer_ids.as_entire_binding()),
+                    (33, oit_buffers.settings.as_entire_binding()),
+                ));
+            }
+
+            let area_light_bind_group_entries = if mesh_pipeline.clustered_area_lights_are_usable {
+                render_area_lights.bind_group_entries(&images, &fallback_image)
+            } else {
+                None
+            };
+            if let Some(RenderAreaLightsBindGroupEntries {
+                area_lights,
+                ref texture_views,
+                ltc_lut,
+                sampler,
+            }) = area_light_bind_group_entries
+            {
+                entries = entries.extend_with_indices((
+                    (34, area_lights),
+                    (35, texture_views.as_slice()),
+                    (36, ltc_lut),
+                    (37, sampler),
+                ));
+            }
+
             commands.entity(entity).insert(MeshViewBindGroup {
                 value: render_device.create_bind_group("mesh_view_bind_group", layout, &entries),
             });
@@ -90,3 +90,27 @@
 
 @group(0) @binding(24) var view_transmission_texture: texture_2d<f32>;
 @group(0) @binding(25) var view_transmission_sampler: sampler;
+
+#ifdef CLUSTERED_DECALS_ARE_USABLE
+@group(0) @binding(26) var<storage> clustered_decals: types::ClusteredDecals;
+// The size of the binding array must match `MAX_DECAL_TEXTURES` on the Rust side.
+@group(0) @binding(27) var clustered_decal_textures: binding_array<texture_2d<f32>, 16u>;
+@group(0) @binding(28) var clustered_decal_sampler: sampler;
+#endif
+
+@group(0) @binding(29) var point_shadow_textures_depth_sampler: sampler;
+@group(0) @binding(30) var directional_shadow_textures_depth_sampler: sampler;
+
+#ifdef OIT_ENABLED
+@group(0) @binding(31) var<storage, read_write> oit_layers: array<vec2<u32>>;
+@group(0) @binding(32) var<storage, read_write> oit_layer_ids: array<atomic<u32>>;
+@group(0) @binding(33) var<uniform> oit_settings: types::OrderIndependentTransparencySettings;
+#endif
+
+#ifdef CLUSTERED_AREA_LIGHTS_ARE_USABLE
+@group(0) @binding(34) var<storage> clustered_area_lights: types::ClusteredAreaLights;
+// The size of the binding array must match `MAX_AREA_LIGHT_TEXTURES` on the Rust side.
+@group(0) @binding(35) var area_light_textures: binding_array<texture_2d<f32>, 8u>;
+@group(0) @binding(36) var area_light_ltc_lut: texture_2d_array<f32>;
+@group(0) @binding(37) var area_light_sampler: sampler;
+#endif
@@ -15,6 +15,7 @@ struct PointLight {
 
 const POINT_LIGHT_FLAGS_SHADOWS_ENABLED_BIT: u32   = 1u;
 const POINT_LIGHT_FLAGS_SPOT_LIGHT_Y_NEGATIVE: u32 = 2u;
+const POINT_LIGHT_FLAGS_SOFT_SHADOWS_ENABLED_BIT: u32 = 4u;
 
 struct DirectionalCascade {
     view_projection: mat4x4<f32>,
@@ -34,6 +35,8 @@ struct DirectionalLight {
     cascades_overlap_proportion: f32,
     depth_texture_base_index: u32,
     render_layers: u32,
+    // The angular diameter of the light, or 0.0 for hard shadows.
+    soft_shadow_size: f32,
 };
 
 const DIRECTIONAL_LIGHT_FLAGS_SHADOWS_ENABLED_BIT: u32 = 1u;
@@ -117,8 +120,15 @@ struct LightProbe {
     inverse_transpose_transform: mat3x4<f32>,
     cubemap_index: i32,
     intensity: f32,
+    // The fraction of the distance from the faces of the box to its center over which a reflection
+    // probe fades out.
+    blend_fraction: f32,
+    // 'flags' is a bit field indicating various options. u32 is 32 bits so we have up to 32 options.
+    flags: u32,
 };
 
+const REFLECTION_PROBE_FLAGS_PARALLAX_CORRECTION_BIT: u32 = 1u;
+
 struct LightProbes {
     // This must match `MAX_VIEW_REFLECTION_PROBES` on the Rust side.
     reflection_probes: array<LightProbe, 8u>,
@@ -134,3 +144,47 @@ struct LightProbes {
     // The intensity of the environment map associated with the view.
     intensity_for_view: f32,
 };
+
+struct ClusteredDecal {
+    // The transform from world space to the projection box of the decal. Like the one of light
+    // probes, it's stored as the transpose in order to save space in this structure.
+    inverse_transpose_transform: mat3x4<f32>,
+    base_color: vec4<f32>,
+    // The indices of the textures in the binding array of decal textures, or -1 if the decal
+    // doesn't have that texture.
+    base_color_texture_index: i32,
+    normal_map_texture_index: i32,
+    occlusion_roughness_metallic_texture_index: i32,
+    depth_fade: f32,
+    angle_fade: f32,
+};
+
+struct ClusteredDecals {
+    data: array<ClusteredDecal>,
+};
+
+const AREA_LIGHT_FLAGS_DISK_BIT: u32      = 1u;
+const AREA_LIGHT_FLAGS_TWO_SIDED_BIT: u32 = 2u;
+
+struct ClusteredAreaLight {
+    position: vec3<f32>,
+    inverse_range_squared: f32,
+    // Half the width and half the height of the light, along its local X and Y axes, in world
+    // space. The light emits along the opposite of their cross product.
+    x_axis: vec3<f32>,
+    flags: u32,
+    y_axis: vec3<f32>,
+    // The index of the texture in the binding array of area light textures, or -1 if the light
+    // doesn't have one.
+    texture_index: i32,
+    radiance: vec3<f32>,
+};
+
+struct ClusteredAreaLights {
+    data: array<ClusteredAreaLight>,
+};
+
+// Must match `OrderIndependentTransparencyUniform` on the Rust side.
+struct OrderIndependentTransparencySettings {
+    layer_count: u32,
+};
@@ -12,15 +12,33 @@
 #import bevy_pbr::{
     forward_io::{VertexOutput, FragmentOutput},
     pbr_functions::{apply_pbr_lighting, main_pass_post_lighting_processing},
-    pbr_types::STANDARD_MATERIAL_FLAGS_UNLIT_BIT,
+    pbr_types,
+    pbr_bindings,
+    subsurface_scattering::{subsurface_scattering, subsurface_scattering_marker},
 }
 #endif
 
+#ifdef OIT_ENABLED
+#import bevy_core_pipeline::oit::oit_draw
+#endif
+
 @fragment
 fn fragment(
     in: VertexOutput,
     @builtin(front_facing) is_front: bool,
 ) -> FragmentOutput {
+#ifdef SUBSURFACE_SCATTERING_PASS
+    // This surface was already lit by the opaque pass, so the subsurface scattering pass only
+    // diffuses that light over it.
+    var out: FragmentOutput;
+    out.color = subsurface_scattering(
+        in.position,
+        pbr_bindings::material.subsurface_scattering,
+        pbr_bindings::material.subsurface_color.rgb,
+        pbr_bindings::material.subsurface_radius,
+    );
+#else
+
     // generate a PbrInput struct from the StandardMaterial bindings
     var pbr_input = pbr_input_from_standard_material(in, is_front);
 
@@ -34,7 +52,7 @@ fn fragment(
     // in forward mode, we calculate the lit color immediately, and then apply some post-lighting effects here.
     // in deferred mode the lit color and these effects will be calculated in the deferred lighting shader
     var out: FragmentOutput;
-    if (pbr_input.material.flags & STANDARD_MATERIAL_FLAGS_UNLIT_BIT) == 0u {
+    if (pbr_input.material.flags & pbr_types::STANDARD_MATERIAL_FLAGS_UNLIT_BIT) == 0u {
         out.color = apply_pbr_lighting(pbr_input);
     } else {
         out.color = pbr_input.material.base_color;
@@ -43,6 +61,26 @@ fn fragment(
     // apply in-shader post processing (fog, alpha-premultiply, and also tonemapping, debanding if the camera is non-hdr)
     // note this does not include fullscreen postprocessing effects like bloom.
     out.color = main_pass_post_lighting_processing(pbr_input, out.color);
+
+#ifdef SCREEN_SPACE_SUBSURFACE_SCATTERING
+    // Mark the surface for the subsurface scattering pass, which writes an opaque alpha back.
+    out.color.a = subsurface_scattering_marker(in.world_position.xyz);
+#endif
+
+#ifdef OIT_ENABLED
+    // The blended fragments are stored for the order independent transparency, which blends them
+    // in the order of their depth in the resolve pass.
+    let alpha_mode = pbr_input.material.flags & pbr_types::STANDARD_MATERIAL_FLAGS_ALPHA_MODE_RESERVED_BITS;
+    if alpha_mode == pbr_types::STANDARD_MATERIAL_FLAGS_ALPHA_MODE_BLEND {
+        oit_draw(in.position, vec4(out.color.rgb * out.color.a, out.color.a));
+        discard;
+    } else if alpha_mode == pbr_types::STANDARD_MATERIAL_FLAGS_ALPHA_MODE_PREMULTIPLIED
+        || alpha_mode == pbr_types::STANDARD_MATERIAL_FLAGS_ALPHA_MODE_ADD {
+        oit_draw(in.position, out.color);
+        discard;
+    }
+#endif
+#endif
 #endif
 
     return out;
@@ -18,6 +18,10 @@
 #import bevy_pbr::gtao_utils::gtao_multibounce
 #endif
 
+#ifdef CLUSTERED_DECALS_ARE_USABLE
+#import bevy_pbr::decal::apply_decals
+#endif
+
 #ifdef PREPASS_PIPELINE
 #import bevy_pbr::prepass_io::VertexOutput
 #else
@@ -211,5 +215,9 @@ fn pbr_input_from_standard_material(
 #endif
     }
 
+#ifdef CLUSTERED_DECALS_ARE_USABLE
+    pbr_input = apply_decals(pbr_input);
+#endif
+
     return pbr_input;
 }
@@ -19,6 +19,10 @@
 #import bevy_pbr::environment_map
 #endif
 
+#ifdef CLUSTERED_AREA_LIGHTS_ARE_USABLE
+#import bevy_pbr::area_light::area_light
+#endif
+
 #import bevy_core_pipeline::tonemapping::{screen_space_dither, powsafe, tone_mapping}
 
 fn alpha_discard(material: pbr_types::StandardMaterial, output_color: vec4<f32>) -> vec4<f32> {
@@ -266,6 +270,15 @@ fn apply_pbr_lighting(
 #endif
     }
 
+#ifdef CLUSTERED_AREA_LIGHTS_ARE_USABLE
+    // Area lights (direct), which don't cast shadows
+    let area_light_offset_and_count = clustering::unpack_area_light_offset_and_count(cluster_index);
+    for (var i: u32 = area_light_offset_and_count[0]; i < area_light_offset_and_count[0] + area_light_offset_and_count[1]; i = i + 1u) {
+        let light_id = clustering::get_light_id(i);
+        direct_light += area_light(light_id, in.world_position.xyz, perceptual_roughness, NdotV, in.N, in.V, F0, f_ab, diffuse_color);
+    }
+#endif
+
     // directional lights (direct)
     let n_directional_lights = view_bindings::lights.n_directional_lights;
     for (var i: u32 = 0u; i < n_directional_lights; i = i + 1u) {
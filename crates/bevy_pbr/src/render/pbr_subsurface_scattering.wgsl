@@ -0,0 +1,94 @@
+#define_import_path bevy_pbr::subsurface_scattering
+
+#import bevy_pbr::{
+    mesh_view_bindings as view_bindings,
+    utils::{PI, interleaved_gradient_noise},
+    view_transformations::position_world_to_view,
+}
+
+// The number of pixels sampled around each pixel of a subsurface scattering surface.
+const SUBSURFACE_SCATTERING_SAMPLE_COUNT: u32 = 16u;
+// Keeps the kernel from getting too sparse when a surface fills the screen.
+const SUBSURFACE_SCATTERING_MAX_PIXEL_RADIUS: f32 = 64.0;
+const GOLDEN_ANGLE: f32 = 2.399963229728653;
+
+// The alpha that the opaque pass writes for subsurface scattering surfaces: their view space Z,
+// which is negative. Every other opaque surface writes a positive alpha, which tells them apart
+// when the scattering is blurred from the view transmission texture.
+fn subsurface_scattering_marker(world_position: vec3<f32>) -> f32 {
+    return min(position_world_to_view(world_position).z, -1e-4);
+}
+
+// Screen space subsurface scattering, after Jorge Jimenez's "Separable Subsurface Scattering".
+//
+// Diffuses the light the opaque pass left in the view transmission texture over the surface,
+// following a gaussian diffusion profile per color channel whose widths are given by `radius`, in
+// world units. Samples are weighted by their distance to the pixel in view space, so the light
+// doesn't bleed across depth discontinuities, and samples of surfaces that don't scatter light
+// are replaced by the pixel's own color.
+fn subsurface_scattering(
+    frag_coord: vec4<f32>,
+    strength: f32,
+    color: vec3<f32>,
+    radius: vec3<f32>,
+) -> vec4<f32> {
+    let dimensions = vec2<i32>(textureDimensions(view_bindings::view_transmission_texture));
+    let center_texel = vec2<i32>(frag_coord.xy);
+    let center = textureLoad(view_bindings::view_transmission_texture, center_texel, 0);
+
+    // This pixel wasn't marked by the opaque pass, for instance because the material's alpha mask
+    // discarded it there, so this isn't our surface.
+    if center.a >= 0.0 {
+        discard;
+    }
+    let center_depth = -center.a;
+
+    // The number of pixels per world unit at the depth of the pixel.
+    let projection = view_bindings::view.projection;
+    var pixels_per_unit = 0.5 * projection[1][1] * view_bindings::view.viewport.w;
+    if projection[3][3] != 1.0 {
+        pixels_per_unit /= center_depth;
+    }
+
+    let max_radius = max(radius.x, max(radius.y, radius.z));
+    let pixel_radius = min(max_radius * pixels_per_unit, SUBSURFACE_SCATTERING_MAX_PIXEL_RADIUS);
+    if strength <= 0.0 || pixel_radius < 1.0 {
+        return vec4(center.rgb, 1.0);
+    }
+
+    // The variance of the profile of each channel, which fades out at the scattering radius.
+    let variance = max(radius * radius * 0.25, vec3(1e-8));
+
+    // Rotate the spiral per pixel and per frame, which temporal antialiasing smooths out.
+    let noise = interleaved_gradient_noise(frag_coord.xy, view_bindings::globals.frame_count);
+    let rotation = noise * 2.0 * PI;
+
+    var total = center.rgb;
+    var total_weight = vec3(1.0);
+    for (var i = 0u; i < SUBSURFACE_SCATTERING_SAMPLE_COUNT; i += 1u) {
+        // Spread the samples evenly over the disk, so that each covers the same area.
+        let t = (f32(i) + 0.5) / f32(SUBSURFACE_SCATTERING_SAMPLE_COUNT);
+        let angle = f32(i) * GOLDEN_ANGLE + rotation;
+        let offset = vec2(cos(angle), sin(angle)) * sqrt(t) * pixel_radius;
+        let texel = clamp(center_texel + vec2<i32>(round(offset)), vec2(0), dimensions - 1);
+
+        var neighbor = textureLoad(view_bindings::view_transmission_texture, texel, 0);
+        var neighbor_depth = -neighbor.a;
+        if neighbor.a >= 0.0 {
+            neighbor = center;
+            neighbor_depth = center_depth;
+        }
+
+        let distance_in_plane = length(offset) / pixels_per_unit;
+        let distance_in_depth = neighbor_depth - center_depth;
+        let distance_squared = distance_in_plane * distance_in_plane
+            + distance_in_depth * distance_in_depth;
+        let weight = exp(-distance_squared / (2.0 * variance));
+
+        total += neighbor.rgb * weight;
+        total_weight += weight;
+    }
+
+    let scattered = total / total_weight * color;
+    return vec4(mix(center.rgb, scattered, strength), 1.0);
+}
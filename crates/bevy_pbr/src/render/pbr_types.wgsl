@@ -6,6 +6,9 @@ struct StandardMaterial {
     base_color: vec4<f32>,
     emissive: vec4<f32>,
     attenuation_color: vec4<f32>,
+    subsurface_color: vec4<f32>,
+    subsurface_radius: vec3<f32>,
+    subsurface_scattering: f32,
     uv_transform: mat3x2<f32>,
     perceptual_roughness: f32,
     metallic: f32,
@@ -71,6 +74,9 @@ fn standard_material_new() -> StandardMaterial {
     material.ior = 1.5;
     material.attenuation_distance = 1.0;
     material.attenuation_color = vec4<f32>(1.0, 1.0, 1.0, 1.0);
+    material.subsurface_color = vec4<f32>(1.0, 1.0, 1.0, 1.0);
+    material.subsurface_radius = vec3<f32>(0.01, 0.01, 0.01);
+    material.subsurface_scattering = 0.0;
     material.flags = STANDARD_MATERIAL_FLAGS_ALPHA_MODE_OPAQUE;
     material.alpha_cutoff = 0.5;
     material.parallax_depth_scale = 0.1;
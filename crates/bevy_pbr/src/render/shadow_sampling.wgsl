@@ -126,3 +126,195 @@ fn sample_shadow_map(light_local: vec2<f32>, depth: f32, array_index: i32, texel
     return 0.0;
 #endif
 }
+
+// Percentage-closer soft shadows.
+// https://developer.download.nvidia.com/shaderlibrary/docs/shadow_PCSS.pdf
+//
+// The blocker search averages the depths of the occluders found in a disc around the fragment in
+// the shadow map. The penumbra is then estimated from the distances from the light to the
+// occluders and to the fragment, and the shadow map is filtered over it. Both use the spiral of
+// `sample_shadow_map_jimenez_fourteen`, randomly rotated per pixel, so their noise is smoothed by
+// temporal anti-aliasing.
+
+// The random rotation of the samples of the soft shadows of the fragment at `frag_position`.
+fn soft_shadow_rotation(frag_position: vec4<f32>) -> mat2x2<f32> {
+    let clip_position = view_bindings::view.view_proj * vec4(frag_position.xyz, 1.0);
+    let uv = clip_position.xy / clip_position.w * vec2(0.5, -0.5) + 0.5;
+    let pixel = uv * view_bindings::view.viewport.zw;
+    let random_angle = 2.0 * PI * interleaved_gradient_noise(pixel, view_bindings::globals.frame_count);
+    let m = vec2(sin(random_angle), cos(random_angle));
+    return mat2x2(
+        m.y, -m.x,
+        m.x, m.y
+    );
+}
+
+fn soft_shadow_sample_offset(index: u32, rotation: mat2x2<f32>) -> vec2<f32> {
+    var offsets = array(
+        utils::SPIRAL_OFFSET_0_,
+        utils::SPIRAL_OFFSET_1_,
+        utils::SPIRAL_OFFSET_2_,
+        utils::SPIRAL_OFFSET_3_,
+        utils::SPIRAL_OFFSET_4_,
+        utils::SPIRAL_OFFSET_5_,
+        utils::SPIRAL_OFFSET_6_,
+        utils::SPIRAL_OFFSET_7_,
+    );
+    return rotation * offsets[index];
+}
+
+// Reads the depth stored in the shadow map, without comparison.
+fn sample_shadow_map_depth(light_local: vec2<f32>, array_index: i32) -> f32 {
+#ifdef NO_ARRAY_TEXTURES_SUPPORT
+    return textureSampleLevel(
+        view_bindings::directional_shadow_textures,
+        view_bindings::directional_shadow_textures_depth_sampler,
+        light_local,
+        0.0,
+    );
+#else
+    return textureSampleLevel(
+        view_bindings::directional_shadow_textures,
+        view_bindings::directional_shadow_textures_depth_sampler,
+        light_local,
+        array_index,
+        0.0,
+    );
+#endif
+}
+
+// The average depth of the occluders of `depth` within `search_radius` of `light_local` in the
+// shadow map, or 0.0 if there are none.
+fn search_for_blockers_in_shadow_map(
+    light_local: vec2<f32>,
+    depth: f32,
+    array_index: i32,
+    search_radius: f32,
+    rotation: mat2x2<f32>,
+) -> f32 {
+    var blocker_depth_sum = 0.0;
+    var blocker_count = 0.0;
+    for (var i = 0u; i < 8u; i += 1u) {
+        let offset = soft_shadow_sample_offset(i, rotation) * search_radius;
+        let blocker_depth = sample_shadow_map_depth(light_local + offset, array_index);
+        // The depth is reversed, so the occluders are closer to 1.0.
+        if blocker_depth > depth {
+            blocker_depth_sum += blocker_depth;
+            blocker_count += 1.0;
+        }
+    }
+    if blocker_count == 0.0 {
+        return 0.0;
+    }
+    return blocker_depth_sum / blocker_count;
+}
+
+// Filters the shadow map over a disc of `filter_radius` around `light_local`.
+fn sample_shadow_map_pcss(
+    light_local: vec2<f32>,
+    depth: f32,
+    array_index: i32,
+    filter_radius: f32,
+    rotation: mat2x2<f32>,
+) -> f32 {
+    var sum = 0.0;
+    for (var i = 0u; i < 8u; i += 1u) {
+        let offset = soft_shadow_sample_offset(i, rotation) * filter_radius;
+        sum += sample_shadow_map_hardware(light_local + offset, depth, array_index);
+    }
+    return sum / 8.0;
+}
+
+fn sample_shadow_cubemap_hardware(light_local: vec3<f32>, depth: f32, light_id: u32) -> f32 {
+#ifdef NO_CUBE_ARRAY_TEXTURES_SUPPORT
+    return textureSampleCompare(
+        view_bindings::point_shadow_textures,
+        view_bindings::point_shadow_textures_sampler,
+        light_local,
+        depth,
+    );
+#else
+    return textureSampleCompareLevel(
+        view_bindings::point_shadow_textures,
+        view_bindings::point_shadow_textures_sampler,
+        light_local,
+        i32(light_id),
+        depth,
+    );
+#endif
+}
+
+// Reads the depth stored in the point light shadow cubemap, without comparison.
+fn sample_shadow_cubemap_depth(light_local: vec3<f32>, light_id: u32) -> f32 {
+#ifdef NO_CUBE_ARRAY_TEXTURES_SUPPORT
+    return textureSampleLevel(
+        view_bindings::point_shadow_textures,
+        view_bindings::point_shadow_textures_depth_sampler,
+        light_local,
+        0.0,
+    );
+#else
+    return textureSampleLevel(
+        view_bindings::point_shadow_textures,
+        view_bindings::point_shadow_textures_depth_sampler,
+        light_local,
+        i32(light_id),
+        0.0,
+    );
+#endif
+}
+
+// The offsets of the samples around `light_local` in the shadow cubemap are in the plane
+// perpendicular to it, in world units at the distance of `light_local` from the light.
+fn shadow_cubemap_tangent_frame(light_local: vec3<f32>) -> mat2x3<f32> {
+    let direction = normalize(light_local);
+    var up = vec3(0.0, 1.0, 0.0);
+    if abs(direction.y) > 0.99 {
+        up = vec3(1.0, 0.0, 0.0);
+    }
+    let tangent = normalize(cross(up, direction));
+    return mat2x3(tangent, cross(direction, tangent));
+}
+
+// The average depth of the occluders of `depth` within `search_radius` of `light_local` in the
+// shadow cubemap, or 0.0 if there are none.
+fn search_for_blockers_in_shadow_cubemap(
+    light_local: vec3<f32>,
+    depth: f32,
+    light_id: u32,
+    search_radius: f32,
+    rotation: mat2x2<f32>,
+) -> f32 {
+    let tangent_frame = shadow_cubemap_tangent_frame(light_local);
+    var blocker_depth_sum = 0.0;
+    var blocker_count = 0.0;
+    for (var i = 0u; i < 8u; i += 1u) {
+        let offset = tangent_frame * (soft_shadow_sample_offset(i, rotation) * search_radius);
+        let blocker_depth = sample_shadow_cubemap_depth(light_local + offset, light_id);
+        if blocker_depth > depth {
+            blocker_depth_sum += blocker_depth;
+            blocker_count += 1.0;
+        }
+    }
+    if blocker_count == 0.0 {
+        return 0.0;
+    }
+    return blocker_depth_sum / blocker_count;
+}
+
+// Filters the shadow cubemap over a disc of `filter_radius` around `light_local`.
+fn sample_shadow_cubemap_pcss(
+    light_local: vec3<f32>,
+    depth: f32,
+    light_id: u32,
+    filter_radius: f32,
+    rotation: mat2x2<f32>,
+) -> f32 {
+    let tangent_frame = shadow_cubemap_tangent_frame(light_local);
+    var sum = 0.0;
+    for (var i = 0u; i < 8u; i += 1u) {
+        let offset = tangent_frame * (soft_shadow_sample_offset(i, rotation) * filter_radius);
+        sum += sample_shadow_cubemap_hardware(light_local + offset, depth, light_id);
+    }
+    return sum / 8.0;
+}
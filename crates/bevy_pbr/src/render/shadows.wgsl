@@ -1,10 +1,16 @@
 #define_import_path bevy_pbr::shadows
 
 #import bevy_pbr::{
-    mesh_view_types::POINT_LIGHT_FLAGS_SPOT_LIGHT_Y_NEGATIVE,
+    mesh_view_types::{
+        POINT_LIGHT_FLAGS_SOFT_SHADOWS_ENABLED_BIT, POINT_LIGHT_FLAGS_SPOT_LIGHT_Y_NEGATIVE,
+    },
     mesh_view_bindings as view_bindings,
     utils::hsv2rgb,
-    shadow_sampling::sample_shadow_map
+    shadow_sampling::{
+        sample_shadow_map, sample_shadow_map_pcss, search_for_blockers_in_shadow_map,
+        sample_shadow_cubemap_hardware, sample_shadow_cubemap_pcss,
+        search_for_blockers_in_shadow_cubemap, soft_shadow_rotation,
+    },
 }
 
 const flip_z: vec3<f32> = vec3<f32>(1.0, 1.0, -1.0);
@@ -37,6 +43,10 @@ fn fetch_point_shadow(light_id: u32, frag_position: vec4<f32>, surface_normal: v
     let zw = -major_axis_magnitude * (*light).light_custom_data.xy + (*light).light_custom_data.zw;
     let depth = zw.x / zw.y;
 
+    if (((*light).flags & POINT_LIGHT_FLAGS_SOFT_SHADOWS_ENABLED_BIT) != 0u) {
+        return fetch_point_soft_shadow(light_id, frag_position, frag_ls * flip_z, depth, major_axis_magnitude);
+    }
+
     // Do the lookup, using HW PCF and comparison. Cubemaps assume a left-handed coordinate space,
     // so we have to flip the z-axis when sampling.
     // NOTE: Due to the non-uniform control flow above, we must use the Level variant of
@@ -44,11 +54,37 @@ fn fetch_point_shadow(light_id: u32, frag_position: vec4<f32>, surface_normal: v
     // a quad (2x2 fragments) being processed not being sampled, and this messing with
     // mip-mapping functionality. The shadow maps have no mipmaps so Level just samples
     // from LOD 0.
-#ifdef NO_CUBE_ARRAY_TEXTURES_SUPPORT
-    return textureSampleCompare(view_bindings::point_shadow_textures, view_bindings::point_shadow_textures_sampler, frag_ls * flip_z, depth);
-#else
-    return textureSampleCompareLevel(view_bindings::point_shadow_textures, view_bindings::point_shadow_textures_sampler, frag_ls * flip_z, i32(light_id), depth);
-#endif
+    return sample_shadow_cubemap_hardware(frag_ls * flip_z, depth, light_id);
+}
+
+// Inverts the projection of the depth of the point light shadow maps, to get the distance from the
+// light along the major axis.
+fn point_shadow_distance(light_custom_data: vec4<f32>, depth: f32) -> f32 {
+    return (light_custom_data.z - depth * light_custom_data.w) /
+        (light_custom_data.x - depth * light_custom_data.y);
+}
+
+// Percentage-closer soft shadows of a point light, whose radius is the size of the light.
+fn fetch_point_soft_shadow(
+    light_id: u32,
+    frag_position: vec4<f32>,
+    light_local: vec3<f32>,
+    depth: f32,
+    distance_to_light: f32,
+) -> f32 {
+    let light = &view_bindings::point_lights.data[light_id];
+    let light_size = (*light).position_radius.w;
+    let rotation = soft_shadow_rotation(frag_position);
+
+    // The occluders halfway to the light cast penumbras of the size of the light.
+    let blocker_depth = search_for_blockers_in_shadow_cubemap(light_local, depth, light_id, light_size, rotation);
+    if (blocker_depth == 0.0) {
+        return 1.0;
+    }
+
+    let blocker_distance = point_shadow_distance((*light).light_custom_data, blocker_depth);
+    let penumbra = light_size * (distance_to_light - blocker_distance) / max(blocker_distance, 1e-4);
+    return sample_shadow_cubemap_pcss(light_local, depth, light_id, min(penumbra, distance_to_light), rotation);
 }
 
 fn fetch_spot_shadow(light_id: u32, frag_position: vec4<f32>, surface_normal: vec3<f32>) -> f32 {
@@ -99,9 +135,28 @@ fn fetch_spot_shadow(light_id: u32, frag_position: vec4<f32>, surface_normal: ve
     // 0.1 must match POINT_LIGHT_NEAR_Z
     let depth = 0.1 / -projected_position.z;
 
+    let array_index = i32(light_id) + view_bindings::lights.spot_light_shadowmap_offset;
+
+    if (((*light).flags & POINT_LIGHT_FLAGS_SOFT_SHADOWS_ENABLED_BIT) != 0u) {
+        // Percentage-closer soft shadows, whose radius is the size of the light. The occluders
+        // halfway to the light cast penumbras of the size of the light.
+        let light_size = (*light).position_radius.w;
+        let rotation = soft_shadow_rotation(frag_position);
+        let uv_per_world = 0.5 * f_div_minus_z;
+        let blocker_depth = search_for_blockers_in_shadow_map(shadow_uv, depth, array_index, light_size * uv_per_world, rotation);
+        if (blocker_depth == 0.0) {
+            return 1.0;
+        }
+
+        // 0.1 must match POINT_LIGHT_NEAR_Z
+        let blocker_distance = 0.1 / blocker_depth;
+        let penumbra = light_size * (-projected_position.z - blocker_distance) / blocker_distance;
+        return sample_shadow_map_pcss(shadow_uv, depth, array_index, penumbra * uv_per_world, rotation);
+    }
+
      // Number determined by trial and error that gave nice results.
      let texel_size = 0.0134277345;
-    return sample_shadow_map(shadow_uv, depth, i32(light_id) + view_bindings::lights.spot_light_shadowmap_offset, texel_size);
+    return sample_shadow_map(shadow_uv, depth, array_index, texel_size);
 }
 
 fn get_cascade_index(light_id: u32, view_z: f32) -> u32 {
@@ -143,9 +198,45 @@ fn sample_directional_cascade(light_id: u32, cascade_index: u32, frag_position:
     let depth = offset_position_ndc.z;
 
     let array_index = i32((*light).depth_texture_base_index + cascade_index);
+    if ((*light).soft_shadow_size > 0.0) {
+        return sample_directional_cascade_soft(light_id, cascade_index, frag_position, light_local, depth, array_index);
+    }
     return sample_shadow_map(light_local, depth, array_index, (*cascade).texel_size);
 }
 
+// Percentage-closer soft shadows of a directional light, whose angular diameter is the size of
+// the light.
+fn sample_directional_cascade_soft(
+    light_id: u32,
+    cascade_index: u32,
+    frag_position: vec4<f32>,
+    light_local: vec2<f32>,
+    depth: f32,
+    array_index: i32,
+) -> f32 {
+    let light = &view_bindings::lights.directional_lights[light_id];
+    let cascade = &(*light).cascades[cascade_index];
+    let rotation = soft_shadow_rotation(frag_position);
+
+    // The orthographic projection maps the distances along the light linearly to depths.
+    let view_projection = (*cascade).view_projection;
+    let depth_per_world = length(vec3(view_projection[0].z, view_projection[1].z, view_projection[2].z));
+    let shadow_map_size = f32(textureDimensions(view_bindings::directional_shadow_textures).x);
+    let uv_per_world = 1.0 / ((*cascade).texel_size * shadow_map_size);
+    let penumbra_per_depth = tan(0.5 * (*light).soft_shadow_size) / depth_per_world * uv_per_world;
+
+    // The occluders up to the near plane of the cascade are searched for, within a limit that
+    // keeps the search local.
+    let search_radius = min((1.0 - depth) * penumbra_per_depth, 64.0 / shadow_map_size);
+    let blocker_depth = search_for_blockers_in_shadow_map(light_local, depth, array_index, search_radius, rotation);
+    if (blocker_depth == 0.0) {
+        return 1.0;
+    }
+
+    let penumbra = (blocker_depth - depth) * penumbra_per_depth;
+    return sample_shadow_map_pcss(light_local, depth, array_index, min(penumbra, search_radius), rotation);
+}
+
 fn fetch_directional_shadow(light_id: u32, frag_position: vec4<f32>, surface_normal: vec3<f32>, view_z: f32) -> f32 {
     let light = &view_bindings::lights.directional_lights[light_id];
     let cascade_index = get_cascade_index(light_id, view_z);
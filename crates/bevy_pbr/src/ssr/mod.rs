@@ -0,0 +1,429 @@
+use crate::{
+    environment_map::EnvironmentMapLight,
+    graph::NodePbr,
+    irradiance_volume::{IrradianceVolume, IRRADIANCE_VOLUMES_ARE_USABLE},
+    MeshPipeline, MeshPipelineKey, MeshViewBindGroup, RenderViewLightProbes, ViewFogUniformOffset,
+    ViewLightProbesUniformOffset, ViewLightsUniformOffset,
+};
+use bevy_app::{App, Plugin};
+use bevy_asset::{load_internal_asset, Handle};
+use bevy_core_pipeline::{
+    core_3d::graph::{Core3d, Node3d},
+    fullscreen_vertex_shader::fullscreen_shader_vertex_state,
+    oit::OrderIndependentTransparencySettings,
+    prepass::{DeferredPrepass, DepthPrepass, MotionVectorPrepass, NormalPrepass},
+};
+use bevy_ecs::{prelude::*, query::QueryItem};
+use bevy_reflect::{std_traits::ReflectDefault, Reflect};
+use bevy_render::{
+    camera::Camera,
+    extract_component::{
+        ComponentUniforms, DynamicUniformIndex, ExtractComponent, ExtractComponentPlugin,
+        UniformComponentPlugin,
+    },
+    render_graph::{NodeRunError, RenderGraphApp, RenderGraphContext, ViewNode, ViewNodeRunner},
+    render_resource::{
+        binding_types::{sampler, texture_2d, uniform_buffer},
+        *,
+    },
+    renderer::{RenderContext, RenderDevice},
+    texture::BevyDefault,
+    view::{ExtractedView, Msaa, ViewTarget, ViewUniformOffset},
+    Render, RenderApp, RenderSet,
+};
+use bevy_utils::tracing::warn;
+
+const SSR_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(10438925299917978850);
+
+/// Plugin for screen space reflections.
+///
+/// Screen space reflections aren't supported on WebGL 2, where the depth prepass can't be read:
+/// the surfaces only reflect their environment maps there.
+pub struct ScreenSpaceReflectionsPlugin;
+
+impl Plugin for ScreenSpaceReflectionsPlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(app, SSR_SHADER_HANDLE, "ssr.wgsl", Shader::from_wgsl);
+
+        app.register_type::<ScreenSpaceReflectionsSettings>()
+            .add_plugins((
+                ExtractComponentPlugin::<ScreenSpaceReflectionsSettings>::default(),
+                UniformComponentPlugin::<ScreenSpaceReflectionsUniform>::default(),
+            ));
+    }
+
+    fn finish(&self, app: &mut App) {
+        let Ok(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        if cfg!(all(
+            feature = "webgl",
+            target_arch = "wasm32",
+            not(feature = "webgpu")
+        )) {
+            warn!("ScreenSpaceReflectionsPlugin not loaded. WebGL 2 can't read the depth prepass.");
+            return;
+        }
+
+        render_app
+            .init_resource::<ScreenSpaceReflectionsPipeline>()
+            .init_resource::<SpecializedRenderPipelines<ScreenSpaceReflectionsPipeline>>()
+            .add_systems(Render, prepare_ssr_pipelines.in_set(RenderSet::Prepare))
+            .add_render_graph_node::<ViewNodeRunner<ScreenSpaceReflectionsNode>>(
+                Core3d,
+                NodePbr::ScreenSpaceReflections,
+            )
+            .add_render_graph_edges(
+                Core3d,
+                (
+                    // MAIN_OPAQUE_PASS -> SCREEN_SPACE_REFLECTIONS -> MAIN_TRANSMISSIVE_PASS
+                    Node3d::MainOpaquePass,
+                    NodePbr::ScreenSpaceReflections,
+                    Node3d::MainTransmissivePass,
+                ),
+            );
+    }
+}
+
+/// Bundle to apply screen space reflections.
+#[derive(Bundle, Default)]
+pub struct ScreenSpaceReflectionsBundle {
+    pub settings: ScreenSpaceReflectionsSettings,
+    pub depth_prepass: DepthPrepass,
+    pub deferred_prepass: DeferredPrepass,
+}
+
+/// Component to apply screen space reflections to a 3d camera.
+///
+/// Screen space reflections (SSR) reflect the objects visible on the screen on the smooth
+/// surfaces, by ray-marching the depth prepass along the reflected view rays. Where a ray hits an
+/// object, its color replaces the specular light of the environment map of the surface; where it
+/// misses or leaves the screen, the surface keeps reflecting its environment map.
+///
+/// SSR requires a [`DepthPrepass`] and a [`DeferredPrepass`], whose G-buffer gives the normals and
+/// the materials of the surfaces: only the opaque materials rendered with
+/// [`OpaqueRendererMethod::Deferred`](crate::OpaqueRendererMethod::Deferred) reflect the screen.
+/// Use [`ScreenSpaceReflectionsBundle`] to add them together.
+///
+/// SSR isn't supported on WebGL 2.
+#[derive(Component, Reflect, Clone, Copy, Debug, PartialEq)]
+#[reflect(Component, Default)]
+pub struct ScreenSpaceReflectionsSettings {
+    /// The perceptual roughness above which the surfaces don't reflect the screen.
+    ///
+    /// The reflections fade out as the roughness approaches this value, since rough surfaces
+    /// scatter their reflections in a way SSR doesn't approximate.
+    ///
+    /// The default value is 0.1.
+    pub perceptual_roughness_threshold: f32,
+    /// The thickness given to the objects on the screen, in meters.
+    ///
+    /// A ray which passes behind an object by less than this distance hits it. Thinner objects
+    /// let more rays pass behind them, thicker objects give more wrong hits.
+    ///
+    /// The default value is 0.25.
+    pub thickness: f32,
+    /// The number of steps taken along each ray to find where it hits the depth prepass.
+    ///
+    /// More steps miss fewer thin objects, but are more expensive.
+    ///
+    /// The default value is 16.
+    pub max_steps: u32,
+    /// The number of steps refining the position of a hit between the last two steps of the ray.
+    ///
+    /// The default value is 4.
+    pub bisection_steps: u32,
+}
+
+impl Default for ScreenSpaceReflectionsSettings {
+    fn default() -> Self {
+        Self {
+            perceptual_roughness_threshold: 0.1,
+            thickness: 0.25,
+            max_steps: 16,
+            bisection_steps: 4,
+        }
+    }
+}
+
+/// The uniform struct extracted from [`ScreenSpaceReflectionsSettings`] attached to a [`Camera`].
+#[doc(hidden)]
+#[derive(Component, ShaderType, Clone)]
+pub struct ScreenSpaceReflectionsUniform {
+    perceptual_roughness_threshold: f32,
+    thickness: f32,
+    max_steps: u32,
+    bisection_steps: u32,
+}
+
+impl ExtractComponent for ScreenSpaceReflectionsSettings {
+    type QueryData = &'static Self;
+    type QueryFilter = (With<Camera>, With<DepthPrepass>, With<DeferredPrepass>);
+    type Out = ScreenSpaceReflectionsUniform;
+
+    fn extract_component(settings: QueryItem<Self::QueryData>) -> Option<Self::Out> {
+        if settings.max_steps == 0 {
+            return None;
+        }
+        Some(ScreenSpaceReflectionsUniform {
+            perceptual_roughness_threshold: settings.perceptual_roughness_threshold,
+            thickness: settings.thickness,
+            max_steps: settings.max_steps,
+            bisection_steps: settings.bisection_steps,
+        })
+    }
+}
+
+#[derive(Default)]
+struct ScreenSpaceReflectionsNode;
+
+impl ViewNode for ScreenSpaceReflectionsNode {
+    type ViewQuery = (
+        &'static ViewTarget,
+        &'static ViewUniformOffset,
+        &'static ViewLightsUniformOffset,
+        &'static ViewFogUniformOffset,
+        &'static ViewLightProbesUniformOffset,
+        &'static MeshViewBindGroup,
+        &'static ScreenSpaceReflectionsPipelineId,
+        &'static DynamicUniformIndex<ScreenSpaceReflectionsUniform>,
+    );
+
+    fn run(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        (
+            view_target,
+            view_uniform_offset,
+            view_lights_offset,
+            view_fog_offset,
+            view_light_probes_offset,
+            mesh_view_bind_group,
+            pipeline_id,
+            ssr_uniform_index,
+        ): QueryItem<Self::ViewQuery>,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        let ssr_pipeline = world.resource::<ScreenSpaceReflectionsPipeline>();
+        let pipeline_cache = world.resource::<PipelineCache>();
+        let (Some(pipeline), Some(ssr_uniforms)) = (
+            pipeline_cache.get_render_pipeline(pipeline_id.0),
+            world
+                .resource::<ComponentUniforms<ScreenSpaceReflectionsUniform>>()
+                .binding(),
+        ) else {
+            return Ok(());
+        };
+
+        let post_process = view_target.post_process_write();
+        let bind_group = render_context.render_device().create_bind_group(
+            "ssr_bind_group",
+            &ssr_pipeline.bind_group_layout,
+            &BindGroupEntries::sequential((
+                post_process.source,
+                &ssr_pipeline.color_sampler,
+                ssr_uniforms,
+            )),
+        );
+
+        let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+            label: Some("ssr_pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: post_process.destination,
+                resolve_target: None,
+                ops: Operations::default(),
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        render_pass.set_render_pipeline(pipeline);
+        render_pass.set_bind_group(
+            0,
+            &mesh_view_bind_group.value,
+            &[
+                view_uniform_offset.offset,
+                view_lights_offset.offset,
+                view_fog_offset.offset,
+                **view_light_probes_offset,
+            ],
+        );
+        render_pass.set_bind_group(1, &bind_group, &[ssr_uniform_index.index()]);
+        render_pass.draw(0..3, 0..1);
+
+        Ok(())
+    }
+}
+
+#[derive(Resource)]
+struct ScreenSpaceReflectionsPipeline {
+    mesh_pipeline: MeshPipeline,
+    bind_group_layout: BindGroupLayout,
+    color_sampler: Sampler,
+}
+
+impl FromWorld for ScreenSpaceReflectionsPipeline {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+
+        let bind_group_layout = render_device.create_bind_group_layout(
+            "ssr_bind_group_layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::FRAGMENT,
+                (
+                    // View target (read)
+                    texture_2d(TextureSampleType::Float { filterable: true }),
+                    sampler(SamplerBindingType::Filtering),
+                    uniform_buffer::<ScreenSpaceReflectionsUniform>(true),
+                ),
+            ),
+        );
+
+        let color_sampler = render_device.create_sampler(&SamplerDescriptor {
+            label: Some("ssr_color_sampler"),
+            mag_filter: FilterMode::Linear,
+            min_filter: FilterMode::Linear,
+            ..SamplerDescriptor::default()
+        });
+
+        Self {
+            mesh_pipeline: world.resource::<MeshPipeline>().clone(),
+            bind_group_layout,
+            color_sampler,
+        }
+    }
+}
+
+impl SpecializedRenderPipeline for ScreenSpaceReflectionsPipeline {
+    type Key = MeshPipelineKey;
+
+    fn specialize(&self, key: Self::Key) -> RenderPipelineDescriptor {
+        let mut shader_defs = vec!["DEFERRED_PREPASS".into(), "DEPTH_PREPASS".into()];
+
+        if key.contains(MeshPipelineKey::NORMAL_PREPASS) {
+            shader_defs.push("NORMAL_PREPASS".into());
+        }
+
+        if key.contains(MeshPipelineKey::MOTION_VECTOR_PREPASS) {
+            shader_defs.push("MOTION_VECTOR_PREPASS".into());
+        }
+
+        if key.contains(MeshPipelineKey::ENVIRONMENT_MAP) {
+            shader_defs.push("ENVIRONMENT_MAP".into());
+        }
+
+        if key.contains(MeshPipelineKey::IRRADIANCE_VOLUME) {
+            shader_defs.push("IRRADIANCE_VOLUME".into());
+        }
+
+        if self.mesh_pipeline.binding_arrays_are_usable {
+            shader_defs.push("MULTIPLE_LIGHT_PROBES_IN_ARRAY".into());
+        }
+
+        if IRRADIANCE_VOLUMES_ARE_USABLE {
+            shader_defs.push("IRRADIANCE_VOLUMES_ARE_USABLE".into());
+        }
+
+        RenderPipelineDescriptor {
+            label: Some("ssr_pipeline".into()),
+            layout: vec![
+                self.mesh_pipeline.get_view_layout(key.into()).clone(),
+                self.bind_group_layout.clone(),
+            ],
+            vertex: fullscreen_shader_vertex_state(),
+            fragment: Some(FragmentState {
+                shader: SSR_SHADER_HANDLE,
+                shader_defs,
+                entry_point: "fragment".into(),
+                targets: vec![Some(ColorTargetState {
+                    format: if key.contains(MeshPipelineKey::HDR) {
+                        ViewTarget::TEXTURE_FORMAT_HDR
+                    } else {
+                        TextureFormat::bevy_default()
+                    },
+                    blend: None,
+                    write_mask: ColorWrites::ALL,
+                })],
+            }),
+            primitive: PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: MultisampleState::default(),
+            push_constant_ranges: vec![],
+        }
+    }
+}
+
+#[derive(Component)]
+struct ScreenSpaceReflectionsPipelineId(CachedRenderPipelineId);
+
+#[allow(clippy::type_complexity)]
+fn prepare_ssr_pipelines(
+    mut commands: Commands,
+    pipeline_cache: Res<PipelineCache>,
+    mut pipelines: ResMut<SpecializedRenderPipelines<ScreenSpaceReflectionsPipeline>>,
+    ssr_pipeline: Res<ScreenSpaceReflectionsPipeline>,
+    msaa: Res<Msaa>,
+    views: Query<
+        (
+            Entity,
+            &ExtractedView,
+            Has<NormalPrepass>,
+            Has<MotionVectorPrepass>,
+            Has<RenderViewLightProbes<EnvironmentMapLight>>,
+            Has<RenderViewLightProbes<IrradianceVolume>>,
+            Has<OrderIndependentTransparencySettings>,
+        ),
+        (
+            With<ScreenSpaceReflectionsUniform>,
+            With<DepthPrepass>,
+            With<DeferredPrepass>,
+        ),
+    >,
+) {
+    // The deferred renderer doesn't support MSAA.
+    if msaa.samples() > 1 {
+        return;
+    }
+
+    for (
+        entity,
+        view,
+        normal_prepass,
+        motion_vector_prepass,
+        has_environment_maps,
+        has_irradiance_volumes,
+        oit,
+    ) in &views
+    {
+        let mut view_key = MeshPipelineKey::from_hdr(view.hdr)
+            | MeshPipelineKey::DEPTH_PREPASS
+            | MeshPipelineKey::DEFERRED_PREPASS;
+
+        if normal_prepass {
+            view_key |= MeshPipelineKey::NORMAL_PREPASS;
+        }
+
+        if motion_vector_prepass {
+            view_key |= MeshPipelineKey::MOTION_VECTOR_PREPASS;
+        }
+
+        if has_environment_maps {
+            view_key |= MeshPipelineKey::ENVIRONMENT_MAP;
+        }
+
+        if has_irradiance_volumes {
+            view_key |= MeshPipelineKey::IRRADIANCE_VOLUME;
+        }
+
+        if oit {
+            view_key |= MeshPipelineKey::OIT_ENABLED;
+        }
+
+        let pipeline_id = pipelines.specialize(&pipeline_cache, &ssr_pipeline, view_key);
+        commands
+            .entity(entity)
+            .insert(ScreenSpaceReflectionsPipelineId(pipeline_id));
+    }
+}
@@ -0,0 +1,180 @@
+// Screen space reflections.
+//
+// The reflected ray of each pixel of the deferred G-buffer is marched through the depth prepass
+// in screen space. Where it hits the scene, the reflected color replaces the specular light of the
+// environment map that the deferred lighting pass already added.
+
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+#import bevy_pbr::{
+    environment_map::{compute_radiances, single_scattering_specular},
+    lighting::{F_AB, perceptualRoughnessToRoughness},
+    mesh_view_bindings::{deferred_prepass_texture, depth_prepass_texture, view},
+    pbr_deferred_functions::pbr_input_from_deferred_gbuffer,
+    pbr_types::STANDARD_MATERIAL_FLAGS_UNLIT_BIT,
+    view_transformations::{depth_ndc_to_view_z, direction_world_to_view, ndc_to_uv, position_world_to_view},
+}
+
+struct ScreenSpaceReflectionsSettings {
+    perceptual_roughness_threshold: f32,
+    thickness: f32,
+    max_steps: u32,
+    bisection_steps: u32,
+}
+
+@group(1) @binding(0) var color_texture: texture_2d<f32>;
+@group(1) @binding(1) var color_sampler: sampler;
+@group(1) @binding(2) var<uniform> ssr_settings: ScreenSpaceReflectionsSettings;
+
+// The length of the reflected rays in view space, in meters, before they are clipped to the
+// screen.
+const MAX_RAY_LENGTH: f32 = 1000.0;
+
+// Jitters the first step of neighboring pixels, trading banding for noise.
+// https://www.iryoku.com/next-generation-post-processing-in-call-of-duty-advanced-warfare
+fn interleaved_gradient_noise(coords: vec2<f32>) -> f32 {
+    return fract(52.9829189 * fract(dot(coords, vec2(0.06711056, 0.00583715))));
+}
+
+// How far behind the depth prepass the point `ndc` of the ray is, in meters. Negative values are
+// in front of the scene.
+fn depth_behind_scene(ndc: vec3<f32>) -> f32 {
+    let frag_coord = ndc_to_uv(ndc.xy) * view.viewport.zw + view.viewport.xy;
+    let size = vec2<i32>(textureDimensions(depth_prepass_texture));
+    let texel = clamp(vec2<i32>(frag_coord), vec2(0), size - 1);
+    let scene_depth = textureLoad(depth_prepass_texture, texel, 0);
+    return depth_ndc_to_view_z(scene_depth) - depth_ndc_to_view_z(ndc.z);
+}
+
+// The distance along the ray from `start` by `delta` to the boundaries `min_value` and
+// `max_value`, as a fraction of `delta`.
+fn clip_fraction(start: f32, delta: f32, min_value: f32, max_value: f32) -> f32 {
+    if delta > 0.0 {
+        return (max_value - start) / delta;
+    }
+    if delta < 0.0 {
+        return (min_value - start) / delta;
+    }
+    return 1.0;
+}
+
+// Marches the ray from the view space `origin` along the `direction`.
+//
+// Returns the viewport uv of the hit in `xy`, and its confidence in `z`, which is zero when the
+// ray misses the scene.
+fn raymarch(origin: vec3<f32>, direction: vec3<f32>, jitter: f32) -> vec3<f32> {
+    // The end of the ray stays in front of the camera, where it can be projected.
+    var ray_length = MAX_RAY_LENGTH;
+    if direction.z > 0.0 {
+        ray_length = min(ray_length, (-0.001 - origin.z) / direction.z);
+    }
+    if ray_length <= 0.0 {
+        return vec3(0.0);
+    }
+
+    let start_clip = view.projection * vec4(origin, 1.0);
+    let end_clip = view.projection * vec4(origin + direction * ray_length, 1.0);
+    let start = start_clip.xyz / start_clip.w;
+    let delta = end_clip.xyz / end_clip.w - start;
+
+    // Clip the ray to the screen.
+    var max_s = 1.0;
+    max_s = min(max_s, clip_fraction(start.x, delta.x, -1.0, 1.0));
+    max_s = min(max_s, clip_fraction(start.y, delta.y, -1.0, 1.0));
+    max_s = min(max_s, clip_fraction(start.z, delta.z, 0.0, 1.0));
+
+    // The march starts a pixel away from the surface, so that it doesn't hit itself.
+    let delta_pixels = length(delta.xy * 0.5 * view.viewport.zw);
+    let min_s = 1.0 / delta_pixels;
+    if min_s >= max_s {
+        return vec3(0.0);
+    }
+
+    var front_s = min_s;
+    var hit_s = -1.0;
+    for (var i = 0u; i < ssr_settings.max_steps; i += 1u) {
+        let s = mix(min_s, max_s, (f32(i) + jitter) / f32(ssr_settings.max_steps));
+        let depth_behind = depth_behind_scene(start + delta * s);
+        if depth_behind > 0.0 && depth_behind < ssr_settings.thickness {
+            hit_s = s;
+            break;
+        }
+        // The ray keeps going when it passes behind an object thicker than `thickness`.
+        if depth_behind <= 0.0 {
+            front_s = s;
+        }
+    }
+    if hit_s < 0.0 {
+        return vec3(0.0);
+    }
+
+    // Refine the hit between the last step in front of the scene and the step behind it.
+    for (var i = 0u; i < ssr_settings.bisection_steps; i += 1u) {
+        let s = 0.5 * (front_s + hit_s);
+        if depth_behind_scene(start + delta * s) > 0.0 {
+            hit_s = s;
+        } else {
+            front_s = s;
+        }
+    }
+
+    let uv = ndc_to_uv((start + delta * hit_s).xy);
+    // The reflections fade out at the edges of the screen, instead of being cut abruptly.
+    let edge_distance = min(uv, 1.0 - uv);
+    let confidence = saturate(min(edge_distance.x, edge_distance.y) * 10.0);
+    return vec3(uv, confidence);
+}
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let frag_coord = in.position;
+    let color = textureLoad(color_texture, vec2<i32>(frag_coord.xy), 0);
+
+    // Only the opaque deferred materials are in the G-buffer.
+    let gbuffer = textureLoad(deferred_prepass_texture, vec2<i32>(frag_coord.xy), 0);
+    if all(gbuffer == vec4(0u)) {
+        return color;
+    }
+    let depth = textureLoad(depth_prepass_texture, vec2<i32>(frag_coord.xy), 0);
+    let pbr_input = pbr_input_from_deferred_gbuffer(vec4(frag_coord.xy, depth, 1.0), gbuffer);
+
+    let perceptual_roughness = pbr_input.material.perceptual_roughness;
+    let roughness_threshold = ssr_settings.perceptual_roughness_threshold;
+    if (pbr_input.material.flags & STANDARD_MATERIAL_FLAGS_UNLIT_BIT) != 0u ||
+            perceptual_roughness >= roughness_threshold {
+        return color;
+    }
+
+    let N = pbr_input.N;
+    let V = pbr_input.V;
+    let R = reflect(-V, N);
+    let hit = raymarch(
+        position_world_to_view(pbr_input.world_position.xyz),
+        direction_world_to_view(R),
+        interleaved_gradient_noise(frag_coord.xy)
+    );
+    // The reflections fade out as the surface gets rough, where they would show more noise.
+    let weight = hit.z * (1.0 - smoothstep(0.5 * roughness_threshold, roughness_threshold, perceptual_roughness));
+    if weight <= 0.0 {
+        return color;
+    }
+
+    let texture_uv = (hit.xy * view.viewport.zw + view.viewport.xy) / vec2<f32>(textureDimensions(color_texture));
+    let reflected_color = textureSampleLevel(color_texture, color_sampler, texture_uv, 0.0).rgb;
+
+    // The environment map light that the reflection replaces, as computed by the lighting pass.
+    var environment_radiance = vec3(0.0);
+#ifdef ENVIRONMENT_MAP
+    environment_radiance = view.exposure *
+        compute_radiances(perceptual_roughness, N, R, pbr_input.world_position.xyz, true).radiance;
+#endif
+
+    let NdotV = max(dot(N, V), 0.0001);
+    let roughness = perceptualRoughnessToRoughness(perceptual_roughness);
+    let metallic = pbr_input.material.metallic;
+    let reflectance = pbr_input.material.reflectance;
+    let F0 = 0.16 * reflectance * reflectance * (1.0 - metallic) +
+        pbr_input.material.base_color.rgb * metallic;
+    let specular = single_scattering_specular(roughness, NdotV, F_AB(perceptual_roughness, NdotV), F0);
+
+    return vec4(color.rgb + specular * (reflected_color - environment_radiance) * weight, color.a);
+}
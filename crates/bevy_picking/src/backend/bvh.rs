@@ -0,0 +1,415 @@
+//! Bounding volume hierarchies over the triangles of a [`Mesh`], and over the bounds of the
+//! entities, to cast rays against them without testing all of them.
+
+use bevy_ecs::entity::Entity;
+use bevy_math::Vec3;
+use bevy_render::{
+    mesh::{Mesh, VertexAttributeValues},
+    render_resource::PrimitiveTopology,
+};
+
+/// The maximum number of triangles in a leaf of a [`MeshBvh`].
+const LEAF_TRIANGLES: usize = 4;
+
+/// The maximum number of entities in a leaf of an [`EntityBvh`].
+const LEAF_ENTITIES: usize = 2;
+
+/// A primitive sorted in a hierarchy.
+trait Bounded {
+    /// The minimum and maximum corners of the bounds of the primitive.
+    fn bounds(&self) -> (Vec3, Vec3);
+
+    fn centroid(&self) -> Vec3;
+}
+
+/// A triangle of a mesh, with the indices of its vertices.
+#[derive(Debug, Clone, Copy)]
+struct Triangle {
+    positions: [Vec3; 3],
+    indices: [usize; 3],
+}
+
+impl Bounded for Triangle {
+    fn bounds(&self) -> (Vec3, Vec3) {
+        let [a, b, c] = self.positions;
+        (a.min(b).min(c), a.max(b).max(c))
+    }
+
+    fn centroid(&self) -> Vec3 {
+        (self.positions[0] + self.positions[1] + self.positions[2]) / 3.0
+    }
+}
+
+/// An entity with its bounds in world space.
+#[derive(Debug, Clone, Copy)]
+struct BoundedEntity {
+    entity: Entity,
+    min: Vec3,
+    max: Vec3,
+}
+
+impl Bounded for BoundedEntity {
+    fn bounds(&self) -> (Vec3, Vec3) {
+        (self.min, self.max)
+    }
+
+    fn centroid(&self) -> Vec3 {
+        (self.min + self.max) / 2.0
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum BvhContent {
+    /// The primitives of a leaf, as a range of the primitives of the hierarchy.
+    Leaf { start: usize, end: usize },
+    /// The indices of the children of a branch in the nodes of the hierarchy.
+    Branch { left: usize, right: usize },
+}
+
+/// A node of a hierarchy, with the bounds of all the primitives under it.
+#[derive(Debug, Clone, Copy)]
+struct BvhNode {
+    min: Vec3,
+    max: Vec3,
+    content: BvhContent,
+}
+
+/// The intersection of a ray with a triangle of a [`MeshBvh`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct TriangleHit {
+    /// The distance along the ray, in multiples of the length of its direction.
+    pub distance: f32,
+    /// The indices of the vertices of the triangle.
+    pub indices: [usize; 3],
+    /// The weights of the vertices of the triangle at the intersection.
+    pub barycentric: Vec3,
+    /// The normal of the triangle, following its winding.
+    pub normal: Vec3,
+}
+
+/// A bounding volume hierarchy over the triangles of a [`Mesh`], in the space of the mesh.
+#[derive(Debug)]
+pub(crate) struct MeshBvh {
+    triangles: Vec<Triangle>,
+    nodes: Vec<BvhNode>,
+}
+
+impl MeshBvh {
+    /// Builds the hierarchy of the triangles of the `mesh`.
+    ///
+    /// Returns `None` if the mesh has no triangles, or no positions stored as [`Vec3`]s.
+    pub fn new(mesh: &Mesh) -> Option<Self> {
+        let positions = mesh.attribute(Mesh::ATTRIBUTE_POSITION)?.as_float3()?;
+        let indices: Vec<usize> = match mesh.indices() {
+            Some(indices) => indices.iter().collect(),
+            None => (0..positions.len()).collect(),
+        };
+        let triangle = |indices: [usize; 3]| {
+            let positions = [
+                Vec3::from(*positions.get(indices[0])?),
+                Vec3::from(*positions.get(indices[1])?),
+                Vec3::from(*positions.get(indices[2])?),
+            ];
+            Some(Triangle { positions, indices })
+        };
+        let mut triangles: Vec<Triangle> = match mesh.primitive_topology() {
+            PrimitiveTopology::TriangleList => indices
+                .chunks_exact(3)
+                .filter_map(|indices| triangle([indices[0], indices[1], indices[2]]))
+                .collect(),
+            // Every other triangle of a strip has its winding reversed.
+            PrimitiveTopology::TriangleStrip => indices
+                .windows(3)
+                .enumerate()
+                .filter_map(|(i, indices)| match i % 2 {
+                    0 => triangle([indices[0], indices[1], indices[2]]),
+                    _ => triangle([indices[1], indices[0], indices[2]]),
+                })
+                .collect(),
+            _ => return None,
+        };
+        if triangles.is_empty() {
+            return None;
+        }
+
+        let mut nodes = Vec::with_capacity(2 * triangles.len() / LEAF_TRIANGLES + 1);
+        build_node(&mut nodes, &mut triangles, 0, LEAF_TRIANGLES);
+        Some(Self { triangles, nodes })
+    }
+
+    /// Finds the closest triangle hit by the ray from the `origin` along the `direction`, in the
+    /// space of the mesh.
+    ///
+    /// Both sides of the triangles are hit.
+    pub fn cast_ray(&self, origin: Vec3, direction: Vec3) -> Option<TriangleHit> {
+        let inverse_direction = direction.recip();
+        let mut closest: Option<TriangleHit> = None;
+        // The root is the last node, added after all the others.
+        let mut stack = vec![self.nodes.len() - 1];
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            let mut max_distance = closest.map_or(f32::INFINITY, |hit| hit.distance);
+            if !ray_hits_bounds(origin, inverse_direction, node.min, node.max, max_distance) {
+                continue;
+            }
+            match node.content {
+                BvhContent::Leaf { start, end } => {
+                    for triangle in &self.triangles[start..end] {
+                        if let Some(hit) = ray_triangle_intersection(origin, direction, triangle) {
+                            if hit.distance < max_distance {
+                                max_distance = hit.distance;
+                                closest = Some(hit);
+                            }
+                        }
+                    }
+                }
+                BvhContent::Branch { left, right } => {
+                    stack.push(right);
+                    stack.push(left);
+                }
+            }
+        }
+        closest
+    }
+}
+
+/// Adds the node of the `primitives` starting at the `offset` of the primitives of the hierarchy
+/// to the `nodes`, after its children, and returns its index.
+fn build_node<T: Bounded>(
+    nodes: &mut Vec<BvhNode>,
+    primitives: &mut [T],
+    offset: usize,
+    leaf_size: usize,
+) -> usize {
+    let (min, max) = primitives.iter().map(Bounded::bounds).fold(
+        (Vec3::INFINITY, Vec3::NEG_INFINITY),
+        |(min, max), (primitive_min, primitive_max)| {
+            (min.min(primitive_min), max.max(primitive_max))
+        },
+    );
+
+    let content = if primitives.len() <= leaf_size {
+        BvhContent::Leaf {
+            start: offset,
+            end: offset + primitives.len(),
+        }
+    } else {
+        // Splits the primitives in two halves along the longest axis of their bounds.
+        let extents = max - min;
+        let axis = if extents.x >= extents.y && extents.x >= extents.z {
+            0
+        } else if extents.y >= extents.z {
+            1
+        } else {
+            2
+        };
+        let middle = primitives.len() / 2;
+        primitives.select_nth_unstable_by(middle, |a, b| {
+            a.centroid()[axis].total_cmp(&b.centroid()[axis])
+        });
+        let (left_primitives, right_primitives) = primitives.split_at_mut(middle);
+        let left = build_node(nodes, left_primitives, offset, leaf_size);
+        let right = build_node(nodes, right_primitives, offset + middle, leaf_size);
+        BvhContent::Branch { left, right }
+    };
+
+    nodes.push(BvhNode { min, max, content });
+    nodes.len() - 1
+}
+
+/// A bounding volume hierarchy over the bounds of entities in world space.
+#[derive(Debug, Default)]
+pub(crate) struct EntityBvh {
+    entities: Vec<BoundedEntity>,
+    nodes: Vec<BvhNode>,
+}
+
+impl EntityBvh {
+    /// Builds the hierarchy of the `entities`, with the minimum and maximum corners of their
+    /// bounds.
+    pub fn new(entities: impl IntoIterator<Item = (Entity, Vec3, Vec3)>) -> Self {
+        let mut entities: Vec<_> = entities
+            .into_iter()
+            .map(|(entity, min, max)| BoundedEntity { entity, min, max })
+            .collect();
+        let mut nodes = Vec::with_capacity(2 * entities.len() / LEAF_ENTITIES + 1);
+        if !entities.is_empty() {
+            build_node(&mut nodes, &mut entities, 0, LEAF_ENTITIES);
+        }
+        Self { entities, nodes }
+    }
+
+    /// Calls `f` with the entities whose bounds are hit by the ray from the `origin` along the
+    /// `direction`, in world space.
+    pub fn cast_ray(&self, origin: Vec3, direction: Vec3, mut f: impl FnMut(Entity)) {
+        let Some(root) = self.nodes.len().checked_sub(1) else {
+            return;
+        };
+        let inverse_direction = direction.recip();
+        let mut stack = vec![root];
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            if !ray_hits_bounds(origin, inverse_direction, node.min, node.max, f32::INFINITY) {
+                continue;
+            }
+            match node.content {
+                BvhContent::Leaf { start, end } => {
+                    for entity in &self.entities[start..end] {
+                        if ray_hits_bounds(
+                            origin,
+                            inverse_direction,
+                            entity.min,
+                            entity.max,
+                            f32::INFINITY,
+                        ) {
+                            f(entity.entity);
+                        }
+                    }
+                }
+                BvhContent::Branch { left, right } => {
+                    stack.push(right);
+                    stack.push(left);
+                }
+            }
+        }
+    }
+}
+
+pub(crate) fn ray_hits_bounds(
+    origin: Vec3,
+    inverse_direction: Vec3,
+    min: Vec3,
+    max: Vec3,
+    max_distance: f32,
+) -> bool {
+    let t1 = (min - origin) * inverse_direction;
+    let t2 = (max - origin) * inverse_direction;
+    let near = t1.min(t2).max_element().max(0.0);
+    let far = t1.max(t2).min_element().min(max_distance);
+    near <= far
+}
+
+/// The Möller–Trumbore intersection of a ray with a triangle.
+fn ray_triangle_intersection(
+    origin: Vec3,
+    direction: Vec3,
+    triangle: &Triangle,
+) -> Option<TriangleHit> {
+    let [v0, v1, v2] = triangle.positions;
+    let edge1 = v1 - v0;
+    let edge2 = v2 - v0;
+    let p = direction.cross(edge2);
+    let determinant = edge1.dot(p);
+    if determinant.abs() < f32::EPSILON {
+        // The ray is parallel to the triangle.
+        return None;
+    }
+    let inverse_determinant = determinant.recip();
+    let s = origin - v0;
+    let u = s.dot(p) * inverse_determinant;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+    let q = s.cross(edge1);
+    let v = direction.dot(q) * inverse_determinant;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+    let distance = edge2.dot(q) * inverse_determinant;
+    if distance < 0.0 {
+        return None;
+    }
+    Some(TriangleHit {
+        distance,
+        indices: triangle.indices,
+        barycentric: Vec3::new(1.0 - u - v, u, v),
+        normal: edge1.cross(edge2).normalize_or_zero(),
+    })
+}
+
+/// Interpolates the vertex `attribute` of a mesh at a [`TriangleHit`].
+pub(crate) fn interpolate<const N: usize>(
+    attribute: &[[f32; N]],
+    hit: &TriangleHit,
+) -> Option<[f32; N]> {
+    let mut value = [0.0; N];
+    for (vertex, weight) in hit.indices.iter().zip(hit.barycentric.to_array()) {
+        let vertex_value = attribute.get(*vertex)?;
+        for (value, vertex_value) in value.iter_mut().zip(vertex_value) {
+            *value += vertex_value * weight;
+        }
+    }
+    Some(value)
+}
+
+/// The UVs of a mesh, if they are stored as pairs of floats.
+pub(crate) fn float2_attribute(values: &VertexAttributeValues) -> Option<&[[f32; 2]]> {
+    match values {
+        VertexAttributeValues::Float32x2(values) => Some(values),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_math::primitives::Cuboid;
+    use bevy_render::render_asset::RenderAssetUsages;
+
+    #[test]
+    fn rays_hit_the_closest_triangle() {
+        let mesh = Mesh::from(Cuboid::new(2.0, 2.0, 2.0));
+        let bvh = MeshBvh::new(&mesh).unwrap();
+        assert!(bvh.nodes.len() > 1);
+
+        let hit = bvh
+            .cast_ray(Vec3::new(0.5, 0.25, 5.0), Vec3::new(0.0, 0.0, -2.0))
+            .unwrap();
+        // The front face is 4 units away, which is 2 lengths of the direction.
+        assert!((hit.distance - 2.0).abs() < 1e-5);
+        assert!((hit.normal - Vec3::Z).length() < 1e-5);
+
+        let uvs = float2_attribute(mesh.attribute(Mesh::ATTRIBUTE_UV_0).unwrap()).unwrap();
+        let uv = interpolate(uvs, &hit).unwrap();
+        assert!((uv[0] - 0.75).abs() < 1e-5 && (uv[1] - 0.625).abs() < 1e-5);
+
+        assert!(bvh
+            .cast_ray(Vec3::new(1.5, 0.0, 5.0), Vec3::NEG_Z)
+            .is_none());
+        assert!(bvh.cast_ray(Vec3::new(0.0, 0.0, 5.0), Vec3::Z).is_none());
+
+        let empty = Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::default(),
+        );
+        assert!(MeshBvh::new(&empty).is_none());
+    }
+
+    #[test]
+    fn rays_find_the_entities_whose_bounds_they_hit() {
+        let entities: Vec<_> = (0..10)
+            .map(|i| {
+                let center = Vec3::new(i as f32 * 3.0, 0.0, 0.0);
+                (Entity::from_raw(i), center - Vec3::ONE, center + Vec3::ONE)
+            })
+            .collect();
+        let bvh = EntityBvh::new(entities);
+        assert!(bvh.nodes.len() > 1);
+
+        let hits = |origin, direction| {
+            let mut hits = Vec::new();
+            bvh.cast_ray(origin, direction, |entity| hits.push(entity.index()));
+            hits.sort();
+            hits
+        };
+        assert_eq!(hits(Vec3::new(9.5, 0.0, 5.0), Vec3::NEG_Z), vec![3]);
+        assert_eq!(
+            hits(Vec3::new(-5.0, 0.5, 0.0), Vec3::X),
+            Vec::from_iter(0..10)
+        );
+        assert_eq!(hits(Vec3::new(-5.0, 0.5, 0.0), Vec3::NEG_X), vec![]);
+        assert_eq!(hits(Vec3::new(1.5, 0.0, 5.0), Vec3::NEG_Z), vec![]);
+
+        EntityBvh::new([]).cast_ray(Vec3::ZERO, Vec3::X, |_| panic!());
+    }
+}
@@ -0,0 +1,358 @@
+//! The backend picking the entities with a [`Handle<Mesh>`], by casting the rays of the pointers
+//! against the triangles of their meshes.
+//!
+//! The bounds of the entities are sorted in a bounding volume hierarchy, rebuilt when they move,
+//! so that a ray is only tested against the meshes whose bounds it hits. The triangles of each
+//! mesh are sorted in another hierarchy the first time a ray reaches the bounds of an entity using
+//! it, and the hierarchy is kept until the mesh is modified or removed. The meshes are hit in their
+//! bind pose: their skinning and morph targets are ignored.
+
+use bevy_app::{App, Plugin, PreUpdate};
+use bevy_asset::{AssetEvent, AssetId, Assets, Handle};
+use bevy_ecs::{prelude::*, system::SystemParam};
+use bevy_input::pointer::Pointers;
+use bevy_math::{Ray3d, Vec2, Vec3, Vec3A};
+use bevy_render::{
+    camera::Camera,
+    mesh::Mesh,
+    primitives::Aabb,
+    view::{RenderLayers, ViewVisibility},
+};
+use bevy_transform::components::GlobalTransform;
+use bevy_utils::HashMap;
+use bevy_window::PrimaryWindow;
+
+use super::{
+    bvh::{float2_attribute, interpolate, ray_hits_bounds, EntityBvh, MeshBvh},
+    pointer_viewport_position, HitData, PointerHits,
+};
+use crate::PickSet;
+
+/// Adds the mesh backend.
+pub struct MeshBackendPlugin;
+
+impl Plugin for MeshBackendPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MeshBvhCache>()
+            .init_resource::<MeshBoundsBvh>()
+            .add_systems(
+                PreUpdate,
+                (invalidate_mesh_bvhs, update_mesh_bounds_bvh, mesh_picking)
+                    .chain()
+                    .in_set(PickSet::Backend),
+            );
+    }
+}
+
+/// The bounding volume hierarchies of the meshes hit by the rays of [`MeshRayCast`].
+#[derive(Resource, Default)]
+pub struct MeshBvhCache {
+    /// The hierarchies of the meshes, or `None` for the meshes without triangles.
+    bvhs: HashMap<AssetId<Mesh>, Option<MeshBvh>>,
+}
+
+/// The bounding volume hierarchy of the world space bounds of the entities with a
+/// [`Handle<Mesh>`], used by [`MeshRayCast`] to skip the entities a ray doesn't reach.
+///
+/// It's rebuilt in [`PickSet::Backend`] when an entity moves, so the rays cast after the
+/// [`GlobalTransform`]s are propagated in `PostUpdate` hit the entities where they were before
+/// moving.
+#[derive(Resource, Default)]
+pub struct MeshBoundsBvh {
+    bvh: EntityBvh,
+    /// The entities without an [`Aabb`], which are tested against every ray.
+    unbounded: Vec<Entity>,
+    /// The number of entities in the hierarchy, to rebuild it when one is removed.
+    len: usize,
+}
+
+/// Where a ray hit a mesh, returned by [`MeshRayCast::cast_ray`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RayMeshHit {
+    /// The distance along the ray, in world units.
+    pub distance: f32,
+    /// The position of the hit in world space.
+    pub position: Vec3,
+    /// The normal of the mesh at the hit in world space, facing the origin of the ray.
+    ///
+    /// It's interpolated from the normals of the vertices of the triangle if the mesh has
+    /// [`Mesh::ATTRIBUTE_NORMAL`], and is the normal of the triangle otherwise.
+    pub normal: Vec3,
+    /// The texture coordinates of the hit, if the mesh has [`Mesh::ATTRIBUTE_UV_0`].
+    pub uv: Option<Vec2>,
+}
+
+/// Casts rays against the visible entities with a [`Handle<Mesh>`].
+///
+/// The meshes need to be kept in the main world by their
+/// [`RenderAssetUsages`](bevy_render::render_asset::RenderAssetUsages) to be hit.
+///
+/// ```
+/// # use bevy_ecs::prelude::*;
+/// # use bevy_math::{Ray3d, Vec3};
+/// # use bevy_picking::backend::mesh::MeshRayCast;
+/// fn shoot(mut ray_cast: MeshRayCast) {
+///     let ray = Ray3d::new(Vec3::ZERO, Vec3::NEG_Z);
+///     if let Some((entity, hit)) = ray_cast.cast_ray(ray, |_| true).first() {
+///         println!("{entity:?} was hit at {}", hit.position);
+///     }
+/// }
+/// # bevy_ecs::system::assert_is_system(shoot);
+/// ```
+#[derive(SystemParam)]
+pub struct MeshRayCast<'w, 's> {
+    meshes: Res<'w, Assets<Mesh>>,
+    cache: ResMut<'w, MeshBvhCache>,
+    bounds: Res<'w, MeshBoundsBvh>,
+    entities: Query<
+        'w,
+        's,
+        (
+            Entity,
+            &'static Handle<Mesh>,
+            &'static GlobalTransform,
+            Option<&'static Aabb>,
+            &'static ViewVisibility,
+        ),
+    >,
+}
+
+impl<'w, 's> MeshRayCast<'w, 's> {
+    /// Casts the `ray` against the meshes of the visible entities for which `filter` returns
+    /// `true`, and returns the entities it hits, from the closest to the farthest.
+    pub fn cast_ray(
+        &mut self,
+        ray: Ray3d,
+        mut filter: impl FnMut(Entity) -> bool,
+    ) -> Vec<(Entity, RayMeshHit)> {
+        let mut candidates = self.bounds.unbounded.clone();
+        self.bounds
+            .bvh
+            .cast_ray(ray.origin, *ray.direction, |entity| candidates.push(entity));
+
+        let mut hits = Vec::new();
+        for candidate in candidates {
+            let Ok((entity, handle, transform, aabb, visibility)) = self.entities.get(candidate)
+            else {
+                continue;
+            };
+            if !visibility.get() || !filter(entity) {
+                continue;
+            }
+
+            // The ray is cast in the space of the mesh, keeping the distances of the world.
+            let world_to_mesh = transform.affine().inverse();
+            let origin = world_to_mesh.transform_point3(ray.origin);
+            let direction = world_to_mesh.transform_vector3(*ray.direction);
+            if let Some(aabb) = aabb {
+                let min = Vec3::from(aabb.center - aabb.half_extents);
+                let max = Vec3::from(aabb.center + aabb.half_extents);
+                if !ray_hits_bounds(origin, direction.recip(), min, max, f32::INFINITY) {
+                    continue;
+                }
+            }
+
+            let Some(mesh) = self.meshes.get(handle) else {
+                continue;
+            };
+            let Some(bvh) = self
+                .cache
+                .bvhs
+                .entry(handle.id())
+                .or_insert_with(|| MeshBvh::new(mesh))
+            else {
+                continue;
+            };
+            let Some(hit) = bvh.cast_ray(origin, direction) else {
+                continue;
+            };
+
+            let normal = mesh
+                .attribute(Mesh::ATTRIBUTE_NORMAL)
+                .and_then(|normals| normals.as_float3())
+                .and_then(|normals| interpolate(normals, &hit))
+                .map_or(hit.normal, Vec3::from);
+            let mut normal = (world_to_mesh.matrix3.transpose() * Vec3A::from(normal))
+                .normalize_or_zero()
+                .into();
+            if Vec3::dot(normal, *ray.direction) > 0.0 {
+                normal = -normal;
+            }
+            let uv = mesh
+                .attribute(Mesh::ATTRIBUTE_UV_0)
+                .and_then(float2_attribute)
+                .and_then(|uvs| interpolate(uvs, &hit))
+                .map(Vec2::from);
+            hits.push((
+                entity,
+                RayMeshHit {
+                    distance: hit.distance,
+                    position: ray.get_point(hit.distance),
+                    normal,
+                    uv,
+                },
+            ));
+        }
+        hits.sort_by(|(_, a), (_, b)| a.distance.total_cmp(&b.distance));
+        hits
+    }
+}
+
+/// Forgets the hierarchies of the modified and removed meshes.
+fn invalidate_mesh_bvhs(
+    mut events: EventReader<AssetEvent<Mesh>>,
+    mut cache: ResMut<MeshBvhCache>,
+) {
+    for event in events.read() {
+        match event {
+            AssetEvent::Modified { id } | AssetEvent::Removed { id } => {
+                cache.bvhs.remove(id);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Rebuilds the [`MeshBoundsBvh`] when an entity with a [`Handle<Mesh>`] moves, changes bounds,
+/// or is added or removed.
+pub fn update_mesh_bounds_bvh(
+    mut bounds: ResMut<MeshBoundsBvh>,
+    entities: Query<(
+        Entity,
+        Ref<Handle<Mesh>>,
+        Ref<GlobalTransform>,
+        Option<Ref<Aabb>>,
+    )>,
+    mut removed_aabbs: RemovedComponents<Aabb>,
+) {
+    let aabbs_removed = removed_aabbs.read().count() > 0;
+    let changed = entities.iter().any(|(_, handle, transform, aabb)| {
+        handle.is_changed() || transform.is_changed() || aabb.is_some_and(|aabb| aabb.is_changed())
+    });
+    if !changed && !aabbs_removed && entities.iter().len() == bounds.len {
+        return;
+    }
+
+    let mut unbounded = Vec::new();
+    let bounded = entities
+        .iter()
+        .filter_map(|(entity, _, transform, aabb)| {
+            let Some(aabb) = aabb else {
+                unbounded.push(entity);
+                return None;
+            };
+            let affine = transform.affine();
+            let center = affine.transform_point3a(aabb.center);
+            let half_extents = Vec3A::new(
+                affine.matrix3.row(0).abs().dot(aabb.half_extents),
+                affine.matrix3.row(1).abs().dot(aabb.half_extents),
+                affine.matrix3.row(2).abs().dot(aabb.half_extents),
+            );
+            Some((
+                entity,
+                Vec3::from(center - half_extents),
+                Vec3::from(center + half_extents),
+            ))
+        })
+        .collect::<Vec<_>>();
+    *bounds = MeshBoundsBvh {
+        bvh: EntityBvh::new(bounded),
+        unbounded,
+        len: entities.iter().len(),
+    };
+}
+
+/// Sends the meshes hit by the pointers through each camera, on the render layers of the camera.
+pub fn mesh_picking(
+    pointers: Res<Pointers>,
+    primary_window: Query<Entity, With<PrimaryWindow>>,
+    cameras: Query<(Entity, &Camera, &GlobalTransform, Option<&RenderLayers>)>,
+    layers: Query<&RenderLayers>,
+    mut ray_cast: MeshRayCast,
+    mut output: EventWriter<PointerHits>,
+) {
+    let primary_window = primary_window.get_single().ok();
+    for (pointer_id, pointer) in pointers.iter() {
+        for (camera_entity, camera, camera_transform, camera_layers) in &cameras {
+            let Some(ray) = pointer_viewport_position(camera, primary_window, pointer)
+                .and_then(|position| camera.viewport_to_world(camera_transform, position))
+            else {
+                continue;
+            };
+            let camera_layers = camera_layers.copied().unwrap_or_default();
+            let picks = ray_cast
+                .cast_ray(ray, |entity| {
+                    let entity_layers = layers.get(entity).copied().unwrap_or_default();
+                    camera_layers.intersects(&entity_layers)
+                })
+                .into_iter()
+                .map(|(entity, hit)| {
+                    let hit_data = HitData {
+                        position: Some(hit.position),
+                        normal: Some(hit.normal),
+                        uv: hit.uv,
+                        ..HitData::new(camera_entity, hit.distance)
+                    };
+                    (entity, hit_data)
+                })
+                .collect();
+            output.send(PointerHits {
+                pointer: pointer_id,
+                picks,
+                order: camera.order as f32,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_ecs::system::RunSystemOnce;
+    use bevy_math::primitives::Cuboid;
+    use bevy_transform::components::Transform;
+
+    #[test]
+    fn rays_hit_the_meshes_where_they_moved() {
+        let mut world = World::new();
+        world.init_resource::<Assets<Mesh>>();
+        world.init_resource::<MeshBvhCache>();
+        world.init_resource::<MeshBoundsBvh>();
+        let mesh = Mesh::from(Cuboid::new(2.0, 2.0, 2.0));
+        let aabb = mesh.compute_aabb().unwrap();
+        let handle = world.resource_mut::<Assets<Mesh>>().add(mesh);
+        let cubes: Vec<_> = (0..8)
+            .map(|i| {
+                let transform = Transform::from_xyz(i as f32 * 4.0, 0.0, 0.0);
+                world
+                    .spawn((
+                        handle.clone(),
+                        GlobalTransform::from(transform),
+                        aabb,
+                        ViewVisibility::HIDDEN,
+                    ))
+                    .id()
+            })
+            .collect();
+        for cube in &cubes {
+            world.get_mut::<ViewVisibility>(*cube).unwrap().set();
+        }
+
+        let cast = |world: &mut World, x| {
+            world.run_system_once(update_mesh_bounds_bvh);
+            world.run_system_once(move |mut ray_cast: MeshRayCast| {
+                let ray = Ray3d::new(Vec3::new(x, 0.0, 10.0), Vec3::NEG_Z);
+                Vec::from_iter(ray_cast.cast_ray(ray, |_| true).into_iter().map(|(e, _)| e))
+            })
+        };
+        assert_eq!(cast(&mut world, 12.5), vec![cubes[3]]);
+        assert_eq!(cast(&mut world, 14.5), vec![]);
+
+        *world.get_mut::<GlobalTransform>(cubes[5]).unwrap() =
+            GlobalTransform::from_xyz(14.0, 0.0, 0.0);
+        assert_eq!(cast(&mut world, 14.5), vec![cubes[5]]);
+
+        world.despawn(cubes[3]);
+        assert_eq!(cast(&mut world, 12.5), vec![]);
+    }
+}
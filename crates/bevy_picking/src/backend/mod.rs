@@ -0,0 +1,110 @@
+//! The backends finding the entities under the pointers.
+//!
+//! A backend is a system in the [`PickSet::Backend`](crate::PickSet::Backend) set, which sends
+//! a [`PointerHits`] event with the entities hit by each pointer, for every camera the pointer is
+//! over. The backends don't need to know about the other backends: the [focus](crate::focus)
+//! sorts the hits of all of them.
+
+mod bvh;
+pub mod mesh;
+#[cfg(feature = "bevy_sprite")]
+pub mod sprite;
+#[cfg(feature = "bevy_ui")]
+pub mod ui;
+
+use bevy_app::{App, Plugin};
+use bevy_ecs::prelude::*;
+use bevy_input::pointer::{Pointer, PointerId};
+use bevy_math::{Vec2, Vec3};
+use bevy_reflect::Reflect;
+use bevy_render::camera::{Camera, NormalizedRenderTarget};
+
+/// The entities hit by a pointer, sent by a backend for a camera the pointer is over.
+#[derive(Event, Debug, Clone, PartialEq, Reflect)]
+pub struct PointerHits {
+    /// The pointer.
+    pub pointer: PointerId,
+    /// The entities hit by the pointer, and where they were hit.
+    ///
+    /// They don't need to be sorted, as the [focus](crate::focus) sorts them by their
+    /// [`HitData::depth`].
+    pub picks: Vec<(Entity, HitData)>,
+    /// The order of the hits relative to the hits of the other cameras and backends: the hits of
+    /// a higher order are on top of the hits of a lower one.
+    ///
+    /// This is usually the [`Camera::order`] of the camera, with an offset for the entities
+    /// drawn on top of the others, like the UI.
+    pub order: f32,
+}
+
+/// Where a pointer hit an entity.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+pub struct HitData {
+    /// The camera the entity was hit through.
+    pub camera: Entity,
+    /// The distance from the camera to the hit, used to sort the hits of a [`PointerHits`]: the
+    /// hits with a smaller depth are on top of the others.
+    ///
+    /// For meshes and sprites, this is the distance in world units along the ray of the pointer.
+    pub depth: f32,
+    /// The position of the hit in world space, if the backend knows it.
+    pub position: Option<Vec3>,
+    /// The normal of the surface at the hit in world space, if the backend knows it.
+    pub normal: Option<Vec3>,
+    /// The texture coordinates of the hit, if the backend knows them.
+    pub uv: Option<Vec2>,
+}
+
+impl HitData {
+    /// Creates the data of a hit at the `depth` through the `camera`, without its position,
+    /// normal and texture coordinates.
+    pub fn new(camera: Entity, depth: f32) -> Self {
+        Self {
+            camera,
+            depth,
+            position: None,
+            normal: None,
+            uv: None,
+        }
+    }
+}
+
+/// The position of the `pointer` in the viewport of the `camera`, in logical pixels from the top
+/// left corner of the viewport, if the pointer is over the viewport.
+///
+/// `primary_window` is the entity of the [`PrimaryWindow`](bevy_window::PrimaryWindow), to
+/// resolve the cameras rendering to it.
+pub fn pointer_viewport_position(
+    camera: &Camera,
+    primary_window: Option<Entity>,
+    pointer: &Pointer,
+) -> Option<Vec2> {
+    if !camera.is_active {
+        return None;
+    }
+    let Some(NormalizedRenderTarget::Window(window)) = camera.target.normalize(primary_window)
+    else {
+        return None;
+    };
+    if window.entity() != pointer.window {
+        return None;
+    }
+    let viewport = camera.logical_viewport_rect()?;
+    viewport
+        .contains(pointer.position)
+        .then(|| pointer.position - viewport.min)
+}
+
+/// Adds the backends of the enabled features.
+pub(crate) struct BackendPlugin;
+
+impl Plugin for BackendPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<PointerHits>()
+            .add_plugins(mesh::MeshBackendPlugin);
+        #[cfg(feature = "bevy_sprite")]
+        app.add_plugins(sprite::SpriteBackendPlugin);
+        #[cfg(feature = "bevy_ui")]
+        app.add_plugins(ui::UiBackendPlugin);
+    }
+}
@@ -0,0 +1,168 @@
+//! The backend picking the [`Sprite`]s seen by the 2D cameras, by intersecting the rays of the
+//! pointers with their rectangles.
+//!
+//! The transparent pixels of the sprites are hit like the opaque ones.
+
+use bevy_app::{App, Plugin, PreUpdate};
+use bevy_asset::{Assets, Handle};
+use bevy_core_pipeline::core_2d::Camera2d;
+use bevy_ecs::prelude::*;
+use bevy_input::pointer::Pointers;
+use bevy_math::{Rect, Vec2, Vec3, Vec3A};
+use bevy_render::{
+    camera::Camera,
+    texture::Image,
+    view::{RenderLayers, ViewVisibility},
+};
+use bevy_sprite::{Sprite, TextureAtlas, TextureAtlasLayout};
+use bevy_transform::components::GlobalTransform;
+use bevy_window::PrimaryWindow;
+
+use super::{pointer_viewport_position, HitData, PointerHits};
+use crate::PickSet;
+
+/// Adds the sprite backend.
+pub struct SpriteBackendPlugin;
+
+impl Plugin for SpriteBackendPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PreUpdate, sprite_picking.in_set(PickSet::Backend));
+    }
+}
+
+/// Sends the sprites hit by the pointers through each 2D camera, on the render layers of the
+/// camera.
+#[allow(clippy::too_many_arguments)]
+pub fn sprite_picking(
+    pointers: Res<Pointers>,
+    primary_window: Query<Entity, With<PrimaryWindow>>,
+    cameras: Query<(Entity, &Camera, &GlobalTransform, Option<&RenderLayers>), With<Camera2d>>,
+    sprites: Query<(
+        Entity,
+        &Sprite,
+        &Handle<Image>,
+        Option<&TextureAtlas>,
+        &GlobalTransform,
+        &ViewVisibility,
+        Option<&RenderLayers>,
+    )>,
+    images: Res<Assets<Image>>,
+    texture_atlases: Res<Assets<TextureAtlasLayout>>,
+    mut output: EventWriter<PointerHits>,
+) {
+    let primary_window = primary_window.get_single().ok();
+    for (pointer_id, pointer) in pointers.iter() {
+        for (camera_entity, camera, camera_transform, camera_layers) in &cameras {
+            let Some(ray) = pointer_viewport_position(camera, primary_window, pointer)
+                .and_then(|position| camera.viewport_to_world(camera_transform, position))
+            else {
+                continue;
+            };
+            let camera_layers = camera_layers.copied().unwrap_or_default();
+
+            let picks = sprites
+                .iter()
+                .filter(|(.., visibility, layers)| {
+                    visibility.get()
+                        && camera_layers.intersects(&layers.copied().unwrap_or_default())
+                })
+                .filter_map(|(entity, sprite, image, atlas, transform, ..)| {
+                    let size = sprite_size(sprite, image, atlas, &images, &texture_atlases)?;
+                    let world_to_sprite = transform.affine().inverse();
+                    let origin = world_to_sprite.transform_point3(ray.origin);
+                    let direction = world_to_sprite.transform_vector3(*ray.direction);
+                    if direction.z.abs() < f32::EPSILON {
+                        return None;
+                    }
+                    // The sprite is on the XY plane of its transform.
+                    let distance = -origin.z / direction.z;
+                    if distance < 0.0 {
+                        return None;
+                    }
+                    let position = (origin + direction * distance).truncate();
+                    let uv = sprite_uv(sprite, size, position)?;
+
+                    let mut normal: Vec3 = (world_to_sprite.matrix3.transpose() * Vec3A::Z)
+                        .normalize_or_zero()
+                        .into();
+                    if normal.dot(*ray.direction) > 0.0 {
+                        normal = -normal;
+                    }
+                    let hit_data = HitData {
+                        position: Some(ray.get_point(distance)),
+                        normal: Some(normal),
+                        uv: Some(uv),
+                        ..HitData::new(camera_entity, distance)
+                    };
+                    Some((entity, hit_data))
+                })
+                .collect();
+            output.send(PointerHits {
+                pointer: pointer_id,
+                picks,
+                order: camera.order as f32,
+            });
+        }
+    }
+}
+
+/// The size of the `sprite` as it's rendered, if its image is loaded.
+fn sprite_size(
+    sprite: &Sprite,
+    image: &Handle<Image>,
+    atlas: Option<&TextureAtlas>,
+    images: &Assets<Image>,
+    texture_atlases: &Assets<TextureAtlasLayout>,
+) -> Option<Vec2> {
+    if let Some(custom_size) = sprite.custom_size {
+        return Some(custom_size);
+    }
+    let atlas_rect = atlas.and_then(|atlas| atlas.texture_rect(texture_atlases));
+    match (atlas_rect, sprite.rect) {
+        (_, Some(rect)) | (Some(rect), None) => Some(rect.size()),
+        (None, None) => images.get(image).map(Image::size_f32),
+    }
+}
+
+/// The texture coordinates of the `position` on the `sprite`, in the space of the sprite, if it's
+/// in its rectangle.
+///
+/// The coordinates go from the top left to the bottom right corner of the image of the sprite,
+/// or of its part displayed by the sprite.
+fn sprite_uv(sprite: &Sprite, size: Vec2, position: Vec2) -> Option<Vec2> {
+    let min = (-Vec2::splat(0.5) - sprite.anchor.as_vec()) * size;
+    let rect = Rect::from_corners(min, min + size);
+    if !rect.contains(position) {
+        return None;
+    }
+    let mut uv = (position - rect.min) / size;
+    uv.y = 1.0 - uv.y;
+    if sprite.flip_x {
+        uv.x = 1.0 - uv.x;
+    }
+    if sprite.flip_y {
+        uv.y = 1.0 - uv.y;
+    }
+    Some(uv)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_sprite::Anchor;
+
+    #[test]
+    fn sprite_uv_follows_anchor_and_flips() {
+        let sprite = Sprite {
+            anchor: Anchor::BottomLeft,
+            flip_x: true,
+            ..Default::default()
+        };
+        let size = Vec2::new(4.0, 2.0);
+        assert_eq!(
+            sprite_uv(&sprite, size, Vec2::new(1.0, 0.5)),
+            Some(Vec2::new(0.75, 0.75))
+        );
+        assert_eq!(sprite_uv(&sprite, size, Vec2::new(-1.0, 0.5)), None);
+    }
+}
@@ -0,0 +1,92 @@
+//! The backend picking the UI nodes under the pointers, on top of the other entities seen by their
+//! cameras.
+//!
+//! The nodes whose [`FocusPolicy`] is [`FocusPolicy::Pass`] are only picked if they have a
+//! [`Pickable`], so that the nodes laying out the others don't block the entities under them.
+
+use bevy_app::{App, Plugin, PreUpdate};
+use bevy_ecs::prelude::*;
+use bevy_input::pointer::Pointers;
+use bevy_render::{camera::Camera, view::ViewVisibility};
+use bevy_transform::components::GlobalTransform;
+use bevy_ui::{CalculatedClip, DefaultUiCamera, FocusPolicy, Node, TargetCamera, UiScale, UiStack};
+use bevy_window::PrimaryWindow;
+
+use super::{pointer_viewport_position, HitData, PointerHits};
+use crate::{PickSet, Pickable};
+
+/// Adds the UI backend.
+pub struct UiBackendPlugin;
+
+impl Plugin for UiBackendPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PreUpdate, ui_picking.in_set(PickSet::Backend));
+    }
+}
+
+/// Sends the UI nodes hit by the pointers for each camera, with the depth of their rank from the
+/// top of the [`UiStack`].
+#[allow(clippy::too_many_arguments)]
+pub fn ui_picking(
+    pointers: Res<Pointers>,
+    primary_window: Query<Entity, With<PrimaryWindow>>,
+    cameras: Query<(Entity, &Camera)>,
+    default_ui_camera: DefaultUiCamera,
+    ui_scale: Res<UiScale>,
+    ui_stack: Res<UiStack>,
+    nodes: Query<(
+        &Node,
+        &GlobalTransform,
+        &ViewVisibility,
+        Option<&CalculatedClip>,
+        Option<&TargetCamera>,
+        Option<&FocusPolicy>,
+        Has<Pickable>,
+    )>,
+    mut output: EventWriter<PointerHits>,
+) {
+    let primary_window = primary_window.get_single().ok();
+    let default_camera = default_ui_camera.get();
+    for (pointer_id, pointer) in pointers.iter() {
+        for (camera_entity, camera) in &cameras {
+            let Some(cursor) = pointer_viewport_position(camera, primary_window, pointer) else {
+                continue;
+            };
+            // The positions of the nodes are scaled by the `UiScale`.
+            let cursor = cursor / ui_scale.0;
+
+            let mut picks = Vec::new();
+            // From the top node to the bottom one.
+            for entity in ui_stack.uinodes.iter().rev() {
+                let Ok((node, transform, visibility, clip, target_camera, focus_policy, pickable)) =
+                    nodes.get(*entity)
+                else {
+                    continue;
+                };
+                if !visibility.get()
+                    || (focus_policy != Some(&FocusPolicy::Block) && !pickable)
+                    || target_camera.map(TargetCamera::entity).or(default_camera)
+                        != Some(camera_entity)
+                {
+                    continue;
+                }
+                let node_rect = node.logical_rect(transform);
+                let visible_rect = clip.map_or(node_rect, |clip| node_rect.intersect(clip.clip));
+                if !visible_rect.contains(cursor) {
+                    continue;
+                }
+                let hit_data = HitData {
+                    uv: Some((cursor - node_rect.min) / node_rect.size()),
+                    ..HitData::new(camera_entity, picks.len() as f32)
+                };
+                picks.push((*entity, hit_data));
+            }
+            output.send(PointerHits {
+                pointer: pointer_id,
+                picks,
+                // The UI is drawn on top of the other entities seen by its camera.
+                order: camera.order as f32 + 0.5,
+            });
+        }
+    }
+}
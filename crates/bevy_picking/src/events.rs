@@ -0,0 +1,447 @@
+//! The [`Pointer`] events sent to the entities when the pointers hover, press, click or drag them.
+//!
+//! For each pointer, the events are sent in that order within a frame: [`Over`], [`Move`],
+//! [`Down`], [`DragStart`], [`Drag`], [`Up`], [`Click`], [`DragEnd`] and [`Out`].
+
+use std::{fmt::Debug, ops::Deref};
+
+use bevy_app::{App, Plugin, PreUpdate};
+use bevy_ecs::{prelude::*, system::SystemParam};
+use bevy_input::pointer::{PointerButton, PointerId, Pointers};
+use bevy_math::Vec2;
+use bevy_utils::{HashMap, HashSet};
+
+use crate::{
+    backend::HitData,
+    focus::{HoverMap, PreviousHoverMap},
+    PickSet,
+};
+
+/// Adds the [`Pointer`] events and the system sending them.
+pub(crate) struct EventsPlugin;
+
+impl Plugin for EventsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Pointer<Over>>()
+            .add_event::<Pointer<Out>>()
+            .add_event::<Pointer<Move>>()
+            .add_event::<Pointer<Down>>()
+            .add_event::<Pointer<Up>>()
+            .add_event::<Pointer<Click>>()
+            .add_event::<Pointer<DragStart>>()
+            .add_event::<Pointer<Drag>>()
+            .add_event::<Pointer<DragEnd>>()
+            .add_systems(PreUpdate, send_pointer_events.in_set(PickSet::Events));
+    }
+}
+
+/// An event sent to the `target` entity by a pointer, like [`Pointer<Click>`].
+///
+/// The data of the event, like the [`HitData`] of a [`Click`], can be accessed through the
+/// `Deref` implementation.
+#[derive(Event, Debug, Clone, PartialEq)]
+pub struct Pointer<E: Debug + Clone + Send + Sync + 'static> {
+    /// The entity the event is sent to.
+    pub target: Entity,
+    /// The pointer sending the event.
+    pub pointer_id: PointerId,
+    /// The position of the pointer in its window, in logical pixels.
+    pub pointer_position: Vec2,
+    /// The data of the event.
+    pub event: E,
+}
+
+impl<E: Debug + Clone + Send + Sync + 'static> Pointer<E> {
+    /// Creates an `event` sent to the `target` by the pointer `pointer_id` at `pointer_position`.
+    pub fn new(target: Entity, pointer_id: PointerId, pointer_position: Vec2, event: E) -> Self {
+        Self {
+            target,
+            pointer_id,
+            pointer_position,
+            event,
+        }
+    }
+}
+
+impl<E: Debug + Clone + Send + Sync + 'static> Deref for Pointer<E> {
+    type Target = E;
+
+    fn deref(&self) -> &Self::Target {
+        &self.event
+    }
+}
+
+/// The pointer started hovering the entity.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Over {
+    /// Where the entity was hit.
+    pub hit: HitData,
+}
+
+/// The pointer stopped hovering the entity.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Out {
+    /// Where the entity was last hit.
+    pub hit: HitData,
+}
+
+/// The pointer moved while hovering the entity.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Move {
+    /// Where the entity was hit.
+    pub hit: HitData,
+    /// How much the pointer moved since the previous frame, in logical pixels.
+    pub delta: Vec2,
+}
+
+/// A button of the pointer was pressed over the entity.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Down {
+    /// The pressed button.
+    pub button: PointerButton,
+    /// Where the entity was hit.
+    pub hit: HitData,
+}
+
+/// A button of the pointer was released over the entity.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Up {
+    /// The released button.
+    pub button: PointerButton,
+    /// Where the entity was hit.
+    pub hit: HitData,
+}
+
+/// A button of the pointer was pressed, then released over the entity.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Click {
+    /// The clicked button.
+    pub button: PointerButton,
+    /// Where the entity was hit when the button was released.
+    pub hit: HitData,
+}
+
+/// The pointer moved while a button was pressed since the entity was hovered.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DragStart {
+    /// The pressed button.
+    pub button: PointerButton,
+    /// Where the entity was hit when the button was pressed.
+    pub hit: HitData,
+}
+
+/// The entity is dragged by the pointer, and the pointer moved.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Drag {
+    /// The pressed button.
+    pub button: PointerButton,
+    /// How much the pointer moved since the button was pressed, in logical pixels.
+    pub distance: Vec2,
+    /// How much the pointer moved since the previous frame, in logical pixels.
+    pub delta: Vec2,
+}
+
+/// The button dragging the entity was released.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DragEnd {
+    /// The released button.
+    pub button: PointerButton,
+    /// How much the pointer moved since the button was pressed, in logical pixels.
+    pub distance: Vec2,
+}
+
+/// A button pressed over some entities.
+#[derive(Debug, Clone)]
+struct Press {
+    position: Vec2,
+    targets: Vec<(Entity, HitData)>,
+    dragging: bool,
+}
+
+/// The state of the pointers tracked by [`send_pointer_events`] across frames.
+#[derive(Default)]
+pub struct PointerEventState {
+    positions: HashMap<PointerId, Vec2>,
+    presses: HashMap<(PointerId, PointerButton), Press>,
+}
+
+/// The writers of all the [`Pointer`] events.
+#[derive(SystemParam)]
+pub struct PointerEventWriters<'w> {
+    over: EventWriter<'w, Pointer<Over>>,
+    out: EventWriter<'w, Pointer<Out>>,
+    moves: EventWriter<'w, Pointer<Move>>,
+    down: EventWriter<'w, Pointer<Down>>,
+    up: EventWriter<'w, Pointer<Up>>,
+    click: EventWriter<'w, Pointer<Click>>,
+    drag_start: EventWriter<'w, Pointer<DragStart>>,
+    drag: EventWriter<'w, Pointer<Drag>>,
+    drag_end: EventWriter<'w, Pointer<DragEnd>>,
+}
+
+const BUTTONS: [PointerButton; 3] = [
+    PointerButton::Primary,
+    PointerButton::Secondary,
+    PointerButton::Middle,
+];
+
+/// Sends the [`Pointer`] events from the changes of the [`HoverMap`] and of the [`Pointers`].
+pub fn send_pointer_events(
+    pointers: Res<Pointers>,
+    hover_map: Res<HoverMap>,
+    previous_hover_map: Res<PreviousHoverMap>,
+    mut state: Local<PointerEventState>,
+    mut writers: PointerEventWriters,
+) {
+    let pointer_ids: HashSet<PointerId> = pointers
+        .iter()
+        .map(|(id, _)| id)
+        .chain(previous_hover_map.0.keys().copied())
+        .chain(state.positions.keys().copied())
+        .chain(state.presses.keys().map(|(id, _)| *id))
+        .collect();
+
+    for pointer_id in pointer_ids {
+        let current = pointers.get(pointer_id).map(|pointer| pointer.position);
+        let last_position = state.positions.get(&pointer_id).copied();
+        let Some(position) = current.or(last_position) else {
+            continue;
+        };
+        let hovered = hover_map.get(pointer_id);
+        let previously_hovered = previous_hover_map
+            .0
+            .get(&pointer_id)
+            .map_or(&[][..], Vec::as_slice);
+
+        for &(entity, hit) in hovered {
+            if !contains(previously_hovered, entity) {
+                writers
+                    .over
+                    .send(Pointer::new(entity, pointer_id, position, Over { hit }));
+            }
+        }
+
+        let delta = current
+            .zip(last_position)
+            .map(|(current, last)| current - last)
+            .filter(|delta| *delta != Vec2::ZERO);
+        if let Some(delta) = delta {
+            for &(entity, hit) in hovered {
+                writers.moves.send(Pointer::new(
+                    entity,
+                    pointer_id,
+                    position,
+                    Move { hit, delta },
+                ));
+            }
+        }
+
+        // A lifted touch leaves the window when it's released, so it's released over the
+        // entities it hovered.
+        let release_targets = if current.is_some() {
+            hovered
+        } else {
+            previously_hovered
+        };
+        for button in BUTTONS {
+            if pointers.just_pressed(pointer_id, button) {
+                for &(entity, hit) in hovered {
+                    writers.down.send(Pointer::new(
+                        entity,
+                        pointer_id,
+                        position,
+                        Down { button, hit },
+                    ));
+                }
+                state.presses.insert(
+                    (pointer_id, button),
+                    Press {
+                        position,
+                        targets: hovered.to_vec(),
+                        dragging: false,
+                    },
+                );
+            }
+
+            if let (Some(press), Some(delta)) =
+                (state.presses.get_mut(&(pointer_id, button)), delta)
+            {
+                if !press.dragging {
+                    press.dragging = true;
+                    for &(entity, hit) in &press.targets {
+                        writers.drag_start.send(Pointer::new(
+                            entity,
+                            pointer_id,
+                            position,
+                            DragStart { button, hit },
+                        ));
+                    }
+                }
+                let distance = position - press.position;
+                for &(entity, _) in &press.targets {
+                    writers.drag.send(Pointer::new(
+                        entity,
+                        pointer_id,
+                        position,
+                        Drag {
+                            button,
+                            distance,
+                            delta,
+                        },
+                    ));
+                }
+            }
+
+            if !pointers.pressed(pointer_id, button) {
+                let released = pointers.just_released(pointer_id, button);
+                let press = state.presses.remove(&(pointer_id, button));
+                if released {
+                    for &(entity, hit) in release_targets {
+                        writers.up.send(Pointer::new(
+                            entity,
+                            pointer_id,
+                            position,
+                            Up { button, hit },
+                        ));
+                    }
+                }
+                let Some(press) = press else {
+                    continue;
+                };
+                if released {
+                    for &(entity, hit) in release_targets {
+                        if contains(&press.targets, entity) {
+                            writers.click.send(Pointer::new(
+                                entity,
+                                pointer_id,
+                                position,
+                                Click { button, hit },
+                            ));
+                        }
+                    }
+                }
+                if press.dragging {
+                    let distance = position - press.position;
+                    for &(entity, _) in &press.targets {
+                        writers.drag_end.send(Pointer::new(
+                            entity,
+                            pointer_id,
+                            position,
+                            DragEnd { button, distance },
+                        ));
+                    }
+                }
+            }
+        }
+
+        for &(entity, hit) in previously_hovered {
+            if !contains(hovered, entity) {
+                writers
+                    .out
+                    .send(Pointer::new(entity, pointer_id, position, Out { hit }));
+            }
+        }
+
+        match current {
+            Some(position) => {
+                state.positions.insert(pointer_id, position);
+            }
+            None => {
+                state.positions.remove(&pointer_id);
+            }
+        }
+    }
+}
+
+fn contains(hits: &[(Entity, HitData)], entity: Entity) -> bool {
+    hits.iter().any(|(hit_entity, _)| *hit_entity == entity)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{backend::PointerHits, focus::FocusPlugin};
+    use bevy_ecs::event::Events;
+    use bevy_input::{
+        pointer::{PointerAction, PointerInput},
+        InputPlugin, InputSystem,
+    };
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins((InputPlugin, FocusPlugin, EventsPlugin))
+            .add_event::<PointerHits>()
+            .configure_sets(
+                PreUpdate,
+                (PickSet::Backend, PickSet::Focus, PickSet::Events)
+                    .chain()
+                    .after(InputSystem),
+            );
+        app
+    }
+
+    fn update(app: &mut App, action: PointerAction, position: Vec2, picks: &[Entity]) {
+        let window = Entity::PLACEHOLDER;
+        app.world.send_event(PointerInput {
+            id: PointerId::Touch(0),
+            action,
+            window,
+            position,
+            pressure: 0.5,
+        });
+        app.world.send_event(PointerHits {
+            pointer: PointerId::Touch(0),
+            picks: picks
+                .iter()
+                .map(|entity| (*entity, HitData::new(window, 1.0)))
+                .collect(),
+            order: 0.0,
+        });
+        app.update();
+    }
+
+    fn targets<E: Debug + Clone + Send + Sync + 'static>(app: &mut App) -> Vec<Entity> {
+        app.world
+            .resource_mut::<Events<Pointer<E>>>()
+            .drain()
+            .map(|event| event.target)
+            .collect()
+    }
+
+    #[test]
+    fn touches_hover_click_and_drag_entities() {
+        let mut app = app();
+        let entity = app.world.spawn_empty().id();
+
+        update(&mut app, PointerAction::Moved, Vec2::ZERO, &[entity]);
+        assert_eq!(targets::<Over>(&mut app), vec![entity]);
+
+        update(
+            &mut app,
+            PointerAction::Pressed(PointerButton::Primary),
+            Vec2::ZERO,
+            &[entity],
+        );
+        assert_eq!(targets::<Down>(&mut app), vec![entity]);
+        assert!(targets::<Move>(&mut app).is_empty());
+
+        update(&mut app, PointerAction::Moved, Vec2::X, &[entity]);
+        assert_eq!(targets::<Move>(&mut app), vec![entity]);
+        assert_eq!(targets::<DragStart>(&mut app), vec![entity]);
+        assert_eq!(targets::<Drag>(&mut app), vec![entity]);
+
+        // The touch is lifted, leaving the window.
+        app.world.send_event(PointerInput {
+            id: PointerId::Touch(0),
+            action: PointerAction::Released(PointerButton::Primary),
+            window: Entity::PLACEHOLDER,
+            position: Vec2::X,
+            pressure: 0.0,
+        });
+        update(&mut app, PointerAction::Left, Vec2::X, &[]);
+        assert_eq!(targets::<Up>(&mut app), vec![entity]);
+        assert_eq!(targets::<Click>(&mut app), vec![entity]);
+        assert_eq!(targets::<DragEnd>(&mut app), vec![entity]);
+        assert_eq!(targets::<Out>(&mut app), vec![entity]);
+    }
+}
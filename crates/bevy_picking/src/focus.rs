@@ -0,0 +1,149 @@
+//! The entities hovered by the pointers, merged from the hits of all the backends.
+
+use bevy_app::{App, Plugin, PreUpdate};
+use bevy_ecs::prelude::*;
+use bevy_input::pointer::PointerId;
+use bevy_utils::HashMap;
+
+use crate::{
+    backend::{HitData, PointerHits},
+    PickSet, Pickable,
+};
+
+/// Adds the [`HoverMap`] and the system updating it.
+pub(crate) struct FocusPlugin;
+
+impl Plugin for FocusPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HoverMap>()
+            .init_resource::<PreviousHoverMap>()
+            .add_systems(PreUpdate, update_focus.in_set(PickSet::Focus));
+    }
+}
+
+/// The entities hovered by each pointer, from the top to the bottom, and where they were hit.
+///
+/// The pointers which don't hover any entity aren't in the map.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct HoverMap(pub HashMap<PointerId, Vec<(Entity, HitData)>>);
+
+impl HoverMap {
+    /// The entities hovered by the `pointer`, from the top to the bottom.
+    pub fn get(&self, pointer: PointerId) -> &[(Entity, HitData)] {
+        self.0.get(&pointer).map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns `true` if the `entity` is hovered by any pointer.
+    pub fn is_hovered(&self, entity: Entity) -> bool {
+        self.0
+            .values()
+            .any(|hovered| hovered.iter().any(|(hovered, _)| *hovered == entity))
+    }
+}
+
+/// The [`HoverMap`] of the previous frame, to find the entities whose hover changed.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct PreviousHoverMap(pub HashMap<PointerId, Vec<(Entity, HitData)>>);
+
+/// Updates the [`HoverMap`] from the [`PointerHits`] sent by the backends this frame.
+pub fn update_focus(
+    mut pointer_hits: EventReader<PointerHits>,
+    pickables: Query<&Pickable>,
+    mut hover_map: ResMut<HoverMap>,
+    mut previous_hover_map: ResMut<PreviousHoverMap>,
+) {
+    previous_hover_map.0 = std::mem::take(&mut hover_map.0);
+
+    let mut hits_by_pointer: HashMap<PointerId, Vec<&PointerHits>> = HashMap::default();
+    for hits in pointer_hits.read() {
+        hits_by_pointer.entry(hits.pointer).or_default().push(hits);
+    }
+    for (pointer, hits) in hits_by_pointer {
+        let hovered = hovered_entities(&hits, |entity| {
+            pickables.get(entity).copied().unwrap_or_default()
+        });
+        if !hovered.is_empty() {
+            hover_map.0.insert(pointer, hovered);
+        }
+    }
+}
+
+/// Merges the `hits` of a pointer from the top to the bottom, keeping the hoverable entities until
+/// an entity blocks the lower ones.
+fn hovered_entities(
+    hits: &[&PointerHits],
+    pickable: impl Fn(Entity) -> Pickable,
+) -> Vec<(Entity, HitData)> {
+    let mut picks: Vec<(f32, &(Entity, HitData))> = hits
+        .iter()
+        .flat_map(|hits| hits.picks.iter().map(|pick| (hits.order, pick)))
+        .collect();
+    // The highest order first, then the smallest depth.
+    picks.sort_by(|(order_a, (_, hit_a)), (order_b, (_, hit_b))| {
+        order_b
+            .total_cmp(order_a)
+            .then(hit_a.depth.total_cmp(&hit_b.depth))
+    });
+
+    let mut hovered: Vec<(Entity, HitData)> = Vec::new();
+    for (_, (entity, hit)) in picks {
+        let pickable = pickable(*entity);
+        if pickable.is_hoverable && hovered.iter().all(|(hovered, _)| hovered != entity) {
+            hovered.push((*entity, *hit));
+        }
+        if pickable.should_block_lower {
+            break;
+        }
+    }
+    hovered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hits_are_sorted_until_blocked() {
+        let camera = Entity::from_raw(0);
+        let [ui, near, far, glass] = [1, 2, 3, 4].map(Entity::from_raw);
+        let hit = |entity, depth| (entity, HitData::new(camera, depth));
+        let meshes = PointerHits {
+            pointer: PointerId::Mouse,
+            picks: vec![hit(far, 5.0), hit(near, 2.0), hit(glass, 1.0)],
+            order: 0.0,
+        };
+        let pickable = |entity| {
+            if entity == glass {
+                Pickable {
+                    should_block_lower: false,
+                    is_hoverable: true,
+                }
+            } else {
+                Pickable::default()
+            }
+        };
+
+        let hovered: Vec<Entity> = hovered_entities(&[&meshes], pickable)
+            .into_iter()
+            .map(|(entity, _)| entity)
+            .collect();
+        assert_eq!(hovered, vec![glass, near]);
+
+        let ui_hits = PointerHits {
+            pointer: PointerId::Mouse,
+            picks: vec![hit(ui, 0.0)],
+            order: 0.5,
+        };
+        let hovered = hovered_entities(&[&meshes, &ui_hits], pickable);
+        assert_eq!(hovered, vec![hit(ui, 0.0)]);
+
+        let hovered = hovered_entities(&[&meshes, &ui_hits], |entity| {
+            if entity == ui {
+                Pickable::IGNORE
+            } else {
+                pickable(entity)
+            }
+        });
+        assert_eq!(hovered.len(), 2);
+    }
+}
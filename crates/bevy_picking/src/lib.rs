@@ -0,0 +1,114 @@
+//! This crate finds the entities under the [`Pointers`](bevy_input::pointer::Pointers) —
+//! the mouse cursor, touches and pens — and sends [`Pointer`](events::Pointer) events when they
+//! are hovered, clicked or dragged.
+//!
+//! Picking is split in three steps, each in its own [`PickSet`]:
+//! - The [backends](backend) cast the pointers into the world and send the entities they hit
+//!   as [`PointerHits`](backend::PointerHits). This crate provides backends for meshes, sprites
+//!   and UI nodes, and other backends can be added for other kinds of entities.
+//! - The [focus](focus) merges the hits of all the backends, from the top to the bottom, into
+//!   the [`HoverMap`](focus::HoverMap) of the entities hovered by each pointer, stopping at the
+//!   first entity which blocks the entities under it.
+//! - The [events](events) compare the hovered entities and the pointers with the previous frame
+//!   to send the [`Pointer`](events::Pointer) events.
+//!
+//! # Example
+//!
+//! ```
+//! # use bevy_ecs::prelude::*;
+//! # use bevy_picking::prelude::*;
+//! fn print_clicks(mut clicks: EventReader<Pointer<Click>>) {
+//!     for click in clicks.read() {
+//!         println!("{:?} clicked {:?} at {:?}", click.pointer_id, click.target, click.hit.position);
+//!     }
+//! }
+//! # bevy_ecs::system::assert_is_system(print_clicks);
+//! ```
+
+pub mod backend;
+pub mod events;
+pub mod focus;
+
+/// The `bevy_picking` prelude.
+pub mod prelude {
+    #[doc(hidden)]
+    pub use crate::{
+        backend::{mesh::MeshRayCast, HitData, PointerHits},
+        events::{Click, Down, Drag, DragEnd, DragStart, Move, Out, Over, Pointer, Up},
+        focus::HoverMap,
+        PickSet, Pickable, PickingPlugin,
+    };
+}
+
+use bevy_app::{App, Plugin, PreUpdate};
+use bevy_ecs::prelude::*;
+use bevy_input::InputSystem;
+use bevy_reflect::{std_traits::ReflectDefault, Reflect};
+
+/// Configures how an entity takes part in picking.
+///
+/// The entities without a [`Pickable`] are picked like with [`Pickable::default`], except for the
+/// UI nodes whose `FocusPolicy` lets the interactions pass through them, which aren't picked at
+/// all.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component, Default)]
+pub struct Pickable {
+    /// Whether the entities under this entity can't be hovered by the same pointer.
+    pub should_block_lower: bool,
+    /// Whether this entity is hovered, and receives [`Pointer`](events::Pointer) events.
+    ///
+    /// An entity which isn't hoverable can still block the entities under it.
+    pub is_hoverable: bool,
+}
+
+impl Pickable {
+    /// An entity which is ignored by picking: it isn't hovered and doesn't block the entities
+    /// under it.
+    pub const IGNORE: Self = Self {
+        should_block_lower: false,
+        is_hoverable: false,
+    };
+}
+
+impl Default for Pickable {
+    fn default() -> Self {
+        Self {
+            should_block_lower: true,
+            is_hoverable: true,
+        }
+    }
+}
+
+/// The system sets of picking, running in that order in the [`PreUpdate`] schedule, after the
+/// [`InputSystem`].
+#[derive(SystemSet, Debug, Hash, PartialEq, Eq, Clone, Copy)]
+pub enum PickSet {
+    /// The backends send the [`PointerHits`](backend::PointerHits) of the pointers.
+    Backend,
+    /// The [`HoverMap`](focus::HoverMap) is updated from the hits of the backends.
+    Focus,
+    /// The [`Pointer`](events::Pointer) events are sent.
+    Events,
+}
+
+/// Adds picking, with the backends for the meshes, and for the sprites and UI nodes if their
+/// features are enabled.
+#[derive(Default)]
+pub struct PickingPlugin;
+
+impl Plugin for PickingPlugin {
+    fn build(&self, app: &mut App) {
+        app.configure_sets(
+            PreUpdate,
+            (PickSet::Backend, PickSet::Focus, PickSet::Events)
+                .chain()
+                .after(InputSystem),
+        )
+        .register_type::<Pickable>()
+        .add_plugins((
+            backend::BackendPlugin,
+            focus::FocusPlugin,
+            events::EventsPlugin,
+        ));
+    }
+}
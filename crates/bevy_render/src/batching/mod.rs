@@ -5,6 +5,8 @@ use bevy_ecs::{
     system::{Query, ResMut, StaticSystemParam, SystemParam, SystemParamItem},
 };
 use bevy_utils::nonmax::NonMaxU32;
+#[cfg(feature = "trace")]
+use bevy_utils::{get_short_name, tracing::info_span};
 
 use crate::{
     render_phase::{CachedRenderPipelinePhaseItem, DrawFunctionId, RenderPhase},
@@ -100,6 +102,14 @@ pub fn batch_and_prepare_render_phase<I: CachedRenderPipelinePhaseItem, F: GetBa
     };
 
     for mut phase in &mut views {
+        #[cfg(feature = "trace")]
+        let _span = info_span!(
+            "batch_and_prepare_render_phase",
+            phase = get_short_name(std::any::type_name::<I>()),
+            items = phase.items.len(),
+        )
+        .entered();
+
         let items = phase.items.iter_mut().map(|item| {
             let batch_data = process_item(item);
             (item.batch_range_mut(), batch_data)
@@ -0,0 +1,611 @@
+//! Runs compute shaders at the request of the main world, and reads their results back.
+//!
+//! Implement [`ComputeWorker`] to declare the buffers and passes of the shaders, add a
+//! [`ComputeWorkerPlugin`] for it, then write, dispatch and read the buffers from the systems of
+//! the main world with the [`AppComputeWorker`] resource.
+
+use std::marker::PhantomData;
+
+use async_channel::{Receiver, Sender};
+use bevy_app::{App, First, Last, Plugin};
+use bevy_asset::Handle;
+use bevy_ecs::prelude::*;
+use bevy_tasks::AsyncComputeTaskPool;
+use bevy_utils::tracing::error;
+use encase::{
+    private::{CreateFrom, WriteInto},
+    ShaderType, StorageBuffer, UniformBuffer,
+};
+
+use crate::{
+    graph::CameraDriverLabel,
+    render_graph::{Node, NodeRunError, RenderGraph, RenderGraphContext, RenderLabel},
+    render_resource::{
+        binding_types::{storage_buffer_sized, uniform_buffer_sized},
+        BindGroup, BindGroupEntry, BindGroupLayout, Buffer, BufferDescriptor, BufferInitDescriptor,
+        BufferUsages, CachedComputePipelineId, ComputePassDescriptor, ComputePipelineDescriptor,
+        MapMode, PipelineCache, Shader, ShaderStages,
+    },
+    renderer::{RenderContext, RenderDevice, RenderQueue},
+    Render, RenderApp, RenderSet,
+};
+
+/// A set of compute shaders and of the buffers they read and write, run on the GPU at the
+/// request of the main world.
+///
+/// # Example
+///
+/// ```
+/// # use bevy_asset::AssetServer;
+/// # use bevy_ecs::prelude::*;
+/// # use bevy_render::compute_worker::*;
+/// struct SquareWorker;
+///
+/// impl ComputeWorker for SquareWorker {
+///     fn build(world: &mut World) -> ComputeWorkerBuilder<Self> {
+///         // `square.wgsl` has a `square` entry point, with the values bound at binding 0 of
+///         // group 0.
+///         let shader = world.resource::<AssetServer>().load("shaders/square.wgsl");
+///         ComputeWorkerBuilder::new()
+///             .add_readback("values", &vec![0.0f32; 64])
+///             .add_pass(shader, "square", [1, 1, 1], &["values"])
+///     }
+/// }
+///
+/// fn square(mut worker: ResMut<AppComputeWorker<SquareWorker>>) {
+///     if !worker.is_ready() {
+///         return;
+///     }
+///     if let Some(values) = worker.read::<Vec<f32>>("values") {
+///         println!("{values:?}");
+///     }
+///     worker.write("values", &(0..64).map(|i| i as f32).collect::<Vec<_>>());
+///     worker.dispatch();
+/// }
+/// # bevy_ecs::system::assert_is_system(square);
+/// ```
+pub trait ComputeWorker: Send + Sync + Sized + 'static {
+    /// Declares the buffers and passes of the worker, once the app is built.
+    fn build(world: &mut World) -> ComputeWorkerBuilder<Self>;
+}
+
+/// How the shaders of a [`ComputeWorker`] use one of its buffers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ComputeBufferKind {
+    Uniform,
+    Storage,
+    /// A storage buffer read back into the main world after each dispatch.
+    Readback,
+}
+
+struct ComputeBuffer {
+    name: &'static str,
+    kind: ComputeBufferKind,
+    data: Vec<u8>,
+}
+
+struct ComputeWorkerPass {
+    shader: Handle<Shader>,
+    entry_point: &'static str,
+    workgroups: [u32; 3],
+    buffers: Vec<usize>,
+}
+
+/// Declares the buffers and passes of a [`ComputeWorker`].
+pub struct ComputeWorkerBuilder<W: ComputeWorker> {
+    buffers: Vec<ComputeBuffer>,
+    passes: Vec<ComputeWorkerPass>,
+    marker: PhantomData<W>,
+}
+
+impl<W: ComputeWorker> Default for ComputeWorkerBuilder<W> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<W: ComputeWorker> ComputeWorkerBuilder<W> {
+    pub fn new() -> Self {
+        Self {
+            buffers: Vec::new(),
+            passes: Vec::new(),
+            marker: PhantomData,
+        }
+    }
+
+    /// Adds a uniform buffer, with its initial value.
+    pub fn add_uniform<T: ShaderType + WriteInto>(self, name: &'static str, value: &T) -> Self {
+        self.add_buffer(name, ComputeBufferKind::Uniform, value)
+    }
+
+    /// Adds a storage buffer, with its initial value, which the shaders can read and write.
+    pub fn add_storage<T: ShaderType + WriteInto>(self, name: &'static str, value: &T) -> Self {
+        self.add_buffer(name, ComputeBufferKind::Storage, value)
+    }
+
+    /// Adds a storage buffer like [`add_storage`](Self::add_storage), whose value is read back
+    /// into the main world after each dispatch.
+    pub fn add_readback<T: ShaderType + WriteInto>(self, name: &'static str, value: &T) -> Self {
+        self.add_buffer(name, ComputeBufferKind::Readback, value)
+    }
+
+    fn add_buffer<T: ShaderType + WriteInto>(
+        mut self,
+        name: &'static str,
+        kind: ComputeBufferKind,
+        value: &T,
+    ) -> Self {
+        if self.buffers.iter().any(|buffer| buffer.name == name) {
+            panic!("The compute worker already has a buffer named {name}");
+        }
+        self.buffers.push(ComputeBuffer {
+            name,
+            kind,
+            data: encode(kind, value),
+        });
+        self
+    }
+
+    /// Adds a pass dispatching the `entry_point` of a shader over `workgroups`, after the
+    /// previous passes.
+    ///
+    /// The `buffers` are bound in order to the bindings of group 0, from binding 0.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no buffer was added with one of the names of `buffers`.
+    pub fn add_pass(
+        mut self,
+        shader: impl Into<Handle<Shader>>,
+        entry_point: &'static str,
+        workgroups: [u32; 3],
+        buffers: &[&str],
+    ) -> Self {
+        let buffers = buffers
+            .iter()
+            .map(|&name| buffer_index(&self.buffers, name))
+            .collect();
+        self.passes.push(ComputeWorkerPass {
+            shader: shader.into(),
+            entry_point,
+            workgroups,
+            buffers,
+        });
+        self
+    }
+}
+
+fn buffer_index(buffers: &[ComputeBuffer], name: &str) -> usize {
+    buffers
+        .iter()
+        .position(|buffer| buffer.name == name)
+        .unwrap_or_else(|| panic!("The compute worker has no buffer named {name}"))
+}
+
+/// Encodes a value with the layout of its buffer.
+fn encode<T: ShaderType + WriteInto>(kind: ComputeBufferKind, value: &T) -> Vec<u8> {
+    if kind == ComputeBufferKind::Uniform {
+        let mut buffer = UniformBuffer::new(Vec::new());
+        buffer.write(value).unwrap();
+        buffer.into_inner()
+    } else {
+        let mut buffer = StorageBuffer::new(Vec::new());
+        buffer.write(value).unwrap();
+        buffer.into_inner()
+    }
+}
+
+/// Adds the [`AppComputeWorker`] of a [`ComputeWorker`], and runs its shaders when dispatched.
+pub struct ComputeWorkerPlugin<W: ComputeWorker>(PhantomData<W>);
+
+impl<W: ComputeWorker> Default for ComputeWorkerPlugin<W> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<W: ComputeWorker> Plugin for ComputeWorkerPlugin<W> {
+    fn build(&self, _app: &mut App) {}
+
+    fn finish(&self, app: &mut App) {
+        if app.get_sub_app(RenderApp).is_err() {
+            return;
+        }
+
+        let builder = W::build(&mut app.world);
+        let (request_sender, request_receiver) = async_channel::unbounded();
+        let (result_sender, result_receiver) = async_channel::unbounded();
+
+        app.insert_resource(AppComputeWorker::<W> {
+            buffers: builder
+                .buffers
+                .iter()
+                .map(|buffer| (buffer.name, buffer.kind))
+                .collect(),
+            writes: Vec::new(),
+            dispatch: false,
+            in_flight: 0,
+            results: vec![None; builder.buffers.len()],
+            request_sender,
+            result_receiver,
+            marker: PhantomData,
+        })
+        .add_systems(First, receive_compute_worker_results::<W>)
+        .add_systems(Last, send_compute_worker_requests::<W>);
+
+        let render_app = app.sub_app_mut(RenderApp);
+        let worker = RenderComputeWorker::<W>::new(
+            &render_app.world,
+            builder,
+            request_receiver,
+            result_sender,
+        );
+        render_app.insert_resource(worker).add_systems(
+            Render,
+            (
+                prepare_compute_worker::<W>.in_set(RenderSet::PrepareResources),
+                read_back_compute_worker::<W>.in_set(RenderSet::Cleanup),
+            ),
+        );
+
+        let label = ComputeWorkerLabel(std::any::type_name::<W>());
+        let mut render_graph = render_app.world.resource_mut::<RenderGraph>();
+        render_graph.add_node(label.clone(), ComputeWorkerNode::<W>(PhantomData));
+        render_graph.add_node_edge(label, CameraDriverLabel);
+    }
+}
+
+/// The buffers of a [`ComputeWorker`] in the main world, to write, dispatch and read back.
+///
+/// Writes and dispatches are sent to the GPU at the end of the frame, and the values of the
+/// readback buffers are received at the start of a later frame, once the GPU is done.
+#[derive(Resource)]
+pub struct AppComputeWorker<W: ComputeWorker> {
+    buffers: Vec<(&'static str, ComputeBufferKind)>,
+    writes: Vec<(usize, Vec<u8>)>,
+    dispatch: bool,
+    /// The number of dispatches whose results weren't received yet.
+    in_flight: u32,
+    results: Vec<Option<Vec<u8>>>,
+    request_sender: Sender<ComputeWorkerRequest>,
+    result_receiver: Receiver<Vec<(usize, Vec<u8>)>>,
+    marker: PhantomData<W>,
+}
+
+struct ComputeWorkerRequest {
+    writes: Vec<(usize, Vec<u8>)>,
+    dispatch: bool,
+}
+
+impl<W: ComputeWorker> AppComputeWorker<W> {
+    /// Writes the value of a buffer, before the next dispatch.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the worker has no buffer named `name`.
+    pub fn write<T: ShaderType + WriteInto>(&mut self, name: &str, value: &T) {
+        let index = self.index(name);
+        let data = encode(self.buffers[index].1, value);
+        self.writes.retain(|&(write_index, _)| write_index != index);
+        self.writes.push((index, data));
+    }
+
+    /// Runs the passes of the worker, once the previous writes are done.
+    pub fn dispatch(&mut self) {
+        self.dispatch = true;
+    }
+
+    /// Whether the results of all the dispatches were read back.
+    pub fn is_ready(&self) -> bool {
+        !self.dispatch && self.in_flight == 0
+    }
+
+    /// Returns the value of a readback buffer after the last dispatch whose results were
+    /// received, or `None` if none were.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the worker has no buffer named `name`.
+    pub fn read<T: ShaderType + CreateFrom>(&self, name: &str) -> Option<T> {
+        let data = self.results[self.index(name)].as_ref()?;
+        match StorageBuffer::new(data.as_slice()).create() {
+            Ok(value) => Some(value),
+            Err(err) => {
+                error!("Could not read the compute worker buffer {name}: {err}");
+                None
+            }
+        }
+    }
+
+    fn index(&self, name: &str) -> usize {
+        self.buffers
+            .iter()
+            .position(|&(buffer_name, _)| buffer_name == name)
+            .unwrap_or_else(|| panic!("The compute worker has no buffer named {name}"))
+    }
+}
+
+fn send_compute_worker_requests<W: ComputeWorker>(mut worker: ResMut<AppComputeWorker<W>>) {
+    if worker.writes.is_empty() && !worker.dispatch {
+        return;
+    }
+
+    let request = ComputeWorkerRequest {
+        writes: std::mem::take(&mut worker.writes),
+        dispatch: std::mem::take(&mut worker.dispatch),
+    };
+    if request.dispatch {
+        worker.in_flight += 1;
+    }
+    // The render world only drops the receiver when the app exits.
+    let _ = worker.request_sender.try_send(request);
+}
+
+fn receive_compute_worker_results<W: ComputeWorker>(mut worker: ResMut<AppComputeWorker<W>>) {
+    while let Ok(results) = worker.result_receiver.try_recv() {
+        worker.in_flight = worker.in_flight.saturating_sub(1);
+        for (index, data) in results {
+            worker.results[index] = Some(data);
+        }
+    }
+}
+
+/// The buffers and pipelines of a [`ComputeWorker`] in the render world.
+#[derive(Resource)]
+struct RenderComputeWorker<W: ComputeWorker> {
+    buffers: Vec<(ComputeBufferKind, Buffer)>,
+    passes: Vec<RenderComputeWorkerPass>,
+    request_receiver: Receiver<ComputeWorkerRequest>,
+    result_sender: Sender<Vec<(usize, Vec<u8>)>>,
+    /// The number of dispatches waiting for the pipelines to be ready.
+    pending_dispatches: u32,
+    /// The staging buffers of the readback buffers, when the passes are dispatched this frame.
+    readbacks: Option<Vec<(usize, Buffer)>>,
+    marker: PhantomData<W>,
+}
+
+struct RenderComputeWorkerPass {
+    pipeline_id: CachedComputePipelineId,
+    layout: BindGroupLayout,
+    bind_group: BindGroup,
+    workgroups: [u32; 3],
+    buffers: Vec<usize>,
+}
+
+impl<W: ComputeWorker> RenderComputeWorker<W> {
+    fn new(
+        world: &World,
+        builder: ComputeWorkerBuilder<W>,
+        request_receiver: Receiver<ComputeWorkerRequest>,
+        result_sender: Sender<Vec<(usize, Vec<u8>)>>,
+    ) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+        let pipeline_cache = world.resource::<PipelineCache>();
+
+        let buffers: Vec<_> = builder
+            .buffers
+            .iter()
+            .map(|buffer| {
+                (
+                    buffer.kind,
+                    create_buffer(render_device, buffer.kind, &buffer.data),
+                )
+            })
+            .collect();
+
+        let passes = builder
+            .passes
+            .into_iter()
+            .map(|pass| {
+                let entries: Vec<_> = pass
+                    .buffers
+                    .iter()
+                    .enumerate()
+                    .map(|(binding, &index)| {
+                        let entry = match buffers[index].0 {
+                            ComputeBufferKind::Uniform => uniform_buffer_sized(false, None),
+                            _ => storage_buffer_sized(false, None),
+                        };
+                        entry.build(binding as u32, ShaderStages::COMPUTE)
+                    })
+                    .collect();
+                let layout =
+                    render_device.create_bind_group_layout("compute_worker_layout", &entries);
+                let pipeline_id =
+                    pipeline_cache.queue_compute_pipeline(ComputePipelineDescriptor {
+                        label: Some(format!("compute_worker_{}", pass.entry_point).into()),
+                        layout: vec![layout.clone()],
+                        push_constant_ranges: vec![],
+                        shader: pass.shader,
+                        shader_defs: vec![],
+                        entry_point: pass.entry_point.into(),
+                    });
+                let bind_group = create_bind_group(render_device, &layout, &buffers, &pass.buffers);
+                RenderComputeWorkerPass {
+                    pipeline_id,
+                    layout,
+                    bind_group,
+                    workgroups: pass.workgroups,
+                    buffers: pass.buffers,
+                }
+            })
+            .collect();
+
+        Self {
+            buffers,
+            passes,
+            request_receiver,
+            result_sender,
+            pending_dispatches: 0,
+            readbacks: None,
+            marker: PhantomData,
+        }
+    }
+}
+
+fn create_buffer(render_device: &RenderDevice, kind: ComputeBufferKind, data: &[u8]) -> Buffer {
+    let usage = match kind {
+        ComputeBufferKind::Uniform => BufferUsages::UNIFORM,
+        _ => BufferUsages::STORAGE | BufferUsages::COPY_SRC,
+    };
+    render_device.create_buffer_with_data(&BufferInitDescriptor {
+        label: Some("compute_worker_buffer"),
+        contents: data,
+        usage: usage | BufferUsages::COPY_DST,
+    })
+}
+
+fn create_bind_group(
+    render_device: &RenderDevice,
+    layout: &BindGroupLayout,
+    buffers: &[(ComputeBufferKind, Buffer)],
+    pass_buffers: &[usize],
+) -> BindGroup {
+    let entries: Vec<_> = pass_buffers
+        .iter()
+        .enumerate()
+        .map(|(binding, &index)| BindGroupEntry {
+            binding: binding as u32,
+            resource: buffers[index].1.as_entire_binding(),
+        })
+        .collect();
+    render_device.create_bind_group("compute_worker_bind_group", layout, &entries)
+}
+
+/// Writes the buffers of a worker, and readies the dispatch of the frame once the pipelines are.
+fn prepare_compute_worker<W: ComputeWorker>(
+    mut worker: ResMut<RenderComputeWorker<W>>,
+    pipeline_cache: Res<PipelineCache>,
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+) {
+    let worker = &mut *worker;
+
+    let mut resized = false;
+    while let Ok(request) = worker.request_receiver.try_recv() {
+        for (index, data) in request.writes {
+            let (kind, buffer) = &mut worker.buffers[index];
+            if buffer.size() == data.len() as u64 {
+                render_queue.write_buffer(buffer, 0, &data);
+            } else {
+                *buffer = create_buffer(&render_device, *kind, &data);
+                resized = true;
+            }
+        }
+        if request.dispatch {
+            worker.pending_dispatches += 1;
+        }
+    }
+
+    if resized {
+        for pass in &mut worker.passes {
+            pass.bind_group =
+                create_bind_group(&render_device, &pass.layout, &worker.buffers, &pass.buffers);
+        }
+    }
+
+    worker.readbacks = None;
+    let pipelines_ready = worker.passes.iter().all(|pass| {
+        pipeline_cache
+            .get_compute_pipeline(pass.pipeline_id)
+            .is_some()
+    });
+    if worker.pending_dispatches == 0 || !pipelines_ready {
+        return;
+    }
+
+    worker.pending_dispatches -= 1;
+    worker.readbacks = Some(
+        worker
+            .buffers
+            .iter()
+            .enumerate()
+            .filter(|(_, (kind, _))| *kind == ComputeBufferKind::Readback)
+            .map(|(index, (_, buffer))| {
+                let staging_buffer = render_device.create_buffer(&BufferDescriptor {
+                    label: Some("compute_worker_staging_buffer"),
+                    size: buffer.size(),
+                    usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+                    mapped_at_creation: false,
+                });
+                (index, staging_buffer)
+            })
+            .collect(),
+    );
+}
+
+/// Maps the staging buffers of the dispatch of the frame, once submitted, and sends their values
+/// to the main world.
+fn read_back_compute_worker<W: ComputeWorker>(mut worker: ResMut<RenderComputeWorker<W>>) {
+    let Some(readbacks) = worker.readbacks.take() else {
+        return;
+    };
+
+    let result_sender = worker.result_sender.clone();
+    let read_back = async move {
+        let mut results = Vec::with_capacity(readbacks.len());
+        for (index, staging_buffer) in readbacks {
+            let (sender, receiver) = async_channel::bounded(1);
+            let buffer_slice = staging_buffer.slice(..);
+            // The map is polled each frame, when the command queue is submitted.
+            buffer_slice.map_async(MapMode::Read, move |result| {
+                let _ = sender.try_send(result);
+            });
+            match receiver.recv().await {
+                Ok(Ok(())) => results.push((index, buffer_slice.get_mapped_range().to_vec())),
+                Ok(Err(err)) => error!("Could not read back a compute worker buffer: {err}"),
+                Err(_) => {}
+            }
+        }
+        // The main world only drops the receiver when the app exits.
+        let _ = result_sender.try_send(results);
+    };
+    AsyncComputeTaskPool::get().spawn(read_back).detach();
+}
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+struct ComputeWorkerLabel(&'static str);
+
+/// Dispatches the passes of a worker and copies its readback buffers to their staging buffers,
+/// on the frames where it is dispatched.
+struct ComputeWorkerNode<W: ComputeWorker>(PhantomData<W>);
+
+impl<W: ComputeWorker> Node for ComputeWorkerNode<W> {
+    fn run(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        let worker = world.resource::<RenderComputeWorker<W>>();
+        let Some(readbacks) = &worker.readbacks else {
+            return Ok(());
+        };
+        let pipeline_cache = world.resource::<PipelineCache>();
+
+        let command_encoder = render_context.command_encoder();
+        {
+            let mut pass = command_encoder.begin_compute_pass(&ComputePassDescriptor {
+                label: Some("compute_worker"),
+                timestamp_writes: None,
+            });
+            for worker_pass in &worker.passes {
+                let Some(pipeline) = pipeline_cache.get_compute_pipeline(worker_pass.pipeline_id)
+                else {
+                    continue;
+                };
+                let [x, y, z] = worker_pass.workgroups;
+                pass.set_pipeline(pipeline);
+                pass.set_bind_group(0, &worker_pass.bind_group, &[]);
+                pass.dispatch_workgroups(x, y, z);
+            }
+        }
+
+        for (index, staging_buffer) in readbacks {
+            let buffer = &worker.buffers[*index].1;
+            command_encoder.copy_buffer_to_buffer(buffer, 0, staging_buffer, 0, buffer.size());
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,438 @@
+//! Diagnostics measured by the renderer, recorded in the
+//! [`DiagnosticsStore`](bevy_diagnostic::DiagnosticsStore) of the main world.
+
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        mpsc::{channel, Receiver},
+        Arc, Mutex, PoisonError,
+    },
+};
+
+use bevy_app::{App, Plugin, Update};
+use bevy_diagnostic::{
+    Diagnostic, DiagnosticMeasurement, DiagnosticPath, Diagnostics, DiagnosticsStore,
+    RegisterDiagnostic,
+};
+use bevy_ecs::prelude::*;
+#[cfg(feature = "trace")]
+use bevy_utils::tracing::info_span;
+use bevy_utils::{Duration, Instant};
+use wgpu::{
+    BufferAsyncError, BufferDescriptor, BufferUsages, CommandEncoder, Features, QuerySet,
+    QuerySetDescriptor, QueryType, QUERY_SIZE,
+};
+
+use crate::{
+    render_resource::Buffer,
+    renderer::{RenderDevice, RenderQueue},
+    Render, RenderApp, RenderSet,
+};
+
+/// The maximum number of render graph nodes timed on the GPU per frame.
+const MAX_TIMED_NODES: u32 = 256;
+
+/// The maximum number of frames whose GPU timings are read back at the same time.
+const MAX_TIMED_FRAMES: usize = 3;
+
+/// Adds the diagnostics of the renderer to an [`App`].
+///
+/// With the `gpu_timestamps` feature, when the GPU supports [`Features::TIMESTAMP_QUERY`], the
+/// time the GPU spends running each node of the render graph is measured with timestamp queries,
+/// and recorded in a diagnostic named `render/gpu/<graph>/<node>` in milliseconds, or
+/// `render/gpu/<node>` for the nodes of the main graph. The nodes run for several views, like the
+/// nodes of the `Core3d` graph, are summed over the views. The total is recorded in
+/// [`GPU_TIME`](Self::GPU_TIME). The work of the command buffers encoded in parallel by a node is
+/// included in its time.
+///
+/// The feature requests [`Features::TIMESTAMP_QUERY`] from the adapter whatever the
+/// [`WgpuSettings`](crate::settings::WgpuSettings) priority, unless it is disabled there.
+///
+/// With the `trace` feature, each measured node is also recorded as a `gpu_node` span, with its
+/// GPU time in the `gpu_time_ms` field, when its timing is read back a few frames later.
+///
+/// # See also
+///
+/// [`LogDiagnosticsPlugin`](bevy_diagnostic::LogDiagnosticsPlugin) to output diagnostics to the
+/// console.
+#[derive(Default)]
+pub struct RenderDiagnosticsPlugin;
+
+impl Plugin for RenderDiagnosticsPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_diagnostic(Diagnostic::new(Self::DRAW_CALLS))
+            .init_resource::<RenderStatistics>()
+            .register_diagnostic(Diagnostic::new(Self::GPU_TIME).with_suffix("ms"))
+            .add_systems(
+                Update,
+                (Self::diagnostic_system, Self::gpu_diagnostic_system),
+            );
+
+        let statistics = app.world.resource::<RenderStatistics>().clone();
+        if let Ok(render_app) = app.get_sub_app_mut(RenderApp) {
+            render_app.insert_resource(statistics).add_systems(
+                Render,
+                (record_render_statistics, read_gpu_timings).in_set(RenderSet::Cleanup),
+            );
+        }
+    }
+
+    fn finish(&self, app: &mut App) {
+        let Ok(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        let (Some(render_device), Some(render_queue)) = (
+            render_app.world.get_resource::<RenderDevice>(),
+            render_app.world.get_resource::<RenderQueue>(),
+        ) else {
+            return;
+        };
+        if let Some(gpu_timer) = GpuTimer::new(render_device, render_queue) {
+            render_app.insert_resource(gpu_timer);
+        }
+    }
+}
+
+impl RenderDiagnosticsPlugin {
+    /// The number of draw calls issued to render the last frame.
+    pub const DRAW_CALLS: DiagnosticPath = DiagnosticPath::const_new("render/draw_calls");
+
+    /// The time the GPU spent running the render graph for the last frame whose timings were
+    /// read back, in milliseconds.
+    pub const GPU_TIME: DiagnosticPath = DiagnosticPath::const_new("render/gpu_time");
+
+    pub fn diagnostic_system(mut diagnostics: Diagnostics, statistics: Res<RenderStatistics>) {
+        diagnostics.add_measurement(&Self::DRAW_CALLS, || statistics.draw_calls() as f64);
+    }
+
+    /// Records the GPU timings of the render graph nodes, registering the diagnostics of the
+    /// nodes measured for the first time.
+    pub fn gpu_diagnostic_system(
+        mut store: ResMut<DiagnosticsStore>,
+        statistics: Res<RenderStatistics>,
+        mut last_frame: Local<u64>,
+    ) {
+        let timings = statistics
+            .gpu_timings
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if timings.frame == *last_frame {
+            return;
+        }
+        *last_frame = timings.frame;
+
+        let time = Instant::now();
+        let mut add_measurement = |path: DiagnosticPath, duration: Duration| {
+            let diagnostic = match store.get_mut(&path) {
+                Some(diagnostic) => diagnostic,
+                None => {
+                    store.add(Diagnostic::new(path.clone()).with_suffix("ms"));
+                    store.get_mut(&path).unwrap()
+                }
+            };
+            if diagnostic.is_enabled {
+                diagnostic.add_measurement(DiagnosticMeasurement {
+                    time,
+                    value: duration.as_secs_f64() * 1000.0,
+                });
+            }
+        };
+        for (node, duration) in &timings.nodes {
+            add_measurement(DiagnosticPath::new(format!("render/gpu/{node}")), *duration);
+        }
+        add_measurement(Self::GPU_TIME, timings.nodes.iter().map(|(_, d)| *d).sum());
+    }
+}
+
+/// The statistics of the last rendered frame, shared by the main world and the render world.
+#[derive(Resource, Clone, Default)]
+pub struct RenderStatistics {
+    draw_calls: Arc<AtomicU32>,
+    gpu_timings: Arc<Mutex<GpuTimings>>,
+}
+
+#[derive(Default)]
+struct GpuTimings {
+    /// Incremented when the timings of a new frame are read back.
+    frame: u64,
+    nodes: Vec<(String, Duration)>,
+}
+
+impl RenderStatistics {
+    /// The number of draw calls issued to render the last frame.
+    pub fn draw_calls(&self) -> u32 {
+        self.draw_calls.load(Ordering::Relaxed)
+    }
+
+    /// The time the GPU spent running each node of the render graph, named `<graph>/<node>` or
+    /// `<node>` for the nodes of the main graph, for the last frame whose timings were read back.
+    ///
+    /// Empty without the `gpu_timestamps` feature, or if the GPU doesn't support timestamp queries.
+    pub fn gpu_timings(&self) -> Vec<(String, Duration)> {
+        self.gpu_timings
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .nodes
+            .clone()
+    }
+}
+
+/// Measures the time the GPU spends running the nodes of the render graph, with timestamp
+/// queries written before and after each node.
+#[derive(Resource)]
+pub(crate) struct GpuTimer {
+    query_set: QuerySet,
+    resolve_buffer: Buffer,
+    /// The number of nanoseconds per tick of the timestamps.
+    timestamp_period: f32,
+    /// The buffers which aren't used to read back the timestamps of a frame.
+    free_buffers: Mutex<Vec<Buffer>>,
+    /// The frames whose timestamps are being read back, from the oldest to the newest.
+    pending_frames: Mutex<VecDeque<PendingFrame>>,
+}
+
+/// The timestamps of a frame being read back.
+struct PendingFrame {
+    buffer: Buffer,
+    nodes: Vec<String>,
+    mapped: Receiver<Result<(), BufferAsyncError>>,
+}
+
+impl GpuTimer {
+    fn new(render_device: &RenderDevice, render_queue: &RenderQueue) -> Option<Self> {
+        if !cfg!(feature = "gpu_timestamps")
+            || !render_device.features().contains(Features::TIMESTAMP_QUERY)
+        {
+            return None;
+        }
+        let size = (MAX_TIMED_NODES * 2 * QUERY_SIZE) as u64;
+        let create_buffer = |label, usage| {
+            render_device.create_buffer(&BufferDescriptor {
+                label: Some(label),
+                size,
+                usage,
+                mapped_at_creation: false,
+            })
+        };
+        Some(Self {
+            query_set: render_device
+                .wgpu_device()
+                .create_query_set(&QuerySetDescriptor {
+                    label: Some("gpu_timer_query_set"),
+                    ty: QueryType::Timestamp,
+                    count: MAX_TIMED_NODES * 2,
+                }),
+            resolve_buffer: create_buffer(
+                "gpu_timer_resolve_buffer",
+                BufferUsages::QUERY_RESOLVE | BufferUsages::COPY_SRC,
+            ),
+            timestamp_period: render_queue.get_timestamp_period(),
+            free_buffers: Mutex::new(
+                (0..MAX_TIMED_FRAMES)
+                    .map(|_| {
+                        create_buffer(
+                            "gpu_timer_readback_buffer",
+                            BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+                        )
+                    })
+                    .collect(),
+            ),
+            pending_frames: Mutex::default(),
+        })
+    }
+
+    /// Starts measuring a frame, unless the timestamps of too many frames are being read back.
+    pub(crate) fn begin_frame(&self) -> Option<GpuTimerFrame<'_>> {
+        let buffer = self
+            .free_buffers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .pop()?;
+        Some(GpuTimerFrame {
+            timer: self,
+            buffer,
+            nodes: Vec::new(),
+        })
+    }
+}
+
+/// The timestamps written for the nodes of the frame being rendered.
+pub(crate) struct GpuTimerFrame<'w> {
+    timer: &'w GpuTimer,
+    buffer: Buffer,
+    nodes: Vec<String>,
+}
+
+impl GpuTimerFrame<'_> {
+    /// Writes the timestamp before running the `node`, returning the index of its timing.
+    pub(crate) fn begin_node(
+        &mut self,
+        encoder: &mut CommandEncoder,
+        node: impl FnOnce() -> String,
+    ) -> Option<u32> {
+        let index = self.nodes.len() as u32;
+        if index == MAX_TIMED_NODES {
+            return None;
+        }
+        encoder.write_timestamp(&self.timer.query_set, index * 2);
+        self.nodes.push(node());
+        Some(index)
+    }
+
+    /// Writes the timestamp after running the node of the timing `index`.
+    pub(crate) fn end_node(&mut self, encoder: &mut CommandEncoder, index: u32) {
+        encoder.write_timestamp(&self.timer.query_set, index * 2 + 1);
+    }
+
+    /// Copies the timestamps of the frame to be read back, once all of its nodes ran.
+    pub(crate) fn resolve(&self, encoder: &mut CommandEncoder) {
+        if self.nodes.is_empty() {
+            return;
+        }
+        let count = self.nodes.len() as u32 * 2;
+        encoder.resolve_query_set(
+            &self.timer.query_set,
+            0..count,
+            &self.timer.resolve_buffer,
+            0,
+        );
+        encoder.copy_buffer_to_buffer(
+            &self.timer.resolve_buffer,
+            0,
+            &self.buffer,
+            0,
+            (count * QUERY_SIZE) as u64,
+        );
+    }
+
+    /// Starts reading back the timestamps, once the commands of the frame were submitted.
+    pub(crate) fn read_back(self) {
+        let (sender, mapped) = channel();
+        if self.nodes.is_empty() {
+            let _ = sender.send(Ok(()));
+        } else {
+            let size = (self.nodes.len() as u32 * 2 * QUERY_SIZE) as u64;
+            self.buffer
+                .slice(..size)
+                .map_async(wgpu::MapMode::Read, move |result| {
+                    let _ = sender.send(result);
+                });
+        }
+        self.timer
+            .pending_frames
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push_back(PendingFrame {
+                buffer: self.buffer,
+                nodes: self.nodes,
+                mapped,
+            });
+    }
+}
+
+fn record_render_statistics(render_device: Res<RenderDevice>, statistics: Res<RenderStatistics>) {
+    statistics
+        .draw_calls
+        .store(render_device.take_draw_calls(), Ordering::Relaxed);
+}
+
+/// Reads the GPU timings of the frames whose timestamps were read back.
+fn read_gpu_timings(gpu_timer: Option<Res<GpuTimer>>, statistics: Res<RenderStatistics>) {
+    let Some(gpu_timer) = gpu_timer else {
+        return;
+    };
+    let mut pending_frames = gpu_timer
+        .pending_frames
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    while let Some(frame) = pending_frames.front() {
+        let Ok(mapped) = frame.mapped.try_recv() else {
+            // The frames are read back in order.
+            break;
+        };
+        let frame = pending_frames.pop_front().unwrap();
+        if mapped.is_ok() && !frame.nodes.is_empty() {
+            let nodes = timings(&frame, gpu_timer.timestamp_period);
+            frame.buffer.unmap();
+
+            #[cfg(feature = "trace")]
+            for (node, duration) in &nodes {
+                let _span = info_span!(
+                    "gpu_node",
+                    name = node.as_str(),
+                    gpu_time_ms = duration.as_secs_f64() * 1000.0
+                )
+                .entered();
+            }
+
+            let mut timings = statistics
+                .gpu_timings
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            timings.frame += 1;
+            timings.nodes = nodes;
+        }
+        gpu_timer
+            .free_buffers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(frame.buffer);
+    }
+}
+
+/// The time between the timestamps of each node of the `frame`, summed for the nodes run several
+/// times, in the order the nodes first ran.
+fn timings(frame: &PendingFrame, timestamp_period: f32) -> Vec<(String, Duration)> {
+    let size = (frame.nodes.len() as u32 * 2 * QUERY_SIZE) as u64;
+    let data = frame.buffer.slice(..size).get_mapped_range();
+    let timestamps: Vec<u64> = data
+        .chunks_exact(QUERY_SIZE as usize)
+        .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()))
+        .collect();
+    drop(data);
+    sum_node_timings(&frame.nodes, &timestamps, timestamp_period)
+}
+
+fn sum_node_timings(
+    nodes: &[String],
+    timestamps: &[u64],
+    timestamp_period: f32,
+) -> Vec<(String, Duration)> {
+    let mut timings: Vec<(String, Duration)> = Vec::new();
+    for (node, timestamps) in nodes.iter().zip(timestamps.chunks_exact(2)) {
+        let ticks = timestamps[1].saturating_sub(timestamps[0]);
+        let duration = Duration::from_nanos((ticks as f64 * timestamp_period as f64) as u64);
+        match timings.iter_mut().find(|(name, _)| name == node) {
+            Some((_, total)) => *total += duration,
+            None => timings.push((node.clone(), duration)),
+        }
+    }
+    timings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn node_timings_are_summed_over_views() {
+        let nodes = [
+            "Core3d/MainOpaquePass",
+            "Core3d/Bloom",
+            "Core3d/MainOpaquePass",
+        ]
+        .map(String::from);
+        let timestamps = [100, 300, 300, 350, 400, 700];
+        assert_eq!(
+            sum_node_timings(&nodes, &timestamps, 2.0),
+            vec![
+                (
+                    "Core3d/MainOpaquePass".to_string(),
+                    Duration::from_nanos(1000)
+                ),
+                ("Core3d/Bloom".to_string(), Duration::from_nanos(100)),
+            ]
+        );
+    }
+}
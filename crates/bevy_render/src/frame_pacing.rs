@@ -0,0 +1,197 @@
+use std::sync::{Arc, Mutex, PoisonError};
+
+use bevy_app::{App, Plugin};
+use bevy_ecs::prelude::*;
+use bevy_reflect::{std_traits::ReflectDefault, Reflect};
+use bevy_time::{Real, Time};
+use bevy_utils::{Duration, Instant};
+
+use crate::{
+    extract_resource::{ExtractResource, ExtractResourcePlugin},
+    Extract, ExtractSchedule, Render, RenderApp, RenderSet,
+};
+
+/// Adds the [`FrameLimiter`] and [`FramePacingStats`] resources, to cap the frame rate and
+/// measure the latency of the frames.
+pub struct FramePacingPlugin;
+
+impl Plugin for FramePacingPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<FrameLimiter>()
+            .register_type::<FrameLimitStrategy>()
+            .init_resource::<FrameLimiter>()
+            .init_resource::<FramePacingStats>()
+            .add_plugins(ExtractResourcePlugin::<FrameLimiter>::default());
+
+        let stats = app.world.resource::<FramePacingStats>().clone();
+        if let Ok(render_app) = app.get_sub_app_mut(RenderApp) {
+            render_app
+                .insert_resource(stats)
+                .init_resource::<FramePacingState>()
+                .add_systems(ExtractSchedule, extract_frame_start)
+                .add_systems(
+                    Render,
+                    pace_frames
+                        .after(RenderSet::Render)
+                        .before(RenderSet::Cleanup),
+                );
+        }
+    }
+}
+
+/// Caps the frame rate of the app, by waiting once a frame is presented until the next frame
+/// is due.
+///
+/// Waiting after presenting a frame, instead of before presenting it, delays the processing of
+/// the input of the next frame, which reduces the input latency compared to
+/// [`PresentMode::Fifo`](bevy_window::PresentMode::Fifo) when the frame rate is capped below
+/// the refresh rate of the monitor.
+///
+/// The frame rate isn't capped on the web, where the browser paces the frames.
+#[derive(Resource, ExtractResource, Debug, Clone, Copy, Default, PartialEq, Reflect)]
+#[reflect(Resource, Default)]
+pub struct FrameLimiter {
+    /// The number of frames per second to cap the frame rate at, or `None` not to cap it.
+    pub target_fps: Option<f64>,
+    /// How to wait for the next frame.
+    pub strategy: FrameLimitStrategy,
+}
+
+impl FrameLimiter {
+    /// Caps the frame rate at `target_fps` frames per second.
+    pub fn with_target_fps(target_fps: f64) -> Self {
+        Self {
+            target_fps: Some(target_fps),
+            ..Default::default()
+        }
+    }
+}
+
+/// How the [`FrameLimiter`] waits for the next frame.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Default)]
+pub enum FrameLimitStrategy {
+    /// Sleeps until the next frame. Uses the least CPU, but the precision of the sleep depends on
+    /// the scheduler of the OS, typically around a millisecond.
+    Sleep,
+    /// Spins until the next frame. Is the most precise, but keeps a CPU core busy.
+    Spin,
+    /// Sleeps until `spin_duration` before the next frame, then spins until the next frame.
+    SleepAndSpin {
+        /// How long to spin before the next frame.
+        spin_duration: Duration,
+    },
+}
+
+impl Default for FrameLimitStrategy {
+    fn default() -> Self {
+        Self::SleepAndSpin {
+            spin_duration: Duration::from_millis(1),
+        }
+    }
+}
+
+impl FrameLimitStrategy {
+    /// Waits until the `deadline`.
+    pub fn wait_until(self, deadline: Instant) {
+        let spin_duration = match self {
+            FrameLimitStrategy::Sleep => Duration::ZERO,
+            FrameLimitStrategy::Spin => Duration::MAX,
+            FrameLimitStrategy::SleepAndSpin { spin_duration } => spin_duration,
+        };
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining > spin_duration {
+            std::thread::sleep(remaining - spin_duration);
+        }
+        if spin_duration > Duration::ZERO {
+            while Instant::now() < deadline {
+                std::hint::spin_loop();
+            }
+        }
+    }
+}
+
+/// The measured timings of the last presented frame, updated by the renderer.
+///
+/// The resource is shared by the main world and the render world, so it can be read from either.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct FramePacingStats(Arc<Mutex<FramePacingMeasurements>>);
+
+#[derive(Debug, Clone, Copy, Default)]
+struct FramePacingMeasurements {
+    present_latency: Option<Duration>,
+    limiter_wait: Duration,
+}
+
+impl FramePacingStats {
+    /// The time between the start of the update of the last presented frame, when its input was
+    /// processed, and the end of its presentation.
+    ///
+    /// This doesn't include the time the OS and the GPU take to display the frame.
+    pub fn present_latency(&self) -> Option<Duration> {
+        self.measurements().present_latency
+    }
+
+    /// The time the [`FrameLimiter`] waited after presenting the last frame.
+    pub fn limiter_wait(&self) -> Duration {
+        self.measurements().limiter_wait
+    }
+
+    fn measurements(&self) -> FramePacingMeasurements {
+        *self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// The state of the frame pacing in the render world.
+#[derive(Resource, Default)]
+struct FramePacingState {
+    /// The start of the update of the frame being rendered.
+    frame_start: Option<Instant>,
+    /// When the next frame is due, if the frame rate is capped.
+    next_frame: Option<Instant>,
+}
+
+fn extract_frame_start(mut state: ResMut<FramePacingState>, time: Extract<Res<Time<Real>>>) {
+    state.frame_start = time.last_update();
+}
+
+fn pace_frames(
+    mut state: ResMut<FramePacingState>,
+    limiter: Res<FrameLimiter>,
+    stats: Res<FramePacingStats>,
+) {
+    let presented = Instant::now();
+    let present_latency = state.frame_start.map(|start| presented - start);
+
+    let mut limiter_wait = Duration::ZERO;
+    match limiter.target_fps.filter(|fps| *fps > 0.0) {
+        Some(target_fps) if cfg!(not(target_arch = "wasm32")) => {
+            let frame_duration = Duration::from_secs_f64(1.0 / target_fps);
+            let deadline = state.next_frame.unwrap_or(presented);
+            if deadline > presented {
+                limiter.strategy.wait_until(deadline);
+                limiter_wait = Instant::now() - presented;
+            }
+            // Don't try to catch up with the frames which were late.
+            state.next_frame = Some(deadline.max(presented) + frame_duration);
+        }
+        _ => state.next_frame = None,
+    }
+
+    *stats.0.lock().unwrap_or_else(PoisonError::into_inner) = FramePacingMeasurements {
+        present_latency,
+        limiter_wait,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wait_until_deadline() {
+        let deadline = Instant::now() + Duration::from_millis(5);
+        FrameLimitStrategy::default().wait_until(deadline);
+        assert!(Instant::now() >= deadline);
+    }
+}
@@ -0,0 +1,123 @@
+//! Rendering without any window, to offscreen targets read back synchronously.
+//!
+//! To render headlessly, disable the windowing backend and the primary window: e.g. disable the
+//! `WinitPlugin` and [`PipelinedRenderingPlugin`](crate::pipelined_rendering::PipelinedRenderingPlugin),
+//! and set `WindowPlugin::primary_window` to `None`. Then render the cameras to images created
+//! with [`Image::new_render_target`], drive the app with [`HeadlessRenderExt::render_frames`],
+//! and read the images back with [`HeadlessRenderExt::read_image`].
+//!
+//! This is useful for screenshot tests run in CI, or to generate thumbnails on a server.
+
+use bevy_app::{App, PluginsState};
+use bevy_asset::Handle;
+use bevy_utils::tracing::warn;
+use wgpu::{
+    BufferDescriptor, BufferUsages, CommandEncoderDescriptor, Extent3d, ImageCopyBuffer,
+    TextureDimension,
+};
+
+use crate::{
+    render_asset::{RenderAssetUsages, RenderAssets},
+    renderer::{RenderDevice, RenderQueue},
+    texture::{Image, TextureFormatPixelInfo},
+    view::screenshot::{align_byte_size, get_aligned_size, layout_data},
+    RenderApp,
+};
+
+/// Methods to drive a headless [`App`] frame by frame and read back the images it rendered.
+pub trait HeadlessRenderExt {
+    /// Runs `frames` updates of the app, rendering a frame for each of them.
+    ///
+    /// Finishes the setup of the plugins first if the app wasn't run yet, waiting for the
+    /// renderer to be initialized.
+    fn render_frames(&mut self, frames: u32);
+
+    /// Reads back the `image` from the GPU, as rendered by the last frame.
+    ///
+    /// Returns `None` if the image isn't on the GPU yet, or if the render world can't be
+    /// accessed, like with the
+    /// [`PipelinedRenderingPlugin`](crate::pipelined_rendering::PipelinedRenderingPlugin).
+    /// The image must have the [`TextureUsages::COPY_SRC`](wgpu::TextureUsages::COPY_SRC) usage.
+    /// This blocks until the GPU has finished all the submitted work.
+    fn read_image(&self, image: &Handle<Image>) -> Option<Image>;
+}
+
+impl HeadlessRenderExt for App {
+    fn render_frames(&mut self, frames: u32) {
+        if self.plugins_state() != PluginsState::Cleaned {
+            while self.plugins_state() == PluginsState::Adding {
+                #[cfg(not(target_arch = "wasm32"))]
+                bevy_tasks::tick_global_task_pools_on_main_thread();
+            }
+            self.finish();
+            self.cleanup();
+        }
+
+        for _ in 0..frames {
+            self.update();
+        }
+    }
+
+    fn read_image(&self, image: &Handle<Image>) -> Option<Image> {
+        let world = &self.get_sub_app(RenderApp).ok()?.world;
+        let gpu_image = world.resource::<RenderAssets<Image>>().get(image)?;
+        let render_device = world.resource::<RenderDevice>();
+        let render_queue = world.resource::<RenderQueue>();
+
+        let size = Extent3d {
+            depth_or_array_layers: 1,
+            ..gpu_image.texture.size()
+        };
+        let format = gpu_image.texture_format;
+        let pixel_size = format.pixel_size();
+        let buffer = render_device.create_buffer(&BufferDescriptor {
+            label: Some("headless_readback_buffer"),
+            size: get_aligned_size(size.width, size.height, pixel_size as u32) as u64,
+            usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let mut encoder = render_device.create_command_encoder(&CommandEncoderDescriptor {
+            label: Some("headless_readback_encoder"),
+        });
+        encoder.copy_texture_to_buffer(
+            gpu_image.texture.as_image_copy(),
+            ImageCopyBuffer {
+                buffer: &buffer,
+                layout: layout_data(size.width, size.height, format),
+            },
+            size,
+        );
+        render_queue.submit([encoder.finish()]);
+
+        let buffer_slice = buffer.slice(..);
+        let (tx, rx) = std::sync::mpsc::channel();
+        buffer_slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = tx.send(result);
+        });
+        render_device.poll(wgpu::Maintain::Wait);
+        if let Err(err) = rx.recv().ok()? {
+            warn!("Could not read back the image: {err}");
+            return None;
+        }
+
+        let padded = buffer_slice.get_mapped_range();
+        // Rows are padded to be aligned to `COPY_BYTES_PER_ROW_ALIGNMENT` when there are several.
+        let row_bytes = size.width as usize * pixel_size;
+        let padded_row_bytes = align_byte_size(row_bytes as u32) as usize;
+        let mut data = Vec::with_capacity(row_bytes * size.height as usize);
+        for row in 0..size.height as usize {
+            let start = row * padded_row_bytes;
+            data.extend_from_slice(&padded[start..start + row_bytes]);
+        }
+        drop(padded);
+        buffer.unmap();
+
+        Some(Image::new(
+            size,
+            TextureDimension::D2,
+            data,
+            format,
+            RenderAssetUsages::default(),
+        ))
+    }
+}
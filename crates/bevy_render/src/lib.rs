@@ -10,14 +10,19 @@ pub mod alpha;
 pub mod batching;
 pub mod camera;
 pub mod color;
+pub mod compute_worker;
 pub mod deterministic;
+pub mod diagnostic;
 pub mod extract_component;
 pub mod extract_instances;
 mod extract_param;
 pub mod extract_resource;
+pub mod frame_pacing;
 pub mod globals;
 pub mod gpu_component_array_buffer;
+pub mod headless;
 pub mod mesh;
+pub mod occlusion_culling;
 #[cfg(not(target_arch = "wasm32"))]
 pub mod pipelined_rendering;
 pub mod primitives;
@@ -27,6 +32,7 @@ pub mod render_phase;
 pub mod render_resource;
 pub mod renderer;
 pub mod settings;
+pub mod spatial;
 mod spatial_bundle;
 pub mod texture;
 pub mod view;
@@ -41,6 +47,7 @@ pub mod prelude {
         color::Color,
         mesh::{morph::MorphWeights, primitives::Meshable, Mesh},
         render_resource::Shader,
+        spatial::SpatialQuery,
         spatial_bundle::SpatialBundle,
         texture::{Image, ImagePlugin},
         view::{InheritedVisibility, Msaa, ViewVisibility, Visibility, VisibilityBundle},
@@ -327,6 +334,9 @@ impl Plugin for RenderPlugin {
             MeshPlugin,
             GlobalsPlugin,
             MorphPlugin,
+            occlusion_culling::OcclusionCullingPlugin,
+            frame_pacing::FramePacingPlugin,
+            spatial::SpatialQueryPlugin,
         ));
 
         app.register_type::<alpha::AlphaMode>()
@@ -382,6 +392,10 @@ struct ScratchMainWorld(World);
 /// Executes the [`ExtractSchedule`] step of the renderer.
 /// This updates the render world with the extracted ECS data of the current frame.
 fn extract(main_world: &mut World, render_app: &mut App) {
+    #[cfg(feature = "trace")]
+    let _span = bevy_utils::tracing::info_span!("extract", entities = main_world.entities().len())
+        .entered();
+
     // temporarily add the app world to the render world as a resource
     let scratch_world = main_world.remove_resource::<ScratchMainWorld>().unwrap();
     let inserted_world = std::mem::replace(main_world, scratch_world.0);
@@ -10,7 +10,7 @@ use crate::{
     render_resource::{Buffer, TextureView, VertexBufferLayout},
     renderer::RenderDevice,
 };
-use bevy_asset::{Asset, Handle};
+use bevy_asset::{Asset, Handle, MeasuredAsset};
 use bevy_core::cast_slice;
 use bevy_derive::EnumVariantMeta;
 use bevy_ecs::system::{lifetimeless::SRes, SystemParamItem};
@@ -922,6 +922,22 @@ impl Mesh {
     }
 }
 
+impl MeasuredAsset for Mesh {
+    fn memory_size(&self) -> usize {
+        let attributes_size: usize = self
+            .attributes
+            .values()
+            .map(|attribute| attribute.values.get_bytes().len())
+            .sum();
+        let indices_size = match &self.indices {
+            Some(Indices::U16(indices)) => indices.len() * std::mem::size_of::<u16>(),
+            Some(Indices::U32(indices)) => indices.len() * std::mem::size_of::<u32>(),
+            None => 0,
+        };
+        attributes_size + indices_size
+    }
+}
+
 impl core::ops::Mul<Mesh> for Transform {
     type Output = Mesh;
 
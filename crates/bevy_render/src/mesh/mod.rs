@@ -6,10 +6,17 @@ pub mod primitives;
 pub use mesh::*;
 pub use primitives::*;
 
-use crate::{prelude::Image, render_asset::RenderAssetPlugin};
-use bevy_app::{App, Plugin};
-use bevy_asset::{AssetApp, Handle};
-use bevy_ecs::entity::Entity;
+use crate::{
+    prelude::Image,
+    render_asset::RenderAssetPlugin,
+    view::{touch_visible_assets, VisibilitySystems},
+};
+use bevy_app::{App, Plugin, PostUpdate};
+use bevy_asset::{AssetApp, AssetBudget, Handle};
+use bevy_ecs::{
+    entity::Entity,
+    schedule::{common_conditions::resource_exists, IntoSystemConfigs},
+};
 
 /// Adds the [`Mesh`] as an asset and makes sure that they are extracted and prepared for the GPU.
 pub struct MeshPlugin;
@@ -26,6 +33,12 @@ impl Plugin for MeshPlugin {
             .register_type::<skinning::SkinnedMesh>()
             .register_type::<Vec<Entity>>()
             // 'Mesh' must be prepared after 'Image' as meshes rely on the morph target image being ready
-            .add_plugins(RenderAssetPlugin::<Mesh, Image>::default());
+            .add_plugins(RenderAssetPlugin::<Mesh, Image>::default())
+            .add_systems(
+                PostUpdate,
+                touch_visible_assets::<Mesh>
+                    .run_if(resource_exists::<AssetBudget<Mesh>>)
+                    .after(VisibilitySystems::CheckVisibility),
+            );
     }
 }
@@ -5,7 +5,7 @@ use crate::{
     texture::Image,
 };
 use bevy_app::{Plugin, PostUpdate};
-use bevy_asset::Handle;
+use bevy_asset::{Assets, Handle};
 use bevy_ecs::prelude::*;
 use bevy_hierarchy::Children;
 use bevy_math::Vec3;
@@ -159,6 +159,32 @@ impl MorphWeights {
     pub fn weights_mut(&mut self) -> &mut [f32] {
         &mut self.weights
     }
+    /// The index of the morph target called `name`, as given by the
+    /// [`Mesh::morph_target_names`] of [`MorphWeights::first_mesh`].
+    ///
+    /// Returns `None` if the mesh isn't loaded, has no morph target names, or
+    /// no morph target called `name`.
+    pub fn target_index(&self, meshes: &Assets<Mesh>, name: &str) -> Option<usize> {
+        let mesh = meshes.get(self.first_mesh.as_ref()?)?;
+        mesh.morph_target_names()?
+            .iter()
+            .position(|target_name| target_name == name)
+            .filter(|&index| index < self.weights.len())
+    }
+    /// The weight of the morph target called `name`.
+    ///
+    /// See [`MorphWeights::target_index`].
+    pub fn weight_by_name(&self, meshes: &Assets<Mesh>, name: &str) -> Option<f32> {
+        self.target_index(meshes, name)
+            .map(|index| self.weights[index])
+    }
+    /// The weight of the morph target called `name`, mutably.
+    ///
+    /// See [`MorphWeights::target_index`].
+    pub fn weight_by_name_mut(&mut self, meshes: &Assets<Mesh>, name: &str) -> Option<&mut f32> {
+        self.target_index(meshes, name)
+            .map(|index| &mut self.weights[index])
+    }
 }
 
 /// Control a specific [`Mesh`] instance's [morph targets]. These control the weights of
@@ -0,0 +1,62 @@
+// Builds the hierarchical Z buffer (HZB) of a view, whose mips hold the farthest depth of the
+// pixels they cover. With reverse Z, the farthest depth is the smallest.
+
+#ifdef COPY_DEPTH
+
+#ifdef MULTISAMPLED
+@group(0) @binding(0) var depth_texture: texture_depth_multisampled_2d;
+#else
+@group(0) @binding(0) var depth_texture: texture_depth_2d;
+#endif
+@group(0) @binding(1) var hzb_mip: texture_storage_2d<r32float, write>;
+
+// Copies the depth of the view into the first mip, keeping the farthest of its samples.
+@compute @workgroup_size(8, 8, 1)
+fn copy_depth(@builtin(global_invocation_id) id: vec3<u32>) {
+    if any(id.xy >= textureDimensions(hzb_mip)) {
+        return;
+    }
+
+#ifdef MULTISAMPLED
+    var depth = 1.0;
+    for (var i = 0u; i < textureNumSamples(depth_texture); i += 1u) {
+        depth = min(depth, textureLoad(depth_texture, id.xy, i32(i)));
+    }
+#else
+    let depth = textureLoad(depth_texture, id.xy, 0i);
+#endif
+
+    textureStore(hzb_mip, id.xy, vec4(depth));
+}
+
+#else
+
+@group(0) @binding(0) var source_mip: texture_2d<f32>;
+@group(0) @binding(1) var destination_mip: texture_storage_2d<r32float, write>;
+
+// Keeps the farthest depth of the 2x2 texels of the previous mip covered by each texel.
+@compute @workgroup_size(8, 8, 1)
+fn downsample(@builtin(global_invocation_id) id: vec3<u32>) {
+    let size = textureDimensions(destination_mip);
+    if any(id.xy >= size) {
+        return;
+    }
+
+    // The sizes of the mips are rounded down, so the last texels cover 3 texels of an odd sized
+    // previous mip.
+    let source_size = textureDimensions(source_mip);
+    let last = (id.xy == size - 1u) & ((source_size & vec2(1u)) == vec2(1u));
+    let end = select(vec2(2u), vec2(3u), last);
+
+    var depth = 1.0;
+    for (var y = 0u; y < end.y; y += 1u) {
+        for (var x = 0u; x < end.x; x += 1u) {
+            let texel = min(id.xy * 2u + vec2(x, y), source_size - 1u);
+            depth = min(depth, textureLoad(source_mip, texel, 0i).r);
+        }
+    }
+
+    textureStore(destination_mip, id.xy, vec4(depth));
+}
+
+#endif
@@ -0,0 +1,597 @@
+//! GPU occlusion culling, which skips the draws of the meshes hidden behind other meshes.
+//!
+//! Add [`OcclusionCulling`] to a 3D camera with a depth prepass to enable it.
+
+use crate::{
+    camera::{Camera, ExtractedCamera},
+    primitives::Aabb,
+    render_graph::{NodeRunError, RenderGraphContext, ViewNode},
+    render_resource::{
+        binding_types::{
+            storage_buffer_read_only_sized, storage_buffer_sized, texture_2d, texture_depth_2d,
+            texture_depth_2d_multisampled, texture_storage_2d, uniform_buffer,
+        },
+        *,
+    },
+    renderer::{RenderContext, RenderDevice, RenderQueue},
+    texture::{CachedTexture, TextureCache},
+    view::{ViewDepthTexture, ViewUniform, ViewUniformOffset, ViewUniforms, ViewVisibility},
+    Extract, ExtractSchedule, Render, RenderApp, RenderSet,
+};
+use bevy_app::{App, Plugin};
+use bevy_asset::{load_internal_asset, Handle};
+use bevy_derive::{Deref, DerefMut};
+use bevy_ecs::{entity::EntityHashMap, prelude::*, query::QueryItem};
+use bevy_math::{Mat3A, Vec3};
+use bevy_reflect::{std_traits::ReflectDefault, Reflect};
+use bevy_transform::components::GlobalTransform;
+use bevy_utils::{warn_once, HashMap};
+use bytemuck::{Pod, Zeroable};
+use std::{num::NonZeroU64, ops::Range};
+
+const HZB_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(8437155236740526712);
+const OCCLUSION_CULLING_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(1470637823395843107);
+
+/// The number of `u32` of each indirect draw, the size of the arguments of an indexed draw.
+const INDIRECT_ARGS_LEN: usize = 5;
+
+/// Adds support for GPU occlusion culling.
+///
+/// See [`OcclusionCulling`] for more details.
+pub struct OcclusionCullingPlugin;
+
+impl Plugin for OcclusionCullingPlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(app, HZB_SHADER_HANDLE, "hzb.wgsl", Shader::from_wgsl);
+        load_internal_asset!(
+            app,
+            OCCLUSION_CULLING_SHADER_HANDLE,
+            "occlusion_culling.wgsl",
+            Shader::from_wgsl
+        );
+
+        app.register_type::<OcclusionCulling>();
+
+        let Ok(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        render_app
+            .init_resource::<OcclusionCullingViews>()
+            .init_resource::<OcclusionCullingAabbs>()
+            .add_systems(ExtractSchedule, extract_occlusion_culling);
+    }
+
+    fn finish(&self, app: &mut App) {
+        let Ok(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        if !occlusion_culling_is_supported(render_app.world.resource::<RenderDevice>()) {
+            return;
+        }
+
+        render_app
+            .init_resource::<OcclusionCullingPipeline>()
+            .add_systems(
+                Render,
+                (
+                    prepare_occlusion_culling_views.in_set(RenderSet::PrepareResources),
+                    write_occlusion_culling_buffers.in_set(RenderSet::PrepareResourcesFlush),
+                ),
+            );
+    }
+}
+
+/// Whether the GPU supports occlusion culling, which needs compute shaders writing to storage
+/// buffers and textures, and indirect draws starting at any instance.
+pub fn occlusion_culling_is_supported(render_device: &RenderDevice) -> bool {
+    let limits = render_device.limits();
+    render_device
+        .features()
+        .contains(WgpuFeatures::INDIRECT_FIRST_INSTANCE)
+        && limits.max_compute_workgroup_storage_size != 0
+        && limits.max_storage_buffers_per_shader_stage >= 2
+        && limits.max_storage_textures_per_shader_stage >= 1
+}
+
+/// Component to cull the meshes hidden behind other meshes from the main opaque passes of a 3D
+/// camera, on the GPU.
+///
+/// After the depth prepass, a compute pass builds a hierarchical Z buffer (HZB) from the depth of
+/// the camera: a chain of mips holding the farthest depth of the pixels they cover. The bounding
+/// box of each opaque and alpha masked mesh instance is then tested against the mip where it
+/// covers at most 2x2 texels, and the instances behind the depth of the prepass have their draws
+/// skipped with indirect draws. Instances batched together are drawn when any of them is
+/// visible.
+///
+/// The depth prepass itself still draws every mesh, so this saves the shading of the hidden
+/// meshes in the main passes, not their vertices in the prepass.
+///
+/// Requires a `DepthPrepass` or a `DeferredPrepass`, and GPU support for compute shaders and
+/// indirect draws starting at any instance, so it has no effect on WebGL 2.
+#[derive(Component, Reflect, Clone, Copy, Debug, Default)]
+#[reflect(Component, Default)]
+pub struct OcclusionCulling;
+
+/// The world space bounding boxes of the visible entities, extracted when a camera has
+/// [`OcclusionCulling`].
+#[derive(Resource, Default, Deref, DerefMut)]
+pub struct OcclusionCullingAabbs(EntityHashMap<Aabb>);
+
+/// The indirect draws of the views with [`OcclusionCulling`], and the resources culling them.
+#[derive(Resource, Default, Deref, DerefMut)]
+pub struct OcclusionCullingViews(EntityHashMap<ViewOcclusionCulling>);
+
+/// The arguments of a draw culled by [`OcclusionCulling`], besides its instances.
+#[derive(Clone, Copy, Debug)]
+pub enum OcclusionCullingDraw {
+    Indexed { index_count: u32 },
+    NonIndexed { vertex_count: u32 },
+}
+
+/// The bounding box of an instance, with the indirect draw it belongs to, matching
+/// `occlusion_culling.wgsl`.
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+struct OcclusionCullingInstance {
+    center: Vec3,
+    draw: u32,
+    half_extents: Vec3,
+    instance_count: u32,
+}
+
+/// The indirect draws of a view with [`OcclusionCulling`], rebuilt each frame.
+pub struct ViewOcclusionCulling {
+    hzb: Option<CachedTexture>,
+    instances: BufferVec<OcclusionCullingInstance>,
+    indirect_args: BufferVec<u32>,
+    /// The offsets of the indirect draws in `indirect_args`, by their first instance.
+    draw_offsets: HashMap<u32, u64>,
+}
+
+impl Default for ViewOcclusionCulling {
+    fn default() -> Self {
+        let mut instances = BufferVec::new(BufferUsages::STORAGE);
+        instances.set_label(Some("occlusion_culling_instances"));
+        let mut indirect_args = BufferVec::new(BufferUsages::STORAGE | BufferUsages::INDIRECT);
+        indirect_args.set_label(Some("occlusion_culling_indirect_args"));
+        Self {
+            hzb: None,
+            instances,
+            indirect_args,
+            draw_offsets: HashMap::default(),
+        }
+    }
+}
+
+impl ViewOcclusionCulling {
+    /// Adds an indirect draw of `instances`, culled by their world space bounding boxes `aabbs`.
+    ///
+    /// The draw is skipped when all of the instances are occluded.
+    pub fn push_draw(
+        &mut self,
+        draw: OcclusionCullingDraw,
+        instances: Range<u32>,
+        aabbs: impl IntoIterator<Item = Aabb>,
+    ) {
+        let draw_index = (self.indirect_args.len() / INDIRECT_ARGS_LEN) as u32;
+        let instance_count = instances.len() as u32;
+        // The instance counts are only zeroed by the culling pass, so the draws are complete
+        // while its pipelines are compiling.
+        let args = match draw {
+            OcclusionCullingDraw::Indexed { index_count } => {
+                [index_count, instance_count, 0, 0, instances.start]
+            }
+            OcclusionCullingDraw::NonIndexed { vertex_count } => {
+                [vertex_count, instance_count, 0, instances.start, 0]
+            }
+        };
+        self.indirect_args.extend(args);
+        self.instances
+            .extend(aabbs.into_iter().map(|aabb| OcclusionCullingInstance {
+                center: aabb.center.into(),
+                draw: draw_index,
+                half_extents: aabb.half_extents.into(),
+                instance_count,
+            }));
+        self.draw_offsets.insert(
+            instances.start,
+            (draw_index as usize * INDIRECT_ARGS_LEN * std::mem::size_of::<u32>()) as u64,
+        );
+    }
+
+    /// The buffer and offset of the indirect draw of the instances starting at `first_instance`,
+    /// if they were added with [`push_draw`](Self::push_draw).
+    pub fn indirect_draw(&self, first_instance: u32) -> Option<(&Buffer, u64)> {
+        let offset = *self.draw_offsets.get(&first_instance)?;
+        Some((self.indirect_args.buffer()?, offset))
+    }
+
+    fn clear(&mut self) {
+        self.instances.clear();
+        self.indirect_args.clear();
+        self.draw_offsets.clear();
+    }
+}
+
+/// Extracts the [`OcclusionCulling`] of the active cameras, and the bounding boxes of the
+/// visible entities when any camera has it.
+fn extract_occlusion_culling(
+    mut commands: Commands,
+    pipeline: Option<Res<OcclusionCullingPipeline>>,
+    mut aabbs: ResMut<OcclusionCullingAabbs>,
+    cameras: Extract<Query<(Entity, &Camera), With<OcclusionCulling>>>,
+    entities: Extract<Query<(Entity, &ViewVisibility, &Aabb, &GlobalTransform)>>,
+) {
+    aabbs.clear();
+
+    let mut culling = false;
+    for (entity, camera) in &cameras {
+        if !camera.is_active {
+            continue;
+        }
+        if pipeline.is_none() {
+            warn_once!("OcclusionCulling has no effect, as the GPU lacks support for it.");
+            return;
+        }
+        commands.get_or_spawn(entity).insert(OcclusionCulling);
+        culling = true;
+    }
+    if !culling {
+        return;
+    }
+
+    aabbs.extend(
+        entities
+            .iter()
+            .filter(|(_, view_visibility, _, _)| view_visibility.get())
+            .map(|(entity, _, aabb, transform)| {
+                let affine = transform.affine();
+                let matrix = affine.matrix3;
+                let abs_matrix = Mat3A::from_cols(
+                    matrix.x_axis.abs(),
+                    matrix.y_axis.abs(),
+                    matrix.z_axis.abs(),
+                );
+                let world_aabb = Aabb {
+                    center: affine.transform_point3a(aabb.center),
+                    half_extents: abs_matrix * aabb.half_extents,
+                };
+                (entity, world_aabb)
+            }),
+    );
+}
+
+/// Clears the indirect draws of the views with [`OcclusionCulling`], before the render phases
+/// add theirs, and gets their HZB textures.
+pub fn prepare_occlusion_culling_views(
+    mut occlusion_culling_views: ResMut<OcclusionCullingViews>,
+    mut texture_cache: ResMut<TextureCache>,
+    render_device: Res<RenderDevice>,
+    views: Query<(Entity, &ExtractedCamera), With<OcclusionCulling>>,
+) {
+    occlusion_culling_views.retain(|entity, _| views.contains(*entity));
+
+    for (entity, camera) in &views {
+        let view_occlusion_culling = occlusion_culling_views.entry(entity).or_default();
+        view_occlusion_culling.clear();
+
+        // The HZB matches the depth texture of the view, which covers its whole target.
+        view_occlusion_culling.hzb = camera.physical_target_size.map(|size| {
+            texture_cache.get(
+                &render_device,
+                TextureDescriptor {
+                    label: Some("occlusion_culling_hzb"),
+                    size: Extent3d {
+                        width: size.x,
+                        height: size.y,
+                        depth_or_array_layers: 1,
+                    },
+                    mip_level_count: 32 - size.x.max(size.y).leading_zeros(),
+                    sample_count: 1,
+                    dimension: TextureDimension::D2,
+                    format: TextureFormat::R32Float,
+                    usage: TextureUsages::STORAGE_BINDING | TextureUsages::TEXTURE_BINDING,
+                    view_formats: &[],
+                },
+            )
+        });
+    }
+}
+
+fn write_occlusion_culling_buffers(
+    mut occlusion_culling_views: ResMut<OcclusionCullingViews>,
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+) {
+    for view_occlusion_culling in occlusion_culling_views.values_mut() {
+        view_occlusion_culling
+            .instances
+            .write_buffer(&render_device, &render_queue);
+        view_occlusion_culling
+            .indirect_args
+            .write_buffer(&render_device, &render_queue);
+    }
+}
+
+#[derive(Resource)]
+pub struct OcclusionCullingPipeline {
+    copy_depth_layout: BindGroupLayout,
+    copy_depth_multisampled_layout: BindGroupLayout,
+    downsample_layout: BindGroupLayout,
+    cull_layout: BindGroupLayout,
+    copy_depth_pipeline: CachedComputePipelineId,
+    copy_depth_multisampled_pipeline: CachedComputePipelineId,
+    downsample_pipeline: CachedComputePipelineId,
+    reset_pipeline: CachedComputePipelineId,
+    cull_pipeline: CachedComputePipelineId,
+}
+
+impl FromWorld for OcclusionCullingPipeline {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+
+        let hzb_storage =
+            texture_storage_2d(TextureFormat::R32Float, StorageTextureAccess::WriteOnly);
+        let copy_depth_layout = render_device.create_bind_group_layout(
+            "occlusion_culling_copy_depth_bind_group_layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::COMPUTE,
+                (texture_depth_2d(), hzb_storage),
+            ),
+        );
+        let copy_depth_multisampled_layout = render_device.create_bind_group_layout(
+            "occlusion_culling_copy_depth_multisampled_bind_group_layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::COMPUTE,
+                (texture_depth_2d_multisampled(), hzb_storage),
+            ),
+        );
+        let downsample_layout = render_device.create_bind_group_layout(
+            "occlusion_culling_downsample_bind_group_layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::COMPUTE,
+                (
+                    texture_2d(TextureSampleType::Float { filterable: false }),
+                    hzb_storage,
+                ),
+            ),
+        );
+        let cull_layout = render_device.create_bind_group_layout(
+            "occlusion_culling_bind_group_layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::COMPUTE,
+                (
+                    uniform_buffer::<ViewUniform>(true),
+                    texture_2d(TextureSampleType::Float { filterable: false }),
+                    storage_buffer_read_only_sized(false, None),
+                    storage_buffer_sized(false, None),
+                ),
+            ),
+        );
+
+        let pipeline_cache = world.resource::<PipelineCache>();
+        let pipeline = |label: &'static str,
+                        layout: &BindGroupLayout,
+                        shader: Handle<Shader>,
+                        shader_defs: Vec<ShaderDefVal>,
+                        entry_point: &'static str| {
+            pipeline_cache.queue_compute_pipeline(ComputePipelineDescriptor {
+                label: Some(label.into()),
+                layout: vec![layout.clone()],
+                push_constant_ranges: vec![],
+                shader,
+                shader_defs,
+                entry_point: entry_point.into(),
+            })
+        };
+        let copy_depth_pipeline = pipeline(
+            "occlusion_culling_copy_depth_pipeline",
+            &copy_depth_layout,
+            HZB_SHADER_HANDLE,
+            vec!["COPY_DEPTH".into()],
+            "copy_depth",
+        );
+        let copy_depth_multisampled_pipeline = pipeline(
+            "occlusion_culling_copy_depth_multisampled_pipeline",
+            &copy_depth_multisampled_layout,
+            HZB_SHADER_HANDLE,
+            vec!["COPY_DEPTH".into(), "MULTISAMPLED".into()],
+            "copy_depth",
+        );
+        let downsample_pipeline = pipeline(
+            "occlusion_culling_downsample_pipeline",
+            &downsample_layout,
+            HZB_SHADER_HANDLE,
+            Vec::new(),
+            "downsample",
+        );
+        let reset_pipeline = pipeline(
+            "occlusion_culling_reset_pipeline",
+            &cull_layout,
+            OCCLUSION_CULLING_SHADER_HANDLE,
+            Vec::new(),
+            "reset",
+        );
+        let cull_pipeline = pipeline(
+            "occlusion_culling_pipeline",
+            &cull_layout,
+            OCCLUSION_CULLING_SHADER_HANDLE,
+            Vec::new(),
+            "cull",
+        );
+
+        Self {
+            copy_depth_layout,
+            copy_depth_multisampled_layout,
+            downsample_layout,
+            cull_layout,
+            copy_depth_pipeline,
+            copy_depth_multisampled_pipeline,
+            downsample_pipeline,
+            reset_pipeline,
+            cull_pipeline,
+        }
+    }
+}
+
+/// Render [`crate::render_graph::Node`] building the HZB of the views with [`OcclusionCulling`]
+/// from their depth, and culling their indirect draws with it.
+///
+/// It must run after the depth prepass, and before the passes drawing the culled draws.
+#[derive(Default)]
+pub struct OcclusionCullingNode;
+
+impl ViewNode for OcclusionCullingNode {
+    type ViewQuery = (
+        &'static ViewDepthTexture,
+        &'static ViewUniformOffset,
+        &'static OcclusionCulling,
+    );
+
+    fn run(
+        &self,
+        graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        (depth, view_uniform_offset, _): QueryItem<Self::ViewQuery>,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        let Some(view_occlusion_culling) = world
+            .resource::<OcclusionCullingViews>()
+            .get(&graph.view_entity())
+        else {
+            return Ok(());
+        };
+        let ViewOcclusionCulling {
+            hzb: Some(hzb),
+            instances,
+            indirect_args,
+            ..
+        } = view_occlusion_culling
+        else {
+            return Ok(());
+        };
+        let (Some(instances_buffer), Some(indirect_args_buffer)) =
+            (instances.buffer(), indirect_args.buffer())
+        else {
+            return Ok(());
+        };
+        if instances.is_empty() {
+            return Ok(());
+        }
+
+        let occlusion_culling_pipeline = world.resource::<OcclusionCullingPipeline>();
+        let pipeline_cache = world.resource::<PipelineCache>();
+        let multisampled = depth.texture.sample_count() > 1;
+        let (copy_depth_pipeline, copy_depth_layout) = if multisampled {
+            (
+                occlusion_culling_pipeline.copy_depth_multisampled_pipeline,
+                &occlusion_culling_pipeline.copy_depth_multisampled_layout,
+            )
+        } else {
+            (
+                occlusion_culling_pipeline.copy_depth_pipeline,
+                &occlusion_culling_pipeline.copy_depth_layout,
+            )
+        };
+        let (
+            Some(copy_depth_pipeline),
+            Some(downsample_pipeline),
+            Some(reset_pipeline),
+            Some(cull_pipeline),
+            Some(view_uniforms),
+        ) = (
+            pipeline_cache.get_compute_pipeline(copy_depth_pipeline),
+            pipeline_cache.get_compute_pipeline(occlusion_culling_pipeline.downsample_pipeline),
+            pipeline_cache.get_compute_pipeline(occlusion_culling_pipeline.reset_pipeline),
+            pipeline_cache.get_compute_pipeline(occlusion_culling_pipeline.cull_pipeline),
+            world.resource::<ViewUniforms>().uniforms.binding(),
+        )
+        else {
+            return Ok(());
+        };
+
+        let render_device = render_context.render_device().clone();
+        let mip_view = |mip: u32| {
+            hzb.texture.create_view(&TextureViewDescriptor {
+                label: Some("occlusion_culling_hzb_mip_view"),
+                base_mip_level: mip,
+                mip_level_count: Some(1),
+                ..Default::default()
+            })
+        };
+        let mip_views: Vec<_> = (0..hzb.texture.mip_level_count()).map(mip_view).collect();
+
+        let copy_depth_bind_group = render_device.create_bind_group(
+            "occlusion_culling_copy_depth_bind_group",
+            copy_depth_layout,
+            &BindGroupEntries::sequential((depth.view(), &mip_views[0])),
+        );
+        let downsample_bind_groups: Vec<_> = mip_views
+            .windows(2)
+            .map(|views| {
+                render_device.create_bind_group(
+                    "occlusion_culling_downsample_bind_group",
+                    &occlusion_culling_pipeline.downsample_layout,
+                    &BindGroupEntries::sequential((&views[0], &views[1])),
+                )
+            })
+            .collect();
+        let cull_bind_group = render_device.create_bind_group(
+            "occlusion_culling_bind_group",
+            &occlusion_culling_pipeline.cull_layout,
+            &BindGroupEntries::sequential((
+                view_uniforms,
+                &hzb.default_view,
+                BufferBinding {
+                    buffer: instances_buffer,
+                    offset: 0,
+                    size: NonZeroU64::new(
+                        (instances.len() * std::mem::size_of::<OcclusionCullingInstance>()) as u64,
+                    ),
+                },
+                BufferBinding {
+                    buffer: indirect_args_buffer,
+                    offset: 0,
+                    size: NonZeroU64::new(
+                        (indirect_args.len() * std::mem::size_of::<u32>()) as u64,
+                    ),
+                },
+            )),
+        );
+
+        let mut compute_pass =
+            render_context
+                .command_encoder()
+                .begin_compute_pass(&ComputePassDescriptor {
+                    label: Some("occlusion_culling_pass"),
+                    timestamp_writes: None,
+                });
+
+        let size = hzb.texture.size();
+        compute_pass.set_pipeline(copy_depth_pipeline);
+        compute_pass.set_bind_group(0, &copy_depth_bind_group, &[]);
+        compute_pass.dispatch_workgroups(size.width.div_ceil(8), size.height.div_ceil(8), 1);
+
+        compute_pass.set_pipeline(downsample_pipeline);
+        for (mip, bind_group) in downsample_bind_groups.iter().enumerate() {
+            let mip_size = size.mip_level_size(mip as u32 + 1, TextureDimension::D2);
+            compute_pass.set_bind_group(0, bind_group, &[]);
+            compute_pass.dispatch_workgroups(
+                mip_size.width.div_ceil(8),
+                mip_size.height.div_ceil(8),
+                1,
+            );
+        }
+
+        let draw_count = (indirect_args.len() / INDIRECT_ARGS_LEN) as u32;
+        compute_pass.set_bind_group(0, &cull_bind_group, &[view_uniform_offset.offset]);
+        compute_pass.set_pipeline(reset_pipeline);
+        compute_pass.dispatch_workgroups(draw_count.div_ceil(64), 1, 1);
+        compute_pass.set_pipeline(cull_pipeline);
+        compute_pass.dispatch_workgroups((instances.len() as u32).div_ceil(64), 1, 1);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,84 @@
+// Culls the indirect draws of a view whose instances are all behind the depth of its HZB.
+
+#import bevy_render::view::View
+
+// The number of `u32` of each indirect draw, and the index of its instance count.
+const INDIRECT_ARGS_LEN: u32 = 5u;
+const INSTANCE_COUNT: u32 = 1u;
+
+struct OcclusionCullingInstance {
+    center: vec3<f32>,
+    draw: u32,
+    half_extents: vec3<f32>,
+    instance_count: u32,
+}
+
+@group(0) @binding(0) var<uniform> view: View;
+@group(0) @binding(1) var hzb_texture: texture_2d<f32>;
+@group(0) @binding(2) var<storage> instances: array<OcclusionCullingInstance>;
+@group(0) @binding(3) var<storage, read_write> indirect_args: array<atomic<u32>>;
+
+// Whether a world space bounding box may be in front of the depth of the HZB.
+fn is_visible(center: vec3<f32>, half_extents: vec3<f32>) -> bool {
+    var uv_min = vec2(1.0);
+    var uv_max = vec2(0.0);
+    var nearest_depth = 0.0;
+    for (var i = 0u; i < 8u; i += 1u) {
+        let corner_sign = vec3(f32(i & 1u), f32((i >> 1u) & 1u), f32((i >> 2u) & 1u)) * 2.0 - 1.0;
+        let clip = view.view_proj * vec4(center + half_extents * corner_sign, 1.0);
+        // Boxes behind the camera cross its near plane, so nothing is in front of them.
+        if clip.w <= 0.0 {
+            return true;
+        }
+        let ndc = clip.xyz / clip.w;
+        let uv = ndc.xy * vec2(0.5, -0.5) + 0.5;
+        uv_min = min(uv_min, uv);
+        uv_max = max(uv_max, uv);
+        nearest_depth = max(nearest_depth, ndc.z);
+    }
+
+    // The HZB covers the whole target of the view, of which the viewport is a part.
+    let pixel_min = view.viewport.xy + saturate(uv_min) * view.viewport.zw;
+    let pixel_max = view.viewport.xy + saturate(uv_max) * view.viewport.zw;
+
+    // In the mip where the box is at most one texel wide, it covers at most 2x2 texels.
+    let extent = max(pixel_max.x - pixel_min.x, pixel_max.y - pixel_min.y);
+    let mip = min(u32(ceil(log2(max(extent, 1.0)))), textureNumLevels(hzb_texture) - 1u);
+    let mip_size = textureDimensions(hzb_texture, mip);
+    let texel_min = min(vec2<u32>(pixel_min) >> vec2(mip), mip_size - 1u);
+    let texel_max = min(vec2<u32>(pixel_max) >> vec2(mip), mip_size - 1u);
+
+    var farthest_depth = 1.0;
+    for (var y = texel_min.y; y <= texel_max.y; y += 1u) {
+        for (var x = texel_min.x; x <= texel_max.x; x += 1u) {
+            let depth = textureLoad(hzb_texture, vec2(x, y), i32(mip)).r;
+            farthest_depth = min(farthest_depth, depth);
+        }
+    }
+
+    // With reverse Z, greater depths are nearer.
+    return nearest_depth >= farthest_depth;
+}
+
+// Zeroes the instance counts of the draws, before the visible instances restore them.
+@compute @workgroup_size(64, 1, 1)
+fn reset(@builtin(global_invocation_id) id: vec3<u32>) {
+    if id.x >= arrayLength(&indirect_args) / INDIRECT_ARGS_LEN {
+        return;
+    }
+    atomicStore(&indirect_args[id.x * INDIRECT_ARGS_LEN + INSTANCE_COUNT], 0u);
+}
+
+@compute @workgroup_size(64, 1, 1)
+fn cull(@builtin(global_invocation_id) id: vec3<u32>) {
+    if id.x >= arrayLength(&instances) {
+        return;
+    }
+
+    // A visible instance draws all the instances batched with it.
+    let instance = instances[id.x];
+    if is_visible(instance.center, instance.half_extents) {
+        let index = instance.draw * INDIRECT_ARGS_LEN + INSTANCE_COUNT;
+        atomicStore(&indirect_args[index], instance.instance_count);
+    }
+}
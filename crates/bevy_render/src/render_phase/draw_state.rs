@@ -8,7 +8,13 @@ use crate::{
     renderer::RenderDevice,
 };
 use bevy_utils::{default, detailed_trace};
-use std::ops::Range;
+use std::{
+    ops::Range,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+};
 use wgpu::{IndexFormat, RenderPass};
 
 /// Tracks the state of a [`TrackedRenderPass`].
@@ -103,6 +109,10 @@ impl DrawState {
 pub struct TrackedRenderPass<'a> {
     pass: RenderPass<'a>,
     state: DrawState,
+    /// The number of draw calls issued by this pass, added to the `draw_call_counter` of the
+    /// [`RenderDevice`] when the pass is dropped.
+    draw_calls: u32,
+    draw_call_counter: Arc<AtomicU32>,
 }
 
 impl<'a> TrackedRenderPass<'a> {
@@ -118,6 +128,8 @@ impl<'a> TrackedRenderPass<'a> {
                 ..default()
             },
             pass,
+            draw_calls: 0,
+            draw_call_counter: device.draw_call_counter().clone(),
         }
     }
 
@@ -246,6 +258,7 @@ impl<'a> TrackedRenderPass<'a> {
     pub fn draw(&mut self, vertices: Range<u32>, instances: Range<u32>) {
         detailed_trace!("draw: {:?} {:?}", vertices, instances);
         self.pass.draw(vertices, instances);
+        self.draw_calls += 1;
     }
 
     /// Draws indexed primitives using the active index buffer and the active vertex buffer(s).
@@ -260,6 +273,7 @@ impl<'a> TrackedRenderPass<'a> {
             instances
         );
         self.pass.draw_indexed(indices, base_vertex, instances);
+        self.draw_calls += 1;
     }
 
     /// Draws primitives from the active vertex buffer(s) based on the contents of the
@@ -282,6 +296,7 @@ impl<'a> TrackedRenderPass<'a> {
     pub fn draw_indirect(&mut self, indirect_buffer: &'a Buffer, indirect_offset: u64) {
         detailed_trace!("draw indirect: {:?} {}", indirect_buffer, indirect_offset);
         self.pass.draw_indirect(indirect_buffer, indirect_offset);
+        self.draw_calls += 1;
     }
 
     /// Draws indexed primitives using the active index buffer and the active vertex buffers,
@@ -311,6 +326,7 @@ impl<'a> TrackedRenderPass<'a> {
         );
         self.pass
             .draw_indexed_indirect(indirect_buffer, indirect_offset);
+        self.draw_calls += 1;
     }
 
     /// Dispatches multiple draw calls from the active vertex buffer(s) based on the contents of the
@@ -344,6 +360,7 @@ impl<'a> TrackedRenderPass<'a> {
         );
         self.pass
             .multi_draw_indirect(indirect_buffer, indirect_offset, count);
+        self.draw_calls += count;
     }
 
     /// Dispatches multiple draw calls from the active vertex buffer(s) based on the contents of
@@ -391,6 +408,8 @@ impl<'a> TrackedRenderPass<'a> {
             count_offset,
             max_count,
         );
+        // The number of draws is only known by the GPU, count them as one.
+        self.draw_calls += 1;
     }
 
     /// Dispatches multiple draw calls from the active index buffer and the active vertex buffers,
@@ -426,6 +445,7 @@ impl<'a> TrackedRenderPass<'a> {
         );
         self.pass
             .multi_draw_indexed_indirect(indirect_buffer, indirect_offset, count);
+        self.draw_calls += count;
     }
 
     /// Dispatches multiple draw calls from the active index buffer and the active vertex buffers,
@@ -475,6 +495,7 @@ impl<'a> TrackedRenderPass<'a> {
             count_offset,
             max_count,
         );
+        self.draw_calls += 1;
     }
 
     /// Sets the stencil reference.
@@ -603,3 +624,10 @@ impl<'a> TrackedRenderPass<'a> {
         self.pass.set_blend_constant(wgpu::Color::from(color));
     }
 }
+
+impl Drop for TrackedRenderPass<'_> {
+    fn drop(&mut self) {
+        self.draw_call_counter
+            .fetch_add(self.draw_calls, Ordering::Relaxed);
+    }
+}
@@ -29,7 +29,12 @@ mod draw;
 mod draw_state;
 mod rangefinder;
 
-use bevy_utils::nonmax::NonMaxU32;
+#[cfg(feature = "trace")]
+use bevy_utils::{
+    get_short_name,
+    tracing::{field, info_span},
+};
+use bevy_utils::{nonmax::NonMaxU32, HashMap};
 pub use draw::*;
 pub use draw_state::*;
 pub use rangefinder::*;
@@ -39,7 +44,7 @@ use bevy_ecs::{
     prelude::*,
     system::{lifetimeless::SRes, SystemParamItem},
 };
-use std::{ops::Range, slice::SliceIndex};
+use std::{hash::Hash, ops::Range, slice::SliceIndex};
 
 /// A collection of all rendering instructions, that will be executed by the GPU, for a
 /// single render phase for a single view.
@@ -103,6 +108,17 @@ impl<I: PhaseItem> RenderPhase<I> {
             .get(range)
             .expect("`Range` provided to `render_range()` is out of bounds");
 
+        #[cfg(feature = "trace")]
+        let span = info_span!(
+            "render_phase",
+            phase = get_short_name(std::any::type_name::<I>()),
+            items = items.len(),
+            draws = field::Empty,
+        )
+        .entered();
+        #[cfg(feature = "trace")]
+        let mut draws = 0;
+
         let draw_functions = world.resource::<DrawFunctions<I>>();
         let mut draw_functions = draw_functions.write();
         draw_functions.prepare(world);
@@ -117,8 +133,15 @@ impl<I: PhaseItem> RenderPhase<I> {
                 let draw_function = draw_functions.get_mut(item.draw_function()).unwrap();
                 draw_function.draw(world, render_pass, view, item);
                 index += batch_range.len();
+                #[cfg(feature = "trace")]
+                {
+                    draws += 1;
+                }
             }
         }
+
+        #[cfg(feature = "trace")]
+        span.record("draws", draws);
     }
 }
 
@@ -191,6 +214,38 @@ pub trait CachedRenderPipelinePhaseItem: PhaseItem {
     fn cached_pipeline(&self) -> CachedRenderPipelineId;
 }
 
+/// A [`PhaseItem`] whose draw order doesn't matter, like an opaque mesh, which is sorted into bins
+/// of the items it can be drawn together with.
+///
+/// Use [`sort_binned`] as the [`PhaseItem::sort`] implementation. It makes the items with the
+/// same pipeline, draw function and [`BinnedPhaseItem::BinKey`] contiguous, so
+/// [`batch_and_prepare_render_phase`](crate::batching::batch_and_prepare_render_phase) merges
+/// them into a single instanced draw, with the data of each instance in its
+/// [`GpuArrayBuffer`](crate::render_resource::GpuArrayBuffer). The bin key should thus contain
+/// the data that the batching compares, besides the pipeline and the draw function.
+pub trait BinnedPhaseItem: CachedRenderPipelinePhaseItem {
+    /// The data that must be equal for two items to be drawn in the same instanced draw.
+    type BinKey: Hash + Eq;
+
+    /// Returns the key of the bin of this item.
+    fn bin_key(&self) -> Self::BinKey;
+}
+
+/// Sorts binned phase items by pipeline and draw function, then by bin.
+///
+/// The bins are numbered in the order they are first seen in, so the keys are only hashed rather
+/// than compared, and the items are radix sorted.
+pub fn sort_binned<I: BinnedPhaseItem>(items: &mut [I]) {
+    let mut bins = HashMap::default();
+    radsort::sort_by_cached_key(items, |item| {
+        let next_bin = bins.len() as u32;
+        let bin = *bins
+            .entry((item.draw_function(), item.bin_key()))
+            .or_insert(next_bin);
+        (item.cached_pipeline().id(), bin)
+    });
+}
+
 /// A [`RenderCommand`] that sets the pipeline for the [`CachedRenderPipelinePhaseItem`].
 pub struct SetItemPipeline;
 
@@ -221,6 +276,14 @@ impl<P: CachedRenderPipelinePhaseItem> RenderCommand<P> for SetItemPipeline {
 /// This system sorts the [`PhaseItem`]s of all [`RenderPhase`]s of this type.
 pub fn sort_phase_system<I: PhaseItem>(mut render_phases: Query<&mut RenderPhase<I>>) {
     for mut phase in &mut render_phases {
+        #[cfg(feature = "trace")]
+        let _span = info_span!(
+            "sort_phase",
+            phase = get_short_name(std::any::type_name::<I>()),
+            items = phase.items.len(),
+        )
+        .entered();
+
         phase.sort();
     }
 }
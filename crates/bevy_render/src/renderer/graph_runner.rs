@@ -10,6 +10,7 @@ use std::{borrow::Cow, collections::VecDeque};
 use thiserror::Error;
 
 use crate::{
+    diagnostic::GpuTimer,
     render_graph::{
         Edge, InternedRenderLabel, InternedRenderSubGraph, NodeRunError, NodeState, RenderGraph,
         RenderGraphContext, SlotLabel, SlotType, SlotValue,
@@ -62,14 +63,24 @@ impl RenderGraphRunner {
         finalizer: impl FnOnce(&mut wgpu::CommandEncoder),
     ) -> Result<(), RenderGraphRunnerError> {
         let mut render_context = RenderContext::new(render_device, adapter.get_info());
+        render_context.gpu_timer = world
+            .get_resource::<GpuTimer>()
+            .and_then(GpuTimer::begin_frame);
         Self::run_graph(graph, None, &mut render_context, world, &[], None)?;
         finalizer(render_context.command_encoder());
+        let gpu_timer = render_context.gpu_timer.take();
+        if let Some(gpu_timer) = &gpu_timer {
+            gpu_timer.resolve(render_context.command_encoder());
+        }
 
         {
             #[cfg(feature = "trace")]
             let _span = info_span!("submit_graph_commands").entered();
             queue.submit(render_context.finish());
         }
+        if let Some(gpu_timer) = gpu_timer {
+            gpu_timer.read_back();
+        }
         Ok(())
     }
 
@@ -191,9 +202,20 @@ impl RenderGraphRunner {
 
                 {
                     #[cfg(feature = "trace")]
-                    let _span = info_span!("node", name = node_state.type_name).entered();
+                    let _span = info_span!(
+                        "node",
+                        name = node_state.type_name,
+                        label = ?node_state.label,
+                        graph = ?sub_graph,
+                    )
+                    .entered();
 
+                    let gpu_timing = render_context.begin_gpu_timing(|| match sub_graph {
+                        Some(sub_graph) => format!("{sub_graph:?}/{:?}", node_state.label),
+                        None => format!("{:?}", node_state.label),
+                    });
                     node_state.node.run(&mut context, render_context, world)?;
+                    render_context.end_gpu_timing(gpu_timing);
                 }
 
                 for run_sub_graph in context.finish() {
@@ -8,6 +8,7 @@ pub use graph_runner::*;
 pub use render_device::*;
 
 use crate::{
+    diagnostic::GpuTimerFrame,
     render_graph::RenderGraph,
     render_phase::TrackedRenderPass,
     render_resource::RenderPassDescriptor,
@@ -40,6 +41,8 @@ pub fn render_system(world: &mut World, state: &mut SystemState<Query<Entity, Wi
         world,
         |encoder| {
             crate::view::screenshot::submit_screenshot_commands(world, encoder);
+            #[cfg(not(target_arch = "wasm32"))]
+            crate::view::recording::submit_image_capture_commands(world, encoder);
         },
     ) {
         error!("Error running render graph:");
@@ -89,6 +92,8 @@ pub fn render_system(world: &mut World, state: &mut SystemState<Query<Entity, Wi
     }
 
     crate::view::screenshot::collect_screenshots(world);
+    #[cfg(not(target_arch = "wasm32"))]
+    crate::view::recording::collect_image_captures(world);
 
     // update the time and send it to the app world
     let time_sender = world.resource::<TimeSender>();
@@ -168,6 +173,12 @@ pub async fn initialize_renderer(
         limits = adapter.limits();
     }
 
+    // The GPU timings are measured when the adapter supports timestamps, whatever the priority.
+    #[cfg(feature = "gpu_timestamps")]
+    {
+        features |= adapter.features() & wgpu::Features::TIMESTAMP_QUERY;
+    }
+
     // Enforce the disabled features
     if let Some(disabled_features) = options.disabled_features {
         features -= disabled_features;
@@ -306,6 +317,7 @@ pub struct RenderContext<'w> {
     command_encoder: Option<CommandEncoder>,
     command_buffer_queue: Vec<QueuedCommandBuffer<'w>>,
     force_serial: bool,
+    gpu_timer: Option<GpuTimerFrame<'w>>,
 }
 
 impl<'w> RenderContext<'w> {
@@ -326,6 +338,7 @@ impl<'w> RenderContext<'w> {
             command_encoder: None,
             command_buffer_queue: Vec::new(),
             force_serial,
+            gpu_timer: None,
         }
     }
 
@@ -418,6 +431,29 @@ impl<'w> RenderContext<'w> {
         command_buffers.into_iter().map(|(_, cb)| cb).collect()
     }
 
+    /// Writes the GPU timestamp before running a node of the render graph, if the GPU timings are
+    /// measured.
+    fn begin_gpu_timing(&mut self, node: impl FnOnce() -> String) -> Option<u32> {
+        let gpu_timer = self.gpu_timer.as_mut()?;
+        let command_encoder = self.command_encoder.get_or_insert_with(|| {
+            self.render_device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor::default())
+        });
+        gpu_timer.begin_node(command_encoder, node)
+    }
+
+    /// Writes the GPU timestamp after running the node of the timing `index`.
+    fn end_gpu_timing(&mut self, index: Option<u32>) {
+        let (Some(gpu_timer), Some(index)) = (self.gpu_timer.as_mut(), index) else {
+            return;
+        };
+        let command_encoder = self.command_encoder.get_or_insert_with(|| {
+            self.render_device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor::default())
+        });
+        gpu_timer.end_node(command_encoder, index);
+    }
+
     fn flush_encoder(&mut self) {
         if let Some(encoder) = self.command_encoder.take() {
             self.command_buffer_queue
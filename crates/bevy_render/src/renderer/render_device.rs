@@ -3,6 +3,10 @@ use crate::render_resource::{
     RenderPipeline, Sampler, Texture,
 };
 use bevy_ecs::system::Resource;
+use std::sync::{
+    atomic::{AtomicU32, Ordering},
+    Arc,
+};
 use wgpu::{
     util::DeviceExt, BindGroupDescriptor, BindGroupEntry, BindGroupLayoutDescriptor,
     BindGroupLayoutEntry, BufferAsyncError, BufferBindingType, MaintainResult,
@@ -18,17 +22,32 @@ render_resource_wrapper!(ErasedRenderDevice, wgpu::Device);
 #[derive(Resource, Clone)]
 pub struct RenderDevice {
     device: ErasedRenderDevice,
+    draw_call_counter: Arc<AtomicU32>,
 }
 
 impl From<wgpu::Device> for RenderDevice {
     fn from(device: wgpu::Device) -> Self {
         Self {
             device: ErasedRenderDevice::new(device),
+            draw_call_counter: Default::default(),
         }
     }
 }
 
 impl RenderDevice {
+    /// Returns the number of draw calls issued by the
+    /// [`TrackedRenderPass`](crate::render_phase::TrackedRenderPass)es of this device since the
+    /// last call, and resets it.
+    ///
+    /// The draw calls of a pass are counted once it's dropped.
+    pub fn take_draw_calls(&self) -> u32 {
+        self.draw_call_counter.swap(0, Ordering::Relaxed)
+    }
+
+    pub(crate) fn draw_call_counter(&self) -> &Arc<AtomicU32> {
+        &self.draw_call_counter
+    }
+
     /// List all [`Features`](wgpu::Features) that may be used with this device.
     ///
     /// Functions may panic if you use unsupported features.
@@ -0,0 +1,401 @@
+//! A spatial index of the entities with an [`Aabb`], to find the entities in a region of the
+//! world without testing all of them.
+//!
+//! The index is a grid of cubic cells, updated in [`PostUpdate`] with the entities whose
+//! [`GlobalTransform`] or [`Aabb`] changed. Use the [`SpatialQuery`] system parameter to query
+//! it.
+
+use bevy_app::{App, Plugin, PostUpdate};
+use bevy_ecs::{
+    entity::{EntityHashMap, EntityHashSet},
+    prelude::*,
+    system::SystemParam,
+};
+use bevy_math::{
+    bounding::{Aabb3d, BoundingSphere, IntersectsVolume},
+    I64Vec3, IVec3, Vec3, Vec3A,
+};
+use bevy_transform::{components::GlobalTransform, TransformSystem};
+use bevy_utils::HashMap;
+
+use crate::{
+    primitives::{Aabb, Frustum},
+    view::VisibilitySystems,
+};
+
+/// Keeps the [`SpatialIndex`] up to date.
+pub struct SpatialQueryPlugin;
+
+impl Plugin for SpatialQueryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SpatialIndex>().add_systems(
+            PostUpdate,
+            update_spatial_index
+                .after(VisibilitySystems::CalculateBounds)
+                .after(TransformSystem::TransformPropagate),
+        );
+    }
+}
+
+/// The entities covering more cells than this are kept out of the grid, and tested against every
+/// query.
+const MAX_ENTITY_CELLS: i64 = 64;
+
+/// An entity of the [`SpatialIndex`], with its bounds in world space.
+#[derive(Debug, Clone, Copy)]
+struct SpatialEntry {
+    bounds: Aabb3d,
+    /// The first and last cells covered by the entity, or `None` if it covers too many cells to
+    /// be in the grid.
+    cells: Option<(IVec3, IVec3)>,
+}
+
+/// A grid of the world space bounds of the entities with an [`Aabb`] and a [`GlobalTransform`].
+///
+/// The index is updated in [`PostUpdate`], after the transforms are propagated, so the entities
+/// moved during a frame are found where they were at the end of the previous frame until then.
+#[derive(Resource, Debug)]
+pub struct SpatialIndex {
+    cell_size: f32,
+    cells: HashMap<IVec3, Vec<Entity>>,
+    entries: EntityHashMap<SpatialEntry>,
+    /// The entities covering too many cells to be in the grid.
+    large: EntityHashSet,
+}
+
+impl Default for SpatialIndex {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_CELL_SIZE)
+    }
+}
+
+impl SpatialIndex {
+    /// The default size of the cells, in world units.
+    pub const DEFAULT_CELL_SIZE: f32 = 16.0;
+
+    /// Creates an empty index with cells of `cell_size` world units.
+    ///
+    /// The cells should be about as large as the queries and the entities: smaller cells make
+    /// each entity cover more of them, and larger cells hold more entities to test.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `cell_size` isn't positive.
+    pub fn new(cell_size: f32) -> Self {
+        assert!(cell_size > 0.0, "the cells must have a positive size");
+        Self {
+            cell_size,
+            cells: HashMap::default(),
+            entries: EntityHashMap::default(),
+            large: EntityHashSet::default(),
+        }
+    }
+
+    /// The size of the cells, in world units.
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+
+    /// The number of entities in the index.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the index has no entity.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The bounds of the `entity` in world space, if it is in the index.
+    pub fn bounds(&self, entity: Entity) -> Option<Aabb3d> {
+        self.entries.get(&entity).map(|entry| entry.bounds)
+    }
+
+    /// The first and last cells covering the `bounds`, and their number.
+    fn cells(&self, bounds: &Aabb3d) -> (IVec3, IVec3, i64) {
+        // The float to int casts saturate, so infinite bounds cover a huge number of cells.
+        let first = (bounds.min / self.cell_size).floor().as_ivec3();
+        let last = (bounds.max / self.cell_size).floor().as_ivec3();
+        let count = (last.as_i64vec3() - first.as_i64vec3() + 1).max(I64Vec3::ZERO);
+        let count = count.x.saturating_mul(count.y).saturating_mul(count.z);
+        (first, last, count)
+    }
+
+    /// Adds the `entity` with its `bounds` in world space, or moves it if it is already in the
+    /// index.
+    pub fn insert(&mut self, entity: Entity, bounds: Aabb3d) {
+        let (first, last, count) = self.cells(&bounds);
+        let cells = (count <= MAX_ENTITY_CELLS).then_some((first, last));
+        if let Some(entry) = self.entries.get_mut(&entity) {
+            let previous_cells = entry.cells;
+            entry.bounds = bounds;
+            if previous_cells == cells {
+                return;
+            }
+            self.remove(entity);
+        }
+
+        self.entries.insert(entity, SpatialEntry { bounds, cells });
+        match cells {
+            Some((first, last)) => {
+                for cell in cell_range(first, last) {
+                    self.cells.entry(cell).or_default().push(entity);
+                }
+            }
+            None => {
+                self.large.insert(entity);
+            }
+        }
+    }
+
+    /// Removes the `entity` from the index, returning whether it was in it.
+    pub fn remove(&mut self, entity: Entity) -> bool {
+        let Some(entry) = self.entries.remove(&entity) else {
+            return false;
+        };
+        match entry.cells {
+            Some((first, last)) => {
+                for cell in cell_range(first, last) {
+                    let Some(entities) = self.cells.get_mut(&cell) else {
+                        continue;
+                    };
+                    if let Some(index) = entities.iter().position(|e| *e == entity) {
+                        entities.swap_remove(index);
+                    }
+                    if entities.is_empty() {
+                        self.cells.remove(&cell);
+                    }
+                }
+            }
+            None => {
+                self.large.remove(&entity);
+            }
+        }
+        true
+    }
+
+    /// Calls `f` with the entities whose bounds are in the cells covering the `bounds`, each
+    /// once, and the entities too large to be in the grid.
+    fn for_each_candidate(&self, bounds: &Aabb3d, mut f: impl FnMut(Entity, &Aabb3d)) {
+        let mut visit = |entity: Entity| {
+            if let Some(entry) = self.entries.get(&entity) {
+                f(entity, &entry.bounds);
+            }
+        };
+        let (first, last, count) = self.cells(bounds);
+        if count > self.entries.len() as i64 {
+            // Looking up the cells would be slower than testing all the entities.
+            self.entries.keys().copied().for_each(visit);
+            return;
+        }
+
+        let mut visited = EntityHashSet::default();
+        for cell in cell_range(first, last) {
+            for entity in self.cells.get(&cell).into_iter().flatten() {
+                if visited.insert(*entity) {
+                    visit(*entity);
+                }
+            }
+        }
+        self.large.iter().copied().for_each(visit);
+    }
+
+    /// The entities whose bounds intersect the `aabb`.
+    pub fn intersecting_aabb(&self, aabb: Aabb3d) -> Vec<Entity> {
+        let mut entities = Vec::new();
+        self.for_each_candidate(&aabb, |entity, bounds| {
+            if bounds.intersects(&aabb) {
+                entities.push(entity);
+            }
+        });
+        entities
+    }
+
+    /// The entities whose bounds intersect the `sphere`.
+    pub fn intersecting_sphere(&self, sphere: BoundingSphere) -> Vec<Entity> {
+        let mut entities = Vec::new();
+        self.for_each_candidate(&sphere.aabb_3d(), |entity, bounds| {
+            if bounds.intersects(&sphere) {
+                entities.push(entity);
+            }
+        });
+        entities
+    }
+
+    /// The entities whose bounds intersect the `frustum`, like the ones a camera sees.
+    ///
+    /// A frustum has no bounds to look up the cells of, so all the entities are tested. Like for
+    /// frustum culling, the bounds are tested against each plane of the frustum separately, so
+    /// large entities outside of a corner of the frustum may be returned.
+    pub fn intersecting_frustum(&self, frustum: &Frustum) -> Vec<Entity> {
+        let mut entities = Vec::new();
+        for (entity, entry) in &self.entries {
+            let aabb = Aabb::from_min_max(entry.bounds.min, entry.bounds.max);
+            if frustum.intersects_obb(&aabb, &Default::default(), true, true) {
+                entities.push(*entity);
+            }
+        }
+        entities
+    }
+}
+
+/// The cells from `first` to `last`, included.
+fn cell_range(first: IVec3, last: IVec3) -> impl Iterator<Item = IVec3> {
+    (first.z..=last.z).flat_map(move |z| {
+        (first.y..=last.y).flat_map(move |y| (first.x..=last.x).map(move |x| IVec3::new(x, y, z)))
+    })
+}
+
+/// Finds the entities with an [`Aabb`] in a region of the world, from the [`SpatialIndex`].
+///
+/// ```
+/// # use bevy_ecs::prelude::*;
+/// # use bevy_math::{bounding::BoundingSphere, Vec3};
+/// # use bevy_render::spatial::SpatialQuery;
+/// fn explode(spatial_query: SpatialQuery, mut commands: Commands) {
+///     let blast = BoundingSphere::new(Vec3::ZERO, 5.0);
+///     for entity in spatial_query.intersecting_sphere(blast) {
+///         commands.entity(entity).despawn();
+///     }
+/// }
+/// # bevy_ecs::system::assert_is_system(explode);
+/// ```
+#[derive(SystemParam)]
+pub struct SpatialQuery<'w> {
+    index: Res<'w, SpatialIndex>,
+}
+
+impl<'w> SpatialQuery<'w> {
+    /// The entities whose bounds intersect the `aabb`.
+    pub fn intersecting_aabb(&self, aabb: Aabb3d) -> Vec<Entity> {
+        self.index.intersecting_aabb(aabb)
+    }
+
+    /// The entities whose bounds intersect the `sphere`.
+    pub fn intersecting_sphere(&self, sphere: BoundingSphere) -> Vec<Entity> {
+        self.index.intersecting_sphere(sphere)
+    }
+
+    /// The entities whose bounds intersect the `frustum`.
+    pub fn intersecting_frustum(&self, frustum: &Frustum) -> Vec<Entity> {
+        self.index.intersecting_frustum(frustum)
+    }
+
+    /// The bounds of the `entity` in world space, if it is in the index.
+    pub fn bounds(&self, entity: Entity) -> Option<Aabb3d> {
+        self.index.bounds(entity)
+    }
+}
+
+/// Moves the entities whose [`GlobalTransform`] or [`Aabb`] changed in the [`SpatialIndex`], and
+/// removes the entities which lost their [`Aabb`].
+pub fn update_spatial_index(
+    mut index: ResMut<SpatialIndex>,
+    entities: Query<
+        (Entity, &GlobalTransform, &Aabb),
+        Or<(Changed<GlobalTransform>, Changed<Aabb>)>,
+    >,
+    mut removed: RemovedComponents<Aabb>,
+) {
+    for entity in removed.read() {
+        index.remove(entity);
+    }
+    for (entity, transform, aabb) in &entities {
+        let affine = transform.affine();
+        let center = affine.transform_point3a(aabb.center);
+        let half_extents = Vec3A::new(
+            affine.matrix3.row(0).abs().dot(aabb.half_extents),
+            affine.matrix3.row(1).abs().dot(aabb.half_extents),
+            affine.matrix3.row(2).abs().dot(aabb.half_extents),
+        );
+        index.insert(
+            entity,
+            Aabb3d {
+                min: Vec3::from(center - half_extents),
+                max: Vec3::from(center + half_extents),
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives::HalfSpace;
+    use bevy_ecs::system::RunSystemOnce;
+    use bevy_math::Mat4;
+
+    fn sorted(mut entities: Vec<Entity>) -> Vec<Entity> {
+        entities.sort();
+        entities
+    }
+
+    #[test]
+    fn entities_are_found_in_their_region() {
+        let mut world = World::new();
+        world.insert_resource(SpatialIndex::new(1.0));
+        let unit = Aabb::from_min_max(Vec3::splat(-0.5), Vec3::splat(0.5));
+        let cubes: Vec<_> = (0..5)
+            .map(|i| {
+                let transform = GlobalTransform::from_xyz(i as f32 * 3.0, 0.0, 0.0);
+                world.spawn((transform, unit)).id()
+            })
+            .collect();
+        let ground = world
+            .spawn((
+                GlobalTransform::IDENTITY,
+                Aabb::from_min_max(
+                    Vec3::new(-100.0, -3.0, -100.0),
+                    Vec3::new(100.0, -2.0, 100.0),
+                ),
+            ))
+            .id();
+        world.run_system_once(update_spatial_index);
+
+        let index = world.resource::<SpatialIndex>();
+        assert_eq!(index.len(), 6);
+        assert_eq!(
+            sorted(index.intersecting_sphere(BoundingSphere::new(Vec3::new(4.5, 0.0, 0.0), 1.2))),
+            vec![cubes[1], cubes[2]]
+        );
+        assert_eq!(
+            index.intersecting_aabb(Aabb3d::new(Vec3::new(9.0, 0.0, 0.0), Vec3::splat(0.1))),
+            vec![cubes[3]]
+        );
+        assert_eq!(
+            index.intersecting_aabb(Aabb3d::new(Vec3::new(20.0, -2.5, 0.0), Vec3::splat(0.1))),
+            vec![ground]
+        );
+
+        // A camera looking at the first cubes along the X axis, up to 5 units away from the
+        // origin.
+        let view = Mat4::look_to_rh(Vec3::new(-2.0, 0.0, 0.0), Vec3::X, Vec3::Y);
+        let projection = Mat4::perspective_infinite_reverse_rh(0.2, 1.0, 0.1);
+        let mut frustum = Frustum::from_view_projection(&(projection * view));
+        frustum.half_spaces[5] = HalfSpace::new(Vec3::NEG_X.extend(5.0));
+        // The ground crosses all the planes of the frustum, so it isn't culled.
+        assert_eq!(
+            sorted(index.intersecting_frustum(&frustum)),
+            vec![cubes[0], cubes[1], ground]
+        );
+
+        // Moved and removed entities are updated.
+        world
+            .entity_mut(cubes[0])
+            .insert(GlobalTransform::from_xyz(12.0, 0.0, 0.0));
+        world.entity_mut(cubes[2]).remove::<Aabb>();
+        world.despawn(cubes[3]);
+        world.run_system_once(update_spatial_index);
+
+        let index = world.resource::<SpatialIndex>();
+        assert_eq!(index.len(), 4);
+        assert_eq!(
+            sorted(index.intersecting_aabb(Aabb3d::new(
+                Vec3::new(6.0, 0.0, 0.0),
+                Vec3::new(6.0, 1.0, 1.0)
+            ))),
+            vec![cubes[0], cubes[1], cubes[4]]
+        );
+        assert!(index.bounds(cubes[2]).is_none());
+    }
+}
@@ -1,21 +1,80 @@
 use crate::texture::{Image, ImageFormat, ImageFormatSetting, ImageLoader, ImageLoaderSettings};
 use bevy_asset::saver::{AssetSaver, SavedAsset};
 use futures_lite::{AsyncWriteExt, FutureExt};
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
+use wgpu::TextureFormat;
 
+/// An [`AssetSaver`] that compresses [`Image`]s to the [Basis Universal](https://github.com/BinomialLLC/basis_universal)
+/// format, to be used as the saver of an [asset processor](bevy_asset::processor::AssetProcessor).
+///
+/// Basis Universal textures are transcoded when loaded to a compressed format supported by the
+/// GPU, such as BC7, ASTC or ETC2, which uses a fraction of the memory of uncompressed RGBA8
+/// textures. How each image is compressed is configured by the [`CompressedImageSaverSettings`]
+/// in its `.meta` file.
 pub struct CompressedImageSaver;
 
+/// The kind of data stored in an image compressed by the [`CompressedImageSaver`].
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum CompressedImageKind {
+    /// A color map, compressed in the color space of the loaded image and optimized for how
+    /// colors are perceived.
+    #[default]
+    Color,
+    /// A normal map, which is always compressed in linear space and without perceptual
+    /// optimizations, to preserve the directions of the normals.
+    NormalMap,
+    /// Any other non-color data, like roughness or occlusion maps, compressed in linear space.
+    Linear,
+}
+
+/// The codec used by the [`CompressedImageSaver`].
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum CompressedImageCodec {
+    /// High quality compression, which transcodes well to BC7 and ASTC. Recommended for normal
+    /// maps.
+    #[default]
+    Uastc,
+    /// Low quality compression, which results in much smaller files.
+    Etc1s,
+}
+
+/// The settings of the [`CompressedImageSaver`], which can be set per image in its `.meta` file.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CompressedImageSaverSettings {
+    /// The kind of data stored in the image.
+    pub kind: CompressedImageKind,
+    /// The codec used to compress the image.
+    pub codec: CompressedImageCodec,
+    /// Whether to generate the mipmaps of the image.
+    pub generate_mipmaps: bool,
+}
+
+impl Default for CompressedImageSaverSettings {
+    fn default() -> Self {
+        Self {
+            kind: CompressedImageKind::Color,
+            codec: CompressedImageCodec::Uastc,
+            generate_mipmaps: true,
+        }
+    }
+}
+
 #[non_exhaustive]
 #[derive(Debug, Error)]
 pub enum CompressedImageSaverError {
     #[error(transparent)]
     Io(#[from] std::io::Error),
+    #[error("Cannot compress an image with the texture format {0:?}")]
+    UnsupportedFormat(TextureFormat),
+    #[error("Failed to compress the image: {0:?}")]
+    Compression(basis_universal::CompressorErrorCode),
 }
 
 impl AssetSaver for CompressedImageSaver {
     type Asset = Image;
 
-    type Settings = ();
+    type Settings = CompressedImageSaverSettings;
     type OutputLoader = ImageLoader;
     type Error = CompressedImageSaverError;
 
@@ -23,35 +82,13 @@ impl AssetSaver for CompressedImageSaver {
         &'a self,
         writer: &'a mut bevy_asset::io::Writer,
         image: SavedAsset<'a, Self::Asset>,
-        _settings: &'a Self::Settings,
+        settings: &'a Self::Settings,
     ) -> bevy_utils::BoxedFuture<'a, Result<ImageLoaderSettings, Self::Error>> {
-        // PERF: this should live inside the future, but CompressorParams and Compressor are not Send / can't be owned by the BoxedFuture (which _is_ Send)
-        let mut compressor_params = basis_universal::CompressorParams::new();
-        compressor_params.set_basis_format(basis_universal::BasisTextureFormat::UASTC4x4);
-        compressor_params.set_generate_mipmaps(true);
-        let is_srgb = image.texture_descriptor.format.is_srgb();
-        let color_space = if is_srgb {
-            basis_universal::ColorSpace::Srgb
-        } else {
-            basis_universal::ColorSpace::Linear
-        };
-        compressor_params.set_color_space(color_space);
-        compressor_params.set_uastc_quality_level(basis_universal::UASTC_QUALITY_DEFAULT);
-
-        let mut source_image = compressor_params.source_image_mut(0);
-        let size = image.size();
-        source_image.init(&image.data, size.x, size.y, 4);
-
-        let mut compressor = basis_universal::Compressor::new(4);
-        // SAFETY: the CompressorParams are "valid" to the best of our knowledge. The basis-universal
-        // library bindings note that invalid params might produce undefined behavior.
-        unsafe {
-            compressor.init(&compressor_params);
-            compressor.process().unwrap();
-        }
-        let compressed_basis_data = compressor.basis_file().to_vec();
+        let is_srgb = settings.kind == CompressedImageKind::Color
+            && image.texture_descriptor.format.is_srgb();
+        let compressed_basis_data = compress(&image, settings, is_srgb);
         async move {
-            writer.write_all(&compressed_basis_data).await?;
+            writer.write_all(&compressed_basis_data?).await?;
             Ok(ImageLoaderSettings {
                 format: ImageFormatSetting::Format(ImageFormat::Basis),
                 is_srgb,
@@ -62,3 +99,62 @@ impl AssetSaver for CompressedImageSaver {
         .boxed()
     }
 }
+
+/// Compresses `image` to a Basis Universal file.
+// PERF: this should live inside the future, but CompressorParams and Compressor are not Send / can't be owned by the BoxedFuture (which _is_ Send)
+fn compress(
+    image: &Image,
+    settings: &CompressedImageSaverSettings,
+    is_srgb: bool,
+) -> Result<Vec<u8>, CompressedImageSaverError> {
+    // The compressor only accepts 8-bit RGBA data.
+    let rgba_format = if is_srgb {
+        TextureFormat::Rgba8UnormSrgb
+    } else {
+        TextureFormat::Rgba8Unorm
+    };
+    let converted;
+    let image = match image.texture_descriptor.format {
+        TextureFormat::Rgba8Unorm | TextureFormat::Rgba8UnormSrgb => image,
+        format => {
+            converted = image
+                .convert(rgba_format)
+                .ok_or(CompressedImageSaverError::UnsupportedFormat(format))?;
+            &converted
+        }
+    };
+
+    let mut compressor_params = basis_universal::CompressorParams::new();
+    match settings.codec {
+        CompressedImageCodec::Uastc => {
+            compressor_params.set_basis_format(basis_universal::BasisTextureFormat::UASTC4x4);
+            compressor_params.set_uastc_quality_level(basis_universal::UASTC_QUALITY_DEFAULT);
+        }
+        CompressedImageCodec::Etc1s => {
+            compressor_params.set_basis_format(basis_universal::BasisTextureFormat::ETC1S);
+            compressor_params.set_etc1s_quality_level(basis_universal::ETC1S_QUALITY_DEFAULT);
+        }
+    }
+    compressor_params.set_generate_mipmaps(settings.generate_mipmaps);
+    let color_space = if is_srgb {
+        basis_universal::ColorSpace::Srgb
+    } else {
+        basis_universal::ColorSpace::Linear
+    };
+    compressor_params.set_color_space(color_space);
+
+    let mut source_image = compressor_params.source_image_mut(0);
+    let size = image.size();
+    source_image.init(&image.data, size.x, size.y, 4);
+
+    let mut compressor = basis_universal::Compressor::new(4);
+    // SAFETY: the CompressorParams are "valid" to the best of our knowledge. The basis-universal
+    // library bindings note that invalid params might produce undefined behavior.
+    unsafe {
+        compressor.init(&compressor_params);
+        compressor
+            .process()
+            .map_err(CompressedImageSaverError::Compression)?;
+    }
+    Ok(compressor.basis_file().to_vec())
+}
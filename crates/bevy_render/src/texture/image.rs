@@ -11,7 +11,7 @@ use crate::{
     renderer::{RenderDevice, RenderQueue},
     texture::BevyDefault,
 };
-use bevy_asset::Asset;
+use bevy_asset::{Asset, MeasuredAsset};
 use bevy_derive::{Deref, DerefMut};
 use bevy_ecs::system::{lifetimeless::SRes, Resource, SystemParamItem};
 use bevy_math::{AspectRatio, UVec2, Vec2};
@@ -19,7 +19,7 @@ use bevy_reflect::Reflect;
 use serde::{Deserialize, Serialize};
 use std::hash::Hash;
 use thiserror::Error;
-use wgpu::{Extent3d, TextureDimension, TextureFormat, TextureViewDescriptor};
+use wgpu::{Extent3d, TextureDimension, TextureFormat, TextureUsages, TextureViewDescriptor};
 
 pub const TEXTURE_ASSET_INDEX: u64 = 0;
 pub const SAMPLER_ASSET_INDEX: u64 = 1;
@@ -454,6 +454,12 @@ impl<'a> From<wgpu::SamplerDescriptor<'a>> for ImageSamplerDescriptor {
     }
 }
 
+impl MeasuredAsset for Image {
+    fn memory_size(&self) -> usize {
+        self.data.len()
+    }
+}
+
 impl Default for Image {
     /// default is a 1x1x1 all '1.0' texture
     fn default() -> Self {
@@ -545,6 +551,30 @@ impl Image {
         value
     }
 
+    /// Creates a new 2D image to be used as a [`RenderTarget`](crate::camera::RenderTarget) of a
+    /// camera, filled with transparent black.
+    ///
+    /// The image can be read back from the GPU after rendering, e.g. with
+    /// [`HeadlessRenderExt::read_image`](crate::headless::HeadlessRenderExt::read_image).
+    pub fn new_render_target(size: UVec2, format: TextureFormat) -> Self {
+        let mut image = Image::new_fill(
+            Extent3d {
+                width: size.x,
+                height: size.y,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            &vec![0; format.pixel_size()],
+            format,
+            RenderAssetUsages::default(),
+        );
+        image.texture_descriptor.usage = TextureUsages::TEXTURE_BINDING
+            | TextureUsages::COPY_SRC
+            | TextureUsages::COPY_DST
+            | TextureUsages::RENDER_ATTACHMENT;
+        image
+    }
+
     /// Returns the width of a 2D image.
     #[inline]
     pub fn width(&self) -> u32 {
@@ -967,4 +997,15 @@ mod test {
         let image = Image::default();
         assert_eq!(Vec2::ONE, image.size_f32());
     }
+
+    #[test]
+    fn render_target_image() {
+        let image = Image::new_render_target(UVec2::new(16, 8), TextureFormat::Rgba8UnormSrgb);
+        assert_eq!(image.size(), UVec2::new(16, 8));
+        assert_eq!(image.data.len(), 16 * 8 * 4);
+        assert!(image
+            .texture_descriptor
+            .usage
+            .contains(TextureUsages::COPY_SRC | TextureUsages::RENDER_ATTACHMENT));
+    }
 }
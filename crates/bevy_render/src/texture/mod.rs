@@ -37,10 +37,13 @@ pub use texture_attachment::*;
 pub use texture_cache::*;
 
 use crate::{
-    render_asset::RenderAssetPlugin, renderer::RenderDevice, Render, RenderApp, RenderSet,
+    render_asset::RenderAssetPlugin,
+    renderer::RenderDevice,
+    view::{touch_visible_assets, VisibilitySystems},
+    Render, RenderApp, RenderSet,
 };
-use bevy_app::{App, Plugin};
-use bevy_asset::{AssetApp, Assets, Handle};
+use bevy_app::{App, Plugin, PostUpdate};
+use bevy_asset::{AssetApp, AssetBudget, Assets, Handle};
 use bevy_ecs::prelude::*;
 
 // TODO: replace Texture names with Image names?
@@ -87,7 +90,13 @@ impl Plugin for ImagePlugin {
         app.add_plugins(RenderAssetPlugin::<Image>::default())
             .register_type::<Image>()
             .init_asset::<Image>()
-            .register_asset_reflect::<Image>();
+            .register_asset_reflect::<Image>()
+            .add_systems(
+                PostUpdate,
+                touch_visible_assets::<Image>
+                    .run_if(resource_exists::<AssetBudget<Image>>)
+                    .after(VisibilitySystems::CheckVisibility),
+            );
         app.world
             .resource_mut::<Assets<Image>>()
             .insert(Handle::default(), Image::default());
@@ -99,8 +108,10 @@ impl Plugin for ImagePlugin {
             processor.register_processor::<bevy_asset::processor::LoadAndSave<ImageLoader, CompressedImageSaver>>(
                 CompressedImageSaver.into(),
             );
-            processor
-                .set_default_processor::<bevy_asset::processor::LoadAndSave<ImageLoader, CompressedImageSaver>>("png");
+            for extension in ["png", "jpg", "jpeg"] {
+                processor
+                    .set_default_processor::<bevy_asset::processor::LoadAndSave<ImageLoader, CompressedImageSaver>>(extension);
+            }
         }
 
         if let Ok(render_app) = app.get_sub_app_mut(RenderApp) {
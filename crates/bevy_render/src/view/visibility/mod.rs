@@ -4,7 +4,7 @@ use bevy_derive::Deref;
 pub use render_layers::*;
 
 use bevy_app::{Plugin, PostUpdate};
-use bevy_asset::{Assets, Handle};
+use bevy_asset::{AssetBudget, Assets, Handle, MeasuredAsset};
 use bevy_ecs::prelude::*;
 use bevy_hierarchy::{Children, Parent};
 use bevy_reflect::{std_traits::ReflectDefault, Reflect};
@@ -454,6 +454,21 @@ pub fn check_visibility(
     }
 }
 
+/// Marks the assets of type `A` used by visible entities as used in their [`AssetBudget`], so
+/// that they're not evicted, or reloaded if they were.
+///
+/// This should run after [`VisibilitySystems::CheckVisibility`].
+pub fn touch_visible_assets<A: MeasuredAsset>(
+    mut budget: ResMut<AssetBudget<A>>,
+    query: Query<(&Handle<A>, &ViewVisibility)>,
+) {
+    for (handle, view_visibility) in &query {
+        if view_visibility.get() {
+            budget.touch(handle);
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use bevy_app::prelude::*;
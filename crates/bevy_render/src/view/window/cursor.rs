@@ -0,0 +1,146 @@
+use crate::texture::Image;
+use bevy_asset::{AssetEvent, AssetId, Assets, Handle};
+use bevy_ecs::{entity::EntityHashMap, prelude::*};
+use bevy_math::UVec2;
+use bevy_reflect::{std_traits::ReflectDefault, Reflect};
+use bevy_time::{Real, Time};
+use bevy_utils::{tracing::warn, Duration, HashMap};
+use bevy_window::CustomCursor;
+use wgpu::TextureFormat;
+
+/// Sets the [`CustomCursor`] of the window entity from an [`Image`], or from a sequence of images
+/// to animate it.
+///
+/// The images must be kept in the main world, by the
+/// [`RenderAssetUsages::MAIN_WORLD`](crate::render_asset::RenderAssetUsages::MAIN_WORLD) usage.
+/// Removing this component also removes the [`CustomCursor`].
+///
+/// On the platforms where the windowing backend can't display custom cursors, `bevy_ui` draws the
+/// image in place of the cursor icon instead.
+#[derive(Component, Debug, Default, Clone, PartialEq, Reflect)]
+#[reflect(Component, Default)]
+pub struct CursorImage {
+    /// The frames of the cursor, a single one if it isn't animated.
+    pub frames: Vec<Handle<Image>>,
+    /// The pixel of the frames pointing at the position of the cursor, from their top left.
+    pub hotspot: UVec2,
+    /// How long each frame of an animated cursor is displayed.
+    pub frame_duration: Duration,
+}
+
+impl CursorImage {
+    /// A cursor displaying the `image`, pointing at the `hotspot` pixel.
+    pub fn new(image: Handle<Image>, hotspot: UVec2) -> Self {
+        Self {
+            frames: vec![image],
+            hotspot,
+            frame_duration: Duration::ZERO,
+        }
+    }
+
+    /// A cursor displaying the `frames` in a loop, each for `frame_duration`.
+    pub fn animated(frames: Vec<Handle<Image>>, hotspot: UVec2, frame_duration: Duration) -> Self {
+        Self {
+            frames,
+            hotspot,
+            frame_duration,
+        }
+    }
+
+    /// The frame displayed after `elapsed` time, the [`Time<Real>`] elapsed time for the cursors of
+    /// the windows.
+    pub fn frame(&self, elapsed: Duration) -> Option<&Handle<Image>> {
+        if self.frames.is_empty() {
+            return None;
+        }
+        let index = match self.frame_duration.as_nanos() {
+            0 => 0,
+            frame_duration => (elapsed.as_nanos() / frame_duration) as usize % self.frames.len(),
+        };
+        self.frames.get(index)
+    }
+}
+
+/// The frames converted to [`CustomCursor`]s, and the frame displayed by each window.
+#[derive(Default)]
+pub(crate) struct CursorImageCache {
+    cursors: HashMap<(AssetId<Image>, UVec2), CustomCursor>,
+    displayed: EntityHashMap<(AssetId<Image>, UVec2)>,
+}
+
+/// Sets the cursors of the windows with a [`CursorImage`].
+pub(crate) fn update_cursor_images(
+    mut commands: Commands,
+    windows: Query<(Entity, Ref<CursorImage>)>,
+    mut removed: RemovedComponents<CursorImage>,
+    mut image_events: EventReader<AssetEvent<Image>>,
+    images: Res<Assets<Image>>,
+    time: Res<Time<Real>>,
+    mut cache: Local<CursorImageCache>,
+) {
+    for entity in removed.read() {
+        if cache.displayed.remove(&entity).is_some() {
+            if let Some(mut entity) = commands.get_entity(entity) {
+                entity.remove::<CustomCursor>();
+            }
+        }
+    }
+
+    let mut changed_images = Vec::new();
+    for event in image_events.read() {
+        if let AssetEvent::Modified { id } | AssetEvent::Removed { id } = event {
+            cache.cursors.retain(|(image, _), _| image != id);
+            changed_images.push(*id);
+        }
+    }
+
+    for (entity, cursor_image) in &windows {
+        let Some(frame) = cursor_image.frame(time.elapsed()) else {
+            continue;
+        };
+        let key = (frame.id(), cursor_image.hotspot);
+        if !cursor_image.is_changed()
+            && !changed_images.contains(&key.0)
+            && cache.displayed.get(&entity) == Some(&key)
+        {
+            continue;
+        }
+
+        if !cache.cursors.contains_key(&key) {
+            // Wait for the frame to be loaded.
+            let Some(image) = images.get(frame) else {
+                continue;
+            };
+            let Some(rgba) = image.convert(TextureFormat::Rgba8UnormSrgb) else {
+                warn!("Could not convert the cursor image to RGBA");
+                continue;
+            };
+            let size = rgba.size();
+            let Some(cursor) = CustomCursor::from_rgba(rgba.data, size.x, size.y, key.1) else {
+                warn!("The hotspot of the cursor is outside of its image");
+                continue;
+            };
+            cache.cursors.insert(key, cursor);
+        }
+        commands.entity(entity).insert(cache.cursors[&key].clone());
+        cache.displayed.insert(entity, key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn animated_cursor_loops_over_its_frames() {
+        let frames: Vec<Handle<Image>> = (0..3u128).map(Handle::weak_from_u128).collect();
+        let cursor = CursorImage::animated(frames.clone(), UVec2::ZERO, Duration::from_millis(100));
+        assert_eq!(cursor.frame(Duration::from_millis(50)), Some(&frames[0]));
+        assert_eq!(cursor.frame(Duration::from_millis(250)), Some(&frames[2]));
+        assert_eq!(cursor.frame(Duration::from_millis(320)), Some(&frames[0]));
+
+        let cursor = CursorImage::new(frames[1].clone(), UVec2::ZERO);
+        assert_eq!(cursor.frame(Duration::from_secs(10)), Some(&frames[1]));
+        assert_eq!(CursorImage::default().frame(Duration::ZERO), None);
+    }
+}
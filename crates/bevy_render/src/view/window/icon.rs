@@ -0,0 +1,46 @@
+use crate::texture::Image;
+use bevy_asset::{AssetEvent, Assets, Handle};
+use bevy_ecs::prelude::*;
+use bevy_reflect::{std_traits::ReflectDefault, Reflect};
+use bevy_utils::tracing::warn;
+use bevy_window::{Window, WindowIcon};
+use wgpu::TextureFormat;
+
+/// Sets the [`Window::icon`] of the window entity from an [`Image`], once it's loaded and
+/// whenever it changes.
+///
+/// The image must be kept in the main world, by the
+/// [`RenderAssetUsages::MAIN_WORLD`](crate::render_asset::RenderAssetUsages::MAIN_WORLD) usage.
+#[derive(Component, Debug, Default, Clone, PartialEq, Eq, Reflect)]
+#[reflect(Component, Default)]
+pub struct WindowIconImage(pub Handle<Image>);
+
+/// Sets the icons of the windows with a [`WindowIconImage`].
+pub(crate) fn update_window_icon_images(
+    mut windows: Query<(Ref<WindowIconImage>, &mut Window)>,
+    mut image_events: EventReader<AssetEvent<Image>>,
+    images: Res<Assets<Image>>,
+) {
+    let changed_images: Vec<_> = image_events
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+
+    for (icon_image, mut window) in &mut windows {
+        if !icon_image.is_changed() && !changed_images.contains(&icon_image.0.id()) {
+            continue;
+        }
+        let Some(image) = images.get(&icon_image.0) else {
+            continue;
+        };
+        let Some(rgba) = image.convert(TextureFormat::Rgba8UnormSrgb) else {
+            warn!("Could not convert the window icon image to RGBA");
+            continue;
+        };
+        let size = rgba.size();
+        window.icon = WindowIcon::from_rgba(rgba.data, size.x, size.y);
+    }
+}
@@ -3,11 +3,13 @@ use crate::{
         BindGroupEntries, PipelineCache, SpecializedRenderPipelines, SurfaceTexture, TextureView,
     },
     renderer::{RenderAdapter, RenderDevice, RenderInstance},
-    texture::TextureFormatPixelInfo,
+    texture::{Image, TextureFormatPixelInfo},
     Extract, ExtractSchedule, Render, RenderApp, RenderSet,
 };
-use bevy_app::{App, Plugin};
+use bevy_app::{App, Plugin, PostUpdate};
+use bevy_asset::Assets;
 use bevy_ecs::{entity::EntityHashMap, prelude::*};
+use bevy_time::{Real, Time};
 use bevy_utils::{default, tracing::debug, HashSet};
 use bevy_window::{
     CompositeAlphaMode, PresentMode, PrimaryWindow, RawHandleWrapper, Window, WindowClosed,
@@ -20,8 +22,15 @@ use wgpu::{
     BufferUsages, SurfaceTargetUnsafe, TextureFormat, TextureUsages, TextureViewDescriptor,
 };
 
+mod cursor;
+mod icon;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod recording;
 pub mod screenshot;
 
+pub use cursor::CursorImage;
+pub use icon::WindowIconImage;
+
 use screenshot::{
     ScreenshotManager, ScreenshotPlugin, ScreenshotPreparedState, ScreenshotToScreenPipeline,
 };
@@ -32,7 +41,20 @@ pub struct WindowRenderPlugin;
 
 impl Plugin for WindowRenderPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins(ScreenshotPlugin);
+        app.add_plugins(ScreenshotPlugin)
+            .register_type::<WindowIconImage>()
+            .register_type::<CursorImage>()
+            .add_systems(
+                PostUpdate,
+                (
+                    icon::update_window_icon_images.run_if(resource_exists::<Assets<Image>>),
+                    cursor::update_cursor_images
+                        .run_if(resource_exists::<Assets<Image>>)
+                        .run_if(resource_exists::<Time<Real>>),
+                ),
+            );
+        #[cfg(not(target_arch = "wasm32"))]
+        app.add_plugins(recording::RecordingPlugin);
 
         if let Ok(render_app) = app.get_sub_app_mut(RenderApp) {
             render_app
@@ -73,6 +95,9 @@ pub struct ExtractedWindow {
     pub size_changed: bool,
     pub present_mode_changed: bool,
     pub alpha_mode: CompositeAlphaMode,
+    /// Whether the window is transparent, to choose an alpha mode blending it with what's behind
+    /// it when the [`alpha_mode`](Self::alpha_mode) is [`CompositeAlphaMode::Auto`].
+    pub transparent: bool,
     pub screenshot_func: Option<screenshot::ScreenshotFn>,
 }
 
@@ -139,6 +164,7 @@ fn extract_windows(
             swap_chain_texture_format: None,
             present_mode_changed: false,
             alpha_mode: window.composite_alpha_mode,
+            transparent: window.transparent,
             screenshot_func: None,
             screenshot_memory: None,
         });
@@ -199,6 +225,9 @@ struct SurfaceData {
     // TODO: what lifetime should this be?
     surface: wgpu::Surface<'static>,
     format: TextureFormat,
+    /// The alpha mode used for transparent windows with [`CompositeAlphaMode::Auto`], if the
+    /// surface supports blending with what's behind it.
+    transparent_alpha_mode: Option<wgpu::CompositeAlphaMode>,
 }
 
 #[derive(Resource, Default)]
@@ -274,6 +303,9 @@ pub fn prepare_windows(
             // has to wait for the cpu to finish to start on the next frame.
             desired_maximum_frame_latency: 2,
             alpha_mode: match window.alpha_mode {
+                CompositeAlphaMode::Auto if window.transparent => surface_data
+                    .transparent_alpha_mode
+                    .unwrap_or(wgpu::CompositeAlphaMode::Auto),
                 CompositeAlphaMode::Auto => wgpu::CompositeAlphaMode::Auto,
                 CompositeAlphaMode::Opaque => wgpu::CompositeAlphaMode::Opaque,
                 CompositeAlphaMode::PreMultiplied => wgpu::CompositeAlphaMode::PreMultiplied,
@@ -462,6 +494,13 @@ pub fn create_surfaces(
                         .expect("Failed to create wgpu surface")
                 };
                 let caps = surface.get_capabilities(&render_adapter);
+                let transparent_alpha_mode = [
+                    wgpu::CompositeAlphaMode::PostMultiplied,
+                    wgpu::CompositeAlphaMode::PreMultiplied,
+                    wgpu::CompositeAlphaMode::Inherit,
+                ]
+                .into_iter()
+                .find(|alpha_mode| caps.alpha_modes.contains(alpha_mode));
                 let formats = caps.formats;
                 // For future HDR output support, we'll need to request a format that supports HDR,
                 // but as of wgpu 0.15 that is not yet supported.
@@ -477,7 +516,11 @@ pub fn create_surfaces(
                     }
                 }
 
-                SurfaceData { surface, format }
+                SurfaceData {
+                    surface,
+                    format,
+                    transparent_alpha_mode,
+                }
             });
     }
 }
@@ -0,0 +1,715 @@
+use std::{
+    collections::BTreeMap,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex, PoisonError,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use async_channel::{Receiver, Sender};
+use bevy_app::{App, Last, Plugin};
+use bevy_asset::AssetId;
+use bevy_ecs::{entity::EntityHashMap, prelude::*};
+use bevy_log::{error, warn};
+use bevy_time::TimeUpdateStrategy;
+use bevy_utils::error_once;
+use bevy_window::{PrimaryWindow, Window};
+use thiserror::Error;
+use wgpu::{BufferUsages, CommandEncoder, Extent3d, TextureFormat, TextureUsages};
+
+use crate::{
+    camera::{Camera, RenderTarget},
+    prelude::Image,
+    render_asset::RenderAssets,
+    render_resource::{Buffer, Texture},
+    renderer::RenderDevice,
+    texture::TextureFormatPixelInfo,
+    Extract, ExtractSchedule, Render, RenderApp, RenderSet,
+};
+
+use super::screenshot::{
+    get_aligned_size, layout_data, read_buffer_to_image, ScreenshotFn, ScreenshotManager,
+};
+
+/// How the frames of a recording are captured.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RecordingMode {
+    /// Frames are captured as the app runs. Frames are dropped when more than
+    /// [`max_pending_frames`](RecordingSettings::max_pending_frames) wait to be encoded.
+    #[default]
+    RealTime,
+    /// [`Time`](bevy_time::Time) advances by exactly one frame of the
+    /// [`framerate`](RecordingSettings::framerate) per update, and the app waits for the encoder
+    /// instead of dropping frames.
+    ///
+    /// Use this for trailers and visual regression tests, so that the recording doesn't depend
+    /// on how fast the app runs. Recordings in this mode running at the same time should have
+    /// the same framerate.
+    FixedFramestep,
+}
+
+/// Where the frames of a recording are written.
+pub enum RecordingOutput {
+    /// Saves the frames as numbered PNG images, `00000.png`, `00001.png`..., in the given
+    /// directory, which is created if needed.
+    ///
+    /// Requires the `png` feature.
+    ImageSequence(PathBuf),
+    /// Encodes the frames into a video with the `ffmpeg` executable, which must be in the `PATH`.
+    ///
+    /// The container and codec are chosen by `ffmpeg` from the extension of the path, such as
+    /// `.mp4` or `.webm`.
+    #[cfg(feature = "video_recording")]
+    Video(PathBuf),
+    /// Calls the function with the index and the image of each frame, in order, on the encoder
+    /// thread.
+    Custom(Box<dyn FnMut(u64, Image) -> Result<(), RecordingError> + Send>),
+}
+
+/// Settings of a recording started with [`Recorder::start`].
+pub struct RecordingSettings {
+    /// Where the frames are written.
+    pub output: RecordingOutput,
+    /// How the frames are captured.
+    pub mode: RecordingMode,
+    /// The framerate of the recording, used as the timestep of
+    /// [`RecordingMode::FixedFramestep`] and as the framerate of videos.
+    pub framerate: f64,
+    /// The maximum number of frames which were captured but aren't encoded yet.
+    pub max_pending_frames: usize,
+}
+
+impl RecordingSettings {
+    /// Creates settings to record frames in real time at 60 frames per second to `output`.
+    pub fn new(output: RecordingOutput) -> Self {
+        Self {
+            output,
+            mode: RecordingMode::RealTime,
+            framerate: 60.0,
+            max_pending_frames: 8,
+        }
+    }
+}
+
+/// Errors that can occur while recording.
+#[derive(Error, Debug)]
+pub enum RecordingError {
+    /// The window or camera is already being recorded.
+    #[error("this window or camera is already being recorded")]
+    AlreadyRecording,
+    /// The frames have a texture format which can't be converted to an image.
+    #[error("frames with the texture format {0:?} cannot be recorded")]
+    UnsupportedFormat(TextureFormat),
+    /// The size of the frames changed during the recording of a video.
+    #[error("the frame size changed during the recording of a video")]
+    FrameSizeChanged,
+    /// A frame couldn't be saved.
+    #[error("cannot save the frame: {0}")]
+    Image(#[from] image::ImageError),
+    /// An IO error occurred.
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    /// The video encoder failed.
+    #[error("the video encoder failed with {0}")]
+    Encoder(std::process::ExitStatus),
+}
+
+type Frame = (u64, Option<Image>);
+
+/// A resource which allows for recording the frames rendered to windows and cameras.
+///
+/// Between [`start`](Self::start) and [`stop`](Self::stop), each frame of the window or camera
+/// is sent through a bounded channel to an encoder thread, which writes it to the
+/// [`RecordingOutput`]. Recording a camera which renders to a window captures the whole window.
+/// The image a camera renders to must have the [`TextureUsages::COPY_SRC`] usage.
+#[derive(Resource, Default)]
+pub struct Recorder {
+    recordings: EntityHashMap<Recording>,
+    // The time update strategy to restore once the fixed framestep recordings stopped.
+    previous_time_strategy: Option<TimeUpdateStrategy>,
+    // this is in a mutex to enable extraction with only an immutable reference
+    image_captures: Mutex<Vec<(AssetId<Image>, ScreenshotFn)>>,
+}
+
+struct Recording {
+    sender: Sender<Frame>,
+    mode: RecordingMode,
+    framerate: f64,
+    max_pending_frames: usize,
+    next_frame: u64,
+    pending_frames: Arc<AtomicUsize>,
+    captured_frames: u64,
+    dropped_frames: u64,
+    encoder: JoinHandle<Result<u64, RecordingError>>,
+}
+
+impl Recorder {
+    /// Starts recording the frames rendered to the window or camera `target`.
+    pub fn start(
+        &mut self,
+        target: Entity,
+        settings: RecordingSettings,
+    ) -> Result<(), RecordingError> {
+        if self.recordings.contains_key(&target) {
+            return Err(RecordingError::AlreadyRecording);
+        }
+
+        let max_pending_frames = settings.max_pending_frames.max(1);
+        let (sender, receiver) = async_channel::bounded(max_pending_frames);
+        let pending_frames = Arc::new(AtomicUsize::new(0));
+        let encoder = FrameEncoder::new(settings.output, settings.framerate)?;
+        let encoder = {
+            let pending_frames = pending_frames.clone();
+            thread::Builder::new()
+                .name("recording encoder".to_string())
+                .spawn(move || encoder.run(&receiver, &pending_frames))?
+        };
+
+        self.recordings.insert(
+            target,
+            Recording {
+                sender,
+                mode: settings.mode,
+                framerate: settings.framerate,
+                max_pending_frames,
+                next_frame: 0,
+                pending_frames,
+                captured_frames: 0,
+                dropped_frames: 0,
+                encoder,
+            },
+        );
+        Ok(())
+    }
+
+    /// Stops recording the window or camera `target`.
+    ///
+    /// The frames already captured are still encoded, see [`FinishedRecording`].
+    pub fn stop(&mut self, target: Entity) -> Option<FinishedRecording> {
+        let recording = self.recordings.remove(&target)?;
+        Some(FinishedRecording {
+            captured_frames: recording.captured_frames,
+            dropped_frames: recording.dropped_frames,
+            encoder: recording.encoder,
+        })
+    }
+
+    /// Returns `true` if the window or camera `target` is being recorded.
+    pub fn is_recording(&self, target: Entity) -> bool {
+        self.recordings.contains_key(&target)
+    }
+}
+
+/// A recording stopped with [`Recorder::stop`], whose last frames may still be encoded.
+pub struct FinishedRecording {
+    captured_frames: u64,
+    dropped_frames: u64,
+    encoder: JoinHandle<Result<u64, RecordingError>>,
+}
+
+impl FinishedRecording {
+    /// The number of frames captured during the recording.
+    pub fn captured_frames(&self) -> u64 {
+        self.captured_frames
+    }
+
+    /// The number of frames dropped because the encoder couldn't keep up.
+    pub fn dropped_frames(&self) -> u64 {
+        self.dropped_frames
+    }
+
+    /// Returns `true` once all the frames are written.
+    pub fn is_finished(&self) -> bool {
+        self.encoder.is_finished()
+    }
+
+    /// Blocks until all the frames are written, returning the number of frames written.
+    ///
+    /// The last frames are read back from the GPU as the app renders, so this shouldn't be
+    /// called from the app before [`is_finished`](Self::is_finished) returns `true`.
+    pub fn wait(self) -> Result<u64, RecordingError> {
+        self.encoder
+            .join()
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+    }
+}
+
+pub struct RecordingPlugin;
+
+impl Plugin for RecordingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Recorder>()
+            .add_systems(Last, request_recorded_frames);
+
+        if let Ok(render_app) = app.get_sub_app_mut(RenderApp) {
+            render_app
+                .init_resource::<ImageCaptures>()
+                .add_systems(ExtractSchedule, extract_image_captures)
+                .add_systems(
+                    Render,
+                    prepare_image_captures.in_set(RenderSet::PrepareResources),
+                );
+        }
+    }
+}
+
+/// A frame requested from the renderer, sent to the encoder once captured, or as missing if the
+/// request is dropped, so that the encoder doesn't wait for it.
+struct FrameSlot {
+    index: u64,
+    sender: Option<Sender<Frame>>,
+}
+
+impl FrameSlot {
+    fn send(mut self, image: Image) {
+        if let Some(sender) = self.sender.take() {
+            // The channel can't be full, as it's bounded by the number of pending frames.
+            let _ = sender.try_send((self.index, Some(image)));
+        }
+    }
+}
+
+impl Drop for FrameSlot {
+    fn drop(&mut self) {
+        if let Some(sender) = self.sender.take() {
+            let _ = sender.try_send((self.index, None));
+        }
+    }
+}
+
+enum CaptureSource {
+    Window(Entity),
+    Image(AssetId<Image>),
+}
+
+fn request_recorded_frames(
+    mut recorder: ResMut<Recorder>,
+    mut screenshot_manager: ResMut<ScreenshotManager>,
+    time_strategy: Option<ResMut<TimeUpdateStrategy>>,
+    render_device: Option<Res<RenderDevice>>,
+    windows: Query<(), With<Window>>,
+    cameras: Query<&Camera>,
+    primary_window: Query<Entity, With<PrimaryWindow>>,
+) {
+    let Recorder {
+        recordings,
+        previous_time_strategy,
+        image_captures,
+    } = &mut *recorder;
+    let mut stopped = Vec::new();
+
+    for (&target, recording) in recordings.iter_mut() {
+        if recording.encoder.is_finished() {
+            stopped.push(target);
+            continue;
+        }
+
+        let source = if windows.contains(target) {
+            Some(CaptureSource::Window(target))
+        } else if let Ok(camera) = cameras.get(target) {
+            match &camera.target {
+                RenderTarget::Window(window_ref) => window_ref
+                    .normalize(primary_window.get_single().ok())
+                    .map(|window_ref| CaptureSource::Window(window_ref.entity())),
+                RenderTarget::Image(image) => Some(CaptureSource::Image(image.id())),
+                RenderTarget::TextureView(_) => None,
+            }
+        } else {
+            None
+        };
+        let Some(source) = source else {
+            error!("Cannot record {target:?}, which isn't a window or a camera rendering to a window or an image");
+            stopped.push(target);
+            continue;
+        };
+
+        if recording.pending_frames.load(Ordering::Acquire) >= recording.max_pending_frames {
+            match recording.mode {
+                RecordingMode::RealTime => {
+                    recording.dropped_frames += 1;
+                    continue;
+                }
+                RecordingMode::FixedFramestep => {
+                    while recording.pending_frames.load(Ordering::Acquire)
+                        >= recording.max_pending_frames
+                        && !recording.encoder.is_finished()
+                    {
+                        // The frames are read back when the device is polled, which the renderer
+                        // won't do while the app waits.
+                        if let Some(render_device) = &render_device {
+                            render_device.wgpu_device().poll(wgpu::Maintain::Poll);
+                        }
+                        thread::sleep(Duration::from_millis(1));
+                    }
+                }
+            }
+        }
+
+        recording.pending_frames.fetch_add(1, Ordering::AcqRel);
+        let slot = FrameSlot {
+            index: recording.next_frame,
+            sender: Some(recording.sender.clone()),
+        };
+        recording.next_frame += 1;
+        let callback = move |image| slot.send(image);
+        match source {
+            CaptureSource::Window(window) => {
+                // The callback is dropped if the request fails, which skips the frame.
+                if screenshot_manager
+                    .take_screenshot(window, callback)
+                    .is_err()
+                {
+                    warn!("Dropped a recorded frame of {target:?}, as a screenshot of the window was requested in the same frame");
+                    recording.dropped_frames += 1;
+                    continue;
+                }
+            }
+            CaptureSource::Image(image) => image_captures
+                .get_mut()
+                .unwrap_or_else(PoisonError::into_inner)
+                .push((image, Box::new(callback))),
+        }
+        recording.captured_frames += 1;
+    }
+
+    for target in stopped {
+        let Some(recording) = recordings.remove(&target) else {
+            continue;
+        };
+        if recording.encoder.is_finished() {
+            match recording.encoder.join() {
+                Ok(Err(err)) => error!("Recording of {target:?} failed: {err}"),
+                Ok(Ok(_)) => {}
+                Err(panic) => std::panic::resume_unwind(panic),
+            }
+        }
+    }
+
+    let Some(mut time_strategy) = time_strategy else {
+        return;
+    };
+    let fixed_framerate = recordings
+        .values()
+        .filter(|recording| recording.mode == RecordingMode::FixedFramestep)
+        .map(|recording| recording.framerate)
+        .reduce(f64::max);
+    match fixed_framerate {
+        Some(framerate) => {
+            let timestep =
+                TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(1.0 / framerate));
+            let previous = std::mem::replace(&mut *time_strategy, timestep);
+            previous_time_strategy.get_or_insert(previous);
+        }
+        None => {
+            if let Some(previous) = previous_time_strategy.take() {
+                *time_strategy = previous;
+            }
+        }
+    }
+}
+
+/// Writes the frames of a recording to its output, on the encoder thread.
+struct FrameEncoder {
+    output: RecordingOutput,
+    #[cfg_attr(not(feature = "video_recording"), allow(dead_code))]
+    framerate: f64,
+    #[cfg(feature = "video_recording")]
+    video: Option<VideoEncoder>,
+}
+
+impl FrameEncoder {
+    fn new(output: RecordingOutput, framerate: f64) -> Result<Self, RecordingError> {
+        if let RecordingOutput::ImageSequence(directory) = &output {
+            std::fs::create_dir_all(directory)?;
+        }
+        Ok(Self {
+            output,
+            framerate,
+            #[cfg(feature = "video_recording")]
+            video: None,
+        })
+    }
+
+    fn run(
+        mut self,
+        receiver: &Receiver<Frame>,
+        pending_frames: &AtomicUsize,
+    ) -> Result<u64, RecordingError> {
+        // Frames can arrive out of order, as they are read back on different threads.
+        let mut frames = BTreeMap::new();
+        let mut next_frame = 0;
+        let mut written_frames = 0;
+
+        while let Ok((index, image)) = receiver.recv_blocking() {
+            frames.insert(index, image);
+            while let Some(image) = frames.remove(&next_frame) {
+                next_frame += 1;
+                if let Some(image) = image {
+                    self.write(written_frames, image)?;
+                    written_frames += 1;
+                }
+                pending_frames.fetch_sub(1, Ordering::AcqRel);
+            }
+        }
+
+        #[cfg(feature = "video_recording")]
+        if let Some(video) = self.video {
+            video.finish()?;
+        }
+        Ok(written_frames)
+    }
+
+    fn write(&mut self, index: u64, image: Image) -> Result<(), RecordingError> {
+        let format = image.texture_descriptor.format;
+        match &mut self.output {
+            RecordingOutput::ImageSequence(directory) => {
+                // discard the alpha channel which stores brightness values when HDR is enabled,
+                // like screenshots do
+                let image = image
+                    .try_into_dynamic()
+                    .map_err(|_| RecordingError::UnsupportedFormat(format))?
+                    .to_rgb8();
+                image.save_with_format(
+                    directory.join(format!("{index:05}.png")),
+                    image::ImageFormat::Png,
+                )?;
+            }
+            #[cfg(feature = "video_recording")]
+            RecordingOutput::Video(path) => {
+                let image = image
+                    .try_into_dynamic()
+                    .map_err(|_| RecordingError::UnsupportedFormat(format))?
+                    .to_rgb8();
+                let size = image.dimensions();
+                let video = match &mut self.video {
+                    Some(video) if video.size != size => {
+                        return Err(RecordingError::FrameSizeChanged)
+                    }
+                    Some(video) => video,
+                    None => self
+                        .video
+                        .insert(VideoEncoder::new(path, size, self.framerate)?),
+                };
+                video.write(image.as_raw())?;
+            }
+            RecordingOutput::Custom(write) => write(index, image)?,
+        }
+        Ok(())
+    }
+}
+
+/// Pipes raw RGB frames to an `ffmpeg` process.
+#[cfg(feature = "video_recording")]
+struct VideoEncoder {
+    size: (u32, u32),
+    process: std::process::Child,
+}
+
+#[cfg(feature = "video_recording")]
+impl VideoEncoder {
+    fn new(
+        path: &std::path::Path,
+        size: (u32, u32),
+        framerate: f64,
+    ) -> Result<Self, RecordingError> {
+        let process = std::process::Command::new("ffmpeg")
+            .args([
+                "-y",
+                "-loglevel",
+                "error",
+                "-f",
+                "rawvideo",
+                "-pix_fmt",
+                "rgb24",
+            ])
+            .args(["-video_size", &format!("{}x{}", size.0, size.1)])
+            .args(["-framerate", &framerate.to_string(), "-i", "-"])
+            // Most codecs need the chroma subsampled frames to have an even size.
+            .args([
+                "-vf",
+                "pad=ceil(iw/2)*2:ceil(ih/2)*2",
+                "-pix_fmt",
+                "yuv420p",
+            ])
+            .arg(path)
+            .stdin(std::process::Stdio::piped())
+            .spawn()?;
+        Ok(Self { size, process })
+    }
+
+    fn write(&mut self, data: &[u8]) -> Result<(), RecordingError> {
+        use std::io::Write;
+
+        self.process
+            .stdin
+            .as_mut()
+            .expect("the standard input of the encoder is piped")
+            .write_all(data)?;
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<(), RecordingError> {
+        // Closing the standard input ends the video.
+        drop(self.process.stdin.take());
+        let status = self.process.wait()?;
+        if !status.success() {
+            return Err(RecordingError::Encoder(status));
+        }
+        Ok(())
+    }
+}
+
+/// The captures of the images cameras render to, requested by the [`Recorder`].
+#[derive(Resource, Default)]
+pub(crate) struct ImageCaptures {
+    requested: Vec<(AssetId<Image>, ScreenshotFn)>,
+    prepared: Vec<PreparedImageCapture>,
+}
+
+struct PreparedImageCapture {
+    texture: Texture,
+    buffer: Buffer,
+    width: u32,
+    height: u32,
+    format: TextureFormat,
+    callback: ScreenshotFn,
+}
+
+fn extract_image_captures(recorder: Extract<Res<Recorder>>, mut captures: ResMut<ImageCaptures>) {
+    captures.requested.extend(
+        recorder
+            .image_captures
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .drain(..),
+    );
+}
+
+fn prepare_image_captures(
+    mut captures: ResMut<ImageCaptures>,
+    images: Res<RenderAssets<Image>>,
+    render_device: Res<RenderDevice>,
+) {
+    let ImageCaptures {
+        requested,
+        prepared,
+    } = &mut *captures;
+
+    // Dropping the callbacks of the images which can't be captured skips their frames.
+    for (id, callback) in requested.drain(..) {
+        let Some(image) = images.get(id) else {
+            continue;
+        };
+        if !image.texture.usage().contains(TextureUsages::COPY_SRC) {
+            error_once!("Cannot record an image without the `COPY_SRC` texture usage");
+            continue;
+        }
+
+        let width = image.size.x as u32;
+        let height = image.size.y as u32;
+        let buffer = render_device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("recording-transfer-buffer"),
+            size: get_aligned_size(width, height, image.texture_format.pixel_size() as u32) as u64,
+            usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        prepared.push(PreparedImageCapture {
+            texture: image.texture.clone(),
+            buffer,
+            width,
+            height,
+            format: image.texture_format,
+            callback,
+        });
+    }
+}
+
+pub(crate) fn submit_image_capture_commands(world: &World, encoder: &mut CommandEncoder) {
+    let Some(captures) = world.get_resource::<ImageCaptures>() else {
+        return;
+    };
+
+    for capture in &captures.prepared {
+        encoder.copy_texture_to_buffer(
+            capture.texture.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &capture.buffer,
+                layout: layout_data(capture.width, capture.height, capture.format),
+            },
+            Extent3d {
+                width: capture.width,
+                height: capture.height,
+                ..Default::default()
+            },
+        );
+    }
+}
+
+pub(crate) fn collect_image_captures(world: &mut World) {
+    let Some(mut captures) = world.get_resource_mut::<ImageCaptures>() else {
+        return;
+    };
+
+    for capture in captures.prepared.drain(..) {
+        read_buffer_to_image(
+            capture.buffer,
+            capture.width,
+            capture.height,
+            capture.format,
+            capture.callback,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render_asset::RenderAssetUsages;
+    use wgpu::TextureDimension;
+
+    fn frame(value: u8) -> Image {
+        Image::new(
+            Extent3d::default(),
+            TextureDimension::D2,
+            vec![value; 4],
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::RENDER_WORLD,
+        )
+    }
+
+    #[test]
+    fn encoder_writes_frames_in_order() {
+        let written = Arc::new(Mutex::new(Vec::new()));
+        let output = {
+            let written = written.clone();
+            RecordingOutput::Custom(Box::new(move |index, image| {
+                written.lock().unwrap().push((index, image.data[0]));
+                Ok(())
+            }))
+        };
+        let (sender, receiver) = async_channel::bounded(4);
+        let pending_frames = AtomicUsize::new(4);
+
+        // The frames are read back on different threads, and the third one was dropped.
+        let slots = (0..4)
+            .map(|index| FrameSlot {
+                index,
+                sender: Some(sender.clone()),
+            })
+            .collect::<Vec<_>>();
+        drop(sender);
+        let [first, second, third, fourth] = <[FrameSlot; 4]>::try_from(slots).ok().unwrap();
+        fourth.send(frame(4));
+        second.send(frame(2));
+        drop(third);
+        first.send(frame(1));
+
+        let encoder = FrameEncoder::new(output, 60.0).unwrap();
+        assert_eq!(encoder.run(&receiver, &pending_frames).unwrap(), 3);
+        assert_eq!(*written.lock().unwrap(), vec![(0, 1), (1, 2), (2, 4)]);
+        assert_eq!(pending_frames.load(Ordering::Acquire), 0);
+    }
+}
@@ -311,63 +311,75 @@ pub(crate) fn collect_screenshots(world: &mut World) {
     let mut windows = world.resource_mut::<ExtractedWindows>();
     for window in windows.values_mut() {
         if let Some(screenshot_func) = window.screenshot_func.take() {
-            let width = window.physical_width;
-            let height = window.physical_height;
-            let texture_format = window.swap_chain_texture_format.unwrap();
-            let pixel_size = texture_format.pixel_size();
             let ScreenshotPreparedState { buffer, .. } = window.screenshot_memory.take().unwrap();
-
-            let finish = async move {
-                let (tx, rx) = async_channel::bounded(1);
-                let buffer_slice = buffer.slice(..);
-                // The polling for this map call is done every frame when the command queue is submitted.
-                buffer_slice.map_async(wgpu::MapMode::Read, move |result| {
-                    let err = result.err();
-                    if err.is_some() {
-                        panic!("{}", err.unwrap().to_string());
-                    }
-                    tx.try_send(()).unwrap();
-                });
-                rx.recv().await.unwrap();
-                let data = buffer_slice.get_mapped_range();
-                // we immediately move the data to CPU memory to avoid holding the mapped view for long
-                let mut result = Vec::from(&*data);
-                drop(data);
-                drop(buffer);
-
-                if result.len() != ((width * height) as usize * pixel_size) {
-                    // Our buffer has been padded because we needed to align to a multiple of 256.
-                    // We remove this padding here
-                    let initial_row_bytes = width as usize * pixel_size;
-                    let buffered_row_bytes = align_byte_size(width * pixel_size as u32) as usize;
-
-                    let mut take_offset = buffered_row_bytes;
-                    let mut place_offset = initial_row_bytes;
-                    for _ in 1..height {
-                        result.copy_within(
-                            take_offset..take_offset + buffered_row_bytes,
-                            place_offset,
-                        );
-                        take_offset += buffered_row_bytes;
-                        place_offset += initial_row_bytes;
-                    }
-                    result.truncate(initial_row_bytes * height as usize);
-                }
-
-                screenshot_func(Image::new(
-                    Extent3d {
-                        width,
-                        height,
-                        depth_or_array_layers: 1,
-                    },
-                    wgpu::TextureDimension::D2,
-                    result,
-                    texture_format,
-                    RenderAssetUsages::RENDER_WORLD,
-                ));
-            };
-
-            AsyncComputeTaskPool::get().spawn(finish).detach();
+            read_buffer_to_image(
+                buffer,
+                window.physical_width,
+                window.physical_height,
+                window.swap_chain_texture_format.unwrap(),
+                screenshot_func,
+            );
         }
     }
 }
+
+/// Reads back a buffer a texture was copied to, with the layout of [`layout_data`], and calls
+/// `callback` with its content on one of the [`AsyncComputeTaskPool`]s threads.
+pub(crate) fn read_buffer_to_image(
+    buffer: Buffer,
+    width: u32,
+    height: u32,
+    texture_format: TextureFormat,
+    callback: ScreenshotFn,
+) {
+    let pixel_size = texture_format.pixel_size();
+
+    let finish = async move {
+        let (tx, rx) = async_channel::bounded(1);
+        let buffer_slice = buffer.slice(..);
+        // The polling for this map call is done every frame when the command queue is submitted.
+        buffer_slice.map_async(wgpu::MapMode::Read, move |result| {
+            let err = result.err();
+            if err.is_some() {
+                panic!("{}", err.unwrap().to_string());
+            }
+            tx.try_send(()).unwrap();
+        });
+        rx.recv().await.unwrap();
+        let data = buffer_slice.get_mapped_range();
+        // we immediately move the data to CPU memory to avoid holding the mapped view for long
+        let mut result = Vec::from(&*data);
+        drop(data);
+        drop(buffer);
+
+        if result.len() != ((width * height) as usize * pixel_size) {
+            // Our buffer has been padded because we needed to align to a multiple of 256.
+            // We remove this padding here
+            let initial_row_bytes = width as usize * pixel_size;
+            let buffered_row_bytes = align_byte_size(width * pixel_size as u32) as usize;
+
+            let mut take_offset = buffered_row_bytes;
+            let mut place_offset = initial_row_bytes;
+            for _ in 1..height {
+                result.copy_within(take_offset..take_offset + buffered_row_bytes, place_offset);
+                take_offset += buffered_row_bytes;
+                place_offset += initial_row_bytes;
+            }
+            result.truncate(initial_row_bytes * height as usize);
+        }
+
+        callback(Image::new(
+            Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            wgpu::TextureDimension::D2,
+            result,
+            texture_format,
+            RenderAssetUsages::RENDER_WORLD,
+        ));
+    };
+
+    AsyncComputeTaskPool::get().spawn(finish).detach();
+}
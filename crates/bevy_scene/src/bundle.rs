@@ -89,6 +89,13 @@ pub fn scene_spawner(
         }
     }
     for (entity, dynamic_scene, instance) in &mut dynamic_scene_to_spawn {
+        // Syncing the scene containing this entity may mark the handle as changed, without
+        // changing the scene it points to: keep the instance and its overrides in that case.
+        if instance.as_ref().is_some_and(|instance| {
+            scene_spawner.is_dynamic_instance_of(***instance, dynamic_scene.id())
+        }) {
+            continue;
+        }
         let new_instance = scene_spawner.spawn_dynamic_as_child(dynamic_scene.clone(), entity);
         if let Some(mut old_instance) = instance {
             scene_spawner.despawn_instance(**old_instance);
@@ -1,9 +1,9 @@
-use crate::{ron, DynamicSceneBuilder, Scene, SceneSpawnError};
+use crate::{ron, DynamicSceneBuilder, Scene, SceneOverrides, SceneSpawnError};
 use bevy_ecs::entity::EntityHashMap;
 use bevy_ecs::{
     entity::Entity,
     reflect::{AppTypeRegistry, ReflectComponent, ReflectMapEntities},
-    world::World,
+    world::{EntityRef, World},
 };
 use bevy_reflect::{Reflect, TypePath, TypeRegistryArc};
 use bevy_utils::TypeIdMap;
@@ -68,6 +68,22 @@ impl DynamicScene {
         world: &mut World,
         entity_map: &mut EntityHashMap<Entity>,
         type_registry: &AppTypeRegistry,
+    ) -> Result<(), SceneSpawnError> {
+        self.write_to_world_with_overrides(world, entity_map, type_registry, None)
+    }
+
+    /// Write the resources, the dynamic entities, and their corresponding components to the given
+    /// world, leaving the entities and components in `overrides` as they are.
+    ///
+    /// With overrides, the entities of `entity_map` which don't exist anymore are spawned again,
+    /// and the components already equal to the ones in the scene aren't written, so that they
+    /// aren't marked as changed.
+    pub(crate) fn write_to_world_with_overrides(
+        &self,
+        world: &mut World,
+        entity_map: &mut EntityHashMap<Entity>,
+        type_registry: &AppTypeRegistry,
+        overrides: Option<&SceneOverrides>,
     ) -> Result<(), SceneSpawnError> {
         let type_registry = type_registry.read();
 
@@ -103,9 +119,17 @@ impl DynamicScene {
             // Fetch the entity with the given entity id from the `entity_map`
             // or spawn a new entity with a transiently unique id if there is
             // no corresponding entry.
-            let entity = *entity_map
-                .entry(scene_entity.entity)
-                .or_insert_with(|| world.spawn_empty().id());
+            let entity = match (entity_map.get(&scene_entity.entity), overrides) {
+                (Some(&entity), Some(overrides)) if overrides.is_despawned(entity) => continue,
+                (Some(&entity), Some(_)) if world.get_entity(entity).is_none() => {
+                    let entity = world.spawn_empty().id();
+                    entity_map.insert(scene_entity.entity, entity);
+                    entity
+                }
+                _ => *entity_map
+                    .entry(scene_entity.entity)
+                    .or_insert_with(|| world.spawn_empty().id()),
+            };
             let entity_mut = &mut world.entity_mut(entity);
 
             // Apply/ add each component to the given entity.
@@ -127,6 +151,20 @@ impl DynamicScene {
                         }
                     })?;
 
+                if let Some(overrides) = overrides {
+                    // The values of components referencing entities in the scene can't be
+                    // compared with the mapped ones in the world.
+                    if overrides.contains_component(entity, registration.type_id())
+                        || registration.data::<ReflectMapEntities>().is_none()
+                            && reflect_component
+                                .reflect(EntityRef::from(&*entity_mut))
+                                .and_then(|value| value.reflect_partial_eq(&**component))
+                                .unwrap_or(false)
+                    {
+                        continue;
+                    }
+                }
+
                 // If this component references entities in the scene, track it
                 // so we can update it to the entity in the world.
                 if registration.data::<ReflectMapEntities>().is_some() {
@@ -8,9 +8,11 @@ mod bundle;
 mod dynamic_scene;
 mod dynamic_scene_builder;
 mod scene;
+mod scene_diff;
 mod scene_filter;
 mod scene_loader;
 mod scene_spawner;
+mod world_snapshot;
 
 #[cfg(feature = "serialize")]
 pub mod serde;
@@ -23,16 +25,18 @@ pub use bundle::*;
 pub use dynamic_scene::*;
 pub use dynamic_scene_builder::*;
 pub use scene::*;
+pub use scene_diff::*;
 pub use scene_filter::*;
 pub use scene_loader::*;
 pub use scene_spawner::*;
+pub use world_snapshot::*;
 
 #[allow(missing_docs)]
 pub mod prelude {
     #[doc(hidden)]
     pub use crate::{
         DynamicScene, DynamicSceneBuilder, DynamicSceneBundle, Scene, SceneBundle, SceneFilter,
-        SceneSpawner,
+        SceneSpawner, WorldSnapshot,
     };
 }
 
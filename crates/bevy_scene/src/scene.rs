@@ -1,4 +1,4 @@
-use crate::{DynamicScene, InstanceInfo, SceneSpawnError};
+use crate::{DynamicScene, InstanceInfo, SceneOverrides, SceneSpawnError};
 use bevy_asset::Asset;
 use bevy_ecs::entity::EntityHashMap;
 use bevy_ecs::{
@@ -58,6 +58,7 @@ impl Scene {
     ) -> Result<InstanceInfo, SceneSpawnError> {
         let mut instance_info = InstanceInfo {
             entity_map: EntityHashMap::default(),
+            overrides: SceneOverrides::default(),
         };
 
         let type_registry = type_registry.read();
@@ -0,0 +1,265 @@
+use std::any::TypeId;
+
+use bevy_ecs::{
+    entity::{Entity, EntityHashMap, EntityHashSet},
+    reflect::{ReflectComponent, ReflectMapEntities},
+    world::World,
+};
+use bevy_reflect::TypeRegistry;
+use bevy_utils::HashSet;
+
+use crate::{DynamicEntity, DynamicScene, SceneFilter};
+
+/// The parts of a scene instance which are kept as they are when its source [`DynamicScene`] is
+/// reloaded, instead of being synced with it.
+///
+/// When the source scene of an instance is modified, the [`SceneSpawner`](crate::SceneSpawner)
+/// first records the components changed or removed in the instance since it was last synced,
+/// and the entities despawned from it, as overrides. The other components are then updated to
+/// their new values in the scene. Overrides can also be added or removed by hand with
+/// [`SceneSpawner::instance_overrides_mut`](crate::SceneSpawner::instance_overrides_mut), for
+/// example to sync a component with the scene again.
+///
+/// The entities are the entities of the instance in the world, not the ones in the scene.
+#[derive(Debug, Default, Clone)]
+pub struct SceneOverrides {
+    components: EntityHashMap<HashSet<TypeId>>,
+    despawned: EntityHashSet,
+    // The overrides removed since the last sync, which aren't recorded again on the next one.
+    removed_components: EntityHashMap<HashSet<TypeId>>,
+    removed_despawned: EntityHashSet,
+}
+
+impl SceneOverrides {
+    /// Returns `true` if the component with the given [`TypeId`] is overridden on `entity`.
+    pub fn contains_component(&self, entity: Entity, type_id: TypeId) -> bool {
+        self.components
+            .get(&entity)
+            .is_some_and(|components| components.contains(&type_id))
+    }
+
+    /// Overrides the component with the given [`TypeId`] on `entity`.
+    ///
+    /// Returns `false` if it was already overridden.
+    pub fn insert_component(&mut self, entity: Entity, type_id: TypeId) -> bool {
+        self.components.entry(entity).or_default().insert(type_id)
+    }
+
+    /// Syncs the component with the given [`TypeId`] on `entity` with the source scene again.
+    ///
+    /// Returns `false` if it wasn't overridden.
+    pub fn remove_component(&mut self, entity: Entity, type_id: TypeId) -> bool {
+        let Some(components) = self.components.get_mut(&entity) else {
+            return false;
+        };
+        let removed = components.remove(&type_id);
+        if components.is_empty() {
+            self.components.remove(&entity);
+        }
+        if removed {
+            self.removed_components
+                .entry(entity)
+                .or_default()
+                .insert(type_id);
+        }
+        removed
+    }
+
+    /// Returns an iterator over the [`TypeId`]s of the components overridden on `entity`.
+    pub fn iter_components(&self, entity: Entity) -> impl Iterator<Item = TypeId> + '_ {
+        self.components
+            .get(&entity)
+            .into_iter()
+            .flat_map(|components| components.iter().copied())
+    }
+
+    /// Returns `true` if `entity` was despawned from the instance, and isn't spawned again when
+    /// the source scene is reloaded.
+    pub fn is_despawned(&self, entity: Entity) -> bool {
+        self.despawned.contains(&entity)
+    }
+
+    /// Marks `entity` as despawned from the instance.
+    ///
+    /// Returns `false` if it was already marked as despawned.
+    pub fn insert_despawned(&mut self, entity: Entity) -> bool {
+        self.despawned.insert(entity)
+    }
+
+    /// Spawns `entity` again the next time the source scene is synced, if it was despawned.
+    ///
+    /// Returns `false` if it wasn't marked as despawned.
+    pub fn remove_despawned(&mut self, entity: Entity) -> bool {
+        let removed = self.despawned.remove(&entity);
+        if removed {
+            self.removed_despawned.insert(entity);
+        }
+        removed
+    }
+
+    /// Returns `true` if nothing is overridden.
+    pub fn is_empty(&self) -> bool {
+        self.components.is_empty() && self.despawned.is_empty()
+    }
+
+    /// Removes all the overrides, syncing the whole instance with the source scene again.
+    pub fn clear(&mut self) {
+        for (entity, components) in self.components.drain() {
+            self.removed_components
+                .entry(entity)
+                .or_default()
+                .extend(components);
+        }
+        self.removed_despawned.extend(self.despawned.drain());
+    }
+
+    /// Forgets the overrides removed since the last sync, once the instance is synced again.
+    pub(crate) fn finish_sync(&mut self) {
+        self.removed_components.clear();
+        self.removed_despawned.clear();
+    }
+}
+
+/// The differences between a scene instance and its source [`DynamicScene`].
+///
+/// See [`SceneSpawner::diff_instance`](crate::SceneSpawner::diff_instance).
+#[derive(Default)]
+pub struct SceneInstanceDiff {
+    /// The components of the instance which differ from the scene, or aren't in it, with their
+    /// values in the instance.
+    pub changed: Vec<DynamicEntity>,
+    /// The [`TypeId`]s of the components of the scene which were removed from the instance.
+    pub removed: Vec<(Entity, Vec<TypeId>)>,
+    /// The entities of the scene which were despawned from the instance.
+    pub despawned: Vec<Entity>,
+}
+
+impl SceneInstanceDiff {
+    /// Returns `true` if the instance is the same as its source scene.
+    pub fn is_empty(&self) -> bool {
+        self.changed.is_empty() && self.removed.is_empty() && self.despawned.is_empty()
+    }
+
+    /// Records the differences as overrides of the instance, except for the overrides removed
+    /// since the last sync.
+    pub(crate) fn record_overrides(&self, overrides: &mut SceneOverrides) {
+        let changed = self.changed.iter().flat_map(|entity| {
+            entity.components.iter().filter_map(|component| {
+                let type_info = component.get_represented_type_info()?;
+                Some((entity.entity, type_info.type_id()))
+            })
+        });
+        let removed = self
+            .removed
+            .iter()
+            .flat_map(|(entity, type_ids)| type_ids.iter().map(|&type_id| (*entity, type_id)));
+        for (entity, type_id) in changed.chain(removed) {
+            let was_removed = overrides
+                .removed_components
+                .get(&entity)
+                .is_some_and(|components| components.contains(&type_id));
+            if !was_removed {
+                overrides.insert_component(entity, type_id);
+            }
+        }
+        for &entity in &self.despawned {
+            if !overrides.removed_despawned.contains(&entity) {
+                overrides.insert_despawned(entity);
+            }
+        }
+    }
+}
+
+/// Compares the entities of a scene instance with the ones of its source scene.
+///
+/// Components referencing other entities are skipped, as their values in the instance are mapped
+/// to the entities of the instance.
+pub(crate) fn diff_instance(
+    world: &World,
+    scene: &DynamicScene,
+    entity_map: &EntityHashMap<Entity>,
+    filter: &SceneFilter,
+    type_registry: &TypeRegistry,
+) -> SceneInstanceDiff {
+    let mut diff = SceneInstanceDiff::default();
+
+    for scene_entity in &scene.entities {
+        let Some(&entity) = entity_map.get(&scene_entity.entity) else {
+            continue;
+        };
+        let Some(entity_ref) = world.get_entity(entity) else {
+            diff.despawned.push(entity);
+            continue;
+        };
+
+        let mut changed = DynamicEntity {
+            entity,
+            components: Vec::new(),
+        };
+        let mut removed = Vec::new();
+        let mut scene_components = HashSet::new();
+
+        for component in &scene_entity.components {
+            let Some(registration) = component
+                .get_represented_type_info()
+                .and_then(|type_info| type_registry.get(type_info.type_id()))
+            else {
+                continue;
+            };
+            let type_id = registration.type_id();
+            scene_components.insert(type_id);
+            if filter.is_denied_by_id(type_id)
+                || registration.data::<ReflectMapEntities>().is_some()
+            {
+                continue;
+            }
+            let Some(reflect_component) = registration.data::<ReflectComponent>() else {
+                continue;
+            };
+
+            match reflect_component.reflect(entity_ref) {
+                // Values which can't be compared are assumed to be unchanged.
+                Some(value) => {
+                    if value.reflect_partial_eq(&**component) == Some(false) {
+                        changed.components.push(value.clone_value());
+                    }
+                }
+                None => removed.push(type_id),
+            }
+        }
+
+        for component_id in entity_ref.archetype().components() {
+            let Some(type_id) = world
+                .components()
+                .get_info(component_id)
+                .and_then(|info| info.type_id())
+            else {
+                continue;
+            };
+            if scene_components.contains(&type_id) || filter.is_denied_by_id(type_id) {
+                continue;
+            }
+            let Some(registration) = type_registry.get(type_id) else {
+                continue;
+            };
+            if registration.data::<ReflectMapEntities>().is_some() {
+                continue;
+            }
+            if let Some(value) = registration
+                .data::<ReflectComponent>()
+                .and_then(|reflect_component| reflect_component.reflect(entity_ref))
+            {
+                changed.components.push(value.clone_value());
+            }
+        }
+
+        if !changed.components.is_empty() {
+            diff.changed.push(changed);
+        }
+        if !removed.is_empty() {
+            diff.removed.push((entity, removed));
+        }
+    }
+
+    diff
+}
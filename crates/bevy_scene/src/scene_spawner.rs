@@ -1,10 +1,13 @@
-use crate::{DynamicScene, Scene};
+use crate::{
+    scene_diff::diff_instance, DynamicEntity, DynamicScene, Scene, SceneFilter, SceneInstanceDiff,
+    SceneOverrides,
+};
 use bevy_asset::{AssetEvent, AssetId, Assets, Handle};
 use bevy_ecs::entity::EntityHashMap;
 use bevy_ecs::{
     entity::Entity,
     event::{Event, Events, ManualEventReader},
-    reflect::AppTypeRegistry,
+    reflect::{AppTypeRegistry, ReflectComponent},
     system::{Command, Resource},
     world::{Mut, World},
 };
@@ -27,6 +30,8 @@ pub struct SceneInstanceReady {
 pub struct InstanceInfo {
     /// Mapping of entities from the scene world to the instance world.
     pub entity_map: EntityHashMap<Entity>,
+    /// Entities and components of the instance which aren't synced with its source scene.
+    pub overrides: SceneOverrides,
 }
 
 /// Unique id identifying a scene instance.
@@ -51,6 +56,11 @@ impl InstanceId {
 /// - [`despawn_queued_scenes`](Self::despawn_queued_scenes)
 /// - [`despawn_queued_instances`](Self::despawn_queued_instances)
 ///
+/// Instances of a [`DynamicScene`] are synced with it when it is modified, except for their
+/// [`SceneOverrides`]. Entities with a [`Handle<DynamicScene>`] in the scene spawn nested instances,
+/// which are synced with their own scene and keep their own overrides.
+/// [`diff_instance`](Self::diff_instance) returns the differences between an instance and its scene.
+///
 /// Deferred methods: (Scene operations will be processed when the [`scene_spawner_system`] is run)
 /// - [`spawn_dynamic`](Self::spawn_dynamic)
 /// - [`spawn_dynamic_as_child`](Self::spawn_dynamic_as_child)
@@ -63,6 +73,8 @@ pub struct SceneSpawner {
     spawned_scenes: HashMap<AssetId<Scene>, Vec<InstanceId>>,
     spawned_dynamic_scenes: HashMap<AssetId<DynamicScene>, Vec<InstanceId>>,
     spawned_instances: HashMap<InstanceId, InstanceInfo>,
+    /// Copies of the dynamic scenes as they were when their instances were last synced.
+    dynamic_scene_sources: HashMap<AssetId<DynamicScene>, DynamicScene>,
     scene_asset_event_reader: ManualEventReader<AssetEvent<DynamicScene>>,
     dynamic_scenes_to_spawn: Vec<(Handle<DynamicScene>, InstanceId)>,
     scenes_to_spawn: Vec<(Handle<Scene>, InstanceId)>,
@@ -124,6 +136,12 @@ pub enum SceneSpawnError {
         /// Id of the non-existent scene.
         id: AssetId<Scene>,
     },
+    /// Instance of a dynamic scene with the given id does not exist.
+    #[error("dynamic scene instance does not exist")]
+    NonExistentDynamicInstance {
+        /// Id of the non-existent instance.
+        id: InstanceId,
+    },
 }
 
 impl SceneSpawner {
@@ -177,7 +195,9 @@ impl SceneSpawner {
         world: &mut World,
         id: impl Into<AssetId<DynamicScene>>,
     ) -> Result<(), SceneSpawnError> {
-        if let Some(instance_ids) = self.spawned_dynamic_scenes.remove(&id.into()) {
+        let id = id.into();
+        self.dynamic_scene_sources.remove(&id);
+        if let Some(instance_ids) = self.spawned_dynamic_scenes.remove(&id) {
             for instance_id in instance_ids {
                 self.despawn_instance_sync(world, &instance_id);
             }
@@ -202,16 +222,22 @@ impl SceneSpawner {
     ) -> Result<InstanceId, SceneSpawnError> {
         let mut entity_map = EntityHashMap::default();
         let id = id.into();
-        Self::spawn_dynamic_internal(world, id, &mut entity_map)?;
+        self.spawn_dynamic_internal(world, id, &mut entity_map)?;
         let instance_id = InstanceId::new();
-        self.spawned_instances
-            .insert(instance_id, InstanceInfo { entity_map });
+        self.spawned_instances.insert(
+            instance_id,
+            InstanceInfo {
+                entity_map,
+                overrides: SceneOverrides::default(),
+            },
+        );
         let spawned = self.spawned_dynamic_scenes.entry(id).or_default();
         spawned.push(instance_id);
         Ok(instance_id)
     }
 
     fn spawn_dynamic_internal(
+        &mut self,
         world: &mut World,
         id: AssetId<DynamicScene>,
         entity_map: &mut EntityHashMap<Entity>,
@@ -220,7 +246,11 @@ impl SceneSpawner {
             let scene = scenes
                 .get(id)
                 .ok_or(SceneSpawnError::NonExistentScene { id })?;
-            scene.write_to_world(world, entity_map)
+            scene.write_to_world(world, entity_map)?;
+            self.dynamic_scene_sources
+                .entry(id)
+                .or_insert_with(|| clone_dynamic_scene(scene));
+            Ok(())
         })
     }
 
@@ -257,19 +287,54 @@ impl SceneSpawner {
     /// Iterate through all instances of the provided scenes and update those immediately.
     ///
     /// Useful for updating already spawned scene instances after their corresponding scene has been modified.
+    ///
+    /// The components changed or removed in an instance since it was last synced, and the entities
+    /// despawned from it, are first added to its [`SceneOverrides`], and are left as they are.
+    /// The entities and components removed from the scene are removed from the instance, unless
+    /// they are overridden.
     pub fn update_spawned_scenes(
         &mut self,
         world: &mut World,
         scene_ids: &[AssetId<DynamicScene>],
     ) -> Result<(), SceneSpawnError> {
+        let type_registry = world.resource::<AppTypeRegistry>().clone();
         for id in scene_ids {
-            if let Some(spawned_instances) = self.spawned_dynamic_scenes.get(id) {
+            let Some(spawned_instances) = self.spawned_dynamic_scenes.get(id) else {
+                continue;
+            };
+            let previous = self.dynamic_scene_sources.remove(id);
+            world.resource_scope(|world, scenes: Mut<Assets<DynamicScene>>| {
+                let scene = scenes
+                    .get(*id)
+                    .ok_or(SceneSpawnError::NonExistentScene { id: *id })?;
                 for instance_id in spawned_instances {
-                    if let Some(instance_info) = self.spawned_instances.get_mut(instance_id) {
-                        Self::spawn_dynamic_internal(world, *id, &mut instance_info.entity_map)?;
+                    let Some(instance_info) = self.spawned_instances.get_mut(instance_id) else {
+                        continue;
+                    };
+                    if let Some(previous) = &previous {
+                        let registry = type_registry.read();
+                        diff_instance(
+                            world,
+                            previous,
+                            &instance_info.entity_map,
+                            &SceneFilter::allow_all(),
+                            &registry,
+                        )
+                        .record_overrides(&mut instance_info.overrides);
+                        remove_from_instance(world, instance_info, previous, scene, &registry);
                     }
+                    scene.write_to_world_with_overrides(
+                        world,
+                        &mut instance_info.entity_map,
+                        &type_registry,
+                        Some(&instance_info.overrides),
+                    )?;
+                    instance_info.overrides.finish_sync();
                 }
-            }
+                self.dynamic_scene_sources
+                    .insert(*id, clone_dynamic_scene(scene));
+                Ok(())
+            })?;
         }
         Ok(())
     }
@@ -300,10 +365,15 @@ impl SceneSpawner {
         for (handle, instance_id) in scenes_to_spawn {
             let mut entity_map = EntityHashMap::default();
 
-            match Self::spawn_dynamic_internal(world, handle.id(), &mut entity_map) {
+            match self.spawn_dynamic_internal(world, handle.id(), &mut entity_map) {
                 Ok(_) => {
-                    self.spawned_instances
-                        .insert(instance_id, InstanceInfo { entity_map });
+                    self.spawned_instances.insert(
+                        instance_id,
+                        InstanceInfo {
+                            entity_map,
+                            overrides: SceneOverrides::default(),
+                        },
+                    );
                     let spawned = self
                         .spawned_dynamic_scenes
                         .entry(handle.id())
@@ -384,6 +454,147 @@ impl SceneSpawner {
             .flatten()
             .copied()
     }
+
+    /// Returns `true` if `instance_id` is a spawned instance of the dynamic scene `id`.
+    pub(crate) fn is_dynamic_instance_of(
+        &self,
+        instance_id: InstanceId,
+        id: AssetId<DynamicScene>,
+    ) -> bool {
+        self.spawned_dynamic_scenes
+            .get(&id)
+            .is_some_and(|instances| instances.contains(&instance_id))
+    }
+
+    /// Get the [`SceneOverrides`] of an instance, once it's spawned.
+    pub fn instance_overrides(&self, instance_id: InstanceId) -> Option<&SceneOverrides> {
+        self.spawned_instances
+            .get(&instance_id)
+            .map(|instance| &instance.overrides)
+    }
+
+    /// Get the [`SceneOverrides`] of an instance mutably, once it's spawned.
+    ///
+    /// The changes take effect the next time the source scene of the instance is modified.
+    pub fn instance_overrides_mut(
+        &mut self,
+        instance_id: InstanceId,
+    ) -> Option<&mut SceneOverrides> {
+        self.spawned_instances
+            .get_mut(&instance_id)
+            .map(|instance| &mut instance.overrides)
+    }
+
+    /// Compares an instance of a dynamic scene with the scene, as it was when the instance was
+    /// last synced.
+    ///
+    /// Only the components allowed by `filter` are compared. Components referencing other
+    /// entities, such as [`Parent`], are skipped, as are the ones whose values can't be compared.
+    pub fn diff_instance(
+        &self,
+        world: &World,
+        instance_id: InstanceId,
+        filter: &SceneFilter,
+    ) -> Result<SceneInstanceDiff, SceneSpawnError> {
+        let (instance, scene) = self
+            .spawned_instances
+            .get(&instance_id)
+            .zip(
+                self.spawned_dynamic_scenes
+                    .iter()
+                    .find_map(|(id, instances)| {
+                        instances
+                            .contains(&instance_id)
+                            .then(|| self.dynamic_scene_sources.get(id))
+                            .flatten()
+                    }),
+            )
+            .ok_or(SceneSpawnError::NonExistentDynamicInstance { id: instance_id })?;
+        let type_registry = world.resource::<AppTypeRegistry>().read();
+        Ok(diff_instance(
+            world,
+            scene,
+            &instance.entity_map,
+            filter,
+            &type_registry,
+        ))
+    }
+}
+
+/// Copies a dynamic scene, to compare its instances with it after it is modified.
+fn clone_dynamic_scene(scene: &DynamicScene) -> DynamicScene {
+    DynamicScene {
+        resources: scene
+            .resources
+            .iter()
+            .map(|resource| resource.clone_value())
+            .collect(),
+        entities: scene
+            .entities
+            .iter()
+            .map(|entity| DynamicEntity {
+                entity: entity.entity,
+                components: entity
+                    .components
+                    .iter()
+                    .map(|component| component.clone_value())
+                    .collect(),
+            })
+            .collect(),
+    }
+}
+
+/// Removes the entities and components removed from the `previous` version of the source scene
+/// of an instance, unless they are overridden.
+fn remove_from_instance(
+    world: &mut World,
+    instance: &mut InstanceInfo,
+    previous: &DynamicScene,
+    scene: &DynamicScene,
+    type_registry: &bevy_reflect::TypeRegistry,
+) {
+    let scene_entities: EntityHashMap<&DynamicEntity> = scene
+        .entities
+        .iter()
+        .map(|scene_entity| (scene_entity.entity, scene_entity))
+        .collect();
+
+    for previous_entity in &previous.entities {
+        let Some(&entity) = instance.entity_map.get(&previous_entity.entity) else {
+            continue;
+        };
+        let Some(scene_entity) = scene_entities.get(&previous_entity.entity) else {
+            instance.entity_map.remove(&previous_entity.entity);
+            if !instance.overrides.is_despawned(entity) {
+                let _ = world.despawn(entity);
+            }
+            continue;
+        };
+        let Some(mut entity_mut) = world.get_entity_mut(entity) else {
+            continue;
+        };
+
+        for component in &previous_entity.components {
+            let Some(registration) = component
+                .get_represented_type_info()
+                .and_then(|type_info| type_registry.get(type_info.type_id()))
+            else {
+                continue;
+            };
+            let type_id = registration.type_id();
+            let in_scene = scene_entity.components.iter().any(|component| {
+                component
+                    .get_represented_type_info()
+                    .is_some_and(|type_info| type_info.type_id() == type_id)
+            });
+            if in_scene || instance.overrides.contains_component(entity, type_id) {
+                continue;
+            }
+            if let Some(reflect_component) = registration.data::<ReflectComponent>() {
+                reflect_component.remove(&mut entity_mut);
+            }
+        }
+    }
 }
 
 /// System that handles scheduled scene instance spawning and despawning through a [`SceneSpawner`].
@@ -453,6 +664,7 @@ mod tests {
     use crate::{DynamicScene, DynamicSceneBuilder, SceneInstanceReady, ScenePlugin, SceneSpawner};
 
     use super::*;
+    use std::any::TypeId;
 
     #[derive(Reflect, Component, Debug, PartialEq, Eq, Clone, Copy, Default)]
     #[reflect(Component)]
@@ -505,6 +717,93 @@ mod tests {
         assert_eq!(old_a, new_a);
     }
 
+    #[derive(Reflect, Component, Debug, PartialEq, Eq, Clone, Copy, Default)]
+    #[reflect(Component)]
+    struct B(usize);
+
+    #[test]
+    fn update_keeps_instance_overrides() {
+        let mut world = World::default();
+        let atr = AppTypeRegistry::default();
+        atr.write().register::<A>();
+        atr.write().register::<B>();
+        world.insert_resource(atr);
+        world.insert_resource(Assets::<DynamicScene>::default());
+
+        let scene = |first: Vec<Box<dyn Reflect>>, second: Vec<Box<dyn Reflect>>| DynamicScene {
+            resources: Vec::new(),
+            entities: vec![
+                DynamicEntity {
+                    entity: Entity::from_raw(0),
+                    components: first,
+                },
+                DynamicEntity {
+                    entity: Entity::from_raw(1),
+                    components: second,
+                },
+            ],
+        };
+        let scene_id = world.resource_mut::<Assets<DynamicScene>>().add(scene(
+            vec![Box::new(A(1)), Box::new(B(1))],
+            vec![Box::new(A(2))],
+        ));
+        let scene_id = scene_id.id();
+
+        let mut scene_spawner = SceneSpawner::default();
+        let instance_id = scene_spawner
+            .spawn_dynamic_sync(&mut world, scene_id)
+            .unwrap();
+        let instance = &scene_spawner.spawned_instances[&instance_id];
+        let first = instance.entity_map[&Entity::from_raw(0)];
+        let second = instance.entity_map[&Entity::from_raw(1)];
+        assert!(scene_spawner
+            .diff_instance(&world, instance_id, &SceneFilter::allow_all())
+            .unwrap()
+            .is_empty());
+
+        // Edit the instance, then the scene.
+        *world.get_mut::<A>(first).unwrap() = A(10);
+        world.despawn(second);
+        world
+            .resource_mut::<Assets<DynamicScene>>()
+            .insert(scene_id, scene(vec![Box::new(A(3))], vec![Box::new(A(4))]));
+        scene_spawner
+            .update_spawned_scenes(&mut world, &[scene_id])
+            .unwrap();
+
+        // The edits are kept, and the rest is synced with the scene.
+        assert_eq!(world.get::<A>(first), Some(&A(10)));
+        assert_eq!(world.get::<B>(first), None);
+        assert!(world.get_entity(second).is_none());
+        let overrides = scene_spawner.instance_overrides(instance_id).unwrap();
+        assert!(overrides.contains_component(first, TypeId::of::<A>()));
+        assert!(!overrides.contains_component(first, TypeId::of::<B>()));
+        assert!(overrides.is_despawned(second));
+
+        let diff = scene_spawner
+            .diff_instance(&world, instance_id, &SceneFilter::allow_all())
+            .unwrap();
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].entity, first);
+        assert_eq!(diff.changed[0].components.len(), 1);
+        assert_eq!(
+            diff.changed[0].components[0].reflect_partial_eq(&A(10)),
+            Some(true)
+        );
+        assert!(diff.removed.is_empty());
+        assert_eq!(diff.despawned, vec![second]);
+
+        // Removing the overrides syncs the instance again.
+        let overrides = scene_spawner.instance_overrides_mut(instance_id).unwrap();
+        overrides.clear();
+        scene_spawner
+            .update_spawned_scenes(&mut world, &[scene_id])
+            .unwrap();
+        assert_eq!(world.get::<A>(first), Some(&A(3)));
+        let second = scene_spawner.spawned_instances[&instance_id].entity_map[&Entity::from_raw(1)];
+        assert_eq!(world.get::<A>(second), Some(&A(4)));
+    }
+
     #[derive(Component, Reflect, Default)]
     #[reflect(Component)]
     struct ComponentA;
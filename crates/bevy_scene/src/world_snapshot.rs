@@ -0,0 +1,230 @@
+use crate::{DynamicScene, DynamicSceneBuilder, SceneFilter, SceneSpawnError};
+use bevy_ecs::{
+    entity::{Entity, EntityHashMap},
+    world::World,
+};
+
+#[cfg(feature = "serialize")]
+use {
+    crate::{
+        ron,
+        serde::{SceneDeserializer, SceneSerializer},
+    },
+    bevy_reflect::{TypeRegistry, TypeRegistryArc},
+    serde::de::DeserializeSeed,
+};
+
+/// A snapshot of the reflectable state of a [`World`]: all of its entities with their
+/// reflectable components, and its reflectable resources.
+///
+/// Only the types registered in the world's [`AppTypeRegistry`](bevy_ecs::reflect::AppTypeRegistry)
+/// with [`ReflectComponent`](bevy_ecs::reflect::ReflectComponent) or
+/// [`ReflectResource`](bevy_ecs::reflect::ReflectResource) are captured, and the captured types
+/// can be further restricted with [`SceneFilter`]s.
+///
+/// Unlike writing a [`DynamicScene`] to a world, restoring a snapshot keeps the captured
+/// [`Entity`] ids whenever they're available in the target world, which makes snapshots a
+/// foundation for save games and rollback.
+///
+/// A snapshot can be serialized with any [`serde`] format, self-describing like RON or binary
+/// like `postcard` or `bincode`, through [`WorldSnapshot::serializer`] and
+/// [`WorldSnapshot::deserializer`].
+pub struct WorldSnapshot {
+    scene: DynamicScene,
+}
+
+impl WorldSnapshot {
+    /// Captures all the reflectable components and resources of `world`.
+    pub fn capture(world: &World) -> Self {
+        Self::capture_filtered(world, SceneFilter::allow_all(), SceneFilter::allow_all())
+    }
+
+    /// Captures the reflectable components and resources of `world` allowed by
+    /// `component_filter` and `resource_filter` respectively.
+    ///
+    /// Entities that have none of the allowed components are still captured, so that they keep
+    /// their ids when the snapshot is restored.
+    pub fn capture_filtered(
+        world: &World,
+        component_filter: SceneFilter,
+        resource_filter: SceneFilter,
+    ) -> Self {
+        let scene = DynamicSceneBuilder::from_world(world)
+            .with_filter(component_filter)
+            .with_resource_filter(resource_filter)
+            .extract_entities(world.iter_entities().map(|entity| entity.id()))
+            .extract_resources()
+            .build();
+        Self { scene }
+    }
+
+    /// Returns the [`DynamicScene`] holding the captured entities and resources.
+    pub fn scene(&self) -> &DynamicScene {
+        &self.scene
+    }
+
+    /// Returns the [`DynamicScene`] holding the captured entities and resources.
+    pub fn into_scene(self) -> DynamicScene {
+        self.scene
+    }
+
+    /// Restores the captured entities and resources into `world`, and returns the map from the
+    /// captured entities to the entities of `world` they were restored to.
+    ///
+    /// Each captured entity is restored to the entity with the same id, which is spawned if it
+    /// doesn't exist. An entity whose id is taken by another generation of it is restored to a
+    /// new entity instead, and the references to it in the restored components are remapped.
+    ///
+    /// The captured components are inserted into the restored entities, replacing their current
+    /// values, but the components and entities that weren't captured are left untouched.
+    ///
+    /// This method will return a [`SceneSpawnError`] if a type of the snapshot either isn't
+    /// registered in the world's [`AppTypeRegistry`](bevy_ecs::reflect::AppTypeRegistry)
+    /// resource, or doesn't reflect the [`Component`](bevy_ecs::component::Component) or
+    /// [`Resource`](bevy_ecs::system::Resource) trait.
+    pub fn restore(&self, world: &mut World) -> Result<EntityHashMap<Entity>, SceneSpawnError> {
+        let mut entity_map = EntityHashMap::default();
+        for scene_entity in &self.scene.entities {
+            let entity = match world.get_or_spawn(scene_entity.entity) {
+                Some(entity) => entity.id(),
+                None => world.spawn_empty().id(),
+            };
+            entity_map.insert(scene_entity.entity, entity);
+        }
+        self.scene.write_to_world(world, &mut entity_map)?;
+        Ok(entity_map)
+    }
+
+    /// Returns a [`serde::Serialize`] implementation for this snapshot, which can be used with
+    /// any [`serde`] format.
+    #[cfg(feature = "serialize")]
+    pub fn serializer<'a>(&'a self, registry: &'a TypeRegistryArc) -> SceneSerializer<'a> {
+        SceneSerializer::new(&self.scene, registry)
+    }
+
+    /// Returns a [`DeserializeSeed`] for snapshots serialized with [`WorldSnapshot::serializer`].
+    #[cfg(feature = "serialize")]
+    pub fn deserializer(type_registry: &TypeRegistry) -> WorldSnapshotDeserializer<'_> {
+        WorldSnapshotDeserializer { type_registry }
+    }
+
+    /// Serializes this snapshot into rust object notation (ron).
+    #[cfg(feature = "serialize")]
+    pub fn serialize_ron(&self, registry: &TypeRegistryArc) -> Result<String, ron::Error> {
+        self.scene.serialize_ron(registry)
+    }
+
+    /// Deserializes a snapshot serialized with [`WorldSnapshot::serialize_ron`].
+    #[cfg(feature = "serialize")]
+    pub fn deserialize_ron(
+        input: &str,
+        type_registry: &TypeRegistry,
+    ) -> Result<Self, ron::de::SpannedError> {
+        let mut deserializer = ron::de::Deserializer::from_str(input)?;
+        Self::deserializer(type_registry)
+            .deserialize(&mut deserializer)
+            .map_err(|e| deserializer.span_error(e))
+    }
+}
+
+impl From<DynamicScene> for WorldSnapshot {
+    fn from(scene: DynamicScene) -> Self {
+        Self { scene }
+    }
+}
+
+/// Handles [`WorldSnapshot`] deserialization.
+#[cfg(feature = "serialize")]
+pub struct WorldSnapshotDeserializer<'a> {
+    /// Type registry in which the components and resources types of the snapshot are registered.
+    pub type_registry: &'a TypeRegistry,
+}
+
+#[cfg(feature = "serialize")]
+impl<'a, 'de> DeserializeSeed<'de> for WorldSnapshotDeserializer<'a> {
+    type Value = WorldSnapshot;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let scene = SceneDeserializer {
+            type_registry: self.type_registry,
+        }
+        .deserialize(deserializer)?;
+        Ok(WorldSnapshot { scene })
+    }
+}
+
+#[cfg(all(test, feature = "serialize"))]
+mod tests {
+    use super::*;
+    use bevy_ecs::{
+        prelude::*,
+        reflect::{AppTypeRegistry, ReflectComponent, ReflectResource},
+    };
+    use bevy_reflect::Reflect;
+
+    #[derive(Component, Reflect, Default, PartialEq, Debug)]
+    #[reflect(Component)]
+    struct Health(u32);
+
+    #[derive(Component, Reflect, Default, PartialEq, Debug)]
+    #[reflect(Component)]
+    struct Secret(u32);
+
+    #[derive(Resource, Reflect, Default, PartialEq, Debug)]
+    #[reflect(Resource)]
+    struct Score(u32);
+
+    fn create_world() -> World {
+        let mut world = World::new();
+        let registry = AppTypeRegistry::default();
+        {
+            let mut registry = registry.write();
+            registry.register::<Health>();
+            registry.register::<Secret>();
+            registry.register::<Score>();
+        }
+        world.insert_resource(registry);
+        world
+    }
+
+    #[test]
+    fn restore_keeps_entity_ids() {
+        let mut world = create_world();
+        world.spawn_empty();
+        let a = world.spawn((Health(3), Secret(7))).id();
+        let b = world.spawn(Health(5)).id();
+        world.insert_resource(Score(42));
+
+        let snapshot = WorldSnapshot::capture_filtered(
+            &world,
+            SceneFilter::allow_all().deny::<Secret>(),
+            SceneFilter::allow_all(),
+        );
+        let registry = world.resource::<AppTypeRegistry>().clone();
+        let serialized = snapshot.serialize_ron(&registry.0).unwrap();
+        let snapshot = WorldSnapshot::deserialize_ron(&serialized, &registry.read()).unwrap();
+
+        let mut restored = create_world();
+        let entity_map = snapshot.restore(&mut restored).unwrap();
+        assert_eq!(entity_map.get(&a), Some(&a));
+        assert_eq!(entity_map.get(&b), Some(&b));
+        assert_eq!(restored.get::<Health>(a), Some(&Health(3)));
+        assert_eq!(restored.get::<Secret>(a), None);
+        assert_eq!(restored.get::<Health>(b), Some(&Health(5)));
+        assert_eq!(restored.get_resource::<Score>(), Some(&Score(42)));
+    }
+
+    #[test]
+    fn restore_rolls_back_captured_components() {
+        let mut world = create_world();
+        let entity = world.spawn(Health(10)).id();
+        let snapshot = WorldSnapshot::capture(&world);
+
+        world.get_mut::<Health>(entity).unwrap().0 = 0;
+        snapshot.restore(&mut world).unwrap();
+        assert_eq!(world.get::<Health>(entity), Some(&Health(10)));
+    }
+}
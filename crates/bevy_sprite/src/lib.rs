@@ -4,32 +4,41 @@
 //! Provides 2D sprite rendering functionality.
 mod bundle;
 mod dynamic_texture_atlas_builder;
+mod light2d;
 mod mesh2d;
 mod render;
 mod sprite;
 mod texture_atlas;
 mod texture_atlas_builder;
 mod texture_slice;
+mod tilemap;
 
 pub mod prelude {
     #[doc(hidden)]
     pub use crate::{
         bundle::{SpriteBundle, SpriteSheetBundle},
-        sprite::{ImageScaleMode, Sprite},
+        light2d::{
+            AmbientLight2d, LightOccluder2d, PointLight2d, PointLight2dBundle, SpotLight2d,
+            SpotLight2dBundle,
+        },
+        sprite::{ImageScaleMode, Sprite, SpriteNormalMap},
         texture_atlas::{TextureAtlas, TextureAtlasLayout},
         texture_slice::{BorderRect, SliceScaleMode, TextureSlice, TextureSlicer},
+        tilemap::{Tile, TileAnimation, Tilemap, TilemapBundle, TilemapTexture},
         ColorMaterial, ColorMesh2dBundle, TextureAtlasBuilder,
     };
 }
 
 pub use bundle::*;
 pub use dynamic_texture_atlas_builder::*;
+pub use light2d::*;
 pub use mesh2d::*;
 pub use render::*;
 pub use sprite::*;
 pub use texture_atlas::*;
 pub use texture_atlas_builder::*;
 pub use texture_slice::*;
+pub use tilemap::*;
 
 use bevy_app::prelude::*;
 use bevy_asset::{load_internal_asset, AssetApp, Assets, Handle};
@@ -69,12 +78,18 @@ impl Plugin for SpritePlugin {
         app.init_asset::<TextureAtlasLayout>()
             .register_asset_reflect::<TextureAtlasLayout>()
             .register_type::<Sprite>()
+            .register_type::<SpriteNormalMap>()
             .register_type::<ImageScaleMode>()
             .register_type::<TextureSlicer>()
             .register_type::<Anchor>()
             .register_type::<TextureAtlas>()
             .register_type::<Mesh2dHandle>()
-            .add_plugins((Mesh2dRenderPlugin, ColorMaterialPlugin))
+            .add_plugins((
+                Mesh2dRenderPlugin,
+                ColorMaterialPlugin,
+                Light2dPlugin,
+                TilemapPlugin,
+            ))
             .add_systems(
                 PostUpdate,
                 (
@@ -82,6 +97,7 @@ impl Plugin for SpritePlugin {
                     (
                         compute_slices_on_asset_event,
                         compute_slices_on_sprite_change,
+                        remove_slices_on_scale_mode_removal,
                     )
                         .in_set(SpriteSystem::ComputeSlices),
                 ),
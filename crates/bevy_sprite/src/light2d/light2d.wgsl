@@ -0,0 +1,111 @@
+#define_import_path bevy_sprite::light2d
+
+// NOTE: These must match the constants and bit flags in bevy_sprite/src/light2d/mod.rs!
+const MAX_LIGHTS_2D: u32 = 64u;
+const MAX_OCCLUDERS_2D: u32 = 64u;
+const LIGHT_2D_FLAGS_SHADOWS_ENABLED_BIT: u32 = 1u;
+
+struct Light2d {
+    // The linear color of the light, multiplied by its intensity.
+    color: vec4<f32>,
+    position: vec2<f32>,
+    direction: vec2<f32>,
+    radius: f32,
+    height: f32,
+    cos_inner_angle: f32,
+    cos_outer_angle: f32,
+    flags: u32,
+};
+
+struct Occluder2d {
+    world_to_local: mat2x2<f32>,
+    center: vec2<f32>,
+    half_size: vec2<f32>,
+};
+
+struct Lights2d {
+    lights: array<Light2d, MAX_LIGHTS_2D>,
+    occluders: array<Occluder2d, MAX_OCCLUDERS_2D>,
+    ambient_color: vec4<f32>,
+    light_count: u32,
+    occluder_count: u32,
+};
+
+@group(0) @binding(2) var<uniform> lights: Lights2d;
+
+// Returns whether the segment from `start` to `end` crosses the occluder. Occluders containing
+// either end are ignored, so that the sprites overlapping them are lit, and the lights inside them
+// shine out.
+fn segment_crosses_occluder(start: vec2<f32>, end: vec2<f32>, occluder: Occluder2d) -> bool {
+    let a = occluder.world_to_local * (start - occluder.center);
+    let b = occluder.world_to_local * (end - occluder.center);
+    if all(abs(a) <= occluder.half_size) || all(abs(b) <= occluder.half_size) {
+        return false;
+    }
+
+    // Clips the segment by both slabs of the rectangle.
+    let delta = b - a;
+    var t_min = 0.0;
+    var t_max = 1.0;
+    for (var axis = 0u; axis < 2u; axis += 1u) {
+        if abs(delta[axis]) < 1e-6 {
+            if abs(a[axis]) > occluder.half_size[axis] {
+                return false;
+            }
+        } else {
+            let t0 = (-occluder.half_size[axis] - a[axis]) / delta[axis];
+            let t1 = (occluder.half_size[axis] - a[axis]) / delta[axis];
+            t_min = max(t_min, min(t0, t1));
+            t_max = min(t_max, max(t0, t1));
+        }
+    }
+    return t_min <= t_max;
+}
+
+fn shadowed(world_position: vec2<f32>, light_position: vec2<f32>) -> bool {
+    for (var i = 0u; i < lights.occluder_count; i += 1u) {
+        if segment_crosses_occluder(world_position, light_position, lights.occluders[i]) {
+            return true;
+        }
+    }
+    return false;
+}
+
+// The light reaching `world_position`. The `normal` of the surface is only used when it is
+// `normal_mapped`, other surfaces are lit as if the lights were in their plane.
+fn lighting(world_position: vec2<f32>, normal: vec3<f32>, normal_mapped: bool) -> vec3<f32> {
+    var light = lights.ambient_color.rgb;
+    for (var i = 0u; i < lights.light_count; i += 1u) {
+        let light_2d = lights.lights[i];
+        let to_light = light_2d.position - world_position;
+        let distance = length(to_light);
+        if distance >= light_2d.radius {
+            continue;
+        }
+
+        // Fades out smoothly up to the radius.
+        let window = 1.0 - (distance * distance) / (light_2d.radius * light_2d.radius);
+        var attenuation = window * window;
+        if distance > 0.0 {
+            attenuation *= smoothstep(
+                light_2d.cos_outer_angle,
+                light_2d.cos_inner_angle,
+                dot(light_2d.direction, -to_light / distance),
+            );
+        }
+        if normal_mapped && (distance > 0.0 || light_2d.height != 0.0) {
+            let direction = normalize(vec3<f32>(to_light, light_2d.height));
+            attenuation *= max(dot(normal, direction), 0.0);
+        }
+        if attenuation <= 0.0 {
+            continue;
+        }
+
+        if (light_2d.flags & LIGHT_2D_FLAGS_SHADOWS_ENABLED_BIT) != 0u
+            && shadowed(world_position, light_2d.position) {
+            continue;
+        }
+        light += light_2d.color.rgb * attenuation;
+    }
+    return light;
+}
@@ -0,0 +1,384 @@
+//! Lights for the 2D pipeline, lighting the sprites and the [`ColorMaterial`](crate::ColorMaterial)
+//! meshes, with their normal maps and the shadows cast by [`LightOccluder2d`]s.
+//!
+//! The light reaching a sprite is the [`AmbientLight2d`] plus the light of the [`PointLight2d`]s
+//! and [`SpotLight2d`]s in range, and multiplies its color. Lights brighter than the sprites'
+//! textures make them brighter than white, which blooms on HDR cameras with bloom enabled.
+
+use bevy_app::{App, Plugin};
+use bevy_asset::{load_internal_asset, Handle};
+use bevy_ecs::{
+    prelude::*,
+    reflect::{ReflectComponent, ReflectResource},
+};
+use bevy_math::{Mat2, Vec2, Vec4};
+use bevy_reflect::{std_traits::ReflectDefault, Reflect};
+use bevy_render::{
+    color::Color,
+    render_resource::{Shader, ShaderType, UniformBuffer},
+    renderer::{RenderDevice, RenderQueue},
+    view::{InheritedVisibility, ViewVisibility, Visibility},
+    Extract, ExtractSchedule, Render, RenderApp, RenderSet,
+};
+use bevy_transform::components::{GlobalTransform, Transform};
+use bevy_utils::warn_once;
+
+pub const LIGHT2D_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(7405162837562019441);
+
+/// The maximum number of [`PointLight2d`]s and [`SpotLight2d`]s lighting the sprites.
+///
+/// NOTE: This must match the array length in `bevy_sprite/src/light2d/light2d.wgsl`!
+pub const MAX_LIGHTS_2D: usize = 64;
+/// The maximum number of [`LightOccluder2d`]s casting shadows.
+///
+/// NOTE: This must match the array length in `bevy_sprite/src/light2d/light2d.wgsl`!
+pub const MAX_OCCLUDERS_2D: usize = 64;
+
+/// Adds the 2D lights, their extraction and their uniform buffer.
+#[derive(Default)]
+pub struct Light2dPlugin;
+
+impl Plugin for Light2dPlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(
+            app,
+            LIGHT2D_SHADER_HANDLE,
+            "light2d.wgsl",
+            Shader::from_wgsl
+        );
+
+        app.register_type::<PointLight2d>()
+            .register_type::<SpotLight2d>()
+            .register_type::<LightOccluder2d>()
+            .register_type::<AmbientLight2d>()
+            .init_resource::<AmbientLight2d>();
+
+        if let Ok(render_app) = app.get_sub_app_mut(RenderApp) {
+            render_app
+                .init_resource::<ExtractedLights2d>()
+                .init_resource::<Lights2dMeta>()
+                .add_systems(ExtractSchedule, extract_lights_2d)
+                .add_systems(
+                    Render,
+                    prepare_lights_2d.in_set(RenderSet::PrepareResources),
+                );
+        }
+    }
+}
+
+/// The light lighting all the sprites evenly, added to the light of the 2D lights.
+///
+/// The default full white light keeps the sprites as bright as their textures, so the 2D lights
+/// only brighten them. Lower its brightness to make the 2D lights stand out in the dark.
+#[derive(Resource, Clone, Copy, Debug, Reflect)]
+#[reflect(Resource, Default)]
+pub struct AmbientLight2d {
+    pub color: Color,
+    /// A multiplier of the color of the light.
+    pub brightness: f32,
+}
+
+impl Default for AmbientLight2d {
+    fn default() -> Self {
+        Self {
+            color: Color::WHITE,
+            brightness: 1.0,
+        }
+    }
+}
+
+/// A light shining in all directions from its position, up to its `radius`.
+#[derive(Component, Clone, Copy, Debug, Reflect)]
+#[reflect(Component, Default)]
+pub struct PointLight2d {
+    pub color: Color,
+    /// A multiplier of the color of the light.
+    pub intensity: f32,
+    /// The distance at which the light fades out.
+    pub radius: f32,
+    /// The height of the light above the sprites, lighting their normal maps at a grazing angle
+    /// when low. Sprites without a normal map are lit as if the light were in their plane.
+    pub height: f32,
+    /// Whether the [`LightOccluder2d`]s cast shadows from this light.
+    pub shadows_enabled: bool,
+}
+
+impl Default for PointLight2d {
+    fn default() -> Self {
+        Self {
+            color: Color::WHITE,
+            intensity: 1.0,
+            radius: 200.0,
+            height: 50.0,
+            shadows_enabled: false,
+        }
+    }
+}
+
+/// A light shining in a cone from its position, along the local X axis of its transform, up to
+/// its `radius`.
+#[derive(Component, Clone, Copy, Debug, Reflect)]
+#[reflect(Component, Default)]
+pub struct SpotLight2d {
+    pub color: Color,
+    /// A multiplier of the color of the light.
+    pub intensity: f32,
+    /// The distance at which the light fades out.
+    pub radius: f32,
+    /// The height of the light above the sprites, lighting their normal maps at a grazing angle
+    /// when low. Sprites without a normal map are lit as if the light were in their plane.
+    pub height: f32,
+    /// Whether the [`LightOccluder2d`]s cast shadows from this light.
+    pub shadows_enabled: bool,
+    /// Angle from the direction of the light, in radians, at which the light starts fading out.
+    pub inner_angle: f32,
+    /// Angle from the direction of the light, in radians, at which the light is faded out.
+    pub outer_angle: f32,
+}
+
+impl Default for SpotLight2d {
+    fn default() -> Self {
+        Self {
+            color: Color::WHITE,
+            intensity: 1.0,
+            radius: 200.0,
+            height: 50.0,
+            shadows_enabled: false,
+            inner_angle: 0.0,
+            outer_angle: std::f32::consts::FRAC_PI_4,
+        }
+    }
+}
+
+/// A rectangle blocking the light of the 2D lights with shadows enabled, centered on the entity
+/// and transformed by its [`GlobalTransform`].
+///
+/// The sprites overlapping the occluder are lit on their whole surface, so that a wall shadows
+/// what is behind it but not itself.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Reflect)]
+#[reflect(Component, Default, PartialEq)]
+pub struct LightOccluder2d {
+    pub half_size: Vec2,
+}
+
+impl LightOccluder2d {
+    /// Creates an occluder of the given `size`, like the size of the sprite it is added to.
+    pub fn from_size(size: Vec2) -> Self {
+        Self {
+            half_size: size / 2.0,
+        }
+    }
+}
+
+/// A component bundle for [`PointLight2d`] entities.
+#[derive(Bundle, Clone, Debug, Default)]
+pub struct PointLight2dBundle {
+    pub point_light: PointLight2d,
+    pub transform: Transform,
+    pub global_transform: GlobalTransform,
+    /// Enables or disables the light
+    pub visibility: Visibility,
+    /// Inherited visibility of an entity.
+    pub inherited_visibility: InheritedVisibility,
+    /// Algorithmically-computed indication of whether an entity is visible and should be extracted for rendering
+    pub view_visibility: ViewVisibility,
+}
+
+/// A component bundle for [`SpotLight2d`] entities.
+#[derive(Bundle, Clone, Debug, Default)]
+pub struct SpotLight2dBundle {
+    pub spot_light: SpotLight2d,
+    pub transform: Transform,
+    pub global_transform: GlobalTransform,
+    /// Enables or disables the light
+    pub visibility: Visibility,
+    /// Inherited visibility of an entity.
+    pub inherited_visibility: InheritedVisibility,
+    /// Algorithmically-computed indication of whether an entity is visible and should be extracted for rendering
+    pub view_visibility: ViewVisibility,
+}
+
+// NOTE: These must match the bit flags in bevy_sprite/src/light2d/light2d.wgsl!
+bitflags::bitflags! {
+    #[repr(transparent)]
+    pub struct Light2dFlags: u32 {
+        const SHADOWS_ENABLED = 1 << 0;
+        const NONE            = 0;
+    }
+}
+
+/// The GPU representation of a [`PointLight2d`] or a [`SpotLight2d`].
+#[derive(Clone, Copy, Default, ShaderType)]
+pub struct GpuLight2d {
+    /// The linear color of the light, multiplied by its intensity.
+    pub color: Vec4,
+    pub position: Vec2,
+    pub direction: Vec2,
+    pub radius: f32,
+    pub height: f32,
+    pub cos_inner_angle: f32,
+    pub cos_outer_angle: f32,
+    pub flags: u32,
+}
+
+/// The GPU representation of a [`LightOccluder2d`].
+#[derive(Clone, Copy, Default, ShaderType)]
+pub struct GpuOccluder2d {
+    /// The inverse of the 2D part of the [`GlobalTransform`] of the occluder.
+    pub world_to_local: Mat2,
+    pub center: Vec2,
+    pub half_size: Vec2,
+}
+
+/// The uniform of the 2D lights, bound to the view bind groups of the sprites and 2D meshes.
+#[derive(Clone, ShaderType)]
+pub struct GpuLights2d {
+    pub lights: [GpuLight2d; MAX_LIGHTS_2D],
+    pub occluders: [GpuOccluder2d; MAX_OCCLUDERS_2D],
+    pub ambient_color: Vec4,
+    pub light_count: u32,
+    pub occluder_count: u32,
+}
+
+impl Default for GpuLights2d {
+    fn default() -> Self {
+        Self {
+            lights: [GpuLight2d::default(); MAX_LIGHTS_2D],
+            occluders: [GpuOccluder2d::default(); MAX_OCCLUDERS_2D],
+            ambient_color: Vec4::ONE,
+            light_count: 0,
+            occluder_count: 0,
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct ExtractedLights2d {
+    pub lights: Vec<GpuLight2d>,
+    pub occluders: Vec<GpuOccluder2d>,
+    pub ambient_color: Vec4,
+}
+
+#[derive(Resource, Default)]
+pub struct Lights2dMeta {
+    pub uniform: UniformBuffer<GpuLights2d>,
+}
+
+pub fn extract_lights_2d(
+    mut extracted_lights: ResMut<ExtractedLights2d>,
+    ambient_light: Extract<Res<AmbientLight2d>>,
+    point_lights: Extract<Query<(&PointLight2d, &GlobalTransform, &ViewVisibility)>>,
+    spot_lights: Extract<Query<(&SpotLight2d, &GlobalTransform, &ViewVisibility)>>,
+    occluders: Extract<
+        Query<(
+            &LightOccluder2d,
+            &GlobalTransform,
+            Option<&InheritedVisibility>,
+        )>,
+    >,
+) {
+    let ExtractedLights2d {
+        lights,
+        occluders: extracted_occluders,
+        ambient_color,
+    } = &mut *extracted_lights;
+    lights.clear();
+    extracted_occluders.clear();
+    *ambient_color =
+        Vec4::from(ambient_light.color.as_linear_rgba_f32()) * ambient_light.brightness;
+    ambient_color.w = 1.0;
+
+    let flags = |shadows_enabled| {
+        if shadows_enabled {
+            Light2dFlags::SHADOWS_ENABLED
+        } else {
+            Light2dFlags::NONE
+        }
+        .bits()
+    };
+    for (light, transform, view_visibility) in &point_lights {
+        if !view_visibility.get() {
+            continue;
+        }
+        lights.push(GpuLight2d {
+            color: Vec4::from(light.color.as_linear_rgba_f32()) * light.intensity,
+            position: transform.translation().truncate(),
+            direction: Vec2::X,
+            radius: light.radius,
+            height: light.height,
+            // Lights all the directions.
+            cos_inner_angle: -1.0,
+            cos_outer_angle: -2.0,
+            flags: flags(light.shadows_enabled),
+        });
+    }
+    for (light, transform, view_visibility) in &spot_lights {
+        if !view_visibility.get() {
+            continue;
+        }
+        let cos_outer_angle = light.outer_angle.cos();
+        lights.push(GpuLight2d {
+            color: Vec4::from(light.color.as_linear_rgba_f32()) * light.intensity,
+            position: transform.translation().truncate(),
+            direction: transform
+                .affine()
+                .matrix3
+                .x_axis
+                .truncate()
+                .normalize_or_zero(),
+            radius: light.radius,
+            height: light.height,
+            // The shader fades the light out between the angles, which must not be equal.
+            cos_inner_angle: light.inner_angle.cos().max(cos_outer_angle + 1e-4),
+            cos_outer_angle,
+            flags: flags(light.shadows_enabled),
+        });
+    }
+    if lights.len() > MAX_LIGHTS_2D {
+        warn_once!(
+            "More than {MAX_LIGHTS_2D} 2D lights are visible, the extra ones don't light the sprites."
+        );
+        lights.truncate(MAX_LIGHTS_2D);
+    }
+
+    for (occluder, transform, inherited_visibility) in &occluders {
+        if inherited_visibility.is_some_and(|visibility| !visibility.get()) {
+            continue;
+        }
+        let matrix = transform.affine().matrix3;
+        let local_to_world = Mat2::from_cols(matrix.x_axis.truncate(), matrix.y_axis.truncate());
+        if local_to_world.determinant().abs() <= f32::EPSILON {
+            continue;
+        }
+        extracted_occluders.push(GpuOccluder2d {
+            world_to_local: local_to_world.inverse(),
+            center: transform.translation().truncate(),
+            half_size: occluder.half_size,
+        });
+    }
+    if extracted_occluders.len() > MAX_OCCLUDERS_2D {
+        warn_once!(
+            "More than {MAX_OCCLUDERS_2D} 2D light occluders exist, the extra ones don't cast shadows."
+        );
+        extracted_occluders.truncate(MAX_OCCLUDERS_2D);
+    }
+}
+
+pub fn prepare_lights_2d(
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+    extracted_lights: Res<ExtractedLights2d>,
+    mut lights_meta: ResMut<Lights2dMeta>,
+) {
+    let uniform = lights_meta.uniform.get_mut();
+    uniform.lights[..extracted_lights.lights.len()].copy_from_slice(&extracted_lights.lights);
+    uniform.occluders[..extracted_lights.occluders.len()]
+        .copy_from_slice(&extracted_lights.occluders);
+    uniform.ambient_color = extracted_lights.ambient_color;
+    uniform.light_count = extracted_lights.lights.len() as u32;
+    uniform.occluder_count = extracted_lights.occluders.len() as u32;
+
+    lights_meta
+        .uniform
+        .write_buffer(&render_device, &render_queue);
+}
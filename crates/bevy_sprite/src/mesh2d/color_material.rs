@@ -36,6 +36,8 @@ impl Plugin for ColorMaterialPlugin {
 }
 
 /// A [2d material](Material2d) that renders [2d meshes](crate::Mesh2dHandle) with a texture tinted by a uniform color
+///
+/// The meshes are lit by the [2d lights](crate::PointLight2d).
 #[derive(Asset, AsBindGroup, Reflect, Debug, Clone)]
 #[reflect(Default, Debug)]
 #[uniform(0, ColorMaterialUniform)]
@@ -44,6 +46,13 @@ pub struct ColorMaterial {
     #[texture(1)]
     #[sampler(2)]
     pub texture: Option<Handle<Image>>,
+    /// The normal map shading the mesh with the [2d lights](crate::PointLight2d).
+    ///
+    /// Its normals are in the tangent space of the UVs of the mesh, with green pointing up the
+    /// texture, and it is usually stored in a linear texture.
+    #[texture(3)]
+    #[sampler(4)]
+    pub normal_map: Option<Handle<Image>>,
 }
 
 impl Default for ColorMaterial {
@@ -51,6 +60,7 @@ impl Default for ColorMaterial {
         ColorMaterial {
             color: Color::WHITE,
             texture: None,
+            normal_map: None,
         }
     }
 }
@@ -78,6 +88,7 @@ bitflags::bitflags! {
     #[repr(transparent)]
     pub struct ColorMaterialFlags: u32 {
         const TEXTURE           = 1 << 0;
+        const NORMAL_MAP_TEXTURE = 1 << 1;
         const NONE              = 0;
         const UNINITIALIZED     = 0xFFFF;
     }
@@ -96,6 +107,9 @@ impl AsBindGroupShaderType<ColorMaterialUniform> for ColorMaterial {
         if self.texture.is_some() {
             flags |= ColorMaterialFlags::TEXTURE;
         }
+        if self.normal_map.is_some() {
+            flags |= ColorMaterialFlags::NORMAL_MAP_TEXTURE;
+        }
 
         ColorMaterialUniform {
             color: self.color.as_linear_rgba_f32().into(),
@@ -1,4 +1,5 @@
 #import bevy_sprite::{
+    light2d,
     mesh2d_vertex_output::VertexOutput,
     mesh2d_view_bindings::view,
 }
@@ -13,10 +14,13 @@ struct ColorMaterial {
     flags: u32,
 };
 const COLOR_MATERIAL_FLAGS_TEXTURE_BIT: u32 = 1u;
+const COLOR_MATERIAL_FLAGS_NORMAL_MAP_TEXTURE_BIT: u32 = 2u;
 
 @group(2) @binding(0) var<uniform> material: ColorMaterial;
 @group(2) @binding(1) var texture: texture_2d<f32>;
 @group(2) @binding(2) var texture_sampler: sampler;
+@group(2) @binding(3) var normal_map_texture: texture_2d<f32>;
+@group(2) @binding(4) var normal_map_sampler: sampler;
 
 @fragment
 fn fragment(
@@ -29,6 +33,30 @@ fn fragment(
     if ((material.flags & COLOR_MATERIAL_FLAGS_TEXTURE_BIT) != 0u) {
         output_color = output_color * textureSample(texture, texture_sampler, mesh.uv);
     }
+
+    // The axes of the normal map follow the UVs, found from their screen space derivatives. The V
+    // axis points down the image, while the Y axis of the normals points up.
+    let dpos_dx = dpdx(mesh.world_position.xy);
+    let dpos_dy = dpdy(mesh.world_position.xy);
+    let duv_dx = dpdx(mesh.uv);
+    let duv_dy = dpdy(mesh.uv);
+    let uv_determinant = duv_dx.x * duv_dy.y - duv_dy.x * duv_dx.y;
+    let dpos_du = (dpos_dx * duv_dy.y - dpos_dy * duv_dx.y) * sign(uv_determinant);
+    let dpos_dv = (dpos_dy * duv_dx.x - dpos_dx * duv_dy.x) * sign(uv_determinant);
+
+    // Sampled outside of the branch, which depends on the derivatives and isn't uniform.
+    let tangent_normal = textureSample(normal_map_texture, normal_map_sampler, mesh.uv).xyz * 2.0 - 1.0;
+    var normal = vec3<f32>(0.0, 0.0, 1.0);
+    let normal_mapped = (material.flags & COLOR_MATERIAL_FLAGS_NORMAL_MAP_TEXTURE_BIT) != 0u
+        && uv_determinant != 0.0;
+    if normal_mapped {
+        normal = normalize(vec3<f32>(
+            normalize(dpos_du) * tangent_normal.x - normalize(dpos_dv) * tangent_normal.y,
+            tangent_normal.z,
+        ));
+    }
+    let light = light2d::lighting(mesh.world_position.xy, normal, normal_mapped);
+    output_color = vec4<f32>(output_color.rgb * light, output_color.a);
 #ifdef TONEMAP_IN_SHADER
     output_color = tonemapping::tone_mapping(output_color, view.color_grading);
 #endif
@@ -32,7 +32,7 @@ use bevy_render::{
 };
 use bevy_transform::components::GlobalTransform;
 
-use crate::Material2dBindGroupId;
+use crate::{GpuLights2d, Lights2dMeta, Material2dBindGroupId};
 
 /// Component for rendering with meshes in the 2d pipeline, usually with a [2d material](crate::Material2d) such as [`ColorMaterial`](crate::ColorMaterial).
 ///
@@ -264,6 +264,8 @@ impl FromWorld for Mesh2dPipeline {
                     // View
                     uniform_buffer::<ViewUniform>(true),
                     uniform_buffer::<GlobalsUniform>(false),
+                    // Lights
+                    uniform_buffer::<GpuLights2d>(false),
                 ),
             ),
         );
@@ -595,16 +597,22 @@ pub fn prepare_mesh2d_view_bind_groups(
     view_uniforms: Res<ViewUniforms>,
     views: Query<Entity, With<ExtractedView>>,
     globals_buffer: Res<GlobalsBuffer>,
+    lights_meta: Res<Lights2dMeta>,
 ) {
-    if let (Some(view_binding), Some(globals)) = (
+    if let (Some(view_binding), Some(globals), Some(lights)) = (
         view_uniforms.uniforms.binding(),
         globals_buffer.buffer.binding(),
+        lights_meta.uniform.binding(),
     ) {
         for entity in &views {
             let view_bind_group = render_device.create_bind_group(
                 "mesh2d_view_bind_group",
                 &mesh2d_pipeline.view_layout,
-                &BindGroupEntries::sequential((view_binding.clone(), globals.clone())),
+                &BindGroupEntries::sequential((
+                    view_binding.clone(),
+                    globals.clone(),
+                    lights.clone(),
+                )),
             );
 
             commands.entity(entity).insert(Mesh2dViewBindGroup {
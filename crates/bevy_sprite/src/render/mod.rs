@@ -2,7 +2,8 @@ use std::ops::Range;
 
 use crate::{
     texture_atlas::{TextureAtlas, TextureAtlasLayout},
-    ComputedTextureSlices, Sprite, SPRITE_SHADER_HANDLE,
+    ComputedTextureSlices, GpuLights2d, Lights2dMeta, Sprite, SpriteNormalMap,
+    SPRITE_SHADER_HANDLE,
 };
 use bevy_asset::{AssetEvent, AssetId, Assets, Handle};
 use bevy_core_pipeline::{
@@ -59,9 +60,14 @@ impl FromWorld for SpritePipeline {
 
         let view_layout = render_device.create_bind_group_layout(
             "sprite_view_layout",
-            &BindGroupLayoutEntries::single(
+            &BindGroupLayoutEntries::with_indices(
                 ShaderStages::VERTEX_FRAGMENT,
-                uniform_buffer::<ViewUniform>(true),
+                (
+                    (0, uniform_buffer::<ViewUniform>(true)),
+                    // The lights are at the same binding as in the 2D mesh view bind group, for
+                    // both to import them from `bevy_sprite::light2d`.
+                    (2, uniform_buffer::<GpuLights2d>(false)),
+                ),
             ),
         );
 
@@ -126,6 +132,7 @@ bitflags::bitflags! {
         const HDR                               = 1 << 1;
         const TONEMAP_IN_SHADER                 = 1 << 2;
         const DEBAND_DITHER                     = 1 << 3;
+        const NORMAL_MAP                        = 1 << 4;
         const MSAA_RESERVED_BITS                = Self::MSAA_MASK_BITS << Self::MSAA_SHIFT_BITS;
         const TONEMAP_METHOD_RESERVED_BITS      = Self::TONEMAP_METHOD_MASK_BITS << Self::TONEMAP_METHOD_SHIFT_BITS;
         const TONEMAP_METHOD_NONE               = 0 << Self::TONEMAP_METHOD_SHIFT_BITS;
@@ -175,6 +182,15 @@ impl SpritePipelineKey {
             SpritePipelineKey::NONE
         }
     }
+
+    #[inline]
+    pub const fn from_normal_mapped(normal_mapped: bool) -> Self {
+        if normal_mapped {
+            SpritePipelineKey::NORMAL_MAP
+        } else {
+            SpritePipelineKey::NONE
+        }
+    }
 }
 
 impl SpecializedRenderPipeline for SpritePipeline {
@@ -182,6 +198,13 @@ impl SpecializedRenderPipeline for SpritePipeline {
 
     fn specialize(&self, key: Self::Key) -> RenderPipelineDescriptor {
         let mut shader_defs = Vec::new();
+        let mut layout = vec![self.view_layout.clone(), self.material_layout.clone()];
+        if key.contains(SpritePipelineKey::NORMAL_MAP) {
+            shader_defs.push("NORMAL_MAP".into());
+            // The normal map is bound like the image of the sprite.
+            layout.push(self.material_layout.clone());
+        }
+
         if key.contains(SpritePipelineKey::TONEMAP_IN_SHADER) {
             shader_defs.push("TONEMAP_IN_SHADER".into());
 
@@ -271,7 +294,7 @@ impl SpecializedRenderPipeline for SpritePipeline {
                     write_mask: ColorWrites::ALL,
                 })],
             }),
-            layout: vec![self.view_layout.clone(), self.material_layout.clone()],
+            layout,
             primitive: PrimitiveState {
                 front_face: FrontFace::Ccw,
                 cull_mode: None,
@@ -303,6 +326,8 @@ pub struct ExtractedSprite {
     /// Asset ID of the [`Image`] of this sprite
     /// PERF: storing an `AssetId` instead of `Handle<Image>` enables some optimizations (`ExtractedSprite` becomes `Copy` and doesn't need to be dropped)
     pub image_handle_id: AssetId<Image>,
+    /// Asset ID of the [`Image`] of the normal map of this sprite, if any
+    pub normal_map_handle_id: Option<AssetId<Image>>,
     pub flip_x: bool,
     pub flip_y: bool,
     pub anchor: Vec2,
@@ -344,21 +369,25 @@ pub fn extract_sprites(
             &Sprite,
             &GlobalTransform,
             &Handle<Image>,
+            Option<&SpriteNormalMap>,
             Option<&TextureAtlas>,
             Option<&ComputedTextureSlices>,
         )>,
     >,
 ) {
     extracted_sprites.sprites.clear();
-    for (entity, view_visibility, sprite, transform, handle, sheet, slices) in sprite_query.iter() {
+    for (entity, view_visibility, sprite, transform, handle, normal_map, sheet, slices) in
+        sprite_query.iter()
+    {
         if !view_visibility.get() {
             continue;
         }
 
+        let normal_map_handle_id = normal_map.map(|normal_map| normal_map.0.id());
         if let Some(slices) = slices {
             extracted_sprites.sprites.extend(
                 slices
-                    .extract_sprites(transform, entity, sprite, handle)
+                    .extract_sprites(transform, entity, sprite, handle, normal_map_handle_id)
                     .map(|e| (commands.spawn_empty().id(), e)),
             );
         } else {
@@ -387,6 +416,7 @@ pub fn extract_sprites(
                     flip_x: sprite.flip_x,
                     flip_y: sprite.flip_y,
                     image_handle_id: handle.id(),
+                    normal_map_handle_id,
                     anchor: sprite.anchor.as_vec(),
                     original_entity: None,
                 },
@@ -440,6 +470,7 @@ impl Default for SpriteMeta {
 #[derive(Component, PartialEq, Eq, Clone)]
 pub struct SpriteBatch {
     image_handle_id: AssetId<Image>,
+    normal_map_handle_id: Option<AssetId<Image>>,
     range: Range<u32>,
 }
 
@@ -495,16 +526,18 @@ pub fn queue_sprites(
             }
         }
 
-        let pipeline = pipelines.specialize(
-            &pipeline_cache,
-            &sprite_pipeline,
-            view_key | SpritePipelineKey::from_colored(false),
-        );
-        let colored_pipeline = pipelines.specialize(
-            &pipeline_cache,
-            &sprite_pipeline,
-            view_key | SpritePipelineKey::from_colored(true),
-        );
+        // Indexed by whether the sprites are colored, then whether they are normal mapped.
+        let pipelines = [false, true].map(|colored| {
+            [false, true].map(|normal_mapped| {
+                pipelines.specialize(
+                    &pipeline_cache,
+                    &sprite_pipeline,
+                    view_key
+                        | SpritePipelineKey::from_colored(colored)
+                        | SpritePipelineKey::from_normal_mapped(normal_mapped),
+                )
+            })
+        });
 
         view_entities.clear();
         view_entities.extend(visible_entities.entities.iter().map(|e| e.index() as usize));
@@ -524,27 +557,17 @@ pub fn queue_sprites(
             let sort_key = FloatOrd(extracted_sprite.transform.translation().z);
 
             // Add the item to the render phase
-            if extracted_sprite.color != Color::WHITE {
-                transparent_phase.add(Transparent2d {
-                    draw_function: draw_sprite_function,
-                    pipeline: colored_pipeline,
-                    entity: *entity,
-                    sort_key,
-                    // batch_range and dynamic_offset will be calculated in prepare_sprites
-                    batch_range: 0..0,
-                    dynamic_offset: None,
-                });
-            } else {
-                transparent_phase.add(Transparent2d {
-                    draw_function: draw_sprite_function,
-                    pipeline,
-                    entity: *entity,
-                    sort_key,
-                    // batch_range and dynamic_offset will be calculated in prepare_sprites
-                    batch_range: 0..0,
-                    dynamic_offset: None,
-                });
-            }
+            let colored = extracted_sprite.color != Color::WHITE;
+            let normal_mapped = extracted_sprite.normal_map_handle_id.is_some();
+            transparent_phase.add(Transparent2d {
+                draw_function: draw_sprite_function,
+                pipeline: pipelines[colored as usize][normal_mapped as usize],
+                entity: *entity,
+                sort_key,
+                // batch_range and dynamic_offset will be calculated in prepare_sprites
+                batch_range: 0..0,
+                dynamic_offset: None,
+            });
         }
     }
 }
@@ -558,6 +581,7 @@ pub fn prepare_sprites(
     mut sprite_meta: ResMut<SpriteMeta>,
     view_uniforms: Res<ViewUniforms>,
     sprite_pipeline: Res<SpritePipeline>,
+    lights_meta: Res<Lights2dMeta>,
     mut image_bind_groups: ResMut<ImageBindGroups>,
     gpu_images: Res<RenderAssets<Image>>,
     extracted_sprites: Res<ExtractedSprites>,
@@ -577,7 +601,10 @@ pub fn prepare_sprites(
         };
     }
 
-    if let Some(view_binding) = view_uniforms.uniforms.binding() {
+    if let (Some(view_binding), Some(lights_binding)) = (
+        view_uniforms.uniforms.binding(),
+        lights_meta.uniform.binding(),
+    ) {
         let mut batches: Vec<(Entity, SpriteBatch)> = Vec::with_capacity(*previous_len);
 
         // Clear the sprite instances
@@ -586,7 +613,7 @@ pub fn prepare_sprites(
         sprite_meta.view_bind_group = Some(render_device.create_bind_group(
             "sprite_view_bind_group",
             &sprite_pipeline.view_layout,
-            &BindGroupEntries::single(view_binding),
+            &BindGroupEntries::with_indices(((0, view_binding), (2, lights_binding))),
         ));
 
         // Index buffer indices
@@ -598,6 +625,7 @@ pub fn prepare_sprites(
             let mut batch_item_index = 0;
             let mut batch_image_size = Vec2::ZERO;
             let mut batch_image_handle = AssetId::invalid();
+            let mut batch_normal_map_handle = None;
 
             // Iterate through the phase items and detect when successive sprites that can be batched.
             // Spawn an entity with a `SpriteBatch` component for each possible batch.
@@ -612,18 +640,31 @@ pub fn prepare_sprites(
                     continue;
                 };
 
-                let batch_image_changed = batch_image_handle != extracted_sprite.image_handle_id;
+                let batch_image_changed = batch_image_handle != extracted_sprite.image_handle_id
+                    || batch_normal_map_handle != extracted_sprite.normal_map_handle_id;
                 if batch_image_changed {
                     let Some(gpu_image) = gpu_images.get(extracted_sprite.image_handle_id) else {
                         continue;
                     };
+                    let gpu_normal_map = match extracted_sprite.normal_map_handle_id {
+                        Some(normal_map_handle_id) => {
+                            let Some(gpu_normal_map) = gpu_images.get(normal_map_handle_id) else {
+                                continue;
+                            };
+                            Some((normal_map_handle_id, gpu_normal_map))
+                        }
+                        None => None,
+                    };
 
                     batch_image_size = Vec2::new(gpu_image.size.x, gpu_image.size.y);
                     batch_image_handle = extracted_sprite.image_handle_id;
-                    image_bind_groups
-                        .values
-                        .entry(batch_image_handle)
-                        .or_insert_with(|| {
+                    batch_normal_map_handle = extracted_sprite.normal_map_handle_id;
+                    // The normal maps are bound with the same layout as the images.
+                    for (handle, gpu_image) in [(batch_image_handle, gpu_image)]
+                        .into_iter()
+                        .chain(gpu_normal_map)
+                    {
+                        image_bind_groups.values.entry(handle).or_insert_with(|| {
                             render_device.create_bind_group(
                                 "sprite_material_bind_group",
                                 &sprite_pipeline.material_layout,
@@ -633,6 +674,7 @@ pub fn prepare_sprites(
                                 )),
                             )
                         });
+                    }
                 }
 
                 // By default, the size of the quad is the size of the texture
@@ -691,6 +733,7 @@ pub fn prepare_sprites(
                         item.entity,
                         SpriteBatch {
                             image_handle_id: batch_image_handle,
+                            normal_map_handle_id: batch_normal_map_handle,
                             range: index..index,
                         },
                     ));
@@ -741,6 +784,7 @@ pub type DrawSprite = (
     SetItemPipeline,
     SetSpriteViewBindGroup<0>,
     SetSpriteTextureBindGroup<1>,
+    SetSpriteNormalMapBindGroup<2>,
     DrawSpriteBatch,
 );
 
@@ -795,6 +839,36 @@ impl<P: PhaseItem, const I: usize> RenderCommand<P> for SetSpriteTextureBindGrou
     }
 }
 
+/// Sets the bind group of the normal map of the sprites, if they have one.
+pub struct SetSpriteNormalMapBindGroup<const I: usize>;
+impl<P: PhaseItem, const I: usize> RenderCommand<P> for SetSpriteNormalMapBindGroup<I> {
+    type Param = SRes<ImageBindGroups>;
+    type ViewQuery = ();
+    type ItemQuery = Read<SpriteBatch>;
+
+    fn render<'w>(
+        _item: &P,
+        _view: (),
+        batch: Option<&'_ SpriteBatch>,
+        image_bind_groups: SystemParamItem<'w, '_, Self::Param>,
+        pass: &mut TrackedRenderPass<'w>,
+    ) -> RenderCommandResult {
+        let image_bind_groups = image_bind_groups.into_inner();
+        let Some(batch) = batch else {
+            return RenderCommandResult::Failure;
+        };
+
+        if let Some(normal_map_handle_id) = batch.normal_map_handle_id {
+            pass.set_bind_group(
+                I,
+                image_bind_groups.values.get(&normal_map_handle_id).unwrap(),
+                &[],
+            );
+        }
+        RenderCommandResult::Success
+    }
+}
+
 pub struct DrawSpriteBatch;
 impl<P: PhaseItem> RenderCommand<P> for DrawSpriteBatch {
     type Param = SRes<SpriteMeta>;
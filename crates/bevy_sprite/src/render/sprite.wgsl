@@ -6,6 +6,7 @@
     maths::affine3_to_square,
     view::View,
 }
+#import bevy_sprite::light2d
 
 @group(0) @binding(0) var<uniform> view: View;
 
@@ -25,6 +26,12 @@ struct VertexOutput {
     @builtin(position) clip_position: vec4<f32>,
     @location(0) uv: vec2<f32>,
     @location(1) @interpolate(flat) color: vec4<f32>,
+    @location(2) world_position: vec2<f32>,
+#ifdef NORMAL_MAP
+    // The world space directions of the X and Y axes of the normal map.
+    @location(3) @interpolate(flat) tangent: vec2<f32>,
+    @location(4) @interpolate(flat) bitangent: vec2<f32>,
+#endif
 };
 
 @vertex
@@ -37,24 +44,50 @@ fn vertex(in: VertexInput) -> VertexOutput {
         0.0
     );
 
-    out.clip_position = view.view_proj * affine3_to_square(mat3x4<f32>(
+    let model = affine3_to_square(mat3x4<f32>(
         in.i_model_transpose_col0,
         in.i_model_transpose_col1,
         in.i_model_transpose_col2,
-    )) * vec4<f32>(vertex_position, 1.0);
+    ));
+    let world_position = model * vec4<f32>(vertex_position, 1.0);
+    out.clip_position = view.view_proj * world_position;
+    out.world_position = world_position.xy;
     out.uv = vec2<f32>(vertex_position.xy) * in.i_uv_offset_scale.zw + in.i_uv_offset_scale.xy;
     out.color = in.i_color;
 
+#ifdef NORMAL_MAP
+    // The axes of the normal map follow the UVs, which are flipped with the sprite. The V axis
+    // points down the image, while the Y axis of the normals points up.
+    out.tangent = normalize(model[0].xy) * sign(in.i_uv_offset_scale.z);
+    out.bitangent = normalize(model[1].xy) * -sign(in.i_uv_offset_scale.w);
+#endif
+
     return out;
 }
 
 @group(1) @binding(0) var sprite_texture: texture_2d<f32>;
 @group(1) @binding(1) var sprite_sampler: sampler;
 
+#ifdef NORMAL_MAP
+@group(2) @binding(0) var normal_map_texture: texture_2d<f32>;
+@group(2) @binding(1) var normal_map_sampler: sampler;
+#endif
+
 @fragment
 fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
     var color = in.color * textureSample(sprite_texture, sprite_sampler, in.uv);
 
+#ifdef NORMAL_MAP
+    let normal = textureSample(normal_map_texture, normal_map_sampler, in.uv).xyz * 2.0 - 1.0;
+    let world_normal = normalize(
+        vec3<f32>(in.tangent * normal.x + in.bitangent * normal.y, normal.z)
+    );
+    let light = light2d::lighting(in.world_position, world_normal, true);
+#else
+    let light = light2d::lighting(in.world_position, vec3<f32>(0.0, 0.0, 1.0), false);
+#endif
+    color = vec4<f32>(color.rgb * light, color.a);
+
 #ifdef TONEMAP_IN_SHADER
     color = tonemapping::tone_mapping(color, view.color_grading);
 #endif
@@ -1,7 +1,8 @@
+use bevy_asset::Handle;
 use bevy_ecs::{component::Component, reflect::ReflectComponent};
 use bevy_math::{Rect, Vec2};
 use bevy_reflect::{std_traits::ReflectDefault, Reflect};
-use bevy_render::color::Color;
+use bevy_render::{color::Color, texture::Image};
 
 use crate::TextureSlicer;
 
@@ -31,6 +32,15 @@ pub struct Sprite {
     pub anchor: Anchor,
 }
 
+/// The normal map of a sprite, shading it with the 2D lights.
+///
+/// The normal map is sampled like the image of the sprite, with the same rect, texture atlas,
+/// slices and flips, so it should have the same layout. Its normals are in the tangent space of
+/// the image, with green pointing up, and are usually stored in a linear texture.
+#[derive(Component, Debug, Default, Clone, PartialEq, Reflect)]
+#[reflect(Component, Default, PartialEq)]
+pub struct SpriteNormalMap(pub Handle<Image>);
+
 /// Controls how the image is altered when scaled.
 ///
 /// Note: This is not yet compatible with texture atlases
@@ -1,7 +1,7 @@
-use crate::{ExtractedSprite, ImageScaleMode, Sprite};
+use crate::{ExtractedSprite, ImageScaleMode, Sprite, TextureAtlas, TextureAtlasLayout};
 
 use super::TextureSlice;
-use bevy_asset::{AssetEvent, Assets, Handle};
+use bevy_asset::{AssetEvent, AssetId, Assets, Handle};
 use bevy_ecs::prelude::*;
 use bevy_math::{Rect, Vec2};
 use bevy_render::texture::Image;
@@ -23,6 +23,7 @@ impl ComputedTextureSlices {
     /// * `original_entity` - the sprite entity
     /// * `sprite` - The sprite component
     /// * `handle` - The sprite texture handle
+    /// * `normal_map_handle_id` - The sprite normal map, if any
     #[must_use]
     pub(crate) fn extract_sprites<'a>(
         &'a self,
@@ -30,6 +31,7 @@ impl ComputedTextureSlices {
         original_entity: Entity,
         sprite: &'a Sprite,
         handle: &'a Handle<Image>,
+        normal_map_handle_id: Option<AssetId<Image>>,
     ) -> impl ExactSizeIterator<Item = ExtractedSprite> + 'a {
         let mut flip = Vec2::ONE;
         let [mut flip_x, mut flip_y] = [false; 2];
@@ -53,6 +55,7 @@ impl ComputedTextureSlices {
                 flip_x,
                 flip_y,
                 image_handle_id: handle.id(),
+                normal_map_handle_id,
                 anchor: sprite.anchor.as_vec(),
             }
         })
@@ -60,15 +63,18 @@ impl ComputedTextureSlices {
 }
 
 /// Generates sprite slices for a `sprite` given a `scale_mode`. The slices
-/// will be computed according to the `image_handle` dimensions or the sprite rect.
+/// will be computed according to the `image_handle` dimensions, the section of the
+/// `atlas` or the sprite rect.
 ///
-/// Returns `None` if the image asset is not loaded
+/// Returns `None` if the image asset or the atlas layout is not loaded
 #[must_use]
 fn compute_sprite_slices(
     sprite: &Sprite,
     scale_mode: &ImageScaleMode,
     image_handle: &Handle<Image>,
     images: &Assets<Image>,
+    atlas: Option<&TextureAtlas>,
+    atlas_layouts: &Assets<TextureAtlasLayout>,
 ) -> Option<ComputedTextureSlices> {
     let image_size = images.get(image_handle).map(|i| {
         Vec2::new(
@@ -76,25 +82,34 @@ fn compute_sprite_slices(
             i.texture_descriptor.size.height as f32,
         )
     })?;
+    let atlas_rect = match atlas {
+        Some(atlas) => Some(atlas.texture_rect(atlas_layouts)?),
+        None => None,
+    };
+    // The sprite rect is relative to the section of the atlas, like in `extract_sprites`
+    let texture_rect = match (atlas_rect, sprite.rect) {
+        (None, None) => Rect {
+            min: Vec2::ZERO,
+            max: image_size,
+        },
+        (None, Some(sprite_rect)) => sprite_rect,
+        (Some(atlas_rect), None) => atlas_rect,
+        (Some(atlas_rect), Some(mut sprite_rect)) => {
+            sprite_rect.min += atlas_rect.min;
+            sprite_rect.max += atlas_rect.min;
+            sprite_rect
+        }
+    };
     let slices = match scale_mode {
-        ImageScaleMode::Sliced(slicer) => slicer.compute_slices(
-            sprite.rect.unwrap_or(Rect {
-                min: Vec2::ZERO,
-                max: image_size,
-            }),
-            sprite.custom_size,
-        ),
+        ImageScaleMode::Sliced(slicer) => slicer.compute_slices(texture_rect, sprite.custom_size),
         ImageScaleMode::Tiled {
             tile_x,
             tile_y,
             stretch_value,
         } => {
             let slice = TextureSlice {
-                texture_rect: sprite.rect.unwrap_or(Rect {
-                    min: Vec2::ZERO,
-                    max: image_size,
-                }),
-                draw_size: sprite.custom_size.unwrap_or(image_size),
+                texture_rect,
+                draw_size: sprite.custom_size.unwrap_or(texture_rect.size()),
                 offset: Vec2::ZERO,
             };
             slice.tiled(*stretch_value, (*tile_x, *tile_y))
@@ -109,7 +124,14 @@ pub(crate) fn compute_slices_on_asset_event(
     mut commands: Commands,
     mut events: EventReader<AssetEvent<Image>>,
     images: Res<Assets<Image>>,
-    sprites: Query<(Entity, &ImageScaleMode, &Sprite, &Handle<Image>)>,
+    atlas_layouts: Res<Assets<TextureAtlasLayout>>,
+    sprites: Query<(
+        Entity,
+        &ImageScaleMode,
+        &Sprite,
+        &Handle<Image>,
+        Option<&TextureAtlas>,
+    )>,
 ) {
     // We store the asset ids of added/modified image assets
     let added_handles: HashSet<_> = events
@@ -123,11 +145,18 @@ pub(crate) fn compute_slices_on_asset_event(
         return;
     }
     // We recompute the sprite slices for sprite entities with a matching asset handle id
-    for (entity, scale_mode, sprite, image_handle) in &sprites {
+    for (entity, scale_mode, sprite, image_handle, atlas) in &sprites {
         if !added_handles.contains(&image_handle.id()) {
             continue;
         }
-        if let Some(slices) = compute_sprite_slices(sprite, scale_mode, image_handle, &images) {
+        if let Some(slices) = compute_sprite_slices(
+            sprite,
+            scale_mode,
+            image_handle,
+            &images,
+            atlas,
+            &atlas_layouts,
+        ) {
             commands.entity(entity).insert(slices);
         }
     }
@@ -138,18 +167,119 @@ pub(crate) fn compute_slices_on_asset_event(
 pub(crate) fn compute_slices_on_sprite_change(
     mut commands: Commands,
     images: Res<Assets<Image>>,
+    atlas_layouts: Res<Assets<TextureAtlasLayout>>,
     changed_sprites: Query<
-        (Entity, &ImageScaleMode, &Sprite, &Handle<Image>),
+        (
+            Entity,
+            &ImageScaleMode,
+            &Sprite,
+            &Handle<Image>,
+            Option<&TextureAtlas>,
+        ),
         Or<(
             Changed<ImageScaleMode>,
             Changed<Handle<Image>>,
             Changed<Sprite>,
+            Changed<TextureAtlas>,
         )>,
     >,
 ) {
-    for (entity, scale_mode, sprite, image_handle) in &changed_sprites {
-        if let Some(slices) = compute_sprite_slices(sprite, scale_mode, image_handle, &images) {
+    for (entity, scale_mode, sprite, image_handle, atlas) in &changed_sprites {
+        if let Some(slices) = compute_sprite_slices(
+            sprite,
+            scale_mode,
+            image_handle,
+            &images,
+            atlas,
+            &atlas_layouts,
+        ) {
             commands.entity(entity).insert(slices);
         }
     }
 }
+
+/// System removing the sprite slices of sprite entities whose [`ImageScaleMode`] was removed,
+/// so that they are drawn stretched again
+pub(crate) fn remove_slices_on_scale_mode_removal(
+    mut commands: Commands,
+    sliced_sprites: Query<Entity, (With<ComputedTextureSlices>, Without<ImageScaleMode>)>,
+) {
+    for entity in &sliced_sprites {
+        commands.entity(entity).remove::<ComputedTextureSlices>();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BorderRect, TextureSlicer};
+    use bevy_render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    };
+
+    #[test]
+    fn sliced_sprites_use_their_atlas_section() {
+        let mut images = Assets::<Image>::default();
+        let image = images.add(Image::new_fill(
+            Extent3d {
+                width: 64,
+                height: 32,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            &[255; 4],
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::default(),
+        ));
+        let mut atlas_layouts = Assets::<TextureAtlasLayout>::default();
+        let atlas = TextureAtlas {
+            layout: atlas_layouts.add(TextureAtlasLayout::from_grid(
+                Vec2::splat(32.),
+                2,
+                1,
+                None,
+                None,
+            )),
+            index: 1,
+        };
+        let section = Rect::new(32., 0., 64., 32.);
+        let sprite = Sprite {
+            custom_size: Some(Vec2::splat(100.)),
+            ..Default::default()
+        };
+        let scale_mode = ImageScaleMode::Sliced(TextureSlicer {
+            border: BorderRect::square(8.),
+            ..Default::default()
+        });
+
+        let slices = compute_sprite_slices(
+            &sprite,
+            &scale_mode,
+            &image,
+            &images,
+            Some(&atlas),
+            &atlas_layouts,
+        )
+        .unwrap();
+        assert_eq!(slices.0.len(), 9);
+        for slice in &slices.0 {
+            assert_eq!(slice.texture_rect.union(section), section);
+        }
+
+        // Without its layout, the atlas section is unknown.
+        let missing_atlas = TextureAtlas {
+            layout: Handle::default(),
+            index: 1,
+        };
+        assert!(compute_sprite_slices(
+            &sprite,
+            &scale_mode,
+            &image,
+            &images,
+            Some(&missing_atlas),
+            &atlas_layouts,
+        )
+        .is_none());
+    }
+}
@@ -7,7 +7,8 @@ pub use border_rect::BorderRect;
 pub use slicer::{SliceScaleMode, TextureSlicer};
 
 pub(crate) use computed_slices::{
-    compute_slices_on_asset_event, compute_slices_on_sprite_change, ComputedTextureSlices,
+    compute_slices_on_asset_event, compute_slices_on_sprite_change,
+    remove_slices_on_scale_mode_removal, ComputedTextureSlices,
 };
 
 /// Single texture slice, representing a texture rect to draw in a given area
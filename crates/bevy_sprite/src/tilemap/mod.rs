@@ -0,0 +1,403 @@
+//! Tilemaps, drawing large grids of tiles from a tileset in a few draw calls.
+//!
+//! The tiles of a [`Tilemap`] are stored in chunks. Only the chunks with changed tiles are
+//! uploaded to the GPU again, and each chunk is drawn in a single draw call when it is on screen.
+
+mod render;
+
+pub use render::*;
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use bevy_app::{App, Plugin, PostUpdate};
+use bevy_asset::{load_internal_asset, Handle};
+use bevy_core_pipeline::core_2d::Transparent2d;
+use bevy_ecs::{prelude::*, reflect::ReflectComponent};
+use bevy_math::{UVec2, Vec2};
+use bevy_reflect::{std_traits::ReflectDefault, Reflect};
+use bevy_render::{
+    color::Color,
+    primitives::Aabb,
+    render_phase::AddRenderCommand,
+    render_resource::{Shader, SpecializedRenderPipelines},
+    texture::Image,
+    view::{InheritedVisibility, NoFrustumCulling, ViewVisibility, Visibility, VisibilitySystems},
+    ExtractSchedule, Render, RenderApp, RenderSet,
+};
+use bevy_transform::components::{GlobalTransform, Transform};
+
+pub const TILEMAP_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(1630559283017384725);
+
+/// Adds the [`Tilemap`]s and their rendering.
+#[derive(Default)]
+pub struct TilemapPlugin;
+
+impl Plugin for TilemapPlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(
+            app,
+            TILEMAP_SHADER_HANDLE,
+            "tilemap.wgsl",
+            Shader::from_wgsl
+        );
+
+        app.register_type::<Tilemap>()
+            .register_type::<TilemapTexture>()
+            .add_systems(
+                PostUpdate,
+                calculate_tilemap_bounds.in_set(VisibilitySystems::CalculateBounds),
+            );
+
+        if let Ok(render_app) = app.get_sub_app_mut(RenderApp) {
+            render_app
+                .init_resource::<ExtractedTilemaps>()
+                .init_resource::<GpuTilemaps>()
+                .init_resource::<SpecializedRenderPipelines<TilemapPipeline>>()
+                .add_render_command::<Transparent2d, DrawTilemap>()
+                .add_systems(ExtractSchedule, extract_tilemaps)
+                .add_systems(
+                    Render,
+                    (
+                        queue_tilemaps.in_set(RenderSet::Queue),
+                        prepare_tilemaps.in_set(RenderSet::PrepareBindGroups),
+                    ),
+                );
+        }
+    }
+
+    fn finish(&self, app: &mut App) {
+        if let Ok(render_app) = app.get_sub_app_mut(RenderApp) {
+            render_app.init_resource::<TilemapPipeline>();
+        }
+    }
+}
+
+/// A tile of a [`Tilemap`].
+#[derive(Clone, Copy, Debug, PartialEq, Reflect)]
+#[reflect(Default, PartialEq)]
+pub struct Tile {
+    /// The index of the tile in the [`TilemapTexture`].
+    pub index: u32,
+    /// The tile's color tint
+    pub color: Color,
+    /// Flip the tile along the `X` axis
+    pub flip_x: bool,
+    /// Flip the tile along the `Y` axis
+    pub flip_y: bool,
+    /// Flip the tile along its diagonal, before the other flips. Combined with one of them, it
+    /// rotates the tile by 90 degrees.
+    pub flip_diagonal: bool,
+    /// Animates the tile through the tiles following its `index`.
+    pub animation: Option<TileAnimation>,
+}
+
+impl Default for Tile {
+    fn default() -> Self {
+        Self {
+            index: 0,
+            color: Color::WHITE,
+            flip_x: false,
+            flip_y: false,
+            flip_diagonal: false,
+            animation: None,
+        }
+    }
+}
+
+impl Tile {
+    /// Creates a tile drawing the tile at `index` in the [`TilemapTexture`].
+    pub fn new(index: u32) -> Self {
+        Self {
+            index,
+            ..Default::default()
+        }
+    }
+}
+
+/// The animation of a [`Tile`], looping through the `frame_count` tiles starting at its index in
+/// the [`TilemapTexture`].
+///
+/// The animations run on the GPU, so animated tiles don't need to be updated.
+#[derive(Clone, Copy, Debug, PartialEq, Reflect)]
+#[reflect(PartialEq)]
+pub struct TileAnimation {
+    pub frame_count: u16,
+    pub frames_per_second: f32,
+}
+
+/// The tileset of a [`Tilemap`], whose tiles are drawn by their [`Tile::index`].
+#[derive(Component, Clone, Debug, PartialEq, Reflect)]
+#[reflect(Component, Default, PartialEq)]
+pub enum TilemapTexture {
+    /// An image divided into a grid of tiles of `tile_size` pixels, separated by `padding` pixels,
+    /// and indexed row by row from the top left one.
+    Atlas {
+        image: Handle<Image>,
+        tile_size: UVec2,
+        padding: UVec2,
+    },
+    /// A 2D array texture with a tile in each layer, indexed by layer.
+    ///
+    /// The image should be reinterpreted as an array with
+    /// [`Image::reinterpret_stacked_2d_as_array`], and have a
+    /// [`TextureViewDimension::D2Array`](bevy_render::render_resource::TextureViewDimension::D2Array)
+    /// texture view.
+    Array(Handle<Image>),
+}
+
+impl Default for TilemapTexture {
+    fn default() -> Self {
+        Self::Array(Default::default())
+    }
+}
+
+impl TilemapTexture {
+    /// The image of the tileset.
+    pub fn image(&self) -> &Handle<Image> {
+        match self {
+            TilemapTexture::Atlas { image, .. } | TilemapTexture::Array(image) => image,
+        }
+    }
+}
+
+/// A chunk of the tiles of a [`Tilemap`], uploaded to the GPU when its `generation` changes.
+#[derive(Clone, Debug, Default, Reflect)]
+struct TilemapChunk {
+    /// The tiles of the chunk, row by row from the bottom left one.
+    tiles: Vec<Option<Tile>>,
+    #[reflect(ignore)]
+    generation: u64,
+}
+
+/// Returns a generation unique to the app, so that the chunks of different tilemaps, or of a
+/// replaced tilemap, never share one.
+fn next_generation() -> u64 {
+    static NEXT_GENERATION: AtomicU64 = AtomicU64::new(0);
+    NEXT_GENERATION.fetch_add(1, Ordering::Relaxed)
+}
+
+/// A grid of [`Tile`]s, drawn from its [`TilemapTexture`].
+///
+/// The tile at `(x, y)` covers the rectangle from `(x, y) * tile_size` to
+/// `(x + 1, y + 1) * tile_size` in the local space of the tilemap, so the tilemap extends right
+/// and up from its [`Transform`].
+#[derive(Component, Clone, Debug, Reflect)]
+#[reflect(Component, Default)]
+pub struct Tilemap {
+    /// The size of the tiles in world units.
+    pub tile_size: Vec2,
+    size: UVec2,
+    chunk_size: UVec2,
+    /// The chunks, row by row from the bottom left one.
+    chunks: Vec<TilemapChunk>,
+}
+
+impl Default for Tilemap {
+    fn default() -> Self {
+        Self::new(UVec2::ZERO, Vec2::ONE)
+    }
+}
+
+impl Tilemap {
+    /// The default number of tiles along each axis of the chunks.
+    pub const DEFAULT_CHUNK_SIZE: UVec2 = UVec2::splat(64);
+
+    /// Creates an empty tilemap of `size` tiles of `tile_size` world units.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the tilemap has more than 65536 tiles along an axis.
+    pub fn new(size: UVec2, tile_size: Vec2) -> Self {
+        Self::with_chunk_size(size, tile_size, Self::DEFAULT_CHUNK_SIZE)
+    }
+
+    /// Creates an empty tilemap like [`Tilemap::new`], with chunks of `chunk_size` tiles.
+    ///
+    /// Smaller chunks are culled more precisely and uploaded faster when a tile changes, but
+    /// take more draw calls.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the tilemap has more than 65536 tiles along an axis, or if a chunk has none.
+    pub fn with_chunk_size(size: UVec2, tile_size: Vec2, chunk_size: UVec2) -> Self {
+        assert!(
+            size.max_element() <= 1 << 16,
+            "tilemaps have at most 65536 tiles along each axis"
+        );
+        assert!(chunk_size.min_element() > 0, "chunks must have tiles");
+        let chunk_count = (size + chunk_size - 1) / chunk_size;
+        let chunks = (0..chunk_count.x * chunk_count.y)
+            .map(|_| TilemapChunk {
+                tiles: vec![None; (chunk_size.x * chunk_size.y) as usize],
+                generation: next_generation(),
+            })
+            .collect();
+        Self {
+            tile_size,
+            size,
+            chunk_size,
+            chunks,
+        }
+    }
+
+    /// The number of tiles along each axis.
+    pub fn size(&self) -> UVec2 {
+        self.size
+    }
+
+    /// The number of tiles along each axis of the chunks.
+    pub fn chunk_size(&self) -> UVec2 {
+        self.chunk_size
+    }
+
+    /// The number of chunks along each axis.
+    pub fn chunk_count(&self) -> UVec2 {
+        (self.size + self.chunk_size - 1) / self.chunk_size
+    }
+
+    /// The index of the chunk containing the tile at `position`, and the index of the tile in it.
+    fn indices(&self, position: UVec2) -> Option<(usize, usize)> {
+        if position.cmpge(self.size).any() {
+            return None;
+        }
+        let chunk = position / self.chunk_size;
+        let tile = position % self.chunk_size;
+        Some((
+            (chunk.y * self.chunk_count().x + chunk.x) as usize,
+            (tile.y * self.chunk_size.x + tile.x) as usize,
+        ))
+    }
+
+    /// The tile at `position`, if any.
+    pub fn get(&self, position: UVec2) -> Option<&Tile> {
+        let (chunk, tile) = self.indices(position)?;
+        self.chunks[chunk].tiles[tile].as_ref()
+    }
+
+    /// The tile at `position`, if any, to change it.
+    pub fn get_mut(&mut self, position: UVec2) -> Option<&mut Tile> {
+        let (chunk, tile) = self.indices(position)?;
+        let chunk = &mut self.chunks[chunk];
+        let tile = chunk.tiles[tile].as_mut()?;
+        chunk.generation = next_generation();
+        Some(tile)
+    }
+
+    /// Sets or removes the tile at `position`, returning the previous one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `position` is outside of the tilemap.
+    pub fn set(&mut self, position: UVec2, tile: impl Into<Option<Tile>>) -> Option<Tile> {
+        let Some((chunk, tile_index)) = self.indices(position) else {
+            panic!(
+                "the tile position {position} is outside of the tilemap of size {}",
+                self.size
+            );
+        };
+        let chunk = &mut self.chunks[chunk];
+        chunk.generation = next_generation();
+        std::mem::replace(&mut chunk.tiles[tile_index], tile.into())
+    }
+
+    /// Sets or removes all the tiles.
+    pub fn fill(&mut self, tile: impl Into<Option<Tile>>) {
+        let tile = tile.into();
+        for chunk in &mut self.chunks {
+            chunk.tiles.fill(tile);
+            chunk.generation = next_generation();
+        }
+    }
+
+    /// The position of the tile at the `local_position` in the tilemap, if it is inside it.
+    pub fn tile_position(&self, local_position: Vec2) -> Option<UVec2> {
+        let position = (local_position / self.tile_size).floor();
+        if position.cmplt(Vec2::ZERO).any() || position.cmpge(self.size.as_vec2()).any() {
+            return None;
+        }
+        Some(position.as_uvec2())
+    }
+
+    /// The size of the tilemap in world units.
+    pub fn world_size(&self) -> Vec2 {
+        self.size.as_vec2() * self.tile_size
+    }
+}
+
+/// A [`Bundle`] of components for drawing a [`Tilemap`].
+#[derive(Bundle, Clone, Debug, Default)]
+pub struct TilemapBundle {
+    pub tilemap: Tilemap,
+    pub texture: TilemapTexture,
+    /// The local transform of the tilemap, relative to its parent.
+    pub transform: Transform,
+    /// The absolute transform of the tilemap. This should generally not be written to directly.
+    pub global_transform: GlobalTransform,
+    /// User indication of whether an entity is visible
+    pub visibility: Visibility,
+    /// Inherited visibility of an entity.
+    pub inherited_visibility: InheritedVisibility,
+    /// Algorithmically-computed indication of whether an entity is visible and should be extracted for rendering
+    pub view_visibility: ViewVisibility,
+}
+
+/// Inserts the [`Aabb`] of the [`Tilemap`]s, whose chunks are then culled separately.
+pub fn calculate_tilemap_bounds(
+    mut commands: Commands,
+    tilemaps: Query<
+        (Entity, &Tilemap),
+        (
+            Or<(Without<Aabb>, Changed<Tilemap>)>,
+            Without<NoFrustumCulling>,
+        ),
+    >,
+) {
+    for (entity, tilemap) in &tilemaps {
+        let half_size = tilemap.world_size() / 2.0;
+        commands.entity(entity).try_insert(Aabb {
+            center: half_size.extend(0.0).into(),
+            half_extents: half_size.extend(0.0).into(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_math::{uvec2, vec2};
+
+    use super::*;
+
+    #[test]
+    fn tiles_are_set_in_their_chunk() {
+        let mut tilemap =
+            Tilemap::with_chunk_size(uvec2(10, 5), Vec2::splat(16.0), UVec2::splat(4));
+        assert_eq!(tilemap.chunk_count(), uvec2(3, 2));
+        let generations =
+            |tilemap: &Tilemap| Vec::from_iter(tilemap.chunks.iter().map(|chunk| chunk.generation));
+        let before = generations(&tilemap);
+
+        assert_eq!(tilemap.set(uvec2(9, 4), Tile::new(3)), None);
+        assert_eq!(tilemap.get(uvec2(9, 4)), Some(&Tile::new(3)));
+        assert_eq!(tilemap.get(uvec2(8, 4)), None);
+        assert_eq!(tilemap.get(uvec2(10, 4)), None);
+
+        // Only the chunk of the tile is uploaded again.
+        let after = generations(&tilemap);
+        assert_eq!(
+            Vec::from_iter((0..6).filter(|&chunk| before[chunk] != after[chunk])),
+            vec![5]
+        );
+
+        assert_eq!(tilemap.set(uvec2(9, 4), None), Some(Tile::new(3)));
+        assert_eq!(tilemap.get(uvec2(9, 4)), None);
+    }
+
+    #[test]
+    fn tile_positions_are_found_from_local_positions() {
+        let tilemap = Tilemap::new(uvec2(10, 5), Vec2::splat(16.0));
+        assert_eq!(tilemap.tile_position(vec2(0.0, 0.0)), Some(uvec2(0, 0)));
+        assert_eq!(tilemap.tile_position(vec2(159.0, 33.0)), Some(uvec2(9, 2)));
+        assert_eq!(tilemap.tile_position(vec2(160.0, 33.0)), None);
+        assert_eq!(tilemap.tile_position(vec2(-1.0, 33.0)), None);
+    }
+}
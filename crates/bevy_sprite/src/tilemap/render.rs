@@ -0,0 +1,668 @@
+use bevy_asset::AssetId;
+use bevy_core_pipeline::{
+    core_2d::Transparent2d,
+    tonemapping::{DebandDither, Tonemapping},
+};
+use bevy_ecs::{
+    entity::EntityHashMap,
+    prelude::*,
+    system::{lifetimeless::*, SystemParamItem},
+};
+use bevy_math::{Mat4, UVec2, Vec2};
+use bevy_render::{
+    primitives::{Aabb, Frustum},
+    render_asset::RenderAssets,
+    render_phase::{
+        DrawFunctions, PhaseItem, RenderCommand, RenderCommandResult, RenderPhase, SetItemPipeline,
+        TrackedRenderPass,
+    },
+    render_resource::{
+        binding_types::{sampler, texture_2d, texture_2d_array, uniform_buffer},
+        *,
+    },
+    renderer::{RenderDevice, RenderQueue},
+    texture::{BevyDefault, Image},
+    view::{ExtractedView, Msaa, ViewTarget, VisibleEntities},
+    Extract,
+};
+use bevy_transform::components::GlobalTransform;
+use bevy_utils::FloatOrd;
+use bytemuck::{Pod, Zeroable};
+
+use super::{Tilemap, TilemapTexture, TILEMAP_SHADER_HANDLE};
+use crate::{tonemapping_pipeline_key, Mesh2dPipeline, Mesh2dPipelineKey, SetMesh2dViewBindGroup};
+
+#[derive(Resource)]
+pub struct TilemapPipeline {
+    mesh2d_pipeline: Mesh2dPipeline,
+    atlas_layout: BindGroupLayout,
+    array_layout: BindGroupLayout,
+}
+
+impl FromWorld for TilemapPipeline {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+        let layout = |label, texture| {
+            render_device.create_bind_group_layout(
+                label,
+                &BindGroupLayoutEntries::sequential(
+                    ShaderStages::VERTEX_FRAGMENT,
+                    (
+                        uniform_buffer::<TilemapUniform>(false),
+                        texture,
+                        sampler(SamplerBindingType::Filtering),
+                    ),
+                ),
+            )
+        };
+        let atlas_layout = layout(
+            "tilemap_atlas_layout",
+            texture_2d(TextureSampleType::Float { filterable: true }),
+        );
+        let array_layout = layout(
+            "tilemap_array_layout",
+            texture_2d_array(TextureSampleType::Float { filterable: true }),
+        );
+
+        Self {
+            mesh2d_pipeline: world.resource::<Mesh2dPipeline>().clone(),
+            atlas_layout,
+            array_layout,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TilemapPipelineKey {
+    pub mesh_key: Mesh2dPipelineKey,
+    /// Whether the tiles are drawn from a [`TilemapTexture::Array`].
+    pub array_texture: bool,
+}
+
+impl SpecializedRenderPipeline for TilemapPipeline {
+    type Key = TilemapPipelineKey;
+
+    fn specialize(&self, key: Self::Key) -> RenderPipelineDescriptor {
+        let mut shader_defs = Vec::new();
+        if key.array_texture {
+            shader_defs.push("ARRAY_TEXTURE".into());
+        }
+
+        let mesh_key = key.mesh_key;
+        if mesh_key.contains(Mesh2dPipelineKey::TONEMAP_IN_SHADER) {
+            shader_defs.push("TONEMAP_IN_SHADER".into());
+
+            let method = mesh_key.intersection(Mesh2dPipelineKey::TONEMAP_METHOD_RESERVED_BITS);
+
+            if method == Mesh2dPipelineKey::TONEMAP_METHOD_NONE {
+                shader_defs.push("TONEMAP_METHOD_NONE".into());
+            } else if method == Mesh2dPipelineKey::TONEMAP_METHOD_REINHARD {
+                shader_defs.push("TONEMAP_METHOD_REINHARD".into());
+            } else if method == Mesh2dPipelineKey::TONEMAP_METHOD_REINHARD_LUMINANCE {
+                shader_defs.push("TONEMAP_METHOD_REINHARD_LUMINANCE".into());
+            } else if method == Mesh2dPipelineKey::TONEMAP_METHOD_ACES_FITTED {
+                shader_defs.push("TONEMAP_METHOD_ACES_FITTED".into());
+            } else if method == Mesh2dPipelineKey::TONEMAP_METHOD_AGX {
+                shader_defs.push("TONEMAP_METHOD_AGX".into());
+            } else if method == Mesh2dPipelineKey::TONEMAP_METHOD_SOMEWHAT_BORING_DISPLAY_TRANSFORM
+            {
+                shader_defs.push("TONEMAP_METHOD_SOMEWHAT_BORING_DISPLAY_TRANSFORM".into());
+            } else if method == Mesh2dPipelineKey::TONEMAP_METHOD_BLENDER_FILMIC {
+                shader_defs.push("TONEMAP_METHOD_BLENDER_FILMIC".into());
+            } else if method == Mesh2dPipelineKey::TONEMAP_METHOD_TONY_MC_MAPFACE {
+                shader_defs.push("TONEMAP_METHOD_TONY_MC_MAPFACE".into());
+            }
+
+            // Debanding is tied to tonemapping in the shader, cannot run without it.
+            if mesh_key.contains(Mesh2dPipelineKey::DEBAND_DITHER) {
+                shader_defs.push("DEBAND_DITHER".into());
+            }
+        }
+
+        let format = match mesh_key.contains(Mesh2dPipelineKey::HDR) {
+            true => ViewTarget::TEXTURE_FORMAT_HDR,
+            false => TextureFormat::bevy_default(),
+        };
+
+        let instance_rate_vertex_buffer_layout = VertexBufferLayout {
+            array_stride: std::mem::size_of::<TileInstance>() as u64,
+            step_mode: VertexStepMode::Instance,
+            attributes: vec![
+                // @location(0) i_position: vec2<u32>,
+                VertexAttribute {
+                    format: VertexFormat::Uint16x2,
+                    offset: 0,
+                    shader_location: 0,
+                },
+                // @location(1) i_index: u32,
+                VertexAttribute {
+                    format: VertexFormat::Uint32,
+                    offset: 4,
+                    shader_location: 1,
+                },
+                // @location(2) i_flags: u32,
+                VertexAttribute {
+                    format: VertexFormat::Uint32,
+                    offset: 8,
+                    shader_location: 2,
+                },
+                // @location(3) i_frames_per_second: f32,
+                VertexAttribute {
+                    format: VertexFormat::Float32,
+                    offset: 12,
+                    shader_location: 3,
+                },
+                // @location(4) i_color: vec4<f32>,
+                VertexAttribute {
+                    format: VertexFormat::Float32x4,
+                    offset: 16,
+                    shader_location: 4,
+                },
+            ],
+        };
+
+        let tilemap_layout = if key.array_texture {
+            self.array_layout.clone()
+        } else {
+            self.atlas_layout.clone()
+        };
+
+        RenderPipelineDescriptor {
+            vertex: VertexState {
+                shader: TILEMAP_SHADER_HANDLE,
+                entry_point: "vertex".into(),
+                shader_defs: shader_defs.clone(),
+                buffers: vec![instance_rate_vertex_buffer_layout],
+            },
+            fragment: Some(FragmentState {
+                shader: TILEMAP_SHADER_HANDLE,
+                shader_defs,
+                entry_point: "fragment".into(),
+                targets: vec![Some(ColorTargetState {
+                    format,
+                    blend: Some(BlendState::ALPHA_BLENDING),
+                    write_mask: ColorWrites::ALL,
+                })],
+            }),
+            layout: vec![self.mesh2d_pipeline.view_layout.clone(), tilemap_layout],
+            primitive: PrimitiveState {
+                front_face: FrontFace::Ccw,
+                cull_mode: None,
+                unclipped_depth: false,
+                polygon_mode: PolygonMode::Fill,
+                conservative: false,
+                topology: PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+            },
+            depth_stencil: None,
+            multisample: MultisampleState {
+                count: mesh_key.msaa_samples(),
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            label: Some("tilemap_pipeline".into()),
+            push_constant_ranges: Vec::new(),
+        }
+    }
+}
+
+// NOTE: These must match the bit flags in bevy_sprite/src/tilemap/tilemap.wgsl!
+bitflags::bitflags! {
+    #[repr(transparent)]
+    pub struct TileFlags: u32 {
+        const FLIP_X                  = 1 << 0;
+        const FLIP_Y                  = 1 << 1;
+        const FLIP_DIAGONAL           = 1 << 2;
+        const FRAME_COUNT_RESERVED_BITS = 0xFFFF << Self::FRAME_COUNT_SHIFT_BITS;
+        const NONE                    = 0;
+    }
+}
+
+impl TileFlags {
+    const FRAME_COUNT_SHIFT_BITS: u32 = 16;
+
+    pub fn from_frame_count(frame_count: u16) -> Self {
+        Self::from_bits_retain((frame_count as u32) << Self::FRAME_COUNT_SHIFT_BITS)
+    }
+}
+
+/// The instance data of a tile, uploaded with the other tiles of its chunk.
+#[repr(C)]
+#[derive(Copy, Clone, Pod, Zeroable)]
+pub struct TileInstance {
+    pub position: [u16; 2],
+    pub index: u32,
+    pub flags: u32,
+    pub frames_per_second: f32,
+    pub color: [f32; 4],
+}
+
+#[derive(Clone, ShaderType)]
+pub struct TilemapUniform {
+    pub model: Mat4,
+    pub tile_size: Vec2,
+    /// The size of the tiles in the atlas, in UV coordinates.
+    pub atlas_tile_size: Vec2,
+    /// The distance between the top left corners of neighbouring tiles in the atlas, in UV
+    /// coordinates.
+    pub atlas_tile_stride: Vec2,
+    pub atlas_columns: u32,
+}
+
+impl Default for TilemapUniform {
+    fn default() -> Self {
+        Self {
+            model: Mat4::IDENTITY,
+            tile_size: Vec2::ONE,
+            atlas_tile_size: Vec2::ONE,
+            atlas_tile_stride: Vec2::ONE,
+            atlas_columns: 1,
+        }
+    }
+}
+
+/// The phase item of a chunk of a tilemap, drawn when the chunk is on screen.
+#[derive(Component)]
+pub struct TilemapChunkItem {
+    pub tilemap: Entity,
+    pub chunk: usize,
+}
+
+pub struct ExtractedTilemapChunk {
+    /// The render world entity of the [`TilemapChunkItem`] of the chunk.
+    pub item: Entity,
+    pub generation: u64,
+    pub instance_count: u32,
+    /// The tiles of the chunk, if they changed since they were last uploaded.
+    pub instances: Option<Vec<TileInstance>>,
+}
+
+pub struct ExtractedTilemap {
+    pub transform: GlobalTransform,
+    pub size: UVec2,
+    pub tile_size: Vec2,
+    pub chunk_size: UVec2,
+    pub chunk_count: UVec2,
+    pub image: AssetId<Image>,
+    /// The tile size and padding of a [`TilemapTexture::Atlas`], in pixels.
+    pub atlas: Option<(UVec2, UVec2)>,
+    pub chunks: Vec<ExtractedTilemapChunk>,
+}
+
+#[derive(Resource, Default)]
+pub struct ExtractedTilemaps {
+    pub tilemaps: EntityHashMap<ExtractedTilemap>,
+}
+
+pub struct GpuTilemapChunk {
+    pub generation: u64,
+    pub instance_count: u32,
+    pub instances: BufferVec<TileInstance>,
+}
+
+pub struct GpuTilemap {
+    pub chunks: Vec<GpuTilemapChunk>,
+    pub uniform: UniformBuffer<TilemapUniform>,
+    pub bind_group: Option<BindGroup>,
+}
+
+/// The tiles of the tilemaps on the GPU, kept from frame to frame so that only the changed
+/// chunks are uploaded.
+#[derive(Resource, Default)]
+pub struct GpuTilemaps {
+    pub tilemaps: EntityHashMap<GpuTilemap>,
+}
+
+pub fn extract_tilemaps(
+    mut commands: Commands,
+    mut extracted_tilemaps: ResMut<ExtractedTilemaps>,
+    gpu_tilemaps: Res<GpuTilemaps>,
+    tilemaps: Extract<Query<(Entity, &Tilemap, &TilemapTexture, &GlobalTransform)>>,
+) {
+    extracted_tilemaps.tilemaps.clear();
+    for (entity, tilemap, texture, transform) in &tilemaps {
+        let gpu_chunks = gpu_tilemaps
+            .tilemaps
+            .get(&entity)
+            .map(|gpu_tilemap| &gpu_tilemap.chunks[..])
+            .filter(|gpu_chunks| gpu_chunks.len() == tilemap.chunks.len())
+            .unwrap_or_default();
+
+        let chunk_count = tilemap.chunk_count();
+        let chunks = tilemap
+            .chunks
+            .iter()
+            .enumerate()
+            .map(|(index, chunk)| {
+                let item = commands
+                    .spawn(TilemapChunkItem {
+                        tilemap: entity,
+                        chunk: index,
+                    })
+                    .id();
+                if let Some(gpu_chunk) = gpu_chunks
+                    .get(index)
+                    .filter(|gpu_chunk| gpu_chunk.generation == chunk.generation)
+                {
+                    return ExtractedTilemapChunk {
+                        item,
+                        generation: chunk.generation,
+                        instance_count: gpu_chunk.instance_count,
+                        instances: None,
+                    };
+                }
+
+                let origin = UVec2::new(index as u32 % chunk_count.x, index as u32 / chunk_count.x)
+                    * tilemap.chunk_size;
+                let instances: Vec<_> = chunk
+                    .tiles
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(tile_index, tile)| {
+                        let tile = tile.as_ref()?;
+                        let position = origin
+                            + UVec2::new(
+                                tile_index as u32 % tilemap.chunk_size.x,
+                                tile_index as u32 / tilemap.chunk_size.x,
+                            );
+                        let mut flags = TileFlags::NONE;
+                        flags.set(TileFlags::FLIP_X, tile.flip_x);
+                        flags.set(TileFlags::FLIP_Y, tile.flip_y);
+                        flags.set(TileFlags::FLIP_DIAGONAL, tile.flip_diagonal);
+                        let mut frames_per_second = 0.0;
+                        if let Some(animation) = tile.animation {
+                            flags |= TileFlags::from_frame_count(animation.frame_count);
+                            frames_per_second = animation.frames_per_second;
+                        }
+                        Some(TileInstance {
+                            position: [position.x as u16, position.y as u16],
+                            index: tile.index,
+                            flags: flags.bits(),
+                            frames_per_second,
+                            color: tile.color.as_linear_rgba_f32(),
+                        })
+                    })
+                    .collect();
+                ExtractedTilemapChunk {
+                    item,
+                    generation: chunk.generation,
+                    instance_count: instances.len() as u32,
+                    instances: Some(instances),
+                }
+            })
+            .collect();
+
+        let (image, atlas) = match texture {
+            TilemapTexture::Atlas {
+                image,
+                tile_size,
+                padding,
+            } => (image.id(), Some((*tile_size, *padding))),
+            TilemapTexture::Array(image) => (image.id(), None),
+        };
+        extracted_tilemaps.tilemaps.insert(
+            entity,
+            ExtractedTilemap {
+                transform: *transform,
+                size: tilemap.size,
+                tile_size: tilemap.tile_size,
+                chunk_size: tilemap.chunk_size,
+                chunk_count,
+                image,
+                atlas,
+                chunks,
+            },
+        );
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn queue_tilemaps(
+    draw_functions: Res<DrawFunctions<Transparent2d>>,
+    tilemap_pipeline: Res<TilemapPipeline>,
+    mut pipelines: ResMut<SpecializedRenderPipelines<TilemapPipeline>>,
+    pipeline_cache: Res<PipelineCache>,
+    msaa: Res<Msaa>,
+    extracted_tilemaps: Res<ExtractedTilemaps>,
+    gpu_images: Res<RenderAssets<Image>>,
+    mut views: Query<(
+        &mut RenderPhase<Transparent2d>,
+        &VisibleEntities,
+        &ExtractedView,
+        Option<&Tonemapping>,
+        Option<&DebandDither>,
+    )>,
+) {
+    if extracted_tilemaps.tilemaps.is_empty() {
+        return;
+    }
+
+    let draw_tilemap_function = draw_functions.read().id::<DrawTilemap>();
+
+    for (mut transparent_phase, visible_entities, view, tonemapping, dither) in &mut views {
+        let mut mesh_key = Mesh2dPipelineKey::from_msaa_samples(msaa.samples())
+            | Mesh2dPipelineKey::from_hdr(view.hdr);
+        if !view.hdr {
+            if let Some(tonemapping) = tonemapping {
+                mesh_key |= Mesh2dPipelineKey::TONEMAP_IN_SHADER;
+                mesh_key |= tonemapping_pipeline_key(*tonemapping);
+            }
+            if let Some(DebandDither::Enabled) = dither {
+                mesh_key |= Mesh2dPipelineKey::DEBAND_DITHER;
+            }
+        }
+        let pipelines = [false, true].map(|array_texture| {
+            pipelines.specialize(
+                &pipeline_cache,
+                &tilemap_pipeline,
+                TilemapPipelineKey {
+                    mesh_key,
+                    array_texture,
+                },
+            )
+        });
+
+        let view_projection = view
+            .view_projection
+            .unwrap_or_else(|| view.projection * view.transform.compute_matrix().inverse());
+        let frustum = Frustum::from_view_projection(&view_projection);
+
+        for entity in &visible_entities.entities {
+            let Some(tilemap) = extracted_tilemaps.tilemaps.get(entity) else {
+                continue;
+            };
+            if gpu_images.get(tilemap.image).is_none() {
+                continue;
+            }
+
+            let pipeline = pipelines[tilemap.atlas.is_none() as usize];
+            let affine = tilemap.transform.affine();
+            // These items will be sorted by depth with other phase items
+            let sort_key = FloatOrd(tilemap.transform.translation().z);
+            for (index, chunk) in tilemap.chunks.iter().enumerate() {
+                if chunk.instance_count == 0 {
+                    continue;
+                }
+
+                // Culls the chunks outside of the view.
+                let index = index as u32;
+                let min = UVec2::new(index % tilemap.chunk_count.x, index / tilemap.chunk_count.x)
+                    * tilemap.chunk_size;
+                let max = (min + tilemap.chunk_size).min(tilemap.size);
+                let aabb = Aabb::from_min_max(
+                    (min.as_vec2() * tilemap.tile_size).extend(0.0),
+                    (max.as_vec2() * tilemap.tile_size).extend(0.0),
+                );
+                if !frustum.intersects_obb(&aabb, &affine, true, false) {
+                    continue;
+                }
+
+                transparent_phase.add(Transparent2d {
+                    draw_function: draw_tilemap_function,
+                    pipeline,
+                    entity: chunk.item,
+                    sort_key,
+                    // The chunks are not batched
+                    batch_range: 0..1,
+                    dynamic_offset: None,
+                });
+            }
+        }
+    }
+}
+
+pub fn prepare_tilemaps(
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+    tilemap_pipeline: Res<TilemapPipeline>,
+    gpu_images: Res<RenderAssets<Image>>,
+    mut extracted_tilemaps: ResMut<ExtractedTilemaps>,
+    mut gpu_tilemaps: ResMut<GpuTilemaps>,
+) {
+    let extracted_tilemaps = &mut extracted_tilemaps.tilemaps;
+    gpu_tilemaps
+        .tilemaps
+        .retain(|entity, _| extracted_tilemaps.contains_key(entity));
+
+    for (entity, tilemap) in extracted_tilemaps.iter_mut() {
+        let gpu_tilemap = gpu_tilemaps
+            .tilemaps
+            .entry(*entity)
+            .or_insert_with(|| GpuTilemap {
+                chunks: Vec::new(),
+                uniform: UniformBuffer::default(),
+                bind_group: None,
+            });
+
+        // Uploads the changed chunks.
+        if gpu_tilemap.chunks.len() != tilemap.chunks.len() {
+            gpu_tilemap.chunks = tilemap
+                .chunks
+                .iter()
+                .map(|_| GpuTilemapChunk {
+                    generation: u64::MAX,
+                    instance_count: 0,
+                    instances: BufferVec::new(BufferUsages::VERTEX),
+                })
+                .collect();
+        }
+        for (chunk, gpu_chunk) in tilemap.chunks.iter_mut().zip(&mut gpu_tilemap.chunks) {
+            let Some(instances) = chunk.instances.take() else {
+                continue;
+            };
+            gpu_chunk.generation = chunk.generation;
+            gpu_chunk.instance_count = chunk.instance_count;
+            gpu_chunk.instances.clear();
+            gpu_chunk.instances.extend(instances);
+            gpu_chunk
+                .instances
+                .write_buffer(&render_device, &render_queue);
+            // The tiles are kept on the GPU only.
+            gpu_chunk.instances.clear();
+        }
+
+        let Some(gpu_image) = gpu_images.get(tilemap.image) else {
+            gpu_tilemap.bind_group = None;
+            continue;
+        };
+        let (atlas_tile_size, atlas_tile_stride, atlas_columns) = match tilemap.atlas {
+            Some((tile_size, padding)) => {
+                let stride = tile_size + padding;
+                let columns = (gpu_image.size.x as u32 + padding.x) / stride.x.max(1);
+                (
+                    tile_size.as_vec2() / gpu_image.size,
+                    stride.as_vec2() / gpu_image.size,
+                    columns.max(1),
+                )
+            }
+            None => (Vec2::ONE, Vec2::ONE, 1),
+        };
+        gpu_tilemap.uniform.set(TilemapUniform {
+            model: tilemap.transform.compute_matrix(),
+            tile_size: tilemap.tile_size,
+            atlas_tile_size,
+            atlas_tile_stride,
+            atlas_columns,
+        });
+        gpu_tilemap
+            .uniform
+            .write_buffer(&render_device, &render_queue);
+
+        let Some(uniform) = gpu_tilemap.uniform.binding() else {
+            continue;
+        };
+        let layout = match tilemap.atlas {
+            Some(_) => &tilemap_pipeline.atlas_layout,
+            None => &tilemap_pipeline.array_layout,
+        };
+        gpu_tilemap.bind_group = Some(render_device.create_bind_group(
+            "tilemap_bind_group",
+            layout,
+            &BindGroupEntries::sequential((uniform, &gpu_image.texture_view, &gpu_image.sampler)),
+        ));
+    }
+}
+
+pub type DrawTilemap = (
+    SetItemPipeline,
+    SetMesh2dViewBindGroup<0>,
+    SetTilemapBindGroup<1>,
+    DrawTilemapChunk,
+);
+
+pub struct SetTilemapBindGroup<const I: usize>;
+impl<P: PhaseItem, const I: usize> RenderCommand<P> for SetTilemapBindGroup<I> {
+    type Param = SRes<GpuTilemaps>;
+    type ViewQuery = ();
+    type ItemQuery = Read<TilemapChunkItem>;
+
+    fn render<'w>(
+        _item: &P,
+        _view: (),
+        chunk_item: Option<&'_ TilemapChunkItem>,
+        gpu_tilemaps: SystemParamItem<'w, '_, Self::Param>,
+        pass: &mut TrackedRenderPass<'w>,
+    ) -> RenderCommandResult {
+        let Some(bind_group) = chunk_item
+            .and_then(|chunk_item| gpu_tilemaps.into_inner().tilemaps.get(&chunk_item.tilemap))
+            .and_then(|gpu_tilemap| gpu_tilemap.bind_group.as_ref())
+        else {
+            return RenderCommandResult::Failure;
+        };
+        pass.set_bind_group(I, bind_group, &[]);
+        RenderCommandResult::Success
+    }
+}
+
+pub struct DrawTilemapChunk;
+impl<P: PhaseItem> RenderCommand<P> for DrawTilemapChunk {
+    type Param = SRes<GpuTilemaps>;
+    type ViewQuery = ();
+    type ItemQuery = Read<TilemapChunkItem>;
+
+    fn render<'w>(
+        _item: &P,
+        _view: (),
+        chunk_item: Option<&'_ TilemapChunkItem>,
+        gpu_tilemaps: SystemParamItem<'w, '_, Self::Param>,
+        pass: &mut TrackedRenderPass<'w>,
+    ) -> RenderCommandResult {
+        let Some(chunk) = chunk_item.and_then(|chunk_item| {
+            gpu_tilemaps
+                .into_inner()
+                .tilemaps
+                .get(&chunk_item.tilemap)?
+                .chunks
+                .get(chunk_item.chunk)
+        }) else {
+            return RenderCommandResult::Failure;
+        };
+        let Some(buffer) = chunk.instances.buffer() else {
+            return RenderCommandResult::Failure;
+        };
+        pass.set_vertex_buffer(0, buffer.slice(..));
+        // The 6 vertices of the two triangles of each tile are computed in the vertex shader.
+        pass.draw(0..6, 0..chunk.instance_count);
+        RenderCommandResult::Success
+    }
+}
@@ -0,0 +1,113 @@
+#ifdef TONEMAP_IN_SHADER
+#import bevy_core_pipeline::tonemapping
+#endif
+
+#import bevy_sprite::{
+    mesh2d_view_bindings::{view, globals},
+    light2d,
+}
+
+// NOTE: These must match the bit flags in bevy_sprite/src/tilemap/render.rs!
+const TILE_FLAGS_FLIP_X_BIT: u32 = 1u;
+const TILE_FLAGS_FLIP_Y_BIT: u32 = 2u;
+const TILE_FLAGS_FLIP_DIAGONAL_BIT: u32 = 4u;
+const TILE_FLAGS_FRAME_COUNT_SHIFT_BITS: u32 = 16u;
+
+struct Tilemap {
+    model: mat4x4<f32>,
+    tile_size: vec2<f32>,
+    // The size of the tiles in the atlas, and the distance between their top left corners, in
+    // UV coordinates.
+    atlas_tile_size: vec2<f32>,
+    atlas_tile_stride: vec2<f32>,
+    atlas_columns: u32,
+};
+
+@group(1) @binding(0) var<uniform> tilemap: Tilemap;
+#ifdef ARRAY_TEXTURE
+@group(1) @binding(1) var tile_texture: texture_2d_array<f32>;
+#else
+@group(1) @binding(1) var tile_texture: texture_2d<f32>;
+#endif
+@group(1) @binding(2) var tile_sampler: sampler;
+
+struct VertexInput {
+    @builtin(vertex_index) index: u32,
+    // NOTE: Instance-rate vertex buffer members prefixed with i_
+    @location(0) i_position: vec2<u32>,
+    @location(1) i_index: u32,
+    @location(2) i_flags: u32,
+    @location(3) i_frames_per_second: f32,
+    @location(4) i_color: vec4<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) @interpolate(flat) color: vec4<f32>,
+    @location(2) world_position: vec2<f32>,
+#ifdef ARRAY_TEXTURE
+    @location(3) @interpolate(flat) layer: u32,
+#endif
+};
+
+@vertex
+fn vertex(in: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+
+    // The corners of the two triangles of the tile are (0, 0), (1, 0), (0, 1), then (0, 1),
+    // (1, 0), (1, 1), whose coordinates are stored in the bits of these masks.
+    let corner = vec2<f32>(vec2<u32>((0x32u >> in.index) & 1u, (0x2Cu >> in.index) & 1u));
+
+    let local_position = (vec2<f32>(in.i_position) + corner) * tilemap.tile_size;
+    let world_position = tilemap.model * vec4<f32>(local_position, 0.0, 1.0);
+    out.clip_position = view.view_proj * world_position;
+    out.world_position = world_position.xy;
+    out.color = in.i_color;
+
+    // The Y axis of the textures points down.
+    var uv = vec2<f32>(corner.x, 1.0 - corner.y);
+    if (in.i_flags & TILE_FLAGS_FLIP_DIAGONAL_BIT) != 0u {
+        uv = uv.yx;
+    }
+    if (in.i_flags & TILE_FLAGS_FLIP_X_BIT) != 0u {
+        uv.x = 1.0 - uv.x;
+    }
+    if (in.i_flags & TILE_FLAGS_FLIP_Y_BIT) != 0u {
+        uv.y = 1.0 - uv.y;
+    }
+
+    var index = in.i_index;
+    let frame_count = in.i_flags >> TILE_FLAGS_FRAME_COUNT_SHIFT_BITS;
+    if frame_count > 1u {
+        index += u32(globals.time * in.i_frames_per_second) % frame_count;
+    }
+
+#ifdef ARRAY_TEXTURE
+    out.uv = uv;
+    out.layer = index;
+#else
+    let cell = vec2<u32>(index % tilemap.atlas_columns, index / tilemap.atlas_columns);
+    out.uv = vec2<f32>(cell) * tilemap.atlas_tile_stride + uv * tilemap.atlas_tile_size;
+#endif
+
+    return out;
+}
+
+@fragment
+fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+#ifdef ARRAY_TEXTURE
+    var color = in.color * textureSample(tile_texture, tile_sampler, in.uv, in.layer);
+#else
+    var color = in.color * textureSample(tile_texture, tile_sampler, in.uv);
+#endif
+
+    let light = light2d::lighting(in.world_position, vec3<f32>(0.0, 0.0, 1.0), false);
+    color = vec4<f32>(color.rgb * light, color.a);
+
+#ifdef TONEMAP_IN_SHADER
+    color = tonemapping::tone_mapping(color, view.color_grading);
+#endif
+
+    return color;
+}
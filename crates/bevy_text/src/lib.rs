@@ -8,6 +8,7 @@ mod font_atlas_set;
 mod font_loader;
 mod glyph_brush;
 mod pipeline;
+mod shaping;
 mod text;
 mod text2d;
 
@@ -27,9 +28,9 @@ pub mod prelude {
 }
 
 use bevy_app::prelude::*;
-use bevy_asset::AssetApp;
 #[cfg(feature = "default_font")]
-use bevy_asset::{load_internal_binary_asset, Handle};
+use bevy_asset::load_internal_binary_asset;
+use bevy_asset::{AssetApp, Handle};
 use bevy_ecs::prelude::*;
 use bevy_render::{camera::CameraUpdateSystem, ExtractSchedule, RenderApp};
 use bevy_sprite::SpriteSystem;
@@ -51,6 +52,12 @@ pub struct TextSettings {
     /// Allows font size to be set dynamically exceeding the amount set in `soft_max_font_atlases`.
     /// Note each font size has to be generated which can have a strong performance impact.
     pub allow_dynamic_font_size: bool,
+    /// Fonts used, in order, for the chars missing from the font of their [`TextSection`], like
+    /// emoji or the letters of other scripts.
+    ///
+    /// Only glyphs with outlines are drawn, so emoji need a font with outlined emoji, not color
+    /// bitmaps. The fallback fonts must be loaded before the text using them is laid out.
+    pub font_fallbacks: Vec<Handle<Font>>,
 }
 
 impl Default for TextSettings {
@@ -58,6 +65,7 @@ impl Default for TextSettings {
         Self {
             soft_max_font_atlases: NonZeroUsize::new(16).unwrap(),
             allow_dynamic_font_size: false,
+            font_fallbacks: Vec::new(),
         }
     }
 }
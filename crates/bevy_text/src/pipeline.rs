@@ -1,6 +1,7 @@
 use crate::{
-    compute_text_bounds, error::TextError, glyph_brush::GlyphBrush, scale_value, BreakLineOn, Font,
-    FontAtlasSets, JustifyText, PositionedGlyph, Text, TextSection, TextSettings, YAxisOrientation,
+    compute_text_bounds, error::TextError, glyph_brush::GlyphBrush, scale_value, shaping,
+    BreakLineOn, Font, FontAtlasSets, JustifyText, PositionedGlyph, Text, TextSection,
+    TextSettings, YAxisOrientation,
 };
 use ab_glyph::PxScale;
 use bevy_asset::{AssetId, Assets, Handle};
@@ -55,41 +56,46 @@ impl TextPipeline {
         text_settings: &TextSettings,
         y_axis_orientation: YAxisOrientation,
     ) -> Result<TextLayoutInfo, TextError> {
-        let mut scaled_fonts = Vec::with_capacity(sections.len());
-        let sections = sections
+        let runs = shaping::shape_sections(sections, fonts, &text_settings.font_fallbacks)?;
+
+        let mut scaled_fonts = Vec::with_capacity(runs.len());
+        let run_sections = runs
             .iter()
-            .map(|section| {
-                let font = fonts
-                    .get(&section.style.font)
-                    .ok_or(TextError::NoSuchFont)?;
-                let font_id = self.get_or_insert_font_id(&section.style.font, font);
-                let font_size = scale_value(section.style.font_size, scale_factor);
+            .map(|run| {
+                let font = fonts.get(&run.font).ok_or(TextError::NoSuchFont)?;
+                let font_id = self.get_or_insert_font_id(&run.font, font);
+                let font_size = scale_value(run.font_size, scale_factor);
 
                 scaled_fonts.push(ab_glyph::Font::as_scaled(&font.font, font_size));
 
                 let section = SectionText {
                     font_id,
                     scale: PxScale::from(font_size),
-                    text: &section.value,
+                    text: &run.text,
                 };
 
                 Ok(section)
             })
             .collect::<Result<Vec<_>, _>>()?;
 
-        let section_glyphs =
+        let mut section_glyphs =
             self.brush
-                .compute_glyphs(&sections, bounds, text_alignment, linebreak_behavior)?;
+                .compute_glyphs(&run_sections, bounds, text_alignment, linebreak_behavior)?;
 
         if section_glyphs.is_empty() {
             return Ok(TextLayoutInfo::default());
         }
 
+        let run_fonts: Vec<_> = scaled_fonts.iter().map(|font| font.font).collect();
+        shaping::reorder_glyphs(&mut section_glyphs, &runs, &run_fonts, |glyph| {
+            ab_glyph::ScaleFont::h_advance(&scaled_fonts[glyph.section_index], glyph.glyph.id)
+        });
+
         let size = compute_text_bounds(&section_glyphs, |index| scaled_fonts[index]).size();
 
-        let glyphs = self.brush.process_glyphs(
+        let mut glyphs = self.brush.process_glyphs(
             section_glyphs,
-            &sections,
+            &run_sections,
             font_atlas_sets,
             fonts,
             texture_atlases,
@@ -98,6 +104,15 @@ impl TextPipeline {
             y_axis_orientation,
         )?;
 
+        // Point the glyphs back to the sections of the text.
+        for glyph in &mut glyphs {
+            let run = &runs[glyph.section_index];
+            glyph.section_index = run.section_index;
+            if let Some(char) = run.char_at(glyph.byte_index) {
+                glyph.byte_index = char.section_byte_index;
+            }
+        }
+
         Ok(TextLayoutInfo {
             glyphs,
             logical_size: size,
@@ -123,33 +138,31 @@ pub struct TextMeasureInfo {
 }
 
 impl TextMeasureInfo {
+    /// Prepares the measurement of `text`, drawing the chars missing from its fonts with the
+    /// first of the `font_fallbacks` that has them, like [`TextPipeline::queue_text`].
     pub fn from_text(
         text: &Text,
         fonts: &Assets<Font>,
+        font_fallbacks: &[Handle<Font>],
         scale_factor: f32,
     ) -> Result<TextMeasureInfo, TextError> {
-        let sections = &text.sections;
-        for section in sections {
-            if !fonts.contains(&section.style.font) {
-                return Err(TextError::NoSuchFont);
-            }
-        }
-        let (auto_fonts, sections) = sections
-            .iter()
+        let runs = shaping::shape_sections(&text.sections, fonts, font_fallbacks)?;
+        let (auto_fonts, sections) = runs
+            .into_iter()
             .enumerate()
-            .map(|(i, section)| {
-                // SAFETY: we exited early earlier in this function if
-                // one of the fonts was missing.
-                let font = unsafe { fonts.get(&section.style.font).unwrap_unchecked() };
-                (
+            .map(|(i, run)| {
+                let font = fonts.get(&run.font).ok_or(TextError::NoSuchFont)?;
+                Ok((
                     font.font.clone(),
                     TextMeasureSection {
                         font_id: FontId(i),
-                        scale: scale_value(section.style.font_size, scale_factor),
-                        text: section.value.clone().into_boxed_str(),
+                        scale: scale_value(run.font_size, scale_factor),
+                        text: run.text.into_boxed_str(),
                     },
-                )
+                ))
             })
+            .collect::<Result<Vec<_>, TextError>>()?
+            .into_iter()
             .unzip();
 
         Ok(Self::new(
@@ -0,0 +1,556 @@
+//! Prepares the sections of a [`Text`](crate::Text) for layout, and reorders the laid out glyphs.
+//!
+//! - Arabic letters are replaced by their contextual forms, joining them to their neighbors.
+//! - Chars missing from the font of their section are drawn with the first font of
+//!   [`TextSettings::font_fallbacks`](crate::TextSettings::font_fallbacks) that has them.
+//! - Right-to-left text, like Hebrew and Arabic, is reordered line by line with a simplified
+//!   version of the Unicode bidirectional algorithm, without explicit embeddings.
+
+use ab_glyph::{Font as _, FontArc, GlyphId};
+use bevy_asset::{Assets, Handle};
+use glyph_brush_layout::SectionGlyph;
+
+use crate::{error::TextError, Font, TextSection};
+
+/// A run of text drawn with a single font, from a single [`TextSection`].
+#[derive(Debug, Clone)]
+pub(crate) struct ShapedRun {
+    pub text: String,
+    pub font: Handle<Font>,
+    pub font_size: f32,
+    pub section_index: usize,
+    /// The chars of the text, in order.
+    pub chars: Vec<ShapedChar>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ShapedChar {
+    pub char: char,
+    /// The index of the char in the text of the run.
+    pub byte_index: usize,
+    /// The index of the char the char was shaped from in the value of its section.
+    pub section_byte_index: usize,
+    /// The bidirectional embedding level of the char: odd levels are right-to-left.
+    pub level: u8,
+    /// The embedding level of the paragraph of the char.
+    pub paragraph_level: u8,
+}
+
+impl ShapedRun {
+    /// The char at `byte_index` in the text of the run.
+    pub fn char_at(&self, byte_index: usize) -> Option<&ShapedChar> {
+        self.chars
+            .binary_search_by_key(&byte_index, |char| char.byte_index)
+            .ok()
+            .map(|index| &self.chars[index])
+    }
+}
+
+/// A char of the sections of a text, before shaping.
+#[derive(Clone, Copy)]
+struct SourceChar {
+    char: char,
+    section_index: usize,
+    byte_index: usize,
+}
+
+/// Shapes the `sections` of a text into runs with a single font.
+///
+/// Returns [`TextError::NoSuchFont`] if the font of a section isn't loaded. The fallback fonts that
+/// aren't loaded are ignored.
+pub(crate) fn shape_sections(
+    sections: &[TextSection],
+    fonts: &Assets<Font>,
+    font_fallbacks: &[Handle<Font>],
+) -> Result<Vec<ShapedRun>, TextError> {
+    let section_fonts = sections
+        .iter()
+        .map(|section| fonts.get(&section.style.font).ok_or(TextError::NoSuchFont))
+        .collect::<Result<Vec<_>, _>>()?;
+    let fallback_fonts: Vec<_> = font_fallbacks
+        .iter()
+        .filter_map(|handle| Some((handle, fonts.get(handle)?)))
+        .collect();
+
+    let source: Vec<SourceChar> = sections
+        .iter()
+        .enumerate()
+        .flat_map(|(section_index, section)| {
+            section
+                .value
+                .char_indices()
+                .map(move |(byte_index, char)| SourceChar {
+                    char,
+                    section_index,
+                    byte_index,
+                })
+        })
+        .collect();
+    let chars: Vec<char> = source.iter().map(|source| source.char).collect();
+    let (levels, paragraph_levels) = resolve_levels(&chars);
+
+    let mut runs: Vec<ShapedRun> = Vec::new();
+    for (char, index) in join_arabic(&chars) {
+        if is_default_ignorable(char) {
+            continue;
+        }
+        let SourceChar {
+            section_index,
+            byte_index,
+            ..
+        } = source[index];
+        let section = &sections[section_index];
+        let has_glyph = |font: &FontArc| font.glyph_id(char) != GlyphId(0);
+        let continues_run = runs
+            .last()
+            .is_some_and(|run| run.section_index == section_index);
+        let font = if has_glyph(&section_fonts[section_index].font) {
+            &section.style.font
+        } else if char.is_whitespace() && continues_run {
+            &runs.last().unwrap().font
+        } else {
+            fallback_fonts
+                .iter()
+                .find(|(_, font)| has_glyph(&font.font))
+                .map_or(&section.style.font, |(handle, _)| handle)
+        };
+        if !(continues_run && runs.last().unwrap().font == *font) {
+            runs.push(ShapedRun {
+                text: String::new(),
+                font: font.clone(),
+                font_size: section.style.font_size,
+                section_index,
+                chars: Vec::new(),
+            });
+        }
+        let run = runs.last_mut().unwrap();
+        run.chars.push(ShapedChar {
+            char,
+            byte_index: run.text.len(),
+            section_byte_index: byte_index,
+            level: levels[index],
+            paragraph_level: paragraph_levels[index],
+        });
+        run.text.push(char);
+    }
+    Ok(runs)
+}
+
+/// Reorders the laid out `glyphs` of the `runs` of a text into their visual order, line by line,
+/// and mirrors the glyphs of right-to-left brackets.
+///
+/// `fonts` are the fonts of the runs.
+pub(crate) fn reorder_glyphs(
+    glyphs: &mut [SectionGlyph],
+    runs: &[ShapedRun],
+    fonts: &[&FontArc],
+    h_advance: impl Fn(&SectionGlyph) -> f32,
+) {
+    let char_at = |glyph: &SectionGlyph| runs[glyph.section_index].char_at(glyph.byte_index);
+
+    // The glyphs of a line share their baseline.
+    let mut line_start = 0;
+    while line_start < glyphs.len() {
+        let y = glyphs[line_start].glyph.position.y;
+        let line_end = glyphs[line_start..]
+            .iter()
+            .position(|glyph| glyph.glyph.position.y != y)
+            .map_or(glyphs.len(), |length| line_start + length);
+        let line = &mut glyphs[line_start..line_end];
+        line_start = line_end;
+
+        let mut levels: Vec<u8> = line
+            .iter()
+            .map(|glyph| char_at(glyph).map_or(0, |char| char.level))
+            .collect();
+        // Trailing whitespace is in the direction of the paragraph.
+        for (glyph, level) in line.iter().zip(levels.iter_mut()).rev() {
+            match char_at(glyph) {
+                Some(char) if char.char.is_whitespace() => *level = char.paragraph_level,
+                _ => break,
+            }
+        }
+        if levels.iter().all(|level| *level == 0) {
+            continue;
+        }
+
+        for (glyph, level) in line.iter_mut().zip(&levels) {
+            let Some(mirrored) = char_at(glyph)
+                .filter(|_| level % 2 == 1)
+                .and_then(|char| mirror(char.char))
+            else {
+                continue;
+            };
+            let id = fonts[glyph.section_index].glyph_id(mirrored);
+            if id != GlyphId(0) {
+                glyph.glyph.id = id;
+            }
+        }
+
+        // The advances of the glyphs in the line, including kerning.
+        let advances: Vec<f32> = line
+            .windows(2)
+            .map(|pair| pair[1].glyph.position.x - pair[0].glyph.position.x)
+            .chain(line.last().map(&h_advance))
+            .collect();
+        let mut x = line[0].glyph.position.x;
+        for index in visual_order(&levels) {
+            line[index].glyph.position.x = x;
+            x += advances[index];
+        }
+    }
+}
+
+/// The indices of a line of chars with the given embedding `levels` in their visual order,
+/// from left to right.
+fn visual_order(levels: &[u8]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..levels.len()).collect();
+    let max_level = levels.iter().copied().max().unwrap_or(0);
+    let min_odd_level = levels
+        .iter()
+        .copied()
+        .filter(|level| level % 2 == 1)
+        .min()
+        .unwrap_or(1);
+    // Reverse the runs at each level and above, from the highest level to the lowest odd level.
+    for level in (min_odd_level..=max_level).rev() {
+        let mut start = 0;
+        while start < order.len() {
+            if levels[order[start]] < level {
+                start += 1;
+                continue;
+            }
+            let end = order[start..]
+                .iter()
+                .position(|index| levels[*index] < level)
+                .map_or(order.len(), |length| start + length);
+            order[start..end].reverse();
+            start = end;
+        }
+    }
+    order
+}
+
+/// The direction of a char for the bidirectional algorithm.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum BidiClass {
+    /// Strong left-to-right.
+    Left,
+    /// Strong right-to-left.
+    Right,
+    /// Digits, left-to-right even in right-to-left text.
+    Number,
+    /// Spaces, punctuation and symbols, taking the direction of the text around them.
+    Neutral,
+    /// The end of a paragraph.
+    Separator,
+}
+
+fn bidi_class(char: char) -> BidiClass {
+    match char {
+        '\n' | '\r' | '\u{2029}' => BidiClass::Separator,
+        '0'..='9' | '\u{0660}'..='\u{0669}' | '\u{06F0}'..='\u{06F9}' => BidiClass::Number,
+        // Hebrew, Arabic, Syriac, Thaana, NKo and the other right-to-left scripts, their
+        // presentation forms, and the right-to-left mark.
+        '\u{0590}'..='\u{08FF}'
+        | '\u{200F}'
+        | '\u{FB1D}'..='\u{FDFF}'
+        | '\u{FE70}'..='\u{FEFE}'
+        | '\u{10800}'..='\u{10FFF}'
+        | '\u{1E800}'..='\u{1EFFF}' => BidiClass::Right,
+        '\u{200E}' => BidiClass::Left,
+        char if char.is_alphabetic() => BidiClass::Left,
+        _ => BidiClass::Neutral,
+    }
+}
+
+/// Resolves the embedding level of each char, and of its paragraph.
+fn resolve_levels(chars: &[char]) -> (Vec<u8>, Vec<u8>) {
+    let classes: Vec<BidiClass> = chars.iter().copied().map(bidi_class).collect();
+    let mut levels = vec![0; chars.len()];
+    let mut paragraph_levels = vec![0; chars.len()];
+
+    let mut start = 0;
+    while start < chars.len() {
+        let end = classes[start..]
+            .iter()
+            .position(|class| *class == BidiClass::Separator)
+            .map_or(chars.len(), |length| start + length + 1);
+        let paragraph = &classes[start..end];
+
+        // The direction of a paragraph is the direction of its first strong char.
+        let rtl = paragraph
+            .iter()
+            .find(|class| matches!(class, BidiClass::Left | BidiClass::Right))
+            == Some(&BidiClass::Right);
+        let base_direction = if rtl {
+            BidiClass::Right
+        } else {
+            BidiClass::Left
+        };
+
+        // Numbers after left-to-right text are left-to-right text.
+        let mut resolved = paragraph.to_vec();
+        let mut last_strong = base_direction;
+        for class in &mut resolved {
+            match *class {
+                BidiClass::Left | BidiClass::Right => last_strong = *class,
+                BidiClass::Number if last_strong == BidiClass::Left => *class = BidiClass::Left,
+                _ => {}
+            }
+        }
+
+        // Neutrals between chars of the same direction take that direction, the others take the
+        // direction of the paragraph. Numbers count as right-to-left.
+        let direction = |class: BidiClass| match class {
+            BidiClass::Number => BidiClass::Right,
+            class => class,
+        };
+        let mut index = 0;
+        while index < resolved.len() {
+            if !matches!(resolved[index], BidiClass::Neutral | BidiClass::Separator) {
+                index += 1;
+                continue;
+            }
+            let neutral_end = resolved[index..]
+                .iter()
+                .position(|class| !matches!(class, BidiClass::Neutral | BidiClass::Separator))
+                .map_or(resolved.len(), |length| index + length);
+            let before = match index {
+                0 => base_direction,
+                _ => direction(resolved[index - 1]),
+            };
+            let after = resolved
+                .get(neutral_end)
+                .copied()
+                .map_or(base_direction, direction);
+            let class = if before == after {
+                before
+            } else {
+                base_direction
+            };
+            for neutral in &mut resolved[index..neutral_end] {
+                if *neutral == BidiClass::Neutral {
+                    *neutral = class;
+                }
+            }
+            index = neutral_end;
+        }
+
+        let paragraph_level = u8::from(rtl);
+        for (offset, class) in resolved.iter().enumerate() {
+            levels[start + offset] = match (class, rtl) {
+                (BidiClass::Separator | BidiClass::Neutral, _) => paragraph_level,
+                (BidiClass::Left, false) => 0,
+                (BidiClass::Right, _) => 1,
+                (BidiClass::Left | BidiClass::Number, _) => 2,
+            };
+            paragraph_levels[start + offset] = paragraph_level;
+        }
+        start = end;
+    }
+    (levels, paragraph_levels)
+}
+
+/// The mirrored glyph of a char drawn right-to-left, like `(` for `)`.
+fn mirror(char: char) -> Option<char> {
+    Some(match char {
+        '(' => ')',
+        ')' => '(',
+        '[' => ']',
+        ']' => '[',
+        '{' => '}',
+        '}' => '{',
+        '<' => '>',
+        '>' => '<',
+        '«' => '»',
+        '»' => '«',
+        '‹' => '›',
+        '›' => '‹',
+        _ => return None,
+    })
+}
+
+/// Returns `true` for the chars that have no glyph, like joiners, direction marks and emoji
+/// variation selectors.
+fn is_default_ignorable(char: char) -> bool {
+    matches!(
+        char,
+        '\u{00AD}'
+            | '\u{200B}'..='\u{200F}'
+            | '\u{202A}'..='\u{202E}'
+            | '\u{2060}'..='\u{206F}'
+            | '\u{FE00}'..='\u{FE0F}'
+            | '\u{FEFF}'
+            | '\u{E0000}'..='\u{E0FFF}'
+    )
+}
+
+/// How an Arabic letter joins its neighbors.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Joining {
+    /// Joins the letters on both sides.
+    Dual,
+    /// Only joins the letter before it, on its right.
+    Right,
+    /// Doesn't join other letters.
+    None,
+    /// Diacritics, ignored when joining letters.
+    Transparent,
+}
+
+/// The isolated form of an Arabic letter, followed by its final, initial and medial forms for
+/// [`Joining::Dual`] letters and its final form for [`Joining::Right`] letters.
+fn arabic_forms(char: char) -> Option<(char, Joining)> {
+    use Joining::*;
+    let (isolated, joining) = match char {
+        '\u{0621}' => ('\u{FE80}', None),
+        '\u{0622}' => ('\u{FE81}', Right),
+        '\u{0623}' => ('\u{FE83}', Right),
+        '\u{0624}' => ('\u{FE85}', Right),
+        '\u{0625}' => ('\u{FE87}', Right),
+        '\u{0626}' => ('\u{FE89}', Dual),
+        '\u{0627}' => ('\u{FE8D}', Right),
+        '\u{0628}' => ('\u{FE8F}', Dual),
+        '\u{0629}' => ('\u{FE93}', Right),
+        '\u{062A}' => ('\u{FE95}', Dual),
+        '\u{062B}' => ('\u{FE99}', Dual),
+        '\u{062C}' => ('\u{FE9D}', Dual),
+        '\u{062D}' => ('\u{FEA1}', Dual),
+        '\u{062E}' => ('\u{FEA5}', Dual),
+        '\u{062F}' => ('\u{FEA9}', Right),
+        '\u{0630}' => ('\u{FEAB}', Right),
+        '\u{0631}' => ('\u{FEAD}', Right),
+        '\u{0632}' => ('\u{FEAF}', Right),
+        '\u{0633}' => ('\u{FEB1}', Dual),
+        '\u{0634}' => ('\u{FEB5}', Dual),
+        '\u{0635}' => ('\u{FEB9}', Dual),
+        '\u{0636}' => ('\u{FEBD}', Dual),
+        '\u{0637}' => ('\u{FEC1}', Dual),
+        '\u{0638}' => ('\u{FEC5}', Dual),
+        '\u{0639}' => ('\u{FEC9}', Dual),
+        '\u{063A}' => ('\u{FECD}', Dual),
+        '\u{0641}' => ('\u{FED1}', Dual),
+        '\u{0642}' => ('\u{FED5}', Dual),
+        '\u{0643}' => ('\u{FED9}', Dual),
+        '\u{0644}' => ('\u{FEDD}', Dual),
+        '\u{0645}' => ('\u{FEE1}', Dual),
+        '\u{0646}' => ('\u{FEE5}', Dual),
+        '\u{0647}' => ('\u{FEE9}', Dual),
+        '\u{0648}' => ('\u{FEED}', Right),
+        '\u{0649}' => ('\u{FEEF}', Right),
+        '\u{064A}' => ('\u{FEF1}', Dual),
+        // Persian letters, from the presentation forms A.
+        '\u{067E}' => ('\u{FB56}', Dual),
+        '\u{0686}' => ('\u{FB7A}', Dual),
+        '\u{0698}' => ('\u{FB8A}', Right),
+        '\u{06A9}' => ('\u{FB8E}', Dual),
+        '\u{06AF}' => ('\u{FB92}', Dual),
+        '\u{06CC}' => ('\u{FBFC}', Dual),
+        // The tatweel and the zero width joiner join both sides without changing.
+        '\u{0640}' | '\u{200D}' => (char, Dual),
+        '\u{064B}'..='\u{065F}' | '\u{0670}' | '\u{06D6}'..='\u{06ED}' => (char, Transparent),
+        _ => return Option::None,
+    };
+    Some((isolated, joining))
+}
+
+fn joining(char: char) -> Joining {
+    arabic_forms(char).map_or(Joining::None, |(_, joining)| joining)
+}
+
+/// The ligature of lam and an alef, in its isolated form followed by its final form.
+fn lam_alef(alef: char) -> Option<char> {
+    match alef {
+        '\u{0622}' => Some('\u{FEF5}'),
+        '\u{0623}' => Some('\u{FEF7}'),
+        '\u{0625}' => Some('\u{FEF9}'),
+        '\u{0627}' => Some('\u{FEFB}'),
+        _ => None,
+    }
+}
+
+/// Replaces the Arabic letters of `chars` by their contextual presentation forms. Returns the
+/// shaped chars with the index of the char they were shaped from.
+fn join_arabic(chars: &[char]) -> Vec<(char, usize)> {
+    // The joining of the nearest letter at the given indices, skipping diacritics.
+    let neighbor = |indices: &mut dyn Iterator<Item = usize>| {
+        indices
+            .map(|index| joining(chars[index]))
+            .find(|joining| *joining != Joining::Transparent)
+            .unwrap_or(Joining::None)
+    };
+    let form = |offset: u32, isolated: char| char::from_u32(isolated as u32 + offset).unwrap();
+
+    let mut shaped = Vec::with_capacity(chars.len());
+    let mut index = 0;
+    while index < chars.len() {
+        let char = chars[index];
+        let Some((isolated, joining)) = arabic_forms(char) else {
+            shaped.push((char, index));
+            index += 1;
+            continue;
+        };
+        if matches!(joining, Joining::None | Joining::Transparent) || isolated == char {
+            shaped.push((isolated, index));
+            index += 1;
+            continue;
+        }
+        let joins_before = neighbor(&mut (0..index).rev()) == Joining::Dual;
+
+        // Lam followed by an alef is a ligature.
+        if char == '\u{0644}' {
+            if let Some(ligature) = chars.get(index + 1).copied().and_then(lam_alef) {
+                shaped.push((form(u32::from(joins_before), ligature), index));
+                index += 2;
+                continue;
+            }
+        }
+
+        let joins_after = joining == Joining::Dual
+            && matches!(
+                neighbor(&mut (index + 1..chars.len())),
+                Joining::Dual | Joining::Right
+            );
+        let offset = match (joins_before, joins_after) {
+            (false, false) => 0,
+            (true, false) => 1,
+            (false, true) => 2,
+            (true, true) => 3,
+        };
+        shaped.push((form(offset, isolated), index));
+        index += 1;
+    }
+    shaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arabic_letters_join_their_neighbors() {
+        // Beh, teh and alef: initial beh, medial teh, final alef.
+        let chars: Vec<char> = "\u{0628}\u{062A}\u{0627} \u{0644}\u{0627}"
+            .chars()
+            .collect();
+        let shaped: String = join_arabic(&chars).into_iter().map(|(c, _)| c).collect();
+        assert_eq!(shaped, "\u{FE91}\u{FE98}\u{FE8E} \u{FEFB}");
+    }
+
+    #[test]
+    fn right_to_left_runs_are_reversed() {
+        // Hebrew in English text, with a number in the Hebrew.
+        let chars: Vec<char> = "ab \u{05D0}\u{05D1} 12 \u{05D2}.".chars().collect();
+        let (levels, _) = resolve_levels(&chars);
+        assert_eq!(levels, [0, 0, 0, 1, 1, 1, 2, 2, 1, 1, 0]);
+        assert_eq!(visual_order(&levels), [0, 1, 2, 9, 8, 6, 7, 5, 4, 3, 10]);
+
+        // A right-to-left paragraph with English in it.
+        let chars: Vec<char> = "\u{05D0} ab!".chars().collect();
+        let (levels, paragraph_levels) = resolve_levels(&chars);
+        assert_eq!(levels, [1, 1, 2, 2, 1]);
+        assert_eq!(paragraph_levels, [1; 5]);
+        assert_eq!(visual_order(&levels), [4, 2, 3, 1, 0]);
+    }
+}
@@ -139,6 +139,7 @@ pub fn extract_text2d_sprite(
                     rect: Some(atlas.textures[atlas_info.glyph_index]),
                     custom_size: None,
                     image_handle_id: atlas_info.texture.id(),
+                    normal_map_handle_id: None,
                     flip_x: false,
                     flip_y: false,
                     anchor: Anchor::Center.as_vec(),
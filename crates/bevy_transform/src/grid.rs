@@ -0,0 +1,264 @@
+//! Large worlds with a floating origin, placing the root entities in the cells of a grid.
+//!
+//! [`Transform`]s are made of `f32`s, which lose precision far from the origin: a few kilometers
+//! away, objects start to jitter. In large worlds, root entities are placed in a [`GridCell`] of
+//! the [`Grid`], and their [`Transform`] is relative to the center of their cell. Their
+//! [`GlobalTransform`] is then relative to the cell of the [`FloatingOrigin`], usually the camera,
+//! so the entities near the camera are always precise, and so is everything computed from their
+//! [`GlobalTransform`]s, like rendering.
+
+use bevy_ecs::{
+    prelude::*,
+    reflect::{ReflectComponent, ReflectResource},
+};
+use bevy_hierarchy::Parent;
+use bevy_math::{DVec3, IVec3, Vec3};
+use bevy_reflect::{std_traits::ReflectDefault, Reflect};
+
+use crate::components::{GlobalTransform, Transform};
+
+/// The cell of the [`Grid`] a root entity is in.
+///
+/// The [`Transform`] of the entity is relative to the center of the cell. When the entity moves
+/// more than half a cell away from the center, it is moved to the cell it entered, and its
+/// [`Transform`] is made relative to that cell, before the transforms are propagated.
+///
+/// Its [`GlobalTransform`], and the [`GlobalTransform`]s of its descendants, are relative to the
+/// center of the cell of the [`FloatingOrigin`].
+///
+/// Setting this component on a child entity has no effect.
+#[derive(Component, Debug, Default, PartialEq, Eq, Clone, Copy, Hash, Reflect)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component, Default, PartialEq)]
+pub struct GridCell(pub IVec3);
+
+impl GridCell {
+    /// The cell at the origin of the grid.
+    pub const ZERO: Self = Self(IVec3::ZERO);
+
+    /// Creates a cell from its coordinates in the grid.
+    pub const fn new(x: i32, y: i32, z: i32) -> Self {
+        Self(IVec3::new(x, y, z))
+    }
+}
+
+/// Marks the root entity whose [`GridCell`] is the origin of the [`GlobalTransform`]s, usually
+/// the camera.
+///
+/// There should be a single floating origin. Without one, the [`GlobalTransform`]s are relative to
+/// the center of [`GridCell::ZERO`].
+#[derive(Component, Debug, Default, PartialEq, Eq, Clone, Copy, Reflect)]
+#[reflect(Component, Default, PartialEq)]
+pub struct FloatingOrigin;
+
+/// The grid of the [`GridCell`]s, and the cell of the [`FloatingOrigin`].
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Resource, Default, PartialEq)]
+pub struct Grid {
+    /// The length of the edges of the cells.
+    ///
+    /// Positions are precise to about a millimeter up to 10 kilometers from the origin, so the
+    /// default of 2 kilometers keeps everything within a few cells of the origin precise.
+    pub cell_size: f32,
+    /// The cell of the [`FloatingOrigin`].
+    origin: IVec3,
+}
+
+impl Default for Grid {
+    fn default() -> Self {
+        Self {
+            cell_size: 2000.0,
+            origin: IVec3::ZERO,
+        }
+    }
+}
+
+impl Grid {
+    /// Creates a grid with cells of `cell_size`.
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size,
+            ..Default::default()
+        }
+    }
+
+    /// The cell of the [`FloatingOrigin`], as of the last transform propagation.
+    pub fn origin(&self) -> GridCell {
+        GridCell(self.origin)
+    }
+
+    /// The position of the center of the `cell`, relative to the center of the cell of the
+    /// [`FloatingOrigin`].
+    pub fn cell_offset(&self, cell: GridCell) -> Vec3 {
+        ((cell.0 - self.origin).as_dvec3() * self.cell_size as f64).as_vec3()
+    }
+
+    /// The cell containing the absolute `position`, and the position relative to the center of the
+    /// cell.
+    pub fn cell_at(&self, position: DVec3) -> (GridCell, Vec3) {
+        let cell_size = self.cell_size as f64;
+        let cell = (position / cell_size).round();
+        (
+            GridCell(cell.as_ivec3()),
+            (position - cell * cell_size).as_vec3(),
+        )
+    }
+
+    /// The absolute position of the `translation` relative to the center of the `cell`.
+    pub fn position(&self, cell: GridCell, translation: Vec3) -> DVec3 {
+        cell.0.as_dvec3() * self.cell_size as f64 + translation.as_dvec3()
+    }
+
+    /// The [`GlobalTransform`] of a root entity with the `transform`, in the `cell`.
+    pub(crate) fn global_transform(
+        &self,
+        cell: GridCell,
+        transform: &Transform,
+    ) -> GlobalTransform {
+        let mut transform = *transform;
+        transform.translation += self.cell_offset(cell);
+        transform.into()
+    }
+}
+
+/// The [`GlobalTransform`] of a root entity, relative to the [`FloatingOrigin`] if it is in a
+/// [`GridCell`].
+pub(crate) fn root_global_transform(
+    transform: &Transform,
+    cell: Option<&GridCell>,
+    grid: Option<&Grid>,
+) -> GlobalTransform {
+    match (cell, grid) {
+        (Some(cell), Some(grid)) => grid.global_transform(*cell, transform),
+        _ => GlobalTransform::from(*transform),
+    }
+}
+
+/// Moves the root entities which left their [`GridCell`] to the cell they entered.
+pub fn recenter_grid_cells(
+    grid: Res<Grid>,
+    mut query: Query<(&mut GridCell, &mut Transform), (Changed<Transform>, Without<Parent>)>,
+) {
+    let half_cell = grid.cell_size / 2.0;
+    query.par_iter_mut().for_each(|(mut cell, mut transform)| {
+        if transform.translation.abs().max_element() <= half_cell {
+            return;
+        }
+        let offset = (transform.translation / grid.cell_size).round();
+        cell.0 += offset.as_ivec3();
+        transform.translation -= offset * grid.cell_size;
+    });
+}
+
+/// Moves the origin of the [`Grid`] to the cell of the [`FloatingOrigin`].
+pub fn update_grid_origin(
+    mut grid: ResMut<Grid>,
+    origins: Query<&GridCell, (With<FloatingOrigin>, Without<Parent>)>,
+) {
+    let origin = origins.get_single().map_or(IVec3::ZERO, |cell| cell.0);
+    if grid.origin != origin {
+        grid.origin = origin;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_app::prelude::*;
+    use bevy_math::{dvec3, vec3};
+
+    use super::*;
+    use crate::{TransformBundle, TransformPlugin};
+    use bevy_hierarchy::BuildWorldChildren;
+
+    #[test]
+    fn positions_round_trip_through_cells() {
+        let grid = Grid::new(100.0);
+        let (cell, translation) = grid.cell_at(dvec3(1_000_000_049.0, -151.0, 0.25));
+        assert_eq!(cell, GridCell::new(10_000_000, -2, 0));
+        assert_eq!(translation, vec3(49.0, 49.0, 0.25));
+        assert_eq!(
+            grid.position(cell, translation),
+            dvec3(1_000_000_049.0, -151.0, 0.25)
+        );
+    }
+
+    #[test]
+    fn global_transforms_are_relative_to_the_floating_origin() {
+        let mut app = App::new();
+        app.add_plugins(TransformPlugin)
+            .insert_resource(Grid::new(100.0));
+
+        let camera = app
+            .world
+            .spawn((
+                TransformBundle::from_transform(Transform::from_xyz(10.0, 0.0, 0.0)),
+                GridCell::new(1_000_000, 0, 0),
+                FloatingOrigin,
+            ))
+            .id();
+        let child = app
+            .world
+            .spawn(TransformBundle::from_transform(Transform::from_xyz(
+                0.0, 1.0, 0.0,
+            )))
+            .id();
+        let object = app
+            .world
+            .spawn((
+                TransformBundle::from_transform(Transform::from_xyz(70.0, 0.0, 0.0)),
+                GridCell::new(1_000_000, 0, 0),
+            ))
+            .add_child(child)
+            .id();
+        app.update();
+
+        // The object left its cell, so it was moved to the next one.
+        assert_eq!(
+            *app.world.get::<GridCell>(object).unwrap(),
+            GridCell::new(1_000_001, 0, 0)
+        );
+        assert_eq!(
+            app.world.get::<Transform>(object).unwrap().translation,
+            vec3(-30.0, 0.0, 0.0)
+        );
+        assert_eq!(
+            app.world
+                .get::<GlobalTransform>(object)
+                .unwrap()
+                .translation(),
+            vec3(70.0, 0.0, 0.0)
+        );
+        assert_eq!(
+            app.world
+                .get::<GlobalTransform>(child)
+                .unwrap()
+                .translation(),
+            vec3(70.0, 1.0, 0.0)
+        );
+
+        // Moving the origin moves the global transforms of the entities in cells.
+        app.world.get_mut::<GridCell>(camera).unwrap().0.x += 1;
+        app.update();
+        assert_eq!(
+            app.world
+                .get::<GlobalTransform>(camera)
+                .unwrap()
+                .translation(),
+            vec3(10.0, 0.0, 0.0)
+        );
+        assert_eq!(
+            app.world
+                .get::<GlobalTransform>(object)
+                .unwrap()
+                .translation(),
+            vec3(-30.0, 0.0, 0.0)
+        );
+        assert_eq!(
+            app.world
+                .get::<GlobalTransform>(child)
+                .unwrap()
+                .translation(),
+            vec3(-30.0, 1.0, 0.0)
+        );
+    }
+}
@@ -3,12 +3,15 @@
 use bevy_ecs::{
     prelude::Entity,
     query::QueryEntityError,
-    system::{Query, SystemParam},
+    system::{Query, Res, SystemParam},
 };
 use bevy_hierarchy::{HierarchyQueryExt, Parent};
 use thiserror::Error;
 
-use crate::components::{GlobalTransform, Transform};
+use crate::{
+    components::{GlobalTransform, Transform},
+    grid::{Grid, GridCell},
+};
 
 /// System parameter for computing up-to-date [`GlobalTransform`]s.
 ///
@@ -20,6 +23,8 @@ use crate::components::{GlobalTransform, Transform};
 pub struct TransformHelper<'w, 's> {
     parent_query: Query<'w, 's, &'static Parent>,
     transform_query: Query<'w, 's, &'static Transform>,
+    grid_cell_query: Query<'w, 's, &'static GridCell>,
+    grid: Option<Res<'w, Grid>>,
 }
 
 impl<'w, 's> TransformHelper<'w, 's> {
@@ -34,6 +39,7 @@ impl<'w, 's> TransformHelper<'w, 's> {
             .map_err(|err| map_error(err, false))?;
 
         let mut global_transform = GlobalTransform::from(*transform);
+        let mut root = entity;
 
         for entity in self.parent_query.iter_ancestors(entity) {
             let transform = self
@@ -42,6 +48,13 @@ impl<'w, 's> TransformHelper<'w, 's> {
                 .map_err(|err| map_error(err, true))?;
 
             global_transform = *transform * global_transform;
+            root = entity;
+        }
+
+        // The root is placed relative to the floating origin, like in the propagation systems.
+        if let (Some(grid), Ok(cell)) = (&self.grid, self.grid_cell_query.get(root)) {
+            global_transform =
+                Transform::from_translation(grid.cell_offset(*cell)) * global_transform;
         }
 
         Ok(global_transform)
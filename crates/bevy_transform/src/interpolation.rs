@@ -0,0 +1,169 @@
+//! Smooth rendering of entities moved in the fixed timestep schedules.
+
+use bevy_ecs::{prelude::*, reflect::ReflectComponent};
+use bevy_reflect::{std_traits::ReflectDefault, Reflect};
+use bevy_time::{Fixed, Time};
+
+use crate::components::Transform;
+
+/// Interpolates the [`Transform`] of an entity moved in the fixed timestep schedules, like
+/// [`FixedUpdate`](bevy_app::FixedUpdate), so that it moves smoothly when rendered.
+///
+/// The fixed timestep schedules run zero, one or several times per frame, so an entity they move
+/// would be rendered with a stutter at its last fixed position. With this component, its
+/// [`Transform`] is snapshot at the end of each fixed timestep, and is set before the transforms
+/// are propagated to the interpolation between its last two snapshots, by the
+/// [`overstep_fraction`](Time::overstep_fraction) of the fixed timestep. The entity is thus
+/// rendered up to one fixed timestep behind the simulation.
+///
+/// The last snapshot is restored before the next fixed timestep, so the systems of the fixed
+/// timestep schedules always see the simulated [`Transform`]. When the [`Transform`] is changed
+/// outside of them, like to teleport the entity, the entity is moved there without interpolation.
+#[derive(Component, Debug, Default, PartialEq, Clone, Copy, Reflect)]
+#[reflect(Component, Default, PartialEq)]
+pub struct TransformInterpolation {
+    /// The [`Transform`] at the end of the fixed timestep before the last one.
+    previous: Option<Transform>,
+    /// The [`Transform`] at the end of the last fixed timestep.
+    current: Option<Transform>,
+    /// The interpolated [`Transform`] set for rendering, if it wasn't restored since.
+    interpolated: Option<Transform>,
+}
+
+impl TransformInterpolation {
+    /// The [`Transform`] at the end of the fixed timestep before the last one, if any.
+    pub fn previous(&self) -> Option<Transform> {
+        self.previous
+    }
+
+    /// The [`Transform`] at the end of the last fixed timestep, if any.
+    pub fn current(&self) -> Option<Transform> {
+        self.current
+    }
+
+    /// Moves the entity to the `transform` without interpolation.
+    fn teleport(&mut self, transform: Transform) {
+        self.previous = Some(transform);
+        self.current = Some(transform);
+        self.interpolated = None;
+    }
+}
+
+/// Returns `true` if the [`Transform`] was changed since it was last interpolated or restored.
+fn is_teleported(transform: &Transform, interpolation: &TransformInterpolation) -> bool {
+    match (interpolation.interpolated, interpolation.current) {
+        (Some(interpolated), _) => *transform != interpolated,
+        (None, Some(current)) => *transform != current,
+        (None, None) => true,
+    }
+}
+
+/// Restores the simulated [`Transform`] of the entities with a [`TransformInterpolation`], before
+/// a fixed timestep.
+pub fn restore_interpolated_transforms(
+    mut query: Query<(&mut Transform, &mut TransformInterpolation)>,
+) {
+    for (mut transform, mut interpolation) in &mut query {
+        if is_teleported(&transform, &interpolation) {
+            interpolation.teleport(*transform);
+            continue;
+        }
+        if let Some(current) = interpolation.current {
+            transform.set_if_neq(current);
+        }
+        interpolation.previous = interpolation.current;
+        interpolation.interpolated = None;
+    }
+}
+
+/// Snapshots the [`Transform`] of the entities with a [`TransformInterpolation`], after a fixed
+/// timestep.
+pub fn snapshot_interpolated_transforms(
+    mut query: Query<(&Transform, &mut TransformInterpolation)>,
+) {
+    for (transform, mut interpolation) in &mut query {
+        interpolation.current = Some(*transform);
+    }
+}
+
+/// Sets the [`Transform`] of the entities with a [`TransformInterpolation`] to the interpolation
+/// between their last two snapshots, by the overstep of the fixed timestep.
+pub fn interpolate_transforms(
+    time: Option<Res<Time<Fixed>>>,
+    mut query: Query<(&mut Transform, &mut TransformInterpolation)>,
+) {
+    let overstep = time.map_or(1.0, |time| time.overstep_fraction().clamp(0.0, 1.0));
+    for (mut transform, mut interpolation) in &mut query {
+        if is_teleported(&transform, &interpolation) {
+            interpolation.teleport(*transform);
+        }
+        let (Some(previous), Some(current)) = (interpolation.previous, interpolation.current)
+        else {
+            continue;
+        };
+        let interpolated = Transform {
+            translation: previous.translation.lerp(current.translation, overstep),
+            rotation: previous.rotation.slerp(current.rotation, overstep),
+            scale: previous.scale.lerp(current.scale, overstep),
+        };
+        transform.set_if_neq(interpolated);
+        interpolation.interpolated = Some(interpolated);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_app::prelude::*;
+    use bevy_math::Vec3;
+    use bevy_time::{TimePlugin, TimeUpdateStrategy};
+    use bevy_utils::Duration;
+
+    use super::*;
+    use crate::{TransformBundle, TransformPlugin};
+
+    #[test]
+    fn transforms_are_interpolated_between_fixed_timesteps() {
+        let mut app = App::new();
+        app.add_plugins((TimePlugin, TransformPlugin))
+            .insert_resource(Time::<Fixed>::from_seconds(0.1))
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+                25,
+            )))
+            .add_systems(
+                FixedUpdate,
+                |mut query: Query<&mut Transform, With<TransformInterpolation>>| {
+                    for mut transform in &mut query {
+                        transform.translation.x += 1.0;
+                    }
+                },
+            );
+        let entity = app
+            .world
+            .spawn((
+                TransformBundle::default(),
+                TransformInterpolation::default(),
+            ))
+            .id();
+        let translation = |app: &App| app.world.get::<Transform>(entity).unwrap().translation;
+
+        // Moved by 1 every 4 frames after the first one, and rendered one fixed timestep behind.
+        let mut rendered = Vec::new();
+        for _ in 0..10 {
+            app.update();
+            rendered.push(translation(&app).x);
+        }
+        let expected = [0.0, 0.0, 0.0, 0.0, 0.0, 0.25, 0.5, 0.75, 1.0, 1.25];
+        assert!(
+            rendered
+                .iter()
+                .zip(expected)
+                .all(|(rendered, expected)| (rendered - expected).abs() < 1e-4),
+            "{rendered:?}"
+        );
+
+        // Teleporting the entity skips the interpolation.
+        app.world.get_mut::<Transform>(entity).unwrap().translation = Vec3::splat(10.0);
+        app.update();
+        assert_eq!(translation(&app), Vec3::splat(10.0));
+    }
+}
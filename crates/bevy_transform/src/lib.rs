@@ -3,7 +3,9 @@
 pub mod commands;
 /// The basic components of the transform crate
 pub mod components;
+pub mod grid;
 pub mod helper;
+pub mod interpolation;
 /// Systems responsible for transform propagation
 pub mod systems;
 
@@ -11,7 +13,11 @@ pub mod systems;
 pub mod prelude {
     #[doc(hidden)]
     pub use crate::{
-        commands::BuildChildrenTransformExt, components::*, helper::TransformHelper,
+        commands::BuildChildrenTransformExt,
+        components::*,
+        grid::{FloatingOrigin, Grid, GridCell},
+        helper::TransformHelper,
+        interpolation::TransformInterpolation,
         TransformBundle, TransformPlugin, TransformPoint,
     };
 }
@@ -21,6 +27,11 @@ use bevy_ecs::prelude::*;
 use bevy_hierarchy::ValidParentCheckPlugin;
 use bevy_math::{Affine3A, Mat4, Vec3};
 
+use grid::{recenter_grid_cells, update_grid_origin, FloatingOrigin, Grid, GridCell};
+use interpolation::{
+    interpolate_transforms, restore_interpolated_transforms, snapshot_interpolated_transforms,
+    TransformInterpolation,
+};
 use prelude::{GlobalTransform, Transform};
 use systems::{propagate_transforms, sync_simple_transforms};
 
@@ -97,8 +108,23 @@ impl Plugin for TransformPlugin {
         #[derive(Debug, Hash, PartialEq, Eq, Clone, SystemSet)]
         struct PropagateTransformsSet;
 
+        // Places the root entities in their grid cells, then the floating origin, before the
+        // transforms are propagated relative to it.
+        let update_grid = || {
+            (recenter_grid_cells, update_grid_origin)
+                .chain()
+                .in_set(TransformSystem::TransformPropagate)
+                .before(sync_simple_transforms)
+                .before(PropagateTransformsSet)
+        };
+
         app.register_type::<Transform>()
             .register_type::<GlobalTransform>()
+            .register_type::<GridCell>()
+            .register_type::<FloatingOrigin>()
+            .register_type::<Grid>()
+            .register_type::<TransformInterpolation>()
+            .init_resource::<Grid>()
             .add_plugins(ValidParentCheckPlugin::<GlobalTransform>::default())
             .configure_sets(
                 PostStartup,
@@ -108,6 +134,7 @@ impl Plugin for TransformPlugin {
             .add_systems(
                 PostStartup,
                 (
+                    update_grid(),
                     sync_simple_transforms
                         .in_set(TransformSystem::TransformPropagate)
                         // FIXME: https://github.com/bevyengine/bevy/issues/4381
@@ -124,12 +151,16 @@ impl Plugin for TransformPlugin {
             .add_systems(
                 PostUpdate,
                 (
+                    update_grid(),
                     sync_simple_transforms
                         .in_set(TransformSystem::TransformPropagate)
                         .ambiguous_with(PropagateTransformsSet),
                     propagate_transforms.in_set(PropagateTransformsSet),
+                    interpolate_transforms.before(TransformSystem::TransformPropagate),
                 ),
-            );
+            )
+            .add_systems(FixedFirst, restore_interpolated_transforms)
+            .add_systems(FixedLast, snapshot_interpolated_transforms);
     }
 }
 
@@ -1,43 +1,70 @@
-use crate::components::{GlobalTransform, Transform};
+use crate::{
+    components::{GlobalTransform, Transform},
+    grid::{root_global_transform, Grid, GridCell},
+};
 use bevy_ecs::{
     change_detection::Ref,
     prelude::{Changed, DetectChanges, Entity, Query, With, Without},
     query::{Added, Or},
     removal_detection::RemovedComponents,
-    system::{Local, ParamSet},
+    system::{Local, ParamSet, Res},
 };
 use bevy_hierarchy::{Children, Parent};
 
 /// Update [`GlobalTransform`] component of entities that aren't in the hierarchy
 ///
+/// The [`GlobalTransform`] of the entities in a [`GridCell`] is relative to the origin of the
+/// [`Grid`].
+///
 /// Third party plugins should ensure that this is used in concert with [`propagate_transforms`].
+#[allow(clippy::type_complexity)]
 pub fn sync_simple_transforms(
     mut query: ParamSet<(
         Query<
-            (&Transform, &mut GlobalTransform),
+            (&Transform, Option<&GridCell>, &mut GlobalTransform),
             (
-                Or<(Changed<Transform>, Added<GlobalTransform>)>,
+                Or<(
+                    Changed<Transform>,
+                    Added<GlobalTransform>,
+                    Changed<GridCell>,
+                )>,
                 Without<Parent>,
                 Without<Children>,
             ),
         >,
-        Query<(Ref<Transform>, &mut GlobalTransform), (Without<Parent>, Without<Children>)>,
+        Query<
+            (Ref<Transform>, Option<&GridCell>, &mut GlobalTransform),
+            (Without<Parent>, Without<Children>),
+        >,
+        Query<(&Transform, &GridCell, &mut GlobalTransform), (Without<Parent>, Without<Children>)>,
     )>,
     mut orphaned: RemovedComponents<Parent>,
+    grid: Option<Res<Grid>>,
 ) {
+    let grid_changed = grid.as_ref().is_some_and(DetectChanges::is_changed);
+    let grid = grid.as_deref();
+    // Update all the entities in cells when the grid or its origin changed.
+    if let Some(grid) = grid.filter(|_| grid_changed) {
+        query
+            .p2()
+            .par_iter_mut()
+            .for_each(|(transform, cell, mut global_transform)| {
+                *global_transform = grid.global_transform(*cell, transform);
+            });
+    }
     // Update changed entities.
     query
         .p0()
         .par_iter_mut()
-        .for_each(|(transform, mut global_transform)| {
-            *global_transform = GlobalTransform::from(*transform);
+        .for_each(|(transform, cell, mut global_transform)| {
+            *global_transform = root_global_transform(transform, cell, grid);
         });
     // Update orphaned entities.
     let mut query = query.p1();
     let mut iter = query.iter_many_mut(orphaned.read());
-    while let Some((transform, mut global_transform)) = iter.fetch_next() {
+    while let Some((transform, cell, mut global_transform)) = iter.fetch_next() {
         if !transform.is_changed() && !global_transform.is_added() {
-            *global_transform = GlobalTransform::from(*transform);
+            *global_transform = root_global_transform(&transform, cell, grid);
         }
     }
 }
@@ -45,25 +72,39 @@ pub fn sync_simple_transforms(
 /// Update [`GlobalTransform`] component of entities based on entity hierarchy and
 /// [`Transform`] component.
 ///
+/// The [`GlobalTransform`] of the roots in a [`GridCell`], and of their descendants, is relative
+/// to the origin of the [`Grid`].
+///
 /// Third party plugins should ensure that this is used in concert with [`sync_simple_transforms`].
+#[allow(clippy::type_complexity)]
 pub fn propagate_transforms(
     mut root_query: Query<
-        (Entity, &Children, Ref<Transform>, &mut GlobalTransform),
+        (
+            Entity,
+            &Children,
+            Ref<Transform>,
+            Option<Ref<GridCell>>,
+            &mut GlobalTransform,
+        ),
         Without<Parent>,
     >,
     mut orphaned: RemovedComponents<Parent>,
     transform_query: Query<(Ref<Transform>, &mut GlobalTransform, Option<&Children>), With<Parent>>,
     parent_query: Query<(Entity, Ref<Parent>)>,
+    grid: Option<Res<Grid>>,
     mut orphaned_entities: Local<Vec<Entity>>,
 ) {
     orphaned_entities.clear();
     orphaned_entities.extend(orphaned.read());
     orphaned_entities.sort_unstable();
+    let grid_changed = grid.as_ref().is_some_and(DetectChanges::is_changed);
+    let grid = grid.as_deref();
     root_query.par_iter_mut().for_each(
-        |(entity, children, transform, mut global_transform)| {
-            let changed = transform.is_changed() || global_transform.is_added() || orphaned_entities.binary_search(&entity).is_ok();
+        |(entity, children, transform, cell, mut global_transform)| {
+            let cell_changed = cell.as_ref().is_some_and(|cell| grid_changed || cell.is_changed());
+            let changed = transform.is_changed() || cell_changed || global_transform.is_added() || orphaned_entities.binary_search(&entity).is_ok();
             if changed {
-                *global_transform = GlobalTransform::from(*transform);
+                *global_transform = root_global_transform(&transform, cell.as_deref(), grid);
             }
 
             for (child, actual_parent) in parent_query.iter_many(children) {
@@ -0,0 +1,160 @@
+//! This module contains the systems drawing the cursor images of the windows on platforms where
+//! the windowing backend can't display them
+
+use crate::{FocusPolicy, PositionType, Style, TargetCamera, UiImage, UiScale, Val, ZIndex};
+use bevy_asset::Assets;
+use bevy_ecs::{prelude::*, reflect::ReflectComponent};
+use bevy_reflect::Reflect;
+use bevy_render::{
+    camera::{Camera, NormalizedRenderTarget},
+    texture::Image,
+    view::{CursorImage, Visibility},
+};
+use bevy_time::{Real, Time};
+use bevy_utils::HashSet;
+use bevy_window::{PrimaryWindow, Window};
+
+/// A root node drawing the [`CursorImage`] of a window in place of its cursor icon.
+///
+/// Spawned for the windows with a [`CursorImage`] on the platforms where the windowing backend
+/// can't display custom cursors, and despawned when the [`CursorImage`] is removed. The cursor icon
+/// of the window is hidden while the node exists.
+#[derive(Component, Copy, Clone, Debug, PartialEq, Reflect)]
+#[reflect(Component, PartialEq)]
+pub struct SoftwareCursor {
+    /// The window of the cursor.
+    pub window: Entity,
+    /// Whether the cursor icon of the window was visible before being hidden.
+    cursor_was_visible: bool,
+}
+
+/// Spawns, moves and despawns the [`SoftwareCursor`] nodes of the windows with a [`CursorImage`].
+#[allow(clippy::too_many_arguments)]
+pub fn software_cursor_system(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    ui_scale: Res<UiScale>,
+    images: Res<Assets<Image>>,
+    mut windows: Query<(Entity, &mut Window, Option<&CursorImage>)>,
+    primary_window: Query<Entity, With<PrimaryWindow>>,
+    cameras: Query<(Entity, &Camera)>,
+    mut cursors: Query<(
+        Entity,
+        &SoftwareCursor,
+        &mut Style,
+        &mut UiImage,
+        &mut Visibility,
+    )>,
+) {
+    let mut drawn = HashSet::new();
+    for (entity, cursor, mut style, mut ui_image, mut visibility) in &mut cursors {
+        let Ok((_, mut window, cursor_image)) = windows.get_mut(cursor.window) else {
+            commands.entity(entity).despawn();
+            continue;
+        };
+        let Some(cursor_image) = cursor_image else {
+            if cursor.cursor_was_visible {
+                window.cursor.visible = true;
+            }
+            commands.entity(entity).despawn();
+            continue;
+        };
+        drawn.insert(cursor.window);
+
+        let frame = cursor_image
+            .frame(time.elapsed())
+            .filter(|frame| images.contains(*frame));
+        let (Some(frame), Some(position)) = (frame, window.cursor_position()) else {
+            visibility.set_if_neq(Visibility::Hidden);
+            continue;
+        };
+        visibility.set_if_neq(Visibility::Inherited);
+        if ui_image.texture != *frame {
+            ui_image.texture = frame.clone();
+        }
+
+        // The image is scaled like the other images of the UI, so is the hotspot.
+        let hotspot = cursor_image.hotspot.as_vec2();
+        let left = Val::Px(position.x / ui_scale.0 - hotspot.x);
+        let top = Val::Px(position.y / ui_scale.0 - hotspot.y);
+        if style.left != left || style.top != top {
+            style.left = left;
+            style.top = top;
+        }
+    }
+
+    let primary_window = primary_window.get_single().ok();
+    for (entity, mut window, cursor_image) in &mut windows {
+        if cursor_image.is_none() || drawn.contains(&entity) {
+            continue;
+        }
+        let Some((camera, _)) = cameras.iter().find(|(_, camera)| {
+            matches!(
+                camera.target.normalize(primary_window),
+                Some(NormalizedRenderTarget::Window(window)) if window.entity() == entity
+            )
+        }) else {
+            continue;
+        };
+
+        commands.spawn((
+            SoftwareCursor {
+                window: entity,
+                cursor_was_visible: window.cursor.visible,
+            },
+            crate::node_bundles::ImageBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    ..Default::default()
+                },
+                focus_policy: FocusPolicy::Pass,
+                z_index: ZIndex::Global(i32::MAX),
+                visibility: Visibility::Hidden,
+                ..Default::default()
+            },
+            TargetCamera(camera),
+        ));
+        window.cursor.visible = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_asset::Handle;
+    use bevy_math::UVec2;
+    use bevy_render::camera::RenderTarget;
+    use bevy_window::WindowRef;
+
+    #[test]
+    fn software_cursor_hides_the_cursor_icon_while_drawn() {
+        let mut world = World::new();
+        world.init_resource::<Time<Real>>();
+        world.init_resource::<UiScale>();
+        world.init_resource::<Assets<Image>>();
+        let window = world.spawn((Window::default(), PrimaryWindow)).id();
+        world.spawn(Camera {
+            target: RenderTarget::Window(WindowRef::Primary),
+            ..Default::default()
+        });
+        world
+            .entity_mut(window)
+            .insert(CursorImage::new(Handle::default(), UVec2::ZERO));
+
+        let mut schedule = Schedule::default();
+        schedule.add_systems(software_cursor_system);
+        schedule.run(&mut world);
+        schedule.run(&mut world);
+
+        let mut cursors = world.query::<&SoftwareCursor>();
+        assert_eq!(cursors.iter(&world).count(), 1);
+        assert_eq!(cursors.single(&world).window, window);
+        assert!(!world.get::<Window>(window).unwrap().cursor.visible);
+
+        world.entity_mut(window).remove::<CursorImage>();
+        schedule.run(&mut world);
+
+        assert_eq!(cursors.iter(&world).count(), 0);
+        assert!(world.get::<Window>(window).unwrap().cursor.visible);
+    }
+}
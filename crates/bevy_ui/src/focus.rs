@@ -7,7 +7,7 @@ use bevy_ecs::{
     reflect::ReflectComponent,
     system::{Local, Query, Res},
 };
-use bevy_input::{mouse::MouseButton, touch::Touches, ButtonInput};
+use bevy_input::pointer::{PointerButton, Pointers};
 use bevy_math::{Rect, Vec2};
 use bevy_reflect::{std_traits::ReflectDefault, Reflect};
 use bevy_render::{camera::NormalizedRenderTarget, prelude::Camera, view::ViewVisibility};
@@ -142,7 +142,8 @@ pub struct NodeQuery {
     target_camera: Option<&'static TargetCamera>,
 }
 
-/// The system that sets Interaction for all UI elements based on the mouse cursor activity
+/// The system that sets Interaction for all UI elements based on the activity of the [`Pointers`]:
+/// the mouse cursor, touches and pens.
 ///
 /// Entities with a hidden [`ViewVisibility`] are always treated as released.
 #[allow(clippy::too_many_arguments)]
@@ -152,8 +153,7 @@ pub fn ui_focus_system(
     default_ui_camera: DefaultUiCamera,
     primary_window: Query<Entity, With<PrimaryWindow>>,
     windows: Query<&Window>,
-    mouse_button_input: Res<ButtonInput<MouseButton>>,
-    touches_input: Res<Touches>,
+    pointers: Res<Pointers>,
     ui_scale: Res<UiScale>,
     ui_stack: Res<UiStack>,
     mut node_query: Query<NodeQuery>,
@@ -171,8 +171,7 @@ pub fn ui_focus_system(
         }
     }
 
-    let mouse_released =
-        mouse_button_input.just_released(MouseButton::Left) || touches_input.any_just_released();
+    let mouse_released = pointers.any_just_released(PointerButton::Primary);
     if mouse_released {
         for node in &mut node_query {
             if let Some(mut interaction) = node.interaction {
@@ -183,8 +182,7 @@ pub fn ui_focus_system(
         }
     }
 
-    let mouse_clicked =
-        mouse_button_input.just_pressed(MouseButton::Left) || touches_input.any_just_pressed();
+    let mouse_clicked = pointers.any_just_pressed(PointerButton::Primary);
 
     let camera_cursor_positions: HashMap<Entity, Vec2> = camera_query
         .iter()
@@ -200,11 +198,14 @@ pub fn ui_focus_system(
                 .logical_viewport_rect()
                 .map(|rect| rect.min)
                 .unwrap_or_default();
-            windows
-                .get(window_ref.entity())
-                .ok()
-                .and_then(|window| window.cursor_position())
-                .or_else(|| touches_input.first_pressed_position())
+            pointers
+                .position(window_ref.entity())
+                .or_else(|| {
+                    windows
+                        .get(window_ref.entity())
+                        .ok()
+                        .and_then(|window| window.cursor_position())
+                })
                 .map(|cursor_position| (entity, cursor_position - viewport_position))
         })
         // The cursor position returned by `Window` only takes into account the window scale factor and not `UiScale`.
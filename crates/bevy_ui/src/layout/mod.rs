@@ -1,7 +1,10 @@
 mod convert;
 pub mod debug;
 
-use crate::{ContentSize, DefaultUiCamera, Node, Outline, Style, TargetCamera, UiScale};
+use crate::{
+    scroll::clamp_scroll_offset, ContentSize, DefaultUiCamera, Node, Outline, ScrollPosition,
+    Style, TargetCamera, UiScale,
+};
 use bevy_ecs::entity::EntityHashMap;
 use bevy_ecs::{
     change_detection::{DetectChanges, DetectChangesMut},
@@ -230,6 +233,47 @@ without UI components as a child of an entity with UI components, results may be
 
     /// Get the layout geometry for the taffy node corresponding to the ui node [`Entity`].
     /// Does not compute the layout geometry, `compute_window_layouts` should be run before using this function.
+    /// Gets the size of the children of the node of `entity` in physical pixels, including its
+    /// padding and border at their end. Percentages are resolved based on `parent_width`, in
+    /// physical pixels.
+    fn content_size(
+        &self,
+        entity: Entity,
+        parent_width: f32,
+        children_query: &Query<&Children>,
+    ) -> Vec2 {
+        let Some(taffy_node) = self.entity_to_taffy.get(&entity) else {
+            return Vec2::ZERO;
+        };
+        let Ok(layout) = self.taffy.layout(*taffy_node) else {
+            return Vec2::ZERO;
+        };
+        let size = Vec2::new(layout.size.width, layout.size.height);
+        let Ok(style) = self.taffy.style(*taffy_node) else {
+            return size;
+        };
+        let resolve = |length: taffy::style::LengthPercentage| match length {
+            taffy::style::LengthPercentage::Points(points) => points,
+            taffy::style::LengthPercentage::Percent(percent) => percent * parent_width,
+        };
+        let end = Vec2::new(
+            resolve(style.padding.right) + resolve(style.border.right),
+            resolve(style.padding.bottom) + resolve(style.border.bottom),
+        );
+        children_query
+            .get(entity)
+            .into_iter()
+            .flatten()
+            .filter_map(|child| self.get_layout(*child).ok())
+            .map(|child| {
+                Vec2::new(
+                    child.location.x + child.size.width,
+                    child.location.y + child.size.height,
+                ) + end
+            })
+            .fold(size, Vec2::max)
+    }
+
     pub fn get_layout(&self, entity: Entity) -> Result<&taffy::layout::Layout, LayoutError> {
         if let Some(taffy_node) = self.entity_to_taffy.get(&entity) {
             self.taffy
@@ -271,7 +315,12 @@ pub fn ui_layout_system(
     mut removed_children: RemovedComponents<Children>,
     mut removed_content_sizes: RemovedComponents<ContentSize>,
     mut removed_nodes: RemovedComponents<Node>,
-    mut node_transform_query: Query<(&mut Node, &mut Transform)>,
+    mut node_transform_query: Query<(
+        &mut Node,
+        &mut Transform,
+        &Style,
+        Option<&mut ScrollPosition>,
+    )>,
 ) {
     struct CameraLayoutInfo {
         size: UVec2,
@@ -397,25 +446,36 @@ pub fn ui_layout_system(
                 inverse_target_scale_factor,
                 Vec2::ZERO,
                 Vec2::ZERO,
+                Vec2::ZERO,
             );
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn update_uinode_geometry_recursive(
         entity: Entity,
         ui_surface: &UiSurface,
-        node_transform_query: &mut Query<(&mut Node, &mut Transform)>,
+        node_transform_query: &mut Query<(
+            &mut Node,
+            &mut Transform,
+            &Style,
+            Option<&mut ScrollPosition>,
+        )>,
         children_query: &Query<&Children>,
         inverse_target_scale_factor: f32,
         parent_size: Vec2,
+        parent_scroll_offset: Vec2,
         mut absolute_location: Vec2,
     ) {
-        if let Ok((mut node, mut transform)) = node_transform_query.get_mut(entity) {
+        if let Ok((mut node, mut transform, style, scroll_position)) =
+            node_transform_query.get_mut(entity)
+        {
             let layout = ui_surface.get_layout(entity).unwrap();
             let layout_size =
                 inverse_target_scale_factor * Vec2::new(layout.size.width, layout.size.height);
-            let layout_location =
-                inverse_target_scale_factor * Vec2::new(layout.location.x, layout.location.y);
+            let layout_location = inverse_target_scale_factor
+                * Vec2::new(layout.location.x, layout.location.y)
+                - parent_scroll_offset;
 
             absolute_location += layout_location;
 
@@ -433,6 +493,25 @@ pub fn ui_layout_system(
             if transform.translation.truncate() != rounded_location {
                 transform.translation = rounded_location.extend(0.);
             }
+
+            let content_size = ui_surface.content_size(
+                entity,
+                parent_size.x / inverse_target_scale_factor,
+                children_query,
+            ) * inverse_target_scale_factor;
+            if node.content_size != content_size {
+                node.content_size = content_size;
+            }
+            let scroll_offset = match scroll_position {
+                Some(mut scroll_position) => {
+                    let offset =
+                        clamp_scroll_offset(&node, style.overflow, scroll_position.offset());
+                    scroll_position.set_if_neq(offset.into());
+                    offset
+                }
+                None => Vec2::ZERO,
+            };
+
             if let Ok(children) = children_query.get(entity) {
                 for &child_uinode in children {
                     update_uinode_geometry_recursive(
@@ -442,6 +521,7 @@ pub fn ui_layout_system(
                         children_query,
                         inverse_target_scale_factor,
                         rounded_size,
+                        scroll_offset,
                         absolute_location,
                     );
                 }
@@ -528,9 +608,11 @@ mod tests {
     use bevy_hierarchy::Children;
     use bevy_math::vec2;
     use bevy_math::Vec2;
+    use bevy_math::Vec3;
     use bevy_render::camera::ManualTextureViews;
     use bevy_render::camera::OrthographicProjection;
     use bevy_render::texture::Image;
+    use bevy_transform::components::Transform;
     use bevy_utils::prelude::default;
     use bevy_utils::HashMap;
     use bevy_window::PrimaryWindow;
@@ -908,4 +990,57 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn scroll_position_offsets_children_and_is_clamped() {
+        let (mut world, mut ui_schedule) = setup_ui_test_world();
+
+        let parent = world
+            .spawn((
+                NodeBundle {
+                    style: Style {
+                        width: Val::Px(100.),
+                        height: Val::Px(100.),
+                        flex_direction: FlexDirection::Column,
+                        padding: UiRect::all(Val::Px(10.)),
+                        overflow: Overflow::scroll_y(),
+                        ..default()
+                    },
+                    ..default()
+                },
+                ScrollPosition::default(),
+            ))
+            .with_children(|commands| {
+                for _ in 0..3 {
+                    commands.spawn(NodeBundle {
+                        style: Style {
+                            width: Val::Px(50.),
+                            height: Val::Px(100.),
+                            flex_shrink: 0.,
+                            ..default()
+                        },
+                        ..default()
+                    });
+                }
+            })
+            .id();
+        let child = world.get::<Children>(parent).unwrap()[0];
+
+        ui_schedule.run(&mut world);
+        let node = world.get::<Node>(parent).unwrap();
+        // The children and the padding at both ends.
+        assert_eq!(node.content_size(), vec2(100., 320.));
+        assert_eq!(node.max_scroll_offset(), vec2(0., 220.));
+        let unscrolled = world.get::<Transform>(child).unwrap().translation;
+
+        // The offset is clamped to the content, and ignored on the axis that doesn't scroll.
+        *world.get_mut::<ScrollPosition>(parent).unwrap() = ScrollPosition::new(30., 500.);
+        ui_schedule.run(&mut world);
+        assert_eq!(
+            *world.get::<ScrollPosition>(parent).unwrap(),
+            ScrollPosition::new(0., 220.)
+        );
+        let scrolled = world.get::<Transform>(child).unwrap().translation;
+        assert_eq!(scrolled - unscrolled, Vec3::new(0., -220., 0.));
+    }
 }
@@ -16,25 +16,34 @@ use bevy_derive::{Deref, DerefMut};
 use bevy_reflect::Reflect;
 #[cfg(feature = "bevy_text")]
 mod accessibility;
+mod cursor;
 mod focus;
 mod geometry;
 mod layout;
 mod render;
+mod scroll;
 mod stack;
 mod texture_slice;
 mod ui_node;
+mod world_ui;
 
+pub use cursor::*;
 pub use focus::*;
 pub use geometry::*;
 pub use layout::*;
 pub use measurement::*;
 pub use render::*;
+pub use scroll::*;
 pub use ui_material::*;
 pub use ui_node::*;
 use widget::UiImageSize;
+pub use world_ui::*;
 
 #[doc(hidden)]
 pub mod prelude {
+    #[doc(hidden)]
+    #[cfg(feature = "bevy_text")]
+    pub use crate::widget::{TextInput, TextInputFocus, TextInputSubmit};
     #[doc(hidden)]
     pub use crate::{
         geometry::*, node_bundles::*, ui_material::*, ui_node::*, widget::Button, widget::Label,
@@ -48,7 +57,7 @@ pub mod prelude {
 use bevy_app::prelude::*;
 use bevy_ecs::prelude::*;
 use bevy_input::InputSystem;
-use bevy_render::RenderApp;
+use bevy_render::{view::VisibilitySystems, RenderApp};
 use bevy_transform::TransformSystem;
 use stack::ui_stack_system;
 pub use stack::UiStack;
@@ -133,9 +142,21 @@ impl Plugin for UiPlugin {
             .register_type::<widget::Label>()
             .register_type::<ZIndex>()
             .register_type::<Outline>()
+            .register_type::<BorderRadius>()
+            .register_type::<BorderSideColors>()
+            .register_type::<BoxShadow>()
+            .register_type::<ScrollPosition>()
+            .register_type::<ScrollView>()
+            .register_type::<SoftwareCursor>()
+            .register_type::<Scrollbars>()
+            .register_type::<WorldUi>()
+            .register_type::<WorldUiScaling>()
             .add_systems(
                 PreUpdate,
-                ui_focus_system.in_set(UiSystem::Focus).after(InputSystem),
+                (
+                    ui_focus_system.in_set(UiSystem::Focus).after(InputSystem),
+                    ui_scroll_system.after(InputSystem),
+                ),
             );
 
         app.add_systems(
@@ -156,12 +177,19 @@ impl Plugin for UiPlugin {
                     .in_set(AmbiguousWithTextSystem),
                 ui_stack_system
                     .in_set(UiSystem::Stack)
+                    // world UI roots are sorted by their distance to the camera
+                    .after(world_ui_system)
                     // the systems don't care about stack index
                     .ambiguous_with(update_clipping_system)
                     .ambiguous_with(resolve_outlines_system)
                     .ambiguous_with(ui_layout_system)
                     .in_set(AmbiguousWithTextSystem),
-                update_clipping_system.after(TransformSystem::TransformPropagate),
+                world_ui_system
+                    .after(TransformSystem::TransformPropagate)
+                    .after(VisibilitySystems::CheckVisibility),
+                update_clipping_system
+                    .after(TransformSystem::TransformPropagate)
+                    .after(world_ui_system),
                 // Potential conflicts: `Assets<Image>`
                 // They run independently since `widget::image_node_system` will only ever observe
                 // its own UiImage, and `widget::text_system` & `bevy_text::update_text2d_layout`
@@ -174,12 +202,22 @@ impl Plugin for UiPlugin {
                     (
                         texture_slice::compute_slices_on_asset_event,
                         texture_slice::compute_slices_on_image_change,
+                        texture_slice::remove_slices_on_scale_mode_removal,
                     ),
                 )
                     .chain(),
             ),
         );
 
+        // The windowing backend displays the cursor images on the web.
+        #[cfg(not(target_arch = "wasm32"))]
+        app.add_systems(
+            PostUpdate,
+            software_cursor_system
+                .before(update_target_camera_system)
+                .in_set(AmbiguousWithTextSystem),
+        );
+
         #[cfg(feature = "bevy_text")]
         build_text_interop(app);
 
@@ -202,7 +240,11 @@ fn build_text_interop(app: &mut App) {
     use bevy_text::TextLayoutInfo;
 
     app.register_type::<TextLayoutInfo>()
-        .register_type::<TextFlags>();
+        .register_type::<TextFlags>()
+        .register_type::<widget::TextInput>()
+        .init_resource::<widget::TextInputFocus>()
+        .add_event::<widget::TextInputSubmit>()
+        .add_systems(PreUpdate, widget::text_input_system.after(UiSystem::Focus));
 
     app.add_systems(
         PostUpdate,
@@ -226,6 +268,9 @@ fn build_text_interop(app: &mut App) {
                 .after(bevy_text::remove_dropped_font_atlas_sets)
                 // Text2d and bevy_ui text are entirely on separate entities
                 .ambiguous_with(bevy_text::update_text2d_layout),
+            widget::text_input_ime_position_system
+                .after(widget::text_system)
+                .after(TransformSystem::TransformPropagate),
         ),
     );
 
@@ -5,7 +5,7 @@ mod ui_material_pipeline;
 use bevy_core_pipeline::core_2d::graph::{Core2d, Node2d};
 use bevy_core_pipeline::core_3d::graph::{Core3d, Node3d};
 use bevy_core_pipeline::{core_2d::Camera2d, core_3d::Camera3d};
-use bevy_hierarchy::Parent;
+use bevy_hierarchy::{Children, Parent};
 use bevy_render::{
     render_phase::PhaseItem, render_resource::BindGroupEntries, view::ViewVisibility,
     ExtractSchedule, Render,
@@ -17,8 +17,9 @@ pub use ui_material_pipeline::*;
 
 use crate::graph::{NodeUi, SubGraphUi};
 use crate::{
-    texture_slice::ComputedTextureSlices, BackgroundColor, BorderColor, CalculatedClip,
-    ContentSize, DefaultUiCamera, Node, Outline, Style, TargetCamera, UiImage, UiScale, Val,
+    texture_slice::ComputedTextureSlices, BackgroundColor, BorderColor, BorderRadius,
+    BorderSideColors, BoxShadow, CalculatedClip, ContentSize, DefaultUiCamera, Node, Outline,
+    ScrollPosition, Scrollbars, Style, TargetCamera, UiImage, UiScale, Val,
 };
 
 use bevy_app::prelude::*;
@@ -90,6 +91,8 @@ pub fn build_ui_render(app: &mut App) {
                 #[cfg(feature = "bevy_text")]
                 extract_text_uinodes,
                 extract_uinode_outlines,
+                extract_uinode_box_shadows,
+                extract_uinode_scrollbars,
             ),
         )
         .add_systems(
@@ -144,6 +147,41 @@ pub struct ExtractedUiNode {
     // it is defaulted to a single camera if only one exists.
     // Nodes with ambiguous camera will be ignored.
     pub camera_entity: Entity,
+    /// The rounded rectangle drawn by the node, relative to the center of its quad.
+    ///
+    /// Only used by rounded nodes, borders and shadows.
+    pub shape: Rect,
+    /// The radii of the corners of `shape` in logical pixels, clockwise from the top left corner.
+    pub border_radius: [f32; 4],
+    pub node_type: NodeType,
+}
+
+/// What an [`ExtractedUiNode`] draws of its [`shape`](ExtractedUiNode::shape).
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum NodeType {
+    /// The whole shape, filled with the color and image of the node.
+    #[default]
+    Rect,
+    /// The border of the shape, of the given widths in logical pixels: left, top, right and
+    /// bottom. If `side` is set, only that side of the border is drawn.
+    Border {
+        widths: [f32; 4],
+        side: Option<BorderSide>,
+    },
+    /// The shape blurred by a gaussian blur with a standard deviation of half `blur_radius`.
+    Shadow { blur_radius: f32 },
+    /// The whole shape, filled with the color of the node, drawn above the node with the same
+    /// stack index.
+    Scrollbar,
+}
+
+/// A side of the border of a UI node.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BorderSide {
+    Left,
+    Top,
+    Right,
+    Bottom,
 }
 
 #[derive(Resource, Default)]
@@ -163,6 +201,23 @@ pub(crate) fn resolve_border_thickness(value: Val, parent_width: f32, viewport_s
     }
 }
 
+/// The size of the UI viewport of `camera_entity`, in logical pixels.
+fn ui_logical_viewport_size(
+    camera_query: &Query<(Entity, &Camera)>,
+    camera_entity: Entity,
+    ui_scale: &UiScale,
+) -> Vec2 {
+    camera_query
+        .get(camera_entity)
+        .ok()
+        .and_then(|(_, c)| c.logical_viewport_size())
+        .unwrap_or(Vec2::ZERO)
+        // The logical window resolution returned by `Window` only takes into account the window scale factor and not `UiScale`,
+        // so we have to divide by `UiScale` to get the size of the UI viewport.
+        / ui_scale.0
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn extract_uinode_borders(
     mut commands: Commands,
     mut extracted_uinodes: ResMut<ExtractedUiNodes>,
@@ -176,6 +231,8 @@ pub fn extract_uinode_borders(
                 &GlobalTransform,
                 &Style,
                 &BorderColor,
+                Option<&BorderSideColors>,
+                Option<&BorderRadius>,
                 Option<&Parent>,
                 &ViewVisibility,
                 Option<&CalculatedClip>,
@@ -188,8 +245,18 @@ pub fn extract_uinode_borders(
 ) {
     let image = AssetId::<Image>::default();
 
-    for (node, global_transform, style, border_color, parent, view_visibility, clip, camera) in
-        &uinode_query
+    for (
+        node,
+        global_transform,
+        style,
+        border_color,
+        side_colors,
+        border_radius,
+        parent,
+        view_visibility,
+        clip,
+        camera,
+    ) in &uinode_query
     {
         let Some(camera_entity) = camera.map(TargetCamera::entity).or(default_ui_camera.get())
         else {
@@ -197,21 +264,19 @@ pub fn extract_uinode_borders(
         };
         // Skip invisible borders
         if !view_visibility.get()
-            || border_color.0.is_fully_transparent()
+            || side_colors.map_or(border_color.0.is_fully_transparent(), |colors| {
+                [colors.left, colors.top, colors.right, colors.bottom]
+                    .iter()
+                    .all(Color::is_fully_transparent)
+            })
             || node.size().x <= 0.
             || node.size().y <= 0.
         {
             continue;
         }
 
-        let ui_logical_viewport_size = camera_query
-            .get(camera_entity)
-            .ok()
-            .and_then(|(_, c)| c.logical_viewport_size())
-            .unwrap_or(Vec2::ZERO)
-            // The logical window resolution returned by `Window` only takes into account the window scale factor and not `UiScale`,
-            // so we have to divide by `UiScale` to get the size of the UI viewport.
-            / ui_scale.0;
+        let ui_logical_viewport_size =
+            ui_logical_viewport_size(&camera_query, camera_entity, &ui_scale);
 
         // Both vertical and horizontal percentage border values are calculated based on the width of the parent node
         // <https://developer.mozilla.org/en-US/docs/Web/CSS/border-width>
@@ -227,73 +292,93 @@ pub fn extract_uinode_borders(
             resolve_border_thickness(style.border.top, parent_width, ui_logical_viewport_size);
         let bottom =
             resolve_border_thickness(style.border.bottom, parent_width, ui_logical_viewport_size);
-
-        // Calculate the border rects, ensuring no overlap.
-        // The border occupies the space between the node's bounding rect and the node's bounding rect inset in each direction by the node's corresponding border value.
-        let max = 0.5 * node.size();
-        let min = -max;
-        let inner_min = min + Vec2::new(left, top);
-        let inner_max = (max - Vec2::new(right, bottom)).max(inner_min);
-        let border_rects = [
-            // Left border
-            Rect {
-                min,
-                max: Vec2::new(inner_min.x, max.y),
-            },
-            // Right border
-            Rect {
-                min: Vec2::new(inner_max.x, min.y),
-                max,
-            },
-            // Top border
-            Rect {
-                min: Vec2::new(inner_min.x, min.y),
-                max: Vec2::new(inner_max.x, inner_min.y),
-            },
-            // Bottom border
-            Rect {
-                min: Vec2::new(inner_min.x, inner_max.y),
-                max: Vec2::new(inner_max.x, max.y),
-            },
-        ];
+        if left + right + top + bottom <= 0. {
+            continue;
+        }
+        let widths = [left, top, right, bottom];
+        let border_radius = border_radius
+            .map(|radius| radius.resolve(node.size(), ui_logical_viewport_size))
+            .unwrap_or_default();
 
         let transform = global_transform.compute_matrix();
-
-        for edge in border_rects {
-            if edge.min.x < edge.max.x && edge.min.y < edge.max.y {
-                extracted_uinodes.uinodes.insert(
-                    commands.spawn_empty().id(),
-                    ExtractedUiNode {
-                        stack_index: node.stack_index,
-                        // This translates the uinode's transform to the center of the current border rectangle
-                        transform: transform * Mat4::from_translation(edge.center().extend(0.)),
-                        color: border_color.0,
-                        rect: Rect {
-                            max: edge.size(),
-                            ..Default::default()
-                        },
-                        image,
-                        atlas_size: None,
-                        clip: clip.map(|clip| clip.clip),
-                        flip_x: false,
-                        flip_y: false,
-                        camera_entity,
+        let max = 0.5 * node.size();
+        let node_rect = Rect { min: -max, max };
+        let mut extract_border = |rect: Rect, color: Color, side: Option<BorderSide>| {
+            if color.is_fully_transparent() || rect.is_empty() {
+                return;
+            }
+            extracted_uinodes.uinodes.insert(
+                commands.spawn_empty().id(),
+                ExtractedUiNode {
+                    stack_index: node.stack_index,
+                    // This translates the uinode's transform to the center of the current border rectangle
+                    transform: transform * Mat4::from_translation(rect.center().extend(0.)),
+                    color,
+                    rect: Rect {
+                        max: rect.size(),
+                        ..Default::default()
                     },
-                );
+                    image,
+                    atlas_size: None,
+                    clip: clip.map(|clip| clip.clip),
+                    flip_x: false,
+                    flip_y: false,
+                    camera_entity,
+                    shape: Rect::from_center_size(-rect.center(), node.size()),
+                    border_radius,
+                    node_type: NodeType::Border { widths, side },
+                },
+            );
+        };
+
+        match side_colors {
+            Some(colors) => {
+                // Each side is drawn on the half of the node it's on, and the shader only keeps
+                // the part of the border on that side.
+                let sides = [
+                    (
+                        Rect::new(-max.x, -max.y, 0., max.y),
+                        colors.left,
+                        BorderSide::Left,
+                    ),
+                    (
+                        Rect::new(-max.x, -max.y, max.x, 0.),
+                        colors.top,
+                        BorderSide::Top,
+                    ),
+                    (
+                        Rect::new(0., -max.y, max.x, max.y),
+                        colors.right,
+                        BorderSide::Right,
+                    ),
+                    (
+                        Rect::new(-max.x, 0., max.x, max.y),
+                        colors.bottom,
+                        BorderSide::Bottom,
+                    ),
+                ];
+                for (rect, color, side) in sides {
+                    extract_border(rect, color, Some(side));
+                }
             }
+            None => extract_border(node_rect, border_color.0, None),
         }
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn extract_uinode_outlines(
     mut commands: Commands,
     mut extracted_uinodes: ResMut<ExtractedUiNodes>,
+    camera_query: Extract<Query<(Entity, &Camera)>>,
     default_ui_camera: Extract<DefaultUiCamera>,
+    ui_scale: Extract<Res<UiScale>>,
     uinode_query: Extract<
         Query<(
             &Node,
             &GlobalTransform,
             &Outline,
+            Option<&BorderRadius>,
             &ViewVisibility,
             Option<&CalculatedClip>,
             Option<&TargetCamera>,
@@ -301,7 +386,9 @@ pub fn extract_uinode_outlines(
     >,
 ) {
     let image = AssetId::<Image>::default();
-    for (node, global_transform, outline, view_visibility, maybe_clip, camera) in &uinode_query {
+    for (node, global_transform, outline, border_radius, view_visibility, maybe_clip, camera) in
+        &uinode_query
+    {
         let Some(camera_entity) = camera.map(TargetCamera::entity).or(default_ui_camera.get())
         else {
             continue;
@@ -314,73 +401,254 @@ pub fn extract_uinode_outlines(
             continue;
         }
 
-        // Calculate the outline rects.
-        let inner_rect = Rect::from_center_size(Vec2::ZERO, node.size() + 2. * node.outline_offset);
-        let outer_rect = inner_rect.inset(node.outline_width());
-        let outline_edges = [
-            // Left edge
-            Rect::new(
-                outer_rect.min.x,
-                outer_rect.min.y,
-                inner_rect.min.x,
-                outer_rect.max.y,
-            ),
-            // Right edge
-            Rect::new(
-                inner_rect.max.x,
-                outer_rect.min.y,
-                outer_rect.max.x,
-                outer_rect.max.y,
-            ),
-            // Top edge
-            Rect::new(
-                inner_rect.min.x,
-                outer_rect.min.y,
-                inner_rect.max.x,
-                inner_rect.min.y,
-            ),
-            // Bottom edge
-            Rect::new(
-                inner_rect.min.x,
-                inner_rect.max.y,
-                inner_rect.max.x,
-                outer_rect.max.y,
-            ),
-        ];
+        // The outline is the border of the node grown by its offset and width, and its rounded
+        // corners follow the corners of the node.
+        let outline_rect =
+            Rect::from_center_size(Vec2::ZERO, node.size() + 2. * node.outline_offset)
+                .inset(node.outline_width());
+        let border_radius = border_radius
+            .map(|radius| {
+                let viewport_size =
+                    ui_logical_viewport_size(&camera_query, camera_entity, &ui_scale);
+                radius
+                    .resolve(node.size(), viewport_size)
+                    .map(|radius| match radius {
+                        0. => 0.,
+                        radius => radius + node.outline_offset + node.outline_width(),
+                    })
+            })
+            .unwrap_or_default();
 
-        let transform = global_transform.compute_matrix();
+        extracted_uinodes.uinodes.insert(
+            commands.spawn_empty().id(),
+            ExtractedUiNode {
+                stack_index: node.stack_index,
+                transform: global_transform.compute_matrix(),
+                color: outline.color,
+                rect: Rect {
+                    max: outline_rect.size(),
+                    ..Default::default()
+                },
+                image,
+                atlas_size: None,
+                clip: maybe_clip.map(|clip| clip.clip),
+                flip_x: false,
+                flip_y: false,
+                camera_entity,
+                shape: outline_rect,
+                border_radius,
+                node_type: NodeType::Border {
+                    widths: [node.outline_width(); 4],
+                    side: None,
+                },
+            },
+        );
+    }
+}
 
-        for edge in outline_edges {
-            if edge.min.x < edge.max.x && edge.min.y < edge.max.y {
-                extracted_uinodes.uinodes.insert(
-                    commands.spawn_empty().id(),
-                    ExtractedUiNode {
-                        stack_index: node.stack_index,
-                        // This translates the uinode's transform to the center of the current border rectangle
-                        transform: transform * Mat4::from_translation(edge.center().extend(0.)),
-                        color: outline.color,
-                        rect: Rect {
-                            max: edge.size(),
-                            ..Default::default()
-                        },
-                        image,
-                        atlas_size: None,
-                        clip: maybe_clip.map(|clip| clip.clip),
-                        flip_x: false,
-                        flip_y: false,
-                        camera_entity,
-                    },
-                );
+#[allow(clippy::too_many_arguments)]
+pub fn extract_uinode_box_shadows(
+    mut commands: Commands,
+    mut extracted_uinodes: ResMut<ExtractedUiNodes>,
+    camera_query: Extract<Query<(Entity, &Camera)>>,
+    default_ui_camera: Extract<DefaultUiCamera>,
+    ui_scale: Extract<Res<UiScale>>,
+    uinode_query: Extract<
+        Query<(
+            &Node,
+            &GlobalTransform,
+            &BoxShadow,
+            Option<&BorderRadius>,
+            &ViewVisibility,
+            Option<&CalculatedClip>,
+            Option<&TargetCamera>,
+        )>,
+    >,
+) {
+    let image = AssetId::<Image>::default();
+    for (node, global_transform, shadow, border_radius, view_visibility, clip, camera) in
+        &uinode_query
+    {
+        let Some(camera_entity) = camera.map(TargetCamera::entity).or(default_ui_camera.get())
+        else {
+            continue;
+        };
+        // Skip invisible shadows
+        if !view_visibility.get()
+            || shadow.color.is_fully_transparent()
+            || node.size().x <= 0.
+            || node.size().y <= 0.
+        {
+            continue;
+        }
+
+        let viewport_size = ui_logical_viewport_size(&camera_query, camera_entity, &ui_scale);
+        let resolve = |value: Val| value.resolve(node.size().x, viewport_size).unwrap_or(0.);
+        let offset = Vec2::new(resolve(shadow.x_offset), resolve(shadow.y_offset));
+        let spread = resolve(shadow.spread_radius);
+        let blur_radius = resolve(shadow.blur_radius).max(0.);
+        let size = (node.size() + 2. * spread).max(Vec2::ZERO);
+        if size.x <= 0. || size.y <= 0. {
+            continue;
+        }
+        // Like in CSS, the spread grows the rounded corners along with the shadow.
+        let border_radius = border_radius
+            .map(|radius| {
+                radius
+                    .resolve(node.size(), viewport_size)
+                    .map(|radius| match radius {
+                        0. => 0.,
+                        radius => (radius + spread).clamp(0., 0.5 * size.min_element()),
+                    })
+            })
+            .unwrap_or_default();
+        // The gaussian blur is negligible beyond three standard deviations.
+        let quad_size = size + 3. * blur_radius;
+
+        extracted_uinodes.uinodes.insert(
+            commands.spawn_empty().id(),
+            ExtractedUiNode {
+                stack_index: node.stack_index,
+                transform: global_transform.compute_matrix()
+                    * Mat4::from_translation(offset.extend(0.)),
+                color: shadow.color,
+                rect: Rect {
+                    max: quad_size,
+                    ..Default::default()
+                },
+                image,
+                atlas_size: None,
+                clip: clip.map(|clip| clip.clip),
+                flip_x: false,
+                flip_y: false,
+                camera_entity,
+                shape: Rect::from_center_size(Vec2::ZERO, size),
+                border_radius,
+                node_type: NodeType::Shadow { blur_radius },
+            },
+        );
+    }
+}
+
+/// The highest stack index of `entity` and its descendants.
+fn max_descendant_stack_index(
+    entity: Entity,
+    stack_index: u32,
+    node_query: &Query<&Node>,
+    children_query: &Query<&Children>,
+) -> u32 {
+    children_query
+        .get(entity)
+        .into_iter()
+        .flatten()
+        .filter_map(|child| {
+            let node = node_query.get(*child).ok()?;
+            Some(max_descendant_stack_index(
+                *child,
+                node.stack_index,
+                node_query,
+                children_query,
+            ))
+        })
+        .fold(stack_index, u32::max)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn extract_uinode_scrollbars(
+    mut commands: Commands,
+    mut extracted_uinodes: ResMut<ExtractedUiNodes>,
+    default_ui_camera: Extract<DefaultUiCamera>,
+    uinode_query: Extract<
+        Query<(
+            Entity,
+            &Node,
+            &GlobalTransform,
+            &Style,
+            &ScrollPosition,
+            &Scrollbars,
+            &ViewVisibility,
+            Option<&CalculatedClip>,
+            Option<&TargetCamera>,
+        )>,
+    >,
+    node_query: Extract<Query<&Node>>,
+    children_query: Extract<Query<&Children>>,
+) {
+    let image = AssetId::<Image>::default();
+    for (
+        entity,
+        node,
+        global_transform,
+        style,
+        scroll_position,
+        scrollbars,
+        view_visibility,
+        clip,
+        camera,
+    ) in &uinode_query
+    {
+        let Some(camera_entity) = camera.map(TargetCamera::entity).or(default_ui_camera.get())
+        else {
+            continue;
+        };
+        if !view_visibility.get() {
+            continue;
+        }
+
+        // Scrollbars are drawn above the content of the node.
+        let stack_index =
+            max_descendant_stack_index(entity, node.stack_index, &node_query, &children_query);
+        let transform = global_transform.compute_matrix();
+        let scrollbar_rects = scrollbars
+            .rects(node, style.overflow, scroll_position.offset())
+            .into_iter()
+            .flatten()
+            .flat_map(|scrollbar| {
+                [
+                    (scrollbar.track, scrollbars.track_color),
+                    (scrollbar.thumb, scrollbars.thumb_color),
+                ]
+            });
+        for (rect, color) in scrollbar_rects {
+            if color.is_fully_transparent() || rect.is_empty() {
+                continue;
             }
+            let size = rect.size();
+            // The rects are relative to the top left corner of the node.
+            let center = rect.center() - 0.5 * node.size();
+            extracted_uinodes.uinodes.insert(
+                commands.spawn_empty().id(),
+                ExtractedUiNode {
+                    stack_index,
+                    transform: transform * Mat4::from_translation(center.extend(0.)),
+                    color,
+                    rect: Rect {
+                        max: size,
+                        ..Default::default()
+                    },
+                    image,
+                    atlas_size: None,
+                    clip: clip.map(|clip| clip.clip),
+                    flip_x: false,
+                    flip_y: false,
+                    camera_entity,
+                    shape: Rect::from_center_size(Vec2::ZERO, size),
+                    border_radius: [0.5 * size.min_element(); 4],
+                    node_type: NodeType::Scrollbar,
+                },
+            );
         }
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn extract_uinodes(
     mut commands: Commands,
     mut extracted_uinodes: ResMut<ExtractedUiNodes>,
+    camera_query: Extract<Query<(Entity, &Camera)>>,
     texture_atlases: Extract<Res<Assets<TextureAtlasLayout>>>,
     default_ui_camera: Extract<DefaultUiCamera>,
+    ui_scale: Extract<Res<UiScale>>,
     uinode_query: Extract<
         Query<(
             Entity,
@@ -393,6 +661,7 @@ pub fn extract_uinodes(
             Option<&TextureAtlas>,
             Option<&TargetCamera>,
             Option<&ComputedTextureSlices>,
+            Option<&BorderRadius>,
         )>,
     >,
 ) {
@@ -407,6 +676,7 @@ pub fn extract_uinodes(
         atlas,
         camera,
         slices,
+        border_radius,
     ) in uinode_query.iter()
     {
         let Some(camera_entity) = camera.map(TargetCamera::entity).or(default_ui_camera.get())
@@ -418,10 +688,26 @@ pub fn extract_uinodes(
             continue;
         }
 
+        let border_radius = border_radius
+            .map(|radius| {
+                let viewport_size =
+                    ui_logical_viewport_size(&camera_query, camera_entity, &ui_scale);
+                radius.resolve(uinode.size(), viewport_size)
+            })
+            .unwrap_or_default();
+
         if let Some((image, slices)) = maybe_image.zip(slices) {
             extracted_uinodes.uinodes.extend(
                 slices
-                    .extract_ui_nodes(transform, uinode, color, image, clip, camera_entity)
+                    .extract_ui_nodes(
+                        transform,
+                        uinode,
+                        color,
+                        image,
+                        clip,
+                        camera_entity,
+                        border_radius,
+                    )
                     .map(|e| (commands.spawn_empty().id(), e)),
             );
             continue;
@@ -469,6 +755,9 @@ pub fn extract_uinodes(
                 flip_x,
                 flip_y,
                 camera_entity,
+                shape: Rect::from_center_size(Vec2::ZERO, rect.size()),
+                border_radius,
+                node_type: NodeType::Rect,
             },
         );
     }
@@ -633,6 +922,9 @@ pub fn extract_text_uinodes(
                     flip_x: false,
                     flip_y: false,
                     camera_entity,
+                    shape: Rect::default(),
+                    border_radius: [0.; 4],
+                    node_type: NodeType::Rect,
                 },
             );
         }
@@ -645,7 +937,17 @@ struct UiVertex {
     pub position: [f32; 3],
     pub uv: [f32; 2],
     pub color: [f32; 4],
-    pub mode: u32,
+    /// Flags describing how the node is drawn.
+    pub flags: u32,
+    /// Radii of the corners of the shape, clockwise from the top left.
+    pub radius: [f32; 4],
+    /// Border widths of the shape: left, top, right and bottom. For shadows, the first one is
+    /// the blur radius.
+    pub border: [f32; 4],
+    /// Size of the shape.
+    pub size: [f32; 2],
+    /// Position relative to the center of the shape.
+    pub point: [f32; 2],
 }
 
 #[derive(Resource)]
@@ -679,8 +981,30 @@ pub struct UiBatch {
     pub camera: Entity,
 }
 
-const TEXTURED_QUAD: u32 = 0;
-const UNTEXTURED_QUAD: u32 = 1;
+/// Flags of [`UiVertex`], matching the ones in `ui.wgsl`.
+pub mod shader_flags {
+    /// The node is colored by its image.
+    pub const TEXTURED: u32 = 1;
+    /// The node is masked by its rounded shape.
+    pub const ROUNDED: u32 = 2;
+    /// The node draws the border of its shape.
+    pub const BORDER: u32 = 4;
+    /// The node draws the blurred shadow of its shape.
+    pub const SHADOW: u32 = 8;
+    /// Only the left side of the border is drawn.
+    pub const BORDER_LEFT: u32 = 16;
+    /// Only the top side of the border is drawn.
+    pub const BORDER_TOP: u32 = 32;
+    /// Only the right side of the border is drawn.
+    pub const BORDER_RIGHT: u32 = 64;
+    /// Only the bottom side of the border is drawn.
+    pub const BORDER_BOTTOM: u32 = 128;
+}
+
+/// Shadows are drawn behind their node, and above the nodes below it.
+const SHADOW_STACK_OFFSET: f32 = -0.5;
+/// Scrollbars are drawn above the content of their node, and below the nodes above it.
+const SCROLLBAR_STACK_OFFSET: f32 = 0.5;
 
 #[allow(clippy::too_many_arguments)]
 pub fn queue_uinodes(
@@ -708,7 +1032,15 @@ pub fn queue_uinodes(
             pipeline,
             entity: *entity,
             sort_key: (
-                FloatOrd(extracted_uinode.stack_index as f32),
+                FloatOrd(match extracted_uinode.node_type {
+                    NodeType::Shadow { .. } => {
+                        extracted_uinode.stack_index as f32 + SHADOW_STACK_OFFSET
+                    }
+                    NodeType::Scrollbar => {
+                        extracted_uinode.stack_index as f32 + SCROLLBAR_STACK_OFFSET
+                    }
+                    _ => extracted_uinode.stack_index as f32,
+                }),
                 entity.index(),
             ),
             // batch_range will be calculated in prepare_uinodes
@@ -835,11 +1167,30 @@ pub fn prepare_uinodes(
                         }
                     }
 
-                    let mode = if extracted_uinode.image != AssetId::default() {
-                        TEXTURED_QUAD
-                    } else {
-                        UNTEXTURED_QUAD
+                    let textured = extracted_uinode.image != AssetId::default();
+                    let (border, mut flags) = match extracted_uinode.node_type {
+                        NodeType::Rect if extracted_uinode.border_radius == [0.; 4] => ([0.; 4], 0),
+                        NodeType::Rect | NodeType::Scrollbar => ([0.; 4], shader_flags::ROUNDED),
+                        NodeType::Border { widths, side } => (
+                            widths,
+                            shader_flags::ROUNDED
+                                | shader_flags::BORDER
+                                | match side {
+                                    None => 0,
+                                    Some(BorderSide::Left) => shader_flags::BORDER_LEFT,
+                                    Some(BorderSide::Top) => shader_flags::BORDER_TOP,
+                                    Some(BorderSide::Right) => shader_flags::BORDER_RIGHT,
+                                    Some(BorderSide::Bottom) => shader_flags::BORDER_BOTTOM,
+                                },
+                        ),
+                        NodeType::Shadow { blur_radius } => (
+                            [blur_radius, 0., 0., 0.],
+                            shader_flags::ROUNDED | shader_flags::SHADOW,
+                        ),
                     };
+                    if textured {
+                        flags |= shader_flags::TEXTURED;
+                    }
 
                     let mut uinode_rect = extracted_uinode.rect;
 
@@ -899,7 +1250,15 @@ pub fn prepare_uinodes(
                             continue;
                         }
                     }
-                    let uvs = if mode == UNTEXTURED_QUAD {
+                    // The corners of the quad relative to the center of the shape, so that the
+                    // shape isn't moved by clipping.
+                    let points = [0, 1, 2, 3].map(|i| {
+                        QUAD_VERTEX_POSITIONS[i].truncate() * rect_size.truncate()
+                            + positions_diff[i]
+                            - extracted_uinode.shape.center()
+                    });
+
+                    let uvs = if !textured {
                         [Vec2::ZERO, Vec2::X, Vec2::ONE, Vec2::Y]
                     } else {
                         let atlas_extent = extracted_uinode.atlas_size.unwrap_or(uinode_rect.max);
@@ -939,12 +1298,17 @@ pub fn prepare_uinodes(
                     };
 
                     let color = extracted_uinode.color.as_linear_rgba_f32();
+                    let size = extracted_uinode.shape.size().into();
                     for i in QUAD_INDICES {
                         ui_meta.vertices.push(UiVertex {
                             position: positions_clipped[i].into(),
                             uv: uvs[i].into(),
                             color,
-                            mode,
+                            flags,
+                            radius: extracted_uinode.border_radius,
+                            border,
+                            size,
+                            point: points[i].into(),
                         });
                     }
                     index += QUAD_INDICES.len() as u32;
@@ -63,8 +63,16 @@ impl SpecializedRenderPipeline for UiPipeline {
                 VertexFormat::Float32x2,
                 // color
                 VertexFormat::Float32x4,
-                // mode
+                // flags
                 VertexFormat::Uint32,
+                // border radius
+                VertexFormat::Float32x4,
+                // border widths
+                VertexFormat::Float32x4,
+                // shape size
+                VertexFormat::Float32x2,
+                // position relative to the center of the shape
+                VertexFormat::Float32x2,
             ],
         );
         let shader_defs = Vec::new();
@@ -1,13 +1,30 @@
 #import bevy_render::view::View
 
-const TEXTURED_QUAD: u32 = 0u;
+// Flags of the vertices, matching `shader_flags` in `render/mod.rs`.
+const TEXTURED: u32 = 1u;
+const ROUNDED: u32 = 2u;
+const BORDER: u32 = 4u;
+const SHADOW: u32 = 8u;
+const BORDER_LEFT: u32 = 16u;
+const BORDER_TOP: u32 = 32u;
+const BORDER_RIGHT: u32 = 64u;
+const BORDER_BOTTOM: u32 = 128u;
+const BORDER_SIDES: u32 = 240u;
 
 @group(0) @binding(0) var<uniform> view: View;
 
 struct VertexOutput {
     @location(0) uv: vec2<f32>,
     @location(1) color: vec4<f32>,
-    @location(3) @interpolate(flat) mode: u32,
+    @location(3) @interpolate(flat) flags: u32,
+    // Radii of the corners of the shape, clockwise from the top left.
+    @location(4) @interpolate(flat) radius: vec4<f32>,
+    // Border widths of the shape: left, top, right and bottom.
+    // For shadows, the first one is the blur radius.
+    @location(5) @interpolate(flat) border: vec4<f32>,
+    @location(6) @interpolate(flat) size: vec2<f32>,
+    // Position relative to the center of the shape.
+    @location(7) point: vec2<f32>,
     @builtin(position) position: vec4<f32>,
 };
 
@@ -16,27 +33,136 @@ fn vertex(
     @location(0) vertex_position: vec3<f32>,
     @location(1) vertex_uv: vec2<f32>,
     @location(2) vertex_color: vec4<f32>,
-    @location(3) mode: u32,
+    @location(3) flags: u32,
+    @location(4) radius: vec4<f32>,
+    @location(5) border: vec4<f32>,
+    @location(6) size: vec2<f32>,
+    @location(7) point: vec2<f32>,
 ) -> VertexOutput {
     var out: VertexOutput;
     out.uv = vertex_uv;
     out.position = view.view_proj * vec4<f32>(vertex_position, 1.0);
     out.color = vertex_color;
-    out.mode = mode;
+    out.flags = flags;
+    out.radius = radius;
+    out.border = border;
+    out.size = size;
+    out.point = point;
     return out;
 }
 
 @group(1) @binding(0) var sprite_texture: texture_2d<f32>;
 @group(1) @binding(1) var sprite_sampler: sampler;
 
+// The signed distance from `point` to the edge of a box of size `size` centered at the origin,
+// with rounded corners of radii `corner_radii`, clockwise from the top left.
+fn sd_rounded_box(point: vec2<f32>, size: vec2<f32>, corner_radii: vec4<f32>) -> f32 {
+    // Select the radii of the bottom corners below the center, in left to right order.
+    let rs = select(corner_radii.xy, corner_radii.wz, 0.0 < point.y);
+    let radius = select(rs.x, rs.y, 0.0 < point.x);
+    // Vector from the center of the radius circle to the point.
+    let q = abs(point) - 0.5 * size + radius;
+    return length(max(q, vec2(0.0))) + min(max(q.x, q.y), 0.0) - radius;
+}
+
+// The signed distance from `point` to the inner edge of a border of widths `inset`, left, top,
+// right and bottom, of a rounded box.
+fn sd_inset_rounded_box(point: vec2<f32>, size: vec2<f32>, radius: vec4<f32>, inset: vec4<f32>) -> f32 {
+    let inner_size = max(size - inset.xy - inset.zw, vec2(0.0));
+    let inner_center = inset.xy + 0.5 * inner_size - 0.5 * size;
+    // The inner corners are rounded by the outer radii minus the widths of the border.
+    var r = radius - vec4(
+        max(inset.x, inset.y),
+        max(inset.z, inset.y),
+        max(inset.z, inset.w),
+        max(inset.x, inset.w),
+    );
+    r = clamp(r, vec4(0.0), vec4(0.5 * min(inner_size.x, inner_size.y)));
+    return sd_rounded_box(point - inner_center, inner_size, r);
+}
+
+// The coverage of a pixel of size `pixel` at the signed distance `distance` from an edge.
+fn antialias(distance: f32, pixel: f32) -> f32 {
+    return saturate(0.5 - distance / pixel);
+}
+
+// An approximation of the error function.
+fn erf(x: f32) -> f32 {
+    let a = abs(x);
+    let t = 1.0 + (0.278393 + (0.230389 + 0.078108 * a * a) * a) * a;
+    let t2 = t * t;
+    return sign(x) * (1.0 - 1.0 / (t2 * t2));
+}
+
+// The coverage of a shadow at the signed distance `distance` from the edge of its shape, blurred
+// by a gaussian with a standard deviation of half `blur_radius`.
+fn shadow(distance: f32, blur_radius: f32, pixel: f32) -> f32 {
+    let sigma = 0.5 * blur_radius;
+    if sigma <= 0.0 {
+        return antialias(distance, pixel);
+    }
+    return 0.5 - 0.5 * erf(distance / (sigma * sqrt(2.0)));
+}
+
+// Returns true if `point` is on the sides of the border selected by `flags`. The corners are
+// split between their sides along the line from the outer to the inner corner of the border.
+fn on_border_side(point: vec2<f32>, size: vec2<f32>, border: vec4<f32>, flags: u32) -> bool {
+    if (flags & BORDER_SIDES) == 0u {
+        return true;
+    }
+    let half_size = 0.5 * size;
+    // The distances to each edge relative to the width of its side, ignoring sides without a
+    // border.
+    let distances = vec4(
+        point.x + half_size.x,
+        point.y + half_size.y,
+        half_size.x - point.x,
+        half_size.y - point.y,
+    ) / max(border, vec4(1e-6));
+    let d = select(distances, vec4(1e30), border <= vec4(0.0));
+    var side = BORDER_LEFT;
+    var nearest = d.x;
+    if d.y < nearest {
+        side = BORDER_TOP;
+        nearest = d.y;
+    }
+    if d.z < nearest {
+        side = BORDER_RIGHT;
+        nearest = d.z;
+    }
+    if d.w < nearest {
+        side = BORDER_BOTTOM;
+    }
+    return (flags & side) != 0u;
+}
+
 @fragment
 fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
-    // textureSample can only be called in unform control flow, not inside an if branch.
-    var color = textureSample(sprite_texture, sprite_sampler, in.uv);
-    if in.mode == TEXTURED_QUAD {
-        color = in.color * color;
+    // textureSample and fwidth can only be called in uniform control flow, not inside an if branch.
+    let texture_color = textureSample(sprite_texture, sprite_sampler, in.uv);
+    // The size of a physical pixel in logical pixels, the units of the shape.
+    let pixel = max(fwidth(in.point.x), 1e-4);
+
+    var color = in.color;
+    if (in.flags & TEXTURED) != 0u {
+        color = color * texture_color;
+    }
+    if (in.flags & ROUNDED) == 0u {
+        return color;
+    }
+
+    let distance = sd_rounded_box(in.point, in.size, in.radius);
+    var coverage: f32;
+    if (in.flags & SHADOW) != 0u {
+        coverage = shadow(distance, in.border.x, pixel);
+    } else if (in.flags & BORDER) != 0u {
+        let inner_distance = sd_inset_rounded_box(in.point, in.size, in.radius, in.border);
+        coverage = antialias(max(distance, -inner_distance), pixel);
+        if !on_border_side(in.point, in.size, in.border, in.flags) {
+            coverage = 0.0;
+        }
     } else {
-        color = in.color;
+        coverage = antialias(distance, pixel);
     }
-    return color;
+    return vec4(color.rgb, color.a * coverage);
 }
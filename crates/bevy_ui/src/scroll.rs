@@ -0,0 +1,442 @@
+use crate::{
+    CalculatedClip, DefaultUiCamera, Node, Overflow, ScrollPosition, Style, TargetCamera, UiScale,
+    UiStack,
+};
+use bevy_ecs::{
+    change_detection::DetectChangesMut,
+    entity::Entity,
+    event::EventReader,
+    prelude::{Component, With},
+    query::QueryData,
+    reflect::ReflectComponent,
+    system::{Local, Query, Res},
+};
+use bevy_input::{
+    keyboard::KeyCode,
+    mouse::{MouseScrollUnit, MouseWheel},
+    pointer::{PointerButton, PointerId, Pointers},
+    ButtonInput,
+};
+use bevy_math::{Rect, Vec2};
+use bevy_reflect::{std_traits::ReflectDefault, Reflect};
+use bevy_render::{
+    camera::NormalizedRenderTarget, color::Color, prelude::Camera, view::ViewVisibility,
+};
+use bevy_time::Time;
+use bevy_transform::components::GlobalTransform;
+use bevy_utils::HashMap;
+use bevy_window::PrimaryWindow;
+
+#[cfg(feature = "serialize")]
+use bevy_reflect::{ReflectDeserialize, ReflectSerialize};
+
+/// Scrolls the [`ScrollPosition`] of a node with [`OverflowAxis::Scroll`](crate::OverflowAxis::Scroll)
+/// from user input, in [`ui_scroll_system`].
+///
+/// - The mouse wheel scrolls the innermost view under the cursor that can scroll in its
+///   direction. Holding shift scrolls vertical wheels horizontally.
+/// - Touches and pens scroll the view by dragging its content.
+/// - Any pointer can drag the thumbs of the [`Scrollbars`] of the view.
+///
+/// When [`kinetic`](Self::kinetic) is set, the content keeps scrolling after a drag is released,
+/// slowing down with [`friction`](Self::friction).
+#[derive(Component, Copy, Clone, Debug, PartialEq, Reflect)]
+#[reflect(Component, Default, PartialEq)]
+#[cfg_attr(
+    feature = "serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    reflect(Serialize, Deserialize)
+)]
+pub struct ScrollView {
+    /// The distance scrolled by a line of the mouse wheel, in logical pixels.
+    pub line_height: f32,
+    /// Keep scrolling after a drag is released.
+    pub kinetic: bool,
+    /// The rate at which kinetic scrolling slows down, per second.
+    pub friction: f32,
+    /// The current velocity of kinetic scrolling, in logical pixels per second.
+    pub velocity: Vec2,
+}
+
+impl ScrollView {
+    pub const DEFAULT: Self = Self {
+        line_height: 20.,
+        kinetic: true,
+        friction: 4.,
+        velocity: Vec2::ZERO,
+    };
+}
+
+impl Default for ScrollView {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Draws scrollbars along the right and bottom edges of a node with a [`ScrollPosition`], on the
+/// axes where its content overflows it.
+///
+/// The thumbs can be dragged to scroll the node with a [`ScrollView`].
+#[derive(Component, Copy, Clone, Debug, PartialEq, Reflect)]
+#[reflect(Component, Default, PartialEq)]
+#[cfg_attr(
+    feature = "serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    reflect(Serialize, Deserialize)
+)]
+pub struct Scrollbars {
+    /// The width of the scrollbars, in logical pixels.
+    pub width: f32,
+    /// The color of the thumbs.
+    pub thumb_color: Color,
+    /// The color of the tracks behind the thumbs.
+    pub track_color: Color,
+    /// The minimum length of the thumbs, in logical pixels.
+    pub min_thumb_length: f32,
+}
+
+impl Scrollbars {
+    pub const DEFAULT: Self = Self {
+        width: 8.,
+        thumb_color: Color::rgba(0.5, 0.5, 0.5, 0.8),
+        track_color: Color::NONE,
+        min_thumb_length: 16.,
+    };
+
+    /// The track and thumb rects of the scrollbars of a node, horizontal then vertical, relative
+    /// to the top left corner of the node.
+    pub(crate) fn rects(
+        &self,
+        node: &Node,
+        overflow: Overflow,
+        offset: Vec2,
+    ) -> [Option<Scrollbar>; 2] {
+        let size = node.size();
+        let max_offset = node.max_scroll_offset();
+        let shown = [
+            overflow.x.is_scroll() && 0. < max_offset.x,
+            overflow.y.is_scroll() && 0. < max_offset.y,
+        ];
+        let width = self.width.min(size.x).min(size.y);
+        let scrollbar = |axis: usize| {
+            if !shown[axis] || width <= 0. {
+                return None;
+            }
+            let other = 1 - axis;
+            // Leave the corner to the other scrollbar.
+            let mut track_end = size[axis];
+            if shown[other] {
+                track_end -= width;
+            }
+            let mut track = Rect::new(0., 0., size.x, size.y);
+            track.min[other] = size[other] - width;
+            track.max[axis] = track_end.max(0.);
+            let track_length = track.max[axis];
+            let thumb_length = (track_length * size[axis] / node.content_size()[axis])
+                .max(self.min_thumb_length)
+                .min(track_length);
+            let travel = track_length - thumb_length;
+            let mut thumb = track;
+            thumb.min[axis] = travel * (offset[axis] / max_offset[axis]).clamp(0., 1.);
+            thumb.max[axis] = thumb.min[axis] + thumb_length;
+            Some(Scrollbar {
+                track,
+                thumb,
+                travel,
+            })
+        };
+        [scrollbar(0), scrollbar(1)]
+    }
+}
+
+impl Default for Scrollbars {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// The geometry of a scrollbar, in logical pixels.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) struct Scrollbar {
+    pub track: Rect,
+    pub thumb: Rect,
+    /// How far the thumb moves along the track between the start and end of the content.
+    pub travel: f32,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum DragKind {
+    /// Dragging the content of the view.
+    Content,
+    /// Dragging the thumb of the scrollbar on an axis.
+    Thumb(usize),
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Drag {
+    entity: Entity,
+    kind: DragKind,
+    last_position: Vec2,
+    /// The velocity of the content, for kinetic scrolling.
+    velocity: Vec2,
+}
+
+/// The drags of the pointers scrolling a [`ScrollView`].
+#[derive(Default)]
+pub struct ScrollState {
+    drags: HashMap<PointerId, Drag>,
+}
+
+/// Main query for [`ui_scroll_system`]
+#[derive(QueryData)]
+#[query_data(mutable)]
+pub struct ScrollViewQuery {
+    node: &'static Node,
+    global_transform: &'static GlobalTransform,
+    style: &'static Style,
+    scroll_position: &'static mut ScrollPosition,
+    scroll_view: &'static mut ScrollView,
+    scrollbars: Option<&'static Scrollbars>,
+    calculated_clip: Option<&'static CalculatedClip>,
+    target_camera: Option<&'static TargetCamera>,
+    view_visibility: Option<&'static ViewVisibility>,
+}
+
+/// The system that scrolls [`ScrollView`]s from the mouse wheel and the [`Pointers`].
+#[allow(clippy::too_many_arguments)]
+pub fn ui_scroll_system(
+    mut state: Local<ScrollState>,
+    camera_query: Query<(Entity, &Camera)>,
+    default_ui_camera: DefaultUiCamera,
+    primary_window: Query<Entity, With<PrimaryWindow>>,
+    pointers: Res<Pointers>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut mouse_wheel_events: EventReader<MouseWheel>,
+    time: Res<Time>,
+    ui_scale: Res<UiScale>,
+    ui_stack: Res<UiStack>,
+    mut scroll_query: Query<ScrollViewQuery>,
+) {
+    let primary_window = primary_window.iter().next();
+    let delta_seconds = time.delta_seconds();
+
+    // The window and the viewport position of the cameras rendering to a window.
+    let camera_windows: HashMap<Entity, (Entity, Vec2)> = camera_query
+        .iter()
+        .filter_map(|(entity, camera)| {
+            let Some(NormalizedRenderTarget::Window(window_ref)) =
+                camera.target.normalize(primary_window)
+            else {
+                return None;
+            };
+            let viewport_position = camera
+                .logical_viewport_rect()
+                .map(|rect| rect.min)
+                .unwrap_or_default();
+            Some((entity, (window_ref.entity(), viewport_position)))
+        })
+        .collect();
+
+    // The scroll views under a position in a window, from the innermost to the outermost, with
+    // the position relative to their top left corner. Like for interactions, only the visible
+    // part of a clipped view is under the position.
+    let views_under = |scroll_query: &Query<ScrollViewQuery>, window: Entity, position: Vec2| {
+        ui_stack
+            .uinodes
+            .iter()
+            .rev()
+            .filter_map(|entity| {
+                let view = scroll_query.get(*entity).ok()?;
+                if view
+                    .view_visibility
+                    .is_some_and(|visibility| !visibility.get())
+                {
+                    return None;
+                }
+                let camera = view
+                    .target_camera
+                    .map(TargetCamera::entity)
+                    .or(default_ui_camera.get())?;
+                let (camera_window, viewport_position) = camera_windows.get(&camera)?;
+                if *camera_window != window {
+                    return None;
+                }
+                let position = (position - *viewport_position) / ui_scale.0;
+                let node_rect = view.node.logical_rect(view.global_transform);
+                let visible_rect = view
+                    .calculated_clip
+                    .map_or(node_rect, |clip| node_rect.intersect(clip.clip));
+                visible_rect
+                    .contains(position)
+                    .then_some((*entity, position - node_rect.min))
+            })
+            .collect::<Vec<_>>()
+    };
+
+    // Release the drags of the pointers that are no longer pressed, starting kinetic scrolling.
+    state.drags.retain(|id, drag| {
+        if pointers.pressed(*id, PointerButton::Primary) {
+            return true;
+        }
+        if let Ok(mut view) = scroll_query.get_mut(drag.entity) {
+            if drag.kind == DragKind::Content && view.scroll_view.kinetic {
+                view.scroll_view.velocity = drag.velocity;
+            }
+        }
+        false
+    });
+
+    // Start the drags of the pointers that have just been pressed on a scroll view.
+    for (id, pointer) in pointers.iter() {
+        if !pointers.just_pressed(id, PointerButton::Primary) || state.drags.contains_key(&id) {
+            continue;
+        }
+        let Some((entity, relative_position)) =
+            views_under(&scroll_query, pointer.window, pointer.position)
+                .into_iter()
+                .next()
+        else {
+            continue;
+        };
+        let Ok(mut view) = scroll_query.get_mut(entity) else {
+            continue;
+        };
+        let thumb = view.scrollbars.and_then(|scrollbars| {
+            scrollbars
+                .rects(
+                    view.node,
+                    view.style.overflow,
+                    view.scroll_position.offset(),
+                )
+                .into_iter()
+                .position(|scrollbar| {
+                    scrollbar.is_some_and(|scrollbar| scrollbar.thumb.contains(relative_position))
+                })
+        });
+        let kind = match thumb {
+            Some(axis) => DragKind::Thumb(axis),
+            // The mouse selects and clicks content rather than dragging it.
+            None if id == PointerId::Mouse => continue,
+            None => DragKind::Content,
+        };
+        // Catching the content stops kinetic scrolling.
+        view.scroll_view.velocity = Vec2::ZERO;
+        state.drags.insert(
+            id,
+            Drag {
+                entity,
+                kind,
+                last_position: pointer.position,
+                velocity: Vec2::ZERO,
+            },
+        );
+    }
+
+    // Scroll the views dragged by the pointers.
+    for (id, drag) in &mut state.drags {
+        let Some(pointer) = pointers.get(*id) else {
+            continue;
+        };
+        let delta = (pointer.position - drag.last_position) / ui_scale.0;
+        drag.last_position = pointer.position;
+        let Ok(mut view) = scroll_query.get_mut(drag.entity) else {
+            continue;
+        };
+        let offset = view.scroll_position.offset();
+        let scrolled = match drag.kind {
+            DragKind::Content => -delta,
+            DragKind::Thumb(axis) => {
+                let Some(scrollbar) = view
+                    .scrollbars
+                    .and_then(|scrollbars| {
+                        scrollbars.rects(view.node, view.style.overflow, offset)[axis]
+                    })
+                    .filter(|scrollbar| 0. < scrollbar.travel)
+                else {
+                    continue;
+                };
+                let mut scrolled = Vec2::ZERO;
+                scrolled[axis] =
+                    delta[axis] * view.node.max_scroll_offset()[axis] / scrollbar.travel;
+                scrolled
+            }
+        };
+        let new_offset = clamp_scroll_offset(view.node, view.style.overflow, offset + scrolled);
+        if 0. < delta_seconds {
+            // Smooth the velocity over the last frames, so that a slow last frame doesn't stop
+            // the content.
+            let velocity = (new_offset - offset) / delta_seconds;
+            drag.velocity = drag.velocity.lerp(velocity, 0.5);
+        }
+        view.scroll_position.set_if_neq(new_offset.into());
+    }
+
+    // Scroll the innermost view under the mouse that can scroll in the direction of the wheel.
+    let swap_axes = keyboard.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    for event in mouse_wheel_events.read() {
+        let Some(mouse) = pointers
+            .get(PointerId::Mouse)
+            .filter(|mouse| mouse.window == event.window)
+        else {
+            continue;
+        };
+        let mut wheel = Vec2::new(event.x, event.y);
+        if swap_axes {
+            wheel = Vec2::new(wheel.y, wheel.x);
+        }
+        for (entity, _) in views_under(&scroll_query, mouse.window, mouse.position) {
+            let Ok(mut view) = scroll_query.get_mut(entity) else {
+                continue;
+            };
+            let scrolled = match event.unit {
+                MouseScrollUnit::Line => -wheel * view.scroll_view.line_height,
+                MouseScrollUnit::Pixel => -wheel / ui_scale.0,
+            };
+            let offset = view.scroll_position.offset();
+            let new_offset = clamp_scroll_offset(view.node, view.style.overflow, offset + scrolled);
+            if new_offset != offset {
+                view.scroll_view.velocity = Vec2::ZERO;
+                view.scroll_position.set_if_neq(new_offset.into());
+                break;
+            }
+        }
+    }
+
+    // Keep scrolling the views that have been released with a velocity.
+    for mut view in &mut scroll_query {
+        let velocity = view.scroll_view.velocity;
+        if velocity == Vec2::ZERO {
+            continue;
+        }
+        let offset = view.scroll_position.offset() + velocity * delta_seconds;
+        let new_offset = clamp_scroll_offset(view.node, view.style.overflow, offset);
+        let mut new_velocity = velocity * (-view.scroll_view.friction * delta_seconds).exp();
+        // Stop at the edges of the content.
+        for axis in 0..2 {
+            if new_offset[axis] != offset[axis] {
+                new_velocity[axis] = 0.;
+            }
+        }
+        if new_velocity.length_squared() < 1. {
+            new_velocity = Vec2::ZERO;
+        }
+        view.scroll_view.velocity = new_velocity;
+        view.scroll_position.set_if_neq(new_offset.into());
+    }
+}
+
+/// Clamps a scroll offset between zero and the maximum offset of the node on the axes that
+/// scroll.
+pub(crate) fn clamp_scroll_offset(node: &Node, overflow: Overflow, offset: Vec2) -> Vec2 {
+    let max_offset = node.max_scroll_offset();
+    Vec2::new(
+        match overflow.x.is_scroll() {
+            true => offset.x.clamp(0., max_offset.x),
+            false => 0.,
+        },
+        match overflow.y.is_scroll() {
+            true => offset.y.clamp(0., max_offset.y),
+            false => 0.,
+        },
+    )
+}
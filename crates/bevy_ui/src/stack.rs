@@ -2,8 +2,9 @@
 
 use bevy_ecs::prelude::*;
 use bevy_hierarchy::prelude::*;
+use std::cmp::Ordering;
 
-use crate::{Node, ZIndex};
+use crate::{Node, WorldUi, ZIndex};
 
 /// The current UI stack, which contains all UI nodes ordered by their depth (back-to-front).
 ///
@@ -22,6 +23,8 @@ struct StackingContext {
 
 struct StackingContextEntry {
     pub z_index: i32,
+    /// The distance to the camera of a root node with [`WorldUi`].
+    pub depth: Option<f32>,
     pub entity: Entity,
     pub stack: StackingContext,
 }
@@ -30,9 +33,12 @@ struct StackingContextEntry {
 ///
 /// First generate a UI node tree (`StackingContext`) based on z-index.
 /// Then flatten that tree into back-to-front ordered `UiStack`.
+///
+/// Root nodes with [`WorldUi`] are ordered back-to-front by their distance to the camera, behind
+/// the other root nodes with the same z-index.
 pub fn ui_stack_system(
     mut ui_stack: ResMut<UiStack>,
-    root_node_query: Query<Entity, (With<Node>, Without<Parent>)>,
+    root_node_query: Query<(Entity, Option<&WorldUi>), (With<Node>, Without<Parent>)>,
     zindex_query: Query<&ZIndex, With<Node>>,
     children_query: Query<&Children>,
     mut update_query: Query<&mut Node>,
@@ -41,11 +47,12 @@ pub fn ui_stack_system(
     let mut global_context = StackingContext::default();
     let mut total_entry_count: usize = 0;
 
-    for entity in &root_node_query {
+    for (entity, world_ui) in &root_node_query {
         insert_context_hierarchy(
             &zindex_query,
             &children_query,
             entity,
+            world_ui.map(|world_ui| world_ui.depth),
             &mut global_context,
             None,
             &mut total_entry_count,
@@ -69,6 +76,7 @@ fn insert_context_hierarchy(
     zindex_query: &Query<&ZIndex, With<Node>>,
     children_query: &Query<&Children>,
     entity: Entity,
+    depth: Option<f32>,
     global_context: &mut StackingContext,
     parent_context: Option<&mut StackingContext>,
     total_entry_count: &mut usize,
@@ -85,6 +93,7 @@ fn insert_context_hierarchy(
                 zindex_query,
                 children_query,
                 *entity,
+                None,
                 global_context,
                 Some(&mut new_context),
                 total_entry_count,
@@ -102,6 +111,7 @@ fn insert_context_hierarchy(
     *total_entry_count += 1;
     entity_context.entries.push(StackingContextEntry {
         z_index,
+        depth,
         entity,
         stack: new_context,
     });
@@ -112,7 +122,18 @@ fn fill_stack_recursively(result: &mut Vec<Entity>, stack: &mut StackingContext)
     // Sort entries by ascending z_index, while ensuring that siblings
     // with the same local z_index will keep their ordering. This results
     // in `back-to-front` ordering, low z_index = back; high z_index = front.
-    stack.entries.sort_by_key(|e| e.z_index);
+    // World UI roots with the same z_index are sorted from the farthest to the nearest, behind
+    // the other nodes.
+    stack.entries.sort_by(|a, b| {
+        a.z_index
+            .cmp(&b.z_index)
+            .then_with(|| match (a.depth, b.depth) {
+                (Some(a), Some(b)) => b.total_cmp(&a),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            })
+    });
 
     for entry in &mut stack.entries {
         // Parent node renders before/behind child nodes
@@ -125,13 +146,14 @@ fn fill_stack_recursively(result: &mut Vec<Entity>, stack: &mut StackingContext)
 mod tests {
     use bevy_ecs::{
         component::Component,
+        entity::Entity,
         schedule::Schedule,
         system::{CommandQueue, Commands},
         world::World,
     };
     use bevy_hierarchy::BuildChildren;
 
-    use crate::{Node, UiStack, ZIndex};
+    use crate::{Node, UiStack, WorldUi, ZIndex};
 
     use super::ui_stack_system;
 
@@ -235,4 +257,38 @@ mod tests {
         ];
         assert_eq!(actual_result, expected_result);
     }
+
+    #[test]
+    fn world_ui_roots_are_sorted_by_depth() {
+        let mut world = World::default();
+        world.init_resource::<UiStack>();
+
+        let world_ui = |depth| WorldUi {
+            depth,
+            ..WorldUi::new(Entity::PLACEHOLDER)
+        };
+        world.spawn(node_without_zindex("screen"));
+        world.spawn((node_without_zindex("near"), world_ui(2.)));
+        world.spawn((node_without_zindex("far"), world_ui(10.)));
+        world.spawn((node_with_zindex("front", ZIndex::Global(1)), world_ui(20.)));
+
+        let mut schedule = Schedule::default();
+        schedule.add_systems(ui_stack_system);
+        schedule.run(&mut world);
+
+        let mut query = world.query::<&Label>();
+        let ui_stack = world.resource::<UiStack>();
+        let actual_result = ui_stack
+            .uinodes
+            .iter()
+            .map(|entity| query.get(&world, *entity).unwrap().clone())
+            .collect::<Vec<_>>();
+        let expected_result = vec![
+            Label("far"),
+            Label("near"),
+            Label("screen"),
+            Label("front"), // ZIndex::Global(1)
+        ];
+        assert_eq!(actual_result, expected_result);
+    }
 }
@@ -6,11 +6,11 @@ use bevy_asset::{AssetEvent, Assets};
 use bevy_ecs::prelude::*;
 use bevy_math::{Rect, Vec2};
 use bevy_render::texture::Image;
-use bevy_sprite::{ImageScaleMode, TextureSlice};
+use bevy_sprite::{ImageScaleMode, TextureAtlas, TextureAtlasLayout, TextureSlice};
 use bevy_transform::prelude::*;
 use bevy_utils::HashSet;
 
-use crate::{BackgroundColor, CalculatedClip, ExtractedUiNode, Node, UiImage};
+use crate::{BackgroundColor, CalculatedClip, ExtractedUiNode, Node, NodeType, UiImage};
 
 /// Component storing texture slices for image nodes entities with a tiled or sliced  [`ImageScaleMode`]
 ///
@@ -30,7 +30,9 @@ impl ComputedTextureSlices {
     /// * `original_entity` - the sprite entity
     /// * `sprite` - The sprite component
     /// * `handle` - The sprite texture handle
+    /// * `border_radius` - The resolved radii of the corners of the node
     #[must_use]
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn extract_ui_nodes<'a>(
         &'a self,
         transform: &'a GlobalTransform,
@@ -39,6 +41,7 @@ impl ComputedTextureSlices {
         image: &'a UiImage,
         clip: Option<&'a CalculatedClip>,
         camera_entity: Entity,
+        border_radius: [f32; 4],
     ) -> impl ExactSizeIterator<Item = ExtractedUiNode> + 'a {
         let mut flip = Vec2::new(1.0, -1.0);
         let [mut flip_x, mut flip_y] = [false; 2];
@@ -69,21 +72,27 @@ impl ComputedTextureSlices {
                 atlas_size,
                 clip: clip.map(|clip| clip.clip),
                 camera_entity,
+                // The slices are rounded by the corners of the whole node.
+                shape: Rect::from_center_size(-offset.truncate(), node.size()),
+                border_radius,
+                node_type: NodeType::Rect,
             }
         })
     }
 }
 
 /// Generates sprite slices for a `sprite` given a `scale_mode`. The slices
-/// will be computed according to the `image_handle` dimensions or the sprite rect.
+/// will be computed according to the `image_handle` dimensions or the section of the `atlas`.
 ///
-/// Returns `None` if the image asset is not loaded
+/// Returns `None` if the image asset or the atlas layout is not loaded
 #[must_use]
 fn compute_texture_slices(
     draw_area: Vec2,
     scale_mode: &ImageScaleMode,
     image_handle: &UiImage,
     images: &Assets<Image>,
+    atlas: Option<&TextureAtlas>,
+    atlas_layouts: &Assets<TextureAtlasLayout>,
 ) -> Option<ComputedTextureSlices> {
     let image_size = images.get(&image_handle.texture).map(|i| {
         Vec2::new(
@@ -91,9 +100,12 @@ fn compute_texture_slices(
             i.texture_descriptor.size.height as f32,
         )
     })?;
-    let texture_rect = Rect {
-        min: Vec2::ZERO,
-        max: image_size,
+    let texture_rect = match atlas {
+        Some(atlas) => atlas.texture_rect(atlas_layouts)?,
+        None => Rect {
+            min: Vec2::ZERO,
+            max: image_size,
+        },
     };
     let slices = match scale_mode {
         ImageScaleMode::Sliced(slicer) => slicer.compute_slices(texture_rect, Some(draw_area)),
@@ -119,7 +131,14 @@ pub(crate) fn compute_slices_on_asset_event(
     mut commands: Commands,
     mut events: EventReader<AssetEvent<Image>>,
     images: Res<Assets<Image>>,
-    ui_nodes: Query<(Entity, &ImageScaleMode, &Node, &UiImage)>,
+    atlas_layouts: Res<Assets<TextureAtlasLayout>>,
+    ui_nodes: Query<(
+        Entity,
+        &ImageScaleMode,
+        &Node,
+        &UiImage,
+        Option<&TextureAtlas>,
+    )>,
 ) {
     // We store the asset ids of added/modified image assets
     let added_handles: HashSet<_> = events
@@ -133,11 +152,18 @@ pub(crate) fn compute_slices_on_asset_event(
         return;
     }
     // We recompute the sprite slices for sprite entities with a matching asset handle id
-    for (entity, scale_mode, ui_node, image) in &ui_nodes {
+    for (entity, scale_mode, ui_node, image, atlas) in &ui_nodes {
         if !added_handles.contains(&image.texture.id()) {
             continue;
         }
-        if let Some(slices) = compute_texture_slices(ui_node.size(), scale_mode, image, &images) {
+        if let Some(slices) = compute_texture_slices(
+            ui_node.size(),
+            scale_mode,
+            image,
+            &images,
+            atlas,
+            &atlas_layouts,
+        ) {
             commands.entity(entity).insert(slices);
         }
     }
@@ -148,14 +174,44 @@ pub(crate) fn compute_slices_on_asset_event(
 pub(crate) fn compute_slices_on_image_change(
     mut commands: Commands,
     images: Res<Assets<Image>>,
+    atlas_layouts: Res<Assets<TextureAtlasLayout>>,
     changed_nodes: Query<
-        (Entity, &ImageScaleMode, &Node, &UiImage),
-        Or<(Changed<ImageScaleMode>, Changed<UiImage>, Changed<Node>)>,
+        (
+            Entity,
+            &ImageScaleMode,
+            &Node,
+            &UiImage,
+            Option<&TextureAtlas>,
+        ),
+        Or<(
+            Changed<ImageScaleMode>,
+            Changed<UiImage>,
+            Changed<Node>,
+            Changed<TextureAtlas>,
+        )>,
     >,
 ) {
-    for (entity, scale_mode, ui_node, image) in &changed_nodes {
-        if let Some(slices) = compute_texture_slices(ui_node.size(), scale_mode, image, &images) {
+    for (entity, scale_mode, ui_node, image, atlas) in &changed_nodes {
+        if let Some(slices) = compute_texture_slices(
+            ui_node.size(),
+            scale_mode,
+            image,
+            &images,
+            atlas,
+            &atlas_layouts,
+        ) {
             commands.entity(entity).insert(slices);
         }
     }
 }
+
+/// System removing the texture slices of image nodes whose [`ImageScaleMode`] was removed,
+/// so that they are drawn stretched again
+pub(crate) fn remove_slices_on_scale_mode_removal(
+    mut commands: Commands,
+    sliced_nodes: Query<Entity, (With<ComputedTextureSlices>, Without<ImageScaleMode>)>,
+) {
+    for entity in &sliced_nodes {
+        commands.entity(entity).remove::<ComputedTextureSlices>();
+    }
+}
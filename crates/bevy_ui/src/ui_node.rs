@@ -43,6 +43,10 @@ pub struct Node {
     ///
     /// Automatically calculated by [`super::layout::ui_layout_system`].
     pub(crate) unrounded_size: Vec2,
+    /// The size of the children of the node, including its padding and border, in logical pixels.
+    ///
+    /// Automatically calculated by [`super::layout::ui_layout_system`].
+    pub(crate) content_size: Vec2,
 }
 
 impl Node {
@@ -66,6 +70,19 @@ impl Node {
         self.unrounded_size
     }
 
+    /// The size of the children of the node, including its padding and border, in logical pixels.
+    /// It's larger than [`size`](Self::size) when the children overflow the node.
+    ///
+    /// Automatically calculated by [`super::layout::ui_layout_system`].
+    pub const fn content_size(&self) -> Vec2 {
+        self.content_size
+    }
+
+    /// The maximum [`ScrollPosition`] of the node: how far its content overflows it on each axis.
+    pub fn max_scroll_offset(&self) -> Vec2 {
+        (self.content_size - self.calculated_size).max(Vec2::ZERO)
+    }
+
     /// Returns the size of the node in physical pixels based on the given scale factor and `UiScale`.
     #[inline]
     pub fn physical_size(&self, scale_factor: f32, ui_scale: f32) -> Vec2 {
@@ -76,9 +93,14 @@ impl Node {
     }
 
     /// Returns the logical pixel coordinates of the UI node, based on its [`GlobalTransform`].
+    ///
+    /// The size of the node is scaled by the transform, like for nodes scaled by
+    /// [`WorldUi`](crate::WorldUi).
     #[inline]
     pub fn logical_rect(&self, transform: &GlobalTransform) -> Rect {
-        Rect::from_center_size(transform.translation().truncate(), self.size())
+        let matrix = transform.affine().matrix3;
+        let scale = Vec2::new(matrix.x_axis.length(), matrix.y_axis.length());
+        Rect::from_center_size(transform.translation().truncate(), self.size() * scale)
     }
 
     /// Returns the physical pixel coordinates of the UI node, based on its [`GlobalTransform`] and the scale factor.
@@ -117,6 +139,7 @@ impl Node {
         outline_width: 0.,
         outline_offset: 0.,
         unrounded_size: Vec2::ZERO,
+        content_size: Vec2::ZERO,
     };
 }
 
@@ -872,6 +895,30 @@ impl Overflow {
         }
     }
 
+    /// Clip overflowing items on both axes, and allow scrolling them with a [`ScrollPosition`]
+    pub const fn scroll() -> Self {
+        Self {
+            x: OverflowAxis::Scroll,
+            y: OverflowAxis::Scroll,
+        }
+    }
+
+    /// Clip overflowing items on the x axis, and allow scrolling them with a [`ScrollPosition`]
+    pub const fn scroll_x() -> Self {
+        Self {
+            x: OverflowAxis::Scroll,
+            y: OverflowAxis::Visible,
+        }
+    }
+
+    /// Clip overflowing items on the y axis, and allow scrolling them with a [`ScrollPosition`]
+    pub const fn scroll_y() -> Self {
+        Self {
+            x: OverflowAxis::Visible,
+            y: OverflowAxis::Scroll,
+        }
+    }
+
     /// Overflow is visible on both axes
     pub const fn is_visible(&self) -> bool {
         self.x.is_visible() && self.y.is_visible()
@@ -897,6 +944,8 @@ pub enum OverflowAxis {
     Visible,
     /// Hide overflowing items.
     Clip,
+    /// Hide overflowing items, and allow scrolling them with a [`ScrollPosition`].
+    Scroll,
 }
 
 impl OverflowAxis {
@@ -906,6 +955,11 @@ impl OverflowAxis {
     pub const fn is_visible(&self) -> bool {
         matches!(self, Self::Visible)
     }
+
+    /// Overflow can be scrolled on this axis
+    pub const fn is_scroll(&self) -> bool {
+        matches!(self, Self::Scroll)
+    }
 }
 
 impl Default for OverflowAxis {
@@ -1725,6 +1779,287 @@ impl Outline {
     }
 }
 
+/// How far the content of a node with [`OverflowAxis::Scroll`] is scrolled, in logical pixels.
+///
+/// The children of the node are moved up and left by the offset. It's clamped by the layout
+/// between zero and [`Node::max_scroll_offset`], and ignored on axes that don't scroll.
+///
+/// A [`ScrollView`](crate::ScrollView) scrolls the node with the mouse wheel and touch drags.
+#[derive(Component, Copy, Clone, Debug, Default, PartialEq, Reflect)]
+#[reflect(Component, Default, PartialEq)]
+#[cfg_attr(
+    feature = "serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    reflect(Serialize, Deserialize)
+)]
+pub struct ScrollPosition {
+    /// How far the content is scrolled to the right.
+    pub offset_x: f32,
+    /// How far the content is scrolled down.
+    pub offset_y: f32,
+}
+
+impl ScrollPosition {
+    /// Creates a new [`ScrollPosition`] from its offsets.
+    pub const fn new(offset_x: f32, offset_y: f32) -> Self {
+        Self { offset_x, offset_y }
+    }
+
+    /// The offsets as a vector.
+    pub const fn offset(&self) -> Vec2 {
+        Vec2::new(self.offset_x, self.offset_y)
+    }
+}
+
+impl From<Vec2> for ScrollPosition {
+    fn from(offset: Vec2) -> Self {
+        Self::new(offset.x, offset.y)
+    }
+}
+
+/// The colors of each side of the border of a UI node, overriding its [`BorderColor`].
+///
+/// The corners are split between their two sides along the line from the outer corner to the
+/// inner corner of the border.
+#[derive(Component, Copy, Clone, Debug, PartialEq, Reflect)]
+#[reflect(Component, Default, PartialEq)]
+#[cfg_attr(
+    feature = "serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    reflect(Serialize, Deserialize)
+)]
+pub struct BorderSideColors {
+    /// The color of the left side of the border.
+    pub left: Color,
+    /// The color of the right side of the border.
+    pub right: Color,
+    /// The color of the top side of the border.
+    pub top: Color,
+    /// The color of the bottom side of the border.
+    pub bottom: Color,
+}
+
+impl BorderSideColors {
+    pub const DEFAULT: Self = Self::all(Color::WHITE);
+
+    /// Creates new [`BorderSideColors`] from the colors of each side.
+    pub const fn new(left: Color, right: Color, top: Color, bottom: Color) -> Self {
+        Self {
+            left,
+            right,
+            top,
+            bottom,
+        }
+    }
+
+    /// Creates new [`BorderSideColors`] where all sides have the same color.
+    pub const fn all(color: Color) -> Self {
+        Self::new(color, color, color, color)
+    }
+}
+
+impl Default for BorderSideColors {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// The radii of the corners of a UI node.
+///
+/// The background, image, border and [`Outline`] of the node are rounded, and anti-aliased at
+/// their edges. The content of the node isn't clipped to the rounded corners.
+///
+/// ```
+/// # use bevy_ecs::prelude::*;
+/// # use bevy_ui::prelude::*;
+/// # use bevy_render::prelude::Color;
+/// fn setup_ui(mut commands: Commands) {
+///     commands.spawn((
+///         NodeBundle {
+///             style: Style {
+///                 width: Val::Px(100.),
+///                 height: Val::Px(50.),
+///                 border: UiRect::all(Val::Px(2.)),
+///                 ..Default::default()
+///             },
+///             background_color: Color::BLUE.into(),
+///             border_color: Color::WHITE.into(),
+///             ..Default::default()
+///         },
+///         BorderRadius::all(Val::Px(10.)),
+///     ));
+/// }
+/// ```
+#[derive(Component, Copy, Clone, Debug, PartialEq, Reflect)]
+#[reflect(Component, Default, PartialEq)]
+#[cfg_attr(
+    feature = "serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    reflect(Serialize, Deserialize)
+)]
+pub struct BorderRadius {
+    /// The radius of the top left corner.
+    pub top_left: Val,
+    /// The radius of the top right corner.
+    pub top_right: Val,
+    /// The radius of the bottom right corner.
+    pub bottom_right: Val,
+    /// The radius of the bottom left corner.
+    pub bottom_left: Val,
+}
+
+impl BorderRadius {
+    pub const DEFAULT: Self = Self::ZERO;
+
+    /// Square corners.
+    pub const ZERO: Self = Self::all(Val::ZERO);
+
+    /// Fully rounded corners: the shorter sides of the node are half circles.
+    pub const MAX: Self = Self::all(Val::Px(f32::MAX));
+
+    /// Creates a new [`BorderRadius`] from the radii of each corner.
+    pub const fn new(top_left: Val, top_right: Val, bottom_right: Val, bottom_left: Val) -> Self {
+        Self {
+            top_left,
+            top_right,
+            bottom_right,
+            bottom_left,
+        }
+    }
+
+    /// Creates a new [`BorderRadius`] where all corners have the same radius.
+    pub const fn all(radius: Val) -> Self {
+        Self::new(radius, radius, radius, radius)
+    }
+
+    /// Creates a new [`BorderRadius`] from the radii of each corner in logical pixels.
+    pub const fn px(top_left: f32, top_right: f32, bottom_right: f32, bottom_left: f32) -> Self {
+        Self::new(
+            Val::Px(top_left),
+            Val::Px(top_right),
+            Val::Px(bottom_right),
+            Val::Px(bottom_left),
+        )
+    }
+
+    /// Creates a new [`BorderRadius`] from the radii of each corner as percentages of the
+    /// shorter side of the node.
+    pub const fn percent(
+        top_left: f32,
+        top_right: f32,
+        bottom_right: f32,
+        bottom_left: f32,
+    ) -> Self {
+        Self::new(
+            Val::Percent(top_left),
+            Val::Percent(top_right),
+            Val::Percent(bottom_right),
+            Val::Percent(bottom_left),
+        )
+    }
+
+    /// Creates a new [`BorderRadius`] where the top corners have the given radius, and the
+    /// bottom corners are square.
+    pub const fn top(radius: Val) -> Self {
+        Self::new(radius, radius, Val::ZERO, Val::ZERO)
+    }
+
+    /// Creates a new [`BorderRadius`] where the bottom corners have the given radius, and the
+    /// top corners are square.
+    pub const fn bottom(radius: Val) -> Self {
+        Self::new(Val::ZERO, Val::ZERO, radius, radius)
+    }
+
+    /// Creates a new [`BorderRadius`] where the left corners have the given radius, and the
+    /// right corners are square.
+    pub const fn left(radius: Val) -> Self {
+        Self::new(radius, Val::ZERO, Val::ZERO, radius)
+    }
+
+    /// Creates a new [`BorderRadius`] where the right corners have the given radius, and the
+    /// left corners are square.
+    pub const fn right(radius: Val) -> Self {
+        Self::new(Val::ZERO, radius, radius, Val::ZERO)
+    }
+
+    /// Resolves the radii in logical pixels, clockwise from the top left corner, for a node of
+    /// size `node_size`.
+    ///
+    /// Percentages are resolved based on the shorter side of the node, and the radii are clamped
+    /// to half of it.
+    pub fn resolve(&self, node_size: Vec2, viewport_size: Vec2) -> [f32; 4] {
+        let min_length = node_size.min_element();
+        [
+            self.top_left,
+            self.top_right,
+            self.bottom_right,
+            self.bottom_left,
+        ]
+        .map(|radius| {
+            radius
+                .resolve(min_length, viewport_size)
+                .unwrap_or(0.)
+                .clamp(0., 0.5 * min_length)
+        })
+    }
+}
+
+impl Default for BorderRadius {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Draws a shadow behind a UI node, following its [`BorderRadius`].
+///
+/// Percentage `Val` values are resolved based on the width of the node. Like the box shadows
+/// of CSS, the shadow is blurred by a gaussian blur with a standard deviation of half the blur
+/// radius.
+#[derive(Component, Copy, Clone, Debug, PartialEq, Reflect)]
+#[reflect(Component, Default, PartialEq)]
+#[cfg_attr(
+    feature = "serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    reflect(Serialize, Deserialize)
+)]
+pub struct BoxShadow {
+    /// The color of the shadow.
+    pub color: Color,
+    /// The horizontal offset of the shadow from the node, to the right.
+    pub x_offset: Val,
+    /// The vertical offset of the shadow from the node, downwards.
+    pub y_offset: Val,
+    /// How far the shadow extends beyond the edges of the node, or inside them if negative.
+    pub spread_radius: Val,
+    /// How blurry the edges of the shadow are.
+    pub blur_radius: Val,
+}
+
+impl BoxShadow {
+    /// Creates a new box shadow.
+    pub const fn new(
+        color: Color,
+        x_offset: Val,
+        y_offset: Val,
+        spread_radius: Val,
+        blur_radius: Val,
+    ) -> Self {
+        Self {
+            color,
+            x_offset,
+            y_offset,
+            spread_radius,
+            blur_radius,
+        }
+    }
+}
+
+impl Default for BoxShadow {
+    fn default() -> Self {
+        Self::new(Color::BLACK, Val::ZERO, Val::ZERO, Val::ZERO, Val::ZERO)
+    }
+}
+
 /// The 2D texture displayed for this UI node
 #[derive(Component, Clone, Debug, Reflect, Default)]
 #[reflect(Component, Default)]
@@ -1805,7 +2140,8 @@ impl Default for ZIndex {
 
 #[cfg(test)]
 mod tests {
-    use crate::GridPlacement;
+    use crate::{BorderRadius, GridPlacement, Val};
+    use bevy_math::Vec2;
 
     #[test]
     fn invalid_grid_placement_values() {
@@ -1832,6 +2168,21 @@ mod tests {
         assert_eq!(GridPlacement::start_span(3, 5).get_end(), None);
         assert_eq!(GridPlacement::end_span(-4, 12).get_start(), None);
     }
+
+    #[test]
+    fn border_radius_is_clamped_to_the_node() {
+        let node_size = Vec2::new(100., 40.);
+        let viewport_size = Vec2::new(800., 600.);
+        assert_eq!(
+            BorderRadius::new(Val::Px(10.), Val::Percent(25.), Val::Auto, Val::Px(-5.))
+                .resolve(node_size, viewport_size),
+            [10., 10., 0., 0.]
+        );
+        assert_eq!(
+            BorderRadius::MAX.resolve(node_size, viewport_size),
+            [20.; 4]
+        );
+    }
 }
 
 /// Indicates that this root [`Node`] entity should be rendered to a specific camera.
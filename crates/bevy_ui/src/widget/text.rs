@@ -1,5 +1,5 @@
 use crate::{ContentSize, FixedMeasure, Measure, Node, UiScale};
-use bevy_asset::Assets;
+use bevy_asset::{Assets, Handle};
 use bevy_ecs::{
     prelude::{Component, DetectChanges},
     query::With,
@@ -80,12 +80,13 @@ impl Measure for TextMeasure {
 #[inline]
 fn create_text_measure(
     fonts: &Assets<Font>,
+    font_fallbacks: &[Handle<Font>],
     scale_factor: f32,
     text: Ref<Text>,
     mut content_size: Mut<ContentSize>,
     mut text_flags: Mut<TextFlags>,
 ) {
-    match TextMeasureInfo::from_text(&text, fonts, scale_factor) {
+    match TextMeasureInfo::from_text(&text, fonts, font_fallbacks, scale_factor) {
         Ok(measure) => {
             if text.linebreak_behavior == BreakLineOn::NoWrap {
                 content_size.set(FixedMeasure { size: measure.max });
@@ -111,7 +112,8 @@ fn create_text_measure(
 /// A `Measure` is used by the UI's layout algorithm to determine the appropriate amount of space
 /// to provide for the text given the fonts, the text itself and the constraints of the layout.
 ///
-/// * All measures are regenerated if the primary window's scale factor or [`UiScale`] is changed.
+/// * All measures are regenerated if the primary window's scale factor, [`UiScale`] or
+/// [`TextSettings`] is changed.
 /// * Changes that only modify the colors of a `Text` do not require a new `Measure`. This system
 /// is only able to detect that a `Text` component has changed and will regenerate the `Measure` on
 /// color changes. This can be expensive, particularly for large blocks of text, and the [`bypass_change_detection`](bevy_ecs::change_detection::DetectChangesMut::bypass_change_detection)
@@ -121,6 +123,7 @@ pub fn measure_text_system(
     fonts: Res<Assets<Font>>,
     windows: Query<&Window, With<PrimaryWindow>>,
     ui_scale: Res<UiScale>,
+    text_settings: Res<TextSettings>,
     mut text_query: Query<(Ref<Text>, &mut ContentSize, &mut TextFlags), With<Node>>,
 ) {
     let window_scale_factor = windows
@@ -131,19 +134,33 @@ pub fn measure_text_system(
     let scale_factor = ui_scale.0 * window_scale_factor;
 
     #[allow(clippy::float_cmp)]
-    if *last_scale_factor == scale_factor {
+    if *last_scale_factor == scale_factor && !text_settings.is_changed() {
         // scale factor unchanged, only create new measure funcs for modified text
         for (text, content_size, text_flags) in &mut text_query {
             if text.is_changed() || text_flags.needs_new_measure_func || content_size.is_added() {
-                create_text_measure(&fonts, scale_factor, text, content_size, text_flags);
+                create_text_measure(
+                    &fonts,
+                    &text_settings.font_fallbacks,
+                    scale_factor,
+                    text,
+                    content_size,
+                    text_flags,
+                );
             }
         }
     } else {
-        // scale factor changed, create new measure funcs for all text
+        // scale factor or fallback fonts changed, create new measure funcs for all text
         *last_scale_factor = scale_factor;
 
         for (text, content_size, text_flags) in &mut text_query {
-            create_text_measure(&fonts, scale_factor, text, content_size, text_flags);
+            create_text_measure(
+                &fonts,
+                &text_settings.font_fallbacks,
+                scale_factor,
+                text,
+                content_size,
+                text_flags,
+            );
         }
     }
 }
@@ -236,7 +253,7 @@ pub fn text_system(
 
     let scale_factor = ui_scale.0 * window_scale_factor;
     let inverse_scale_factor = scale_factor.recip();
-    if *last_scale_factor == scale_factor {
+    if *last_scale_factor == scale_factor && !text_settings.is_changed() {
         // Scale factor unchanged, only recompute text for modified text nodes
         for (node, text, text_layout_info, text_flags) in &mut text_query {
             if node.is_changed() || text_flags.needs_recompute {
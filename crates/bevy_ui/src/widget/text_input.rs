@@ -0,0 +1,366 @@
+use crate::{Interaction, Node, UiScale};
+use bevy_ecs::{
+    change_detection::{DetectChanges, DetectChangesMut},
+    prelude::{Component, Entity, Event, EventReader, EventWriter},
+    query::With,
+    reflect::ReflectComponent,
+    system::{Query, Res, ResMut, Resource},
+};
+use bevy_input::{
+    keyboard::{KeyCode, KeyboardInput},
+    pointer::{PointerButton, Pointers},
+    ButtonState,
+};
+use bevy_math::Vec2;
+use bevy_reflect::{std_traits::ReflectDefault, Reflect};
+use bevy_text::{Text, TextLayoutInfo, TextSection, TextStyle};
+use bevy_transform::components::GlobalTransform;
+use bevy_window::{Ime, PrimaryWindow, ReceivedCharacter, Window};
+
+/// A single line of editable text, typed with the keyboard or composed with an input method
+/// editor (IME) for languages like Chinese or Japanese.
+///
+/// Add it to an entity with a [`TextBundle`](crate::node_bundles::TextBundle) and an
+/// [`Interaction`]: pressing the node focuses it, and its [`Text`] is replaced by the value of
+/// the input, drawn with the style of its first section. While an input is focused:
+///
+/// - IME is enabled on the primary window, and its candidate box is placed under the caret.
+/// - The text being composed with IME is shown at the caret, and inserted when committed.
+/// - Backspace, Delete, the arrows, Home and End edit the value, and Enter sends a
+///   [`TextInputSubmit`] event.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component, Default)]
+pub struct TextInput {
+    /// The value of the input.
+    value: String,
+    /// The byte index of the caret in the value.
+    cursor: usize,
+    /// The text being composed with IME.
+    preedit: String,
+    /// The char drawn at the caret while the input is focused.
+    pub caret: char,
+}
+
+impl Default for TextInput {
+    fn default() -> Self {
+        Self {
+            value: String::new(),
+            cursor: 0,
+            preedit: String::new(),
+            caret: '|',
+        }
+    }
+}
+
+impl TextInput {
+    /// Creates an input with the value `value`, and the caret at its end.
+    pub fn new(value: impl Into<String>) -> Self {
+        let value = value.into();
+        Self {
+            cursor: value.len(),
+            value,
+            ..Default::default()
+        }
+    }
+
+    /// The value of the input.
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// Replaces the value of the input, and moves the caret to its end.
+    pub fn set_value(&mut self, value: impl Into<String>) {
+        self.value = value.into();
+        self.cursor = self.value.len();
+    }
+
+    /// The byte index of the caret in the value.
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// Moves the caret to the char at the byte index `cursor`, or to the end of the value.
+    pub fn set_cursor(&mut self, cursor: usize) {
+        let mut cursor = cursor.min(self.value.len());
+        while !self.value.is_char_boundary(cursor) {
+            cursor -= 1;
+        }
+        self.cursor = cursor;
+    }
+
+    /// The text being composed with IME, not yet part of the value.
+    pub fn preedit(&self) -> &str {
+        &self.preedit
+    }
+
+    /// Inserts `text` at the caret, and moves the caret after it.
+    pub fn insert(&mut self, text: &str) {
+        self.value.insert_str(self.cursor, text);
+        self.cursor += text.len();
+    }
+
+    /// Deletes the char before the caret.
+    pub fn delete_backward(&mut self) {
+        if let Some(char) = self.value[..self.cursor].chars().next_back() {
+            self.cursor -= char.len_utf8();
+            self.value.remove(self.cursor);
+        }
+    }
+
+    /// Deletes the char after the caret.
+    pub fn delete_forward(&mut self) {
+        if self.cursor < self.value.len() {
+            self.value.remove(self.cursor);
+        }
+    }
+
+    /// Moves the caret before the previous char.
+    pub fn move_left(&mut self) {
+        if let Some(char) = self.value[..self.cursor].chars().next_back() {
+            self.cursor -= char.len_utf8();
+        }
+    }
+
+    /// Moves the caret after the next char.
+    pub fn move_right(&mut self) {
+        if let Some(char) = self.value[self.cursor..].chars().next() {
+            self.cursor += char.len_utf8();
+        }
+    }
+
+    /// The sections of the [`Text`] of the input: the value before the caret, the text being
+    /// composed, the caret and the value after the caret.
+    fn sections(&self, style: &TextStyle, focused: bool) -> Vec<TextSection> {
+        let caret = if focused {
+            self.caret.to_string()
+        } else {
+            String::new()
+        };
+        [
+            self.value[..self.cursor].to_string(),
+            self.preedit.clone(),
+            caret,
+            self.value[self.cursor..].to_string(),
+        ]
+        .into_iter()
+        .map(|value| TextSection::new(value, style.clone()))
+        .collect()
+    }
+}
+
+/// The index of the section of the caret in the [`Text`] of a [`TextInput`].
+const CARET_SECTION: usize = 2;
+
+/// The [`TextInput`] receiving the keyboard and IME inputs, if any.
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TextInputFocus(pub Option<Entity>);
+
+impl TextInputFocus {
+    /// The focused input, if any.
+    pub fn get(&self) -> Option<Entity> {
+        self.0
+    }
+
+    /// Focuses the input `entity`.
+    pub fn set(&mut self, entity: Entity) {
+        self.0 = Some(entity);
+    }
+
+    /// Unfocuses the focused input.
+    pub fn clear(&mut self) {
+        self.0 = None;
+    }
+}
+
+/// Sent when Enter is pressed in a focused [`TextInput`].
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub struct TextInputSubmit {
+    /// The entity of the input.
+    pub entity: Entity,
+    /// The value of the input.
+    pub value: String,
+}
+
+/// Focuses the pressed [`TextInput`]s, edits the focused one from the keyboard and IME events,
+/// and updates their [`Text`].
+#[allow(clippy::too_many_arguments)]
+pub fn text_input_system(
+    mut focus: ResMut<TextInputFocus>,
+    pointers: Res<Pointers>,
+    mut keyboard_events: EventReader<KeyboardInput>,
+    mut character_events: EventReader<ReceivedCharacter>,
+    mut ime_events: EventReader<Ime>,
+    mut submit_events: EventWriter<TextInputSubmit>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+    mut inputs: Query<(Entity, &mut TextInput, &mut Text, Option<&Interaction>)>,
+) {
+    let previous_focus = focus.get();
+    if pointers.any_just_pressed(PointerButton::Primary) {
+        focus.0 = inputs
+            .iter()
+            .find(|(.., interaction)| *interaction == Some(&Interaction::Pressed))
+            .map(|(entity, ..)| entity);
+    }
+    if focus.get().is_some_and(|entity| !inputs.contains(entity)) {
+        focus.clear();
+    }
+    if focus.get() != previous_focus {
+        if let Some(mut input) = previous_focus.and_then(|entity| inputs.get_mut(entity).ok()) {
+            input.1.preedit.clear();
+        }
+        if let Some(mut input) = focus.get().and_then(|entity| inputs.get_mut(entity).ok()) {
+            input.1.set_changed();
+        }
+        if let Ok(mut window) = windows.get_single_mut() {
+            window.ime_enabled = focus.get().is_some();
+        }
+    }
+
+    let Some((entity, mut input, ..)) = focus.get().and_then(|entity| inputs.get_mut(entity).ok())
+    else {
+        keyboard_events.clear();
+        character_events.clear();
+        ime_events.clear();
+        return;
+    };
+
+    for event in ime_events.read() {
+        match event {
+            Ime::Preedit { value, .. } => input.preedit.clone_from(value),
+            Ime::Commit { value, .. } => {
+                input.preedit.clear();
+                input.insert(value);
+            }
+            Ime::Disabled { .. } => input.preedit.clear(),
+            Ime::Enabled { .. } => {}
+        }
+    }
+    for event in character_events.read() {
+        if !event.char.chars().any(char::is_control) {
+            input.insert(&event.char);
+        }
+    }
+    for event in keyboard_events.read() {
+        // The keys are used by the input method while composing.
+        if event.state != ButtonState::Pressed || !input.preedit.is_empty() {
+            continue;
+        }
+        match event.key_code {
+            KeyCode::Backspace => input.delete_backward(),
+            KeyCode::Delete => input.delete_forward(),
+            KeyCode::ArrowLeft => input.move_left(),
+            KeyCode::ArrowRight => input.move_right(),
+            KeyCode::Home => input.set_cursor(0),
+            KeyCode::End => input.set_cursor(usize::MAX),
+            KeyCode::Enter | KeyCode::NumpadEnter => {
+                submit_events.send(TextInputSubmit {
+                    entity,
+                    value: input.value.clone(),
+                });
+            }
+            _ => {}
+        }
+    }
+
+    for (entity, input, mut text, _) in &mut inputs {
+        if input.is_changed() {
+            let style = text
+                .sections
+                .first()
+                .map(|section| section.style.clone())
+                .unwrap_or_default();
+            text.sections = input.sections(&style, focus.get() == Some(entity));
+        }
+    }
+}
+
+/// Places the IME candidate box of the primary window under the caret of the focused
+/// [`TextInput`], once its text has been laid out.
+pub fn text_input_ime_position_system(
+    focus: Res<TextInputFocus>,
+    ui_scale: Res<UiScale>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+    inputs: Query<(&Node, &GlobalTransform, &TextLayoutInfo), With<TextInput>>,
+) {
+    let Some((node, transform, layout)) = focus.get().and_then(|entity| inputs.get(entity).ok())
+    else {
+        return;
+    };
+    let Ok(mut window) = windows.get_single_mut() else {
+        return;
+    };
+    let scale_factor = window.scale_factor();
+
+    // The left edge of the caret, in physical pixels from the left of the text.
+    let caret_x = layout
+        .glyphs
+        .iter()
+        .find(|glyph| glyph.section_index >= CARET_SECTION)
+        .map(|glyph| glyph.position.x - glyph.size.x / 2.)
+        .or_else(|| {
+            layout
+                .glyphs
+                .last()
+                .map(|glyph| glyph.position.x + glyph.size.x / 2.)
+        })
+        .unwrap_or(0.);
+
+    let rect = node.logical_rect(transform);
+    let position = Vec2::new(
+        rect.min.x * ui_scale.0 + caret_x / scale_factor,
+        rect.max.y * ui_scale.0,
+    );
+    if window.ime_position != position {
+        window.ime_position = position;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn editing_moves_the_caret_by_chars() {
+        let mut input = TextInput::new("héllo");
+        assert_eq!(input.cursor(), 6);
+
+        input.move_left();
+        input.move_left();
+        input.move_left();
+        input.move_left();
+        assert_eq!(input.cursor(), 1);
+        input.move_right();
+        assert_eq!(input.cursor(), 3);
+        input.delete_backward();
+        assert_eq!(input.value(), "hllo");
+        input.insert("日本");
+        assert_eq!(input.value(), "h日本llo");
+        input.delete_forward();
+        assert_eq!(input.value(), "h日本lo");
+
+        input.set_cursor(2);
+        assert_eq!(input.cursor(), 1);
+        input.set_cursor(usize::MAX);
+        input.delete_forward();
+        assert_eq!(input.value(), "h日本lo");
+    }
+
+    #[test]
+    fn sections_show_the_preedit_and_the_caret() {
+        let mut input = TextInput::new("ab");
+        input.move_left();
+        input.preedit = "か".to_string();
+
+        let values = |sections: Vec<TextSection>| -> Vec<String> {
+            sections.into_iter().map(|section| section.value).collect()
+        };
+        assert_eq!(
+            values(input.sections(&TextStyle::default(), true)),
+            ["a", "か", "|", "b"]
+        );
+        assert_eq!(
+            values(input.sections(&TextStyle::default(), false)),
+            ["a", "か", "", "b"]
+        );
+    }
+}
@@ -0,0 +1,270 @@
+//! This module contains the systems that anchor UI trees to entities in the world
+
+use crate::{DefaultUiCamera, Node, TargetCamera, UiScale};
+use bevy_ecs::{prelude::*, reflect::ReflectComponent};
+use bevy_hierarchy::{Children, Parent};
+use bevy_math::{Vec2, Vec3};
+use bevy_reflect::{std_traits::ReflectDefault, Reflect};
+use bevy_render::{
+    camera::Camera,
+    view::{InheritedVisibility, ViewVisibility},
+};
+use bevy_transform::components::{GlobalTransform, Transform};
+
+/// Anchors a root [`Node`] to an entity in the world, like a nameplate or a health bar.
+///
+/// Every frame, the node is moved over the position of the [`anchor`](Self::anchor) as seen by
+/// the camera of the node, which is usually a 3D camera rendering the UI. The node always faces
+/// the camera, and is drawn in front of the world.
+///
+/// Anchored nodes are drawn behind the other root nodes with the same [`ZIndex`](crate::ZIndex),
+/// and the nearest ones in front of the farthest ones. They are hidden while their anchor is
+/// hidden or behind the camera. Interactions use their position and size on the screen, so
+/// buttons in anchored UI work like in the rest of the UI.
+///
+/// Setting this component on a non-root node has no effect.
+#[derive(Component, Copy, Clone, Debug, PartialEq, Reflect)]
+#[reflect(Component, PartialEq)]
+pub struct WorldUi {
+    /// The entity the node follows.
+    pub anchor: Entity,
+    /// The offset of the node from the anchor, in world units, like the height of a character
+    /// for a nameplate above its head.
+    pub offset: Vec3,
+    /// The point of the node placed on the anchor, from `(0., 0.)` for the top left corner to
+    /// `(1., 1.)` for the bottom right corner.
+    pub pivot: Vec2,
+    /// How the node is scaled with the distance to the camera.
+    pub scaling: WorldUiScaling,
+    /// The distance from the camera to the anchor, for sorting the anchored nodes.
+    #[reflect(ignore)]
+    pub(crate) depth: f32,
+}
+
+impl WorldUi {
+    /// Anchors a node by its center to `anchor`, with a constant size on the screen.
+    pub const fn new(anchor: Entity) -> Self {
+        Self {
+            anchor,
+            offset: Vec3::ZERO,
+            pivot: Vec2::splat(0.5),
+            scaling: WorldUiScaling::Screen,
+            depth: 0.,
+        }
+    }
+
+    /// Sets the [`offset`](Self::offset) of the node from the anchor.
+    pub const fn with_offset(mut self, offset: Vec3) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Sets the [`pivot`](Self::pivot) of the node.
+    pub const fn with_pivot(mut self, pivot: Vec2) -> Self {
+        self.pivot = pivot;
+        self
+    }
+
+    /// Sets the [`scaling`](Self::scaling) of the node.
+    pub const fn with_scaling(mut self, scaling: WorldUiScaling) -> Self {
+        self.scaling = scaling;
+        self
+    }
+}
+
+/// How a node with [`WorldUi`] is scaled with the distance to the camera.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Reflect)]
+#[reflect(Default, PartialEq)]
+pub enum WorldUiScaling {
+    /// The node keeps its size on the screen, like a marker.
+    #[default]
+    Screen,
+    /// The node has a size in the world, shrinking with the distance like the objects around
+    /// it: a logical pixel of the node is `1. / pixels_per_unit` world units wide.
+    World { pixels_per_unit: f32 },
+}
+
+/// Moves the root nodes with [`WorldUi`] and their descendants over their anchors.
+///
+/// Runs after the transforms are propagated, and overrides the [`GlobalTransform`]s of the
+/// anchored trees computed from their layout.
+pub fn world_ui_system(
+    camera_query: Query<(&Camera, &GlobalTransform), Without<Node>>,
+    default_ui_camera: DefaultUiCamera,
+    ui_scale: Res<UiScale>,
+    anchor_query: Query<(&GlobalTransform, Option<&InheritedVisibility>), Without<Node>>,
+    mut root_query: Query<(Entity, &mut WorldUi, &Node, Option<&TargetCamera>), Without<Parent>>,
+    mut node_query: Query<(&Transform, &mut GlobalTransform, &mut ViewVisibility), With<Node>>,
+    children_query: Query<&Children, With<Node>>,
+) {
+    for (entity, mut world_ui, node, target_camera) in &mut root_query {
+        let placement = target_camera
+            .map(TargetCamera::entity)
+            .or(default_ui_camera.get())
+            .and_then(|camera_entity| camera_query.get(camera_entity).ok())
+            .zip(anchor_query.get(world_ui.anchor).ok())
+            .filter(|(_, (_, visibility))| !visibility.is_some_and(|visibility| !visibility.get()))
+            .and_then(|((camera, camera_transform), (anchor_transform, _))| {
+                let position = anchor_transform.translation() + world_ui.offset;
+                let screen_position = camera.world_to_viewport(camera_transform, position)?;
+                let scale = match world_ui.scaling {
+                    WorldUiScaling::Screen => 1.,
+                    WorldUiScaling::World { pixels_per_unit } => {
+                        // The length on the screen of a world unit at the anchor, facing the camera.
+                        let unit = camera.world_to_viewport(
+                            camera_transform,
+                            position + camera_transform.right(),
+                        )?;
+                        unit.distance(screen_position) / (pixels_per_unit * ui_scale.0)
+                    }
+                };
+                let depth = camera_transform.translation().distance(position);
+                Some((screen_position / ui_scale.0, scale, depth))
+            });
+
+        let Some((screen_position, scale, depth)) = placement else {
+            // Hide the tree while its anchor can't be seen.
+            hide_recursive(entity, &mut node_query, &children_query);
+            continue;
+        };
+        if world_ui.depth != depth {
+            world_ui.depth = depth;
+        }
+
+        let size = node.size() * scale;
+        let center = screen_position + (Vec2::splat(0.5) - world_ui.pivot) * size;
+        let root_transform = GlobalTransform::from(
+            Transform::from_translation(center.extend(0.)).with_scale(Vec3::new(scale, scale, 1.)),
+        );
+        let Ok((_, mut global_transform, _)) = node_query.get_mut(entity) else {
+            continue;
+        };
+        *global_transform = root_transform;
+        if let Ok(children) = children_query.get(entity) {
+            for child in children {
+                propagate_recursive(*child, root_transform, &mut node_query, &children_query);
+            }
+        }
+    }
+}
+
+fn propagate_recursive(
+    entity: Entity,
+    parent: GlobalTransform,
+    node_query: &mut Query<(&Transform, &mut GlobalTransform, &mut ViewVisibility), With<Node>>,
+    children_query: &Query<&Children, With<Node>>,
+) {
+    let Ok((transform, mut global_transform, _)) = node_query.get_mut(entity) else {
+        return;
+    };
+    let global = parent.mul_transform(*transform);
+    *global_transform = global;
+    if let Ok(children) = children_query.get(entity) {
+        for child in children {
+            propagate_recursive(*child, global, node_query, children_query);
+        }
+    }
+}
+
+fn hide_recursive(
+    entity: Entity,
+    node_query: &mut Query<(&Transform, &mut GlobalTransform, &mut ViewVisibility), With<Node>>,
+    children_query: &Query<&Children, With<Node>>,
+) {
+    if let Ok((_, _, mut view_visibility)) = node_query.get_mut(entity) {
+        *view_visibility = ViewVisibility::HIDDEN;
+    }
+    if let Ok(children) = children_query.get(entity) {
+        for child in children {
+            hide_recursive(*child, node_query, children_query);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node_bundles::NodeBundle;
+    use bevy_asset::{AssetEvent, Assets};
+    use bevy_core_pipeline::core_3d::Camera3dBundle;
+    use bevy_ecs::{event::Events, schedule::Schedule};
+    use bevy_hierarchy::BuildWorldChildren;
+    use bevy_render::{
+        camera::{camera_system, ManualTextureViews, Projection},
+        texture::Image,
+    };
+    use bevy_window::{
+        PrimaryWindow, Window, WindowCreated, WindowResized, WindowResolution,
+        WindowScaleFactorChanged,
+    };
+
+    #[test]
+    fn world_ui_follows_its_anchor() {
+        let mut world = World::new();
+        world.init_resource::<UiScale>();
+        world.init_resource::<Events<WindowScaleFactorChanged>>();
+        world.init_resource::<Events<WindowResized>>();
+        world.init_resource::<Events<WindowCreated>>();
+        world.init_resource::<Events<AssetEvent<Image>>>();
+        world.init_resource::<Assets<Image>>();
+        world.init_resource::<ManualTextureViews>();
+        world.spawn((
+            Window {
+                resolution: WindowResolution::new(1000., 100.),
+                ..Default::default()
+            },
+            PrimaryWindow,
+        ));
+        // Looking down the -Z axis.
+        world.spawn(Camera3dBundle::default());
+
+        let anchor = world
+            .spawn((
+                GlobalTransform::from_translation(Vec3::new(0., 0., -10.)),
+                InheritedVisibility::VISIBLE,
+            ))
+            .id();
+        let mut root_node = NodeBundle::default();
+        root_node.node.calculated_size = Vec2::new(20., 10.);
+        let root = world
+            .spawn((
+                root_node,
+                WorldUi::new(anchor).with_pivot(Vec2::new(0.5, 1.)),
+            ))
+            .id();
+        let child = world
+            .spawn(NodeBundle {
+                transform: Transform::from_xyz(0., 2., 0.),
+                ..Default::default()
+            })
+            .id();
+        world.entity_mut(root).add_child(child);
+
+        let mut schedule = Schedule::default();
+        schedule.add_systems((camera_system::<Projection>, world_ui_system).chain());
+        schedule.run(&mut world);
+
+        // The bottom center of the node is on the anchor, at the center of the window.
+        let translation = |world: &World, entity| {
+            world
+                .get::<GlobalTransform>(entity)
+                .unwrap()
+                .translation()
+                .truncate()
+        };
+        assert_eq!(translation(&world, root), Vec2::new(500., 45.));
+        assert_eq!(translation(&world, child), Vec2::new(500., 47.));
+        assert_eq!(world.get::<WorldUi>(root).unwrap().depth, 10.);
+
+        // Behind the camera, the whole tree is hidden.
+        for entity in [root, child] {
+            world.get_mut::<ViewVisibility>(entity).unwrap().set();
+        }
+        *world.get_mut::<GlobalTransform>(anchor).unwrap() =
+            GlobalTransform::from_translation(Vec3::new(0., 0., 10.));
+        schedule.run(&mut world);
+        for entity in [root, child] {
+            assert!(!world.get::<ViewVisibility>(entity).unwrap().get());
+        }
+    }
+}
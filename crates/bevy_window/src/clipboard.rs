@@ -0,0 +1,394 @@
+use std::sync::{Arc, Mutex};
+
+use bevy_ecs::system::Resource;
+use thiserror::Error;
+
+/// Access to the clipboard of the system.
+///
+/// Reads return a [`ClipboardRead`], as they complete asynchronously on some platforms, like the
+/// web, where the browser may also ask the user for permission.
+///
+/// ```
+/// # use bevy_ecs::prelude::*;
+/// # use bevy_window::{Clipboard, ClipboardRead};
+/// #[derive(Resource, Default)]
+/// struct Paste(Option<ClipboardRead<String>>);
+///
+/// fn copy(mut clipboard: ResMut<Clipboard>) {
+///     if let Err(err) = clipboard.set_text("Hello, clipboard!") {
+///         eprintln!("Can't copy: {err}");
+///     }
+/// }
+///
+/// fn paste(mut clipboard: ResMut<Clipboard>, mut paste: ResMut<Paste>) {
+///     let read = paste.0.get_or_insert_with(|| clipboard.fetch_text());
+///     if let Some(result) = read.poll() {
+///         if let Ok(text) = result {
+///             println!("Pasted {text}");
+///         }
+///         paste.0 = None;
+///     }
+/// }
+/// ```
+///
+/// On desktop platforms, the clipboard is accessed through the commands of the system:
+/// `pbcopy` and `pbpaste` on macOS, PowerShell on Windows, and `wl-copy` and `wl-paste` or
+/// `xclip` on Linux. When they aren't available, the clipboard only works within the app. Other
+/// platforms can provide their own [`ClipboardProvider`] with [`Clipboard::new`].
+#[derive(Resource)]
+pub struct Clipboard {
+    provider: Box<dyn ClipboardProvider>,
+}
+
+impl Default for Clipboard {
+    fn default() -> Self {
+        Self {
+            provider: default_provider(),
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn default_provider() -> Box<dyn ClipboardProvider> {
+    Box::new(web::WebClipboard)
+}
+
+#[cfg(all(
+    not(target_arch = "wasm32"),
+    any(target_os = "windows", target_os = "macos", target_os = "linux")
+))]
+fn default_provider() -> Box<dyn ClipboardProvider> {
+    Box::<command::CommandClipboard>::default()
+}
+
+#[cfg(not(any(
+    target_arch = "wasm32",
+    target_os = "windows",
+    target_os = "macos",
+    target_os = "linux"
+)))]
+fn default_provider() -> Box<dyn ClipboardProvider> {
+    Box::<MemoryClipboard>::default()
+}
+
+impl Clipboard {
+    /// Creates a clipboard accessed through `provider`.
+    pub fn new(provider: impl ClipboardProvider) -> Self {
+        Self {
+            provider: Box::new(provider),
+        }
+    }
+
+    /// Copies `text` to the clipboard.
+    pub fn set_text(&mut self, text: impl Into<String>) -> Result<(), ClipboardError> {
+        self.provider.set_text(text.into())
+    }
+
+    /// Reads the text of the clipboard.
+    pub fn fetch_text(&mut self) -> ClipboardRead<String> {
+        self.provider.fetch_text()
+    }
+
+    /// Copies `image` to the clipboard, where supported.
+    pub fn set_image(&mut self, image: ClipboardImage) -> Result<(), ClipboardError> {
+        self.provider.set_image(image)
+    }
+
+    /// Reads the image of the clipboard, where supported.
+    pub fn fetch_image(&mut self) -> ClipboardRead<ClipboardImage> {
+        self.provider.fetch_image()
+    }
+}
+
+/// An implementation of the clipboard of a platform.
+pub trait ClipboardProvider: Send + Sync + 'static {
+    /// Copies `text` to the clipboard.
+    fn set_text(&mut self, text: String) -> Result<(), ClipboardError>;
+
+    /// Reads the text of the clipboard.
+    fn fetch_text(&mut self) -> ClipboardRead<String>;
+
+    /// Copies `image` to the clipboard. Unsupported by default.
+    fn set_image(&mut self, image: ClipboardImage) -> Result<(), ClipboardError> {
+        let _ = image;
+        Err(ClipboardError::Unsupported)
+    }
+
+    /// Reads the image of the clipboard. Unsupported by default.
+    fn fetch_image(&mut self) -> ClipboardRead<ClipboardImage> {
+        ClipboardRead::ready(Err(ClipboardError::Unsupported))
+    }
+}
+
+/// An image in the clipboard.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClipboardImage {
+    /// The width of the image, in pixels.
+    pub width: u32,
+    /// The height of the image, in pixels.
+    pub height: u32,
+    /// The pixels of the image, row by row, as 8-bit RGBA.
+    pub rgba: Vec<u8>,
+}
+
+/// An error accessing the clipboard.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ClipboardError {
+    /// The clipboard doesn't support this content on this platform.
+    #[error("the clipboard content is unsupported on this platform")]
+    Unsupported,
+    /// The clipboard doesn't contain this content.
+    #[error("the clipboard is empty")]
+    Empty,
+    /// The system denied access to the clipboard, or failed to access it.
+    #[error("failed to access the clipboard: {0}")]
+    Access(String),
+}
+
+/// The result of a read of the [`Clipboard`], which may complete asynchronously.
+pub struct ClipboardRead<T> {
+    result: Arc<Mutex<Option<Result<T, ClipboardError>>>>,
+}
+
+impl<T> ClipboardRead<T> {
+    /// Creates a completed read.
+    pub fn ready(result: Result<T, ClipboardError>) -> Self {
+        Self {
+            result: Arc::new(Mutex::new(Some(result))),
+        }
+    }
+
+    /// Creates a pending read, completed by the returned [`ClipboardReadSender`].
+    pub fn pending() -> (Self, ClipboardReadSender<T>) {
+        let result = Arc::new(Mutex::new(None));
+        (
+            Self {
+                result: result.clone(),
+            },
+            ClipboardReadSender { result },
+        )
+    }
+
+    /// Returns the result of the read once it has completed, or `None` while it's pending.
+    ///
+    /// The result is only returned once.
+    pub fn poll(&mut self) -> Option<Result<T, ClipboardError>> {
+        self.result.lock().unwrap().take()
+    }
+}
+
+/// Completes a pending [`ClipboardRead`].
+pub struct ClipboardReadSender<T> {
+    result: Arc<Mutex<Option<Result<T, ClipboardError>>>>,
+}
+
+impl<T> ClipboardReadSender<T> {
+    /// Completes the read with `result`.
+    pub fn send(self, result: Result<T, ClipboardError>) {
+        *self.result.lock().unwrap() = Some(result);
+    }
+}
+
+/// A clipboard only shared within the app.
+#[derive(Debug, Default)]
+pub struct MemoryClipboard {
+    text: Option<String>,
+    image: Option<ClipboardImage>,
+}
+
+impl ClipboardProvider for MemoryClipboard {
+    fn set_text(&mut self, text: String) -> Result<(), ClipboardError> {
+        self.text = Some(text);
+        Ok(())
+    }
+
+    fn fetch_text(&mut self) -> ClipboardRead<String> {
+        ClipboardRead::ready(self.text.clone().ok_or(ClipboardError::Empty))
+    }
+
+    fn set_image(&mut self, image: ClipboardImage) -> Result<(), ClipboardError> {
+        self.image = Some(image);
+        Ok(())
+    }
+
+    fn fetch_image(&mut self) -> ClipboardRead<ClipboardImage> {
+        ClipboardRead::ready(self.image.clone().ok_or(ClipboardError::Empty))
+    }
+}
+
+#[cfg(all(
+    not(target_arch = "wasm32"),
+    any(target_os = "windows", target_os = "macos", target_os = "linux")
+))]
+mod command {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    use super::*;
+
+    /// A clipboard accessed through the commands of the system, falling back to a clipboard
+    /// within the app when they aren't available.
+    #[derive(Default)]
+    pub(super) struct CommandClipboard {
+        fallback: MemoryClipboard,
+    }
+
+    /// The commands copying from their input, and pasting to their output.
+    fn commands() -> (Command, Command) {
+        #[cfg(target_os = "macos")]
+        return (Command::new("pbcopy"), Command::new("pbpaste"));
+
+        #[cfg(target_os = "windows")]
+        return {
+            let powershell = |command: &str| {
+                let mut powershell = Command::new("powershell");
+                powershell.args(["-NoProfile", "-NonInteractive", "-Command", command]);
+                powershell
+            };
+            (
+                powershell("$input | Set-Clipboard"),
+                powershell("Get-Clipboard -Raw"),
+            )
+        };
+
+        #[cfg(target_os = "linux")]
+        return if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+            let mut paste = Command::new("wl-paste");
+            paste.arg("--no-newline");
+            (Command::new("wl-copy"), paste)
+        } else {
+            let mut copy = Command::new("xclip");
+            copy.args(["-selection", "clipboard"]);
+            let mut paste = Command::new("xclip");
+            paste.args(["-selection", "clipboard", "-out"]);
+            (copy, paste)
+        };
+    }
+
+    fn copy(text: &str) -> std::io::Result<bool> {
+        let mut child = commands()
+            .0
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(text.as_bytes())?;
+        }
+        Ok(child.wait()?.success())
+    }
+
+    fn paste() -> std::io::Result<Option<String>> {
+        let output = commands().1.stderr(Stdio::null()).output()?;
+        Ok(output
+            .status
+            .success()
+            .then(|| String::from_utf8_lossy(&output.stdout).into_owned()))
+    }
+
+    impl ClipboardProvider for CommandClipboard {
+        fn set_text(&mut self, text: String) -> Result<(), ClipboardError> {
+            match copy(&text) {
+                Ok(true) => Ok(()),
+                Ok(false) => Err(ClipboardError::Access(
+                    "the copy command failed".to_string(),
+                )),
+                // The command isn't available.
+                Err(_) => self.fallback.set_text(text),
+            }
+        }
+
+        fn fetch_text(&mut self) -> ClipboardRead<String> {
+            match paste() {
+                Ok(Some(text)) if !text.is_empty() => ClipboardRead::ready(Ok(text)),
+                Ok(_) => ClipboardRead::ready(Err(ClipboardError::Empty)),
+                Err(_) => self.fallback.fetch_text(),
+            }
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod web {
+    use js_sys::{Function, Promise, Reflect};
+    use wasm_bindgen::{JsCast, JsValue};
+    use wasm_bindgen_futures::JsFuture;
+
+    use super::*;
+
+    /// The [asynchronous clipboard](https://developer.mozilla.org/en-US/docs/Web/API/Clipboard)
+    /// of the browser.
+    pub(super) struct WebClipboard;
+
+    /// Calls the method `name` of the clipboard of the browser.
+    fn call(name: &str, args: &[JsValue]) -> Result<Promise, ClipboardError> {
+        let unsupported = |_| ClipboardError::Unsupported;
+        let navigator = web_sys::window()
+            .ok_or(ClipboardError::Unsupported)?
+            .navigator();
+        let clipboard = Reflect::get(&navigator, &"clipboard".into()).map_err(unsupported)?;
+        let method = Reflect::get(&clipboard, &name.into())
+            .map_err(unsupported)?
+            .dyn_into::<Function>()
+            .map_err(|_| ClipboardError::Unsupported)?;
+        let args: js_sys::Array = args.iter().collect();
+        method
+            .apply(&clipboard, &args)
+            .map_err(|err| ClipboardError::Access(format!("{err:?}")))?
+            .dyn_into::<Promise>()
+            .map_err(|_| ClipboardError::Unsupported)
+    }
+
+    impl ClipboardProvider for WebClipboard {
+        fn set_text(&mut self, text: String) -> Result<(), ClipboardError> {
+            let promise = call("writeText", &[text.into()])?;
+            wasm_bindgen_futures::spawn_local(async move {
+                if let Err(err) = JsFuture::from(promise).await {
+                    bevy_utils::tracing::warn!("Failed to copy to the clipboard: {err:?}");
+                }
+            });
+            Ok(())
+        }
+
+        fn fetch_text(&mut self) -> ClipboardRead<String> {
+            let promise = match call("readText", &[]) {
+                Ok(promise) => promise,
+                Err(err) => return ClipboardRead::ready(Err(err)),
+            };
+            let (read, sender) = ClipboardRead::pending();
+            wasm_bindgen_futures::spawn_local(async move {
+                sender.send(match JsFuture::from(promise).await {
+                    Ok(text) => text.as_string().ok_or(ClipboardError::Empty),
+                    Err(err) => Err(ClipboardError::Access(format!("{err:?}"))),
+                });
+            });
+            read
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn memory_clipboard_round_trips() {
+        let mut clipboard = Clipboard::new(MemoryClipboard::default());
+        assert_eq!(
+            clipboard.fetch_text().poll(),
+            Some(Err(ClipboardError::Empty))
+        );
+        clipboard.set_text("copied").unwrap();
+        let mut read = clipboard.fetch_text();
+        assert_eq!(read.poll(), Some(Ok("copied".to_string())));
+        assert_eq!(read.poll(), None);
+    }
+
+    #[test]
+    fn pending_reads_complete_once_sent() {
+        let (mut read, sender) = ClipboardRead::<String>::pending();
+        assert_eq!(read.poll(), None);
+        sender.send(Ok("later".to_string()));
+        assert_eq!(read.poll(), Some(Ok("later".to_string())));
+    }
+}
@@ -301,7 +301,11 @@ pub struct WindowBackendScaleFactorChanged {
 }
 
 /// Events related to files being dragged and dropped on a window.
-#[derive(Event, Debug, Clone, PartialEq, Eq, Reflect)]
+///
+/// Each file dropped at once is sent as a [`FileDragAndDrop::DroppedFile`], followed by a
+/// single [`FileDragAndDrop::FilesDropped`] with all of them. On the web, where files have no
+/// path, only [`FileDragAndDrop::FilesDropped`] is sent, with the content of the files.
+#[derive(Event, Debug, Clone, PartialEq, Reflect)]
 #[reflect(Debug, PartialEq)]
 #[cfg_attr(
     feature = "serialize",
@@ -330,6 +334,70 @@ pub enum FileDragAndDrop {
         /// Window that had a canceled file drop.
         window: Entity,
     },
+
+    /// Files started being dragged over a window.
+    DragEntered {
+        /// Window the files are dragged over.
+        window: Entity,
+        /// Logical position of the cursor in the window, if known.
+        position: Option<Vec2>,
+    },
+
+    /// Files being dragged over a window moved.
+    DragMoved {
+        /// Window the files are dragged over.
+        window: Entity,
+        /// Logical position of the cursor in the window.
+        position: Vec2,
+    },
+
+    /// Files being dragged left a window, or the drag was canceled.
+    DragLeft {
+        /// Window the files were dragged over.
+        window: Entity,
+    },
+
+    /// Files were dropped into a window.
+    FilesDropped {
+        /// Window the files were dropped into.
+        window: Entity,
+        /// Logical position of the cursor in the window, if known.
+        position: Option<Vec2>,
+        /// The files that were dropped.
+        files: Vec<DroppedFile>,
+    },
+}
+
+/// A file dropped into a window, as sent by [`FileDragAndDrop::FilesDropped`].
+#[derive(Debug, Clone, PartialEq, Eq, Reflect)]
+#[reflect(Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    reflect(Serialize, Deserialize)
+)]
+pub struct DroppedFile {
+    /// The name of the file.
+    pub name: String,
+    /// The path of the file, on platforms with a file system.
+    pub path: Option<PathBuf>,
+    /// The content of the file, on platforms where the file can't be read from its path, like
+    /// the web.
+    pub content: Option<Vec<u8>>,
+}
+
+impl DroppedFile {
+    /// Creates a dropped file read from `path`.
+    pub fn from_path(path: PathBuf) -> Self {
+        Self {
+            name: path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            path: Some(path),
+            content: None,
+        }
+    }
 }
 
 /// An event that is sent when a window is repositioned in physical pixels.
@@ -0,0 +1,195 @@
+use bevy_ecs::{
+    entity::Entity,
+    prelude::{Component, ReflectComponent},
+    world::World,
+};
+use bevy_input::{
+    mouse::{MouseButton, MouseButtonInput},
+    pointer::{PointerAction, PointerButton, PointerId, PointerInput, Pointers},
+    ButtonState,
+};
+use bevy_math::{DVec2, UVec2};
+use bevy_reflect::{std_traits::ReflectDefault, Reflect};
+
+use crate::{
+    CursorLeft, CursorMoved, RawHandleWrapper, Window, WindowCreated, WindowResized,
+    WindowScaleFactorChanged,
+};
+
+/// Marks a [`Window`] whose surface is provided by the host application, instead of being created
+/// by the windowing backend.
+///
+/// This is used to embed Bevy as a viewport inside a larger application, e.g. a native toolkit or
+/// an editor, which owns the native window or child view and forwards its size and input to Bevy.
+/// The windowing backend ignores these windows: use the methods of [`ExternalWindowsExt`] to
+/// spawn them, resize them and forward their cursor.
+///
+/// Other input events, like [`KeyboardInput`](bevy_input::keyboard::KeyboardInput), can be sent
+/// directly with [`World::send_event`], using the window entity.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component, Default)]
+pub struct ExternalWindow;
+
+/// Methods to drive the [`ExternalWindow`]s of a [`World`] from the host application.
+pub trait ExternalWindowsExt {
+    /// Spawns an [`ExternalWindow`] rendering to the surface of the native window `handle`.
+    ///
+    /// The physical size and the scale factor of the `window` should match the native window.
+    fn spawn_external_window(&mut self, window: Window, handle: RawHandleWrapper) -> Entity;
+
+    /// Resizes the external `window`, e.g. when the host application lays out its viewport.
+    ///
+    /// Sends a [`WindowResized`] event, and a [`WindowScaleFactorChanged`] event if the scale
+    /// factor changed.
+    fn resize_external_window(&mut self, window: Entity, physical_size: UVec2, scale_factor: f32);
+
+    /// Moves the cursor of the external `window` to the `physical_position` in the window, or
+    /// removes it if `None`.
+    ///
+    /// Sends the [`CursorMoved`] or [`CursorLeft`] event and the mouse [`PointerInput`].
+    fn forward_cursor_position(&mut self, window: Entity, physical_position: Option<DVec2>);
+
+    /// Presses or releases a mouse `button` in the external `window`.
+    ///
+    /// Sends the [`MouseButtonInput`] event and the mouse [`PointerInput`].
+    fn forward_mouse_button(&mut self, window: Entity, button: MouseButton, state: ButtonState);
+}
+
+impl ExternalWindowsExt for World {
+    fn spawn_external_window(&mut self, window: Window, handle: RawHandleWrapper) -> Entity {
+        let entity = self.spawn((window, handle, ExternalWindow)).id();
+        self.send_event(WindowCreated { window: entity });
+        entity
+    }
+
+    fn resize_external_window(&mut self, window: Entity, physical_size: UVec2, scale_factor: f32) {
+        let Some(mut win) = self.get_mut::<Window>(window) else {
+            return;
+        };
+        let scale_factor_changed = win.resolution.base_scale_factor() != scale_factor;
+        if scale_factor_changed {
+            win.resolution.set_scale_factor(scale_factor);
+        }
+        win.resolution
+            .set_physical_resolution(physical_size.x, physical_size.y);
+        let resized = WindowResized {
+            window,
+            width: win.width(),
+            height: win.height(),
+        };
+
+        if scale_factor_changed {
+            self.send_event(WindowScaleFactorChanged {
+                window,
+                scale_factor: scale_factor as f64,
+            });
+        }
+        self.send_event(resized);
+    }
+
+    fn forward_cursor_position(&mut self, window: Entity, physical_position: Option<DVec2>) {
+        let Some(mut win) = self.get_mut::<Window>(window) else {
+            return;
+        };
+        let scale_factor = win.resolution.scale_factor();
+        let last_position = win.cursor_position();
+        win.set_physical_cursor_position(physical_position);
+        let pressure = self
+            .get_resource::<Pointers>()
+            .and_then(|pointers| pointers.get(PointerId::Mouse))
+            .map_or(0.0, |pointer| pointer.pressure);
+
+        match physical_position {
+            Some(physical_position) => {
+                let position = (physical_position / scale_factor as f64).as_vec2();
+                self.send_event(CursorMoved {
+                    window,
+                    position,
+                    delta: last_position.map(|last_position| position - last_position),
+                });
+                self.send_event(PointerInput {
+                    id: PointerId::Mouse,
+                    action: PointerAction::Moved,
+                    window,
+                    position,
+                    pressure,
+                });
+            }
+            None => {
+                self.send_event(CursorLeft { window });
+                self.send_event(PointerInput {
+                    id: PointerId::Mouse,
+                    action: PointerAction::Left,
+                    window,
+                    position: last_position.unwrap_or_default(),
+                    pressure: 0.0,
+                });
+            }
+        }
+    }
+
+    fn forward_mouse_button(&mut self, window: Entity, button: MouseButton, state: ButtonState) {
+        let Some(win) = self.get::<Window>(window) else {
+            return;
+        };
+        let position = win.cursor_position().unwrap_or_default();
+        self.send_event(MouseButtonInput {
+            button,
+            state,
+            window,
+        });
+
+        let pointer_button = match button {
+            MouseButton::Left => PointerButton::Primary,
+            MouseButton::Right => PointerButton::Secondary,
+            MouseButton::Middle => PointerButton::Middle,
+            _ => return,
+        };
+        let (action, pressure) = match state {
+            ButtonState::Pressed => (PointerAction::Pressed(pointer_button), 0.5),
+            ButtonState::Released => (PointerAction::Released(pointer_button), 0.0),
+        };
+        self.send_event(PointerInput {
+            id: PointerId::Mouse,
+            action,
+            window,
+            position,
+            pressure,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_ecs::event::Events;
+    use raw_window_handle::{RawDisplayHandle, RawWindowHandle, WebDisplayHandle, WebWindowHandle};
+
+    #[test]
+    fn resizing_an_external_window_sends_events() {
+        let mut world = World::new();
+        world.init_resource::<Events<WindowCreated>>();
+        world.init_resource::<Events<WindowResized>>();
+        world.init_resource::<Events<WindowScaleFactorChanged>>();
+        let handle = RawHandleWrapper {
+            window_handle: RawWindowHandle::Web(WebWindowHandle::new(1)),
+            display_handle: RawDisplayHandle::Web(WebDisplayHandle::new()),
+        };
+        let window = world.spawn_external_window(Window::default(), handle);
+
+        world.resize_external_window(window, UVec2::new(800, 600), 2.0);
+        let resolution = &world.get::<Window>(window).unwrap().resolution;
+        assert_eq!(resolution.physical_width(), 800);
+        assert_eq!(resolution.width(), 400.0);
+
+        let events = world.resource::<Events<WindowResized>>();
+        let mut reader = events.get_reader();
+        let resized = reader.read(events).last().unwrap();
+        assert_eq!((resized.width, resized.height), (400.0, 300.0));
+        assert_eq!(
+            world.resource::<Events<WindowScaleFactorChanged>>().len(),
+            1
+        );
+        assert_eq!(world.resource::<Events<WindowCreated>>().len(), 1);
+    }
+}
@@ -7,16 +7,22 @@
 
 use bevy_a11y::Focus;
 
+mod clipboard;
 mod cursor;
 mod event;
+mod external;
+mod monitor;
 mod raw_handle;
 mod system;
 mod window;
 
 pub use crate::raw_handle::*;
 
+pub use clipboard::*;
 pub use cursor::*;
 pub use event::*;
+pub use external::*;
+pub use monitor::*;
 pub use system::*;
 pub use window::*;
 
@@ -98,7 +104,12 @@ impl Plugin for WindowPlugin {
             .add_event::<FileDragAndDrop>()
             .add_event::<WindowMoved>()
             .add_event::<WindowThemeChanged>()
-            .add_event::<ApplicationLifetime>();
+            .add_event::<ApplicationLifetime>()
+            .add_event::<VideoModeChanged>()
+            .add_event::<MonitorConnected>()
+            .add_event::<MonitorDisconnected>()
+            .init_resource::<Clipboard>()
+            .init_resource::<Monitors>();
 
         if let Some(primary_window) = &self.primary_window {
             let initial_focus = app
@@ -141,15 +152,24 @@ impl Plugin for WindowPlugin {
             .register_type::<WindowScaleFactorChanged>()
             .register_type::<WindowBackendScaleFactorChanged>()
             .register_type::<FileDragAndDrop>()
+            .register_type::<DroppedFile>()
             .register_type::<WindowMoved>()
             .register_type::<WindowThemeChanged>()
-            .register_type::<ApplicationLifetime>();
+            .register_type::<ApplicationLifetime>()
+            .register_type::<VideoModeChanged>()
+            .register_type::<VideoMode>()
+            .register_type::<MonitorInfo>()
+            .register_type::<MonitorConnected>()
+            .register_type::<MonitorDisconnected>();
 
         // Register window descriptor and related types
         app.register_type::<Window>()
             .register_type::<PrimaryWindow>()
+            .register_type::<ExternalWindow>()
             .register_type::<Cursor>()
             .register_type::<CursorIcon>()
+            .register_type::<CustomCursor>()
+            .register_type::<HitTestRegions>()
             .register_type::<CursorGrabMode>()
             .register_type::<CompositeAlphaMode>()
             .register_type::<WindowResolution>()
@@ -161,7 +181,8 @@ impl Plugin for WindowPlugin {
             .register_type::<MonitorSelection>()
             .register_type::<WindowResizeConstraints>()
             .register_type::<WindowTheme>()
-            .register_type::<EnabledButtons>();
+            .register_type::<EnabledButtons>()
+            .register_type::<WindowIcon>();
 
         // Register `PathBuf` as it's used by `FileDragAndDrop`
         app.register_type::<PathBuf>();
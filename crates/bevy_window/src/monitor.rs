@@ -0,0 +1,197 @@
+use bevy_ecs::{entity::Entity, event::Event, system::Resource};
+use bevy_math::{IVec2, UVec2};
+use bevy_reflect::Reflect;
+
+use crate::WindowPosition;
+
+#[cfg(feature = "serialize")]
+use bevy_reflect::{ReflectDeserialize, ReflectSerialize};
+
+/// A video mode of a monitor, which can be used for exclusive fullscreen with
+/// [`WindowMode::ExclusiveFullscreen`](crate::WindowMode::ExclusiveFullscreen).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+#[cfg_attr(
+    feature = "serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    reflect(Serialize, Deserialize)
+)]
+#[reflect(Debug, PartialEq, Hash)]
+pub struct VideoMode {
+    /// The resolution of the monitor in physical pixels.
+    pub physical_size: UVec2,
+    /// The number of bits per pixel.
+    pub bit_depth: u16,
+    /// The refresh rate of the monitor, in millihertz.
+    pub refresh_rate_millihertz: u32,
+}
+
+/// A monitor connected to the system, as listed by [`Monitors`].
+#[derive(Debug, Clone, PartialEq, Reflect)]
+#[cfg_attr(
+    feature = "serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    reflect(Serialize, Deserialize)
+)]
+#[reflect(Debug, PartialEq)]
+pub struct MonitorInfo {
+    /// The name of the monitor, if known.
+    pub name: Option<String>,
+    /// Whether the monitor is the primary monitor of the system.
+    pub primary: bool,
+    /// The resolution of the monitor in physical pixels.
+    pub physical_size: UVec2,
+    /// The position of the top-left corner of the monitor on the desktop, in physical pixels.
+    pub physical_position: IVec2,
+    /// The scale factor of the monitor, to convert logical pixels to physical pixels.
+    pub scale_factor: f64,
+    /// The refresh rate of the monitor in its current video mode, in millihertz, if known.
+    pub refresh_rate_millihertz: Option<u32>,
+    /// Whether the monitor supports high dynamic range, or `None` if the windowing backend can't
+    /// tell.
+    pub hdr: Option<bool>,
+    /// The video modes the monitor supports for exclusive fullscreen.
+    pub video_modes: Vec<VideoMode>,
+}
+
+impl MonitorInfo {
+    /// The [`WindowPosition`] placing the top-left corner of a window at `offset` physical pixels
+    /// from the top-left corner of the monitor.
+    ///
+    /// Use [`WindowPosition::Centered`] to center a window on a monitor instead.
+    pub fn window_position(&self, offset: IVec2) -> WindowPosition {
+        WindowPosition::At(self.physical_position + offset)
+    }
+
+    /// Returns `true` if the `physical_position` on the desktop is on the monitor.
+    pub fn contains(&self, physical_position: IVec2) -> bool {
+        let relative = physical_position - self.physical_position;
+        relative.cmpge(IVec2::ZERO).all() && relative.as_uvec2().cmplt(self.physical_size).all()
+    }
+}
+
+/// The monitors connected to the system, updated by the windowing backend.
+///
+/// The monitors are listed in the order used by
+/// [`MonitorSelection::Index`](crate::MonitorSelection::Index). A [`MonitorConnected`] or
+/// [`MonitorDisconnected`] event is sent when the list changes.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct Monitors {
+    monitors: Vec<MonitorInfo>,
+}
+
+impl Monitors {
+    /// Gets the monitor at `index`.
+    pub fn get(&self, index: usize) -> Option<&MonitorInfo> {
+        self.monitors.get(index)
+    }
+
+    /// Gets the primary monitor, if known.
+    pub fn primary(&self) -> Option<&MonitorInfo> {
+        self.monitors.iter().find(|monitor| monitor.primary)
+    }
+
+    /// Gets the index of the monitor containing the `physical_position` on the desktop, like the
+    /// position of a window, to be used with
+    /// [`MonitorSelection::Index`](crate::MonitorSelection::Index).
+    pub fn index_at(&self, physical_position: IVec2) -> Option<usize> {
+        self.monitors
+            .iter()
+            .position(|monitor| monitor.contains(physical_position))
+    }
+
+    /// An iterator over the monitors.
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = &MonitorInfo> {
+        self.monitors.iter()
+    }
+
+    /// The number of monitors.
+    pub fn len(&self) -> usize {
+        self.monitors.len()
+    }
+
+    /// Returns `true` if no monitor is known.
+    pub fn is_empty(&self) -> bool {
+        self.monitors.is_empty()
+    }
+
+    /// Replaces the monitors, as listed by the windowing backend.
+    pub fn set(&mut self, monitors: Vec<MonitorInfo>) {
+        self.monitors = monitors;
+    }
+}
+
+/// An event sent when a monitor is connected, after it's added to [`Monitors`].
+#[derive(Event, Debug, Clone, PartialEq, Reflect)]
+#[reflect(Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    reflect(Serialize, Deserialize)
+)]
+pub struct MonitorConnected {
+    /// The index of the monitor in [`Monitors`].
+    pub index: usize,
+    /// The monitor.
+    pub monitor: MonitorInfo,
+}
+
+/// An event sent when a monitor is disconnected, after it's removed from [`Monitors`].
+#[derive(Event, Debug, Clone, PartialEq, Reflect)]
+#[reflect(Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    reflect(Serialize, Deserialize)
+)]
+pub struct MonitorDisconnected {
+    /// The monitor.
+    pub monitor: MonitorInfo,
+}
+
+/// An event sent when the video mode of a window changes, when it enters or leaves exclusive
+/// fullscreen.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    reflect(Serialize, Deserialize)
+)]
+pub struct VideoModeChanged {
+    /// The window whose video mode changed.
+    pub window: Entity,
+    /// The new video mode of the window, or `None` if it isn't in exclusive fullscreen.
+    pub video_mode: Option<VideoMode>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn monitor(x: i32, primary: bool) -> MonitorInfo {
+        MonitorInfo {
+            name: None,
+            primary,
+            physical_size: UVec2::new(1920, 1080),
+            physical_position: IVec2::new(x, 0),
+            scale_factor: 1.0,
+            refresh_rate_millihertz: Some(60000),
+            hdr: None,
+            video_modes: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn monitors_at_positions() {
+        let mut monitors = Monitors::default();
+        monitors.set(vec![monitor(0, true), monitor(1920, false)]);
+        assert_eq!(monitors.index_at(IVec2::new(100, 100)), Some(0));
+        assert_eq!(monitors.index_at(IVec2::new(1920, 1079)), Some(1));
+        assert_eq!(monitors.index_at(IVec2::new(1920, 1080)), None);
+        assert_eq!(monitors.index_at(IVec2::new(-1, 0)), None);
+        assert_eq!(
+            monitors.get(1).unwrap().window_position(IVec2::new(10, 20)),
+            WindowPosition::At(IVec2::new(1930, 20))
+        );
+    }
+}
@@ -18,6 +18,15 @@ pub struct RawHandleWrapper {
 }
 
 impl RawHandleWrapper {
+    /// Creates a [`RawHandleWrapper`] from a native window, e.g. the window or child view of the
+    /// host application for an [`ExternalWindow`](crate::ExternalWindow).
+    pub fn new(window: &(impl HasWindowHandle + HasDisplayHandle)) -> Result<Self, HandleError> {
+        Ok(Self {
+            window_handle: window.window_handle()?.as_raw(),
+            display_handle: window.display_handle()?.as_raw(),
+        })
+    }
+
     /// Returns a [`HasWindowHandle`] + [`HasDisplayHandle`] impl, which exposes [`WindowHandle`] and [`DisplayHandle`].
     ///
     /// # Safety
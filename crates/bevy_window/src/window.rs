@@ -2,7 +2,7 @@ use bevy_ecs::{
     entity::{Entity, EntityMapper, MapEntities},
     prelude::{Component, ReflectComponent},
 };
-use bevy_math::{DVec2, IVec2, Vec2};
+use bevy_math::{DVec2, IVec2, Rect, UVec2, Vec2};
 use bevy_reflect::{std_traits::ReflectDefault, Reflect};
 
 #[cfg(feature = "serialize")]
@@ -10,7 +10,7 @@ use bevy_reflect::{ReflectDeserialize, ReflectSerialize};
 
 use bevy_utils::tracing::warn;
 
-use crate::CursorIcon;
+use crate::{CursorIcon, VideoMode};
 
 /// Marker [`Component`] for the window considered the primary window.
 ///
@@ -183,12 +183,14 @@ pub struct Window {
     ///
     /// Defines whether the background of the window should be transparent.
     ///
+    /// The clear color of the cameras rendering to the window should be transparent too. With
+    /// [`CompositeAlphaMode::Auto`], the renderer blends the pixels of the window with what's
+    /// behind it according to their alpha, if the surface of the window supports it.
+    ///
+    /// See [`HitTestRegions`] to make an overlay window letting the cursor input pass through.
+    ///
     /// ## Platform-specific
     /// - iOS / Android / Web: Unsupported.
-    /// - macOS: Not working as expected.
-    ///
-    /// macOS transparent works with winit out of the box, so this issue might be related to: <https://github.com/gfx-rs/wgpu/issues/687>.
-    /// You should also set the window `composite_alpha_mode` to `CompositeAlphaMode::PostMultiplied`.
     pub transparent: bool,
     /// Get/set whether the window is focused.
     pub focused: bool,
@@ -251,6 +253,21 @@ pub struct Window {
     ///
     /// - **Android / Wayland / Web:** Unsupported.
     pub visible: bool,
+    /// The icon of the window.
+    ///
+    /// To use an image asset as the icon, add a `WindowIconImage` component to the window entity,
+    /// which sets this field once the image is loaded.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **`Windows`**: Sets the icon of the title bar and of the taskbar, which should be 16x16
+    ///   and 32x32 pixels respectively.
+    /// - **`X11`**: Sets the icon used by the window manager, usually 32x32 pixels.
+    /// - **`macOS`**: Windows don't have icons, the icon of the application in the dock is set
+    ///   instead.
+    /// - **`Wayland`**: Unsupported, the icon comes from the desktop entry of the application.
+    /// - **`iOS`** / **`Android`** / **`Web`**: Unsupported.
+    pub icon: Option<WindowIcon>,
 }
 
 impl Default for Window {
@@ -278,6 +295,7 @@ impl Default for Window {
             canvas: None,
             window_theme: None,
             visible: true,
+            icon: None,
         }
     }
 }
@@ -387,6 +405,116 @@ impl Window {
     }
 }
 
+/// The icon of a [`Window`], as the colors of its pixels.
+#[derive(Debug, Clone, PartialEq, Eq, Reflect)]
+#[cfg_attr(
+    feature = "serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    reflect(Serialize, Deserialize)
+)]
+#[reflect(Debug, PartialEq)]
+pub struct WindowIcon {
+    /// The width of the icon in pixels.
+    pub width: u32,
+    /// The height of the icon in pixels.
+    pub height: u32,
+    /// The red, green, blue and alpha components of the pixels, in sRGB space, row by row from
+    /// the top left of the icon.
+    pub rgba: Vec<u8>,
+}
+
+impl WindowIcon {
+    /// Creates an icon from the components of its pixels.
+    ///
+    /// Returns `None` if `rgba` doesn't hold 4 components for each pixel.
+    pub fn from_rgba(rgba: Vec<u8>, width: u32, height: u32) -> Option<Self> {
+        (rgba.len() as u64 == width as u64 * height as u64 * 4).then_some(Self {
+            width,
+            height,
+            rgba,
+        })
+    }
+}
+
+/// The regions of a [`Window`] receiving the input of the cursor, in logical pixels from the top
+/// left of the window.
+///
+/// Outside of these regions, the cursor input passes through the window to the windows below,
+/// like when [`Cursor::hit_test`] is `false`. Together with [`Window::transparent`] and
+/// [`WindowLevel::AlwaysOnTop`], this makes overlay windows and desktop widgets, where only
+/// their visible parts can be clicked.
+///
+/// The regions have no effect while [`Cursor::hit_test`] is `false`.
+///
+/// ## Platform-specific
+///
+/// - **Windows / macOS:** Supported.
+/// - **X11 / Wayland / Web / iOS / Android:** Unsupported.
+#[derive(Component, Debug, Clone, Default, PartialEq, Reflect)]
+#[cfg_attr(
+    feature = "serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    reflect(Serialize, Deserialize)
+)]
+#[reflect(Component, Debug, PartialEq, Default)]
+pub struct HitTestRegions(pub Vec<Rect>);
+
+impl HitTestRegions {
+    /// Returns `true` if the logical `position` in the window is in one of the regions.
+    pub fn contains(&self, position: Vec2) -> bool {
+        self.0.iter().any(|region| region.contains(position))
+    }
+}
+
+/// A custom image for the cursor of a [`Window`], displayed instead of its [`CursorIcon`].
+///
+/// Add this component to a window entity to change its cursor, and remove it to restore the
+/// [`CursorIcon`] of [`Window::cursor`]. To animate the cursor, replace the component with the
+/// next frame.
+///
+/// ## Platform-specific
+///
+/// - **Web:** Supported, the cursor is displayed by the browser from a PNG image.
+/// - **Windows / macOS / X11 / Wayland:** Unsupported by the windowing backend, the cursor
+///   icon is displayed instead.
+/// - **iOS / Android:** Don't have cursors.
+#[derive(Component, Debug, Clone, PartialEq, Eq, Reflect)]
+#[cfg_attr(
+    feature = "serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    reflect(Serialize, Deserialize)
+)]
+#[reflect(Component, Debug, PartialEq)]
+pub struct CustomCursor {
+    /// The width of the cursor in pixels.
+    pub width: u32,
+    /// The height of the cursor in pixels.
+    pub height: u32,
+    /// The red, green, blue and alpha components of the pixels, in sRGB space, row by row from
+    /// the top left of the cursor.
+    pub rgba: Vec<u8>,
+    /// The pixel of the cursor pointing at its position, from the top left of the cursor.
+    pub hotspot: UVec2,
+}
+
+impl CustomCursor {
+    /// Creates a cursor from the components of its pixels and its `hotspot`.
+    ///
+    /// Returns `None` if `rgba` doesn't hold 4 components for each pixel, or if the `hotspot` is
+    /// outside of the cursor.
+    pub fn from_rgba(rgba: Vec<u8>, width: u32, height: u32, hotspot: UVec2) -> Option<Self> {
+        (rgba.len() as u64 == width as u64 * height as u64 * 4
+            && hotspot.x < width
+            && hotspot.y < height)
+            .then_some(Self {
+                width,
+                height,
+                rgba,
+                hotspot,
+            })
+    }
+}
+
 /// The size limits on a [`Window`].
 ///
 /// These values are measured in logical pixels (see [`WindowResolution`]), so the user's
@@ -1024,6 +1152,25 @@ pub enum WindowMode {
     /// If you want to avoid that behavior, you can use the [`WindowResolution::set_scale_factor_override`] function
     /// or the [`WindowResolution::with_scale_factor_override`] builder method to set the scale factor to 1.0.
     Fullscreen,
+    /// The window should be in "true"/"legacy" Fullscreen mode on the `monitor`, using the
+    /// `video_mode`.
+    ///
+    /// The video modes of the monitors are listed by the [`Monitors`](crate::Monitors) resource.
+    /// If the monitor doesn't support the video mode, the video mode with the closest refresh
+    /// rate among the ones fitting its resolution is used instead.
+    /// A [`VideoModeChanged`](crate::VideoModeChanged) event is sent when the video mode of the
+    /// window changes.
+    ///
+    /// Note: As this mode respects the scale factor provided by the operating system,
+    /// the window's logical size may be different from its physical size.
+    ExclusiveFullscreen {
+        /// The monitor to use.
+        ///
+        /// [`MonitorSelection::Current`] uses the primary monitor when creating the window.
+        monitor: MonitorSelection,
+        /// The video mode to use.
+        video_mode: VideoMode,
+    },
 }
 
 /// Specifies where a [`Window`] should appear relative to other overlapping windows (on top or under) .
@@ -1163,4 +1310,16 @@ mod tests {
         window.set_physical_cursor_position(Some(DVec2::new(400., 600.)));
         assert!(window.physical_cursor_position().is_none());
     }
+
+    #[test]
+    fn hit_test_regions_contain_position() {
+        let regions = HitTestRegions(vec![
+            Rect::new(0., 0., 100., 50.),
+            Rect::new(200., 200., 300., 300.),
+        ]);
+        assert!(regions.contains(Vec2::new(50., 25.)));
+        assert!(regions.contains(Vec2::new(250., 300.)));
+        assert!(!regions.contains(Vec2::new(150., 25.)));
+        assert!(!HitTestRegions::default().contains(Vec2::ZERO));
+    }
 }
@@ -2,11 +2,13 @@ use bevy_ecs::entity::Entity;
 use bevy_input::{
     keyboard::{KeyCode, KeyboardInput, NativeKeyCode},
     mouse::MouseButton,
+    pen::{PenButtons, PenInput, PenPhase, PenTilt},
+    pointer::{PointerAction, PointerButton, PointerId, PointerInput},
     touch::{ForceTouch, TouchInput, TouchPhase},
     ButtonState,
 };
-use bevy_math::Vec2;
-use bevy_window::{CursorIcon, EnabledButtons, WindowLevel, WindowTheme};
+use bevy_math::{UVec2, Vec2};
+use bevy_window::{CursorIcon, EnabledButtons, VideoMode, WindowLevel, WindowTheme};
 use winit::keyboard::{Key, NamedKey, NativeKey};
 
 pub fn convert_keyboard_input(
@@ -69,6 +71,96 @@ pub fn convert_touch_input(
     }
 }
 
+/// Converts touches of pens to [`PenInput`]s. Only Apple Pencils report their altitude, which
+/// tells them apart from fingers.
+pub fn convert_pen_input(
+    touch_input: &winit::event::Touch,
+    location: winit::dpi::LogicalPosition<f64>,
+    window_entity: Entity,
+) -> Option<PenInput> {
+    let Some(winit::event::Force::Calibrated {
+        force,
+        max_possible_force,
+        altitude_angle: Some(altitude_angle),
+    }) = touch_input.force
+    else {
+        return None;
+    };
+    Some(PenInput {
+        phase: match touch_input.phase {
+            winit::event::TouchPhase::Started => PenPhase::Started,
+            winit::event::TouchPhase::Moved => PenPhase::Moved,
+            winit::event::TouchPhase::Ended => PenPhase::Ended,
+            winit::event::TouchPhase::Cancelled => PenPhase::Canceled,
+        },
+        position: Vec2::new(location.x as f32, location.y as f32),
+        window: window_entity,
+        pressure: if max_possible_force > 0.0 {
+            (force / max_possible_force).clamp(0.0, 1.0) as f32
+        } else {
+            1.0
+        },
+        tilt: Some(PenTilt {
+            altitude: altitude_angle as f32,
+            azimuth: None,
+        }),
+        buttons: PenButtons::default(),
+        id: touch_input.id,
+    })
+}
+
+/// Converts mouse buttons to the [`PointerButton`]s of the mouse pointer, if they have one.
+pub fn convert_pointer_button(mouse_button: winit::event::MouseButton) -> Option<PointerButton> {
+    match mouse_button {
+        winit::event::MouseButton::Left => Some(PointerButton::Primary),
+        winit::event::MouseButton::Right => Some(PointerButton::Secondary),
+        winit::event::MouseButton::Middle => Some(PointerButton::Middle),
+        _ => None,
+    }
+}
+
+/// Converts touches, or the touches of pens, to the [`PointerInput`]s of their pointers.
+pub fn convert_touch_pointer_input(
+    touch_input: &TouchInput,
+    pen_input: Option<&PenInput>,
+) -> Vec<PointerInput> {
+    let (id, pressure) = match pen_input {
+        Some(pen_input) => (PointerId::Pen(pen_input.id), pen_input.pressure),
+        None => (
+            PointerId::Touch(touch_input.id),
+            match touch_input.force {
+                Some(ForceTouch::Calibrated {
+                    force,
+                    max_possible_force,
+                    ..
+                }) if max_possible_force > 0.0 => {
+                    (force / max_possible_force).clamp(0.0, 1.0) as f32
+                }
+                Some(ForceTouch::Normalized(force)) => force.clamp(0.0, 1.0) as f32,
+                _ => 0.5,
+            },
+        ),
+    };
+    let event = |action| PointerInput {
+        id,
+        action,
+        window: touch_input.window,
+        position: touch_input.position,
+        pressure,
+    };
+    match touch_input.phase {
+        TouchPhase::Started => vec![event(PointerAction::Pressed(PointerButton::Primary))],
+        TouchPhase::Moved => vec![event(PointerAction::Moved)],
+        // A lifted touch leaves the window, pens are still tracked by their `PenInput`s but
+        // winit doesn't report hovering pens.
+        TouchPhase::Ended => vec![
+            event(PointerAction::Released(PointerButton::Primary)),
+            event(PointerAction::Left),
+        ],
+        TouchPhase::Canceled => vec![event(PointerAction::Canceled)],
+    }
+}
+
 pub fn convert_physical_native_key_code(
     native_key_code: winit::keyboard::NativeKeyCode,
 ) -> NativeKeyCode {
@@ -701,3 +793,12 @@ pub fn convert_enabled_buttons(enabled_buttons: EnabledButtons) -> winit::window
     }
     window_buttons
 }
+
+pub fn convert_video_mode(video_mode: &winit::monitor::VideoMode) -> VideoMode {
+    let size = video_mode.size();
+    VideoMode {
+        physical_size: UVec2::new(size.width, size.height),
+        bit_depth: video_mode.bit_depth(),
+        refresh_rate_millihertz: video_mode.refresh_rate_millihertz(),
+    }
+}
@@ -0,0 +1,129 @@
+use bevy_ecs::{
+    entity::Entity,
+    query::{Changed, Or},
+    removal_detection::RemovedComponents,
+    system::{Local, NonSend, Query},
+    world::Ref,
+};
+use bevy_window::{CustomCursor, Window};
+
+#[cfg(target_arch = "wasm32")]
+use bevy_ecs::entity::EntityHashMap;
+#[cfg(not(target_arch = "wasm32"))]
+use bevy_utils::warn_once;
+
+use crate::{converters, WinitWindows};
+
+/// The custom cursors converted for the windowing backend.
+#[derive(Default)]
+pub(crate) struct CustomCursorCache {
+    /// The CSS cursor property of the windows with a custom cursor, without the fallback icon.
+    #[cfg(target_arch = "wasm32")]
+    css: EntityHashMap<String>,
+}
+
+impl CustomCursorCache {
+    #[cfg(target_arch = "wasm32")]
+    fn set_cursor(
+        &mut self,
+        winit_window: &winit::window::Window,
+        entity: Entity,
+        cursor: Ref<CustomCursor>,
+        window: &Window,
+    ) {
+        if cursor.is_changed() || !self.css.contains_key(&entity) {
+            let Some(css) = web::cursor_css(&cursor) else {
+                bevy_utils::tracing::warn!("Could not encode the custom cursor");
+                return;
+            };
+            self.css.insert(entity, css);
+        }
+        // The cursor icon is displayed by the browser while the image loads.
+        let icon = converters::convert_cursor_icon(window.cursor.icon);
+        web::set_cursor_css(
+            winit_window,
+            &format!("{}, {}", self.css[&entity], icon.name()),
+        );
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn set_cursor(
+        &mut self,
+        _winit_window: &winit::window::Window,
+        _entity: Entity,
+        _cursor: Ref<CustomCursor>,
+        _window: &Window,
+    ) {
+        warn_once!(
+            "Custom cursor images aren't supported by the windowing backend on this platform, they are drawn by `bevy_ui` instead when it is enabled"
+        );
+    }
+
+    fn remove(&mut self, _entity: Entity) {
+        #[cfg(target_arch = "wasm32")]
+        self.css.remove(&_entity);
+    }
+}
+
+/// Sets the [`CustomCursor`]s of the windows, after their cursor icon is set.
+pub(crate) fn update_custom_cursors(
+    cursors: Query<
+        (Entity, Ref<CustomCursor>, &Window),
+        Or<(Changed<CustomCursor>, Changed<Window>)>,
+    >,
+    windows: Query<&Window>,
+    mut removed: RemovedComponents<CustomCursor>,
+    winit_windows: NonSend<WinitWindows>,
+    mut cache: Local<CustomCursorCache>,
+) {
+    for entity in removed.read() {
+        cache.remove(entity);
+        if let (Some(winit_window), Ok(window)) =
+            (winit_windows.get_window(entity), windows.get(entity))
+        {
+            winit_window.set_cursor_icon(converters::convert_cursor_icon(window.cursor.icon));
+        }
+    }
+
+    for (entity, cursor, window) in &cursors {
+        if let Some(winit_window) = winit_windows.get_window(entity) {
+            cache.set_cursor(winit_window, entity, cursor, window);
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod web {
+    use bevy_window::CustomCursor;
+    use wasm_bindgen::{Clamped, JsCast};
+    use winit::platform::web::WindowExtWebSys;
+
+    /// Encodes the cursor as a PNG image, in the CSS cursor property with its hotspot.
+    pub(super) fn cursor_css(cursor: &CustomCursor) -> Option<String> {
+        let document = web_sys::window()?.document()?;
+        let canvas: web_sys::HtmlCanvasElement =
+            document.create_element("canvas").ok()?.dyn_into().ok()?;
+        canvas.set_width(cursor.width);
+        canvas.set_height(cursor.height);
+        let context: web_sys::CanvasRenderingContext2d =
+            canvas.get_context("2d").ok()??.dyn_into().ok()?;
+        let image_data = web_sys::ImageData::new_with_u8_clamped_array_and_sh(
+            Clamped(&cursor.rgba),
+            cursor.width,
+            cursor.height,
+        )
+        .ok()?;
+        context.put_image_data(&image_data, 0.0, 0.0).ok()?;
+        let data_url = canvas.to_data_url().ok()?;
+        Some(format!(
+            "url({data_url}) {} {}",
+            cursor.hotspot.x, cursor.hotspot.y
+        ))
+    }
+
+    pub(super) fn set_cursor_css(winit_window: &winit::window::Window, css: &str) {
+        if let Some(canvas) = winit_window.canvas() {
+            let _ = canvas.style().set_property("cursor", css);
+        }
+    }
+}
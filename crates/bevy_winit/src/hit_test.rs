@@ -0,0 +1,116 @@
+use bevy_ecs::{
+    entity::{Entity, EntityHashMap},
+    removal_detection::RemovedComponents,
+    system::{Local, NonSend, Query},
+};
+use bevy_math::DVec2;
+use bevy_utils::warn_once;
+use bevy_window::{HitTestRegions, Window};
+
+use crate::WinitWindows;
+
+/// Lets the cursor input pass through the windows with [`HitTestRegions`] while the cursor is
+/// outside of their regions.
+///
+/// The windows don't receive cursor events while the input passes through them, so the position
+/// of the cursor on the desktop is used.
+pub(crate) fn update_hit_test_regions(
+    windows: Query<(Entity, &Window, &HitTestRegions)>,
+    all_windows: Query<&Window>,
+    mut removed: RemovedComponents<HitTestRegions>,
+    winit_windows: NonSend<WinitWindows>,
+    mut hit_tests: Local<EntityHashMap<bool>>,
+) {
+    for entity in removed.read() {
+        if hit_tests.remove(&entity).is_none() {
+            continue;
+        }
+        if let (Some(winit_window), Ok(window)) =
+            (winit_windows.get_window(entity), all_windows.get(entity))
+        {
+            let _ = winit_window.set_cursor_hittest(window.cursor.hit_test);
+        }
+    }
+
+    for (entity, window, regions) in &windows {
+        let Some(winit_window) = winit_windows.get_window(entity) else {
+            continue;
+        };
+        if !window.cursor.hit_test {
+            // The hit test of the window was disabled by the app.
+            hit_tests.remove(&entity);
+            continue;
+        }
+
+        // Without the position of the cursor on the desktop, the hit test couldn't be enabled
+        // again once the cursor leaves the regions.
+        let Some(cursor) = desktop_cursor_position(winit_window) else {
+            warn_once!("Hit test regions aren't supported on this platform");
+            continue;
+        };
+        let Ok(window_position) = winit_window.inner_position() else {
+            continue;
+        };
+        let physical_position =
+            cursor - DVec2::new(window_position.x as f64, window_position.y as f64);
+        let position = (physical_position / window.resolution.scale_factor() as f64).as_vec2();
+        let hit_test = regions.contains(position);
+        if hit_tests.get(&entity) == Some(&hit_test) {
+            continue;
+        }
+        if winit_window.set_cursor_hittest(hit_test).is_ok() {
+            hit_tests.insert(entity, hit_test);
+        }
+    }
+}
+
+/// The position of the cursor on the desktop, in physical pixels.
+#[cfg(target_os = "windows")]
+fn desktop_cursor_position(_winit_window: &winit::window::Window) -> Option<DVec2> {
+    use windows_sys::Win32::{Foundation::POINT, UI::WindowsAndMessaging::GetCursorPos};
+
+    let mut point = POINT { x: 0, y: 0 };
+    // SAFETY: `point` is valid to write the position of the cursor to.
+    (unsafe { GetCursorPos(&mut point) } != 0).then(|| DVec2::new(point.x as f64, point.y as f64))
+}
+
+/// The position of the cursor on the desktop, in physical pixels.
+#[cfg(target_os = "macos")]
+fn desktop_cursor_position(winit_window: &winit::window::Window) -> Option<DVec2> {
+    use objc::{class, msg_send, runtime::Object, sel, sel_impl};
+
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    struct NSPoint {
+        x: f64,
+        y: f64,
+    }
+
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    struct NSRect {
+        _origin: NSPoint,
+        size: NSPoint,
+    }
+
+    // SAFETY: The cursor and the screens are queried on the main thread, where the windows are
+    // updated.
+    unsafe {
+        let location: NSPoint = msg_send![class!(NSEvent), mouseLocation];
+        let screens: *mut Object = msg_send![class!(NSScreen), screens];
+        let main_screen: *mut Object = msg_send![screens, firstObject];
+        if main_screen.is_null() {
+            return None;
+        }
+        let frame: NSRect = msg_send![main_screen, frame];
+        // The desktop coordinates of AppKit start from the bottom left of the main screen, while
+        // the positions of the windows start from its top left.
+        Some(DVec2::new(location.x, frame.size.y - location.y) * winit_window.scale_factor())
+    }
+}
+
+/// The position of the cursor on the desktop, in physical pixels.
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn desktop_cursor_position(_winit_window: &winit::window::Window) -> Option<DVec2> {
+    None
+}
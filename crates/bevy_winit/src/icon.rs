@@ -0,0 +1,129 @@
+use bevy_utils::tracing::warn;
+use bevy_window::WindowIcon;
+
+/// Sets the icon of a window, or the icon of the application in the dock on macOS.
+pub(crate) fn set_window_icon(winit_window: &winit::window::Window, icon: Option<&WindowIcon>) {
+    let winit_icon = icon.and_then(|icon| {
+        winit::window::Icon::from_rgba(icon.rgba.clone(), icon.width, icon.height)
+            .map_err(|err| warn!("Could not set the window icon: {err}"))
+            .ok()
+    });
+    winit_window.set_window_icon(winit_icon);
+
+    #[cfg(target_os = "macos")]
+    macos::set_dock_icon(icon);
+}
+
+/// Encodes an icon as an uncompressed TIFF image, a format `NSImage` can read.
+#[cfg(any(target_os = "macos", test))]
+fn encode_tiff(icon: &WindowIcon) -> Vec<u8> {
+    const SHORT: u16 = 3;
+    const LONG: u16 = 4;
+    const ENTRY_COUNT: u32 = 11;
+    // The header, followed by the directory of entries, the bits per sample and the pixels.
+    const BITS_PER_SAMPLE_OFFSET: u32 = 8 + 2 + ENTRY_COUNT * 12 + 4;
+    const PIXELS_OFFSET: u32 = BITS_PER_SAMPLE_OFFSET + 4 * 2;
+
+    let mut tiff = Vec::with_capacity(PIXELS_OFFSET as usize + icon.rgba.len());
+    tiff.extend_from_slice(b"II");
+    tiff.extend_from_slice(&42u16.to_le_bytes());
+    tiff.extend_from_slice(&8u32.to_le_bytes());
+
+    tiff.extend_from_slice(&(ENTRY_COUNT as u16).to_le_bytes());
+    let entries: [(u16, u16, u32, u32); ENTRY_COUNT as usize] = [
+        // Image width and height.
+        (256, LONG, 1, icon.width),
+        (257, LONG, 1, icon.height),
+        // 8 bits per sample.
+        (258, SHORT, 4, BITS_PER_SAMPLE_OFFSET),
+        // No compression.
+        (259, SHORT, 1, 1),
+        // RGB.
+        (262, SHORT, 1, 2),
+        // A single strip with all the pixels.
+        (273, LONG, 1, PIXELS_OFFSET),
+        (277, SHORT, 1, 4),
+        (278, LONG, 1, icon.height),
+        (279, LONG, 1, icon.rgba.len() as u32),
+        // Interleaved samples.
+        (284, SHORT, 1, 1),
+        // Unassociated alpha.
+        (338, SHORT, 1, 2),
+    ];
+    for (tag, field_type, count, value) in entries {
+        tiff.extend_from_slice(&tag.to_le_bytes());
+        tiff.extend_from_slice(&field_type.to_le_bytes());
+        tiff.extend_from_slice(&count.to_le_bytes());
+        // Short values are stored in the first bytes, which is where little-endian puts them.
+        tiff.extend_from_slice(&value.to_le_bytes());
+    }
+    // No other directory.
+    tiff.extend_from_slice(&0u32.to_le_bytes());
+
+    for _ in 0..4 {
+        tiff.extend_from_slice(&8u16.to_le_bytes());
+    }
+    tiff.extend_from_slice(&icon.rgba);
+    tiff
+}
+
+#[cfg(target_os = "macos")]
+mod macos {
+    use bevy_window::WindowIcon;
+    use objc::{class, msg_send, runtime::Object, sel, sel_impl};
+    use std::ffi::c_void;
+
+    /// Sets the icon of the application in the dock, or restores the icon of its bundle.
+    pub(super) fn set_dock_icon(icon: Option<&WindowIcon>) {
+        let tiff = icon.map(super::encode_tiff);
+        // SAFETY: Windows are updated on the main thread, as required by AppKit. `NSData` copies
+        // the bytes of the image, and the image is retained by the application before its
+        // release.
+        unsafe {
+            let image: *mut Object = match &tiff {
+                Some(tiff) => {
+                    let data: *mut Object = msg_send![
+                        class!(NSData),
+                        dataWithBytes: tiff.as_ptr() as *const c_void
+                        length: tiff.len()
+                    ];
+                    let image: *mut Object = msg_send![class!(NSImage), alloc];
+                    msg_send![image, initWithData: data]
+                }
+                None => std::ptr::null_mut(),
+            };
+            let application: *mut Object = msg_send![class!(NSApplication), sharedApplication];
+            let _: () = msg_send![application, setApplicationIconImage: image];
+            if !image.is_null() {
+                let _: () = msg_send![image, release];
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tiff_holds_the_pixels_after_its_header() {
+        let icon = WindowIcon::from_rgba(vec![255; 2 * 3 * 4], 2, 3).unwrap();
+        let tiff = encode_tiff(&icon);
+        assert_eq!(&tiff[..4], b"II\x2a\x00");
+        let pixels_offset = tiff.len() - icon.rgba.len();
+        // The offset of the pixels is the value of the strip offsets entry.
+        let strip_offsets_entry = 8 + 2 + 5 * 12;
+        assert_eq!(
+            &tiff[strip_offsets_entry..strip_offsets_entry + 4],
+            &[0x11, 0x01, 4, 0]
+        );
+        assert_eq!(
+            u32::from_le_bytes(
+                tiff[strip_offsets_entry + 8..strip_offsets_entry + 12]
+                    .try_into()
+                    .unwrap()
+            ),
+            pixels_offset as u32
+        );
+    }
+}
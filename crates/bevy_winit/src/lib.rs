@@ -7,13 +7,19 @@
 
 pub mod accessibility;
 mod converters;
+mod cursor;
+mod hit_test;
+mod icon;
 mod system;
+#[cfg(target_arch = "wasm32")]
+mod web_file_drop;
 mod winit_config;
 mod winit_windows;
 
 use approx::relative_eq;
 use bevy_a11y::AccessibilityRequested;
-use bevy_utils::{Duration, Instant};
+use bevy_utils::{Duration, FixedState, HashMap, HashSet, Instant};
+use std::hash::BuildHasher;
 use system::{changed_windows, create_windows, despawn_windows, CachedWindow};
 use winit::dpi::{LogicalSize, PhysicalSize};
 pub use winit_config::*;
@@ -24,16 +30,17 @@ use bevy_ecs::event::{Events, ManualEventReader};
 use bevy_ecs::prelude::*;
 use bevy_ecs::system::SystemState;
 use bevy_input::{
-    mouse::{MouseButtonInput, MouseMotion, MouseScrollUnit, MouseWheel},
-    touchpad::{TouchpadMagnify, TouchpadRotate},
+    mouse::{MouseButtonInput, MouseMotion, MouseScrollUnit, MouseWheel, RawMouseSettings},
+    pointer::{PointerAction, PointerButton, PointerId, PointerInput},
+    touchpad::{TouchpadMagnify, TouchpadRotate, TouchpadSmartMagnify},
 };
 use bevy_math::{ivec2, DVec2, Vec2};
 #[cfg(not(target_arch = "wasm32"))]
 use bevy_tasks::tick_global_task_pools_on_main_thread;
 use bevy_utils::tracing::{error, trace, warn};
 use bevy_window::{
-    exit_on_all_closed, ApplicationLifetime, CursorEntered, CursorLeft, CursorMoved,
-    FileDragAndDrop, Ime, ReceivedCharacter, RequestRedraw, Window,
+    exit_on_all_closed, ApplicationLifetime, CursorEntered, CursorLeft, CursorMoved, DroppedFile,
+    ExternalWindow, FileDragAndDrop, Ime, ReceivedCharacter, RequestRedraw, Window,
     WindowBackendScaleFactorChanged, WindowCloseRequested, WindowCreated, WindowDestroyed,
     WindowFocused, WindowMoved, WindowOccluded, WindowResized, WindowScaleFactorChanged,
     WindowThemeChanged,
@@ -45,7 +52,7 @@ use bevy_window::{PrimaryWindow, RawHandleWrapper};
 pub use winit::platform::android::activity as android_activity;
 
 use winit::{
-    event::{self, DeviceEvent, Event, WindowEvent},
+    event::{self, DeviceEvent, DeviceId, Event, WindowEvent},
     event_loop::{ControlFlow, EventLoop, EventLoopBuilder, EventLoopWindowTarget},
 };
 
@@ -124,17 +131,24 @@ impl Plugin for WinitPlugin {
                     // `exit_on_all_closed` only checks if windows exist but doesn't access data,
                     // so we don't need to care about its ordering relative to `changed_windows`
                     changed_windows.ambiguous_with(exit_on_all_closed),
+                    cursor::update_custom_cursors,
+                    hit_test::update_hit_test_regions,
                     despawn_windows,
                 )
                     .chain(),
             );
 
+        #[cfg(target_arch = "wasm32")]
+        app.add_systems(bevy_app::PreUpdate, web_file_drop::send_web_file_drops);
+
         app.add_plugins(AccessKitPlugin);
 
         let event_loop = event_loop_builder
             .build()
             .expect("Failed to build event loop");
 
+        update_monitors(&event_loop, &mut app.world);
+
         // iOS, macOS, and Android don't like it if you create windows before the event loop is
         // initialized.
         //
@@ -146,7 +160,10 @@ impl Plugin for WinitPlugin {
             // Otherwise, we want to create a window before `bevy_render` initializes the renderer
             // so that we have a surface to use as a hint. This improves compatibility with `wgpu`
             // backends, especially WASM/WebGL2.
-            let mut create_window = SystemState::<CreateWindowParams>::from_world(&mut app.world);
+            let mut create_window =
+                SystemState::<CreateWindowParams<Without<ExternalWindow>>>::from_world(
+                    &mut app.world,
+                );
             create_windows(&event_loop, create_window.get_mut(&mut app.world));
             create_window.apply(&mut app.world);
         }
@@ -157,6 +174,9 @@ impl Plugin for WinitPlugin {
     }
 }
 
+/// How often the monitors are listed, to find the ones connected or disconnected.
+const MONITOR_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
 trait AppSendEvent {
     fn send_event<E: bevy_ecs::event::Event>(&mut self, event: E);
 }
@@ -185,6 +205,14 @@ struct WinitAppRunnerState {
     scheduled_update: Option<Instant>,
     /// Number of "forced" updates to trigger on application start
     startup_forced_updates: u32,
+    /// The buttons of the mouse pointer being pressed.
+    pressed_mouse_buttons: HashSet<PointerButton>,
+    /// The windows files are being dragged over.
+    file_drags: HashSet<Entity>,
+    /// The files dropped since the last event loop iteration, with the position of the cursor.
+    dropped_files: HashMap<Entity, (Option<Vec2>, Vec<DroppedFile>)>,
+    /// The time the monitors were last listed.
+    last_monitor_poll: Instant,
 }
 
 impl WinitAppRunnerState {
@@ -194,6 +222,15 @@ impl WinitAppRunnerState {
         self.device_event_received = false;
         self.wait_elapsed = false;
     }
+
+    /// The pressure of the mouse pointer, which isn't sensitive to pressure.
+    fn mouse_pressure(&self) -> f32 {
+        if self.pressed_mouse_buttons.is_empty() {
+            0.0
+        } else {
+            0.5
+        }
+    }
 }
 
 #[derive(PartialEq, Eq)]
@@ -226,6 +263,10 @@ impl Default for WinitAppRunnerState {
             scheduled_update: None,
             // 3 seems to be enough, 5 is a safe margin
             startup_forced_updates: 5,
+            pressed_mouse_buttons: HashSet::default(),
+            file_drags: HashSet::default(),
+            dropped_files: HashMap::default(),
+            last_monitor_poll: Instant::now(),
         }
     }
 }
@@ -274,8 +315,9 @@ pub fn winit_runner(mut app: App) {
         NonSend<AccessKitAdapters>,
     )> = SystemState::new(&mut app.world);
 
-    let mut create_window =
-        SystemState::<CreateWindowParams<Added<Window>>>::from_world(&mut app.world);
+    let mut create_window = SystemState::<
+        CreateWindowParams<(Added<Window>, Without<ExternalWindow>)>,
+    >::from_world(&mut app.world);
     // set up the event loop
     let event_handler = move |event, event_loop: &EventLoopWindowTarget<()>| {
         handle_winit_event(
@@ -303,7 +345,7 @@ fn handle_winit_event(
     app: &mut App,
     app_exit_event_reader: &mut ManualEventReader<AppExit>,
     runner_state: &mut WinitAppRunnerState,
-    create_window: &mut SystemState<CreateWindowParams<Added<Window>>>,
+    create_window: &mut SystemState<CreateWindowParams<(Added<Window>, Without<ExternalWindow>)>>,
     event_writer_system_state: &mut SystemState<(
         EventWriter<WindowResized>,
         NonSend<WinitWindows>,
@@ -338,6 +380,21 @@ fn handle_winit_event(
 
     match event {
         Event::AboutToWait => {
+            // Files dropped at once have all been received.
+            for (window, (position, files)) in runner_state.dropped_files.drain() {
+                app.send_event(FileDragAndDrop::FilesDropped {
+                    window,
+                    position,
+                    files,
+                });
+            }
+
+            // `winit` doesn't send events when monitors are connected or disconnected.
+            if runner_state.last_monitor_poll.elapsed() >= MONITOR_POLL_INTERVAL {
+                update_monitors(event_loop, &mut app.world);
+                runner_state.last_monitor_poll = Instant::now();
+            }
+
             let (config, windows) = focused_windows_state.get(&app.world);
             let focused = windows.iter().any(|window| window.focused);
             let mut should_update = match config.update_mode(focused) {
@@ -458,20 +515,55 @@ fn handle_winit_event(
                         position,
                         delta,
                     });
+                    if runner_state.file_drags.contains(&window) {
+                        app.send_event(FileDragAndDrop::DragMoved { window, position });
+                    }
+                    app.send_event(PointerInput {
+                        id: PointerId::Mouse,
+                        action: PointerAction::Moved,
+                        window,
+                        position,
+                        pressure: runner_state.mouse_pressure(),
+                    });
                 }
                 WindowEvent::CursorEntered { .. } => {
                     app.send_event(CursorEntered { window });
                 }
                 WindowEvent::CursorLeft { .. } => {
+                    let position = win.cursor_position().unwrap_or_default();
                     win.set_physical_cursor_position(None);
                     app.send_event(CursorLeft { window });
+                    app.send_event(PointerInput {
+                        id: PointerId::Mouse,
+                        action: PointerAction::Left,
+                        window,
+                        position,
+                        pressure: 0.0,
+                    });
                 }
                 WindowEvent::MouseInput { state, button, .. } => {
+                    let position = win.cursor_position().unwrap_or_default();
                     app.send_event(MouseButtonInput {
                         button: converters::convert_mouse_button(button),
                         state: converters::convert_element_state(state),
                         window,
                     });
+                    if let Some(pointer_button) = converters::convert_pointer_button(button) {
+                        let action = if state.is_pressed() {
+                            runner_state.pressed_mouse_buttons.insert(pointer_button);
+                            PointerAction::Pressed(pointer_button)
+                        } else {
+                            runner_state.pressed_mouse_buttons.remove(&pointer_button);
+                            PointerAction::Released(pointer_button)
+                        };
+                        app.send_event(PointerInput {
+                            id: PointerId::Mouse,
+                            action,
+                            window,
+                            position,
+                            pressure: runner_state.mouse_pressure(),
+                        });
+                    }
                 }
                 WindowEvent::TouchpadMagnify { delta, .. } => {
                     app.send_event(TouchpadMagnify(delta as f32));
@@ -479,6 +571,9 @@ fn handle_winit_event(
                 WindowEvent::TouchpadRotate { delta, .. } => {
                     app.send_event(TouchpadRotate(delta));
                 }
+                WindowEvent::SmartMagnify { .. } => {
+                    app.send_event(TouchpadSmartMagnify);
+                }
                 WindowEvent::MouseWheel { delta, .. } => match delta {
                     event::MouseScrollDelta::LineDelta(x, y) => {
                         app.send_event(MouseWheel {
@@ -501,7 +596,15 @@ fn handle_winit_event(
                     let location = touch
                         .location
                         .to_logical(win.resolution.scale_factor() as f64);
-                    app.send_event(converters::convert_touch_input(touch, location, window));
+                    let pen = converters::convert_pen_input(&touch, location, window);
+                    let touch = converters::convert_touch_input(touch, location, window);
+                    for pointer in converters::convert_touch_pointer_input(&touch, pen.as_ref()) {
+                        app.send_event(pointer);
+                    }
+                    if let Some(pen) = pen {
+                        app.send_event(pen);
+                    }
+                    app.send_event(touch);
                 }
                 WindowEvent::ScaleFactorChanged {
                     scale_factor,
@@ -563,12 +666,27 @@ fn handle_winit_event(
                     app.send_event(WindowOccluded { window, occluded });
                 }
                 WindowEvent::DroppedFile(path_buf) => {
+                    runner_state.file_drags.remove(&window);
+                    runner_state
+                        .dropped_files
+                        .entry(window)
+                        .or_insert_with(|| (win.cursor_position(), Vec::new()))
+                        .1
+                        .push(DroppedFile::from_path(path_buf.clone()));
                     app.send_event(FileDragAndDrop::DroppedFile { window, path_buf });
                 }
                 WindowEvent::HoveredFile(path_buf) => {
+                    // Each file being dragged is hovered, but they enter the window together.
+                    if runner_state.file_drags.insert(window) {
+                        let position = win.cursor_position();
+                        app.send_event(FileDragAndDrop::DragEntered { window, position });
+                    }
                     app.send_event(FileDragAndDrop::HoveredFile { window, path_buf });
                 }
                 WindowEvent::HoveredFileCancelled => {
+                    if runner_state.file_drags.remove(&window) {
+                        app.send_event(FileDragAndDrop::DragLeft { window });
+                    }
                     app.send_event(FileDragAndDrop::HoveredFileCanceled { window });
                 }
                 WindowEvent::Moved(position) => {
@@ -624,11 +742,10 @@ fn handle_winit_event(
                 }
             }
         }
-        Event::DeviceEvent { event, .. } => {
+        Event::DeviceEvent { device_id, event } => {
             runner_state.device_event_received = true;
             if let DeviceEvent::MouseMotion { delta: (x, y) } = event {
-                let delta = Vec2::new(x as f32, y as f32);
-                app.send_event(MouseMotion { delta });
+                send_mouse_motion(app, device_id, Vec2::new(x as f32, y as f32));
             }
         }
         Event::Suspended => {
@@ -638,6 +755,9 @@ fn handle_winit_event(
             runner_state.active = ActiveState::WillSuspend;
         }
         Event::Resumed => {
+            // Monitors may have been connected or disconnected while the app was suspended.
+            update_monitors(event_loop, &mut app.world);
+
             #[cfg(any(target_os = "android", target_os = "ios", target_os = "macos"))]
             {
                 if runner_state.active == ActiveState::NotYetStarted {
@@ -699,7 +819,7 @@ fn run_app_update_if_should(
     app: &mut App,
     focused_windows_state: &mut SystemState<(Res<WinitSettings>, Query<&Window>)>,
     event_loop: &EventLoopWindowTarget<()>,
-    create_window: &mut SystemState<CreateWindowParams<Added<Window>>>,
+    create_window: &mut SystemState<CreateWindowParams<(Added<Window>, Without<ExternalWindow>)>>,
     app_exit_event_reader: &mut ManualEventReader<AppExit>,
     redraw_event_reader: &mut ManualEventReader<RequestRedraw>,
 ) {
@@ -768,6 +888,26 @@ fn run_app_update_if_should(
     create_window.apply(&mut app.world);
 }
 
+/// Sends the [`MouseMotion`] event of a motion of `device_id`, and its [`RawMouseMotion`] event
+/// if enabled by the [`RawMouseSettings`].
+///
+/// The device is identified by a hash with fixed keys, so it keeps its identifier across runs.
+///
+/// [`RawMouseMotion`]: bevy_input::mouse::RawMouseMotion
+fn send_mouse_motion(app: &mut App, device_id: DeviceId, delta: Vec2) {
+    app.send_event(MouseMotion { delta });
+
+    let raw_mouse_settings = app
+        .world
+        .get_resource::<RawMouseSettings>()
+        .copied()
+        .unwrap_or_default();
+    let device = FixedState.hash_one(device_id);
+    if let Some(raw_motion) = raw_mouse_settings.raw_motion(device, delta, Instant::now()) {
+        app.send_event(raw_motion);
+    }
+}
+
 fn react_to_resize(
     win: &mut Mut<'_, Window>,
     size: winit::dpi::PhysicalSize<u32>,
@@ -783,3 +923,38 @@ fn react_to_resize(
         height: win.height(),
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_input::{mouse::RawMouseMotion, InputPlugin};
+
+    #[test]
+    fn raw_motion_timestamps_keep_their_order() {
+        let mut app = App::new();
+        app.add_plugins(InputPlugin)
+            .insert_resource(RawMouseSettings::ENABLED);
+
+        // SAFETY: the dummy id is only hashed, never passed back to winit.
+        let device_id = unsafe { DeviceId::dummy() };
+        let start = Instant::now();
+        for _ in 0..3 {
+            send_mouse_motion(&mut app, device_id, Vec2::X);
+        }
+
+        let events = app.world.resource::<Events<RawMouseMotion>>();
+        let motions: Vec<_> = events.get_reader().read(events).copied().collect();
+        assert_eq!(motions.len(), 3);
+        assert!(motions
+            .iter()
+            .all(|motion| motion.device == FixedState.hash_one(device_id)));
+        let timestamps: Vec<_> = motions
+            .iter()
+            .filter_map(|motion| motion.timestamp)
+            .collect();
+        assert_eq!(timestamps.len(), 3);
+        assert!(start <= timestamps[0]);
+        assert!(timestamps.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert_eq!(app.world.resource::<Events<MouseMotion>>().len(), 3);
+    }
+}
@@ -8,7 +8,8 @@ use bevy_ecs::{
 };
 use bevy_utils::tracing::{error, info, warn};
 use bevy_window::{
-    RawHandleWrapper, Window, WindowClosed, WindowCreated, WindowMode, WindowResized,
+    RawHandleWrapper, VideoModeChanged, Window, WindowClosed, WindowCreated, WindowMode,
+    WindowResized,
 };
 
 use raw_window_handle::{HasDisplayHandle, HasWindowHandle};
@@ -19,14 +20,16 @@ use winit::{
 
 use crate::{
     converters::{
-        self, convert_enabled_buttons, convert_window_level, convert_window_theme,
-        convert_winit_theme,
+        self, convert_enabled_buttons, convert_video_mode, convert_window_level,
+        convert_window_theme, convert_winit_theme,
     },
-    get_best_videomode, get_fitting_videomode, CreateWindowParams, WinitWindows,
+    get_best_videomode, get_fitting_videomode, get_selected_videomode,
+    winit_windows::select_monitor,
+    CreateWindowParams, WinitWindows,
 };
 
 /// Creates new windows on the [`winit`] backend for each entity with a newly-added
-/// [`Window`] component.
+/// [`Window`] component, except the [`ExternalWindow`](bevy_window::ExternalWindow)s.
 ///
 /// If any of these entities are missing required components, those will be added with their
 /// default values.
@@ -119,6 +122,7 @@ pub(crate) fn changed_windows(
     mut changed_windows: Query<(Entity, &mut Window, &mut CachedWindow), Changed<Window>>,
     winit_windows: NonSendMut<WinitWindows>,
     mut window_resized: EventWriter<WindowResized>,
+    mut video_mode_changed: EventWriter<VideoModeChanged>,
 ) {
     for (entity, mut window, mut cache) in &mut changed_windows {
         let Some(winit_window) = winit_windows.get_window(entity) else {
@@ -152,12 +156,46 @@ pub(crate) fn changed_windows(
                         None
                     }
                 }
+                WindowMode::ExclusiveFullscreen {
+                    monitor,
+                    video_mode,
+                } => {
+                    if let Some(monitor) = select_monitor(
+                        monitor,
+                        winit_window.current_monitor(),
+                        winit_window.primary_monitor(),
+                        winit_window.available_monitors(),
+                    ) {
+                        Some(Some(winit::window::Fullscreen::Exclusive(
+                            get_selected_videomode(&monitor, &video_mode),
+                        )))
+                    } else {
+                        warn!("Could not determine the monitor, ignoring exclusive fullscreen request for window {:?}", window.title);
+                        None
+                    }
+                }
                 WindowMode::Windowed => Some(None),
             };
 
             if let Some(new_mode) = new_mode {
-                if winit_window.fullscreen() != new_mode {
+                let fullscreen = winit_window.fullscreen();
+                if fullscreen != new_mode {
+                    let video_mode =
+                        |fullscreen: &Option<winit::window::Fullscreen>| match fullscreen {
+                            Some(winit::window::Fullscreen::Exclusive(video_mode)) => {
+                                Some(convert_video_mode(video_mode))
+                            }
+                            _ => None,
+                        };
+                    let new_video_mode = video_mode(&new_mode);
+                    let old_video_mode = video_mode(&fullscreen);
                     winit_window.set_fullscreen(new_mode);
+                    if new_video_mode != old_video_mode {
+                        video_mode_changed.send(VideoModeChanged {
+                            window: entity,
+                            video_mode: new_video_mode,
+                        });
+                    }
                 }
             }
         }
@@ -304,6 +342,10 @@ pub(crate) fn changed_windows(
             winit_window.set_visible(window.visible);
         }
 
+        if window.icon != cache.window.icon {
+            crate::icon::set_window_icon(winit_window, window.icon.as_ref());
+        }
+
         cache.window = window.clone();
     }
 }
@@ -0,0 +1,108 @@
+//! File drag-and-drop on the web, where the browser gives access to the content of the dropped
+//! files instead of their paths.
+
+use bevy_ecs::{entity::Entity, event::EventWriter};
+use bevy_math::Vec2;
+use bevy_window::{DroppedFile, FileDragAndDrop};
+use crossbeam_channel::{Receiver, Sender};
+use std::sync::OnceLock;
+use wasm_bindgen::{closure::Closure, JsCast};
+use wasm_bindgen_futures::JsFuture;
+use winit::platform::web::WindowExtWebSys;
+
+fn channel() -> &'static (Sender<FileDragAndDrop>, Receiver<FileDragAndDrop>) {
+    static CHANNEL: OnceLock<(Sender<FileDragAndDrop>, Receiver<FileDragAndDrop>)> =
+        OnceLock::new();
+    CHANNEL.get_or_init(crossbeam_channel::unbounded)
+}
+
+fn position(event: &web_sys::DragEvent) -> Vec2 {
+    Vec2::new(event.offset_x() as f32, event.offset_y() as f32)
+}
+
+fn add_listener(
+    canvas: &web_sys::HtmlCanvasElement,
+    name: &str,
+    mut listener: impl FnMut(&web_sys::DragEvent) + 'static,
+) {
+    let closure = Closure::wrap(Box::new(move |event: web_sys::DragEvent| {
+        // Keeps the browser from opening the dropped files.
+        event.prevent_default();
+        listener(&event);
+    }) as Box<dyn FnMut(web_sys::DragEvent)>);
+    if let Err(err) =
+        canvas.add_event_listener_with_callback(name, closure.as_ref().unchecked_ref())
+    {
+        bevy_utils::tracing::warn!("Could not listen to {name} events: {err:?}");
+    }
+    // The listener lives as long as the canvas.
+    closure.forget();
+}
+
+/// Listens to the files dragged over and dropped on the canvas of a window.
+pub(crate) fn listen_file_drops(winit_window: &winit::window::Window, window: Entity) {
+    let Some(canvas) = winit_window.canvas() else {
+        return;
+    };
+    let sender = channel().0.clone();
+
+    let enter_sender = sender.clone();
+    add_listener(&canvas, "dragenter", move |event| {
+        let position = Some(position(event));
+        let _ = enter_sender.send(FileDragAndDrop::DragEntered { window, position });
+    });
+
+    let move_sender = sender.clone();
+    add_listener(&canvas, "dragover", move |event| {
+        let position = position(event);
+        let _ = move_sender.send(FileDragAndDrop::DragMoved { window, position });
+    });
+
+    let leave_sender = sender.clone();
+    add_listener(&canvas, "dragleave", move |_| {
+        let _ = leave_sender.send(FileDragAndDrop::DragLeft { window });
+    });
+
+    add_listener(&canvas, "drop", move |event| {
+        let position = Some(position(event));
+        let Some(file_list) = event.data_transfer().and_then(|data| data.files()) else {
+            return;
+        };
+        let files: Vec<web_sys::File> = (0..file_list.length())
+            .filter_map(|index| file_list.get(index))
+            .collect();
+        let sender = sender.clone();
+        // The content of the files is read asynchronously, so the drop is sent once all of them
+        // have been read.
+        wasm_bindgen_futures::spawn_local(async move {
+            let mut dropped_files = Vec::with_capacity(files.len());
+            for file in files {
+                let content = match JsFuture::from(file.array_buffer()).await {
+                    Ok(buffer) => Some(js_sys::Uint8Array::new(&buffer).to_vec()),
+                    Err(err) => {
+                        bevy_utils::tracing::warn!(
+                            "Could not read the dropped file {}: {err:?}",
+                            file.name()
+                        );
+                        None
+                    }
+                };
+                dropped_files.push(DroppedFile {
+                    name: file.name(),
+                    path: None,
+                    content,
+                });
+            }
+            let _ = sender.send(FileDragAndDrop::FilesDropped {
+                window,
+                position,
+                files: dropped_files,
+            });
+        });
+    });
+}
+
+/// Sends the [`FileDragAndDrop`] events received from the browser.
+pub(crate) fn send_web_file_drops(mut events: EventWriter<FileDragAndDrop>) {
+    events.send_batch(channel().1.try_iter());
+}
@@ -3,11 +3,15 @@ use bevy_a11y::{
     accesskit::{NodeBuilder, NodeClassSet, NodeId, Role, Tree, TreeUpdate},
     AccessibilityRequested,
 };
-use bevy_ecs::entity::Entity;
+use bevy_ecs::{entity::Entity, world::World};
+use bevy_math::{IVec2, UVec2};
 
 use bevy_ecs::entity::EntityHashMap;
 use bevy_utils::{tracing::warn, HashMap};
-use bevy_window::{CursorGrabMode, Window, WindowMode, WindowPosition, WindowResolution};
+use bevy_window::{
+    CursorGrabMode, MonitorConnected, MonitorDisconnected, MonitorInfo, MonitorSelection, Monitors,
+    VideoMode, Window, WindowMode, WindowPosition, WindowResolution,
+};
 
 use winit::{
     dpi::{LogicalSize, PhysicalPosition},
@@ -16,7 +20,9 @@ use winit::{
 
 use crate::{
     accessibility::{AccessKitAdapters, WinitActionHandler, WinitActionHandlers},
-    converters::{convert_enabled_buttons, convert_window_level, convert_window_theme},
+    converters::{
+        convert_enabled_buttons, convert_video_mode, convert_window_level, convert_window_theme,
+    },
 };
 
 /// A resource mapping window entities to their `winit`-backend [`Window`](winit::window::Window)
@@ -29,6 +35,8 @@ pub struct WinitWindows {
     pub entity_to_winit: EntityHashMap<winit::window::WindowId>,
     /// Maps `winit` window identifiers to entities.
     pub winit_to_entity: HashMap<winit::window::WindowId, Entity>,
+    /// The monitors listed in [`Monitors`], to find the ones connected or disconnected since.
+    pub(crate) monitors: Vec<MonitorHandle>,
     // Many `winit` window functions (e.g. `set_window_icon`) can only be called on the main thread.
     // If they're called on other threads, the program might hang. This marker indicates that this
     // type is not thread-safe and will be `!Send` and `!Sync`.
@@ -75,6 +83,28 @@ impl WinitWindows {
                     winit_window_builder
                 }
             }
+            WindowMode::ExclusiveFullscreen {
+                monitor,
+                video_mode,
+            } => {
+                // The window doesn't have a monitor before its creation.
+                if let Some(monitor) = select_monitor(
+                    monitor,
+                    event_loop.primary_monitor(),
+                    event_loop.primary_monitor(),
+                    event_loop.available_monitors(),
+                ) {
+                    winit_window_builder.with_fullscreen(Some(
+                        winit::window::Fullscreen::Exclusive(get_selected_videomode(
+                            &monitor,
+                            &video_mode,
+                        )),
+                    ))
+                } else {
+                    warn!("Could not determine the monitor, ignoring exclusive fullscreen request for window {:?}", window.title);
+                    winit_window_builder
+                }
+            }
             WindowMode::Windowed => {
                 if let Some(position) = winit_window_position(
                     &window.position,
@@ -205,6 +235,8 @@ impl WinitWindows {
         }
 
         let winit_window = winit_window_builder.build(event_loop).unwrap();
+        #[cfg(target_arch = "wasm32")]
+        crate::web_file_drop::listen_file_drops(&winit_window, entity);
         let name = window.title.clone();
 
         let mut root_builder = NodeBuilder::new(Role::Window);
@@ -236,6 +268,10 @@ impl WinitWindows {
 
         winit_window.set_cursor_visible(window.cursor.visible);
 
+        if window.icon.is_some() {
+            crate::icon::set_window_icon(&winit_window, window.icon.as_ref());
+        }
+
         // Do not set the cursor hittest on window creation if it's false, as it will always fail on
         // some platforms and log an unfixable warning.
         if !window.cursor.hit_test {
@@ -336,6 +372,102 @@ pub fn get_best_videomode(monitor: &MonitorHandle) -> winit::monitor::VideoMode
     modes.first().unwrap().clone()
 }
 
+/// Gets the video mode of the monitor matching the given [`VideoMode`].
+///
+/// Falls back to the video mode with the closest refresh rate among the ones which fit its
+/// dimensions if the monitor doesn't support it.
+pub fn get_selected_videomode(
+    monitor: &MonitorHandle,
+    video_mode: &VideoMode,
+) -> winit::monitor::VideoMode {
+    if let Some(selected) = monitor
+        .video_modes()
+        .find(|mode| convert_video_mode(mode) == *video_mode)
+    {
+        return selected;
+    }
+
+    let size = video_mode.physical_size;
+    let fitting = get_fitting_videomode(monitor, size.x, size.y);
+    monitor
+        .video_modes()
+        .filter(|mode| mode.size() == fitting.size())
+        .min_by_key(|mode| {
+            mode.refresh_rate_millihertz()
+                .abs_diff(video_mode.refresh_rate_millihertz)
+        })
+        .unwrap_or(fitting)
+}
+
+/// Gets the monitor handle for the given [`MonitorSelection`], using `current_monitor` for
+/// [`MonitorSelection::Current`].
+pub(crate) fn select_monitor(
+    monitor: MonitorSelection,
+    current_monitor: Option<MonitorHandle>,
+    primary_monitor: Option<MonitorHandle>,
+    mut available_monitors: impl Iterator<Item = MonitorHandle>,
+) -> Option<MonitorHandle> {
+    match monitor {
+        MonitorSelection::Current => current_monitor,
+        MonitorSelection::Primary => primary_monitor,
+        MonitorSelection::Index(n) => available_monitors.nth(n),
+    }
+}
+
+/// Lists the monitors of the event loop in the [`Monitors`] resource, in the order used by
+/// [`MonitorSelection::Index`].
+///
+/// Sends the [`MonitorConnected`] and [`MonitorDisconnected`] events for the monitors which were
+/// connected or disconnected since the last update.
+pub(crate) fn update_monitors(
+    event_loop: &winit::event_loop::EventLoopWindowTarget<()>,
+    world: &mut World,
+) {
+    let handles: Vec<_> = event_loop.available_monitors().collect();
+    let primary_monitor = event_loop.primary_monitor();
+    let infos: Vec<_> = handles
+        .iter()
+        .map(|monitor| MonitorInfo {
+            name: monitor.name(),
+            primary: primary_monitor.as_ref() == Some(monitor),
+            physical_size: UVec2::new(monitor.size().width, monitor.size().height),
+            physical_position: IVec2::new(monitor.position().x, monitor.position().y),
+            scale_factor: monitor.scale_factor(),
+            refresh_rate_millihertz: monitor.refresh_rate_millihertz(),
+            // `winit` doesn't tell if a monitor supports HDR.
+            hdr: None,
+            video_modes: monitor
+                .video_modes()
+                .map(|video_mode| convert_video_mode(&video_mode))
+                .collect(),
+        })
+        .collect();
+
+    let Some(mut winit_windows) = world.get_non_send_resource_mut::<WinitWindows>() else {
+        return;
+    };
+    let old_handles = std::mem::replace(&mut winit_windows.monitors, handles.clone());
+    let Some(mut monitors) = world.get_resource_mut::<Monitors>() else {
+        return;
+    };
+    if monitors.iter().eq(infos.iter()) {
+        return;
+    }
+    let old_infos: Vec<_> = monitors.iter().cloned().collect();
+    monitors.set(infos.clone());
+
+    for (handle, monitor) in old_handles.iter().zip(old_infos) {
+        if !handles.contains(handle) {
+            world.send_event(MonitorDisconnected { monitor });
+        }
+    }
+    for (index, (handle, monitor)) in handles.iter().zip(infos).enumerate() {
+        if !old_handles.contains(handle) {
+            world.send_event(MonitorConnected { index, monitor });
+        }
+    }
+}
+
 pub(crate) fn attempt_grab(winit_window: &winit::window::Window, grab_mode: CursorGrabMode) {
     let grab_result = match grab_mode {
         CursorGrabMode::None => winit_window.set_cursor_grab(winit::window::CursorGrabMode::None),
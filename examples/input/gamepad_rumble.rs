@@ -2,7 +2,7 @@
 //! pressed.
 
 use bevy::{
-    input::gamepad::{GamepadRumbleIntensity, GamepadRumbleRequest},
+    input::gamepad::{GamepadRumbleIntensity, GamepadRumbleRequest, GamepadRumbleStacking},
     prelude::*,
     utils::Duration,
 };
@@ -35,6 +35,7 @@ fn gamepad_system(
                 gamepad,
                 intensity: GamepadRumbleIntensity::strong_motor(0.1),
                 duration: Duration::from_secs(5),
+                stacking: GamepadRumbleStacking::Add,
             });
         }
 
@@ -44,6 +45,7 @@ fn gamepad_system(
                 gamepad,
                 duration: Duration::from_secs(5),
                 intensity: GamepadRumbleIntensity::MAX,
+                stacking: GamepadRumbleStacking::Add,
             });
         }
 
@@ -53,6 +55,7 @@ fn gamepad_system(
                 gamepad,
                 duration: Duration::from_secs_f32(0.5),
                 intensity: GamepadRumbleIntensity::weak_motor(0.25),
+                stacking: GamepadRumbleStacking::Add,
             });
         }
 
@@ -65,8 +68,10 @@ fn gamepad_system(
                     strong_motor: 0.5,
                     // intensity of high-frequency motor, usually on the right-hand side
                     weak_motor: 0.25,
+                    ..default()
                 },
                 duration: Duration::from_secs(5),
+                stacking: GamepadRumbleStacking::Add,
             });
         }
 
@@ -573,6 +573,7 @@ fn init_materials(
     materials.push(assets.add(ColorMaterial {
         color: Color::WHITE,
         texture: textures.first().cloned(),
+        ..default()
     }));
 
     let mut color_rng = StdRng::seed_from_u64(42);
@@ -582,6 +583,7 @@ fn init_materials(
             assets.add(ColorMaterial {
                 color: Color::rgb_u8(color_rng.gen(), color_rng.gen(), color_rng.gen()),
                 texture: textures.choose(&mut texture_rng).cloned(),
+                ..default()
             })
         })
         .take(capacity - materials.len()),
@@ -40,6 +40,9 @@ crates=(
     bevy_text
     bevy_a11y
     bevy_ui
+    bevy_particles
+    bevy_picking
+    bevy_dev_tools
     bevy_winit
     bevy_internal
     bevy_dylib